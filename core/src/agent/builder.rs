@@ -25,16 +25,16 @@ use crate::agent::{
 };
 use crate::agent::runtime::{
     LLMCapability, ToolCapability, ApprovalCapability, 
-    WorkerCapability, TelemetryCapability,
+    WorkerCapability, TelemetryCapability, TraceRecorder,
 };
 use crate::agent::runtime::capabilities::{
     LlmClientCapability, TerminalApprovalCapability,
     AutoApproveCapability, LocalWorkerCapability, ConsoleTelemetry,
-    MemoryCapability, AgentMemoryManager,
+    MemoryCapability, AgentMemoryManager, SessionPlan,
 };
-use crate::agent::tools::{ToolRegistry, WebSearchTool, WebSearchConfig, SearchProvider};
+use crate::agent::tools::{PlanTool, ToolRegistry, WebSearchTool, WebSearchConfig, SearchProvider};
 use crate::conversation::ContextManager;
-use crate::memory::store::VectorStore;
+use crate::memory::backend::MemoryBackend;
 use crate::provider::LlmClient;
 use std::sync::Arc;
 
@@ -48,6 +48,7 @@ pub struct AgentBuilder {
     config: SessionConfig,
     engine: Option<Box<dyn StepEngine + Send>>,
     memory_manager: Option<Arc<AgentMemoryManager>>,
+    trace: Option<Arc<TraceRecorder>>,
 }
 
 impl AgentBuilder {
@@ -61,6 +62,7 @@ impl AgentBuilder {
             config: SessionConfig::default(),
             engine: None,
             memory_manager: None,
+            trace: None,
         }
     }
     
@@ -170,11 +172,11 @@ impl AgentBuilder {
         self
     }
     
-    /// Add memory capability with a pre-initialized VectorStore
+    /// Add memory capability with a pre-initialized memory backend
     /// 
-    /// This is useful when you want to share the same VectorStore across multiple agents
+    /// This is useful when you want to share the same store across multiple agents
     /// or when you've already initialized it elsewhere.
-    pub fn with_memory_store(mut self, store: Arc<VectorStore>) -> Self {
+    pub fn with_memory_store(mut self, store: Arc<dyn MemoryBackend>) -> Self {
         // Create memory capability with the store
         let memory_capability = Arc::new(MemoryCapability::with_manager(
             Arc::new(AgentMemoryManager::from_store(store))
@@ -204,17 +206,35 @@ impl AgentBuilder {
         self
     }
     
+    /// Record inputs, decisions and runtime events to a trace for `mylm replay`
+    pub fn with_trace(mut self, recorder: Arc<TraceRecorder>) -> Self {
+        self.trace = Some(recorder);
+        self
+    }
+    
+    /// Record a trace when `features.record_traces` is on in `config`
+    pub fn with_config_trace(mut self, config: &crate::config::Config) -> Self {
+        if let Some(recorder) = TraceRecorder::for_session(config) {
+            self.trace = Some(Arc::new(recorder));
+        }
+        self
+    }
+    
     /// Build the runtime (without session)
     pub fn build_runtime(&mut self) -> AgentRuntime {
+        // Default registries get a plan tool over the graph's plan
+        let plan = Arc::new(SessionPlan::new());
+        let default_tools = || ToolRegistry::new().with_plan(PlanTool::new(plan.clone()));
+
         // Build tools, injecting memory tool if memory manager is available
         let tools: Arc<dyn ToolCapability> = if let Some(ref memory_manager) = self.memory_manager {
             // If we have a memory manager, create a ToolRegistry with memory enabled
-            let store = memory_manager.vector_store().clone();
-            let registry = ToolRegistry::new().with_memory(store);
+            let store = memory_manager.store().clone();
+            let registry = default_tools().with_memory(store);
             Arc::new(registry)
         } else {
             // Use existing tools or create default
-            self.tools.clone().unwrap_or_else(|| Arc::new(ToolRegistry::new()))
+            self.tools.clone().unwrap_or_else(|| Arc::new(default_tools()))
         };
         
         let graph = CapabilityGraph::new(
@@ -223,9 +243,14 @@ impl AgentBuilder {
             self.approval.clone().unwrap_or_else(|| Arc::new(AutoApproveCapability::new())),
             self.workers.clone().unwrap_or_else(|| Arc::new(crate::agent::runtime::stubs::StubWorkers)),
             self.telemetry.clone().unwrap_or_else(|| Arc::new(crate::agent::runtime::stubs::StubTelemetry)),
-        );
+        )
+        .with_plan(plan);
         
-        AgentRuntime::new(graph)
+        let runtime = AgentRuntime::new(graph);
+        match self.trace.clone() {
+            Some(trace) => runtime.with_trace(trace),
+            None => runtime,
+        }
     }
     
     /// Build a complete session with LLM-based step engine
@@ -304,6 +329,37 @@ mod tests {
         // Should compile and create runtime
     }
     
+    #[test]
+    fn test_config_trace_off_by_default() {
+        let runtime = AgentBuilder::new()
+            .with_config_trace(&crate::config::Config::default())
+            .build_runtime();
+        assert!(runtime.trace().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_plan_calls_reach_the_graph_plan() {
+        use crate::agent::cognition::{AgentDecision, InputEvent};
+        use crate::agent::runtime::RuntimeContext;
+        use crate::agent::types::events::ToolResult;
+        use crate::agent::types::intents::ToolCall;
+
+        assert!(!ToolRegistry::new().uses_session_plan("plan"));
+        let runtime = AgentBuilder::new().build_runtime();
+        let call = ToolCall {
+            name: "plan".to_string(),
+            arguments: serde_json::json!({ "action": "create", "goal": "Ship", "steps": ["Build it"] }),
+            working_dir: None,
+            timeout_secs: None,
+        };
+        match runtime.interpret(&RuntimeContext::new(), AgentDecision::CallTool(call)).await.unwrap() {
+            Some(InputEvent::ToolResult { result: ToolResult::Success { output, .. }, .. }) => {
+                assert!(output.contains("Build it"));
+            }
+            other => panic!("Expected a plan, got {:?}", other),
+        }
+    }
+    
     #[tokio::test]
     async fn test_preset_testing_agent() {
         let mut _session = presets::testing_agent();
@@ -21,7 +21,7 @@ pub enum AgentExitReason {
 }
 
 /// Agent decision - pure intent, no execution
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum AgentDecision {
     /// Call a tool
     CallTool(ToolCall),
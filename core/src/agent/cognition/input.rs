@@ -5,7 +5,7 @@
 //!
 //! NOTE: Types are re-exported from types module for consistency.
 
-// serde re-exported from types module
+use serde::{Deserialize, Serialize};
 
 // Re-export types from unified types module
 pub use crate::agent::types::events::{
@@ -13,13 +13,13 @@ pub use crate::agent::types::events::{
 };
 
 /// Error from worker execution
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WorkerError {
     pub message: String,
 }
 
 /// External input events
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum InputEvent {
     /// User sent a message
     UserMessage(String),
@@ -105,6 +105,11 @@ pub trait GraphEngine {
     /// Changes only happen through process().
     fn state(&self) -> &AgentState;
 
+    /// System prompt sent with every LLM request, for context budgeting
+    fn system_prompt(&self) -> String {
+        String::new()
+    }
+
     /// Check if kernel has reached a terminal state
     ///
     /// Default implementation checks if state indicates completion
@@ -413,7 +418,7 @@ impl GraphEngine for StubGraphEngine {
 
         // Simple stub: echo first user message or halt
         for event in events {
-            if let KernelEvent::UserMessage { content } = event {
+            if let KernelEvent::UserMessage { content, .. } = event {
                 let mut graph = IntentGraph::new();
                 
                 if self.state.at_limit() {
@@ -455,6 +460,7 @@ mod tests {
 
         let events = vec![KernelEvent::UserMessage {
             content: "hello".to_string(),
+            images: Vec::new(),
         }];
 
         let graph = kernel.process(&events).unwrap();
@@ -471,11 +477,13 @@ mod tests {
         // Process first event
         kernel.process(&[KernelEvent::UserMessage {
             content: "first".to_string(),
+            images: Vec::new(),
         }]).unwrap();
 
         // Process second event
         kernel.process(&[KernelEvent::UserMessage {
             content: "second".to_string(),
+            images: Vec::new(),
         }]).unwrap();
 
         // Should be at limit now
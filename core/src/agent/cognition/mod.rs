@@ -35,7 +35,7 @@ pub mod error;
 pub use kernel::{GraphEngine, AgentState, KernelError, TokenUsage, PendingApproval, StubGraphEngine, Message};
 pub use planner::Planner;
 pub use step::llm_engine::LlmEngine;
-pub use prompts::system::{ToolDescription, build_system_prompt, build_tool_defs};
+pub use prompts::system::{ToolDescription, build_system_prompt, build_system_prompt_with_time, build_tool_defs};
 pub use policy::{ApprovalPolicy, requires_approval};
 
 // Supporting module re-exports
@@ -12,14 +12,16 @@ use crate::agent::types::{
     graph::IntentGraph,
     intents::IntentNode,
     ids::IntentId,
-    intents::{Intent, ExitReason, Context, LLMRequest},
+    intents::{Intent, ExitReason, Context, LLMRequest, ToolCall},
     events::KernelEvent,
     config::KernelConfig,
     parser::{ShortKeyParser, ParsedResponse, ShortKeyExtracted},
 };
+use crate::conversation::context_compression::{CompressedSegment, SUMMARY_PREFIX};
 use crate::conversation::manager::Message;
 
-use super::prompts::system::{ToolDescription, build_tool_defs, build_system_prompt};
+use super::prompts::system::{ToolDescription, build_tool_defs, build_system_prompt, build_system_prompt_with_time};
+use super::prompts::tools::with_tools_section;
 use super::policy::approval::ApprovalPolicy;
 
 /// Planner implementation
@@ -58,6 +60,21 @@ impl Planner {
         self
     }
     
+    /// Use the default system prompt, with the time in this zone and locale
+    pub fn with_time_context(mut self, time: &crate::environment::TimeContext) -> Self {
+        self.system_prompt = build_system_prompt_with_time(time);
+        self
+    }
+    
+    /// Append project instructions to the system prompt
+    pub fn with_project_instructions(mut self, instructions: Option<&str>) -> Self {
+        if let Some(text) = instructions {
+            self.system_prompt.push_str("\n\n## Project Instructions\n");
+            self.system_prompt.push_str(text);
+        }
+        self
+    }
+    
     /// Set tool descriptions for prompt generation
     pub fn with_tool_descriptions(mut self, descriptions: Vec<ToolDescription>) -> Self {
         self.tool_descriptions = descriptions;
@@ -109,6 +126,10 @@ impl GraphEngine for Planner {
     fn state(&self) -> &AgentState {
         &self.state
     }
+
+    fn system_prompt(&self) -> String {
+        with_tools_section(&self.system_prompt, &self.tool_descriptions)
+    }
 }
 
 impl Planner {
@@ -116,11 +137,53 @@ impl Planner {
     fn handle_event(&mut self, event: &KernelEvent, graph: &mut IntentGraph) -> Result<(), KernelError> {
         crate::info_log!("[LLM_KERNEL] handle_event: {:?}", std::mem::discriminant(event));
         match event {
-            KernelEvent::UserMessage { content } => {
-                self.handle_user_message(content, graph)
+            KernelEvent::UserMessage { content, images } => {
+                self.handle_user_message(content, images, graph)
+            }
+            KernelEvent::DirectExchange { question, answer } => {
+                self.state.history.push(Message::new("user", question));
+                self.state.history.push(Message::new("assistant", answer));
+                Ok(())
+            }
+            KernelEvent::HistoryCondensed { replaced, summary, kept } => {
+                let replaced = (*replaced).min(self.state.history.len());
+                let condensed = std::iter::once(Message::new("user", summary)).chain(kept.iter().cloned());
+                self.state.history.splice(..replaced, condensed);
+                crate::info_log!("[PLANNER] Condensed {} messages into a summary", replaced);
+                Ok(())
+            }
+            KernelEvent::HistoryRestored { segment_id, messages } => {
+                // Back in place of the summary, or at the start if it is gone
+                let at = self
+                    .state
+                    .history
+                    .iter()
+                    .position(|m| CompressedSegment::is_summary_of(&m.content, segment_id));
+                match at {
+                    Some(i) => self.state.history.splice(i..=i, messages.iter().cloned()),
+                    None => self.state.history.splice(..0, messages.iter().cloned()),
+                };
+                Ok(())
+            }
+            KernelEvent::HistoryRewound => {
+                // The last message the user typed; a condensed summary is not one
+                let last = self
+                    .state
+                    .history
+                    .iter()
+                    .rposition(|m| m.role == "user" && !m.content.starts_with(SUMMARY_PREFIX));
+                if let Some(i) = last {
+                    crate::info_log!("[PLANNER] Rewound {} messages", self.state.history.len() - i);
+                    self.state.history.truncate(i);
+                }
+                Ok(())
             }
             KernelEvent::LLMCompleted { intent_id, response } => {
-                self.handle_llm_response(&response.content, *intent_id, graph)
+                if response.tool_calls.is_empty() {
+                    self.handle_llm_response(&response.content, *intent_id, graph)
+                } else {
+                    self.handle_native_tool_calls(&response.content, &response.tool_calls, *intent_id, graph)
+                }
             }
             KernelEvent::ToolCompleted { tool, result, .. } => {
                 self.handle_tool_result(tool, result, graph)
@@ -175,7 +238,12 @@ impl Planner {
     }
 
     /// Handle user message - requests LLM
-    fn handle_user_message(&mut self, content: &str, graph: &mut IntentGraph) -> Result<(), KernelError> {
+    fn handle_user_message(
+        &mut self,
+        content: &str,
+        images: &[crate::provider::chat::ImageAttachment],
+        graph: &mut IntentGraph,
+    ) -> Result<(), KernelError> {
         if self.check_limits(graph)? {
             return Ok(());
         }
@@ -184,7 +252,7 @@ impl Planner {
         self.state.increment_step();
         
         // Add message to history
-        self.state.history.push(Message::new("user", content));
+        self.state.history.push(Message::new("user", content).with_images(images.to_vec()));
         
         // Use different prompt based on message type:
         // - Chitchat: Just respond conversationally
@@ -476,6 +544,45 @@ impl Planner {
         Ok(())
     }
     
+    /// Handle structured tool calls returned through the provider's native API
+    ///
+    /// Native calls skip text parsing entirely. Each call is routed through the
+    /// same Y-switch as a Short-Key response, so approval policy still applies.
+    /// History records the calls in Short-Key form because follow-up turns
+    /// replay history as plain text.
+    fn handle_native_tool_calls(
+        &mut self,
+        content: &str,
+        calls: &[ToolCall],
+        intent_id: IntentId,
+        graph: &mut IntentGraph,
+    ) -> Result<(), KernelError> {
+        if self.check_limits(graph)? {
+            return Ok(());
+        }
+
+        crate::info_log!("[PLANNER] Received {} native tool call(s)", calls.len());
+        self.state.llm_retry_counts.remove(&intent_id);
+
+        for (i, call) in calls.iter().enumerate() {
+            let thought = if i == 0 { content.trim().to_string() } else { String::new() };
+            let recorded = serde_json::json!({
+                "t": thought,
+                "a": call.name,
+                "i": call.arguments,
+            });
+            self.state.history.push(Message::new("assistant", recorded.to_string()));
+
+            self.handle_short_key_response(ShortKeyExtracted {
+                thought,
+                tool_call: Some(call.clone()),
+                ..Default::default()
+            }, graph)?;
+        }
+
+        Ok(())
+    }
+    
     /// Handle tool result - request LLM interpretation
     fn handle_tool_result(
         &mut self,
@@ -658,7 +765,7 @@ impl Planner {
     /// Build LLM context with current state
     fn build_context(&self, scratchpad: &str) -> Context {
         Context::new(scratchpad.to_string())
-            .with_system(self.system_prompt.clone())
+            .with_system(with_tools_section(&self.system_prompt, &self.tool_descriptions))
             .with_history(self.state.history.clone())
             .with_tools(build_tool_defs(&self.tool_descriptions))
     }
@@ -689,12 +796,77 @@ mod tests {
         
         let events = vec![KernelEvent::UserMessage {
             content: "hello".to_string(),
+            images: Vec::new(),
         }];
         
         let graph = planner.process(&events).unwrap();
         assert!(!graph.is_empty());
     }
-    
+
+    #[test]
+    fn test_direct_exchange_is_only_recorded() {
+        let mut planner = Planner::new();
+        planner.init(KernelConfig::default()).unwrap();
+
+        let events = vec![KernelEvent::DirectExchange {
+            question: "what does -R do in chmod".to_string(),
+            answer: "It applies the change recursively.".to_string(),
+        }];
+
+        let graph = planner.process(&events).unwrap();
+        assert!(graph.is_empty());
+        assert_eq!(planner.state().step_count, 0);
+        assert_eq!(planner.state().history.len(), 2);
+        assert_eq!(planner.state().history[1].role, "assistant");
+    }
+
+    #[test]
+    fn test_condensed_history_can_be_restored() {
+        let turns: Vec<Message> = (0..6)
+            .map(|i| Message::new(if i % 2 == 0 { "user" } else { "assistant" }, format!("turn {}", i)))
+            .collect();
+        let mut planner = Planner::new().with_history(turns.clone());
+        let mut segment = CompressedSegment::new(turns[..4].to_vec(), 10);
+        segment.summary = "Four turns".to_string();
+
+        planner.process(&[KernelEvent::HistoryCondensed {
+            replaced: 4,
+            summary: segment.summary_message(),
+            kept: Vec::new(),
+        }]).unwrap();
+        let history = &planner.state().history;
+        assert_eq!(history.len(), 3);
+        assert!(CompressedSegment::is_summary_of(&history[0].content, &segment.id));
+        assert_eq!(history[1].content, "turn 4");
+
+        planner.process(&[KernelEvent::HistoryRestored {
+            segment_id: segment.id.clone(),
+            messages: segment.messages.clone(),
+        }]).unwrap();
+        assert_eq!(planner.state().history, turns);
+    }
+
+    #[test]
+    fn test_rewind_drops_the_last_turn() {
+        let mut segment = CompressedSegment::new(vec![Message::new("user", "old")], 1);
+        segment.summary = "Old turn".to_string();
+        let history = vec![
+            Message::new("user", segment.summary_message()),
+            Message::new("user", "list files"),
+            Message::new("assistant", "Running ls"),
+            Message::new("tool", "a.txt"),
+            Message::new("assistant", "One file: a.txt"),
+        ];
+        let mut planner = Planner::new().with_history(history.clone());
+
+        planner.process(&[KernelEvent::HistoryRewound]).unwrap();
+        assert_eq!(planner.state().history, history[..1]);
+
+        // Only the summary is left, which is kept
+        planner.process(&[KernelEvent::HistoryRewound]).unwrap();
+        assert_eq!(planner.state().history, history[..1]);
+    }
+
     #[test]
     fn test_is_plain_text_response_markdown() {
         // Markdown responses should be accepted as plain text
@@ -749,4 +921,32 @@ mod tests {
         let bullets = "- Item one\n- Item two\n- Item three";
         assert!(Planner::is_plain_text_response(bullets));
     }
+
+    #[test]
+    fn test_native_tool_calls_bypass_text_parsing() {
+        use crate::agent::types::events::{LLMResponse, TokenUsage, FinishReason};
+
+        let mut planner = Planner::new();
+        planner.init(KernelConfig::default()).unwrap();
+
+        let response = LLMResponse {
+            content: String::new(),
+            usage: TokenUsage::default(),
+            model: "test".to_string(),
+            provider: "test".to_string(),
+            finish_reason: FinishReason::ToolCall,
+            structured: None,
+            tool_calls: vec![ToolCall::new("list_files", serde_json::json!({"path": "."}))],
+        };
+        let events = vec![KernelEvent::LLMCompleted {
+            intent_id: IntentId::new(1),
+            response,
+        }];
+
+        let graph = planner.process(&events).unwrap();
+        assert_eq!(graph.len(), 1);
+        let node = graph.nodes().next().unwrap();
+        assert!(matches!(&node.intent, Intent::CallTool(call) if call.name == "list_files"));
+        assert!(planner.state().history.last().unwrap().content.contains("\"a\":\"list_files\""));
+    }
 }
@@ -3,13 +3,16 @@
 //! Determines which tools/commands require user approval before execution.
 
 /// Default dangerous tools that always require approval
-const DANGEROUS_TOOLS: &[&str] = &["shell", "write_file", "rm", "sudo"];
+const DANGEROUS_TOOLS: &[&str] = &["shell", "write_file", "apply_patch", "config", "docker", "kubectl", "run_tests", "git_commit", "http", "lsp", "rm", "sudo"];
 
 /// Dangerous command patterns that require approval
 const DANGEROUS_PATTERNS: &[&str] = &["rm -rf", "sudo", "curl | sh", "wget | sh"];
 
 /// Check if a tool requires approval based on policy
 pub fn requires_approval(tool: &str, args: &str) -> bool {
+    if is_read_only(tool, args) {
+        return false;
+    }
     if DANGEROUS_TOOLS.contains(&tool) {
         return true;
     }
@@ -18,6 +21,31 @@ pub fn requires_approval(tool: &str, args: &str) -> bool {
     DANGEROUS_PATTERNS.iter().any(|p| command.contains(p))
 }
 
+/// Read-only calls of tools that can also change things
+///
+/// `config` only needs approval for `set`; listing settings is harmless.
+/// `apply_patch` with `check` only validates the patch.
+/// `docker` list/logs/inspect only look at containers.
+/// `kubectl` get/describe/logs/events/contexts only read cluster state.
+/// `shell` with `dry_run` only analyses the command.
+/// `git_commit` with `draft` only writes a message.
+fn is_read_only(tool: &str, args: &str) -> bool {
+    let value = match serde_json::from_str::<serde_json::Value>(args) {
+        Ok(value) => value,
+        Err(_) => return false,
+    };
+    let action = value.get("action").and_then(|a| a.as_str()).map(str::to_string);
+    match tool {
+        "shell" => value.get("dry_run").and_then(|v| v.as_bool()) == Some(true),
+        "config" => matches!(action.as_deref(), None | Some("get") | Some("list")),
+        "apply_patch" => action.as_deref() == Some("check"),
+        "git_commit" => matches!(action.as_deref(), None | Some("draft")),
+        "docker" => action.is_some_and(|a| crate::agent::tools::docker::READ_ONLY_ACTIONS.contains(&a.as_str())),
+        "kubectl" => action.is_some_and(|a| crate::agent::tools::kubectl::READ_ONLY_ACTIONS.contains(&a.as_str())),
+        _ => false,
+    }
+}
+
 /// Approval policy configuration
 #[derive(Debug, Clone)]
 pub struct ApprovalPolicy {
@@ -42,6 +70,9 @@ impl Default for ApprovalPolicy {
 impl ApprovalPolicy {
     /// Check if tool/args requires approval under this policy
     pub fn check(&self, tool: &str, args: &str) -> bool {
+        if is_read_only(tool, args) {
+            return false;
+        }
         if self.dangerous_tools.iter().any(|t| t == tool) {
             return true;
         }
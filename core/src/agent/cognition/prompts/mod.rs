@@ -1,5 +1,7 @@
 //! Prompt construction modules
 
 pub mod system;
+pub mod tools;
 
-pub use system::{build_system_prompt, ToolDescription, build_tool_defs};
+pub use system::{build_system_prompt, build_system_prompt_with_time, ToolDescription, build_tool_defs};
+pub use tools::{build_tools_section, with_tools_section};
@@ -2,16 +2,24 @@
 //!
 //! Builds the system prompt with current date/time and format instructions.
 
-/// Build system prompt with current date/time
+use crate::environment::TimeContext;
+
+/// Build system prompt with current date/time in the machine's time zone
 pub fn build_system_prompt() -> String {
-    let now = chrono::Local::now();
-    let date_time_str = now.format("%A, %B %d, %Y at %I:%M:%S %p %Z").to_string();
-    
+    build_system_prompt_with_time(&TimeContext::default())
+}
+
+/// Build system prompt with current date/time in the given zone and locale
+pub fn build_system_prompt_with_time(time: &TimeContext) -> String {
+    let date_time_str = time.describe(chrono::Utc::now());
+
     format!(r#"You are an personal AI assistant that helps users by using tools and reasoning step by step in MyLM framework.
     You are the main agent that can delegate tasks to workers when needed. Your primary role is to remain context aware of user's workloads and manage workers to efficiently accomplish tasks.
     CRITICAL: When you receive responses from document workers (e.g., from `query_file` or `query_chunk_worker`), YOU MUST synthesize and present their findings to the user in your own voice. Do not just blindly output the worker's raw response. Act as the orchestrator who has read the worker's report and is now explaining it to the user clearly.
 
-Current Date and Time: {date_time}
+Current Date and Time:
+{date_time}
+Dates and times the user gives ("tomorrow 9am", "next Friday") are in this time zone.
 
 Response Format (Short-Key JSON - MANDATORY):
 
@@ -66,15 +74,6 @@ Examples:
 {{"t": "Reading a file", "a": "read_file", "i": {{"path": "src/main.rs"}}}}
 {{"t": "User likes Python", "r": "User prefers Python over other languages", "f": "I'll use Python for this task"}}
 
-Tool Selection Guide:
-- read_file: Use to READ contents of a FILE (pass "path": "file_path")
-  - Optional: line_offset (NUMBER, 1-based), n_lines (NUMBER, max 1000)
-  - Example: {{"path": "src/main.rs", "line_offset": 1, "n_lines": 50}}
-- list_files: Use to LIST contents of a DIRECTORY (pass "path": "dir_path")
-  - Example: {{"path": "/home/user"}}
-- shell: Use to EXECUTE shell commands (pass "command": "cmd")
-  - Example: {{"command": "ls -la"}}
-
 ⚠️ CRITICAL RULES:
 1. Check if path is a file or directory BEFORE choosing tool:
    - Use read_file for files: {{"path": "debug.log"}}
@@ -109,6 +108,10 @@ Use suggest mode when:
 - Interactive commands (vim, less, htop, watch)
 - Destructive operations (rm, git rebase, git push --force)
 
+Planning:
+For tasks with three or more steps, first write a plan with the "plan" tool (action "create"), listing dependencies between steps.
+Mark each step "in_progress" when you start it and "done", "failed" or "skipped" when it ends. If the user edited the plan, follow their version.
+
 Worker Delegation Strategy:
 Use the "delegate" tool to spawn workers when tasks can be parallelized or benefit from independent processing.
 This strategy allows you to offload work while continue maintaing repsponsiveness and context awareness.
@@ -159,6 +162,8 @@ pub struct ToolDescription {
     pub name: String,
     pub description: String,
     pub usage: String,
+    /// JSON Schema of the arguments (empty for tools without one)
+    pub parameters: serde_json::Value,
 }
 
 impl From<crate::agent::tools::ToolDescription> for ToolDescription {
@@ -167,6 +172,7 @@ impl From<crate::agent::tools::ToolDescription> for ToolDescription {
             name: desc.name.to_string(),
             description: desc.description.to_string(),
             usage: desc.usage.to_string(),
+            parameters: desc.parameters,
         }
     }
 }
@@ -179,7 +185,7 @@ pub fn build_tool_defs(
         crate::agent::types::intents::ToolDef {
             name: desc.name.clone(),
             description: desc.description.clone(),
-            parameters: serde_json::json!({}),
+            parameters: desc.parameters.clone(),
             usage: Some(desc.usage.clone()),
         }
     }).collect()
@@ -0,0 +1,136 @@
+//! Tool section of the system prompt
+//!
+//! Generated from the tools a session actually has: each tool's
+//! description and usage examples from its `ToolCapability` impl, and its
+//! arguments as declared in `tools::schema`. The same schema checks the
+//! model's arguments before a tool runs, so a new tool shows up in the
+//! prompt exactly as the registry will validate it.
+
+use serde_json::Value;
+
+use super::system::ToolDescription;
+
+/// The "Available Tools" section for `descriptions` (empty without tools)
+pub fn build_tools_section(descriptions: &[ToolDescription]) -> String {
+    if descriptions.is_empty() {
+        return String::new();
+    }
+    let mut section = String::from("Available Tools:\nCall a tool with {\"t\": \"...\", \"a\": \"<tool>\", \"i\": {<arguments>}}. Required arguments are marked; pass numbers and booleans as JSON values, not strings.\n");
+    for tool in descriptions {
+        section.push_str(&format!("\n- `{}`: {}\n", tool.name, tool.description));
+        let arguments = describe_arguments(&tool.parameters);
+        if !arguments.is_empty() {
+            section.push_str("  Arguments:\n");
+            for argument in arguments {
+                section.push_str(&format!("    - {}\n", argument));
+            }
+        }
+        if !tool.usage.is_empty() {
+            section.push_str(&format!("  Usage: {}\n", tool.usage));
+        }
+    }
+    section
+}
+
+/// Append the tool section for `descriptions` to `system_prompt`
+pub fn with_tools_section(system_prompt: &str, descriptions: &[ToolDescription]) -> String {
+    let section = build_tools_section(descriptions);
+    if section.is_empty() {
+        system_prompt.to_string()
+    } else {
+        format!("{}\n\n{}", system_prompt, section.trim_end())
+    }
+}
+
+/// One line per property of an object schema, required ones first
+fn describe_arguments(schema: &Value) -> Vec<String> {
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|names| names.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    let mut names: Vec<&String> = properties.keys().collect();
+    names.sort_by_key(|name| !required.contains(&name.as_str()));
+    names
+        .into_iter()
+        .map(|name| {
+            let property = &properties[name];
+            let mut line = format!("`{}` ({}", name, type_name(property));
+            if required.contains(&name.as_str()) {
+                line.push_str(", required");
+            }
+            line.push(')');
+            if let Some(description) = property.get("description").and_then(Value::as_str) {
+                line.push_str(": ");
+                line.push_str(description);
+            }
+            line
+        })
+        .collect()
+}
+
+/// Short type of a property, e.g. `integer >= 0`, `"a" | "b"`, `string[]`
+fn type_name(schema: &Value) -> String {
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        return options
+            .iter()
+            .map(|option| option.to_string())
+            .collect::<Vec<_>>()
+            .join(" | ");
+    }
+    let name = match schema.get("type") {
+        Some(Value::String(name)) if name == "array" => match schema.get("items") {
+            Some(items) if items.get("properties").is_some() => "object[]".to_string(),
+            Some(items) => format!("{}[]", type_name(items)),
+            None => "array".to_string(),
+        },
+        Some(Value::String(name)) => name.clone(),
+        Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect::<Vec<_>>().join(" | "),
+        _ => "any".to_string(),
+    };
+    match schema.get("minimum").and_then(Value::as_i64) {
+        Some(minimum) => format!("{} >= {}", name, minimum),
+        None => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::tools::schema::tool_schema;
+    use crate::agent::tools::ToolRegistry;
+
+    fn tool(name: &str) -> ToolDescription {
+        ToolDescription {
+            name: name.to_string(),
+            description: format!("The {} tool", name),
+            usage: format!("{{\"a\": \"{}\"}}", name),
+            parameters: tool_schema(name).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_section_follows_declared_schemas() {
+        let section = build_tools_section(&[tool("read_file"), tool("delegate"), tool("git_status")]);
+        assert!(section.contains("- `read_file`: The read_file tool\n  Arguments:\n    - `path` (string, required): File to read\n"));
+        assert!(section.contains("`line_offset` (integer >= 0): First line to read, starting at 1"));
+        assert!(section.contains("`strategy` (\"auto\" | \"direct\" | \"chunked\" | \"search\")"));
+        assert!(section.contains("`workers` (object[], required)"));
+        // Tools without arguments list none
+        assert!(section.contains("- `git_status`: The git_status tool\n  Usage: {\"a\": \"git_status\"}\n"));
+
+        // The registry describes every tool it has through the tool itself
+        let registry: Vec<ToolDescription> = ToolRegistry::new().descriptions().into_iter().map(Into::into).collect();
+        assert!(registry.iter().all(|d| !d.description.is_empty() && !d.usage.is_empty()));
+        let section = build_tools_section(&registry);
+        assert!(section.contains("- `read_file`: Read file contents."));
+        assert!(section.contains("`path` (string, required): File to read"));
+
+        assert_eq!(with_tools_section("prompt", &[]), "prompt");
+        assert!(with_tools_section("prompt", &[tool("shell")]).starts_with("prompt\n\nAvailable Tools:"));
+    }
+}
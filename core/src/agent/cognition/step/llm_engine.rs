@@ -13,19 +13,12 @@ use crate::agent::cognition::{
     input::InputEvent,
     decision::{Transition, AgentDecision, LLMRequest, AgentExitReason, ApprovalRequest},
     error::CognitiveError,
-    prompts::system::build_system_prompt,
+    prompts::system::{build_system_prompt, ToolDescription},
+    prompts::tools::with_tools_section,
 };
 use crate::agent::types::parser::{ShortKeyParser, ParsedResponse};
 use crate::memory::store::sanitize_memory_content;
 
-/// Tool description for dynamic prompt generation
-#[derive(Debug, Clone)]
-pub struct ToolDescription {
-    pub name: String,
-    pub description: String,
-    pub usage: String,
-}
-
 /// LLM-based cognitive engine
 /// 
 /// This engine is PURE - it doesn't make actual LLM calls.
@@ -69,7 +62,7 @@ impl LlmEngine {
             crate::agent::types::intents::ToolDef {
                 name: desc.name.clone(),
                 description: desc.description.clone(),
-                parameters: serde_json::json!({}),
+                parameters: desc.parameters.clone(),
                 usage: Some(desc.usage.clone()),
             }
         }).collect()
@@ -129,16 +122,6 @@ impl Default for LlmEngine {
     }
 }
 
-impl From<crate::agent::tools::ToolDescription> for ToolDescription {
-    fn from(desc: crate::agent::tools::ToolDescription) -> Self {
-        Self {
-            name: desc.name.to_string(),
-            description: desc.description.to_string(),
-            usage: desc.usage.to_string(),
-        }
-    }
-}
-
 impl StepEngine for LlmEngine {
     fn step(
         &mut self,
@@ -188,7 +171,7 @@ impl StepEngine for LlmEngine {
                 let history = state_with_message.history.clone();
                 
                 let context = crate::agent::types::intents::Context::new(scratchpad)
-                    .with_system(self.system_prompt.clone())
+                    .with_system(with_tools_section(&self.system_prompt, &self.tool_descriptions))
                     .with_history(history)
                     .with_tools(self.build_tool_defs());
                 
@@ -212,7 +195,14 @@ impl StepEngine for LlmEngine {
                 let state_with_response = state.clone()
                     .with_message("assistant", llm_resp.content.clone());
                 
-                match self.parse_response(state, &llm_resp.content) {
+                // Prefer structured tool calls; fall back to text parsing. Requests
+                // with tools disable parallel calls, so there is at most one.
+                let parsed = match llm_resp.tool_calls.first() {
+                    Some(call) => Ok(AgentDecision::CallTool(call.clone())),
+                    None => self.parse_response(state, &llm_resp.content),
+                };
+                
+                match parsed {
                     Ok(decision) => {
                         let (final_decision, pending_tool) = if let AgentDecision::CallTool(ref call) = decision {
                             let args_str = call.arguments.to_string();
@@ -269,7 +259,7 @@ impl StepEngine for LlmEngine {
                 );
                 
                 let context = crate::agent::types::intents::Context::new(scratchpad)
-                    .with_system(self.system_prompt.clone())
+                    .with_system(with_tools_section(&self.system_prompt, &self.tool_descriptions))
                     .with_history(history)
                     .with_tools(self.build_tool_defs());
                 
@@ -308,7 +298,7 @@ impl StepEngine for LlmEngine {
                         next_state.increment_rejection();
                         let scratchpad = "Tool execution was denied by user. What should I do instead?".to_string();
                         let context = crate::agent::types::intents::Context::new(scratchpad)
-                            .with_system(self.system_prompt.clone())
+                            .with_system(with_tools_section(&self.system_prompt, &self.tool_descriptions))
                             .with_tools(self.build_tool_defs());
                         let decision = AgentDecision::RequestLLM(LLMRequest {
                             context,
@@ -334,7 +324,7 @@ impl StepEngine for LlmEngine {
                 
                 let scratchpad = format!("{}\n\nWhat should I do next?", output);
                 let context = crate::agent::types::intents::Context::new(scratchpad)
-                    .with_system(self.system_prompt.clone())
+                    .with_system(with_tools_section(&self.system_prompt, &self.tool_descriptions))
                     .with_tools(self.build_tool_defs());
                 let decision = AgentDecision::RequestLLM(LLMRequest {
                     context,
@@ -382,15 +372,7 @@ impl StepEngine for LlmEngine {
     }
     
     fn requires_approval(&self, tool: &str, args: &str) -> bool {
-        let dangerous_tools = ["shell", "write_file", "rm", "sudo"];
-        let dangerous_patterns = ["rm -rf", "sudo", "curl | sh", "wget | sh"];
-        
-        if dangerous_tools.contains(&tool) {
-            return true;
-        }
-        
-        let command = format!("{} {}", tool, args);
-        dangerous_patterns.iter().any(|p| command.contains(p))
+        crate::agent::cognition::policy::requires_approval(tool, args)
     }
 }
 
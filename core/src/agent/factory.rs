@@ -3,19 +3,27 @@
 //! Creates agent sessions from unified Config.
 //! This is the main entry point for creating agent instances.
 
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use crate::config::{Config, BridgeError, config_to_llm_config, config_to_kernel_config};
-use crate::provider::LlmClient;
+use crate::provider::rate_limiter::{RateLimitConfig, RateLimiter};
+use crate::provider::{LlmClient, ModelRole, UsageLog};
+use crate::environment::TimeContext;
+use crate::idle::{IdleResources, Suspendable};
+use crate::scheduler::WorkerPool;
+use crate::agent::reload::SessionReloader;
 use crate::agent::{
     // Session types
     runtime::orchestrator::orchestrator::AgencySession,
-    runtime::orchestrator::ContractRuntime,
-    runtime::capabilities::InMemoryTransport,
-    tools::{ToolRegistry, DelegateTool},
+    runtime::orchestrator::{Condenser, ContractRuntime, FastPath},
+    runtime::capabilities::{InMemoryTransport, LlmClientCapability, SessionPlan},
+    tools::{ToolRegistry, AnnotateTerminalTool, DelegateTool, CodeIndexTool, ConfigTool, DockerTool, GitCommitTool, HttpTool, KubectlTool, LspTool, LiveConfig, PatchQueue, PlanTool, ScratchpadTool, SharedScratchpad, UndoJournal},
     runtime::core::terminal::TerminalExecutor,
+    runtime::core::SandboxedTerminalExecutor,
     runtime::core::ApprovalCapability,
     runtime::core::LLMCapability,
+    runtime::governance::{SecretGuard, SessionBudget},
     // Coordination
     runtime::orchestrator::commonbox::Commonbox,
     // Cognition
@@ -25,6 +33,14 @@ use crate::agent::{
     memory::AgentMemoryManager,
 };
 
+/// Memory store sessions use unless a factory is given its own path
+/// (shared with the Javi frontend)
+pub fn shared_memory_path() -> Option<PathBuf> {
+    dirs::config_dir()
+        .map(|d| d.join("javi").join("memory"))
+        .or_else(|| dirs::data_dir().map(|d| d.join("javi").join("memory")))
+}
+
 /// Factory for creating agent sessions from configuration
 /// 
 /// This is the main entry point for creating agent instances.
@@ -54,6 +70,38 @@ pub struct AgentSessionFactory {
     /// Not cloned - workers get their own factory without commonbox
     #[allow(clippy::skip_vec_init)]
     commonbox: Option<Arc<Commonbox>>,
+    /// Cost/token budget shared by the session and its workers
+    budget: Arc<SessionBudget>,
+    /// Config as edited by the config tool; workers are built from it
+    live_config: LiveConfig,
+    /// Memory store location, overriding the shared default (e.g. per server user)
+    memory_path: Option<PathBuf>,
+    /// Journal, user profile and memory feedback location (e.g. per server user)
+    data_dir: Option<PathBuf>,
+    /// Tools the session may see and run (None = every available tool)
+    allowed_tools: Option<Vec<String>>,
+    /// Changeset queue that file edits are staged in, shared with workers
+    patch_queue: Arc<PatchQueue>,
+    /// Previous contents of the files sessions write, shared with workers
+    undo_journal: Arc<UndoJournal>,
+    /// The frontend shows a terminal pane the agent can annotate
+    terminal_pane: bool,
+    /// Concurrency limit for delegated workers, shared with the frontend
+    worker_pool: Arc<WorkerPool>,
+    /// Plan written through the plan tool, shared with the frontend
+    plan: Arc<SessionPlan>,
+    /// Notes written through the scratchpad tool, shared with the frontend
+    scratchpad: SharedScratchpad,
+    /// What sessions of this factory and their workers release when idle
+    idle_resources: Arc<IdleResources>,
+    /// Applies config changes to the sessions of this factory
+    reloader: Arc<SessionReloader>,
+    /// Request and token budgets shared by the session and its workers
+    rate_limiter: Option<Arc<RateLimiter>>,
+    /// Refuse tool calls that would change files or system state
+    read_only: bool,
+    /// Give sessions the config tool, which edits the global config file
+    config_tool: bool,
 }
 
 /// Configuration for worker session creation
@@ -95,12 +143,40 @@ pub enum FactoryError {
 impl AgentSessionFactory {
     /// Create a new factory with the given configuration
     pub fn new(config: Config) -> Self {
+        let budget = Arc::new(SessionBudget::from_config(&config));
+        let live_config = Arc::new(std::sync::RwLock::new(config.clone()));
+        let worker_pool = Arc::new(WorkerPool::from_settings(&config.features.worker_pool));
+        // A project overlay may restrict the tools; `with_allowed_tools` overrides it
+        let allowed_tools = config.project_tools().map(<[String]>::to_vec);
+        let reloader = Arc::new(SessionReloader::new(Arc::clone(&live_config), Arc::clone(&worker_pool)));
+        let rate_limiter = RateLimitConfig::shared_budget(&config.features.rate_limits)
+            .map(|limits| Arc::new(RateLimiter::new(limits)));
+        let read_only = config.read_only || config.active_profile().read_only;
         Self { 
             config,
             terminal: None,
             approval: None,
             llm: None,
             commonbox: None,
+            budget,
+            live_config,
+            memory_path: None,
+            data_dir: None,
+            allowed_tools,
+            patch_queue: Arc::new(PatchQueue::new()),
+            undo_journal: Arc::new(UndoJournal::for_session(
+                &std::env::current_dir().unwrap_or_default(),
+                &uuid::Uuid::new_v4().simple().to_string(),
+            )),
+            terminal_pane: false,
+            worker_pool,
+            plan: Arc::new(SessionPlan::new()),
+            scratchpad: crate::agent::tools::create_shared_scratchpad(),
+            idle_resources: Arc::new(IdleResources::new()),
+            reloader,
+            rate_limiter,
+            read_only,
+            config_tool: true,
         }
     }
     
@@ -140,6 +216,147 @@ impl AgentSessionFactory {
         self
     }
     
+    /// Keep this factory's memories in `path` instead of the shared store
+    pub fn with_memory_path(mut self, path: PathBuf) -> Self {
+        self.memory_path = Some(path);
+        self
+    }
+    
+    /// Keep the journal, user profile and memory ratings in `dir` instead of
+    /// the shared data directory
+    pub fn with_data_dir(mut self, dir: PathBuf) -> Self {
+        self.data_dir = Some(dir);
+        self
+    }
+    
+    /// Sessions are shown next to a terminal pane; enables `annotate_terminal`
+    pub fn with_terminal_pane(mut self) -> Self {
+        self.terminal_pane = true;
+        self
+    }
+    
+    /// Restrict sessions to the named tools; others are hidden from the
+    /// model and refused if called anyway
+    pub fn with_allowed_tools(mut self, tools: Vec<String>) -> Self {
+        self.allowed_tools = Some(tools);
+        self
+    }
+    
+    /// Refuse tool calls that would change files or system state, whatever
+    /// the profile says
+    pub fn with_read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+    
+    /// Leave out the config tool, e.g. in server sessions, where the
+    /// config file is the server's and shared by every user
+    pub fn without_config_tool(mut self) -> Self {
+        self.config_tool = false;
+        self
+    }
+    
+    /// Share an existing session budget
+    /// 
+    /// By default each factory starts its own budget from `features.budget`.
+    pub fn with_budget(mut self, budget: Arc<SessionBudget>) -> Self {
+        self.budget = budget;
+        self
+    }
+    
+    /// Budget charged by sessions created from this factory
+    pub fn budget(&self) -> Arc<SessionBudget> {
+        Arc::clone(&self.budget)
+    }
+    
+    /// Queue that stages file edits while a changeset is open
+    pub fn patch_queue(&self) -> Arc<PatchQueue> {
+        Arc::clone(&self.patch_queue)
+    }
+    
+    /// Journal of the files sessions wrote, for undoing a turn
+    pub fn undo_journal(&self) -> Arc<UndoJournal> {
+        Arc::clone(&self.undo_journal)
+    }
+    
+    /// Pool that delegated workers wait in for a slot
+    pub fn worker_pool(&self) -> Arc<WorkerPool> {
+        Arc::clone(&self.worker_pool)
+    }
+    
+    /// Plan of the sessions created by this factory (workers keep their own)
+    pub fn plan(&self) -> Arc<SessionPlan> {
+        Arc::clone(&self.plan)
+    }
+    
+    /// Scratchpad of the sessions created by this factory (workers keep their own)
+    pub fn scratchpad(&self) -> SharedScratchpad {
+        Arc::clone(&self.scratchpad)
+    }
+    
+    /// Resources to release when the frontend finds the session idle
+    pub fn idle_resources(&self) -> Arc<IdleResources> {
+        Arc::clone(&self.idle_resources)
+    }
+    
+    /// Reloader for the sessions this factory creates
+    pub fn reloader(&self) -> Arc<SessionReloader> {
+        Arc::clone(&self.reloader)
+    }
+    
+    /// Latest config, including changes made through the config tool
+    pub fn current_config(&self) -> Config {
+        self.live_config.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+    
+    /// Terminal for sessions on `profile_name`: a sandbox when the profile
+    /// configures one, otherwise the factory's terminal (if any)
+    fn terminal_for(&self, config: &Config, profile_name: &str) -> Result<Option<Arc<dyn TerminalExecutor>>, FactoryError> {
+        let sandbox = config
+            .profiles
+            .get(profile_name)
+            .map(|profile| &profile.sandbox)
+            .filter(|sandbox| !sandbox.is_disabled());
+        match sandbox {
+            Some(settings) => {
+                let executor = SandboxedTerminalExecutor::new(settings.clone()).map_err(FactoryError::Creation)?;
+                crate::info_log!(
+                    "[FACTORY] Profile '{}' runs commands in a {:?} sandbox at {}",
+                    profile_name, settings.backend, executor.workdir().display()
+                );
+                Ok(Some(Arc::new(executor)))
+            }
+            None => Ok(self.terminal.clone()),
+        }
+    }
+    
+    /// Log `client`'s requests on `profile` as `role` usage when
+    /// `features.telemetry` is on
+    fn track_usage(&self, client: &LlmClient, role: ModelRole, profile: &str) {
+        if !self.current_config().features.telemetry {
+            return;
+        }
+        if let Some(log) = UsageLog::default_log() {
+            client.set_usage_callback(log.recorder(role, profile));
+        }
+    }
+    
+    /// `client` drawing on the shared rate limits, when any are configured
+    fn limit_rate(&self, client: LlmClient, role: ModelRole) -> LlmClient {
+        match &self.rate_limiter {
+            Some(limiter) => client
+                .with_rate_limiter(Arc::clone(limiter))
+                .set_worker(role == ModelRole::Worker),
+            None => client,
+        }
+    }
+    
+    /// Workers are read-only with their parent or with a read-only
+    /// `worker` profile
+    fn worker_read_only(&self, config: &Config) -> bool {
+        self.read_only || config.profiles.get("worker").is_some_and(|profile| profile.read_only)
+    }
+
     /// Create ContractRuntime with optional custom LLM and memory provider
     fn create_runtime(
         &self, 
@@ -147,7 +364,7 @@ impl AgentSessionFactory {
         tools: Arc<ToolRegistry>,
         memory_provider: Option<Arc<dyn crate::agent::memory::MemoryProvider>>,
     ) -> ContractRuntime {
-        match &self.llm {
+        let runtime = match &self.llm {
             Some(custom_llm) => {
                 crate::info_log!("[FACTORY] Using custom LLM capability");
                 // Custom LLM doesn't support memory injection currently
@@ -159,7 +376,69 @@ impl AgentSessionFactory {
             None => {
                 ContractRuntime::with_tools_and_memory(llm_client, tools, memory_provider)
             }
+        };
+        runtime.with_budget(Arc::clone(&self.budget))
+    }
+    
+    /// Condenser for sessions of `client`, summarizing with the same model
+    fn create_condenser(&self, client: &Arc<LlmClient>) -> Condenser {
+        let llm_config = client.config();
+        let llm: Arc<dyn LLMCapability> = match &self.llm {
+            Some(custom_llm) => Arc::clone(custom_llm),
+            None => {
+                let context_manager = crate::conversation::ContextManager::from_llm_client(client);
+                Arc::new(LlmClientCapability::new(Arc::clone(client), Arc::new(tokio::sync::Mutex::new(context_manager))))
+            }
+        };
+        Condenser::new(llm, llm_config.max_context_tokens)
+            .with_threshold(llm_config.condense_threshold)
+            .with_tokenizer(crate::conversation::Tokenizer::for_model(&llm_config.model))
+    }
+    
+    /// Fast path for trivial questions, when `features.fast_path` is enabled
+    ///
+    /// A fast profile that cannot be built is logged and skipped rather than
+    /// failing the session.
+    fn create_fast_path(&self, profile_name: &str) -> Option<FastPath> {
+        let settings = &self.config.features.fast_path;
+        if !settings.enabled {
+            return None;
         }
+        let llm: Arc<dyn LLMCapability> = match &self.llm {
+            Some(custom_llm) => Arc::clone(custom_llm),
+            None => {
+                let profile = settings.profile.as_deref().unwrap_or(profile_name);
+                let client = config_to_llm_config(&self.config, profile)
+                    .map_err(|e| e.to_string())
+                    .and_then(|mut llm_config| {
+                        if let Some(ref model) = settings.model {
+                            llm_config.model = model.clone();
+                        }
+                        LlmClient::new(llm_config).map_err(|e| e.to_string())
+                    })
+                    .map(|client| self.limit_rate(client, ModelRole::Categorizer));
+                let client = match client {
+                    Ok(client) => {
+                        self.track_usage(&client, ModelRole::Categorizer, profile);
+                        Arc::new(client)
+                    }
+                    Err(e) => {
+                        crate::warn_log!("[FACTORY] Fast path disabled, could not create its model: {}", e);
+                        return None;
+                    }
+                };
+                crate::info_log!("[FACTORY] Enabling fast path with model {}", client.model());
+                let context_manager = crate::conversation::ContextManager::new(crate::conversation::ContextConfig::default());
+                let capability = LlmClientCapability::new(client, Arc::new(tokio::sync::Mutex::new(context_manager)));
+                self.reloader.register_fast_path_client(profile_name, capability.shared_client());
+                Arc::new(capability)
+            }
+        };
+        Some(
+            FastPath::new(llm)
+                .with_model_classifier(settings.classify_with_model)
+                .with_budget(Arc::clone(&self.budget)),
+        )
     }
     
     /// Create a new session for the specified profile
@@ -186,7 +465,9 @@ impl AgentSessionFactory {
         let llm_config = config_to_llm_config(&self.config, profile_name)?;
         
         // Step 2: Create LLM client
-        let llm_client = Arc::new(LlmClient::new(llm_config)?);
+        let role = if profile_name == "worker" { ModelRole::Worker } else { ModelRole::Main };
+        let llm_client = Arc::new(self.limit_rate(LlmClient::new(llm_config)?, role));
+        self.track_usage(&llm_client, role, profile_name);
         
         // Step 3: Create output channel for streaming events FIRST
         // (needed for both runtime and delegate tool)
@@ -200,9 +481,7 @@ impl AgentSessionFactory {
         crate::info_log!("[FACTORY] features.memory = {}", self.config.features.memory);
         
         // Use Javi's memory path for consistency with Tauri frontend
-        let javi_memory_path = dirs::config_dir()
-            .map(|d| d.join("javi").join("memory"))
-            .or_else(|| dirs::data_dir().map(|d| d.join("javi").join("memory")));
+        let javi_memory_path = self.memory_path.clone().or_else(shared_memory_path);
         
         // Get memory settings from config (with defaults)
         let memory_settings = &self.config.features.memory_settings;
@@ -211,18 +490,26 @@ impl AgentSessionFactory {
             MemoryConfig {
                 enabled: true,
                 storage_path: javi_memory_path,
+                data_dir: self.data_dir.clone(),
                 context_window: memory_settings.context_window,
                 semantic_search_limit: memory_settings.semantic_search_limit,
                 tool_search_limit: memory_settings.tool_search_limit,
+                embedding: memory_settings.embedding.clone(),
+                retention: memory_settings.retention.clone(),
+                backend: memory_settings.backend,
                 ..MemoryConfig::default()
             }
         } else {
             MemoryConfig {
                 enabled: false,
                 storage_path: javi_memory_path,
+                data_dir: self.data_dir.clone(),
                 context_window: memory_settings.context_window,
                 semantic_search_limit: memory_settings.semantic_search_limit,
                 tool_search_limit: memory_settings.tool_search_limit,
+                embedding: memory_settings.embedding.clone(),
+                retention: memory_settings.retention.clone(),
+                backend: memory_settings.backend,
                 ..MemoryConfig::default()
             }
         };
@@ -234,7 +521,10 @@ impl AgentSessionFactory {
             match AgentMemoryManager::new(memory_config).await {
                 Ok(mm) => {
                     crate::info_log!("[FACTORY] Memory manager initialized successfully");
-                    Some(Arc::new(mm))
+                    let mm = Arc::new(mm);
+                    let suspendable: Arc<dyn Suspendable> = mm.clone();
+                    self.idle_resources.register("memory store", &suspendable);
+                    Some(mm)
                 }
                 Err(e) => {
                     crate::warn_log!("[FACTORY] Failed to initialize memory manager: {}", e);
@@ -247,13 +537,17 @@ impl AgentSessionFactory {
         };
         
         // Step 5: Create ToolRegistry with basic tools
-        let tool_registry = ToolRegistry::new();
+        let tool_registry = ToolRegistry::new()
+            .with_secret_guard(SecretGuard::from_config(&self.config))
+            .with_command_limits(self.config.features.command_limits.clone())
+            .with_patch_queue(Arc::clone(&self.patch_queue))
+            .with_undo_journal(Arc::clone(&self.undo_journal));
         
         // Step 5a: Add memory tool if memory manager is available
         let tool_registry = if let Some(ref mm) = memory_manager {
             crate::info_log!("[FACTORY] Enabling memory tool");
             tool_registry.with_memory_and_limit(
-                Arc::clone(mm.vector_store()),
+                Arc::clone(mm.store()),
                 memory_settings.tool_search_limit
             )
         } else {
@@ -261,7 +555,7 @@ impl AgentSessionFactory {
         };
         
         // Step 5b: Add scratchpad tool for agent-local persistent notes
-        let tool_registry = tool_registry.with_scratchpad(crate::agent::tools::ScratchpadTool::new_standalone());
+        let tool_registry = tool_registry.with_scratchpad(ScratchpadTool::new(Arc::clone(&self.scratchpad)));
         
         // Step 5c: Add search_files tool for full-text file search
         let tool_registry = match tool_registry.with_search_files(None) {
@@ -272,16 +566,20 @@ impl AgentSessionFactory {
             Err(e) => {
                 crate::warn_log!("[FACTORY] Failed to enable search_files: {}", e);
                 // Start fresh with a new registry since the old one was consumed
-                let fresh = ToolRegistry::new();
+                let fresh = ToolRegistry::new()
+                    .with_secret_guard(SecretGuard::from_config(&self.config))
+                    .with_command_limits(self.config.features.command_limits.clone())
+                    .with_patch_queue(Arc::clone(&self.patch_queue))
+                    .with_undo_journal(Arc::clone(&self.undo_journal));
                 // Re-add memory if it was enabled
                 if let Some(ref mm) = memory_manager {
                     fresh.with_memory_and_limit(
-                        Arc::clone(mm.vector_store()),
+                        Arc::clone(mm.store()),
                         memory_settings.tool_search_limit
                     )
                 } else {
                     fresh
-                }.with_scratchpad(crate::agent::tools::ScratchpadTool::new_standalone())
+                }.with_scratchpad(ScratchpadTool::new(Arc::clone(&self.scratchpad)))
             }
         };
         
@@ -316,6 +614,22 @@ impl AgentSessionFactory {
                 approval: self.approval.clone(),
                 llm: self.llm.clone(),
                 commonbox: None,
+                budget: Arc::clone(&self.budget),
+                live_config: Arc::clone(&self.live_config),
+                memory_path: self.memory_path.clone(),
+                data_dir: self.data_dir.clone(),
+                allowed_tools: self.allowed_tools.clone(),
+                patch_queue: Arc::clone(&self.patch_queue),
+                undo_journal: Arc::clone(&self.undo_journal),
+                terminal_pane: false,
+                worker_pool: Arc::clone(&self.worker_pool),
+                plan: Arc::new(SessionPlan::new()),
+                scratchpad: crate::agent::tools::create_shared_scratchpad(),
+                idle_resources: Arc::clone(&self.idle_resources),
+                reloader: Arc::clone(&self.reloader),
+                rate_limiter: self.rate_limiter.clone(),
+                read_only: self.read_only,
+                config_tool: self.config_tool,
             };
             
             // Create delegate tool with output sender for worker events
@@ -332,6 +646,120 @@ impl AgentSessionFactory {
             tool_registry
         };
         
+        // Step 5f: Add config tool so the agent can change settings on request
+        let tool_registry = match Config::default_path().filter(|_| self.config_tool) {
+            Some(path) => tool_registry.with_config_tool(
+                ConfigTool::new(path, Arc::clone(&self.live_config))
+                    .with_budget(Arc::clone(&self.budget))
+                    .with_reloader(Arc::clone(&self.reloader))
+                    .with_output_sender(crate::agent::runtime::orchestrator::OutputSender::Broadcast(output_tx.clone())),
+            ),
+            None => tool_registry,
+        };
+        
+        // Step 5g: Add http tool when the profile or project allows any hosts
+        let http = HttpTool::for_profile(
+            self.config.profiles.get(profile_name).unwrap_or_else(|| self.config.active_profile()),
+        );
+        let tool_registry = if http.is_enabled() {
+            crate::info_log!("[FACTORY] Enabling http tool for allowlisted hosts");
+            tool_registry.with_http(http)
+        } else {
+            tool_registry
+        };
+        
+        // Step 5h: Add docker tool when docker is installed
+        let tool_registry = match DockerTool::detect() {
+            Some(docker) => {
+                crate::info_log!("[FACTORY] Enabling docker tool");
+                tool_registry.with_docker(docker)
+            }
+            None => tool_registry,
+        };
+        
+        // Step 5i: Add kubectl tool when kubectl is installed
+        let tool_registry = match KubectlTool::detect() {
+            Some(kubectl) => {
+                crate::info_log!("[FACTORY] Enabling kubectl tool");
+                tool_registry.with_kubectl(kubectl)
+            }
+            None => tool_registry,
+        };
+        
+        // Step 5j: Add lsp tool when enabled and a language server for the project is installed
+        let lsp = std::env::current_dir()
+            .ok()
+            .filter(|_| self.config.features.lsp)
+            .and_then(|root| LspTool::detect(&root));
+        let tool_registry = match lsp {
+            Some(lsp) => {
+                crate::info_log!("[FACTORY] Enabling lsp tool ({})", lsp.server());
+                let server: Arc<dyn Suspendable> = lsp.idle_handle();
+                self.idle_resources.register("language server", &server);
+                tool_registry.with_lsp(lsp.with_secret_guard(Arc::new(SecretGuard::from_config(&self.config))))
+            }
+            None => tool_registry,
+        };
+        
+        // Step 5k: Add semantic code search when the repository index is enabled
+        let tool_registry = match std::env::current_dir() {
+            Ok(root) if self.config.features.code_index => {
+                crate::info_log!("[FACTORY] Enabling code_search_semantic for {}", root.display());
+                tool_registry.with_code_index(
+                    CodeIndexTool::new(&root).with_secret_guard(Arc::new(SecretGuard::from_config(&self.config))),
+                )
+            }
+            _ => tool_registry,
+        };
+        
+        // Step 5l: Let the agent annotate the terminal pane, when the frontend has one
+        let tool_registry = if self.terminal_pane {
+            crate::info_log!("[FACTORY] Enabling annotate_terminal tool");
+            tool_registry.with_annotate_terminal(AnnotateTerminalTool::new(
+                crate::agent::runtime::orchestrator::OutputSender::Broadcast(output_tx.clone()),
+            ))
+        } else {
+            tool_registry
+        };
+        
+        // Step 5m: Plan tool over the factory's session plan
+        let tool_registry = tool_registry.with_plan(PlanTool::new(self.plan.clone()));
+        
+        // Step 5n: Add git_commit in a git repository, drafting with the worker model
+        let tool_registry = if crate::update::git::is_git_repo() {
+            match crate::config::worker_llm_config(&self.config).map_err(anyhow::Error::from).and_then(LlmClient::new) {
+                Ok(client) => {
+                    let client = Arc::new(self.limit_rate(client, ModelRole::Worker));
+                    self.track_usage(&client, ModelRole::Worker, "worker");
+                    crate::info_log!("[FACTORY] Enabling git_commit tool");
+                    tool_registry.with_git_commit(GitCommitTool::new(client))
+                }
+                Err(e) => {
+                    crate::warn_log!("[FACTORY] git_commit disabled, no worker model: {}", e);
+                    tool_registry
+                }
+            }
+        } else {
+            tool_registry
+        };
+        
+        // Step 5o: Apply the tool allowlist, if any
+        let tool_registry = match self.allowed_tools {
+            Some(ref allowed) => {
+                crate::info_log!("[FACTORY] Restricting session to tools: {:?}", allowed);
+                tool_registry.with_allowlist(allowed)
+            }
+            None => tool_registry,
+        };
+        
+        // Step 5p: Read-only mode hides and refuses mutating tools
+        let tool_registry = if self.read_only {
+            crate::info_log!("[FACTORY] Read-only mode: refusing tools that change the system");
+            tool_registry.with_read_only()
+        } else {
+            tool_registry
+        };
+        
         let tool_descriptions: Vec<ToolDescription> = tool_registry.descriptions()
             .into_iter()
             .map(|d| d.into())
@@ -346,11 +774,14 @@ impl AgentSessionFactory {
         // Step 7: Create ContractRuntime with LLM client, tools, memory provider, and output sender
         let mut runtime = self.create_runtime(llm_client.clone(), Arc::new(tool_registry), memory_provider)
             .with_output_sender(output_tx.clone());
+        if let Some(client) = runtime.shared_llm_client() {
+            self.reloader.register_client(profile_name, client);
+        }
         
-        // Step 8: Attach terminal executor if provided
-        if let Some(ref terminal) = self.terminal {
+        // Step 8: Attach terminal executor (sandboxed if the profile asks for it)
+        if let Some(terminal) = self.terminal_for(&self.config, profile_name)? {
             crate::info_log!("[FACTORY] Attaching terminal executor to runtime");
-            runtime = runtime.with_terminal(Arc::clone(terminal));
+            runtime = runtime.with_terminal(terminal);
         }
         
         // Step 9: Attach approval capability if provided
@@ -365,6 +796,8 @@ impl AgentSessionFactory {
         // Step 11: Create planner directly with dynamic tools
         // NOTE: Memory tool is now available for explicit memory operations via ToolRegistry
         let mut kernel_builder = Planner::new()
+            .with_time_context(&TimeContext::detect(&self.config.features.time))
+            .with_project_instructions(self.config.project_instructions())
             .with_tool_descriptions(tool_descriptions);
         
         // Seed with initial history if provided (for session resumption)
@@ -385,6 +818,15 @@ impl AgentSessionFactory {
         // The runtime's MemoryProvider and tools hold references - this ensures the Arcs stay alive
         let session = AgencySession::new_with_memory(kernel, runtime, transport, output_tx, memory_manager);
         
+        // Step 14: Route trivial questions to the fast model, if enabled
+        let session = match self.create_fast_path(profile_name) {
+            Some(fast_path) => session.with_fast_path(fast_path),
+            None => session,
+        };
+        
+        // Step 15: Summarize old turns as history nears the context window
+        let session = session.with_condenser(self.create_condenser(&llm_client));
+        
         Ok(session)
     }
     
@@ -439,13 +881,23 @@ impl AgentSessionFactory {
         
         // Use worker profile for LLM config but don't use create_session 
         // to avoid memory injection
-        let llm_config = config_to_llm_config(&self.config, "worker")
+        let config = self.current_config();
+        let llm_config = config_to_llm_config(&config, "worker")
             .map_err(FactoryError::Config)?;
-        let llm_client = Arc::new(LlmClient::new(llm_config)?);
+        let llm_client = Arc::new(self.limit_rate(LlmClient::new(llm_config)?, ModelRole::Worker));
+        self.track_usage(&llm_client, ModelRole::Worker, "worker");
         
         // Create a simple tool registry for workers - no memory
         let tool_registry = ToolRegistry::new()
-            .with_scratchpad(crate::agent::tools::ScratchpadTool::new_standalone());
+            .with_secret_guard(SecretGuard::from_config(&config))
+            .with_command_limits(config.features.command_limits.clone())
+            .with_scratchpad(crate::agent::tools::ScratchpadTool::new_standalone())
+            .with_undo_journal(Arc::clone(&self.undo_journal));
+        let tool_registry = if self.worker_read_only(&config) {
+            tool_registry.with_read_only()
+        } else {
+            tool_registry
+        };
         
         // Create runtime WITHOUT memory provider
         let (output_tx, _): (tokio::sync::broadcast::Sender<crate::agent::runtime::orchestrator::OutputEvent>, _) = tokio::sync::broadcast::channel(100);
@@ -458,7 +910,9 @@ impl AgentSessionFactory {
         }
         
         // Create kernel
-        let kernel = Planner::new();
+        let kernel = Planner::new()
+            .with_time_context(&TimeContext::detect(&self.config.features.time))
+            .with_project_instructions(self.config.project_instructions());
         
         // Create transport
         let transport = InMemoryTransport::new(100);
@@ -540,13 +994,19 @@ impl AgentSessionFactory {
         crate::info_log!("[FACTORY] Creating configured worker session for {} with {} allowed tools", 
             worker_id, config.allowed_tools.len());
         
-        // Step 1: Create LLM config from unified Config
-        let llm_config = config_to_llm_config(&self.config, "worker")?;
-        let llm_client = Arc::new(LlmClient::new(llm_config)?);
+        // Step 1: Create LLM config from the live config so worker settings
+        // changed mid-session apply to the next worker
+        let live_config = self.current_config();
+        let llm_config = config_to_llm_config(&live_config, "worker")?;
+        let llm_client = Arc::new(self.limit_rate(LlmClient::new(llm_config)?, ModelRole::Worker));
+        self.track_usage(&llm_client, ModelRole::Worker, "worker");
         
         // Step 2: Create tool registry with all tools + agent-local scratchpad + commonboard
         let tool_registry = ToolRegistry::new()
-            .with_scratchpad(crate::agent::tools::ScratchpadTool::new_standalone());
+            .with_secret_guard(SecretGuard::from_config(&live_config))
+            .with_command_limits(live_config.features.command_limits.clone())
+            .with_scratchpad(crate::agent::tools::ScratchpadTool::new_standalone())
+            .with_undo_journal(Arc::clone(&self.undo_journal));
         
         // Add commonboard if commonbox is available (for coordination)
         // Note: Use config.commonbox (passed from parent) rather than self.commonbox
@@ -558,6 +1018,11 @@ impl AgentSessionFactory {
         } else {
             tool_registry
         };
+        let tool_registry = if self.worker_read_only(&live_config) {
+            tool_registry.with_read_only()
+        } else {
+            tool_registry
+        };
         
         // Step 3: Filter tool descriptions based on allowed_tools
         let all_descriptions = tool_registry.descriptions();
@@ -586,10 +1051,16 @@ impl AgentSessionFactory {
         let mut runtime = self.create_runtime(llm_client.clone(), Arc::new(tool_registry), None)
             .with_output_sender(broadcast_tx.clone());
         
-        // Step 6: Attach terminal executor if provided
-        if let Some(ref terminal) = self.terminal {
+        // Step 5a: Space the worker's LLM requests when the pool is rate limited
+        if let Some(limit) = self.worker_pool.request_limit() {
+            runtime = runtime.with_request_limit(limit);
+        }
+        
+        // Step 6: Attach terminal executor; auto-approved worker commands run
+        // in the worker profile's sandbox when it has one
+        if let Some(terminal) = self.terminal_for(&live_config, "worker")? {
             crate::info_log!("[FACTORY] Attaching terminal executor to worker runtime");
-            runtime = runtime.with_terminal(Arc::clone(terminal));
+            runtime = runtime.with_terminal(terminal);
         }
         
         // Step 7: Workers use restricted approval based on allowed/forbidden command patterns
@@ -610,6 +1081,7 @@ impl AgentSessionFactory {
         // Step 8: Create kernel with filtered tool descriptions
         crate::info_log!("[FACTORY] Creating kernel with {} tool descriptions", filtered_descriptions.len());
         let kernel = Planner::new()
+            .with_time_context(&TimeContext::detect(&self.config.features.time))
             .with_tool_descriptions(filtered_descriptions);
         crate::info_log!("[FACTORY] Kernel created successfully");
         
@@ -683,6 +1155,13 @@ mod tests {
                 tested_at: None,
                 test_error: None,
                 web_search: crate::config::WebSearchConfig::default(),
+                native_tools: false,
+                prompt_caching: true,
+                write_permissions: Default::default(),
+                http_permissions: Default::default(),
+                fallbacks: Vec::new(),
+                sandbox: Default::default(),
+                read_only: false,
             },
         );
         
@@ -0,0 +1,160 @@
+//! Editing stored memories
+//!
+//! The F3 memory browser and `mylm memory edit` open an editor on a memory's
+//! category and content, laid out like the teach template: header fields, a
+//! `---` line, then the content. Whatever changed is written back.
+
+use anyhow::{bail, Result};
+
+use super::teach::{edit_text, CONTENT_SEPARATOR};
+use crate::memory::backend::MemoryBackend;
+use crate::memory::store::Memory;
+
+/// The editable fields of a memory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryEdit {
+    pub category: Option<String>,
+    pub content: String,
+}
+
+impl MemoryEdit {
+    /// The fields as `memory` has them now
+    pub fn of(memory: &Memory) -> Self {
+        Self {
+            category: memory.category_id.clone().filter(|c| !c.is_empty()),
+            content: memory.content.clone(),
+        }
+    }
+
+    /// Editor text for memory `id`
+    pub fn template(&self, id: i64) -> String {
+        format!(
+            "# Edit memory {}. Lines starting with '#' above the --- line are ignored.\n\
+             # Save the file unchanged to cancel.\n\
+             #\n\
+             # category: any name, or empty for none (starred marks a favourite)\n\
+             category: {}\n{}\n{}\n",
+            id,
+            self.category.as_deref().unwrap_or_default(),
+            CONTENT_SEPARATOR,
+            self.content.trim()
+        )
+    }
+
+    /// Parse an edited template
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut lines = text.lines();
+        let mut category = None;
+        let mut found_separator = false;
+        for line in lines.by_ref() {
+            let line = line.trim();
+            if line == CONTENT_SEPARATOR {
+                found_separator = true;
+                break;
+            }
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.split_once(':') {
+                Some((key, value)) if key.trim().eq_ignore_ascii_case("category") => {
+                    category = Some(value.trim().to_string()).filter(|c| !c.is_empty());
+                }
+                Some((key, _)) => bail!("Unknown field '{}'", key.trim()),
+                None => bail!("Expected 'field: value', found '{}'", line),
+            }
+        }
+        if !found_separator {
+            bail!("Missing '{}' line before the memory content", CONTENT_SEPARATOR);
+        }
+        let content = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+        if content.is_empty() {
+            bail!("Memory content is empty (delete the memory instead)");
+        }
+        Ok(Self { category, content })
+    }
+
+    /// Write the fields that differ from `memory`; false if none do
+    pub async fn apply(&self, store: &dyn MemoryBackend, memory: &Memory) -> Result<bool> {
+        let current = Self::of(memory);
+        if self.content != current.content.trim() {
+            store.update_memory(memory.id, &self.content).await?;
+        }
+        if self.category != current.category {
+            store
+                .update_memory_category(memory.id, self.category.clone().unwrap_or_default())
+                .await?;
+        }
+        Ok(self.content != current.content.trim() || self.category != current.category)
+    }
+
+    /// `memory` with these fields
+    pub fn applied_to(&self, memory: &Memory) -> Memory {
+        Memory {
+            content: self.content.clone(),
+            category_id: self.category.clone(),
+            ..memory.clone()
+        }
+    }
+}
+
+/// Open `editor` on `memory` until the text parses or the user cancels
+///
+/// Parse errors are shown at the top of the file on the next round. Returns
+/// None if the file is saved empty or unchanged. Blocks while the editor runs.
+pub fn edit_interactive(editor: &str, memory: &Memory) -> Result<Option<MemoryEdit>> {
+    let original = MemoryEdit::of(memory).template(memory.id);
+    let mut text = original.clone();
+    loop {
+        let edited = edit_text(editor, &text)?;
+        if edited.trim().is_empty() || edited == original {
+            return Ok(None);
+        }
+        match MemoryEdit::parse(&edited) {
+            Ok(edit) => return Ok(Some(edit)),
+            Err(_) if edited == text => return Ok(None),
+            Err(e) => {
+                let body: String = edited
+                    .lines()
+                    .filter(|l| !l.starts_with("# Error:"))
+                    .map(|l| format!("{}\n", l))
+                    .collect();
+                text = format!("# Error: {}\n{}", e, body);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::store::MemoryType;
+
+    #[test]
+    fn test_template_round_trip() {
+        let memory = Memory {
+            id: 7,
+            content: "Deploys go through `make release`.".to_string(),
+            summary: None,
+            created_at: 0,
+            r#type: MemoryType::Decision,
+            session_id: None,
+            metadata: None,
+            category_id: Some("ops".to_string()),
+            embedding: None,
+        };
+        let edit = MemoryEdit::of(&memory);
+        assert_eq!(MemoryEdit::parse(&edit.template(memory.id)).unwrap(), edit);
+
+        let text = edit
+            .template(memory.id)
+            .replace("category: ops", "category:")
+            .replace("`make release`.", "`make release`.\nNever push tags by hand.");
+        let edited = MemoryEdit::parse(&text).unwrap();
+        assert_eq!(edited.category, None);
+        assert_eq!(edited.content, "Deploys go through `make release`.\nNever push tags by hand.");
+        assert_eq!(edited.applied_to(&memory).id, 7);
+
+        assert!(MemoryEdit::parse("category: ops\n---\n").is_err());
+        assert!(MemoryEdit::parse("title: x\n---\ncontent").is_err());
+    }
+}
@@ -4,13 +4,25 @@
 //! Handles hot memory (recent activity), cold memory (vector search),
 //! and user profile (personalized context).
 
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use anyhow::Result;
 use tracing::{info, debug, warn};
 
+use crate::memory::backend::MemoryBackend;
+use crate::memory::embedding::{create_embedder, EmbeddingSettings};
+use crate::memory::lazy::LazyBackend;
 use crate::memory::store::{VectorStore, Memory, MemoryType};
 use crate::memory::journal::{Journal, InteractionType};
+use crate::memory::feedback::{FeedbackCounts, FeedbackFile, MemoryFeedback, MemoryRating};
+use crate::memory::retention::{self, ArchiveReason, RetentionSettings};
+use crate::memory::dedup::{self, DuplicateGroup};
 use crate::config::agent::{MemoryConfig, UserProfile};
+use super::teach::{self, CuratedMemory};
+
+/// File names of the profile and ratings inside a `MemoryConfig::data_dir`
+const PROFILE_FILE: &str = "user_profile.json";
+const FEEDBACK_FILE: &str = "memory_feedback.json";
 
 /// Memory operation mode
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -27,15 +39,32 @@ impl Default for MemoryMode {
     }
 }
 
+/// Drop expired curated memories and project memories from other projects
+fn retain_applicable(memories: &mut Vec<Memory>) {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let now = chrono::Utc::now().timestamp();
+    memories.retain(|m| teach::is_applicable(m, &cwd, now));
+}
+
 /// Unified memory manager for agent
 pub struct AgentMemoryManager {
-    vector_store: Arc<VectorStore>,
+    store: Arc<dyn MemoryBackend>,
+    /// Set when `store` can be released while the session is idle
+    lazy_store: Option<Arc<LazyBackend>>,
     
     journal: Option<Arc<tokio::sync::Mutex<Journal>>>,
     config: MemoryConfig,
     mode: MemoryMode,
     /// User profile for personalized context
     profile: std::sync::Mutex<UserProfile>,
+    /// Where the profile is saved; None when it is not persisted
+    profile_path: Option<PathBuf>,
+    /// This store's section of the rating file shared by every manager;
+    /// None when memory is not persisted
+    feedback_file: Option<FeedbackFile>,
+    /// Cold store that compaction moves stale memories to; None when memory
+    /// is not persisted
+    archive_path: Option<PathBuf>,
 }
 
 impl AgentMemoryManager {
@@ -51,7 +80,7 @@ impl AgentMemoryManager {
         
         if config.incognito {
             info!("Memory is in incognito mode, no persistence");
-            return Self::incognito().await;
+            return Self::incognito(&config.embedding).await;
         }
         
         let storage_path = config.effective_storage_path();
@@ -67,15 +96,22 @@ impl AgentMemoryManager {
     /// 
     /// Uses a temporary in-memory store. This is async to avoid blocking issues.
     async fn disabled() -> Result<Self> {
+        Self::temporary(&EmbeddingSettings::default()).await
+    }
+
+    /// Non-persistent manager whose store embeds with the provider
+    /// `embedding` selects
+    async fn temporary(embedding: &EmbeddingSettings) -> Result<Self> {
         // Create a temporary in-memory store that won't persist
         let temp_dir = std::env::temp_dir().join(format!("mylm_memory_{}", uuid::Uuid::new_v4()));
         std::fs::create_dir_all(&temp_dir)?;
         
         // Create the store asynchronously (no block_on needed)
-        let store = VectorStore::new(temp_dir.to_str().unwrap()).await?;
+        let store = VectorStore::with_embedder(temp_dir.to_str().unwrap(), create_embedder(embedding)?).await?;
         
         Ok(Self {
-            vector_store: Arc::new(store),
+            store: Arc::new(store),
+            lazy_store: None,
             journal: None,
             config: MemoryConfig {
                 enabled: false,
@@ -83,12 +119,19 @@ impl AgentMemoryManager {
             },
             mode: MemoryMode::default(),
             profile: std::sync::Mutex::new(UserProfile::default()),
+            profile_path: None,
+            feedback_file: None,
+            archive_path: None,
         })
     }
     
-    /// Create an incognito memory manager (in-memory only)
-    async fn incognito() -> Result<Self> {
-        Self::disabled().await // Same as disabled - no persistence
+    /// Create an incognito memory manager
+    ///
+    /// Unlike `disabled`, its throwaway store in the temp directory embeds
+    /// with the configured provider, so memories added this session can be
+    /// searched; nothing reaches the persistent store.
+    async fn incognito(embedding: &EmbeddingSettings) -> Result<Self> {
+        Self::temporary(embedding).await
     }
     
     /// Create a new memory manager with custom storage path
@@ -97,13 +140,20 @@ impl AgentMemoryManager {
             return Self::disabled().await;
         }
         
-        info!("Initializing AgentMemoryManager at: {}", path);
+        info!("Initializing AgentMemoryManager at: {} ({} backend)", path, config.backend);
         
-        let vector_store = Arc::new(VectorStore::new(path).await?);
+        let lazy_store = Arc::new(LazyBackend::open(config.backend, std::path::Path::new(path), &config.embedding).await?);
+        let store: Arc<dyn MemoryBackend> = lazy_store.clone();
         
-        // Journal is optional - can be None if not needed
+        // Journal, profile and feedback live in `data_dir` when one is set
+        // (a server user's directory), otherwise in the shared data directory
+        let data_dir = config.data_dir.clone();
         let journal = if config.enabled && !config.incognito {
-            match Journal::new() {
+            let journal = match &data_dir {
+                Some(dir) => Journal::in_dir(&dir.join("journals")),
+                None => Journal::new(),
+            };
+            match journal {
                 Ok(j) => Some(Arc::new(tokio::sync::Mutex::new(j))),
                 Err(e) => {
                     warn!("Failed to initialize journal: {}", e);
@@ -114,15 +164,25 @@ impl AgentMemoryManager {
             None
         };
         
-        // Load user profile
-        let profile = UserProfile::load().unwrap_or_default();
+        let (profile_path, feedback_path) = match &data_dir {
+            Some(dir) => (Some(dir.join(PROFILE_FILE)), Some(dir.join(FEEDBACK_FILE))),
+            None => (UserProfile::default_path().ok(), MemoryFeedback::default_path().ok()),
+        };
+        let profile = profile_path
+            .as_deref()
+            .map(|path| UserProfile::load_from(path).unwrap_or_default())
+            .unwrap_or_default();
         
         Ok(Self {
-            vector_store,
+            store,
+            lazy_store: Some(lazy_store),
             journal,
             config,
             mode: MemoryMode::default(),
             profile: std::sync::Mutex::new(profile),
+            profile_path,
+            feedback_file: feedback_path.map(|file| FeedbackFile::new(file, path)),
+            archive_path: Some(retention::archive_path(Path::new(path))),
         })
     }
     
@@ -134,25 +194,41 @@ impl AgentMemoryManager {
         Ok(manager)
     }
     
-    /// Create from an existing VectorStore
+    /// Create from an existing memory backend
     /// 
-    /// This is useful when you want to share a VectorStore across multiple components
-    /// or when the VectorStore was initialized elsewhere.
-    pub fn from_store(store: Arc<VectorStore>) -> Self {
+    /// This is useful when you want to share a store across multiple components
+    /// or when the store was initialized elsewhere. Ratings are not kept
+    /// unless `with_feedback_path` says which store this is.
+    pub fn from_store(store: Arc<dyn MemoryBackend>) -> Self {
         let profile = UserProfile::load().unwrap_or_default();
         
         Self {
-            vector_store: store,
+            store,
+            lazy_store: None,
             journal: None, // Journal not available when using from_store
             config: MemoryConfig::default(),
             mode: MemoryMode::default(),
             profile: std::sync::Mutex::new(profile),
+            profile_path: UserProfile::default_path().ok(),
+            feedback_file: None,
+            archive_path: None,
         }
     }
     
-    /// Get a reference to the underlying VectorStore
-    pub fn vector_store(&self) -> &Arc<VectorStore> {
-        &self.vector_store
+    /// Keep the feedback on `store` in the file at `path`
+    pub fn with_feedback_path(mut self, path: PathBuf, store: &str) -> Self {
+        self.feedback_file = Some(FeedbackFile::new(path, store));
+        self
+    }
+    
+    /// Close the memory store until it is next used; true if it was open
+    pub fn release_store(&self) -> bool {
+        self.lazy_store.as_ref().is_some_and(|store| store.release())
+    }
+    
+    /// Get a reference to the underlying memory backend
+    pub fn store(&self) -> &Arc<dyn MemoryBackend> {
+        &self.store
     }
     
     /// Check if memory is enabled
@@ -167,7 +243,7 @@ impl AgentMemoryManager {
     
     /// Add a new memory entry
     /// 
-    /// Content is sanitized in VectorStore::add_memory_typed_with_id before storage.
+    /// Content is sanitized by the backend before storage.
     pub async fn add_memory(
         &self,
         content: &str,
@@ -196,8 +272,8 @@ impl AgentMemoryManager {
         let id = chrono::Utc::now().timestamp_nanos_opt()
             .unwrap_or_else(|| chrono::Utc::now().timestamp());
         
-        // Note: sanitization happens in VectorStore::add_memory_typed_with_id
-        self.vector_store.add_memory_typed_with_id(
+        // Note: sanitization happens in the backend's add_memory_typed_with_id
+        self.store.add_memory_typed_with_id(
             id,
             content,
             memory_type,
@@ -211,6 +287,22 @@ impl AgentMemoryManager {
         Ok(id)
     }
     
+    /// Store a memory written through the teach template
+    ///
+    /// Project-scoped memories are tied to the project containing `cwd`.
+    pub async fn teach(&self, memory: &CuratedMemory, cwd: &Path) -> Result<i64> {
+        let project = teach::project_root(cwd);
+        self.add_memory_full(
+            &memory.content,
+            memory.memory_type.clone(),
+            None,
+            Some(memory.metadata(Some(&project))),
+            None,
+            None,
+        )
+        .await
+    }
+    
     /// Add a user note memory
     pub async fn add_user_note(&self, content: &str) -> Result<i64> {
         self.add_memory(content, MemoryType::UserNote).await
@@ -236,12 +328,118 @@ impl AgentMemoryManager {
         debug!("Searching memories: query='{}', limit={}", query, limit);
         
         let effective_limit = limit.min(self.config.max_memories);
-        let results = self.vector_store.search_memory(query, effective_limit).await?;
+        let feedback = self.feedback();
+        let retention = &self.config.retention;
+        // Over-fetch so fresh or well-rated memories just below the cut can move up
+        let reorders = !feedback.ratings.is_empty() || retention.half_life_days > 0.0;
+        let fetch_limit = if reorders { effective_limit * 2 } else { effective_limit };
+        let mut results = self.store.search_memory(query, fetch_limit).await?;
+        retain_applicable(&mut results);
+        let results = feedback.rerank(results, effective_limit, retention, chrono::Utc::now().timestamp());
         
         info!("Memory search returned {} results", results.len());
         Ok(results)
     }
     
+    /// Current ratings and the memories injected into the latest prompt
+    pub fn feedback(&self) -> MemoryFeedback {
+        let Some(ref file) = self.feedback_file else {
+            return MemoryFeedback::default();
+        };
+        file.load().unwrap_or_else(|e| {
+            warn!("Ignoring unreadable memory feedback: {}", e);
+            MemoryFeedback::default()
+        })
+    }
+    
+    /// Remember which stored memories were injected into a prompt
+    pub fn record_injected(&self, memories: &[Memory]) {
+        if let Some(ref file) = self.feedback_file {
+            file.update(|f| f.set_last_injected(memories));
+        }
+    }
+    
+    /// Rate a memory as helpful or irrelevant
+    pub fn rate_memory(&self, id: i64, rating: MemoryRating) -> Result<FeedbackCounts> {
+        let file = self.feedback_file.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Memory feedback is not available without persistent memory"))?;
+        let mut counts = FeedbackCounts::default();
+        file.update(|f| counts = f.rate(id, rating));
+        Ok(counts)
+    }
+    
+    /// Stored memories that feedback suggests deleting, worst first
+    pub async fn prune_suggestions(&self) -> Result<Vec<(Memory, FeedbackCounts)>> {
+        let mut suggestions = Vec::new();
+        for (id, counts) in self.feedback().prune_candidates() {
+            if let Some(memory) = self.store.get_memory_by_id(id).await? {
+                suggestions.push((memory, counts));
+            }
+        }
+        Ok(suggestions)
+    }
+    
+    /// Move stale memories to the archive, returning them and why; with
+    /// `dry_run` only list them
+    pub async fn compact(&self, settings: &RetentionSettings, dry_run: bool) -> Result<Vec<(Memory, ArchiveReason)>> {
+        let archive = self.archive_path.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Memories are not archived without persistent memory"))?;
+        let archived = retention::compact(self.store.as_ref(), &self.feedback(), settings, archive, dry_run).await?;
+        if !dry_run && !archived.is_empty() {
+            if let Some(ref file) = self.feedback_file {
+                file.update(|f| archived.iter().for_each(|(m, _)| f.forget(m.id)));
+            }
+        }
+        Ok(archived)
+    }
+    
+    /// Merge near-duplicate memories, returning the groups merged; with
+    /// `dry_run` only find them
+    ///
+    /// Ratings of the merged memories move to the one kept, and so does a
+    /// category from `retention.keep_categories` such as `starred`.
+    pub async fn dedupe(&self, threshold: f32, dry_run: bool) -> Result<Vec<DuplicateGroup>> {
+        if !self.config.enabled {
+            return Ok(Vec::new());
+        }
+        let groups = dedup::dedupe(
+            self.store.as_ref(),
+            &self.config.embedding,
+            threshold,
+            &self.config.retention.keep_categories,
+            dry_run,
+        )
+        .await?;
+        if !dry_run && !groups.is_empty() {
+            if let Some(ref file) = self.feedback_file {
+                file.update(|f| {
+                    for group in &groups {
+                        for (duplicate, _) in &group.duplicates {
+                            let counts = f.counts(duplicate.id);
+                            f.forget(duplicate.id);
+                            if counts == FeedbackCounts::default() {
+                                continue;
+                            }
+                            let kept = f.ratings.entry(group.keeper.id).or_default();
+                            kept.helpful += counts.helpful;
+                            kept.irrelevant += counts.irrelevant;
+                            kept.last_rated = kept.last_rated.max(counts.last_rated);
+                        }
+                    }
+                });
+            }
+        }
+        Ok(groups)
+    }
+    
+    /// Memories compaction has archived, which retrieval leaves out
+    pub fn archived_memories(&self) -> Result<Vec<Memory>> {
+        match self.archive_path {
+            Some(ref path) => retention::read_archive(path),
+            None => Ok(Vec::new()),
+        }
+    }
+    
     /// Search memories by type
     pub async fn search_by_type(
         &self,
@@ -253,7 +451,7 @@ impl AgentMemoryManager {
             return Ok(Vec::new());
         }
         
-        let results = self.vector_store.search_by_type(query, memory_type, limit).await?;
+        let results = self.store.search_by_type(query, memory_type, limit).await?;
         Ok(results)
     }
     
@@ -304,7 +502,8 @@ impl AgentMemoryManager {
         
         // Fallback: get most recent from vector store
         // Use get_recent_memories to get the newest memories by created_at
-        let results = self.vector_store.get_recent_memories(limit).await?;
+        let mut results = self.store.get_recent_memories(limit).await?;
+        retain_applicable(&mut results);
         Ok(results)
     }
     
@@ -323,12 +522,17 @@ impl AgentMemoryManager {
                 .unwrap_or_else(|| "unknown".to_string());
             
             // Content is already sanitized when stored
+            let first_line = mem.content.lines().next().unwrap_or(&mem.content);
+            let line = match teach::title(mem) {
+                Some(title) => format!("{}: {}", title, first_line),
+                None => first_line.to_string(),
+            };
             context.push_str(&format!(
                 "{}. [{} | {}] {}\n",
                 i + 1,
                 mem.r#type,
                 timestamp,
-                mem.content.lines().next().unwrap_or(&mem.content)
+                line
             ));
         }
         
@@ -361,7 +565,7 @@ impl AgentMemoryManager {
     /// Get memory statistics
     pub async fn stats(&self) -> Result<MemoryStats> {
         let count = if self.config.enabled {
-            self.vector_store.count_memories().await.unwrap_or(0)
+            self.store.count_memories().await.unwrap_or(0)
         } else {
             0
         };
@@ -389,7 +593,7 @@ impl AgentMemoryManager {
             return Ok(Vec::new());
         }
         
-        self.vector_store.get_recent_memories_with_offset(limit, offset).await
+        self.store.get_recent_memories_with_offset(limit, offset).await
     }
     
     /// Get a single memory by ID
@@ -398,7 +602,7 @@ impl AgentMemoryManager {
             return Ok(None);
         }
         
-        self.vector_store.get_memory_by_id(id).await
+        self.store.get_memory_by_id(id).await
     }
     
     /// Delete a memory by ID
@@ -407,7 +611,11 @@ impl AgentMemoryManager {
             return Ok(());
         }
         
-        self.vector_store.delete_memory(id).await
+        self.store.delete_memory(id).await?;
+        if let Some(ref file) = self.feedback_file {
+            file.update(|f| f.forget(id));
+        }
+        Ok(())
     }
     
     /// Update memory content
@@ -416,7 +624,7 @@ impl AgentMemoryManager {
             return Ok(());
         }
         
-        self.vector_store.update_memory(id, content).await
+        self.store.update_memory(id, content).await
     }
     
     /// Repair database (adaptive mode only)
@@ -425,7 +633,7 @@ impl AgentMemoryManager {
             return Ok("Repair not available in Legacy mode".to_string());
         }
         
-        self.vector_store.repair_database().await
+        self.store.repair_database().await
     }
     
     // ===== Config-aware convenience methods =====
@@ -479,7 +687,9 @@ impl AgentMemoryManager {
         let mut profile = self.profile.lock()
             .map_err(|e| anyhow::anyhow!("Failed to lock profile: {}", e))?;
         f(&mut profile);
-        profile.save()?;
+        if let Some(ref path) = self.profile_path {
+            profile.save_to(path)?;
+        }
         Ok(())
     }
     
@@ -756,6 +966,8 @@ impl MemoryProvider for AgentMemoryProvider {
         let semantic_limit = manager.config().semantic_search_limit;
         let semantic_memories = manager.search_memories(user_message, semantic_limit).await.unwrap_or_default();
         crate::info_log!("[MEMORY_PROVIDER] Got {} semantic matches", semantic_memories.len());
+        // Only store-backed matches can be rated; journal entries have no stable id
+        manager.record_injected(&semantic_memories);
         
         crate::debug_log!("[MEMORY_PROVIDER] Hot memories: {}, Semantic matches: {}", 
             hot_memories.len(), semantic_memories.len());
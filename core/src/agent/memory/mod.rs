@@ -33,10 +33,13 @@
 pub mod manager;
 pub mod context;
 pub mod extraction;
+pub mod teach;
+pub mod edit;
 
 pub use manager::{AgentMemoryManager, AgentMemoryProvider, MemoryMode, MemoryStats};
 pub use context::{MemoryContextBuilder, InjectionStrategy, inject_memory_context, get_context_for_query};
 pub use extraction::{MemoryExtractor, ExtractedMemory, extract_memories};
+pub use teach::{CuratedMemory, MemoryScope};
 
 /// Trait for memory providers that can inject context into prompts
 /// 
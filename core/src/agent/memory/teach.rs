@@ -0,0 +1,370 @@
+//! Curated memories
+//!
+//! `mylm memory teach` and the TUI `/teach` command open an editor on a
+//! template and store the result as a memory with a title, tags, a scope and
+//! an optional expiry. Those fields live in the memory's metadata, so any
+//! backend can hold them; [`is_applicable`] hides expired memories and
+//! project memories outside their project when context is built.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::memory::store::{Memory, MemoryType};
+
+/// Separator between the header fields and the memory content
+pub(super) const CONTENT_SEPARATOR: &str = "---";
+
+const TEMPLATE_HEADER: &str = "\
+# Teach mylm something worth remembering.
+# Lines starting with '#' above the --- line are ignored.
+# Save an empty file to cancel.
+#
+# type:    user_note | decision | discovery | bugfix | command
+# scope:   global (every session) | project (only inside this project)
+# expires: never, a date (2026-12-31) or a duration (12h, 30d, 8w)
+";
+
+/// Where a curated memory applies
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryScope {
+    /// Every session
+    #[default]
+    Global,
+    /// Only sessions started inside the project it was taught in
+    Project,
+}
+
+impl std::fmt::Display for MemoryScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MemoryScope::Global => write!(f, "global"),
+            MemoryScope::Project => write!(f, "project"),
+        }
+    }
+}
+
+/// A memory written through the teach template
+#[derive(Debug, Clone, PartialEq)]
+pub struct CuratedMemory {
+    pub title: String,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub memory_type: MemoryType,
+    pub scope: MemoryScope,
+    /// Unix timestamp after which the memory is ignored
+    pub expires_at: Option<i64>,
+}
+
+/// Metadata stored alongside a curated memory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CuratedMetadata {
+    #[serde(default)]
+    curated: bool,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    scope: MemoryScope,
+    #[serde(default)]
+    project: Option<PathBuf>,
+    #[serde(default)]
+    expires_at: Option<i64>,
+}
+
+impl CuratedMemory {
+    /// Editor template, with `content` prefilled below the separator
+    pub fn template(content: &str) -> String {
+        format!(
+            "{}title: \ntags: \ntype: user_note\nscope: global\nexpires: never\n{}\n{}\n",
+            TEMPLATE_HEADER,
+            CONTENT_SEPARATOR,
+            content.trim()
+        )
+    }
+
+    /// Parse a filled-in template
+    pub fn parse(text: &str, now: DateTime<Utc>) -> Result<Self> {
+        let mut lines = text.lines();
+        let mut memory = CuratedMemory {
+            title: String::new(),
+            content: String::new(),
+            tags: Vec::new(),
+            memory_type: MemoryType::UserNote,
+            scope: MemoryScope::Global,
+            expires_at: None,
+        };
+
+        let mut found_separator = false;
+        for line in lines.by_ref() {
+            let line = line.trim();
+            if line == CONTENT_SEPARATOR {
+                found_separator = true;
+                break;
+            }
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                bail!("Expected 'field: value', found '{}'", line);
+            };
+            let value = value.trim();
+            match key.trim().to_lowercase().as_str() {
+                "title" => memory.title = value.to_string(),
+                "tags" => {
+                    memory.tags = value
+                        .split(',')
+                        .map(|t| t.trim().trim_start_matches('#').to_lowercase())
+                        .filter(|t| !t.is_empty())
+                        .collect();
+                }
+                "type" => memory.memory_type = parse_type(value)?,
+                "scope" => memory.scope = parse_scope(value)?,
+                "expires" => memory.expires_at = parse_expiry(value, now)?,
+                other => bail!("Unknown field '{}'", other),
+            }
+        }
+        if !found_separator {
+            bail!("Missing '{}' line before the memory content", CONTENT_SEPARATOR);
+        }
+
+        memory.content = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+        if memory.content.is_empty() {
+            bail!("Memory content is empty");
+        }
+        Ok(memory)
+    }
+
+    /// Metadata to store with the memory; `project` is recorded for project scope
+    pub fn metadata(&self, project: Option<&Path>) -> serde_json::Value {
+        let metadata = CuratedMetadata {
+            curated: true,
+            title: Some(self.title.clone()).filter(|t| !t.is_empty()),
+            tags: self.tags.clone(),
+            scope: self.scope,
+            project: match self.scope {
+                MemoryScope::Project => project.map(Path::to_path_buf),
+                MemoryScope::Global => None,
+            },
+            expires_at: self.expires_at,
+        };
+        serde_json::to_value(metadata).unwrap_or_default()
+    }
+}
+
+/// Whether a stored memory should be used in a session at `cwd`
+///
+/// Memories without curated metadata always apply.
+pub fn is_applicable(memory: &Memory, cwd: &Path, now: i64) -> bool {
+    let Some(metadata) = curated_metadata(memory) else {
+        return true;
+    };
+    if metadata.expires_at.is_some_and(|expires| expires <= now) {
+        return false;
+    }
+    match (metadata.scope, metadata.project) {
+        (MemoryScope::Project, Some(project)) => cwd.starts_with(project),
+        _ => true,
+    }
+}
+
+/// Title of a curated memory, if it has one
+pub fn title(memory: &Memory) -> Option<String> {
+    curated_metadata(memory).and_then(|m| m.title)
+}
+
+fn curated_metadata(memory: &Memory) -> Option<CuratedMetadata> {
+    let value = memory.metadata.as_ref()?;
+    let metadata: CuratedMetadata = serde_json::from_value(value.clone()).ok()?;
+    metadata.curated.then_some(metadata)
+}
+
+/// Root of the project containing `cwd`: the nearest directory with `.git`, else `cwd`
+pub fn project_root(cwd: &Path) -> PathBuf {
+    cwd.ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .unwrap_or(cwd)
+        .to_path_buf()
+}
+
+/// Open `editor` on the teach template until it parses or the user cancels
+///
+/// Parse errors are shown at the top of the file on the next round. Returns
+/// None if the file is saved empty, or saved unchanged while it does not
+/// parse. Blocks while the editor runs.
+pub fn teach_interactive(editor: &str, prefill: &str) -> Result<Option<CuratedMemory>> {
+    let mut text = CuratedMemory::template(prefill);
+    loop {
+        let edited = edit_text(editor, &text)?;
+        if edited.trim().is_empty() {
+            return Ok(None);
+        }
+        match CuratedMemory::parse(&edited, Utc::now()) {
+            Ok(memory) => return Ok(Some(memory)),
+            Err(_) if edited == text => return Ok(None),
+            Err(e) => {
+                let body: String = edited
+                    .lines()
+                    .filter(|l| !l.starts_with("# Error:"))
+                    .map(|l| format!("{}\n", l))
+                    .collect();
+                text = format!("# Error: {}\n{}", e, body);
+            }
+        }
+    }
+}
+
+/// Write `text` to a temp file, open it in `editor` and return the result
+pub(super) fn edit_text(editor: &str, text: &str) -> Result<String> {
+    let path = std::env::temp_dir().join(format!("mylm-teach-{}.md", uuid::Uuid::new_v4()));
+    std::fs::write(&path, text).with_context(|| format!("Failed to write {}", path.display()))?;
+
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().context("No editor configured")?;
+    let status = Command::new(program)
+        .args(parts)
+        .arg(&path)
+        .status()
+        .with_context(|| format!("Failed to launch editor '{}'", editor));
+
+    let result = status.and_then(|status| {
+        if !status.success() {
+            bail!("Editor exited with {}", status);
+        }
+        std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))
+    });
+    let _ = std::fs::remove_file(&path);
+    result
+}
+
+fn parse_type(value: &str) -> Result<MemoryType> {
+    Ok(match value.to_lowercase().as_str() {
+        "" | "user_note" | "note" => MemoryType::UserNote,
+        "decision" => MemoryType::Decision,
+        "discovery" => MemoryType::Discovery,
+        "bugfix" => MemoryType::Bugfix,
+        "command" => MemoryType::Command,
+        other => bail!("Unknown type '{}'", other),
+    })
+}
+
+fn parse_scope(value: &str) -> Result<MemoryScope> {
+    Ok(match value.to_lowercase().as_str() {
+        "" | "global" => MemoryScope::Global,
+        "project" => MemoryScope::Project,
+        other => bail!("Unknown scope '{}' (use global or project)", other),
+    })
+}
+
+fn parse_expiry(value: &str, now: DateTime<Utc>) -> Result<Option<i64>> {
+    let value = value.trim().to_lowercase();
+    if value.is_empty() || value == "never" {
+        return Ok(None);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(&value, "%Y-%m-%d") {
+        let end_of_day = date
+            .and_hms_opt(23, 59, 59)
+            .context("Invalid expiry date")?
+            .and_utc();
+        return Ok(Some(end_of_day.timestamp()));
+    }
+
+    let unit = value.chars().last().unwrap_or_default();
+    let amount: i64 = value[..value.len() - unit.len_utf8()]
+        .parse()
+        .with_context(|| format!("Invalid expiry '{}'", value))?;
+    let duration = match unit {
+        'h' => Duration::hours(amount),
+        'd' => Duration::days(amount),
+        'w' => Duration::weeks(amount),
+        _ => bail!("Invalid expiry '{}' (use never, YYYY-MM-DD, or 12h/30d/8w)", value),
+    };
+    Ok(Some((now + duration).timestamp()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        DateTime::from_timestamp(1_760_000_000, 0).unwrap()
+    }
+
+    fn stored(metadata: serde_json::Value) -> Memory {
+        Memory {
+            id: 1,
+            content: "x".to_string(),
+            summary: None,
+            created_at: 0,
+            r#type: MemoryType::UserNote,
+            session_id: None,
+            metadata: Some(metadata),
+            category_id: None,
+            embedding: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_filled_template() {
+        let text = CuratedMemory::template("Deploys go through `make release`.\n\nNever push tags by hand.")
+            .replace("title: \n", "title: Release process\n")
+            .replace("tags: \n", "tags: Release, #ci\n")
+            .replace("type: user_note", "type: decision")
+            .replace("scope: global", "scope: project")
+            .replace("expires: never", "expires: 30d");
+
+        let memory = CuratedMemory::parse(&text, now()).unwrap();
+        assert_eq!(memory.title, "Release process");
+        assert_eq!(memory.tags, vec!["release", "ci"]);
+        assert_eq!(memory.memory_type, MemoryType::Decision);
+        assert_eq!(memory.scope, MemoryScope::Project);
+        assert_eq!(memory.expires_at, Some(now().timestamp() + 30 * 86_400));
+        assert!(memory.content.starts_with("Deploys go through"));
+        assert!(memory.content.ends_with("by hand."));
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_input() {
+        assert!(CuratedMemory::parse("title: x\nno separator", now()).is_err());
+        assert!(CuratedMemory::parse(&CuratedMemory::template(""), now()).is_err());
+        let bad_scope = CuratedMemory::template("body").replace("scope: global", "scope: team");
+        assert!(CuratedMemory::parse(&bad_scope, now()).is_err());
+        let bad_expiry = CuratedMemory::template("body").replace("expires: never", "expires: soon");
+        assert!(CuratedMemory::parse(&bad_expiry, now()).is_err());
+    }
+
+    #[test]
+    fn test_expiry_date() {
+        let expires = parse_expiry("2026-12-31", now()).unwrap().unwrap();
+        assert_eq!(
+            DateTime::from_timestamp(expires, 0).unwrap().to_rfc3339(),
+            "2026-12-31T23:59:59+00:00"
+        );
+    }
+
+    #[test]
+    fn test_applicability() {
+        let memory = CuratedMemory {
+            title: "t".to_string(),
+            content: "c".to_string(),
+            tags: vec![],
+            memory_type: MemoryType::UserNote,
+            scope: MemoryScope::Project,
+            expires_at: Some(100),
+        };
+        let stored_memory = stored(memory.metadata(Some(Path::new("/work/app"))));
+
+        assert!(is_applicable(&stored_memory, Path::new("/work/app/src"), 50));
+        assert!(!is_applicable(&stored_memory, Path::new("/work/other"), 50));
+        assert!(!is_applicable(&stored_memory, Path::new("/work/app"), 100));
+        assert_eq!(title(&stored_memory).as_deref(), Some("t"));
+
+        // Uncurated metadata is left alone
+        assert!(is_applicable(&stored(serde_json::json!({"source": "chat"})), Path::new("/"), 0));
+    }
+}
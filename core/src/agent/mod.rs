@@ -80,6 +80,7 @@ pub mod tools;
 pub mod builder;
 pub mod worker;
 pub mod factory;
+pub mod reload;
 pub mod memory;
 pub mod identity;
 
@@ -124,6 +125,8 @@ pub use factory::{
     AgentSessionFactory, FactoryError,
 };
 
+pub use reload::{ReloadSummary, SessionReloader};
+
 pub use crate::config::agent::{
     AgentConfig, ToolConfig, LlmConfig,
     RetryConfig, MemoryConfig, WorkerConfig, TelemetryConfig, EnvConfig,
@@ -132,7 +135,7 @@ pub use crate::config::agent::{
 pub use session::{
     Session, SessionConfig, SessionError,
     SessionInput, WorkerEvent,
-    SessionPersistence, PersistedSession, SessionMetadata,
+    SessionPersistence, PersistedSession, SessionMetadata, SessionBookmark,
     AgentStateCheckpoint, SessionBuilder,
     persistence::SessionData,
 };
@@ -0,0 +1,183 @@
+//! Applying a changed config to running sessions
+//!
+//! A `SessionReloader` is shared by a factory and its frontend. The factory
+//! registers the LLM clients of every session it creates (the agent's and
+//! the fast path's); the frontend
+//! registers its approval policy. `apply` then rebuilds the clients,
+//! approval rules and worker request limits from the new config, so the
+//! next request uses them without restarting the session. Conversation
+//! history, tools and memory are kept.
+
+use std::sync::{Arc, Weak};
+
+use parking_lot::{Mutex, RwLock};
+
+use crate::agent::runtime::capabilities::{PolicyApprovalCapability, SharedLlmClient};
+use crate::agent::tools::LiveConfig;
+use crate::agent::FactoryError;
+use crate::config::{config_to_llm_config, Config};
+use crate::provider::{LlmClient, LlmConfig};
+use crate::scheduler::WorkerPool;
+
+/// What a reload changed, for status messages
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReloadSummary {
+    /// Profile and model of each rebuilt client
+    pub clients: Vec<(String, String)>,
+    /// Approval policies whose rules were replaced
+    pub approvals: usize,
+}
+
+/// How a registered client is rebuilt from a new config
+#[derive(Debug, Clone)]
+enum ClientSource {
+    /// A session's client, from its profile
+    Profile(String),
+    /// The fast-path client of a session with this profile, from
+    /// `features.fast_path`
+    FastPath(String),
+}
+
+impl ClientSource {
+    /// Profile the client is built from and its settings under `config`
+    fn llm_config(&self, config: &Config) -> Result<(String, LlmConfig), FactoryError> {
+        match self {
+            Self::Profile(profile) => Ok((profile.clone(), config_to_llm_config(config, profile)?)),
+            Self::FastPath(session_profile) => {
+                let settings = &config.features.fast_path;
+                let profile = settings.profile.clone().unwrap_or_else(|| session_profile.clone());
+                let mut llm_config = config_to_llm_config(config, &profile)?;
+                if let Some(model) = &settings.model {
+                    llm_config.model = model.clone();
+                }
+                Ok((profile, llm_config))
+            }
+        }
+    }
+}
+
+/// Swaps the config of running sessions
+pub struct SessionReloader {
+    live_config: LiveConfig,
+    worker_pool: Arc<WorkerPool>,
+    /// Clients of live sessions; dropped sessions are skipped
+    clients: Mutex<Vec<(ClientSource, Weak<RwLock<Arc<LlmClient>>>)>>,
+    approvals: Mutex<Vec<Arc<PolicyApprovalCapability>>>,
+}
+
+impl SessionReloader {
+    pub fn new(live_config: LiveConfig, worker_pool: Arc<WorkerPool>) -> Self {
+        Self {
+            live_config,
+            worker_pool,
+            clients: Mutex::new(Vec::new()),
+            approvals: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Rebuild `client` from the `profile` section on reload
+    pub fn register_client(&self, profile: &str, client: SharedLlmClient) {
+        self.clients
+            .lock()
+            .push((ClientSource::Profile(profile.to_string()), Arc::downgrade(&client)));
+    }
+
+    /// Rebuild the fast-path `client` of a session using `profile` from
+    /// `features.fast_path` on reload
+    pub fn register_fast_path_client(&self, profile: &str, client: SharedLlmClient) {
+        self.clients
+            .lock()
+            .push((ClientSource::FastPath(profile.to_string()), Arc::downgrade(&client)));
+    }
+
+    /// Replace the rules of `approval` on reload
+    pub fn register_approval(&self, approval: Arc<PolicyApprovalCapability>) {
+        self.approvals.lock().push(approval);
+    }
+
+    /// Apply `config` to everything registered
+    ///
+    /// All clients are built before any is swapped in, so a config that
+    /// fails for one profile changes nothing.
+    pub fn apply(&self, config: &Config) -> Result<ReloadSummary, FactoryError> {
+        let clients: Vec<(ClientSource, SharedLlmClient)> = {
+            let mut registered = self.clients.lock();
+            registered.retain(|(_, slot)| slot.strong_count() > 0);
+            registered
+                .iter()
+                .filter_map(|(source, slot)| Some((source.clone(), slot.upgrade()?)))
+                .collect()
+        };
+        let rebuilt = clients
+            .iter()
+            .map(|(source, _)| {
+                let (profile, llm_config) = source.llm_config(config)?;
+                Ok((profile, Arc::new(LlmClient::new(llm_config)?)))
+            })
+            .collect::<Result<Vec<_>, FactoryError>>()?;
+
+        let mut summary = ReloadSummary::default();
+        for ((_, slot), (profile, client)) in clients.into_iter().zip(rebuilt) {
+            let mut current = slot.write();
+            client.adopt_callbacks(&current);
+            summary.clients.push((profile, client.model().to_string()));
+            *current = client;
+        }
+
+        let approvals = self.approvals.lock();
+        for approval in approvals.iter() {
+            approval.reload(config);
+        }
+        summary.approvals = approvals.len();
+
+        self.worker_pool.set_requests_per_minute(config.features.worker_pool.requests_per_minute);
+        *self.live_config.write().unwrap_or_else(|e| e.into_inner()) = config.clone();
+        crate::info_log!("[RELOAD] Applied config: {:?}", summary);
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ProviderConfig;
+
+    fn config(model: &str) -> Config {
+        let mut config = Config::default();
+        config.providers.insert("mock".to_string(), ProviderConfig::mock(""));
+        let profile = config.active_profile_mut();
+        profile.provider = "mock".to_string();
+        profile.model = Some(model.to_string());
+        config
+    }
+
+    #[test]
+    fn test_apply_swaps_clients_and_limits() {
+        let old = config("old-model");
+        let live: LiveConfig = Arc::new(std::sync::RwLock::new(old.clone()));
+        let pool = Arc::new(WorkerPool::new(2));
+        let reloader = SessionReloader::new(Arc::clone(&live), Arc::clone(&pool));
+
+        let client = Arc::new(LlmClient::new(config_to_llm_config(&old, "default").unwrap()).unwrap());
+        let slot: SharedLlmClient = Arc::new(parking_lot::RwLock::new(client));
+        reloader.register_client("default", Arc::clone(&slot));
+        let fast_slot: SharedLlmClient = Arc::new(parking_lot::RwLock::new(Arc::clone(&*slot.read())));
+        reloader.register_fast_path_client("default", Arc::clone(&fast_slot));
+
+        let mut new = config("new-model");
+        new.features.worker_pool.requests_per_minute = Some(30);
+        new.features.fast_path.model = Some("fast-model".to_string());
+        let summary = reloader.apply(&new).unwrap();
+        assert_eq!(summary.clients[0], ("default".to_string(), "new-model".to_string()));
+        assert_eq!(slot.read().model(), "new-model");
+        assert_eq!(fast_slot.read().model(), "fast-model");
+        assert!(pool.request_limit().is_some());
+        assert_eq!(live.read().unwrap().active_profile().model.as_deref(), Some("new-model"));
+
+        // A profile that no longer resolves leaves everything as it was
+        let mut broken = config("broken-model");
+        broken.active_profile_mut().provider = "missing".to_string();
+        assert!(reloader.apply(&broken).is_err());
+        assert_eq!(slot.read().model(), "new-model");
+    }
+}
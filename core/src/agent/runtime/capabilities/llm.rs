@@ -13,22 +13,26 @@
 use crate::agent::runtime::core::{
     Capability, LLMCapability, StreamChunk, RuntimeContext, LLMError,
 };
-use crate::agent::types::intents::LLMRequest;
+use crate::agent::types::intents::{LLMRequest, ToolCall};
 use crate::agent::types::events::LLMResponse;
 use crate::agent::memory::MemoryProvider;
 use crate::conversation::ContextManager;
 use crate::provider::LlmClient;
-use crate::provider::chat::{ChatRequest, ChatMessage};
+use crate::provider::chat::{ChatRequest, ChatMessage, ChatTool, ToolCall as ProviderToolCall};
 use std::sync::Arc;
 use std::pin::Pin;
 use futures::{Stream, StreamExt};
 use tracing::{debug, info, warn};
 
+/// Client of an `LlmClientCapability`, replaceable while the session runs
+/// (config hot-reload)
+pub type SharedLlmClient = Arc<parking_lot::RwLock<Arc<LlmClient>>>;
+
 /// LLM capability backed by existing LlmClient
 /// 
 /// Enforces context size limits via ContextManager integration.
 pub struct LlmClientCapability {
-    client: Arc<LlmClient>,
+    client: SharedLlmClient,
     memory_provider: Option<Arc<dyn MemoryProvider>>,
     context_manager: Arc<tokio::sync::Mutex<ContextManager>>,
 }
@@ -40,12 +44,22 @@ impl LlmClientCapability {
         context_manager: Arc<tokio::sync::Mutex<ContextManager>>,
     ) -> Self {
         Self { 
-            client,
+            client: Arc::new(parking_lot::RwLock::new(client)),
             memory_provider: None,
             context_manager,
         }
     }
     
+    /// Handle for swapping the client of this capability
+    pub fn shared_client(&self) -> SharedLlmClient {
+        Arc::clone(&self.client)
+    }
+    
+    /// The current client
+    fn client(&self) -> Arc<LlmClient> {
+        Arc::clone(&self.client.read())
+    }
+    
     /// Set the memory provider for context augmentation
     pub fn with_memory_provider(mut self, provider: Arc<dyn MemoryProvider>) -> Self {
         self.memory_provider = Some(provider);
@@ -95,7 +109,7 @@ impl LlmClientCapability {
             system_parts.push(req.context.system_prompt.clone());
         }
         
-        // Note: Tool descriptions are embedded in the main system prompt (system.rs).
+        // Note: Tool descriptions are embedded in the main system prompt (prompts/tools.rs).
         // We do NOT re-inject available_tools here — doing so causes models like Hermes/Mixtral
         // to activate XML tool-calling format, which breaks our ShortKey JSON parser.
         
@@ -110,7 +124,7 @@ impl LlmClientCapability {
         // protocol instead of OpenAI's native tool calling API
         for msg in &req.context.history {
             let chat_msg = match msg.role.as_str() {
-                "user" => ChatMessage::user(msg.content.clone()),
+                "user" => ChatMessage::user(msg.content.clone()).with_images(&msg.images),
                 "assistant" => ChatMessage::assistant(msg.content.clone()),
                 "system" => ChatMessage::system(msg.content.clone()),
                 "tool" => ChatMessage::user(msg.content.clone()), // Tool results as user messages
@@ -180,7 +194,7 @@ impl LlmClientCapability {
             system_parts.push(req.context.system_prompt.clone());
         }
         
-        // Note: Tool descriptions are embedded in the main system prompt (system.rs).
+        // Note: Tool descriptions are embedded in the main system prompt (prompts/tools.rs).
         // We do NOT re-inject available_tools here — doing so causes models like Hermes/Mixtral
         // to activate XML tool-calling format, which breaks our ShortKey JSON parser.
         
@@ -202,7 +216,7 @@ impl LlmClientCapability {
         // Add conversation history
         for msg in &req.context.history {
             let chat_msg = match msg.role.as_str() {
-                "user" => ChatMessage::user(msg.content.clone()),
+                "user" => ChatMessage::user(msg.content.clone()).with_images(&msg.images),
                 "assistant" => ChatMessage::assistant(msg.content.clone()),
                 "system" => ChatMessage::system(msg.content.clone()),
                 "tool" => ChatMessage::user(msg.content.clone()),
@@ -217,6 +231,8 @@ impl LlmClientCapability {
         }
         
         // === STEP 3: Set full history in context manager and prune ===
+        // Count with the current model's encoding; a reload may switch models
+        cm.set_tokenizer(crate::conversation::Tokenizer::for_model(self.client().model()));
         cm.set_history(&full_messages);
         
         // Get pre-pruning metrics
@@ -268,6 +284,29 @@ impl LlmClientCapability {
         // Return pruned messages (system message with memory is included)
         Ok(pruned)
     }
+
+    /// Native tool definitions for the request, if the client uses native tool calling
+    ///
+    /// Returns None when native tool calling is disabled so the request
+    /// falls back to the Short-Key text protocol.
+    fn native_tools(&self, req: &LLMRequest) -> Option<Vec<ChatTool>> {
+        if !self.client().supports_native_tools() || req.context.available_tools.is_empty() {
+            return None;
+        }
+        Some(req.context.available_tools.iter().map(|def| {
+            let parameters = match &def.parameters {
+                serde_json::Value::Object(map) if !map.is_empty() => def.parameters.clone(),
+                // Providers reject empty schemas; accept arbitrary arguments instead
+                _ => serde_json::json!({ "type": "object", "properties": {}, "additionalProperties": true }),
+            };
+            ChatTool::function(def.name.clone(), def.description.clone(), parameters)
+        }).collect())
+    }
+}
+
+/// Convert a provider tool call into the agent's tool call intent
+fn to_intent_tool_call(call: &ProviderToolCall) -> ToolCall {
+    ToolCall::new(call.function.name.clone(), call.parsed_arguments())
 }
 
 impl Capability for LlmClientCapability {
@@ -286,6 +325,7 @@ impl LLMCapability for LlmClientCapability {
         // Prepare sized context (prunes/condenses if needed)
         let messages = self.prepare_sized_context(&req).await?;
         
+        let tools = self.native_tools(&req);
         let chat_request = ChatRequest {
             model: req.model.unwrap_or_default(), // Will be filled by LlmClient from its config if empty
             messages,
@@ -293,25 +333,38 @@ impl LLMCapability for LlmClientCapability {
             temperature: req.temperature,
             stream: req.stream,
             stop: None,
-            tools: None,
+            tools,
+            tool_choice: None,
             response_format: None,
         };
 
-        match self.client.chat(&chat_request).await {
-            Ok(response) => Ok(LLMResponse {
-                content: response.choices
-                    .first()
-                    .map(|c| c.message.content.clone())
-                    .unwrap_or_default(),
-                usage: crate::agent::types::events::TokenUsage::new(
-                    response.usage.as_ref().map(|u| u.prompt_tokens).unwrap_or(0) as u32,
-                    response.usage.as_ref().map(|u| u.completion_tokens).unwrap_or(0) as u32,
-                ),
-                model: "unknown".to_string(),
-                provider: "unknown".to_string(),
-                finish_reason: crate::agent::types::events::FinishReason::Stop,
-                structured: None,
-            }),
+        match self.client().chat(&chat_request).await {
+            Ok(response) => {
+                let tool_calls: Vec<ToolCall> = response.tool_calls()
+                    .iter()
+                    .map(to_intent_tool_call)
+                    .collect();
+                Ok(LLMResponse {
+                    content: response.choices
+                        .first()
+                        .map(|c| c.message.content.clone())
+                        .unwrap_or_default(),
+                    usage: crate::agent::types::events::TokenUsage::new(
+                        response.usage.as_ref().map(|u| u.prompt_tokens).unwrap_or(0) as u32,
+                        response.usage.as_ref().map(|u| u.completion_tokens).unwrap_or(0) as u32,
+                    )
+                    .with_cached(response.usage.as_ref().map(|u| u.cached_tokens).unwrap_or(0)),
+                    model: "unknown".to_string(),
+                    provider: "unknown".to_string(),
+                    finish_reason: if tool_calls.is_empty() {
+                        crate::agent::types::events::FinishReason::Stop
+                    } else {
+                        crate::agent::types::events::FinishReason::ToolCall
+                    },
+                    structured: None,
+                    tool_calls,
+                })
+            }
             Err(e) => Err(LLMError::new(e.to_string())),
         }
     }
@@ -331,6 +384,7 @@ impl LLMCapability for LlmClientCapability {
                 }
             };
             
+            let tools = self.native_tools(&req);
             let chat_request = ChatRequest {
                 model: req.model.unwrap_or_default(),
                 messages,
@@ -338,12 +392,15 @@ impl LLMCapability for LlmClientCapability {
                 temperature: req.temperature,
                 stream: true,
                 stop: None,
-                tools: None,
+                tools,
+                tool_choice: None,
                 response_format: None,
             };
             
-            let mut stream = self.client.chat_stream(&chat_request);
+            let client = self.client();
+            let mut stream = client.chat_stream(&chat_request);
             let mut accumulated_usage: Option<crate::agent::types::events::TokenUsage> = None;
+            let mut tool_calls: Vec<ToolCall> = Vec::new();
             
             while let Some(event) = stream.next().await {
                 match event {
@@ -352,6 +409,7 @@ impl LLMCapability for LlmClientCapability {
                             content,
                             is_final: false,
                             usage: None,
+                            tool_calls: Vec::new(),
                         };
                     }
                     Ok(crate::provider::chat::StreamEvent::Done) => {
@@ -359,15 +417,20 @@ impl LLMCapability for LlmClientCapability {
                             content: String::new(),
                             is_final: true,
                             usage: accumulated_usage.clone(),
+                            tool_calls: std::mem::take(&mut tool_calls),
                         };
                         break;
                     }
+                    Ok(crate::provider::chat::StreamEvent::ToolCall(call)) => {
+                        tool_calls.push(to_intent_tool_call(&call));
+                    }
                     Ok(crate::provider::chat::StreamEvent::Usage(usage)) => {
                         // Accumulate usage for final reporting
                         accumulated_usage = Some(crate::agent::types::events::TokenUsage {
                             prompt_tokens: usage.prompt_tokens,
                             completion_tokens: usage.completion_tokens,
                             total_tokens: usage.total_tokens,
+                            cached_tokens: usage.cached_tokens,
                         });
                     }
                     Ok(crate::provider::chat::StreamEvent::Error(msg)) => {
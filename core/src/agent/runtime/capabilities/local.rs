@@ -41,6 +41,7 @@ impl LLMCapability for LocalLLMStub {
             provider: "stub".to_string(),
             finish_reason: crate::agent::types::events::FinishReason::Stop,
             structured: None,
+            tool_calls: Vec::new(),
         })
     }
     
@@ -54,6 +55,7 @@ impl LLMCapability for LocalLLMStub {
                 content: "Local LLM stub response".to_string(),
                 is_final: true,
                 usage: None,
+                tool_calls: Vec::new(),
             })
         }))
     }
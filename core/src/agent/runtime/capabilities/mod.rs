@@ -9,11 +9,15 @@ pub mod telemetry;
 pub mod memory;
 pub mod retry;
 pub mod local;
+pub mod plan;
 // Transport moved to orchestrator::transport
 
-pub use llm::LlmClientCapability;
+pub use llm::{LlmClientCapability, SharedLlmClient};
 pub use crate::agent::tools::{ToolRegistry, ToolDescription};
-pub use approval::{TerminalApprovalCapability, AutoApproveCapability, WorkerRestrictedApprovalCapability};
+pub use approval::{
+    TerminalApprovalCapability, AutoApproveCapability, WorkerRestrictedApprovalCapability,
+    PolicyApprovalCapability, ApprovalPrompter, ApprovalSource, ApprovalAuditEntry,
+};
 pub use worker::LocalWorkerCapability;
 pub use telemetry::ConsoleTelemetry;
 pub use memory::{MemoryCapability, MemoryCategory};
@@ -22,6 +26,7 @@ pub use retry::{
     CircuitBreakerLLM, ResilientLLM,
 };
 pub use local::SimpleToolExecutor;
+pub use plan::SessionPlan;
 pub use crate::agent::runtime::orchestrator::transport::{InMemoryTransport, connected_pair};
 
 // Re-export from agent::memory for convenience
@@ -0,0 +1,113 @@
+//! Session plan
+//!
+//! In-memory `PlanCapability` shared by the plan tool (the engine's side)
+//! and the frontend (checklist and `/plan`). Edits from the frontend are
+//! remembered until the engine is shown the plan again, so the next message
+//! can tell it what the user changed.
+
+use parking_lot::Mutex;
+
+use crate::agent::runtime::core::{Capability, PlanCapability};
+use crate::agent::types::plan::{Plan, PlanError};
+
+#[derive(Default)]
+struct PlanState {
+    plan: Option<Plan>,
+    /// The user edited the plan since the engine last saw it
+    edited_by_user: bool,
+}
+
+/// Plan of one agent session
+#[derive(Default)]
+pub struct SessionPlan {
+    state: Mutex<PlanState>,
+}
+
+impl SessionPlan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Edit the plan on the user's behalf (flags it for the engine)
+    pub fn user_edit(
+        &self,
+        edit: &mut dyn FnMut(&mut Plan) -> Result<(), PlanError>,
+    ) -> Result<Plan, PlanError> {
+        let plan = self.edit(edit)?;
+        self.state.lock().edited_by_user = true;
+        Ok(plan)
+    }
+
+    /// Clear the plan on the user's behalf
+    pub fn user_clear(&self) -> Option<Plan> {
+        let mut state = self.state.lock();
+        state.edited_by_user = true;
+        state.plan.take()
+    }
+
+    /// The plan if the user edited it since the engine last saw it
+    /// (an empty plan when they cleared it); resets the flag
+    pub fn take_user_edits(&self) -> Option<Plan> {
+        let mut state = self.state.lock();
+        if !std::mem::take(&mut state.edited_by_user) {
+            return None;
+        }
+        Some(state.plan.clone().unwrap_or_default())
+    }
+}
+
+impl Capability for SessionPlan {
+    fn name(&self) -> &'static str {
+        "session-plan"
+    }
+}
+
+impl PlanCapability for SessionPlan {
+    fn current(&self) -> Option<Plan> {
+        self.state.lock().plan.clone()
+    }
+
+    fn set(&self, plan: Option<Plan>) {
+        let mut state = self.state.lock();
+        state.plan = plan;
+        state.edited_by_user = false;
+    }
+
+    fn edit(
+        &self,
+        edit: &mut dyn FnMut(&mut Plan) -> Result<(), PlanError>,
+    ) -> Result<Plan, PlanError> {
+        let mut state = self.state.lock();
+        let current = state
+            .plan
+            .as_ref()
+            .ok_or_else(|| PlanError("There is no plan yet".to_string()))?;
+        let mut edited = current.clone();
+        edit(&mut edited)?;
+        state.plan = Some(edited.clone());
+        state.edited_by_user = false;
+        Ok(edited)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::types::plan::StepStatus;
+
+    #[test]
+    fn test_user_edits_are_reported_once() {
+        let plan = SessionPlan::new();
+        let steps = vec![("Build".to_string(), vec![]), ("Deploy".to_string(), vec![1])];
+        plan.set(Some(Plan::new("Ship it", steps).unwrap()));
+        assert!(plan.take_user_edits().is_none());
+
+        plan.user_edit(&mut |p| p.add_step("Smoke test", vec![2]).map(|_| ())).unwrap();
+        assert_eq!(plan.take_user_edits().unwrap().steps.len(), 3);
+        assert!(plan.take_user_edits().is_none());
+
+        // A refused edit leaves the plan as it was
+        assert!(plan.update_step(2, StepStatus::Done, None).is_err());
+        assert_eq!(plan.current().unwrap().steps[1].status, StepStatus::Pending);
+    }
+}
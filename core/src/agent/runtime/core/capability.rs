@@ -7,6 +7,7 @@ use crate::agent::runtime::core::{RuntimeContext, LLMError, ToolError, ApprovalE
 use crate::agent::types::intents::{LLMRequest, ToolCall, ApprovalRequest, WorkerSpec};
 use crate::agent::types::events::{LLMResponse, ToolResult, ApprovalOutcome};
 use crate::agent::types::events::WorkerId;
+use crate::agent::types::plan::{Plan, PlanError, StepStatus};
 use crate::agent::cognition::{AgentDecision, InputEvent};
 use std::pin::Pin;
 use futures::Stream;
@@ -23,6 +24,8 @@ pub struct StreamChunk {
     pub is_final: bool,
     /// Token usage (only present in final chunk when available)
     pub usage: Option<crate::agent::types::events::TokenUsage>,
+    /// Native tool calls (only present in final chunk)
+    pub tool_calls: Vec<ToolCall>,
 }
 
 /// LLM capability - text completion
@@ -50,6 +53,30 @@ pub trait ToolCapability: Capability {
         ctx: &RuntimeContext,
         call: ToolCall,
     ) -> Result<ToolResult, ToolError>;
+
+    /// What the tool does, for the prompt's tool section
+    fn description(&self) -> &'static str {
+        ""
+    }
+
+    /// Example calls, for the prompt's tool section
+    fn usage(&self) -> &'static str {
+        ""
+    }
+
+    /// JSON Schema of the arguments, declared in `tools::schema` by name
+    fn schema(&self) -> serde_json::Value {
+        crate::agent::tools::schema::tool_schema(self.name()).unwrap_or_else(|| serde_json::json!({}))
+    }
+
+    /// Whether a call to `tool` works on the session plan
+    ///
+    /// The runtime runs such calls over its `PlanCapability`, so the plan
+    /// the frontend shows is the one the agent edits. Capabilities that
+    /// hold several tools answer for the one `tool` names.
+    fn uses_session_plan(&self, _tool: &str) -> bool {
+        false
+    }
 }
 
 /// Approval capability - user approval requests
@@ -79,6 +106,31 @@ pub trait TelemetryCapability: Capability {
     async fn record_result(&self, ctx: &RuntimeContext, event: &InputEvent);
 }
 
+/// Plan capability - the session's explicit multi-step plan
+///
+/// The engine writes the plan before a multi-step task and reports step
+/// progress; frontends read it to show a checklist and may edit it. Edits
+/// made by the user are flagged so the engine can be told about them.
+pub trait PlanCapability: Capability {
+    /// The current plan, if any
+    fn current(&self) -> Option<Plan>;
+
+    /// Replace the plan (None clears it)
+    fn set(&self, plan: Option<Plan>);
+
+    /// Apply `edit` to the current plan and return the result; nothing
+    /// changes if `edit` fails or there is no plan
+    fn edit(
+        &self,
+        edit: &mut dyn FnMut(&mut Plan) -> Result<(), PlanError>,
+    ) -> Result<Plan, PlanError>;
+
+    /// Set a step's status (dependencies must be settled to start or finish it)
+    fn update_step(&self, id: usize, status: StepStatus, note: Option<String>) -> Result<Plan, PlanError> {
+        self.edit(&mut |plan| plan.set_status(id, status, note.clone()))
+    }
+}
+
 /// Minimal worker identifier returned by spawn
 /// 
 /// This is a lightweight handle containing only the worker ID.
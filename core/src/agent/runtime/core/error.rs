@@ -274,6 +274,12 @@ pub enum TelemetryEvent {
         intent_id: Option<IntentId>,
         error: String,
     },
+    /// An LLM request moved to the next endpoint in the fallback chain
+    ProviderFailover {
+        from: String,
+        to: String,
+        reason: String,
+    },
     Metrics {
         active_executions: usize,
         queued_executions: usize,
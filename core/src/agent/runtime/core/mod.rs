@@ -7,6 +7,8 @@ pub mod context;
 pub mod error;
 pub mod capability;
 pub mod terminal;
+pub mod sandbox;
+pub mod shell;
 
 pub use context::{RuntimeContext, TraceId};
 pub use error::{
@@ -16,6 +18,8 @@ pub use error::{
 };
 pub use capability::{
     Capability, LLMCapability, ToolCapability, ApprovalCapability, 
-    WorkerCapability, TelemetryCapability, StreamChunk, WorkerSpawnHandle,
+    WorkerCapability, TelemetryCapability, PlanCapability, StreamChunk, WorkerSpawnHandle,
 };
-pub use terminal::{TerminalExecutor, DefaultTerminalExecutor, SharedTerminalExecutor, TerminalExecutorRef};
+pub use terminal::{ExecError, TerminalExecutor, DefaultTerminalExecutor, SharedTerminalExecutor, TerminalExecutorRef};
+pub use sandbox::SandboxedTerminalExecutor;
+pub use shell::{Shell, ShellKind};
@@ -0,0 +1,189 @@
+//! Sandboxed terminal executor
+//!
+//! Runs each command through bubblewrap or a throwaway docker container
+//! instead of directly on the host. The host filesystem is visible read-only,
+//! a per-executor scratch directory is the working directory and the only
+//! writable place (plus any configured `writable_paths`), and the network is
+//! cut off unless the profile allows it.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use super::terminal::{format_output, ExecError, TerminalExecutor};
+use crate::config::{SandboxBackend, SandboxSettings};
+
+/// Image used by the docker backend when the profile names none
+pub const DEFAULT_SANDBOX_IMAGE: &str = "debian:stable-slim";
+
+/// Terminal executor that isolates every command
+pub struct SandboxedTerminalExecutor {
+    settings: SandboxSettings,
+    program: PathBuf,
+    /// Scratch working directory, removed when the executor is dropped
+    scratch: PathBuf,
+    /// Directory the session was started in, visible read-only
+    project: PathBuf,
+}
+
+impl SandboxedTerminalExecutor {
+    /// Create an executor for `settings`
+    ///
+    /// Fails if the backend is `none` or its program is not installed, so a
+    /// profile that asks for isolation never silently runs on the host.
+    pub fn new(settings: SandboxSettings) -> Result<Self, String> {
+        let name = match settings.backend {
+            SandboxBackend::None => return Err("Sandbox backend is 'none'".to_string()),
+            SandboxBackend::Bubblewrap => "bwrap",
+            SandboxBackend::Docker => "docker",
+        };
+        let program = find_in_path(name)
+            .ok_or_else(|| format!("Sandbox backend needs `{}`, which is not on PATH", name))?;
+        let scratch = std::env::temp_dir().join(format!("mylm-sandbox-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&scratch)
+            .map_err(|e| format!("Failed to create sandbox workdir {}: {}", scratch.display(), e))?;
+        let project = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("/"));
+        Ok(Self {
+            settings,
+            program,
+            scratch,
+            project,
+        })
+    }
+
+    /// The scratch working directory commands run in
+    pub fn workdir(&self) -> &Path {
+        &self.scratch
+    }
+
+    /// Host paths mounted read-write besides the scratch directory
+    fn writable_paths(&self) -> Vec<PathBuf> {
+        self.settings
+            .writable_paths
+            .iter()
+            .map(|p| self.project.join(crate::agent::tools::expand_tilde(p)))
+            .filter(|p| p.exists())
+            .collect()
+    }
+
+    /// Arguments for the backend program that run `command` in the sandbox
+    fn wrap(&self, command: &str) -> Vec<String> {
+        let scratch = self.scratch.to_string_lossy().to_string();
+        let project = self.project.to_string_lossy().to_string();
+        let mut args: Vec<String> = Vec::new();
+        match self.settings.backend {
+            SandboxBackend::Bubblewrap => {
+                args.extend(["--ro-bind", "/", "/", "--dev", "/dev", "--proc", "/proc", "--tmpfs", "/tmp"].map(String::from));
+                args.extend(["--bind".to_string(), scratch.clone(), scratch.clone()]);
+                for path in self.writable_paths() {
+                    let path = path.to_string_lossy().to_string();
+                    args.extend(["--bind".to_string(), path.clone(), path]);
+                }
+                args.extend(["--unshare-all", "--die-with-parent", "--new-session"].map(String::from));
+                if self.settings.network {
+                    args.push("--share-net".to_string());
+                }
+                args.extend(["--setenv".to_string(), "MYLM_PROJECT_DIR".to_string(), project]);
+                args.extend(["--chdir".to_string(), scratch]);
+                args.extend(["sh", "-c", command].map(String::from));
+            }
+            SandboxBackend::Docker => {
+                args.extend(["run", "--rm", "--read-only", "--tmpfs", "/tmp"].map(String::from));
+                args.push(format!("--network={}", if self.settings.network { "bridge" } else { "none" }));
+                args.push(format!("--volume={}:/work", scratch));
+                args.push(format!("--volume={}:{}:ro", project, project));
+                for path in self.writable_paths() {
+                    let path = path.to_string_lossy().to_string();
+                    args.push(format!("--volume={}:{}", path, path));
+                }
+                args.extend(["--workdir=/work".to_string(), format!("--env=MYLM_PROJECT_DIR={}", project)]);
+                args.push(self.settings.image.clone().unwrap_or_else(|| DEFAULT_SANDBOX_IMAGE.to_string()));
+                args.extend(["sh", "-c", command].map(String::from));
+            }
+            SandboxBackend::None => args.extend(["-c", command].map(String::from)),
+        }
+        args
+    }
+}
+
+impl Drop for SandboxedTerminalExecutor {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.scratch);
+    }
+}
+
+pub(crate) fn find_in_path(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+#[async_trait]
+impl TerminalExecutor for SandboxedTerminalExecutor {
+    async fn execute_command(&self, command: String, timeout: Option<Duration>) -> Result<String, ExecError> {
+        let child = tokio::process::Command::new(&self.program)
+            .args(self.wrap(&command))
+            .kill_on_drop(true)
+            .output();
+        let output = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, child)
+                .await
+                .map_err(|_| ExecError::TimedOut)?,
+            None => child.await,
+        };
+        match output {
+            Ok(output) => format_output(&output),
+            Err(e) => Err(ExecError::Failed(format!("Command failed: {}", e))),
+        }
+    }
+
+    async fn get_screen(&self) -> Result<String, String> {
+        // Every command runs in a fresh sandbox; there is no screen to show
+        Ok(String::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn executor(backend: SandboxBackend, network: bool) -> SandboxedTerminalExecutor {
+        SandboxedTerminalExecutor {
+            settings: SandboxSettings {
+                backend,
+                network,
+                ..SandboxSettings::default()
+            },
+            program: PathBuf::from("/usr/bin/true"),
+            scratch: PathBuf::from("/tmp/mylm-sandbox-test"),
+            project: PathBuf::from("/home/me/project"),
+        }
+    }
+
+    #[test]
+    fn test_bubblewrap_args() {
+        let args = executor(SandboxBackend::Bubblewrap, false).wrap("cargo check");
+        let joined = args.join(" ");
+        assert!(joined.starts_with("--ro-bind / / --dev /dev --proc /proc --tmpfs /tmp"));
+        assert!(joined.contains("--bind /tmp/mylm-sandbox-test /tmp/mylm-sandbox-test"));
+        assert!(joined.contains("--unshare-all"));
+        assert!(!joined.contains("--share-net"));
+        assert!(joined.contains("--chdir /tmp/mylm-sandbox-test"));
+        assert_eq!(&args[args.len() - 3..], ["sh", "-c", "cargo check"]);
+
+        let networked = executor(SandboxBackend::Bubblewrap, true).wrap("true");
+        assert!(networked.contains(&"--share-net".to_string()));
+    }
+
+    #[test]
+    fn test_docker_args() {
+        let args = executor(SandboxBackend::Docker, false).wrap("ls");
+        assert_eq!(&args[..5], ["run", "--rm", "--read-only", "--tmpfs", "/tmp"]);
+        assert!(args.contains(&"--network=none".to_string()));
+        assert!(args.contains(&"--volume=/tmp/mylm-sandbox-test:/work".to_string()));
+        assert!(args.contains(&"--volume=/home/me/project:/home/me/project:ro".to_string()));
+        assert!(args.contains(&DEFAULT_SANDBOX_IMAGE.to_string()));
+    }
+}
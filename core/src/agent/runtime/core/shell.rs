@@ -0,0 +1,107 @@
+//! Host shell selection and command wrapping
+//!
+//! Commands run through `sh -c` on Unix and `cmd /C` on Windows. The TUI's
+//! interactive terminal runs bash, or PowerShell (pwsh when installed) under
+//! ConPTY on Windows.
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use super::sandbox::find_in_path;
+
+/// Shell families, by how they take a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellKind {
+    /// sh, bash, zsh: `-c <command>`
+    Posix,
+    /// powershell.exe, pwsh: `-Command <command>`
+    PowerShell,
+    /// cmd.exe: `/C <command>`
+    Cmd,
+}
+
+/// A shell program and how to hand it a command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shell {
+    pub kind: ShellKind,
+    pub program: PathBuf,
+}
+
+impl Shell {
+    pub fn new(kind: ShellKind, program: impl Into<PathBuf>) -> Self {
+        Self {
+            kind,
+            program: program.into(),
+        }
+    }
+
+    /// Shell for one-off commands: `sh`, or `cmd` on Windows
+    pub fn for_commands() -> Self {
+        if cfg!(windows) {
+            Self::new(ShellKind::Cmd, "cmd")
+        } else {
+            Self::new(ShellKind::Posix, "sh")
+        }
+    }
+
+    /// Shell for the interactive terminal: `bash`, or PowerShell on
+    /// Windows (pwsh when installed); looked up once per process
+    pub fn interactive() -> Self {
+        static INTERACTIVE: OnceLock<Shell> = OnceLock::new();
+        INTERACTIVE
+            .get_or_init(|| {
+                if !cfg!(windows) {
+                    return Self::new(ShellKind::Posix, "bash");
+                }
+                match find_in_path("pwsh.exe") {
+                    Some(pwsh) => Self::new(ShellKind::PowerShell, pwsh),
+                    None => Self::new(ShellKind::PowerShell, "powershell.exe"),
+                }
+            })
+            .clone()
+    }
+
+    /// Arguments that run `command` and exit
+    pub fn args(&self, command: &str) -> Vec<String> {
+        match self.kind {
+            ShellKind::Posix => vec!["-c".to_string(), command.to_string()],
+            ShellKind::PowerShell => ["-NoLogo", "-NoProfile", "-NonInteractive", "-Command", command]
+                .map(String::from)
+                .to_vec(),
+            ShellKind::Cmd => vec!["/C".to_string(), command.to_string()],
+        }
+    }
+
+    /// A `tokio` command running `command` in this shell
+    pub fn command(&self, command: &str) -> tokio::process::Command {
+        let mut cmd = tokio::process::Command::new(&self.program);
+        cmd.args(self.args(command));
+        cmd
+    }
+
+    /// Bytes that turn terminal echo back on after a command that turned it
+    /// off was interrupted; None for shells that always echo
+    pub fn restore_echo(&self) -> Option<&'static [u8]> {
+        match self.kind {
+            ShellKind::Posix => Some(b"([ -t 0 ] && stty echo) 2>/dev/null\r"),
+            ShellKind::PowerShell | ShellKind::Cmd => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_args_per_shell_kind() {
+        assert_eq!(Shell::new(ShellKind::Posix, "sh").args("ls"), ["-c", "ls"]);
+        assert_eq!(Shell::new(ShellKind::Cmd, "cmd").args("dir"), ["/C", "dir"]);
+        assert_eq!(
+            Shell::new(ShellKind::PowerShell, "pwsh").args("ls")[..3],
+            ["-NoLogo", "-NoProfile", "-NonInteractive"]
+        );
+        assert_eq!(Shell::interactive(), Shell::interactive());
+        assert!(Shell::new(ShellKind::Cmd, "cmd").restore_echo().is_none());
+    }
+}
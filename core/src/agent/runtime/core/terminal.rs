@@ -7,6 +7,19 @@ use std::time::Duration;
 use async_trait::async_trait;
 use std::sync::Arc;
 
+use super::shell::Shell;
+
+/// Why a command run through a `TerminalExecutor` failed
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ExecError {
+    /// The timeout ran out and the command was killed
+    #[error("Command timed out")]
+    TimedOut,
+    /// The command could not be started or exited non-zero
+    #[error("{0}")]
+    Failed(String),
+}
+
 /// Trait for executing terminal commands and retrieving screen content.
 ///
 /// This abstraction allows core tools (like ShellTool) to interact with a terminal
@@ -25,8 +38,8 @@ pub trait TerminalExecutor: Send + Sync {
     ///
     /// # Returns
     /// * `Ok(String)` - The command output (stdout + stderr)
-    /// * `Err(String)` - Error message if execution failed
-    async fn execute_command(&self, command: String, timeout: Option<Duration>) -> Result<String, String>;
+    /// * `Err(ExecError)` - Why execution failed, `TimedOut` if `timeout` ran out
+    async fn execute_command(&self, command: String, timeout: Option<Duration>) -> Result<String, ExecError>;
 
     /// Get the current terminal screen content.
     ///
@@ -60,39 +73,17 @@ impl Default for DefaultTerminalExecutor {
 
 #[async_trait]
 impl TerminalExecutor for DefaultTerminalExecutor {
-    async fn execute_command(&self, command: String, timeout: Option<Duration>) -> Result<String, String> {
-        use tokio::process::Command;
+    async fn execute_command(&self, command: String, timeout: Option<Duration>) -> Result<String, ExecError> {
         use tokio::time::timeout as tokio_timeout;
 
+        let shell = Shell::for_commands();
         let output_result = if let Some(timeout_duration) = timeout {
             tokio_timeout(
                 timeout_duration,
-                async {
-                    if cfg!(target_os = "windows") {
-                        Command::new("cmd")
-                            .args(["/C", &command])
-                            .output()
-                            .await
-                    } else {
-                        Command::new("sh")
-                            .args(["-c", &command])
-                            .output()
-                            .await
-                    }
-                }
+                shell.command(&command).kill_on_drop(true).output(),
             ).await
         } else {
-            let result = if cfg!(target_os = "windows") {
-                Command::new("cmd")
-                    .args(["/C", &command])
-                    .output()
-                    .await
-            } else {
-                Command::new("sh")
-                    .args(["-c", &command])
-                    .output()
-                    .await
-            };
+            let result = shell.command(&command).output().await;
             match result {
                 Ok(output) => Ok(Ok(output)),
                 Err(e) => Ok(Err(e)),
@@ -100,33 +91,9 @@ impl TerminalExecutor for DefaultTerminalExecutor {
         };
 
         match output_result {
-            Ok(Ok(output)) => {
-                let mut result = String::new();
-                
-                // Add stdout
-                if !output.stdout.is_empty() {
-                    result.push_str(&String::from_utf8_lossy(&output.stdout));
-                }
-                
-                // Add stderr
-                if !output.stderr.is_empty() {
-                    if !result.is_empty() {
-                        result.push_str("\n\n[stderr]:\n");
-                    } else {
-                        result.push_str("[stderr]:\n");
-                    }
-                    result.push_str(&String::from_utf8_lossy(&output.stderr));
-                }
-                
-                if output.status.success() {
-                    Ok(result)
-                } else {
-                    let exit_code = output.status.code().unwrap_or(-1);
-                    Err(format!("Exit code {}: {}", exit_code, result))
-                }
-            }
-            Ok(Err(e)) => Err(format!("Command failed: {}", e)),
-            Err(_) => Err("Command timed out".to_string()),
+            Ok(Ok(output)) => format_output(&output),
+            Ok(Err(e)) => Err(ExecError::Failed(format!("Command failed: {}", e))),
+            Err(_) => Err(ExecError::TimedOut),
         }
     }
 
@@ -137,6 +104,34 @@ impl TerminalExecutor for DefaultTerminalExecutor {
     }
 }
 
+/// Combine stdout and stderr the way every executor reports them; a
+/// non-zero exit becomes `Err` with the exit code
+pub(crate) fn format_output(output: &std::process::Output) -> Result<String, ExecError> {
+    let mut result = String::new();
+    
+    // Add stdout
+    if !output.stdout.is_empty() {
+        result.push_str(&String::from_utf8_lossy(&output.stdout));
+    }
+    
+    // Add stderr
+    if !output.stderr.is_empty() {
+        if !result.is_empty() {
+            result.push_str("\n\n[stderr]:\n");
+        } else {
+            result.push_str("[stderr]:\n");
+        }
+        result.push_str(&String::from_utf8_lossy(&output.stderr));
+    }
+    
+    if output.status.success() {
+        Ok(result)
+    } else {
+        let exit_code = output.status.code().unwrap_or(-1);
+        Err(ExecError::Failed(format!("Exit code {}: {}", exit_code, result)))
+    }
+}
+
 /// A terminal executor that wraps an Arc<dyn TerminalExecutor>.
 ///
 /// This allows sharing a single terminal executor instance across multiple tools.
@@ -166,7 +161,7 @@ impl Clone for SharedTerminalExecutor {
 
 #[async_trait]
 impl TerminalExecutor for SharedTerminalExecutor {
-    async fn execute_command(&self, command: String, timeout: Option<Duration>) -> Result<String, String> {
+    async fn execute_command(&self, command: String, timeout: Option<Duration>) -> Result<String, ExecError> {
         self.inner.execute_command(command, timeout).await
     }
 
@@ -177,3 +172,17 @@ impl TerminalExecutor for SharedTerminalExecutor {
 
 /// Type alias for a shared terminal executor reference
 pub type TerminalExecutorRef = Arc<dyn TerminalExecutor>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_default_executor_reports_timeouts() {
+        let executor = DefaultTerminalExecutor::new();
+        let result = executor.execute_command("sleep 5".to_string(), Some(Duration::from_millis(100))).await;
+        assert_eq!(result, Err(ExecError::TimedOut));
+        let result = executor.execute_command("echo 'timed out' >&2; exit 3".to_string(), None).await;
+        assert!(matches!(result, Err(ExecError::Failed(e)) if e.starts_with("Exit code 3")));
+    }
+}
@@ -5,7 +5,9 @@
 use std::sync::Arc;
 use crate::agent::runtime::core::{
     LLMCapability, ToolCapability, ApprovalCapability, WorkerCapability, TelemetryCapability,
+    PlanCapability,
 };
+use crate::agent::runtime::capabilities::SessionPlan;
 
 /// Capability graph - all capabilities wired together
 #[derive(Clone)]
@@ -15,6 +17,8 @@ pub struct CapabilityGraph {
     pub approval: Arc<dyn ApprovalCapability>,
     pub workers: Arc<dyn WorkerCapability>,
     pub telemetry: Arc<dyn TelemetryCapability>,
+    /// Session plan; calls to tools that use it go here rather than to `tools`
+    pub plan: Arc<dyn PlanCapability>,
 }
 
 impl CapabilityGraph {
@@ -32,9 +36,16 @@ impl CapabilityGraph {
             approval,
             workers,
             telemetry,
+            plan: Arc::new(SessionPlan::new()),
         }
     }
     
+    /// Share `plan` with the frontend instead of a private one
+    pub fn with_plan(mut self, plan: Arc<dyn PlanCapability>) -> Self {
+        self.plan = plan;
+        self
+    }
+    
     /// Create a stub capability graph for testing
     pub fn stub() -> Self {
         use crate::agent::runtime::stubs::{StubLLM, StubTools, StubApproval, StubWorkers, StubTelemetry};
@@ -44,6 +55,7 @@ impl CapabilityGraph {
             approval: Arc::new(StubApproval),
             workers: Arc::new(StubWorkers),
             telemetry: Arc::new(StubTelemetry),
+            plan: Arc::new(SessionPlan::new()),
         }
     }
 }
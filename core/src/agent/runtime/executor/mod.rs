@@ -5,6 +5,8 @@
 
 pub mod runtime;
 pub mod graph;
+pub mod trace;
 
 pub use runtime::AgentRuntime;
 pub use graph::CapabilityGraph;
+pub use trace::{TraceEntry, TraceRecorder, ReplayCapabilities};
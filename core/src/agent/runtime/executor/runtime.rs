@@ -3,18 +3,51 @@
 //! Interprets AgentDecision using CapabilityGraph.
 //! No decision logic. Pure intent dispatch.
 
-use crate::agent::runtime::core::{RuntimeContext, RuntimeError};
+use std::sync::Arc;
+use crate::agent::runtime::core::{RuntimeContext, RuntimeError, ToolCapability};
+use crate::agent::tools::{schema, PlanTool};
 use crate::agent::runtime::executor::graph::CapabilityGraph;
+use crate::agent::runtime::executor::trace::{TraceEntry, TraceRecorder};
 use crate::agent::cognition::{AgentDecision, InputEvent};
 /// Agent runtime - intent interpreter
 #[derive(Clone)]
 pub struct AgentRuntime {
     graph: CapabilityGraph,
+    /// Records events for replay (optional)
+    trace: Option<Arc<TraceRecorder>>,
 }
 
 impl AgentRuntime {
     pub fn new(graph: CapabilityGraph) -> Self {
-        Self { graph }
+        Self { graph, trace: None }
+    }
+    
+    /// Record every event this runtime produces to `recorder`
+    pub fn with_trace(mut self, recorder: Arc<TraceRecorder>) -> Self {
+        self.trace = Some(recorder);
+        self
+    }
+    
+    /// The trace recorder, if any (the session records inputs and transitions)
+    pub fn trace(&self) -> Option<&Arc<TraceRecorder>> {
+        self.trace.as_ref()
+    }
+    
+    /// Interpret a decision, recording its event or error in the trace
+    pub async fn interpret(
+        &self,
+        ctx: &RuntimeContext,
+        decision: AgentDecision,
+    ) -> Result<Option<InputEvent>, RuntimeError> {
+        let result = self.dispatch(ctx, decision).await;
+        if let Some(trace) = &self.trace {
+            match &result {
+                Ok(Some(event)) => trace.record(TraceEntry::Event { event: event.clone() }),
+                Ok(None) => {}
+                Err(e) => trace.record(TraceEntry::Error { message: e.to_string() }),
+            }
+        }
+        result
     }
     
     /// Interpret AgentDecision, optionally producing InputEvent
@@ -24,7 +57,7 @@ impl AgentRuntime {
     /// 2. Dispatch to capability
     /// 3. Record result
     /// 4. Return event (if any)
-    pub async fn interpret(
+    async fn dispatch(
         &self,
         ctx: &RuntimeContext,
         decision: AgentDecision,
@@ -41,7 +74,14 @@ impl AgentRuntime {
         let result = match decision {
             AgentDecision::CallTool(call) => {
                 let tool_name = call.name.clone();
-                let tool_result = self.graph.tools.execute(ctx, call).await?;
+                let tool_result = if self.graph.tools.uses_session_plan(&tool_name) {
+                    match schema::validate_call(call) {
+                        Ok(call) => PlanTool::new(self.graph.plan.clone()).execute(ctx, call).await?,
+                        Err(invalid) => invalid,
+                    }
+                } else {
+                    self.graph.tools.execute(ctx, call).await?
+                };
                 let event = InputEvent::ToolResult {
                     tool: tool_name,
                     result: tool_result,
@@ -0,0 +1,319 @@
+//! Execution traces
+//!
+//! A trace is a JSONL file of everything that crossed the cognition/runtime
+//! boundary in a session: external inputs, every transition's decision, and
+//! every event the runtime produced (LLM responses, tool results, approval
+//! outcomes, errors). Since the engine is deterministic, feeding the same
+//! inputs and runtime events back in must reproduce the same decisions;
+//! `ReplayCapabilities` serves the recorded events instead of calling the
+//! LLM, tools or user.
+
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::agent::cognition::{AgentDecision, InputEvent};
+use crate::agent::runtime::core::{
+    ApprovalCapability, ApprovalError, Capability, LLMCapability, LLMError, RuntimeContext, StreamChunk,
+    ToolCapability, ToolError,
+};
+use crate::agent::types::events::{ApprovalOutcome, LLMResponse, ToolResult};
+use crate::agent::types::intents::{ApprovalRequest, LLMRequest, ToolCall};
+
+/// Trace format version, written in the header line
+pub const TRACE_VERSION: u32 = 1;
+
+/// One line of a trace file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TraceEntry {
+    /// First line of every trace
+    Header { version: u32, created_at: String },
+    /// External input handed to the session, with its step limit
+    Input { event: InputEvent, max_steps: usize },
+    /// Decision of one cognitive step
+    Transition { step: usize, decision: AgentDecision },
+    /// Event the runtime produced for a decision
+    Event { event: InputEvent },
+    /// A capability failed instead of producing an event
+    Error { message: String },
+}
+
+/// Where trace entries go
+enum Sink {
+    File(std::fs::File),
+    Memory(Vec<TraceEntry>),
+}
+
+/// Appends trace entries to a file (or memory, for replays and tests)
+pub struct TraceRecorder {
+    sink: Mutex<Sink>,
+}
+
+impl TraceRecorder {
+    /// Create `path` (truncating it) and write the header
+    pub fn create(path: &Path) -> std::io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let recorder = Self {
+            sink: Mutex::new(Sink::File(std::fs::File::create(path)?)),
+        };
+        recorder.record(TraceEntry::Header {
+            version: TRACE_VERSION,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        });
+        Ok(recorder)
+    }
+
+    /// A recorder for a new session when `features.record_traces` is on
+    ///
+    /// The trace goes to `traces/<time>-<id>.jsonl` in the config directory.
+    /// A trace that cannot be created is logged and the session runs without.
+    pub fn for_session(config: &crate::config::Config) -> Option<Self> {
+        if !config.features.record_traces {
+            return None;
+        }
+        let path = session_trace_path()?;
+        match Self::create(&path) {
+            Ok(recorder) => {
+                crate::info_log!("[TRACE] Recording session trace to {}", path.display());
+                Some(recorder)
+            }
+            Err(e) => {
+                crate::warn_log!("[TRACE] Failed to create {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Keep entries in memory; read them back with `entries()`
+    pub fn in_memory() -> Self {
+        Self {
+            sink: Mutex::new(Sink::Memory(Vec::new())),
+        }
+    }
+
+    /// Append one entry; write failures are logged, never fatal
+    pub fn record(&self, entry: TraceEntry) {
+        let mut sink = self.sink.lock().unwrap_or_else(|e| e.into_inner());
+        match &mut *sink {
+            Sink::File(file) => {
+                let written = serde_json::to_string(&entry)
+                    .map_err(|e| e.to_string())
+                    .and_then(|line| writeln!(file, "{}", line).map_err(|e| e.to_string()));
+                if let Err(e) = written {
+                    crate::warn_log!("[TRACE] Failed to write trace entry: {}", e);
+                }
+            }
+            Sink::Memory(entries) => entries.push(entry),
+        }
+    }
+
+    /// Entries recorded so far (empty for file recorders)
+    pub fn entries(&self) -> Vec<TraceEntry> {
+        match &*self.sink.lock().unwrap_or_else(|e| e.into_inner()) {
+            Sink::Memory(entries) => entries.clone(),
+            Sink::File(_) => Vec::new(),
+        }
+    }
+}
+
+/// A fresh trace path in the config directory's `traces/`
+pub fn session_trace_path() -> Option<PathBuf> {
+    let name = format!(
+        "{}-{}.jsonl",
+        chrono::Utc::now().format("%Y%m%dT%H%M%S"),
+        &uuid::Uuid::new_v4().simple().to_string()[..8]
+    );
+    crate::config::get_config_dir().map(|dir| dir.join("traces").join(name))
+}
+
+/// Read a trace file, checking its version
+pub fn load_trace(path: &Path) -> Result<Vec<TraceEntry>, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut entries = Vec::new();
+    for (number, line) in content.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+        let entry: TraceEntry = serde_json::from_str(line)
+            .map_err(|e| format!("{}:{}: invalid trace entry: {}", path.display(), number + 1, e))?;
+        if let TraceEntry::Header { version, .. } = &entry {
+            if *version != TRACE_VERSION {
+                return Err(format!("Unsupported trace version {} (expected {})", version, TRACE_VERSION));
+            }
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// LLM, tool and approval capabilities that answer from a trace
+///
+/// Recorded events and errors are served in order; a request of the wrong
+/// kind, or one past the end of the trace, fails with an explanation so the
+/// replay stops at the point of divergence.
+pub struct ReplayCapabilities {
+    outcomes: Mutex<VecDeque<TraceEntry>>,
+}
+
+impl ReplayCapabilities {
+    pub fn new(trace: &[TraceEntry]) -> Self {
+        let outcomes = trace
+            .iter()
+            .filter(|entry| matches!(entry, TraceEntry::Event { .. } | TraceEntry::Error { .. }))
+            .cloned()
+            .collect();
+        Self {
+            outcomes: Mutex::new(outcomes),
+        }
+    }
+
+    /// Recorded events not served yet
+    pub fn remaining(&self) -> usize {
+        self.outcomes.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Next recorded event, if `accept` takes it; errors are returned as-is
+    fn next<T>(&self, wanted: &str, accept: impl FnOnce(InputEvent) -> Option<T>) -> Result<T, String> {
+        let mut outcomes = self.outcomes.lock().unwrap_or_else(|e| e.into_inner());
+        match outcomes.pop_front() {
+            Some(TraceEntry::Event { event }) => {
+                let recorded = format!("{:?}", event);
+                accept(event).ok_or_else(|| {
+                    format!("replay diverged: runtime asked for {} but the trace has {:.120}", wanted, recorded)
+                })
+            }
+            Some(TraceEntry::Error { message }) => Err(message),
+            _ => Err(format!("replay diverged: runtime asked for {} past the end of the trace", wanted)),
+        }
+    }
+}
+
+impl Capability for ReplayCapabilities {
+    fn name(&self) -> &'static str {
+        "replay"
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMCapability for ReplayCapabilities {
+    async fn complete(&self, _ctx: &RuntimeContext, _req: LLMRequest) -> Result<LLMResponse, LLMError> {
+        self.next("an LLM response", |event| match event {
+            InputEvent::LLMResponse(response) => Some(response),
+            _ => None,
+        })
+        .map_err(LLMError::new)
+    }
+
+    fn complete_stream<'a>(
+        &'a self,
+        ctx: &'a RuntimeContext,
+        req: LLMRequest,
+    ) -> std::pin::Pin<Box<dyn futures::Stream<Item = Result<StreamChunk, LLMError>> + Send + 'a>> {
+        Box::pin(futures::stream::once(async move {
+            let response = self.complete(ctx, req).await?;
+            Ok(StreamChunk {
+                content: response.content,
+                is_final: true,
+                usage: Some(response.usage),
+                tool_calls: response.tool_calls,
+            })
+        }))
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolCapability for ReplayCapabilities {
+    async fn execute(&self, _ctx: &RuntimeContext, call: ToolCall) -> Result<ToolResult, ToolError> {
+        self.next(&format!("the result of tool '{}'", call.name), |event| match event {
+            InputEvent::ToolResult { tool, result } if tool == call.name => Some(result),
+            _ => None,
+        })
+        .map_err(ToolError::new)
+    }
+}
+
+#[async_trait::async_trait]
+impl ApprovalCapability for ReplayCapabilities {
+    async fn request(&self, _ctx: &RuntimeContext, req: ApprovalRequest) -> Result<ApprovalOutcome, ApprovalError> {
+        self.next(&format!("approval of '{}'", req.tool), |event| match event {
+            InputEvent::ApprovalResult(outcome) => Some(outcome),
+            _ => None,
+        })
+        .map_err(ApprovalError::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::types::events::{FinishReason, TokenUsage};
+
+    #[test]
+    fn test_trace_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("traces").join("run.jsonl");
+        let recorder = TraceRecorder::create(&path).unwrap();
+        let entries = vec![
+            TraceEntry::Input { event: InputEvent::UserMessage("list files".to_string()), max_steps: 50 },
+            TraceEntry::Transition { step: 1, decision: AgentDecision::EmitResponse("done".to_string()) },
+            TraceEntry::Event {
+                event: InputEvent::ToolResult {
+                    tool: "list_files".to_string(),
+                    result: ToolResult::Success { output: "a.rs".to_string(), structured: None },
+                },
+            },
+            TraceEntry::Error { message: "LLM error: timeout".to_string() },
+        ];
+        for entry in &entries {
+            recorder.record(entry.clone());
+        }
+
+        let loaded = load_trace(&path).unwrap();
+        assert!(matches!(loaded[0], TraceEntry::Header { version: TRACE_VERSION, .. }));
+        assert_eq!(loaded[1..], entries[..]);
+    }
+
+    #[tokio::test]
+    async fn test_replay_serves_events_in_order() {
+        let response = LLMResponse {
+            content: "hi".to_string(),
+            usage: TokenUsage::default(),
+            model: "m".to_string(),
+            provider: "p".to_string(),
+            finish_reason: FinishReason::Stop,
+            structured: None,
+            tool_calls: Vec::new(),
+        };
+        let replay = ReplayCapabilities::new(&[
+            TraceEntry::Input { event: InputEvent::UserMessage("hi".to_string()), max_steps: 5 },
+            TraceEntry::Event { event: InputEvent::LLMResponse(response.clone()) },
+            TraceEntry::Error { message: "rate limited".to_string() },
+        ]);
+        assert_eq!(replay.remaining(), 2);
+
+        let ctx = RuntimeContext::new();
+        let request = LLMRequest {
+            context: crate::agent::types::intents::Context::new(""),
+            max_tokens: None,
+            temperature: None,
+            model: None,
+            response_format: None,
+            stream: false,
+            retry_attempt: 0,
+            extra_system_messages: Vec::new(),
+        };
+        assert_eq!(replay.complete(&ctx, request.clone()).await.unwrap(), response);
+        assert_eq!(replay.complete(&ctx, request).await.unwrap_err().message, "rate limited");
+        let call = ToolCall {
+            name: "shell".to_string(),
+            arguments: serde_json::json!({}),
+            working_dir: None,
+            timeout_secs: None,
+        };
+        let error = ToolCapability::execute(&replay, &ctx, call).await.unwrap_err();
+        assert!(error.message.contains("past the end of the trace"));
+    }
+}
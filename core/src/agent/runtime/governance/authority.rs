@@ -83,6 +83,7 @@ impl Default for WorkerPermissions {
         let mut allowed_tools = HashSet::new();
         allowed_tools.insert("read_file".to_string());
         allowed_tools.insert("list_files".to_string());
+        allowed_tools.insert("code_search".to_string());
         allowed_tools.insert("git_status".to_string());
         allowed_tools.insert("git_log".to_string());
         allowed_tools.insert("git_diff".to_string());
@@ -92,6 +93,10 @@ impl Default for WorkerPermissions {
         let mut escalate_tools = HashSet::new();
         escalate_tools.insert("shell".to_string());
         escalate_tools.insert("write_file".to_string());
+        escalate_tools.insert("apply_patch".to_string());
+        escalate_tools.insert("docker".to_string());
+        escalate_tools.insert("kubectl".to_string());
+        escalate_tools.insert("run_tests".to_string());
         escalate_tools.insert("delegate".to_string()); // Workers can't spawn workers
 
         let mut forbidden_tools = HashSet::new();
@@ -173,6 +178,7 @@ impl WorkerPermissions {
             "curl *".to_string(),
             "wget *".to_string(),
             "docker *".to_string(),
+            "kubectl *".to_string(),
             "git push*".to_string(),
             "git pull*".to_string(),
         ]
@@ -0,0 +1,206 @@
+//! Per-session cost and token budget
+//!
+//! `SessionBudget` adds up the token usage of every LLM call in a session
+//! (including its workers) and refuses further calls once a limit from
+//! `features.budget` is reached. The runtime turns a refusal into a halt.
+//!
+//! ```toml
+//! [features.budget]
+//! max_session_cost_usd = 2.50
+//! max_session_tokens = 500000
+//! ```
+
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::agent::types::events::TokenUsage;
+use crate::config::{BudgetSettings, Config};
+
+/// A limit that has been reached
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum BudgetExceeded {
+    #[error("session cost budget exhausted: ${spent:.4} spent of ${limit:.2}")]
+    Cost { spent: f64, limit: f64 },
+    #[error("session token budget exhausted: {used} tokens used of {limit}")]
+    Tokens { used: u64, limit: u64 },
+}
+
+/// Snapshot of a budget for display
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BudgetStatus {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost_usd: f64,
+    pub max_session_cost_usd: Option<f64>,
+    pub max_session_tokens: Option<u64>,
+    /// False when a cost limit is set but the profile has no pricing
+    pub priced: bool,
+}
+
+impl BudgetStatus {
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+
+    pub fn remaining_tokens(&self) -> Option<u64> {
+        self.max_session_tokens
+            .map(|limit| limit.saturating_sub(self.total_tokens()))
+    }
+
+    pub fn remaining_cost_usd(&self) -> Option<f64> {
+        self.max_session_cost_usd
+            .map(|limit| (limit - self.cost_usd).max(0.0))
+    }
+}
+
+#[derive(Debug, Default)]
+struct Spent {
+    prompt_tokens: u64,
+    completion_tokens: u64,
+    cost_usd: f64,
+}
+
+/// Running totals and limits for one session
+#[derive(Debug, Default)]
+pub struct SessionBudget {
+    settings: Mutex<BudgetSettings>,
+    input_price_per_million: f64,
+    output_price_per_million: f64,
+    spent: Mutex<Spent>,
+}
+
+impl SessionBudget {
+    /// Budget with the given limits and per-million-token prices
+    pub fn new(settings: BudgetSettings, input_price_per_million: f64, output_price_per_million: f64) -> Self {
+        Self {
+            settings: Mutex::new(settings),
+            input_price_per_million,
+            output_price_per_million,
+            spent: Mutex::new(Spent::default()),
+        }
+    }
+
+    /// Limits from `features.budget`, priced by the active profile
+    pub fn from_config(config: &Config) -> Self {
+        let profile = config.active_profile();
+        let budget = Self::new(
+            config.features.budget.clone(),
+            profile.input_price.unwrap_or(0.0),
+            profile.output_price.unwrap_or(0.0),
+        );
+        if budget.limits().max_session_cost_usd.is_some() && !budget.is_priced() {
+            crate::warn_log!(
+                "[BUDGET] max_session_cost_usd is set but the active profile has no input_price/output_price; only the token limit applies"
+            );
+        }
+        budget
+    }
+
+    /// No limits; usage is still tracked
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Current limits
+    pub fn limits(&self) -> BudgetSettings {
+        self.settings.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Replace the limits; usage so far is kept
+    pub fn set_limits(&self, settings: BudgetSettings) {
+        *self.settings.lock().unwrap_or_else(|e| e.into_inner()) = settings;
+    }
+
+    fn is_priced(&self) -> bool {
+        self.input_price_per_million > 0.0 || self.output_price_per_million > 0.0
+    }
+
+    /// Add the usage of one LLM call
+    pub fn record(&self, usage: &TokenUsage) {
+        let cost = usage.prompt_tokens as f64 * self.input_price_per_million / 1_000_000.0
+            + usage.completion_tokens as f64 * self.output_price_per_million / 1_000_000.0;
+        let mut spent = self.spent.lock().unwrap_or_else(|e| e.into_inner());
+        spent.prompt_tokens += u64::from(usage.prompt_tokens);
+        spent.completion_tokens += u64::from(usage.completion_tokens);
+        spent.cost_usd += cost;
+    }
+
+    /// Whether another LLM call is allowed
+    pub fn check(&self) -> Result<(), BudgetExceeded> {
+        let status = self.status();
+        if let Some(limit) = status.max_session_tokens {
+            if status.total_tokens() >= limit {
+                return Err(BudgetExceeded::Tokens {
+                    used: status.total_tokens(),
+                    limit,
+                });
+            }
+        }
+        if let Some(limit) = status.max_session_cost_usd {
+            if status.priced && status.cost_usd >= limit {
+                return Err(BudgetExceeded::Cost {
+                    spent: status.cost_usd,
+                    limit,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn status(&self) -> BudgetStatus {
+        let limits = self.limits();
+        let spent = self.spent.lock().unwrap_or_else(|e| e.into_inner());
+        BudgetStatus {
+            prompt_tokens: spent.prompt_tokens,
+            completion_tokens: spent.completion_tokens,
+            cost_usd: spent.cost_usd,
+            max_session_cost_usd: limits.max_session_cost_usd,
+            max_session_tokens: limits.max_session_tokens,
+            priced: self.is_priced(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(cost: Option<f64>, tokens: Option<u64>) -> BudgetSettings {
+        BudgetSettings {
+            max_session_cost_usd: cost,
+            max_session_tokens: tokens,
+        }
+    }
+
+    #[test]
+    fn test_token_limit() {
+        let budget = SessionBudget::new(limits(None, Some(1000)), 0.0, 0.0);
+        budget.record(&TokenUsage::new(600, 300));
+        assert!(budget.check().is_ok());
+        budget.record(&TokenUsage::new(80, 20));
+        assert_eq!(budget.check(), Err(BudgetExceeded::Tokens { used: 1000, limit: 1000 }));
+        assert_eq!(budget.status().remaining_tokens(), Some(0));
+    }
+
+    #[test]
+    fn test_cost_limit_uses_profile_pricing() {
+        // $3 in / $15 out per million tokens
+        let budget = SessionBudget::new(limits(Some(0.05), None), 3.0, 15.0);
+        budget.record(&TokenUsage::new(10_000, 1_000));
+        let status = budget.status();
+        assert!((status.cost_usd - 0.045).abs() < 1e-9);
+        assert!(budget.check().is_ok());
+
+        budget.record(&TokenUsage::new(2_000, 0));
+        assert!(matches!(budget.check(), Err(BudgetExceeded::Cost { .. })));
+    }
+
+    #[test]
+    fn test_cost_limit_ignored_without_pricing() {
+        let budget = SessionBudget::new(limits(Some(0.01), None), 0.0, 0.0);
+        budget.record(&TokenUsage::new(1_000_000, 1_000_000));
+        assert!(budget.check().is_ok());
+        assert!(!budget.status().priced);
+    }
+}
@@ -65,7 +65,7 @@ impl ClaimEnforcer {
         let mut resources = Vec::new();
 
         match call.name.as_str() {
-            "write_file" | "read_file" | "append_file" | "edit_file" => {
+            "write_file" | "apply_patch" | "read_file" | "append_file" | "edit_file" => {
                 if let Some(path) = call.arguments.get("path").and_then(|v| v.as_str()) {
                     resources.push(path.to_string());
                 }
@@ -92,7 +92,7 @@ impl ClaimEnforcer {
     fn requires_claim(&self, call: &ToolCall) -> bool {
         match call.name.as_str() {
             // Write operations require claims
-            "write_file" | "append_file" | "edit_file" | "shell" | "bash" => true,
+            "write_file" | "apply_patch" | "append_file" | "edit_file" | "shell" | "bash" => true,
             // Read operations don't require claims
             "read_file" | "list_files" | "git" => false,
             _ => false,
@@ -0,0 +1,230 @@
+//! Host allowlist for the http tool
+//!
+//! The http tool only talks to hosts listed in the active profile's
+//! `http_permissions`. Nothing is allowed by default. A project overlay
+//! (`.mylm/permissions.toml`, or `permissions.http` in `.mylm.yaml`) can
+//! only narrow that list: its `[http]` entries apply where a profile entry
+//! already covers them, and replace the profile's list.
+//!
+//! ```toml
+//! # .mylm/permissions.toml
+//! [http]
+//! allowed_hosts = ["api.github.com", "*.internal.example.com", "localhost:8080"]
+//! ```
+
+use std::path::Path;
+
+use reqwest::Url;
+
+use crate::agent::runtime::governance::write_scope::load_project_overlay;
+use crate::config::{HttpPermissions, ProfileConfig};
+
+/// Whether `raw` is a usable allowlist entry
+pub fn is_valid_host(raw: &str) -> bool {
+    HostPattern::parse(raw).is_some()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct HostPattern {
+    raw: String,
+    /// Lowercase host, without the `*.` prefix for wildcards
+    host: String,
+    /// Match subdomains of `host` instead of `host` itself
+    wildcard: bool,
+    port: Option<u16>,
+}
+
+impl HostPattern {
+    fn parse(raw: &str) -> Option<Self> {
+        let trimmed = raw.trim();
+        // Accept full URLs too; only their host and port count
+        let (host, port) = if trimmed.contains("://") {
+            let url = Url::parse(trimmed).ok()?;
+            (url.host_str()?.to_string(), url.port())
+        } else {
+            match trimmed.rsplit_once(':') {
+                Some((host, port)) if !port.is_empty() && port.chars().all(|c| c.is_ascii_digit()) => {
+                    (host.to_string(), Some(port.parse().ok()?))
+                }
+                _ => (trimmed.to_string(), None),
+            }
+        };
+        let host = host.to_lowercase();
+        let (host, wildcard) = match host.strip_prefix("*.") {
+            Some(rest) => (rest.to_string(), true),
+            None => (host, false),
+        };
+        if host.is_empty() || host.contains('*') || host.contains('/') {
+            return None;
+        }
+        Some(Self {
+            raw: raw.to_string(),
+            host,
+            wildcard,
+            port,
+        })
+    }
+
+    /// Whether everything `other` matches, this matches too
+    fn covers(&self, other: &HostPattern) -> bool {
+        let host_covered = match (self.wildcard, other.wildcard) {
+            (false, false) => self.host == other.host,
+            (false, true) => false,
+            (true, _) => other.host.strip_suffix(&self.host).is_some_and(|prefix| prefix.ends_with('.')),
+        };
+        host_covered && self.port.is_none_or(|p| other.port == Some(p))
+    }
+
+    fn matches(&self, host: &str, port: Option<u16>) -> bool {
+        let host_matches = if self.wildcard {
+            host.strip_suffix(&self.host).is_some_and(|prefix| prefix.ends_with('.'))
+        } else {
+            host == self.host
+        };
+        host_matches && self.port.is_none_or(|p| Some(p) == port)
+    }
+}
+
+/// Hosts the http tool may call
+#[derive(Debug, Clone, Default)]
+pub struct HostAllowlist {
+    patterns: Vec<HostPattern>,
+}
+
+impl HostAllowlist {
+    /// Nothing allowed
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hosts from `profile`, narrowed by the project overlay found above `cwd`
+    pub fn for_profile(profile: &ProfileConfig, cwd: &Path) -> Self {
+        let mut allowlist = Self::new();
+        allowlist.add(&profile.http_permissions);
+        if let Some((root, overlay)) = load_project_overlay(cwd) {
+            if !overlay.http.allowed_hosts.is_empty() {
+                crate::info_log!("[HTTP_SCOPE] Using project overlay in {}", root.display());
+                let mut project = Self::new();
+                project.add(&overlay.http);
+                allowlist = allowlist.narrowed_to(project);
+            }
+        }
+        allowlist
+    }
+
+    /// The entries of `other` this list already covers; the rest are dropped
+    pub fn narrowed_to(&self, other: HostAllowlist) -> Self {
+        let patterns = other
+            .patterns
+            .into_iter()
+            .filter(|pattern| {
+                let covered = self.patterns.iter().any(|p| p.covers(pattern));
+                if !covered {
+                    crate::warn_log!("[HTTP_SCOPE] Ignoring project host '{}': not allowed by the profile", pattern.raw);
+                }
+                covered
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    pub fn add(&mut self, permissions: &HttpPermissions) {
+        for raw in &permissions.allowed_hosts {
+            match HostPattern::parse(raw) {
+                Some(pattern) => self.patterns.push(pattern),
+                None => crate::warn_log!("[HTTP_SCOPE] Ignoring invalid host '{}'", raw),
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Allowlist entry that permits `url`, if any
+    pub fn matched_entry(&self, url: &Url) -> Option<&str> {
+        let host = url.host_str()?.to_lowercase();
+        let port = url.port_or_known_default();
+        self.patterns
+            .iter()
+            .find(|p| p.matches(&host, port))
+            .map(|p| p.raw.as_str())
+    }
+
+    pub fn allows(&self, url: &Url) -> bool {
+        self.matched_entry(url).is_some()
+    }
+
+    /// Entries as written in config, for error messages
+    pub fn entries(&self) -> Vec<&str> {
+        self.patterns.iter().map(|p| p.raw.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::runtime::governance::write_scope::PROJECT_OVERLAY;
+
+    fn allowlist(hosts: &[&str]) -> HostAllowlist {
+        let mut allowlist = HostAllowlist::new();
+        allowlist.add(&HttpPermissions {
+            allowed_hosts: hosts.iter().map(|s| s.to_string()).collect(),
+        });
+        allowlist
+    }
+
+    fn allowed(allowlist: &HostAllowlist, url: &str) -> bool {
+        allowlist.allows(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn test_host_patterns() {
+        let list = allowlist(&["api.github.com", "*.example.com", "localhost:8080", "https://httpbin.org"]);
+        assert!(allowed(&list, "https://api.github.com/repos"));
+        assert!(allowed(&list, "https://API.GitHub.com/"));
+        assert!(!allowed(&list, "https://github.com/"));
+        assert!(!allowed(&list, "https://api.github.com.evil.io/"));
+
+        assert!(allowed(&list, "https://a.example.com/"));
+        assert!(allowed(&list, "https://a.b.example.com/"));
+        assert!(!allowed(&list, "https://example.com/"));
+        assert!(!allowed(&list, "https://badexample.com/"));
+
+        assert!(allowed(&list, "http://localhost:8080/health"));
+        assert!(!allowed(&list, "http://localhost:9090/health"));
+        assert!(allowed(&list, "http://httpbin.org/get"));
+    }
+
+    #[test]
+    fn test_empty_allowlist_blocks_everything() {
+        let list = HostAllowlist::new();
+        assert!(list.is_empty());
+        assert!(!allowed(&list, "https://api.github.com/"));
+    }
+
+    #[test]
+    fn test_project_overlay_hosts() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join(".mylm")).unwrap();
+        std::fs::write(
+            root.path().join(PROJECT_OVERLAY),
+            "[http]\nallowed_hosts = [\"localhost:3000\"]\n",
+        )
+        .unwrap();
+
+        // The overlay cannot add hosts the profile does not allow
+        let list = HostAllowlist::for_profile(&ProfileConfig::default(), root.path());
+        assert!(list.is_empty());
+
+        let mut profile = ProfileConfig::default();
+        profile.http_permissions.allowed_hosts = vec!["localhost".to_string(), "*.example.com".to_string()];
+        let list = HostAllowlist::for_profile(&profile, root.path());
+        assert!(allowed(&list, "http://localhost:3000/api"));
+        assert!(!allowed(&list, "http://localhost:9000/api"));
+        assert!(!allowed(&list, "https://a.example.com/"));
+
+        let narrowed = allowlist(&["*.example.com"]).narrowed_to(allowlist(&["api.example.com", "*.b.example.com", "example.com", "evil.io"]));
+        assert_eq!(narrowed.entries(), ["api.example.com", "*.b.example.com"]);
+    }
+}
@@ -14,16 +14,34 @@
 //! - `Authority`: Runtime enforcement engine
 //! - `Enforcer`: Bridges authority to approval system
 //! - `WorkerStall`: Handle stalled worker resolution
+//! - `WriteScopes`: Glob-scoped approval for file writes
+//! - `PolicyRules`: allow/ask/deny approval rules by tool and pattern
+//! - `SecretGuard`: Deny-list that keeps secret files out of file tools
+//! - `SessionBudget`: Cost and token limits for a session
+//! - `HostAllowlist`: Hosts the http tool may call
+//! - `SecretRedactor`: Placeholder redaction of secrets in shared text
 
 pub mod authority;
+pub mod budget;
 pub mod claim_enforcer;
 pub mod enforcer;
+pub mod http_scope;
+pub mod policy;
+pub mod redaction;
+pub mod secret_files;
 pub mod worker_stall;
+pub mod write_scope;
 
 pub use authority::{
     Authority, AuthorityMatrix, MainPermissions, WorkerPermissions,
     ToolAccess, ShellAccess,
 };
+pub use budget::{SessionBudget, BudgetExceeded, BudgetStatus};
 pub use claim_enforcer::{ClaimEnforcer, ClaimEnforcement};
 pub use enforcer::ApprovalEnforcer;
+pub use http_scope::HostAllowlist;
+pub use policy::{PolicyAction, PolicyRule, PolicyRules, SessionRules};
+pub use redaction::SecretRedactor;
+pub use secret_files::{SecretGuard, SecretFileDenied, DEFAULT_SECRET_PATTERNS};
 pub use worker_stall::{WorkerStall, StallResolution, StalledJob};
+pub use write_scope::{WriteScopes, WriteScopeDecision, write_target};
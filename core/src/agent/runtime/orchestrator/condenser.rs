@@ -0,0 +1,297 @@
+//! Condensation of long conversations
+//!
+//! When the history held by the kernel nears the model's context window,
+//! `Condenser` has the model summarize the oldest turns. The session swaps
+//! them for the summary (`KernelEvent::HistoryCondensed`), saves them as a
+//! `CompressedSegment` under the data directory and reports the segment with
+//! `OutputEvent::ContextPruned`. `/pruned` lists saved segments and
+//! `/restore` puts one back (`UserInput::Restore`). Messages the user pinned
+//! (`UserInput::Pinned`) are never summarized; they stay after the summary,
+//! together with the other half of a tool call they belong to.
+
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::agent::runtime::core::{LLMCapability, LLMError, RuntimeContext};
+use crate::agent::types::intents::{Context, LLMRequest};
+use crate::conversation::context_compression::CompressedSegment;
+use crate::conversation::manager::Message;
+use crate::conversation::Tokenizer;
+
+/// Latest messages never summarized
+const KEEP_LAST: usize = 4;
+/// Fewest messages worth a summary
+const MIN_SUMMARIZED: usize = 2;
+/// Characters of one message given to the summarizer; tool output is cut
+const MAX_MESSAGE_CHARS: usize = 4_000;
+/// Summary length cap
+const MAX_SUMMARY_TOKENS: u32 = 1_000;
+
+const SUMMARY_PROMPT: &str = "Summarize the conversation below so an assistant can continue it \
+without the original messages. Keep every fact, decision, file path, command, error and open \
+task; drop greetings and repetition. Write plain bullet points, no preamble.";
+
+/// Oldest turns replaced by a summary
+#[derive(Debug, Clone)]
+pub struct Condensation {
+    /// Messages, from the start of history, the summary replaces
+    pub replaced: usize,
+    /// Pinned messages among those, kept as they are after the summary
+    pub kept: Vec<Message>,
+    /// The replaced messages, with the summary and its ID
+    pub segment: CompressedSegment,
+}
+
+/// Summarizes old turns once history passes a share of the context window
+pub struct Condenser {
+    llm: Arc<dyn LLMCapability>,
+    context_window: usize,
+    threshold: f64,
+    tokenizer: Tokenizer,
+    archive_dir: Option<PathBuf>,
+}
+
+impl Condenser {
+    /// Condense with `llm` for a `context_window`-token model
+    pub fn new(llm: Arc<dyn LLMCapability>, context_window: usize) -> Self {
+        Self {
+            llm,
+            context_window,
+            threshold: 0.8,
+            tokenizer: Tokenizer::default(),
+            archive_dir: CompressedSegment::archive_dir(),
+        }
+    }
+
+    /// Share of the context window (0.0 - 1.0) history may fill
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold.clamp(0.1, 1.0);
+        self
+    }
+
+    /// Count tokens with `tokenizer`
+    pub fn with_tokenizer(mut self, tokenizer: Tokenizer) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    /// Save segments in `dir` (None: keep them in memory only)
+    pub fn with_archive_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.archive_dir = dir;
+        self
+    }
+
+    /// Where segments are saved
+    pub fn archive_dir(&self) -> Option<&Path> {
+        self.archive_dir.as_deref()
+    }
+
+    /// How many of the oldest messages to summarize; None while history fits
+    ///
+    /// History shares the threshold with `system_prompt`, which is sent with
+    /// every request. Enough messages are taken to bring history back to half
+    /// of what is left, never the latest `KEEP_LAST`, and the kept part
+    /// starts with a user message.
+    pub fn split(&self, history: &[Message], system_prompt: &str) -> Option<usize> {
+        let counts: Vec<usize> = history.iter().map(|m| self.tokenizer.count(&m.content)).collect();
+        let total: usize = counts.iter().sum();
+        let limit = ((self.context_window as f64 * self.threshold) as usize)
+            .saturating_sub(self.tokenizer.count(system_prompt));
+        if total <= limit {
+            return None;
+        }
+        let last = history.len().saturating_sub(KEEP_LAST);
+        let mut remaining = total;
+        let mut split = 0;
+        while split < last && remaining > limit / 2 {
+            remaining -= counts[split];
+            split += 1;
+        }
+        while split < last && history[split].role != "user" {
+            split += 1;
+        }
+        (split >= MIN_SUMMARIZED).then_some(split)
+    }
+
+    /// Summarize the oldest turns of `history` if it and `system_prompt`
+    /// are over the threshold, leaving out the messages `pinned_messages` keeps
+    ///
+    /// The segment is saved to the archive directory; a failed save is
+    /// logged and the segment can then only be restored in this process.
+    pub async fn condense(
+        &self,
+        history: &[Message],
+        system_prompt: &str,
+        pinned: &[String],
+    ) -> Result<Option<Condensation>, LLMError> {
+        let Some(split) = self.split(history, system_prompt) else {
+            return Ok(None);
+        };
+        let keep = pinned_messages(&history[..split], pinned);
+        let (mut kept, mut old) = (Vec::new(), Vec::new());
+        for (message, keep) in history[..split].iter().zip(keep) {
+            if keep {
+                kept.push(message.clone());
+            } else {
+                old.push(message.clone());
+            }
+        }
+        if old.len() < MIN_SUMMARIZED {
+            return Ok(None);
+        }
+        let transcript: Vec<String> = old
+            .iter()
+            .map(|m| {
+                let content: String = m.content.chars().take(MAX_MESSAGE_CHARS).collect();
+                format!("{}: {}", m.role, content)
+            })
+            .collect();
+        let mut request = LLMRequest::new(Context::new(transcript.join("\n\n")).with_system(SUMMARY_PROMPT));
+        request.max_tokens = Some(MAX_SUMMARY_TOKENS);
+        let response = self.llm.complete(&RuntimeContext::new(), request).await?;
+        let summary = response.content.trim();
+        if summary.is_empty() {
+            return Err(LLMError::new("The model returned an empty summary"));
+        }
+
+        let old_tokens: usize = old.iter().map(|m| self.tokenizer.count(&m.content)).sum();
+        let mut segment = CompressedSegment::new(old, 0);
+        segment.summary = summary.to_string();
+        segment.tokens_saved = old_tokens.saturating_sub(self.tokenizer.count(&segment.summary_message()));
+        if let Some(ref dir) = self.archive_dir {
+            if let Err(e) = segment.save(dir) {
+                crate::warn_log!("[CONDENSE] Cannot save segment {} to {}: {}", segment.id, dir.display(), e);
+            }
+        }
+        crate::info_log!(
+            "[CONDENSE] Summarized {} messages, kept {} pinned, ~{} tokens saved (segment {})",
+            segment.message_count,
+            kept.len(),
+            segment.tokens_saved,
+            segment.id
+        );
+        Ok(Some(Condensation {
+            replaced: split,
+            kept,
+            segment,
+        }))
+    }
+}
+
+/// The text the user saw for `message`: a tool result without its status
+/// line, the answer of a Short-Key reply, otherwise the content
+fn shown_text(message: &Message) -> Cow<'_, str> {
+    let content = message.content.as_str();
+    match message.role.as_str() {
+        "tool" => content
+            .strip_prefix("Tool '")
+            .and_then(|rest| rest.split_once("' "))
+            .and_then(|(_, rest)| rest.split_once(": "))
+            .map_or(Cow::Borrowed(content), |(_, output)| Cow::Borrowed(output)),
+        "assistant" => serde_json::from_str::<serde_json::Value>(content)
+            .ok()
+            .and_then(|reply| reply.get("f")?.as_str().map(str::to_string))
+            .map_or(Cow::Borrowed(content), Cow::Owned),
+        _ => Cow::Borrowed(content),
+    }
+}
+
+/// Whether `message` is an assistant reply that calls a tool
+fn is_tool_call(message: &Message) -> bool {
+    message.role == "assistant"
+        && serde_json::from_str::<serde_json::Value>(&message.content)
+            .ok()
+            .and_then(|reply| reply.get("a")?.as_str().map(|tool| !tool.is_empty()))
+            .unwrap_or(false)
+}
+
+/// Whether `message` shows exactly one of the `pinned` texts
+pub fn is_pinned(message: &Message, pinned: &[String]) -> bool {
+    let shown = shown_text(message);
+    let shown = shown.trim();
+    pinned
+        .iter()
+        .map(|text| text.trim())
+        .any(|text| !text.is_empty() && text == shown)
+}
+
+/// Which messages of `history` condensation keeps: the pinned ones, and the
+/// call or result paired with a pinned tool result or call
+pub fn pinned_messages(history: &[Message], pinned: &[String]) -> Vec<bool> {
+    let direct: Vec<bool> = history.iter().map(|m| is_pinned(m, pinned)).collect();
+    let mut keep = direct.clone();
+    for (i, message) in history.iter().enumerate().filter(|(i, _)| direct[*i]) {
+        if message.role == "tool" && i > 0 && is_tool_call(&history[i - 1]) {
+            keep[i - 1] = true;
+        }
+        if is_tool_call(message) && history.get(i + 1).is_some_and(|next| next.role == "tool") {
+            keep[i + 1] = true;
+        }
+    }
+    keep
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::runtime::capabilities::LlmClientCapability;
+    use crate::config::{config_to_llm_config, Config, ProviderConfig};
+    use crate::conversation::{ContextConfig, ContextManager};
+    use crate::provider::LlmClient;
+
+    fn condenser(context_window: usize) -> Condenser {
+        let mut config = Config::default();
+        config.providers.insert("mock".to_string(), ProviderConfig::mock(""));
+        config.active_profile_mut().provider = "mock".to_string();
+        let client = Arc::new(LlmClient::new(config_to_llm_config(&config, "default").unwrap()).unwrap());
+        let context = Arc::new(tokio::sync::Mutex::new(ContextManager::new(ContextConfig::default())));
+        Condenser::new(Arc::new(LlmClientCapability::new(client, context)), context_window).with_archive_dir(None)
+    }
+
+    #[test]
+    fn test_split_keeps_recent_turns_and_starts_with_user() {
+        let turn = |i: usize| {
+            let role = if i % 2 == 0 { "user" } else { "assistant" };
+            Message::new(role, "word ".repeat(50))
+        };
+        let history: Vec<Message> = (0..10).map(turn).collect();
+
+        assert_eq!(condenser(10_000).split(&history, ""), None);
+        let split = condenser(400).split(&history, "").unwrap();
+        assert!(split >= MIN_SUMMARIZED && split <= history.len() - KEEP_LAST);
+        assert_eq!(history[split].role, "user");
+        // A long system prompt leaves less room for history
+        assert_eq!(condenser(1_000).split(&history, ""), None);
+        assert!(condenser(1_000).split(&history, &"rule ".repeat(500)).is_some());
+        // Too little old history to be worth a summary
+        assert_eq!(condenser(10).split(&history[..5], ""), None);
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut segment = CompressedSegment::new(history[..2].to_vec(), 100);
+        segment.summary = "Two turns".to_string();
+        segment.save(dir.path()).unwrap();
+        let loaded = CompressedSegment::load(dir.path(), &segment.id).unwrap();
+        assert_eq!(loaded.messages, segment.messages);
+        assert!(CompressedSegment::load(dir.path(), "../config").is_err());
+    }
+
+    #[test]
+    fn test_pins_match_shown_text_and_keep_tool_pairs() {
+        let pinned = vec!["a.txt\nb.txt".to_string(), "Done.".to_string(), "  ".to_string()];
+        assert!(is_pinned(&Message::new("tool", "Tool 'shell' succeeded: a.txt\nb.txt"), &pinned));
+        assert!(is_pinned(&Message::new("assistant", r#"{"t": "ok", "f": "Done."}"#), &pinned));
+        // Only the whole text counts, not a message that happens to contain it
+        assert!(!is_pinned(&Message::new("user", "ls prints a.txt\nb.txt here"), &pinned));
+        assert!(!is_pinned(&Message::new("tool", "a.txt"), &pinned));
+        assert!(!is_pinned(&Message::new("user", "anything"), &["".to_string()]));
+
+        let history = vec![
+            Message::new("user", "list files"),
+            Message::new("assistant", r#"{"t": "", "a": "shell", "i": {"command": "ls"}}"#),
+            Message::new("tool", "Tool 'shell' succeeded: a.txt\nb.txt"),
+            Message::new("assistant", r#"{"f": "Two files."}"#),
+        ];
+        assert_eq!(pinned_messages(&history, &pinned), [false, true, true, false]);
+    }
+}
@@ -17,10 +17,11 @@ use crate::agent::runtime::orchestrator::orchestrator::OutputEvent;
 use crate::agent::runtime::core::{
     AgencyRuntime, AgencyRuntimeError, TelemetryEvent, HealthStatus,
 };
-use crate::agent::runtime::governance::{ClaimEnforcer, ClaimEnforcement};
+use crate::agent::runtime::governance::{ClaimEnforcer, ClaimEnforcement, SessionBudget};
 
 use crate::agent::runtime::capabilities::{
     LlmClientCapability,
+    SharedLlmClient,
     LocalWorkerCapability,
     ConsoleTelemetry,
     AutoApproveCapability,
@@ -35,6 +36,7 @@ use crate::agent::runtime::core::RuntimeContext;
 use crate::agent::memory::MemoryProvider;
 use crate::conversation::ContextManager;
 use crate::provider::LlmClient;
+use crate::scheduler::RequestLimit;
 
 /// Output sender enum to support both broadcast (main session) and mpsc (workers)
 #[derive(Clone)]
@@ -86,6 +88,12 @@ pub struct ContractRuntime {
     terminal: Arc<dyn TerminalExecutor>,
     /// Claim enforcer for resource coordination (optional)
     claim_enforcer: Option<Arc<ClaimEnforcer>>,
+    /// Session cost/token budget checked before each LLM call (optional)
+    budget: Option<Arc<SessionBudget>>,
+    /// Spacing between LLM requests for pooled workers (optional)
+    request_limit: Option<Arc<RequestLimit>>,
+    /// Swappable client behind `llm`, when built from an `LlmClient`
+    shared_llm: Option<SharedLlmClient>,
 }
 
 impl ContractRuntime {
@@ -98,11 +106,14 @@ impl ContractRuntime {
         let tools = Arc::new(ToolRegistry::new());
         let context_config = crate::conversation::ContextConfig::default();
         let context_manager = Arc::new(tokio::sync::Mutex::new(ContextManager::new(context_config)));
-        let llm: Arc<dyn LLMCapability> = Arc::new(LlmClientCapability::new(llm_client, context_manager));
+        let llm_capability = LlmClientCapability::new(Arc::clone(&llm_client), context_manager);
+        let shared_llm = Some(llm_capability.shared_client());
+        let llm: Arc<dyn LLMCapability> = Arc::new(llm_capability);
         let workers = Arc::new(LocalWorkerCapability::new());
         let approval: Arc<dyn ApprovalCapability> = Arc::new(AutoApproveCapability::new());
         let telemetry = Arc::new(ConsoleTelemetry::new());
         let (telemetry_tx, _) = broadcast::channel(100);
+        forward_failovers(&llm_client, &telemetry_tx);
 
         Self {
             tools,
@@ -115,6 +126,9 @@ impl ContractRuntime {
             output_tx: None,
             terminal: Arc::new(DefaultTerminalExecutor::new()),
             claim_enforcer: None,
+            budget: None,
+            request_limit: None,
+            shared_llm,
         }
     }
     
@@ -125,11 +139,14 @@ impl ContractRuntime {
     pub fn with_tools(llm_client: Arc<LlmClient>, tools: Arc<ToolRegistry>) -> Self {
         let context_config = crate::conversation::ContextConfig::default();
         let context_manager = Arc::new(tokio::sync::Mutex::new(ContextManager::new(context_config)));
-        let llm: Arc<dyn LLMCapability> = Arc::new(LlmClientCapability::new(llm_client, context_manager));
+        let llm_capability = LlmClientCapability::new(Arc::clone(&llm_client), context_manager);
+        let shared_llm = Some(llm_capability.shared_client());
+        let llm: Arc<dyn LLMCapability> = Arc::new(llm_capability);
         let workers = Arc::new(LocalWorkerCapability::new());
         let approval: Arc<dyn ApprovalCapability> = Arc::new(AutoApproveCapability::new());
         let telemetry = Arc::new(ConsoleTelemetry::new());
         let (telemetry_tx, _) = broadcast::channel(100);
+        forward_failovers(&llm_client, &telemetry_tx);
 
         Self {
             tools,
@@ -142,6 +159,9 @@ impl ContractRuntime {
             output_tx: None,
             terminal: Arc::new(DefaultTerminalExecutor::new()),
             claim_enforcer: None,
+            budget: None,
+            request_limit: None,
+            shared_llm,
         }
     }
     
@@ -157,7 +177,8 @@ impl ContractRuntime {
     ) -> Self {
         let context_config = crate::conversation::ContextConfig::default();
         let context_manager = Arc::new(tokio::sync::Mutex::new(ContextManager::new(context_config)));
-        let llm_capability = LlmClientCapability::new(llm_client, context_manager);
+        let llm_capability = LlmClientCapability::new(Arc::clone(&llm_client), context_manager);
+        let shared_llm = Some(llm_capability.shared_client());
         
         // Inject memory provider if available
         let llm: Arc<dyn LLMCapability> = if let Some(ref provider) = memory_provider {
@@ -170,6 +191,7 @@ impl ContractRuntime {
         let approval: Arc<dyn ApprovalCapability> = Arc::new(AutoApproveCapability::new());
         let telemetry = Arc::new(ConsoleTelemetry::new());
         let (telemetry_tx, _) = broadcast::channel(100);
+        forward_failovers(&llm_client, &telemetry_tx);
 
         Self {
             tools,
@@ -182,6 +204,9 @@ impl ContractRuntime {
             output_tx: None,
             terminal: Arc::new(DefaultTerminalExecutor::new()),
             claim_enforcer: None,
+            budget: None,
+            request_limit: None,
+            shared_llm,
         }
     }
     
@@ -205,6 +230,9 @@ impl ContractRuntime {
             output_tx: None,
             terminal: Arc::new(DefaultTerminalExecutor::new()),
             claim_enforcer: None,
+            budget: None,
+            request_limit: None,
+            shared_llm: None,
         }
     }
     
@@ -241,16 +269,71 @@ impl ContractRuntime {
         self
     }
 
+    /// Set the session budget
+    ///
+    /// LLM usage is recorded against it, and once a limit is reached the
+    /// next LLM request halts the session instead of calling the provider.
+    pub fn with_budget(mut self, budget: Arc<SessionBudget>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Space LLM requests by `limit`
+    ///
+    /// Used for pooled workers: a request waits for its turn instead of
+    /// hitting the provider's rate limit.
+    pub fn with_request_limit(mut self, limit: Arc<RequestLimit>) -> Self {
+        self.request_limit = Some(limit);
+        self
+    }
+
     /// Get a reference to the tool registry
     pub fn tools(&self) -> &ToolRegistry {
         &self.tools
     }
 
-    /// Execute a single intent with the given intent_id
+    /// Handle for replacing the LLM client while the runtime is in use
+    ///
+    /// None for runtimes built around a custom `LLMCapability`.
+    pub fn shared_llm_client(&self) -> Option<SharedLlmClient> {
+        self.shared_llm.clone()
+    }
+
+    /// Execute a single intent with the given intent_id, enforcing the budget
     async fn execute_intent(
         &self,
         intent_id: IntentId,
         intent: Intent,
+    ) -> Result<Observation, AgencyRuntimeError> {
+        if let (Some(limit), Intent::RequestLLM(_)) = (&self.request_limit, &intent) {
+            limit.wait().await;
+        }
+
+        let Some(ref budget) = self.budget else {
+            return self.execute_intent_unbudgeted(intent_id, intent).await;
+        };
+
+        if matches!(intent, Intent::RequestLLM(_)) {
+            if let Err(exceeded) = budget.check() {
+                crate::warn_log!("[RUNTIME] Refusing LLM request: {}", exceeded);
+                return Ok(Observation::Halted {
+                    intent_id,
+                    reason: HaltReason::BudgetExceeded(exceeded.to_string()),
+                });
+            }
+        }
+
+        let observation = self.execute_intent_unbudgeted(intent_id, intent).await?;
+        if let Observation::LLMCompleted { ref response, .. } = observation {
+            budget.record(&response.usage);
+        }
+        Ok(observation)
+    }
+
+    async fn execute_intent_unbudgeted(
+        &self,
+        intent_id: IntentId,
+        intent: Intent,
     ) -> Result<Observation, AgencyRuntimeError> {
         let start_time = Instant::now();
         
@@ -355,6 +438,7 @@ impl ContractRuntime {
                     let mut stream_failed = false;
                     let mut stream_error = String::new();
                     let mut accumulated_usage: Option<crate::agent::types::events::TokenUsage> = None;
+                    let mut native_tool_calls = Vec::new();
                     
                     crate::info_log!("[RUNTIME] Starting LLM stream polling (60s timeout)...");
                     let mut chunk_count = 0;
@@ -367,6 +451,7 @@ impl ContractRuntime {
                                 // crate::debug_log!("[RUNTIME] Got chunk: is_final={}, content_len={}", chunk.is_final, chunk.content.len());
                                 if chunk.is_final {
                                     accumulated_usage = chunk.usage;
+                                    native_tool_calls = chunk.tool_calls;
                                     break;
                                 }
                                 if !chunk.content.is_empty() {
@@ -454,8 +539,13 @@ impl ContractRuntime {
                             usage,
                             model: "unknown".to_string(),
                             provider: "unknown".to_string(),
-                            finish_reason: crate::agent::types::events::FinishReason::Stop,
+                            finish_reason: if native_tool_calls.is_empty() {
+                                crate::agent::types::events::FinishReason::Stop
+                            } else {
+                                crate::agent::types::events::FinishReason::ToolCall
+                            },
                             structured: None,
+                            tool_calls: native_tool_calls,
                         },
                     })
                 } else {
@@ -591,6 +681,18 @@ impl ContractRuntime {
     }
 }
 
+/// Report the LLM client's fallback-chain failovers as telemetry
+fn forward_failovers(llm_client: &LlmClient, telemetry_tx: &broadcast::Sender<TelemetryEvent>) {
+    let telemetry_tx = telemetry_tx.clone();
+    llm_client.set_failover_callback(Arc::new(move |event: &crate::provider::FailoverEvent| {
+        let _ = telemetry_tx.send(TelemetryEvent::ProviderFailover {
+            from: event.from.clone(),
+            to: event.to.clone(),
+            reason: event.reason.clone(),
+        });
+    }));
+}
+
 /// Get a human-readable name for an intent type
 fn intent_type_name(intent: &Intent) -> &'static str {
     match intent {
@@ -693,6 +795,9 @@ impl Clone for ContractRuntime {
             output_tx: self.output_tx.clone(),
             terminal: Arc::clone(&self.terminal),
             claim_enforcer: self.claim_enforcer.clone(),
+            budget: self.budget.clone(),
+            request_limit: self.request_limit.clone(),
+            shared_llm: self.shared_llm.clone(),
         }
     }
 }
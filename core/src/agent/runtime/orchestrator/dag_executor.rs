@@ -8,8 +8,9 @@
 use std::collections::HashSet;
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use tracing::Instrument;
 
-use crate::agent::types::{IntentGraph, IntentId, Observation};
+use crate::agent::types::{Intent, IntentGraph, IntentId, Observation};
 use crate::agent::runtime::core::{AgencyRuntime, AgencyRuntimeError};
 use crate::agent::types::observations::ExecutionSummary;
 
@@ -84,10 +85,11 @@ pub async fn execute_dag<R: AgencyRuntime + 'static>(
             
             in_flight.lock().await.insert(node.id);
             
+            let span = intent_span(&node.intent);
             let handle = tokio::spawn(async move {
                 let result = runtime.execute_with_id(node.id, node.intent.clone()).await;
                 (node.id, result)
-            });
+            }.instrument(span));
             handles.push(handle);
         }
 
@@ -124,6 +126,15 @@ pub async fn execute_dag<R: AgencyRuntime + 'static>(
     })
 }
 
+/// Tracing span an intent runs in: its own for tool calls, otherwise the
+/// caller's (the agent turn), which spawned tasks would lose
+fn intent_span(intent: &Intent) -> tracing::Span {
+    match intent {
+        Intent::CallTool(call) => tracing::info_span!("tool.call", tool = %call.name),
+        _ => tracing::Span::current(),
+    }
+}
+
 /// Simple DAG executor struct for convenience
 pub struct DagExecutor;
 
@@ -0,0 +1,294 @@
+//! Fast path for trivial questions
+//!
+//! A question like "what does -R do in chmod" does not need the planner,
+//! tool descriptions or a large model: it needs one short answer, quickly.
+//! Before a message reaches the agent loop, `FastPath::route` classifies it:
+//! cheap rules send action requests (length, imperative verbs, file paths,
+//! references to the workspace) to the agent and answer chitchat directly;
+//! anything else, questions included, gets a one-word verdict from the fast
+//! model, since "is docker running?" needs the system and rules cannot tell.
+//! Trivial messages are answered by that model with tools disabled;
+//! everything else goes to the agent as before.
+
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use futures::StreamExt;
+use regex::Regex;
+use tokio::sync::broadcast;
+
+use crate::agent::runtime::core::{LLMCapability, LLMError, RuntimeContext};
+use crate::agent::runtime::governance::SessionBudget;
+use crate::agent::runtime::orchestrator::OutputEvent;
+use crate::agent::types::events::TokenUsage;
+use crate::agent::types::intents::{Context, LLMRequest};
+use crate::conversation::manager::Message;
+
+/// Longest message (in characters) the rules consider for the fast path
+const MAX_FAST_CHARS: usize = 160;
+/// Previous messages given to the fast model for follow-up questions
+const HISTORY_MESSAGES: usize = 4;
+/// Time the model classifier gets before the message goes to the agent
+const CLASSIFY_TIMEOUT: Duration = Duration::from_secs(2);
+/// Answer length cap for the fast model
+const MAX_ANSWER_TOKENS: u32 = 600;
+
+const ANSWER_PROMPT: &str = "You are a terminal assistant answering a quick question. \
+Answer directly and concisely in plain text or short markdown: a sentence or two, plus a \
+one-line example when it helps. You cannot run commands or read files.";
+
+const CLASSIFY_PROMPT: &str = "Decide whether the user's message is a self-contained question \
+that can be answered from general knowledge in a few sentences, without reading files, \
+running commands, checking the state of this machine (processes, ports, disk, services), \
+looking at the terminal or changing anything. Reply with exactly one word: \
+SIMPLE or AGENT.";
+
+/// Where a message is handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    /// Answered by the fast model, no tools
+    Fast,
+    /// Handled by the full agent loop
+    Agent,
+}
+
+struct Rules {
+    action: Regex,
+    workspace: Regex,
+    path: Regex,
+    chitchat: Regex,
+}
+
+fn rules() -> &'static Rules {
+    static RULES: OnceLock<Rules> = OnceLock::new();
+    RULES.get_or_init(|| Rules {
+        action: Regex::new(
+            r"(?i)^(please\s+|(can|could|would|will)\s+you\s+(please\s+)?)?(fix|create|write|edit|add|remove|delete|refactor|run|install|build|deploy|implement|update|rename|move|debug|test|find|search|open|commit|push|generate|make|set\s+up|configure|clean|analy[sz]e|review|change|convert|migrate|investigate|look)\b",
+        )
+        .expect("Valid action regex"),
+        workspace: Regex::new(
+            r"(?i)\b(this|my|our|the current)\s+(repo|repository|project|codebase|code|file|branch|directory|folder|build|function|error|output|test|script|config)s?\b|\b(above|terminal|here)\b",
+        )
+        .expect("Valid workspace regex"),
+        path: Regex::new(
+            r"(^|\s)(~|\.{1,2})?/[\w.-]+|\b[\w-]+\.(rs|py|js|ts|tsx|go|toml|json|ya?ml|md|txt|sh|c|h|cpp|java|rb|lock|env)\b",
+        )
+        .expect("Valid path regex"),
+        chitchat: Regex::new(r"(?i)^(hi|hello|hey|thanks|thank you|ok|okay|got it|cool|nice|great)\b[\s!.]*$")
+            .expect("Valid chitchat regex"),
+    })
+}
+
+/// Text the user typed, without the context packs (terminal, git status)
+/// the frontend appends as `## Title` sections
+fn user_text(message: &str) -> &str {
+    message.split("\n\n## ").next().unwrap_or(message).trim()
+}
+
+/// Route a message by rules alone; None when they cannot decide
+pub fn classify(message: &str) -> Option<Route> {
+    let text = user_text(message);
+    let rules = rules();
+    if text.is_empty()
+        || text.chars().count() > MAX_FAST_CHARS
+        || text.contains('\n')
+        || text.contains("```")
+        || rules.action.is_match(text)
+        || rules.workspace.is_match(text)
+        || rules.path.is_match(text)
+    {
+        return Some(Route::Agent);
+    }
+    if rules.chitchat.is_match(text) {
+        return Some(Route::Fast);
+    }
+    None
+}
+
+/// Small model that answers trivial questions without tools
+pub struct FastPath {
+    llm: Arc<dyn LLMCapability>,
+    classify_with_model: bool,
+    budget: Option<Arc<SessionBudget>>,
+}
+
+impl FastPath {
+    /// Answer (and classify) with `llm`
+    pub fn new(llm: Arc<dyn LLMCapability>) -> Self {
+        Self {
+            llm,
+            classify_with_model: true,
+            budget: None,
+        }
+    }
+
+    /// Whether messages the rules cannot decide are classified by the model
+    /// (otherwise they go to the agent)
+    pub fn with_model_classifier(mut self, enabled: bool) -> Self {
+        self.classify_with_model = enabled;
+        self
+    }
+
+    /// Count fast answers against the session budget, and leave the fast
+    /// path once it is spent so the agent halts as usual
+    pub fn with_budget(mut self, budget: Arc<SessionBudget>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Decide where `message` is handled
+    pub async fn route(&self, message: &str) -> Route {
+        if self.budget.as_ref().is_some_and(|budget| budget.check().is_err()) {
+            return Route::Agent;
+        }
+        match classify(message) {
+            Some(route) => route,
+            None if self.classify_with_model => self.classify_with_model(user_text(message)).await,
+            None => Route::Agent,
+        }
+    }
+
+    /// One-word verdict from the model; anything unexpected means Agent
+    async fn classify_with_model(&self, text: &str) -> Route {
+        let mut request = LLMRequest::new(Context::new(text).with_system(CLASSIFY_PROMPT));
+        request.max_tokens = Some(3);
+        request.temperature = Some(0.0);
+        match tokio::time::timeout(CLASSIFY_TIMEOUT, self.llm.complete(&RuntimeContext::new(), request)).await {
+            Ok(Ok(response)) => {
+                self.record(&response.usage);
+                if response.content.trim().to_uppercase().starts_with("SIMPLE") {
+                    Route::Fast
+                } else {
+                    Route::Agent
+                }
+            }
+            Ok(Err(e)) => {
+                crate::warn_log!("[FAST_PATH] Classifier failed, using the agent: {}", e);
+                Route::Agent
+            }
+            Err(_) => {
+                crate::warn_log!("[FAST_PATH] Classifier timed out, using the agent");
+                Route::Agent
+            }
+        }
+    }
+
+    /// Stream an answer to `message` as `ResponseChunk`s and return it
+    ///
+    /// Chunks are wrapped as the `"f"` field of a Short-Key object, which is
+    /// what frontends parse out of streamed responses. Fails only if nothing
+    /// was streamed, so the caller can still hand the message to the agent;
+    /// a failure mid-answer is noted in the answer instead.
+    pub async fn answer(
+        &self,
+        history: &[Message],
+        message: &str,
+        output_tx: &broadcast::Sender<OutputEvent>,
+    ) -> Result<(String, Option<TokenUsage>), LLMError> {
+        let recent: Vec<Message> = history[history.len().saturating_sub(HISTORY_MESSAGES)..]
+            .iter()
+            .filter(|m| m.role == "user" || m.role == "assistant")
+            .map(|m| Message::new(m.role.clone(), user_text(&m.content)))
+            .collect();
+        let mut request = LLMRequest::new(
+            Context::new(user_text(message))
+                .with_system(ANSWER_PROMPT)
+                .with_history(recent),
+        );
+        request.max_tokens = Some(MAX_ANSWER_TOKENS);
+        request.stream = true;
+
+        let ctx = RuntimeContext::new();
+        let mut stream = self.llm.complete_stream(&ctx, request);
+        let mut answer = String::new();
+        let mut usage = None;
+        while let Some(chunk) = stream.next().await {
+            match chunk {
+                Ok(chunk) => {
+                    if !chunk.content.is_empty() {
+                        let prefix = if answer.is_empty() { "{\"f\": \"" } else { "" };
+                        answer.push_str(&chunk.content);
+                        let _ = output_tx.send(OutputEvent::ResponseChunk {
+                            content: format!("{}{}", prefix, escape(&chunk.content)),
+                        });
+                    }
+                    if chunk.usage.is_some() {
+                        usage = chunk.usage;
+                    }
+                    if chunk.is_final {
+                        break;
+                    }
+                }
+                Err(e) if answer.is_empty() => return Err(e),
+                Err(e) => {
+                    let note = format!("\n\n(answer interrupted: {})", e.message);
+                    answer.push_str(&note);
+                    let _ = output_tx.send(OutputEvent::ResponseChunk { content: escape(&note) });
+                    break;
+                }
+            }
+        }
+        if answer.is_empty() {
+            return Err(LLMError::new("Fast model returned an empty answer"));
+        }
+        let _ = output_tx.send(OutputEvent::ResponseChunk { content: "\"}".to_string() });
+        if let Some(usage) = &usage {
+            self.record(usage);
+        }
+        Ok((answer, usage))
+    }
+
+    fn record(&self, usage: &TokenUsage) {
+        if let Some(budget) = &self.budget {
+            budget.record(usage);
+        }
+    }
+}
+
+/// `text` as the inside of a JSON string literal
+fn escape(text: &str) -> String {
+    let quoted = serde_json::to_string(text).unwrap_or_default();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::runtime::stubs::StubLLM;
+    use crate::agent::types::parser::ShortKeyStreamParser;
+
+    #[test]
+    fn test_rules_route_obvious_messages() {
+        assert_eq!(classify("thanks!"), Some(Route::Fast));
+        // The message alone decides, not the terminal snapshot appended to it
+        assert_eq!(classify("thanks!\n\n## Terminal\n$ ls -la\ntotal 0"), Some(Route::Fast));
+
+        assert_eq!(classify("fix the failing test"), Some(Route::Agent));
+        assert_eq!(classify("can you run the migrations?"), Some(Route::Agent));
+        assert_eq!(classify("why does src/main.rs panic?"), Some(Route::Agent));
+        assert_eq!(classify("what is wrong with the error above?"), Some(Route::Agent));
+        assert_eq!(classify("how is retry handled in this repo?"), Some(Route::Agent));
+        assert_eq!(classify("the deploy keeps timing out on staging"), None);
+        // Questions go to the model classifier: some need the system
+        assert_eq!(classify("what does -R do in chmod"), None);
+        assert_eq!(classify("is docker running?"), None);
+        assert_eq!(classify("how much disk is left?"), None);
+    }
+
+    #[tokio::test]
+    async fn test_answer_streams_short_key_chunks() {
+        let fast_path = FastPath::new(Arc::new(StubLLM)).with_model_classifier(false);
+        assert_eq!(fast_path.route("the deploy keeps timing out").await, Route::Agent);
+
+        let (tx, mut rx) = broadcast::channel(8);
+        let (answer, _) = fast_path.answer(&[], "what is a stub?", &tx).await.unwrap();
+        assert_eq!(answer, "stub");
+
+        let mut parser = ShortKeyStreamParser::new();
+        while let Ok(OutputEvent::ResponseChunk { content }) = rx.try_recv() {
+            parser.push(&content);
+        }
+        assert_eq!(parser.final_answer(), "stub");
+        assert!(parser.is_complete());
+    }
+}
@@ -8,6 +8,8 @@ pub mod transport;
 pub mod contract_bridge;
 pub mod dag_executor;
 pub mod commonbox;
+pub mod fast_path;
+pub mod condenser;
 
 pub use orchestrator::{
     Session, UserInput, OutputEvent, SessionStatus, SessionResult, SessionError,
@@ -17,6 +19,8 @@ pub use transport::{
     EventTransport, TransportError, TransportCapabilities, DeliveryGuarantee, TransportConfig,
 };
 pub use contract_bridge::{ContractRuntime, OutputSender};
+pub use fast_path::{FastPath, Route};
+pub use condenser::{Condensation, Condenser};
 pub use commonbox::{
     Commonbox, CommonboxEntry, CommonboxEvent, CommonboxError,
     Job, JobId, JobStatus, JobResult,
@@ -69,16 +69,19 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use tokio::sync::{broadcast, mpsc};
 use tokio::time::Duration;
+use tracing::Instrument;
 use std::collections::{HashMap, HashSet};
 use std::sync::atomic::AtomicU64;
 
 use crate::agent::cognition::kernel::{GraphEngine};
 use crate::agent::runtime::core::{AgencyRuntimeError, AgencyRuntime};
 use crate::agent::runtime::orchestrator::transport::EventTransport;
+use crate::agent::runtime::orchestrator::condenser::Condenser;
+use crate::agent::runtime::orchestrator::fast_path::{FastPath, Route};
 use crate::agent::types::graph::IntentGraph;
 use crate::agent::types::ids::IntentId;
 use crate::agent::types::intents::Intent;
-use crate::agent::types::observations::Observation;
+use crate::agent::types::observations::{HaltReason, Observation};
 use crate::agent::types::envelope::KernelEventEnvelope;
 use crate::agent::types::{
     events::{KernelEvent, TokenUsage},
@@ -133,12 +136,24 @@ pub enum UserInput {
     /// User message
     Message(String),
     
+    /// User message with images the user attached, already read
+    MessageWithImages { content: String, images: Vec<crate::provider::chat::ImageAttachment> },
+    
     /// Command (slash command)
     Command(String),
     
     /// Approval/denial for a tool
     Approval { intent_id: IntentId, approved: bool },
     
+    /// Put a condensed segment back into history
+    Restore { segment_id: String },
+    
+    /// Drop the last user message and what followed, to edit or retry it
+    Rewind,
+    
+    /// Texts of the messages the user pinned, replacing the previous ones
+    Pinned { contents: Vec<String> },
+    
     /// Interrupt request
     Interrupt,
 }
@@ -260,6 +275,21 @@ pub enum OutputEvent {
         /// Previews of retrieved memories
         memory_previews: Vec<String>,
     },
+    
+    /// A setting was changed through the config tool
+    ConfigChanged {
+        /// Setting key, e.g. `worker.model`
+        key: String,
+        /// Value as stored
+        value: String,
+    },
+    
+    /// The agent annotated lines of the terminal pane
+    TerminalAnnotations {
+        annotations: Vec<crate::agent::tools::TerminalAnnotation>,
+        /// Remove earlier annotations first
+        clear: bool,
+    },
 }
 
 /// Session result
@@ -348,6 +378,19 @@ where
     #[allow(dead_code)]
     memory_manager: Option<std::sync::Arc<crate::agent::memory::AgentMemoryManager>>,
     
+    // Small model for trivial questions (None = every message goes to the kernel)
+    fast_path: Option<FastPath>,
+    
+    // Summarizes old turns when history nears the context window (None = never)
+    condenser: Option<Condenser>,
+    
+    // Texts of messages the user pinned; condensation keeps them verbatim
+    pinned: Vec<String>,
+    
+    // Tracing span of the user message being worked on; LLM calls, tools
+    // and workers run inside it
+    turn_span: Option<tracing::Span>,
+    
     // INVARIANT: Transport identity check - ensures transport is never swapped
     
     transport_instance_id: u64,
@@ -415,10 +458,26 @@ where
             consecutive_errors: 0,
             max_consecutive_errors: 3,
             memory_manager,
+            fast_path: None,
+            condenser: None,
+            pinned: Vec::new(),
+            turn_span: None,
             transport_instance_id,
         }
     }
 
+    /// Answer trivial questions with a small model instead of the kernel
+    pub fn with_fast_path(mut self, fast_path: FastPath) -> Self {
+        self.fast_path = Some(fast_path);
+        self
+    }
+
+    /// Summarize old turns with `condenser` before they overflow the context
+    pub fn with_condenser(mut self, condenser: Condenser) -> Self {
+        self.condenser = Some(condenser);
+        self
+    }
+
     /// Get a clone of the input sender
     /// 
     /// This allows sending input to the session while it's running
@@ -475,6 +534,98 @@ where
         }
     }
 
+    /// Answer `content` on the fast path if it is routed there
+    ///
+    /// Returns false when the message should go to the kernel instead,
+    /// including when the fast model fails before answering.
+    async fn try_fast_path(&mut self, content: &str) -> Result<bool, SessionError> {
+        let Some(fast_path) = &self.fast_path else {
+            return Ok(false);
+        };
+        // Messages sent while the agent is working belong to that task
+        if self.pending_graph.is_some() || fast_path.route(content).await == Route::Agent {
+            return Ok(false);
+        }
+        crate::info_log!("[SESSION] Answering on the fast path");
+        let answer = match fast_path.answer(&self.kernel.state().history, content, &self.output_tx).await {
+            Ok((answer, usage)) => {
+                let _ = self.output_tx.send(OutputEvent::ResponseComplete { usage });
+                answer
+            }
+            Err(e) => {
+                crate::warn_log!("[SESSION] Fast path failed, handing the message to the kernel: {}", e);
+                return Ok(false);
+            }
+        };
+        self.publish_event(KernelEvent::DirectExchange {
+            question: content.to_string(),
+            answer,
+        }).await?;
+        Ok(true)
+    }
+
+    /// Replace the oldest turns with a summary if history is over the
+    /// condenser's threshold
+    ///
+    /// A failed summary is logged; history then stays as it was and the
+    /// LLM capability prunes what does not fit.
+    async fn condense_history(&mut self) -> Result<(), SessionError> {
+        let Some(condenser) = &self.condenser else {
+            return Ok(());
+        };
+        let system_prompt = self.kernel.system_prompt();
+        let condensation = match condenser.condense(&self.kernel.state().history, &system_prompt, &self.pinned).await {
+            Ok(Some(condensation)) => condensation,
+            Ok(None) => return Ok(()),
+            Err(e) => {
+                crate::warn_log!("[SESSION] Context condensation failed: {}", e);
+                return Ok(());
+            }
+        };
+        let segment = condensation.segment;
+        self.publish_event(KernelEvent::HistoryCondensed {
+            replaced: condensation.replaced,
+            summary: segment.summary_message(),
+            kept: condensation.kept,
+        }).await?;
+        let _ = self.output_tx.send(OutputEvent::ContextPruned {
+            summary: segment.summary.clone(),
+            message_count: segment.message_count,
+            tokens_saved: segment.tokens_saved,
+            extracted_memories: segment.extracted_memories.clone(),
+            segment_id: segment.id.clone(),
+        });
+        Ok(())
+    }
+
+    /// Put the saved segment `segment_id` back into history
+    async fn restore_segment(&mut self, segment_id: &str) -> Result<(), SessionError> {
+        let Some(dir) = self.condenser.as_ref().and_then(|c| c.archive_dir()) else {
+            let _ = self.output_tx.send(OutputEvent::Error {
+                message: "This session does not archive condensed context".to_string(),
+            });
+            return Ok(());
+        };
+        match crate::conversation::CompressedSegment::load(dir, segment_id) {
+            Ok(segment) => {
+                let count = segment.messages.len();
+                self.publish_event(KernelEvent::HistoryRestored {
+                    segment_id: segment_id.to_string(),
+                    messages: segment.messages,
+                }).await?;
+                let _ = self.output_tx.send(OutputEvent::Status {
+                    message: format!("Restored {} condensed messages", count),
+                });
+            }
+            Err(e) => {
+                let _ = self.output_tx.send(OutputEvent::Error {
+                    message: format!("Cannot restore segment {}: {}", segment_id, e),
+                });
+            }
+        }
+        Ok(())
+    }
+
     /// Process a batch of events through the kernel
     async fn process_events(&mut self, events: Vec<KernelEvent>) -> Result<IntentGraph, SessionError> {
         // Process through kernel
@@ -544,7 +695,8 @@ where
         }
 
         // Execute via runtime
-        let observations = self.runtime.execute_dag(graph).await
+        let turn = self.turn_span.clone().unwrap_or_else(tracing::Span::current);
+        let observations = self.runtime.execute_dag(graph).instrument(turn).await
             .map_err(SessionError::Runtime)?;
         
         crate::debug_log!("[SESSION] execute_dag returned {} observations", observations.len());
@@ -726,6 +878,8 @@ where
                     // Graph complete but not halted - wait for more input
                     crate::debug_log!("[SESSION] Waiting for input");
                     self.pending_graph = None;
+                    // Closing the span ends the turn in the trace
+                    self.turn_span = None;
                 }
             }
 
@@ -930,13 +1084,13 @@ where
                                 
                                 // Check for halt observation
                                 for (_, obs) in &observations {
-                                    if matches!(obs, Observation::Halted { .. }) {
-                                        crate::info_log!("[SESSION] Halt observation received, stopping loop");
+                                    if let Observation::Halted { reason, .. } = obs {
+                                        crate::info_log!("[SESSION] Halt observation received ({}), stopping loop", reason);
                                         let _ = self.output_tx.send(OutputEvent::Halted {
-                                            reason: "Agent halted".to_string(),
+                                            reason: reason.to_string(),
                                         });
                                         return Ok(SessionResult {
-                                            completed_successfully: true,
+                                            completed_successfully: !matches!(reason, HaltReason::BudgetExceeded(_)),
                                             total_steps: self.kernel.state().step_count,
                                             total_tokens: TokenUsage::default(),
                                             halt_reason: self.kernel.state().halt_reason.clone(),
@@ -965,11 +1119,39 @@ where
                     crate::debug_log!("[SESSION] input_rx.recv() returned: {:?}", input.is_some());                    match input {
                         Some(UserInput::Message(content)) => {
                             crate::info_log!("[SESSION] User message received ({} bytes)", content.len());
-                            self.publish_event(KernelEvent::UserMessage { content }).await?;
+                            self.turn_span = Some(tracing::info_span!(
+                                "agent.turn",
+                                step = self.kernel.state().step_count,
+                                message_bytes = content.len(),
+                            ));
+                            if !self.try_fast_path(&content).await? {
+                                // Between tasks only; a running task keeps its history
+                                if self.pending_graph.is_none() {
+                                    self.condense_history().await?;
+                                }
+                                self.publish_event(KernelEvent::UserMessage { content, images: Vec::new() }).await?;
+                            }
+                        }
+                        Some(UserInput::MessageWithImages { content, images }) => {
+                            crate::info_log!(
+                                "[SESSION] User message received ({} bytes, {} image(s))",
+                                content.len(),
+                                images.len()
+                            );
+                            self.turn_span = Some(tracing::info_span!(
+                                "agent.turn",
+                                step = self.kernel.state().step_count,
+                                message_bytes = content.len(),
+                            ));
+                            // The fast path is text-only, so images always go to the planner
+                            if self.pending_graph.is_none() {
+                                self.condense_history().await?;
+                            }
+                            self.publish_event(KernelEvent::UserMessage { content, images }).await?;
                         }
                         Some(UserInput::Command(cmd)) => {
                             crate::debug_log!("[SESSION] Command received: {}", &cmd);
-                            self.publish_event(KernelEvent::UserMessage { content: cmd }).await?;
+                            self.publish_event(KernelEvent::UserMessage { content: cmd, images: Vec::new() }).await?;
                         }
                         Some(UserInput::Approval { intent_id, approved }) => {
                             crate::info_log!("[SESSION] Approval for intent_id={}: approved={}", 
@@ -984,6 +1166,18 @@ where
                             }).await?;
                             crate::info_log!("[SESSION] ApprovalGiven event published, loop will continue");
                         }
+                        Some(UserInput::Restore { segment_id }) => {
+                            crate::info_log!("[SESSION] Restoring segment {}", segment_id);
+                            self.restore_segment(&segment_id).await?;
+                        }
+                        Some(UserInput::Rewind) => {
+                            crate::info_log!("[SESSION] Rewinding the last turn");
+                            self.publish_event(KernelEvent::HistoryRewound).await?;
+                        }
+                        Some(UserInput::Pinned { contents }) => {
+                            crate::info_log!("[SESSION] {} pinned messages", contents.len());
+                            self.pinned = contents;
+                        }
                         Some(UserInput::Interrupt) => {
                             crate::warn_log!("[SESSION] Received UserInput::Interrupt");
                             return Err(SessionError::Interrupted);
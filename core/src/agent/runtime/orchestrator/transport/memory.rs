@@ -259,6 +259,7 @@ mod tests {
             SessionId::new("test"),
             KernelEvent::UserMessage {
                 content: "hello".to_string(),
+                images: Vec::new(),
             },
             1,
         );
@@ -285,6 +286,7 @@ mod tests {
                 SessionId::new("test"),
                 KernelEvent::UserMessage {
                     content: format!("msg{}", i),
+                    images: Vec::new(),
                 },
                 i as u64,
             );
@@ -312,6 +314,7 @@ mod tests {
                 SessionId::new("test"),
                 KernelEvent::UserMessage {
                     content: format!("msg{}", i),
+                    images: Vec::new(),
                 },
                 i as u64,
             );
@@ -29,6 +29,7 @@ impl LLMCapability for StubLLM {
             provider: "stub".to_string(),
             finish_reason: FinishReason::Stop,
             structured: None,
+            tool_calls: Vec::new(),
         })
     }
     
@@ -42,6 +43,7 @@ impl LLMCapability for StubLLM {
                 content: "stub".to_string(),
                 is_final: true,
                 usage: None,
+                tool_calls: Vec::new(),
             })
         }))
     }
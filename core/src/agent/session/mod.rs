@@ -5,10 +5,12 @@
 pub mod session;
 pub mod input;
 pub mod persistence;
+pub mod replay;
 
 pub use session::*;
 pub use input::*;
+pub use replay::{replay, ReplayReport, Divergence};
 pub use persistence::{
-    SessionPersistence, PersistedSession, SessionMetadata,
+    SessionPersistence, PersistedSession, SessionMetadata, SessionBookmark,
     AgentStateCheckpoint, SessionBuilder,
 };
@@ -77,6 +77,24 @@ pub struct SessionMetadata {
     /// Session title (if named by user)
     #[serde(default)]
     pub title: Option<String>,
+    /// Messages the user bookmarked
+    #[serde(default)]
+    pub bookmarks: Vec<SessionBookmark>,
+    /// Indices of the messages the user pinned
+    #[serde(default)]
+    pub pins: Vec<usize>,
+    /// Model that wrote each AI response, by message index
+    #[serde(default)]
+    pub models: std::collections::BTreeMap<usize, String>,
+}
+
+/// A message the user bookmarked, shown in the notes panel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionBookmark {
+    /// Index of the message in the history
+    pub message: usize,
+    /// Start of the message text
+    pub excerpt: String,
 }
 
 impl Default for SessionMetadata {
@@ -90,6 +108,9 @@ impl Default for SessionMetadata {
             cost: 0.0,
             elapsed_seconds: 0,
             title: None,
+            bookmarks: Vec::new(),
+            pins: Vec::new(),
+            models: Default::default(),
         }
     }
 }
@@ -0,0 +1,164 @@
+//! Trace replay
+//!
+//! Re-drives a session from a recorded trace: the recorded inputs are fed
+//! in turn, LLM responses, tool results and approvals come from the trace
+//! instead of the network, tools or user, and the decisions the engine makes
+//! are compared with the recorded ones. Any difference points at
+//! non-determinism or a behaviour change in the engine.
+
+use std::sync::Arc;
+
+use crate::agent::cognition::decision::AgentDecision;
+use crate::agent::cognition::engine::StepEngine;
+use crate::agent::cognition::input::InputEvent;
+use crate::agent::runtime::stubs::{StubTelemetry, StubWorkers};
+use crate::agent::runtime::{AgentRuntime, CapabilityGraph, ReplayCapabilities, TraceEntry, TraceRecorder};
+use crate::agent::session::input::{SessionInput, WorkerEvent};
+use crate::agent::session::{Session, SessionConfig};
+
+/// First decision that differs from the trace
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    /// 0-based index into the trace's transitions
+    pub index: usize,
+    /// None when the replay made more decisions than the trace has
+    pub expected: Option<AgentDecision>,
+    /// None when the replay stopped early
+    pub actual: Option<AgentDecision>,
+}
+
+/// Outcome of a replay
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayReport {
+    pub inputs: usize,
+    /// Decisions recorded in the trace
+    pub transitions: usize,
+    pub divergence: Option<Divergence>,
+    /// Recorded runtime events the replay never asked for
+    pub unused_events: usize,
+}
+
+impl ReplayReport {
+    /// Whether the replay reproduced the trace exactly
+    pub fn is_identical(&self) -> bool {
+        self.divergence.is_none() && self.unused_events == 0
+    }
+}
+
+/// The session input a recorded input event came from
+fn session_input(event: &InputEvent) -> Option<SessionInput> {
+    Some(match event.clone() {
+        InputEvent::UserMessage(message) => SessionInput::Chat(message),
+        InputEvent::ApprovalResult(outcome) => SessionInput::Approval(outcome),
+        InputEvent::WorkerResult(id, Ok(result)) => SessionInput::Worker(WorkerEvent::Completed {
+            job_id: id.0.to_string(),
+            result,
+        }),
+        InputEvent::WorkerResult(id, Err(error)) => SessionInput::Worker(WorkerEvent::Failed {
+            job_id: id.0.to_string(),
+            error: error.message,
+        }),
+        _ => return None,
+    })
+}
+
+fn transitions(trace: &[TraceEntry]) -> Vec<AgentDecision> {
+    trace
+        .iter()
+        .filter_map(|entry| match entry {
+            TraceEntry::Transition { decision, .. } => Some(decision.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Replay `trace` through `engine` and compare its decisions with the recorded ones
+pub async fn replay<E: StepEngine>(engine: E, trace: &[TraceEntry]) -> ReplayReport {
+    let capabilities = Arc::new(ReplayCapabilities::new(trace));
+    let graph = CapabilityGraph::new(
+        capabilities.clone(),
+        capabilities.clone(),
+        capabilities.clone(),
+        Arc::new(StubWorkers),
+        Arc::new(StubTelemetry),
+    );
+    let recorder = Arc::new(TraceRecorder::in_memory());
+    let runtime = AgentRuntime::new(graph).with_trace(Arc::clone(&recorder));
+
+    let inputs: Vec<(&InputEvent, usize)> = trace
+        .iter()
+        .filter_map(|entry| match entry {
+            TraceEntry::Input { event, max_steps } => Some((event, *max_steps)),
+            _ => None,
+        })
+        .collect();
+    let max_steps = inputs.first().map_or(SessionConfig::default().max_steps, |(_, max)| *max);
+    let mut session = Session::new(engine, runtime, SessionConfig { max_steps });
+    session.disable_autosave();
+
+    for (event, _) in &inputs {
+        let Some(input) = session_input(event) else { continue };
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        let _ = tx.send(input).await;
+        // Recorded errors end a run just as they did originally
+        if let Err(e) = session.run(rx).await {
+            crate::info_log!("[REPLAY] Run ended with error: {}", e);
+        }
+    }
+
+    let expected = transitions(trace);
+    let actual = transitions(&recorder.entries());
+    let divergence = (0..expected.len().max(actual.len()))
+        .find(|&i| expected.get(i) != actual.get(i))
+        .map(|index| Divergence {
+            index,
+            expected: expected.get(index).cloned(),
+            actual: actual.get(index).cloned(),
+        });
+    ReplayReport {
+        inputs: inputs.len(),
+        transitions: expected.len(),
+        divergence,
+        unused_events: capabilities.remaining(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::cognition::LlmEngine;
+    use crate::agent::runtime::capabilities::AutoApproveCapability;
+    use crate::agent::runtime::stubs::{StubLLM, StubTools};
+
+    #[tokio::test]
+    async fn test_recorded_session_replays_identically() {
+        // Record a session against the stub LLM
+        let recorder = Arc::new(TraceRecorder::in_memory());
+        let graph = CapabilityGraph::new(
+            Arc::new(StubLLM),
+            Arc::new(StubTools),
+            Arc::new(AutoApproveCapability::new()),
+            Arc::new(StubWorkers),
+            Arc::new(StubTelemetry),
+        );
+        let runtime = AgentRuntime::new(graph).with_trace(Arc::clone(&recorder));
+        let engine = LlmEngine::new().with_system_prompt("test prompt");
+        let mut session = Session::new(engine, runtime, SessionConfig { max_steps: 10 });
+        session.disable_autosave();
+        for message in ["what is in this directory?", "thanks"] {
+            let (tx, rx) = tokio::sync::mpsc::channel(1);
+            tx.send(SessionInput::Chat(message.to_string())).await.unwrap();
+            session.run(rx).await.unwrap();
+        }
+        let trace = recorder.entries();
+        assert!(trace.iter().any(|e| matches!(e, TraceEntry::Event { event: InputEvent::LLMResponse(_) })));
+
+        let report = replay(LlmEngine::new().with_system_prompt("test prompt"), &trace).await;
+        assert_eq!(report.inputs, 2);
+        assert!(report.is_identical(), "{:?}", report);
+
+        // A different engine configuration shows up as a divergence
+        let report = replay(LlmEngine::new().with_system_prompt("other prompt"), &trace).await;
+        assert_eq!(report.divergence.map(|d| d.index), Some(0));
+    }
+}
@@ -10,7 +10,7 @@ use crate::agent::cognition::kernel::AgentState;
 use crate::agent::types::events::WorkerId;
 use crate::agent::cognition::decision::{AgentDecision, AgentExitReason};
 use crate::agent::cognition::error::CognitiveError;
-use crate::agent::runtime::{AgentRuntime, RuntimeContext, RuntimeError};
+use crate::agent::runtime::{AgentRuntime, RuntimeContext, RuntimeError, TraceEntry};
 use crate::agent::session::input::{SessionInput, WorkerEvent};
 use crate::agent::session::persistence::{SessionPersistence, PersistedSession, SessionBuilder};
 use crate::agent::memory::AgentMemoryManager;
@@ -371,6 +371,9 @@ where
                     return Err(SessionError::Cancelled);
                 }
                 last_observation = self.translate_input(input);
+                if let (Some(trace), Some(event)) = (self.runtime.trace(), &last_observation) {
+                    trace.record(TraceEntry::Input { event: event.clone(), max_steps: self.state.max_steps });
+                }
             }
             
             // Check cancellation
@@ -383,6 +386,9 @@ where
             
             // Update state
             self.state = transition.next_state.clone();
+            if let Some(trace) = self.runtime.trace() {
+                trace.record(TraceEntry::Transition { step: self.state.step_count, decision: transition.decision.clone() });
+            }
             
             // Sync session history with ContextManager for token-aware management
             self.sync_history_to_context_manager().await;
@@ -62,6 +62,7 @@ mod example {
                     provider: "mock".to_string(),
                     finish_reason: crate::agent::types::events::FinishReason::Stop,
                     structured: None,
+                    tool_calls: Vec::new(),
                 })
             }
             
@@ -75,6 +76,7 @@ mod example {
                         content: "Mock response".to_string(),
                         is_final: true,
                         usage: None,
+                        tool_calls: Vec::new(),
                     })
                 }))
             }
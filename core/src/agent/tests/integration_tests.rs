@@ -51,6 +51,7 @@ mod tests {
                 provider: "mock".to_string(),
                 finish_reason: crate::agent::types::events::FinishReason::Stop,
                 structured: None,
+                tool_calls: Vec::new(),
             })
         }
         
@@ -68,6 +69,7 @@ mod tests {
                     content: response,
                     is_final: true,
                     usage: None,
+                    tool_calls: Vec::new(),
                 })
             }))
         }
@@ -234,6 +236,10 @@ mod tests {
             memory: Default::default(),
             extra_params: Default::default(),
             web_search_enabled: false,
+            native_tool_calling: false,
+            prompt_cache_hints: true,
+            fallbacks: Vec::new(),
+            response_cache: None,
         };
         
         let client = Arc::new(LlmClient::new(config).expect("Failed to create LLM client"));
@@ -100,6 +100,7 @@ mod tests {
                 provider: "mock".to_string(),
                 finish_reason: FinishReason::Stop,
                 structured: None,
+                tool_calls: Vec::new(),
             })
         }
 
@@ -113,6 +114,7 @@ mod tests {
                     content: "Mock streaming response".to_string(),
                     is_final: true,
                     usage: None,
+                    tool_calls: Vec::new(),
                 })
             }))
         }
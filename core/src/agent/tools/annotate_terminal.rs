@@ -0,0 +1,194 @@
+//! Annotate Terminal Tool - Point at lines of the user's terminal pane
+//!
+//! Instead of restating a 200-line stack trace in chat, the agent marks the
+//! lines that matter ("this is the actual error") and the frontend shows a
+//! marker in the pane's margin with the note in a popup. Annotations are
+//! anchored by text rather than line numbers: the frontend attaches each one
+//! to the most recent line containing `anchor`, so they follow the output as
+//! it scrolls.
+//!
+//! # Usage
+//!
+//! - `{"annotations": [{"anchor": "error[E0382]: borrow of moved value", "note": "`cfg` is moved into the thread on line 40", "severity": "error"}]}`
+//! - `{"action": "clear"}` - remove the agent's annotations
+
+use serde::{Deserialize, Serialize};
+
+use crate::agent::runtime::core::{Capability, RuntimeContext, ToolCapability, ToolError};
+use crate::agent::runtime::orchestrator::{OutputEvent, OutputSender};
+use crate::agent::tools::parse_args;
+use crate::agent::types::events::ToolResult;
+use crate::agent::types::intents::ToolCall;
+
+/// Most annotations accepted in one call
+const MAX_ANNOTATIONS: usize = 20;
+/// Longest note shown in a popup
+const MAX_NOTE_LEN: usize = 500;
+
+/// How an annotation is highlighted, most severe first
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnnotationSeverity {
+    Error,
+    Warning,
+    #[default]
+    Info,
+}
+
+/// A note attached to a line of terminal output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TerminalAnnotation {
+    /// Text on the annotated line (the latest line containing it is used)
+    pub anchor: String,
+    pub note: String,
+    #[serde(default)]
+    pub severity: AnnotationSeverity,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnnotateArgs {
+    #[serde(default)]
+    action: Option<String>,
+    #[serde(default)]
+    annotations: Vec<TerminalAnnotation>,
+}
+
+/// Tool for annotating the terminal pane next to the chat
+pub struct AnnotateTerminalTool {
+    output_tx: OutputSender,
+}
+
+impl AnnotateTerminalTool {
+    /// Send annotations to the frontend through `output_tx`
+    pub fn new(output_tx: OutputSender) -> Self {
+        Self { output_tx }
+    }
+
+    fn send(&self, annotations: Vec<TerminalAnnotation>, clear: bool) -> Result<(), ToolResult> {
+        self.output_tx
+            .send(OutputEvent::TerminalAnnotations { annotations, clear })
+            .map_err(|e| ToolResult::Error {
+                message: format!("No terminal pane to annotate: {}", e),
+                code: Some("NO_TERMINAL".to_string()),
+                retryable: false,
+            })
+    }
+}
+
+/// Check and tidy annotations from the model
+fn validate(annotations: Vec<TerminalAnnotation>) -> Result<Vec<TerminalAnnotation>, String> {
+    if annotations.is_empty() {
+        return Err("'annotations' must contain at least one {\"anchor\", \"note\"} entry".to_string());
+    }
+    if annotations.len() > MAX_ANNOTATIONS {
+        return Err(format!(
+            "Too many annotations ({}); mark at most {} lines",
+            annotations.len(),
+            MAX_ANNOTATIONS
+        ));
+    }
+    annotations
+        .into_iter()
+        .map(|mut annotation| {
+            annotation.anchor = annotation.anchor.trim().to_string();
+            annotation.note = annotation.note.trim().to_string();
+            if annotation.anchor.is_empty() || annotation.anchor.contains('\n') {
+                return Err("Each 'anchor' must be text from a single terminal line".to_string());
+            }
+            if annotation.note.is_empty() {
+                return Err(format!("Annotation for '{}' has an empty note", annotation.anchor));
+            }
+            if let Some((cut, _)) = annotation.note.char_indices().nth(MAX_NOTE_LEN) {
+                annotation.note.truncate(cut);
+                annotation.note.push_str("...");
+            }
+            Ok(annotation)
+        })
+        .collect()
+}
+
+impl Capability for AnnotateTerminalTool {
+    fn name(&self) -> &'static str {
+        "annotate_terminal"
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolCapability for AnnotateTerminalTool {
+    fn description(&self) -> &'static str {
+        "Mark lines of the user's terminal pane with short notes (shown as margin markers with a popup) instead of quoting long output in chat, e.g. to point at the real error in a stack trace. 'anchor' is text from the line; severity is error, warning or info"
+    }
+
+    fn usage(&self) -> &'static str {
+        r#"{"a": "annotate_terminal", "i": {"annotations": [{"anchor": "error[E0382]: borrow of moved value: `cfg`", "note": "cfg is moved into the spawned thread; clone it first", "severity": "error"}]}} | Remove: {"a": "annotate_terminal", "i": {"action": "clear"}}"#
+    }
+
+    async fn execute(&self, _ctx: &RuntimeContext, call: ToolCall) -> Result<ToolResult, ToolError> {
+        let args: AnnotateArgs = parse_args(&call.arguments)?;
+        match args.action.as_deref().unwrap_or("annotate") {
+            "clear" => Ok(match self.send(Vec::new(), true) {
+                Ok(()) => ToolResult::Success {
+                    output: "Cleared terminal annotations".to_string(),
+                    structured: None,
+                },
+                Err(error) => error,
+            }),
+            "annotate" => {
+                let annotations = validate(args.annotations).map_err(ToolError::new)?;
+                let count = annotations.len();
+                if let Err(error) = self.send(annotations, false) {
+                    return Ok(error);
+                }
+                Ok(ToolResult::Success {
+                    output: format!(
+                        "Added {} annotation(s) to the terminal pane. Refer to them briefly instead of repeating the output.",
+                        count
+                    ),
+                    structured: Some(serde_json::json!({ "annotations": count })),
+                })
+            }
+            other => Err(ToolError::new(format!("Unknown action '{}'. Use annotate or clear", other))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_annotations_are_sent_to_the_frontend() {
+        let (tx, mut rx) = tokio::sync::broadcast::channel(4);
+        let tool = AnnotateTerminalTool::new(OutputSender::Broadcast(tx));
+        let call = ToolCall::new(
+            "annotate_terminal",
+            serde_json::json!({"annotations": [
+                {"anchor": "  panicked at src/main.rs:12:5 ", "note": "unwrap on a missing env var", "severity": "error"},
+                {"anchor": "note: run with RUST_BACKTRACE=1", "note": "backtrace hint"}
+            ]}),
+        );
+
+        let result = tool.execute(&RuntimeContext::new(), call).await.unwrap();
+        assert!(matches!(result, ToolResult::Success { .. }));
+        match rx.recv().await.unwrap() {
+            OutputEvent::TerminalAnnotations { annotations, clear } => {
+                assert!(!clear);
+                assert_eq!(annotations[0].anchor, "panicked at src/main.rs:12:5");
+                assert_eq!(annotations[0].severity, AnnotationSeverity::Error);
+                assert_eq!(annotations[1].severity, AnnotationSeverity::Info);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_multiline_anchor_is_rejected() {
+        let error = validate(vec![TerminalAnnotation {
+            anchor: "line one\nline two".to_string(),
+            note: "spans lines".to_string(),
+            severity: AnnotationSeverity::Warning,
+        }])
+        .unwrap_err();
+        assert!(error.contains("single terminal line"));
+    }
+}
@@ -0,0 +1,531 @@
+//! Apply Patch Tool - Edit files with unified diffs
+//!
+//! `write_file` replaces whole files, which is slow and risky for large
+//! ones. This tool takes a unified diff for a single file, checks every hunk
+//! against the current contents before touching anything, and keeps a backup
+//! of the previous version so the change can be reverted. While a changeset
+//! is open in the patch queue, patches apply to the queued content and are
+//! queued too; revert then drops the file's latest queued change. Patches
+//! written to disk are also recorded in the undo journal.
+//!
+//! # Usage
+//!
+//! - Apply: `{"path": "src/lib.rs", "patch": "@@ -1,3 +1,3 @@\n fn a() {}\n-fn b() {}\n+fn c() {}\n"}`
+//! - Validate only: `{"action": "check", "path": "src/lib.rs", "patch": "..."}`
+//! - Undo the last patch: `{"action": "revert", "path": "src/lib.rs"}`
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+use crate::agent::runtime::core::{Capability, RuntimeContext, ToolCapability, ToolError};
+use crate::agent::runtime::governance::SecretGuard;
+use crate::agent::tools::patch_queue::PatchQueue;
+use crate::agent::tools::undo_journal::UndoJournal;
+use crate::agent::tools::write_file::queue_write;
+use crate::agent::tools::{expand_tilde, parse_args};
+use crate::agent::types::events::ToolResult;
+use crate::agent::types::intents::ToolCall;
+
+/// How far from its stated position a hunk may have drifted
+const MAX_HUNK_DRIFT: usize = 200;
+
+/// One `@@` block of a unified diff
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// 1-based start line in the original file (0 for an empty file)
+    pub old_start: usize,
+    pub header: String,
+    pub lines: Vec<HunkLine>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HunkLine {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+impl Hunk {
+    /// Lines the hunk expects to find in the file
+    fn old_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|l| match l {
+                HunkLine::Context(s) | HunkLine::Remove(s) => Some(s.as_str()),
+                HunkLine::Add(_) => None,
+            })
+            .collect()
+    }
+
+    /// Lines the hunk leaves in the file
+    fn new_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|l| match l {
+                HunkLine::Context(s) | HunkLine::Add(s) => Some(s.as_str()),
+                HunkLine::Remove(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// Parse the hunks of a single-file unified diff
+///
+/// `---`/`+++`/`diff`/`index` headers are skipped. Blank lines inside a hunk
+/// are read as empty context lines, since models often drop the leading space.
+pub fn parse_patch(patch: &str) -> Result<Vec<Hunk>, String> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    let mut lines = patch.trim_end().lines().peekable();
+    while let Some(line) = lines.next() {
+        if line.starts_with("@@") {
+            hunks.push(Hunk {
+                old_start: parse_hunk_header(line)?,
+                header: line.to_string(),
+                lines: Vec::new(),
+            });
+            continue;
+        }
+        let Some(hunk) = hunks.last_mut() else {
+            // Preamble before the first hunk (file headers, prose)
+            continue;
+        };
+        if line.starts_with("--- ") && lines.peek().is_some_and(|next| next.starts_with("+++ ")) {
+            // Header of a second file; this tool patches one file at a time
+            return Err("Patch touches more than one file; send one patch per file".to_string());
+        }
+        match line.chars().next() {
+            Some(' ') => hunk.lines.push(HunkLine::Context(line[1..].to_string())),
+            Some('-') => hunk.lines.push(HunkLine::Remove(line[1..].to_string())),
+            Some('+') => hunk.lines.push(HunkLine::Add(line[1..].to_string())),
+            Some('\\') => {} // "\ No newline at end of file"
+            None => hunk.lines.push(HunkLine::Context(String::new())),
+            Some(_) => return Err(format!("Unexpected line in hunk {}: {:?}", hunk.header, line)),
+        }
+    }
+    if hunks.is_empty() {
+        return Err("Patch has no hunks (expected lines starting with @@)".to_string());
+    }
+    if let Some(empty) = hunks.iter().find(|h| h.lines.is_empty()) {
+        return Err(format!("Hunk {} has no lines", empty.header));
+    }
+    Ok(hunks)
+}
+
+/// Old start line from `@@ -12,5 +12,6 @@`
+fn parse_hunk_header(line: &str) -> Result<usize, String> {
+    line.split_whitespace()
+        .nth(1)
+        .and_then(|range| range.strip_prefix('-'))
+        .and_then(|range| range.split(',').next())
+        .and_then(|start| start.parse().ok())
+        .ok_or_else(|| format!("Malformed hunk header: {}", line))
+}
+
+/// Result of applying hunks in memory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchedFile {
+    pub content: String,
+    pub added: usize,
+    pub removed: usize,
+}
+
+/// Apply `hunks` to `original`, failing without changes if any hunk does not fit
+///
+/// Each hunk is looked for at its stated line first, then at the nearest
+/// position within `MAX_HUNK_DRIFT` lines; trailing whitespace is ignored
+/// when comparing.
+pub fn apply_hunks(original: &str, hunks: &[Hunk]) -> Result<PatchedFile, String> {
+    let lines: Vec<&str> = original.lines().collect();
+    let mut output: Vec<&str> = Vec::with_capacity(lines.len());
+    let mut cursor = 0;
+    let mut drift: isize = 0;
+    let (mut added, mut removed) = (0, 0);
+
+    for (index, hunk) in hunks.iter().enumerate() {
+        let expected = hunk.old_lines();
+        let stated = (hunk.old_start.saturating_sub(1) as isize + drift).max(cursor as isize) as usize;
+        let position = find_hunk(&lines, &expected, stated, cursor).ok_or_else(|| {
+            format!(
+                "Hunk {} ({}) does not match the file. Expected near line {}:\n{}\nRe-read the file and regenerate the patch.",
+                index + 1,
+                hunk.header,
+                hunk.old_start,
+                expected.iter().take(5).map(|l| format!("  {}", l)).collect::<Vec<_>>().join("\n")
+            )
+        })?;
+        drift = position as isize - hunk.old_start.saturating_sub(1) as isize;
+        output.extend_from_slice(&lines[cursor..position]);
+        output.extend(hunk.new_lines());
+        cursor = position + expected.len();
+        added += hunk.lines.iter().filter(|l| matches!(l, HunkLine::Add(_))).count();
+        removed += hunk.lines.iter().filter(|l| matches!(l, HunkLine::Remove(_))).count();
+    }
+    output.extend_from_slice(&lines[cursor..]);
+
+    let mut content = output.join("\n");
+    if !content.is_empty() && (original.ends_with('\n') || original.is_empty()) {
+        content.push('\n');
+    }
+    Ok(PatchedFile { content, added, removed })
+}
+
+fn find_hunk(lines: &[&str], expected: &[&str], stated: usize, min: usize) -> Option<usize> {
+    let fits = |at: usize| {
+        at + expected.len() <= lines.len()
+            && lines[at..at + expected.len()]
+                .iter()
+                .zip(expected)
+                .all(|(have, want)| have.trim_end() == want.trim_end())
+    };
+    if expected.is_empty() {
+        return Some(stated.min(lines.len()));
+    }
+    (0..=MAX_HUNK_DRIFT)
+        .flat_map(|offset| [stated.checked_add(offset), stated.checked_sub(offset)])
+        .flatten()
+        .filter(|&at| at >= min)
+        .find(|&at| fits(at))
+}
+
+#[derive(Debug, Deserialize)]
+struct PatchArgs {
+    #[serde(default = "default_action")]
+    action: String,
+    path: String,
+    #[serde(default)]
+    patch: Option<String>,
+}
+
+fn default_action() -> String {
+    "apply".to_string()
+}
+
+/// Tool for editing files with unified diffs
+#[derive(Debug, Clone)]
+pub struct ApplyPatchTool {
+    backup_dir: PathBuf,
+    /// Queue that takes patches while a changeset is open
+    patch_queue: Option<Arc<PatchQueue>>,
+    /// Journal that keeps the previous content of patched files
+    undo_journal: Option<Arc<UndoJournal>>,
+    secret_guard: Arc<SecretGuard>,
+}
+
+impl Default for ApplyPatchTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ApplyPatchTool {
+    /// Create a tool that keeps backups in the data directory
+    pub fn new() -> Self {
+        let backup_dir = dirs::data_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("mylm")
+            .join("patch_backups");
+        Self {
+            backup_dir,
+            patch_queue: None,
+            undo_journal: None,
+            secret_guard: Arc::new(SecretGuard::new()),
+        }
+    }
+
+    /// Keep backups in `dir` instead
+    pub fn with_backup_dir(mut self, dir: PathBuf) -> Self {
+        self.backup_dir = dir;
+        self
+    }
+
+    /// Queue patches in `queue` while it has a changeset open
+    pub fn with_patch_queue(mut self, queue: Arc<PatchQueue>) -> Self {
+        self.patch_queue = Some(queue);
+        self
+    }
+
+    /// Save the previous content of patched files in `journal`
+    pub fn with_undo_journal(mut self, journal: Arc<UndoJournal>) -> Self {
+        self.undo_journal = Some(journal);
+        self
+    }
+
+    /// Refuse files the guard denies
+    pub fn with_secret_guard(mut self, guard: Arc<SecretGuard>) -> Self {
+        self.secret_guard = guard;
+        self
+    }
+
+    /// The patch queue, if it has a changeset open
+    fn active_queue(&self) -> Option<&PatchQueue> {
+        self.patch_queue.as_deref().filter(|queue| queue.is_active())
+    }
+
+    /// Backup location for `path`; one backup per file, from its latest patch
+    fn backup_path(&self, path: &Path) -> PathBuf {
+        let name = path.to_string_lossy().replace(['/', '\\', ':'], "%");
+        self.backup_dir.join(format!("{}.orig", name))
+    }
+
+    /// Marker left when a patch created the file, so revert deletes it
+    fn created_marker(&self, path: &Path) -> PathBuf {
+        self.backup_path(path).with_extension("created")
+    }
+
+    fn error(message: String, code: &str) -> ToolResult {
+        ToolResult::Error {
+            message,
+            code: Some(code.to_string()),
+            retryable: false,
+        }
+    }
+
+    async fn apply(&self, path: &Path, patch: &str, dry_run: bool) -> Result<ToolResult, ToolError> {
+        let hunks = match parse_patch(patch) {
+            Ok(hunks) => hunks,
+            Err(e) => return Ok(Self::error(e, "INVALID_PATCH")),
+        };
+        let staged = match self.active_queue().map(|queue| queue.staged(path)) {
+            Some(Err(e)) => return Ok(Self::error(e, "QUEUE_ERROR")),
+            Some(Ok(staged)) => staged,
+            None => None,
+        };
+        let existing = match staged {
+            Some(content) => Some(content),
+            None => match tokio::fs::read_to_string(path).await {
+                Ok(content) => Some(content),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => return Ok(Self::error(format!("Cannot read {}: {}", path.display(), e), "READ_ERROR")),
+            },
+        };
+        let patched = match apply_hunks(existing.as_deref().unwrap_or(""), &hunks) {
+            Ok(patched) => patched,
+            Err(e) => return Ok(Self::error(e, "HUNK_MISMATCH")),
+        };
+        let summary = format!(
+            "{} hunk(s), +{} -{} lines",
+            hunks.len(),
+            patched.added,
+            patched.removed
+        );
+        if dry_run {
+            return Ok(ToolResult::Success {
+                output: format!("Patch applies cleanly to {}: {}", path.display(), summary),
+                structured: None,
+            });
+        }
+
+        if let Some(queue) = self.active_queue() {
+            return Ok(queue_write(queue, path, &patched.content));
+        }
+
+        if let Err(e) = self.save_backup(path, existing.as_deref()).await {
+            return Ok(Self::error(format!("Cannot save backup, patch not applied: {}", e), "BACKUP_ERROR"));
+        }
+        if let Some(Err(e)) = self.undo_journal.as_ref().map(|journal| journal.record(path)) {
+            return Ok(Self::error(format!("{}; patch not applied", e), "UNDO_ERROR"));
+        }
+        if let Some(parent) = path.parent() {
+            if let Err(e) = tokio::fs::create_dir_all(parent).await {
+                return Ok(Self::error(format!("Cannot create directory: {}", e), "MKDIR_ERROR"));
+            }
+        }
+        crate::watch::note_agent_write(path);
+        if let Err(e) = tokio::fs::write(path, &patched.content).await {
+            return Ok(Self::error(format!("Error writing file: {}", e), "WRITE_ERROR"));
+        }
+        if let Some(journal) = &self.undo_journal {
+            journal.written(path, patched.content.as_bytes());
+        }
+
+        Ok(ToolResult::Success {
+            output: format!(
+                "Patched {}: {}. Undo with {{\"action\": \"revert\", \"path\": \"{}\"}}",
+                path.display(),
+                summary,
+                path.display()
+            ),
+            structured: Some(serde_json::json!({
+                "path": path.to_string_lossy(),
+                "hunks": hunks.len(),
+                "added": patched.added,
+                "removed": patched.removed,
+            })),
+        })
+    }
+
+    async fn save_backup(&self, path: &Path, existing: Option<&str>) -> std::io::Result<()> {
+        tokio::fs::create_dir_all(&self.backup_dir).await?;
+        let backup = self.backup_path(path);
+        let marker = self.created_marker(path);
+        match existing {
+            Some(content) => {
+                tokio::fs::write(&backup, content).await?;
+                let _ = tokio::fs::remove_file(&marker).await;
+            }
+            None => {
+                tokio::fs::write(&marker, "").await?;
+                let _ = tokio::fs::remove_file(&backup).await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn revert(&self, path: &Path) -> Result<ToolResult, ToolError> {
+        if let Some(queue) = self.active_queue() {
+            return Ok(match queue.unstage(path) {
+                Ok(()) => ToolResult::Success {
+                    output: format!("Dropped the latest queued change to {}", path.display()),
+                    structured: None,
+                },
+                Err(e) => Self::error(e, "NO_BACKUP"),
+            });
+        }
+        let backup = self.backup_path(path);
+        let marker = self.created_marker(path);
+        if marker.exists() {
+            if let Err(e) = tokio::fs::remove_file(path).await {
+                return Ok(Self::error(format!("Cannot remove {}: {}", path.display(), e), "WRITE_ERROR"));
+            }
+            let _ = tokio::fs::remove_file(&marker).await;
+            return Ok(ToolResult::Success {
+                output: format!("Reverted {}: removed the file the patch created", path.display()),
+                structured: None,
+            });
+        }
+        let content = match tokio::fs::read_to_string(&backup).await {
+            Ok(content) => content,
+            Err(_) => {
+                return Ok(Self::error(
+                    format!("No patch backup for {}", path.display()),
+                    "NO_BACKUP",
+                ))
+            }
+        };
+        crate::watch::note_agent_write(path);
+        if let Err(e) = tokio::fs::write(path, content).await {
+            return Ok(Self::error(format!("Error writing file: {}", e), "WRITE_ERROR"));
+        }
+        let _ = tokio::fs::remove_file(&backup).await;
+        Ok(ToolResult::Success {
+            output: format!("Reverted {} to its content before the last patch", path.display()),
+            structured: None,
+        })
+    }
+}
+
+impl Capability for ApplyPatchTool {
+    fn name(&self) -> &'static str {
+        "apply_patch"
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolCapability for ApplyPatchTool {
+    fn description(&self) -> &'static str {
+        "Edit an existing file with a unified diff (one file per call). Prefer this over write_file for changes to large files. Hunks are checked before anything is written; action \"revert\" restores the file from before the last patch"
+    }
+
+    fn usage(&self) -> &'static str {
+        r#"{"a": "apply_patch", "i": {"path": "src/lib.rs", "patch": "@@ -10,3 +10,3 @@\n fn keep() {}\n-fn old() {}\n+fn new() {}\n"}} | Undo: {"a": "apply_patch", "i": {"action": "revert", "path": "src/lib.rs"}}"#
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &RuntimeContext,
+        call: ToolCall,
+    ) -> Result<ToolResult, ToolError> {
+        let args: PatchArgs = parse_args(&call.arguments)?;
+        let path = expand_tilde(&args.path);
+        let path = Path::new(&path);
+        // The patch result echoes file content back to the model
+        if let Err(denied) = self.secret_guard.check("apply_patch", path) {
+            return Ok(Self::error(denied.to_string(), "SECRET_FILE"));
+        }
+        match args.action.as_str() {
+            "apply" | "check" => {
+                let patch = args
+                    .patch
+                    .ok_or_else(|| ToolError::new("'patch' is required to apply or check a patch"))?;
+                self.apply(path, &patch, args.action == "check").await
+            }
+            "revert" => self.revert(path).await,
+            other => Err(ToolError::new(format!(
+                "Unknown action '{}'. Use apply, check or revert",
+                other
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const ORIGINAL: &str = "fn one() {}\nfn two() {}\nfn three() {}\nfn four() {}\n";
+
+    async fn run(tool: &ApplyPatchTool, args: serde_json::Value) -> ToolResult {
+        tool.execute(&RuntimeContext::new(), ToolCall::new("apply_patch", args)).await.unwrap()
+    }
+
+    #[test]
+    fn test_hunks_apply_with_drift() {
+        let patch = "--- a/lib.rs\n+++ b/lib.rs\n@@ -1,2 +1,2 @@\n fn one() {}\n-fn two() {}\n+fn deux() {}\n@@ -9,2 +9,3 @@\n fn four() {}\n+fn five() {}\n";
+        let hunks = parse_patch(patch).unwrap();
+        let patched = apply_hunks(ORIGINAL, &hunks).unwrap();
+        assert_eq!(patched.content, "fn one() {}\nfn deux() {}\nfn three() {}\nfn four() {}\nfn five() {}\n");
+        assert_eq!((patched.added, patched.removed), (2, 1));
+    }
+
+    #[test]
+    fn test_mismatched_hunk_is_rejected() {
+        let hunks = parse_patch("@@ -2,1 +2,1 @@\n-fn zwei() {}\n+fn deux() {}\n").unwrap();
+        let err = apply_hunks(ORIGINAL, &hunks).unwrap_err();
+        assert!(err.contains("Hunk 1"));
+        assert!(parse_patch("no hunks here").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_apply_and_revert() {
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join("lib.rs");
+        tokio::fs::write(&file, ORIGINAL).await.unwrap();
+        let tool = ApplyPatchTool::new().with_backup_dir(temp.path().join("backups"));
+        let path = file.to_str().unwrap();
+
+        let bad = run(&tool, serde_json::json!({"path": path, "patch": "@@ -1 +1 @@\n-nope\n+yes\n"})).await;
+        assert!(matches!(bad, ToolResult::Error { code: Some(ref c), .. } if c == "HUNK_MISMATCH"));
+        assert_eq!(tokio::fs::read_to_string(&file).await.unwrap(), ORIGINAL);
+
+        let result = run(&tool, serde_json::json!({"path": path, "patch": "@@ -3 +3 @@\n-fn three() {}\n+fn drei() {}\n"})).await;
+        assert!(matches!(result, ToolResult::Success { .. }));
+        assert!(tokio::fs::read_to_string(&file).await.unwrap().contains("fn drei()"));
+
+        let result = run(&tool, serde_json::json!({"action": "revert", "path": path})).await;
+        assert!(matches!(result, ToolResult::Success { .. }));
+        assert_eq!(tokio::fs::read_to_string(&file).await.unwrap(), ORIGINAL);
+
+        let env = temp.path().join(".env");
+        tokio::fs::write(&env, "TOKEN=x\n").await.unwrap();
+        let result = run(&tool, serde_json::json!({"action": "check", "path": env.to_str().unwrap(), "patch": "@@ -1 +1 @@\n-TOKEN=x\n+TOKEN=y\n"})).await;
+        assert!(matches!(result, ToolResult::Error { code: Some(ref c), .. } if c == "SECRET_FILE"));
+    }
+
+    #[tokio::test]
+    async fn test_patch_can_create_and_revert_new_file() {
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join("new.txt");
+        let tool = ApplyPatchTool::new().with_backup_dir(temp.path().join("backups"));
+        let path = file.to_str().unwrap();
+
+        let result = run(&tool, serde_json::json!({"path": path, "patch": "--- /dev/null\n+++ b/new.txt\n@@ -0,0 +1,2 @@\n+hello\n+world\n"})).await;
+        assert!(matches!(result, ToolResult::Success { .. }));
+        assert_eq!(tokio::fs::read_to_string(&file).await.unwrap(), "hello\nworld\n");
+
+        run(&tool, serde_json::json!({"action": "revert", "path": path})).await;
+        assert!(!file.exists());
+    }
+}
@@ -0,0 +1,101 @@
+//! Helpers shared by the tools that wrap a command line program
+//!
+//! The docker and kubectl tools build an argv from named actions. Both need
+//! names that can never be read as a flag, an exec command split the same
+//! way, a run that cannot hang, and output cut to a size the model can take.
+
+use std::path::Path;
+use std::time::Duration;
+
+use tokio::process::Command;
+
+use crate::agent::runtime::core::ToolError;
+
+/// Output beyond this is cut
+pub const MAX_OUTPUT_CHARS: usize = 20_000;
+
+/// Container, resource, namespace and similar names, never a flag
+pub fn validate_name(kind: &str, name: &str) -> Result<(), ToolError> {
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphanumeric())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-' | ':' | '/' | '@'));
+    if valid {
+        Ok(())
+    } else {
+        Err(ToolError::new(format!("Invalid {} '{}'", kind, name)))
+    }
+}
+
+/// The argv of an `exec` action: a list of strings, or a string split like a
+/// shell would
+pub fn exec_command(value: Option<&serde_json::Value>) -> Result<Vec<String>, ToolError> {
+    let argv = match value {
+        Some(serde_json::Value::String(command)) => shell_words::split(command)
+            .map_err(|e| ToolError::new(format!("Could not split command: {}", e)))?,
+        Some(serde_json::Value::Array(items)) => items
+            .iter()
+            .map(|item| item.as_str().map(str::to_string))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| ToolError::new("'command' must be a list of strings"))?,
+        _ => return Err(ToolError::new("'command' is required for exec")),
+    };
+    if argv.is_empty() {
+        return Err(ToolError::new("'command' is empty"));
+    }
+    Ok(argv)
+}
+
+/// Run `binary` with `args`, killed after `timeout`; returns whether it
+/// succeeded, its stdout and its stderr
+pub async fn run_program(
+    binary: &Path,
+    args: &[String],
+    timeout: Duration,
+) -> Result<(bool, String, String), ToolError> {
+    let child = Command::new(binary).args(args).kill_on_drop(true).output();
+    let program = binary.file_name().unwrap_or(binary.as_os_str()).to_string_lossy();
+    let output = tokio::time::timeout(timeout, child)
+        .await
+        .map_err(|_| ToolError::new(format!("{} {} timed out after {}s", program, args.join(" "), timeout.as_secs())))?
+        .map_err(|e| ToolError::new(format!("Failed to run {}: {}", binary.display(), e)))?;
+    Ok((
+        output.status.success(),
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+    ))
+}
+
+/// `text` cut to its last `MAX_OUTPUT_CHARS` characters
+pub fn clip(text: &str) -> String {
+    if text.chars().count() <= MAX_OUTPUT_CHARS {
+        return text.to_string();
+    }
+    // Keep the end: the latest log lines, events and errors matter most
+    let skip = text.chars().count() - MAX_OUTPUT_CHARS;
+    format!("…[{} chars cut]\n{}", skip, text.chars().skip(skip).collect::<String>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_commands_and_clipping() {
+        assert!(validate_name("container", "api-1").is_ok());
+        assert!(validate_name("context", "admin@prod").is_ok());
+        assert!(validate_name("container", "--privileged").is_err());
+        assert!(validate_name("name", "").is_err());
+
+        let argv = exec_command(Some(&serde_json::json!("psql -c 'select 1'"))).unwrap();
+        assert_eq!(argv, vec!["psql", "-c", "select 1"]);
+        assert!(exec_command(Some(&serde_json::json!(["ls", 1]))).is_err());
+        assert!(exec_command(Some(&serde_json::json!(""))).is_err());
+        assert!(exec_command(None).is_err());
+
+        let long = format!("{}end", "x".repeat(MAX_OUTPUT_CHARS));
+        let clipped = clip(&long);
+        assert!(clipped.starts_with("…[3 chars cut]\n"));
+        assert!(clipped.ends_with("end"));
+        assert_eq!(clip("short"), "short");
+    }
+}
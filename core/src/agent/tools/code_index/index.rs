@@ -0,0 +1,398 @@
+//! On-disk code embedding index
+//!
+//! Source files are cut into chunks (one per function or type where the
+//! code_outline grammars apply, line windows elsewhere), embedded and stored
+//! in `.mylm/code_index.db` under the repository root. A refresh walks the
+//! tree like code_search does and only re-embeds files whose size or
+//! modification time changed. Chunk text is not stored; hits are read back
+//! from the (freshly indexed) files.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use ignore::WalkBuilder;
+use rusqlite::{params, Connection};
+
+use crate::agent::runtime::governance::SecretGuard;
+use crate::agent::tools::code_outline;
+
+/// Index location, relative to the repository root
+pub const INDEX_PATH: &str = ".mylm/code_index.db";
+
+/// Current schema version, stored in `PRAGMA user_version`
+const SCHEMA_VERSION: i64 = 1;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS files (
+    path TEXT PRIMARY KEY,
+    mtime INTEGER NOT NULL,
+    size INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS chunks (
+    id INTEGER PRIMARY KEY,
+    path TEXT NOT NULL,
+    start_line INTEGER NOT NULL,
+    end_line INTEGER NOT NULL,
+    label TEXT NOT NULL,
+    embedding BLOB NOT NULL
+);
+CREATE INDEX IF NOT EXISTS chunks_path ON chunks(path);
+";
+
+/// Extensions of files worth indexing
+const INDEXED_EXTENSIONS: &[&str] = &[
+    "rs", "py", "pyi", "js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "go", "java", "kt", "scala", "c", "h",
+    "cc", "cpp", "hpp", "cs", "rb", "php", "swift", "sh", "bash", "lua", "sql", "md", "toml", "yaml", "yml",
+];
+/// Files larger than this are skipped
+const MAX_FILE_BYTES: u64 = 512 * 1024;
+/// Longest chunk; longer symbols are split
+const MAX_CHUNK_LINES: usize = 80;
+/// Line windows for files without symbols
+const WINDOW_LINES: usize = 60;
+const WINDOW_STEP: usize = 50;
+/// Text embedded per chunk
+const MAX_EMBED_CHARS: usize = 1500;
+
+/// Turns texts into embedding vectors (one per text, same order)
+pub type EmbedFn = Arc<dyn Fn(Vec<String>) -> Result<Vec<Vec<f32>>, String> + Send + Sync>;
+
+/// A span of a file that is embedded as one unit
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    /// 1-based, inclusive
+    pub start_line: usize,
+    pub end_line: usize,
+    /// e.g. "fn Parser::advance" or "lines 51-110"
+    pub label: String,
+}
+
+/// One search result
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Hit {
+    pub path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub label: String,
+    pub score: f32,
+}
+
+/// What a refresh did
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RefreshStats {
+    /// Files (re-)embedded
+    pub indexed: usize,
+    /// Files dropped because they were deleted or are now ignored
+    pub removed: usize,
+    /// Totals after the refresh
+    pub files: usize,
+    pub chunks: usize,
+}
+
+/// Split a file into chunks: its innermost symbols, or line windows
+pub fn chunk_file(path: &Path, source: &str) -> Vec<Chunk> {
+    let total = source.lines().count();
+    let symbols = code_outline::symbols(path, source).unwrap_or_default();
+    // Innermost symbols only: an impl is covered by its methods
+    let leaves: Vec<_> = symbols
+        .iter()
+        .filter(|outer| {
+            !symbols.iter().any(|inner| {
+                inner.depth > outer.depth && inner.start_line >= outer.start_line && inner.end_line <= outer.end_line
+            })
+        })
+        .collect();
+
+    let mut chunks = Vec::new();
+    if leaves.is_empty() {
+        let mut start = 1;
+        while start <= total {
+            let end = (start + WINDOW_LINES - 1).min(total);
+            chunks.push(Chunk { start_line: start, end_line: end, label: format!("lines {}-{}", start, end) });
+            if end == total {
+                break;
+            }
+            start += WINDOW_STEP;
+        }
+        return chunks;
+    }
+    for entry in leaves {
+        let label = format!("{} {}", entry.kind, entry.qualified_name());
+        let mut start = entry.start_line;
+        while start <= entry.end_line {
+            let end = (start + MAX_CHUNK_LINES - 1).min(entry.end_line);
+            chunks.push(Chunk { start_line: start, end_line: end, label: label.clone() });
+            start = end + 1;
+        }
+    }
+    chunks
+}
+
+/// Text handed to the embedding model for a chunk
+fn embed_text(path: &str, chunk: &Chunk, lines: &[&str]) -> String {
+    let body = lines[chunk.start_line - 1..chunk.end_line.min(lines.len())].join("\n");
+    let mut text = format!("{}\n{}\n{}", path, chunk.label, body);
+    if text.len() > MAX_EMBED_CHARS {
+        let mut cut = MAX_EMBED_CHARS;
+        while !text.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        text.truncate(cut);
+    }
+    text
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+fn to_blob(embedding: &[f32]) -> Vec<u8> {
+    embedding.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn from_blob(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect()
+}
+
+/// `prefix` as a LIKE pattern matching paths that start with it; `\` is
+/// the escape character
+fn like_prefix(prefix: &str) -> String {
+    let mut pattern = String::with_capacity(prefix.len() + 1);
+    for c in prefix.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+/// The index of one repository
+pub struct CodeIndex {
+    root: PathBuf,
+    conn: Connection,
+}
+
+impl CodeIndex {
+    /// Open (or create) the index under `root`
+    pub fn open(root: &Path) -> Result<Self, String> {
+        let path = root.join(INDEX_PATH);
+        let dir = path.parent().unwrap_or(root);
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        // Keep the index out of commits without touching the project's .gitignore
+        let ignore = dir.join(".gitignore");
+        if !ignore.exists() {
+            let _ = std::fs::write(&ignore, "code_index.db*\n");
+        }
+
+        let conn = Connection::open(&path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        let version: i64 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        if version != SCHEMA_VERSION {
+            conn.execute_batch("DROP TABLE IF EXISTS files; DROP TABLE IF EXISTS chunks;")
+                .map_err(|e| e.to_string())?;
+        }
+        conn.execute_batch(SCHEMA).map_err(|e| e.to_string())?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION).map_err(|e| e.to_string())?;
+        conn.pragma_update(None, "journal_mode", "WAL").map_err(|e| e.to_string())?;
+        Ok(Self { root: root.to_path_buf(), conn })
+    }
+
+    /// Indexable files under the root: relative path -> (mtime ms, size)
+    fn scan(&self, guard: &SecretGuard) -> HashMap<String, (i64, i64)> {
+        let mut files = HashMap::new();
+        let mut walker = WalkBuilder::new(&self.root);
+        walker.require_git(false);
+        for entry in walker.build().flatten() {
+            let path = entry.path();
+            let indexable = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| INDEXED_EXTENSIONS.contains(&e));
+            if !indexable || guard.check("code_search_semantic", path).is_err() {
+                continue;
+            }
+            let Ok(meta) = entry.metadata() else { continue };
+            if !meta.is_file() || meta.len() > MAX_FILE_BYTES {
+                continue;
+            }
+            let mtime = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_millis() as i64);
+            let Ok(relative) = path.strip_prefix(&self.root) else { continue };
+            files.insert(relative.to_string_lossy().into_owned(), (mtime, meta.len() as i64));
+        }
+        files
+    }
+
+    /// Bring the index up to date with the files on disk
+    pub fn refresh(&mut self, embed: &EmbedFn, guard: &SecretGuard) -> Result<RefreshStats, String> {
+        let current = self.scan(guard);
+        let known: HashMap<String, (i64, i64)> = {
+            let mut stmt = self.conn.prepare("SELECT path, mtime, size FROM files").map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get::<_, String>(0)?, (row.get(1)?, row.get(2)?))))
+                .map_err(|e| e.to_string())?;
+            rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+        };
+
+        let mut stats = RefreshStats::default();
+        for path in known.keys().filter(|path| !current.contains_key(*path)) {
+            let tx = self.conn.transaction().map_err(|e| e.to_string())?;
+            tx.execute("DELETE FROM chunks WHERE path = ?1", params![path]).map_err(|e| e.to_string())?;
+            tx.execute("DELETE FROM files WHERE path = ?1", params![path]).map_err(|e| e.to_string())?;
+            tx.commit().map_err(|e| e.to_string())?;
+            stats.removed += 1;
+        }
+
+        let mut changed: Vec<(&String, &(i64, i64))> =
+            current.iter().filter(|(path, meta)| known.get(*path) != Some(*meta)).collect();
+        changed.sort();
+        for (path, (mtime, size)) in changed {
+            // Unreadable or non-UTF-8 files are recorded with no chunks so they are not retried
+            let source = std::fs::read_to_string(self.root.join(path)).unwrap_or_default();
+            let lines: Vec<&str> = source.lines().collect();
+            let chunks: Vec<Chunk> = chunk_file(Path::new(path), &source)
+                .into_iter()
+                .filter(|c| lines[c.start_line - 1..c.end_line.min(lines.len())].iter().any(|l| !l.trim().is_empty()))
+                .collect();
+            let embeddings = if chunks.is_empty() {
+                Vec::new()
+            } else {
+                embed(chunks.iter().map(|c| embed_text(path, c, &lines)).collect())?
+            };
+            if embeddings.len() != chunks.len() {
+                return Err(format!("Embedding model returned {} vectors for {} chunks", embeddings.len(), chunks.len()));
+            }
+
+            let tx = self.conn.transaction().map_err(|e| e.to_string())?;
+            tx.execute("DELETE FROM chunks WHERE path = ?1", params![path]).map_err(|e| e.to_string())?;
+            for (chunk, embedding) in chunks.iter().zip(&embeddings) {
+                tx.execute(
+                    "INSERT INTO chunks (path, start_line, end_line, label, embedding) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![path, chunk.start_line as i64, chunk.end_line as i64, chunk.label, to_blob(embedding)],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+            tx.execute(
+                "INSERT OR REPLACE INTO files (path, mtime, size) VALUES (?1, ?2, ?3)",
+                params![path, mtime, size],
+            )
+            .map_err(|e| e.to_string())?;
+            tx.commit().map_err(|e| e.to_string())?;
+            stats.indexed += 1;
+        }
+
+        stats.files = current.len();
+        stats.chunks = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM chunks", [], |row| row.get::<_, i64>(0))
+            .map_err(|e| e.to_string())? as usize;
+        Ok(stats)
+    }
+
+    /// Chunks closest to `query`, best first, optionally under a path prefix
+    pub fn search(&self, query: &[f32], limit: usize, prefix: Option<&str>) -> Result<Vec<Hit>, String> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path, start_line, end_line, label, embedding FROM chunks WHERE path LIKE ?1 ESCAPE '\\'")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![like_prefix(prefix.unwrap_or_default())], |row| {
+                let embedding: Vec<u8> = row.get(4)?;
+                Ok(Hit {
+                    path: row.get(0)?,
+                    start_line: row.get::<_, i64>(1)? as usize,
+                    end_line: row.get::<_, i64>(2)? as usize,
+                    label: row.get(3)?,
+                    score: cosine_similarity(query, &from_blob(&embedding)),
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        let mut hits: Vec<Hit> = rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?;
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(limit);
+        Ok(hits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bag-of-words embedding: enough to tell files apart by vocabulary
+    fn word_embedder() -> EmbedFn {
+        Arc::new(|texts: Vec<String>| {
+            Ok(texts
+                .iter()
+                .map(|text| {
+                    let mut v = vec![0.0f32; 64];
+                    for word in text.split(|c: char| !c.is_alphanumeric()).filter(|w| w.len() > 2) {
+                        let hash = word.to_lowercase().bytes().fold(7u32, |h, b| h.wrapping_mul(31).wrapping_add(b as u32));
+                        v[(hash % 64) as usize] += 1.0;
+                    }
+                    v
+                })
+                .collect())
+        })
+    }
+
+    #[test]
+    fn test_chunk_file() {
+        let rust = "struct Client;\n\nimpl Client {\n    fn get(&self) {}\n\n    fn retry(&self) {}\n}\n";
+        let chunks = chunk_file(Path::new("client.rs"), rust);
+        let labels: Vec<_> = chunks.iter().map(|c| (c.label.as_str(), c.start_line, c.end_line)).collect();
+        assert_eq!(labels, [("struct Client", 1, 1), ("method Client::get", 4, 4), ("method Client::retry", 6, 6)]);
+
+        let notes = (1..=120).map(|i| format!("line {}", i)).collect::<Vec<_>>().join("\n");
+        let windows: Vec<_> = chunk_file(Path::new("NOTES.md"), &notes).iter().map(|c| (c.start_line, c.end_line)).collect();
+        assert_eq!(windows, [(1, 60), (51, 110), (101, 120)]);
+    }
+
+    #[test]
+    fn test_refresh_and_search() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("retry.rs"), "/// Retry a request with exponential backoff\nfn retry_with_backoff(attempts: u32) {\n    sleep(backoff(attempts));\n}\n").unwrap();
+        std::fs::write(root.join("parse.py"), "def parse_config(text):\n    return toml.loads(text)\n").unwrap();
+        std::fs::write(root.join(".env"), "TOKEN=secret\n").unwrap();
+        let embed = word_embedder();
+        let guard = SecretGuard::new();
+
+        let mut index = CodeIndex::open(root).unwrap();
+        let stats = index.refresh(&embed, &guard).unwrap();
+        assert_eq!((stats.indexed, stats.files, stats.chunks), (2, 2, 2));
+        assert!(root.join(".mylm/.gitignore").exists());
+
+        let query = &embed(vec!["where is the retry backoff logic".to_string()]).unwrap()[0];
+        let hits = index.search(query, 5, None).unwrap();
+        assert_eq!(hits[0].path, "retry.rs");
+        assert_eq!((hits[0].start_line, hits[0].end_line, hits[0].label.as_str()), (2, 4, "fn retry_with_backoff"));
+        assert!(index.search(query, 5, Some("parse")).unwrap().iter().all(|h| h.path == "parse.py"));
+        // Wildcards in the prefix are taken literally
+        assert!(index.search(query, 5, Some("%")).unwrap().is_empty());
+        assert!(index.search(query, 5, Some("pars_")).unwrap().is_empty());
+
+        // Unchanged files are skipped; deleted ones are dropped
+        assert_eq!(index.refresh(&embed, &guard).unwrap().indexed, 0);
+        std::fs::remove_file(root.join("parse.py")).unwrap();
+        let stats = CodeIndex::open(root).unwrap().refresh(&embed, &guard).unwrap();
+        assert_eq!((stats.indexed, stats.removed, stats.chunks), (0, 1, 1));
+    }
+}
@@ -0,0 +1,180 @@
+//! Semantic code search tool
+//!
+//! Answers "where is the retry logic?" style questions that literal search
+//! cannot, by ranking chunks of the repository against the query with the
+//! same local embedding model the memory store uses. The index lives in
+//! `.mylm/code_index.db` and is refreshed before every search, so edited
+//! files are re-embedded and deleted ones dropped; only the first search in
+//! a repository pays for a full build.
+
+pub mod index;
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::agent::runtime::core::{Capability, RuntimeContext, ToolCapability, ToolError};
+use crate::agent::runtime::governance::SecretGuard;
+use crate::agent::tools::parse_args;
+use crate::agent::types::events::ToolResult;
+use crate::agent::types::intents::ToolCall;
+
+pub use index::{CodeIndex, EmbedFn, Hit, RefreshStats};
+
+/// Default and largest number of hits returned
+const DEFAULT_LIMIT: usize = 8;
+const MAX_LIMIT: usize = 30;
+/// Source lines shown per hit
+const PREVIEW_LINES: usize = 12;
+
+#[derive(Debug, Deserialize)]
+struct SemanticSearchArgs {
+    query: String,
+    #[serde(default)]
+    limit: Option<usize>,
+    /// Only search under this path (relative to the repository root)
+    #[serde(default)]
+    path: Option<String>,
+}
+
+/// Local fastembed model (BGE-Small, 384 dims), loaded on first use
+pub fn fastembed_embedder() -> EmbedFn {
+    let model: Arc<OnceLock<Result<parking_lot::Mutex<fastembed::TextEmbedding>, String>>> = Arc::new(OnceLock::new());
+    Arc::new(move |texts: Vec<String>| {
+        let model = model.get_or_init(|| {
+            let cache_dir = dirs::cache_dir()
+                .ok_or("Could not find cache directory")?
+                .join("mylm")
+                .join("models");
+            std::fs::create_dir_all(&cache_dir).map_err(|e| e.to_string())?;
+            fastembed::TextEmbedding::try_new(
+                fastembed::InitOptions::new(fastembed::EmbeddingModel::BGESmallENV15).with_cache_dir(cache_dir),
+            )
+            .map(parking_lot::Mutex::new)
+            .map_err(|e| format!("Failed to load embedding model: {}", e))
+        });
+        let model = model.as_ref().map_err(Clone::clone)?;
+        model.lock().embed(texts, None).map_err(|e| format!("Embedding failed: {}", e))
+    })
+}
+
+/// Tool for semantic search over the current repository
+pub struct CodeIndexTool {
+    root: PathBuf,
+    embed: EmbedFn,
+    secret_guard: Arc<SecretGuard>,
+    /// One refresh at a time
+    lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl CodeIndexTool {
+    /// Index the repository at `root` with the local embedding model
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            embed: fastembed_embedder(),
+            secret_guard: Arc::new(SecretGuard::new()),
+            lock: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    /// Use a different embedding function
+    pub fn with_embedder(mut self, embed: EmbedFn) -> Self {
+        self.embed = embed;
+        self
+    }
+
+    /// Leave files the guard denies out of the index
+    pub fn with_secret_guard(mut self, guard: Arc<SecretGuard>) -> Self {
+        self.secret_guard = guard;
+        self
+    }
+
+    /// Refresh the index and rank it against `query`
+    async fn search(&self, query: String, limit: usize, prefix: Option<String>) -> Result<(RefreshStats, Vec<Hit>), String> {
+        let _guard = self.lock.lock().await;
+        let root = self.root.clone();
+        let embed = Arc::clone(&self.embed);
+        let secret_guard = Arc::clone(&self.secret_guard);
+        tokio::task::spawn_blocking(move || {
+            let mut index = CodeIndex::open(&root)?;
+            let stats = index.refresh(&embed, &secret_guard)?;
+            let query = embed(vec![query])?.pop().ok_or("No embedding generated")?;
+            let hits = index.search(&query, limit, prefix.as_deref())?;
+            Ok((stats, hits))
+        })
+        .await
+        .map_err(|e| format!("Index task failed: {}", e))?
+    }
+}
+
+impl Capability for CodeIndexTool {
+    fn name(&self) -> &'static str {
+        "code_search_semantic"
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolCapability for CodeIndexTool {
+    fn description(&self) -> &'static str {
+        "Search the repository by meaning rather than exact text (e.g. 'where is retry logic implemented'). Returns the best-matching functions and types with a preview. Use code_search for exact names"
+    }
+
+    fn usage(&self) -> &'static str {
+        r#"{"a": "code_search_semantic", "i": {"query": "retry with exponential backoff"}} | Under a directory: {"a": "code_search_semantic", "i": {"query": "config file parsing", "path": "src/config", "limit": 5}}"#
+    }
+
+    async fn execute(&self, _ctx: &RuntimeContext, call: ToolCall) -> Result<ToolResult, ToolError> {
+        let args: SemanticSearchArgs = parse_args(&call.arguments)?;
+        if args.query.trim().is_empty() {
+            return Err(ToolError::new("'query' must not be empty"));
+        }
+        let limit = args.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let prefix = args
+            .path
+            .map(|p| p.trim_start_matches("./").trim_end_matches('/').to_string())
+            .filter(|p| !p.is_empty() && p != ".");
+
+        let (stats, hits) = match self.search(args.query.clone(), limit, prefix).await {
+            Ok(found) => found,
+            Err(message) => {
+                return Ok(ToolResult::Error {
+                    message,
+                    code: Some("INDEX_ERROR".to_string()),
+                    retryable: false,
+                })
+            }
+        };
+
+        let mut output = String::new();
+        if stats.indexed > 0 || stats.removed > 0 {
+            output.push_str(&format!(
+                "Index updated: {} file(s) embedded, {} removed ({} chunks in {} files)\n\n",
+                stats.indexed, stats.removed, stats.chunks, stats.files
+            ));
+        }
+        if hits.is_empty() {
+            output.push_str(&format!("No indexed code matches '{}'", args.query));
+        }
+        for hit in &hits {
+            output.push_str(&format!(
+                "{}:{}-{}  {}  (score {:.2})\n",
+                hit.path, hit.start_line, hit.end_line, hit.label, hit.score
+            ));
+            let source = std::fs::read_to_string(self.root.join(&hit.path)).unwrap_or_default();
+            for (i, line) in source.lines().enumerate().skip(hit.start_line - 1).take(PREVIEW_LINES.min(hit.end_line + 1 - hit.start_line)) {
+                output.push_str(&format!("{:>5} {}\n", i + 1, line));
+            }
+            if hit.end_line + 1 - hit.start_line > PREVIEW_LINES {
+                output.push_str("      ...\n");
+            }
+            output.push('\n');
+        }
+        Ok(ToolResult::Success {
+            output: output.trim_end().to_string(),
+            structured: Some(json!({ "hits": hits })),
+        })
+    }
+}
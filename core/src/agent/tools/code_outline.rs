@@ -0,0 +1,481 @@
+//! Code outline tool - structure and symbol extraction with tree-sitter
+//!
+//! Lists the functions, types and impls of a source file with their line
+//! ranges, or returns the source of one named symbol, so the agent can read
+//! the 200 lines it needs instead of a whole 5000-line file. Supports Rust,
+//! Python, JavaScript, TypeScript and Go.
+
+use crate::agent::runtime::core::{Capability, RuntimeContext, ToolCapability, ToolError};
+use crate::agent::runtime::governance::SecretGuard;
+use crate::agent::tools::{expand_tilde, parse_args};
+use crate::agent::types::events::ToolResult;
+use crate::agent::types::intents::ToolCall;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use tree_sitter::{Language, Node, Parser};
+
+/// Files larger than this are not parsed
+const MAX_FILE_BYTES: u64 = 4 * 1024 * 1024;
+/// Outline entries returned
+const MAX_OUTLINE_ENTRIES: usize = 400;
+/// Symbols returned for one name, and lines shown per symbol
+const MAX_SYMBOL_MATCHES: usize = 5;
+const MAX_SYMBOL_LINES: usize = 600;
+
+/// One symbol in a file
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct OutlineEntry {
+    /// e.g. "fn", "struct", "impl", "class", "method"
+    pub kind: &'static str,
+    pub name: String,
+    /// Enclosing impl/class/module, if any
+    pub parent: Option<String>,
+    /// 1-based, inclusive
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Nesting depth (0 = top level)
+    pub depth: usize,
+}
+
+impl OutlineEntry {
+    pub fn qualified_name(&self) -> String {
+        match &self.parent {
+            Some(parent) => format!("{}::{}", parent, self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// An entry with the byte span of its source, leading comments included
+type Spanned = (OutlineEntry, (usize, usize));
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Lang {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Tsx,
+    Go,
+}
+
+impl Lang {
+    fn from_path(path: &Path) -> Option<Self> {
+        Some(match path.extension()?.to_str()? {
+            "rs" => Lang::Rust,
+            "py" | "pyi" => Lang::Python,
+            "js" | "jsx" | "mjs" | "cjs" => Lang::JavaScript,
+            "ts" | "mts" | "cts" => Lang::TypeScript,
+            "tsx" => Lang::Tsx,
+            "go" => Lang::Go,
+            _ => return None,
+        })
+    }
+
+    fn language(self) -> Language {
+        match self {
+            Lang::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Lang::Python => tree_sitter_python::LANGUAGE.into(),
+            Lang::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+            Lang::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Lang::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            Lang::Go => tree_sitter_go::LANGUAGE.into(),
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Lang::Rust => "rust",
+            Lang::Python => "python",
+            Lang::JavaScript => "javascript",
+            Lang::TypeScript | Lang::Tsx => "typescript",
+            Lang::Go => "go",
+        }
+    }
+
+    /// Outline kind for a node, if it is a symbol
+    fn kind(self, node: &Node) -> Option<&'static str> {
+        Some(match (self, node.kind()) {
+            (Lang::Rust, "function_item" | "function_signature_item") => "fn",
+            (Lang::Rust, "struct_item") => "struct",
+            (Lang::Rust, "enum_item") => "enum",
+            (Lang::Rust, "union_item") => "union",
+            (Lang::Rust, "trait_item") => "trait",
+            (Lang::Rust, "impl_item") => "impl",
+            (Lang::Rust, "mod_item") => "mod",
+            (Lang::Rust, "const_item") => "const",
+            (Lang::Rust, "static_item") => "static",
+            (Lang::Rust, "type_item") => "type",
+            (Lang::Rust, "macro_definition") => "macro",
+            (Lang::Python, "function_definition") => "def",
+            (Lang::Python, "class_definition") => "class",
+            (Lang::JavaScript | Lang::TypeScript | Lang::Tsx, kind) => match kind {
+                "function_declaration" | "generator_function_declaration" => "function",
+                "class_declaration" | "abstract_class_declaration" => "class",
+                "method_definition" | "method_signature" | "abstract_method_signature" => "method",
+                "interface_declaration" => "interface",
+                "type_alias_declaration" => "type",
+                "enum_declaration" => "enum",
+                "variable_declarator"
+                    if node.child_by_field_name("value").is_some_and(|v| {
+                        matches!(v.kind(), "arrow_function" | "function_expression" | "function")
+                    }) =>
+                {
+                    "function"
+                }
+                _ => return None,
+            },
+            (Lang::Go, "function_declaration" | "method_declaration") => "func",
+            (Lang::Go, "type_spec") => match node.child_by_field_name("type").map(|t| t.kind()) {
+                Some("struct_type") => "struct",
+                Some("interface_type") => "interface",
+                _ => "type",
+            },
+            _ => return None,
+        })
+    }
+}
+
+/// Kinds whose members are listed one level deeper
+fn is_container(kind: &str) -> bool {
+    matches!(kind, "impl" | "trait" | "mod" | "class" | "interface")
+}
+
+fn node_text<'a>(node: Node, source: &'a str) -> &'a str {
+    node.utf8_text(source.as_bytes()).unwrap_or_default()
+}
+
+/// Display name of a symbol node
+fn symbol_name(lang: Lang, node: Node, source: &str) -> String {
+    if lang == Lang::Rust && node.kind() == "impl_item" {
+        let ty = node.child_by_field_name("type").map(|t| node_text(t, source)).unwrap_or("?");
+        return match node.child_by_field_name("trait") {
+            Some(t) => format!("{} for {}", node_text(t, source), ty),
+            None => ty.to_string(),
+        };
+    }
+    if lang == Lang::Go && node.kind() == "method_declaration" {
+        let name = node.child_by_field_name("name").map(|n| node_text(n, source)).unwrap_or("?");
+        let receiver = node
+            .child_by_field_name("receiver")
+            .map(|r| node_text(r, source).trim_matches(|c| c == '(' || c == ')'))
+            .and_then(|r| r.split_whitespace().last())
+            .map(|ty| ty.trim_start_matches('*').to_string())
+            .unwrap_or_default();
+        return if receiver.is_empty() { name.to_string() } else { format!("{}.{}", receiver, name) };
+    }
+    node.child_by_field_name("name")
+        .map(|n| node_text(n, source).to_string())
+        .unwrap_or_else(|| "?".to_string())
+}
+
+/// Name used as the parent of a container's members (the type for impls)
+fn parent_name(lang: Lang, node: Node, source: &str, name: &str) -> String {
+    if lang == Lang::Rust && node.kind() == "impl_item" {
+        if let Some(ty) = node.child_by_field_name("type") {
+            return node_text(ty, source).to_string();
+        }
+    }
+    name.to_string()
+}
+
+fn walk(lang: Lang, node: Node, source: &str, parent: Option<&str>, depth: usize, out: &mut Vec<Spanned>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        match lang.kind(&child) {
+            Some(kind) => {
+                // A declarator spans only `name = ...`; show the whole statement
+                let span = if child.kind() == "variable_declarator" {
+                    child.parent().unwrap_or(child)
+                } else {
+                    child
+                };
+                let name = symbol_name(lang, child, source);
+                let entry = OutlineEntry {
+                    kind: if parent.is_some() && matches!(kind, "fn" | "def" | "function") { "method" } else { kind },
+                    name: name.clone(),
+                    parent: parent.map(str::to_string),
+                    start_line: span.start_position().row + 1,
+                    end_line: span.end_position().row + 1,
+                    depth,
+                };
+                out.push((entry, (leading_start(span), span.end_byte())));
+                if is_container(kind) {
+                    let body = child.child_by_field_name("body").unwrap_or(child);
+                    let parent = parent_name(lang, child, source, &name);
+                    walk(lang, body, source, Some(&parent), depth + 1, out);
+                }
+            }
+            // Look through wrappers: export statements, decorators, `type (...)` blocks
+            None => walk(lang, child, source, parent, depth, out),
+        }
+    }
+}
+
+/// Start byte including doc comments and attributes directly above `node`
+fn leading_start(node: Node) -> usize {
+    let mut start = node;
+    while let Some(previous) = start.prev_sibling() {
+        let kind = previous.kind();
+        let attached = previous.end_position().row + 1 >= start.start_position().row;
+        if attached && (kind.contains("comment") || kind == "attribute_item" || kind == "decorator") {
+            start = previous;
+        } else {
+            break;
+        }
+    }
+    // Python decorators live in a wrapping decorated_definition
+    match node.parent() {
+        Some(parent) if parent.kind() == "decorated_definition" => parent.start_byte().min(start.start_byte()),
+        _ => start.start_byte(),
+    }
+}
+
+/// Parse `source` and list its symbols with their byte spans
+fn outline(lang: Lang, source: &str) -> Result<Vec<Spanned>, String> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&lang.language())
+        .map_err(|e| format!("Failed to load {} grammar: {}", lang.label(), e))?;
+    let tree = parser.parse(source, None).ok_or("Parsing failed")?;
+    let mut entries = Vec::new();
+    walk(lang, tree.root_node(), source, None, 0, &mut entries);
+    Ok(entries)
+}
+
+/// Symbols of a source file, or None when its language is not supported
+pub(crate) fn symbols(path: &Path, source: &str) -> Option<Vec<OutlineEntry>> {
+    let entries = outline(Lang::from_path(path)?, source).ok()?;
+    Some(entries.into_iter().map(|(entry, _)| entry).collect())
+}
+
+/// Whether `query` names `entry`: the bare name, `Parent::name` or `Parent.name`
+fn matches_symbol(entry: &OutlineEntry, query: &str) -> bool {
+    let query = query.replace('.', "::");
+    entry.name == query || entry.qualified_name() == query || entry.qualified_name().replace('.', "::") == query
+}
+
+#[derive(Debug, Deserialize)]
+struct OutlineArgs {
+    path: String,
+    /// Return the source of this symbol instead of the outline
+    #[serde(default)]
+    symbol: Option<String>,
+}
+
+/// Tool for listing and extracting symbols
+#[derive(Debug)]
+pub struct CodeOutlineTool {
+    secret_guard: Arc<SecretGuard>,
+}
+
+impl CodeOutlineTool {
+    pub fn new() -> Self {
+        Self {
+            secret_guard: Arc::new(SecretGuard::new()),
+        }
+    }
+
+    /// Refuse files the guard denies
+    pub fn with_secret_guard(mut self, guard: Arc<SecretGuard>) -> Self {
+        self.secret_guard = guard;
+        self
+    }
+}
+
+impl Default for CodeOutlineTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Capability for CodeOutlineTool {
+    fn name(&self) -> &'static str {
+        "code_outline"
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolCapability for CodeOutlineTool {
+    fn description(&self) -> &'static str {
+        "Outline a source file (functions, types, impls with line ranges) or return one symbol's source. Read the outline first, then only the symbols you need, instead of whole large files. Rust, Python, JS/TS, Go"
+    }
+
+    fn usage(&self) -> &'static str {
+        r#"{"a": "code_outline", "i": {"path": "src/parser.rs"}} | One symbol: {"a": "code_outline", "i": {"path": "src/parser.rs", "symbol": "Parser::advance"}}"#
+    }
+
+    async fn execute(&self, _ctx: &RuntimeContext, call: ToolCall) -> Result<ToolResult, ToolError> {
+        let args: OutlineArgs = parse_args(&call.arguments)?;
+        let path = std::path::PathBuf::from(expand_tilde(&args.path));
+        let error = |message: String, code: &str| ToolResult::Error {
+            message,
+            code: Some(code.to_string()),
+            retryable: false,
+        };
+
+        let Some(lang) = Lang::from_path(&path) else {
+            return Ok(error(
+                format!("No outline support for '{}'; supported: .rs, .py, .js, .jsx, .ts, .tsx, .go", args.path),
+                "UNSUPPORTED_LANGUAGE",
+            ));
+        };
+        if let Err(denied) = self.secret_guard.check("code_outline", &path) {
+            return Ok(error(denied.to_string(), "SECRET_FILE"));
+        }
+        match std::fs::metadata(&path) {
+            Ok(meta) if meta.len() > MAX_FILE_BYTES => {
+                return Ok(error(format!("'{}' is too large to outline", args.path), "FILE_TOO_LARGE"))
+            }
+            Ok(_) => {}
+            Err(e) => return Ok(error(format!("Cannot read '{}': {}", args.path, e), "NOT_FOUND")),
+        }
+        let source = tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|e| ToolError::new(format!("Failed to read {}: {}", args.path, e)))?;
+
+        let parsed = tokio::task::spawn_blocking({
+            let source = source.clone();
+            move || outline(lang, &source)
+        })
+        .await
+        .map_err(|e| ToolError::new(format!("Outline task failed: {}", e)))?;
+        let entries = match parsed {
+            Ok(entries) => entries,
+            Err(e) => return Ok(error(e, "PARSE_ERROR")),
+        };
+
+        let Some(symbol) = args.symbol else {
+            let total_lines = source.lines().count();
+            let mut output = format!("{} ({}, {} lines)\n", args.path, lang.label(), total_lines);
+            for (entry, _) in entries.iter().take(MAX_OUTLINE_ENTRIES) {
+                output.push_str(&format!(
+                    "{}{:>5}-{:<5} {} {}\n",
+                    "  ".repeat(entry.depth + 1),
+                    entry.start_line,
+                    entry.end_line,
+                    entry.kind,
+                    entry.name
+                ));
+            }
+            if entries.len() > MAX_OUTLINE_ENTRIES {
+                output.push_str(&format!("  ... and {} more\n", entries.len() - MAX_OUTLINE_ENTRIES));
+            }
+            if entries.is_empty() {
+                output.push_str("  (no symbols found)\n");
+            }
+            let list: Vec<&OutlineEntry> = entries.iter().take(MAX_OUTLINE_ENTRIES).map(|(e, _)| e).collect();
+            return Ok(ToolResult::Success {
+                output,
+                structured: Some(serde_json::json!({ "path": args.path, "language": lang.label(), "symbols": list })),
+            });
+        };
+
+        let found: Vec<&Spanned> =
+            entries.iter().filter(|(entry, _)| matches_symbol(entry, &symbol)).collect();
+        if found.is_empty() {
+            let names: Vec<String> = entries.iter().take(40).map(|(e, _)| e.qualified_name()).collect();
+            return Ok(error(
+                format!("No symbol '{}' in {}. Symbols include: {}", symbol, args.path, names.join(", ")),
+                "SYMBOL_NOT_FOUND",
+            ));
+        }
+        let mut output = String::new();
+        for (entry, (start, end)) in found.iter().take(MAX_SYMBOL_MATCHES) {
+            let first_line = source[..*start].matches('\n').count() + 1;
+            let text = &source[*start..*end];
+            let lines: Vec<&str> = text.lines().collect();
+            output.push_str(&format!(
+                "{}:{}-{} {} {}\n",
+                args.path,
+                first_line,
+                entry.end_line,
+                entry.kind,
+                entry.qualified_name()
+            ));
+            for (i, line) in lines.iter().take(MAX_SYMBOL_LINES).enumerate() {
+                output.push_str(&format!("{:>5} {}\n", first_line + i, line));
+            }
+            if lines.len() > MAX_SYMBOL_LINES {
+                output.push_str(&format!("... {} more lines; outline the symbol to find a smaller part\n", lines.len() - MAX_SYMBOL_LINES));
+            }
+            output.push('\n');
+        }
+        if found.len() > MAX_SYMBOL_MATCHES {
+            output.push_str(&format!("{} more matches; qualify the name (e.g. Type::method)\n", found.len() - MAX_SYMBOL_MATCHES));
+        }
+        Ok(ToolResult::Success {
+            output: output.trim_end().to_string(),
+            structured: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUST: &str = "use std::fmt;\n\n/// A parser\n#[derive(Debug)]\npub struct Parser {\n    pos: usize,\n}\n\nimpl Parser {\n    pub fn new() -> Self {\n        Self { pos: 0 }\n    }\n\n    fn advance(&mut self) {\n        self.pos += 1;\n    }\n}\n\nimpl fmt::Display for Parser {\n    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {\n        write!(f, \"{}\", self.pos)\n    }\n}\n";
+
+    #[test]
+    fn test_rust_outline() {
+        let entries = outline(Lang::Rust, RUST).unwrap();
+        let summary: Vec<String> = entries
+            .iter()
+            .map(|(e, _)| format!("{} {} {} {}-{}", e.depth, e.kind, e.qualified_name(), e.start_line, e.end_line))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                "0 struct Parser 5-7",
+                "0 impl Parser 9-17",
+                "1 method Parser::new 10-12",
+                "1 method Parser::advance 14-16",
+                "0 impl fmt::Display for Parser 19-23",
+                "1 method Parser::fmt 20-22",
+            ]
+        );
+        // Doc comments and attributes come with the symbol
+        let (_, (start, end)) = &entries[0];
+        assert!(RUST[*start..*end].starts_with("/// A parser\n#[derive(Debug)]\npub struct Parser"));
+    }
+
+    #[test]
+    fn test_python_and_typescript_outline() {
+        let py = "import os\n\n@dataclass\nclass Job:\n    def run(self):\n        pass\n\ndef main():\n    Job().run()\n";
+        let names: Vec<String> = outline(Lang::Python, py).unwrap().iter().map(|(e, _)| format!("{} {}", e.kind, e.qualified_name())).collect();
+        assert_eq!(names, ["class Job", "method Job::run", "def main"]);
+
+        let ts = "export interface Opts { a: number }\nexport class Api {\n  get(id: string) { return id; }\n}\nexport const handler = async (e: Event) => {};\n";
+        let names: Vec<String> = outline(Lang::TypeScript, ts).unwrap().iter().map(|(e, _)| format!("{} {}", e.kind, e.qualified_name())).collect();
+        assert_eq!(names, ["interface Opts", "class Api", "method Api::get", "function handler"]);
+    }
+
+    #[tokio::test]
+    async fn test_extract_symbol() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("parser.rs");
+        std::fs::write(&file, RUST).unwrap();
+        let call = |symbol: &str| ToolCall {
+            name: "code_outline".to_string(),
+            arguments: serde_json::json!({ "path": file.to_string_lossy(), "symbol": symbol }),
+            working_dir: None,
+            timeout_secs: None,
+        };
+        let tool = CodeOutlineTool::new();
+
+        match tool.execute(&RuntimeContext::new(), call("Parser::advance")).await.unwrap() {
+            ToolResult::Success { output, .. } => {
+                assert!(output.contains("parser.rs:14-16 method Parser::advance"));
+                assert!(output.contains("   15         self.pos += 1;"));
+                assert!(!output.contains("fn new"));
+            }
+            other => panic!("Expected success, got {:?}", other),
+        }
+        match tool.execute(&RuntimeContext::new(), call("missing")).await.unwrap() {
+            ToolResult::Error { code, .. } => assert_eq!(code.as_deref(), Some("SYMBOL_NOT_FOUND")),
+            other => panic!("Expected error, got {:?}", other),
+        }
+    }
+}
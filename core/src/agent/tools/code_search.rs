@@ -0,0 +1,364 @@
+//! Code search tool - regex/literal search over the workspace
+//!
+//! Walks the tree the way ripgrep does (respecting .gitignore, skipping hidden
+//! and binary files) and returns each match with its line number and context,
+//! so the agent doesn't need `grep -rn` through the shell.
+
+use crate::agent::runtime::core::{Capability, RuntimeContext, ToolCapability, ToolError};
+use crate::agent::runtime::governance::SecretGuard;
+use crate::agent::tools::{expand_tilde, parse_args};
+use crate::agent::types::events::ToolResult;
+use crate::agent::types::intents::ToolCall;
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Default and largest number of matches returned
+const DEFAULT_MAX_RESULTS: usize = 50;
+const MAX_RESULTS_LIMIT: usize = 500;
+/// Default and largest number of context lines around a match
+const DEFAULT_CONTEXT: usize = 2;
+const MAX_CONTEXT: usize = 10;
+/// Files larger than this are skipped
+const MAX_FILE_BYTES: u64 = 2 * 1024 * 1024;
+/// Matched lines longer than this are cut
+const MAX_LINE_CHARS: usize = 300;
+
+/// One matching line
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CodeMatch {
+    /// Path relative to the search root
+    pub path: String,
+    /// Line number (1-based)
+    pub line: usize,
+    pub text: String,
+    /// Lines before the match, oldest first
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+/// Result of one search
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CodeSearchResult {
+    pub matches: Vec<CodeMatch>,
+    pub files_searched: usize,
+    /// More matches exist than were returned
+    pub truncated: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchArgs {
+    pattern: String,
+    #[serde(default)]
+    path: Option<String>,
+    /// Treat the pattern as plain text instead of a regex
+    #[serde(default)]
+    literal: bool,
+    #[serde(default)]
+    ignore_case: bool,
+    /// Only search files matching this glob (e.g. "*.rs")
+    #[serde(default)]
+    glob: Option<String>,
+    #[serde(default)]
+    context: Option<usize>,
+    #[serde(default)]
+    max_results: Option<usize>,
+}
+
+/// Tool for searching file contents by pattern
+#[derive(Debug)]
+pub struct CodeSearchTool {
+    secret_guard: Arc<SecretGuard>,
+}
+
+impl CodeSearchTool {
+    pub fn new() -> Self {
+        Self {
+            secret_guard: Arc::new(SecretGuard::new()),
+        }
+    }
+
+    /// Skip files the secret guard denies instead of showing their contents
+    pub fn with_secret_guard(mut self, guard: Arc<SecretGuard>) -> Self {
+        self.secret_guard = guard;
+        self
+    }
+
+    /// Search every non-ignored file under `root`
+    pub fn search(
+        &self,
+        root: &Path,
+        regex: &Regex,
+        glob: Option<&str>,
+        context: usize,
+        max_results: usize,
+    ) -> Result<CodeSearchResult, ToolError> {
+        let mut walker = WalkBuilder::new(root);
+        // Honour .gitignore even outside a git checkout
+        walker.require_git(false);
+        if let Some(glob) = glob {
+            let mut overrides = OverrideBuilder::new(root);
+            overrides
+                .add(glob)
+                .map_err(|e| ToolError::new(format!("Invalid glob '{}': {}", glob, e)))?;
+            let overrides = overrides
+                .build()
+                .map_err(|e| ToolError::new(format!("Invalid glob '{}': {}", glob, e)))?;
+            walker.overrides(overrides);
+        }
+
+        let mut result = CodeSearchResult::default();
+        let mut paths: Vec<PathBuf> = walker
+            .build()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+            .map(|entry| entry.into_path())
+            .collect();
+        // Stable output regardless of directory order
+        paths.sort();
+
+        for path in paths {
+            if self.secret_guard.matched_pattern(&path).is_some() {
+                continue;
+            }
+            if std::fs::metadata(&path).map(|m| m.len() > MAX_FILE_BYTES).unwrap_or(true) {
+                continue;
+            }
+            let Ok(bytes) = std::fs::read(&path) else {
+                continue;
+            };
+            if bytes.iter().take(8192).any(|b| *b == 0) {
+                continue;
+            }
+            result.files_searched += 1;
+
+            let content = String::from_utf8_lossy(&bytes);
+            let lines: Vec<&str> = content.lines().collect();
+            let display = path.strip_prefix(root).unwrap_or(&path).to_string_lossy().to_string();
+            for (i, line) in lines.iter().enumerate() {
+                if !regex.is_match(line) {
+                    continue;
+                }
+                if result.matches.len() == max_results {
+                    result.truncated = true;
+                    return Ok(result);
+                }
+                let start = i.saturating_sub(context);
+                let end = (i + 1 + context).min(lines.len());
+                result.matches.push(CodeMatch {
+                    path: display.clone(),
+                    line: i + 1,
+                    text: clip(line),
+                    before: lines[start..i].iter().map(|l| clip(l)).collect(),
+                    after: lines[i + 1..end].iter().map(|l| clip(l)).collect(),
+                });
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl Default for CodeSearchTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn clip(line: &str) -> String {
+    if line.chars().count() <= MAX_LINE_CHARS {
+        line.to_string()
+    } else {
+        let cut: String = line.chars().take(MAX_LINE_CHARS).collect();
+        format!("{}…", cut)
+    }
+}
+
+/// grep-style text: `path:line: text` for matches, `path-line- text` for context
+fn format_matches(result: &CodeSearchResult) -> String {
+    let mut output = String::new();
+    for (i, m) in result.matches.iter().enumerate() {
+        if i > 0 && !(m.before.is_empty() && m.after.is_empty()) {
+            output.push_str("--\n");
+        }
+        let first = m.line - m.before.len();
+        for (offset, line) in m.before.iter().enumerate() {
+            output.push_str(&format!("{}-{}- {}\n", m.path, first + offset, line));
+        }
+        output.push_str(&format!("{}:{}: {}\n", m.path, m.line, m.text));
+        for (offset, line) in m.after.iter().enumerate() {
+            output.push_str(&format!("{}-{}- {}\n", m.path, m.line + 1 + offset, line));
+        }
+    }
+    output
+}
+
+impl Capability for CodeSearchTool {
+    fn name(&self) -> &'static str {
+        "code_search"
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolCapability for CodeSearchTool {
+    fn description(&self) -> &'static str {
+        "Search file contents by regex (or literal text) under a directory, respecting .gitignore. Returns file, line number and context lines. Use this instead of grep/rg through shell"
+    }
+
+    fn usage(&self) -> &'static str {
+        r#"{"a": "code_search", "i": {"pattern": "fn \\w+_handler", "path": "src"}} | Literal, Rust only: {"a": "code_search", "i": {"pattern": "unwrap()", "literal": true, "glob": "*.rs", "context": 3}}"#
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &RuntimeContext,
+        call: ToolCall,
+    ) -> Result<ToolResult, ToolError> {
+        let args: SearchArgs = if let Some(pattern) = call.arguments.as_str() {
+            SearchArgs {
+                pattern: pattern.to_string(),
+                path: None,
+                literal: false,
+                ignore_case: false,
+                glob: None,
+                context: None,
+                max_results: None,
+            }
+        } else {
+            parse_args(&call.arguments)?
+        };
+
+        let source = if args.literal { regex::escape(&args.pattern) } else { args.pattern.clone() };
+        let regex = match RegexBuilder::new(&source).case_insensitive(args.ignore_case).build() {
+            Ok(regex) => regex,
+            Err(e) => {
+                return Ok(ToolResult::Error {
+                    message: format!("Invalid regex '{}': {} (set \"literal\": true to search plain text)", args.pattern, e),
+                    code: Some("INVALID_PATTERN".to_string()),
+                    retryable: false,
+                })
+            }
+        };
+
+        let root = PathBuf::from(expand_tilde(args.path.as_deref().unwrap_or(".")));
+        if !root.exists() {
+            return Ok(ToolResult::Error {
+                message: format!("'{}' does not exist", root.display()),
+                code: Some("NOT_FOUND".to_string()),
+                retryable: false,
+            });
+        }
+        let context = args.context.unwrap_or(DEFAULT_CONTEXT).min(MAX_CONTEXT);
+        let max_results = args.max_results.unwrap_or(DEFAULT_MAX_RESULTS).clamp(1, MAX_RESULTS_LIMIT);
+
+        let guard = Arc::clone(&self.secret_guard);
+        let glob = args.glob.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            CodeSearchTool { secret_guard: guard }.search(&root, &regex, glob.as_deref(), context, max_results)
+        })
+        .await
+        .map_err(|e| ToolError::new(format!("Search task failed: {}", e)))??;
+
+        let output = if result.matches.is_empty() {
+            format!("No matches for '{}' in {} files", args.pattern, result.files_searched)
+        } else {
+            let mut output = format!(
+                "{} matches for '{}' ({} files searched){}:\n\n",
+                result.matches.len(),
+                args.pattern,
+                result.files_searched,
+                if result.truncated { ", more not shown; narrow the search" } else { "" }
+            );
+            output.push_str(&format_matches(&result));
+            output
+        };
+
+        Ok(ToolResult::Success {
+            output,
+            structured: Some(serde_json::json!({
+                "pattern": args.pattern,
+                "matches": result.matches,
+                "count": result.matches.len(),
+                "files_searched": result.files_searched,
+                "truncated": result.truncated,
+            })),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn workspace() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::create_dir_all(dir.path().join("target")).unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "target/\n").unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "use std::io;\n\nfn parse() {}\n// TODO: fix parse()\nfn main() {}\n").unwrap();
+        std::fs::write(dir.path().join("src/notes.md"), "TODO: docs\n").unwrap();
+        std::fs::write(dir.path().join("target/out.rs"), "// TODO: generated\n").unwrap();
+        std::fs::write(dir.path().join("blob.bin"), b"TODO\0\x01").unwrap();
+        dir
+    }
+
+    async fn run(args: serde_json::Value) -> ToolResult {
+        CodeSearchTool::new()
+            .execute(&RuntimeContext::new(), ToolCall::new("code_search", args))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_search_respects_gitignore_and_glob() {
+        let dir = workspace();
+        let root = dir.path().to_string_lossy().to_string();
+
+        let ToolResult::Success { structured: Some(json), .. } =
+            run(serde_json::json!({"pattern": "TODO", "path": root})).await
+        else {
+            panic!("search failed");
+        };
+        let paths: Vec<&str> = json["matches"].as_array().unwrap().iter().map(|m| m["path"].as_str().unwrap()).collect();
+        assert_eq!(paths, vec!["src/lib.rs", "src/notes.md"]);
+
+        let ToolResult::Success { structured: Some(json), output } =
+            run(serde_json::json!({"pattern": "TODO", "path": root, "glob": "*.rs", "context": 1})).await
+        else {
+            panic!("search failed");
+        };
+        assert_eq!(json["count"], 1);
+        let m: CodeMatch = serde_json::from_value(json["matches"][0].clone()).unwrap();
+        assert_eq!(m.line, 4);
+        assert_eq!(m.before, vec!["fn parse() {}"]);
+        assert_eq!(m.after, vec!["fn main() {}"]);
+        assert!(output.contains("src/lib.rs:4: // TODO: fix parse()"));
+    }
+
+    #[tokio::test]
+    async fn test_literal_and_invalid_patterns() {
+        let dir = workspace();
+        let root = dir.path().to_string_lossy().to_string();
+
+        let ToolResult::Success { structured: Some(json), .. } =
+            run(serde_json::json!({"pattern": "parse()", "path": root, "literal": true})).await
+        else {
+            panic!("search failed");
+        };
+        assert_eq!(json["count"], 2);
+
+        let result = run(serde_json::json!({"pattern": "fn (", "path": root})).await;
+        assert!(matches!(result, ToolResult::Error { code: Some(ref c), .. } if c == "INVALID_PATTERN"));
+
+        let ToolResult::Success { structured: Some(json), .. } =
+            run(serde_json::json!({"pattern": "^fn", "path": root, "max_results": 1})).await
+        else {
+            panic!("search failed");
+        };
+        assert_eq!(json["count"], 1);
+        assert_eq!(json["truncated"], true);
+    }
+}
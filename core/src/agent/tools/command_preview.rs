@@ -0,0 +1,394 @@
+//! Static preview of a shell command
+//!
+//! Used by dry runs: the command is parsed, checked against the shell tool's
+//! safety rules and scanned for the paths it would read, write or delete,
+//! without spawning anything. The analysis is a heuristic over well-known
+//! programs and redirections; scripts, `eval` and command substitution are
+//! opaque to it, and the preview says so.
+
+use serde::Serialize;
+
+/// Substrings the shell tool refuses to run
+pub const BLOCKED_PATTERNS: &[&str] = &["rm -rf /", "> /dev/sda", "dd if=/dev/zero"];
+
+/// The substring of `command` that makes the shell tool refuse it, if any
+pub fn blocked_pattern(command: &str) -> Option<&'static str> {
+    BLOCKED_PATTERNS.iter().copied().find(|pattern| command.contains(pattern))
+}
+
+/// Programs that talk to the network
+const NETWORK_PROGRAMS: &[&str] = &[
+    "curl", "wget", "ssh", "scp", "rsync", "nc", "ping", "ftp", "sftp", "telnet", "http",
+];
+/// Git subcommands that talk to the network
+const GIT_NETWORK: &[&str] = &["push", "pull", "fetch", "clone", "ls-remote"];
+/// Programs that read their path arguments
+const READERS: &[&str] = &[
+    "cat", "less", "more", "head", "tail", "wc", "diff", "file", "stat", "ls", "du", "sort", "uniq",
+    "cut", "md5sum", "sha256sum", "bat",
+];
+
+/// Programs that only read, whatever their arguments; programs that only
+/// read with some arguments are checked in `CommandPreview::segment`
+const READ_ONLY_PROGRAMS: &[&str] = &[
+    "pwd", "echo", "printf", "true", "false", "grep", "egrep", "rg", "tr", "nl", "column", "jq",
+    "find", "tree", "which", "whereis", "type", "whoami", "id", "groups", "uname", "uptime",
+    "printenv", "ps", "pgrep", "free", "df", "lsblk", "lscpu", "lsof", "ss", "netstat",
+    "dmesg", "journalctl", "readlink", "realpath", "basename", "dirname", "zcat", "zgrep", "xxd", "od",
+    "strings", "test", "[",
+];
+/// Git subcommands that only read
+const GIT_READ_ONLY: &[&str] = &[
+    "status", "log", "diff", "show", "blame", "rev-parse", "ls-files", "grep", "describe", "shortlog",
+];
+/// systemctl subcommands that only read
+const SYSTEMCTL_READ_ONLY: &[&str] = &[
+    "status", "show", "cat", "list-units", "list-unit-files", "list-timers", "is-active", "is-enabled", "is-failed",
+];
+
+/// What a command would do, as far as static analysis can tell
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct CommandPreview {
+    pub command: String,
+    /// Programs run, in order
+    pub programs: Vec<String>,
+    /// Safety rule that blocks the command, if any
+    pub blocked: Option<String>,
+    pub reads: Vec<String>,
+    pub writes: Vec<String>,
+    pub deletes: Vec<String>,
+    /// Programs that would reach the network
+    pub network: Vec<String>,
+    /// Things the analysis could not see through or that deserve a look
+    pub notes: Vec<String>,
+    /// Programs not known to only read, e.g. `systemctl restart`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub may_modify: Vec<String>,
+}
+
+impl CommandPreview {
+    pub fn analyze(command: &str) -> Self {
+        let mut preview = CommandPreview {
+            command: command.to_string(),
+            blocked: blocked_pattern(command).map(|p| format!("contains '{}'", p)),
+            ..Default::default()
+        };
+        if command.contains("$(") || command.contains('`') {
+            preview.note("Uses command substitution; its output decides some arguments");
+        }
+        for segment in split_segments(command) {
+            let words = shell_words::split(&segment)
+                .unwrap_or_else(|_| segment.split_whitespace().map(String::from).collect());
+            preview.segment(words);
+        }
+        for list in [&mut preview.reads, &mut preview.writes, &mut preview.deletes, &mut preview.network, &mut preview.may_modify] {
+            dedup(list);
+        }
+        preview
+    }
+
+    /// Whether the command only reads: no writes or deletes, no code the
+    /// analysis cannot see, and only programs known to leave things as
+    /// they are
+    pub fn is_read_only(&self) -> bool {
+        let opaque = ["$(", "`", "<(", ">("].iter().any(|s| self.command.contains(s));
+        self.blocked.is_none()
+            && !opaque
+            && self.writes.is_empty()
+            && self.deletes.is_empty()
+            && self.may_modify.is_empty()
+            && !self.programs.is_empty()
+    }
+
+    fn note(&mut self, note: &str) {
+        if !self.notes.iter().any(|n| n == note) {
+            self.notes.push(note.to_string());
+        }
+    }
+
+    /// Analyse one simple command (no `;`, `&&`, `|`)
+    fn segment(&mut self, words: Vec<String>) {
+        // Redirections first; what is left is the program and its arguments
+        let mut args = Vec::new();
+        let mut iter = words.into_iter().peekable();
+        while let Some(word) = iter.next() {
+            let trimmed = word.trim_start_matches(|c: char| c.is_ascii_digit() || c == '&');
+            let (op, target) = if let Some(rest) = trimmed.strip_prefix(">>") {
+                (">", rest)
+            } else if let Some(rest) = trimmed.strip_prefix('>') {
+                (">", rest)
+            } else if let Some(rest) = trimmed.strip_prefix('<') {
+                ("<", rest)
+            } else {
+                args.push(word);
+                continue;
+            };
+            let target = if target.is_empty() { iter.next().unwrap_or_default() } else { target.to_string() };
+            if target.starts_with('&') || target == "/dev/null" || target.is_empty() {
+                continue;
+            }
+            match op {
+                ">" => self.writes.push(target),
+                _ => self.reads.push(target),
+            }
+        }
+
+        // Skip `VAR=value` prefixes and wrappers like sudo/env/time
+        let start = args
+            .iter()
+            .position(|a| {
+                let name = a.rsplit('/').next().unwrap_or(a);
+                (!a.contains('=') || a.starts_with('-')) && !["sudo", "env", "time", "nice", "nohup"].contains(&name)
+            })
+            .unwrap_or(args.len());
+        if args[..start].iter().any(|a| a.rsplit('/').next() == Some("sudo")) {
+            self.note("Runs with sudo");
+        }
+        let Some(program) = args.get(start).cloned() else {
+            return;
+        };
+        let rest: Vec<String> = args[start + 1..].to_vec();
+        let paths: Vec<String> = rest.iter().filter(|a| !a.starts_with('-')).cloned().collect();
+        let name = program.rsplit('/').next().unwrap_or(&program).to_string();
+        self.programs.push(name.clone());
+        let subcommand = rest.iter().find(|a| !a.starts_with('-')).map(String::as_str);
+        let read_only = match name.as_str() {
+            "git" => subcommand.is_some_and(|sub| GIT_READ_ONLY.contains(&sub)),
+            "systemctl" => subcommand.is_some_and(|sub| SYSTEMCTL_READ_ONLY.contains(&sub)),
+            "find" => !rest.iter().any(|a| a.starts_with("-exec") || a.starts_with("-ok") || a == "-delete" || a.starts_with("-fprint")),
+            "sed" => !rest.iter().any(|a| a.starts_with("-i") || a == "--in-place"),
+            "journalctl" => !rest.iter().any(|a| a.starts_with("--vacuum") || a == "--rotate" || a == "--flush"),
+            "dmesg" => !rest.iter().any(|a| a == "-c" || a == "-C" || a == "--clear" || a == "--read-clear"),
+            "sort" => sort_output(&rest).is_none(),
+            "uniq" | "xxd" => paths.len() <= 1,
+            "yq" => !in_place(&rest),
+            // Scripts can write files, pipe to commands or call system()
+            "awk" | "gawk" => !rest.iter().any(|a| a.starts_with("-f") || a.contains("system") || a.contains('>') || a.contains('|')),
+            "hostname" => paths.is_empty() && !rest.iter().any(|a| ["-F", "--file", "-b", "--boot"].contains(&a.as_str())),
+            "date" => !rest.iter().any(|a| a.starts_with("-s") || a.starts_with("--set") || !a.starts_with(['-', '+'])),
+            name => READ_ONLY_PROGRAMS.contains(&name) || READERS.contains(&name),
+        };
+        if !read_only {
+            let label = match subcommand {
+                Some(sub) if ["git", "systemctl"].contains(&name.as_str()) => format!("{} {}", name, sub),
+                _ => name.clone(),
+            };
+            self.may_modify.push(label);
+        }
+
+        match name.as_str() {
+            "rm" | "rmdir" | "unlink" | "shred" => self.deletes.extend(paths),
+            "mv" => {
+                if let Some((dest, sources)) = paths.split_last() {
+                    self.deletes.extend(sources.iter().cloned());
+                    self.writes.push(dest.clone());
+                }
+            }
+            "cp" | "ln" | "install" => {
+                if let Some((dest, sources)) = paths.split_last() {
+                    self.reads.extend(sources.iter().cloned());
+                    self.writes.push(dest.clone());
+                }
+            }
+            "touch" | "mkdir" | "tee" | "truncate" => self.writes.extend(paths),
+            "chmod" | "chown" | "chgrp" => self.writes.extend(paths.into_iter().skip(1)),
+            "sed" | "perl" | "yq" if in_place(&rest) => {
+                // First non-flag argument is the script
+                self.writes.extend(paths.into_iter().skip(1));
+            }
+            "sed" | "awk" | "grep" | "rg" => self.reads.extend(paths.into_iter().skip(1)),
+            "dd" => {
+                for arg in &rest {
+                    if let Some(path) = arg.strip_prefix("if=") {
+                        self.reads.push(path.to_string());
+                    } else if let Some(path) = arg.strip_prefix("of=") {
+                        self.writes.push(path.to_string());
+                    }
+                }
+            }
+            "find" if rest.iter().any(|a| a == "-delete" || a == "-exec") => {
+                self.note("`find` with -delete/-exec acts on every match");
+                self.deletes.extend(paths.into_iter().take(1));
+            }
+            "git" => self.git(&rest),
+            "sort" if sort_output(&rest).is_some() => {
+                let output = sort_output(&rest);
+                self.reads.extend(paths.into_iter().filter(|path| Some(path) != output.as_ref()));
+                self.writes.extend(output);
+            }
+            "uniq" | "xxd" if paths.len() > 1 => {
+                self.reads.push(paths[0].clone());
+                self.writes.push(paths[1].clone());
+            }
+            "sh" | "bash" | "zsh" | "eval" | "xargs" | "python" | "python3" | "node" | "ruby" | "source" | "." => {
+                self.note(&format!("`{}` runs code this preview cannot inspect", name));
+            }
+            name if READERS.contains(&name) => self.reads.extend(paths),
+            name if NETWORK_PROGRAMS.contains(&name) => self.network.push(name.to_string()),
+            _ => {}
+        }
+        if ["curl", "wget"].contains(&name.as_str()) {
+            let output = rest
+                .iter()
+                .position(|a| a == "-o" || a == "-O" || a == "--output")
+                .and_then(|i| rest.get(i + 1));
+            if let Some(path) = output {
+                self.writes.push(path.clone());
+            }
+        }
+    }
+
+    fn git(&mut self, args: &[String]) {
+        let Some(sub) = args.iter().find(|a| !a.starts_with('-')) else {
+            return;
+        };
+        if GIT_NETWORK.contains(&sub.as_str()) {
+            self.network.push(format!("git {}", sub));
+        }
+        match sub.as_str() {
+            "clean" => self.note("`git clean` deletes untracked files"),
+            "reset" if args.iter().any(|a| a == "--hard") => self.note("`git reset --hard` discards uncommitted changes"),
+            "checkout" | "switch" | "restore" | "merge" | "rebase" | "pull" | "stash" => {
+                self.note(&format!("`git {}` may rewrite files in the working tree", sub))
+            }
+            "push" if args.iter().any(|a| a == "--force" || a == "-f") => self.note("Force-pushes"),
+            _ => {}
+        }
+    }
+
+    /// Human-readable report
+    pub fn render(&self) -> String {
+        let mut out = format!("Dry run — nothing was executed.\nCommand: {}\n", self.command);
+        out.push_str(&match &self.blocked {
+            Some(reason) => format!("Safety: BLOCKED ({})\n", reason),
+            None => "Safety: allowed\n".to_string(),
+        });
+        if !self.programs.is_empty() {
+            out.push_str(&format!("Programs: {}\n", self.programs.join(", ")));
+        }
+        for (label, list) in [("Would read", &self.reads), ("Would write", &self.writes), ("Would delete", &self.deletes)] {
+            if !list.is_empty() {
+                out.push_str(&format!("{}: {}\n", label, list.join(", ")));
+            }
+        }
+        if !self.network.is_empty() {
+            out.push_str(&format!("Network access: {}\n", self.network.join(", ")));
+        }
+        for note in &self.notes {
+            out.push_str(&format!("Note: {}\n", note));
+        }
+        if self.reads.is_empty() && self.writes.is_empty() && self.deletes.is_empty() {
+            out.push_str("No file access detected.\n");
+        }
+        out
+    }
+}
+
+/// Words of `command` if it is one simple command: no chaining, pipes,
+/// redirections or substitutions
+pub fn simple_command(command: &str) -> Option<Vec<String>> {
+    if ["$(", "`", "<(", ">("].iter().any(|s| command.contains(s)) {
+        return None;
+    }
+    let segments = split_segments(command);
+    let [segment] = segments.as_slice() else {
+        return None;
+    };
+    let words = shell_words::split(segment).ok()?;
+    let redirects = words.iter().any(|word| {
+        let trimmed = word.trim_start_matches(|c: char| c.is_ascii_digit() || c == '&');
+        trimmed.starts_with('>') || trimmed.starts_with('<')
+    });
+    (!redirects && !words.is_empty()).then_some(words)
+}
+
+/// Split on `;`, `&&`, `||`, `|`, `&` and newlines outside quotes
+fn split_segments(command: &str) -> Vec<String> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut chars = command.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => {
+                quote = None;
+                current.push(c);
+            }
+            (Some(_), c) => current.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                current.push(c);
+            }
+            (None, '\\') => {
+                current.push(c);
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            (None, '&') if current.ends_with('>') || chars.peek() == Some(&'>') => current.push(c),
+            (None, ';' | '|' | '&' | '\n') => {
+                if matches!(chars.peek(), Some('|' | '&')) && c != ';' && c != '\n' {
+                    chars.next();
+                }
+                segments.push(std::mem::take(&mut current));
+            }
+            (None, c) => current.push(c),
+        }
+    }
+    segments.push(current);
+    segments.into_iter().map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
+}
+
+/// Whether `-i`/`--in-place` is among `args` (sed, perl, yq)
+fn in_place(args: &[String]) -> bool {
+    args.iter().any(|a| a.starts_with("-i") || a == "--in-place" || a == "--inplace")
+}
+
+/// The file `sort -o`/`--output` would write, if any
+fn sort_output(args: &[String]) -> Option<String> {
+    args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == "-o" || arg == "--output" {
+            return args.get(i + 1).cloned();
+        }
+        arg.strip_prefix("--output=")
+            .or_else(|| arg.strip_prefix("-o").filter(|rest| !rest.is_empty()))
+            .map(str::to_string)
+    })
+}
+
+fn dedup(list: &mut Vec<String>) {
+    let mut seen = std::collections::HashSet::new();
+    list.retain(|item| seen.insert(item.clone()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_pipeline() {
+        let preview = CommandPreview::analyze(
+            "cat src/main.rs | grep -n TODO > todos.txt 2>&1 && rm -f old.log 'a b.tmp'; mv draft.md docs/final.md",
+        );
+        assert_eq!(preview.programs, ["cat", "grep", "rm", "mv"]);
+        assert_eq!(preview.reads, ["src/main.rs"]);
+        assert_eq!(preview.writes, ["todos.txt", "docs/final.md"]);
+        assert_eq!(preview.deletes, ["old.log", "a b.tmp", "draft.md"]);
+        assert!(preview.blocked.is_none());
+        assert!(preview.network.is_empty());
+    }
+
+    #[test]
+    fn test_analyze_flags_risks() {
+        let preview = CommandPreview::analyze("sudo rm -rf / ; curl -o out.bin https://x.io && git push --force");
+        assert_eq!(preview.blocked.as_deref(), Some("contains 'rm -rf /'"));
+        assert_eq!(preview.network, ["curl", "git push"]);
+        assert_eq!(preview.writes, ["out.bin"]);
+        assert!(preview.notes.contains(&"Runs with sudo".to_string()));
+        assert!(preview.notes.contains(&"Force-pushes".to_string()));
+
+        let inline = CommandPreview::analyze("sed -i 's/a/b/' Cargo.toml");
+        assert_eq!(inline.writes, ["Cargo.toml"]);
+        assert!(inline.render().contains("Would write: Cargo.toml"));
+    }
+}
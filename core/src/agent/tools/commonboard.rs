@@ -123,6 +123,14 @@ impl Capability for CommonboardTool {
 
 #[async_trait::async_trait]
 impl ToolCapability for CommonboardTool {
+    fn description(&self) -> &'static str {
+        "Inter-agent coordination (claims, progress, completion)"
+    }
+
+    fn usage(&self) -> &'static str {
+        "{\"a\": \"commonboard\", \"i\": {\"action\": \"claim\", \"resource\": \"file.rs\"}} or {\"a\": \"commonboard\", \"i\": {\"action\": \"query\"}}"
+    }
+
     async fn execute(
         &self,
         _ctx: &RuntimeContext,
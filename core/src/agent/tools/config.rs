@@ -0,0 +1,436 @@
+//! Config Tool
+//!
+//! Lets the agent read and change a whitelist of settings on the user's
+//! behalf ("switch the worker model to gpt-4o-mini", "raise max iterations
+//! to 20"). Every `set` goes through approval like `shell` and `write_file`.
+//! Values are validated before anything is written.
+//!
+//! # Usage
+//!
+//! - `{"action": "get"}` - list the editable settings and their values
+//! - `{"action": "set", "key": "worker.model", "value": "gpt-4o-mini"}`
+//!
+//! A change is saved to `config.toml` and applied to the live config shared
+//! with the session factory. When it is used depends on the setting:
+//!
+//! - `budget.*`: at once, by the running session's budget
+//! - `model`, `temperature`, `context_window`: from the next request, by
+//!   rebuilding the running sessions' clients through the `SessionReloader`
+//! - `worker.*`: by the next worker
+//! - `max_iterations`, `features.*`: from the next session, since they
+//!   decide how a session and its tools are built
+
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use crate::agent::reload::SessionReloader;
+use crate::agent::runtime::core::{Capability, RuntimeContext, ToolCapability, ToolError};
+use crate::agent::runtime::governance::SessionBudget;
+use crate::agent::runtime::orchestrator::{OutputEvent, OutputSender};
+use crate::agent::types::events::ToolResult;
+use crate::agent::types::intents::ToolCall;
+use crate::config::{Config, ProfileConfig};
+
+/// Config shared between the session factory and the config tool
+pub type LiveConfig = Arc<RwLock<Config>>;
+
+/// Profile used by worker sessions
+const WORKER_PROFILE: &str = "worker";
+
+/// A setting the agent may change
+#[derive(Debug, Clone, Copy)]
+pub struct EditableSetting {
+    pub key: &'static str,
+    pub description: &'static str,
+}
+
+/// Everything the config tool can touch; credentials, providers and
+/// approval rules are deliberately absent
+pub const EDITABLE_SETTINGS: &[EditableSetting] = &[
+    EditableSetting { key: "model", description: "Model of the active profile (\"default\" = provider default)" },
+    EditableSetting { key: "temperature", description: "Sampling temperature of the active profile (0.0-2.0)" },
+    EditableSetting { key: "max_iterations", description: "Agent iteration limit of the active profile (1-500)" },
+    EditableSetting { key: "context_window", description: "Context window of the active profile in tokens (1024-10000000)" },
+    EditableSetting { key: "worker.model", description: "Model used by worker agents (\"default\" = provider default)" },
+    EditableSetting { key: "worker.max_iterations", description: "Iteration limit of worker agents (1-500)" },
+    EditableSetting { key: "features.web_search", description: "Enable the web_search tool (true/false)" },
+    EditableSetting { key: "features.memory", description: "Enable long-term memory (true/false)" },
+    EditableSetting { key: "features.workers", description: "Enable worker delegation (true/false)" },
+    EditableSetting { key: "features.code_index", description: "Index the current repository for code_search_semantic (true/false)" },
+    EditableSetting { key: "budget.max_session_cost_usd", description: "Session cost limit in USD (\"none\" = no limit)" },
+    EditableSetting { key: "budget.max_session_tokens", description: "Session token limit (\"none\" = no limit)" },
+];
+
+/// A rejected change
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ConfigEditError {
+    #[error("'{0}' is not an editable setting; use the get action to list them")]
+    UnknownKey(String),
+    #[error("invalid value for {key}: {reason}")]
+    InvalidValue { key: String, reason: String },
+}
+
+/// Current value of an editable setting, formatted for display
+pub fn current_value(config: &Config, key: &str) -> Option<String> {
+    let worker = config.profiles.get(WORKER_PROFILE).unwrap_or_else(|| config.active_profile());
+    let model = |p: &ProfileConfig| p.model.clone().unwrap_or_else(|| "default".to_string());
+    let optional = |v: Option<String>| v.unwrap_or_else(|| "none".to_string());
+    Some(match key {
+        "model" => model(config.active_profile()),
+        "temperature" => config.active_profile().temperature.to_string(),
+        "max_iterations" => config.active_profile().max_iterations.to_string(),
+        "context_window" => config.active_profile().context_window.to_string(),
+        "worker.model" => model(worker),
+        "worker.max_iterations" => worker.max_iterations.to_string(),
+        "features.web_search" => config.features.web_search.to_string(),
+        "features.memory" => config.features.memory.to_string(),
+        "features.workers" => config.features.workers.to_string(),
+        "features.code_index" => config.features.code_index.to_string(),
+        "budget.max_session_cost_usd" => optional(config.features.budget.max_session_cost_usd.map(|v| v.to_string())),
+        "budget.max_session_tokens" => optional(config.features.budget.max_session_tokens.map(|v| v.to_string())),
+        _ => return None,
+    })
+}
+
+/// Validate `value` and write it into `config`
+///
+/// Returns the value as stored. Worker settings create the worker profile
+/// from the active profile if it does not exist yet.
+pub fn apply_setting(config: &mut Config, key: &str, value: &str) -> Result<String, ConfigEditError> {
+    let value = value.trim();
+    let invalid = |reason: &str| ConfigEditError::InvalidValue {
+        key: key.to_string(),
+        reason: reason.to_string(),
+    };
+    match key {
+        "model" => config.active_profile_mut().model = parse_model(value).map_err(|r| invalid(&r))?,
+        "temperature" => config.active_profile_mut().temperature = parse_in_range(value, 0.0, 2.0).map_err(|r| invalid(&r))?,
+        "max_iterations" => config.active_profile_mut().max_iterations = parse_in_range(value, 1, 500).map_err(|r| invalid(&r))?,
+        "context_window" => {
+            config.active_profile_mut().context_window = parse_in_range(value, 1024, 10_000_000).map_err(|r| invalid(&r))?
+        }
+        "worker.model" => worker_profile_mut(config).model = parse_model(value).map_err(|r| invalid(&r))?,
+        "worker.max_iterations" => worker_profile_mut(config).max_iterations = parse_in_range(value, 1, 500).map_err(|r| invalid(&r))?,
+        "features.web_search" => config.features.web_search = parse_bool(value).map_err(|r| invalid(&r))?,
+        "features.memory" => config.features.memory = parse_bool(value).map_err(|r| invalid(&r))?,
+        "features.workers" => config.features.workers = parse_bool(value).map_err(|r| invalid(&r))?,
+        "features.code_index" => config.features.code_index = parse_bool(value).map_err(|r| invalid(&r))?,
+        "budget.max_session_cost_usd" => {
+            config.features.budget.max_session_cost_usd =
+                parse_optional(value, |v| parse_in_range(v, 0.0, 10_000.0)).map_err(|r| invalid(&r))?
+        }
+        "budget.max_session_tokens" => {
+            config.features.budget.max_session_tokens =
+                parse_optional(value, |v| parse_in_range(v, 1, u64::MAX)).map_err(|r| invalid(&r))?
+        }
+        _ => return Err(ConfigEditError::UnknownKey(key.to_string())),
+    }
+    Ok(current_value(config, key).unwrap_or_default())
+}
+
+/// Settings the `SessionReloader` applies to running sessions
+fn reloads_clients(key: &str) -> bool {
+    matches!(key, "model" | "temperature" | "context_window")
+}
+
+/// When a change to `key` reaches the agent
+fn takes_effect(key: &str, reloaded: bool) -> &'static str {
+    if key.starts_with("budget.") {
+        "immediately"
+    } else if reloaded {
+        "from the next request"
+    } else if key.starts_with("worker.") {
+        "for the next worker"
+    } else {
+        "from the next session"
+    }
+}
+
+fn worker_profile_mut(config: &mut Config) -> &mut ProfileConfig {
+    if !config.profiles.contains_key(WORKER_PROFILE) {
+        let base = config.active_profile().clone();
+        config.profiles.insert(WORKER_PROFILE.to_string(), base);
+    }
+    config.profiles.get_mut(WORKER_PROFILE).expect("worker profile was just inserted")
+}
+
+fn parse_model(value: &str) -> Result<Option<String>, String> {
+    if value.is_empty() || value.contains(char::is_whitespace) {
+        return Err("expected a model name without spaces".to_string());
+    }
+    Ok(match value {
+        "default" | "none" => None,
+        other => Some(other.to_string()),
+    })
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value.to_lowercase().as_str() {
+        "true" | "on" | "yes" => Ok(true),
+        "false" | "off" | "no" => Ok(false),
+        _ => Err("expected true or false".to_string()),
+    }
+}
+
+fn parse_in_range<T>(value: &str, min: T, max: T) -> Result<T, String>
+where
+    T: std::str::FromStr + PartialOrd + std::fmt::Display,
+{
+    let parsed: T = value.parse().map_err(|_| format!("'{}' is not a number", value))?;
+    if parsed < min || parsed > max {
+        return Err(format!("must be between {} and {}", min, max));
+    }
+    Ok(parsed)
+}
+
+fn parse_optional<T>(value: &str, parse: impl Fn(&str) -> Result<T, String>) -> Result<Option<T>, String> {
+    match value {
+        "none" | "null" | "off" => Ok(None),
+        other => parse(other).map(Some),
+    }
+}
+
+/// Tool that edits whitelisted settings
+pub struct ConfigTool {
+    path: PathBuf,
+    live: LiveConfig,
+    budget: Option<Arc<SessionBudget>>,
+    reloader: Option<Arc<SessionReloader>>,
+    output_tx: Option<OutputSender>,
+}
+
+impl ConfigTool {
+    /// Edit the config file at `path` and the shared live config
+    pub fn new(path: PathBuf, live: LiveConfig) -> Self {
+        Self {
+            path,
+            live,
+            budget: None,
+            reloader: None,
+            output_tx: None,
+        }
+    }
+
+    /// Apply budget changes to the running session's budget
+    pub fn with_budget(mut self, budget: Arc<SessionBudget>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Apply model changes to the running sessions
+    pub fn with_reloader(mut self, reloader: Arc<SessionReloader>) -> Self {
+        self.reloader = Some(reloader);
+        self
+    }
+
+    /// Announce changes so the frontend can update its copy of the config
+    pub fn with_output_sender(mut self, output_tx: OutputSender) -> Self {
+        self.output_tx = Some(output_tx);
+        self
+    }
+
+    fn live_snapshot(&self) -> Config {
+        self.live.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn list_settings(&self) -> ToolResult {
+        let config = self.live_snapshot();
+        let mut output = String::from("Editable settings:\n");
+        for setting in EDITABLE_SETTINGS {
+            output.push_str(&format!(
+                "- {} = {}  ({})\n",
+                setting.key,
+                current_value(&config, setting.key).unwrap_or_default(),
+                setting.description
+            ));
+        }
+        ToolResult::Success { output, structured: None }
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<ToolResult, ToolError> {
+        // Edit the file as it is on disk so unrelated changes are kept; never
+        // the live config, which has project and user overlays merged in
+        let mut on_disk = if self.path.exists() {
+            Config::load(&self.path)
+                .map_err(|e| ToolError::new(format!("Failed to read {}: {}", self.path.display(), e)))?
+        } else {
+            Config::default()
+        };
+        let stored = match apply_setting(&mut on_disk, key, value) {
+            Ok(stored) => stored,
+            Err(e) => {
+                return Ok(ToolResult::Error {
+                    message: e.to_string(),
+                    code: Some("INVALID_SETTING".to_string()),
+                    retryable: false,
+                })
+            }
+        };
+        on_disk
+            .save(&self.path)
+            .map_err(|e| ToolError::new(format!("Failed to save {}: {}", self.path.display(), e)))?;
+
+        {
+            let mut live = self.live.write().unwrap_or_else(|e| e.into_inner());
+            // Validated above, so this cannot fail
+            let _ = apply_setting(&mut live, key, value);
+            if key.starts_with("budget.") {
+                if let Some(ref budget) = self.budget {
+                    budget.set_limits(live.features.budget.clone());
+                }
+            }
+        }
+        crate::info_log!("[CONFIG_TOOL] Set {} = {}", key, stored);
+
+        let reloaded = match self.reloader {
+            Some(ref reloader) if reloads_clients(key) => match reloader.apply(&self.live_snapshot()) {
+                Ok(_) => true,
+                Err(e) => {
+                    crate::warn_log!("[CONFIG_TOOL] Could not apply {} to the running session: {}", key, e);
+                    false
+                }
+            },
+            _ => false,
+        };
+
+        if let Some(ref output_tx) = self.output_tx {
+            let _ = output_tx.send(OutputEvent::ConfigChanged {
+                key: key.to_string(),
+                value: stored.clone(),
+            });
+        }
+
+        Ok(ToolResult::Success {
+            output: format!(
+                "Set {} = {} (saved to {}; takes effect {})",
+                key,
+                stored,
+                self.path.display(),
+                takes_effect(key, reloaded)
+            ),
+            structured: None,
+        })
+    }
+}
+
+impl Capability for ConfigTool {
+    fn name(&self) -> &'static str {
+        "config"
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolCapability for ConfigTool {
+    fn description(&self) -> &'static str {
+        "Read or change the user's mylm settings (model, worker model, iterations, budget, features) when they ask. Changes need approval"
+    }
+
+    fn usage(&self) -> &'static str {
+        r#"List: {"a": "config", "i": {"action": "get"}} | Change: {"a": "config", "i": {"action": "set", "key": "worker.model", "value": "gpt-4o-mini"}}"#
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &RuntimeContext,
+        call: ToolCall,
+    ) -> Result<ToolResult, ToolError> {
+        let args = &call.arguments;
+        let action = args.get("action").and_then(|v| v.as_str()).unwrap_or("get");
+
+        match action {
+            "get" | "list" => Ok(self.list_settings()),
+            "set" => {
+                let key = args.get("key")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| ToolError::new("Set requires a 'key' field"))?;
+                let value = match args.get("value") {
+                    Some(serde_json::Value::String(s)) => s.clone(),
+                    Some(serde_json::Value::Null) => "none".to_string(),
+                    Some(other) => other.to_string(),
+                    None => return Err(ToolError::new("Set requires a 'value' field")),
+                };
+                self.set(key, &value)
+            }
+            _ => Ok(ToolResult::Error {
+                message: format!("Unknown action: {}. Use 'get' or 'set'", action),
+                code: Some("INVALID_ACTION".to_string()),
+                retryable: false,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BudgetSettings;
+
+    #[test]
+    fn test_apply_setting_validates() {
+        let mut config = Config::default();
+        assert_eq!(apply_setting(&mut config, "max_iterations", "20"), Ok("20".to_string()));
+        assert_eq!(config.active_profile().max_iterations, 20);
+
+        assert!(matches!(
+            apply_setting(&mut config, "max_iterations", "0"),
+            Err(ConfigEditError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            apply_setting(&mut config, "temperature", "hot"),
+            Err(ConfigEditError::InvalidValue { .. })
+        ));
+        assert_eq!(
+            apply_setting(&mut config, "providers.openai.api_key", "x"),
+            Err(ConfigEditError::UnknownKey("providers.openai.api_key".to_string()))
+        );
+
+        apply_setting(&mut config, "budget.max_session_tokens", "5000").unwrap();
+        assert_eq!(config.features.budget.max_session_tokens, Some(5000));
+        apply_setting(&mut config, "budget.max_session_tokens", "none").unwrap();
+        assert_eq!(config.features.budget.max_session_tokens, None);
+    }
+
+    #[test]
+    fn test_worker_model_creates_worker_profile() {
+        let mut config = Config::default();
+        config.active_profile_mut().max_iterations = 42;
+        apply_setting(&mut config, "worker.model", "gpt-4o-mini").unwrap();
+
+        let worker = &config.profiles[WORKER_PROFILE];
+        assert_eq!(worker.model.as_deref(), Some("gpt-4o-mini"));
+        assert_eq!(worker.max_iterations, 42);
+        assert_eq!(config.active_profile().model, None);
+    }
+
+    #[tokio::test]
+    async fn test_set_saves_and_updates_live_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let mut merged = Config::default();
+        // As if a project overlay had added it
+        merged.features.approval.auto_approve_commands.push("cargo *".to_string());
+        let live: LiveConfig = Arc::new(RwLock::new(merged));
+        let budget = Arc::new(SessionBudget::new(BudgetSettings::default(), 0.0, 0.0));
+        let pool = Arc::new(crate::scheduler::WorkerPool::from_settings(&Default::default()));
+        let tool = ConfigTool::new(path.clone(), Arc::clone(&live))
+            .with_budget(Arc::clone(&budget))
+            .with_reloader(Arc::new(SessionReloader::new(Arc::clone(&live), pool)));
+
+        let call = ToolCall::new("config", serde_json::json!({"action": "set", "key": "budget.max_session_tokens", "value": 1000}));
+        let result = tool.execute(&RuntimeContext::new(), call).await.unwrap();
+        assert!(matches!(result, ToolResult::Success { .. }), "{:?}", result);
+
+        assert_eq!(Config::load(&path).unwrap().features.budget.max_session_tokens, Some(1000));
+        assert!(Config::load(&path).unwrap().features.approval.auto_approve_commands.is_empty());
+        assert_eq!(live.read().unwrap().features.budget.max_session_tokens, Some(1000));
+        assert_eq!(budget.limits().max_session_tokens, Some(1000));
+
+        let call = ToolCall::new("config", serde_json::json!({"action": "set", "key": "temperature", "value": 0.5}));
+        let result = tool.execute(&RuntimeContext::new(), call).await.unwrap();
+        assert!(matches!(result, ToolResult::Success { ref output, .. } if output.ends_with("takes effect from the next request)")), "{:?}", result);
+
+        let call = ToolCall::new("config", serde_json::json!({"action": "set", "key": "temperature", "value": 9}));
+        let result = tool.execute(&RuntimeContext::new(), call).await.unwrap();
+        assert!(matches!(result, ToolResult::Error { .. }));
+    }
+}
@@ -123,6 +123,14 @@ impl Capability for DelegateTool {
 
 #[async_trait::async_trait]
 impl ToolCapability for DelegateTool {
+    fn description(&self) -> &'static str {
+        "Spawn worker agents for parallel/independent tasks. USE FOR: large file analysis, batch processing, background tasks, parallel searches. Workers run independently with isolated shells"
+    }
+
+    fn usage(&self) -> &'static str {
+        r#"Large file analysis: {"a": "delegate", "i": {"workers": [{"id": "analyzer", "objective": "Read src/main.rs and summarize key functions", "tools": ["read_file", "shell"]}]}} | Parallel tasks: {"a": "delegate", "i": {"workers": [{"id": "w1", "objective": "Find TODOs in src/", "tools": ["shell"], "allowed_commands": ["grep -r TODO src/"]}, {"id": "w2", "objective": "Find FIXMEs in src/", "tools": ["shell"], "allowed_commands": ["grep -r FIXME src/"]}]}}"#
+    }
+
     async fn execute(
         &self,
         _ctx: &RuntimeContext,
@@ -226,16 +234,16 @@ Received: {}"#,
         // Shared job ID mapping
         let id_to_job: Arc<RwLock<HashMap<String, crate::agent::runtime::orchestrator::commonbox::JobId>>> = Arc::new(RwLock::new(HashMap::new()));
         
-        // Spawn all workers
+        // Spawn all workers; sessions are created concurrently and each
+        // worker then waits for a slot in the factory's worker pool
         let mut spawned = Vec::new();
         let mut errors = Vec::new();
         
         crate::info_log!("[DELEGATE] Spawning {} workers...", args.workers.len());
         
-        for (index, config) in args.workers.iter().enumerate() {
+        let results = futures::future::join_all(args.workers.iter().enumerate().map(|(index, config)| {
             crate::info_log!("[DELEGATE] Spawning worker {}/{}: {}", index + 1, args.workers.len(), config.id);
-            
-            match spawn_worker(
+            spawn_worker(
                 config,
                 &args.shared_context,
                 index,
@@ -243,7 +251,11 @@ Received: {}"#,
                 self.commonbox.clone(),
                 self.factory.clone(),
                 self.output_tx.clone(),
-            ).await {
+            )
+        })).await;
+        
+        for (config, result) in args.workers.iter().zip(results) {
+            match result {
                 Ok(worker) => {
                     crate::info_log!("[DELEGATE] Worker {} spawned successfully (job_id: {})", config.id, worker.job_id);
                     spawned.push(worker);
@@ -268,10 +280,17 @@ Received: {}"#,
             }))
             .collect();
         
+        // Workers beyond the pool's capacity wait for a slot
+        let pool = self.factory.worker_pool();
+        let queued = spawned.len().saturating_sub(pool.capacity());
         let result = serde_json::json!({
             "message": format!("Spawned {} workers", spawned.len()),
             "workers": worker_infos,
             "commonboard_ready": true,
+            "pool": {
+                "max_concurrent": pool.capacity(),
+                "queued": queued,
+            },
         });
         
         if !errors.is_empty() {
@@ -284,7 +303,15 @@ Received: {}"#,
         }
         
         Ok(ToolResult::Success {
-            output: format!("Successfully spawned {} workers", spawned.len()),
+            output: if queued > 0 {
+                format!(
+                    "Successfully spawned {} workers ({} queued until a worker slot frees up)",
+                    spawned.len(),
+                    queued
+                )
+            } else {
+                format!("Successfully spawned {} workers", spawned.len())
+            },
             structured: Some(result),
         })
     }
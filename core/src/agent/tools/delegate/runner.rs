@@ -16,12 +16,17 @@ use crate::agent::runtime::orchestrator::{Session, UserInput, OutputEvent};
 use crate::agent::types::events::WorkerId;
 use crate::agent::runtime::orchestrator::OutputSender;
 use crate::memory::store::sanitize_memory_content;
+use crate::scheduler::WorkerPool;
 
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{mpsc, RwLock};
 use tokio::time::timeout;
+use tracing::Instrument;
+
+/// Job status message while a worker waits for a pool slot
+pub const QUEUED_STATUS: &str = "Queued for a worker slot";
 
 /// Spawn a single worker - COORDINATION entry point
 /// 
@@ -72,11 +77,14 @@ pub async fn spawn_worker(
     let commonbox_clone = commonbox.clone();
     let id_to_job_clone = id_to_job.clone();
     let parent_output_tx = output_tx.clone();
+    let pool = factory.worker_pool();
     
     crate::info_log!("[RUNNER] Spawning worker [{}] with session at {:p}", config.id, &session);
     
     // Step 4: COORDINATION - Spawn the runner task
     // The runner task handles: dependency wait, session run, idle loop, cleanup
+    // Its span sits under the delegate tool call that spawned it
+    let span = tracing::info_span!("worker.run", worker = %config.id, job = %job_id.0, objective = %config.objective);
     let handle = tokio::spawn(async move {
         run_worker_session(
             config_clone,
@@ -88,8 +96,9 @@ pub async fn spawn_worker(
             id_to_job_clone,
             worker_output_rx,
             parent_output_tx,
+            pool,
         ).await;
-    });
+    }.instrument(span));
     
     Ok(SpawnedWorker {
         config: config.clone(),
@@ -109,6 +118,7 @@ pub async fn spawn_worker(
 /// - Job state transitions (start, idle, complete, fail)
 /// 
 /// The session is pre-created by creator.rs (creation is separate from coordination).
+#[allow(clippy::too_many_arguments)]
 pub async fn run_worker_session(
     config: WorkerConfig,
     shared_context: Option<String>,
@@ -125,14 +135,14 @@ pub async fn run_worker_session(
     mut worker_output_rx: tokio::sync::mpsc::Receiver<OutputEvent>,
     // Parent channel: forwarder sends filtered events here
     parent_output_tx: Option<OutputSender>,
+    // Concurrency limit: the worker waits here for a slot before running
+    pool: Arc<WorkerPool>,
 ) {
     crate::info_log!("[WORKER] Worker [{}] (job={}) STARTING with objective: {}", config.id, job_id.0, config.objective);
     crate::info_log!("[WORKER] Worker [{}] has isolated mpsc channel, parent forwarding: {}", config.id, parent_output_tx.is_some());
     
     // Wait for dependencies before starting
     if !config.depends_on.is_empty() {
-        let id_map = id_to_job.read().await.clone();
-        
         // Subscribe to job events
         let mut subscriber = commonbox.subscribe();
         let mut pending: std::collections::HashSet<String> = config.depends_on.iter().cloned().collect();
@@ -146,6 +156,9 @@ pub async fn run_worker_session(
                 Ok(CommonboxEvent::JobCompleted { job_id: completed_id, .. }) |
                 Ok(CommonboxEvent::JobFailed { job_id: completed_id, .. }) |
                 Ok(CommonboxEvent::JobStalled { job_id: completed_id, .. }) => {
+                    // Siblings are spawned concurrently, so read the mapping
+                    // when an event arrives rather than once up front
+                    let id_map = id_to_job.read().await;
                     if let Some(dep_str) = config.depends_on.iter()
                         .find(|dep| id_map.get(*dep) == Some(&completed_id)) {
                         pending.remove(dep_str);
@@ -167,6 +180,11 @@ pub async fn run_worker_session(
         crate::info_log!("Worker [{}] all dependencies satisfied", config.id);
     }
     
+    // Wait for a pool slot; held until the initial task is done
+    commonbox.update_job_status_message(&job_id, QUEUED_STATUS).await;
+    crate::info_log!("Worker [{}] waiting for a pool slot ({:?})", config.id, pool.status());
+    let slot = pool.acquire(job_id).await;
+    
     // Mark as running
     if let Err(e) = commonbox.start_job(&job_id).await {
         crate::error_log!("Worker [{}] failed to start: {}", config.id, e);
@@ -334,7 +352,11 @@ pub async fn run_worker_session(
         config.timeout_secs.unwrap_or(DEFAULT_WORKER_TIMEOUT_SECS)
     );
     
-    match timeout(timeout_duration, session.run()).await {
+    let outcome = timeout(timeout_duration, session.run()).await;
+    // Idle workers only answer routed queries; let queued workers run
+    drop(slot);
+    
+    match outcome {
         Ok(Ok(result)) => {
             crate::info_log!("Worker [{}] completed initial task", config.id);
             
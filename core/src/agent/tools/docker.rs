@@ -0,0 +1,335 @@
+//! Docker tool - structured container operations
+//!
+//! Exposes list/logs/inspect/exec and compose up/down as named actions rather
+//! than raw `docker` command lines, so approval policy can tell a harmless
+//! `logs` from an `exec` by the action alone. Container and service names are
+//! validated so they can never be read as flags.
+
+use crate::agent::runtime::core::{Capability, RuntimeContext, ToolCapability, ToolError};
+use crate::agent::tools::cli::{clip, exec_command, run_program, validate_name};
+use crate::agent::tools::{expand_tilde, parse_args};
+use crate::agent::types::events::ToolResult;
+use crate::agent::types::intents::ToolCall;
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Default and largest number of log lines returned
+const DEFAULT_LOG_LINES: usize = 100;
+const MAX_LOG_LINES: usize = 2000;
+/// Time limits for quick queries and for exec/compose
+const QUERY_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_RUN_TIMEOUT_SECS: u64 = 120;
+const MAX_RUN_TIMEOUT_SECS: u64 = 900;
+
+/// Actions that only read state; everything else needs approval
+pub const READ_ONLY_ACTIONS: &[&str] = &["list", "logs", "inspect"];
+
+#[derive(Debug, Deserialize)]
+struct DockerArgs {
+    action: String,
+    #[serde(default)]
+    container: Option<String>,
+    /// `list`: include stopped containers
+    #[serde(default)]
+    all: bool,
+    /// `logs`: number of lines from the end
+    #[serde(default)]
+    tail: Option<usize>,
+    /// `logs`: only lines newer than this (e.g. "10m", an RFC 3339 time)
+    #[serde(default)]
+    since: Option<String>,
+    /// `exec`: argv list, or a string split like a shell would
+    #[serde(default)]
+    command: Option<serde_json::Value>,
+    /// `compose_*`: compose file, defaults to the one docker finds
+    #[serde(default)]
+    file: Option<String>,
+    /// `compose_up`: only these services
+    #[serde(default)]
+    services: Vec<String>,
+    /// `compose_up`: rebuild images first
+    #[serde(default)]
+    build: bool,
+    /// `compose_down`: also remove named volumes
+    #[serde(default)]
+    volumes: bool,
+    #[serde(default)]
+    timeout_secs: Option<u64>,
+}
+
+/// Tool for inspecting and managing containers
+#[derive(Debug, Clone)]
+pub struct DockerTool {
+    binary: PathBuf,
+}
+
+impl DockerTool {
+    /// Use `docker` from PATH, if it is installed
+    pub fn detect() -> Option<Self> {
+        let path = std::env::var_os("PATH")?;
+        std::env::split_paths(&path)
+            .map(|dir| dir.join("docker"))
+            .find(|candidate| candidate.is_file())
+            .map(Self::with_binary)
+    }
+
+    /// Use a specific docker-compatible binary (e.g. podman)
+    pub fn with_binary(binary: impl Into<PathBuf>) -> Self {
+        Self { binary: binary.into() }
+    }
+}
+
+fn required_container(args: &DockerArgs) -> Result<String, ToolError> {
+    let container = args
+        .container
+        .clone()
+        .ok_or_else(|| ToolError::new(format!("'container' is required for {}", args.action)))?;
+    validate_name("container", &container)?;
+    Ok(container)
+}
+
+/// The docker argv for a call
+fn build_args(args: &DockerArgs) -> Result<Vec<String>, ToolError> {
+    let mut argv: Vec<String> = Vec::new();
+    match args.action.as_str() {
+        "list" => {
+            argv.extend(["ps", "--no-trunc", "--format", "{{json .}}"].map(String::from));
+            if args.all {
+                argv.push("--all".to_string());
+            }
+        }
+        "logs" => {
+            let container = required_container(args)?;
+            let tail = args.tail.unwrap_or(DEFAULT_LOG_LINES).clamp(1, MAX_LOG_LINES);
+            argv.extend(["logs".to_string(), "--tail".to_string(), tail.to_string()]);
+            if let Some(since) = &args.since {
+                argv.push(format!("--since={}", since));
+            }
+            argv.push(container);
+        }
+        "inspect" => {
+            argv.extend(["inspect".to_string(), "--type=container".to_string(), required_container(args)?]);
+        }
+        "exec" => {
+            let container = required_container(args)?;
+            argv.extend(["exec".to_string(), container]);
+            argv.extend(exec_command(args.command.as_ref())?);
+        }
+        "compose_up" | "compose_down" => {
+            argv.push("compose".to_string());
+            if let Some(file) = &args.file {
+                argv.push(format!("--file={}", expand_tilde(file)));
+            }
+            if args.action == "compose_up" {
+                argv.extend(["up", "--detach"].map(String::from));
+                if args.build {
+                    argv.push("--build".to_string());
+                }
+                for service in &args.services {
+                    validate_name("service", service)?;
+                }
+                argv.push("--".to_string());
+                argv.extend(args.services.iter().cloned());
+            } else {
+                argv.push("down".to_string());
+                if args.volumes {
+                    argv.push("--volumes".to_string());
+                }
+            }
+        }
+        other => {
+            return Err(ToolError::new(format!(
+                "Unknown action '{}'. Use list, logs, inspect, exec, compose_up or compose_down",
+                other
+            )))
+        }
+    }
+    Ok(argv)
+}
+
+/// `docker ps --format '{{json .}}'` lines as container summaries
+fn parse_ps(stdout: &str) -> Vec<serde_json::Value> {
+    stdout
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .map(|c| {
+            serde_json::json!({
+                "id": c["ID"].as_str().map(|id| id.chars().take(12).collect::<String>()),
+                "name": c["Names"],
+                "image": c["Image"],
+                "state": c["State"],
+                "status": c["Status"],
+                "ports": c["Ports"],
+            })
+        })
+        .collect()
+}
+
+/// The parts of `docker inspect` worth showing the model
+///
+/// Environment variables often hold passwords and tokens, so only their
+/// names are kept.
+fn summarize_inspect(stdout: &str) -> Option<serde_json::Value> {
+    let all: serde_json::Value = serde_json::from_str(stdout).ok()?;
+    let c = all.get(0)?;
+    Some(serde_json::json!({
+        "id": c["Id"],
+        "name": c["Name"].as_str().map(|n| n.trim_start_matches('/')),
+        "image": c["Config"]["Image"],
+        "state": c["State"],
+        "restart_count": c["RestartCount"],
+        "command": c["Config"]["Cmd"],
+        "env_names": c["Config"]["Env"].as_array().map(|env| {
+            env.iter()
+                .filter_map(|var| var.as_str())
+                .map(|var| var.split('=').next().unwrap_or_default().to_string())
+                .collect::<Vec<_>>()
+        }),
+        "ports": c["NetworkSettings"]["Ports"],
+        "mounts": c["Mounts"].as_array().map(|mounts| {
+            mounts
+                .iter()
+                .map(|m| format!("{} -> {}", m["Source"].as_str().unwrap_or("?"), m["Destination"].as_str().unwrap_or("?")))
+                .collect::<Vec<_>>()
+        }),
+        "networks": c["NetworkSettings"]["Networks"].as_object().map(|n| n.keys().cloned().collect::<Vec<_>>()),
+    }))
+}
+
+impl Capability for DockerTool {
+    fn name(&self) -> &'static str {
+        "docker"
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolCapability for DockerTool {
+    fn description(&self) -> &'static str {
+        "Work with containers instead of running docker in the shell. Actions: list, logs, inspect (no approval); exec, compose_up, compose_down (need approval)"
+    }
+
+    fn usage(&self) -> &'static str {
+        r#"List: {"a": "docker", "i": {"action": "list", "all": true}} | Logs: {"a": "docker", "i": {"action": "logs", "container": "api", "tail": 200, "since": "10m"}} | Exec: {"a": "docker", "i": {"action": "exec", "container": "db", "command": ["psql", "-c", "select 1"]}} | Compose: {"a": "docker", "i": {"action": "compose_up", "services": ["web"], "build": true}}"#
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &RuntimeContext,
+        call: ToolCall,
+    ) -> Result<ToolResult, ToolError> {
+        let args: DockerArgs = parse_args(&call.arguments)?;
+        let argv = build_args(&args)?;
+        let timeout = if READ_ONLY_ACTIONS.contains(&args.action.as_str()) {
+            QUERY_TIMEOUT
+        } else {
+            Duration::from_secs(args.timeout_secs.unwrap_or(DEFAULT_RUN_TIMEOUT_SECS).clamp(1, MAX_RUN_TIMEOUT_SECS))
+        };
+        let (ok, stdout, stderr) = run_program(&self.binary, &argv, timeout).await?;
+
+        if !ok && args.action != "exec" {
+            return Ok(ToolResult::Error {
+                message: format!("docker {} failed: {}", args.action, stderr.trim()),
+                code: Some("DOCKER_ERROR".to_string()),
+                retryable: false,
+            });
+        }
+
+        let (output, structured) = match args.action.as_str() {
+            "list" => {
+                let containers = parse_ps(&stdout);
+                let output = if containers.is_empty() {
+                    "No containers".to_string()
+                } else {
+                    containers
+                        .iter()
+                        .map(|c| {
+                            format!(
+                                "{} {} ({}) {}",
+                                c["id"].as_str().unwrap_or_default(),
+                                c["name"].as_str().unwrap_or_default(),
+                                c["image"].as_str().unwrap_or_default(),
+                                c["status"].as_str().unwrap_or_default()
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                };
+                (output, serde_json::json!({ "containers": containers }))
+            }
+            "inspect" => match summarize_inspect(&stdout) {
+                Some(summary) => (
+                    serde_json::to_string_pretty(&summary).unwrap_or_default(),
+                    serde_json::json!({ "container": summary }),
+                ),
+                None => (clip(&stdout), serde_json::Value::Null),
+            },
+            "logs" => {
+                // Containers write to both streams; docker passes them through as is
+                let combined = format!("{}{}", stdout, stderr);
+                (clip(&combined), serde_json::json!({ "lines": combined.lines().count() }))
+            }
+            _ => {
+                let mut output = clip(stdout.trim_end());
+                if !stderr.trim().is_empty() {
+                    output.push_str(&format!("\n[stderr]\n{}", clip(stderr.trim_end())));
+                }
+                (output, serde_json::json!({ "success": ok }))
+            }
+        };
+
+        if !ok {
+            return Ok(ToolResult::Error {
+                message: format!("Command in container exited with an error:\n{}", output),
+                code: Some("EXEC_FAILED".to_string()),
+                retryable: false,
+            });
+        }
+        Ok(ToolResult::Success {
+            output,
+            structured: Some(structured),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(value: serde_json::Value) -> DockerArgs {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_actions_build_argv() {
+        let logs = build_args(&args(serde_json::json!({"action": "logs", "container": "api-1", "tail": 5000}))).unwrap();
+        assert_eq!(logs, vec!["logs", "--tail", "2000", "api-1"]);
+
+        let exec = build_args(&args(serde_json::json!({"action": "exec", "container": "db", "command": "psql -c 'select 1'"}))).unwrap();
+        assert_eq!(exec, vec!["exec", "db", "psql", "-c", "select 1"]);
+
+        let up = build_args(&args(serde_json::json!({"action": "compose_up", "services": ["web"], "build": true}))).unwrap();
+        assert_eq!(up, vec!["compose", "up", "--detach", "--build", "--", "web"]);
+
+        // Names can never smuggle in flags
+        assert!(build_args(&args(serde_json::json!({"action": "logs", "container": "--privileged"}))).is_err());
+        assert!(build_args(&args(serde_json::json!({"action": "exec", "container": "db"}))).is_err());
+        assert!(build_args(&args(serde_json::json!({"action": "rm", "container": "db"}))).is_err());
+    }
+
+    #[test]
+    fn test_parse_ps_and_inspect() {
+        let ps = r#"{"ID":"0123456789abcdef","Image":"postgres:16","Names":"db","State":"running","Status":"Up 2 hours","Ports":"5432/tcp"}"#;
+        let containers = parse_ps(ps);
+        assert_eq!(containers[0]["id"], "0123456789ab");
+        assert_eq!(containers[0]["name"], "db");
+
+        let inspect = r#"[{"Id":"abc","Name":"/db","RestartCount":2,"State":{"Status":"running"},"Config":{"Image":"postgres:16","Cmd":["postgres"],"Env":["POSTGRES_PASSWORD=hunter2","PGDATA=/data"]},"Mounts":[{"Source":"/data","Destination":"/var/lib/postgresql"}],"NetworkSettings":{"Ports":{},"Networks":{"bridge":{}}}}]"#;
+        let summary = summarize_inspect(inspect).unwrap();
+        assert_eq!(summary["name"], "db");
+        assert_eq!(summary["mounts"][0], "/data -> /var/lib/postgresql");
+        assert_eq!(summary["networks"][0], "bridge");
+        assert_eq!(summary["env_names"], serde_json::json!(["POSTGRES_PASSWORD", "PGDATA"]));
+        assert!(!summary.to_string().contains("hunter2"));
+    }
+}
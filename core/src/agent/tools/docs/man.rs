@@ -0,0 +1,286 @@
+//! Man pages: rendered with the local `man`, split into sections
+//!
+//! The page is rendered as plain text (no pager, no bold/underline) at a
+//! fixed width, then split into its headed sections (NAME, SYNOPSIS,
+//! OPTIONS, ...). Each section is split further into entries: an option tag
+//! with its indented description, or a paragraph. Queries return entries,
+//! never whole sections, which keeps the result small.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tokio::process::Command;
+
+/// Width pages are rendered at
+const MAN_WIDTH: &str = "100";
+/// Time limit for rendering a page
+const MAN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A headed section of a man page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManSection {
+    pub heading: String,
+    /// Body lines, with their original indentation
+    pub lines: Vec<String>,
+}
+
+/// A rendered man page
+#[derive(Debug, Clone)]
+pub struct ManPage {
+    pub name: String,
+    /// Manual section, e.g. "1" or "3p"
+    pub section: Option<String>,
+    /// Source file of the page
+    pub path: Option<PathBuf>,
+    pub sections: Vec<ManSection>,
+}
+
+impl ManPage {
+    /// How the page is cited, e.g. `tar(1)`
+    pub fn citation(&self) -> String {
+        match &self.section {
+            Some(section) => format!("{}({})", self.name, section),
+            None => self.name.clone(),
+        }
+    }
+
+    pub fn section(&self, heading: &str) -> Option<&ManSection> {
+        self.sections.iter().find(|s| s.heading == heading)
+    }
+
+    /// Entries mentioning `query`, as (section heading, entry text)
+    ///
+    /// Entries whose first line matches (an option tag such as
+    /// `-p, --preserve-permissions`) come before entries that only mention
+    /// the query in their text.
+    pub fn search(&self, query: &str) -> Vec<(String, String)> {
+        let needle = query.to_lowercase();
+        let mut tagged = Vec::new();
+        let mut mentioned = Vec::new();
+        for section in &self.sections {
+            if matches!(section.heading.as_str(), "NAME" | "SYNOPSIS") {
+                continue;
+            }
+            for entry in entries(&section.lines) {
+                let first = entry.lines().next().unwrap_or_default().to_lowercase();
+                if first.contains(&needle) {
+                    tagged.push((section.heading.clone(), entry));
+                } else if entry.to_lowercase().contains(&needle) {
+                    mentioned.push((section.heading.clone(), entry));
+                }
+            }
+        }
+        tagged.extend(mentioned);
+        tagged
+    }
+}
+
+/// Render and parse the page for `name`, optionally from one section
+pub async fn lookup(name: &str, section: Option<&str>) -> Result<ManPage, String> {
+    let mut args: Vec<&str> = section.into_iter().collect();
+    args.push(name);
+
+    let located = run_man(&[&["-w"], args.as_slice()].concat()).await?;
+    let path = located.lines().next().map(|line| PathBuf::from(line.trim()));
+    let text = clean(&run_man(&args).await?);
+
+    Ok(ManPage {
+        name: name.to_string(),
+        section: section
+            .map(str::to_string)
+            .or_else(|| path.as_deref().and_then(section_from_path)),
+        path,
+        sections: parse_sections(&text),
+    })
+}
+
+async fn run_man(args: &[&str]) -> Result<String, String> {
+    let child = Command::new("man")
+        .args(args)
+        .env("MANPAGER", "cat")
+        .env("PAGER", "cat")
+        .env("MANWIDTH", MAN_WIDTH)
+        .env("GROFF_NO_SGR", "1")
+        .env_remove("MAN_KEEP_FORMATTING")
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(MAN_TIMEOUT, child)
+        .await
+        .map_err(|_| "man timed out".to_string())?
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => "man is not installed".to_string(),
+            _ => format!("Failed to run man: {}", e),
+        })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(if stderr.is_empty() {
+            format!("No manual entry for {}", args.join(" "))
+        } else {
+            stderr
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// `/usr/share/man/man1/tar.1.gz` -> `1`
+fn section_from_path(path: &Path) -> Option<String> {
+    let file = path.file_name()?.to_str()?;
+    let file = [".gz", ".bz2", ".xz", ".zst", ".lzma"]
+        .iter()
+        .find_map(|ext| file.strip_suffix(ext))
+        .unwrap_or(file);
+    file.rsplit_once('.').map(|(_, section)| section.to_string())
+}
+
+/// Drop overstrike (bold/underline) and ANSI escapes from rendered output
+pub fn clean(raw: &str) -> String {
+    let mut out: Vec<char> = Vec::with_capacity(raw.len());
+    let mut chars = raw.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            // `X\bX` is bold and `_\bX` underline: keep the last character
+            '\u{8}' => {
+                out.pop();
+            }
+            '\u{1b}' if chars.peek() == Some(&'[') => {
+                chars.next();
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            c => out.push(c),
+        }
+    }
+    out.into_iter().collect()
+}
+
+/// Split rendered text into headed sections
+///
+/// Headings are the unindented, all-caps lines; the page header and footer
+/// (`TAR(1) ... TAR(1)`, `GNU tar 1.34 ...`) are not.
+pub fn parse_sections(text: &str) -> Vec<ManSection> {
+    let mut sections: Vec<ManSection> = Vec::new();
+    for line in text.lines() {
+        let is_heading = !line.starts_with(char::is_whitespace)
+            && line.chars().any(|c| c.is_ascii_uppercase())
+            && line.chars().all(|c| c.is_ascii_uppercase() || c == ' ' || c == '-');
+        if is_heading {
+            sections.push(ManSection {
+                heading: line.trim().to_string(),
+                lines: Vec::new(),
+            });
+        } else if let Some(section) = sections.last_mut() {
+            if line.starts_with(char::is_whitespace) || line.is_empty() {
+                section.lines.push(line.trim_end().to_string());
+            }
+        }
+    }
+    sections
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Split a section body into entries
+///
+/// Entries are separated by blank lines, except that text indented deeper
+/// than the entry's first line (an option's description, even across
+/// paragraphs) stays with it. Entries are returned dedented.
+pub fn entries(lines: &[String]) -> Vec<String> {
+    let mut blocks: Vec<Vec<&str>> = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut after_blank = false;
+    for line in lines {
+        if line.trim().is_empty() {
+            after_blank = !current.is_empty();
+            continue;
+        }
+        if after_blank {
+            if indent(line) > indent(current[0]) {
+                current.push("");
+            } else {
+                blocks.push(std::mem::take(&mut current));
+            }
+            after_blank = false;
+        }
+        current.push(line);
+    }
+    if !current.is_empty() {
+        blocks.push(current);
+    }
+
+    blocks
+        .into_iter()
+        .map(|block| {
+            let base = indent(block[0]);
+            block
+                .iter()
+                .map(|line| line.get(base.min(indent(line))..).unwrap_or_default())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = "TAR(1)                     GNU TAR Manual                     TAR(1)
+
+N\u{8}NA\u{8}AM\u{8}ME\u{8}E
+       tar - an archiving utility
+
+SYNOPSIS
+       tar [-] A --catenate --concatenate | c --create [options] [_\u{8}F_\u{8}I_\u{8}L_\u{8}E...]
+
+OPTION STYLES
+       Options may be given in traditional, UNIX or GNU style.
+
+OPTIONS
+       -p, --preserve-permissions, --same-permissions
+              extract information about file permissions (default for superuser)
+
+              Applies when extracting.
+
+       -v, --verbose
+              Verbosely list files processed.  Implies permissions are printed with -t.
+
+GNU tar 1.34                       February 2021                      TAR(1)
+";
+
+    #[test]
+    fn test_parse_sections_and_entries() {
+        let text = clean(PAGE);
+        let sections = parse_sections(&text);
+        let headings: Vec<&str> = sections.iter().map(|s| s.heading.as_str()).collect();
+        assert_eq!(headings, ["NAME", "SYNOPSIS", "OPTION STYLES", "OPTIONS"]);
+        assert!(sections[1].lines[0].ends_with("[FILE...]"));
+
+        let options = entries(&sections[3].lines);
+        assert_eq!(options.len(), 2);
+        assert_eq!(
+            options[0],
+            "-p, --preserve-permissions, --same-permissions\n       extract information about file permissions (default for superuser)\n\n       Applies when extracting."
+        );
+    }
+
+    #[test]
+    fn test_search_prefers_option_tags() {
+        let page = ManPage {
+            name: "tar".to_string(),
+            section: section_from_path(Path::new("/usr/share/man/man1/tar.1.gz")),
+            path: None,
+            sections: parse_sections(&clean(PAGE)),
+        };
+        assert_eq!(page.citation(), "tar(1)");
+
+        let hits = page.search("permissions");
+        assert_eq!(hits.len(), 2);
+        assert!(hits[0].1.starts_with("-p, --preserve-permissions"));
+        assert!(hits[1].1.starts_with("-v, --verbose"));
+    }
+}
@@ -0,0 +1,228 @@
+//! Docs Tool - Offline man pages and tldr pages
+//!
+//! Command questions ("which tar flag keeps permissions?") are best answered
+//! from the documentation installed on this machine: it matches the installed
+//! version, needs no network, and can be cited by man section. Instead of a
+//! whole man page the tool returns a bounded extract: NAME and SYNOPSIS, plus
+//! the option entries or paragraphs that mention `query`. tldr examples are
+//! added when a page exists locally or in the set bundled with mylm.
+//!
+//! # Usage
+//!
+//! - `{"command": "tar", "query": "--preserve-permissions"}` - man extract plus tldr examples
+//! - `{"command": "git commit", "source": "tldr"}` - examples only
+//! - `{"command": "printf", "section": "3"}` - a specific manual section
+
+pub mod man;
+pub mod tldr;
+
+use serde::Deserialize;
+
+use crate::agent::runtime::core::{Capability, RuntimeContext, ToolCapability, ToolError};
+use crate::agent::tools::parse_args;
+use crate::agent::types::events::ToolResult;
+use crate::agent::types::intents::ToolCall;
+
+/// Default and largest size of the returned text
+const DEFAULT_MAX_CHARS: usize = 4_000;
+const MAX_CHARS: usize = 12_000;
+/// Share of the output the DESCRIPTION gets when there is no query
+const DESCRIPTION_CHARS: usize = 1_200;
+/// Most man entries returned for a query
+const MAX_ENTRIES: usize = 8;
+
+#[derive(Debug, Deserialize)]
+struct DocsArgs {
+    /// Command or page, e.g. "tar", "git commit", "git-commit"
+    command: String,
+    /// auto (man, then tldr), man or tldr
+    #[serde(default)]
+    source: Option<String>,
+    /// Manual section, e.g. "1", "5", "3p"
+    #[serde(default)]
+    section: Option<String>,
+    /// Option or word to find in the man page, e.g. "-R" or "symlink"
+    #[serde(default)]
+    query: Option<String>,
+    #[serde(default)]
+    max_chars: Option<usize>,
+}
+
+/// Tool for reading local man pages and tldr pages
+#[derive(Debug, Clone, Default)]
+pub struct DocsTool;
+
+impl DocsTool {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// `git commit` -> `git-commit`, the page name man and tldr both use
+fn page_name(command: &str) -> Result<String, ToolError> {
+    let name = command.split_whitespace().collect::<Vec<_>>().join("-").to_lowercase();
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphanumeric())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '+'));
+    if valid {
+        Ok(name)
+    } else {
+        Err(ToolError::new(format!("Invalid command name '{}'", command)))
+    }
+}
+
+/// Cut `text` to `max` characters, marking the cut
+fn bounded(text: &str, max: usize) -> (String, bool) {
+    match text.char_indices().nth(max) {
+        Some((cut, _)) => (format!("{}\n[... truncated]", &text[..cut]), true),
+        None => (text.to_string(), false),
+    }
+}
+
+/// The man page extract for the model
+fn man_extract(page: &man::ManPage, query: Option<&str>) -> (String, usize) {
+    let mut out = format!("man {}", page.citation());
+    if let Some(path) = &page.path {
+        out.push_str(&format!(" ({})", path.display()));
+    }
+    out.push('\n');
+    for heading in ["NAME", "SYNOPSIS"] {
+        if let Some(section) = page.section(heading) {
+            out.push_str(&format!("\n{}\n{}\n", heading, man::entries(&section.lines).join("\n")));
+        }
+    }
+
+    match query {
+        Some(query) => {
+            let hits = page.search(query);
+            if hits.is_empty() {
+                out.push_str(&format!("\nNothing in {} mentions '{}'\n", page.citation(), query));
+            }
+            for (heading, entry) in hits.iter().take(MAX_ENTRIES) {
+                out.push_str(&format!("\n[{}]\n{}\n", heading, entry));
+            }
+            if hits.len() > MAX_ENTRIES {
+                out.push_str(&format!("\n({} more entries mention '{}'; narrow the query)\n", hits.len() - MAX_ENTRIES, query));
+            }
+            (out, hits.len())
+        }
+        None => {
+            if let Some(description) = page.section("DESCRIPTION") {
+                let (text, _) = bounded(&man::entries(&description.lines).join("\n\n"), DESCRIPTION_CHARS);
+                out.push_str(&format!("\nDESCRIPTION\n{}\n", text));
+            }
+            let headings: Vec<&str> = page.sections.iter().map(|s| s.heading.as_str()).collect();
+            out.push_str(&format!(
+                "\nSections: {}\nPass 'query' (e.g. an option like \"-R\") to read specific entries.\n",
+                headings.join(", ")
+            ));
+            (out, 0)
+        }
+    }
+}
+
+impl Capability for DocsTool {
+    fn name(&self) -> &'static str {
+        "docs"
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolCapability for DocsTool {
+    fn description(&self) -> &'static str {
+        "Look up a command in the local man pages and tldr pages (no web access). Returns NAME/SYNOPSIS and the entries matching 'query', plus tldr examples; cite the man section, e.g. tar(1). Prefer this over web_search for command-line flags"
+    }
+
+    fn usage(&self) -> &'static str {
+        r#"Option: {"a": "docs", "i": {"command": "tar", "query": "--preserve-permissions"}} | Examples: {"a": "docs", "i": {"command": "git commit", "source": "tldr"}} | Section: {"a": "docs", "i": {"command": "printf", "section": "3"}}"#
+    }
+
+    async fn execute(&self, _ctx: &RuntimeContext, call: ToolCall) -> Result<ToolResult, ToolError> {
+        let args: DocsArgs = parse_args(&call.arguments)?;
+        let name = page_name(&args.command)?;
+        if let Some(section) = &args.section {
+            if section.is_empty() || section.len() > 4 || !section.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(ToolError::new(format!("Invalid manual section '{}'", section)));
+            }
+        }
+        let source = args.source.as_deref().unwrap_or("auto");
+        if !matches!(source, "auto" | "man" | "tldr") {
+            return Err(ToolError::new(format!("Unknown source '{}'. Use auto, man or tldr", source)));
+        }
+        let query = args.query.as_deref().map(str::trim).filter(|q| !q.is_empty());
+        let max_chars = args.max_chars.unwrap_or(DEFAULT_MAX_CHARS).clamp(500, MAX_CHARS);
+
+        let mut text = String::new();
+        let mut structured = serde_json::json!({ "page": name });
+        let mut man_error = None;
+
+        if source != "tldr" {
+            match man::lookup(&name, args.section.as_deref()).await {
+                Ok(page) => {
+                    let (extract, matches) = man_extract(&page, query);
+                    text.push_str(&extract);
+                    structured["man"] = serde_json::json!({
+                        "citation": page.citation(),
+                        "section": page.section,
+                        "path": page.path.as_ref().map(|p| p.display().to_string()),
+                        "sections": page.sections.iter().map(|s| s.heading.clone()).collect::<Vec<_>>(),
+                        "matches": matches,
+                    });
+                }
+                Err(e) => man_error = Some(e),
+            }
+        }
+        if source != "man" {
+            if let Some(page) = tldr::find(&name) {
+                if !text.is_empty() {
+                    text.push_str("\n---\n");
+                }
+                text.push_str(&page.render());
+                structured["tldr"] = serde_json::json!({
+                    "origin": page.origin,
+                    "examples": page.examples.len(),
+                });
+            }
+        }
+
+        if text.is_empty() {
+            let mut message = format!("No local documentation for '{}'", args.command);
+            if let Some(e) = man_error {
+                message.push_str(&format!(" (man: {})", e));
+            }
+            return Ok(ToolResult::Error {
+                message,
+                code: Some("NO_DOCS".to_string()),
+                retryable: false,
+            });
+        }
+
+        let (output, truncated) = bounded(&text, max_chars);
+        structured["truncated"] = serde_json::json!(truncated);
+        Ok(ToolResult::Success {
+            output,
+            structured: Some(structured),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tldr_source_uses_bundled_pages() {
+        let call = ToolCall::new("docs", serde_json::json!({"command": "git  Rebase", "source": "tldr"}));
+        let result = DocsTool::new().execute(&RuntimeContext::new(), call).await.unwrap();
+        match result {
+            ToolResult::Success { output, structured } => {
+                assert!(output.starts_with("tldr git rebase"));
+                assert_eq!(structured.unwrap()["page"], "git-rebase");
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        assert!(page_name("-rf").is_err());
+        assert!(page_name("ls; rm").is_err());
+    }
+}
@@ -0,0 +1,136 @@
+//! tldr pages: example-first summaries of common commands
+//!
+//! Pages are read from a local tldr client's cache (tealdeer, the Node and
+//! Python clients) so they match what the user sees with `tldr`, and fall
+//! back to the small set bundled here, which works on a fresh machine.
+
+use std::path::PathBuf;
+
+/// Pages shipped with mylm, by page name
+const BUNDLED: &[(&str, &str)] = &[
+    ("chmod", include_str!("pages/chmod.md")),
+    ("curl", include_str!("pages/curl.md")),
+    ("find", include_str!("pages/find.md")),
+    ("git-commit", include_str!("pages/git-commit.md")),
+    ("git-rebase", include_str!("pages/git-rebase.md")),
+    ("grep", include_str!("pages/grep.md")),
+    ("rsync", include_str!("pages/rsync.md")),
+    ("sed", include_str!("pages/sed.md")),
+    ("ssh", include_str!("pages/ssh.md")),
+    ("tar", include_str!("pages/tar.md")),
+    ("xargs", include_str!("pages/xargs.md")),
+];
+
+/// A parsed tldr page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TldrPage {
+    pub title: String,
+    pub description: Vec<String>,
+    /// (what it does, command)
+    pub examples: Vec<(String, String)>,
+    /// Where the page came from: a file path or "bundled"
+    pub origin: String,
+}
+
+impl TldrPage {
+    /// Page as plain text for the model
+    pub fn render(&self) -> String {
+        let mut out = format!("tldr {} ({})\n", self.title, self.origin);
+        for line in &self.description {
+            out.push_str(line);
+            out.push('\n');
+        }
+        for (what, command) in &self.examples {
+            out.push_str(&format!("\n- {}\n  {}\n", what, command));
+        }
+        out
+    }
+}
+
+/// Parse a page in the tldr markdown format
+pub fn parse(markdown: &str, origin: &str) -> TldrPage {
+    let mut page = TldrPage {
+        title: String::new(),
+        description: Vec::new(),
+        examples: Vec::new(),
+        origin: origin.to_string(),
+    };
+    let mut pending: Option<String> = None;
+    for line in markdown.lines().map(str::trim) {
+        if let Some(title) = line.strip_prefix("# ") {
+            page.title = title.trim().to_string();
+        } else if let Some(text) = line.strip_prefix('>') {
+            page.description.push(text.trim().to_string());
+        } else if let Some(text) = line.strip_prefix("- ") {
+            pending = Some(text.trim().trim_end_matches(':').to_string());
+        } else if let Some(command) = line.strip_prefix('`').and_then(|l| l.strip_suffix('`')) {
+            // `{{path/to/file}}` placeholders read better without the braces
+            let command = command.replace("{{", "").replace("}}", "");
+            page.examples.push((pending.take().unwrap_or_default(), command));
+        }
+    }
+    page
+}
+
+/// Directories local tldr clients keep pages in
+fn cache_roots() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    if let Some(cache) = dirs::cache_dir() {
+        roots.push(cache.join("tealdeer/tldr-pages/pages.en"));
+        roots.push(cache.join("tealdeer/tldr-pages/pages"));
+        roots.push(cache.join("tldr/pages"));
+    }
+    if let Some(home) = dirs::home_dir() {
+        roots.push(home.join(".tldrc/tldr/pages"));
+    }
+    roots
+}
+
+fn platform() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "osx"
+    } else if cfg!(windows) {
+        "windows"
+    } else {
+        "linux"
+    }
+}
+
+/// Find the page named `name` (e.g. "tar", "git-commit")
+pub fn find(name: &str) -> Option<TldrPage> {
+    let file = format!("{}.md", name);
+    for root in cache_roots() {
+        for dir in [platform(), "common"] {
+            let path = root.join(dir).join(&file);
+            if let Ok(markdown) = std::fs::read_to_string(&path) {
+                return Some(parse(&markdown, &path.display().to_string()));
+            }
+        }
+    }
+    BUNDLED
+        .iter()
+        .find(|(page, _)| *page == name)
+        .map(|(_, markdown)| parse(markdown, "bundled"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_pages_parse() {
+        for (name, markdown) in BUNDLED {
+            let page = parse(markdown, "bundled");
+            assert_eq!(page.title.replace(' ', "-"), *name);
+            assert!(!page.description.is_empty(), "{} has no description", name);
+            assert!(page.examples.len() >= 4, "{} has too few examples", name);
+            assert!(page.examples.iter().all(|(what, command)| !what.is_empty() && !command.contains("{{")));
+        }
+
+        let tar = parse(BUNDLED.iter().find(|(n, _)| *n == "tar").unwrap().1, "bundled");
+        assert_eq!(tar.examples[1], (
+            "Extract an archive (compression is detected automatically) into the current directory".to_string(),
+            "tar xf archive.tar.gz".to_string(),
+        ));
+    }
+}
@@ -6,6 +6,7 @@
 //! 3. `close_file` - Cleans up chunk workers for a file
 
 use crate::agent::runtime::core::{Capability, RuntimeContext, ToolCapability, ToolError};
+use crate::agent::runtime::governance::SecretGuard;
 use crate::agent::types::events::ToolResult;
 use crate::agent::types::intents::ToolCall;
 use crate::agent::tools::expand_tilde;
@@ -141,6 +142,8 @@ pub struct QueryFileTool {
     max_utilization: f32,
     /// Output sender to emit progress events to UI
     output_tx: Option<crate::agent::runtime::orchestrator::OutputSender>,
+    /// Refuses paths on the secret-file deny-list
+    secret_guard: Arc<SecretGuard>,
 }
 
 impl QueryFileTool {
@@ -156,9 +159,16 @@ impl QueryFileTool {
             worker_context_window,
             max_utilization: 0.5,
             output_tx: Some(output_tx),
+            secret_guard: Arc::new(SecretGuard::new()),
         }
     }
 
+    /// Replace the default secret-file guard
+    pub fn with_secret_guard(mut self, guard: Arc<SecretGuard>) -> Self {
+        self.secret_guard = guard;
+        self
+    }
+
     /// Spawn a sandboxed worker task for a single chunk
     /// 
     /// The worker runs a loop listening for queries on the mpsc channel.
@@ -249,6 +259,14 @@ impl QueryFileTool {
 
 #[async_trait::async_trait]
 impl ToolCapability for QueryFileTool {
+    fn description(&self) -> &'static str {
+        "ALWAYS use this tool to process user-uploaded files or large documents. It splits the document into chunks, spawns sandboxed LLM workers for each chunk to prevent overwhelming context, and returns a summary plus chunk IDs for follow-ups."
+    }
+
+    fn usage(&self) -> &'static str {
+        r#"Process file: {"a": "query_file", "i": {"file_path": "large_document.pdf", "prompt": "Summarize the key points"}}"#
+    }
+
     async fn execute(
         &self,
         _ctx: &RuntimeContext,
@@ -267,6 +285,13 @@ impl ToolCapability for QueryFileTool {
 
         let path = expand_tilde(&args.file_path);
         let path_buf = PathBuf::from(&path);
+        if let Err(denied) = self.secret_guard.check("query_file", &path_buf) {
+            return Ok(ToolResult::Error {
+                message: denied.to_string(),
+                code: Some("SECRET_FILE".to_string()),
+                retryable: false,
+            });
+        }
         let file_name = path_buf.file_name()
             .and_then(|n| n.to_str())
             .unwrap_or("unknown")
@@ -671,6 +696,14 @@ impl QueryChunkTool {
 
 #[async_trait::async_trait]
 impl ToolCapability for QueryChunkTool {
+    fn description(&self) -> &'static str {
+        "Send a query to a specific chunk worker after using query_file. Use this for follow-up questions about specific chunks"
+    }
+
+    fn usage(&self) -> &'static str {
+        r#"Query specific chunk: {"a": "query_chunk_worker", "i": {"chunk_id": "document_chunk_0", "prompt": "What does this section say about error handling?"}}"#
+    }
+
     async fn execute(
         &self,
         _ctx: &RuntimeContext,
@@ -795,6 +828,14 @@ impl CloseFileTool {
 
 #[async_trait::async_trait]
 impl ToolCapability for CloseFileTool {
+    fn description(&self) -> &'static str {
+        "Clean up chunk workers for a file when done. Call this to free memory after finishing analysis"
+    }
+
+    fn usage(&self) -> &'static str {
+        r#"Close file workers: {"a": "close_file", "i": {"file_name": "large_document.pdf"}}"#
+    }
+
     async fn execute(
         &self,
         _ctx: &RuntimeContext,
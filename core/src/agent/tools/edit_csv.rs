@@ -6,22 +6,58 @@
 //! - Insert new rows
 //! - Delete rows
 //! - Validate CSV output before writing
+//!
+//! Like `write_file`, edits are staged in the open changeset of the patch
+//! queue instead of hitting disk, and writes to disk are recorded in the
+//! undo journal.
 
 use crate::agent::runtime::core::{Capability, RuntimeContext, ToolCapability, ToolError};
+use crate::agent::runtime::governance::SecretGuard;
 use crate::agent::types::events::ToolResult;
 use crate::agent::types::intents::ToolCall;
 use crate::agent::tools::expand_tilde;
+use crate::agent::tools::patch_queue::PatchQueue;
+use crate::agent::tools::undo_journal::UndoJournal;
+use crate::agent::tools::write_file::queue_write;
 use serde::Deserialize;
 use std::path::Path;
+use std::sync::Arc;
 
 /// Tool for editing CSV files with structured operations
 #[derive(Debug, Default)]
-pub struct EditCsvTool;
+pub struct EditCsvTool {
+    secret_guard: Arc<SecretGuard>,
+    patch_queue: Option<Arc<PatchQueue>>,
+    undo_journal: Option<Arc<UndoJournal>>,
+}
 
 impl EditCsvTool {
     /// Create a new edit_csv tool
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Refuse files the guard denies
+    pub fn with_secret_guard(mut self, guard: Arc<SecretGuard>) -> Self {
+        self.secret_guard = guard;
+        self
+    }
+
+    /// Queue edits in `queue` while it has a changeset open
+    pub fn with_patch_queue(mut self, queue: Arc<PatchQueue>) -> Self {
+        self.patch_queue = Some(queue);
+        self
+    }
+
+    /// Save the previous content of edited files in `journal`
+    pub fn with_undo_journal(mut self, journal: Arc<UndoJournal>) -> Self {
+        self.undo_journal = Some(journal);
+        self
+    }
+
+    /// The patch queue, if it has a changeset open
+    fn active_queue(&self) -> Option<&PatchQueue> {
+        self.patch_queue.as_deref().filter(|queue| queue.is_active())
     }
 
     /// Execute the CSV edit operation
@@ -29,8 +65,29 @@ impl EditCsvTool {
         let path = expand_tilde(&args.path);
         let path = Path::new(&path);
 
+        if let Err(denied) = self.secret_guard.check("edit_csv", path) {
+            return Ok(ToolResult::Error {
+                message: denied.to_string(),
+                code: Some("SECRET_FILE".to_string()),
+                retryable: false,
+            });
+        }
+
+        // Edit the file as the queued edits left it
+        let staged = match self.active_queue().map(|queue| queue.staged(path)) {
+            Some(Err(e)) => {
+                return Ok(ToolResult::Error {
+                    message: e,
+                    code: Some("QUEUE_ERROR".to_string()),
+                    retryable: false,
+                })
+            }
+            Some(Ok(staged)) => staged,
+            None => None,
+        };
+
         // Check if file exists
-        if !path.exists() {
+        if staged.is_none() && !path.exists() {
             return Ok(ToolResult::Error {
                 message: format!("File not found: {}", path.display()),
                 code: Some("FILE_NOT_FOUND".to_string()),
@@ -39,7 +96,11 @@ impl EditCsvTool {
         }
 
         // Read and parse CSV
-        let (headers, mut rows) = match self.read_csv(path).await {
+        let parsed = match staged {
+            Some(content) => parse_csv(content.as_bytes()),
+            None => self.read_csv(path).await,
+        };
+        let (headers, mut rows) = match parsed {
             Ok(data) => data,
             Err(e) => return Ok(self.error_result(&e)),
         };
@@ -71,10 +132,28 @@ impl EditCsvTool {
             return Ok(self.error_result(&e));
         }
 
+        let content = match to_csv(&headers, &rows) {
+            Ok(content) => content,
+            Err(e) => return Ok(self.error_result(&e)),
+        };
+        if let Some(queue) = self.active_queue() {
+            return Ok(queue_write(queue, path, &content));
+        }
+
         // Write back to file
-        if let Err(e) = self.write_csv(path, &headers, &rows).await {
+        if let Some(Err(e)) = self.undo_journal.as_ref().map(|journal| journal.record(path)) {
+            return Ok(ToolResult::Error {
+                message: format!("{}; file not written", e),
+                code: Some("UNDO_ERROR".to_string()),
+                retryable: false,
+            });
+        }
+        if let Err(e) = self.write_csv(path, &content).await {
             return Ok(self.error_result(&e));
         }
+        if let Some(journal) = &self.undo_journal {
+            journal.written(path, content.as_bytes());
+        }
 
         let rows_affected = rows_affected.unwrap_or(0);
 
@@ -298,68 +377,18 @@ impl EditCsvTool {
         tokio::task::spawn_blocking(move || {
             let file = std::fs::File::open(&path)
                 .map_err(|e| CsvError::ReadError(format!("Cannot open CSV: {}", e)))?;
-
-            let mut reader = csv::Reader::from_reader(file);
-
-            // Read headers
-            let headers = reader
-                .headers()
-                .map_err(|e| CsvError::ReadError(format!("Failed to read headers: {}", e)))?
-                .iter()
-                .map(|s| s.to_string())
-                .collect();
-
-            // Read rows
-            let mut rows = Vec::new();
-            for result in reader.records() {
-                let record = result
-                    .map_err(|e| CsvError::ReadError(format!("Failed to read row: {}", e)))?;
-                rows.push(record);
-            }
-
-            Ok((headers, rows))
+            parse_csv(file)
         })
         .await
         .map_err(|e| CsvError::ReadError(format!("CSV read task panicked: {}", e)))?
     }
 
-    /// Write CSV file with headers and rows
-    async fn write_csv(
-        &self,
-        path: &Path,
-        headers: &[String],
-        rows: &[csv::StringRecord],
-    ) -> Result<(), CsvError> {
-        let path = path.to_path_buf();
-        let headers: Vec<String> = headers.to_vec();
-        let rows: Vec<csv::StringRecord> = rows.to_vec();
-
-        tokio::task::spawn_blocking(move || {
-            let file = std::fs::File::create(&path)
-                .map_err(|e| CsvError::WriteError(format!("Cannot create file: {}", e)))?;
-
-            let mut writer = csv::Writer::from_writer(file);
-
-            // Write headers
-            writer
-                .write_record(&headers)
-                .map_err(|e| CsvError::WriteError(format!("Failed to write headers: {}", e)))?;
-
-            // Write rows
-            for row in &rows {
-                writer
-                    .write_record(row)
-                    .map_err(|e| CsvError::WriteError(format!("Failed to write row: {}", e)))?;
-            }
-
-            writer
-                .flush()
-                .map_err(|e| CsvError::WriteError(format!("Failed to flush: {}", e)))?;
-
-            Ok(())
-        })
-        .await
-        .map_err(|e| CsvError::WriteError(format!("CSV write task panicked: {}", e)))?
+    /// Write the serialized CSV to the file
+    async fn write_csv(&self, path: &Path, content: &str) -> Result<(), CsvError> {
+        crate::watch::note_agent_write(path);
+        tokio::fs::write(path, content)
+            .await
+            .map_err(|e| CsvError::WriteError(format!("Cannot write file: {}", e)))
     }
 
     /// Create error ToolResult
@@ -372,6 +401,50 @@ impl EditCsvTool {
     }
 }
 
+/// Headers and rows of the CSV read from `input`
+fn parse_csv(input: impl std::io::Read) -> Result<(Vec<String>, Vec<csv::StringRecord>), CsvError> {
+    let mut reader = csv::Reader::from_reader(input);
+
+    // Read headers
+    let headers = reader
+        .headers()
+        .map_err(|e| CsvError::ReadError(format!("Failed to read headers: {}", e)))?
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+    // Read rows
+    let mut rows = Vec::new();
+    for result in reader.records() {
+        let record = result.map_err(|e| CsvError::ReadError(format!("Failed to read row: {}", e)))?;
+        rows.push(record);
+    }
+
+    Ok((headers, rows))
+}
+
+/// `headers` and `rows` serialized as CSV
+fn to_csv(headers: &[String], rows: &[csv::StringRecord]) -> Result<String, CsvError> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+
+    // Write headers
+    writer
+        .write_record(headers)
+        .map_err(|e| CsvError::WriteError(format!("Failed to write headers: {}", e)))?;
+
+    // Write rows
+    for row in rows {
+        writer
+            .write_record(row)
+            .map_err(|e| CsvError::WriteError(format!("Failed to write row: {}", e)))?;
+    }
+
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| CsvError::WriteError(format!("Failed to flush: {}", e)))?;
+    String::from_utf8(bytes).map_err(|e| CsvError::WriteError(format!("CSV is not UTF-8: {}", e)))
+}
+
 impl Capability for EditCsvTool {
     fn name(&self) -> &'static str {
         "edit_csv"
@@ -466,6 +539,14 @@ impl CsvError {
 
 #[async_trait::async_trait]
 impl ToolCapability for EditCsvTool {
+    fn description(&self) -> &'static str {
+        "Edit CSV files with structured operations (update, delete, insert, update_where)"
+    }
+
+    fn usage(&self) -> &'static str {
+        "Update cell: {\"a\": \"edit_csv\", \"i\": {\"path\": \"<path>\", \"operation\": \"update\", \"row\": 1, \"column\": \"Name\", \"value\": \"New\"}} | Update where: {\"a\": \"edit_csv\", \"i\": {\"path\": \"<path>\", \"operation\": \"update_where\", \"where\": {\"column\": \"Status\", \"equals\": \"inactive\"}, \"column\": \"Status\", \"value\": \"active\"}}"
+    }
+
     async fn execute(
         &self,
         _ctx: &RuntimeContext,
@@ -803,4 +884,26 @@ mod tests {
             _ => panic!("Expected error"),
         }
     }
+
+    #[tokio::test]
+    async fn test_edits_are_queued_while_changeset_open() {
+        let temp = TempDir::new().unwrap();
+        let path = create_test_csv(&temp, "Name,Age\nAlice,30\n").await;
+        let queue = Arc::new(PatchQueue::with_dir(temp.path().join("changesets")));
+        queue.open("review").unwrap();
+        let tool = EditCsvTool::new().with_patch_queue(Arc::clone(&queue));
+
+        for age in ["31", "32"] {
+            let call = ToolCall::new(
+                "edit_csv",
+                serde_json::json!({ "path": path.to_str().unwrap(), "row": 1, "column": "Age", "value": age }),
+            );
+            let result = tool.execute(&RuntimeContext::new(), call).await.unwrap();
+            assert!(matches!(result, ToolResult::Success { .. }), "{:?}", result);
+        }
+
+        // The second edit builds on the first, and neither is on disk yet
+        assert_eq!(fs::read_to_string(&path).await.unwrap(), "Name,Age\nAlice,30\n");
+        assert_eq!(queue.staged(&path).unwrap().as_deref(), Some("Name,Age\nAlice,32\n"));
+    }
 }
@@ -1,10 +1,17 @@
 //! Git tools for repository operations
+//!
+//! `git_diff` leaves out the contents of files the secret guard denies.
+
+use std::path::PathBuf;
+use std::sync::Arc;
 
 use crate::agent::runtime::core::{Capability, ToolCapability};
 use crate::agent::runtime::core::RuntimeContext;
 use crate::agent::runtime::core::ToolError;
 use crate::agent::types::intents::ToolCall;
 use crate::agent::types::events::ToolResult;
+use crate::agent::runtime::governance::SecretGuard;
+use crate::agent::tools::expand_tilde;
 
 use tokio::process::Command;
 
@@ -26,6 +33,14 @@ impl Capability for GitStatusTool {
 
 #[async_trait::async_trait]
 impl ToolCapability for GitStatusTool {
+    fn description(&self) -> &'static str {
+        "Show git working tree status"
+    }
+
+    fn usage(&self) -> &'static str {
+        "{\"a\": \"git_status\"}"
+    }
+
     async fn execute(
         &self,
         _ctx: &RuntimeContext,
@@ -78,6 +93,14 @@ impl Capability for GitLogTool {
 
 #[async_trait::async_trait]
 impl ToolCapability for GitLogTool {
+    fn description(&self) -> &'static str {
+        "Show git commit history"
+    }
+
+    fn usage(&self) -> &'static str {
+        "{\"a\": \"git_log\", \"i\": {\"limit\": 10}}"
+    }
+
     async fn execute(
         &self,
         _ctx: &RuntimeContext,
@@ -119,14 +142,30 @@ impl ToolCapability for GitLogTool {
 
 /// Git diff tool - show changes
 #[derive(Debug, Default)]
-pub struct GitDiffTool;
+pub struct GitDiffTool {
+    secret_guard: Arc<SecretGuard>,
+}
 
 impl GitDiffTool {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Leave files `guard` denies out of the diff
+    pub fn with_secret_guard(mut self, guard: Arc<SecretGuard>) -> Self {
+        self.secret_guard = guard;
+        self
     }
 }
 
+async fn git_output(args: &[&str]) -> Result<std::process::Output, ToolError> {
+    Command::new("git")
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| ToolError::new(format!("Failed to execute git: {}", e)))
+}
+
 impl Capability for GitDiffTool {
     fn name(&self) -> &'static str {
         "git_diff"
@@ -135,6 +174,14 @@ impl Capability for GitDiffTool {
 
 #[async_trait::async_trait]
 impl ToolCapability for GitDiffTool {
+    fn description(&self) -> &'static str {
+        "Show git changes"
+    }
+
+    fn usage(&self) -> &'static str {
+        "{\"a\": \"git_diff\", \"i\": {\"path\": \"<file>\"}}"
+    }
+
     async fn execute(
         &self,
         _ctx: &RuntimeContext,
@@ -146,21 +193,40 @@ impl ToolCapability for GitDiffTool {
             .and_then(|v| v.as_str())
             .map(|s| s.to_string());
 
-        let mut cmd = Command::new("git");
-        cmd.arg("diff");
-        
-        if let Some(p) = path {
-            cmd.arg(p);
+        if let Some(ref p) = path {
+            let full = std::env::current_dir().unwrap_or_default().join(expand_tilde(p));
+            if let Err(denied) = self.secret_guard.check("git_diff", &full) {
+                return Ok(ToolResult::Error {
+                    message: denied.to_string(),
+                    code: Some("SECRET_FILE".to_string()),
+                    retryable: false,
+                });
+            }
         }
+        let scope: Vec<&str> = path.as_deref().into_iter().collect();
 
-        let output = cmd
-            .output()
-            .await
-            .map_err(|e| ToolError::new(format!("Failed to execute git: {}", e)))?;
+        // Changed files the guard denies are excluded, like git_commit's drafts
+        let mut withheld = Vec::new();
+        let root = git_output(&["rev-parse", "--show-toplevel"]).await?;
+        if root.status.success() {
+            let root = PathBuf::from(String::from_utf8_lossy(&root.stdout).trim());
+            let names = git_output(&[&["diff", "--name-only", "--"][..], &scope[..]].concat()).await?;
+            for name in String::from_utf8_lossy(&names.stdout).lines().filter(|line| !line.is_empty()) {
+                if self.secret_guard.check("git_diff", &root.join(name)).is_err() {
+                    withheld.push(name.to_string());
+                }
+            }
+        }
+        let excludes: Vec<String> = withheld.iter().map(|name| format!(":(exclude,top){}", name)).collect();
+        let mut args = vec!["diff", "--"];
+        args.extend(&scope);
+        args.extend(excludes.iter().map(String::as_str));
+
+        let output = git_output(&args).await?;
 
         if output.status.success() {
             let diff = String::from_utf8_lossy(&output.stdout);
-            let result = if diff.trim().is_empty() {
+            let mut result = if diff.trim().is_empty() {
                 "No changes detected.".to_string()
             } else {
                 // Limit diff output size
@@ -171,6 +237,12 @@ impl ToolCapability for GitDiffTool {
                 }
                 result
             };
+            if !withheld.is_empty() {
+                result.push_str(&format!(
+                    "\n[Also changed, contents withheld as secret files: {}]",
+                    withheld.join(", ")
+                ));
+            }
 
             Ok(ToolResult::Success {
                 output: result,
@@ -219,4 +291,18 @@ mod tests {
         let result = tool.execute(&RuntimeContext::new(), call).await;
         assert!(matches!(result, Ok(_) | Err(_)));
     }
+
+    #[tokio::test]
+    async fn test_git_diff_refuses_secret_files() {
+        let tool = GitDiffTool::new();
+        let call = ToolCall {
+            name: "git_diff".to_string(),
+            arguments: serde_json::json!({"path": ".env"}),
+            working_dir: None,
+            timeout_secs: None,
+        };
+
+        let result = tool.execute(&RuntimeContext::new(), call).await.unwrap();
+        assert!(matches!(result, ToolResult::Error { code: Some(ref c), .. } if c == "SECRET_FILE"));
+    }
 }
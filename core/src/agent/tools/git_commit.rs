@@ -0,0 +1,339 @@
+//! Git Commit Tool - Commit with a message written by the worker model
+//!
+//! `draft` asks the worker model for a Conventional Commits message for the
+//! pending change; it reads the diff only. `commit` stages the change and
+//! commits it with the message it is given. Only `commit` needs approval,
+//! so the approval prompt shows the message that will be used.
+//! `mylm commit` uses the same helpers outside an agent session.
+//!
+//! The change is the listed `files`; without files it is what is staged,
+//! or every change (new files included) when nothing is staged. `commit`
+//! never stages implicitly: with nothing staged it needs the files listed,
+//! so the approval prompt shows exactly what goes in. Files the secret
+//! guard denies are left out of the diff the model sees.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use tokio::process::Command;
+
+use crate::agent::runtime::core::{Capability, RuntimeContext, ToolCapability, ToolError};
+use crate::agent::runtime::governance::SecretGuard;
+use crate::agent::tools::parse_args;
+use crate::agent::types::events::ToolResult;
+use crate::agent::types::intents::ToolCall;
+use crate::provider::LlmClient;
+
+/// Diff text sent to the model; longer diffs are cut
+const MAX_DIFF_CHARS: usize = 16_000;
+
+const MESSAGE_INSTRUCTIONS: &str = "Write a git commit message for the diff below, in the Conventional Commits format: \
+a subject line `type(scope): summary` (type is one of feat, fix, docs, style, refactor, perf, test, build, ci, chore; \
+scope is optional; imperative mood, at most 72 characters, no trailing period), then, only if the change needs \
+explaining, a blank line and a short body wrapped at 72 columns saying what changed and why. \
+Reply with the message only.\n\n";
+
+fn default_action() -> String {
+    "draft".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitArgs {
+    #[serde(default = "default_action")]
+    action: String,
+    #[serde(default)]
+    files: Vec<String>,
+    #[serde(default)]
+    message: Option<String>,
+}
+
+/// Tool for committing changes with a generated message
+pub struct GitCommitTool {
+    llm: Arc<LlmClient>,
+    secret_guard: Arc<SecretGuard>,
+}
+
+impl GitCommitTool {
+    /// Draft messages with `llm` (the worker model)
+    pub fn new(llm: Arc<LlmClient>) -> Self {
+        Self {
+            llm,
+            secret_guard: Arc::new(SecretGuard::new()),
+        }
+    }
+
+    /// Keep files the secret guard denies out of drafted diffs
+    pub fn with_secret_guard(mut self, guard: Arc<SecretGuard>) -> Self {
+        self.secret_guard = guard;
+        self
+    }
+
+    fn error(message: String, code: &str) -> ToolResult {
+        ToolResult::Error {
+            message,
+            code: Some(code.to_string()),
+            retryable: false,
+        }
+    }
+}
+
+/// Run git; stdout, or stderr as the error. Exit code 1 counts as success
+/// when `diff_status` is set, as `git diff --no-index` uses it for "differs".
+async fn git(args: &[&str], diff_status: bool) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute git: {}", e))?;
+    let ok = output.status.success() || (diff_status && output.status.code() == Some(1));
+    if !ok {
+        return Err(format!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// `args` followed by `--` and `files`
+fn with_files<'a>(args: &[&'a str], files: &'a [String]) -> Vec<&'a str> {
+    let mut all = args.to_vec();
+    all.push("--");
+    all.extend(files.iter().map(String::as_str));
+    all
+}
+
+fn lines(output: &str) -> Vec<String> {
+    output.lines().filter(|line| !line.is_empty()).map(str::to_string).collect()
+}
+
+/// The change `files` describe, by paths relative to the repository root
+struct Change {
+    /// The staged change, diffed against the index
+    staged: bool,
+    tracked: Vec<String>,
+    untracked: Vec<String>,
+}
+
+impl Change {
+    async fn of(files: &[String]) -> Result<Self, String> {
+        if files.is_empty() {
+            let staged = lines(&git(&["diff", "--cached", "--name-only"], false).await?);
+            if !staged.is_empty() {
+                return Ok(Self {
+                    staged: true,
+                    tracked: staged,
+                    untracked: Vec::new(),
+                });
+            }
+        }
+        let tracked = lines(&git(&with_files(&["diff", "HEAD", "--name-only"], files), false).await?);
+        let untracked_args = ["ls-files", "--others", "--exclude-standard", "--full-name"];
+        let untracked = lines(&git(&with_files(&untracked_args, files), false).await?);
+        if tracked.is_empty() && untracked.is_empty() {
+            return Err("Nothing to commit".to_string());
+        }
+        Ok(Self {
+            staged: false,
+            tracked,
+            untracked,
+        })
+    }
+}
+
+/// `path`, relative to the repository root, as a pathspec from the working
+/// directory `prefix` (what `git rev-parse --show-prefix` prints)
+fn pathspec(prefix: &str, path: &str) -> String {
+    match path.strip_prefix(prefix) {
+        Some(relative) if !relative.is_empty() => relative.to_string(),
+        _ => format!(":(top){}", path),
+    }
+}
+
+/// Diff of the change `files` describe (see the module docs), without
+/// touching the index
+///
+/// Files `guard` denies are named but their contents left out, so a
+/// changed `.env` never reaches the model.
+pub async fn pending_diff(files: &[String], guard: &SecretGuard) -> Result<String, String> {
+    let root = PathBuf::from(git(&["rev-parse", "--show-toplevel"], false).await?.trim());
+    let change = Change::of(files).await?;
+    let base = if change.staged { "--cached" } else { "HEAD" };
+    let mut diff = String::new();
+    let mut withheld = Vec::new();
+    for path in &change.tracked {
+        if guard.check("git_commit", &root.join(path)).is_err() {
+            withheld.push(path.as_str());
+            continue;
+        }
+        diff.push_str(&git(&["diff", base, "--", &format!(":(top){}", path)], false).await?);
+    }
+    for path in &change.untracked {
+        let full = root.join(path);
+        if guard.check("git_commit", &full).is_err() {
+            withheld.push(path.as_str());
+            continue;
+        }
+        let full = full.to_string_lossy();
+        diff.push_str(&git(&["diff", "--no-index", "--", "/dev/null", &full], true).await?);
+    }
+    if !withheld.is_empty() {
+        diff.push_str(&format!(
+            "\n[Also changed, contents withheld as secret files: {}]\n",
+            withheld.join(", ")
+        ));
+    }
+    Ok(diff)
+}
+
+/// Files to pass to `commit` for the change `files` describe
+///
+/// Empty when `files` is empty and something is staged; otherwise every
+/// changed file, so what gets committed is listed up front.
+pub async fn commit_files(files: &[String]) -> Result<Vec<String>, String> {
+    if !files.is_empty() {
+        return Ok(files.to_vec());
+    }
+    let change = Change::of(files).await?;
+    if change.staged {
+        return Ok(Vec::new());
+    }
+    let prefix = git(&["rev-parse", "--show-prefix"], false).await?;
+    Ok(change
+        .tracked
+        .iter()
+        .chain(&change.untracked)
+        .map(|path| pathspec(prefix.trim(), path))
+        .collect())
+}
+
+/// Ask `llm` for a Conventional Commits message describing `diff`
+pub async fn draft_message(llm: &LlmClient, diff: &str) -> Result<String, String> {
+    let diff = match diff.char_indices().nth(MAX_DIFF_CHARS) {
+        Some((cut, _)) => format!("{}\n[... diff truncated]", &diff[..cut]),
+        None => diff.to_string(),
+    };
+    let response = llm
+        .complete(&format!("{}{}", MESSAGE_INSTRUCTIONS, diff))
+        .await
+        .map_err(|e| format!("Could not draft a commit message: {}", e))?;
+    let message = clean_message(&response.content());
+    if message.is_empty() {
+        return Err("The model returned an empty commit message".to_string());
+    }
+    Ok(message)
+}
+
+/// The message without code fences or surrounding blank lines
+fn clean_message(reply: &str) -> String {
+    let lines: Vec<&str> = reply.trim().lines().filter(|line| !line.trim_start().starts_with("```")).collect();
+    lines.join("\n").trim().to_string()
+}
+
+/// Stage `files` and commit them, or commit what is staged when `files` is
+/// empty; returns the new commit's one-line summary and file stats
+///
+/// Nothing is staged implicitly: with no files and an empty index the
+/// commit is refused and the changed files are listed.
+pub async fn commit(files: &[String], message: &str) -> Result<String, String> {
+    if !files.is_empty() {
+        git(&with_files(&["add"], files), false).await?;
+    } else if git(&["diff", "--cached", "--name-only"], false).await?.trim().is_empty() {
+        let changed = commit_files(files).await?;
+        return Err(format!(
+            "Nothing is staged. List the files to commit in 'files': {}",
+            changed.join(", ")
+        ));
+    }
+    git(&["commit", "-q", "-m", message], false).await?;
+    git(&["show", "--stat", "--oneline", "--no-color", "HEAD"], false).await
+}
+
+impl Capability for GitCommitTool {
+    fn name(&self) -> &'static str {
+        "git_commit"
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolCapability for GitCommitTool {
+    fn description(&self) -> &'static str {
+        "Commit changes with a Conventional Commits message. 'draft' writes a message from the diff of the listed files (default: what is staged, or every change); then 'commit' with that message, edited if needed, stages the files and commits"
+    }
+
+    fn usage(&self) -> &'static str {
+        r#"Draft: {"a": "git_commit", "i": {"action": "draft", "files": ["src/parser.rs"]}} | Commit: {"a": "git_commit", "i": {"action": "commit", "files": ["src/parser.rs"], "message": "fix(parser): handle empty input"}}"#
+    }
+
+    async fn execute(&self, _ctx: &RuntimeContext, call: ToolCall) -> Result<ToolResult, ToolError> {
+        let args: CommitArgs = parse_args(&call.arguments)?;
+        if !matches!(args.action.as_str(), "draft" | "commit") {
+            return Err(ToolError::new(format!("Unknown action '{}'. Use draft or commit", args.action)));
+        }
+        if args.action == "commit" {
+            let Some(message) = args.message.as_deref().map(str::trim).filter(|m| !m.is_empty()) else {
+                return Ok(Self::error(
+                    "A message is required to commit; draft one with {\"action\": \"draft\"} first".to_string(),
+                    "MISSING_MESSAGE",
+                ));
+            };
+            return Ok(match commit(&args.files, message).await {
+                Ok(summary) => ToolResult::Success {
+                    output: format!("Committed:\n{}", summary),
+                    structured: Some(serde_json::json!({ "message": message })),
+                },
+                Err(e) => Self::error(e, "GIT_ERROR"),
+            });
+        }
+
+        let diff = match pending_diff(&args.files, &self.secret_guard).await {
+            Ok(diff) => diff,
+            Err(e) => return Ok(Self::error(e, "GIT_ERROR")),
+        };
+        let files = match commit_files(&args.files).await {
+            Ok(files) => files,
+            Err(e) => return Ok(Self::error(e, "GIT_ERROR")),
+        };
+        let message = match draft_message(&self.llm, &diff).await {
+            Ok(message) => message,
+            Err(e) => return Ok(Self::error(e, "LLM_ERROR")),
+        };
+        Ok(ToolResult::Success {
+            output: format!(
+                "Drafted commit message:\n\n{}\n\nCommit with {{\"action\": \"commit\", \"message\": <this or an edited message>, \"files\": {}}}",
+                message,
+                serde_json::to_string(&files).unwrap_or_default()
+            ),
+            structured: Some(serde_json::json!({ "message": message, "files": files })),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_message() {
+        assert_eq!(
+            clean_message("```\nfix(parser): handle empty input\n\nReturn None instead of panicking.\n```\n"),
+            "fix(parser): handle empty input\n\nReturn None instead of panicking."
+        );
+        assert_eq!(clean_message("  feat: add commit tool  "), "feat: add commit tool");
+        assert_eq!(
+            with_files(&["diff", "HEAD"], &["a.rs".to_string()]),
+            vec!["diff", "HEAD", "--", "a.rs"]
+        );
+    }
+
+    #[test]
+    fn test_pathspec_from_subdirectory() {
+        assert_eq!(pathspec("", "src/main.rs"), "src/main.rs");
+        assert_eq!(pathspec("src/", "src/main.rs"), "main.rs");
+        assert_eq!(pathspec("src/", "README.md"), ":(top)README.md");
+        assert_eq!(pathspec("src/", "srcs/lib.rs"), ":(top)srcs/lib.rs");
+        assert_eq!(lines("a.rs\n\nb.rs\n"), vec!["a.rs", "b.rs"]);
+    }
+}
@@ -0,0 +1,305 @@
+//! HTTP Tool
+//!
+//! Sends GET, POST and PUT requests with headers and JSON bodies, so API
+//! debugging does not need curl and shell quoting. Only hosts on the
+//! profile's `http_permissions.allowed_hosts` (or the project overlay's
+//! `[http]` section) can be reached; redirects are held to the same list.
+//!
+//! # Usage
+//!
+//! - `{"method": "GET", "url": "https://api.github.com/repos/rust-lang/rust"}`
+//! - `{"method": "POST", "url": "http://localhost:8080/items", "headers": {"Authorization": "Bearer x"}, "body": {"name": "a"}}`
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::{Method, Url};
+use serde::Deserialize;
+
+use crate::agent::runtime::core::{Capability, RuntimeContext, ToolCapability, ToolError};
+use crate::agent::runtime::governance::HostAllowlist;
+use crate::agent::types::events::ToolResult;
+use crate::agent::types::intents::ToolCall;
+use crate::config::ProfileConfig;
+
+use super::parse_args;
+
+/// Default request timeout
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+/// Longest timeout a call may ask for
+const MAX_TIMEOUT_SECS: u64 = 300;
+/// Response body characters returned to the model
+const MAX_BODY_CHARS: usize = 20_000;
+/// Redirects followed before giving up
+const MAX_REDIRECTS: usize = 5;
+/// Response headers worth showing the model
+const SHOWN_HEADERS: &[&str] = &["content-type", "content-length", "location", "retry-after", "www-authenticate"];
+
+#[derive(Debug, Deserialize)]
+struct HttpArgs {
+    #[serde(default = "default_method")]
+    method: String,
+    url: String,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    /// JSON value sent as `application/json`, or a string sent as-is
+    #[serde(default)]
+    body: Option<serde_json::Value>,
+    #[serde(default)]
+    timeout_secs: Option<u64>,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+/// HTTP request tool limited to allowlisted hosts
+pub struct HttpTool {
+    allowlist: Arc<HostAllowlist>,
+    client: reqwest::Client,
+}
+
+impl HttpTool {
+    /// Create a tool that may only reach hosts on `allowlist`
+    pub fn new(allowlist: HostAllowlist) -> Self {
+        let allowlist = Arc::new(allowlist);
+        let redirect_allowlist = Arc::clone(&allowlist);
+        let client = reqwest::Client::builder()
+            .user_agent("mylm-assistant/0.1")
+            .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= MAX_REDIRECTS {
+                    attempt.error("too many redirects")
+                } else if redirect_allowlist.allows(attempt.url()) {
+                    attempt.follow()
+                } else {
+                    let url = attempt.url().to_string();
+                    attempt.error(format!("redirect to {} is outside the allowed hosts", url))
+                }
+            }))
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
+        Self { allowlist, client }
+    }
+
+    /// Hosts from `profile` plus the project overlay above the working directory
+    pub fn for_profile(profile: &ProfileConfig) -> Self {
+        let cwd = std::env::current_dir().unwrap_or_default();
+        Self::new(HostAllowlist::for_profile(profile, &cwd))
+    }
+
+    /// Whether any host can be reached at all
+    pub fn is_enabled(&self) -> bool {
+        !self.allowlist.is_empty()
+    }
+
+    fn reject(message: String, code: &str) -> ToolResult {
+        ToolResult::Error {
+            message,
+            code: Some(code.to_string()),
+            retryable: false,
+        }
+    }
+
+    async fn send(&self, args: HttpArgs) -> Result<ToolResult, ToolError> {
+        let method = match args.method.to_uppercase().as_str() {
+            "GET" => Method::GET,
+            "POST" => Method::POST,
+            "PUT" => Method::PUT,
+            other => {
+                return Ok(Self::reject(
+                    format!("Unsupported method {}. Use GET, POST or PUT", other),
+                    "INVALID_METHOD",
+                ))
+            }
+        };
+        let url = match Url::parse(&args.url) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => url,
+            Ok(url) => return Ok(Self::reject(format!("Unsupported scheme '{}'", url.scheme()), "INVALID_URL")),
+            Err(e) => return Ok(Self::reject(format!("Invalid URL '{}': {}", args.url, e), "INVALID_URL")),
+        };
+        if !self.allowlist.allows(&url) {
+            let allowed = self.allowlist.entries();
+            return Ok(Self::reject(
+                format!(
+                    "Host '{}' is not allowed. Allowed hosts: {}. Ask the user to add it to http_permissions.allowed_hosts.",
+                    url.host_str().unwrap_or_default(),
+                    if allowed.is_empty() { "none".to_string() } else { allowed.join(", ") }
+                ),
+                "HOST_NOT_ALLOWED",
+            ));
+        }
+
+        let timeout = args.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS).clamp(1, MAX_TIMEOUT_SECS);
+        let mut request = self
+            .client
+            .request(method.clone(), url.clone())
+            .timeout(Duration::from_secs(timeout));
+        for (name, value) in &args.headers {
+            request = request.header(name, value);
+        }
+        request = match args.body {
+            None | Some(serde_json::Value::Null) => request,
+            Some(serde_json::Value::String(text)) => request.body(text),
+            Some(json) => request.json(&json),
+        };
+
+        crate::info_log!("[HTTP_TOOL] {} {}", method, url);
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => {
+                return Ok(ToolResult::Error {
+                    message: format!("{} {} failed: {}", method, url, e),
+                    code: Some("HTTP_ERROR".to_string()),
+                    retryable: e.is_timeout() || e.is_connect(),
+                })
+            }
+        };
+
+        let status = response.status();
+        let mut output = format!("{} {}\nHTTP {}\n", method, response.url(), status);
+        for name in SHOWN_HEADERS {
+            if let Some(value) = response.headers().get(*name).and_then(|v| v.to_str().ok()) {
+                output.push_str(&format!("{}: {}\n", name, value));
+            }
+        }
+        let body = response
+            .text()
+            .await
+            .map_err(|e| ToolError::new(format!("Failed to read response body: {}", e)))?;
+        output.push('\n');
+        output.push_str(&format_body(&body));
+
+        Ok(ToolResult::Success {
+            output,
+            structured: Some(serde_json::json!({ "status": status.as_u16() })),
+        })
+    }
+}
+
+/// Pretty-print JSON bodies and cap the length
+fn format_body(body: &str) -> String {
+    let pretty = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|json| serde_json::to_string_pretty(&json).ok())
+        .unwrap_or_else(|| body.to_string());
+    let total = pretty.chars().count();
+    if total <= MAX_BODY_CHARS {
+        return pretty;
+    }
+    let mut truncated: String = pretty.chars().take(MAX_BODY_CHARS).collect();
+    truncated.push_str(&format!("\n... [truncated, {} of {} characters shown]", MAX_BODY_CHARS, total));
+    truncated
+}
+
+impl Capability for HttpTool {
+    fn name(&self) -> &'static str {
+        "http"
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolCapability for HttpTool {
+    fn description(&self) -> &'static str {
+        "Send a GET, POST or PUT request with headers and a JSON body to an allowlisted host; returns status, key headers and the body"
+    }
+
+    fn usage(&self) -> &'static str {
+        r#"GET: {"a": "http", "i": {"url": "https://api.github.com/repos/owner/repo"}} | POST: {"a": "http", "i": {"method": "POST", "url": "http://localhost:8080/items", "headers": {"Authorization": "Bearer TOKEN"}, "body": {"name": "x"}}}"#
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &RuntimeContext,
+        call: ToolCall,
+    ) -> Result<ToolResult, ToolError> {
+        let args: HttpArgs = parse_args(&call.arguments)?;
+        self.send(args).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::HttpPermissions;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn tool(hosts: &[&str]) -> HttpTool {
+        let mut allowlist = HostAllowlist::new();
+        allowlist.add(&HttpPermissions {
+            allowed_hosts: hosts.iter().map(|s| s.to_string()).collect(),
+        });
+        HttpTool::new(allowlist)
+    }
+
+    async fn run(tool: &HttpTool, args: serde_json::Value) -> ToolResult {
+        tool.execute(&RuntimeContext::new(), ToolCall::new("http", args)).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_disallowed_host_is_refused() {
+        let tool = tool(&["api.github.com"]);
+        let result = run(&tool, serde_json::json!({"url": "https://example.com/"})).await;
+        match result {
+            ToolResult::Error { code, message, .. } => {
+                assert_eq!(code.as_deref(), Some("HOST_NOT_ALLOWED"));
+                assert!(message.contains("api.github.com"));
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        let result = run(&tool, serde_json::json!({"method": "DELETE", "url": "https://api.github.com/"})).await;
+        assert!(matches!(result, ToolResult::Error { code: Some(ref c), .. } if c == "INVALID_METHOD"));
+    }
+
+    #[tokio::test]
+    async fn test_post_json_to_local_server() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 4096];
+            let n = socket.read(&mut buf).await.unwrap();
+            let request = String::from_utf8_lossy(&buf[..n]).to_string();
+            let body = r#"{"ok":true}"#;
+            let response = format!(
+                "HTTP/1.1 201 Created\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            request
+        });
+
+        let tool = tool(&[&format!("127.0.0.1:{}", port)]);
+        let result = run(
+            &tool,
+            serde_json::json!({
+                "method": "post",
+                "url": format!("http://127.0.0.1:{}/items", port),
+                "headers": {"X-Token": "abc"},
+                "body": {"name": "widget"}
+            }),
+        )
+        .await;
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /items"));
+        assert!(request.to_lowercase().contains("x-token: abc"));
+        assert!(request.contains(r#"{"name":"widget"}"#));
+        match result {
+            ToolResult::Success { output, .. } => {
+                assert!(output.contains("HTTP 201 Created"));
+                assert!(output.contains("\"ok\": true"));
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_long_bodies_are_truncated() {
+        let body = "x".repeat(MAX_BODY_CHARS + 10);
+        let formatted = format_body(&body);
+        assert!(formatted.ends_with(&format!("[truncated, {} of {} characters shown]", MAX_BODY_CHARS, MAX_BODY_CHARS + 10)));
+    }
+}
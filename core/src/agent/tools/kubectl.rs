@@ -0,0 +1,346 @@
+//! Kubectl tool - structured Kubernetes operations
+//!
+//! Like the docker tool, calls name an action instead of passing a raw
+//! command line: get/describe/logs/events/contexts only read cluster state and
+//! run without approval, while apply/delete/scale/rollout_restart/exec change
+//! it and go through the approval capability. Names are validated and options
+//! are passed as `--flag=value`, so no argument can be read as another flag.
+//!
+//! Secrets are never read: `get` and `describe` refuse the `secrets`
+//! resource in any spelling, so neither a listing nor a `--output=yaml`
+//! dump can carry their values into the conversation.
+
+use crate::agent::runtime::core::{Capability, RuntimeContext, ToolCapability, ToolError};
+use crate::agent::tools::cli::{clip, exec_command, run_program, validate_name};
+use crate::agent::tools::{expand_tilde, parse_args};
+use crate::agent::types::events::ToolResult;
+use crate::agent::types::intents::ToolCall;
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Default and largest number of log lines returned
+const DEFAULT_LOG_LINES: usize = 100;
+const MAX_LOG_LINES: usize = 2000;
+/// Time limits for queries and for changes
+const QUERY_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_RUN_TIMEOUT_SECS: u64 = 120;
+const MAX_RUN_TIMEOUT_SECS: u64 = 900;
+
+/// Actions that only read cluster state; everything else needs approval
+pub const READ_ONLY_ACTIONS: &[&str] = &["get", "describe", "logs", "events", "contexts"];
+
+#[derive(Debug, Deserialize)]
+struct KubectlArgs {
+    action: String,
+    /// Resource type, e.g. "pods", "deployment", "svc"
+    #[serde(default)]
+    resource: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    /// Defaults to the namespace of the current context
+    #[serde(default)]
+    namespace: Option<String>,
+    /// Defaults to the current context
+    #[serde(default)]
+    context: Option<String>,
+    /// `get`/`events`: look in every namespace
+    #[serde(default)]
+    all_namespaces: bool,
+    /// Label selector, e.g. "app=api,tier!=cache"
+    #[serde(default)]
+    selector: Option<String>,
+    /// `get`: wide (default), yaml, json or name
+    #[serde(default)]
+    output: Option<String>,
+    /// `logs`/`exec`: container within the pod
+    #[serde(default)]
+    container: Option<String>,
+    /// `logs`: number of lines from the end
+    #[serde(default)]
+    tail: Option<usize>,
+    /// `logs`: only lines newer than this (e.g. "10m")
+    #[serde(default)]
+    since: Option<String>,
+    /// `logs`: the previous, crashed container instance
+    #[serde(default)]
+    previous: bool,
+    /// `apply`: manifest file or directory
+    #[serde(default)]
+    file: Option<String>,
+    /// `scale`: desired replica count
+    #[serde(default)]
+    replicas: Option<u32>,
+    /// `exec`: argv list, or a string split like a shell would
+    #[serde(default)]
+    command: Option<serde_json::Value>,
+    #[serde(default)]
+    timeout_secs: Option<u64>,
+}
+
+/// Tool for inspecting and changing Kubernetes resources
+#[derive(Debug, Clone)]
+pub struct KubectlTool {
+    binary: PathBuf,
+}
+
+impl KubectlTool {
+    /// Use `kubectl` from PATH, if it is installed
+    pub fn detect() -> Option<Self> {
+        let path = std::env::var_os("PATH")?;
+        std::env::split_paths(&path)
+            .map(|dir| dir.join("kubectl"))
+            .find(|candidate| candidate.is_file())
+            .map(Self::with_binary)
+    }
+
+    /// Use a specific kubectl binary
+    pub fn with_binary(binary: impl Into<PathBuf>) -> Self {
+        Self { binary: binary.into() }
+    }
+}
+
+fn required<'a>(value: &'a Option<String>, kind: &str, action: &str) -> Result<&'a str, ToolError> {
+    let value = value
+        .as_deref()
+        .ok_or_else(|| ToolError::new(format!("'{}' is required for {}", kind, action)))?;
+    validate_name(kind, value)?;
+    Ok(value)
+}
+
+/// Whether `resource` (possibly a comma list, `kind/name` or `kind.group`)
+/// names Kubernetes secrets
+fn is_secret_resource(resource: &str) -> bool {
+    resource.split(',').any(|item| {
+        let kind = item.split('/').next().unwrap_or_default();
+        let kind = kind.split('.').next().unwrap_or_default();
+        kind.eq_ignore_ascii_case("secret") || kind.eq_ignore_ascii_case("secrets")
+    })
+}
+
+/// `resource/name`, with `default_resource` when no resource was given
+fn target(args: &KubectlArgs, default_resource: &str) -> Result<String, ToolError> {
+    let name = required(&args.name, "name", &args.action)?;
+    let resource = args.resource.as_deref().unwrap_or(default_resource);
+    validate_name("resource", resource)?;
+    Ok(format!("{}/{}", resource, name))
+}
+
+/// The kubectl argv for a call
+fn build_args(args: &KubectlArgs) -> Result<Vec<String>, ToolError> {
+    let mut argv: Vec<String> = Vec::new();
+    if let Some(context) = &args.context {
+        validate_name("context", context)?;
+        argv.push(format!("--context={}", context));
+    }
+    let all_namespaces = args.all_namespaces && matches!(args.action.as_str(), "get" | "events");
+    if all_namespaces {
+        argv.push("--all-namespaces".to_string());
+    } else if let Some(namespace) = &args.namespace {
+        validate_name("namespace", namespace)?;
+        argv.push(format!("--namespace={}", namespace));
+    }
+    let selector = match &args.selector {
+        Some(selector) if selector.starts_with('-') || selector.chars().any(char::is_whitespace) => {
+            return Err(ToolError::new(format!("Invalid selector '{}'", selector)))
+        }
+        Some(selector) => Some(format!("--selector={}", selector)),
+        None => None,
+    };
+
+    match args.action.as_str() {
+        "get" | "describe" => {
+            let resource = required(&args.resource, "resource", &args.action)?;
+            if is_secret_resource(resource) {
+                return Err(ToolError::new("Reading secrets is not allowed; ask the user for the value you need"));
+            }
+            argv.push(args.action.clone());
+            argv.push(resource.to_string());
+            if let Some(name) = &args.name {
+                validate_name("name", name)?;
+                argv.push(name.clone());
+            }
+            if args.action == "get" {
+                let output = args.output.as_deref().unwrap_or("wide");
+                if !matches!(output, "wide" | "yaml" | "json" | "name") {
+                    return Err(ToolError::new(format!("Unknown output '{}'. Use wide, yaml, json or name", output)));
+                }
+                argv.push(format!("--output={}", output));
+            }
+            argv.extend(selector);
+        }
+        "logs" => {
+            argv.push("logs".to_string());
+            argv.push(target(args, "pod")?);
+            let tail = args.tail.unwrap_or(DEFAULT_LOG_LINES).clamp(1, MAX_LOG_LINES);
+            argv.push(format!("--tail={}", tail));
+            if let Some(since) = &args.since {
+                argv.push(format!("--since={}", since));
+            }
+            if let Some(container) = &args.container {
+                validate_name("container", container)?;
+                argv.push(format!("--container={}", container));
+            }
+            if args.previous {
+                argv.push("--previous".to_string());
+            }
+        }
+        "events" => {
+            argv.extend(["get", "events", "--sort-by=.lastTimestamp"].map(String::from));
+            if let Some(name) = &args.name {
+                validate_name("name", name)?;
+                argv.push(format!("--field-selector=involvedObject.name={}", name));
+            }
+        }
+        "contexts" => argv = vec!["config".to_string(), "get-contexts".to_string()],
+        "apply" => {
+            let file = args
+                .file
+                .as_deref()
+                .ok_or_else(|| ToolError::new("'file' is required for apply"))?;
+            argv.extend(["apply".to_string(), format!("--filename={}", expand_tilde(file))]);
+        }
+        "delete" => {
+            argv.extend([
+                "delete".to_string(),
+                required(&args.resource, "resource", &args.action)?.to_string(),
+                required(&args.name, "name", &args.action)?.to_string(),
+            ]);
+        }
+        "scale" => {
+            let replicas = args
+                .replicas
+                .ok_or_else(|| ToolError::new("'replicas' is required for scale"))?;
+            argv.extend(["scale".to_string(), target(args, "deployment")?, format!("--replicas={}", replicas)]);
+        }
+        "rollout_restart" => {
+            argv.extend(["rollout".to_string(), "restart".to_string(), target(args, "deployment")?]);
+        }
+        "exec" => {
+            argv.extend(["exec".to_string(), required(&args.name, "name", &args.action)?.to_string()]);
+            if let Some(container) = &args.container {
+                validate_name("container", container)?;
+                argv.push(format!("--container={}", container));
+            }
+            argv.push("--".to_string());
+            argv.extend(exec_command(args.command.as_ref())?);
+        }
+        other => {
+            return Err(ToolError::new(format!(
+                "Unknown action '{}'. Use get, describe, logs, events, contexts, apply, delete, scale, rollout_restart or exec",
+                other
+            )))
+        }
+    }
+    Ok(argv)
+}
+
+impl Capability for KubectlTool {
+    fn name(&self) -> &'static str {
+        "kubectl"
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolCapability for KubectlTool {
+    fn description(&self) -> &'static str {
+        "Work with Kubernetes instead of running kubectl in the shell; uses the current context unless one is given. Actions: get, describe, logs, events, contexts (no approval); apply, delete, scale, rollout_restart, exec (need approval)"
+    }
+
+    fn usage(&self) -> &'static str {
+        r#"Get: {"a": "kubectl", "i": {"action": "get", "resource": "pods", "namespace": "api", "selector": "app=web"}} | Logs: {"a": "kubectl", "i": {"action": "logs", "name": "web-7d9f", "tail": 200, "previous": true}} | Events: {"a": "kubectl", "i": {"action": "events", "name": "web-7d9f"}} | Scale: {"a": "kubectl", "i": {"action": "scale", "resource": "deployment", "name": "web", "replicas": 3}}"#
+    }
+
+    async fn execute(
+        &self,
+        _ctx: &RuntimeContext,
+        call: ToolCall,
+    ) -> Result<ToolResult, ToolError> {
+        let args: KubectlArgs = parse_args(&call.arguments)?;
+        let argv = build_args(&args)?;
+        let timeout = if READ_ONLY_ACTIONS.contains(&args.action.as_str()) {
+            QUERY_TIMEOUT
+        } else {
+            Duration::from_secs(args.timeout_secs.unwrap_or(DEFAULT_RUN_TIMEOUT_SECS).clamp(1, MAX_RUN_TIMEOUT_SECS))
+        };
+        let (ok, stdout, stderr) = run_program(&self.binary, &argv, timeout).await?;
+
+        if !ok {
+            let detail = if stderr.trim().is_empty() { stdout.trim() } else { stderr.trim() };
+            let (message, code) = if args.action == "exec" {
+                (format!("Command in pod exited with an error:\n{}", clip(detail)), "EXEC_FAILED")
+            } else {
+                (format!("kubectl {} failed: {}", args.action, clip(detail)), "KUBECTL_ERROR")
+            };
+            return Ok(ToolResult::Error {
+                message,
+                code: Some(code.to_string()),
+                retryable: false,
+            });
+        }
+
+        let mut output = clip(stdout.trim_end());
+        if output.is_empty() {
+            output = match args.action.as_str() {
+                "get" | "events" => "No resources found".to_string(),
+                _ => "Done".to_string(),
+            };
+        }
+        // kubectl reports warnings (deprecations, dry runs) on stderr even on success
+        if !stderr.trim().is_empty() && args.action != "logs" {
+            output.push_str(&format!("\n[stderr]\n{}", clip(stderr.trim_end())));
+        }
+        Ok(ToolResult::Success {
+            output,
+            structured: Some(serde_json::json!({
+                "action": args.action,
+                "args": argv,
+            })),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(value: serde_json::Value) -> KubectlArgs {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_actions_build_argv() {
+        let get = build_args(&args(serde_json::json!({"action": "get", "resource": "pods", "namespace": "payments", "selector": "app=api"}))).unwrap();
+        assert_eq!(get, vec!["--namespace=payments", "get", "pods", "--output=wide", "--selector=app=api"]);
+
+        let logs = build_args(&args(serde_json::json!({"action": "logs", "name": "api", "resource": "deployment", "tail": 9999, "previous": true}))).unwrap();
+        assert_eq!(logs, vec!["logs", "deployment/api", "--tail=2000", "--previous"]);
+
+        let scale = build_args(&args(serde_json::json!({"action": "scale", "name": "web", "replicas": 3, "context": "prod"}))).unwrap();
+        assert_eq!(scale, vec!["--context=prod", "scale", "deployment/web", "--replicas=3"]);
+
+        let exec = build_args(&args(serde_json::json!({"action": "exec", "name": "db-0", "command": "psql -c 'select 1'"}))).unwrap();
+        assert_eq!(exec, vec!["exec", "db-0", "--", "psql", "-c", "select 1"]);
+    }
+
+    #[test]
+    fn test_rejects_flags_and_unknown_actions() {
+        assert!(build_args(&args(serde_json::json!({"action": "get", "resource": "--raw=/"}))).is_err());
+        assert!(build_args(&args(serde_json::json!({"action": "get", "resource": "pods", "selector": "-A"}))).is_err());
+        assert!(build_args(&args(serde_json::json!({"action": "delete", "resource": "pod"}))).is_err());
+        assert!(build_args(&args(serde_json::json!({"action": "get", "resource": "pods", "output": "go-template={{.}}"}))).is_err());
+        assert!(build_args(&args(serde_json::json!({"action": "proxy"}))).is_err());
+    }
+
+    #[test]
+    fn test_refuses_secrets() {
+        for resource in ["secrets", "Secret", "secret/db-password", "pods,secrets", "secrets.v1"] {
+            for action in ["get", "describe"] {
+                let call = serde_json::json!({"action": action, "resource": resource, "output": "yaml"});
+                assert!(build_args(&args(call)).is_err(), "{} {}", action, resource);
+            }
+        }
+        assert!(build_args(&args(serde_json::json!({"action": "get", "resource": "secretstores"}))).is_ok());
+        assert!(build_args(&args(serde_json::json!({"action": "get", "resource": "configmaps", "output": "yaml"}))).is_ok());
+    }
+}
@@ -6,6 +6,7 @@ use crate::agent::runtime::core::ToolError;
 use crate::agent::types::intents::ToolCall;
 use crate::agent::types::events::ToolResult;
 use crate::agent::tools::expand_tilde;
+use crate::util::format_size;
 
 use std::path::Path;
 
@@ -105,6 +106,14 @@ impl Capability for ListFilesTool {
 
 #[async_trait::async_trait]
 impl ToolCapability for ListFilesTool {
+    fn description(&self) -> &'static str {
+        "List directory contents"
+    }
+
+    fn usage(&self) -> &'static str {
+        "{\"a\": \"list_files\", \"i\": {\"path\": \"<path>\"}}"
+    }
+
     async fn execute(
         &self,
         _ctx: &RuntimeContext,
@@ -123,24 +132,6 @@ impl ToolCapability for ListFilesTool {
     }
 }
 
-/// Format file size in human-readable form
-fn format_size(size: u64) -> String {
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
-    let mut size = size as f64;
-    let mut unit_idx = 0;
-
-    while size >= 1024.0 && unit_idx < UNITS.len() - 1 {
-        size /= 1024.0;
-        unit_idx += 1;
-    }
-
-    if unit_idx == 0 {
-        format!("{:.0} {}", size, UNITS[unit_idx])
-    } else {
-        format!("{:.1} {}", size, UNITS[unit_idx])
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
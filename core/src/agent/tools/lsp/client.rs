@@ -0,0 +1,283 @@
+//! Minimal Language Server Protocol client
+//!
+//! Speaks JSON-RPC over a language server's stdin/stdout: requests are
+//! matched to responses by id, `publishDiagnostics` notifications are kept
+//! per document (and dropped when the document changes, so a caller never
+//! sees diagnostics for content it no longer has), and requests from the server (configuration, progress
+//! tokens) get an empty reply so it never waits on us.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::oneshot;
+
+type Pending = Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>;
+type Writer = Arc<tokio::sync::Mutex<ChildStdin>>;
+/// Latest diagnostics per document URI, with the document version they are for
+type Diagnostics = Arc<Mutex<HashMap<String, (Option<i64>, Vec<Value>)>>>;
+
+/// A running language server
+pub struct LspClient {
+    writer: Writer,
+    pending: Pending,
+    diagnostics: Diagnostics,
+    /// Open documents: URI -> (version, content)
+    opened: tokio::sync::Mutex<HashMap<String, (i32, String)>>,
+    next_id: AtomicI64,
+    timeout: Duration,
+    _child: Child,
+}
+
+impl LspClient {
+    /// Spawn `command` and run the initialize handshake for `root`
+    pub async fn start(command: &[String], root: &Path, timeout: Duration) -> Result<Self, String> {
+        let (program, args) = command.split_first().ok_or("Empty language server command")?;
+        let mut child = Command::new(program)
+            .args(args)
+            .current_dir(root)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {}", program, e))?;
+        let stdin = child.stdin.take().ok_or("Language server has no stdin")?;
+        let stdout = child.stdout.take().ok_or("Language server has no stdout")?;
+
+        let client = Self {
+            writer: Arc::new(tokio::sync::Mutex::new(stdin)),
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            diagnostics: Arc::new(Mutex::new(HashMap::new())),
+            opened: tokio::sync::Mutex::new(HashMap::new()),
+            next_id: AtomicI64::new(1),
+            timeout,
+            _child: child,
+        };
+        tokio::spawn(read_loop(
+            stdout,
+            Arc::clone(&client.writer),
+            Arc::clone(&client.pending),
+            Arc::clone(&client.diagnostics),
+        ));
+
+        let root_uri = path_to_uri(root);
+        client
+            .request(
+                "initialize",
+                json!({
+                    "processId": std::process::id(),
+                    "rootUri": root_uri,
+                    "workspaceFolders": [{ "uri": root_uri, "name": "root" }],
+                    "capabilities": {
+                        "textDocument": {
+                            "definition": { "linkSupport": true },
+                            "references": {},
+                            "hover": { "contentFormat": ["plaintext", "markdown"] },
+                            "publishDiagnostics": { "relatedInformation": false },
+                            "synchronization": { "didSave": false }
+                        },
+                        "workspace": { "configuration": true, "workspaceFolders": true },
+                        "window": { "workDoneProgress": true }
+                    }
+                }),
+            )
+            .await?;
+        client.notify("initialized", json!({})).await?;
+        Ok(client)
+    }
+
+    /// Send a request and wait for its result
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap_or_else(|e| e.into_inner()).insert(id, tx);
+        write_message(&self.writer, &json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })).await?;
+        match tokio::time::timeout(self.timeout, rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err("Language server exited".to_string()),
+            Err(_) => {
+                self.pending.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+                Err(format!("{} timed out after {}s (the server may still be indexing)", method, self.timeout.as_secs()))
+            }
+        }
+    }
+
+    pub async fn notify(&self, method: &str, params: Value) -> Result<(), String> {
+        write_message(&self.writer, &json!({ "jsonrpc": "2.0", "method": method, "params": params })).await
+    }
+
+    /// Make sure the server sees the current content of `path`; returns its
+    /// URI and the document version the server now has
+    pub async fn sync_document(&self, path: &Path) -> Result<(String, i32), String> {
+        let content = tokio::fs::read_to_string(path)
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let uri = path_to_uri(path);
+        let mut opened = self.opened.lock().await;
+        match opened.get_mut(&uri) {
+            Some((version, previous)) if *previous == content => return Ok((uri, *version)),
+            Some((version, previous)) => {
+                *version += 1;
+                *previous = content.clone();
+                self.clear_diagnostics(&uri);
+                self.notify(
+                    "textDocument/didChange",
+                    json!({
+                        "textDocument": { "uri": uri, "version": *version },
+                        "contentChanges": [{ "text": content }]
+                    }),
+                )
+                .await?;
+                Ok((uri, *version))
+            }
+            None => {
+                self.clear_diagnostics(&uri);
+                self.notify(
+                    "textDocument/didOpen",
+                    json!({
+                        "textDocument": {
+                            "uri": uri,
+                            "languageId": language_id(path),
+                            "version": 1,
+                            "text": content
+                        }
+                    }),
+                )
+                .await?;
+                opened.insert(uri.clone(), (1, content));
+                Ok((uri, 1))
+            }
+        }
+    }
+
+    fn clear_diagnostics(&self, uri: &str) {
+        self.diagnostics.lock().unwrap_or_else(|e| e.into_inner()).remove(uri);
+    }
+
+    /// Diagnostics published for `uri` at `version` or later; None until the
+    /// server sent any for the current content
+    pub fn diagnostics(&self, uri: &str, version: i32) -> Option<Vec<Value>> {
+        let diagnostics = self.diagnostics.lock().unwrap_or_else(|e| e.into_inner());
+        let (published, list) = diagnostics.get(uri)?;
+        // Servers that omit the version are trusted to publish for the latest content
+        published.is_none_or(|published| published >= i64::from(version)).then(|| list.clone())
+    }
+}
+
+async fn write_message(writer: &Writer, message: &Value) -> Result<(), String> {
+    let body = message.to_string();
+    let mut writer = writer.lock().await;
+    writer
+        .write_all(format!("Content-Length: {}\r\n\r\n{}", body.len(), body).as_bytes())
+        .await
+        .map_err(|e| format!("Failed to write to language server: {}", e))?;
+    writer.flush().await.map_err(|e| e.to_string())
+}
+
+/// Read one framed message; None at end of stream
+async fn read_message(reader: &mut BufReader<ChildStdout>) -> Option<Value> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await.ok()? == 0 {
+            return None;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(value) = line.strip_prefix("Content-Length:") {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let mut body = vec![0; length?];
+    reader.read_exact(&mut body).await.ok()?;
+    serde_json::from_slice(&body).ok()
+}
+
+async fn read_loop(
+    stdout: ChildStdout,
+    writer: Writer,
+    pending: Pending,
+    diagnostics: Diagnostics,
+) {
+    let mut reader = BufReader::new(stdout);
+    while let Some(message) = read_message(&mut reader).await {
+        let method = message.get("method").and_then(|m| m.as_str());
+        match (method, message.get("id")) {
+            // Response to one of our requests
+            (None, Some(id)) => {
+                let Some(id) = id.as_i64() else { continue };
+                let Some(tx) = pending.lock().unwrap_or_else(|e| e.into_inner()).remove(&id) else {
+                    continue;
+                };
+                let result = match message.get("error") {
+                    Some(error) => Err(error["message"].as_str().unwrap_or("Language server error").to_string()),
+                    None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                };
+                let _ = tx.send(result);
+            }
+            // Request from the server: answer so it does not block
+            (Some(method), Some(id)) => {
+                let result = match method {
+                    "workspace/configuration" => {
+                        let items = message["params"]["items"].as_array().map_or(0, |items| items.len());
+                        Value::Array(vec![Value::Null; items])
+                    }
+                    _ => Value::Null,
+                };
+                let _ = write_message(&writer, &json!({ "jsonrpc": "2.0", "id": id, "result": result })).await;
+            }
+            (Some("textDocument/publishDiagnostics"), None) => {
+                let params = &message["params"];
+                if let Some(uri) = params["uri"].as_str() {
+                    let list = params["diagnostics"].as_array().cloned().unwrap_or_default();
+                    let version = params["version"].as_i64();
+                    diagnostics.lock().unwrap_or_else(|e| e.into_inner()).insert(uri.to_string(), (version, list));
+                }
+            }
+            _ => {}
+        }
+    }
+    // Server gone: fail everything still waiting
+    for (_, tx) in pending.lock().unwrap_or_else(|e| e.into_inner()).drain() {
+        let _ = tx.send(Err("Language server exited".to_string()));
+    }
+}
+
+/// `file://` URI for an absolute path
+pub fn path_to_uri(path: &Path) -> String {
+    let encoded: Vec<String> = path
+        .to_string_lossy()
+        .split('/')
+        .map(|segment| urlencoding::encode(segment).into_owned())
+        .collect();
+    format!("file://{}", encoded.join("/"))
+}
+
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    urlencoding::decode(path).ok().map(|p| PathBuf::from(p.into_owned()))
+}
+
+fn language_id(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).unwrap_or_default() {
+        "rs" => "rust",
+        "go" => "go",
+        "py" | "pyi" => "python",
+        "ts" => "typescript",
+        "tsx" => "typescriptreact",
+        "js" | "mjs" | "cjs" => "javascript",
+        "jsx" => "javascriptreact",
+        "c" | "h" => "c",
+        "cc" | "cpp" | "cxx" | "hpp" | "hh" => "cpp",
+        "java" => "java",
+        _ => "plaintext",
+    }
+}
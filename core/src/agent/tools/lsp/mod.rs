@@ -0,0 +1,343 @@
+//! LSP tool - code navigation through the project's language server
+//!
+//! Gives the agent go-to-definition, find-references and diagnostics from a
+//! real language server (rust-analyzer, gopls, pyright, ...) instead of
+//! guessing with regex searches. The server is detected from the project's
+//! marker files, started on the first call and kept for the session; an
+//! idle session stops it (see `crate::idle`) and the next call restarts it.
+//!
+//! A language server can run project code (build scripts, proc macros,
+//! plugins), so the tool is off unless `features.lsp` is set, every call
+//! goes through approval, and files matched by the secret file patterns or
+//! outside the project are refused.
+
+pub mod client;
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::agent::runtime::core::{Capability, RuntimeContext, ToolCapability, ToolError};
+use crate::agent::runtime::governance::SecretGuard;
+use crate::agent::tools::{expand_tilde, parse_args};
+use crate::agent::types::events::ToolResult;
+use crate::agent::types::intents::ToolCall;
+
+pub use client::LspClient;
+
+/// Servers tried in order: project marker files, then the server command
+const SERVERS: &[(&[&str], &[&str])] = &[
+    (&["Cargo.toml"], &["rust-analyzer"]),
+    (&["go.mod"], &["gopls"]),
+    (&["tsconfig.json", "package.json"], &["typescript-language-server", "--stdio"]),
+    (&["pyproject.toml", "setup.py", "requirements.txt"], &["pyright-langserver", "--stdio"]),
+    (&["pyproject.toml", "setup.py", "requirements.txt"], &["pylsp"]),
+    (&["compile_commands.json", "CMakeLists.txt"], &["clangd"]),
+];
+/// How long one request may take; the first ones wait for indexing
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// How long to wait for a server to publish diagnostics for new content
+const DIAGNOSTICS_WAIT: Duration = Duration::from_secs(5);
+/// Locations listed per answer
+const MAX_LOCATIONS: usize = 100;
+
+#[derive(Debug, Deserialize)]
+struct LspArgs {
+    action: String,
+    path: String,
+    /// 1-based line
+    #[serde(default)]
+    line: Option<u32>,
+    /// 1-based column
+    #[serde(default)]
+    column: Option<u32>,
+    /// `references`: include the declaration itself
+    #[serde(default = "default_true")]
+    include_declaration: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Tool exposing a language server to the agent
+pub struct LspTool {
+    command: Vec<String>,
+    root: PathBuf,
+    server: Arc<LspServer>,
+    secret_guard: Arc<SecretGuard>,
+}
+
+impl LspTool {
+    /// Find a language server for the project at `root` that is installed
+    pub fn detect(root: &Path) -> Option<Self> {
+        SERVERS
+            .iter()
+            .find(|(markers, command)| markers.iter().any(|m| root.join(m).exists()) && on_path(command[0]))
+            .map(|(_, command)| Self::with_command(command.iter().map(|s| s.to_string()).collect(), root))
+    }
+
+    /// Use a specific server command for the project at `root`
+    pub fn with_command(command: Vec<String>, root: &Path) -> Self {
+        Self {
+            command,
+            root: root.to_path_buf(),
+            server: Arc::new(LspServer::default()),
+            secret_guard: Arc::new(SecretGuard::new()),
+        }
+    }
+
+    /// Refuse files matched by `guard`, and leave their lines out of results
+    pub fn with_secret_guard(mut self, guard: Arc<SecretGuard>) -> Self {
+        self.secret_guard = guard;
+        self
+    }
+
+    /// Name of the server program
+    pub fn server(&self) -> &str {
+        self.command.first().map(String::as_str).unwrap_or_default()
+    }
+
+    /// The server process, for stopping it while the session is idle
+    pub fn idle_handle(&self) -> Arc<LspServer> {
+        Arc::clone(&self.server)
+    }
+
+    /// The running server, started on first use
+    async fn client(&self) -> Result<Arc<LspClient>, String> {
+        let mut slot = self.server.0.lock().await;
+        if let Some(client) = slot.as_ref() {
+            return Ok(Arc::clone(client));
+        }
+        crate::info_log!("[LSP] Starting {} for {}", self.server(), self.root.display());
+        let client = Arc::new(LspClient::start(&self.command, &self.root, REQUEST_TIMEOUT).await?);
+        *slot = Some(Arc::clone(&client));
+        Ok(client)
+    }
+
+    /// `path` within the project, if it is an existing file the secret guard allows
+    fn resolve(&self, path: &str) -> Result<PathBuf, ToolError> {
+        let path = PathBuf::from(expand_tilde(path));
+        let path = if path.is_absolute() { path } else { self.root.join(path) };
+        let resolved = path
+            .canonicalize()
+            .map_err(|_| ToolError::new(format!("No such file: {}", path.display())))?;
+        let root = self.root.canonicalize().unwrap_or_else(|_| self.root.clone());
+        if !resolved.starts_with(&root) || !resolved.is_file() {
+            return Err(ToolError::new(format!("{} is not a file in the project", path.display())));
+        }
+        self.secret_guard
+            .check("lsp", &resolved)
+            .map_err(|denied| ToolError::new(denied.to_string()))?;
+        Ok(resolved)
+    }
+
+    /// `path:line:col  source line`, relative to the project root
+    fn format_location(&self, location: &Value) -> Option<String> {
+        // Location or LocationLink
+        let uri = location.get("uri").or_else(|| location.get("targetUri"))?.as_str()?;
+        let range = location.get("range").or_else(|| location.get("targetSelectionRange"))?;
+        let line = range["start"]["line"].as_u64()? as usize;
+        let column = range["start"]["character"].as_u64()? as usize;
+        let path = client::uri_to_path(uri)?;
+        let text = if self.secret_guard.matched_pattern(&path).is_some() {
+            String::new()
+        } else {
+            std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| content.lines().nth(line).map(|l| l.trim().to_string()))
+                .unwrap_or_default()
+        };
+        let shown = path.strip_prefix(&self.root).unwrap_or(&path);
+        Some(format!("{}:{}:{}  {}", shown.display(), line + 1, column + 1, text))
+    }
+}
+
+/// The language server of an `LspTool` once it was started
+#[derive(Default)]
+pub struct LspServer(tokio::sync::Mutex<Option<Arc<LspClient>>>);
+
+impl LspServer {
+    /// Stop the server; it is killed once a request still using it is done
+    pub fn stop(&self) -> bool {
+        // A call that is starting the server keeps it
+        self.0.try_lock().is_ok_and(|mut slot| slot.take().is_some())
+    }
+}
+
+fn on_path(program: &str) -> bool {
+    std::env::var_os("PATH").is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+}
+
+/// Locations from a definition/references result (single, list or null)
+fn locations(result: &Value) -> Vec<Value> {
+    match result {
+        Value::Array(items) => items.clone(),
+        Value::Null => Vec::new(),
+        other => vec![other.clone()],
+    }
+}
+
+fn format_diagnostic(diagnostic: &Value) -> String {
+    let severity = match diagnostic["severity"].as_u64() {
+        Some(1) => "error",
+        Some(2) => "warning",
+        Some(3) => "info",
+        _ => "hint",
+    };
+    let start = &diagnostic["range"]["start"];
+    let source = diagnostic["source"].as_str().map(|s| format!(" [{}]", s)).unwrap_or_default();
+    format!(
+        "{}:{} {}: {}{}",
+        start["line"].as_u64().unwrap_or(0) + 1,
+        start["character"].as_u64().unwrap_or(0) + 1,
+        severity,
+        diagnostic["message"].as_str().unwrap_or_default().lines().next().unwrap_or_default(),
+        source
+    )
+}
+
+impl Capability for LspTool {
+    fn name(&self) -> &'static str {
+        "lsp"
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolCapability for LspTool {
+    fn description(&self) -> &'static str {
+        "Precise code navigation from the project's language server; prefer it over code_search when refactoring. Actions: definition, references (need line and 1-based column), diagnostics"
+    }
+
+    fn usage(&self) -> &'static str {
+        r#"Definition: {"a": "lsp", "i": {"action": "definition", "path": "src/lib.rs", "line": 42, "column": 17}} | References: {"a": "lsp", "i": {"action": "references", "path": "src/lib.rs", "line": 42, "column": 17}} | Diagnostics: {"a": "lsp", "i": {"action": "diagnostics", "path": "src/lib.rs"}}"#
+    }
+
+    async fn execute(&self, _ctx: &RuntimeContext, call: ToolCall) -> Result<ToolResult, ToolError> {
+        let args: LspArgs = parse_args(&call.arguments)?;
+        let path = self.resolve(&args.path)?;
+        let error = |message: String| ToolResult::Error {
+            message,
+            code: Some("LSP_ERROR".to_string()),
+            retryable: true,
+        };
+        let client = match self.client().await {
+            Ok(client) => client,
+            Err(e) => return Ok(error(e)),
+        };
+        let (uri, version) = match client.sync_document(&path).await {
+            Ok(synced) => synced,
+            Err(e) => return Ok(error(e)),
+        };
+
+        let position = || -> Result<Value, ToolError> {
+            match (args.line, args.column) {
+                (Some(line), Some(column)) if line > 0 && column > 0 => {
+                    Ok(json!({ "line": line - 1, "character": column - 1 }))
+                }
+                _ => Err(ToolError::new(format!("'line' and 'column' (1-based) are required for {}", args.action))),
+            }
+        };
+
+        let (method, params) = match args.action.as_str() {
+            "definition" => (
+                "textDocument/definition",
+                json!({ "textDocument": { "uri": uri }, "position": position()? }),
+            ),
+            "references" => (
+                "textDocument/references",
+                json!({
+                    "textDocument": { "uri": uri },
+                    "position": position()?,
+                    "context": { "includeDeclaration": args.include_declaration }
+                }),
+            ),
+            "diagnostics" => {
+                let started = Instant::now();
+                let diagnostics = loop {
+                    match client.diagnostics(&uri, version) {
+                        Some(found) => break found,
+                        None if started.elapsed() >= DIAGNOSTICS_WAIT => break Vec::new(),
+                        None => tokio::time::sleep(Duration::from_millis(200)).await,
+                    }
+                };
+                let output = if diagnostics.is_empty() {
+                    format!("No diagnostics for {}", args.path)
+                } else {
+                    diagnostics.iter().map(format_diagnostic).collect::<Vec<_>>().join("\n")
+                };
+                return Ok(ToolResult::Success {
+                    output,
+                    structured: Some(json!({ "diagnostics": diagnostics })),
+                });
+            }
+            other => {
+                return Err(ToolError::new(format!(
+                    "Unknown action '{}'; use definition, references or diagnostics",
+                    other
+                )))
+            }
+        };
+
+        let result = match client.request(method, params).await {
+            Ok(result) => result,
+            Err(e) => return Ok(error(e)),
+        };
+        let found = locations(&result);
+        let mut lines: Vec<String> = found.iter().take(MAX_LOCATIONS).filter_map(|l| self.format_location(l)).collect();
+        if found.len() > MAX_LOCATIONS {
+            lines.push(format!("... and {} more", found.len() - MAX_LOCATIONS));
+        }
+        let output = if lines.is_empty() {
+            format!("No {} found", args.action)
+        } else {
+            lines.join("\n")
+        };
+        Ok(ToolResult::Success {
+            output,
+            structured: Some(json!({ "locations": found.into_iter().take(MAX_LOCATIONS).collect::<Vec<_>>() })),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_location_and_diagnostic() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        let file = dir.path().join("src").join("my lib.rs");
+        std::fs::write(&file, "fn main() {}\n    pub fn parse() {}\n").unwrap();
+        let tool = LspTool::with_command(vec!["rust-analyzer".to_string()], dir.path());
+
+        let uri = client::path_to_uri(&file);
+        assert!(uri.ends_with("/src/my%20lib.rs"));
+        assert_eq!(client::uri_to_path(&uri).unwrap(), file);
+
+        let location = json!({ "uri": uri, "range": { "start": { "line": 1, "character": 11 }, "end": { "line": 1, "character": 16 } } });
+        assert_eq!(locations(&location).len(), 1);
+        assert_eq!(tool.format_location(&location).unwrap(), "src/my lib.rs:2:12  pub fn parse() {}");
+        assert!(locations(&Value::Null).is_empty());
+
+        let diagnostic = json!({
+            "range": { "start": { "line": 4, "character": 0 } },
+            "severity": 1,
+            "message": "mismatched types\nexpected u32",
+            "source": "rustc"
+        });
+        assert_eq!(format_diagnostic(&diagnostic), "5:1 error: mismatched types [rustc]");
+
+        std::fs::write(dir.path().join(".env"), "TOKEN=x\n").unwrap();
+        assert!(tool.resolve("src/my lib.rs").is_ok());
+        assert!(tool.resolve(".env").is_err());
+        assert!(tool.resolve("/etc/hostname").is_err());
+        assert!(tool.resolve("src/missing.rs").is_err());
+        // Never started, so there is nothing to stop
+        assert!(!tool.idle_handle().stop());
+    }
+}
@@ -18,23 +18,24 @@ use crate::agent::runtime::core::RuntimeContext;
 use crate::agent::runtime::core::ToolError;
 use crate::agent::types::intents::ToolCall;
 use crate::agent::types::events::ToolResult;
-use crate::memory::store::{VectorStore, MemoryType};
+use crate::memory::backend::MemoryBackend;
+use crate::memory::store::MemoryType;
 use serde::Deserialize;
 
 /// Memory tool for storing and retrieving memories
 pub struct MemoryTool {
-    store: Arc<VectorStore>,
+    store: Arc<dyn MemoryBackend>,
     search_limit: usize,
 }
 
 impl MemoryTool {
     /// Create a new memory tool with default search limit
-    pub fn new(store: Arc<VectorStore>) -> Self {
+    pub fn new(store: Arc<dyn MemoryBackend>) -> Self {
         Self { store, search_limit: 5 }
     }
     
     /// Create a new memory tool with custom search limit
-    pub fn with_search_limit(store: Arc<VectorStore>, limit: usize) -> Self {
+    pub fn with_search_limit(store: Arc<dyn MemoryBackend>, limit: usize) -> Self {
         Self { store, search_limit: limit }
     }
     
@@ -302,6 +303,14 @@ impl Capability for MemoryTool {
 
 #[async_trait::async_trait]
 impl ToolCapability for MemoryTool {
+    fn description(&self) -> &'static str {
+        "Store or search long-term memories. CRITICAL: Use EXACT JSON format shown"
+    }
+
+    fn usage(&self) -> &'static str {
+        "Add: {\"a\": \"memory\", \"i\": {\"add\": \"User prefers dark mode\"}} | Search: {\"a\": \"memory\", \"i\": {\"search\": \"dark mode preference\"}}"
+    }
+
     async fn execute(
         &self,
         _ctx: &RuntimeContext,
@@ -4,12 +4,19 @@
 //! actions on behalf of the agent. All tools implement the `ToolCapability` trait.
 
 pub mod shell;
+pub mod command_preview;
 pub mod read_file;
 pub mod write_file;
+pub mod apply_patch;
+pub mod patch_queue;
+pub mod undo_journal;
 pub mod edit_csv;
 pub mod list_files;
 pub mod git;
+pub mod git_commit;
 pub mod web_search;
+pub mod docs;
+pub mod run_tests;
 pub mod memory;
 pub mod notes;
 pub mod delegate;
@@ -17,15 +24,35 @@ pub mod scratchpad;
 pub mod worker_shell;
 pub mod commonboard;
 pub mod search_files;
+pub mod code_search;
+pub mod code_outline;
+pub mod code_index;
 pub mod document_workers;
+pub mod config;
+pub mod http;
+pub mod cli;
+pub mod docker;
+pub mod kubectl;
+pub mod lsp;
+pub mod annotate_terminal;
+pub mod plan;
+pub mod read_only;
+pub mod schema;
 
 pub use shell::ShellTool;
+pub use command_preview::CommandPreview;
 pub use read_file::ReadFileTool;
 pub use write_file::WriteFileTool;
+pub use apply_patch::ApplyPatchTool;
+pub use patch_queue::{Changeset, PatchQueue};
+pub use undo_journal::{UndoJournal, UndoTurn};
 pub use edit_csv::EditCsvTool;
 pub use list_files::ListFilesTool;
 pub use git::{GitStatusTool, GitLogTool, GitDiffTool};
+pub use git_commit::GitCommitTool;
 pub use web_search::{WebSearchTool, WebSearchConfig, SearchProvider};
+pub use docs::DocsTool;
+pub use run_tests::{RunTestsTool, TestOutcome, TestSummary};
 pub use memory::MemoryTool;
 pub use notes::NotesTool;
 pub use delegate::DelegateTool;
@@ -33,27 +60,45 @@ pub use scratchpad::{ScratchpadTool, create_shared_scratchpad, SharedScratchpad}
 pub use worker_shell::{WorkerShellTool, WorkerShellPermissions, EscalationRequest, EscalationResponse};
 pub use commonboard::CommonboardTool;
 pub use search_files::SearchFilesTool;
+pub use code_search::CodeSearchTool;
+pub use code_outline::CodeOutlineTool;
+pub use code_index::CodeIndexTool;
 pub use document_workers::{QueryFileTool, QueryChunkTool, CloseFileTool, ChunkWorkerRegistry};
+pub use config::{ConfigTool, LiveConfig};
+pub use http::HttpTool;
+pub use docker::DockerTool;
+pub use kubectl::KubectlTool;
+pub use lsp::LspTool;
+pub use annotate_terminal::{AnnotateTerminalTool, AnnotationSeverity, TerminalAnnotation};
+pub use plan::PlanTool;
 
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::path::Path;
 use crate::agent::runtime::core::{Capability, ToolCapability, RuntimeContext, ToolError};
 use crate::agent::runtime::core::terminal::{TerminalExecutor, DefaultTerminalExecutor};
+use crate::agent::runtime::governance::SecretGuard;
+use crate::config::CommandLimits;
 use crate::agent::types::intents::ToolCall;
 use crate::agent::types::events::ToolResult;
-use crate::memory::store::VectorStore;
+use crate::memory::backend::MemoryBackend;
 
 /// Tool registry that combines all available tools
 pub struct ToolRegistry {
     shell: ShellTool,
     read_file: ReadFileTool,
     write_file: WriteFileTool,
+    apply_patch: ApplyPatchTool,
     edit_csv: EditCsvTool,
     list_files: ListFilesTool,
+    code_search: CodeSearchTool,
+    code_outline: CodeOutlineTool,
     git_status: GitStatusTool,
     git_log: GitLogTool,
     git_diff: GitDiffTool,
     web_search: WebSearchTool,
+    docs: DocsTool,
+    run_tests: RunTestsTool,
     memory: Option<MemoryTool>,
     notes: NotesTool,
     terminal: Arc<dyn TerminalExecutor>,
@@ -69,6 +114,30 @@ pub struct ToolRegistry {
     query_file: Option<QueryFileTool>,
     query_chunk_worker: Option<QueryChunkTool>,
     close_file: Option<CloseFileTool>,
+    /// Config tool for editing whitelisted settings (optional)
+    config: Option<ConfigTool>,
+    /// HTTP request tool for allowlisted hosts (optional)
+    http: Option<HttpTool>,
+    /// Container tool, when docker is installed (optional)
+    docker: Option<DockerTool>,
+    /// Kubernetes tool, when kubectl is installed (optional)
+    kubectl: Option<KubectlTool>,
+    /// Language server navigation, when one is installed for the project (optional)
+    lsp: Option<LspTool>,
+    /// Semantic search over the repository's embedding index (optional)
+    code_index: Option<CodeIndexTool>,
+    /// Annotations on the frontend's terminal pane, when there is one (optional)
+    annotate_terminal: Option<AnnotateTerminalTool>,
+    /// Session plan with step tracking (optional)
+    plan: Option<PlanTool>,
+    /// Commits with drafted messages, in a git repository (optional)
+    git_commit: Option<GitCommitTool>,
+    /// Secret-file deny-list shared by the file-reading tools
+    secret_guard: Arc<SecretGuard>,
+    /// Only these tools may be described and run (None = all)
+    allowlist: Option<HashSet<String>>,
+    /// Refuse calls that would change files or system state
+    read_only: bool,
 }
 
 impl ToolRegistry {
@@ -84,12 +153,17 @@ impl ToolRegistry {
             shell: ShellTool::new(),
             read_file: ReadFileTool::simple(),
             write_file: WriteFileTool::new(),
+            apply_patch: ApplyPatchTool::new(),
             edit_csv: EditCsvTool::new(),
             list_files: ListFilesTool::new(),
+            code_search: CodeSearchTool::new(),
+            code_outline: CodeOutlineTool::new(),
             git_status: GitStatusTool::new(),
             git_log: GitLogTool::new(),
             git_diff: GitDiffTool::new(),
             web_search: WebSearchTool::new(),
+            docs: DocsTool::new(),
+            run_tests: RunTestsTool::new(),
             memory: None,
             notes: NotesTool::new(),
             terminal: Arc::new(DefaultTerminalExecutor::new()),
@@ -100,6 +174,18 @@ impl ToolRegistry {
             query_file: None,
             query_chunk_worker: None,
             close_file: None,
+            config: None,
+            http: None,
+            docker: None,
+            kubectl: None,
+            lsp: None,
+            code_index: None,
+            annotate_terminal: None,
+            plan: None,
+            git_commit: None,
+            secret_guard: Arc::new(SecretGuard::new()),
+            allowlist: None,
+            read_only: false,
         }
     }
     
@@ -145,18 +231,132 @@ impl ToolRegistry {
         self
     }
     
-    /// Enable memory tool with a VectorStore (default search limit)
-    pub fn with_memory(mut self, store: Arc<VectorStore>) -> Self {
+    /// Enable memory tool with a memory backend (default search limit)
+    pub fn with_memory(mut self, store: Arc<dyn MemoryBackend>) -> Self {
         self.memory = Some(MemoryTool::new(store));
         self
     }
     
-    /// Enable memory tool with a VectorStore and custom search limit
-    pub fn with_memory_and_limit(mut self, store: Arc<VectorStore>, search_limit: usize) -> Self {
+    /// Enable memory tool with a memory backend and custom search limit
+    pub fn with_memory_and_limit(mut self, store: Arc<dyn MemoryBackend>, search_limit: usize) -> Self {
         self.memory = Some(MemoryTool::with_search_limit(store, search_limit));
         self
     }
     
+    /// Enable the config tool for editing whitelisted settings
+    pub fn with_config_tool(mut self, config: ConfigTool) -> Self {
+        self.config = Some(config);
+        self
+    }
+    
+    /// Enable the http tool; it only reaches the hosts on its allowlist
+    pub fn with_http(mut self, http: HttpTool) -> Self {
+        self.http = Some(http);
+        self
+    }
+    
+    /// Enable the docker tool
+    pub fn with_docker(mut self, docker: DockerTool) -> Self {
+        self.docker = Some(docker);
+        self
+    }
+    
+    /// Enable the kubectl tool; mutating actions still need approval
+    pub fn with_kubectl(mut self, kubectl: KubectlTool) -> Self {
+        self.kubectl = Some(kubectl);
+        self
+    }
+    
+    /// Enable the lsp tool for code navigation
+    pub fn with_lsp(mut self, lsp: LspTool) -> Self {
+        self.lsp = Some(lsp);
+        self
+    }
+    
+    /// Enable semantic code search over the repository
+    pub fn with_code_index(mut self, code_index: CodeIndexTool) -> Self {
+        self.code_index = Some(code_index);
+        self
+    }
+    
+    /// Let the agent annotate the frontend's terminal pane
+    pub fn with_annotate_terminal(mut self, tool: AnnotateTerminalTool) -> Self {
+        self.annotate_terminal = Some(tool);
+        self
+    }
+    
+    /// Let the agent write and track a plan for multi-step tasks
+    pub fn with_plan(mut self, plan: PlanTool) -> Self {
+        self.plan = Some(plan);
+        self
+    }
+    
+    /// Enable git_commit, drafting messages with the worker model
+    pub fn with_git_commit(mut self, tool: GitCommitTool) -> Self {
+        self.git_commit = Some(tool.with_secret_guard(Arc::clone(&self.secret_guard)));
+        self
+    }
+    
+    /// Hide and refuse every tool not named in `tools`
+    ///
+    /// Aliases resolve first, so allowing `read_file` also allows `cat`.
+    pub fn with_allowlist(mut self, tools: &[String]) -> Self {
+        self.allowlist = Some(tools.iter().cloned().collect());
+        self
+    }
+    
+    /// Hide tools that only change things and refuse calls that would
+    /// (see `read_only`)
+    pub fn with_read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    fn is_allowed(&self, name: &str) -> bool {
+        self.allowlist.as_ref().is_none_or(|allowed| allowed.contains(name))
+            && !(self.read_only && read_only::HIDDEN_TOOLS.contains(&name))
+    }
+    
+    /// Apply time, CPU, memory and output limits to shell commands
+    pub fn with_command_limits(mut self, limits: CommandLimits) -> Self {
+        self.shell = self.shell.with_limits(limits);
+        self
+    }
+    
+    /// Use `guard` for every tool that reads or edits file contents
+    pub fn with_secret_guard(mut self, guard: SecretGuard) -> Self {
+        let guard = Arc::new(guard);
+        self.read_file = self.read_file.with_secret_guard(Arc::clone(&guard));
+        self.query_file = self.query_file.map(|tool| tool.with_secret_guard(Arc::clone(&guard)));
+        self.code_search = self.code_search.with_secret_guard(Arc::clone(&guard));
+        self.code_outline = self.code_outline.with_secret_guard(Arc::clone(&guard));
+        self.apply_patch = self.apply_patch.with_secret_guard(Arc::clone(&guard));
+        self.edit_csv = self.edit_csv.with_secret_guard(Arc::clone(&guard));
+        self.git_diff = self.git_diff.with_secret_guard(Arc::clone(&guard));
+        self.git_commit = self.git_commit.map(|tool| tool.with_secret_guard(Arc::clone(&guard)));
+        self.secret_guard = guard;
+        self
+    }
+    
+    /// Stage file edits in `queue` while it has a changeset open, and
+    /// keep shell commands from writing around it
+    pub fn with_patch_queue(mut self, queue: Arc<PatchQueue>) -> Self {
+        self.shell = self.shell.with_patch_queue(Arc::clone(&queue));
+        self.read_file = self.read_file.with_patch_queue(Arc::clone(&queue));
+        self.write_file = self.write_file.with_patch_queue(Arc::clone(&queue));
+        self.edit_csv = self.edit_csv.with_patch_queue(Arc::clone(&queue));
+        self.apply_patch = self.apply_patch.with_patch_queue(queue);
+        self
+    }
+    
+    /// Save the previous content of files the agent writes in `journal`
+    pub fn with_undo_journal(mut self, journal: Arc<UndoJournal>) -> Self {
+        self.write_file = self.write_file.with_undo_journal(Arc::clone(&journal));
+        self.edit_csv = self.edit_csv.with_undo_journal(Arc::clone(&journal));
+        self.apply_patch = self.apply_patch.with_undo_journal(journal);
+        self
+    }
+    
     /// Enable document worker tools with registry and LLM client
     pub fn with_document_workers(
         mut self,
@@ -170,7 +370,7 @@ impl ToolRegistry {
             llm_client.clone(),
             worker_context_window,
             output_tx,
-        ));
+        ).with_secret_guard(Arc::clone(&self.secret_guard)));
         self.query_chunk_worker = Some(QueryChunkTool::new(Arc::clone(&registry)));
         self.close_file = Some(CloseFileTool::new(registry));
         self
@@ -183,16 +383,26 @@ impl ToolRegistry {
 
     /// Get a tool by name
     pub fn get(&self, name: &str) -> Option<&dyn ToolCapability> {
+        self.lookup(name).filter(|tool| self.is_allowed(tool.name()))
+    }
+
+    /// Resolve a tool or alias, ignoring the allowlist
+    fn lookup(&self, name: &str) -> Option<&dyn ToolCapability> {
         match name {
             "shell" => Some(&self.shell),
             "read_file" | "cat" => Some(&self.read_file),
             "write_file" => Some(&self.write_file),
+            "apply_patch" => Some(&self.apply_patch),
             "edit_csv" => Some(&self.edit_csv),
             "list_files" | "ls" | "list_dir" => Some(&self.list_files),
+            "code_search" | "grep" => Some(&self.code_search),
+            "code_outline" | "outline" => Some(&self.code_outline),
             "git_status" => Some(&self.git_status),
             "git_log" => Some(&self.git_log),
             "git_diff" => Some(&self.git_diff),
             "web_search" => Some(&self.web_search),
+            "docs" | "man" | "tldr" => Some(&self.docs),
+            "run_tests" => Some(&self.run_tests),
             "memory" => self.memory.as_ref().map(|m| m as &dyn ToolCapability),
             "notes" => Some(&self.notes),
             "delegate" => self.delegate.as_ref().map(|d| d.as_ref() as &dyn ToolCapability),
@@ -202,6 +412,15 @@ impl ToolRegistry {
             "query_file" => self.query_file.as_ref().map(|q| q as &dyn ToolCapability),
             "query_chunk_worker" => self.query_chunk_worker.as_ref().map(|q| q as &dyn ToolCapability),
             "close_file" => self.close_file.as_ref().map(|c| c as &dyn ToolCapability),
+            "config" => self.config.as_ref().map(|c| c as &dyn ToolCapability),
+            "http" => self.http.as_ref().map(|h| h as &dyn ToolCapability),
+            "docker" => self.docker.as_ref().map(|d| d as &dyn ToolCapability),
+            "kubectl" => self.kubectl.as_ref().map(|k| k as &dyn ToolCapability),
+            "lsp" => self.lsp.as_ref().map(|l| l as &dyn ToolCapability),
+            "code_search_semantic" => self.code_index.as_ref().map(|c| c as &dyn ToolCapability),
+            "annotate_terminal" => self.annotate_terminal.as_ref().map(|a| a as &dyn ToolCapability),
+            "plan" => self.plan.as_ref().map(|p| p as &dyn ToolCapability),
+            "git_commit" => self.git_commit.as_ref().map(|g| g as &dyn ToolCapability),
             _ => None,
         }
     }
@@ -217,12 +436,17 @@ impl ToolRegistry {
             "shell".to_string(),
             "read_file".to_string(),
             "write_file".to_string(),
+            "apply_patch".to_string(),
             "edit_csv".to_string(),
             "list_files".to_string(),
+            "code_search".to_string(),
+            "code_outline".to_string(),
             "git_status".to_string(),
             "git_log".to_string(),
             "git_diff".to_string(),
             "web_search".to_string(),
+            "docs".to_string(),
+            "run_tests".to_string(),
             "notes".to_string(),
         ];
         if self.memory.is_some() {
@@ -249,129 +473,49 @@ impl ToolRegistry {
         if self.close_file.is_some() {
             tools.push("close_file".to_string());
         }
-        tools
-    }
-
-    /// Get tool descriptions for prompt generation
-    pub fn descriptions(&self) -> Vec<ToolDescription> {
-        let mut descriptions = vec![
-            ToolDescription {
-                name: "shell",
-                description: "Execute or suggest shell commands",
-                usage: "Execute: {\"a\": \"shell\", \"i\": {\"command\": \"<cmd>\"}} | Suggest: {\"a\": \"shell\", \"i\": {\"command\": \"<cmd>\", \"mode\": \"suggest\"}}",
-            },
-            ToolDescription {
-                name: "read_file",
-                description: "Read file contents. DO NOT use this tool for files the user uploaded. Only use for quick, small code reading or specific line checks.",
-                usage: r#"Small file: {"a": "read_file", "i": {"path": "<path>"}} | Partial: {"a": "read_file", "i": {"path": "<path>", "line_offset": 1, "n_lines": 100}}"#,
-            },
-            ToolDescription {
-                name: "write_file",
-                description: "Write content to file",
-                usage: "{\"a\": \"write_file\", \"i\": {\"path\": \"<path>\", \"content\": \"<content>\"}}",
-            },
-            ToolDescription {
-                name: "edit_csv",
-                description: "Edit CSV files with structured operations (update, delete, insert, update_where)",
-                usage: "Update cell: {\"a\": \"edit_csv\", \"i\": {\"path\": \"<path>\", \"operation\": \"update\", \"row\": 1, \"column\": \"Name\", \"value\": \"New\"}} | Update where: {\"a\": \"edit_csv\", \"i\": {\"path\": \"<path>\", \"operation\": \"update_where\", \"where\": {\"column\": \"Status\", \"equals\": \"inactive\"}, \"column\": \"Status\", \"value\": \"active\"}}",
-            },
-            ToolDescription {
-                name: "list_files",
-                description: "List directory contents",
-                usage: "{\"a\": \"list_files\", \"i\": {\"path\": \"<path>\"}}",
-            },
-            ToolDescription {
-                name: "git_status",
-                description: "Show git working tree status",
-                usage: "{\"a\": \"git_status\"}",
-            },
-            ToolDescription {
-                name: "git_log",
-                description: "Show git commit history",
-                usage: "{\"a\": \"git_log\", \"i\": {\"limit\": 10}}",
-            },
-            ToolDescription {
-                name: "git_diff",
-                description: "Show git changes",
-                usage: "{\"a\": \"git_diff\", \"i\": {\"path\": \"<file>\"}}",
-            },
-            ToolDescription {
-                name: "web_search",
-                description: "Search the web for information",
-                usage: "{\"a\": \"web_search\", \"i\": {\"query\": \"<search>\"}}",
-            },
-            ToolDescription {
-                name: "notes",
-                description: "Access user's quick notes for context and reminders",
-                usage: "{\"a\": \"notes\", \"i\": {\"action\": \"read\"}} or {\"a\": \"notes\", \"i\": {\"action\": \"search\", \"query\": \"<search>\"}}",
-            },
-        ];
-        
-        if self.memory.is_some() {
-            descriptions.push(ToolDescription {
-                name: "memory",
-                description: "Store or search long-term memories. CRITICAL: Use EXACT JSON format shown",
-                usage: "Add: {\"a\": \"memory\", \"i\": {\"add\": \"User prefers dark mode\"}} | Search: {\"a\": \"memory\", \"i\": {\"search\": \"dark mode preference\"}}",
-            });
+        if self.config.is_some() {
+            tools.push("config".to_string());
         }
-        
-        if self.delegate.is_some() {
-            descriptions.push(ToolDescription {
-                name: "delegate",
-                description: "Spawn worker agents for parallel/independent tasks. USE FOR: large file analysis, batch processing, background tasks, parallel searches. Workers run independently with isolated shells",
-                usage: r#"Large file analysis: {"a": "delegate", "i": {"workers": [{"id": "analyzer", "objective": "Read src/main.rs and summarize key functions", "tools": ["read_file", "shell"]}]}} | Parallel tasks: {"a": "delegate", "i": {"workers": [{"id": "w1", "objective": "Find TODOs in src/", "tools": ["shell"], "allowed_commands": ["grep -r TODO src/"]}, {"id": "w2", "objective": "Find FIXMEs in src/", "tools": ["shell"], "allowed_commands": ["grep -r FIXME src/"]}]}}"#,
-            });
+        if self.http.is_some() {
+            tools.push("http".to_string());
         }
-        
-        if self.scratchpad.is_some() {
-            descriptions.push(ToolDescription {
-                name: "scratchpad",
-                description: "Agent-local persistent notes (survives pruning)",
-                usage: "{\"a\": \"scratchpad\", \"i\": {\"action\": \"append\", \"text\": \"note\"}} or {\"a\": \"scratchpad\", \"i\": {\"action\": \"list\"}}",
-            });
+        if self.docker.is_some() {
+            tools.push("docker".to_string());
         }
-        
-        if self.commonboard.is_some() {
-            descriptions.push(ToolDescription {
-                name: "commonboard",
-                description: "Inter-agent coordination (claims, progress, completion)",
-                usage: "{\"a\": \"commonboard\", \"i\": {\"action\": \"claim\", \"resource\": \"file.rs\"}} or {\"a\": \"commonboard\", \"i\": {\"action\": \"query\"}}",
-            });
+        if self.kubectl.is_some() {
+            tools.push("kubectl".to_string());
         }
-        
-        if self.search_files.is_some() {
-            descriptions.push(ToolDescription {
-                name: "search_files",
-                description: "Full-text search across indexed files",
-                usage: "{\"a\": \"search_files\", \"i\": {\"query\": \"function main\"}} or {\"a\": \"search_files\", \"i\": {\"query\": \"TODO\", \"path_filter\": \"src/\"}}",
-            });
+        if self.lsp.is_some() {
+            tools.push("lsp".to_string());
         }
-        
-        if self.query_file.is_some() {
-            descriptions.push(ToolDescription {
-                name: "query_file",
-                description: "ALWAYS use this tool to process user-uploaded files or large documents. It splits the document into chunks, spawns sandboxed LLM workers for each chunk to prevent overwhelming context, and returns a summary plus chunk IDs for follow-ups.",
-                usage: r#"Process file: {"a": "query_file", "i": {"file_path": "large_document.pdf", "prompt": "Summarize the key points"}}"#,
-            });
+        if self.code_index.is_some() {
+            tools.push("code_search_semantic".to_string());
         }
-        
-        if self.query_chunk_worker.is_some() {
-            descriptions.push(ToolDescription {
-                name: "query_chunk_worker",
-                description: "Send a query to a specific chunk worker after using query_file. Use this for follow-up questions about specific chunks",
-                usage: r#"Query specific chunk: {"a": "query_chunk_worker", "i": {"chunk_id": "document_chunk_0", "prompt": "What does this section say about error handling?"}}"#,
-            });
+        if self.annotate_terminal.is_some() {
+            tools.push("annotate_terminal".to_string());
         }
-        
-        if self.close_file.is_some() {
-            descriptions.push(ToolDescription {
-                name: "close_file",
-                description: "Clean up chunk workers for a file when done. Call this to free memory after finishing analysis",
-                usage: r#"Close file workers: {"a": "close_file", "i": {"file_name": "large_document.pdf"}}"#,
-            });
+        if self.plan.is_some() {
+            tools.push("plan".to_string());
+        }
+        if self.git_commit.is_some() {
+            tools.push("git_commit".to_string());
         }
-        
-        descriptions
+        tools.retain(|name| self.is_allowed(name));
+        tools
+    }
+
+    /// Get tool descriptions for prompt generation, as the tools declare them
+    pub fn descriptions(&self) -> Vec<ToolDescription> {
+        self.list()
+            .iter()
+            .filter_map(|name| self.get(name))
+            .map(|tool| ToolDescription {
+                name: tool.name(),
+                description: tool.description(),
+                usage: tool.usage(),
+                parameters: tool.schema(),
+            })
+            .collect()
     }
 }
 
@@ -389,13 +533,35 @@ impl Capability for ToolRegistry {
 
 #[async_trait::async_trait]
 impl ToolCapability for ToolRegistry {
+    fn uses_session_plan(&self, tool: &str) -> bool {
+        self.get(tool).is_some_and(|t| t.uses_session_plan(tool))
+    }
+
     async fn execute(
         &self,
         ctx: &RuntimeContext,
         call: ToolCall,
     ) -> Result<ToolResult, ToolError> {
-        match self.get(&call.name) {
-            Some(tool) => tool.execute(ctx, call).await,
+        match self.lookup(&call.name) {
+            Some(tool) if !self.is_allowed(tool.name()) => Ok(ToolResult::Error {
+                message: format!("Tool '{}' is not allowed in this session", call.name),
+                code: Some("TOOL_NOT_ALLOWED".to_string()),
+                retryable: false,
+            }),
+            Some(tool) => {
+                let refusal = self.read_only.then(|| read_only::refusal(tool.name(), &call.arguments)).flatten();
+                if let Some(reason) = refusal {
+                    return Ok(ToolResult::Error {
+                        message: format!("Read-only mode: {}", reason),
+                        code: Some("READ_ONLY".to_string()),
+                        retryable: false,
+                    });
+                }
+                match schema::validate_call(call) {
+                    Ok(call) => tool.execute(ctx, call).await,
+                    Err(invalid) => Ok(invalid),
+                }
+            }
             None => Ok(ToolResult::Error {
                 message: format!("Unknown tool: {}", call.name),
                 code: Some("UNKNOWN_TOOL".to_string()),
@@ -411,6 +577,8 @@ pub struct ToolDescription {
     pub name: &'static str,
     pub description: &'static str,
     pub usage: &'static str,
+    /// JSON Schema of the arguments
+    pub parameters: serde_json::Value,
 }
 
 impl ToolDescription {
@@ -195,6 +195,14 @@ impl Capability for NotesTool {
 
 #[async_trait::async_trait]
 impl ToolCapability for NotesTool {
+    fn description(&self) -> &'static str {
+        "Access user's quick notes for context and reminders"
+    }
+
+    fn usage(&self) -> &'static str {
+        "{\"a\": \"notes\", \"i\": {\"action\": \"read\"}} or {\"a\": \"notes\", \"i\": {\"action\": \"search\", \"query\": \"<search>\"}}"
+    }
+
     async fn execute(
         &self,
         _ctx: &RuntimeContext,
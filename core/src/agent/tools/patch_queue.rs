@@ -0,0 +1,592 @@
+//! Patch Queue - Stage file edits as a reviewable changeset
+//!
+//! While a changeset is open, `write_file` and `apply_patch` record their
+//! edits here instead of writing to disk (quilt/stgit style). Every user
+//! turn starts a new patch in the series, and `read_file` shows files with
+//! the queued edits applied so the agent keeps building on its own work.
+//! The user then reviews the series as a whole, reorders or drops patches,
+//! applies some or all of them, or exports it as a git patch series.
+//!
+//! Patches are stored as unified diffs and re-based on the current disk
+//! contents whenever the series changes, so a reorder or a partial apply
+//! either leaves every remaining patch applicable or is refused.
+//!
+//! Changesets are saved to `<data dir>/mylm/changesets/<name>.json` after
+//! every change, so an open queue survives restarts.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+
+use crate::agent::tools::apply_patch::{apply_hunks, parse_patch};
+use crate::util::slug;
+
+/// Lines of context around each change
+const CONTEXT_LINES: usize = 3;
+/// Longest patch title taken from a user message
+const MAX_TITLE_LEN: usize = 72;
+
+/// Change to one file within a patch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileDiff {
+    /// Absolute path of the file
+    pub path: PathBuf,
+    /// Whether the patch creates the file
+    pub created: bool,
+    /// Unified diff hunks, without file headers
+    pub diff: String,
+    pub added: usize,
+    pub removed: usize,
+}
+
+impl FileDiff {
+    /// Diff taking `path` from `before` (None = missing) to `after`
+    fn between(path: &Path, before: Option<&str>, after: &str) -> Self {
+        let text = TextDiff::from_lines(before.unwrap_or(""), after);
+        let (mut added, mut removed) = (0, 0);
+        for change in text.iter_all_changes() {
+            match change.tag() {
+                ChangeTag::Insert => added += 1,
+                ChangeTag::Delete => removed += 1,
+                ChangeTag::Equal => {}
+            }
+        }
+        Self {
+            path: path.to_path_buf(),
+            created: before.is_none(),
+            diff: text.unified_diff().context_radius(CONTEXT_LINES).to_string(),
+            added,
+            removed,
+        }
+    }
+
+    /// Content after applying this diff to `before`
+    fn apply(&self, before: Option<&str>) -> Result<String, String> {
+        if self.created && before.is_some() {
+            return Err("the file it creates already exists".to_string());
+        }
+        let hunks = parse_patch(&self.diff)?;
+        Ok(apply_hunks(before.unwrap_or(""), &hunks)?.content)
+    }
+}
+
+/// One patch of a series: the edits made during one user turn
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedPatch {
+    pub title: String,
+    pub files: Vec<FileDiff>,
+}
+
+impl QueuedPatch {
+    fn stats(&self) -> (usize, usize) {
+        self.files
+            .iter()
+            .fold((0, 0), |(added, removed), f| (added + f.added, removed + f.removed))
+    }
+}
+
+/// A named series of patches waiting for review
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Changeset {
+    pub name: String,
+    pub patches: Vec<QueuedPatch>,
+}
+
+impl Changeset {
+    /// One line per patch and file, with line counts
+    pub fn summary(&self) -> String {
+        let mut out = format!("Changeset '{}': {} patch(es)\n", self.name, self.patches.len());
+        for (i, patch) in self.patches.iter().enumerate() {
+            let (added, removed) = patch.stats();
+            out.push_str(&format!(
+                "{:>3}. {}  ({} file(s), +{} -{})\n",
+                i + 1,
+                patch.title,
+                patch.files.len(),
+                added,
+                removed
+            ));
+            for file in &patch.files {
+                out.push_str(&format!(
+                    "       {}{}  +{} -{}\n",
+                    display_path(&file.path),
+                    if file.created { " (new)" } else { "" },
+                    file.added,
+                    file.removed
+                ));
+            }
+        }
+        out.trim_end().to_string()
+    }
+
+    /// Full diff of the 1-based patch `number`
+    pub fn show(&self, number: usize) -> Option<String> {
+        let patch = self.patches.get(number.checked_sub(1)?)?;
+        let mut out = format!("Patch {}/{}: {}\n", number, self.patches.len(), patch.title);
+        for file in &patch.files {
+            out.push('\n');
+            out.push_str(&file_headers(file));
+            out.push_str(&file.diff);
+        }
+        Some(out.trim_end().to_string())
+    }
+}
+
+/// Current changeset and the title for the next patch
+#[derive(Debug, Default)]
+struct QueueState {
+    changeset: Option<Changeset>,
+    next_title: Option<String>,
+}
+
+/// Shared queue that file-writing tools stage their edits in
+///
+/// Inactive until a changeset is opened; writes then go to disk as before.
+#[derive(Debug)]
+pub struct PatchQueue {
+    dir: PathBuf,
+    state: Mutex<QueueState>,
+}
+
+impl Default for PatchQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PatchQueue {
+    /// Create a queue that saves changesets in the data directory
+    pub fn new() -> Self {
+        let dir = dirs::data_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("mylm")
+            .join("changesets");
+        Self::with_dir(dir)
+    }
+
+    /// Save changesets in `dir` instead
+    pub fn with_dir(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            state: Mutex::new(QueueState::default()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn file_for(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", name))
+    }
+
+    fn save(&self, changeset: &Changeset) -> Result<(), String> {
+        std::fs::create_dir_all(&self.dir).map_err(|e| format!("Cannot create {}: {}", self.dir.display(), e))?;
+        let json = serde_json::to_string_pretty(changeset).map_err(|e| e.to_string())?;
+        std::fs::write(self.file_for(&changeset.name), json).map_err(|e| format!("Cannot save changeset: {}", e))
+    }
+
+    /// Whether edits are currently being queued
+    pub fn is_active(&self) -> bool {
+        self.lock().changeset.is_some()
+    }
+
+    /// Copy of the open changeset
+    pub fn changeset(&self) -> Option<Changeset> {
+        self.lock().changeset.clone()
+    }
+
+    /// Names of saved changesets
+    pub fn saved(&self) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.strip_suffix(".json").map(str::to_string))
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Open the changeset `name`, resuming it if it was saved before
+    pub fn open(&self, name: &str) -> Result<Changeset, String> {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(format!("Invalid changeset name '{}': use letters, digits, '-' and '_'", name));
+        }
+        let path = self.file_for(name);
+        let changeset = match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| format!("Cannot load {}: {}", path.display(), e))?,
+            Err(_) => Changeset {
+                name: name.to_string(),
+                patches: Vec::new(),
+            },
+        };
+        self.save(&changeset)?;
+        let mut state = self.lock();
+        state.changeset = Some(changeset.clone());
+        state.next_title = None;
+        Ok(changeset)
+    }
+
+    /// Stop queueing; the changeset stays saved and can be reopened
+    pub fn close(&self) -> Option<Changeset> {
+        self.lock().changeset.take()
+    }
+
+    /// Close the changeset and delete it with all unapplied patches
+    pub fn discard(&self) -> Result<Changeset, String> {
+        let changeset = self.close().ok_or("No changeset is open")?;
+        let _ = std::fs::remove_file(self.file_for(&changeset.name));
+        Ok(changeset)
+    }
+
+    /// Start a new patch with the next recorded edit, titled after `message`
+    pub fn begin_patch(&self, message: &str) {
+        let mut state = self.lock();
+        if state.changeset.is_none() {
+            return;
+        }
+        state.next_title = Some(title_from(message));
+    }
+
+    /// Content of `path` with every queued edit applied, if the series touches it
+    pub fn staged(&self, path: &Path) -> Result<Option<String>, String> {
+        let state = self.lock();
+        let Some(changeset) = &state.changeset else {
+            return Ok(None);
+        };
+        let path = absolute(path);
+        if !changeset.patches.iter().any(|p| p.files.iter().any(|f| f.path == path)) {
+            return Ok(None);
+        }
+        let mut files = HashMap::new();
+        replay(&changeset.patches, &mut files)?;
+        Ok(files.remove(&path).flatten())
+    }
+
+    /// Queue `content` as the new content of `path` in the current patch
+    pub fn record(&self, path: &Path, content: &str) -> Result<FileDiff, String> {
+        let mut guard = self.lock();
+        let state = &mut *guard;
+        let changeset = state.changeset.as_mut().ok_or("No changeset is open")?;
+        if changeset.patches.is_empty() || state.next_title.is_some() {
+            changeset.patches.push(QueuedPatch {
+                title: state.next_title.take().unwrap_or_else(|| "Agent changes".to_string()),
+                files: Vec::new(),
+            });
+        }
+        let current = changeset.patches.len() - 1;
+        let path = absolute(path);
+
+        // Diff against the file as it is before this patch, so a file edited
+        // several times in one turn still has one diff per patch
+        let mut files = HashMap::new();
+        replay(&changeset.patches[..current], &mut files)?;
+        let before = load(&mut files, &path);
+        let change = FileDiff::between(&path, before.as_deref(), content);
+
+        let patch = &mut changeset.patches[current];
+        patch.files.retain(|f| f.path != path);
+        if !change.diff.is_empty() {
+            patch.files.push(change.clone());
+        }
+        if patch.files.is_empty() {
+            let empty = changeset.patches.pop().map(|p| p.title);
+            state.next_title = empty;
+        }
+        self.save(changeset)?;
+        Ok(change)
+    }
+
+    /// Remove the latest queued edit of `path`
+    pub fn unstage(&self, path: &Path) -> Result<(), String> {
+        let path = absolute(path);
+        self.update(|patches| {
+            let patch = patches
+                .iter_mut()
+                .rev()
+                .find(|p| p.files.iter().any(|f| f.path == path))
+                .ok_or_else(|| format!("No queued change to {}", path.display()))?;
+            patch.files.retain(|f| f.path != path);
+            patches.retain(|p| !p.files.is_empty());
+            Ok(())
+        })
+    }
+
+    /// Move 1-based patch `from` to position `to`
+    pub fn reorder(&self, from: usize, to: usize) -> Result<(), String> {
+        self.update(|patches| {
+            let len = patches.len();
+            check_number(from, len)?;
+            check_number(to, len)?;
+            let patch = patches.remove(from - 1);
+            patches.insert(to - 1, patch);
+            Ok(())
+        })
+    }
+
+    /// Remove 1-based patch `number` from the series
+    pub fn drop_patch(&self, number: usize) -> Result<QueuedPatch, String> {
+        let mut dropped = None;
+        self.update(|patches| {
+            check_number(number, patches.len())?;
+            dropped = Some(patches.remove(number - 1));
+            Ok(())
+        })?;
+        dropped.ok_or_else(|| "No patch dropped".to_string())
+    }
+
+    /// Change the series with `edit`, keeping the old series if the result no longer applies
+    fn update(&self, edit: impl FnOnce(&mut Vec<QueuedPatch>) -> Result<(), String>) -> Result<(), String> {
+        let mut state = self.lock();
+        let changeset = state.changeset.as_mut().ok_or("No changeset is open")?;
+        let mut patches = changeset.patches.clone();
+        edit(&mut patches)?;
+        changeset.patches = replay(&patches, &mut HashMap::new())?;
+        self.save(changeset)
+    }
+
+    /// Write 1-based patches `numbers` (all when empty) to disk
+    ///
+    /// Nothing is written unless the remaining patches still apply on top.
+    /// Returns the files written.
+    pub fn apply(&self, numbers: &[usize]) -> Result<Vec<PathBuf>, String> {
+        let mut state = self.lock();
+        let changeset = state.changeset.as_mut().ok_or("No changeset is open")?;
+        for &number in numbers {
+            check_number(number, changeset.patches.len())?;
+        }
+        let selected = |i: usize| numbers.is_empty() || numbers.contains(&(i + 1));
+        let (chosen, rest): (Vec<_>, Vec<_>) = changeset.patches.iter().enumerate().partition(|(i, _)| selected(*i));
+        let chosen: Vec<QueuedPatch> = chosen.into_iter().map(|(_, p)| p.clone()).collect();
+        let rest: Vec<QueuedPatch> = rest.into_iter().map(|(_, p)| p.clone()).collect();
+
+        let mut files = HashMap::new();
+        replay(&chosen, &mut files)?;
+        let rest = replay(&rest, &mut files.clone()).map_err(|e| format!("{}; nothing was applied", e))?;
+
+        let touched: BTreeSet<PathBuf> = chosen.iter().flat_map(|p| p.files.iter().map(|f| f.path.clone())).collect();
+        let mut written = Vec::new();
+        for path in touched {
+            let Some(Some(content)) = files.get(&path) else { continue };
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| format!("Cannot create {}: {}", parent.display(), e))?;
+            }
+            crate::watch::note_agent_write(&path);
+            std::fs::write(&path, content).map_err(|e| {
+                format!("Error writing {} (already written: {:?}): {}", path.display(), written, e)
+            })?;
+            written.push(path);
+        }
+        changeset.patches = rest;
+        self.save(changeset)?;
+        Ok(written)
+    }
+
+    /// Write the series to `dir` as `git am`-able patch files
+    pub fn export(&self, dir: &Path) -> Result<Vec<PathBuf>, String> {
+        let changeset = self.changeset().ok_or("No changeset is open")?;
+        if changeset.patches.is_empty() {
+            return Err("The changeset has no patches".to_string());
+        }
+        let patches = replay(&changeset.patches, &mut HashMap::new())?;
+        std::fs::create_dir_all(dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+        let date = chrono::Utc::now().to_rfc2822();
+        let total = patches.len();
+        let mut exported = Vec::new();
+        for (i, patch) in patches.iter().enumerate() {
+            let mut body = format!(
+                "From 0000000000000000000000000000000000000000 Mon Sep 17 00:00:00 2001\n\
+                 From: mylm <mylm@localhost>\n\
+                 Date: {}\n\
+                 Subject: [PATCH {}/{}] {}\n\n---\n",
+                date,
+                i + 1,
+                total,
+                patch.title
+            );
+            for file in &patch.files {
+                body.push_str(&format!(" {} | +{} -{}\n", display_path(&file.path), file.added, file.removed));
+            }
+            body.push('\n');
+            for file in &patch.files {
+                let path = display_path(&file.path);
+                body.push_str(&format!("diff --git a/{} b/{}\n", path, path));
+                if file.created {
+                    body.push_str("new file mode 100644\n");
+                }
+                body.push_str(&file_headers(file));
+                body.push_str(&file.diff);
+            }
+            body.push_str("-- \nmylm\n");
+            let out = dir.join(format!("{:04}-{}.patch", i + 1, slug(&patch.title, 52, "patch")));
+            std::fs::write(&out, body).map_err(|e| format!("Cannot write {}: {}", out.display(), e))?;
+            exported.push(out);
+        }
+        Ok(exported)
+    }
+}
+
+/// Apply `patches` in order on top of `files` (loaded from disk as needed),
+/// returning the patches re-diffed against the content they actually applied to
+fn replay(patches: &[QueuedPatch], files: &mut HashMap<PathBuf, Option<String>>) -> Result<Vec<QueuedPatch>, String> {
+    let mut rebased = Vec::with_capacity(patches.len());
+    for (i, patch) in patches.iter().enumerate() {
+        let mut changes = Vec::with_capacity(patch.files.len());
+        for queued in &patch.files {
+            let before = load(files, &queued.path);
+            let after = queued.apply(before.as_deref()).map_err(|e| {
+                format!(
+                    "Patch {} ({}) no longer applies to {}: {}",
+                    i + 1,
+                    patch.title,
+                    display_path(&queued.path),
+                    e.lines().next().unwrap_or_default()
+                )
+            })?;
+            let change = FileDiff::between(&queued.path, before.as_deref(), &after);
+            if !change.diff.is_empty() {
+                changes.push(change);
+            }
+            files.insert(queued.path.clone(), Some(after));
+        }
+        rebased.push(QueuedPatch {
+            title: patch.title.clone(),
+            files: changes,
+        });
+    }
+    Ok(rebased)
+}
+
+/// Content of `path` in `files`, read from disk the first time (None = missing)
+fn load(files: &mut HashMap<PathBuf, Option<String>>, path: &Path) -> Option<String> {
+    files
+        .entry(path.to_path_buf())
+        .or_insert_with(|| std::fs::read_to_string(path).ok())
+        .clone()
+}
+
+fn check_number(number: usize, len: usize) -> Result<(), String> {
+    if number == 0 || number > len {
+        return Err(format!("No patch {} (the series has {})", number, len));
+    }
+    Ok(())
+}
+
+/// First non-empty line of a user message, shortened for a title
+pub(crate) fn title_from(message: &str) -> String {
+    let line = message.lines().find(|l| !l.trim().is_empty()).unwrap_or("Agent changes").trim();
+    match line.char_indices().nth(MAX_TITLE_LEN) {
+        Some((cut, _)) => format!("{}...", &line[..cut]),
+        None => line.to_string(),
+    }
+}
+
+/// `path` made absolute against the working directory
+pub(crate) fn absolute(path: &Path) -> PathBuf {
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().unwrap_or_default().join(path)
+    };
+    path.components().filter(|c| !matches!(c, std::path::Component::CurDir)).collect()
+}
+
+/// `path` relative to the working directory when it is inside it
+pub(crate) fn display_path(path: &Path) -> String {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let relative = path.strip_prefix(&cwd).unwrap_or(path);
+    relative.to_string_lossy().trim_start_matches('/').to_string()
+}
+
+fn file_headers(file: &FileDiff) -> String {
+    let path = display_path(&file.path);
+    let old = if file.created { "/dev/null".to_string() } else { format!("a/{}", path) };
+    format!("--- {}\n+++ b/{}\n", old, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn queue(temp: &TempDir) -> PatchQueue {
+        let queue = PatchQueue::with_dir(temp.path().join("changesets"));
+        queue.open("feature").unwrap();
+        queue
+    }
+
+    #[test]
+    fn test_edits_are_staged_per_turn() {
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join("lib.rs");
+        std::fs::write(&file, "fn one() {}\nfn two() {}\n").unwrap();
+        let queue = queue(&temp);
+
+        queue.begin_patch("rename two");
+        queue.record(&file, "fn one() {}\nfn deux() {}\n").unwrap();
+        queue.record(&file, "fn one() {}\nfn zwei() {}\n").unwrap();
+        queue.begin_patch("add a new file\nwith details");
+        let new_file = temp.path().join("src").join("new.rs");
+        let created = queue.record(&new_file, "fn three() {}\n").unwrap();
+        assert!(created.created);
+
+        // Nothing on disk changed, but reads see the queued content
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "fn one() {}\nfn two() {}\n");
+        assert!(!new_file.exists());
+        assert_eq!(queue.staged(&file).unwrap().as_deref(), Some("fn one() {}\nfn zwei() {}\n"));
+
+        let changeset = queue.changeset().unwrap();
+        assert_eq!(changeset.patches.len(), 2);
+        assert_eq!(changeset.patches[0].title, "rename two");
+        assert_eq!(changeset.patches[0].files.len(), 1);
+        assert_eq!(changeset.patches[1].title, "add a new file");
+        assert!(changeset.show(1).unwrap().contains("+fn zwei() {}"));
+
+        // The changeset survives a restart
+        let reopened = PatchQueue::with_dir(temp.path().join("changesets"));
+        assert_eq!(reopened.open("feature").unwrap(), changeset);
+    }
+
+    #[test]
+    fn test_reorder_partial_apply_and_export() {
+        let temp = TempDir::new().unwrap();
+        let a = temp.path().join("a.txt");
+        let b = temp.path().join("b.txt");
+        std::fs::write(&a, "alpha\n").unwrap();
+        std::fs::write(&b, "beta\n").unwrap();
+        let queue = queue(&temp);
+
+        queue.begin_patch("edit a");
+        queue.record(&a, "alpha\nmore alpha\n").unwrap();
+        queue.begin_patch("edit b");
+        queue.record(&b, "BETA\n").unwrap();
+        queue.begin_patch("edit a again");
+        queue.record(&a, "ALPHA\nmore alpha\n").unwrap();
+
+        // "edit a again" depends on "edit a", so it cannot go first
+        assert!(queue.reorder(3, 1).unwrap_err().contains("no longer applies"));
+        queue.reorder(2, 1).unwrap();
+        assert_eq!(queue.changeset().unwrap().patches[0].title, "edit b");
+
+        let exported = queue.export(&temp.path().join("series")).unwrap();
+        assert_eq!(exported.len(), 3);
+        let first = std::fs::read_to_string(&exported[0]).unwrap();
+        assert!(first.contains("Subject: [PATCH 1/3] edit b"));
+        assert!(first.contains("-beta\n+BETA\n"));
+        assert!(exported[2].ends_with("0003-edit-a-again.patch"));
+
+        // Applying only the b patch leaves the a patches queued
+        assert_eq!(queue.apply(&[1]).unwrap(), vec![b.clone()]);
+        assert_eq!(std::fs::read_to_string(&b).unwrap(), "BETA\n");
+        assert_eq!(std::fs::read_to_string(&a).unwrap(), "alpha\n");
+        assert_eq!(queue.changeset().unwrap().patches.len(), 2);
+
+        assert_eq!(queue.apply(&[]).unwrap(), vec![a.clone()]);
+        assert_eq!(std::fs::read_to_string(&a).unwrap(), "ALPHA\nmore alpha\n");
+        assert!(queue.changeset().unwrap().patches.is_empty());
+    }
+}
@@ -0,0 +1,220 @@
+//! Plan Tool - Write and track an explicit multi-step plan
+//!
+//! For multi-step tasks the engine first writes a plan (numbered steps with
+//! dependencies), then marks steps in progress, done, failed or skipped as
+//! it works. The runtime refuses to start a step before the steps it depends
+//! on are settled. Every result carries the whole checklist, which is also
+//! how the engine sees edits the user made with `/plan`.
+//!
+//! # Usage
+//!
+//! - `{"action": "create", "goal": "Add a --json flag", "steps": ["Read the parser", {"title": "Add the flag", "depends_on": [1]}]}`
+//! - `{"action": "update", "step": 2, "status": "done", "note": "added in cli.rs"}`
+//! - `{"action": "add", "title": "Update the docs", "depends_on": [2]}`
+//! - `{"action": "get"}` / `{"action": "clear"}`
+
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+use crate::agent::runtime::core::{Capability, PlanCapability, RuntimeContext, ToolCapability, ToolError};
+use crate::agent::tools::parse_args;
+use crate::agent::types::events::ToolResult;
+use crate::agent::types::intents::ToolCall;
+use crate::agent::types::plan::{Plan, PlanError, StepStatus};
+
+/// Most steps in one plan
+const MAX_STEPS: usize = 30;
+
+/// A step as the model writes it: a title, or a title with dependencies
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum StepArg {
+    Title(String),
+    Step {
+        title: String,
+        #[serde(default)]
+        depends_on: Vec<usize>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+struct PlanArgs {
+    action: String,
+    #[serde(default)]
+    goal: Option<String>,
+    #[serde(default)]
+    steps: Vec<StepArg>,
+    /// Step number for update
+    #[serde(default)]
+    step: Option<usize>,
+    #[serde(default)]
+    status: Option<StepStatus>,
+    #[serde(default)]
+    note: Option<String>,
+    /// Title for add
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    depends_on: Vec<usize>,
+}
+
+/// Tool for the session plan
+pub struct PlanTool {
+    plan: Arc<dyn PlanCapability>,
+}
+
+impl PlanTool {
+    pub fn new(plan: Arc<dyn PlanCapability>) -> Self {
+        Self { plan }
+    }
+
+    fn run(&self, args: PlanArgs) -> Result<Option<Plan>, PlanError> {
+        match args.action.as_str() {
+            "create" => {
+                if args.steps.len() > MAX_STEPS {
+                    return Err(PlanError(format!("Plans are limited to {} steps", MAX_STEPS)));
+                }
+                let steps = args
+                    .steps
+                    .into_iter()
+                    .map(|step| match step {
+                        StepArg::Title(title) => (title, Vec::new()),
+                        StepArg::Step { title, depends_on } => (title, depends_on),
+                    })
+                    .collect();
+                let plan = Plan::new(args.goal.unwrap_or_default(), steps)?;
+                self.plan.set(Some(plan.clone()));
+                Ok(Some(plan))
+            }
+            "update" => {
+                let step = args.step.ok_or_else(|| PlanError("'step' is required".to_string()))?;
+                let status = args.status.ok_or_else(|| {
+                    PlanError("'status' is required: pending, in_progress, done, failed or skipped".to_string())
+                })?;
+                self.plan.update_step(step, status, args.note).map(Some)
+            }
+            "add" => {
+                let title = args.title.ok_or_else(|| PlanError("'title' is required".to_string()))?;
+                let depends_on = args.depends_on;
+                self.plan
+                    .edit(&mut |plan| {
+                        if plan.steps.len() >= MAX_STEPS {
+                            return Err(PlanError(format!("Plans are limited to {} steps", MAX_STEPS)));
+                        }
+                        plan.add_step(title.clone(), depends_on.clone()).map(|_| ())
+                    })
+                    .map(Some)
+            }
+            "get" => {
+                let plan = self.plan.current();
+                // Reading the plan counts as seeing the user's edits
+                self.plan.set(plan.clone());
+                Ok(plan)
+            }
+            "clear" => {
+                self.plan.set(None);
+                Ok(None)
+            }
+            other => Err(PlanError(format!(
+                "Unknown action '{}'. Use create, update, add, get or clear",
+                other
+            ))),
+        }
+    }
+}
+
+/// Steps that can start now: pending, with every dependency settled
+fn ready_steps(plan: &Plan) -> Vec<usize> {
+    plan.steps
+        .iter()
+        .filter(|s| s.status == StepStatus::Pending && plan.blockers(s.id).is_empty())
+        .map(|s| s.id)
+        .collect()
+}
+
+impl Capability for PlanTool {
+    fn name(&self) -> &'static str {
+        "plan"
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolCapability for PlanTool {
+    fn description(&self) -> &'static str {
+        "Write a plan before a multi-step task (numbered steps; depends_on lists step numbers), then mark each step in_progress, done, failed or skipped as you go. A step cannot start before its dependencies are done or skipped. Results show the whole checklist, including edits the user made"
+    }
+
+    fn usage(&self) -> &'static str {
+        r#"Create: {"a": "plan", "i": {"action": "create", "goal": "Add a --json flag", "steps": ["Read the CLI parser", {"title": "Add the flag", "depends_on": [1]}, {"title": "Update the docs", "depends_on": [2]}]}} | Progress: {"a": "plan", "i": {"action": "update", "step": 1, "status": "done", "note": "clap derive in cli.rs"}} | Add: {"a": "plan", "i": {"action": "add", "title": "Add a test", "depends_on": [2]}} | Read: {"a": "plan", "i": {"action": "get"}}"#
+    }
+
+    fn uses_session_plan(&self, _tool: &str) -> bool {
+        true
+    }
+
+    async fn execute(&self, _ctx: &RuntimeContext, call: ToolCall) -> Result<ToolResult, ToolError> {
+        let args: PlanArgs = parse_args(&call.arguments)?;
+        match self.run(args) {
+            Ok(Some(plan)) => {
+                let ready = ready_steps(&plan);
+                let mut output = plan.checklist();
+                if plan.is_complete() {
+                    output.push_str("\nAll steps are done.");
+                } else if !ready.is_empty() {
+                    output.push_str(&format!("\nReady to start: {:?}", ready));
+                }
+                Ok(ToolResult::Success {
+                    output,
+                    structured: Some(serde_json::json!({ "plan": plan, "ready": ready })),
+                })
+            }
+            Ok(None) => Ok(ToolResult::Success {
+                output: "No plan".to_string(),
+                structured: None,
+            }),
+            Err(e) => Ok(ToolResult::Error {
+                message: e.to_string(),
+                code: Some("INVALID_PLAN".to_string()),
+                retryable: false,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::runtime::capabilities::SessionPlan;
+
+    async fn call(tool: &PlanTool, args: serde_json::Value) -> ToolResult {
+        tool.execute(&RuntimeContext::new(), ToolCall::new("plan", args)).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_create_and_update() {
+        let tool = PlanTool::new(Arc::new(SessionPlan::new()));
+        let created = call(&tool, serde_json::json!({
+            "action": "create",
+            "goal": "Fix the flaky test",
+            "steps": ["Reproduce", {"title": "Fix", "depends_on": [1]}],
+        }))
+        .await;
+        match created {
+            ToolResult::Success { output, .. } => assert!(output.ends_with("Ready to start: [1]")),
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        let blocked = call(&tool, serde_json::json!({"action": "update", "step": 2, "status": "in_progress"})).await;
+        assert!(matches!(blocked, ToolResult::Error { .. }));
+
+        call(&tool, serde_json::json!({"action": "update", "step": 1, "status": "done"})).await;
+        match call(&tool, serde_json::json!({"action": "update", "step": 2, "status": "done", "note": "seeded rng"})).await {
+            ToolResult::Success { output, .. } => {
+                assert!(output.contains("[x] 2. Fix (after 1) - seeded rng"));
+                assert!(output.ends_with("All steps are done."));
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+}
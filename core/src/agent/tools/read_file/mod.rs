@@ -6,6 +6,7 @@
 //! - Integrate with Tantivy for search-based access
 //! - Extract text from PDFs
 //! - Maintain persistent chunk workers for follow-up queries
+//! - Show queued edits while a patch-queue changeset is open
 //!
 //! # Usage
 //!
@@ -45,9 +46,11 @@ pub use types::FileFormat;
 pub use search::{SearchFilesTool, SearchResult};
 
 use crate::agent::runtime::core::{Capability, RuntimeContext, ToolCapability, ToolError};
+use crate::agent::runtime::governance::SecretGuard;
 use crate::agent::types::events::ToolResult;
 use crate::agent::types::intents::ToolCall;
 use crate::agent::tools::expand_tilde;
+use crate::agent::tools::patch_queue::PatchQueue;
 use crate::provider::LlmClient;
 use crate::provider::chat::{ChatMessage, ChatRequest};
 use std::path::Path;
@@ -64,6 +67,10 @@ pub struct ReadFileTool {
     pdf_cache: Arc<tokio::sync::Mutex<std::collections::HashMap<String, String>>>,
     /// Optional LLM client for chunk analysis
     llm_client: Option<Arc<crate::provider::LlmClient>>,
+    /// Refuses paths on the secret-file deny-list
+    secret_guard: Arc<SecretGuard>,
+    /// Queued edits shown in place of the disk contents
+    patch_queue: Option<Arc<PatchQueue>>,
 }
 
 impl ReadFileTool {
@@ -79,6 +86,8 @@ impl ReadFileTool {
             max_direct_size: thresholds::MAX_DIRECT,
             pdf_cache: Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
             llm_client: None,
+            secret_guard: Arc::new(SecretGuard::new()),
+            patch_queue: None,
         }
     }
     
@@ -101,9 +110,23 @@ impl ReadFileTool {
             max_direct_size: thresholds::MAX_DIRECT,
             pdf_cache: Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
             llm_client: None,
+            secret_guard: Arc::new(SecretGuard::new()),
+            patch_queue: None,
         }
     }
     
+    /// Replace the default secret-file guard
+    pub fn with_secret_guard(mut self, guard: Arc<SecretGuard>) -> Self {
+        self.secret_guard = guard;
+        self
+    }
+    
+    /// Show files with the edits queued in `queue` applied
+    pub fn with_patch_queue(mut self, queue: Arc<PatchQueue>) -> Self {
+        self.patch_queue = Some(queue);
+        self
+    }
+    
     /// Set maximum direct read size
     pub fn with_max_direct_size(mut self, size: usize) -> Self {
         self.max_direct_size = size;
@@ -135,6 +158,27 @@ impl ReadFileTool {
         let path = Path::new(&args.path);
         let path_buf = path.to_path_buf();
         
+        if let Err(denied) = self.secret_guard.check("read_file", path) {
+            return Ok(ToolResult::Error {
+                message: denied.to_string(),
+                code: Some("SECRET_FILE".to_string()),
+                retryable: false,
+            });
+        }
+        
+        // Files with queued edits are read from the queue (they may not exist yet)
+        match self.patch_queue.as_ref().map(|queue| queue.staged(path)) {
+            Some(Ok(Some(content))) => return Ok(self.read_staged(path, &content, args.line_offset, args.n_lines)),
+            Some(Err(e)) => {
+                return Ok(ToolResult::Error {
+                    message: e,
+                    code: Some("QUEUE_ERROR".to_string()),
+                    retryable: false,
+                })
+            }
+            _ => {}
+        }
+        
         // Check file accessibility and format
         let format = match chunker::check_file_readable(path).await {
             Ok(f) => f,
@@ -290,6 +334,31 @@ impl ReadFileTool {
         })
     }
     
+    /// Read the queued content of a file with edits in the open changeset
+    fn read_staged(&self, path: &Path, content: &str, line_offset: Option<usize>, n_lines: Option<usize>) -> ToolResult {
+        let total_lines = content.lines().count().max(1);
+        let start_line = line_offset.unwrap_or(1);
+        let end_line = n_lines.map_or(total_lines, |n| (start_line + n - 1).min(total_lines));
+        let content = if line_offset.is_some() || n_lines.is_some() {
+            Self::extract_chunk_content(content, start_line, end_line)
+        } else {
+            content.to_string()
+        };
+        
+        let mut metadata = ReadMetadata::new(path.to_string_lossy(), content.len())
+            .with_strategy(ReadStrategy::Direct)
+            .with_tokens(tokens::estimate_from_content(&content));
+        if line_offset.is_some() || n_lines.is_some() {
+            metadata.line_range = Some((start_line, end_line));
+        }
+        metadata.warnings.push("Includes edits queued in the open changeset; not yet on disk".to_string());
+        
+        ToolResult::Success {
+            output: self.format_output(&content, &metadata),
+            structured: Some(serde_json::to_value(metadata).unwrap_or_default()),
+        }
+    }
+    
     /// Extract chunk content from full file text
     fn extract_chunk_content(content: &str, line_start: usize, line_end: usize) -> String {
         let lines: Vec<&str> = content.lines().collect();
@@ -937,6 +1006,14 @@ For persistent workers with follow-up query support, use the query_file tool."#
 
 #[async_trait::async_trait]
 impl ToolCapability for ReadFileTool {
+    fn description(&self) -> &'static str {
+        "Read file contents. DO NOT use this tool for files the user uploaded. Only use for quick, small code reading or specific line checks."
+    }
+
+    fn usage(&self) -> &'static str {
+        r#"Small file: {"a": "read_file", "i": {"path": "<path>"}} | Partial: {"a": "read_file", "i": {"path": "<path>", "line_offset": 1, "n_lines": 100}}"#
+    }
+
     async fn execute(
         &self,
         _ctx: &RuntimeContext,
@@ -1078,6 +1155,37 @@ mod tests {
         }
     }
     
+    #[tokio::test]
+    async fn test_read_file_refuses_secret_files() {
+        let temp = TempDir::new().unwrap();
+        let env = temp.path().join(".env");
+        let example = temp.path().join(".env.example");
+        fs::write(&env, "API_KEY=secret\n").await.unwrap();
+        fs::write(&example, "API_KEY=\n").await.unwrap();
+        
+        let settings = crate::config::SecretFileSettings {
+            allow: vec![example.to_string_lossy().to_string()],
+            ..Default::default()
+        };
+        let tool = ReadFileTool::simple()
+            .with_secret_guard(Arc::new(SecretGuard::new().with_settings(&settings)));
+        
+        let call = ToolCall::new("read_file", serde_json::json!(env.to_str().unwrap()));
+        match tool.execute(&RuntimeContext::new(), call).await.unwrap() {
+            ToolResult::Error { code, message, .. } => {
+                assert_eq!(code, Some("SECRET_FILE".to_string()));
+                assert!(!message.contains("secret\n"));
+            }
+            _ => panic!("Expected .env to be refused"),
+        }
+        
+        let call = ToolCall::new("read_file", serde_json::json!(example.to_str().unwrap()));
+        assert!(matches!(
+            tool.execute(&RuntimeContext::new(), call).await.unwrap(),
+            ToolResult::Success { .. }
+        ));
+    }
+    
     #[test]
     fn test_read_file_capability_name() {
         let tool = ReadFileTool::simple();
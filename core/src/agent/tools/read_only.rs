@@ -0,0 +1,109 @@
+//! Read-only mode
+//!
+//! With `read_only` on the profile (or `mylm --read-only`), the tool
+//! registry hides the tools that can only change things and refuses calls
+//! that would: file writes, shell commands not known to only read (see
+//! `CommandPreview::is_read_only`), `config set`, docker and kubectl
+//! actions other than listing and inspecting, http requests other than
+//! GET, and `lsp`, whose language server can run build scripts. The
+//! agent's own memory, notes, scratchpad and plan stay writable.
+
+use super::command_preview::CommandPreview;
+
+/// Tools hidden from the model in read-only mode
+pub const HIDDEN_TOOLS: &[&str] = &["write_file", "apply_patch", "edit_csv", "run_tests", "git_commit", "lsp"];
+
+/// Why a call of `tool` with `args` is refused in read-only mode, or None
+/// if it only reads
+pub fn refusal(tool: &str, args: &serde_json::Value) -> Option<String> {
+    let field = |name: &str| args.get(name).and_then(|v| v.as_str());
+    match tool {
+        "write_file" | "edit_csv" => Some(format!("{} writes files", tool)),
+        "apply_patch" if field("action") != Some("check") => Some("apply_patch writes files".to_string()),
+        "run_tests" => Some("run_tests runs project code".to_string()),
+        "lsp" => Some("lsp starts a language server, which can run project code".to_string()),
+        "git_commit" if field("action") == Some("commit") => Some("git_commit creates commits".to_string()),
+        "shell" => {
+            let suggest = field("mode") == Some("suggest");
+            let dry_run = args.get("dry_run").and_then(|v| v.as_bool()) == Some(true);
+            if suggest || dry_run {
+                return None;
+            }
+            let command = args.as_str().or_else(|| field("command")).unwrap_or_default();
+            let preview = CommandPreview::analyze(command);
+            if preview.is_read_only() {
+                return None;
+            }
+            let mut effects: Vec<String> = preview.may_modify.clone();
+            effects.extend(preview.writes.iter().map(|path| format!("writes {}", path)));
+            effects.extend(preview.deletes.iter().map(|path| format!("deletes {}", path)));
+            if effects.is_empty() {
+                effects.push("runs code that cannot be checked".to_string());
+            }
+            Some(format!("`{}` may change the system ({})", command, effects.join(", ")))
+        }
+        "config" if !matches!(field("action"), None | Some("get") | Some("list")) => {
+            Some("config can only be read".to_string())
+        }
+        "docker" | "kubectl" => {
+            let read_only = match tool {
+                "docker" => super::docker::READ_ONLY_ACTIONS,
+                _ => super::kubectl::READ_ONLY_ACTIONS,
+            };
+            let action = field("action").unwrap_or_default();
+            (!read_only.contains(&action)).then(|| {
+                format!("{} {} changes state; allowed: {}", tool, action, read_only.join(", "))
+            })
+        }
+        "http" => {
+            let method = field("method").unwrap_or("GET");
+            (!method.eq_ignore_ascii_case("GET")).then(|| format!("http {} may change state; only GET", method))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_refuses_calls_that_change_things() {
+        let shell = |command: &str| refusal("shell", &json!({ "command": command }));
+        assert_eq!(shell("ls -la /var/log | grep -i error"), None);
+        assert_eq!(shell("git log --oneline -5 && systemctl status nginx"), None);
+        assert_eq!(shell("journalctl -u nginx --since today"), None);
+        assert!(shell("systemctl restart nginx").unwrap().contains("systemctl restart"));
+        assert!(shell("echo hi > /etc/motd").unwrap().contains("writes /etc/motd"));
+        assert!(shell("cat $(rm -f x)").is_some());
+        assert!(shell("find . -name '*.log' -delete").is_some());
+        assert!(shell("sed -i 's/a/b/' f").is_some());
+        assert!(shell("python3 script.py").is_some());
+        assert!(shell("source ./setup.sh").is_some());
+        assert!(shell(". ./setup.sh").is_some());
+        assert!(shell("/usr/bin/env rm -f x").is_some());
+        assert!(shell("sort -o out.txt in.txt").unwrap().contains("writes out.txt"));
+        assert_eq!(shell("sort in.txt"), None);
+        assert!(shell("awk 'BEGIN { system(\"rm x\") }'").is_some());
+        assert!(shell("awk '{ print > \"out\" }' in.txt").is_some());
+        assert_eq!(shell("awk '{ print $1 }' in.txt"), None);
+        assert!(shell("hostname evil").is_some());
+        assert_eq!(shell("hostname -f"), None);
+        assert!(shell("date -s '2020-01-01'").is_some());
+        assert_eq!(shell("date +%s"), None);
+        assert!(shell("yq -i '.a = 1' x.yaml").unwrap().contains("writes x.yaml"));
+        assert!(shell("uniq in.txt out.txt").is_some());
+        assert_eq!(refusal("shell", &json!({ "command": "rm -rf build", "dry_run": true })), None);
+
+        assert!(refusal("write_file", &json!({ "path": "a", "content": "" })).is_some());
+        assert_eq!(refusal("apply_patch", &json!({ "action": "check", "patch": "" })), None);
+        assert_eq!(refusal("config", &json!({ "action": "get", "key": "x" })), None);
+        assert!(refusal("config", &json!({ "action": "set", "key": "x" })).is_some());
+        assert_eq!(refusal("kubectl", &json!({ "action": "get" })), None);
+        assert!(refusal("docker", &json!({ "action": "exec" })).is_some());
+        assert!(refusal("http", &json!({ "method": "post", "url": "http://x" })).is_some());
+        assert_eq!(refusal("read_file", &json!({ "path": "/etc/hosts" })), None);
+        assert!(refusal("lsp", &json!({ "action": "diagnostics", "path": "src/main.rs" })).is_some());
+    }
+}
@@ -7,7 +7,7 @@ use crate::agent::runtime::core::{Capability, ToolCapability};
 use crate::agent::runtime::core::RuntimeContext;
 use crate::agent::runtime::core::ToolError;
 use crate::agent::runtime::tools::{
-    ShellTool, ReadFileTool, WriteFileTool, ListFilesTool, 
+    ShellTool, ReadFileTool, WriteFileTool, ListFilesTool, CodeSearchTool,
     GitStatusTool, GitLogTool, GitDiffTool, WebSearchTool, MemoryTool,
     ScratchpadTool, WorkerShellTool, WorkerShellPermissions, EscalationRequest, EscalationResponse,
 };
@@ -30,6 +30,8 @@ pub struct RestrictedToolRegistry {
     write_file: Option<WriteFileTool>,
     /// List files tool
     list_files: Option<ListFilesTool>,
+    /// Code search tool
+    code_search: Option<CodeSearchTool>,
     /// Git status tool
     git_status: Option<GitStatusTool>,
     /// Git log tool
@@ -57,7 +59,7 @@ impl RestrictedToolRegistry {
                 // If no allowlist specified, include all standard tools
                 [
                     "shell", "read_file", "write_file", "list_files",
-                    "git_status", "git_log", "git_diff", "web_search",
+                    "code_search", "git_status", "git_log", "git_diff", "web_search",
                     "memory", "scratchpad",
                 ].iter().map(|s| s.to_string()).collect()
             });
@@ -101,6 +103,12 @@ impl RestrictedToolRegistry {
                 None
             },
             
+            code_search: if allowed_set.contains("code_search") {
+                Some(CodeSearchTool::new())
+            } else {
+                None
+            },
+            
             git_status: if allowed_set.contains("git_status") {
                 Some(GitStatusTool::new())
             } else {
@@ -147,6 +155,7 @@ impl RestrictedToolRegistry {
             "read_file" | "cat" => self.read_file.is_some(),
             "write_file" => self.write_file.is_some(),
             "list_files" | "ls" | "list_dir" => self.list_files.is_some(),
+            "code_search" | "grep" => self.code_search.is_some(),
             "git_status" => self.git_status.is_some(),
             "git_log" => self.git_log.is_some(),
             "git_diff" => self.git_diff.is_some(),
@@ -164,6 +173,7 @@ impl RestrictedToolRegistry {
             "read_file" | "cat" => self.read_file.as_ref().map(|t| t as &dyn ToolCapability),
             "write_file" => self.write_file.as_ref().map(|t| t as &dyn ToolCapability),
             "list_files" | "ls" | "list_dir" => self.list_files.as_ref().map(|t| t as &dyn ToolCapability),
+            "code_search" | "grep" => self.code_search.as_ref().map(|t| t as &dyn ToolCapability),
             "git_status" => self.git_status.as_ref().map(|t| t as &dyn ToolCapability),
             "git_log" => self.git_log.as_ref().map(|t| t as &dyn ToolCapability),
             "git_diff" => self.git_diff.as_ref().map(|t| t as &dyn ToolCapability),
@@ -181,6 +191,7 @@ impl RestrictedToolRegistry {
         if self.read_file.is_some() { tools.push("read_file".to_string()); }
         if self.write_file.is_some() { tools.push("write_file".to_string()); }
         if self.list_files.is_some() { tools.push("list_files".to_string()); }
+        if self.code_search.is_some() { tools.push("code_search".to_string()); }
         if self.git_status.is_some() { tools.push("git_status".to_string()); }
         if self.git_log.is_some() { tools.push("git_log".to_string()); }
         if self.git_diff.is_some() { tools.push("git_diff".to_string()); }
@@ -226,6 +237,14 @@ impl RestrictedToolRegistry {
             });
         }
         
+        if self.code_search.is_some() {
+            descriptions.push(super::ToolDescription {
+                name: "code_search",
+                description: "Search file contents by regex or literal text, respecting .gitignore; returns file, line and context",
+                usage: r#"{"a": "code_search", "i": {"pattern": "TODO", "path": "src", "glob": "*.rs"}}"#,
+            });
+        }
+        
         if self.git_status.is_some() {
             descriptions.push(super::ToolDescription {
                 name: "git_status",
@@ -292,7 +311,10 @@ impl ToolCapability for RestrictedToolRegistry {
         call: ToolCall,
     ) -> Result<ToolResult, ToolError> {
         match self.get_tool(&call.name) {
-            Some(tool) => tool.execute(ctx, call).await,
+            Some(tool) => match super::schema::validate_call(call) {
+                Ok(call) => tool.execute(ctx, call).await,
+                Err(invalid) => Ok(invalid),
+            },
             None => Ok(ToolResult::Error {
                 message: format!("Tool '{}' is not available to this worker", call.name),
                 code: Some("TOOL_NOT_ALLOWED".to_string()),
@@ -0,0 +1,320 @@
+//! Run Tests Tool - Run the project's tests and summarise the outcome
+//!
+//! Picks the runner from the nearest project manifest (Cargo.toml, go.mod,
+//! package.json, pyproject.toml/setup.py/pytest.ini) and reports whether
+//! the tests compiled, how many passed and failed, and an excerpt of the
+//! output: the compiler errors or the end of the log, not all of it.
+//!
+//! # Usage
+//!
+//! - `{}` - every test of the project around the working directory
+//! - `{"path": "core", "target": "parser::tests"}` - a Rust test name filter
+//! - `{"target": "tests/test_parser.py"}` - one test file (pytest, jest, go)
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::agent::runtime::core::{Capability, RuntimeContext, ToolCapability, ToolError};
+use crate::agent::tools::{expand_tilde, parse_args};
+use crate::agent::types::events::ToolResult;
+use crate::agent::types::intents::ToolCall;
+
+/// Default and largest time for one run (compiling counts)
+const DEFAULT_TIMEOUT_SECS: u64 = 600;
+const MAX_TIMEOUT_SECS: u64 = 1800;
+/// Size of the output excerpt
+const MAX_EXCERPT_CHARS: usize = 8_000;
+
+/// Test runner of a project
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Runner {
+    Cargo,
+    Go,
+    Pytest,
+    Npm,
+}
+
+impl Runner {
+    /// Runner and project root for `start` (a file or directory), from the
+    /// nearest directory with a manifest
+    pub fn detect(start: &Path) -> Option<(Runner, PathBuf)> {
+        let dir = if start.is_dir() { start } else { start.parent()? };
+        dir.ancestors().find_map(|dir| {
+            let runner = if dir.join("Cargo.toml").is_file() {
+                Runner::Cargo
+            } else if dir.join("go.mod").is_file() {
+                Runner::Go
+            } else if dir.join("package.json").is_file() {
+                Runner::Npm
+            } else if ["pyproject.toml", "setup.py", "pytest.ini", "setup.cfg"].iter().any(|f| dir.join(f).is_file()) {
+                Runner::Pytest
+            } else {
+                return None;
+            };
+            Some((runner, dir.to_path_buf()))
+        })
+    }
+
+    /// argv for running the tests in `root`, narrowed to `target` (a test
+    /// file or a name filter)
+    pub fn command(self, root: &Path, target: Option<&str>) -> Vec<String> {
+        let file = target
+            .map(|t| root.join(t))
+            .filter(|p| p.is_file())
+            .and_then(|p| p.strip_prefix(root).ok().map(Path::to_path_buf));
+        let mut argv: Vec<String> = match self {
+            Runner::Cargo => vec!["cargo".into(), "test".into(), "--color".into(), "never".into()],
+            Runner::Go => vec!["go".into(), "test".into()],
+            Runner::Pytest => vec!["python3".into(), "-m".into(), "pytest".into(), "-q".into()],
+            Runner::Npm => vec!["npm".into(), "test".into(), "--silent".into(), "--".into()],
+        };
+        match (self, file, target) {
+            // Integration test files run as their own target
+            (Runner::Cargo, Some(file), _) if file.starts_with("tests") => {
+                argv.push("--test".into());
+                argv.push(file.file_stem().unwrap_or_default().to_string_lossy().into_owned());
+            }
+            (Runner::Go, Some(file), _) => {
+                let dir = file.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+                argv.push(format!("./{}", dir.display()));
+            }
+            (Runner::Go, None, Some(name)) => argv.extend(["./...".into(), "-run".into(), name.to_string()]),
+            (Runner::Go, None, None) => argv.push("./...".into()),
+            (Runner::Pytest | Runner::Npm, Some(file), _) => argv.push(file.display().to_string()),
+            (Runner::Pytest, None, Some(name)) => argv.extend(["-k".into(), name.to_string()]),
+            (Runner::Npm, None, Some(name)) => argv.extend(["-t".into(), name.to_string()]),
+            (Runner::Cargo, _, Some(name)) => argv.push(name.to_string()),
+            _ => {}
+        }
+        argv
+    }
+}
+
+/// How a test run went
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestOutcome {
+    Passed,
+    Failed,
+    /// The tests did not build (or could not be collected)
+    CompileError,
+}
+
+/// Result of one run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestSummary {
+    pub runner: Runner,
+    pub command: String,
+    pub outcome: TestOutcome,
+    pub passed: usize,
+    pub failed: usize,
+    /// Compiler errors, or the end of the output
+    pub excerpt: String,
+}
+
+/// Sum the numbers `pattern` captures in `output`
+fn count(output: &str, pattern: &str) -> usize {
+    let re = Regex::new(pattern).expect("valid count pattern");
+    re.captures_iter(output)
+        .filter_map(|c| c.get(1)?.as_str().parse::<usize>().ok())
+        .sum()
+}
+
+/// The part of `output` worth showing: from the first compiler error, or the tail
+fn excerpt(output: &str, outcome: TestOutcome) -> String {
+    let start = match outcome {
+        TestOutcome::CompileError => output
+            .lines()
+            .position(|l| l.starts_with("error") || l.contains("ERROR collecting") || l.contains("error TS"))
+            .map(|i| output.lines().take(i).map(|l| l.len() + 1).sum())
+            .unwrap_or(0),
+        _ => 0,
+    };
+    let text = &output[start.min(output.len())..];
+    match outcome {
+        TestOutcome::CompileError => text.chars().take(MAX_EXCERPT_CHARS).collect(),
+        _ => {
+            let skip = text.chars().count().saturating_sub(MAX_EXCERPT_CHARS);
+            text.chars().skip(skip).collect()
+        }
+    }
+}
+
+/// Read the outcome and counts from a runner's output
+pub fn summarize(runner: Runner, command: String, success: bool, output: &str) -> TestSummary {
+    let (passed, failed, compile_error) = match runner {
+        Runner::Cargo => (
+            count(output, r"test result: \w+\. (\d+) passed"),
+            count(output, r"test result: \w+\. \d+ passed; (\d+) failed"),
+            output.contains("error: could not compile") || output.contains("error[E"),
+        ),
+        Runner::Go => (
+            output.matches("--- PASS").count(),
+            output.matches("--- FAIL").count(),
+            output.contains("[build failed]") || output.contains("[setup failed]"),
+        ),
+        Runner::Pytest => (
+            count(output, r"(\d+) passed"),
+            count(output, r"(\d+) failed"),
+            output.contains("ERROR collecting") || output.contains("error during collection")
+                || output.contains("errors during collection"),
+        ),
+        Runner::Npm => (
+            count(output, r"Tests:.*?(\d+) passed"),
+            count(output, r"Tests:.*?(\d+) failed"),
+            output.contains("Test suite failed to run") || output.contains("error TS"),
+        ),
+    };
+    let outcome = if !success && compile_error {
+        TestOutcome::CompileError
+    } else if success {
+        TestOutcome::Passed
+    } else {
+        TestOutcome::Failed
+    };
+    TestSummary {
+        runner,
+        command,
+        outcome,
+        passed,
+        failed,
+        excerpt: excerpt(output, outcome),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RunTestsArgs {
+    /// File or directory inside the project (default: working directory)
+    #[serde(default)]
+    path: Option<String>,
+    /// Test file relative to the project root, or a test name filter
+    #[serde(default)]
+    target: Option<String>,
+    #[serde(default)]
+    timeout_secs: Option<u64>,
+}
+
+/// Tool for running a project's tests
+#[derive(Debug, Clone, Default)]
+pub struct RunTestsTool;
+
+impl RunTestsTool {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Run the tests of the project around `path`
+    pub async fn run(&self, path: &Path, target: Option<&str>, timeout: Duration) -> Result<TestSummary, ToolError> {
+        let (runner, root) = Runner::detect(path).ok_or_else(|| {
+            ToolError::new(format!(
+                "No project found around '{}' (looked for Cargo.toml, go.mod, package.json, pyproject.toml)",
+                path.display()
+            ))
+        })?;
+        let argv = runner.command(&root, target);
+        let command = argv.join(" ");
+        let child = Command::new(&argv[0])
+            .args(&argv[1..])
+            .current_dir(&root)
+            .env("CARGO_TERM_COLOR", "never")
+            .env("NO_COLOR", "1")
+            .env("CI", "1")
+            .kill_on_drop(true)
+            .output();
+        let output = tokio::time::timeout(timeout, child)
+            .await
+            .map_err(|_| ToolError::new(format!("`{}` timed out after {}s", command, timeout.as_secs())))?
+            .map_err(|e| ToolError::new(format!("Failed to run `{}`: {}", command, e)))?;
+        let text = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        Ok(summarize(runner, command, output.status.success(), &text))
+    }
+}
+
+impl Capability for RunTestsTool {
+    fn name(&self) -> &'static str {
+        "run_tests"
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolCapability for RunTestsTool {
+    fn description(&self) -> &'static str {
+        "Run the project's tests (cargo, go, pytest or npm, picked from the nearest manifest) and get whether they compiled, pass/fail counts and the compiler errors or failing output. Use it instead of running test commands in the shell"
+    }
+
+    fn usage(&self) -> &'static str {
+        r#"All: {"a": "run_tests"} | Rust filter: {"a": "run_tests", "i": {"path": "core", "target": "parser::tests"}} | One file: {"a": "run_tests", "i": {"target": "tests/test_parser.py"}}"#
+    }
+
+    async fn execute(&self, _ctx: &RuntimeContext, call: ToolCall) -> Result<ToolResult, ToolError> {
+        let args: RunTestsArgs = parse_args(&call.arguments)?;
+        let path = match args.path.as_deref().or(call.working_dir.as_deref()) {
+            Some(path) => PathBuf::from(expand_tilde(path)),
+            None => std::env::current_dir().map_err(|e| ToolError::new(format!("No working directory: {}", e)))?,
+        };
+        let timeout = Duration::from_secs(args.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS).clamp(1, MAX_TIMEOUT_SECS));
+        let summary = self.run(&path, args.target.as_deref(), timeout).await?;
+
+        let headline = match summary.outcome {
+            TestOutcome::CompileError => format!("`{}`: the tests do not compile", summary.command),
+            TestOutcome::Passed => format!("`{}`: {} passed", summary.command, summary.passed),
+            TestOutcome::Failed => format!(
+                "`{}`: {} failed, {} passed",
+                summary.command, summary.failed, summary.passed
+            ),
+        };
+        Ok(ToolResult::Success {
+            output: format!("{}\n\n{}", headline, summary.excerpt),
+            structured: Some(serde_json::to_value(&summary).unwrap_or_default()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize_cargo_output() {
+        let passing = "running 3 tests\ntest result: ok. 3 passed; 0 failed; 0 ignored\n\
+                       running 1 test\ntest result: ok. 1 passed; 0 failed; 0 ignored\n";
+        let summary = summarize(Runner::Cargo, "cargo test".into(), true, passing);
+        assert_eq!((summary.outcome, summary.passed, summary.failed), (TestOutcome::Passed, 4, 0));
+
+        let broken = "   Compiling demo v0.1.0\nerror[E0425]: cannot find value `x` in this scope\n --> src/lib.rs:3:5\n\
+                      error: could not compile `demo` (lib test) due to 1 previous error\n";
+        let summary = summarize(Runner::Cargo, "cargo test".into(), false, broken);
+        assert_eq!(summary.outcome, TestOutcome::CompileError);
+        assert!(summary.excerpt.starts_with("error[E0425]"));
+
+        let pytest = "F..\n=== 1 failed, 2 passed in 0.12s ===\n";
+        let summary = summarize(Runner::Pytest, "pytest".into(), false, pytest);
+        assert_eq!((summary.outcome, summary.passed, summary.failed), (TestOutcome::Failed, 2, 1));
+    }
+
+    #[test]
+    fn test_detect_and_command() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"demo\"\n").unwrap();
+        std::fs::create_dir_all(dir.path().join("tests")).unwrap();
+        std::fs::write(dir.path().join("tests/parser.rs"), "").unwrap();
+
+        let (runner, root) = Runner::detect(&dir.path().join("tests/parser.rs")).unwrap();
+        assert_eq!((runner, root.as_path()), (Runner::Cargo, dir.path()));
+        assert_eq!(runner.command(&root, Some("tests/parser.rs")).join(" "), "cargo test --color never --test parser");
+        assert_eq!(runner.command(&root, Some("lexer::tests")).join(" "), "cargo test --color never lexer::tests");
+        assert_eq!(
+            Runner::Pytest.command(&root, Some("slow")).join(" "),
+            "python3 -m pytest -q -k slow"
+        );
+    }
+}
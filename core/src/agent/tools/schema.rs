@@ -0,0 +1,527 @@
+//! Argument schemas for the built-in tools
+//!
+//! Every built-in tool declares the JSON Schema of its arguments here. The
+//! registry checks the model's arguments against it before the tool runs,
+//! fixing simple mismatches on the way (`"5"` where an integer is expected,
+//! `"true"` for a boolean, `3` for a string, an object sent as JSON text).
+//! Anything it cannot fix comes back as one `INVALID_ARGUMENTS` error that
+//! lists every problem, so the model can correct the call in one turn. The
+//! same schemas are sent to providers that use native tool calling.
+//!
+//! Only the part of JSON Schema used below is understood: `type` (a name or
+//! a list), `properties`, `required`, `items`, `enum` and `minimum`.
+//! Arguments given as a plain string (`"ls -la"` for shell) are left to the
+//! tool, which accepts them as a shorthand.
+
+use serde_json::{json, Map, Value};
+
+use crate::agent::types::events::ToolResult;
+use crate::agent::types::intents::ToolCall;
+
+/// One problem with a tool's arguments
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArgError {
+    /// Where the problem is, e.g. `workers[0].objective` (empty for the root)
+    pub path: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ArgError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "arguments: {}", self.message)
+        } else {
+            write!(f, "`{}`: {}", self.path, self.message)
+        }
+    }
+}
+
+/// An object schema with the given properties
+fn object(properties: Value, required: &[&str]) -> Value {
+    json!({ "type": "object", "properties": properties, "required": required })
+}
+
+fn string(description: &str) -> Value {
+    json!({ "type": "string", "description": description })
+}
+
+fn choice(options: &[&str]) -> Value {
+    json!({ "type": "string", "enum": options })
+}
+
+fn integer(description: &str) -> Value {
+    json!({ "type": "integer", "minimum": 0, "description": description })
+}
+
+fn boolean() -> Value {
+    json!({ "type": "boolean" })
+}
+
+fn strings() -> Value {
+    json!({ "type": "array", "items": { "type": "string" } })
+}
+
+/// Schema of a built-in tool's arguments
+pub fn tool_schema(tool: &str) -> Option<Value> {
+    let schema = match tool {
+        "shell" => object(
+            json!({
+                "command": string("Command line to run"),
+                "mode": choice(&["execute", "suggest"]),
+                "background": boolean(),
+                "dry_run": boolean(),
+            }),
+            &["command"],
+        ),
+        "read_file" => object(
+            json!({
+                "path": string("File to read"),
+                "line_offset": integer("First line to read, starting at 1"),
+                "n_lines": integer("Number of lines to read"),
+                "strategy": choice(&["auto", "direct", "chunked", "search"]),
+                "query": string("What to look for in a large file"),
+            }),
+            &["path"],
+        ),
+        "write_file" => object(json!({ "path": string("File to write"), "content": string("New file contents") }), &["path", "content"]),
+        "apply_patch" => object(
+            json!({
+                "action": choice(&["apply", "check", "revert"]),
+                "path": string("File the patch applies to"),
+                "patch": string("Unified diff hunks"),
+            }),
+            &["path"],
+        ),
+        "edit_csv" => object(
+            json!({
+                "path": string("CSV file"),
+                "operation": choice(&["update", "delete", "insert", "update_where"]),
+                "row": integer("Data row, starting at 0"),
+                "column": string("Column name"),
+                "value": string("New cell value"),
+                "values": strings(),
+                "where": object(json!({ "column": string("Column name"), "equals": string("Value to match") }), &["column", "equals"]),
+            }),
+            &["path"],
+        ),
+        "list_files" => object(json!({ "path": string("Directory to list") }), &[]),
+        "code_search" => object(
+            json!({
+                "pattern": string("Regex, or literal text with literal: true"),
+                "path": string("Directory to search"),
+                "literal": boolean(),
+                "ignore_case": boolean(),
+                "glob": string("Only files matching this glob"),
+                "context": integer("Context lines around each match"),
+                "max_results": integer("Largest number of matches"),
+            }),
+            &["pattern"],
+        ),
+        "code_outline" => object(json!({ "path": string("Source file"), "symbol": string("Symbol to return") }), &["path"]),
+        "git_status" => object(json!({}), &[]),
+        "git_log" => object(json!({ "limit": integer("Number of commits") }), &[]),
+        "git_diff" => object(json!({ "path": string("Limit the diff to this path") }), &[]),
+        "git_commit" => object(
+            json!({
+                "action": choice(&["draft", "commit"]),
+                "files": strings(),
+                "message": string("Commit message"),
+            }),
+            &[],
+        ),
+        "web_search" => object(json!({ "query": string("Search query") }), &[]),
+        "docs" => object(
+            json!({
+                "command": string("Command to look up, e.g. \"git rebase\""),
+                "source": choice(&["auto", "man", "tldr"]),
+                "section": string("Man page section to return"),
+                "query": string("Text to find in the page"),
+                "max_chars": integer("Largest answer"),
+            }),
+            &["command"],
+        ),
+        "run_tests" => object(
+            json!({
+                "path": string("File or directory inside the project"),
+                "target": string("Test file or name filter"),
+                "timeout_secs": integer("Time limit"),
+            }),
+            &[],
+        ),
+        "notes" => object(json!({ "action": choice(&["read", "list", "search"]), "query": string("Search text") }), &["action"]),
+        // Both take the text itself or an object with it
+        "memory" => object(
+            json!({
+                "add": {
+                    "type": ["string", "object"],
+                    "description": "Memory to store",
+                    "properties": { "content": string("Memory to store"), "type": string("Kind of memory") },
+                },
+                "search": {
+                    "type": ["string", "object"],
+                    "description": "What to recall",
+                    "properties": { "query": string("What to recall") },
+                },
+            }),
+            &[],
+        ),
+        "delegate" => object(
+            json!({
+                "shared_context": string("Context every worker gets"),
+                "workers": {
+                    "type": "array",
+                    "items": object(
+                        json!({
+                            "id": string("Unique worker id"),
+                            "objective": string("What the worker should achieve"),
+                            "instructions": string("How to go about it"),
+                            "tools": strings(),
+                            "allowed_commands": strings(),
+                            "forbidden_commands": strings(),
+                            "tags": strings(),
+                            "depends_on": strings(),
+                            "context": {},
+                            "max_iterations": integer("Step limit"),
+                            "timeout_secs": integer("Time limit"),
+                        }),
+                        &["id", "objective"],
+                    ),
+                },
+            }),
+            &["workers"],
+        ),
+        "scratchpad" => object(
+            json!({
+                "action": choice(&["append", "overwrite", "clear", "list", "delete", "tag", "cleanup"]),
+                "text": string("Entry text"),
+                "ttl_seconds": integer("Lifetime of the entry"),
+                "tags": strings(),
+                "persistent": boolean(),
+                "worker_id": string("Worker the entry belongs to"),
+                "entry_id": string("Entry to change"),
+                "force": boolean(),
+            }),
+            &["action"],
+        ),
+        "commonboard" => object(
+            json!({
+                "action": choice(&["claim", "progress", "complete", "query", "list", "check", "release", "list_claims", "list_jobs", "job_status"]),
+                "agent_id": string("Who is acting"),
+                "resource": string("Resource to claim, check or release"),
+                "message": string("Progress message"),
+                "summary": string("Completion summary"),
+                "job_id": string("Job to report on"),
+            }),
+            &["action"],
+        ),
+        "search_files" => object(json!({ "query": string("What to find"), "path_filter": string("Only paths containing this") }), &["query"]),
+        "query_file" => object(json!({ "file_path": string("File to ask about"), "prompt": string("Question") }), &["prompt"]),
+        "query_chunk_worker" => object(
+            json!({ "chunk_id": string("Chunk to ask"), "prompt": string("Question"), "timeout_secs": integer("Time limit") }),
+            &["chunk_id", "prompt"],
+        ),
+        "close_file" => object(json!({ "file_name": string("File to close") }), &["file_name"]),
+        "config" => object(json!({ "action": choice(&["get", "list", "set"]), "key": string("Setting name"), "value": {} }), &[]),
+        "http" => object(
+            json!({
+                "method": string("HTTP method"),
+                "url": string("Request URL"),
+                "headers": { "type": "object" },
+                "body": {},
+                "timeout_secs": integer("Time limit"),
+            }),
+            &["url"],
+        ),
+        "docker" => object(
+            json!({
+                "action": choice(&["list", "logs", "inspect", "exec", "compose_up", "compose_down"]),
+                "container": string("Container name or id"),
+                "all": boolean(),
+                "tail": integer("Log lines"),
+                "since": string("Logs since, e.g. 10m"),
+                "command": { "type": ["string", "array"] },
+                "file": string("Compose file"),
+                "services": strings(),
+                "build": boolean(),
+                "volumes": boolean(),
+                "timeout_secs": integer("Time limit"),
+            }),
+            &["action"],
+        ),
+        "kubectl" => object(
+            json!({
+                "action": choice(&["get", "describe", "logs", "events", "contexts", "apply", "delete", "scale", "rollout_restart", "exec"]),
+                "resource": string("Resource type; secrets are refused"),
+                "name": string("Resource name"),
+                "namespace": string("Namespace"),
+                "context": string("Cluster context"),
+                "all_namespaces": boolean(),
+                "selector": string("Label selector"),
+                "output": string("Output format"),
+                "container": string("Container in the pod"),
+                "tail": integer("Log lines"),
+                "since": string("Logs since, e.g. 10m"),
+                "previous": boolean(),
+                "file": string("Manifest file"),
+                "replicas": integer("Replica count"),
+                "command": { "type": ["string", "array"] },
+                "timeout_secs": integer("Time limit"),
+            }),
+            &["action"],
+        ),
+        "lsp" => object(
+            json!({
+                "action": choice(&["definition", "references", "diagnostics"]),
+                "path": string("Source file"),
+                "line": integer("Line, starting at 1"),
+                "column": integer("Column, starting at 1"),
+                "include_declaration": boolean(),
+            }),
+            &["action", "path"],
+        ),
+        "code_search_semantic" => object(
+            json!({ "query": string("What the code does"), "limit": integer("Number of results"), "path": string("Only under this path") }),
+            &["query"],
+        ),
+        "annotate_terminal" => object(
+            json!({
+                "action": choice(&["annotate", "clear"]),
+                "annotations": {
+                    "type": "array",
+                    "items": object(
+                        json!({
+                            "anchor": string("Text on the terminal line to annotate"),
+                            "note": string("Annotation"),
+                            "severity": choice(&["error", "warning", "info"]),
+                        }),
+                        &["anchor", "note"],
+                    ),
+                },
+            }),
+            &[],
+        ),
+        "plan" => object(
+            json!({
+                "action": choice(&["create", "update", "add", "get", "clear"]),
+                "goal": string("What the plan achieves"),
+                "steps": {
+                    "type": "array",
+                    "items": {
+                        "type": ["string", "object"],
+                        "properties": { "title": string("Step"), "depends_on": { "type": "array", "items": { "type": "integer" } } },
+                        "required": ["title"],
+                    },
+                },
+                "step": integer("Step number"),
+                "status": choice(&["pending", "in_progress", "done", "failed", "skipped"]),
+                "note": string("Note on the step"),
+                "title": string("Title of a new step"),
+                "depends_on": { "type": "array", "items": { "type": "integer" } },
+            }),
+            &["action"],
+        ),
+        _ => return None,
+    };
+    Some(schema)
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn has_type(value: &Value, expected: &str) -> bool {
+    let actual = type_name(value);
+    actual == expected || (expected == "number" && actual == "integer")
+}
+
+/// `value` converted to `expected`, if that is an obvious fix
+fn coerce(value: &Value, expected: &str) -> Option<Value> {
+    match (expected, value) {
+        ("integer", Value::String(s)) => s.trim().parse::<i64>().ok().map(Value::from),
+        ("integer", Value::Number(n)) => n.as_f64().filter(|f| f.fract() == 0.0 && f.abs() < 9e15).map(|f| Value::from(f as i64)),
+        ("number", Value::String(s)) => s.trim().parse::<f64>().ok().filter(|f| f.is_finite()).map(Value::from),
+        ("boolean", Value::String(s)) => match s.trim().to_ascii_lowercase().as_str() {
+            "true" => Some(Value::Bool(true)),
+            "false" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        ("string", Value::Number(n)) => Some(Value::String(n.to_string())),
+        ("string", Value::Bool(b)) => Some(Value::String(b.to_string())),
+        ("array" | "object", Value::String(s)) => serde_json::from_str::<Value>(s).ok().filter(|v| has_type(v, expected)),
+        _ => None,
+    }
+}
+
+fn child(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// Check `value` against `schema`, coercing it in place
+fn check(schema: &Value, value: &mut Value, path: &str, errors: &mut Vec<ArgError>) {
+    let error = |message: String| ArgError { path: path.to_string(), message };
+    let types: Vec<&str> = match schema.get("type") {
+        Some(Value::String(t)) => vec![t.as_str()],
+        Some(Value::Array(list)) => list.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    if !types.is_empty() && !types.iter().any(|t| has_type(value, t)) {
+        match types.iter().find_map(|t| coerce(value, t)) {
+            Some(fixed) => *value = fixed,
+            None => {
+                let shown: String = value.to_string().chars().take(40).collect();
+                errors.push(error(format!("expected {}, got {} {}", types.join(" or "), type_name(value), shown)));
+                return;
+            }
+        }
+    }
+
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        if !options.contains(value) {
+            let listed: Vec<String> = options.iter().map(|o| o.to_string()).collect();
+            errors.push(error(format!("must be one of {}, got {}", listed.join(", "), value)));
+        }
+    }
+    if let (Some(minimum), Some(n)) = (schema.get("minimum").and_then(Value::as_f64), value.as_f64()) {
+        if n < minimum {
+            errors.push(error(format!("must be at least {}, got {}", minimum, n)));
+        }
+    }
+
+    match value {
+        Value::Object(map) => check_object(schema, map, path, errors),
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter_mut().enumerate() {
+                    check(item_schema, item, &format!("{}[{}]", path, i), errors);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_object(schema: &Value, map: &mut Map<String, Value>, path: &str, errors: &mut Vec<ArgError>) {
+    for required in schema.get("required").and_then(Value::as_array).into_iter().flatten() {
+        let Some(key) = required.as_str() else { continue };
+        if map.get(key).is_none_or(Value::is_null) {
+            errors.push(ArgError {
+                path: child(path, key),
+                message: "is required".to_string(),
+            });
+        }
+    }
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return;
+    };
+    for (key, value) in map.iter_mut() {
+        // Unknown keys and explicit nulls are left to the tool
+        if let (Some(property), false) = (properties.get(key), value.is_null()) {
+            check(property, value, &child(path, key), errors);
+        }
+    }
+}
+
+/// Check arguments against a schema; returns them with simple mismatches fixed
+pub fn validate(schema: &Value, args: Value) -> Result<Value, Vec<ArgError>> {
+    let mut args = match args {
+        Value::Null => Value::Object(Map::new()),
+        // A JSON object sent as text is still an object; other text is a shorthand
+        Value::String(text) => match serde_json::from_str::<Value>(&text) {
+            Ok(parsed @ Value::Object(_)) => parsed,
+            _ => return Ok(Value::String(text)),
+        },
+        args => args,
+    };
+    let mut errors = Vec::new();
+    check(schema, &mut args, "", &mut errors);
+    if errors.is_empty() {
+        Ok(args)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Validate a call to a built-in tool; the error result goes back to the model
+pub fn validate_call(mut call: ToolCall) -> Result<ToolCall, ToolResult> {
+    let Some(schema) = tool_schema(&call.name) else {
+        return Ok(call);
+    };
+    let arguments = std::mem::take(&mut call.arguments);
+    match validate(&schema, arguments) {
+        Ok(arguments) => {
+            call.arguments = arguments;
+            Ok(call)
+        }
+        Err(errors) => {
+            let problems: Vec<String> = errors.iter().map(|e| format!("- {}", e)).collect();
+            Err(ToolResult::Error {
+                message: format!(
+                    "Invalid arguments for {}:\n{}\nExpected arguments: {}",
+                    call.name,
+                    problems.join("\n"),
+                    schema["properties"]
+                ),
+                code: Some("INVALID_ARGUMENTS".to_string()),
+                retryable: true,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simple_mismatches_are_coerced() {
+        let schema = tool_schema("read_file").unwrap();
+        let args = validate(&schema, json!({ "path": "src/lib.rs", "line_offset": "5", "n_lines": 20.0 })).unwrap();
+        assert_eq!(args, json!({ "path": "src/lib.rs", "line_offset": 5, "n_lines": 20 }));
+
+        let schema = tool_schema("edit_csv").unwrap();
+        let args = validate(&schema, json!({ "path": "a.csv", "operation": "insert", "values": [1, true, "x"] })).unwrap();
+        assert_eq!(args["values"], json!(["1", "true", "x"]));
+
+        let schema = tool_schema("delegate").unwrap();
+        let args = validate(&schema, json!(r#"{"workers": "[{\"id\": \"a\", \"objective\": \"b\"}]"}"#)).unwrap();
+        assert_eq!(args["workers"][0]["id"], "a");
+
+        let schema = tool_schema("memory").unwrap();
+        let args = json!({ "add": { "content": "Prefers tabs", "type": "preference" }, "search": "tabs" });
+        assert_eq!(validate(&schema, args.clone()).unwrap(), args);
+
+        // Plain-string shorthands are the tool's business
+        assert_eq!(validate(&tool_schema("shell").unwrap(), json!("ls -la")).unwrap(), json!("ls -la"));
+    }
+
+    #[test]
+    fn test_every_problem_is_reported() {
+        let call = ToolCall::new(
+            "delegate",
+            json!({ "workers": [{ "id": "w1" }, { "id": "w2", "objective": "x", "max_iterations": "many" }] }),
+        );
+        let Err(ToolResult::Error { message, code, retryable }) = validate_call(call) else {
+            panic!("expected a validation error");
+        };
+        assert_eq!(code.as_deref(), Some("INVALID_ARGUMENTS"));
+        assert!(retryable);
+        assert!(message.contains("`workers[0].objective`: is required"));
+        assert!(message.contains("`workers[1].max_iterations`: expected integer, got string"));
+
+        let errors = validate(&tool_schema("plan").unwrap(), json!({ "action": "finish", "step": -1 })).unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(validate_call(ToolCall::new("custom_tool", json!(42))).is_ok());
+    }
+}
@@ -32,6 +32,11 @@ pub const DEFAULT_ENTRY_TTL: Option<Duration> = Some(Duration::hours(1));
 /// Age threshold for automatic cleanup (1 hour)
 pub const CLEANUP_AGE_THRESHOLD: Duration = Duration::hours(1);
 
+/// Start of an entry's header line in [`StructuredScratchpad::to_document`]
+const DOCUMENT_HEADER: &str = "--- ";
+/// Header line of a section the user adds to the document
+pub const NEW_ENTRY_HEADER: &str = "--- new";
+
 /// Type alias for entry ID
 pub type EntryId = Uuid;
 
@@ -125,6 +130,42 @@ impl ScratchpadEntry {
     }
 }
 
+/// What [`StructuredScratchpad::apply_document`] changed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DocumentChanges {
+    pub updated: usize,
+    pub added: usize,
+    pub removed: usize,
+}
+
+/// Whether `line` starts a section of a document: `Some` with the entry's
+/// id prefix for `--- <8 hex digits> ...`, `Some(None)` for
+/// [`NEW_ENTRY_HEADER`]; any other line, `---` included, is text
+pub fn document_header(line: &str) -> Option<Option<&str>> {
+    if line.trim_end() == NEW_ENTRY_HEADER {
+        return Some(None);
+    }
+    let prefix = line.strip_prefix(DOCUMENT_HEADER)?.split(' ').next()?;
+    (prefix.len() == 8 && prefix.chars().all(|c| c.is_ascii_hexdigit())).then_some(Some(prefix))
+}
+
+/// Sections of an edited document: the id prefix in each header, if any,
+/// and the trimmed text under it
+fn parse_document(document: &str) -> Vec<(Option<&str>, String)> {
+    let mut sections: Vec<(Option<&str>, Vec<&str>)> = vec![(None, Vec::new())];
+    for line in document.lines() {
+        if let Some(prefix) = document_header(line) {
+            sections.push((prefix, Vec::new()));
+        } else if let Some((_, lines)) = sections.last_mut() {
+            lines.push(line);
+        }
+    }
+    sections
+        .into_iter()
+        .map(|(prefix, lines)| (prefix, lines.join("\n").trim().to_string()))
+        .collect()
+}
+
 /// Structured scratchpad that maintains entries with full metadata
 #[derive(Clone)]
 pub struct StructuredScratchpad {
@@ -294,6 +335,71 @@ impl StructuredScratchpad {
             "total_size": self.get_size(),
         })
     }
+
+    /// Entries as editable text, oldest first, each under a
+    /// `--- <id> ...` header line; see [`Self::apply_document`]
+    pub fn to_document(&self) -> String {
+        let mut sorted: Vec<_> = self.entries.values().collect();
+        sorted.sort_by_key(|e| e.timestamp);
+
+        sorted
+            .iter()
+            .map(|e| {
+                let mut header = format!("{}{}", DOCUMENT_HEADER, &e.id.to_string()[..8]);
+                if e.persistent {
+                    header.push_str(" 🔒");
+                }
+                if !e.tags.is_empty() {
+                    header.push_str(&format!(" [{}]", e.tags.join(", ")));
+                }
+                if let Some(worker) = &e.worker_id {
+                    header.push_str(&format!(" <{}>", worker));
+                }
+                format!("{}\n{}\n", header, e.content)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Apply a document edited from [`Self::to_document`]: entries keep their
+    /// metadata and take the text under their header; a section whose header
+    /// names no entry (or is [`NEW_ENTRY_HEADER`]) becomes a new entry; entries in
+    /// `shown` whose section was deleted or emptied are removed. Entries
+    /// added since the document was made are left alone.
+    pub fn apply_document(&mut self, document: &str, shown: &[EntryId]) -> DocumentChanges {
+        let mut changes = DocumentChanges::default();
+        let mut kept = Vec::new();
+        for (prefix, content) in parse_document(document) {
+            let existing = prefix.and_then(|prefix| {
+                self.entries
+                    .keys()
+                    .find(|id| id.to_string().starts_with(prefix))
+                    .copied()
+            });
+            match existing {
+                Some(id) if !content.is_empty() => {
+                    kept.push(id);
+                    let entry = self.entries.get_mut(&id).expect("id taken from the entries");
+                    if entry.content.trim() != content {
+                        entry.content = content;
+                        changes.updated += 1;
+                    }
+                }
+                Some(_) => {}
+                None if !content.is_empty() => {
+                    self.append(content, None, vec!["user".to_string()], false, None);
+                    changes.added += 1;
+                }
+                None => {}
+            }
+        }
+        for id in shown {
+            if !kept.contains(id) && self.remove(*id) {
+                changes.removed += 1;
+            }
+        }
+        changes
+    }
 }
 
 impl Default for StructuredScratchpad {
@@ -354,6 +460,14 @@ impl Capability for ScratchpadTool {
 
 #[async_trait::async_trait]
 impl ToolCapability for ScratchpadTool {
+    fn description(&self) -> &'static str {
+        "Agent-local persistent notes (survives pruning)"
+    }
+
+    fn usage(&self) -> &'static str {
+        "{\"a\": \"scratchpad\", \"i\": {\"action\": \"append\", \"text\": \"note\"}} or {\"a\": \"scratchpad\", \"i\": {\"action\": \"list\"}}"
+    }
+
     async fn execute(
         &self,
         _ctx: &RuntimeContext,
@@ -696,4 +810,42 @@ mod tests {
         assert_eq!(removed, 2);
         assert_eq!(scratchpad.len(), 1);
     }
+
+    #[test]
+    fn test_document_round_trip() {
+        let mut scratchpad = StructuredScratchpad::new();
+        let claim = scratchpad.append("CLAIM src/lib.rs".to_string(), None, vec!["claim".to_string()], false, None);
+        let todo = scratchpad.append("check edge cases".to_string(), None, Vec::new(), true, None);
+        let shown = scratchpad.get_all_ids();
+
+        let document = scratchpad.to_document();
+        assert!(document.contains(" 🔒\ncheck edge cases"));
+        assert_eq!(scratchpad.apply_document(&document, &shown), DocumentChanges::default());
+
+        // Added by the agent while the document was being edited
+        let late = scratchpad.append("REPORT halfway".to_string(), None, Vec::new(), false, None);
+        let edited = document
+            .replace("check edge cases", "check edge cases\nand empty input")
+            .replace("CLAIM src/lib.rs", "")
+            + "\n--- new\nuse the v2 API";
+        let changes = scratchpad.apply_document(&edited, &shown);
+        assert_eq!(changes, DocumentChanges { updated: 1, added: 1, removed: 1 });
+        assert!(scratchpad.get(claim).is_none());
+        assert_eq!(scratchpad.get(todo).unwrap().content, "check edge cases\nand empty input");
+        assert!(scratchpad.get(todo).unwrap().persistent);
+        assert!(scratchpad.get(late).is_some());
+        assert_eq!(scratchpad.list_by_tag("user")[0].content, "use the v2 API");
+
+        // Rules and diff headers in an entry stay part of it
+        let mut scratchpad = StructuredScratchpad::new();
+        let content = "Findings\n---\n--- a/src/lib.rs\n+++ b/src/lib.rs";
+        let id = scratchpad.append(content.to_string(), None, Vec::new(), false, None);
+        let shown = scratchpad.get_all_ids();
+        let document = scratchpad.to_document();
+        assert_eq!(scratchpad.apply_document(&document, &shown), DocumentChanges::default());
+        assert_eq!(scratchpad.get(id).unwrap().content, content);
+        assert_eq!(document_header("--- 1a2b3c4d 🔒"), Some(Some("1a2b3c4d")));
+        assert_eq!(document_header("--- new"), Some(None));
+        assert_eq!(document_header("---"), None);
+    }
 }
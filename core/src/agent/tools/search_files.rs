@@ -334,6 +334,14 @@ struct SearchArgs {
 
 #[async_trait::async_trait]
 impl ToolCapability for SearchFilesTool {
+    fn description(&self) -> &'static str {
+        "Full-text search across indexed files"
+    }
+
+    fn usage(&self) -> &'static str {
+        "{\"a\": \"search_files\", \"i\": {\"query\": \"function main\"}} or {\"a\": \"search_files\", \"i\": {\"query\": \"TODO\", \"path_filter\": \"src/\"}}"
+    }
+
     async fn execute(
         &self,
         _ctx: &RuntimeContext,
@@ -5,14 +5,22 @@
 use crate::agent::runtime::core::{Capability, ToolCapability};
 use crate::agent::runtime::core::RuntimeContext;
 use crate::agent::runtime::core::ToolError;
-use crate::agent::runtime::core::terminal::TerminalExecutor;
+use crate::agent::runtime::core::terminal::{ExecError, TerminalExecutor};
+use crate::agent::runtime::core::Shell;
 use crate::agent::types::intents::ToolCall;
 use crate::agent::types::events::ToolResult;
 
+use crate::config::CommandLimits;
+use crate::util::format_size_rounded;
+use super::command_preview::{blocked_pattern, CommandPreview};
+use super::patch_queue::PatchQueue;
+
+use std::sync::Arc;
+
 use tokio::time::{timeout, Duration};
 
-const DEFAULT_TIMEOUT_SECS: u64 = 30;
-const MAX_OUTPUT_SIZE: usize = 100_000; // 100KB max output
+/// Exit status of `sh` when its child was killed by SIGXCPU
+const CPU_LIMIT_EXIT: &str = "Exit code 152";
 
 /// Shell command execution tool
 /// 
@@ -20,12 +28,105 @@ const MAX_OUTPUT_SIZE: usize = 100_000; // 100KB max output
 /// When running in TUI mode, this uses the shared PTY so the agent
 /// can see the terminal state and commands run in the same session.
 #[derive(Debug, Default)]
-pub struct ShellTool;
+pub struct ShellTool {
+    limits: CommandLimits,
+    patch_queue: Option<Arc<PatchQueue>>,
+}
 
 impl ShellTool {
     /// Create a new shell tool
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Apply `limits` to every command
+    pub fn with_limits(mut self, limits: CommandLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Refuse commands that may write while `queue` has a changeset open
+    ///
+    /// Shell writes cannot be staged, so they would escape review and revert.
+    pub fn with_patch_queue(mut self, queue: Arc<PatchQueue>) -> Self {
+        self.patch_queue = Some(queue);
+        self
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(self.limits.timeout_secs)
+    }
+
+    /// `command` wrapped in a subshell that sets the CPU and memory limits
+    ///
+    /// Unchanged when neither limit is set, so commands like `cd` keep
+    /// affecting the shared terminal.
+    fn limited(&self, command: &str) -> String {
+        if cfg!(target_os = "windows") {
+            return command.to_string();
+        }
+        let mut ulimits = String::new();
+        if let Some(cpu) = self.limits.cpu_secs {
+            ulimits.push_str(&format!("ulimit -t {}; ", cpu));
+        }
+        if let Some(mb) = self.limits.memory_mb {
+            ulimits.push_str(&format!("ulimit -v {}; ", mb * 1024));
+        }
+        if ulimits.is_empty() {
+            command.to_string()
+        } else {
+            // Newline before `)` so a trailing comment cannot swallow it
+            format!("({}{}\n)", ulimits, command)
+        }
+    }
+
+    /// Cut output at `max_output_bytes` and say so
+    fn truncate(&self, mut output: String) -> String {
+        let max = self.limits.max_output_bytes;
+        if output.len() <= max {
+            return output;
+        }
+        let total = output.len();
+        let mut cut = max;
+        while !output.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        output.truncate(cut);
+        output.push_str(&format!(
+            "\n... [output truncated after {} of {}; narrow the command or filter its output]",
+            format_size_rounded(max as u64),
+            format_size_rounded(total as u64)
+        ));
+        output
+    }
+
+    /// Failure message, naming the limit that killed the command if any
+    fn failure(&self, error: &str) -> String {
+        let error = self.truncate(error.to_string());
+        let mut message = format!("Command failed: {}", error);
+        if let Some(cpu) = self.limits.cpu_secs {
+            if error.starts_with(CPU_LIMIT_EXIT) {
+                message.push_str(&format!("\n[killed after {}s of CPU time (cpu_secs limit)]", cpu));
+            }
+        }
+        if let Some(mb) = self.limits.memory_mb {
+            let lower = error.to_lowercase();
+            if lower.contains("memory") || lower.contains("alloc") {
+                message.push_str(&format!("\n[memory is limited to {} MB per command (memory_mb limit)]", mb));
+            }
+        }
+        message
+    }
+
+    fn timed_out(&self) -> ToolResult {
+        ToolResult::Error {
+            message: format!(
+                "Command killed after {}s (timeout_secs limit); run long jobs in the background or break them up",
+                self.limits.timeout_secs
+            ),
+            code: Some("TIMEOUT".to_string()),
+            retryable: true,
+        }
     }
 
     /// Execute a shell command with context
@@ -36,15 +137,12 @@ impl ShellTool {
         _background: bool
     ) -> Result<ToolResult, ToolError> {
         // SECURITY: Basic command validation
-        let dangerous_patterns = ["rm -rf /", "> /dev/sda", "dd if=/dev/zero"];
-        for pattern in &dangerous_patterns {
-            if command.contains(pattern) {
-                return Ok(ToolResult::Error {
-                    message: format!("Command blocked for safety: contains '{}'", pattern),
-                    code: Some("SAFETY_BLOCK".to_string()),
-                    retryable: false,
-                });
-            }
+        if let Some(pattern) = blocked_pattern(command) {
+            return Ok(ToolResult::Error {
+                message: format!("Command blocked for safety: contains '{}'", pattern),
+                code: Some("SAFETY_BLOCK".to_string()),
+                retryable: false,
+            });
         }
 
         // Get current working directory from context
@@ -66,47 +164,30 @@ impl ShellTool {
         }
 
         // Execute with timeout
-        let result = timeout(
-            Duration::from_secs(DEFAULT_TIMEOUT_SECS),
-            self.run_command(command, &cwd),
-        )
-        .await;
+        let result = timeout(self.timeout(), self.run_command(&self.limited(command), &cwd)).await;
 
         match result {
             Ok(Ok(output)) => Ok(ToolResult::Success {
-                output,
+                output: self.truncate(output),
                 structured: None,
             }),
             Ok(Err(e)) => Ok(ToolResult::Error {
-                message: format!("Command failed: {}", e),
+                message: self.failure(&e.to_string()),
                 code: Some("EXEC_ERROR".to_string()),
                 retryable: false,
             }),
-            Err(_) => Ok(ToolResult::Error {
-                message: format!("Command timed out after {} seconds", DEFAULT_TIMEOUT_SECS),
-                code: Some("TIMEOUT".to_string()),
-                retryable: true,
-            }),
+            Err(_) => Ok(self.timed_out()),
         }
     }
 
     /// Run the actual command with given working directory
     async fn run_command(&self, command: &str, cwd: &std::path::Path) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        use tokio::process::Command;
-        
-        let output = if cfg!(target_os = "windows") {
-            Command::new("cmd")
-                .args(["/C", command])
-                .current_dir(cwd)
-                .output()
-                .await?
-        } else {
-            Command::new("sh")
-                .args(["-c", command])
-                .current_dir(cwd)
-                .output()
-                .await?
-        };
+        let output = Shell::for_commands()
+            .command(command)
+            .current_dir(cwd)
+            .kill_on_drop(true)
+            .output()
+            .await?;
 
         let mut result = String::new();
 
@@ -127,12 +208,6 @@ impl ShellTool {
             result.push_str(&stderr);
         }
 
-        // Truncate if too large
-        if result.len() > MAX_OUTPUT_SIZE {
-            result.truncate(MAX_OUTPUT_SIZE);
-            result.push_str("\n... [output truncated]");
-        }
-
         if output.status.success() {
             Ok(result)
         } else {
@@ -153,15 +228,12 @@ impl ShellTool {
         _background: bool,
     ) -> Result<ToolResult, ToolError> {
         // SECURITY: Basic command validation
-        let dangerous_patterns = ["rm -rf /", "> /dev/sda", "dd if=/dev/zero"];
-        for pattern in &dangerous_patterns {
-            if command.contains(pattern) {
-                return Ok(ToolResult::Error {
-                    message: format!("Command blocked for safety: contains '{}'", pattern),
-                    code: Some("SAFETY_BLOCK".to_string()),
-                    retryable: false,
-                });
-            }
+        if let Some(pattern) = blocked_pattern(command) {
+            return Ok(ToolResult::Error {
+                message: format!("Command blocked for safety: contains '{}'", pattern),
+                code: Some("SAFETY_BLOCK".to_string()),
+                retryable: false,
+            });
         }
 
         // Get current working directory from context
@@ -186,22 +258,11 @@ impl ShellTool {
         let screen_before = terminal.get_screen().await.unwrap_or_default();
 
         // Execute with timeout
-        let result = terminal.execute_command(
-            command.to_string(),
-            Some(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
-        ).await;
+        let result = terminal.execute_command(self.limited(command), Some(self.timeout())).await;
 
         match result {
             Ok(output) => {
-                // Truncate if too large
-                let output = if output.len() > MAX_OUTPUT_SIZE {
-                    let mut truncated = output;
-                    truncated.truncate(MAX_OUTPUT_SIZE);
-                    truncated.push_str("\n... [output truncated]");
-                    truncated
-                } else {
-                    output
-                };
+                let output = self.truncate(output);
 
                 // Combine screen context with output for the agent
                 let combined = if screen_before.is_empty() {
@@ -219,8 +280,9 @@ impl ShellTool {
                     structured: None,
                 })
             }
-            Err(e) => Ok(ToolResult::Error {
-                message: format!("Command failed: {}", e),
+            Err(ExecError::TimedOut) => Ok(self.timed_out()),
+            Err(ExecError::Failed(e)) => Ok(ToolResult::Error {
+                message: self.failure(&e),
                 code: Some("EXEC_ERROR".to_string()),
                 retryable: false,
             }),
@@ -251,6 +313,14 @@ impl Default for ShellMode {
 
 #[async_trait::async_trait]
 impl ToolCapability for ShellTool {
+    fn description(&self) -> &'static str {
+        "Execute or suggest shell commands; dry_run shows what a command would touch without running it"
+    }
+
+    fn usage(&self) -> &'static str {
+        "Execute: {\"a\": \"shell\", \"i\": {\"command\": \"<cmd>\"}} | Suggest: {\"a\": \"shell\", \"i\": {\"command\": \"<cmd>\", \"mode\": \"suggest\"}} | Dry run: {\"a\": \"shell\", \"i\": {\"command\": \"<cmd>\", \"dry_run\": true}}"
+    }
+
     async fn execute(
         &self,
         ctx: &RuntimeContext,
@@ -294,6 +364,30 @@ impl ToolCapability for ShellTool {
             });
         }
 
+        // Dry run: analyse only, never spawn
+        let dry_run = call.arguments
+            .get("dry_run")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        if dry_run {
+            let preview = CommandPreview::analyze(&args_str);
+            return Ok(ToolResult::Success {
+                output: preview.render(),
+                structured: serde_json::to_value(&preview).ok(),
+            });
+        }
+
+        let changeset_open = self.patch_queue.as_ref().is_some_and(|queue| queue.is_active());
+        if changeset_open && !CommandPreview::analyze(&args_str).is_read_only() {
+            return Ok(ToolResult::Error {
+                message: "A changeset is open and this command may change files outside it; \
+                    edit with write_file, apply_patch or edit_csv, or ask the user to apply or discard the changeset first"
+                    .to_string(),
+                code: Some("CHANGESET_OPEN".to_string()),
+                retryable: false,
+            });
+        }
+
         // Use terminal executor from context if available
         if let Some(terminal) = ctx.terminal() {
             self.execute_shell_with_terminal(ctx, terminal, &args_str, background).await
@@ -364,4 +458,100 @@ mod tests {
             _ => panic!("Expected error for blocked command"),
         }
     }
+
+    #[tokio::test]
+    async fn test_shell_limits_surface_in_result() {
+        let call = |command: &str| ToolCall {
+            name: "shell".to_string(),
+            arguments: serde_json::json!(command),
+            working_dir: None,
+            timeout_secs: None,
+        };
+
+        let tool = ShellTool::new().with_limits(CommandLimits {
+            max_output_bytes: 2048,
+            ..CommandLimits::default()
+        });
+        match tool.execute(&RuntimeContext::new(), call("yes | head -c 10000")).await.unwrap() {
+            ToolResult::Success { output, .. } => {
+                assert!(output.contains("[output truncated after 2 KB of 10 KB"));
+            }
+            other => panic!("Expected success, got {:?}", other),
+        }
+
+        let tool = ShellTool::new().with_limits(CommandLimits {
+            timeout_secs: 1,
+            ..CommandLimits::default()
+        });
+        match tool.execute(&RuntimeContext::new(), call("sleep 5")).await.unwrap() {
+            ToolResult::Error { message, code, .. } => {
+                assert_eq!(code.as_deref(), Some("TIMEOUT"));
+                assert!(message.starts_with("Command killed after 1s"));
+            }
+            other => panic!("Expected timeout, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_limited_wraps_only_when_needed() {
+        assert_eq!(ShellTool::new().limited("cd src"), "cd src");
+        let tool = ShellTool::new().with_limits(CommandLimits {
+            cpu_secs: Some(60),
+            memory_mb: Some(512),
+            ..CommandLimits::default()
+        });
+        assert_eq!(tool.limited("make # all"), "(ulimit -t 60; ulimit -v 524288; make # all\n)");
+        assert!(tool.failure("Exit code 152: ").contains("killed after 60s of CPU time"));
+    }
+
+    #[tokio::test]
+    async fn test_shell_dry_run_never_spawns() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let marker = dir.path().join("created");
+        let tool = ShellTool::new();
+        let call = ToolCall {
+            name: "shell".to_string(),
+            arguments: serde_json::json!({
+                "command": format!("touch {}", marker.display()),
+                "dry_run": true
+            }),
+            working_dir: None,
+            timeout_secs: None,
+        };
+
+        match tool.execute(&RuntimeContext::new(), call).await.unwrap() {
+            ToolResult::Success { output, structured } => {
+                assert!(output.starts_with("Dry run"));
+                assert!(output.contains(&format!("Would write: {}", marker.display())));
+                assert!(structured.is_some());
+            }
+            other => panic!("Expected success, got {:?}", other),
+        }
+        assert!(!marker.exists());
+    }
+
+    #[tokio::test]
+    async fn test_shell_refuses_writes_while_changeset_open() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let queue = Arc::new(PatchQueue::with_dir(dir.path().join("changesets")));
+        queue.open("review").unwrap();
+        let tool = ShellTool::new().with_patch_queue(Arc::clone(&queue));
+        let call = |command: String| ToolCall {
+            name: "shell".to_string(),
+            arguments: serde_json::json!(command),
+            working_dir: None,
+            timeout_secs: None,
+        };
+
+        let marker = dir.path().join("created");
+        match tool.execute(&RuntimeContext::new(), call(format!("touch {}", marker.display()))).await.unwrap() {
+            ToolResult::Error { code, .. } => assert_eq!(code.as_deref(), Some("CHANGESET_OPEN")),
+            other => panic!("Expected refusal, got {:?}", other),
+        }
+        assert!(!marker.exists());
+        assert!(matches!(
+            tool.execute(&RuntimeContext::new(), call("echo hello".to_string())).await.unwrap(),
+            ToolResult::Success { .. }
+        ));
+    }
 }
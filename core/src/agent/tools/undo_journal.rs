@@ -0,0 +1,424 @@
+//! Undo Journal - Roll back the agent's file edits turn by turn
+//!
+//! Before `write_file` or `apply_patch` changes a file on disk, the file's
+//! previous content is copied to `undo/<workspace>/<session>/<turn>/` in
+//! the data directory, once per file and turn, along with a hash of what
+//! the agent wrote. `/undo` in the TUI puts back the files of the session's
+//! latest turn and `mylm rollback --last` those of the workspace's latest
+//! turn; both delete the files the turn created and drop it from the
+//! journal, so repeating them walks further back. Only the most recent
+//! turns of each session are kept.
+//!
+//! An undo only touches files inside the workspace, never writes through
+//! a symlink, and refuses to overwrite a file that changed since the agent
+//! wrote it; nothing is restored unless every file of the turn can be.
+//!
+//! Edits staged in a patch queue changeset are not journaled; they are not
+//! on disk until the user applies them.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::agent::tools::patch_queue::{absolute, display_path, title_from};
+
+/// Journal location inside the data directory
+pub const JOURNAL_DIR: &str = "undo";
+/// Turns kept per session; older ones are deleted when a new turn starts
+const MAX_TURNS: usize = 20;
+/// Turn description within a turn directory
+const MANIFEST: &str = "turn.json";
+
+/// A file as it was before a turn changed it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedFile {
+    /// Absolute path of the file
+    pub path: PathBuf,
+    /// Copy of the previous content in the turn directory; None when the
+    /// turn created the file
+    pub backup: Option<String>,
+    /// SHA-256 of the content the agent last wrote, hex
+    #[serde(default)]
+    pub written: Option<String>,
+}
+
+/// The files changed during one agent turn
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UndoTurn {
+    /// Directory name; turns sort by it
+    pub id: String,
+    /// Session directory the turn is in
+    #[serde(default)]
+    pub session: String,
+    /// The user message that started the turn
+    pub title: String,
+    /// Unix timestamp (seconds)
+    pub started: u64,
+    pub files: Vec<SavedFile>,
+}
+
+impl UndoTurn {
+    /// The turn's title and the files an undo puts back
+    pub fn summary(&self) -> String {
+        let mut out = format!("{} ({} file(s))", self.title, self.files.len());
+        for file in &self.files {
+            let note = if file.backup.is_none() { " (created)" } else { "" };
+            out.push_str(&format!("\n  {}{}", display_path(&file.path), note));
+        }
+        out
+    }
+}
+
+#[derive(Debug, Default)]
+struct JournalState {
+    /// Turn edits are recorded in, once it has one
+    current: Option<UndoTurn>,
+    /// Title of the turn the next edit starts
+    next_title: Option<String>,
+}
+
+/// Previous contents of the files changed by the agent, per turn
+#[derive(Debug)]
+pub struct UndoJournal {
+    /// Journal of the workspace, one directory per session
+    dir: PathBuf,
+    /// Workspace root; files outside it are neither journaled nor restored
+    root: PathBuf,
+    /// Session edits are recorded in; None reads every session
+    session: Option<String>,
+    state: Mutex<JournalState>,
+}
+
+/// SHA-256 of `content`, hex
+fn content_hash(content: &[u8]) -> String {
+    hex::encode(Sha256::digest(content))
+}
+
+impl UndoJournal {
+    /// Journal of one session in the workspace at `root`
+    pub fn for_session(root: &Path, session: &str) -> Self {
+        Self::in_dir(&Self::base_dir(), root, Some(session.to_string()))
+    }
+
+    /// Journal of every session in the workspace at `root`, as `mylm
+    /// rollback` sees it
+    pub fn for_workspace(root: &Path) -> Self {
+        Self::in_dir(&Self::base_dir(), root, None)
+    }
+
+    /// `undo` in the data directory
+    fn base_dir() -> PathBuf {
+        dirs::data_dir().unwrap_or_else(std::env::temp_dir).join("mylm").join(JOURNAL_DIR)
+    }
+
+    /// Journal kept under `base`, keyed by the workspace's canonical path
+    pub fn in_dir(base: &Path, root: &Path, session: Option<String>) -> Self {
+        let root = root.canonicalize().unwrap_or_else(|_| absolute(root));
+        let key = content_hash(root.to_string_lossy().as_bytes());
+        Self {
+            dir: base.join(&key[..16]),
+            root,
+            session,
+            state: Mutex::new(JournalState::default()),
+        }
+    }
+
+    fn session_dir(&self) -> PathBuf {
+        self.dir.join(self.session.as_deref().unwrap_or("shared"))
+    }
+
+    fn turn_dir(&self, turn: &UndoTurn) -> PathBuf {
+        self.dir.join(&turn.session).join(&turn.id)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, JournalState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Edits from now on form a new turn, titled after `message`
+    pub fn begin_turn(&self, message: &str) {
+        let mut state = self.lock();
+        state.current = None;
+        state.next_title = Some(title_from(message));
+    }
+
+    /// Save the content of `path` before it is written, unless the current
+    /// turn already saved it
+    pub fn record(&self, path: &Path) -> Result<(), String> {
+        let path = absolute(path);
+        if !path.starts_with(&self.root) {
+            crate::info_log!("[UNDO] Not journaling {}: outside the workspace", path.display());
+            return Ok(());
+        }
+        let mut guard = self.lock();
+        let state = &mut *guard;
+        if state.current.as_ref().is_some_and(|turn| turn.files.iter().any(|f| f.path == path)) {
+            return Ok(());
+        }
+        let turn = match state.current {
+            Some(ref mut turn) => turn,
+            None => {
+                let title = state.next_title.take().unwrap_or_else(|| "Agent changes".to_string());
+                state.current.insert(self.start_turn(title)?)
+            }
+        };
+        let dir = self.turn_dir(turn);
+        let backup = match std::fs::read(&path) {
+            Ok(content) => {
+                let name = turn.files.len().to_string();
+                crate::util::write_private(&dir.join(&name), &content)
+                    .map_err(|e| format!("Cannot save undo copy of {}: {}", path.display(), e))?;
+                Some(name)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(format!("Cannot read {}: {}", path.display(), e)),
+        };
+        turn.files.push(SavedFile { path, backup, written: None });
+        save_manifest(&dir, turn)
+    }
+
+    /// Note what the agent wrote to `path`, so an undo can tell whether the
+    /// file changed since
+    pub fn written(&self, path: &Path, content: &[u8]) {
+        let path = absolute(path);
+        let mut state = self.lock();
+        let Some(turn) = state.current.as_mut() else {
+            return;
+        };
+        let Some(file) = turn.files.iter_mut().find(|f| f.path == path) else {
+            return;
+        };
+        file.written = Some(content_hash(content));
+        let dir = self.turn_dir(turn);
+        if let Err(e) = save_manifest(&dir, turn) {
+            crate::warn_log!("[UNDO] {}", e);
+        }
+    }
+
+    /// Create the directory of a new turn, pruning the session's oldest ones
+    fn start_turn(&self, title: String) -> Result<UndoTurn, String> {
+        let session_dir = self.session_dir();
+        std::fs::create_dir_all(&session_dir).map_err(|e| format!("Cannot create {}: {}", session_dir.display(), e))?;
+        let session = self.session.clone().unwrap_or_else(|| "shared".to_string());
+        let turns: Vec<UndoTurn> = self.turns().into_iter().filter(|turn| turn.session == session).collect();
+        for old in turns.iter().take((turns.len() + 1).saturating_sub(MAX_TURNS)) {
+            let _ = std::fs::remove_dir_all(self.turn_dir(old));
+        }
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let mut millis = now.as_millis();
+        while session_dir.join(format!("{:015}", millis)).exists() {
+            millis += 1;
+        }
+        let turn = UndoTurn {
+            id: format!("{:015}", millis),
+            session,
+            title,
+            started: now.as_secs(),
+            files: Vec::new(),
+        };
+        let dir = self.turn_dir(&turn);
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+        save_manifest(&dir, &turn)?;
+        Ok(turn)
+    }
+
+    /// Journaled turns of the session, or of every session, oldest first
+    pub fn turns(&self) -> Vec<UndoTurn> {
+        let sessions: Vec<PathBuf> = match self.session {
+            Some(_) => vec![self.session_dir()],
+            None => std::fs::read_dir(&self.dir)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .map(|entry| entry.path())
+                .collect(),
+        };
+        let mut turns: Vec<UndoTurn> = sessions
+            .iter()
+            .flat_map(|session| std::fs::read_dir(session).into_iter().flatten().flatten())
+            .filter_map(|entry| {
+                let json = std::fs::read_to_string(entry.path().join(MANIFEST)).ok()?;
+                let mut turn: UndoTurn = serde_json::from_str(&json).ok()?;
+                turn.session = entry.path().parent()?.file_name()?.to_string_lossy().into_owned();
+                Some(turn)
+            })
+            .collect();
+        turns.sort_by(|a, b| a.id.cmp(&b.id));
+        turns
+    }
+
+    /// Put back the files of the latest turn and drop it from the journal
+    ///
+    /// Every file is checked first; if one cannot be restored nothing is,
+    /// and the turn stays in the journal so the undo can be retried.
+    pub fn rollback_last(&self) -> Result<UndoTurn, String> {
+        let turn = self
+            .turns()
+            .into_iter()
+            .rev()
+            .find(|turn| !turn.files.is_empty())
+            .ok_or("No agent edits to undo")?;
+        let dir = self.turn_dir(&turn);
+        let refused: Vec<String> = turn
+            .files
+            .iter()
+            .filter_map(|file| check_restorable(&self.root, &dir, file).err())
+            .collect();
+        if !refused.is_empty() {
+            return Err(refused.join("\n"));
+        }
+        let failures: Vec<String> = turn
+            .files
+            .iter()
+            .filter_map(|file| restore(&dir, file).err())
+            .collect();
+        if !failures.is_empty() {
+            return Err(failures.join("\n"));
+        }
+
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut state = self.lock();
+        if state.current.as_ref().is_some_and(|current| current.id == turn.id) {
+            state.current = None;
+        }
+        Ok(turn)
+    }
+}
+
+fn save_manifest(dir: &Path, turn: &UndoTurn) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(turn).map_err(|e| e.to_string())?;
+    crate::util::write_private(&dir.join(MANIFEST), json.as_bytes()).map_err(|e| format!("Cannot save undo journal: {}", e))
+}
+
+/// Why `file` must not be put back: outside `root`, behind a symlink, or
+/// changed since the agent wrote it
+fn check_restorable(root: &Path, dir: &Path, file: &SavedFile) -> Result<(), String> {
+    let path = &file.path;
+    let refuse = |reason: &str| Err(format!("Not restoring {}: {}", path.display(), reason));
+    if !absolute(path).starts_with(root) {
+        return refuse("it is outside the workspace");
+    }
+    // The nearest existing ancestor must resolve inside the workspace too
+    let ancestor = path.ancestors().skip(1).find(|dir| dir.exists()).unwrap_or(root);
+    if !ancestor.canonicalize().is_ok_and(|dir| dir.starts_with(root)) {
+        return refuse("a parent directory leads outside the workspace");
+    }
+    if std::fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_symlink()) {
+        return refuse("it is now a symlink");
+    }
+    if let Some(ref written) = file.written {
+        let current = std::fs::read(path).ok().map(|content| content_hash(&content));
+        if current.as_ref() != Some(written) {
+            let backup = match file.backup {
+                Some(ref name) => format!("; the earlier version is in {}", dir.join(name).display()),
+                None => String::new(),
+            };
+            return refuse(&format!("it changed since the agent wrote it{}", backup));
+        }
+    }
+    Ok(())
+}
+
+/// Put `file` back as it was, from the turn directory `dir`
+fn restore(dir: &Path, file: &SavedFile) -> Result<(), String> {
+    let failed = |e: std::io::Error| format!("Cannot restore {}: {}", file.path.display(), e);
+    crate::watch::note_agent_write(&file.path);
+    match file.backup {
+        Some(ref name) => {
+            let content = std::fs::read(dir.join(name)).map_err(failed)?;
+            if let Some(parent) = file.path.parent() {
+                std::fs::create_dir_all(parent).map_err(failed)?;
+            }
+            // Renaming over the path replaces a symlink instead of following it
+            let name = file.path.file_name().unwrap_or_default().to_string_lossy();
+            let staged = file.path.with_file_name(format!(".{}.mylm-undo", name));
+            crate::watch::note_agent_write(&staged);
+            std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&staged)
+                .and_then(|mut out| std::io::Write::write_all(&mut out, &content))
+                .and_then(|_| std::fs::rename(&staged, &file.path))
+                .map_err(|e| {
+                    let _ = std::fs::remove_file(&staged);
+                    failed(e)
+                })
+        }
+        None => match std::fs::remove_file(&file.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(failed(e)),
+            _ => Ok(()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rollback_restores_turns_in_reverse() {
+        let temp = TempDir::new().unwrap();
+        let workspace = temp.path().join("ws");
+        std::fs::create_dir_all(&workspace).unwrap();
+        let workspace = workspace.canonicalize().unwrap();
+        let base = temp.path().join("undo");
+        let journal = UndoJournal::in_dir(&base, &workspace, Some("s1".to_string()));
+        let other_tab = UndoJournal::in_dir(&base, &workspace, Some("s2".to_string()));
+        let edited = workspace.join("lib.rs");
+        let created = workspace.join("new/mod.rs");
+        std::fs::write(&edited, "v1").unwrap();
+        let write = |journal: &UndoJournal, path: &Path, content: &str| {
+            journal.record(path).unwrap();
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+            journal.written(path, content.as_bytes());
+        };
+
+        journal.begin_turn("rename things\nin lib.rs");
+        write(&journal, &edited, "v2");
+        write(&journal, &edited, "v3");
+        write(&journal, &created, "mod");
+        journal.record(&temp.path().join("outside.txt")).unwrap();
+
+        journal.begin_turn("no edits this time");
+        journal.begin_turn("tweak");
+        write(&journal, &edited, "v4");
+
+        let turns = journal.turns();
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[0].title, "rename things");
+        assert_eq!(turns[0].files.len(), 2);
+        assert!(other_tab.turns().is_empty());
+        assert_eq!(UndoJournal::in_dir(&base, &workspace, None).turns().len(), 2);
+
+        // A file edited after the agent wrote it is left alone
+        std::fs::write(&edited, "v4 and a user fix").unwrap();
+        assert!(journal.rollback_last().unwrap_err().contains("changed since the agent wrote it"));
+        std::fs::write(&edited, "v4").unwrap();
+
+        assert_eq!(journal.rollback_last().unwrap().title, "tweak");
+        assert_eq!(std::fs::read_to_string(&edited).unwrap(), "v3");
+        let undone = journal.rollback_last().unwrap();
+        assert!(undone.summary().contains("mod.rs (created)"));
+        assert_eq!(std::fs::read_to_string(&edited).unwrap(), "v1");
+        assert!(!created.exists());
+        assert!(journal.rollback_last().is_err());
+
+        #[cfg(unix)]
+        {
+            let target = temp.path().join("target.txt");
+            std::fs::write(&target, "keep").unwrap();
+            journal.begin_turn("symlink");
+            write(&journal, &edited, "v5");
+            std::fs::remove_file(&edited).unwrap();
+            std::os::unix::fs::symlink(&target, &edited).unwrap();
+            assert!(journal.rollback_last().is_err());
+            assert_eq!(std::fs::read_to_string(&target).unwrap(), "keep");
+        }
+    }
+}
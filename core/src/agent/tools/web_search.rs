@@ -172,7 +172,7 @@ impl WebSearchTool {
         
         // Update our config from the profile settings
         self.config.enabled = profile.web_search.enabled;
-        self.config.api_key = profile.web_search.api_key.clone();
+        self.config.api_key = crate::config::secrets::resolve_key(profile.web_search.api_key.as_deref());
         
         // Map the config SearchProvider to our internal SearchProvider
         self.config.provider = match profile.web_search.provider {
@@ -710,6 +710,14 @@ impl Capability for WebSearchTool {
 
 #[async_trait::async_trait]
 impl ToolCapability for WebSearchTool {
+    fn description(&self) -> &'static str {
+        "Search the web for information"
+    }
+
+    fn usage(&self) -> &'static str {
+        "{\"a\": \"web_search\", \"i\": {\"query\": \"<search>\"}}"
+    }
+
     async fn execute(
         &self,
         _ctx: &RuntimeContext,
@@ -796,7 +804,8 @@ impl ToolCapability for WebSearchTool {
         crate::info_log!("[WEB_SEARCH] Searching for query: '{}'", query);
         
         // Get API key from disk config for providers that need it
-        let api_key = profile.web_search.api_key.as_deref();
+        let api_key = crate::config::secrets::resolve_key(profile.web_search.api_key.as_deref());
+        let api_key = api_key.as_deref();
         
         // Execute search and log results
         let result = match provider {
@@ -15,6 +15,7 @@ use crate::agent::runtime::core::{Capability, ToolCapability};
 use crate::agent::runtime::core::RuntimeContext;
 use crate::agent::runtime::core::ToolError;
 use crate::agent::runtime::core::terminal::TerminalExecutor;
+use crate::agent::runtime::core::Shell;
 use crate::agent::types::intents::ToolCall;
 use crate::agent::types::events::ToolResult;
 use tokio::sync::{mpsc, oneshot};
@@ -504,26 +505,11 @@ impl WorkerShellTool {
         command: &str, 
         cwd: &std::path::Path
     ) -> Result<ToolResult, ToolError> {
-        use tokio::process::Command;
         use tokio::time::timeout;
         
         let output = timeout(
             Duration::from_secs(DEFAULT_TIMEOUT_SECS),
-            async {
-                if cfg!(target_os = "windows") {
-                    Command::new("cmd")
-                        .args(["/C", command])
-                        .current_dir(cwd)
-                        .output()
-                        .await
-                } else {
-                    Command::new("sh")
-                        .args(["-c", command])
-                        .current_dir(cwd)
-                        .output()
-                        .await
-                }
-            }
+            Shell::for_commands().command(command).current_dir(cwd).output(),
         ).await;
 
         match output {
@@ -1,22 +1,44 @@
 //! Write File Tool - Write content to files
 //!
 //! Provides file writing capabilities with automatic directory creation.
+//! While a changeset is open in the patch queue, writes are queued there
+//! instead of hitting disk. Writes to disk are recorded in the undo journal.
 
 use crate::agent::runtime::core::{Capability, RuntimeContext, ToolCapability, ToolError};
 use crate::agent::types::events::ToolResult;
 use crate::agent::types::intents::ToolCall;
 use crate::agent::tools::expand_tilde;
+use crate::agent::tools::patch_queue::PatchQueue;
+use crate::agent::tools::undo_journal::UndoJournal;
 use serde::Deserialize;
 use std::path::Path;
+use std::sync::Arc;
 
 /// Tool for writing files
 #[derive(Debug, Default)]
-pub struct WriteFileTool;
+pub struct WriteFileTool {
+    /// Queue that takes writes while a changeset is open
+    patch_queue: Option<Arc<PatchQueue>>,
+    /// Journal that keeps the previous content of written files
+    undo_journal: Option<Arc<UndoJournal>>,
+}
 
 impl WriteFileTool {
     /// Create a new write file tool
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+    
+    /// Queue writes in `queue` while it has a changeset open
+    pub fn with_patch_queue(mut self, queue: Arc<PatchQueue>) -> Self {
+        self.patch_queue = Some(queue);
+        self
+    }
+    
+    /// Save the previous content of written files in `journal`
+    pub fn with_undo_journal(mut self, journal: Arc<UndoJournal>) -> Self {
+        self.undo_journal = Some(journal);
+        self
     }
     
     /// Write content to a file
@@ -26,6 +48,18 @@ impl WriteFileTool {
         let path = expand_tilde(path);
         let path = Path::new(&path);
         
+        if let Some(queue) = self.patch_queue.as_ref().filter(|q| q.is_active()) {
+            return Ok(queue_write(queue, path, content));
+        }
+        
+        if let Some(Err(e)) = self.undo_journal.as_ref().map(|journal| journal.record(path)) {
+            return Ok(ToolResult::Error {
+                message: format!("{}; file not written", e),
+                code: Some("UNDO_ERROR".to_string()),
+                retryable: false,
+            });
+        }
+        
         // Ensure parent directory exists
         if let Some(parent) = path.parent() {
             if let Err(e) = tokio::fs::create_dir_all(parent).await {
@@ -38,14 +72,20 @@ impl WriteFileTool {
         }
         
         // Write file
+        crate::watch::note_agent_write(path);
         match tokio::fs::write(path, content).await {
-            Ok(()) => Ok(ToolResult::Success {
-                output: format!("File written successfully: {}", path.display()),
-                structured: Some(serde_json::json!({
-                    "path": path.to_string_lossy(),
-                    "bytes_written": content.len(),
-                })),
-            }),
+            Ok(()) => {
+                if let Some(journal) = &self.undo_journal {
+                    journal.written(path, content.as_bytes());
+                }
+                Ok(ToolResult::Success {
+                    output: format!("File written successfully: {}", path.display()),
+                    structured: Some(serde_json::json!({
+                        "path": path.to_string_lossy(),
+                        "bytes_written": content.len(),
+                    })),
+                })
+            }
             Err(e) => Ok(ToolResult::Error {
                 message: format!("Error writing file: {}", e),
                 code: Some("WRITE_ERROR".to_string()),
@@ -55,6 +95,37 @@ impl WriteFileTool {
     }
 }
 
+/// Stage a write in the open changeset instead of writing the file
+pub(crate) fn queue_write(queue: &PatchQueue, path: &Path, content: &str) -> ToolResult {
+    match queue.record(path, content) {
+        Ok(change) => {
+            let changeset = queue.changeset().map(|c| (c.name, c.patches.len())).unwrap_or_default();
+            ToolResult::Success {
+                output: format!(
+                    "Queued change to {} (+{} -{}) in patch {} of changeset '{}'. It is not on disk until the user applies the changeset.",
+                    path.display(),
+                    change.added,
+                    change.removed,
+                    changeset.1,
+                    changeset.0
+                ),
+                structured: Some(serde_json::json!({
+                    "path": path.to_string_lossy(),
+                    "queued": true,
+                    "changeset": changeset.0,
+                    "added": change.added,
+                    "removed": change.removed,
+                })),
+            }
+        }
+        Err(e) => ToolResult::Error {
+            message: format!("Cannot queue change to {}: {}", path.display(), e),
+            code: Some("QUEUE_ERROR".to_string()),
+            retryable: false,
+        },
+    }
+}
+
 impl Capability for WriteFileTool {
     fn name(&self) -> &'static str {
         "write_file"
@@ -70,6 +141,14 @@ struct WriteArgs {
 
 #[async_trait::async_trait]
 impl ToolCapability for WriteFileTool {
+    fn description(&self) -> &'static str {
+        "Write content to file"
+    }
+
+    fn usage(&self) -> &'static str {
+        "{\"a\": \"write_file\", \"i\": {\"path\": \"<path>\", \"content\": \"<content>\"}}"
+    }
+
     async fn execute(
         &self,
         _ctx: &RuntimeContext,
@@ -173,6 +252,27 @@ mod tests {
         assert_eq!(content, "new content");
     }
     
+    #[tokio::test]
+    async fn test_write_file_is_queued_while_changeset_open() {
+        let temp = TempDir::new().unwrap();
+        let file_path = temp.path().join("queued.txt");
+        let queue = Arc::new(PatchQueue::with_dir(temp.path().join("changesets")));
+        queue.open("review").unwrap();
+        
+        let tool = WriteFileTool::new().with_patch_queue(Arc::clone(&queue));
+        let call = ToolCall::new("write_file", serde_json::json!({
+            "path": file_path.to_str().unwrap(),
+            "content": "queued content\n"
+        }));
+        
+        let result = tool.execute(&RuntimeContext::new(), call).await.unwrap();
+        assert!(matches!(result, ToolResult::Success { ref output, .. } if output.contains("changeset 'review'")));
+        assert!(!file_path.exists());
+        
+        queue.apply(&[]).unwrap();
+        assert_eq!(tokio::fs::read_to_string(&file_path).await.unwrap(), "queued content\n");
+    }
+    
     #[tokio::test]
     async fn test_write_file_empty_content() {
         let temp = TempDir::new().unwrap();
@@ -310,7 +310,7 @@ mod tests {
             NodeId::new(42),
             LogicalClock::new(5),
             SessionId::generate(),
-            KernelEvent::UserMessage { content: "hello".to_string() },
+            KernelEvent::UserMessage { content: "hello".to_string(), images: Vec::new() },
             1,
         );
 
@@ -327,7 +327,7 @@ mod tests {
             NodeId::new(1),
             LogicalClock::new(5),
             SessionId::generate(),
-            KernelEvent::UserMessage { content: "parent".to_string() },
+            KernelEvent::UserMessage { content: "parent".to_string(), images: Vec::new() },
             1,
         );
 
@@ -353,12 +353,12 @@ mod tests {
         );
 
         let env1 = builder.build_auto(
-            KernelEvent::UserMessage { content: "first".to_string() },
+            KernelEvent::UserMessage { content: "first".to_string(), images: Vec::new() },
             &mut next_id,
         );
         
         let env2 = builder.build_auto(
-            KernelEvent::UserMessage { content: "second".to_string() },
+            KernelEvent::UserMessage { content: "second".to_string(), images: Vec::new() },
             &mut next_id,
         );
 
@@ -22,8 +22,43 @@ pub enum KernelEvent {
     /// User sent a message
     UserMessage {
         content: String,
+        /// Images attached to it, already read
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        images: Vec<crate::provider::chat::ImageAttachment>,
     },
 
+    /// A message answered outside the planner (fast path); only recorded
+    /// in history so later turns see the exchange
+    DirectExchange {
+        /// The user's message
+        question: String,
+        /// The answer shown to the user
+        answer: String,
+    },
+
+    /// The oldest messages were summarized to free context
+    HistoryCondensed {
+        /// How many messages, from the start of history, the summary replaces
+        replaced: usize,
+        /// The summary message
+        summary: String,
+        /// Pinned messages from the replaced ones, kept after the summary
+        #[serde(default)]
+        kept: Vec<crate::conversation::manager::Message>,
+    },
+
+    /// A condensed segment was brought back
+    HistoryRestored {
+        /// The segment's ID
+        segment_id: String,
+        /// Its messages, put back in place of its summary
+        messages: Vec<crate::conversation::manager::Message>,
+    },
+
+    /// The user's last message and everything after it were dropped, so
+    /// the turn can be edited or run again
+    HistoryRewound,
+
     /// A tool was executed and returned a result
     ToolCompleted {
         /// The intent ID that requested this tool
@@ -133,6 +168,10 @@ pub struct LLMResponse {
     pub finish_reason: FinishReason,
     /// Optional structured output
     pub structured: Option<serde_json::Value>,
+    /// Native tool calls returned by the provider (empty when the model
+    /// answered in text or native tool calling is disabled)
+    #[serde(default)]
+    pub tool_calls: Vec<super::intents::ToolCall>,
 }
 
 /// Token usage from an LLM call
@@ -141,6 +180,9 @@ pub struct TokenUsage {
     pub prompt_tokens: u32,
     pub completion_tokens: u32,
     pub total_tokens: u32,
+    /// Prompt tokens the provider served from its prompt cache
+    #[serde(default)]
+    pub cached_tokens: u32,
 }
 
 impl TokenUsage {
@@ -149,13 +191,21 @@ impl TokenUsage {
             prompt_tokens: prompt,
             completion_tokens: completion,
             total_tokens: prompt + completion,
+            cached_tokens: 0,
         }
     }
 
+    /// Set the prompt tokens read from the provider's cache
+    pub fn with_cached(mut self, cached_tokens: u32) -> Self {
+        self.cached_tokens = cached_tokens;
+        self
+    }
+
     pub fn add(&mut self, other: &TokenUsage) {
         self.prompt_tokens += other.prompt_tokens;
         self.completion_tokens += other.completion_tokens;
         self.total_tokens += other.total_tokens;
+        self.cached_tokens += other.cached_tokens;
     }
 }
 
@@ -13,6 +13,7 @@ pub mod config;
 pub mod graph;
 pub mod envelope;
 pub mod error;
+pub mod plan;
 
 // Re-exports for convenience
 pub use ids::*;
@@ -24,4 +25,5 @@ pub use config::*;
 pub use graph::*;
 pub use envelope::*;
 pub use error::*;
+pub use plan::{Plan, PlanError, PlanStep, StepStatus};
 pub use parser::{ResponseParser, ParsedResponse, ParseError};
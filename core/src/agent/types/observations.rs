@@ -158,6 +158,20 @@ pub enum HaltReason {
     StepLimitReached { max_steps: usize },
     Error(String),
     Interrupted,
+    BudgetExceeded(String),
+}
+
+impl std::fmt::Display for HaltReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HaltReason::Completed => write!(f, "Completed"),
+            HaltReason::UserRequest => write!(f, "Stopped by user"),
+            HaltReason::StepLimitReached { max_steps } => write!(f, "Step limit reached ({})", max_steps),
+            HaltReason::Error(msg) => write!(f, "Error: {}", msg),
+            HaltReason::Interrupted => write!(f, "Interrupted"),
+            HaltReason::BudgetExceeded(msg) => write!(f, "Budget exceeded: {}", msg),
+        }
+    }
 }
 
 /// Execution error details
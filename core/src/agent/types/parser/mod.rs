@@ -4,8 +4,10 @@
 //! All parsers are pure functions - no async, no IO.
 
 mod short_key;
+mod stream;
 
 pub use short_key::{ShortKeyParser, parse_short_key_action, ShortKeyAction, ShortKeyExtracted};
+pub use stream::{ShortKeyStreamParser, StreamDelta, StreamField};
 
 /// Result of parsing an LLM response
 /// 
@@ -7,7 +7,7 @@
 
 use serde::{Deserialize, Serialize};
 use crate::agent::types::intents::ToolCall;
-use super::{ParsedResponse, ParseError, extract_json_objects, extract_code_blocks};
+use super::{ParsedResponse, ParseError, ShortKeyStreamParser, extract_json_objects, extract_code_blocks};
 
 /// Extracted Short-Key fields - flat extraction, no decisions made here.
 /// 
@@ -258,84 +258,14 @@ impl ShortKeyParser {
     /// assert!(!done);
     /// ```
     pub fn extract_streaming_content(&self, partial: &str) -> (String, String, bool) {
-        let mut thought = String::new();
-        let mut final_answer = String::new();
-        
-        // Try to find "t" field value
-        if let Some(t_start) = find_field_start(partial, "t") {
-            thought = extract_partial_string_value(&partial[t_start..]);
-        }
-        
-        // Try to find "f" field value
-        if let Some(f_start) = find_field_start(partial, "f") {
-            final_answer = extract_partial_string_value(&partial[f_start..]);
-        }
-        
-        // Check if JSON appears complete (has closing brace)
-        let is_complete = partial.trim().ends_with('}') && 
-                         partial.chars().filter(|&c| c == '{').count() == 
-                         partial.chars().filter(|&c| c == '}').count();
-        
-        (thought, final_answer, is_complete)
-    }
-}
-
-/// Find the start of a field's string value in partial JSON
-fn find_field_start(json: &str, field: &str) -> Option<usize> {
-    // Look for pattern: "t": " or "t":
-    let pattern = format!(r#""{}": ""#, field);
-    if let Some(pos) = json.find(&pattern) {
-        return Some(pos + pattern.len() - 1); // Point to opening quote
-    }
-    
-    // Try without space: "t":"
-    let pattern2 = format!(r#""{}":""#, field);
-    if let Some(pos) = json.find(&pattern2) {
-        return Some(pos + pattern2.len() - 1);
+        let mut stream = ShortKeyStreamParser::new();
+        stream.push(partial);
+        (
+            stream.thought().to_string(),
+            stream.final_answer().to_string(),
+            stream.is_complete(),
+        )
     }
-    
-    None
-}
-
-/// Extract a partial string value from JSON (handles escaped quotes)
-fn extract_partial_string_value(input: &str) -> String {
-    let mut result = String::new();
-    let mut escaped = false;
-    let mut in_string = false;
-    
-    for ch in input.chars() {
-        if escaped {
-            match ch {
-                'n' => result.push('\n'),
-                'r' => result.push('\r'),
-                't' => result.push('\t'),
-                '"' => result.push('"'),
-                '\\' => result.push('\\'),
-                _ => result.push(ch),
-            }
-            escaped = false;
-            continue;
-        }
-        
-        match ch {
-            '\\' => {
-                escaped = true;
-            }
-            '"' => {
-                if in_string {
-                    // End of string - stop here
-                    return result;
-                } else {
-                    in_string = true;
-                }
-            }
-            _ if in_string => result.push(ch),
-            _ => {} // Outside string, ignore
-        }
-    }
-    
-    // Return accumulated content (incomplete string)
-    result
 }
 
 impl super::ResponseParser for ShortKeyParser {
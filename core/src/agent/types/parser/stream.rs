@@ -0,0 +1,520 @@
+//! Incremental Short-Key stream parser
+//!
+//! Feeds streamed LLM output through a small JSON tokenizer and surfaces the
+//! top-level `"t"` (thought) and `"f"` (final answer) string values as they
+//! arrive. Unlike substring matching, it tracks string/escape state and
+//! nesting depth, so:
+//! - `"f"` inside a nested object (e.g. tool input) is never mistaken for the answer
+//! - `"f": "` appearing inside another string value is ignored
+//! - `\uXXXX` escapes (including surrogate pairs) decode correctly even when
+//!   split across chunks
+//!
+//! Pure state machine - no async, no IO. Text before the first `{` (prose,
+//! code fences) is skipped; input after the top-level object closes is ignored.
+
+/// Which Short-Key field a streamed delta belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamField {
+    /// `"t"` - thought/reasoning
+    Thought,
+    /// `"f"` - final answer
+    Final,
+}
+
+/// New text decoded for one field by a single `push`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamDelta {
+    pub field: StreamField,
+    pub text: String,
+}
+
+/// Container on the nesting stack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    Object,
+    Array,
+}
+
+/// Escape sequence currently being decoded inside a string
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Escape {
+    None,
+    /// Saw a backslash
+    Backslash,
+    /// Inside `\u`, with the hex digits collected so far
+    Unicode(String),
+}
+
+/// What the string currently being lexed is used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StringRole {
+    /// Top-level object key
+    Key,
+    /// Value of a captured top-level field
+    Capture(StreamField),
+    /// Anything else
+    Skip,
+}
+
+/// Incremental parser for streamed Short-Key JSON
+///
+/// # Example
+/// ```
+/// use mylm_core::agent::types::parser::ShortKeyStreamParser;
+///
+/// let mut parser = ShortKeyStreamParser::new();
+/// parser.push(r#"{"t": "Chec"#);
+/// parser.push(r#"king", "f": "Done é"}"#);
+/// assert_eq!(parser.thought(), "Checking");
+/// assert_eq!(parser.final_answer(), "Done é");
+/// assert!(parser.is_complete());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ShortKeyStreamParser {
+    stack: Vec<Container>,
+    in_string: bool,
+    escape: Escape,
+    role: StringRole,
+    /// Next string at depth 1 is a key (vs. a value)
+    expecting_key: bool,
+    key_buf: String,
+    /// Key whose value is expected next at depth 1
+    current_key: Option<String>,
+    /// High surrogate waiting for its low half
+    pending_surrogate: Option<u16>,
+    thought: String,
+    final_answer: String,
+    complete: bool,
+}
+
+impl Default for ShortKeyStreamParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShortKeyStreamParser {
+    /// Create a parser waiting for the start of a Short-Key object
+    pub fn new() -> Self {
+        Self {
+            stack: Vec::new(),
+            in_string: false,
+            escape: Escape::None,
+            role: StringRole::Skip,
+            expecting_key: false,
+            key_buf: String::new(),
+            current_key: None,
+            pending_surrogate: None,
+            thought: String::new(),
+            final_answer: String::new(),
+            complete: false,
+        }
+    }
+
+    /// Reset for a new response
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Thought decoded so far
+    pub fn thought(&self) -> &str {
+        &self.thought
+    }
+
+    /// Final answer decoded so far
+    pub fn final_answer(&self) -> &str {
+        &self.final_answer
+    }
+
+    /// True once the top-level object has closed
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Feed the next chunk, returning the text it added to each field
+    pub fn push(&mut self, chunk: &str) -> Vec<StreamDelta> {
+        let mut deltas: Vec<StreamDelta> = Vec::new();
+        for ch in chunk.chars() {
+            if self.complete {
+                break;
+            }
+            if let Some((field, text)) = self.step(ch) {
+                match deltas.last_mut() {
+                    Some(last) if last.field == field => last.text.push_str(&text),
+                    _ => deltas.push(StreamDelta { field, text }),
+                }
+            }
+        }
+        deltas
+    }
+
+    /// Advance by one character, returning decoded text for a captured field
+    fn step(&mut self, ch: char) -> Option<(StreamField, String)> {
+        if self.in_string {
+            return self.step_string(ch);
+        }
+
+        match ch {
+            '"' if !self.stack.is_empty() => {
+                self.in_string = true;
+                self.escape = Escape::None;
+                self.pending_surrogate = None;
+                self.role = self.next_string_role();
+                self.key_buf.clear();
+            }
+            '{' => {
+                if self.stack.is_empty() {
+                    self.expecting_key = true;
+                }
+                self.stack.push(Container::Object);
+            }
+            '[' if !self.stack.is_empty() => self.stack.push(Container::Array),
+            '}' | ']' if !self.stack.is_empty() => {
+                self.stack.pop();
+                if self.stack.is_empty() {
+                    self.complete = true;
+                } else if self.stack.len() == 1 {
+                    // A nested value of a top-level key just finished
+                    self.current_key = None;
+                }
+            }
+            ',' if self.stack.len() == 1 => {
+                self.expecting_key = true;
+                self.current_key = None;
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Decide what the string starting now is used for
+    fn next_string_role(&self) -> StringRole {
+        if self.stack.len() != 1 || self.stack[0] != Container::Object {
+            return StringRole::Skip;
+        }
+        if self.expecting_key {
+            return StringRole::Key;
+        }
+        match self.current_key.as_deref() {
+            Some("t") => StringRole::Capture(StreamField::Thought),
+            Some("f") => StringRole::Capture(StreamField::Final),
+            _ => StringRole::Skip,
+        }
+    }
+
+    fn step_string(&mut self, ch: char) -> Option<(StreamField, String)> {
+        let decoded = match std::mem::replace(&mut self.escape, Escape::None) {
+            Escape::Backslash => match ch {
+                'u' => {
+                    self.escape = Escape::Unicode(String::new());
+                    None
+                }
+                'n' => Some('\n'),
+                'r' => Some('\r'),
+                't' => Some('\t'),
+                'b' => Some('\u{8}'),
+                'f' => Some('\u{c}'),
+                // '"', '\\', '/' and unknown escapes decode to themselves
+                other => Some(other),
+            },
+            Escape::Unicode(mut hex) => {
+                if ch.is_ascii_hexdigit() {
+                    hex.push(ch);
+                    if hex.len() < 4 {
+                        self.escape = Escape::Unicode(hex);
+                        return None;
+                    }
+                    // Four hex digits always fit in a u16
+                    let unit = u16::from_str_radix(&hex, 16).unwrap_or(0xFFFD);
+                    return self.emit_utf16(unit);
+                }
+                // Malformed escape: keep what we saw, then reprocess this char
+                let mut text = self.flush_surrogate();
+                text.push_str("\\u");
+                text.push_str(&hex);
+                let seen = self.emit_text(text);
+                let rest = self.step_string(ch);
+                return merge(seen, rest);
+            }
+            Escape::None => match ch {
+                '\\' => {
+                    self.escape = Escape::Backslash;
+                    None
+                }
+                '"' => {
+                    let text = self.flush_surrogate();
+                    let out = self.emit_text(text);
+                    self.in_string = false;
+                    self.finish_string();
+                    return out;
+                }
+                other => Some(other),
+            },
+        };
+
+        let c = decoded?;
+        let mut text = self.flush_surrogate();
+        text.push(c);
+        self.emit_text(text)
+    }
+
+    /// Handle one decoded UTF-16 code unit from a `\u` escape
+    fn emit_utf16(&mut self, unit: u16) -> Option<(StreamField, String)> {
+        let mut text = String::new();
+        match unit {
+            0xD800..=0xDBFF => {
+                text.push_str(&self.flush_surrogate());
+                self.pending_surrogate = Some(unit);
+            }
+            0xDC00..=0xDFFF => match self.pending_surrogate.take() {
+                Some(high) => {
+                    let c = char::decode_utf16([high, unit])
+                        .next()
+                        .and_then(|r| r.ok())
+                        .unwrap_or(char::REPLACEMENT_CHARACTER);
+                    text.push(c);
+                }
+                None => text.push(char::REPLACEMENT_CHARACTER),
+            },
+            _ => {
+                text.push_str(&self.flush_surrogate());
+                text.push(char::from_u32(unit as u32).unwrap_or(char::REPLACEMENT_CHARACTER));
+            }
+        }
+        self.emit_text(text)
+    }
+
+    /// Replacement char for a high surrogate that never got its pair
+    fn flush_surrogate(&mut self) -> String {
+        match self.pending_surrogate.take() {
+            Some(_) => char::REPLACEMENT_CHARACTER.to_string(),
+            None => String::new(),
+        }
+    }
+
+    /// Route decoded text to the key buffer or a captured field
+    fn emit_text(&mut self, text: String) -> Option<(StreamField, String)> {
+        match self.role {
+            StringRole::Key => {
+                self.key_buf.push_str(&text);
+                None
+            }
+            StringRole::Capture(field) if !text.is_empty() => {
+                match field {
+                    StreamField::Thought => self.thought.push_str(&text),
+                    StreamField::Final => self.final_answer.push_str(&text),
+                }
+                Some((field, text))
+            }
+            _ => None,
+        }
+    }
+
+    /// Called when the closing quote of a string is seen
+    fn finish_string(&mut self) {
+        if self.role == StringRole::Key {
+            self.current_key = Some(std::mem::take(&mut self.key_buf));
+            self.expecting_key = false;
+        }
+        self.role = StringRole::Skip;
+    }
+}
+
+/// Join two pieces of decoded text from the same string
+fn merge(
+    first: Option<(StreamField, String)>,
+    second: Option<(StreamField, String)>,
+) -> Option<(StreamField, String)> {
+    match (first, second) {
+        (Some((field, mut a)), Some((_, b))) => {
+            a.push_str(&b);
+            Some((field, a))
+        }
+        (a, b) => a.or(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn parse_all(input: &str) -> ShortKeyStreamParser {
+        let mut parser = ShortKeyStreamParser::new();
+        parser.push(input);
+        parser
+    }
+
+    /// Feed `input` split at random char boundaries
+    fn parse_chunked(input: &str, rng: &mut StdRng) -> ShortKeyStreamParser {
+        let chars: Vec<char> = input.chars().collect();
+        let mut parser = ShortKeyStreamParser::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let n = rng.gen_range(1..=8).min(chars.len() - i);
+            let chunk: String = chars[i..i + n].iter().collect();
+            parser.push(&chunk);
+            i += n;
+        }
+        parser
+    }
+
+    #[test]
+    fn test_basic_fields() {
+        let p = parse_all(r#"{"t": "Thinking", "f": "Answer"}"#);
+        assert_eq!(p.thought(), "Thinking");
+        assert_eq!(p.final_answer(), "Answer");
+        assert!(p.is_complete());
+    }
+
+    #[test]
+    fn test_partial_value() {
+        let p = parse_all(r#"{"t": "Hel"#);
+        assert_eq!(p.thought(), "Hel");
+        assert!(!p.is_complete());
+    }
+
+    #[test]
+    fn test_nested_f_is_ignored() {
+        let p = parse_all(r#"{"t": "x", "a": "write", "i": {"f": "not me", "t": "nor me"}, "f": "me"}"#);
+        assert_eq!(p.thought(), "x");
+        assert_eq!(p.final_answer(), "me");
+    }
+
+    #[test]
+    fn test_key_pattern_inside_string_is_ignored() {
+        let p = parse_all(r#"{"t": "the json was {\"f\": \"fake\"}", "f": "real"}"#);
+        assert_eq!(p.thought(), r#"the json was {"f": "fake"}"#);
+        assert_eq!(p.final_answer(), "real");
+    }
+
+    #[test]
+    fn test_escapes() {
+        let p = parse_all(r#"{"f": "a\nb\t\"c\" \\ \/ é 😀"}"#);
+        assert_eq!(p.final_answer(), "a\nb\t\"c\" \\ / é 😀");
+    }
+
+    #[test]
+    fn test_unicode_escape_split_across_chunks() {
+        let mut p = ShortKeyStreamParser::new();
+        for chunk in [r#"{"f": "\u"#, "00", "e9 ", r#"\ud8"#, "3d", r#"\ude"#, r#"00"}"#] {
+            p.push(chunk);
+        }
+        assert_eq!(p.final_answer(), "é 😀");
+        assert!(p.is_complete());
+    }
+
+    #[test]
+    fn test_lone_surrogate_becomes_replacement() {
+        let p = parse_all(r#"{"f": "a\ud83db"}"#);
+        assert_eq!(p.final_answer(), "a\u{FFFD}b");
+    }
+
+    #[test]
+    fn test_malformed_unicode_escape_kept_verbatim() {
+        let p = parse_all(r#"{"f": "bad \uZZ end"}"#);
+        assert_eq!(p.final_answer(), r"bad \uZZ end");
+    }
+
+    #[test]
+    fn test_prefix_and_fence_skipped() {
+        let p = parse_all("Sure thing:\n```json\n{\"f\": \"hi\"}\n```");
+        assert_eq!(p.final_answer(), "hi");
+        assert!(p.is_complete());
+    }
+
+    #[test]
+    fn test_input_after_close_ignored() {
+        let p = parse_all(r#"{"f": "one"} {"f": "two"}"#);
+        assert_eq!(p.final_answer(), "one");
+    }
+
+    #[test]
+    fn test_non_string_values_skipped() {
+        let p = parse_all(r#"{"c": true, "n": [1, {"f": "x"}], "t": "ok", "f": null}"#);
+        assert_eq!(p.thought(), "ok");
+        assert_eq!(p.final_answer(), "");
+        assert!(p.is_complete());
+    }
+
+    #[test]
+    fn test_deltas_report_new_text() {
+        let mut p = ShortKeyStreamParser::new();
+        assert!(p.push(r#"{"t": "#).is_empty());
+        let deltas = p.push(r#""ab", "f": "c"#);
+        assert_eq!(
+            deltas,
+            vec![
+                StreamDelta { field: StreamField::Thought, text: "ab".into() },
+                StreamDelta { field: StreamField::Final, text: "c".into() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut p = parse_all(r#"{"f": "one"}"#);
+        p.reset();
+        p.push(r#"{"f": "two"}"#);
+        assert_eq!(p.final_answer(), "two");
+    }
+
+    /// Any chunking of a payload must decode the same as serde_json does
+    #[test]
+    fn test_fuzz_chunking_matches_serde() {
+        let mut rng = StdRng::seed_from_u64(0x5eed);
+        let alphabet: Vec<char> = "ab {}[]\":,\\\n\té😀\u{1}/".chars().collect();
+        for _ in 0..500 {
+            let mut random_string = || -> String {
+                let len = rng.gen_range(0..24);
+                (0..len).map(|_| alphabet[rng.gen_range(0..alphabet.len())]).collect()
+            };
+            let thought = random_string();
+            let answer = random_string();
+            let nested = random_string();
+            let payload = serde_json::json!({
+                "t": thought,
+                "a": "tool",
+                "i": { "f": nested, "list": [nested, { "t": nested }] },
+                "f": answer,
+            });
+            // serde_json escapes control chars as \uXXXX, exercising that path too
+            let text = serde_json::to_string(&payload).unwrap();
+
+            let p = parse_chunked(&text, &mut rng);
+            assert_eq!(p.thought(), thought, "payload: {}", text);
+            assert_eq!(p.final_answer(), answer, "payload: {}", text);
+            assert!(p.is_complete());
+
+            let deltas: String = {
+                let mut q = ShortKeyStreamParser::new();
+                text.chars()
+                    .flat_map(|c| q.push(&c.to_string()))
+                    .filter(|d| d.field == StreamField::Final)
+                    .map(|d| d.text)
+                    .collect()
+            };
+            assert_eq!(deltas, answer);
+        }
+    }
+
+    /// Arbitrary garbage must never panic, and captured text only ever grows
+    #[test]
+    fn test_fuzz_garbage_never_panics() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let alphabet: Vec<char> = "{}[]\":,\\u0123456789abcdefABCDEF tf\nx😀".chars().collect();
+        for _ in 0..2000 {
+            let len = rng.gen_range(0..64);
+            let text: String = (0..len).map(|_| alphabet[rng.gen_range(0..alphabet.len())]).collect();
+            let mut p = ShortKeyStreamParser::new();
+            let mut last = (0, 0);
+            for c in text.chars() {
+                p.push(&c.to_string());
+                let now = (p.thought().len(), p.final_answer().len());
+                assert!(now.0 >= last.0 && now.1 >= last.1);
+                last = now;
+            }
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! Plan types
+//!
+//! An explicit multi-step plan: numbered steps with dependencies and a
+//! status each. The engine writes it before a multi-step task, the runtime
+//! keeps step statuses consistent with the dependencies, and frontends show
+//! it as a checklist.
+
+use serde::{Deserialize, Serialize};
+
+/// Progress of a plan step
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    #[default]
+    Pending,
+    InProgress,
+    Done,
+    Failed,
+    Skipped,
+}
+
+impl StepStatus {
+    /// Checklist marker
+    pub fn marker(self) -> &'static str {
+        match self {
+            StepStatus::Pending => "[ ]",
+            StepStatus::InProgress => "[>]",
+            StepStatus::Done => "[x]",
+            StepStatus::Failed => "[!]",
+            StepStatus::Skipped => "[-]",
+        }
+    }
+
+    /// Done or skipped: dependents may start
+    pub fn is_settled(self) -> bool {
+        matches!(self, StepStatus::Done | StepStatus::Skipped)
+    }
+}
+
+/// One step of a plan
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlanStep {
+    /// 1-based position, stable across status changes
+    pub id: usize,
+    pub title: String,
+    /// Steps that must be done (or skipped) before this one starts
+    #[serde(default)]
+    pub depends_on: Vec<usize>,
+    #[serde(default)]
+    pub status: StepStatus,
+    /// Outcome or reason, e.g. why a step failed
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// A multi-step plan
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Plan {
+    pub goal: String,
+    pub steps: Vec<PlanStep>,
+}
+
+/// A plan change that was refused
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanError(pub String);
+
+impl std::fmt::Display for PlanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for PlanError {}
+
+impl Plan {
+    /// Plan with pending steps numbered from 1; `depends_on` lists step numbers
+    pub fn new(goal: impl Into<String>, steps: Vec<(String, Vec<usize>)>) -> Result<Self, PlanError> {
+        let plan = Self {
+            goal: goal.into(),
+            steps: steps
+                .into_iter()
+                .enumerate()
+                .map(|(i, (title, depends_on))| PlanStep {
+                    id: i + 1,
+                    title,
+                    depends_on,
+                    status: StepStatus::Pending,
+                    note: None,
+                })
+                .collect(),
+        };
+        plan.validate()?;
+        Ok(plan)
+    }
+
+    /// Titles are set and dependencies point at other existing steps, without cycles
+    pub fn validate(&self) -> Result<(), PlanError> {
+        if self.steps.is_empty() {
+            return Err(PlanError("A plan needs at least one step".to_string()));
+        }
+        for step in &self.steps {
+            if step.title.trim().is_empty() {
+                return Err(PlanError(format!("Step {} has no title", step.id)));
+            }
+            for dep in &step.depends_on {
+                if *dep == step.id || self.step(*dep).is_none() {
+                    return Err(PlanError(format!("Step {} depends on unknown step {}", step.id, dep)));
+                }
+            }
+        }
+        // Settle steps whose dependencies are settled until nothing changes;
+        // whatever is left is on a cycle
+        let mut settled: Vec<usize> = Vec::new();
+        while settled.len() < self.steps.len() {
+            let before = settled.len();
+            for step in &self.steps {
+                if !settled.contains(&step.id) && step.depends_on.iter().all(|d| settled.contains(d)) {
+                    settled.push(step.id);
+                }
+            }
+            if settled.len() == before {
+                return Err(PlanError("Step dependencies form a cycle".to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn step(&self, id: usize) -> Option<&PlanStep> {
+        self.steps.iter().find(|s| s.id == id)
+    }
+
+    /// Dependencies of `id` that are not done or skipped yet
+    pub fn blockers(&self, id: usize) -> Vec<usize> {
+        self.step(id)
+            .map(|step| {
+                step.depends_on
+                    .iter()
+                    .copied()
+                    .filter(|dep| !self.step(*dep).is_some_and(|d| d.status.is_settled()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Set a step's status, refusing to start or finish a step whose
+    /// dependencies are not settled
+    pub fn set_status(&mut self, id: usize, status: StepStatus, note: Option<String>) -> Result<(), PlanError> {
+        let blockers = self.blockers(id);
+        if matches!(status, StepStatus::InProgress | StepStatus::Done) && !blockers.is_empty() {
+            return Err(PlanError(format!(
+                "Step {} is waiting on step(s) {:?}; finish or skip those first",
+                id, blockers
+            )));
+        }
+        let step = self
+            .steps
+            .iter_mut()
+            .find(|s| s.id == id)
+            .ok_or_else(|| PlanError(format!("No step {}", id)))?;
+        step.status = status;
+        if note.is_some() {
+            step.note = note;
+        }
+        Ok(())
+    }
+
+    /// Append a step and return its number
+    pub fn add_step(&mut self, title: impl Into<String>, depends_on: Vec<usize>) -> Result<usize, PlanError> {
+        let id = self.steps.iter().map(|s| s.id).max().unwrap_or(0) + 1;
+        self.steps.push(PlanStep {
+            id,
+            title: title.into(),
+            depends_on,
+            status: StepStatus::Pending,
+            note: None,
+        });
+        if let Err(e) = self.validate() {
+            self.steps.pop();
+            return Err(e);
+        }
+        Ok(id)
+    }
+
+    /// Remove a step; steps that depended on it no longer do
+    pub fn remove_step(&mut self, id: usize) -> Result<PlanStep, PlanError> {
+        let index = self
+            .steps
+            .iter()
+            .position(|s| s.id == id)
+            .ok_or_else(|| PlanError(format!("No step {}", id)))?;
+        if self.steps.len() == 1 {
+            return Err(PlanError("Cannot remove the last step; clear the plan instead".to_string()));
+        }
+        let removed = self.steps.remove(index);
+        for step in &mut self.steps {
+            step.depends_on.retain(|dep| *dep != id);
+        }
+        Ok(removed)
+    }
+
+    /// (settled steps, all steps)
+    pub fn progress(&self) -> (usize, usize) {
+        (self.steps.iter().filter(|s| s.status.is_settled()).count(), self.steps.len())
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.steps.iter().all(|s| s.status.is_settled())
+    }
+
+    /// Plan as a numbered checklist
+    pub fn checklist(&self) -> String {
+        let (settled, total) = self.progress();
+        let mut out = format!("Plan: {} ({}/{} done)", self.goal, settled, total);
+        for step in &self.steps {
+            out.push_str(&format!("\n{} {}. {}", step.status.marker(), step.id, step.title));
+            if !step.depends_on.is_empty() {
+                let deps: Vec<String> = step.depends_on.iter().map(|d| d.to_string()).collect();
+                out.push_str(&format!(" (after {})", deps.join(", ")));
+            }
+            if let Some(note) = &step.note {
+                out.push_str(&format!(" - {}", note));
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Plan {
+        Plan::new(
+            "Add a --json flag",
+            vec![
+                ("Read the CLI parser".to_string(), vec![]),
+                ("Add the flag".to_string(), vec![1]),
+                ("Update the docs".to_string(), vec![2]),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_dependencies_gate_progress() {
+        let mut plan = sample();
+        assert!(plan.set_status(2, StepStatus::InProgress, None).is_err());
+        plan.set_status(1, StepStatus::Done, Some("clap derive in cli.rs".to_string())).unwrap();
+        plan.set_status(2, StepStatus::Skipped, None).unwrap();
+        plan.set_status(3, StepStatus::Done, None).unwrap();
+        assert!(plan.is_complete());
+        assert_eq!(
+            plan.checklist(),
+            "Plan: Add a --json flag (3/3 done)\n\
+             [x] 1. Read the CLI parser - clap derive in cli.rs\n\
+             [-] 2. Add the flag (after 1)\n\
+             [x] 3. Update the docs (after 2)"
+        );
+    }
+
+    #[test]
+    fn test_invalid_plans_are_refused() {
+        assert!(Plan::new("x", vec![]).is_err());
+        assert!(Plan::new("x", vec![("a".to_string(), vec![3])]).is_err());
+
+        let mut plan = sample();
+        plan.steps[0].depends_on = vec![3];
+        assert_eq!(plan.validate(), Err(PlanError("Step dependencies form a cycle".to_string())));
+
+        let mut plan = sample();
+        plan.remove_step(2).unwrap();
+        assert!(plan.step(3).unwrap().depends_on.is_empty());
+        assert_eq!(plan.add_step("Release", vec![1, 3]), Ok(4));
+    }
+}
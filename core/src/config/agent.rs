@@ -279,9 +279,13 @@ pub struct SessionSummary {
 impl UserProfile {
     /// Load profile from disk or create default
     pub fn load() -> anyhow::Result<Self> {
-        let path = Self::profile_path()?;
+        Self::load_from(&Self::default_path()?)
+    }
+    
+    /// Load the profile at `path`, or start empty if there is none
+    pub fn load_from(path: &std::path::Path) -> anyhow::Result<Self> {
         if path.exists() {
-            let content = std::fs::read_to_string(&path)?;
+            let content = std::fs::read_to_string(path)?;
             let profile: UserProfile = serde_json::from_str(&content)?;
             Ok(profile)
         } else {
@@ -291,17 +295,20 @@ impl UserProfile {
     
     /// Save profile to disk
     pub fn save(&self) -> anyhow::Result<()> {
-        let path = Self::profile_path()?;
+        self.save_to(&Self::default_path()?)
+    }
+    
+    pub fn save_to(&self, path: &std::path::Path) -> anyhow::Result<()> {
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)?;
         }
         let content = serde_json::to_string_pretty(self)?;
-        std::fs::write(&path, content)?;
+        std::fs::write(path, content)?;
         Ok(())
     }
     
     /// Get profile storage path
-    fn profile_path() -> anyhow::Result<std::path::PathBuf> {
+    pub fn default_path() -> anyhow::Result<std::path::PathBuf> {
         Ok(dirs::data_dir()
             .ok_or_else(|| anyhow::anyhow!("Could not find data directory"))?
             .join("mylm")
@@ -414,6 +421,19 @@ pub struct MemoryConfig {
     /// Custom storage path (None = use default)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub storage_path: Option<std::path::PathBuf>,
+    /// Directory for the journal, user profile and memory feedback
+    /// (None = the shared mylm data directory)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_dir: Option<std::path::PathBuf>,
+    /// Storage engine for long-term memory
+    #[serde(default)]
+    pub backend: crate::memory::MemoryBackendKind,
+    /// Embedding provider for semantic search
+    #[serde(default)]
+    pub embedding: crate::memory::EmbeddingSettings,
+    /// Decay and archival; search ranks old memories down by the same score
+    #[serde(default)]
+    pub retention: crate::memory::RetentionSettings,
     /// Enable autosave for TUI sessions
     #[serde(default = "default_true")]
     pub autosave: bool,
@@ -440,6 +460,10 @@ impl Default for MemoryConfig {
             semantic_search_limit: default_semantic_search_limit(),
             tool_search_limit: default_tool_search_limit(),
             storage_path: None,
+            data_dir: None,
+            backend: crate::memory::MemoryBackendKind::default(),
+            embedding: crate::memory::EmbeddingSettings::default(),
+            retention: crate::memory::RetentionSettings::default(),
             autosave: true,
             incognito: false,
         }
@@ -456,6 +480,12 @@ impl MemoryConfig {
         if other.storage_path.is_some() {
             self.storage_path = other.storage_path;
         }
+        if other.data_dir.is_some() {
+            self.data_dir = other.data_dir;
+        }
+        self.backend = other.backend;
+        self.embedding = other.embedding;
+        self.retention = other.retention;
         self.autosave = other.autosave;
         self.incognito = other.incognito;
     }
@@ -2,8 +2,13 @@
 //!
 //! UI settings, feature toggles, and application preferences.
 
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
 use serde::{Deserialize, Serialize};
 
+use crate::logging::LogLevel;
+
 /// Application settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
@@ -19,9 +24,27 @@ pub struct AppConfig {
     #[serde(default = "default_editor")]
     pub editor: String,
 
-    /// Theme
+    /// Name of the TUI colour theme: a built-in (dark, light, solarized,
+    /// high-contrast), one from `themes`, or a file in `<config dir>/themes`
+    #[serde(default = "default_theme")]
+    pub theme: String,
+
+    /// Custom themes, by name
+    #[serde(default)]
+    pub themes: BTreeMap<String, ThemeDefinition>,
+
+    /// TUI key bindings and vim mode
+    #[serde(default)]
+    pub keymap: KeymapSettings,
+
+    /// How tool results show in the chat
     #[serde(default)]
-    pub theme: Theme,
+    pub tool_results: ToolResultSettings,
+
+    /// TUI pane layout by terminal size class (`narrow`, `medium`, `wide`),
+    /// saved whenever it is changed
+    #[serde(default)]
+    pub layouts: BTreeMap<String, PaneLayout>,
 
     /// Onboarding completed flag
     #[serde(default)]
@@ -43,7 +66,11 @@ impl Default for AppConfig {
             tmux_enabled: true,
             shell: default_shell(),
             editor: default_editor(),
-            theme: Theme::default(),
+            theme: default_theme(),
+            themes: BTreeMap::new(),
+            keymap: KeymapSettings::default(),
+            tool_results: ToolResultSettings::default(),
+            layouts: BTreeMap::new(),
             onboarding_completed: false,
             stt_model_path: None,
             tts_model_path: None,
@@ -73,14 +100,83 @@ fn default_true() -> bool {
     true
 }
 
-/// UI Theme
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-#[serde(rename_all = "snake_case")]
-pub enum Theme {
-    #[default]
-    Default,
-    Dark,
-    Light,
+fn default_theme() -> String {
+    "dark".to_string()
+}
+
+/// A custom TUI theme: colours by role over a base theme
+///
+/// Colours are names (`red`, `lightblue`), indexes (`244`) or `#rrggbb`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ThemeDefinition {
+    /// Theme the unset roles come from (default: dark)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    /// Colour by role (`border`, `user`, `assistant`, `approval`, ...)
+    #[serde(flatten)]
+    pub colors: BTreeMap<String, String>,
+}
+
+/// TUI key bindings
+///
+/// `bindings` maps an action (`toggle_focus`, `scroll_up`, `submit`,
+/// `approve`, ...) to its keys, comma-separated (`"f2, ctrl+o"`); an action
+/// listed here loses its default keys. With `vim`, the chat input has a
+/// normal and an insert mode.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct KeymapSettings {
+    /// Vim-style modal editing in the chat input
+    #[serde(default)]
+    pub vim: bool,
+    /// Keys by action
+    #[serde(default)]
+    pub bindings: BTreeMap<String, String>,
+}
+
+/// Tool results in the TUI chat
+///
+/// Each result shows as a one-line summary that Enter expands. `expanded`
+/// is the starting state; `tools` overrides it per tool
+/// (`tools = { shell = true }`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolResultSettings {
+    /// Show results expanded
+    #[serde(default)]
+    pub expanded: bool,
+    /// Starting state by tool name
+    #[serde(default)]
+    pub tools: BTreeMap<String, bool>,
+}
+
+impl ToolResultSettings {
+    /// Whether results of `tool` start expanded
+    pub fn expanded_for(&self, tool: &str) -> bool {
+        self.tools.get(tool).copied().unwrap_or(self.expanded)
+    }
+}
+
+/// Split between the TUI terminal and chat panes
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PaneLayout {
+    /// Chat width in percent of the window; 100 hides the terminal
+    #[serde(default = "default_chat_width")]
+    pub chat_width: u16,
+    /// Chat on the left of the terminal instead of the right
+    #[serde(default)]
+    pub chat_left: bool,
+}
+
+impl Default for PaneLayout {
+    fn default() -> Self {
+        Self {
+            chat_width: default_chat_width(),
+            chat_left: false,
+        }
+    }
+}
+
+fn default_chat_width() -> u16 {
+    30
 }
 
 /// TTS Engine
@@ -104,6 +200,15 @@ pub struct MemorySettings {
     /// Tool search results limit
     #[serde(default = "default_memory_tool_limit")]
     pub tool_search_limit: usize,
+    /// Embedding provider for semantic search (`local` or `openai`)
+    #[serde(default)]
+    pub embedding: crate::memory::EmbeddingSettings,
+    /// Decay, per-category TTL and archival of old memories
+    #[serde(default)]
+    pub retention: crate::memory::RetentionSettings,
+    /// Storage engine: `lancedb` (semantic search) or `sqlite` (full-text)
+    #[serde(default)]
+    pub backend: crate::memory::MemoryBackendKind,
 }
 
 fn default_memory_context_window() -> usize { 5 }
@@ -116,10 +221,382 @@ impl Default for MemorySettings {
             context_window: default_memory_context_window(),
             semantic_search_limit: default_memory_semantic_limit(),
             tool_search_limit: default_memory_tool_limit(),
+            embedding: crate::memory::EmbeddingSettings::default(),
+            retention: crate::memory::RetentionSettings::default(),
+            backend: crate::memory::MemoryBackendKind::default(),
         }
     }
 }
 
+/// Tool approval settings shared by every frontend
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ApprovalSettings {
+    /// Seconds to wait for a user decision before denying (None = wait forever)
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Command patterns (`*` wildcard) approved without asking
+    #[serde(default)]
+    pub auto_approve_commands: Vec<String>,
+    /// Command patterns always denied; checked before everything else
+    #[serde(default)]
+    pub forbidden_commands: Vec<String>,
+    /// Policy rules, `<allow|ask|deny> <tool>[:<pattern>]`, e.g.
+    /// `allow shell:^git (status|log)` or `ask file_write:outside_workspace`
+    #[serde(default)]
+    pub rules: Vec<String>,
+    /// Append every decision to `approvals.jsonl` in the data directory
+    #[serde(default)]
+    pub audit_log: bool,
+}
+
+/// Secret-file protection for file tools
+///
+/// A built-in deny-list (`.env*`, private keys, cloud credentials) always
+/// applies; these settings extend it and grant per-file exceptions.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SecretFileSettings {
+    /// Extra globs to refuse; patterns without `/` match the file name
+    #[serde(default)]
+    pub deny: Vec<String>,
+    /// Paths or globs that may be read even if they match a deny pattern
+    #[serde(default)]
+    pub allow: Vec<String>,
+}
+
+/// Per-session spending limits
+///
+/// When a limit is reached the session halts before its next LLM call.
+/// Cost is computed from the active profile's `input_price`/`output_price`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct BudgetSettings {
+    /// Maximum spend per session in USD (None = unlimited)
+    #[serde(default)]
+    pub max_session_cost_usd: Option<f64>,
+    /// Maximum prompt + completion tokens per session (None = unlimited)
+    #[serde(default)]
+    pub max_session_tokens: Option<u64>,
+}
+
+/// Resource limits for every command the agent runs
+///
+/// CPU time and memory are enforced with `ulimit` in a subshell, so they only
+/// apply on Unix shells. The result the agent sees says which limit was hit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandLimits {
+    /// Wall-clock seconds before the command is killed
+    #[serde(default = "default_command_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Output beyond this many bytes is cut
+    #[serde(default = "default_command_max_output_bytes")]
+    pub max_output_bytes: usize,
+    /// CPU seconds per command (None = unlimited)
+    #[serde(default)]
+    pub cpu_secs: Option<u64>,
+    /// Virtual memory per command in MB (None = unlimited)
+    #[serde(default)]
+    pub memory_mb: Option<u64>,
+}
+
+impl Default for CommandLimits {
+    fn default() -> Self {
+        Self {
+            timeout_secs: default_command_timeout_secs(),
+            max_output_bytes: default_command_max_output_bytes(),
+            cpu_secs: None,
+            memory_mb: None,
+        }
+    }
+}
+
+/// How delegated workers share the provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerPoolSettings {
+    /// Workers running at once; the rest wait for a free slot
+    #[serde(default = "default_worker_pool_size")]
+    pub max_concurrent: usize,
+    /// LLM requests per minute for each worker (None = unlimited)
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+}
+
+impl Default for WorkerPoolSettings {
+    fn default() -> Self {
+        Self {
+            max_concurrent: default_worker_pool_size(),
+            requests_per_minute: None,
+        }
+    }
+}
+
+fn default_worker_pool_size() -> usize { 4 }
+
+/// Quick answers from a small model
+///
+/// Messages the classifier judges trivial ("what does -R do in chmod") skip
+/// the agent loop and go straight to a small model with tools disabled.
+/// Anything that looks like a task still goes to the agent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FastPathSettings {
+    /// Route trivial questions to the fast model
+    #[serde(default)]
+    pub enabled: bool,
+    /// Profile that answers them (None = the active profile)
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Model override for that profile's provider
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Ask the fast model to classify messages the rules cannot decide
+    #[serde(default = "default_true")]
+    pub classify_with_model: bool,
+}
+
+impl Default for FastPathSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            profile: None,
+            model: None,
+            classify_with_model: true,
+        }
+    }
+}
+
+/// Time zone and locale the agent reasons in
+///
+/// Both default to the machine's settings (`$TZ` or the system zone, and
+/// `$LC_ALL`/`$LC_TIME`/`$LANG`). Set them when the machine is not where you
+/// are, e.g. a server reached over ssh from another time zone.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TimeSettings {
+    /// IANA time zone, e.g. "Europe/Berlin"
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Locale, e.g. "de_DE"
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+
+/// Agent tasks started by file changes
+///
+/// Each trigger watches paths matching its globs (relative to the directory
+/// mylm runs in) and, once changes have settled for `debounce_ms`, sends its
+/// prompt to the running session.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct WatchSettings {
+    /// Start the watcher with the TUI
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub triggers: Vec<WatchTrigger>,
+}
+
+/// One watch trigger, e.g. "review the diff whenever src/ is saved"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchTrigger {
+    pub name: String,
+    /// Globs of paths that fire the trigger, e.g. "src/**/*.rs"
+    pub paths: Vec<String>,
+    /// Globs of paths that never fire it
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    /// Quiet time after the last change before the trigger fires
+    #[serde(default = "default_watch_debounce_ms")]
+    pub debounce_ms: u64,
+    /// Prompt sent to the agent; `{files}`, `{diff}` and `{trigger}` are
+    /// replaced with the changed files, their `git diff` and the trigger name
+    pub prompt: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_watch_debounce_ms() -> u64 { 2_000 }
+
+/// Releasing resources of sessions nobody is using
+///
+/// After `suspend_after_mins` without input or agent activity, a session
+/// closes its memory store and trims its terminal buffers. They come back on
+/// the next message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdleSettings {
+    /// Minutes of inactivity before suspending (0 = never)
+    #[serde(default = "default_suspend_after_mins")]
+    pub suspend_after_mins: u64,
+}
+
+impl Default for IdleSettings {
+    fn default() -> Self {
+        Self {
+            suspend_after_mins: default_suspend_after_mins(),
+        }
+    }
+}
+
+fn default_suspend_after_mins() -> u64 { 30 }
+
+/// On-disk cache of LLM responses
+///
+/// Identical requests (same endpoint, model, messages and parameters) are
+/// answered from disk until the entry is `ttl_secs` old. The oldest entries
+/// go once the cache passes `max_size_mb`. `mylm cache clear` empties it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseCacheSettings {
+    /// Answer repeated requests from the cache
+    #[serde(default)]
+    pub enabled: bool,
+    /// Seconds an entry stays valid
+    #[serde(default = "default_response_cache_ttl_secs")]
+    pub ttl_secs: u64,
+    /// Cache directory (None = `<cache dir>/mylm/responses`)
+    #[serde(default)]
+    pub dir: Option<PathBuf>,
+    /// Size the cache is trimmed to, in MB (0 = unlimited)
+    #[serde(default = "default_response_cache_max_size_mb")]
+    pub max_size_mb: u64,
+}
+
+impl Default for ResponseCacheSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_secs: default_response_cache_ttl_secs(),
+            dir: None,
+            max_size_mb: default_response_cache_max_size_mb(),
+        }
+    }
+}
+
+fn default_response_cache_ttl_secs() -> u64 { 86_400 }
+fn default_response_cache_max_size_mb() -> u64 { 256 }
+
+/// Request and token budgets shared by the main agent and its workers
+///
+/// A request over budget waits in line until the last minute's use leaves
+/// room, for at most `max_wait_secs`, rather than failing. Tokens are
+/// reserved from an estimate and corrected with the provider's count.
+///
+/// ```toml
+/// [features.rate_limits]
+/// tokens_per_minute = 200000
+/// requests_per_minute = 500
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitSettings {
+    /// LLM requests per minute (None = unlimited)
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    /// LLM tokens, prompt and completion, per minute (None = unlimited)
+    #[serde(default)]
+    pub tokens_per_minute: Option<u32>,
+    /// Longest a request waits for budget before failing
+    #[serde(default = "default_rate_limit_max_wait_secs")]
+    pub max_wait_secs: u64,
+}
+
+impl Default for RateLimitSettings {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: None,
+            tokens_per_minute: None,
+            max_wait_secs: default_rate_limit_max_wait_secs(),
+        }
+    }
+}
+
+fn default_rate_limit_max_wait_secs() -> u64 { 120 }
+
+/// Structured tracing of agent sessions
+///
+/// Log lines go to debug.log through a `tracing` subscriber, inside spans
+/// for agent turns, LLM calls, tool calls and worker runs. With
+/// `otlp_endpoint` set (and mylm built with the `otel` feature) the spans
+/// are exported over OTLP/gRPC, e.g. to Jaeger or Grafana Tempo.
+///
+/// ```toml
+/// [features.tracing]
+/// enabled = true
+/// otlp_endpoint = "http://localhost:4317"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TracingSettings {
+    /// Route logging through the tracing pipeline
+    #[serde(default)]
+    pub enabled: bool,
+    /// OTLP/gRPC collector to export spans to (None = debug.log only)
+    #[serde(default)]
+    pub otlp_endpoint: Option<String>,
+    /// `service.name` the spans are exported under
+    #[serde(default = "default_tracing_service_name")]
+    pub service_name: String,
+    /// Which spans and events to keep, in `RUST_LOG` syntax
+    #[serde(default = "default_tracing_filter")]
+    pub filter: String,
+}
+
+impl Default for TracingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            otlp_endpoint: None,
+            service_name: default_tracing_service_name(),
+            filter: default_tracing_filter(),
+        }
+    }
+}
+
+fn default_tracing_service_name() -> String { "mylm".to_string() }
+fn default_tracing_filter() -> String { "warn,mylm=debug,mylm_core=debug".to_string() }
+
+/// debug.log levels and rotation
+///
+/// `MYLM_LOG` overrides the levels in `RUST_LOG` syntax, e.g.
+/// `MYLM_LOG=info,mylm_core::agent=trace`.
+///
+/// ```toml
+/// [features.log]
+/// level = "info"
+/// modules = { "mylm_core::provider::chat" = "warn" }
+/// max_size_mb = 10
+/// keep = 5
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogSettings {
+    /// Least severe level written
+    #[serde(default = "default_log_level")]
+    pub level: LogLevel,
+    /// Levels for module paths and everything under them
+    #[serde(default)]
+    pub modules: BTreeMap<String, LogLevel>,
+    /// Rotate once the file passes this size (0 = never)
+    #[serde(default = "default_log_max_size_mb")]
+    pub max_size_mb: u64,
+    /// Rotate once the file is this old (0 = never)
+    #[serde(default)]
+    pub max_age_hours: u64,
+    /// Rotated files kept as debug.log.1, debug.log.2, ...
+    #[serde(default = "default_log_keep")]
+    pub keep: usize,
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self {
+            level: default_log_level(),
+            modules: BTreeMap::new(),
+            max_size_mb: default_log_max_size_mb(),
+            max_age_hours: 0,
+            keep: default_log_keep(),
+        }
+    }
+}
+
+fn default_log_level() -> LogLevel { LogLevel::Debug }
+fn default_log_max_size_mb() -> u64 { 10 }
+fn default_log_keep() -> usize { 5 }
+
+fn default_command_timeout_secs() -> u64 { 30 }
+fn default_command_max_output_bytes() -> usize { 100_000 }
+
 /// Feature toggles
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeatureConfig {
@@ -139,6 +616,10 @@ pub struct FeatureConfig {
     #[serde(default = "default_true")]
     pub workers: bool,
 
+    /// Worker concurrency and per-worker request limits
+    #[serde(default)]
+    pub worker_pool: WorkerPoolSettings,
+
     /// Enable telemetry
     #[serde(default)]
     pub telemetry: bool,
@@ -147,6 +628,72 @@ pub struct FeatureConfig {
     #[serde(default)]
     pub auto_approve_safe: bool,
 
+    /// Tool approval policy, timeout and audit settings
+    #[serde(default)]
+    pub approval: ApprovalSettings,
+
+    /// Files that file tools refuse to read
+    #[serde(default)]
+    pub secret_files: SecretFileSettings,
+
+    /// Session cost and token limits
+    #[serde(default)]
+    pub budget: BudgetSettings,
+
+    /// Time, CPU, memory and output limits per shell command
+    #[serde(default)]
+    pub command_limits: CommandLimits,
+
+    /// Keep a semantic index of the current repository in `.mylm/` for code_search_semantic
+    #[serde(default)]
+    pub code_index: bool,
+
+    /// Offer the lsp tool, which starts the project's language server
+    #[serde(default)]
+    pub lsp: bool,
+
+    /// Answer trivial questions with a small model instead of the agent loop
+    #[serde(default)]
+    pub fast_path: FastPathSettings,
+
+    /// Time zone and locale for the agent's time context
+    #[serde(default)]
+    pub time: TimeSettings,
+
+    /// Agent tasks started by file changes
+    #[serde(default)]
+    pub watch: WatchSettings,
+
+    /// Resource release for idle sessions
+    #[serde(default)]
+    pub idle: IdleSettings,
+
+    /// Answer repeated LLM requests from disk
+    #[serde(default)]
+    pub response_cache: ResponseCacheSettings,
+
+    /// Requests and tokens per minute across the agent and its workers
+    #[serde(default)]
+    pub rate_limits: RateLimitSettings,
+
+    /// Tracing spans and OTLP export
+    #[serde(default)]
+    pub tracing: TracingSettings,
+
+    /// debug.log levels and rotation
+    #[serde(default)]
+    pub log: LogSettings,
+
+    /// Title sessions from their first exchange with the worker model (an
+    /// extra LLM call per session)
+    #[serde(default)]
+    pub session_titles: bool,
+
+    /// Write a trace of each agent session to `traces/` in the config
+    /// directory, for `mylm replay`
+    #[serde(default)]
+    pub record_traces: bool,
+
     /// PaCoRe (Proactive Context Recall) settings
     #[serde(default)]
     pub pacore: PaCoReConfig,
@@ -159,8 +706,25 @@ impl Default for FeatureConfig {
             memory: true,
             memory_settings: MemorySettings::default(),
             workers: true,
+            worker_pool: WorkerPoolSettings::default(),
             telemetry: false,
             auto_approve_safe: false,
+            approval: ApprovalSettings::default(),
+            secret_files: SecretFileSettings::default(),
+            budget: BudgetSettings::default(),
+            command_limits: CommandLimits::default(),
+            code_index: false,
+            lsp: false,
+            fast_path: FastPathSettings::default(),
+            time: TimeSettings::default(),
+            watch: WatchSettings::default(),
+            idle: IdleSettings::default(),
+            response_cache: ResponseCacheSettings::default(),
+            rate_limits: RateLimitSettings::default(),
+            tracing: TracingSettings::default(),
+            log: LogSettings::default(),
+            session_titles: false,
+            record_traces: false,
             pacore: PaCoReConfig::default(),
         }
     }
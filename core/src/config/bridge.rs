@@ -7,8 +7,8 @@
 //! - `Config` (profile settings) → `KernelConfig` (for agent kernel)
 //! - `Config` (app settings) → `RuntimeConfig` (for agent runtime)
 
-use crate::config::{Config, ProviderType};
-use crate::provider::{LlmConfig, LlmProvider};
+use crate::config::{Config, EnvVarError, FallbackEndpoint, ProviderType};
+use crate::provider::{LlmConfig, LlmProvider, ResponseCache};
 use crate::agent::types::KernelConfig;
 use crate::agent::runtime::core::RuntimeConfig;
 
@@ -26,6 +26,28 @@ pub enum BridgeError {
     
     #[error("Failed to parse provider type: {0}")]
     InvalidProvider(String),
+    
+    #[error("Config value {field}: {source}")]
+    EnvVar {
+        field: String,
+        #[source]
+        source: EnvVarError,
+    },
+}
+
+/// Expand `${VAR}` references in the config value at `field`
+fn expand(value: &str, field: impl FnOnce() -> String) -> Result<String, BridgeError> {
+    crate::config::env::interpolate(value)
+        .map_err(|source| BridgeError::EnvVar { field: field(), source })
+}
+
+/// Fail early when the API key refers to a variable that is not set,
+/// instead of sending requests without a key
+fn check_api_key(provider_name: &str, api_key: Option<&str>) -> Result<(), BridgeError> {
+    match api_key {
+        Some(key) => expand(key, || format!("providers.{}.api_key", provider_name)).map(|_| ()),
+        None => Ok(()),
+    }
 }
 
 /// Convert unified Config to LlmConfig for a specific profile
@@ -52,9 +74,12 @@ pub fn config_to_llm_config(
         ))?;
     
     // Get model (profile override or provider default)
-    let model = profile.model.clone()
-        .or_else(|| config.providers.get(&profile.provider).map(|p| p.default_model.clone()))
-        .ok_or_else(|| BridgeError::NoModelConfigured(profile_name.to_string()))?;
+    let model = match &profile.model {
+        Some(model) => expand(model, || format!("profiles.{}.model", profile_name))?,
+        None => expand(&provider_cfg.default_model, || format!("providers.{}.default_model", profile.provider))?,
+    };
+    let base_url = expand(&provider_cfg.base_url, || format!("providers.{}.base_url", profile.provider))?;
+    check_api_key(&profile.provider, provider_cfg.api_key.as_deref())?;
     
     // Convert provider type
     let provider = provider_type_to_llm_provider(&provider_cfg.provider_type)?;
@@ -62,9 +87,9 @@ pub fn config_to_llm_config(
     // Build base LlmConfig
     let mut llm_config = LlmConfig::new(
         provider,
-        provider_cfg.base_url.clone(),
+        base_url,
         model,
-        provider_cfg.api_key.clone(),
+        provider_cfg.resolved_api_key(),
         profile.context_window,
     )
     .with_temperature(profile.temperature)
@@ -93,7 +118,59 @@ pub fn config_to_llm_config(
     
     // Enable web search if configured for this profile
     llm_config.web_search_enabled = profile.web_search.enabled;
+    llm_config.native_tool_calling = profile.native_tools;
+    llm_config.prompt_cache_hints = profile.prompt_caching;
+    
+    // Fallback endpoints share the profile settings but use their own provider
+    let fallbacks = profile.fallbacks.iter()
+        .filter_map(|fallback| match fallback_llm_config(config, profile_name, &llm_config, fallback) {
+            Ok(fallback_config) => Some(fallback_config),
+            Err(e) => {
+                crate::warn_log!("[CONFIG] Skipping fallback for profile '{}': {}", profile_name, e);
+                None
+            }
+        })
+        .collect();
+    
+    let cache = &config.features.response_cache;
+    let response_cache = if cache.enabled { ResponseCache::from_settings(cache) } else { None };
+    
+    Ok(llm_config.with_fallbacks(fallbacks).with_response_cache(response_cache))
+}
+
+/// Build the LlmConfig for one fallback endpoint from the primary's settings
+fn fallback_llm_config(
+    config: &Config,
+    profile_name: &str,
+    primary: &LlmConfig,
+    fallback: &FallbackEndpoint,
+) -> Result<LlmConfig, BridgeError> {
+    let provider_cfg = config.providers.get(&fallback.provider)
+        .ok_or_else(|| BridgeError::ProviderNotFound(
+            fallback.provider.clone(),
+            profile_name.to_string(),
+        ))?;
     
+    check_api_key(&fallback.provider, provider_cfg.api_key.as_deref())?;
+    
+    let mut llm_config = primary.clone();
+    llm_config.provider = provider_type_to_llm_provider(&provider_cfg.provider_type)?;
+    llm_config.base_url = expand(&provider_cfg.base_url, || format!("providers.{}.base_url", fallback.provider))?;
+    llm_config.api_key = provider_cfg.resolved_api_key();
+    llm_config.model = match &fallback.model {
+        Some(model) => expand(model, || format!("profiles.{}.fallbacks.model", profile_name))?,
+        None => expand(&provider_cfg.default_model, || format!("providers.{}.default_model", fallback.provider))?,
+    };
+    llm_config.extra_params.insert(
+        "provider_type".to_string(),
+        format!("{:?}", provider_cfg.provider_type).to_lowercase(),
+    );
+    if let Some(price) = fallback.input_price {
+        llm_config.input_price_per_1m = price;
+    }
+    if let Some(price) = fallback.output_price {
+        llm_config.output_price_per_1m = price;
+    }
     Ok(llm_config)
 }
 
@@ -107,6 +184,7 @@ fn provider_type_to_llm_provider(pt: &ProviderType) -> Result<LlmProvider, Bridg
         ProviderType::Kimi => Ok(LlmProvider::MoonshotKimi),
         ProviderType::InceptionLabs => Ok(LlmProvider::OpenAiCompatible),
         ProviderType::Custom => Ok(LlmProvider::OpenAiCompatible),
+        ProviderType::Mock => Ok(LlmProvider::Mock),
     }
 }
 
@@ -165,9 +243,14 @@ pub fn default_llm_config(config: &Config) -> Result<LlmConfig, BridgeError> {
 
 /// Helper to get the "worker" profile's LLM config
 /// 
-/// Used for worker/agentic tasks that may use a different model.
+/// Used for worker/agentic tasks that may use a different model. Falls back
+/// to the active profile when there is no "worker" profile.
 pub fn worker_llm_config(config: &Config) -> Result<LlmConfig, BridgeError> {
-    config_to_llm_config(config, "worker")
+    if config.profiles.contains_key("worker") {
+        config_to_llm_config(config, "worker")
+    } else {
+        default_llm_config(config)
+    }
 }
 
 #[cfg(test)]
@@ -208,6 +291,13 @@ mod tests {
                 tested_at: None,
                 test_error: None,
                 web_search: crate::config::WebSearchConfig::default(),
+                native_tools: false,
+                prompt_caching: true,
+                write_permissions: Default::default(),
+                http_permissions: Default::default(),
+                fallbacks: Vec::new(),
+                sandbox: Default::default(),
+                read_only: false,
             },
         );
         
@@ -259,4 +349,62 @@ mod tests {
         let llm_config = config_to_llm_config(&config, "test").unwrap();
         assert_eq!(llm_config.model, "gpt-4o"); // Provider's default
     }
+    
+    #[test]
+    fn test_fallback_chain() {
+        let mut config = create_test_config();
+        config.providers.insert(
+            "ollama".to_string(),
+            ProviderConfig {
+                provider_type: ProviderType::Ollama,
+                base_url: "http://localhost:11434/v1".to_string(),
+                api_key: None,
+                default_model: "llama3.1".to_string(),
+                models: vec![],
+                timeout_secs: 120,
+            },
+        );
+        if let Some(profile) = config.profiles.get_mut("test") {
+            profile.fallbacks = vec![
+                crate::config::FallbackEndpoint { provider: "missing".to_string(), ..Default::default() },
+                crate::config::FallbackEndpoint { provider: "ollama".to_string(), ..Default::default() },
+            ];
+        }
+        
+        let llm_config = config_to_llm_config(&config, "test").unwrap();
+        assert_eq!(llm_config.fallbacks.len(), 1);
+        let fallback = &llm_config.fallbacks[0];
+        assert_eq!(fallback.base_url, "http://localhost:11434/v1");
+        assert_eq!(fallback.model, "llama3.1");
+        assert_eq!(fallback.api_key, None);
+        assert_eq!(fallback.temperature, Some(0.5));
+    }
+    
+    #[test]
+    fn test_env_var_references() {
+        std::env::set_var("MYLM_BRIDGE_TEST_KEY", "sk-from-env");
+        std::env::set_var("MYLM_BRIDGE_TEST_MODEL", "gpt-4.1");
+        let mut config = create_test_config();
+        if let Some(provider) = config.providers.get_mut("openai") {
+            provider.api_key = Some("${MYLM_BRIDGE_TEST_KEY}".to_string());
+        }
+        if let Some(profile) = config.profiles.get_mut("test") {
+            profile.model = Some("${MYLM_BRIDGE_TEST_MODEL}".to_string());
+        }
+        
+        let llm_config = config_to_llm_config(&config, "test").unwrap();
+        assert_eq!(llm_config.api_key, Some("sk-from-env".to_string()));
+        assert_eq!(llm_config.model, "gpt-4.1");
+        
+        // A missing variable names the field and the variable
+        if let Some(provider) = config.providers.get_mut("openai") {
+            provider.base_url = "https://${MYLM_BRIDGE_TEST_UNSET}/v1".to_string();
+        }
+        let err = config_to_llm_config(&config, "test").unwrap_err();
+        assert!(matches!(err, BridgeError::EnvVar { .. }));
+        assert_eq!(
+            err.to_string(),
+            "Config value providers.openai.base_url: environment variable MYLM_BRIDGE_TEST_UNSET is not set (referenced as ${MYLM_BRIDGE_TEST_UNSET})"
+        );
+    }
 }
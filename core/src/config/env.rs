@@ -0,0 +1,92 @@
+//! Environment variable references in config values
+//!
+//! An API key, base URL or model in the config may contain `${NAME}`
+//! references that expand to the environment variable `NAME`, so the same
+//! file can be kept in dotfiles without secrets and pointed elsewhere per
+//! environment. `$${` writes a literal `${`.
+//!
+//! Like `secret://` references, these are expanded when a provider is used
+//! and never written back, so saving the config keeps the references.
+
+/// Why a config value could not be expanded
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum EnvVarError {
+    #[error("environment variable {0} is not set (referenced as ${{{0}}})")]
+    Missing(String),
+
+    #[error("unterminated variable reference in '{0}' (expected ${{NAME}})")]
+    Unterminated(String),
+}
+
+/// `value` with every `${NAME}` replaced by the environment variable `NAME`
+pub fn interpolate(value: &str) -> Result<String, EnvVarError> {
+    interpolate_with(value, |name| std::env::var(name).ok())
+}
+
+/// `value` with every `${NAME}` replaced by `lookup(NAME)`
+pub fn interpolate_with(
+    value: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String, EnvVarError> {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start..];
+        if let Some(escaped) = after.strip_prefix("$${") {
+            out.push_str("${");
+            rest = escaped;
+        } else if let Some(reference) = after.strip_prefix("${") {
+            let end = reference
+                .find('}')
+                .ok_or_else(|| EnvVarError::Unterminated(value.to_string()))?;
+            let name = reference[..end].trim();
+            out.push_str(&lookup(name).ok_or_else(|| EnvVarError::Missing(name.to_string()))?);
+            rest = &reference[end + 1..];
+        } else {
+            out.push('$');
+            rest = &after[1..];
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// `value` expanded, or unchanged with a warning when a variable is
+/// missing; for callers that report a failed request themselves
+pub fn expand_or_keep(value: &str) -> String {
+    interpolate(value).unwrap_or_else(|e| {
+        crate::warn_log!("[CONFIG] {}", e);
+        value.to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "OPENAI_API_KEY" => Some("sk-env".to_string()),
+            "LLM_HOST" => Some("gpu-box".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_references_expand_from_the_environment() {
+        assert_eq!(interpolate_with("${OPENAI_API_KEY}", lookup).unwrap(), "sk-env");
+        assert_eq!(
+            interpolate_with("http://${LLM_HOST}:11434/v1", lookup).unwrap(),
+            "http://gpu-box:11434/v1"
+        );
+        assert_eq!(interpolate_with("gpt-4o", lookup).unwrap(), "gpt-4o");
+        assert_eq!(interpolate_with("cost$5 $${LLM_HOST}", lookup).unwrap(), "cost$5 ${LLM_HOST}");
+
+        assert_eq!(
+            interpolate_with("${MISSING_KEY}", lookup),
+            Err(EnvVarError::Missing("MISSING_KEY".to_string()))
+        );
+        assert!(matches!(interpolate_with("http://${LLM_HOST", lookup), Err(EnvVarError::Unterminated(_))));
+    }
+}
@@ -6,11 +6,16 @@
 //! 
 //! - `base` - Core types: Provider, SearchProvider, ConfigError
 //! - `unified` - Main Config with profiles, providers, app settings
-//! - `app` - AppConfig, FeatureConfig, Theme, PaCoReConfig
+//! - `app` - AppConfig, FeatureConfig, ThemeDefinition, PaCoReConfig
 //! - `profile` - ProfileConfig, ResolvedProfile, WebSearchConfig
 //! - `provider` - ProviderConfig, ProviderType
 //! - `manager` - ConfigManager with hot-reload and rate limiting
 //! - `bridge` - Bridge functions to convert Config to LLM/Agent configs
+//! - `secrets` - SecretStore and `secret://` API key references (OS keyring)
+//! - `env` - `${VAR}` environment variable references in config values
+//! - `project` - Project-local `.mylm.yaml` overlay
+//! - `validate` - Strict config file checks with line numbers
+//! - `reload` - Config file watcher for hot-reload
 //! - `prompt` - Prompt schema definitions
 //! - `agent` - Agent-specific configuration
 //! - `legacy` - DEPRECATED: ConfigV2 for backward compatibility
@@ -27,6 +32,11 @@ pub mod provider;
 // Management and utilities
 pub mod manager;
 pub mod bridge;
+pub mod secrets;
+pub mod env;
+pub mod project;
+pub mod validate;
+pub mod reload;
 pub mod prompt;
 pub mod prompt_schema;
 pub mod agent;
@@ -44,11 +54,23 @@ pub use base::{
 // Re-exports from unified (main config)
 pub use unified::{
     Config,
-    ProfileConfig, ResolvedProfile, WebSearchConfig,
+    FallbackEndpoint, HttpPermissions, ProfileConfig, ResolvedProfile, SandboxBackend, SandboxSettings, WebSearchConfig, WritePermissions,
     ProviderConfig, ProviderType,
-    AppConfig, FeatureConfig, MemorySettings, PaCoReConfig, Theme,
+    AppConfig, ApprovalSettings, BudgetSettings, CommandLimits, FastPathSettings, FeatureConfig, IdleSettings, KeymapSettings, LogSettings, MemorySettings, PaCoReConfig, PaneLayout, RateLimitSettings, ResponseCacheSettings, SecretFileSettings, ThemeDefinition, TimeSettings, ToolResultSettings, TracingSettings, WatchSettings, WatchTrigger, WorkerPoolSettings,
 };
 
+// Re-exports from env
+pub use env::EnvVarError;
+
+// Re-exports from project
+pub use project::{ProjectConfig, ProjectPermissions, ProjectTrust};
+
+// Re-exports from reload
+pub use reload::{ConfigUpdate, ConfigWatcher};
+
+// Re-exports from secrets
+pub use secrets::{KeyringStore, MemorySecretStore, SecretStore};
+
 // Re-exports from manager
 pub use manager::{ConfigManager, CostPerToken, RateLimitError};
 
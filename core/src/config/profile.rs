@@ -58,6 +58,37 @@ pub struct ProfileConfig {
     /// Web search configuration for this profile
     #[serde(default)]
     pub web_search: WebSearchConfig,
+
+    /// Use the provider's native tool-calling API instead of the
+    /// Short-Key text protocol (falls back to text when unsupported)
+    #[serde(default)]
+    pub native_tools: bool,
+
+    /// Mark the system prompt and tool definitions as cacheable for
+    /// providers with explicit prompt caching (Claude models)
+    #[serde(default = "default_prompt_caching")]
+    pub prompt_caching: bool,
+
+    /// Path globs that scope file-write approval for this profile
+    #[serde(default)]
+    pub write_permissions: WritePermissions,
+
+    /// Hosts the http tool may call for this profile
+    #[serde(default)]
+    pub http_permissions: HttpPermissions,
+
+    /// Endpoints tried in order when the provider fails with 429, 5xx or
+    /// a network error
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallbacks: Vec<FallbackEndpoint>,
+
+    /// Isolation for shell commands run by sessions on this profile
+    #[serde(default, skip_serializing_if = "SandboxSettings::is_disabled")]
+    pub sandbox: SandboxSettings,
+
+    /// Refuse tool calls that would change files or system state
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
 }
 
 impl Default for ProfileConfig {
@@ -76,10 +107,116 @@ impl Default for ProfileConfig {
             tested_at: None,
             test_error: None,
             web_search: WebSearchConfig::default(),
+            native_tools: false,
+            prompt_caching: true,
+            write_permissions: WritePermissions::default(),
+            http_permissions: HttpPermissions::default(),
+            fallbacks: Vec::new(),
+            sandbox: SandboxSettings::default(),
+            read_only: false,
         }
     }
 }
 
+/// A fallback endpoint for a profile
+///
+/// ```toml
+/// [[profiles.default.fallbacks]]
+/// provider = "ollama"
+/// model = "llama3.1"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FallbackEndpoint {
+    /// Name of a configured provider
+    pub provider: String,
+    /// Model to use there (defaults to the provider's default model)
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Input price per 1M tokens there (defaults to the profile's)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_price: Option<f64>,
+    /// Output price per 1M tokens there (defaults to the profile's)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_price: Option<f64>,
+}
+
+/// File-write approval scopes
+///
+/// Patterns are globs (`src/**/*.rs`, `/tmp/**`). Relative patterns are
+/// resolved against the working directory, or the project root for a
+/// project overlay. `confirm` wins when a path matches both lists.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WritePermissions {
+    /// Writes to matching paths are approved without asking
+    #[serde(default)]
+    pub auto_approve: Vec<String>,
+    /// Writes to matching paths always ask, even with auto-approve on
+    #[serde(default)]
+    pub confirm: Vec<String>,
+}
+
+/// Hosts the http tool may send requests to
+///
+/// Entries are host names (`api.github.com`), optionally with a port
+/// (`localhost:8080`) or a leading wildcard for subdomains
+/// (`*.example.com`). An empty list blocks every request.
+///
+/// ```toml
+/// [profiles.default.http_permissions]
+/// allowed_hosts = ["api.github.com", "localhost:8080"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HttpPermissions {
+    #[serde(default)]
+    pub allowed_hosts: Vec<String>,
+}
+
+/// Where shell commands run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SandboxBackend {
+    /// Directly on the host
+    #[default]
+    None,
+    /// In Linux namespaces via `bwrap`
+    Bubblewrap,
+    /// In a throwaway container
+    Docker,
+}
+
+/// Command sandbox for a profile
+///
+/// Sandboxed commands see the host read-only, run in a scratch working
+/// directory that is the only writable place (besides `writable_paths`) and
+/// have no network unless `network` is set.
+///
+/// ```toml
+/// [profiles.worker.sandbox]
+/// backend = "bubblewrap"
+/// writable_paths = ["target"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SandboxSettings {
+    #[serde(default)]
+    pub backend: SandboxBackend,
+    /// Image for the docker backend (default: debian:stable-slim)
+    #[serde(default)]
+    pub image: Option<String>,
+    /// Allow network access
+    #[serde(default)]
+    pub network: bool,
+    /// Host paths mounted read-write; relative ones are resolved against
+    /// the working directory
+    #[serde(default)]
+    pub writable_paths: Vec<String>,
+}
+
+impl SandboxSettings {
+    pub fn is_disabled(&self) -> bool {
+        self.backend == SandboxBackend::None
+    }
+}
+
 /// Web search configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebSearchConfig {
@@ -91,7 +228,7 @@ pub struct WebSearchConfig {
     #[serde(default)]
     pub provider: SearchProvider,
 
-    /// API key for search provider
+    /// API key for search provider, or a `secret://` reference to one
     #[serde(default)]
     pub api_key: Option<String>,
 
@@ -148,10 +285,15 @@ impl ResolvedProfile {
             Some(super::ProviderType::Kimi) => "https://api.moonshot.cn/v1".to_string(),
             Some(super::ProviderType::InceptionLabs) => "https://api.inceptionlabs.ai/v1".to_string(),
             Some(super::ProviderType::Custom) | None => "https://api.openai.com/v1".to_string(),
+            Some(super::ProviderType::Mock) => String::new(),
         }
     }
 }
 
+fn default_prompt_caching() -> bool {
+    true
+}
+
 fn default_provider() -> String {
     "openai".to_string()
 }
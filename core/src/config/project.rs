@@ -0,0 +1,431 @@
+//! Project-local configuration overlay
+//!
+//! A `.mylm.yaml` in the working directory or any parent is merged over the
+//! global config when a session starts, so each repository can carry its own
+//! profiles, approval rules, tools and prompt additions:
+//!
+//! ```yaml
+//! # .mylm.yaml
+//! active_profile: local
+//! profiles:
+//!   local:                      # merged over the global profile of that name
+//!     provider: ollama
+//!     model: qwen2.5-coder:14b
+//! allowed_tools: [shell, read_file, write_file, apply_patch, code_search, git_diff]
+//! auto_approve_commands: ["cargo *", "git status"]
+//! forbidden_commands: ["git push *"]
+//! approval_rules: ["ask file_write:outside_workspace"]
+//! permissions:                  # same sections as .mylm/permissions.toml
+//!   write:
+//!     auto_approve: ["src/**"]
+//!   http:
+//!     allowed_hosts: [docs.rs]
+//! instructions: |
+//!   This is a Rust workspace. Run `cargo clippy` before you finish.
+//! ```
+//!
+//! Command lists extend the global ones; approval rules may only `deny` or
+//! `ask`; relative write globs are resolved against the directory holding
+//! the file. The merged config is the
+//! session's runtime copy and is not meant to be saved.
+//!
+//! A repository can ship this file, so it is trusted on first use: until
+//! the user trusts a file (by directory and content hash, asked when a
+//! session starts or with `mylm config trust`), only the parts that narrow
+//! what the agent may do apply: `allowed_tools`, `forbidden_commands`,
+//! approval rules, write `confirm` globs and http hosts (which can only
+//! narrow the profile's list). Profiles, the active profile,
+//! auto-approvals, write `auto_approve` globs and instructions wait for
+//! trust, and any edit to the file withdraws it.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{Config, HttpPermissions, ProfileConfig, WritePermissions};
+
+/// Overlay file name, looked up from the working directory upwards
+pub const FILE_NAME: &str = ".mylm.yaml";
+
+/// Contents of a `.mylm.yaml`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// Directory the file was found in
+    #[serde(skip)]
+    pub root: PathBuf,
+    /// SHA-256 of the file, hex
+    #[serde(skip)]
+    pub hash: String,
+    /// The user trusted this directory's file with this content
+    #[serde(skip)]
+    pub trusted: bool,
+    /// Profile to use in this project
+    #[serde(default)]
+    pub active_profile: Option<String>,
+    /// Profile fields, merged key by key over the global profiles
+    #[serde(default)]
+    pub profiles: HashMap<String, serde_yml::Value>,
+    /// Tools sessions may see and run; empty means every tool
+    #[serde(default)]
+    pub allowed_tools: Vec<String>,
+    /// Command patterns (`*` wildcard) approved without asking
+    #[serde(default)]
+    pub auto_approve_commands: Vec<String>,
+    /// Command patterns always denied
+    #[serde(default)]
+    pub forbidden_commands: Vec<String>,
+    /// `deny` and `ask` approval policy rules, e.g. `ask shell:^cargo publish`;
+    /// `allow` rules are ignored, they belong in the global config
+    #[serde(default)]
+    pub approval_rules: Vec<String>,
+    #[serde(default)]
+    pub permissions: ProjectPermissions,
+    /// Appended to the agent's system prompt
+    #[serde(default)]
+    pub instructions: Option<String>,
+}
+
+/// Path and host permissions of a project
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectPermissions {
+    #[serde(default)]
+    pub write: WritePermissions,
+    #[serde(default)]
+    pub http: HttpPermissions,
+}
+
+/// Overlays the user has trusted with their content hash: `.mylm.yaml`
+/// files by directory, other files such as `.mylm/permissions.toml` by path
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectTrust {
+    #[serde(default)]
+    pub trusted: BTreeMap<PathBuf, String>,
+}
+
+impl ProjectTrust {
+    /// `trusted_projects.json` in the data directory
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("mylm").join("trusted_projects.json"))
+    }
+
+    /// The trusted files, or none if the list is missing or unreadable
+    pub fn load() -> Self {
+        Self::default_path().map(|path| Self::load_from(&path)).unwrap_or_default()
+    }
+
+    pub fn load_from(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::default_path().ok_or_else(|| anyhow::anyhow!("Could not find data directory"))?;
+        crate::util::write_private(&path, serde_json::to_string_pretty(self)?.as_bytes())
+    }
+
+    pub fn is_trusted(&self, project: &ProjectConfig) -> bool {
+        self.trusted.get(&project.root).is_some_and(|hash| *hash == project.hash)
+    }
+
+    /// Trust `project` as it is now
+    pub fn trust(&mut self, project: &ProjectConfig) {
+        self.trusted.insert(project.root.clone(), project.hash.clone());
+    }
+
+    /// Whether the file at `path` is trusted with `content`
+    pub fn is_trusted_file(&self, path: &Path, content: &str) -> bool {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.trusted.get(&path).is_some_and(|hash| *hash == content_hash(content))
+    }
+
+    /// Trust the file at `path` as it is now
+    pub fn trust_file(&mut self, path: &Path) -> Result<()> {
+        let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.trusted.insert(path, content_hash(&content));
+        Ok(())
+    }
+}
+
+/// SHA-256 of an overlay file, hex
+fn content_hash(content: &str) -> String {
+    hex::encode(Sha256::digest(content.as_bytes()))
+}
+
+impl ProjectConfig {
+    /// Parse the overlay at `path`, trusted if the user trusted its content
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::load_with(path, &ProjectTrust::load())
+    }
+
+    pub fn load_with(path: &Path, trust: &ProjectTrust) -> Result<Self> {
+        let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut project: Self =
+            serde_yml::from_str(&content).with_context(|| format!("Invalid project config {}", path.display()))?;
+        let root = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        project.root = root.canonicalize().unwrap_or(root);
+        project.hash = content_hash(&content);
+        project.trusted = trust.is_trusted(&project);
+        Ok(project)
+    }
+
+    /// Sections that only apply once the file is trusted, for the trust prompt
+    pub fn untrusted_sections(&self) -> Vec<&'static str> {
+        let permissions = &self.permissions;
+        [
+            ("profiles", !self.profiles.is_empty()),
+            ("active_profile", self.active_profile.is_some()),
+            ("auto_approve_commands", !self.auto_approve_commands.is_empty()),
+            ("write permissions", !permissions.write.auto_approve.is_empty()),
+            ("instructions", self.instructions.as_deref().is_some_and(|i| !i.trim().is_empty())),
+        ]
+        .into_iter()
+        .filter_map(|(name, present)| present.then_some(name))
+        .collect()
+    }
+
+    /// This overlay with the permission sections of `started`, the overlay a
+    /// session began with (none when it had none)
+    ///
+    /// Auto-approvals only carry over from a trusted `started`.
+    pub fn with_permissions_of(mut self, started: Option<&ProjectConfig>) -> Self {
+        let started = started.cloned().unwrap_or_default();
+        self.allowed_tools = started.allowed_tools;
+        self.forbidden_commands = started.forbidden_commands;
+        self.approval_rules = started.approval_rules;
+        self.permissions = started.permissions;
+        if started.trusted {
+            self.auto_approve_commands = started.auto_approve_commands;
+        } else {
+            self.auto_approve_commands.clear();
+            self.permissions.write.auto_approve.clear();
+        }
+        self
+    }
+
+    /// An overlay with only the permission sections of this one
+    pub fn permissions_only(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            hash: self.hash.clone(),
+            trusted: self.trusted,
+            ..Self::default()
+        }
+        .with_permissions_of(Some(self))
+    }
+
+    /// The nearest `.mylm.yaml` at or above `start`, if any
+    pub fn discover(start: &Path) -> Result<Option<Self>> {
+        match start.ancestors().map(|dir| dir.join(FILE_NAME)).find(|path| path.is_file()) {
+            Some(path) => Self::load(&path).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Merge this overlay into `config`; an untrusted overlay only narrows it
+    pub fn apply(&self, config: &mut Config) -> Result<()> {
+        if !self.trusted {
+            let ignored = self.untrusted_sections();
+            if !ignored.is_empty() {
+                crate::warn_log!(
+                    "[CONFIG] {} is not trusted; ignoring {}",
+                    self.root.join(FILE_NAME).display(),
+                    ignored.join(", ")
+                );
+            }
+        }
+        let profiles = if self.trusted { &self.profiles } else { &HashMap::new() };
+        for (name, overlay) in profiles {
+            let base = config.profiles.get(name).cloned().unwrap_or_default();
+            let mut merged = serde_yml::to_value(base)?;
+            merge(&mut merged, overlay.clone());
+            let profile: ProfileConfig = serde_yml::from_value(merged)
+                .with_context(|| format!("Invalid profile '{}' in {}", name, self.root.join(FILE_NAME).display()))?;
+            config.profiles.insert(name.clone(), profile);
+        }
+        if let Some(active) = &self.active_profile {
+            if !config.profiles.contains_key(active) && !self.profiles.contains_key(active) {
+                anyhow::bail!("{} selects unknown profile '{}'", self.root.join(FILE_NAME).display(), active);
+            }
+            if self.trusted {
+                config.active_profile = active.clone();
+            }
+        }
+
+        let approval = &mut config.features.approval;
+        if self.trusted {
+            approval.auto_approve_commands.extend(self.auto_approve_commands.iter().cloned());
+        }
+        approval.forbidden_commands.extend(self.forbidden_commands.iter().cloned());
+        for rule in &self.approval_rules {
+            if is_narrowing_rule(rule) {
+                approval.rules.push(rule.clone());
+            } else {
+                crate::warn_log!("[CONFIG] Ignoring '{}' in {}: only deny and ask rules apply", rule, FILE_NAME);
+            }
+        }
+        config.project = Some(self.clone());
+        Ok(())
+    }
+}
+
+impl Config {
+    /// This config with the `.mylm.yaml` above `cwd` merged in, if there is one
+    pub fn with_project_overlay(mut self, cwd: &Path) -> Result<Self> {
+        if let Some(project) = ProjectConfig::discover(cwd)? {
+            crate::info_log!("[CONFIG] Using project config {}", project.root.join(FILE_NAME).display());
+            project.apply(&mut self)?;
+        }
+        Ok(self)
+    }
+
+    /// Tools the project restricts sessions to, if it does
+    pub fn project_tools(&self) -> Option<&[String]> {
+        self.project
+            .as_ref()
+            .map(|p| p.allowed_tools.as_slice())
+            .filter(|tools| !tools.is_empty())
+    }
+
+    /// Project instructions for the system prompt, if the project is trusted
+    pub fn project_instructions(&self) -> Option<&str> {
+        self.project
+            .as_ref()
+            .filter(|p| p.trusted)
+            .and_then(|p| p.instructions.as_deref())
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+    }
+}
+
+/// A `deny` or `ask` policy rule, which can only make approval stricter
+pub fn is_narrowing_rule(rule: &str) -> bool {
+    let action = rule.split_whitespace().next().unwrap_or_default();
+    action.eq_ignore_ascii_case("deny") || action.eq_ignore_ascii_case("ask")
+}
+
+/// Overwrite `base` with `overlay`, recursing into mappings present in both
+fn merge(base: &mut serde_yml::Value, overlay: serde_yml::Value) {
+    match (base, overlay) {
+        (serde_yml::Value::Mapping(base), serde_yml::Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const PROJECT: &str = r#"
+active_profile: local
+profiles:
+  default:
+    temperature: 0.2
+  local:
+    provider: ollama
+    model: qwen2.5-coder
+allowed_tools: [shell, read_file]
+auto_approve_commands: ["cargo *"]
+permissions:
+  write:
+    auto_approve: ["src/**"]
+instructions: |
+  Run cargo clippy before finishing.
+"#;
+
+    #[test]
+    fn test_overlay_is_found_above_cwd_and_merged() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join(FILE_NAME), PROJECT).unwrap();
+        let nested = dir.path().join("crates/app/src");
+        std::fs::create_dir_all(&nested).unwrap();
+
+        let mut global = Config::default();
+        global.active_profile_mut().max_iterations = 7;
+        global.features.approval.forbidden_commands = vec!["sudo *".to_string()];
+
+        let mut project = ProjectConfig::discover(&nested).unwrap().unwrap();
+        assert!(!project.trusted);
+        let mut trust = ProjectTrust::default();
+        trust.trust(&project);
+        project = ProjectConfig::load_with(&dir.path().join(FILE_NAME), &trust).unwrap();
+        assert!(project.trusted);
+        let mut config = global;
+        project.apply(&mut config).unwrap();
+        assert_eq!(config.active_profile, "local");
+        assert_eq!(config.active_profile().model.as_deref(), Some("qwen2.5-coder"));
+        // Fields the project does not set keep their global values
+        let default = &config.profiles["default"];
+        assert_eq!(default.temperature, 0.2);
+        assert_eq!(default.max_iterations, 7);
+
+        assert_eq!(config.features.approval.auto_approve_commands, ["cargo *"]);
+        assert_eq!(config.features.approval.forbidden_commands, ["sudo *"]);
+        assert_eq!(config.project_tools().unwrap(), ["shell", "read_file"]);
+        assert_eq!(config.project_instructions(), Some("Run cargo clippy before finishing."));
+        assert_eq!(config.project.as_ref().unwrap().root, dir.path().canonicalize().unwrap());
+
+        // Any edit withdraws the trust
+        std::fs::write(dir.path().join(FILE_NAME), format!("{}\n# edited", PROJECT)).unwrap();
+        assert!(!ProjectConfig::load_with(&dir.path().join(FILE_NAME), &trust).unwrap().trusted);
+    }
+
+    #[test]
+    fn test_untrusted_overlay_only_narrows() {
+        let dir = TempDir::new().unwrap();
+        let project = format!(
+            "{}forbidden_commands: [\"git push *\"]\napproval_rules: [\"allow *\", \"ask shell:^make\"]\n",
+            PROJECT
+        );
+        std::fs::write(dir.path().join(FILE_NAME), project).unwrap();
+        let project = ProjectConfig::load_with(&dir.path().join(FILE_NAME), &ProjectTrust::default()).unwrap();
+
+        let mut config = Config::default();
+        project.apply(&mut config).unwrap();
+        assert!(!config.profiles.contains_key("local"));
+        assert_eq!(config.active_profile, Config::default().active_profile);
+        assert!(config.features.approval.auto_approve_commands.is_empty());
+        assert_eq!(config.features.approval.forbidden_commands, ["git push *"]);
+        assert_eq!(config.features.approval.rules, ["ask shell:^make"]);
+        assert_eq!(config.project_tools().unwrap(), ["shell", "read_file"]);
+        assert_eq!(config.project_instructions(), None);
+        assert_eq!(
+            project.untrusted_sections(),
+            [
+                "profiles",
+                "active_profile",
+                "auto_approve_commands",
+                "write permissions",
+                "instructions"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_overlays_are_reported() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join(FILE_NAME), "allowed_tool: [shell]\n").unwrap();
+        assert!(Config::default().with_project_overlay(dir.path()).is_err());
+
+        std::fs::write(dir.path().join(FILE_NAME), "active_profile: missing\n").unwrap();
+        let err = Config::default().with_project_overlay(dir.path()).unwrap_err();
+        assert!(err.to_string().contains("unknown profile 'missing'"));
+    }
+}
@@ -352,7 +352,18 @@ PDF Support:
 {\"t\":\"Finding auth code\",\"a\":\"search_files\",\"i\":{\"query\":\"auth token validation\"}}
 ```
 
+**code_search** - Regex or literal search over the workspace (respects .gitignore):
+
+```json
+// Regex, results include line numbers and 2 lines of context
+{\"t\":\"Finding handlers\",\"a\":\"code_search\",\"i\":{\"pattern\":\"fn \\\\w+_handler\",\"path\":\"src\"}}
+
+// Literal text in Rust files only
+{\"t\":\"Finding unwraps\",\"a\":\"code_search\",\"i\":{\"pattern\":\"unwrap()\",\"literal\":true,\"glob\":\"*.rs\"}}
+```
+
 Best Practices:
+- Use `code_search` instead of `grep`/`rg` in the shell; it needs no approval
 - Use `search_files` first to locate relevant files, then `read_file` with partial reads
 - For files >100KB, prefer `chunked` or `search` strategy to avoid hitting limits
 - When investigating issues, use `search_files` with error keywords before reading
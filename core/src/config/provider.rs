@@ -11,14 +11,15 @@ pub struct ProviderConfig {
     #[serde(rename = "type")]
     pub provider_type: ProviderType,
 
-    /// API base URL
+    /// API base URL (may contain `${VAR}` references)
     pub base_url: String,
 
-    /// API key (stored in keyring in production)
+    /// API key, or a `secret://` reference to one in the OS keyring; may
+    /// contain `${VAR}` references
     #[serde(skip_serializing_if = "Option::is_none")]
     pub api_key: Option<String>,
 
-    /// Default model
+    /// Default model (may contain `${VAR}` references)
     pub default_model: String,
 
     /// Available models
@@ -31,6 +32,16 @@ pub struct ProviderConfig {
 }
 
 impl ProviderConfig {
+    /// The API key, read from the environment or the keyring if it refers there
+    pub fn resolved_api_key(&self) -> Option<String> {
+        super::secrets::resolve_key(self.api_key.as_deref())
+    }
+
+    /// The base URL with environment references expanded
+    pub fn resolved_base_url(&self) -> String {
+        super::env::expand_or_keep(&self.base_url)
+    }
+
     /// Create OpenAI provider config
     pub fn openai(api_key: String) -> Self {
         Self {
@@ -79,6 +90,19 @@ impl ProviderConfig {
         }
     }
 
+    /// Create a mock provider config answering from the script at `script`
+    /// (echoes when empty)
+    pub fn mock(script: impl Into<String>) -> Self {
+        Self {
+            provider_type: ProviderType::Mock,
+            base_url: script.into(),
+            api_key: None,
+            default_model: "mock".to_string(),
+            models: vec!["mock".to_string()],
+            timeout_secs: default_timeout(),
+        }
+    }
+
     /// Create Inception Labs provider config
     pub fn inception(api_key: String) -> Self {
         Self {
@@ -105,6 +129,8 @@ pub enum ProviderType {
     Kimi,
     InceptionLabs,
     Custom,
+    /// Scripted responses for tests; `base_url` is the script path
+    Mock,
 }
 
 fn default_timeout() -> u64 {
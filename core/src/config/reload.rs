@@ -0,0 +1,212 @@
+//! Config file hot-reload
+//!
+//! `ConfigWatcher` watches the config file, and optionally the project's
+//! `.mylm.yaml`, and reports every settled change as a `ConfigUpdate`: the
+//! reloaded config with any project overlay applied, or why it could not be
+//! loaded. Frontends read it from their event loop and hand new configs to
+//! a `SessionReloader`; an edit that does not load leaves the running session
+//! as it was.
+//!
+//! The overlay's permission sections (allowed tools, command lists,
+//! approval rules, write and http permissions) are not reloaded: they stay
+//! as the session started with them, even if the file is edited or removed,
+//! so the agent cannot widen its own permissions by rewriting `.mylm.yaml`.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+
+use super::{project, validate, Config, ProjectConfig};
+
+/// Quiet time after the last write before the config is read; editors
+/// often save in several steps
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Outcome of a change to a watched config file
+#[derive(Debug, Clone)]
+pub enum ConfigUpdate {
+    /// The files load; the new config
+    Changed(Box<Config>),
+    /// The files do not load, with the first error found
+    Invalid(String),
+}
+
+/// Watches config files and reports changes; stops when dropped
+pub struct ConfigWatcher {
+    _watcher: RecommendedWatcher,
+    updates: mpsc::UnboundedReceiver<ConfigUpdate>,
+    task: tokio::task::JoinHandle<()>,
+    files: Vec<PathBuf>,
+}
+
+impl ConfigWatcher {
+    /// Watch the config at `path`, and with a `project_dir` the project
+    /// overlay that applies there; `started` is the overlay the session
+    /// started with, whose permission sections are kept
+    pub fn start(path: &Path, project_dir: Option<&Path>, started: Option<&ProjectConfig>) -> Result<Self, String> {
+        let mut files = vec![path.to_path_buf()];
+        let overlay = project_dir.and_then(|dir| {
+            dir.ancestors()
+                .map(|dir| dir.join(project::FILE_NAME))
+                .find(|file| file.is_file())
+        });
+        files.extend(overlay);
+        // Editors replace files on save, so watch the directories holding them
+        let dirs: BTreeSet<PathBuf> = files
+            .iter()
+            .filter_map(|file| file.parent())
+            .map(Path::to_path_buf)
+            .collect();
+
+        let (changed_tx, mut changed_rx) = mpsc::unbounded_channel::<PathBuf>();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event {
+                if event.kind.is_create() || event.kind.is_modify() {
+                    for path in event.paths {
+                        let _ = changed_tx.send(path);
+                    }
+                }
+            }
+        })
+        .map_err(|e| format!("Cannot start the config watcher: {}", e))?;
+        for dir in &dirs {
+            watcher
+                .watch(dir, RecursiveMode::NonRecursive)
+                .map_err(|e| format!("Cannot watch {}: {}", dir.display(), e))?;
+        }
+
+        let (update_tx, updates) = mpsc::unbounded_channel();
+        let watched = files.clone();
+        let config_path = path.to_path_buf();
+        let project_dir = project_dir.map(Path::to_path_buf);
+        let started = started.cloned();
+        let task = tokio::spawn(async move {
+            while let Some(changed) = changed_rx.recv().await {
+                if !watched.contains(&changed) {
+                    continue;
+                }
+                // Wait for the writes to settle
+                loop {
+                    match tokio::time::timeout(DEBOUNCE, changed_rx.recv()).await {
+                        Ok(Some(_)) => continue,
+                        Ok(None) => return,
+                        Err(_) => break,
+                    }
+                }
+                crate::info_log!("[CONFIG] {} changed, reloading", changed.display());
+                if update_tx.send(load(&config_path, project_dir.as_deref(), started.as_ref())).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Ok(Self { _watcher: watcher, updates, task, files })
+    }
+
+    /// A config change, if one is waiting
+    pub fn try_next(&mut self) -> Option<ConfigUpdate> {
+        self.updates.try_recv().ok()
+    }
+
+    /// Wait for the next config change
+    pub async fn next(&mut self) -> Option<ConfigUpdate> {
+        self.updates.recv().await
+    }
+
+    /// The watched files
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+}
+
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// The config at `path`, with the project overlay for `project_dir` applied
+/// when one is given, its permission sections taken from `started`
+pub fn load(path: &Path, project_dir: Option<&Path>, started: Option<&ProjectConfig>) -> ConfigUpdate {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => return ConfigUpdate::Invalid(format!("Cannot read {}: {}", path.display(), e)),
+    };
+    let config = match toml::from_str::<Config>(&content) {
+        Ok(config) => config,
+        Err(e) => {
+            // Point at the line when validation can locate the problem
+            let message = validate::validate_str(&content)
+                .into_iter()
+                .find(|d| d.is_error())
+                .map(|d| d.to_string())
+                .unwrap_or_else(|| e.message().trim().to_string());
+            return ConfigUpdate::Invalid(format!("{}:{}", path.display(), message));
+        }
+    };
+    let Some(dir) = project_dir else {
+        return ConfigUpdate::Changed(Box::new(config));
+    };
+    let current = match ProjectConfig::discover(dir) {
+        Ok(current) => current,
+        Err(e) => return ConfigUpdate::Invalid(format!("{:#}", e)),
+    };
+    // A removed overlay still leaves the session's permissions in place
+    let overlay = current
+        .or_else(|| started.map(ProjectConfig::permissions_only))
+        .map(|overlay| overlay.with_permissions_of(started));
+    let mut config = config;
+    if let Some(overlay) = overlay {
+        if let Err(e) = overlay.apply(&mut config) {
+            return ConfigUpdate::Invalid(format!("{:#}", e));
+        }
+    }
+    ConfigUpdate::Changed(Box::new(config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_applies_overlay_and_locates_errors() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, toml::to_string_pretty(&Config::default()).unwrap()).unwrap();
+        std::fs::write(dir.path().join(project::FILE_NAME), "forbidden_commands: [\"cargo publish *\"]\n").unwrap();
+
+        let started = ProjectConfig::discover(dir.path()).unwrap();
+        match load(&path, Some(dir.path()), started.as_ref()) {
+            ConfigUpdate::Changed(config) => {
+                assert!(config.features.approval.forbidden_commands.contains(&"cargo publish *".to_string()));
+            }
+            ConfigUpdate::Invalid(e) => panic!("config should load: {}", e),
+        }
+
+        // Editing or removing the overlay keeps the permissions it started with
+        std::fs::write(dir.path().join(project::FILE_NAME), "forbidden_commands: []\n").unwrap();
+        match load(&path, Some(dir.path()), started.as_ref()) {
+            ConfigUpdate::Changed(config) => {
+                assert!(config.features.approval.forbidden_commands.contains(&"cargo publish *".to_string()));
+            }
+            ConfigUpdate::Invalid(e) => panic!("config should load: {}", e),
+        }
+        std::fs::remove_file(dir.path().join(project::FILE_NAME)).unwrap();
+        match load(&path, Some(dir.path()), started.as_ref()) {
+            ConfigUpdate::Changed(config) => {
+                assert!(config.features.approval.forbidden_commands.contains(&"cargo publish *".to_string()));
+            }
+            ConfigUpdate::Invalid(e) => panic!("config should load: {}", e),
+        }
+
+        std::fs::write(&path, "active_profile = \"default\"\n[features\n").unwrap();
+        match load(&path, Some(dir.path()), None) {
+            ConfigUpdate::Invalid(e) => assert!(e.starts_with(&format!("{}:2:", path.display())), "{}", e),
+            ConfigUpdate::Changed(_) => panic!("broken TOML should not load"),
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! Secret storage for API keys
+//!
+//! An API key in `mylm.yaml` may be a `secret://<name>` reference instead of
+//! the key itself; the key then lives in a [`SecretStore`], by default the OS
+//! keyring (Keychain on macOS, Credential Manager on Windows, Secret Service
+//! on Linux). `mylm config secure-keys` moves plaintext keys there.
+//!
+//! References are resolved when a provider is used, never written back, so
+//! the config file only ever holds the reference.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use anyhow::{Context, Result};
+
+use super::Config;
+
+/// Prefix of a reference to a stored secret
+pub const SECRET_SCHEME: &str = "secret://";
+/// Keyring service mylm's secrets are filed under
+const KEYRING_SERVICE: &str = "mylm";
+
+/// Where referenced secrets are kept
+pub trait SecretStore: Send + Sync {
+    /// The secret called `name`, None if there is none
+    fn get(&self, name: &str) -> Result<Option<String>>;
+    fn set(&self, name: &str, value: &str) -> Result<()>;
+    fn delete(&self, name: &str) -> Result<()>;
+}
+
+/// Secrets in the OS keyring
+pub struct KeyringStore {
+    service: String,
+}
+
+impl KeyringStore {
+    pub fn new() -> Self {
+        Self {
+            service: KEYRING_SERVICE.to_string(),
+        }
+    }
+
+    fn entry(&self, name: &str) -> Result<keyring::Entry> {
+        keyring::Entry::new(&self.service, name).with_context(|| format!("Cannot open keyring entry '{}'", name))
+    }
+}
+
+impl Default for KeyringStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SecretStore for KeyringStore {
+    fn get(&self, name: &str) -> Result<Option<String>> {
+        match self.entry(name)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("Cannot read '{}' from the keyring", name)),
+        }
+    }
+
+    fn set(&self, name: &str, value: &str) -> Result<()> {
+        self.entry(name)?
+            .set_password(value)
+            .with_context(|| format!("Cannot write '{}' to the keyring", name))
+    }
+
+    fn delete(&self, name: &str) -> Result<()> {
+        match self.entry(name)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e).with_context(|| format!("Cannot delete '{}' from the keyring", name)),
+        }
+    }
+}
+
+/// Secrets held in memory, for tests and embedding
+#[derive(Default)]
+pub struct MemorySecretStore {
+    secrets: Mutex<HashMap<String, String>>,
+}
+
+impl SecretStore for MemorySecretStore {
+    fn get(&self, name: &str) -> Result<Option<String>> {
+        Ok(self.secrets.lock().unwrap_or_else(|e| e.into_inner()).get(name).cloned())
+    }
+
+    fn set(&self, name: &str, value: &str) -> Result<()> {
+        self.secrets
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(name.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn delete(&self, name: &str) -> Result<()> {
+        self.secrets.lock().unwrap_or_else(|e| e.into_inner()).remove(name);
+        Ok(())
+    }
+}
+
+/// `secret://<name>` for a secret called `name`
+pub fn reference(name: &str) -> String {
+    format!("{}{}", SECRET_SCHEME, name)
+}
+
+/// The secret a value refers to, None for a plain value
+pub fn reference_name(value: &str) -> Option<&str> {
+    value.strip_prefix(SECRET_SCHEME)
+}
+
+/// Name a provider's API key is stored under
+pub fn provider_secret_name(provider: &str) -> String {
+    format!("providers/{}", provider)
+}
+
+/// Name a profile's web search API key is stored under
+pub fn web_search_secret_name(profile: &str) -> String {
+    format!("web_search/{}", profile)
+}
+
+/// A config value with a reference replaced by the secret from `store`
+pub fn resolve_with(value: &str, store: &dyn SecretStore) -> Result<String> {
+    match reference_name(value) {
+        Some(name) => store
+            .get(name)?
+            .with_context(|| format!("No secret '{}' in the keyring; run `mylm config secure-keys` or set the key again", name)),
+        None => Ok(value.to_string()),
+    }
+}
+
+/// Keyring secrets read so far, by reference
+fn key_cache() -> &'static Mutex<HashMap<String, String>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+/// A config value with `${VAR}` references expanded and a reference
+/// replaced by the secret from the OS keyring. Lookups are cached for the
+/// process, since some keyrings ask the user on every read; a secret or
+/// variable that cannot be read is logged and resolves to None.
+pub fn resolve_key(value: Option<&str>) -> Option<String> {
+    let expanded = match super::env::interpolate(value?) {
+        Ok(expanded) => expanded,
+        Err(e) => {
+            crate::warn_log!("[CONFIG] {}", e);
+            return None;
+        }
+    };
+    let value = expanded.as_str();
+    if reference_name(value).is_none() {
+        return Some(value.to_string());
+    }
+    let cache = key_cache();
+    if let Some(secret) = cache.lock().unwrap_or_else(|e| e.into_inner()).get(value) {
+        return Some(secret.clone());
+    }
+    match resolve_with(value, &KeyringStore::new()) {
+        Ok(secret) => {
+            cache
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(value.to_string(), secret.clone());
+            Some(secret)
+        }
+        Err(e) => {
+            crate::warn_log!("[CONFIG] {:#}", e);
+            None
+        }
+    }
+}
+
+/// Store a key, keeping it in `store` when `current` already refers there;
+/// returns the value to put in the config
+pub fn store_key(current: Option<&str>, key: String, store: &dyn SecretStore) -> Result<String> {
+    match current.and_then(reference_name) {
+        Some(name) => {
+            store.set(name, &key)?;
+            // The next lookup reads the rotated key
+            key_cache().lock().unwrap_or_else(|e| e.into_inner()).remove(&reference(name));
+            Ok(reference(name))
+        }
+        None => Ok(key),
+    }
+}
+
+/// Move plaintext API keys of providers and web search into `store`,
+/// replacing them with references; returns the names of the moved secrets
+pub fn secure_keys(config: &mut Config, store: &dyn SecretStore) -> Result<Vec<String>> {
+    let mut moved = Vec::new();
+    let mut secure = |slot: &mut Option<String>, name: String| -> Result<()> {
+        let Some(key) = slot.as_deref().filter(|key| !key.is_empty() && reference_name(key).is_none()) else {
+            return Ok(());
+        };
+        store.set(&name, key)?;
+        // Read it back before dropping the plaintext
+        if store.get(&name)?.as_deref() != Some(key) {
+            anyhow::bail!("The keyring did not keep '{}'", name);
+        }
+        *slot = Some(reference(&name));
+        moved.push(name);
+        Ok(())
+    };
+    for (name, provider) in config.providers.iter_mut() {
+        secure(&mut provider.api_key, provider_secret_name(name))?;
+    }
+    for (name, profile) in config.profiles.iter_mut() {
+        secure(&mut profile.web_search.api_key, web_search_secret_name(name))?;
+    }
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ProviderConfig;
+
+    #[test]
+    fn test_secure_keys_moves_plaintext_into_store() {
+        let store = MemorySecretStore::default();
+        let mut config = Config::default();
+        config.providers.insert("openai".to_string(), ProviderConfig::openai("sk-plain".to_string()));
+        config.providers.insert("local".to_string(), ProviderConfig::ollama());
+
+        let moved = secure_keys(&mut config, &store).unwrap();
+        assert_eq!(moved, ["providers/openai"]);
+        let reference = config.providers["openai"].api_key.clone().unwrap();
+        assert_eq!(reference, "secret://providers/openai");
+        assert_eq!(resolve_with(&reference, &store).unwrap(), "sk-plain");
+        assert_eq!(config.providers["local"].api_key, None);
+
+        // Already secured: nothing moves again
+        assert!(secure_keys(&mut config, &store).unwrap().is_empty());
+        assert_eq!(resolve_with("sk-inline", &store).unwrap(), "sk-inline");
+        assert!(resolve_with("secret://providers/missing", &store).is_err());
+
+        // Changing a referenced key updates the store, not the file
+        let stored = store_key(Some(&reference), "sk-new".to_string(), &store).unwrap();
+        assert_eq!(stored, reference);
+        assert_eq!(store.get("providers/openai").unwrap().as_deref(), Some("sk-new"));
+        key_cache().lock().unwrap().insert(reference.clone(), "sk-old".to_string());
+        store_key(Some(&reference), "sk-newer".to_string(), &store).unwrap();
+        assert!(!key_cache().lock().unwrap().contains_key(&reference));
+    }
+}
@@ -6,8 +6,8 @@
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
-pub use super::app::{AppConfig, FeatureConfig, MemorySettings, PaCoReConfig, Theme};
-pub use super::profile::{ProfileConfig, ResolvedProfile, WebSearchConfig};
+pub use super::app::{AppConfig, ApprovalSettings, BudgetSettings, CommandLimits, FastPathSettings, FeatureConfig, IdleSettings, KeymapSettings, LogSettings, MemorySettings, PaCoReConfig, PaneLayout, RateLimitSettings, ResponseCacheSettings, SecretFileSettings, ThemeDefinition, TimeSettings, ToolResultSettings, TracingSettings, WatchSettings, WatchTrigger, WorkerPoolSettings};
+pub use super::profile::{FallbackEndpoint, HttpPermissions, ProfileConfig, ResolvedProfile, SandboxBackend, SandboxSettings, WebSearchConfig, WritePermissions};
 pub use super::provider::{ProviderConfig, ProviderType};
 
 /// Unified MyLM Configuration
@@ -39,6 +39,14 @@ pub struct Config {
     /// Feature toggles
     #[serde(default)]
     pub features: FeatureConfig,
+
+    /// Project overlay merged into this config, if any; never saved
+    #[serde(skip)]
+    pub project: Option<super::project::ProjectConfig>,
+
+    /// Read-only mode for every profile (`mylm --read-only`); never saved
+    #[serde(skip)]
+    pub read_only: bool,
 }
 
 impl Default for Config {
@@ -50,6 +58,8 @@ impl Default for Config {
             providers: std::collections::HashMap::new(),
             app: AppConfig::default(),
             features: FeatureConfig::default(),
+            project: None,
+            read_only: false,
         };
 
         // Create default profile
@@ -156,6 +166,13 @@ impl Config {
             tested_at: None,
             test_error: None,
             web_search: WebSearchConfig::default(),
+            native_tools: false,
+            prompt_caching: true,
+            write_permissions: Default::default(),
+            http_permissions: Default::default(),
+            fallbacks: Vec::new(),
+            sandbox: Default::default(),
+            read_only: false,
         };
         config.profiles.insert(legacy.profile.clone(), profile_config);
 
@@ -174,6 +191,17 @@ impl Config {
         Ok(())
     }
 
+    /// Apply `change` to the config file at the default location and save it
+    ///
+    /// The file is read afresh, so a running config merged with a project
+    /// overlay never has its overlay written into the global file.
+    pub fn update_default(change: impl FnOnce(&mut Config)) -> anyhow::Result<()> {
+        let path = Self::default_path().ok_or_else(|| anyhow::anyhow!("No config directory"))?;
+        let mut config = if path.exists() { Self::load(&path)? } else { Self::default() };
+        change(&mut config);
+        config.save(&path)
+    }
+
     /// Get default config file path
     pub fn default_path() -> Option<PathBuf> {
         dirs::config_dir().map(|d| d.join("mylm").join("config.toml"))
@@ -288,9 +316,10 @@ impl Config {
 
         ResolvedProfile {
             provider: provider.map(|p| p.provider_type.clone()),
-            model: profile.model.clone().or_else(|| provider.map(|p| p.default_model.clone())),
-            base_url: provider.map(|p| p.base_url.clone()),
-            api_key: provider.and_then(|p| p.api_key.clone()),
+            model: profile.model.as_deref().or_else(|| provider.map(|p| p.default_model.as_str()))
+                .map(super::env::expand_or_keep),
+            base_url: provider.map(|p| p.resolved_base_url()),
+            api_key: provider.and_then(|p| p.resolved_api_key()),
             timeout_secs: provider.map(|p| p.timeout_secs).unwrap_or(120),
             max_context_tokens: profile.context_window,
             max_iterations: profile.max_iterations,
@@ -344,5 +373,11 @@ mod tests {
         // Should be valid TOML
         let parsed: Config = toml::from_str(&toml_str).unwrap();
         assert_eq!(parsed.version, config.version);
+
+        // Both spellings of the memory backend are accepted
+        let sqlite: MemorySettings = toml::from_str("backend = \"sqlite\"").unwrap();
+        assert_eq!(sqlite.backend, crate::memory::MemoryBackendKind::Sqlite);
+        let lance: MemorySettings = toml::from_str("backend = \"lancedb\"").unwrap();
+        assert_eq!(lance.backend, crate::memory::MemoryBackendKind::Lance);
     }
 }
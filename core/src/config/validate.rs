@@ -0,0 +1,404 @@
+//! Config file validation
+//!
+//! `mylm config validate` checks a config file strictly: TOML syntax, value
+//! types, keys that no setting reads, profiles and providers referenced but
+//! never defined, and malformed globs and hosts in permissions. Each finding
+//! carries the line it was found on:
+//!
+//! ```text
+//! config.toml:14:1: error: unknown key `profiles.default.temprature` (did you mean `temperature`?)
+//! config.toml:22:12: error: invalid type: string "fast", expected u32
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use anyhow::Context;
+use serde::de::{self, DeserializeOwned, Visitor};
+
+use super::{
+    AppConfig, ApprovalSettings, BudgetSettings, CommandLimits, Config, FallbackEndpoint, FastPathSettings,
+    FeatureConfig, HttpPermissions, IdleSettings, KeymapSettings, LogSettings, MemorySettings, PaCoReConfig,
+    PaneLayout, ProfileConfig, ProviderConfig, RateLimitSettings, ResponseCacheSettings, SandboxSettings,
+    SecretFileSettings, TimeSettings, ToolResultSettings, TracingSettings, WatchSettings, WatchTrigger,
+    WebSearchConfig, WorkerPoolSettings, WritePermissions,
+};
+use crate::agent::runtime::governance::http_scope::is_valid_host;
+use crate::agent::runtime::governance::policy::PolicyRule;
+use crate::memory::{EmbeddingSettings, RetentionSettings};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    /// Works, but probably not as intended
+    Warning,
+}
+
+/// One finding in a config file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// 1-based line, when the finding can be located
+    pub line: Option<usize>,
+    /// 1-based column, for syntax and type errors
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn error(line: Option<usize>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            line,
+            column: None,
+            message: message.into(),
+        }
+    }
+
+    fn warning(line: Option<usize>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..Self::error(line, message)
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "{}:{}: ", line, self.column.unwrap_or(1))?;
+        }
+        let level = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}: {}", level, self.message)
+    }
+}
+
+/// Findings for the config file at `path`
+pub fn validate_file(path: &Path) -> anyhow::Result<Vec<Diagnostic>> {
+    let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(validate_str(&content))
+}
+
+/// Findings for config file `content`, in file order
+pub fn validate_str(content: &str) -> Vec<Diagnostic> {
+    let table: toml::Table = match toml::from_str(content) {
+        Ok(table) => table,
+        Err(e) => return vec![from_toml_error(content, &e)],
+    };
+    let locator = Locator::new(content);
+    let mut out = Vec::new();
+    check_keys(&table, &mut Vec::new(), &locator, &mut out);
+    match toml::from_str::<Config>(content) {
+        Ok(config) => check_references(&config, &locator, &mut out),
+        Err(e) => out.push(from_toml_error(content, &e)),
+    }
+    out.sort_by_key(|d| d.line.unwrap_or(usize::MAX));
+    out
+}
+
+fn from_toml_error(content: &str, error: &toml::de::Error) -> Diagnostic {
+    let position = error.span().map(|span| position(content, span.start));
+    Diagnostic {
+        line: position.map(|(line, _)| line),
+        column: position.map(|(_, column)| column),
+        ..Diagnostic::error(None, error.message().trim())
+    }
+}
+
+/// 1-based line and column of byte `offset`
+fn position(content: &str, offset: usize) -> (usize, usize) {
+    let before = content.get(..offset).unwrap_or(content);
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+    (line, column)
+}
+
+/// Report keys that no setting reads
+fn check_keys(table: &toml::Table, path: &mut Vec<String>, locator: &Locator, out: &mut Vec<Diagnostic>) {
+    let known = known_keys(&path.iter().map(String::as_str).collect::<Vec<_>>());
+    for (key, value) in table {
+        path.push(key.clone());
+        match known {
+            Some(fields) if !fields.contains(&key.as_str()) => {
+                let mut message = format!("unknown key `{}`", path.join("."));
+                if let Some(suggestion) = crate::util::closest_match(key, fields.iter().copied()) {
+                    message.push_str(&format!(" (did you mean `{}`?)", suggestion));
+                }
+                out.push(Diagnostic::error(locator.line(path), message));
+            }
+            _ => match value {
+                toml::Value::Table(child) => check_keys(child, path, locator, out),
+                toml::Value::Array(items) => {
+                    for item in items {
+                        if let toml::Value::Table(child) = item {
+                            check_keys(child, path, locator, out);
+                        }
+                    }
+                }
+                _ => {}
+            },
+        }
+        path.pop();
+    }
+}
+
+/// Keys of the table at `path`, or None where any key is allowed (profile
+/// and provider names, extra parameters)
+fn known_keys(path: &[&str]) -> Option<&'static [&'static str]> {
+    Some(match path {
+        [] => field_names::<Config>(),
+        ["profiles", _] => field_names::<ProfileConfig>(),
+        ["profiles", _, "web_search"] => field_names::<WebSearchConfig>(),
+        ["profiles", _, "write_permissions"] => field_names::<WritePermissions>(),
+        ["profiles", _, "http_permissions"] => field_names::<HttpPermissions>(),
+        ["profiles", _, "fallbacks"] => field_names::<FallbackEndpoint>(),
+        ["profiles", _, "sandbox"] => field_names::<SandboxSettings>(),
+        ["providers", _] => field_names::<ProviderConfig>(),
+        ["app"] => field_names::<AppConfig>(),
+        ["app", "keymap"] => field_names::<KeymapSettings>(),
+        ["app", "tool_results"] => field_names::<ToolResultSettings>(),
+        ["app", "layouts", _] => field_names::<PaneLayout>(),
+        ["features"] => field_names::<FeatureConfig>(),
+        ["features", "memory_settings"] => field_names::<MemorySettings>(),
+        ["features", "memory_settings", "embedding"] => field_names::<EmbeddingSettings>(),
+        ["features", "memory_settings", "retention"] => field_names::<RetentionSettings>(),
+        ["features", "worker_pool"] => field_names::<WorkerPoolSettings>(),
+        ["features", "approval"] => field_names::<ApprovalSettings>(),
+        ["features", "secret_files"] => field_names::<SecretFileSettings>(),
+        ["features", "budget"] => field_names::<BudgetSettings>(),
+        ["features", "command_limits"] => field_names::<CommandLimits>(),
+        ["features", "fast_path"] => field_names::<FastPathSettings>(),
+        ["features", "time"] => field_names::<TimeSettings>(),
+        ["features", "watch"] => field_names::<WatchSettings>(),
+        ["features", "watch", "triggers"] => field_names::<WatchTrigger>(),
+        ["features", "idle"] => field_names::<IdleSettings>(),
+        ["features", "response_cache"] => field_names::<ResponseCacheSettings>(),
+        ["features", "rate_limits"] => field_names::<RateLimitSettings>(),
+        ["features", "tracing"] => field_names::<TracingSettings>(),
+        ["features", "log"] => field_names::<LogSettings>(),
+        ["features", "pacore"] => field_names::<PaCoReConfig>(),
+        _ => return None,
+    })
+}
+
+/// Serialized field names of struct `T`, as its derived `Deserialize` lists them
+fn field_names<T: DeserializeOwned>() -> &'static [&'static str] {
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldNames(&mut fields));
+    fields
+}
+
+/// Deserializer that records the field list a struct asks for, then fails
+struct FieldNames<'a>(&'a mut &'static [&'static str]);
+
+impl<'de> de::Deserializer<'de> for FieldNames<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("not a struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = fields;
+        Err(de::Error::custom("field names recorded"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+    }
+}
+
+/// Report references to undefined profiles and providers, and patterns
+/// that would be ignored at runtime
+fn check_references(config: &Config, locator: &Locator, out: &mut Vec<Diagnostic>) {
+    if !config.profiles.contains_key(&config.active_profile) {
+        out.push(Diagnostic::warning(
+            locator.line(&["active_profile"]),
+            format!(
+                "active_profile `{}` is not defined under [profiles]; built-in defaults are used",
+                config.active_profile
+            ),
+        ));
+    }
+
+    let mut names: Vec<&String> = config.profiles.keys().collect();
+    names.sort();
+    for name in names {
+        let profile = &config.profiles[name];
+        let line = |key: &str| locator.line(&["profiles", name.as_str(), key]);
+        if !config.providers.contains_key(&profile.provider) {
+            out.push(Diagnostic::error(
+                line("provider"),
+                format!(
+                    "profile `{}` uses provider `{}`, which is not defined under [providers]",
+                    name, profile.provider
+                ),
+            ));
+        }
+        for fallback in &profile.fallbacks {
+            if !config.providers.contains_key(&fallback.provider) {
+                out.push(Diagnostic::error(
+                    line("fallbacks"),
+                    format!(
+                        "fallback of profile `{}` uses provider `{}`, which is not defined under [providers]",
+                        name, fallback.provider
+                    ),
+                ));
+            }
+        }
+        let writes = &profile.write_permissions;
+        check_globs(writes.auto_approve.iter().chain(&writes.confirm), line("write_permissions"), out);
+        for host in &profile.http_permissions.allowed_hosts {
+            if !is_valid_host(host) {
+                out.push(Diagnostic::error(
+                    line("http_permissions"),
+                    format!("invalid host `{}` in profile `{}` (expected e.g. `api.github.com`, `*.example.com` or `localhost:8080`)", host, name),
+                ));
+            }
+        }
+    }
+
+    let features = &config.features;
+    if let Some(profile) = &features.fast_path.profile {
+        if !config.profiles.contains_key(profile) {
+            out.push(Diagnostic::error(
+                locator.line(&["features", "fast_path", "profile"]),
+                format!("fast_path uses profile `{}`, which is not defined under [profiles]", profile),
+            ));
+        }
+    }
+    let secret_files = &features.secret_files;
+    check_globs(
+        secret_files.deny.iter().chain(&secret_files.allow),
+        locator.line(&["features", "secret_files"]),
+        out,
+    );
+    for rule in &features.approval.rules {
+        if let Err(e) = rule.parse::<PolicyRule>() {
+            out.push(Diagnostic::error(
+                locator.line(&["features", "approval", "rules"]),
+                format!("invalid approval rule: {}", e),
+            ));
+        }
+    }
+    for trigger in &features.watch.triggers {
+        check_globs(
+            trigger.paths.iter().chain(&trigger.ignore),
+            locator.line(&["features", "watch", "triggers"]),
+            out,
+        );
+    }
+}
+
+fn check_globs<'a>(patterns: impl Iterator<Item = &'a String>, line: Option<usize>, out: &mut Vec<Diagnostic>) {
+    for pattern in patterns {
+        if let Err(e) = glob::Pattern::new(pattern) {
+            out.push(Diagnostic::error(line, format!("invalid glob `{}`: {}", pattern, e)));
+        }
+    }
+}
+
+/// First line of every table header and key in the source, by dotted path
+struct Locator {
+    lines: HashMap<String, usize>,
+}
+
+impl Locator {
+    fn new(content: &str) -> Self {
+        let mut lines = HashMap::new();
+        let mut table = String::new();
+        for (i, raw) in content.lines().enumerate() {
+            let line = raw.trim();
+            let path = if line.starts_with('[') {
+                table = dotted(line.trim_start_matches('[').split(']').next().unwrap_or_default());
+                table.clone()
+            } else if let Some((key, _)) = line.split_once('=').filter(|_| !line.starts_with('#')) {
+                match table.as_str() {
+                    "" => dotted(key),
+                    table => format!("{}.{}", table, dotted(key)),
+                }
+            } else {
+                continue;
+            };
+            lines.entry(path).or_insert(i + 1);
+        }
+        Self { lines }
+    }
+
+    /// Line of `path`, or of its nearest located parent
+    fn line<S: AsRef<str>>(&self, path: &[S]) -> Option<usize> {
+        (1..=path.len()).rev().find_map(|n| {
+            let key = path[..n].iter().map(AsRef::as_ref).collect::<Vec<_>>().join(".");
+            self.lines.get(&key).copied()
+        })
+    }
+}
+
+/// `a . "b".c` as `a.b.c`
+fn dotted(key: &str) -> String {
+    key.split('.')
+        .map(|part| part.trim().trim_matches('"').trim_matches('\''))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"active_profile = "default"
+
+[profiles.default]
+provider = "openai"
+temprature = 0.3
+fallbacks = [{ provider = "groq" }]
+
+[profiles.default.write_permissions]
+auto_approve = ["src/[a-"]
+
+[providers.openai]
+type = "openai"
+base_url = "https://api.openai.com/v1"
+default_model = "gpt-4o"
+"#;
+
+    #[test]
+    fn test_findings_are_located() {
+        let found: Vec<String> = validate_str(CONFIG).iter().map(|d| d.to_string()).collect();
+        assert_eq!(found.len(), 3, "{:#?}", found);
+        assert_eq!(found[0], "5:1: error: unknown key `profiles.default.temprature` (did you mean `temperature`?)");
+        assert!(found[1].starts_with("6:1: error: fallback of profile `default` uses provider `groq`"));
+        assert!(found[2].starts_with("8:1: error: invalid glob `src/[a-`"));
+
+        let mistyped = validate_str("[features.approval]\ntimeout_secs = \"soon\"\n");
+        assert_eq!(mistyped.len(), 1);
+        assert_eq!(mistyped[0].line, Some(2));
+        assert!(mistyped[0].message.contains("invalid type"), "{}", mistyped[0].message);
+    }
+
+    #[test]
+    fn test_valid_config_and_syntax_errors() {
+        let mut config = Config::default();
+        config.set_provider("openai".to_string(), ProviderConfig::ollama());
+        let saved = toml::to_string_pretty(&config).unwrap();
+        assert_eq!(validate_str(&saved), []);
+
+        let broken = validate_str("[profiles.default\nprovider = 1\n");
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].line, Some(1));
+    }
+}
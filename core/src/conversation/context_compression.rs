@@ -10,6 +10,10 @@ use crate::conversation::manager::Message;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+/// Start of the history message that stands in for a condensed segment
+pub const SUMMARY_PREFIX: &str = "[Summary of earlier conversation";
 
 /// A segment of compressed/trimmed messages
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -79,6 +83,38 @@ impl CompressedSegment {
             self.summary.chars().take(80).collect::<String>()
         )
     }
+
+    /// History message replacing this segment's messages
+    pub fn summary_message(&self) -> String {
+        format!("{}, segment {}]\n{}", SUMMARY_PREFIX, self.id, self.summary)
+    }
+
+    /// Whether `content` is the summary message of segment `id`
+    pub fn is_summary_of(content: &str, id: &str) -> bool {
+        content.starts_with(&format!("{}, segment {}]", SUMMARY_PREFIX, id))
+    }
+
+    /// Directory pruned segments are saved in
+    pub fn archive_dir() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("mylm").join("pruned"))
+    }
+
+    /// Save this segment as `<dir>/<id>.json`
+    pub fn save(&self, dir: &Path) -> std::io::Result<PathBuf> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}.json", self.id));
+        std::fs::write(&path, serde_json::to_vec_pretty(self)?)?;
+        Ok(path)
+    }
+
+    /// The segment saved in `dir` under `id`
+    pub fn load(dir: &Path, id: &str) -> std::io::Result<Self> {
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("Invalid segment id: {}", id)));
+        }
+        let data = std::fs::read(dir.join(format!("{}.json", id)))?;
+        Ok(serde_json::from_slice(&data)?)
+    }
 }
 
 /// Archive for compressed segments
@@ -323,7 +359,7 @@ pub fn compress_context(
     config: &CompressionConfig,
 ) -> CompressionResult {
     let total_tokens: usize = messages.iter()
-        .map(|m| m.content.len() / 4 + 1)
+        .map(|m| m.token_count)
         .sum();
     
     // If under limit, no pruning needed
@@ -343,7 +379,7 @@ pub fn compress_context(
     
     // Calculate remaining budget
     let important_tokens: usize = important.iter()
-        .map(|m| m.content.len() / 4 + 1)
+        .map(|m| m.token_count)
         .sum();
     let _remaining_budget = token_limit.saturating_sub(important_tokens);
     
@@ -354,7 +390,7 @@ pub fn compress_context(
     
     // Work backwards through remaining messages
     for msg in remaining.into_iter().rev() {
-        let msg_tokens = msg.content.len() / 4 + 1;
+        let msg_tokens = msg.token_count;
         if current_tokens + msg_tokens <= token_limit {
             kept.push(msg);
             current_tokens += msg_tokens;
@@ -368,7 +404,7 @@ pub fn compress_context(
     trimmed.reverse();
     
     // Create compressed segment
-    let tokens_saved = trimmed.iter().map(|m| m.content.len() / 4 + 1).sum();
+    let tokens_saved = trimmed.iter().map(|m| m.token_count).sum();
     let segment = CompressedSegment::new(trimmed.clone(), tokens_saved);
     
     CompressionResult {
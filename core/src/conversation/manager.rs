@@ -3,7 +3,8 @@
 //! Encapsulates all context logic including token counting, compression, condensation,
 //! and UI formatting for conversation history management.
 
-use crate::provider::chat::{ChatMessage, MessageRole};
+use crate::conversation::tokenizer::Tokenizer;
+use crate::provider::chat::{ChatMessage, ImageAttachment, MessageRole};
 use crate::provider::LlmClient;
 use crate::ui::action_stamp::{ActionStamp, ActionStampRegistry};
 use serde::{Deserialize, Serialize};
@@ -26,6 +27,9 @@ pub struct ContextConfig {
     /// Maximum total byte size for the context (API safety limit)
     /// Default is 3MB to stay safely under typical 4MB API limits
     pub max_bytes: usize,
+    /// Encoding tokens are counted with (the model's)
+    #[serde(default)]
+    pub tokenizer: Tokenizer,
 }
 
 /// Context transformation metrics for debugging and monitoring
@@ -159,6 +163,7 @@ impl ContextConfig {
             input_price_per_million: 0.0,
             output_price_per_million: 0.0,
             max_bytes: 3 * 1024 * 1024, // 3MB default
+            tokenizer: Tokenizer::default(),
         }
     }
 
@@ -187,6 +192,12 @@ impl ContextConfig {
         self
     }
 
+    /// Count tokens with `tokenizer`
+    pub fn with_tokenizer(mut self, tokenizer: Tokenizer) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
     /// Calculate the effective context limit (max_tokens - reserve)
     pub fn effective_limit(&self) -> usize {
         self.max_tokens.saturating_sub(self.max_output_tokens)
@@ -209,6 +220,7 @@ impl Default for ContextConfig {
             input_price_per_million: 0.0,
             output_price_per_million: 0.0,
             max_bytes: 3 * 1024 * 1024, // 3MB default
+            tokenizer: Tokenizer::default(),
         }
     }
 }
@@ -226,6 +238,9 @@ pub struct Message {
     /// Byte size of the content (for API limit enforcement)
     #[serde(default = "default_byte_size")]
     pub byte_size: usize,
+    /// Images the user attached (user messages only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<ImageAttachment>,
 }
 
 fn default_token_count() -> usize {
@@ -237,28 +252,43 @@ fn default_byte_size() -> usize {
 }
 
 impl Message {
-    /// Create a new message with token estimation
+    /// Create a new message, counting tokens with the default tokenizer
     pub fn new(role: impl Into<String>, content: impl Into<String>) -> Self {
+        Self::counted(role, content, Tokenizer::default())
+    }
+
+    /// Create a new message, counting tokens with `tokenizer`
+    pub fn counted(role: impl Into<String>, content: impl Into<String>, tokenizer: Tokenizer) -> Self {
         let content_str = content.into();
-        let token_count = TokenCounter::estimate(&content_str);
+        let token_count = tokenizer.count(&content_str);
         let byte_size = content_str.len(); // UTF-8 byte length
         Self {
             role: role.into(),
             content: content_str,
             token_count,
             byte_size,
+            images: Vec::new(),
         }
     }
 
+    /// This message with `images` attached
+    pub fn with_images(mut self, images: Vec<ImageAttachment>) -> Self {
+        self.images = images;
+        self
+    }
+
     /// Convert from ChatMessage
     pub fn from_chat_message(msg: &ChatMessage) -> Self {
-        let role = match msg.role {
+        Self::counted(Self::role_name(&msg.role), &msg.content, Tokenizer::default())
+    }
+
+    fn role_name(role: &MessageRole) -> &'static str {
+        match role {
             MessageRole::System => "system",
             MessageRole::User => "user",
             MessageRole::Assistant => "assistant",
             MessageRole::Tool => "tool",
-        };
-        Self::new(role, &msg.content)
+        }
     }
 
     /// Convert back to ChatMessage
@@ -284,15 +314,14 @@ impl From<ChatMessage> for Message {
     }
 }
 
-/// Simple token counter using character-based estimation
+/// Token counts with the default tokenizer, where no model is known
 #[derive(Debug, Clone, Default)]
 pub struct TokenCounter;
 
 impl TokenCounter {
-    /// Estimate token count from text
-    /// Uses simple heuristic: ~4 characters per token for English text
+    /// Token count of text (`cl100k_base`)
     pub fn estimate(text: &str) -> usize {
-        text.chars().count() / 4 + 1
+        Tokenizer::default().count(text)
     }
 
     /// Estimate tokens for a slice of ChatMessages
@@ -467,7 +496,7 @@ impl ContextManager {
     /// Estimate tokens for a message before adding it
     /// Returns (estimated_tokens, would_fit, remaining_tokens)
     pub fn estimate_message(&self, content: &str) -> (usize, bool, usize) {
-        let estimated = TokenCounter::estimate(content);
+        let estimated = self.config.tokenizer.count(content);
         let current: usize = self.history.iter().map(|m| m.token_count).sum();
         let limit = self.config.effective_limit();
         let remaining = limit.saturating_sub(current);
@@ -509,7 +538,7 @@ impl ContextManager {
         
         // Check new content size
         if let Some(content) = new_content {
-            let estimated = TokenCounter::estimate(content);
+            let estimated = self.config.tokenizer.count(content);
             let remaining = limit.saturating_sub(current);
             
             if estimated > remaining {
@@ -548,6 +577,7 @@ impl ContextManager {
             input_price_per_million: llm_config.input_price_per_1m,
             output_price_per_million: llm_config.output_price_per_1m,
             max_bytes: 3 * 1024 * 1024, // 3MB default - hard safety limit
+            tokenizer: Tokenizer::for_model(&llm_config.model),
         };
         Self::new(config)
     }
@@ -607,7 +637,7 @@ impl ContextManager {
 
     /// Set the pending user message (to be sent)
     pub fn set_pending_user_message(&mut self, content: &str) {
-        self.pending_user_message = Some(Message::new("user", content));
+        self.pending_user_message = Some(self.message("user", content));
     }
 
     /// Clear the pending user message
@@ -617,7 +647,7 @@ impl ContextManager {
 
     /// Add ephemeral context (terminal snapshot, etc.) - cleared after one turn
     pub fn add_ephemeral_context(&mut self, role: &str, content: &str) {
-        let message = Message::new(role, content);
+        let message = self.message(role, content);
         self.ephemeral_context.push(message);
     }
 
@@ -629,7 +659,7 @@ impl ContextManager {
     /// Add a message to the persistent history
     /// Automatically prunes history if token limit is exceeded
     pub fn add_message(&mut self, role: &str, content: &str) {
-        let message = Message::new(role, content);
+        let message = self.message(role, content);
         self.history.push(message);
         
         // Auto-prune if over token limit
@@ -656,13 +686,13 @@ impl ContextManager {
 
     /// Add a ChatMessage to the persistent history
     pub fn add_chat_message(&mut self, msg: &ChatMessage) {
-        let message = Message::from_chat_message(msg);
+        let message = self.chat_message(msg);
         self.history.push(message);
     }
 
     /// Set the entire history from ChatMessages (replaces history, keeps ephemeral)
     pub fn set_history(&mut self, messages: &[ChatMessage]) {
-        self.history = messages.iter().map(Message::from_chat_message).collect();
+        self.history = messages.iter().map(|m| self.chat_message(m)).collect();
     }
 
     /// Called when LLM response is complete
@@ -672,7 +702,7 @@ impl ContextManager {
     /// - Auto-prunes if over limits
     pub fn on_llm_complete(&mut self, assistant_content: &str) {
         // Add assistant response to persistent history
-        self.history.push(Message::new("assistant", assistant_content));
+        self.history.push(self.message("assistant", assistant_content));
         
         // Clear ephemeral context (was only needed for this turn)
         self.ephemeral_context.clear();
@@ -814,7 +844,7 @@ impl ContextManager {
             if let Some(client) = llm_client {
                 match self.condense_history(client).await {
                     Ok(condensed) => {
-                        self.history = condensed.iter().map(Message::from_chat_message).collect();
+                        self.history = condensed.iter().map(|m| self.chat_message(m)).collect();
                         was_condensed = true;
                     }
                 Err(e) => {
@@ -1158,7 +1188,35 @@ impl ContextManager {
 
     /// Update configuration
     pub fn set_config(&mut self, config: ContextConfig) {
+        let tokenizer = config.tokenizer;
         self.config = config;
+        self.set_tokenizer(tokenizer);
+    }
+
+    /// Count tokens with `tokenizer`, recounting the messages held
+    pub fn set_tokenizer(&mut self, tokenizer: Tokenizer) {
+        let changed = self.config.tokenizer != tokenizer;
+        self.config.tokenizer = tokenizer;
+        if !changed {
+            return;
+        }
+        for message in self
+            .history
+            .iter_mut()
+            .chain(self.ephemeral_context.iter_mut())
+            .chain(self.pending_user_message.iter_mut())
+        {
+            message.token_count = tokenizer.count(&message.content);
+        }
+    }
+
+    /// A message counted with this manager's tokenizer
+    fn message(&self, role: &str, content: &str) -> Message {
+        Message::counted(role, content, self.config.tokenizer)
+    }
+
+    fn chat_message(&self, msg: &ChatMessage) -> Message {
+        Message::counted(Message::role_name(&msg.role), &msg.content, self.config.tokenizer)
     }
     
     // ===== Debugging and Inspection Methods =====
@@ -1277,9 +1335,9 @@ mod tests {
 
     #[test]
     fn test_token_counter() {
-        let text = "Hello world"; // 11 chars
+        let text = "Hello world";
         let tokens = TokenCounter::estimate(text);
-        assert_eq!(tokens, 11 / 4 + 1); // 3 + 1 = 4
+        assert_eq!(tokens, 2); // "Hello", " world"
     }
 
     #[test]
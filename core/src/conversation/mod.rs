@@ -5,9 +5,11 @@
 
 pub mod manager;
 pub mod context_compression;
+pub mod tokenizer;
 
 // Re-export conversation manager types
 pub use manager::{ContextConfig, ContextManager, ContextError, Message, TokenCounter, TokenBreakdown};
+pub use tokenizer::Tokenizer;
 
 // Re-export context compression types
 pub use context_compression::{
@@ -0,0 +1,114 @@
+//! Token counting with the model's BPE encoding
+//!
+//! Context budgets, condensation thresholds and the TUI's context gauge all
+//! count tokens through a `Tokenizer`. OpenAI models are counted with their
+//! own encoding; other models have no public tiktoken encoding and use
+//! `cl100k_base`, which tracks Llama, Mistral, Qwen and Claude tokenizers
+//! far more closely than a characters-per-token ratio.
+//!
+//! The encodings are built once per process, on first use.
+
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use tiktoken_rs::CoreBPE;
+
+use crate::provider::chat::ChatMessage;
+
+/// Tokens added per message for role and delimiters (OpenAI chat format)
+const MESSAGE_OVERHEAD: usize = 4;
+
+/// BPE encoding used to count tokens
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Tokenizer {
+    /// GPT-4, GPT-3.5 and the default for models without their own encoding
+    #[default]
+    Cl100k,
+    /// GPT-4o, GPT-4.1, GPT-5 and the o-series
+    O200k,
+}
+
+impl Tokenizer {
+    /// Encoding for `model`, e.g. `gpt-4o-mini` or `openai/gpt-4.1`
+    pub fn for_model(model: &str) -> Self {
+        // Routers prefix the vendor: openai/gpt-4o, azure/gpt-4o
+        let name = model.rsplit('/').next().unwrap_or(model).to_ascii_lowercase();
+        let o200k = ["gpt-4o", "gpt-4.1", "gpt-4.5", "gpt-5", "chatgpt-4o", "gpt-oss"]
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+            || (name.starts_with('o') && name[1..].starts_with(|c: char| c.is_ascii_digit()));
+        if o200k {
+            Tokenizer::O200k
+        } else {
+            Tokenizer::Cl100k
+        }
+    }
+
+    /// Tokens in `text`
+    pub fn count(&self, text: &str) -> usize {
+        match self.bpe() {
+            Some(bpe) => bpe.encode_ordinary(text).len(),
+            // Encoding tables failed to load; fall back to ~4 chars/token
+            None => text.chars().count() / 4 + 1,
+        }
+    }
+
+    /// Tokens in `messages` as sent, including per-message overhead
+    pub fn count_messages(&self, messages: &[ChatMessage]) -> usize {
+        messages
+            .iter()
+            .map(|m| self.count(&m.content) + MESSAGE_OVERHEAD)
+            .sum()
+    }
+
+    fn bpe(&self) -> Option<&'static CoreBPE> {
+        static CL100K: OnceLock<Option<CoreBPE>> = OnceLock::new();
+        static O200K: OnceLock<Option<CoreBPE>> = OnceLock::new();
+        let (cell, load): (_, fn() -> anyhow::Result<CoreBPE>) = match self {
+            Tokenizer::Cl100k => (&CL100K, tiktoken_rs::cl100k_base),
+            Tokenizer::O200k => (&O200K, tiktoken_rs::o200k_base),
+        };
+        cell.get_or_init(|| {
+            load()
+                .map_err(|e| crate::warn_log!("[TOKENS] Cannot load {:?} encoding: {}", self, e))
+                .ok()
+        })
+        .as_ref()
+    }
+}
+
+impl std::fmt::Display for Tokenizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Tokenizer::Cl100k => write!(f, "cl100k_base"),
+            Tokenizer::O200k => write!(f, "o200k_base"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_selects_encoding_and_counts() {
+        assert_eq!(Tokenizer::for_model("gpt-4o-mini"), Tokenizer::O200k);
+        assert_eq!(Tokenizer::for_model("openai/gpt-4.1"), Tokenizer::O200k);
+        assert_eq!(Tokenizer::for_model("o3-mini"), Tokenizer::O200k);
+        assert_eq!(Tokenizer::for_model("gpt-4-turbo"), Tokenizer::Cl100k);
+        assert_eq!(Tokenizer::for_model("llama3.1:8b"), Tokenizer::Cl100k);
+        assert_eq!(Tokenizer::for_model("ollama"), Tokenizer::Cl100k);
+
+        assert_eq!(Tokenizer::Cl100k.count("hello world"), 2);
+        assert_eq!(Tokenizer::O200k.count("hello world"), 2);
+        assert_eq!(Tokenizer::Cl100k.count(""), 0);
+        // Code tokenizes far denser than 4 characters per token
+        let code = "fn main() { println!(\"{}\", 1 + 2); }";
+        assert!(Tokenizer::Cl100k.count(code) > code.len() / 4);
+        assert_eq!(
+            Tokenizer::Cl100k.count_messages(&[ChatMessage::user("hello world")]),
+            2 + MESSAGE_OVERHEAD
+        );
+    }
+}
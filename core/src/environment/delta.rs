@@ -0,0 +1,279 @@
+//! Differential context between turns
+//!
+//! Re-collecting and re-sending git status and system information on every
+//! turn costs tokens and, on big repositories, noticeable time. The tracker
+//! remembers what it sent last turn and a cheap fingerprint of each source's
+//! inputs (HEAD hash, index mtime, working directory). A source is only
+//! re-collected when its fingerprint changed or its entry went stale, and only
+//! the lines that changed are sent, as a "changed since last turn" section.
+//! The local time is sent again once it is stale or the date, zone or UTC
+//! offset changed.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use super::pack::ContextPack;
+use super::time::TimeContext;
+
+/// Re-collect git status at least this often, to catch edits to clean files
+/// (those touch neither HEAD nor the index)
+const GIT_MAX_AGE: Duration = Duration::from_secs(120);
+/// System information changes slowly; look again at most this often
+const SYSTEM_MAX_AGE: Duration = Duration::from_secs(300);
+/// Remind the agent of the time at least this often
+const TIME_MAX_AGE: Duration = Duration::from_secs(900);
+/// Status lines sent per turn
+const MAX_STATUS_LINES: usize = 60;
+
+/// What was sent for one source
+#[derive(Debug, Clone)]
+struct Snapshot {
+    fingerprint: String,
+    content: String,
+    collected_at: Instant,
+}
+
+impl Snapshot {
+    fn is_fresh(&self, fingerprint: &str, max_age: Duration) -> bool {
+        self.fingerprint == fingerprint && self.collected_at.elapsed() < max_age
+    }
+}
+
+/// Tracks git status, system information and local time across turns
+#[derive(Debug, Default)]
+pub struct ContextTracker {
+    git: Option<Snapshot>,
+    system: Option<Snapshot>,
+    time: Option<Snapshot>,
+    time_context: TimeContext,
+}
+
+impl ContextTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Report the time in this zone and locale instead of the machine's
+    pub fn with_time_context(mut self, time_context: TimeContext) -> Self {
+        self.time_context = time_context;
+        self
+    }
+
+    /// Context packs for this turn: everything on the first call, then only
+    /// sources that changed
+    pub async fn changes(&mut self, cwd: &Path) -> Vec<ContextPack> {
+        let mut packs = Vec::new();
+        if let Some(pack) = self.git_changes(cwd).await {
+            packs.push(pack);
+        }
+        if let Some(pack) = self.system_changes() {
+            packs.push(pack);
+        }
+        if let Some(pack) = self.time_changes() {
+            packs.push(pack);
+        }
+        packs
+    }
+
+    /// Forget what was sent, so the next turn includes everything again
+    /// (e.g. after the conversation was cleared or compacted)
+    pub fn reset(&mut self) {
+        self.git = None;
+        self.system = None;
+        self.time = None;
+    }
+
+    async fn git_changes(&mut self, cwd: &Path) -> Option<ContextPack> {
+        let Some(fingerprint) = git_fingerprint(cwd) else {
+            // Left the repository: say so once
+            return self.git.take().map(|_| ContextPack::new("Git Status", "Not in a git repository".to_string()));
+        };
+        if self.git.as_ref().is_some_and(|s| s.is_fresh(&fingerprint, GIT_MAX_AGE)) {
+            return None;
+        }
+        let content = collect_git_status(cwd).await?;
+        // `git status` may refresh the index; fingerprint what it left behind
+        let fingerprint = git_fingerprint(cwd).unwrap_or(fingerprint);
+        let previous = self.git.replace(Snapshot {
+            fingerprint,
+            content: content.clone(),
+            collected_at: Instant::now(),
+        });
+        match previous {
+            None => Some(ContextPack::new("Git Status", content)),
+            Some(previous) => line_delta(&previous.content, &content)
+                .map(|delta| ContextPack::new("Git Status (changed since last turn)", delta)),
+        }
+    }
+
+    fn system_changes(&mut self) -> Option<ContextPack> {
+        if self.system.as_ref().is_some_and(|s| s.collected_at.elapsed() < SYSTEM_MAX_AGE) {
+            return None;
+        }
+        let content = system_summary();
+        let previous = self.system.replace(Snapshot {
+            fingerprint: content.clone(),
+            content: content.clone(),
+            collected_at: Instant::now(),
+        });
+        match previous {
+            None => Some(ContextPack::new("System", content)),
+            Some(previous) if previous.content != content => {
+                Some(ContextPack::new("System (changed since last turn)", content))
+            }
+            Some(_) => None,
+        }
+    }
+
+    fn time_changes(&mut self) -> Option<ContextPack> {
+        let now = self.time_context.now();
+        let fingerprint = now.format("%Y-%m-%d %Z %:z").to_string();
+        if self.time.as_ref().is_some_and(|s| s.is_fresh(&fingerprint, TIME_MAX_AGE)) {
+            return None;
+        }
+        let content = self.time_context.describe(now.with_timezone(&chrono::Utc));
+        self.time = Some(Snapshot {
+            fingerprint,
+            content: content.clone(),
+            collected_at: Instant::now(),
+        });
+        Some(ContextPack::new("Time", content))
+    }
+}
+
+/// The `.git` directory for `cwd`, following `gitdir:` files of worktrees
+fn find_git_dir(cwd: &Path) -> Option<PathBuf> {
+    for dir in cwd.ancestors() {
+        let candidate = dir.join(".git");
+        if candidate.is_dir() {
+            return Some(candidate);
+        }
+        if candidate.is_file() {
+            let content = std::fs::read_to_string(&candidate).ok()?;
+            let target = content.trim().strip_prefix("gitdir:")?.trim();
+            return Some(dir.join(target));
+        }
+    }
+    None
+}
+
+/// Cheap summary of what `git status` depends on: the working directory,
+/// the commit HEAD points at and when the index last changed
+fn git_fingerprint(cwd: &Path) -> Option<String> {
+    let git_dir = find_git_dir(cwd)?;
+    let head = std::fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+    let commit = match head.strip_prefix("ref: ") {
+        Some(reference) => std::fs::read_to_string(git_dir.join(reference))
+            .map(|hash| hash.trim().to_string())
+            .unwrap_or_else(|_| reference.to_string()),
+        None => head.to_string(),
+    };
+    let index = mtime(&git_dir.join("index")).unwrap_or(0);
+    Some(format!("{}|{}|{}|{}", cwd.display(), head, commit, index))
+}
+
+fn mtime(path: &Path) -> Option<u128> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(SystemTime::UNIX_EPOCH).ok()?.as_nanos())
+}
+
+async fn collect_git_status(cwd: &Path) -> Option<String> {
+    let output = tokio::process::Command::new("git")
+        .args(["status", "--short", "--branch"])
+        .current_dir(cwd)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .ok()
+        .filter(|output| output.status.success())?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = text.lines().collect();
+    let mut content = lines.iter().take(MAX_STATUS_LINES).copied().collect::<Vec<_>>().join("\n");
+    if lines.len() > MAX_STATUS_LINES {
+        content.push_str(&format!("\n... and {} more", lines.len() - MAX_STATUS_LINES));
+    }
+    if lines.len() == 1 {
+        content.push_str("\n(working tree clean)");
+    }
+    Some(content)
+}
+
+/// Compact one-line system summary; memory is rounded so small fluctuations
+/// do not count as a change
+fn system_summary() -> String {
+    let mut system = sysinfo::System::new();
+    system.refresh_memory();
+    let gb = |bytes: u64| (bytes as f64 / (1024.0 * 1024.0 * 1024.0)).round();
+    let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    format!(
+        "{} {}, {} cores, RAM {:.0}/{:.0} GB used",
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        cores,
+        gb(system.used_memory()),
+        gb(system.total_memory())
+    )
+}
+
+/// Lines added (`+`) and removed (`-`) between two snapshots; None if equal
+fn line_delta(previous: &str, current: &str) -> Option<String> {
+    let before: HashSet<&str> = previous.lines().collect();
+    let after: HashSet<&str> = current.lines().collect();
+    let mut delta: Vec<String> = current
+        .lines()
+        .filter(|line| !before.contains(line))
+        .map(|line| format!("+ {}", line))
+        .collect();
+    delta.extend(previous.lines().filter(|line| !after.contains(line)).map(|line| format!("- {}", line)));
+    (!delta.is_empty()).then(|| delta.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_delta() {
+        let before = "## main\n M src/lib.rs\n?? notes.txt";
+        let after = "## main...origin/main [ahead 1]\n M src/lib.rs\n M Cargo.toml";
+        assert_eq!(
+            line_delta(before, after).unwrap(),
+            "+ ## main...origin/main [ahead 1]\n+  M Cargo.toml\n- ## main\n- ?? notes.txt"
+        );
+        assert_eq!(line_delta(before, before), None);
+    }
+
+    #[tokio::test]
+    async fn test_tracker_sends_only_changes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(dir.path())
+                .output()
+                .map(|o| o.status.success())
+                .unwrap_or(false)
+        };
+        if !git(&["init", "-q"]) {
+            return; // git not installed
+        }
+
+        let mut tracker = ContextTracker::new();
+        let first = tracker.changes(dir.path()).await;
+        let titles: Vec<&str> = first.iter().map(|p| p.title.as_str()).collect();
+        assert_eq!(titles, ["Git Status", "System", "Time"]);
+
+        // Nothing changed: nothing is sent
+        assert!(tracker.changes(dir.path()).await.is_empty());
+
+        // Staging a file changes the index, so git status is collected again
+        std::fs::write(dir.path().join("a.txt"), "a").unwrap();
+        git(&["add", "a.txt"]);
+        let next = tracker.changes(dir.path()).await;
+        assert_eq!(next.len(), 1);
+        assert_eq!(next[0].title, "Git Status (changed since last turn)");
+        assert!(next[0].content.contains("+ A  a.txt"));
+    }
+}
@@ -3,20 +3,30 @@
 //! Collects information about the user's terminal environment:
 //! - Working directory and file structure
 //! - System information (CPU, memory, processes)
-//! - Shell history and command patterns
-//! - Terminal content for LLM context
+//! - Shell history and command patterns, and commands with exit codes
+//!   recorded by the `mylm shell-init` hooks
+//! - Terminal content for LLM context, from tmux, zellij, GNU screen or
+//!   mylm's own PTY
+//! - Per-turn changes to git status and system information
+//! - Local time, time zone and locale
 
 pub mod system;
 pub mod terminal;
+pub mod multiplexer;
+pub mod shell_hooks;
 pub mod pack;
+pub mod delta;
+pub mod time;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 pub use system::SystemContext;
-pub use terminal::TerminalContext;
+pub use terminal::{KubeContext, TerminalContext};
 pub use pack::{ContextPack, ContextBuilder};
+pub use delta::ContextTracker;
+pub use time::TimeContext;
 
 /// Collected terminal environment context
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
@@ -0,0 +1,255 @@
+//! Terminal multiplexer backends for scrollback capture
+//!
+//! The terminal context includes the recent output of the terminal mylm was
+//! started from. Inside tmux, zellij or GNU screen that output is read from
+//! the multiplexer; elsewhere the fallback is the scrollback of the PTY mylm
+//! hosts itself (the TUI's terminal pane), which its reader feeds through
+//! [`PtyScrollback::record`].
+//!
+//! [`detect`] picks the multiplexer whose session the process runs in, going
+//! by the variables each one sets (`TMUX`, `ZELLIJ`, `STY`).
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+/// Lines of scrollback captured
+pub const SCROLLBACK_LINES: usize = 300;
+/// Raw PTY output kept for the fallback
+const PTY_BUFFER_BYTES: usize = 256 * 1024;
+/// How long to wait for screen to write its hardcopy
+const HARDCOPY_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// A source of terminal scrollback
+pub trait MultiplexerBackend: Send + Sync {
+    /// Short name shown to the user ("tmux", "zellij", ...)
+    fn name(&self) -> &'static str;
+
+    /// Whether this process runs inside one of the backend's sessions
+    fn is_active(&self) -> bool;
+
+    /// The last `lines` lines of output
+    fn capture(&self, lines: usize) -> Option<String>;
+}
+
+/// tmux: `capture-pane` of the current pane
+pub struct Tmux;
+
+impl MultiplexerBackend for Tmux {
+    fn name(&self) -> &'static str {
+        "tmux"
+    }
+
+    fn is_active(&self) -> bool {
+        env::var_os("TMUX").is_some()
+    }
+
+    fn capture(&self, lines: usize) -> Option<String> {
+        let start = format!("-{}", lines);
+        let output = Command::new("tmux")
+            .args(["capture-pane", "-p", "-S", &start, "-e", "-J"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        tidy(&String::from_utf8_lossy(&output.stdout), lines)
+    }
+}
+
+/// zellij: `action dump-screen` of the focused pane
+pub struct Zellij;
+
+impl MultiplexerBackend for Zellij {
+    fn name(&self) -> &'static str {
+        "zellij"
+    }
+
+    fn is_active(&self) -> bool {
+        env::var_os("ZELLIJ").is_some()
+    }
+
+    fn capture(&self, lines: usize) -> Option<String> {
+        let (_dir, file) = dump_file("zellij")?;
+        let status = Command::new("zellij")
+            .args(["action", "dump-screen", "--full"])
+            .arg(&file)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .ok()?;
+        let text = status.success().then(|| read_dump(&file, Duration::ZERO)).flatten();
+        tidy(&text?, lines)
+    }
+}
+
+/// GNU screen: `hardcopy -h` of the current window
+pub struct Screen;
+
+impl MultiplexerBackend for Screen {
+    fn name(&self) -> &'static str {
+        "screen"
+    }
+
+    fn is_active(&self) -> bool {
+        env::var_os("STY").is_some()
+    }
+
+    fn capture(&self, lines: usize) -> Option<String> {
+        let session = env::var("STY").ok()?;
+        let (_dir, file) = dump_file("screen")?;
+        let mut command = Command::new("screen");
+        command.args(["-S", &session]);
+        if let Ok(window) = env::var("WINDOW") {
+            command.args(["-p", &window]);
+        }
+        let status = command
+            .args(["-X", "hardcopy", "-h"])
+            .arg(&file)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .ok()?;
+        // The screen server writes the file after the command returns
+        let text = status.success().then(|| read_dump(&file, HARDCOPY_TIMEOUT)).flatten();
+        tidy(&text?, lines)
+    }
+}
+
+/// Fallback: the scrollback of the PTY mylm hosts
+pub struct PtyScrollback;
+
+fn pty_buffer() -> &'static Mutex<Vec<u8>> {
+    static BUFFER: OnceLock<Mutex<Vec<u8>>> = OnceLock::new();
+    BUFFER.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+impl PtyScrollback {
+    /// Keep `output` read from a hosted PTY; older output is dropped once
+    /// the buffer is full
+    pub fn record(output: &[u8]) {
+        let mut buffer = pty_buffer().lock();
+        buffer.extend_from_slice(output);
+        if buffer.len() > PTY_BUFFER_BYTES {
+            let cut = buffer.len() - PTY_BUFFER_BYTES;
+            // Start at a line so no escape sequence is cut in half
+            let cut = buffer[cut..].iter().position(|b| *b == b'\n').map_or(cut, |i| cut + i + 1);
+            buffer.drain(..cut);
+        }
+    }
+
+    /// Forget the recorded output
+    pub fn clear() {
+        pty_buffer().lock().clear();
+    }
+}
+
+impl MultiplexerBackend for PtyScrollback {
+    fn name(&self) -> &'static str {
+        "pty"
+    }
+
+    /// Whether a hosted PTY has produced output
+    fn is_active(&self) -> bool {
+        !pty_buffer().lock().is_empty()
+    }
+
+    fn capture(&self, lines: usize) -> Option<String> {
+        let raw = pty_buffer().lock().clone();
+        tidy(&render_plain(&raw, lines), lines)
+    }
+}
+
+/// Every backend, the PTY fallback last
+pub fn backends() -> [&'static dyn MultiplexerBackend; 4] {
+    [&Tmux, &Zellij, &Screen, &PtyScrollback]
+}
+
+/// The multiplexer this process runs in, else the PTY fallback
+pub fn detect() -> &'static dyn MultiplexerBackend {
+    backends()
+        .into_iter()
+        .find(|backend| backend.is_active())
+        .unwrap_or(&PtyScrollback)
+}
+
+/// Recent scrollback from the detected backend
+pub fn capture_scrollback() -> Option<String> {
+    detect().capture(SCROLLBACK_LINES)
+}
+
+/// A path for a dump file inside a fresh private directory
+///
+/// The file is only created by the multiplexer, and the directory, with the
+/// file, is removed when the returned guard drops. No other user can plant
+/// or read the dump.
+fn dump_file(backend: &str) -> Option<(tempfile::TempDir, PathBuf)> {
+    let dir = tempfile::Builder::new()
+        .prefix(&format!("mylm-{}-", backend))
+        .tempdir()
+        .ok()?;
+    let file = dir.path().join("scrollback.txt");
+    Some((dir, file))
+}
+
+/// Read a dump file, waiting up to `timeout` for it to appear
+fn read_dump(file: &Path, timeout: Duration) -> Option<String> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Ok(bytes) = std::fs::read(file) {
+            return Some(String::from_utf8_lossy(&bytes).to_string());
+        }
+        if Instant::now() >= deadline {
+            return None;
+        }
+        std::thread::sleep(Duration::from_millis(25));
+    }
+}
+
+/// Plain text of the last `lines` lines of raw terminal output
+fn render_plain(raw: &[u8], lines: usize) -> String {
+    // One spare row for the line the cursor is on
+    let rows = (lines + 1).min(u16::MAX as usize) as u16;
+    let mut parser = vt100::Parser::new(rows, 200, 0);
+    parser.process(raw);
+    parser.screen().rows(0, 200).collect::<Vec<_>>().join("\n")
+}
+
+/// Last `lines` lines, without trailing whitespace (which would give the
+/// text phantom width) or trailing blank lines; None when there is nothing
+fn tidy(text: &str, lines: usize) -> Option<String> {
+    let all: Vec<&str> = text.lines().map(str::trim_end).collect();
+    let end = all.iter().rposition(|line| !line.is_empty())? + 1;
+    let start = end.saturating_sub(lines);
+    Some(all[start..end].join("\r\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pty_fallback_and_tidy() {
+        assert_eq!(tidy("a  \nb\n\n  \n", 10).as_deref(), Some("a\r\nb"));
+        assert_eq!(tidy("1\n2\n3\n", 2).as_deref(), Some("2\r\n3"));
+        assert_eq!(tidy(" \n", 5), None);
+
+        PtyScrollback::clear();
+        assert!(!PtyScrollback.is_active());
+        for i in 0..40 {
+            PtyScrollback::record(format!("\x1b[32mline {}\x1b[0m\r\n", i).as_bytes());
+        }
+        let captured = PtyScrollback.capture(30).unwrap();
+        let lines: Vec<&str> = captured.split("\r\n").collect();
+        assert_eq!(lines.len(), 30);
+        assert_eq!(lines.last(), Some(&"line 39"));
+        assert_eq!(lines[0], "line 10");
+        PtyScrollback::clear();
+    }
+}
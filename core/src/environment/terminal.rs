@@ -1,13 +1,24 @@
 //! Terminal context collection module
 //!
 //! Collects terminal and shell context information including current directory,
-//! file listing, command history, and process information.
+//! file listing, command history, process information, terminal scrollback
+//! (see `multiplexer`), commands recorded by the shell hooks (see
+//! `shell_hooks`) and the active Kubernetes context.
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use super::shell_hooks::{self, RecordedCommand};
+
+/// Commands taken from the shell hook log
+const RECORDED_COMMANDS: usize = 20;
+
+/// Default time budget for each individual collector in [`TerminalContext::collect`]
+pub const DEFAULT_COLLECTOR_TIMEOUT: Duration = Duration::from_millis(1500);
 
 /// Terminal context information
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -26,8 +37,25 @@ pub struct TerminalContext {
     pub processes: Vec<ProcessInfo>,
     /// Network connections
     pub network_connections: Vec<NetworkInfo>,
-    /// Raw terminal scrollback (from tmux)
+    /// Raw terminal scrollback (from the terminal multiplexer, or the PTY
+    /// mylm hosts)
     pub raw_scrollback: Option<String>,
+    /// Active kubectl context, if a kubeconfig selects one
+    #[serde(default)]
+    pub kube: Option<KubeContext>,
+    /// Commands recorded by `mylm shell-init` hooks, with exit codes
+    #[serde(default)]
+    pub recent_commands: Vec<RecordedCommand>,
+}
+
+/// The cluster kubectl would talk to right now
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct KubeContext {
+    /// `current-context` from the kubeconfig
+    pub context: String,
+    /// Namespace of that context ("default" when unset)
+    pub namespace: String,
+    pub cluster: Option<String>,
 }
 
 /// Process information
@@ -62,8 +90,7 @@ pub struct NetworkInfo {
 
 impl TerminalContext {
     fn log_debug(msg: &str) {
-        if let Some(home) = dirs::home_dir() {
-            let log_dir = home.join(".config").join("mylm");
+        if let Some(log_dir) = crate::config::get_config_dir() {
             let _ = std::fs::create_dir_all(&log_dir);
             let log_file = log_dir.join("debug.log");
             if let Ok(mut file) = std::fs::OpenOptions::new()
@@ -86,9 +113,7 @@ impl TerminalContext {
                 PathBuf::from(".")
             });
 
-        let current_dir_str = current_dir
-            .to_string_lossy()
-            .to_string();
+        let current_dir_str = display_dir(&current_dir);
 
         Self::log_debug(&format!("Collecting context for CWD: {}", current_dir_str));
 
@@ -111,8 +136,14 @@ impl TerminalContext {
         // Get network connections (optional)
         let network_connections = Self::get_network_connections().unwrap_or_default();
 
-        // Get tmux scrollback (optional)
-        let raw_scrollback = Self::get_tmux_scrollback();
+        // Get multiplexer scrollback (optional)
+        let raw_scrollback = super::multiplexer::capture_scrollback();
+
+        // Get kubectl context (optional)
+        let kube = Self::get_kube_context().unwrap_or_default();
+
+        // Get commands recorded by the shell hooks (optional)
+        let recent_commands = shell_hooks::recent_commands(RECORDED_COMMANDS);
 
         Ok(TerminalContext {
             current_dir,
@@ -123,9 +154,70 @@ impl TerminalContext {
             processes,
             network_connections,
             raw_scrollback,
+            kube,
+            recent_commands,
         })
     }
 
+    /// Collect all information concurrently with the default per-collector timeout
+    ///
+    /// Unlike [`TerminalContext::new`], a slow source (git on a huge repo, an NFS
+    /// home dir, a wedged `ss`) only costs its own timeout; its field is left
+    /// empty and the rest of the context is still returned.
+    pub async fn collect() -> Self {
+        Self::collect_with_timeout(DEFAULT_COLLECTOR_TIMEOUT).await
+    }
+
+    /// Collect all information concurrently, giving each collector `timeout`
+    pub async fn collect_with_timeout(timeout: Duration) -> Self {
+        let current_dir = env::current_dir()
+            .unwrap_or_else(|_| {
+                Self::log_debug("Failed to get current_dir, falling back to '.'");
+                PathBuf::from(".")
+            });
+
+        let current_dir_str = display_dir(&current_dir);
+
+        Self::log_debug(&format!("Collecting context for CWD: {}", current_dir_str));
+
+        let listing_dir = current_dir.clone();
+        let (
+            directory_listing,
+            command_history,
+            shell_history,
+            processes,
+            network_connections,
+            raw_scrollback,
+            kube,
+            recent_commands,
+        ) = tokio::join!(
+            run_collector("directory_listing", timeout, move || {
+                Self::get_directory_listing(&listing_dir)
+            }),
+            run_collector("shell_history", timeout, Self::get_shell_history),
+            run_collector("file_history", timeout, Self::get_file_based_history),
+            run_collector("processes", timeout, Self::get_processes),
+            run_collector("network_connections", timeout, Self::get_network_connections),
+            run_collector("scrollback", timeout, || Ok(super::multiplexer::capture_scrollback())),
+            run_collector("kube_context", timeout, Self::get_kube_context),
+            run_collector("shell_hooks", timeout, || Ok(shell_hooks::recent_commands(RECORDED_COMMANDS))),
+        );
+
+        TerminalContext {
+            current_dir,
+            current_dir_str,
+            directory_listing: directory_listing
+                .unwrap_or_else(|| "(Directory listing unavailable)".to_string()),
+            command_history: command_history.unwrap_or_default(),
+            shell_history: shell_history.unwrap_or_default(),
+            processes: processes.unwrap_or_default(),
+            network_connections: network_connections.unwrap_or_default(),
+            raw_scrollback: raw_scrollback.flatten(),
+            kube: kube.flatten(),
+            recent_commands: recent_commands.unwrap_or_default(),
+        }
+    }
+
     /// Get directory listing with detailed information
     #[cfg(unix)]
     fn get_directory_listing(path: &PathBuf) -> Result<String> {
@@ -293,9 +385,13 @@ impl TerminalContext {
     /// Get shell history from history file (Windows version)
     #[cfg(windows)]
     fn get_file_based_history() -> Result<Vec<String>> {
-        // Try to get PowerShell history
-        let home_dir = dirs::home_dir().unwrap_or_else(|| PathBuf::from("C:\\"));
-        let ps_history = home_dir.join("AppData/Roaming/Microsoft/Windows/PowerShell/PSReadLine/ConsoleHost_history.txt");
+        // PSReadLine keeps PowerShell history under %APPDATA%
+        let Some(app_data) = dirs::data_dir() else {
+            return Ok(Vec::new());
+        };
+        let ps_history = ["Microsoft", "Windows", "PowerShell", "PSReadLine", "ConsoleHost_history.txt"]
+            .iter()
+            .fold(app_data, |path, part| path.join(part));
         
         if ps_history.exists() {
             if let Ok(content) = std::fs::read_to_string(&ps_history) {
@@ -404,35 +500,23 @@ impl TerminalContext {
         env::var("TMUX").is_ok()
     }
 
-    /// Get tmux scrollback history if running inside tmux
-    fn get_tmux_scrollback() -> Option<String> {
-        if !Self::is_inside_tmux() {
-            return None;
-        }
-
-        let output = Command::new("tmux")
-            .args(["capture-pane", "-p", "-S", "-300", "-e", "-J"])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::null())
-            .output()
-            .ok()?;
-
-        if output.status.success() {
-            let scrollback = String::from_utf8_lossy(&output.stdout).to_string();
-            if scrollback.trim().is_empty() {
-                None
-            } else {
-                // Trim trailing whitespace from each line to avoid phantom width
-                let trimmed = scrollback
-                    .lines()
-                    .map(|line| line.trim_end())
-                    .collect::<Vec<_>>()
-                    .join("\r\n");
-                Some(trimmed)
-            }
-        } else {
-            None
-        }
+    /// Read the active context from the kubeconfig kubectl would use
+    ///
+    /// Parses the file(s) directly instead of running kubectl, which can be
+    /// slow or hang on an unreachable cluster's auth plugin.
+    fn get_kube_context() -> Result<Option<KubeContext>> {
+        let paths: Vec<PathBuf> = match env::var_os("KUBECONFIG") {
+            Some(value) if !value.is_empty() => env::split_paths(&value).collect(),
+            _ => match dirs::home_dir() {
+                Some(home) => vec![home.join(".kube").join("config")],
+                None => return Ok(None),
+            },
+        };
+        let configs: Vec<String> = paths
+            .iter()
+            .filter_map(|path| std::fs::read_to_string(path).ok())
+            .collect();
+        Ok(parse_kubeconfigs(&configs))
     }
 
     /// Get a formatted summary for AI context
@@ -454,6 +538,17 @@ impl TerminalContext {
             ));
         }
 
+        if !self.recent_commands.is_empty() {
+            summary.push(format!(
+                "Recorded Commands (exit code, directory):\n{}",
+                self.recent_commands
+                    .iter()
+                    .map(|c| format!("  [{}] {} (in {})", c.exit_code, c.command, c.cwd))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ));
+        }
+
         if !self.shell_history.is_empty() {
             summary.push(format!(
                 "Shell History (last 5):\n{}",
@@ -482,6 +577,13 @@ impl TerminalContext {
             ));
         }
 
+        if let Some(kube) = &self.kube {
+            summary.push(format!(
+                "Kubernetes Context: {} (namespace: {})",
+                kube.context, kube.namespace
+            ));
+        }
+
         if !self.network_connections.is_empty() {
             summary.push(format!(
                 "Listening Ports:\n{}",
@@ -508,6 +610,8 @@ impl Default for TerminalContext {
             processes: Vec::new(),
             network_connections: Vec::new(),
             raw_scrollback: None,
+            kube: None,
+            recent_commands: Vec::new(),
         }
     }
 }
@@ -526,7 +630,159 @@ pub fn get_current_dir() -> Result<PathBuf> {
 }
 
 pub async fn collect_terminal_context() -> Result<TerminalContext> {
-    tokio::task::spawn_blocking(TerminalContext::new)
-        .await
-        .unwrap_or_else(|e| Err(anyhow::anyhow!("Task failed: {}", e)))
+    Ok(TerminalContext::collect().await)
+}
+
+/// `path` as the user would type it; on Windows without the `\\?\`
+/// prefix of verbatim paths
+pub fn display_dir(path: &Path) -> String {
+    let text = path.to_string_lossy();
+    if cfg!(windows) {
+        if let Some(unc) = text.strip_prefix(r"\\?\UNC\") {
+            return format!(r"\\{}", unc);
+        }
+        if let Some(local) = text.strip_prefix(r"\\?\") {
+            return local.to_string();
+        }
+    }
+    text.to_string()
+}
+
+/// Resolve the active context across merged kubeconfig files
+///
+/// Like kubectl, the first file to set `current-context` wins, and the
+/// context itself is looked up in every file.
+fn parse_kubeconfigs(contents: &[String]) -> Option<KubeContext> {
+    let docs: Vec<serde_yml::Value> = contents
+        .iter()
+        .filter_map(|content| serde_yml::from_str(content).ok())
+        .collect();
+    let current = docs.iter().find_map(|doc| {
+        doc.get("current-context")
+            .and_then(|c| c.as_str())
+            .filter(|c| !c.is_empty())
+    })?;
+    let context = docs
+        .iter()
+        .filter_map(|doc| doc.get("contexts").and_then(|c| c.as_sequence()))
+        .flatten()
+        .find(|entry| entry.get("name").and_then(|n| n.as_str()) == Some(current))
+        .and_then(|entry| entry.get("context"));
+    let field = |key: &str| {
+        context
+            .and_then(|c| c.get(key))
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    };
+    Some(KubeContext {
+        context: current.to_string(),
+        namespace: field("namespace").unwrap_or_else(|| "default".to_string()),
+        cluster: field("cluster"),
+    })
+}
+
+/// Run one blocking collector on the blocking pool, bounded by `timeout`
+///
+/// Returns `None` (and logs why) on error, panic or timeout. A timed-out
+/// collector is abandoned rather than cancelled: its thread finishes on its
+/// own, but nobody waits for it.
+async fn run_collector<T, F>(name: &'static str, timeout: Duration, f: F) -> Option<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    match tokio::time::timeout(timeout, tokio::task::spawn_blocking(f)).await {
+        Ok(Ok(Ok(value))) => Some(value),
+        Ok(Ok(Err(e))) => {
+            TerminalContext::log_debug(&format!("Collector '{}' failed: {}", name, e));
+            None
+        }
+        Ok(Err(e)) => {
+            TerminalContext::log_debug(&format!("Collector '{}' panicked: {}", name, e));
+            None
+        }
+        Err(_) => {
+            TerminalContext::log_debug(&format!(
+                "Collector '{}' timed out after {}ms",
+                name,
+                timeout.as_millis()
+            ));
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[tokio::test]
+    async fn test_run_collector_returns_value() {
+        let value = run_collector("ok", Duration::from_secs(1), || Ok(42)).await;
+        assert_eq!(value, Some(42));
+    }
+
+    #[tokio::test]
+    async fn test_run_collector_degrades_on_error() {
+        let value: Option<u32> =
+            run_collector("err", Duration::from_secs(1), || Err(anyhow::anyhow!("boom"))).await;
+        assert!(value.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_slow_collector_does_not_block_others() {
+        let start = Instant::now();
+        let timeout = Duration::from_millis(100);
+        let (slow, fast) = tokio::join!(
+            run_collector("slow", timeout, || {
+                std::thread::sleep(Duration::from_millis(1500));
+                Ok(1)
+            }),
+            run_collector("fast", timeout, || Ok(2)),
+        );
+
+        assert!(slow.is_none());
+        assert_eq!(fast, Some(2));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_parse_kubeconfigs() {
+        let main = r#"
+apiVersion: v1
+kind: Config
+current-context: prod
+contexts:
+- name: dev
+  context: {cluster: kind-dev, user: me}
+"#
+        .to_string();
+        let extra = r#"
+current-context: ignored
+contexts:
+- name: prod
+  context:
+    cluster: eks-prod
+    namespace: payments
+"#
+        .to_string();
+
+        let kube = parse_kubeconfigs(&[main.clone(), extra]).unwrap();
+        assert_eq!(kube.context, "prod");
+        assert_eq!(kube.namespace, "payments");
+        assert_eq!(kube.cluster.as_deref(), Some("eks-prod"));
+
+        // A context without a namespace uses "default"
+        let dev = parse_kubeconfigs(&[main.replace("current-context: prod", "current-context: dev")]).unwrap();
+        assert_eq!(dev.namespace, "default");
+        assert!(parse_kubeconfigs(&["contexts: []".to_string()]).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_collect_fills_current_dir() {
+        let ctx = TerminalContext::collect_with_timeout(Duration::from_secs(5)).await;
+        assert!(!ctx.current_dir_str.is_empty());
+        assert!(!ctx.directory_listing.is_empty());
+    }
 }
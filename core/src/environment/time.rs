@@ -0,0 +1,400 @@
+//! Local time context
+//!
+//! The agent is told the current time in the user's time zone, with its UTC
+//! offset, the locale and any DST change in the next two weeks. Times given
+//! in words ("tomorrow 9am", "in 2h", "next friday 14:30") are resolved by
+//! [`TimeContext::parse`] against that zone's rules, so a wall-clock time
+//! stays right across DST changes and when the machine's zone is not the
+//! user's (set `features.time` for that).
+
+use chrono::{DateTime, Datelike, Days, Duration, DurationRound, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+
+use crate::config::TimeSettings;
+
+/// How far ahead to look for a DST change worth mentioning
+const DST_LOOKAHEAD: Duration = Duration::days(14);
+/// Time of day for a date given without one ("tomorrow")
+const DEFAULT_HOUR: u32 = 9;
+/// Time of day for "tonight"
+const TONIGHT_HOUR: u32 = 20;
+
+/// Time zone and locale the agent reasons in
+#[derive(Debug, Clone)]
+pub struct TimeContext {
+    tz: Tz,
+    locale: Option<String>,
+}
+
+impl Default for TimeContext {
+    /// The machine's zone and locale
+    fn default() -> Self {
+        Self::detect(&TimeSettings::default())
+    }
+}
+
+impl TimeContext {
+    pub fn new(tz: Tz, locale: Option<String>) -> Self {
+        Self { tz, locale }
+    }
+
+    /// Zone and locale from settings, falling back to `$TZ`, the system zone
+    /// and the locale variables
+    pub fn detect(settings: &TimeSettings) -> Self {
+        let configured = settings.timezone.as_deref().and_then(|name| {
+            let tz = name.parse::<Tz>().ok();
+            if tz.is_none() {
+                crate::warn_log!("[TIME] Unknown time zone '{}' in settings, using the system zone", name);
+            }
+            tz
+        });
+        let tz = configured
+            .or_else(|| std::env::var("TZ").ok().as_deref().and_then(zone_from_tz_var))
+            .or_else(|| iana_time_zone::get_timezone().ok()?.parse().ok())
+            .unwrap_or(Tz::UTC);
+        let locale = settings.locale.clone().or_else(|| {
+            ["LC_ALL", "LC_TIME", "LANG"]
+                .iter()
+                .filter_map(|var| std::env::var(var).ok())
+                .find(|value| !value.is_empty() && value != "C" && value != "POSIX")
+        });
+        Self { tz, locale }
+    }
+
+    pub fn timezone(&self) -> Tz {
+        self.tz
+    }
+
+    pub fn now(&self) -> DateTime<Tz> {
+        Utc::now().with_timezone(&self.tz)
+    }
+
+    /// Whether numeric dates read month first ("03/04" is March 4th)
+    fn month_first(&self) -> bool {
+        self.locale.as_deref().is_none_or(|locale| {
+            let locale = locale.to_ascii_lowercase().replace('-', "_");
+            locale.starts_with("en_us") || locale.starts_with("en_ph")
+        })
+    }
+
+    /// Current time, zone, locale and upcoming DST change, one fact per line
+    pub fn describe(&self, now: DateTime<Utc>) -> String {
+        let local = now.with_timezone(&self.tz);
+        let mut lines = vec![
+            format!("Local time: {}", local.format("%A, %Y-%m-%d %H:%M %Z (UTC%:z)")),
+            format!("Time zone: {}", self.tz.name()),
+        ];
+        if let Some(locale) = &self.locale {
+            let order = if self.month_first() { "month/day" } else { "day/month" };
+            lines.push(format!("Locale: {} (numeric dates are {})", locale, order));
+        }
+        if let Some((at, before, after)) = self.next_offset_change(now) {
+            let zone_at = |t: DateTime<Utc>| t.with_timezone(&self.tz).format("%Z").to_string();
+            lines.push(format!(
+                "DST change: {} local time, from {} (UTC{}) to {} (UTC{})",
+                at.with_timezone(&before).format("%A, %Y-%m-%d %H:%M"),
+                zone_at(at - Duration::seconds(1)),
+                before,
+                zone_at(at),
+                after,
+            ));
+        }
+        lines.join("\n")
+    }
+
+    /// First change of UTC offset within [`DST_LOOKAHEAD`]: when, and the
+    /// offsets before and after
+    fn next_offset_change(&self, now: DateTime<Utc>) -> Option<(DateTime<Utc>, FixedOffset, FixedOffset)> {
+        let offset_at = |t: DateTime<Utc>| t.with_timezone(&self.tz).offset().fix();
+        let current = offset_at(now);
+        // Zones change on quarter hours at the finest
+        let step = Duration::minutes(15);
+        let mut t = now.duration_trunc(step).ok()? + step;
+        while t <= now + DST_LOOKAHEAD {
+            let offset = offset_at(t);
+            if offset != current {
+                return Some((t, current, offset));
+            }
+            t += step;
+        }
+        None
+    }
+
+    /// Resolve a time given in words, relative to `now`
+    ///
+    /// Understands RFC 3339 timestamps, "now", "in 2h" / "in 1 hour 30
+    /// minutes" / "in 3 days", and a day and a time in either order: "today",
+    /// "tonight", "tomorrow", weekday names ("fri", "next monday"),
+    /// "2026-10-20", "20.10.2026", "10/20" (order from the locale); "9am",
+    /// "9:30 pm", "14:00", "noon", "midnight". A time alone is the next time
+    /// the clock shows it; a day alone means 09:00.
+    ///
+    /// Wall-clock times are resolved in the zone: a time skipped by a DST
+    /// change moves forward by the gap, a repeated one takes the first
+    /// occurrence. Spans in days keep the time of day; hours and minutes are
+    /// elapsed time.
+    pub fn parse(&self, text: &str, now: DateTime<Utc>) -> Result<DateTime<Tz>, String> {
+        let text = text.trim();
+        if let Ok(exact) = DateTime::parse_from_rfc3339(text) {
+            return Ok(exact.with_timezone(&self.tz));
+        }
+        let lower = text.to_lowercase().replace(',', " ");
+        let words: Vec<&str> = lower
+            .split_whitespace()
+            .filter(|word| !matches!(*word, "at" | "on" | "and"))
+            .collect();
+        let now = now.with_timezone(&self.tz);
+        match words.as_slice() {
+            [] => Err("No time given".to_string()),
+            ["now"] => Ok(now),
+            ["in", span @ ..] => {
+                // "in an hour"
+                let span: String = span.iter().map(|w| if matches!(*w, "a" | "an") { "1" } else { w }).collect();
+                self.after(now, &span).ok_or_else(|| format!("Cannot read the span in '{}'", text))
+            }
+            _ => self.wall_clock(now, &words).map_err(|word| format!("Cannot understand '{}' in '{}'", word, text)),
+        }
+    }
+
+    /// `now` plus a span like "1h30m" or "2days"; None past the calendar's end
+    fn after(&self, now: DateTime<Tz>, span: &str) -> Option<DateTime<Tz>> {
+        let (mut days, mut elapsed) = (0u64, Duration::zero());
+        let mut rest = span;
+        while !rest.is_empty() {
+            let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+            let amount: u64 = rest[..digits].parse().ok()?;
+            let signed = i64::try_from(amount).ok()?;
+            rest = &rest[digits..];
+            let letters = rest.find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len());
+            match &rest[..letters] {
+                "s" | "sec" | "secs" | "second" | "seconds" => elapsed = elapsed.checked_add(&Duration::try_seconds(signed)?)?,
+                "m" | "min" | "mins" | "minute" | "minutes" => elapsed = elapsed.checked_add(&Duration::try_minutes(signed)?)?,
+                "h" | "hr" | "hrs" | "hour" | "hours" => elapsed = elapsed.checked_add(&Duration::try_hours(signed)?)?,
+                "d" | "day" | "days" => days = days.checked_add(amount)?,
+                "w" | "week" | "weeks" => days = days.checked_add(amount.checked_mul(7)?)?,
+                _ => return None,
+            }
+            rest = &rest[letters..];
+        }
+        let start = match days {
+            0 => now,
+            days => self.resolve(now.date_naive().checked_add_days(Days::new(days))?.and_time(now.time())),
+        };
+        start.checked_add_signed(elapsed)
+    }
+
+    /// A day and/or time of day; Err carries the word not understood
+    fn wall_clock<'a>(&self, now: DateTime<Tz>, words: &[&'a str]) -> Result<DateTime<Tz>, &'a str> {
+        let today = now.date_naive();
+        let mut date: Option<NaiveDate> = None;
+        let mut time: Option<NaiveTime> = None;
+        let mut default_hour = DEFAULT_HOUR;
+        // How far to move a result that is already past: a day for a bare
+        // time, a week for a weekday
+        let mut roll: Option<i64> = None;
+
+        let mut i = 0;
+        while i < words.len() {
+            let word = words[i];
+            // "9 am"
+            if let Some(meridiem @ ("am" | "pm")) = words.get(i + 1).copied() {
+                if let Some(clock) = parse_clock(&format!("{}{}", word, meridiem)) {
+                    time = Some(clock);
+                    i += 2;
+                    continue;
+                }
+            }
+            if let Some(clock) = parse_clock(word) {
+                time = Some(clock);
+            } else if word == "next" {
+                let weekday = words.get(i + 1).and_then(|w| w.parse::<Weekday>().ok()).ok_or(word)?;
+                date = Some(next_weekday(today, weekday, true));
+                i += 1;
+            } else if let Ok(weekday) = word.parse::<Weekday>() {
+                date = Some(next_weekday(today, weekday, false));
+                roll = Some(7);
+            } else {
+                date = Some(match word {
+                    "today" => today,
+                    "tonight" => {
+                        default_hour = TONIGHT_HOUR;
+                        today
+                    }
+                    "tomorrow" => today + Duration::days(1),
+                    _ => self.parse_date(word, today).ok_or(word)?,
+                });
+            }
+            i += 1;
+        }
+
+        if date.is_none() {
+            roll = Some(1);
+        }
+        let time = time.unwrap_or_else(|| NaiveTime::from_hms_opt(default_hour, 0, 0).unwrap_or_default());
+        let date = date.unwrap_or(today);
+        let mut result = self.resolve(date.and_time(time));
+        if let Some(days) = roll.filter(|_| result <= now) {
+            result = self.resolve((date + Duration::days(days)).and_time(time));
+        }
+        Ok(result)
+    }
+
+    /// Calendar dates: ISO, dotted (day first) and slashed (locale order),
+    /// the year defaulting to the next time the date comes round
+    fn parse_date(&self, word: &str, today: NaiveDate) -> Option<NaiveDate> {
+        if let Ok(date) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+            return Some(date);
+        }
+        let (parts, day_first): (Vec<&str>, bool) = if word.contains('.') {
+            (word.split('.').filter(|p| !p.is_empty()).collect(), true)
+        } else {
+            (word.split('/').collect(), !self.month_first())
+        };
+        let numbers: Vec<u32> = parts.iter().map(|p| p.parse().ok()).collect::<Option<_>>()?;
+        let (first, second, year) = match numbers.as_slice() {
+            [a, b] => (*a, *b, None),
+            [a, b, y] => (*a, *b, Some(if *y < 100 { 2000 + *y as i32 } else { *y as i32 })),
+            _ => return None,
+        };
+        let (day, month) = if day_first { (first, second) } else { (second, first) };
+        match year {
+            Some(year) => NaiveDate::from_ymd_opt(year, month, day),
+            None => {
+                let this_year = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+                if this_year >= today {
+                    Some(this_year)
+                } else {
+                    NaiveDate::from_ymd_opt(today.year() + 1, month, day)
+                }
+            }
+        }
+    }
+
+    /// A wall-clock time in the zone: skipped times move forward by the
+    /// gap, repeated times take the first occurrence
+    fn resolve(&self, local: NaiveDateTime) -> DateTime<Tz> {
+        match self.tz.from_local_datetime(&local) {
+            LocalResult::Single(t) => t,
+            LocalResult::Ambiguous(first, _) => first,
+            LocalResult::None => {
+                // Clocks jumped over it: read it with the offset from before
+                // the jump, which lands as far past the gap as it was into it
+                let before = self.tz.offset_from_utc_datetime(&(local - Duration::days(1))).fix();
+                match before.from_local_datetime(&local) {
+                    LocalResult::Single(t) => t.with_timezone(&self.tz),
+                    _ => self.tz.from_utc_datetime(&local),
+                }
+            }
+        }
+    }
+}
+
+/// Zone named by `$TZ`: "Europe/Berlin", ":Europe/Berlin" or a zoneinfo path
+fn zone_from_tz_var(value: &str) -> Option<Tz> {
+    let value = value.trim_start_matches(':');
+    let name = value.split_once("zoneinfo/").map(|(_, name)| name).unwrap_or(value);
+    name.parse().ok()
+}
+
+/// "9am", "9:30pm", "14:00", "14:00:30", "noon", "midnight"
+fn parse_clock(word: &str) -> Option<NaiveTime> {
+    match word {
+        "noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return NaiveTime::from_hms_opt(0, 0, 0),
+        _ => {}
+    }
+    let (clock, pm) = match (word.strip_suffix("am"), word.strip_suffix("pm")) {
+        (Some(clock), _) => (clock, Some(false)),
+        (_, Some(clock)) => (clock, Some(true)),
+        _ => (word, None),
+    };
+    let mut fields = clock.split(':');
+    let hour: u32 = fields.next()?.parse().ok()?;
+    let minute: u32 = match fields.next() {
+        Some(minute) if minute.len() == 2 => minute.parse().ok()?,
+        Some(_) => return None,
+        None if pm.is_some() => 0,
+        // A bare number is not a time
+        None => return None,
+    };
+    let second: u32 = match fields.next() {
+        Some(second) => second.parse().ok()?,
+        None => 0,
+    };
+    if fields.next().is_some() {
+        return None;
+    }
+    let hour = match pm {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(true) => hour % 12 + 12,
+        Some(false) => hour % 12,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, second)
+}
+
+/// The coming `weekday`: today counts unless `skip_today`
+fn next_weekday(today: NaiveDate, weekday: Weekday, skip_today: bool) -> NaiveDate {
+    let ahead = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    let ahead = if ahead == 0 && skip_today { 7 } else { ahead };
+    today + Duration::days(ahead as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn berlin() -> TimeContext {
+        TimeContext::new(chrono_tz::Europe::Berlin, Some("de_DE.UTF-8".to_string()))
+    }
+
+    fn utc(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text).unwrap().with_timezone(&Utc)
+    }
+
+    fn local(time: &TimeContext, text: &str, now: &str) -> String {
+        time.parse(text, utc(now)).unwrap().format("%Y-%m-%d %H:%M %Z").to_string()
+    }
+
+    #[test]
+    fn test_parse_natural_times() {
+        let time = berlin();
+        // Friday 2026-10-23, 20:30 in Berlin (CEST)
+        let now = "2026-10-23T18:30:00Z";
+        assert_eq!(local(&time, "tomorrow 9am", now), "2026-10-24 09:00 CEST");
+        assert_eq!(local(&time, "9:30 pm", now), "2026-10-23 21:30 CEST");
+        // Already past today: the next time the clock shows it
+        assert_eq!(local(&time, "14:00", now), "2026-10-24 14:00 CEST");
+        // Across the DST change on Sunday the wall-clock time stays put
+        assert_eq!(local(&time, "monday 9am", now), "2026-10-26 09:00 CET");
+        assert_eq!(local(&time, "next friday", now), "2026-10-30 09:00 CET");
+        assert_eq!(local(&time, "in 3 days", now), "2026-10-26 20:30 CET");
+        assert_eq!(local(&time, "in 1h30m", now), "2026-10-23 22:00 CEST");
+        assert_eq!(local(&time, "in an hour", now), "2026-10-23 21:30 CEST");
+        // de_DE reads day first
+        assert_eq!(local(&time, "01/11 noon", now), "2026-11-01 12:00 CET");
+        assert_eq!(local(&time, "2026-11-02 at 08:15", now), "2026-11-02 08:15 CET");
+        // 02:30 on 2027-03-28 does not exist; it moves past the gap
+        assert_eq!(local(&time, "28.03.2027 2:30am", now), "2027-03-28 03:30 CEST");
+        assert!(time.parse("whenever", utc(now)).is_err());
+        assert!(time.parse("25:00", utc(now)).is_err());
+        // Past the calendar's end: an error, not a panic
+        assert!(time.parse("in 99999999999 days", utc(now)).is_err());
+        assert!(time.parse("in 9999999999999999 weeks", utc(now)).is_err());
+        assert!(time.parse("in 9223372036854775807 seconds", utc(now)).is_err());
+        assert!(time.parse("in 99999999999999999999 hours", utc(now)).is_err());
+    }
+
+    #[test]
+    fn test_describe_mentions_zone_and_dst_change() {
+        let described = berlin().describe(utc("2026-10-23T18:30:00Z"));
+        assert_eq!(
+            described,
+            "Local time: Friday, 2026-10-23 20:30 CEST (UTC+02:00)\n\
+             Time zone: Europe/Berlin\n\
+             Locale: de_DE.UTF-8 (numeric dates are day/month)\n\
+             DST change: Sunday, 2026-10-25 03:00 local time, from CEST (UTC+02:00) to CET (UTC+01:00)"
+        );
+        assert_eq!(zone_from_tz_var(":/usr/share/zoneinfo/America/New_York"), Some(chrono_tz::America::New_York));
+    }
+}
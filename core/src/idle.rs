@@ -0,0 +1,151 @@
+//! Suspending idle sessions
+//!
+//! A session that nobody has used for `features.idle.suspend_after_mins`
+//! lets go of what it can rebuild: its memory store is closed, its language
+//! server is stopped and frontends trim their own buffers. Nothing has to be restored explicitly; released
+//! resources are reopened by whatever uses them next, so the next message
+//! only pays for reopening them.
+//!
+//! `IdleTimer` decides when a session is idle; `IdleResources` holds the
+//! things a session can release, registered by the factory that created it.
+
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+use crate::agent::memory::AgentMemoryManager;
+use crate::agent::tools::lsp::LspServer;
+use crate::config::IdleSettings;
+
+/// Something a session can release while idle
+pub trait Suspendable: Send + Sync {
+    /// Release what can be rebuilt later; true if anything was freed
+    fn suspend(&self) -> bool;
+}
+
+impl Suspendable for AgentMemoryManager {
+    fn suspend(&self) -> bool {
+        self.release_store()
+    }
+}
+
+impl Suspendable for LspServer {
+    fn suspend(&self) -> bool {
+        self.stop()
+    }
+}
+
+/// Resources of one session that are released together
+#[derive(Default)]
+pub struct IdleResources {
+    /// Held weakly so a finished session's resources are not kept alive
+    items: Mutex<Vec<(String, Weak<dyn Suspendable>)>>,
+}
+
+impl IdleResources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&self, name: &str, item: &Arc<dyn Suspendable>) {
+        self.items.lock().push((name.to_string(), Arc::downgrade(item)));
+    }
+
+    /// Release everything registered; returns the names of what was freed
+    pub fn suspend_all(&self) -> Vec<String> {
+        let mut items = self.items.lock();
+        items.retain(|(_, item)| item.strong_count() > 0);
+        items
+            .iter()
+            .filter(|(_, item)| item.upgrade().is_some_and(|item| item.suspend()))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+}
+
+/// Tracks activity and says when a session has been idle long enough
+#[derive(Debug, Clone)]
+pub struct IdleTimer {
+    /// None when suspension is turned off
+    after: Option<Duration>,
+    last_active: Instant,
+    suspended: bool,
+}
+
+impl IdleTimer {
+    pub fn new(settings: &IdleSettings) -> Self {
+        let after = (settings.suspend_after_mins > 0).then(|| Duration::from_secs(settings.suspend_after_mins * 60));
+        Self::with_timeout(after)
+    }
+
+    pub fn with_timeout(after: Option<Duration>) -> Self {
+        Self {
+            after,
+            last_active: Instant::now(),
+            suspended: false,
+        }
+    }
+
+    /// Record activity; true if the session was suspended until now
+    pub fn touch(&mut self, now: Instant) -> bool {
+        self.last_active = now;
+        std::mem::take(&mut self.suspended)
+    }
+
+    /// Whether the session should be suspended now
+    pub fn is_due(&self, now: Instant) -> bool {
+        !self.suspended && self.after.is_some_and(|after| now.duration_since(self.last_active) >= after)
+    }
+
+    pub fn set_suspended(&mut self) {
+        self.suspended = true;
+    }
+
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_timer_suspends_once_until_touched() {
+        let mut timer = IdleTimer::with_timeout(Some(Duration::from_secs(60)));
+        let start = Instant::now();
+        timer.touch(start);
+        assert!(!timer.is_due(start + Duration::from_secs(59)));
+        assert!(timer.is_due(start + Duration::from_secs(60)));
+
+        timer.set_suspended();
+        assert!(!timer.is_due(start + Duration::from_secs(600)));
+        assert!(timer.touch(start + Duration::from_secs(600)));
+        assert!(!timer.touch(start + Duration::from_secs(601)));
+
+        assert!(!IdleTimer::with_timeout(None).is_due(start + Duration::from_secs(86_400)));
+    }
+
+    struct Counter(AtomicUsize);
+
+    impl Suspendable for Counter {
+        fn suspend(&self) -> bool {
+            self.0.fetch_add(1, Ordering::SeqCst) == 0
+        }
+    }
+
+    #[test]
+    fn test_resources_skip_dropped_items() {
+        let resources = IdleResources::new();
+        let kept: Arc<dyn Suspendable> = Arc::new(Counter(AtomicUsize::new(0)));
+        resources.register("memory", &kept);
+        {
+            let dropped: Arc<dyn Suspendable> = Arc::new(Counter(AtomicUsize::new(0)));
+            resources.register("gone", &dropped);
+        }
+        assert_eq!(resources.suspend_all(), vec!["memory".to_string()]);
+        assert!(resources.suspend_all().is_empty());
+    }
+}
@@ -6,41 +6,38 @@
 
 #![deny(unsafe_code)]
 
-use std::sync::Mutex;
-
-/// Global log file handle for debug.log
-static DEBUG_LOG: Mutex<Option<std::fs::File>> = Mutex::new(None);
-
 /// Initialize debug.log file logging
 pub fn init_debug_log(path: Option<std::path::PathBuf>) -> std::io::Result<()> {
-    let log_path = path.unwrap_or_else(|| std::path::PathBuf::from("debug.log"));
-    let file = std::fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(log_path)?;
-    *DEBUG_LOG.lock().unwrap() = Some(file);
-    Ok(())
+    logging::init(path)
 }
 
 /// Write to debug.log if initialized
-pub fn write_to_debug_log(level: &str, message: &str) {
-    use std::io::Write;
-    if let Ok(mut guard) = DEBUG_LOG.lock() {
-        if let Some(ref mut file) = *guard {
-            let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-            let _ = writeln!(file, "[{}] [{}] {}", timestamp, level, message);
-            let _ = file.flush();
+///
+/// Once `observability::init` has installed the tracing pipeline, the
+/// message is emitted as a tracing event instead.
+pub fn write_to_debug_log(level: logging::LogLevel, message: &str) {
+    use logging::LogLevel;
+    if observability::is_enabled() {
+        match level {
+            LogLevel::Error => tracing::error!(target: "mylm", "{}", message),
+            LogLevel::Warn => tracing::warn!(target: "mylm", "{}", message),
+            LogLevel::Info => tracing::info!(target: "mylm", "{}", message),
+            LogLevel::Debug => tracing::debug!(target: "mylm", "{}", message),
+            LogLevel::Trace => tracing::trace!(target: "mylm", "{}", message),
         }
+        return;
     }
+    logging::write(level, message);
 }
 
-// Logging macros - write ONLY to debug.log (not stderr to avoid TUI pollution)
+// Logging macros - write ONLY to debug.log (not stderr to avoid TUI pollution).
+// Lines below the level configured for the calling module are not formatted.
 #[macro_export]
 macro_rules! info_log {
     ($($arg:tt)*) => {
-        {
+        if $crate::logging::enabled(module_path!(), $crate::logging::LogLevel::Info) {
             let msg = format!($($arg)*);
-            $crate::write_to_debug_log("INFO", &msg);
+            $crate::write_to_debug_log($crate::logging::LogLevel::Info, &msg);
         }
     };
 }
@@ -48,9 +45,9 @@ macro_rules! info_log {
 #[macro_export]
 macro_rules! error_log {
     ($($arg:tt)*) => {
-        {
+        if $crate::logging::enabled(module_path!(), $crate::logging::LogLevel::Error) {
             let msg = format!($($arg)*);
-            $crate::write_to_debug_log("ERROR", &msg);
+            $crate::write_to_debug_log($crate::logging::LogLevel::Error, &msg);
         }
     };
 }
@@ -58,9 +55,9 @@ macro_rules! error_log {
 #[macro_export]
 macro_rules! debug_log {
     ($($arg:tt)*) => {
-        {
+        if $crate::logging::enabled(module_path!(), $crate::logging::LogLevel::Debug) {
             let msg = format!($($arg)*);
-            $crate::write_to_debug_log("DEBUG", &msg);
+            $crate::write_to_debug_log($crate::logging::LogLevel::Debug, &msg);
         }
     };
 }
@@ -68,9 +65,9 @@ macro_rules! debug_log {
 #[macro_export]
 macro_rules! warn_log {
     ($($arg:tt)*) => {
-        {
+        if $crate::logging::enabled(module_path!(), $crate::logging::LogLevel::Warn) {
             let msg = format!($($arg)*);
-            $crate::write_to_debug_log("WARN", &msg);
+            $crate::write_to_debug_log($crate::logging::LogLevel::Warn, &msg);
         }
     };
 }
@@ -78,9 +75,9 @@ macro_rules! warn_log {
 #[macro_export]
 macro_rules! trace_log {
     ($($arg:tt)*) => {
-        {
+        if $crate::logging::enabled(module_path!(), $crate::logging::LogLevel::Trace) {
             let msg = format!($($arg)*);
-            $crate::write_to_debug_log("TRACE", &msg);
+            $crate::write_to_debug_log($crate::logging::LogLevel::Trace, &msg);
         }
     };
 }
@@ -98,8 +95,14 @@ pub mod provider;
 pub mod memory;
 pub mod scheduler;
 pub mod protocol;
+pub mod task;
 pub mod update;
 pub mod util;
+pub mod idle;
+pub mod watch;
+pub mod observability;
+pub mod logging;
+pub mod worktree;
 
 // TODO: Restore factory module or migrate to new architecture
 // pub mod factory;
@@ -0,0 +1,319 @@
+//! debug.log: level filtering and rotation
+//!
+//! The log macros (`info_log!` and friends) check `enabled` for their
+//! module before formatting, then `write` appends the line. The minimum
+//! level and per-module levels come from `features.log`, and the
+//! `MYLM_LOG` environment variable overrides them in `RUST_LOG` syntax:
+//!
+//! ```text
+//! MYLM_LOG=info,mylm_core::provider::chat=warn,mylm_core::agent=trace
+//! ```
+//!
+//! The file is rotated to `debug.log.1`, `debug.log.2`, ... once it passes
+//! `max_size_mb` or is older than `max_age_hours`; `keep` rotated files are
+//! kept. The tracing subscriber writes through `LogWriter`, so its output
+//! is rotated the same way.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::LogSettings;
+
+/// Environment variable overriding the configured levels
+pub const LOG_ENV: &str = "MYLM_LOG";
+
+/// Severity of a log line, least verbose first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Error => "ERROR",
+            LogLevel::Warn => "WARN",
+            LogLevel::Info => "INFO",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Trace => "TRACE",
+        }
+    }
+}
+
+impl std::str::FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "error" => Ok(LogLevel::Error),
+            "warn" | "warning" => Ok(LogLevel::Warn),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            "trace" => Ok(LogLevel::Trace),
+            other => Err(format!("unknown log level '{}'", other)),
+        }
+    }
+}
+
+/// Which lines to keep: a default level and levels for module prefixes
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFilter {
+    level: LogLevel,
+    modules: BTreeMap<String, LogLevel>,
+}
+
+impl LogFilter {
+    pub fn new(level: LogLevel, modules: BTreeMap<String, LogLevel>) -> Self {
+        Self { level, modules }
+    }
+
+    /// Apply `RUST_LOG`-style directives (`info,mylm_core::provider=trace`)
+    /// on top of this filter
+    pub fn with_directives(mut self, directives: &str) -> Result<Self, String> {
+        for directive in directives.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((module, level)) => {
+                    self.modules.insert(module.trim().to_string(), level.parse()?);
+                }
+                None => self.level = directive.parse()?,
+            }
+        }
+        Ok(self)
+    }
+
+    /// Whether a `level` line from `module` is kept; the longest matching
+    /// module prefix decides
+    pub fn enabled(&self, module: &str, level: LogLevel) -> bool {
+        let max = self
+            .modules
+            .iter()
+            .filter(|(prefix, _)| {
+                module == prefix.as_str()
+                    || (module.starts_with(prefix.as_str()) && module[prefix.len()..].starts_with("::"))
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self.level, |(_, level)| *level);
+        level <= max
+    }
+}
+
+/// The open log file and when to rotate it
+struct DebugLog {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    opened: SystemTime,
+    max_size: Option<u64>,
+    max_age: Option<Duration>,
+    keep: usize,
+}
+
+impl DebugLog {
+    fn open(path: PathBuf) -> std::io::Result<Self> {
+        let file = std::fs::OpenOptions::new().create(true).append(true).open(&path)?;
+        let metadata = file.metadata()?;
+        let opened = metadata.created().or_else(|_| metadata.modified()).unwrap_or_else(|_| SystemTime::now());
+        Ok(Self {
+            path,
+            size: metadata.len(),
+            file,
+            opened,
+            max_size: None,
+            max_age: None,
+            keep: 0,
+        })
+    }
+
+    fn configure(&mut self, settings: &LogSettings) {
+        self.max_size = (settings.max_size_mb > 0).then(|| settings.max_size_mb * 1024 * 1024);
+        self.max_age = (settings.max_age_hours > 0).then(|| Duration::from_secs(settings.max_age_hours * 3600));
+        self.keep = settings.keep;
+    }
+
+    fn due(&self, incoming: u64) -> bool {
+        let too_big = self.max_size.is_some_and(|max| self.size > 0 && self.size + incoming > max);
+        let too_old = self
+            .max_age
+            .is_some_and(|max| self.opened.elapsed().is_ok_and(|age| age >= max));
+        too_big || too_old
+    }
+
+    /// Shift `debug.log.N` to `N+1`, dropping the oldest, and start a new file
+    fn rotate(&mut self) -> std::io::Result<()> {
+        let rotated = |n: usize| {
+            let mut name = self.path.clone().into_os_string();
+            name.push(format!(".{}", n));
+            PathBuf::from(name)
+        };
+        if self.keep == 0 {
+            std::fs::remove_file(&self.path)?;
+        } else {
+            let _ = std::fs::remove_file(rotated(self.keep));
+            for n in (1..self.keep).rev() {
+                let _ = std::fs::rename(rotated(n), rotated(n + 1));
+            }
+            std::fs::rename(&self.path, rotated(1))?;
+        }
+        let (max_size, max_age, keep) = (self.max_size, self.max_age, self.keep);
+        *self = Self::open(self.path.clone())?;
+        self.opened = SystemTime::now();
+        (self.max_size, self.max_age, self.keep) = (max_size, max_age, keep);
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &str) {
+        self.write_bytes(format!("{}\n", line).as_bytes());
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        let incoming = bytes.len() as u64;
+        if self.due(incoming) {
+            if let Err(e) = self.rotate() {
+                let _ = writeln!(self.file, "[ROTATE] Could not rotate {}: {}", self.path.display(), e);
+            }
+        }
+        if self.file.write_all(bytes).is_ok() {
+            self.size += incoming;
+        }
+        let _ = self.file.flush();
+    }
+}
+
+static DEBUG_LOG: Mutex<Option<DebugLog>> = Mutex::new(None);
+/// None until configured: every line is kept
+static FILTER: RwLock<Option<LogFilter>> = RwLock::new(None);
+
+/// Open `path` (default `debug.log`) for the log macros
+///
+/// Until `configure` runs, every level is kept unless `MYLM_LOG` says
+/// otherwise.
+pub fn init(path: Option<PathBuf>) -> std::io::Result<()> {
+    let log = DebugLog::open(path.unwrap_or_else(|| PathBuf::from("debug.log")))?;
+    *DEBUG_LOG.lock().unwrap_or_else(|e| e.into_inner()) = Some(log);
+    if let Ok(directives) = std::env::var(LOG_ENV) {
+        if let Ok(filter) = LogFilter::new(LogLevel::Trace, BTreeMap::new()).with_directives(&directives) {
+            *FILTER.write().unwrap_or_else(|e| e.into_inner()) = Some(filter);
+        }
+    }
+    Ok(())
+}
+
+/// Apply `features.log`, with `MYLM_LOG` on top
+///
+/// An invalid `MYLM_LOG` is reported, and the configured levels apply.
+pub fn configure(settings: &LogSettings) -> Result<(), String> {
+    if let Some(ref mut log) = *DEBUG_LOG.lock().unwrap_or_else(|e| e.into_inner()) {
+        log.configure(settings);
+    }
+    let filter = LogFilter::new(settings.level, settings.modules.clone());
+    let (filter, result) = match std::env::var(LOG_ENV) {
+        Ok(directives) => match filter.clone().with_directives(&directives) {
+            Ok(filter) => (filter, Ok(())),
+            Err(e) => (filter, Err(format!("{}: {}", LOG_ENV, e))),
+        },
+        Err(_) => (filter, Ok(())),
+    };
+    *FILTER.write().unwrap_or_else(|e| e.into_inner()) = Some(filter);
+    result
+}
+
+/// Whether a `level` line from `module` would be logged
+pub fn enabled(module: &str, level: LogLevel) -> bool {
+    match *FILTER.read().unwrap_or_else(|e| e.into_inner()) {
+        Some(ref filter) => filter.enabled(module, level),
+        None => true,
+    }
+}
+
+/// Append a line to debug.log, if it is open
+pub fn write(level: LogLevel, message: &str) {
+    if let Ok(mut guard) = DEBUG_LOG.lock() {
+        if let Some(ref mut log) = *guard {
+            let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
+            log.write_line(&format!("[{}] [{}] {}", timestamp, level.as_str(), message));
+        }
+    }
+}
+
+/// Writes formatted tracing events into the open log file
+pub struct LogWriter;
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if let Some(ref mut log) = *DEBUG_LOG.lock().unwrap_or_else(|e| e.into_inner()) {
+            log.write_bytes(buf);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Path of the open log file
+pub fn path() -> Option<PathBuf> {
+    DEBUG_LOG
+        .lock()
+        .ok()
+        .and_then(|guard| guard.as_ref().map(|log| log.path.clone()))
+}
+
+/// Rotated files of `path`, newest first
+pub fn rotated_files(path: &Path) -> Vec<PathBuf> {
+    (1..)
+        .map(|n| {
+            let mut name = path.to_path_buf().into_os_string();
+            name.push(format!(".{}", n));
+            PathBuf::from(name)
+        })
+        .take_while(|file| file.exists())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_levels_and_size_rotation() {
+        let filter = LogFilter::new(LogLevel::Debug, BTreeMap::new())
+            .with_directives("info,mylm_core::provider::chat=warn,mylm_core::agent=trace")
+            .unwrap();
+        assert!(filter.enabled("mylm_core::memory", LogLevel::Info));
+        assert!(!filter.enabled("mylm_core::memory", LogLevel::Debug));
+        assert!(!filter.enabled("mylm_core::provider::chat", LogLevel::Info));
+        assert!(filter.enabled("mylm_core::agent::tools", LogLevel::Trace));
+        // A prefix matches whole path segments only
+        assert!(!filter.enabled("mylm_core::agentx", LogLevel::Trace));
+        assert!(LogFilter::new(LogLevel::Info, BTreeMap::new()).with_directives("a=loud").is_err());
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("debug.log");
+        let mut log = DebugLog::open(path.clone()).unwrap();
+        log.configure(&LogSettings {
+            max_size_mb: 1,
+            keep: 2,
+            ..Default::default()
+        });
+        let line = "x".repeat(400 * 1024);
+        for _ in 0..7 {
+            log.write_line(&line);
+        }
+        // 2 lines fit in 1 MB: 7 lines make 4 files, the oldest dropped
+        assert_eq!(rotated_files(&path).len(), 2);
+        assert!(std::fs::metadata(&path).unwrap().len() <= 1024 * 1024);
+        assert!(!dir.path().join("debug.log.3").exists());
+    }
+}
@@ -0,0 +1,261 @@
+//! Pluggable storage for long-term memory
+//!
+//! `MemoryBackend` is the interface the agent, tools and TUI use to read and
+//! write memories. Two implementations exist:
+//!
+//! - [`VectorStore`]: LanceDB files with FastEmbed vectors (default)
+//! - [`SqliteStore`]: a single SQLite database with an FTS5 index, WAL
+//!   journaling and transactional writes, safe to share between the daemon
+//!   and the TUI at the same time
+//!
+//! Select one with `memory.backend` in the config and open it with
+//! [`open_backend`].
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::memory::embedding::{create_embedder, EmbeddingSettings};
+use crate::memory::sqlite::SqliteStore;
+use crate::memory::store::{Memory, MemoryCategory, MemoryType, VectorStore};
+
+/// File name of the SQLite database inside the memory directory
+pub const SQLITE_DB_FILE: &str = "memory.sqlite3";
+
+/// Which storage engine backs long-term memory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryBackendKind {
+    /// LanceDB vector store with semantic search
+    #[default]
+    #[serde(alias = "lancedb")]
+    Lance,
+    /// SQLite with FTS5 full-text search
+    Sqlite,
+}
+
+impl std::fmt::Display for MemoryBackendKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MemoryBackendKind::Lance => write!(f, "lance"),
+            MemoryBackendKind::Sqlite => write!(f, "sqlite"),
+        }
+    }
+}
+
+/// Storage operations for long-term memory
+#[async_trait]
+pub trait MemoryBackend: Send + Sync {
+    /// Short name of the backend, for logs and status output
+    fn name(&self) -> &'static str;
+
+    /// Add a memory with an explicit id
+    ///
+    /// Backends may skip content they consider a duplicate.
+    #[allow(clippy::too_many_arguments)]
+    async fn add_memory_typed_with_id(
+        &self,
+        id: i64,
+        content: &str,
+        memory_type: MemoryType,
+        session_id: Option<String>,
+        metadata: Option<serde_json::Value>,
+        category_id: Option<String>,
+        summary: Option<String>,
+    ) -> Result<()>;
+
+    /// Add a memory with a time-based id
+    async fn add_memory_typed(
+        &self,
+        content: &str,
+        memory_type: MemoryType,
+        session_id: Option<String>,
+        metadata: Option<serde_json::Value>,
+        category_id: Option<String>,
+        summary: Option<String>,
+    ) -> Result<()> {
+        let id = chrono::Utc::now()
+            .timestamp_nanos_opt()
+            .unwrap_or_else(|| chrono::Utc::now().timestamp());
+        self.add_memory_typed_with_id(id, content, memory_type, session_id, metadata, category_id, summary)
+            .await
+    }
+
+    /// Add a plain user note
+    async fn add_memory(&self, content: &str) -> Result<()> {
+        self.add_memory_typed(content, MemoryType::UserNote, None, None, None, None)
+            .await
+    }
+
+    /// Search memories, best matches first
+    async fn search_memory(&self, query: &str, limit: usize) -> Result<Vec<Memory>>;
+
+    /// Search memories of a single type
+    async fn search_by_type(
+        &self,
+        query: &str,
+        memory_type: MemoryType,
+        limit: usize,
+    ) -> Result<Vec<Memory>> {
+        let all_results = self.search_memory(query, limit * 2).await?;
+        Ok(all_results
+            .into_iter()
+            .filter(|m| m.r#type == memory_type)
+            .take(limit)
+            .collect())
+    }
+
+    /// Memories ordered newest first, skipping `offset`
+    async fn get_recent_memories_with_offset(&self, limit: usize, offset: usize) -> Result<Vec<Memory>>;
+
+    /// Memories ordered newest first
+    async fn get_recent_memories(&self, limit: usize) -> Result<Vec<Memory>> {
+        self.get_recent_memories_with_offset(limit, 0).await
+    }
+
+    async fn get_memory_by_id(&self, id: i64) -> Result<Option<Memory>>;
+
+    async fn delete_memory(&self, id: i64) -> Result<()>;
+
+    /// Replace the content of an existing memory
+    async fn update_memory(&self, id: i64, content: &str) -> Result<()>;
+
+    async fn update_memory_category(&self, memory_id: i64, category_id: String) -> Result<()>;
+
+    /// Replace the metadata of an existing memory
+    async fn update_memory_metadata(&self, id: i64, metadata: Option<serde_json::Value>) -> Result<()>;
+
+    async fn count_memories(&self) -> Result<usize>;
+
+    /// Check and repair the underlying storage, returning a report
+    async fn repair_database(&self) -> Result<String>;
+
+    async fn get_all_categories(&self) -> Result<Vec<MemoryCategory>>;
+
+    async fn get_category_by_id(&self, id: &str) -> Result<Option<MemoryCategory>>;
+
+    async fn get_memories_by_category(&self, category_id: &str) -> Result<Vec<Memory>>;
+
+    /// Insert or replace a category
+    async fn update_category(&self, category: MemoryCategory) -> Result<()>;
+
+    /// Every memory, newest first, with embeddings where the backend keeps them
+    async fn export_memories(&self) -> Result<Vec<Memory>> {
+        let count = self.count_memories().await?;
+        self.get_recent_memories(count).await
+    }
+
+    /// Insert a memory verbatim, keeping its id and timestamp
+    ///
+    /// Skips deduplication. Returns false without writing if a memory with
+    /// the same id already exists.
+    async fn import_memory(&self, memory: Memory) -> Result<bool>;
+}
+
+#[async_trait]
+impl MemoryBackend for VectorStore {
+    fn name(&self) -> &'static str {
+        "lance"
+    }
+
+    async fn add_memory_typed_with_id(
+        &self,
+        id: i64,
+        content: &str,
+        memory_type: MemoryType,
+        session_id: Option<String>,
+        metadata: Option<serde_json::Value>,
+        category_id: Option<String>,
+        summary: Option<String>,
+    ) -> Result<()> {
+        VectorStore::add_memory_typed_with_id(self, id, content, memory_type, session_id, metadata, category_id, summary)
+            .await
+    }
+
+    async fn search_memory(&self, query: &str, limit: usize) -> Result<Vec<Memory>> {
+        VectorStore::search_memory(self, query, limit).await
+    }
+
+    async fn get_recent_memories_with_offset(&self, limit: usize, offset: usize) -> Result<Vec<Memory>> {
+        VectorStore::get_recent_memories_with_offset(self, limit, offset).await
+    }
+
+    async fn get_memory_by_id(&self, id: i64) -> Result<Option<Memory>> {
+        VectorStore::get_memory_by_id(self, id).await
+    }
+
+    async fn delete_memory(&self, id: i64) -> Result<()> {
+        VectorStore::delete_memory(self, id).await
+    }
+
+    async fn update_memory(&self, id: i64, content: &str) -> Result<()> {
+        VectorStore::update_memory(self, id, content).await
+    }
+
+    async fn update_memory_category(&self, memory_id: i64, category_id: String) -> Result<()> {
+        VectorStore::update_memory_category(self, memory_id, category_id).await
+    }
+
+    async fn update_memory_metadata(&self, id: i64, metadata: Option<serde_json::Value>) -> Result<()> {
+        VectorStore::update_memory_metadata(self, id, metadata).await
+    }
+
+    async fn count_memories(&self) -> Result<usize> {
+        VectorStore::count_memories(self).await
+    }
+
+    async fn repair_database(&self) -> Result<String> {
+        VectorStore::repair_database(self).await
+    }
+
+    async fn get_all_categories(&self) -> Result<Vec<MemoryCategory>> {
+        VectorStore::get_all_categories(self).await
+    }
+
+    async fn get_category_by_id(&self, id: &str) -> Result<Option<MemoryCategory>> {
+        VectorStore::get_category_by_id(self, id).await
+    }
+
+    async fn get_memories_by_category(&self, category_id: &str) -> Result<Vec<Memory>> {
+        VectorStore::get_memories_by_category(self, category_id).await
+    }
+
+    async fn update_category(&self, category: MemoryCategory) -> Result<()> {
+        VectorStore::update_category(self, category).await
+    }
+
+    async fn export_memories(&self) -> Result<Vec<Memory>> {
+        VectorStore::export_memories(self).await
+    }
+
+    async fn import_memory(&self, memory: Memory) -> Result<bool> {
+        VectorStore::import_memory(self, memory).await
+    }
+}
+
+/// Open the configured backend inside the memory directory `dir`; a Lance
+/// store embeds with the provider `embedding` selects
+pub async fn open_backend(
+    kind: MemoryBackendKind,
+    dir: &Path,
+    embedding: &EmbeddingSettings,
+) -> Result<Arc<dyn MemoryBackend>> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create memory directory {}", dir.display()))?;
+
+    let backend: Arc<dyn MemoryBackend> = match kind {
+        MemoryBackendKind::Lance => {
+            let path = dir
+                .to_str()
+                .ok_or_else(|| anyhow::anyhow!("Invalid storage path"))?;
+            Arc::new(VectorStore::with_embedder(path, create_embedder(embedding)?).await?)
+        }
+        MemoryBackendKind::Sqlite => Arc::new(SqliteStore::open(dir.join(SQLITE_DB_FILE)).await?),
+    };
+    info!("Opened {} memory backend at {}", backend.name(), dir.display());
+    Ok(backend)
+}
@@ -1,4 +1,5 @@
 use crate::provider::{LlmClient, chat::{ChatMessage, ChatRequest}};
+use crate::memory::backend::MemoryBackend;
 use crate::memory::store::MemoryCategory;
 use anyhow::{Result, Context};
 use std::sync::Arc;
@@ -6,11 +7,11 @@ use chrono::Utc;
 
 pub struct MemoryCategorizer {
     llm_client: Arc<LlmClient>,
-    store: Arc<crate::memory::store::VectorStore>,
+    store: Arc<dyn MemoryBackend>,
 }
 
 impl MemoryCategorizer {
-    pub fn new(llm_client: Arc<LlmClient>, store: Arc<crate::memory::store::VectorStore>) -> Self {
+    pub fn new(llm_client: Arc<LlmClient>, store: Arc<dyn MemoryBackend>) -> Self {
         Self { llm_client, store }
     }
 
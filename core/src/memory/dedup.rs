@@ -0,0 +1,216 @@
+//! Merging near-duplicate memories
+//!
+//! Repeated sessions store the same fact several times in slightly different
+//! words. `mylm memory dedupe` clusters memories of the same type whose
+//! embeddings are at least `threshold` similar, keeps the oldest of each
+//! cluster and deletes the rest. The kept memory lists what it absorbed under
+//! `merged_from` in its metadata (id, timestamp, session and content), so
+//! nothing is lost without a trace. A kept category such as `starred` on a
+//! duplicate moves to the keeper, so merging never drops a star.
+//!
+//! Memories stored without an embedding (the SQLite backend) are embedded
+//! with the configured provider first.
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use tracing::info;
+
+use crate::memory::backend::MemoryBackend;
+use crate::memory::embedding::{create_embedder, EmbeddingSettings};
+use crate::memory::store::Memory;
+
+/// Similarity at which two memories count as duplicates
+pub const DEFAULT_THRESHOLD: f32 = 0.92;
+
+/// Metadata key listing the memories merged into a memory
+pub const MERGED_FROM_KEY: &str = "merged_from";
+
+/// A memory and the near-duplicates to merge into it
+#[derive(Debug, Clone)]
+pub struct DuplicateGroup {
+    pub keeper: Memory,
+    /// Each duplicate with its similarity to the keeper
+    pub duplicates: Vec<(Memory, f32)>,
+}
+
+impl DuplicateGroup {
+    /// The keeper's metadata with the duplicates, and whatever they had
+    /// absorbed themselves, added to `merged_from`
+    pub fn merged_metadata(&self) -> Value {
+        let mut metadata = match self.keeper.metadata.clone() {
+            Some(Value::Object(map)) => map,
+            Some(other) => serde_json::Map::from_iter([("original".to_string(), other)]),
+            None => serde_json::Map::new(),
+        };
+        let mut merged: Vec<Value> = match metadata.remove(MERGED_FROM_KEY) {
+            Some(Value::Array(entries)) => entries,
+            _ => Vec::new(),
+        };
+        for (duplicate, _) in &self.duplicates {
+            merged.push(json!({
+                "id": duplicate.id,
+                "created_at": duplicate.created_at,
+                "session_id": duplicate.session_id,
+                "content": duplicate.content,
+            }));
+            if let Some(Value::Array(earlier)) = duplicate.metadata.as_ref().and_then(|m| m.get(MERGED_FROM_KEY)) {
+                merged.extend(earlier.iter().cloned());
+            }
+        }
+        metadata.insert(MERGED_FROM_KEY.to_string(), Value::Array(merged));
+        Value::Object(metadata)
+    }
+
+    /// A category from `keep` that a duplicate has and the keeper lacks
+    pub fn kept_category(&self, keep: &[String]) -> Option<&str> {
+        let is_kept = |category: Option<&str>| category.is_some_and(|c| keep.iter().any(|k| k == c));
+        if is_kept(self.keeper.category_id.as_deref()) {
+            return None;
+        }
+        self.duplicates
+            .iter()
+            .map(|(duplicate, _)| duplicate.category_id.as_deref())
+            .find(|category| is_kept(*category))
+            .flatten()
+    }
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+/// Group `memories` into clusters of near-duplicates, oldest first
+///
+/// Each memory joins the first older memory of its type it is at least
+/// `threshold` similar to. Memories without an embedding are left out;
+/// only clusters with duplicates are returned.
+pub fn find_duplicates(mut memories: Vec<Memory>, threshold: f32) -> Vec<DuplicateGroup> {
+    memories.retain(|m| m.embedding.is_some());
+    memories.sort_by_key(|m| (m.created_at, m.id));
+
+    let mut groups: Vec<DuplicateGroup> = Vec::new();
+    for memory in memories {
+        let embedding = memory.embedding.as_deref().unwrap_or_default();
+        let best = groups
+            .iter_mut()
+            .filter(|group| group.keeper.r#type == memory.r#type)
+            .map(|group| {
+                let similarity = cosine_similarity(group.keeper.embedding.as_deref().unwrap_or_default(), embedding);
+                (group, similarity)
+            })
+            .find(|(_, similarity)| *similarity >= threshold);
+        match best {
+            Some((group, similarity)) => group.duplicates.push((memory, similarity)),
+            None => groups.push(DuplicateGroup {
+                keeper: memory,
+                duplicates: Vec::new(),
+            }),
+        }
+    }
+    groups.retain(|group| !group.duplicates.is_empty());
+    groups
+}
+
+/// Merge the near-duplicates in `store`, returning the merged groups
+///
+/// With `dry_run` the groups are only found. The keeper's provenance, and a
+/// category from `keep_categories` held by a duplicate, are written before
+/// any duplicate is deleted.
+pub async fn dedupe(
+    store: &dyn MemoryBackend,
+    embedding: &EmbeddingSettings,
+    threshold: f32,
+    keep_categories: &[String],
+    dry_run: bool,
+) -> Result<Vec<DuplicateGroup>> {
+    let mut memories = store.export_memories().await?;
+    let missing: Vec<usize> = (0..memories.len()).filter(|&i| memories[i].embedding.is_none()).collect();
+    if !missing.is_empty() {
+        let embedder = create_embedder(embedding)?;
+        let texts = missing
+            .iter()
+            .map(|&i| memories[i].summary.clone().unwrap_or_else(|| memories[i].content.clone()))
+            .collect();
+        let vectors = embedder.embed(texts).await.context("Failed to embed memories")?;
+        for (i, vector) in missing.into_iter().zip(vectors) {
+            memories[i].embedding = Some(vector);
+        }
+    }
+
+    let mut groups = find_duplicates(memories, threshold);
+    if dry_run {
+        return Ok(groups);
+    }
+    for group in &mut groups {
+        store
+            .update_memory_metadata(group.keeper.id, Some(group.merged_metadata()))
+            .await?;
+        if let Some(category) = group.kept_category(keep_categories).map(str::to_string) {
+            store.update_memory_category(group.keeper.id, category.clone()).await?;
+            group.keeper.category_id = Some(category);
+        }
+        for (duplicate, _) in &group.duplicates {
+            store.delete_memory(duplicate.id).await?;
+        }
+    }
+    let merged: usize = groups.iter().map(|g| g.duplicates.len()).sum();
+    info!("Merged {} duplicate memories into {}", merged, groups.len());
+    Ok(groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::store::MemoryType;
+
+    fn memory(id: i64, r#type: MemoryType, embedding: Vec<f32>) -> Memory {
+        Memory {
+            id,
+            content: format!("memory {}", id),
+            summary: None,
+            created_at: id,
+            r#type,
+            session_id: Some(format!("session-{}", id)),
+            metadata: None,
+            category_id: None,
+            embedding: Some(embedding),
+        }
+    }
+
+    #[test]
+    fn test_find_and_merge_duplicates() {
+        let memories = vec![
+            memory(3, MemoryType::Decision, vec![0.99, 0.1, 0.0]),
+            memory(1, MemoryType::Decision, vec![1.0, 0.0, 0.0]),
+            memory(2, MemoryType::Decision, vec![0.0, 1.0, 0.0]),
+            memory(4, MemoryType::UserNote, vec![1.0, 0.0, 0.0]),
+            memory(5, MemoryType::Decision, vec![1.0, 0.01, 0.0]),
+        ];
+        let groups = find_duplicates(memories, DEFAULT_THRESHOLD);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].keeper.id, 1);
+        let ids: Vec<i64> = groups[0].duplicates.iter().map(|(m, _)| m.id).collect();
+        assert_eq!(ids, vec![3, 5]);
+
+        let mut group = groups[0].clone();
+        group.duplicates[0].0.metadata = Some(json!({ MERGED_FROM_KEY: [{ "id": 0 }] }));
+        let metadata = group.merged_metadata();
+        let merged = metadata[MERGED_FROM_KEY].as_array().unwrap();
+        let merged_ids: Vec<i64> = merged.iter().map(|m| m["id"].as_i64().unwrap()).collect();
+        assert_eq!(merged_ids, vec![3, 0, 5]);
+        assert_eq!(merged[0]["session_id"], "session-3");
+
+        let keep = vec!["starred".to_string()];
+        assert_eq!(group.kept_category(&keep), None);
+        group.duplicates[1].0.category_id = Some("starred".to_string());
+        assert_eq!(group.kept_category(&keep), Some("starred"));
+        group.keeper.category_id = Some("starred".to_string());
+        assert_eq!(group.kept_category(&keep), None);
+    }
+}
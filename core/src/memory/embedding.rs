@@ -0,0 +1,355 @@
+//! Embedding providers for semantic memory search
+//!
+//! The LanceDB store turns memories and queries into vectors through an
+//! `EmbeddingProvider`. Two are built in, selected with
+//! `features.memory_settings.embedding`:
+//!
+//! - `local` (default): BGE-Small-EN-v1.5 run in-process by FastEmbed; the
+//!   model is downloaded once to the cache directory, then works offline
+//! - `openai`: any OpenAI-compatible `/embeddings` endpoint
+//!
+//! ```toml
+//! [features.memory_settings.embedding]
+//! provider = "openai"
+//! base_url = "http://localhost:11434/v1"
+//! model = "nomic-embed-text"
+//! dimensions = 768
+//! api_key = "${EMBEDDINGS_KEY}"
+//! ```
+//!
+//! Remote models are asked for `dimensions` (default `EMBEDDING_DIMS`) and
+//! must return that size. Vectors from different models can't be compared,
+//! so each store records the provider and size it was built with
+//! (`EmbeddingStamp`) and refuses to open with another; export the
+//! memories, move the store away and import them to switch.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+/// Dimensions of the local model, and the default for remote ones
+pub const EMBEDDING_DIMS: usize = 384;
+
+/// File in a store directory recording what built its vectors
+const STAMP_FILE: &str = "embedding.json";
+
+/// Turns text into vectors for similarity search
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    /// Short description for logs, e.g. `local:bge-small-en-v1.5`
+    fn name(&self) -> String;
+
+    /// Size of every vector this provider returns
+    fn dimensions(&self) -> usize {
+        EMBEDDING_DIMS
+    }
+
+    /// One `dimensions()`-sized vector per text, in order
+    async fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>>;
+
+    /// Vector for a single text
+    async fn embed_one(&self, text: String) -> Result<Vec<f32>> {
+        self.embed(vec![text])
+            .await?
+            .into_iter()
+            .next()
+            .context("No embedding generated")
+    }
+}
+
+/// Which embedding provider memory uses
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingProviderKind {
+    /// FastEmbed model run in-process
+    #[default]
+    Local,
+    /// OpenAI-compatible `/embeddings` API
+    #[serde(rename = "openai")]
+    OpenAi,
+}
+
+impl std::fmt::Display for EmbeddingProviderKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmbeddingProviderKind::Local => write!(f, "local"),
+            EmbeddingProviderKind::OpenAi => write!(f, "openai"),
+        }
+    }
+}
+
+/// `features.memory_settings.embedding`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EmbeddingSettings {
+    #[serde(default)]
+    pub provider: EmbeddingProviderKind,
+    /// API base URL, e.g. `https://api.openai.com/v1` (openai only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// API key; may be a `${VAR}` or `secret://` reference (openai only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// Embedding model (openai: defaults to `text-embedding-3-small`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Vector size to request (openai: defaults to `EMBEDDING_DIMS`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<usize>,
+}
+
+/// The provider and vector size a store was built with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmbeddingStamp {
+    pub provider: String,
+    pub dimensions: usize,
+}
+
+impl EmbeddingStamp {
+    pub fn of(embedder: &dyn EmbeddingProvider) -> Self {
+        Self {
+            provider: embedder.name(),
+            dimensions: embedder.dimensions(),
+        }
+    }
+
+    /// The stamp of the store in `dir`, if it has one
+    pub fn read(dir: &Path) -> Result<Option<Self>> {
+        match std::fs::read_to_string(dir.join(STAMP_FILE)) {
+            Ok(text) => Ok(Some(serde_json::from_str(&text).context("Invalid embedding stamp")?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Fail unless the store in `dir` was built with this provider and
+    /// size; a store without a stamp gets this one
+    pub fn check(&self, dir: &Path) -> Result<()> {
+        match Self::read(dir)? {
+            Some(stored) if stored != *self => anyhow::bail!(
+                "The memory store at {} was built with {} ({} dimensions), but the configured embedding \
+                provider is {} ({} dimensions). Switch the provider back, or run `mylm memory export`, \
+                move the store away and `mylm memory import` with the new provider.",
+                dir.display(),
+                stored.provider,
+                stored.dimensions,
+                self.provider,
+                self.dimensions
+            ),
+            Some(_) => Ok(()),
+            None => {
+                std::fs::create_dir_all(dir)?;
+                std::fs::write(dir.join(STAMP_FILE), serde_json::to_string_pretty(self)?)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The provider `settings` select
+pub fn create_embedder(settings: &EmbeddingSettings) -> Result<Arc<dyn EmbeddingProvider>> {
+    Ok(match settings.provider {
+        EmbeddingProviderKind::Local => Arc::new(LocalEmbedder::new()?),
+        EmbeddingProviderKind::OpenAi => Arc::new(OpenAiEmbedder::from_settings(settings)?),
+    })
+}
+
+/// BGE-Small-EN-v1.5 run by FastEmbed
+pub struct LocalEmbedder {
+    model: Arc<Mutex<TextEmbedding>>,
+}
+
+impl LocalEmbedder {
+    /// Load the model, downloading it to the cache directory on first use
+    pub fn new() -> Result<Self> {
+        let cache_dir = dirs::cache_dir()
+            .context("Could not find cache directory")?
+            .join("mylm")
+            .join("models");
+        std::fs::create_dir_all(&cache_dir)?;
+        let model = TextEmbedding::try_new(InitOptions::new(EmbeddingModel::BGESmallENV15).with_cache_dir(cache_dir))
+            .context("Failed to initialize FastEmbed model")?;
+        Ok(Self {
+            model: Arc::new(Mutex::new(model)),
+        })
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for LocalEmbedder {
+    fn name(&self) -> String {
+        "local:bge-small-en-v1.5".to_string()
+    }
+
+    async fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+        let model = Arc::clone(&self.model);
+        // The model is CPU-bound, keep it off the async workers
+        tokio::task::spawn_blocking(move || {
+            let mut model = model.blocking_lock();
+            model.embed(texts, None)
+        })
+        .await
+        .context("Join error during embedding")?
+        .context("Embedding failed")
+    }
+}
+
+/// Default model of the openai provider
+const DEFAULT_OPENAI_MODEL: &str = "text-embedding-3-small";
+
+/// OpenAI-compatible `/embeddings` endpoint
+pub struct OpenAiEmbedder {
+    http: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    model: String,
+    dimensions: usize,
+}
+
+#[derive(Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+    dimensions: usize,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    #[serde(default)]
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+impl OpenAiEmbedder {
+    pub fn new(base_url: impl Into<String>, api_key: Option<String>, model: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            api_key,
+            model: model.into(),
+            dimensions: EMBEDDING_DIMS,
+        }
+    }
+
+    /// Ask for `dimensions`-sized vectors
+    pub fn with_dimensions(mut self, dimensions: usize) -> Self {
+        self.dimensions = dimensions;
+        self
+    }
+
+    pub fn from_settings(settings: &EmbeddingSettings) -> Result<Self> {
+        let base_url = settings
+            .base_url
+            .as_deref()
+            .context("The openai embedding provider needs a base_url")?;
+        let base_url = crate::config::env::interpolate(base_url)?;
+        let api_key = crate::config::secrets::resolve_key(settings.api_key.as_deref());
+        let model = settings.model.as_deref().unwrap_or(DEFAULT_OPENAI_MODEL);
+        let dimensions = settings.dimensions.unwrap_or(EMBEDDING_DIMS);
+        if dimensions == 0 {
+            anyhow::bail!("Embedding dimensions must be above 0");
+        }
+        Ok(Self::new(base_url, api_key, model).with_dimensions(dimensions))
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAiEmbedder {
+    fn name(&self) -> String {
+        format!("openai:{}", self.model)
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    async fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+        let mut request = self
+            .http
+            .post(format!("{}/embeddings", self.base_url))
+            .json(&EmbeddingRequest {
+                model: &self.model,
+                input: &texts,
+                dimensions: self.dimensions,
+            });
+        if let Some(ref key) = self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request.send().await.context("Embedding request failed")?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Embedding request failed with {}: {}", status, body.trim());
+        }
+        let body: EmbeddingResponse = response.json().await.context("Invalid embedding response")?;
+        vectors(body, texts.len(), self.dimensions)
+    }
+}
+
+/// Vectors of `response` in input order, checked for count and size
+fn vectors(mut response: EmbeddingResponse, expected: usize, dimensions: usize) -> Result<Vec<Vec<f32>>> {
+    if response.data.len() != expected {
+        anyhow::bail!("Expected {} embeddings, got {}", expected, response.data.len());
+    }
+    response.data.sort_by_key(|d| d.index);
+    response
+        .data
+        .into_iter()
+        .map(|d| match d.embedding.len() {
+            dims if dims == dimensions => Ok(d.embedding),
+            dims => anyhow::bail!(
+                "Embedding model returned {} dimensions, but {} were configured; set `dimensions = {}` \
+                under [features.memory_settings.embedding]",
+                dims,
+                dimensions,
+                dims
+            ),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_parse_and_response_is_checked() {
+        let settings: EmbeddingSettings =
+            toml::from_str("provider = \"openai\"\nbase_url = \"http://localhost:11434/v1/\"\nmodel = \"nomic\"\ndimensions = 768\n").unwrap();
+        assert_eq!(settings.provider, EmbeddingProviderKind::OpenAi);
+        let embedder = OpenAiEmbedder::from_settings(&settings).unwrap();
+        assert_eq!(embedder.base_url, "http://localhost:11434/v1");
+        assert_eq!(embedder.name(), "openai:nomic");
+        assert_eq!(embedder.dimensions(), 768);
+        assert!(OpenAiEmbedder::from_settings(&EmbeddingSettings {
+            provider: EmbeddingProviderKind::OpenAi,
+            ..Default::default()
+        })
+        .is_err());
+
+        let data = |index, dims| EmbeddingData { index, embedding: vec![index as f32; dims] };
+        let ordered = vectors(EmbeddingResponse { data: vec![data(1, 768), data(0, 768)] }, 2, 768).unwrap();
+        assert_eq!(ordered[0][0], 0.0);
+        assert_eq!(ordered[1][0], 1.0);
+        assert!(vectors(EmbeddingResponse { data: vec![data(0, 1536)] }, 1, EMBEDDING_DIMS).is_err());
+        assert!(vectors(EmbeddingResponse { data: vec![] }, 1, EMBEDDING_DIMS).is_err());
+
+        // A store keeps the provider it was built with
+        let dir = tempfile::TempDir::new().unwrap();
+        let nomic = EmbeddingStamp::of(&embedder);
+        nomic.check(dir.path()).unwrap();
+        nomic.check(dir.path()).unwrap();
+        let local = EmbeddingStamp { provider: "local:bge-small-en-v1.5".to_string(), dimensions: EMBEDDING_DIMS };
+        let err = local.check(dir.path()).unwrap_err().to_string();
+        assert!(err.contains("openai:nomic (768 dimensions)"), "{}", err);
+    }
+}
@@ -0,0 +1,323 @@
+//! User feedback on injected memories
+//!
+//! Every prompt records which stored memories were injected into it. The
+//! user can rate those as helpful or irrelevant (`/context` and `/rate` in
+//! the TUI). Ratings nudge the memory up or down in later retrievals, and
+//! memories that keep being rated irrelevant are suggested for pruning
+//! (`mylm memory prune`).
+//!
+//! Feedback lives in `memory_feedback.json` next to the user profile, so
+//! every memory manager in the process sees the same ratings. Memory ids are
+//! only unique within a store, so the file keeps one section per store.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::memory::retention::RetentionSettings;
+use crate::memory::store::Memory;
+
+/// Score change per net helpful rating
+const FEEDBACK_WEIGHT: f32 = 0.1;
+/// Largest boost a memory can earn
+const MAX_BOOST: f32 = 0.3;
+/// Largest demotion; enough to push a memory below a few fresh matches
+const MAX_DEMOTION: f32 = 0.5;
+/// Irrelevant ratings needed before a memory is suggested for pruning
+pub const PRUNE_MIN_IRRELEVANT: u32 = 3;
+/// Characters of content kept for each injected memory
+const PREVIEW_CHARS: usize = 120;
+
+/// A user's verdict on an injected memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryRating {
+    Helpful,
+    Irrelevant,
+}
+
+impl std::fmt::Display for MemoryRating {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Helpful => write!(f, "helpful"),
+            Self::Irrelevant => write!(f, "irrelevant"),
+        }
+    }
+}
+
+impl std::str::FromStr for MemoryRating {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "helpful" | "good" | "+" => Ok(Self::Helpful),
+            "irrelevant" | "bad" | "-" => Ok(Self::Irrelevant),
+            other => anyhow::bail!("Unknown rating '{}': use helpful or irrelevant", other),
+        }
+    }
+}
+
+/// Rating tally for one memory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedbackCounts {
+    pub helpful: u32,
+    pub irrelevant: u32,
+    /// Unix timestamp of the latest rating
+    pub last_rated: i64,
+}
+
+impl FeedbackCounts {
+    /// Retrieval score adjustment earned by the ratings
+    pub fn adjustment(&self) -> f32 {
+        let net = self.helpful as f32 - self.irrelevant as f32;
+        (net * FEEDBACK_WEIGHT).clamp(-MAX_DEMOTION, MAX_BOOST)
+    }
+
+    /// Rated irrelevant often, and far more often than helpful
+    pub fn suggests_pruning(&self) -> bool {
+        self.irrelevant >= PRUNE_MIN_IRRELEVANT && self.irrelevant > self.helpful * 2
+    }
+}
+
+/// A memory that was injected into the latest prompt
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InjectedMemory {
+    pub id: i64,
+    pub preview: String,
+}
+
+/// Ratings for every memory plus the latest injected set
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MemoryFeedback {
+    #[serde(default)]
+    pub ratings: HashMap<i64, FeedbackCounts>,
+    #[serde(default)]
+    pub last_injected: Vec<InjectedMemory>,
+}
+
+impl MemoryFeedback {
+    /// Default location, next to the user profile
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(dirs::data_dir()
+            .ok_or_else(|| anyhow::anyhow!("Could not find data directory"))?
+            .join("mylm")
+            .join("memory_feedback.json"))
+    }
+
+    /// Record a rating and return the new tally
+    pub fn rate(&mut self, id: i64, rating: MemoryRating) -> FeedbackCounts {
+        let counts = self.ratings.entry(id).or_default();
+        match rating {
+            MemoryRating::Helpful => counts.helpful += 1,
+            MemoryRating::Irrelevant => counts.irrelevant += 1,
+        }
+        counts.last_rated = chrono::Utc::now().timestamp();
+        *counts
+    }
+
+    pub fn counts(&self, id: i64) -> FeedbackCounts {
+        self.ratings.get(&id).copied().unwrap_or_default()
+    }
+
+    /// Remember which memories went into the latest prompt
+    pub fn set_last_injected(&mut self, memories: &[Memory]) {
+        self.last_injected = memories
+            .iter()
+            .map(|m| InjectedMemory {
+                id: m.id,
+                preview: m.content.chars().take(PREVIEW_CHARS).collect(),
+            })
+            .collect();
+    }
+
+    /// Reorder retrieval results by their rank, age and ratings, keeping
+    /// `limit`
+    ///
+    /// `memories` must be in the backend's relevance order; rank decays the
+    /// same way the store's reranker scores it. Age and ratings count as
+    /// `retention` scores them at `now`, so memories compaction would
+    /// archive soon also rank low.
+    pub fn rerank(&self, memories: Vec<Memory>, limit: usize, retention: &RetentionSettings, now: i64) -> Vec<Memory> {
+        if self.ratings.is_empty() && retention.half_life_days <= 0.0 {
+            let mut memories = memories;
+            memories.truncate(limit);
+            return memories;
+        }
+        let mut scored: Vec<(f32, Memory)> = memories
+            .into_iter()
+            .enumerate()
+            .map(|(rank, memory)| {
+                let score = 1.0 / (1.0 + rank as f32 * 0.1) + retention.score(&memory, &self.counts(memory.id), now);
+                (score, memory)
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        scored.truncate(limit);
+        scored.into_iter().map(|(_, memory)| memory).collect()
+    }
+
+    /// Ids of memories that ratings suggest removing, worst first
+    pub fn prune_candidates(&self) -> Vec<(i64, FeedbackCounts)> {
+        let mut candidates: Vec<(i64, FeedbackCounts)> = self
+            .ratings
+            .iter()
+            .filter(|(_, counts)| counts.suggests_pruning())
+            .map(|(id, counts)| (*id, *counts))
+            .collect();
+        candidates.sort_by_key(|(id, counts)| (std::cmp::Reverse(counts.irrelevant - counts.helpful), *id));
+        candidates
+    }
+
+    /// Drop the ratings of a deleted memory
+    pub fn forget(&mut self, id: i64) {
+        self.ratings.remove(&id);
+        self.last_injected.retain(|m| m.id != id);
+    }
+}
+
+/// On-disk layout: the feedback of each store, keyed by the store
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Stores {
+    #[serde(default)]
+    stores: HashMap<String, MemoryFeedback>,
+}
+
+/// The feedback of one store inside a shared feedback file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedbackFile {
+    path: PathBuf,
+    store: String,
+}
+
+impl FeedbackFile {
+    /// Section `store` (usually the store's directory) of the file at `path`
+    pub fn new(path: PathBuf, store: &str) -> Self {
+        Self {
+            path,
+            store: store.to_string(),
+        }
+    }
+
+    fn load_all(&self) -> Result<Stores> {
+        if !self.path.exists() {
+            return Ok(Stores::default());
+        }
+        let content = std::fs::read_to_string(&self.path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    fn save_all(&self, all: &Stores) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(all)?)?;
+        Ok(())
+    }
+
+    /// The store's feedback, or none if nothing was recorded for it
+    pub fn load(&self) -> Result<MemoryFeedback> {
+        Ok(self.load_all()?.stores.remove(&self.store).unwrap_or_default())
+    }
+
+    /// Load, change and save the store's feedback
+    ///
+    /// Failures are logged; feedback must never break retrieval.
+    pub(crate) fn update(&self, f: impl FnOnce(&mut MemoryFeedback)) {
+        let mut all = match self.load_all() {
+            Ok(all) => all,
+            Err(e) => {
+                warn!("Ignoring unreadable memory feedback at {}: {}", self.path.display(), e);
+                Stores::default()
+            }
+        };
+        f(all.stores.entry(self.store.clone()).or_default());
+        if let Err(e) = self.save_all(&all) {
+            warn!("Failed to save memory feedback to {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::store::MemoryType;
+
+    fn memory(id: i64) -> Memory {
+        Memory {
+            id,
+            content: format!("memory {}", id),
+            summary: None,
+            created_at: 0,
+            r#type: MemoryType::UserNote,
+            session_id: None,
+            metadata: None,
+            category_id: None,
+            embedding: None,
+        }
+    }
+
+    fn ids(memories: &[Memory]) -> Vec<i64> {
+        memories.iter().map(|m| m.id).collect()
+    }
+
+    #[test]
+    fn test_ratings_reorder_results() {
+        let mut feedback = MemoryFeedback::default();
+        let candidates = || (1..=4).map(memory).collect::<Vec<_>>();
+        let no_decay = RetentionSettings { half_life_days: 0.0, ..Default::default() };
+        assert_eq!(ids(&feedback.rerank(candidates(), 3, &no_decay, 0)), vec![1, 2, 3]);
+
+        feedback.rate(3, MemoryRating::Helpful);
+        feedback.rate(3, MemoryRating::Helpful);
+        feedback.rate(1, MemoryRating::Irrelevant);
+        feedback.rate(1, MemoryRating::Irrelevant);
+        feedback.rate(1, MemoryRating::Irrelevant);
+        assert_eq!(ids(&feedback.rerank(candidates(), 3, &no_decay, 0)), vec![3, 2, 4]);
+
+        // A year-old memory drops below a fresh one ranked just after it
+        let now = 365 * 86_400;
+        let fresh = Memory { created_at: now, ..memory(2) };
+        let ranked = MemoryFeedback::default().rerank(vec![memory(1), fresh], 2, &RetentionSettings::default(), now);
+        assert_eq!(ids(&ranked), vec![2, 1]);
+    }
+
+    #[test]
+    fn test_prune_candidates() {
+        let mut feedback = MemoryFeedback::default();
+        for _ in 0..3 {
+            feedback.rate(1, MemoryRating::Irrelevant);
+            feedback.rate(2, MemoryRating::Irrelevant);
+        }
+        feedback.rate(2, MemoryRating::Helpful);
+        feedback.rate(2, MemoryRating::Helpful);
+        feedback.rate(3, MemoryRating::Irrelevant);
+
+        let candidates: Vec<i64> = feedback.prune_candidates().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(candidates, vec![1]);
+    }
+
+    #[test]
+    fn test_feedback_is_kept_per_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("memory_feedback.json");
+        let global = FeedbackFile::new(path.clone(), "/data/memory");
+        let project = FeedbackFile::new(path.clone(), "/work/app/.mylm/memory");
+        global.update(|f| f.set_last_injected(&[memory(7)]));
+        global.update(|f| {
+            f.rate(7, MemoryRating::Helpful);
+        });
+        project.update(|f| {
+            f.rate(7, MemoryRating::Irrelevant);
+        });
+
+        let loaded = global.load().unwrap();
+        assert_eq!(loaded.last_injected[0].preview, "memory 7");
+        assert_eq!((loaded.counts(7).helpful, loaded.counts(7).irrelevant), (1, 0));
+        assert_eq!(project.load().unwrap().counts(7).irrelevant, 1);
+        assert!(project.load().unwrap().last_injected.is_empty());
+        assert_eq!("bad".parse::<MemoryRating>().unwrap(), MemoryRating::Irrelevant);
+    }
+}
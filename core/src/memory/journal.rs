@@ -1,6 +1,6 @@
 use std::fs::{self, OpenOptions};
 use std::io::{Write, BufRead, BufReader};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use chrono::Utc;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
@@ -73,6 +73,12 @@ impl Journal {
         Ok(journal)
     }
 
+    /// Today's journal in `dir` instead of the shared journals directory
+    pub fn in_dir(dir: &Path) -> Result<Self> {
+        let today = Utc::now().format("%Y-%m-%d").to_string();
+        Self::with_path(dir.join(format!("{}.md", today)))
+    }
+
     /// Create a journal at a specific path (for incognito mode or custom locations)
     pub fn with_path(path: PathBuf) -> Result<Self> {
         // Ensure parent directory exists
@@ -0,0 +1,171 @@
+//! Memory backend that can let go of its storage while unused
+//!
+//! A LanceDB store keeps its tables and the embedding model in memory for as
+//! long as it is open. `LazyBackend` wraps an opened backend so a session
+//! that has gone idle can `release` it; the next memory operation opens it
+//! again from disk. Operations already running keep their handle until they
+//! finish.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use crate::memory::backend::{open_backend, MemoryBackend, MemoryBackendKind};
+use crate::memory::embedding::EmbeddingSettings;
+use crate::memory::store::{Memory, MemoryCategory, MemoryType};
+
+pub struct LazyBackend {
+    kind: MemoryBackendKind,
+    dir: PathBuf,
+    embedding: EmbeddingSettings,
+    handle: Mutex<Option<Arc<dyn MemoryBackend>>>,
+}
+
+impl LazyBackend {
+    /// Open the backend in `dir` now, so configuration errors show up early
+    pub async fn open(kind: MemoryBackendKind, dir: &Path, embedding: &EmbeddingSettings) -> Result<Self> {
+        let backend = open_backend(kind, dir, embedding).await?;
+        Ok(Self {
+            kind,
+            dir: dir.to_path_buf(),
+            embedding: embedding.clone(),
+            handle: Mutex::new(Some(backend)),
+        })
+    }
+
+    /// Drop the open backend; false if it was not open or is being opened
+    pub fn release(&self) -> bool {
+        match self.handle.try_lock() {
+            Ok(mut handle) => handle.take().is_some(),
+            Err(_) => false,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.handle.try_lock().map(|handle| handle.is_some()).unwrap_or(true)
+    }
+
+    /// The open backend, reopening it after a release
+    async fn get(&self) -> Result<Arc<dyn MemoryBackend>> {
+        let mut handle = self.handle.lock().await;
+        if let Some(backend) = handle.as_ref() {
+            return Ok(Arc::clone(backend));
+        }
+        crate::info_log!("[MEMORY] Reopening {} backend at {}", self.kind, self.dir.display());
+        let backend = open_backend(self.kind, &self.dir, &self.embedding).await?;
+        *handle = Some(Arc::clone(&backend));
+        Ok(backend)
+    }
+}
+
+#[async_trait]
+impl MemoryBackend for LazyBackend {
+    fn name(&self) -> &'static str {
+        match self.kind {
+            MemoryBackendKind::Lance => "lance",
+            MemoryBackendKind::Sqlite => "sqlite",
+        }
+    }
+
+    async fn add_memory_typed_with_id(
+        &self,
+        id: i64,
+        content: &str,
+        memory_type: MemoryType,
+        session_id: Option<String>,
+        metadata: Option<serde_json::Value>,
+        category_id: Option<String>,
+        summary: Option<String>,
+    ) -> Result<()> {
+        self.get()
+            .await?
+            .add_memory_typed_with_id(id, content, memory_type, session_id, metadata, category_id, summary)
+            .await
+    }
+
+    async fn search_memory(&self, query: &str, limit: usize) -> Result<Vec<Memory>> {
+        self.get().await?.search_memory(query, limit).await
+    }
+
+    async fn search_by_type(&self, query: &str, memory_type: MemoryType, limit: usize) -> Result<Vec<Memory>> {
+        self.get().await?.search_by_type(query, memory_type, limit).await
+    }
+
+    async fn get_recent_memories_with_offset(&self, limit: usize, offset: usize) -> Result<Vec<Memory>> {
+        self.get().await?.get_recent_memories_with_offset(limit, offset).await
+    }
+
+    async fn get_memory_by_id(&self, id: i64) -> Result<Option<Memory>> {
+        self.get().await?.get_memory_by_id(id).await
+    }
+
+    async fn delete_memory(&self, id: i64) -> Result<()> {
+        self.get().await?.delete_memory(id).await
+    }
+
+    async fn update_memory(&self, id: i64, content: &str) -> Result<()> {
+        self.get().await?.update_memory(id, content).await
+    }
+
+    async fn update_memory_category(&self, memory_id: i64, category_id: String) -> Result<()> {
+        self.get().await?.update_memory_category(memory_id, category_id).await
+    }
+
+    async fn update_memory_metadata(&self, id: i64, metadata: Option<serde_json::Value>) -> Result<()> {
+        self.get().await?.update_memory_metadata(id, metadata).await
+    }
+
+    async fn count_memories(&self) -> Result<usize> {
+        self.get().await?.count_memories().await
+    }
+
+    async fn repair_database(&self) -> Result<String> {
+        self.get().await?.repair_database().await
+    }
+
+    async fn get_all_categories(&self) -> Result<Vec<MemoryCategory>> {
+        self.get().await?.get_all_categories().await
+    }
+
+    async fn get_category_by_id(&self, id: &str) -> Result<Option<MemoryCategory>> {
+        self.get().await?.get_category_by_id(id).await
+    }
+
+    async fn get_memories_by_category(&self, category_id: &str) -> Result<Vec<Memory>> {
+        self.get().await?.get_memories_by_category(category_id).await
+    }
+
+    async fn update_category(&self, category: MemoryCategory) -> Result<()> {
+        self.get().await?.update_category(category).await
+    }
+
+    async fn export_memories(&self) -> Result<Vec<Memory>> {
+        self.get().await?.export_memories().await
+    }
+
+    async fn import_memory(&self, memory: Memory) -> Result<bool> {
+        self.get().await?.import_memory(memory).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_released_backend_reopens_on_use() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = LazyBackend::open(MemoryBackendKind::Sqlite, dir.path(), &EmbeddingSettings::default()).await.unwrap();
+        backend.add_memory("prefers tabs over spaces").await.unwrap();
+
+        assert!(backend.release());
+        assert!(!backend.is_open());
+        assert!(!backend.release());
+
+        assert_eq!(backend.count_memories().await.unwrap(), 1);
+        assert!(backend.is_open());
+    }
+}
@@ -1,10 +1,24 @@
 pub mod store;
+pub mod embedding;
+pub mod backend;
+pub mod sqlite;
+pub mod lazy;
+pub mod transfer;
+pub mod feedback;
+pub mod dedup;
+pub mod retention;
 pub mod categorizer;
 pub mod graph;
 pub mod journal;
 pub mod scribe;
 
 pub use store::VectorStore;
+pub use embedding::{create_embedder, EmbeddingProvider, EmbeddingProviderKind, EmbeddingSettings, EmbeddingStamp, LocalEmbedder, OpenAiEmbedder, EMBEDDING_DIMS};
+pub use backend::{open_backend, MemoryBackend, MemoryBackendKind};
+pub use sqlite::SqliteStore;
+pub use lazy::LazyBackend;
 pub use categorizer::MemoryCategorizer;
 pub use journal::Journal;
+pub use feedback::{FeedbackCounts, FeedbackFile, MemoryFeedback, MemoryRating};
+pub use retention::RetentionSettings;
 pub use scribe::Scribe;
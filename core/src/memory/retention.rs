@@ -0,0 +1,274 @@
+//! Memory retention: decay, per-category TTL and archival
+//!
+//! Every memory has a relevance score that halves every `half_life_days`
+//! since it was stored or last rated, shifted by its feedback. A compaction
+//! pass moves memories whose score fell below `archive_below`, or whose
+//! category TTL ran out, to `archive.jsonl` in the memory directory and
+//! deletes them from the store, so retrieval no longer sees them. The pass
+//! runs from `mylm memory compact` and, when `enabled`, from `mylm server`.
+//!
+//! ```toml
+//! [features.memory_settings.retention]
+//! enabled = true
+//! half_life_days = 90
+//! archive_below = 0.1
+//! category_ttl_days = { scratch = 7, ops = 180 }
+//! ```
+//!
+//! The archive is a memory export, so `mylm memory import` brings it back.
+
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::memory::backend::MemoryBackend;
+use crate::memory::feedback::{FeedbackCounts, MemoryFeedback};
+use crate::memory::store::Memory;
+use crate::memory::transfer::{ExportRecord, EXPORT_VERSION};
+
+/// File name of the cold store inside the memory directory
+pub const ARCHIVE_FILE: &str = "archive.jsonl";
+
+const SECS_PER_DAY: f64 = 86_400.0;
+
+/// How long memories stay in the active store
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RetentionSettings {
+    /// Compact periodically while `mylm server` runs
+    #[serde(default)]
+    pub enabled: bool,
+    /// Days for a memory's score to halve; 0 turns decay off
+    #[serde(default = "default_half_life_days")]
+    pub half_life_days: f64,
+    /// Memories scoring below this are archived
+    #[serde(default = "default_archive_below")]
+    pub archive_below: f32,
+    /// Days memories of a category are kept, by category id
+    #[serde(default)]
+    pub category_ttl_days: BTreeMap<String, u64>,
+    /// Categories that are never archived
+    #[serde(default = "default_keep_categories")]
+    pub keep_categories: Vec<String>,
+    /// Hours between passes in the server
+    #[serde(default = "default_compact_interval_hours")]
+    pub compact_interval_hours: u64,
+}
+
+fn default_half_life_days() -> f64 { 90.0 }
+fn default_archive_below() -> f32 { 0.1 }
+fn default_keep_categories() -> Vec<String> { vec!["starred".to_string()] }
+fn default_compact_interval_hours() -> u64 { 24 }
+
+impl Default for RetentionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            half_life_days: default_half_life_days(),
+            archive_below: default_archive_below(),
+            category_ttl_days: BTreeMap::new(),
+            keep_categories: default_keep_categories(),
+            compact_interval_hours: default_compact_interval_hours(),
+        }
+    }
+}
+
+/// Why a memory leaves the active store
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArchiveReason {
+    /// Its category's TTL ran out
+    Expired,
+    /// Its score decayed below the threshold
+    Decayed(f32),
+}
+
+impl std::fmt::Display for ArchiveReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Expired => write!(f, "expired"),
+            Self::Decayed(score) => write!(f, "score {:.2}", score),
+        }
+    }
+}
+
+impl RetentionSettings {
+    /// Relevance of `memory` at `now`: 1.0 when fresh, halving every half
+    /// life since it was stored or last rated, plus its feedback adjustment
+    pub fn score(&self, memory: &Memory, counts: &FeedbackCounts, now: i64) -> f32 {
+        let since = memory.created_at.max(counts.last_rated);
+        let decay = if self.half_life_days > 0.0 {
+            let age_days = (now - since).max(0) as f64 / SECS_PER_DAY;
+            0.5f64.powf(age_days / self.half_life_days) as f32
+        } else {
+            1.0
+        };
+        decay + counts.adjustment()
+    }
+
+    /// Whether `memory` should be archived at `now`, and why
+    pub fn archive_reason(&self, memory: &Memory, counts: &FeedbackCounts, now: i64) -> Option<ArchiveReason> {
+        let category = memory.category_id.as_deref().unwrap_or_default();
+        if self.keep_categories.iter().any(|c| c == category) {
+            return None;
+        }
+        if let Some(days) = self.category_ttl_days.get(category) {
+            if now - memory.created_at >= (*days as f64 * SECS_PER_DAY) as i64 {
+                return Some(ArchiveReason::Expired);
+            }
+        }
+        let score = self.score(memory, counts, now);
+        (score < self.archive_below).then_some(ArchiveReason::Decayed(score))
+    }
+
+    /// Memories among `memories` to archive at `now`, oldest first
+    pub fn stale(&self, memories: Vec<Memory>, feedback: &MemoryFeedback, now: i64) -> Vec<(Memory, ArchiveReason)> {
+        let mut stale: Vec<(Memory, ArchiveReason)> = memories
+            .into_iter()
+            .filter_map(|memory| {
+                let reason = self.archive_reason(&memory, &feedback.counts(memory.id), now)?;
+                Some((memory, reason))
+            })
+            .collect();
+        stale.sort_by_key(|(memory, _)| memory.created_at);
+        stale
+    }
+}
+
+/// Archive of the memory store in `memory_dir`
+pub fn archive_path(memory_dir: &Path) -> PathBuf {
+    memory_dir.join(ARCHIVE_FILE)
+}
+
+/// Append `memories` to the archive at `path`, creating it with an export
+/// header if needed
+pub fn append_to_archive(path: &Path, memories: &[Memory]) -> Result<()> {
+    let is_new = !path.exists();
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open memory archive {}", path.display()))?;
+    let mut out = BufWriter::new(file);
+    let mut write = |record: &ExportRecord| -> Result<()> {
+        serde_json::to_writer(&mut out, record)?;
+        out.write_all(b"\n")?;
+        Ok(())
+    };
+    if is_new {
+        write(&ExportRecord::Header {
+            version: EXPORT_VERSION,
+            backend: "archive".to_string(),
+            exported_at: chrono::Utc::now().timestamp(),
+        })?;
+    }
+    for memory in memories {
+        write(&ExportRecord::Memory(memory.clone().into()))?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Memories in the archive at `path`, oldest archived first; empty if there
+/// is none
+pub fn read_archive(path: &Path) -> Result<Vec<Memory>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let reader = BufReader::new(File::open(path).with_context(|| format!("Failed to open {}", path.display()))?);
+    let mut memories = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<ExportRecord>(&line) {
+            Ok(ExportRecord::Memory(record)) => memories.push(record.into()),
+            Ok(_) => {}
+            Err(e) => warn!("Skipping line {} of memory archive: {}", index + 1, e),
+        }
+    }
+    Ok(memories)
+}
+
+/// Move the stale memories of `store` to the archive at `archive`
+///
+/// Returns what was, or with `dry_run` would be, archived. Memories are
+/// written to the archive before they are deleted, so a failed pass loses
+/// nothing.
+pub async fn compact(
+    store: &dyn MemoryBackend,
+    feedback: &MemoryFeedback,
+    settings: &RetentionSettings,
+    archive: &Path,
+    dry_run: bool,
+) -> Result<Vec<(Memory, ArchiveReason)>> {
+    let now = chrono::Utc::now().timestamp();
+    let stale = settings.stale(store.export_memories().await?, feedback, now);
+    if dry_run || stale.is_empty() {
+        return Ok(stale);
+    }
+    let memories: Vec<Memory> = stale.iter().map(|(memory, _)| memory.clone()).collect();
+    append_to_archive(archive, &memories)?;
+    for memory in &memories {
+        store.delete_memory(memory.id).await?;
+    }
+    info!("Archived {} stale memories to {}", memories.len(), archive.display());
+    Ok(stale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::feedback::MemoryRating;
+    use crate::memory::store::MemoryType;
+
+    const DAY: i64 = 86_400;
+
+    fn memory(id: i64, age_days: i64, category: Option<&str>, now: i64) -> Memory {
+        Memory {
+            id,
+            content: format!("memory {}", id),
+            summary: None,
+            created_at: now - age_days * DAY,
+            r#type: MemoryType::UserNote,
+            session_id: None,
+            metadata: None,
+            category_id: category.map(str::to_string),
+            embedding: None,
+        }
+    }
+
+    #[test]
+    fn test_decay_and_ttl() {
+        let now = 1_800_000_000;
+        let settings = RetentionSettings {
+            half_life_days: 30.0,
+            archive_below: 0.2,
+            category_ttl_days: BTreeMap::from([("scratch".to_string(), 7)]),
+            ..RetentionSettings::default()
+        };
+        let counts = FeedbackCounts::default();
+        assert!((settings.score(&memory(1, 30, None, now), &counts, now) - 0.5).abs() < 1e-4);
+
+        let mut feedback = MemoryFeedback::default();
+        let memories = vec![
+            memory(1, 10, None, now),
+            memory(2, 100, None, now),
+            memory(3, 8, Some("scratch"), now),
+            memory(4, 400, Some("starred"), now),
+            memory(5, 100, None, now),
+        ];
+        feedback.rate(5, MemoryRating::Helpful);
+        feedback.ratings.get_mut(&5).unwrap().last_rated = now - 40 * DAY;
+
+        let stale = settings.stale(memories, &feedback, now);
+        let ids: Vec<i64> = stale.iter().map(|(m, _)| m.id).collect();
+        assert_eq!(ids, vec![2, 3]);
+        assert_eq!(stale[1].1, ArchiveReason::Expired);
+        assert!(matches!(stale[0].1, ArchiveReason::Decayed(score) if score < 0.2));
+    }
+}
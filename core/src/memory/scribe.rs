@@ -31,7 +31,8 @@ use serde::{Deserialize, Serialize};
 use crate::provider::LlmClient;
 use crate::provider::chat::{ChatMessage, ChatRequest};
 use crate::memory::journal::{Journal, InteractionType};
-use crate::memory::store::{VectorStore, MemoryType};
+use crate::memory::backend::MemoryBackend;
+use crate::memory::store::MemoryType;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ConsolidationReport {
@@ -49,12 +50,12 @@ struct ExtractedFact {
 
 pub struct Scribe {
     journal: Arc<Mutex<Journal>>,
-    store: Arc<VectorStore>,
+    store: Arc<dyn MemoryBackend>,
     llm: Arc<LlmClient>,
 }
 
 impl Scribe {
-    pub fn new(journal: Arc<Mutex<Journal>>, store: Arc<VectorStore>, llm: Arc<LlmClient>) -> Self {
+    pub fn new(journal: Arc<Mutex<Journal>>, store: Arc<dyn MemoryBackend>, llm: Arc<LlmClient>) -> Self {
         Self {
             journal,
             store,
@@ -139,7 +140,7 @@ impl Scribe {
         self.journal.clone()
     }
 
-    pub fn store(&self) -> Arc<VectorStore> {
+    pub fn store(&self) -> Arc<dyn MemoryBackend> {
         self.store.clone()
     }
 
@@ -0,0 +1,599 @@
+//! SQLite memory backend
+//!
+//! Stores memories and categories in one SQLite database. Search goes through
+//! an FTS5 index kept in sync by triggers, so it stays fast as the store grows
+//! and needs no embedding model. The database runs in WAL mode with a busy
+//! timeout, which lets the daemon and the TUI open the same file at once.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use tokio::task;
+use tracing::info;
+
+use crate::memory::backend::MemoryBackend;
+use crate::memory::store::{rerank_memories, sanitize_memory_content, Memory, MemoryCategory, MemoryType};
+
+/// Current schema version, stored in `PRAGMA user_version`
+const SCHEMA_VERSION: i64 = 1;
+
+/// How long a writer waits for another process to release the database
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Maximum query terms passed to FTS5
+const MAX_QUERY_TERMS: usize = 32;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS memories (
+    id INTEGER PRIMARY KEY,
+    content TEXT NOT NULL,
+    summary TEXT,
+    created_at INTEGER NOT NULL,
+    type TEXT NOT NULL,
+    session_id TEXT,
+    metadata TEXT,
+    category_id TEXT
+);
+CREATE INDEX IF NOT EXISTS memories_created_at ON memories(created_at DESC);
+CREATE INDEX IF NOT EXISTS memories_category_id ON memories(category_id);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS memories_fts USING fts5(
+    content,
+    summary,
+    content = 'memories',
+    content_rowid = 'id',
+    tokenize = 'porter unicode61'
+);
+
+CREATE TRIGGER IF NOT EXISTS memories_ai AFTER INSERT ON memories BEGIN
+    INSERT INTO memories_fts(rowid, content, summary) VALUES (new.id, new.content, new.summary);
+END;
+CREATE TRIGGER IF NOT EXISTS memories_ad AFTER DELETE ON memories BEGIN
+    INSERT INTO memories_fts(memories_fts, rowid, content, summary) VALUES ('delete', old.id, old.content, old.summary);
+END;
+CREATE TRIGGER IF NOT EXISTS memories_au AFTER UPDATE OF content, summary ON memories BEGIN
+    INSERT INTO memories_fts(memories_fts, rowid, content, summary) VALUES ('delete', old.id, old.content, old.summary);
+    INSERT INTO memories_fts(rowid, content, summary) VALUES (new.id, new.content, new.summary);
+END;
+
+CREATE TABLE IF NOT EXISTS categories (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    summary TEXT NOT NULL,
+    last_updated INTEGER NOT NULL
+);
+";
+
+const MEMORY_COLUMNS: &str = "m.id, m.content, m.summary, m.created_at, m.type, m.session_id, m.metadata, m.category_id";
+
+/// Memory backend on a single SQLite database
+pub struct SqliteStore {
+    conn: Arc<Mutex<Connection>>,
+    path: Option<PathBuf>,
+}
+
+impl SqliteStore {
+    /// Open or create the database at `path`
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let db_path = path.clone();
+        let conn = task::spawn_blocking(move || -> Result<Connection> {
+            let conn = Connection::open(&db_path)
+                .with_context(|| format!("Failed to open SQLite database {}", db_path.display()))?;
+            Self::init(&conn)?;
+            Ok(conn)
+        })
+        .await
+        .context("Join error while opening SQLite database")??;
+
+        info!("SQLite memory store ready at {}", path.display());
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            path: Some(path),
+        })
+    }
+
+    /// Create a store that lives only in memory
+    pub fn in_memory() -> Result<Self> {
+        let conn = Connection::open_in_memory().context("Failed to open in-memory SQLite database")?;
+        Self::init(&conn)?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            path: None,
+        })
+    }
+
+    /// Database file, or None for an in-memory store
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    fn init(conn: &Connection) -> Result<()> {
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        // WAL lets readers in other processes proceed while one writer commits
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+
+        let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version > SCHEMA_VERSION {
+            anyhow::bail!(
+                "Memory database schema version {} is newer than supported version {}",
+                version,
+                SCHEMA_VERSION
+            );
+        }
+        conn.execute_batch(SCHEMA).context("Failed to create memory schema")?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        Ok(())
+    }
+
+    /// Run `f` against the connection on the blocking pool
+    async fn with_conn<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut Connection) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let conn = self.conn.clone();
+        task::spawn_blocking(move || {
+            let mut conn = conn.lock();
+            f(&mut conn)
+        })
+        .await
+        .context("Join error during SQLite call")?
+    }
+}
+
+/// Build an FTS5 query that matches any word of `query`
+///
+/// Each word is quoted so FTS5 operators and punctuation in user input are
+/// treated as plain text. Returns None if the query has no searchable words.
+fn fts_query(query: &str) -> Option<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for word in query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+    {
+        let term = format!("\"{}\"", word.to_lowercase());
+        if !terms.contains(&term) {
+            terms.push(term);
+        }
+        if terms.len() == MAX_QUERY_TERMS {
+            break;
+        }
+    }
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" OR "))
+    }
+}
+
+fn memory_from_row(row: &Row<'_>) -> rusqlite::Result<Memory> {
+    let r#type: String = row.get(4)?;
+    let metadata: Option<String> = row.get(6)?;
+    Ok(Memory {
+        id: row.get(0)?,
+        content: row.get(1)?,
+        summary: row.get(2)?,
+        created_at: row.get(3)?,
+        r#type: MemoryType::from(r#type.as_str()),
+        session_id: row.get(5)?,
+        metadata: metadata
+            .filter(|m| !m.is_empty())
+            .and_then(|m| serde_json::from_str(&m).ok()),
+        category_id: row.get(7)?,
+        embedding: None,
+    })
+}
+
+fn category_from_row(row: &Row<'_>) -> rusqlite::Result<MemoryCategory> {
+    Ok(MemoryCategory {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        summary: row.get(2)?,
+        last_updated: row.get(3)?,
+        embedding: None,
+    })
+}
+
+#[async_trait]
+impl MemoryBackend for SqliteStore {
+    fn name(&self) -> &'static str {
+        "sqlite"
+    }
+
+    /// Insert a memory, skipping it if identical content of the same type exists
+    async fn add_memory_typed_with_id(
+        &self,
+        id: i64,
+        content: &str,
+        memory_type: MemoryType,
+        session_id: Option<String>,
+        metadata: Option<serde_json::Value>,
+        category_id: Option<String>,
+        summary: Option<String>,
+    ) -> Result<()> {
+        let content = sanitize_memory_content(content);
+        let r#type = memory_type.to_string();
+        let metadata = metadata.map(|m| m.to_string());
+        let created_at = Utc::now().timestamp();
+
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            let existing: Option<i64> = tx
+                .query_row(
+                    "SELECT id FROM memories WHERE content = ?1 AND type = ?2 LIMIT 1",
+                    params![content, r#type],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if let Some(existing) = existing {
+                info!("[DEDUP] Skipping duplicate memory (matches {})", existing);
+                return Ok(());
+            }
+            tx.execute(
+                "INSERT INTO memories (id, content, summary, created_at, type, session_id, metadata, category_id)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![id, content, summary, created_at, r#type, session_id, metadata, category_id],
+            )
+            .context("Failed to add memory to SQLite")?;
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn search_memory(&self, query: &str, limit: usize) -> Result<Vec<Memory>> {
+        let Some(fts) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+        let candidate_limit = (limit * 3) as i64;
+        let candidates = self
+            .with_conn(move |conn| {
+                let mut stmt = conn.prepare_cached(&format!(
+                    "SELECT {} FROM memories_fts
+                     JOIN memories m ON m.id = memories_fts.rowid
+                     WHERE memories_fts MATCH ?1
+                     ORDER BY bm25(memories_fts)
+                     LIMIT ?2",
+                    MEMORY_COLUMNS
+                ))?;
+                let rows = stmt.query_map(params![fts, candidate_limit], memory_from_row)?;
+                Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+            })
+            .await
+            .context("Full-text search failed")?;
+
+        Ok(rerank_memories(query, candidates, limit))
+    }
+
+    async fn get_recent_memories_with_offset(&self, limit: usize, offset: usize) -> Result<Vec<Memory>> {
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare_cached(&format!(
+                "SELECT {} FROM memories m ORDER BY m.created_at DESC, m.id DESC LIMIT ?1 OFFSET ?2",
+                MEMORY_COLUMNS
+            ))?;
+            let rows = stmt.query_map(params![limit as i64, offset as i64], memory_from_row)?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })
+        .await
+    }
+
+    async fn get_memory_by_id(&self, id: i64) -> Result<Option<Memory>> {
+        self.with_conn(move |conn| {
+            Ok(conn
+                .query_row(
+                    &format!("SELECT {} FROM memories m WHERE m.id = ?1", MEMORY_COLUMNS),
+                    params![id],
+                    memory_from_row,
+                )
+                .optional()?)
+        })
+        .await
+    }
+
+    async fn delete_memory(&self, id: i64) -> Result<()> {
+        self.with_conn(move |conn| {
+            conn.execute("DELETE FROM memories WHERE id = ?1", params![id])
+                .context("Failed to delete memory")?;
+            Ok(())
+        })
+        .await?;
+        info!("Deleted memory with id: {}", id);
+        Ok(())
+    }
+
+    async fn update_memory(&self, id: i64, content: &str) -> Result<()> {
+        let content = content.to_string();
+        self.with_conn(move |conn| {
+            let changed = conn.execute(
+                "UPDATE memories SET content = ?1 WHERE id = ?2",
+                params![content, id],
+            )?;
+            if changed == 0 {
+                anyhow::bail!("Memory with id {} not found", id);
+            }
+            Ok(())
+        })
+        .await?;
+        info!("Updated memory with id: {}", id);
+        Ok(())
+    }
+
+    /// Set a memory's category; an empty id clears it
+    async fn update_memory_category(&self, memory_id: i64, category_id: String) -> Result<()> {
+        let category_id = Some(category_id).filter(|c| !c.is_empty());
+        self.with_conn(move |conn| {
+            conn.execute(
+                "UPDATE memories SET category_id = ?1 WHERE id = ?2",
+                params![category_id, memory_id],
+            )
+            .context("Failed to update memory category")?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn update_memory_metadata(&self, id: i64, metadata: Option<serde_json::Value>) -> Result<()> {
+        let metadata = metadata.map(|m| m.to_string());
+        self.with_conn(move |conn| {
+            let changed = conn.execute(
+                "UPDATE memories SET metadata = ?1 WHERE id = ?2",
+                params![metadata, id],
+            )?;
+            if changed == 0 {
+                anyhow::bail!("Memory with id {} not found", id);
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn count_memories(&self) -> Result<usize> {
+        self.with_conn(|conn| {
+            let count: i64 = conn.query_row("SELECT COUNT(*) FROM memories", [], |row| row.get(0))?;
+            Ok(count as usize)
+        })
+        .await
+    }
+
+    /// Run an integrity check and rebuild the full-text index
+    async fn repair_database(&self) -> Result<String> {
+        info!("Starting SQLite memory repair...");
+        let report = self
+            .with_conn(|conn| {
+                let mut report = String::new();
+                let integrity: String = conn.query_row("PRAGMA integrity_check", [], |row| row.get(0))?;
+                if integrity == "ok" {
+                    report.push_str("✅ Integrity check: OK\n");
+                } else {
+                    report.push_str(&format!("❌ Integrity check: {}\n", integrity));
+                }
+                match conn.execute("INSERT INTO memories_fts(memories_fts) VALUES ('rebuild')", []) {
+                    Ok(_) => report.push_str("✅ Full-text index: rebuilt\n"),
+                    Err(e) => report.push_str(&format!("❌ Full-text index: Failed - {}\n", e)),
+                }
+                Ok(report)
+            })
+            .await?;
+        info!("SQLite memory repair completed:\n{}", report);
+        Ok(report)
+    }
+
+    async fn get_all_categories(&self) -> Result<Vec<MemoryCategory>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT id, name, summary, last_updated FROM categories ORDER BY name",
+            )?;
+            let rows = stmt.query_map([], category_from_row)?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })
+        .await
+    }
+
+    async fn get_category_by_id(&self, id: &str) -> Result<Option<MemoryCategory>> {
+        let id = id.to_string();
+        self.with_conn(move |conn| {
+            Ok(conn
+                .query_row(
+                    "SELECT id, name, summary, last_updated FROM categories WHERE id = ?1",
+                    params![id],
+                    category_from_row,
+                )
+                .optional()?)
+        })
+        .await
+    }
+
+    async fn get_memories_by_category(&self, category_id: &str) -> Result<Vec<Memory>> {
+        let category_id = category_id.to_string();
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare_cached(&format!(
+                "SELECT {} FROM memories m WHERE m.category_id = ?1 ORDER BY m.created_at DESC",
+                MEMORY_COLUMNS
+            ))?;
+            let rows = stmt.query_map(params![category_id], memory_from_row)?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })
+        .await
+    }
+
+    async fn update_category(&self, category: MemoryCategory) -> Result<()> {
+        self.with_conn(move |conn| {
+            conn.execute(
+                "INSERT INTO categories (id, name, summary, last_updated) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(id) DO UPDATE SET
+                     name = excluded.name,
+                     summary = excluded.summary,
+                     last_updated = excluded.last_updated",
+                params![category.id, category.name, category.summary, category.last_updated],
+            )
+            .context("Failed to update category")?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn export_memories(&self) -> Result<Vec<Memory>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM memories m ORDER BY m.created_at DESC, m.id DESC",
+                MEMORY_COLUMNS
+            ))?;
+            let rows = stmt.query_map([], memory_from_row)?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })
+        .await
+    }
+
+    /// Insert a memory as-is; embeddings are dropped since search uses FTS5
+    async fn import_memory(&self, memory: Memory) -> Result<bool> {
+        let r#type = memory.r#type.to_string();
+        let metadata = memory.metadata.map(|m| m.to_string());
+        let category_id = memory.category_id.filter(|c| !c.is_empty());
+        self.with_conn(move |conn| {
+            let inserted = conn
+                .execute(
+                    "INSERT OR IGNORE INTO memories (id, content, summary, created_at, type, session_id, metadata, category_id)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![
+                        memory.id,
+                        memory.content,
+                        memory.summary,
+                        memory.created_at,
+                        r#type,
+                        memory.session_id,
+                        metadata,
+                        category_id
+                    ],
+                )
+                .context("Failed to import memory into SQLite")?;
+            Ok(inserted > 0)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn add(store: &SqliteStore, id: i64, content: &str, memory_type: MemoryType) {
+        store
+            .add_memory_typed_with_id(id, content, memory_type, None, None, None, None)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_fts_query_quotes_terms() {
+        assert_eq!(fts_query("cargo build"), Some("\"cargo\" OR \"build\"".to_string()));
+        assert_eq!(fts_query("NOT \"x\" AND y*"), Some("\"not\" OR \"x\" OR \"and\" OR \"y\"".to_string()));
+        assert_eq!(fts_query("  ?!  "), None);
+    }
+
+    #[tokio::test]
+    async fn test_search_uses_full_text_index() {
+        let store = SqliteStore::in_memory().unwrap();
+        add(&store, 1, "The deploy script lives in scripts/deploy.sh", MemoryType::Discovery).await;
+        add(&store, 2, "User prefers tabs over spaces", MemoryType::UserNote).await;
+        add(&store, 3, "Fixed the deploy failure by pinning openssl", MemoryType::Bugfix).await;
+
+        let results = store.search_memory("deploy", 10).await.unwrap();
+        let ids: Vec<i64> = results.iter().map(|m| m.id).collect();
+        assert_eq!(results.len(), 2);
+        assert!(ids.contains(&1) && ids.contains(&3));
+
+        assert!(store.search_memory("kubernetes", 10).await.unwrap().is_empty());
+        assert!(store.search_memory("\"unbalanced", 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_index_follows_update_and_delete() {
+        let store = SqliteStore::in_memory().unwrap();
+        add(&store, 1, "postgres runs on port 5432", MemoryType::Discovery).await;
+
+        store.update_memory(1, "mysql runs on port 3306").await.unwrap();
+        assert!(store.search_memory("postgres", 5).await.unwrap().is_empty());
+        assert_eq!(store.search_memory("mysql", 5).await.unwrap().len(), 1);
+
+        store.delete_memory(1).await.unwrap();
+        assert!(store.search_memory("mysql", 5).await.unwrap().is_empty());
+        assert_eq!(store.count_memories().await.unwrap(), 0);
+        assert!(store.update_memory(1, "gone").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_are_skipped() {
+        let store = SqliteStore::in_memory().unwrap();
+        add(&store, 1, "same fact", MemoryType::Discovery).await;
+        add(&store, 2, "same fact", MemoryType::Discovery).await;
+        add(&store, 3, "same fact", MemoryType::Decision).await;
+        assert_eq!(store.count_memories().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_recent_memories_paginate() {
+        let store = SqliteStore::in_memory().unwrap();
+        for id in 1..=5 {
+            add(&store, id, &format!("note {}", id), MemoryType::UserNote).await;
+        }
+        let page: Vec<i64> = store
+            .get_recent_memories_with_offset(2, 1)
+            .await
+            .unwrap()
+            .iter()
+            .map(|m| m.id)
+            .collect();
+        assert_eq!(page, vec![4, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_categories_upsert() {
+        let store = SqliteStore::in_memory().unwrap();
+        let mut category = MemoryCategory {
+            id: "infra".to_string(),
+            name: "Infrastructure".to_string(),
+            summary: "Servers".to_string(),
+            last_updated: 1,
+            embedding: None,
+        };
+        store.update_category(category.clone()).await.unwrap();
+        category.summary = "Servers and networks".to_string();
+        store.update_category(category).await.unwrap();
+
+        let all = store.get_all_categories().await.unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].summary, "Servers and networks");
+
+        add(&store, 1, "nginx config", MemoryType::Discovery).await;
+        store.update_memory_category(1, "infra".to_string()).await.unwrap();
+        assert_eq!(store.get_memories_by_category("infra").await.unwrap().len(), 1);
+        store.update_memory_category(1, String::new()).await.unwrap();
+        assert_eq!(store.get_memory_by_id(1).await.unwrap().unwrap().category_id, None);
+    }
+
+    #[tokio::test]
+    async fn test_two_handles_share_one_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("memory.sqlite3");
+        let daemon = SqliteStore::open(&path).await.unwrap();
+        let tui = SqliteStore::open(&path).await.unwrap();
+
+        add(&daemon, 1, "written by the daemon", MemoryType::UserNote).await;
+        assert_eq!(tui.search_memory("daemon", 5).await.unwrap().len(), 1);
+
+        let writes = (10..20).map(|id| {
+            let store = if id % 2 == 0 { &daemon } else { &tui };
+            async move { add(store, id, &format!("concurrent note {}", id), MemoryType::UserNote).await }
+        });
+        futures::future::join_all(writes).await;
+        assert_eq!(daemon.count_memories().await.unwrap(), 11);
+    }
+}
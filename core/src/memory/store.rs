@@ -1,16 +1,13 @@
 use std::sync::Arc;
 use std::collections::HashMap;
-use tokio::sync::Mutex;
 use anyhow::{Context, Result};
 use arrow_array::{RecordBatch, RecordBatchIterator, StringArray, Float32Array, Int64Array, FixedSizeListArray, Array, ArrayRef, new_null_array};
 use arrow_schema::{DataType, Field, Schema};
 use chrono::Utc;
-use fastembed::{TextEmbedding, InitOptions, EmbeddingModel};
 use lancedb::query::{ExecutableQuery, QueryBase};
 use lancedb::{connect, Connection, Table};
-
+use super::embedding::{EmbeddingProvider, EmbeddingStamp, LocalEmbedder};
 use serde::{Deserialize, Serialize};
-use tokio::task;
 use futures::TryStreamExt;
 use tracing::{info, warn, error};
 
@@ -231,6 +228,53 @@ fn importance_score(memory: &Memory) -> f32 {
     (base + boost).min(1.0)
 }
 
+/// Re-rank search candidates with hybrid scoring
+/// 
+/// `candidates` must be ordered by the backend's own relevance (vector
+/// distance, full-text rank). That order becomes the primary signal and is
+/// blended with lexical overlap, recency and importance.
+pub(crate) fn rerank_memories(query: &str, candidates: Vec<Memory>, limit: usize) -> Vec<Memory> {
+    if candidates.is_empty() {
+        return Vec::new();
+    }
+    
+    // Score and re-rank candidates
+    let mut scored: Vec<ScoredMemory> = candidates
+        .into_iter()
+        .enumerate()
+        .map(|(rank, memory)| {
+            // Semantic score: 1.0 for first, decaying by rank
+            let semantic_score = 1.0 / (1.0 + rank as f32 * 0.1);
+            
+            // Lexical score: word match ratio
+            let lexical_score = lexical_score(query, &memory.content);
+            
+            // Recency score: newer is better
+            let recency_score = recency_score(memory.created_at);
+            
+            // Importance score: based on memory type and content
+            let importance_score = importance_score(&memory);
+            
+            // Combined score with weights
+            let final_score = 
+                semantic_score * 0.50 +
+                lexical_score * 0.25 +
+                recency_score * 0.15 +
+                importance_score * 0.10;
+            
+            ScoredMemory { memory, score: final_score }
+        })
+        .collect();
+    
+    // Sort by score descending
+    scored.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    
+    // Take top N
+    scored.truncate(limit);
+    
+    scored.into_iter().map(|s| s.memory).collect()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryCategory {
     pub id: String,
@@ -241,32 +285,53 @@ pub struct MemoryCategory {
     pub embedding: Option<Vec<f32>>,
 }
 
+/// Vector size of an existing memories table
+async fn stored_dimensions(conn: &Connection) -> Option<usize> {
+    let schema = conn.open_table("memories").execute().await.ok()?.schema().await.ok()?;
+    match schema.field_with_name("embedding").ok()?.data_type() {
+        DataType::FixedSizeList(_, size) => Some(*size as usize),
+        _ => None,
+    }
+}
+
 pub struct VectorStore {
     conn: Connection,
-    embedding_model: Arc<Mutex<TextEmbedding>>,
+    embedder: Arc<dyn EmbeddingProvider>,
 }
 
 impl VectorStore {
+    /// Open the store at `path` with the local embedding model
     pub async fn new(path: &str) -> Result<Self> {
+        Self::with_embedder(path, Arc::new(LocalEmbedder::new()?)).await
+    }
+
+    /// Open the store at `path`, embedding text with `embedder`
+    ///
+    /// Fails if the store was built with a different embedding provider.
+    pub async fn with_embedder(path: &str, embedder: Arc<dyn EmbeddingProvider>) -> Result<Self> {
         let conn = connect(path).execute().await.context("Failed to connect to LanceDB")?;
-        
-        let cache_dir = dirs::cache_dir()
-            .context("Could not find cache directory")?
-            .join("mylm")
-            .join("models");
-        std::fs::create_dir_all(&cache_dir)?;
-
-        // Initialize embedding model
-        // We use BGE-Small-EN-v1.5 by default (384 dims)
-        let embedding_model = TextEmbedding::try_new(
-            InitOptions::new(EmbeddingModel::BGESmallENV15)
-                .with_cache_dir(cache_dir)
-        ).context("Failed to initialize FastEmbed model")?;
-
-        Ok(Self {
-            conn,
-            embedding_model: Arc::new(Mutex::new(embedding_model)),
-        })
+        let stamp = EmbeddingStamp::of(embedder.as_ref());
+        let dir = std::path::Path::new(path);
+        if EmbeddingStamp::read(dir)?.is_none() {
+            // Stores from before stamps only say how long their vectors are
+            if let Some(dims) = stored_dimensions(&conn).await.filter(|d| *d != stamp.dimensions) {
+                anyhow::bail!(
+                    "The memory store at {} holds {}-dimension embeddings, but {} makes {}. Run \
+                    `mylm memory export`, move the store away and `mylm memory import` with the new provider.",
+                    path,
+                    dims,
+                    stamp.provider,
+                    stamp.dimensions
+                );
+            }
+        }
+        stamp.check(dir)?;
+        info!("Memory embeddings from {}", embedder.name());
+        Ok(Self { conn, embedder })
+    }
+
+    fn dimensions(&self) -> i32 {
+        self.embedder.dimensions() as i32
     }
 
     fn get_memory_schema(&self) -> Arc<Schema> {
@@ -275,7 +340,7 @@ impl VectorStore {
             Field::new("content", DataType::Utf8, false),
             Field::new("summary", DataType::Utf8, true),
             Field::new("created_at", DataType::Int64, false),
-            Field::new("embedding", DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), 384), false),
+            Field::new("embedding", DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), self.dimensions()), false),
             Field::new("type", DataType::Utf8, false),
             Field::new("session_id", DataType::Utf8, true),
             Field::new("metadata", DataType::Utf8, true),
@@ -290,7 +355,7 @@ impl VectorStore {
             Field::new("name", DataType::Utf8, false),
             Field::new("summary", DataType::Utf8, false),
             Field::new("last_updated", DataType::Int64, false),
-            Field::new("embedding", DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), 384), false),
+            Field::new("embedding", DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), self.dimensions()), false),
         ]))
     }
 
@@ -582,38 +647,45 @@ impl VectorStore {
         // Sanitize content to remove WAF-triggering patterns
         let sanitized_content = sanitize_memory_content(content);
         
-        let model = self.embedding_model.clone();
         // If summary is provided, use it for embedding. Otherwise use sanitized content.
         let text = summary.clone().unwrap_or_else(|| sanitized_content.clone());
         
-        // Run embedding in blocking thread
-        let embeddings = task::spawn_blocking(move || {
-            let mut model = model.blocking_lock();
-            model.embed(vec![text], None)
-        }).await.context("Join error during embedding")?
-        .context("Embedding failed")?;
+        let embedding = self.embedder.embed_one(text).await?;
 
-        let embedding = embeddings.first().context("No embedding generated")?.clone();
-        let created_at = Utc::now().timestamp();
+        let memory = Memory {
+            id,
+            content: sanitized_content,
+            summary,
+            created_at: Utc::now().timestamp(),
+            r#type: memory_type,
+            session_id,
+            metadata,
+            category_id,
+            embedding: None,
+        };
+        self.insert_memory_row(memory, embedding).await
+    }
 
+    /// Append one memory row with a precomputed embedding
+    async fn insert_memory_row(&self, memory: Memory, embedding: Vec<f32>) -> Result<()> {
         let schema = self.get_memory_schema();
         
-        let id_array = Int64Array::from(vec![id]);
-        let content_array = StringArray::from(vec![sanitized_content]);
-        let summary_array = StringArray::from(vec![summary]);
-        let created_at_array = Int64Array::from(vec![created_at]);
+        let id_array = Int64Array::from(vec![memory.id]);
+        let content_array = StringArray::from(vec![memory.content]);
+        let summary_array = StringArray::from(vec![memory.summary]);
+        let created_at_array = Int64Array::from(vec![memory.created_at]);
         
         let flat_embeddings = Float32Array::from(embedding);
         let field = Arc::new(Field::new("item", DataType::Float32, true));
-        let embedding_array = FixedSizeListArray::try_new(field, 384, Arc::new(flat_embeddings), None)?;
+        let embedding_array = FixedSizeListArray::try_new(field, self.dimensions(), Arc::new(flat_embeddings), None)?;
         
-        let type_array = StringArray::from(vec![memory_type.to_string()]);
-        let session_id_array = StringArray::from(vec![session_id.clone()]);
+        let type_array = StringArray::from(vec![memory.r#type.to_string()]);
+        let session_id_array = StringArray::from(vec![memory.session_id]);
         
-        let metadata_str = metadata.map(|m| m.to_string());
+        let metadata_str = memory.metadata.map(|m| m.to_string());
         let metadata_array = StringArray::from(vec![metadata_str]);
         
-        let category_id_array = StringArray::from(vec![category_id]);
+        let category_id_array = StringArray::from(vec![memory.category_id]);
 
         let batch = RecordBatch::try_new(
             schema.clone(),
@@ -697,16 +769,9 @@ impl VectorStore {
         summary: Option<String>,
     ) -> Result<Option<i64>> {
         // Generate embedding for the new content
-        let model = self.embedding_model.clone();
         let text = summary.clone().unwrap_or_else(|| content.to_string());
         
-        let embeddings = task::spawn_blocking(move || {
-            let mut model = model.blocking_lock();
-            model.embed(vec![text], None)
-        }).await.context("Join error during embedding")?
-        .context("Embedding failed")?;
-        
-        let new_embedding = embeddings.first().context("No embedding generated")?;
+        let new_embedding = self.embedder.embed_one(text).await?;
         
         // Search for similar existing memories
         let similar = self.search_memory_semantic(content, 5).await?;
@@ -714,7 +779,7 @@ impl VectorStore {
         // Check for duplicates
         for memory in similar {
             if let Some(ref mem_embedding) = memory.embedding {
-                let similarity = Self::cosine_similarity(new_embedding, mem_embedding);
+                let similarity = Self::cosine_similarity(&new_embedding, mem_embedding);
                 
                 if similarity > 0.95 {
                     // High similarity - skip as duplicate
@@ -769,60 +834,14 @@ impl VectorStore {
     pub async fn search_memory(&self, query: &str, limit: usize) -> Result<Vec<Memory>> {
         // Get more candidates for re-ranking
         let candidates = self.search_memory_semantic(query, limit * 3).await?;
-        
-        if candidates.is_empty() {
-            return Ok(Vec::new());
-        }
-        
-        // Score and re-rank candidates
-        let mut scored: Vec<ScoredMemory> = candidates
-            .into_iter()
-            .enumerate()
-            .map(|(rank, memory)| {
-                // Semantic score: 1.0 for first, decaying by rank
-                let semantic_score = 1.0 / (1.0 + rank as f32 * 0.1);
-                
-                // Lexical score: word match ratio
-                let lexical_score = lexical_score(query, &memory.content);
-                
-                // Recency score: newer is better
-                let recency_score = recency_score(memory.created_at);
-                
-                // Importance score: based on memory type and content
-                let importance_score = importance_score(&memory);
-                
-                // Combined score with weights
-                let final_score = 
-                    semantic_score * 0.50 +
-                    lexical_score * 0.25 +
-                    recency_score * 0.15 +
-                    importance_score * 0.10;
-                
-                ScoredMemory { memory, score: final_score }
-            })
-            .collect();
-        
-        // Sort by score descending
-        scored.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
-        
-        // Take top N
-        scored.truncate(limit);
-        
-        Ok(scored.into_iter().map(|s| s.memory).collect())
+        Ok(rerank_memories(query, candidates, limit))
     }
     
     /// Pure semantic search via LanceDB (internal use)
     async fn search_memory_semantic(&self, query: &str, limit: usize) -> Result<Vec<Memory>> {
-        let model = self.embedding_model.clone();
         let text = query.to_string();
         
-        let embeddings = task::spawn_blocking(move || {
-            let mut model = model.blocking_lock();
-            model.embed(vec![text], None)
-        }).await.context("Join error during embedding")?
-        .context("Embedding failed")?;
-
-        let query_embedding = embeddings.first().context("No embedding generated")?.clone();
+        let query_embedding = self.embedder.embed_one(text).await?;
         
         let table = self.get_or_create_table("memories", self.get_memory_schema()).await?;
         info!("store: starting vector search for query: {}", query);
@@ -1016,16 +1035,9 @@ impl VectorStore {
 
     
     pub async fn update_category(&self, category: MemoryCategory) -> Result<()> {
-        let model = self.embedding_model.clone();
         let text = format!("{}: {}", category.name, category.summary);
         
-        let embeddings = task::spawn_blocking(move || {
-            let mut model = model.blocking_lock();
-            model.embed(vec![text], None)
-        }).await.context("Join error during embedding")?
-        .context("Embedding failed")?;
-
-        let embedding = embeddings.first().context("No embedding generated")?.clone();
+        let embedding = self.embedder.embed_one(text).await?;
         let schema = self.get_category_schema();
         
         let id_array = StringArray::from(vec![category.id.clone()]);
@@ -1034,7 +1046,7 @@ impl VectorStore {
         let last_updated_array = Int64Array::from(vec![category.last_updated]);
         let flat_embeddings = Float32Array::from(embedding);
         let field = Arc::new(Field::new("item", DataType::Float32, true));
-        let embedding_array = FixedSizeListArray::try_new(field, 384, Arc::new(flat_embeddings), None)?;
+        let embedding_array = FixedSizeListArray::try_new(field, self.dimensions(), Arc::new(flat_embeddings), None)?;
 
         let batch = RecordBatch::try_new(
             schema.clone(),
@@ -1078,6 +1090,22 @@ impl VectorStore {
         Ok(())
     }
 
+    /// Replace the metadata of a memory
+    pub async fn update_memory_metadata(&self, memory_id: i64, metadata: Option<serde_json::Value>) -> Result<()> {
+        let table = self.get_or_create_table("memories", self.get_memory_schema()).await?;
+        let value = match metadata {
+            Some(metadata) => format!("'{}'", metadata.to_string().replace('\'', "''")),
+            None => "NULL".to_string(),
+        };
+        table.update()
+            .only_if(format!("id = {}", memory_id))
+            .column("metadata", value)
+            .execute()
+            .await
+            .context("Failed to update memory metadata")?;
+        Ok(())
+    }
+
     /// Get recent memories ordered by created_at (newest first)
     pub async fn get_recent_memories(&self, limit: usize) -> Result<Vec<Memory>> {
         self.get_recent_memories_with_offset(limit, 0).await
@@ -1155,6 +1183,58 @@ impl VectorStore {
         Ok(paginated)
     }
 
+    /// Every memory with its stored embedding, newest first
+    pub async fn export_memories(&self) -> Result<Vec<Memory>> {
+        let table = self.get_or_create_table("memories", self.get_memory_schema()).await?;
+        let batches: Vec<RecordBatch> = table.query()
+            .select(lancedb::query::Select::columns(&["id", "embedding"]))
+            .execute()
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        let mut embeddings: HashMap<i64, Vec<f32>> = HashMap::new();
+        for batch in batches {
+            let id_col = batch.column_by_name("id").context("id column missing")?.as_any().downcast_ref::<Int64Array>().context("Failed downcast id")?;
+            let embedding_col = batch.column_by_name("embedding").context("embedding column missing")?.as_any().downcast_ref::<FixedSizeListArray>().context("Failed downcast embedding")?;
+            for i in 0..batch.num_rows() {
+                if embedding_col.is_null(i) {
+                    continue;
+                }
+                if let Some(values) = embedding_col.value(i).as_any().downcast_ref::<Float32Array>() {
+                    embeddings.insert(id_col.value(i), values.values().to_vec());
+                }
+            }
+        }
+
+        let mut memories = self.get_recent_memories(usize::MAX).await?;
+        for memory in &mut memories {
+            memory.embedding = embeddings.remove(&memory.id);
+        }
+        Ok(memories)
+    }
+
+    /// Insert a memory as-is, keeping its id, timestamp and embedding
+    ///
+    /// The embedding is recomputed if missing or of the wrong size. Returns
+    /// false without writing if a memory with the same id already exists.
+    pub async fn import_memory(&self, memory: Memory) -> Result<bool> {
+        if self.get_memory_by_id(memory.id).await?.is_some() {
+            return Ok(false);
+        }
+
+        let embedding = match memory.embedding.clone().filter(|e| e.len() == self.embedder.dimensions()) {
+            Some(embedding) => embedding,
+            None => {
+                let text = memory.summary.clone().unwrap_or_else(|| memory.content.clone());
+                self.embedder.embed_one(text).await?
+            }
+        };
+
+        self.insert_memory_row(memory, embedding).await?;
+        Ok(true)
+    }
+
     /// Get a single memory by ID
     pub async fn get_memory_by_id(&self, id: i64) -> Result<Option<Memory>> {
         let table = self.get_or_create_table("memories", self.get_memory_schema()).await?;
@@ -1238,16 +1318,9 @@ impl VectorStore {
         let existing = existing.unwrap();
         
         // Generate new embedding for updated content
-        let model = self.embedding_model.clone();
         let text = content.to_string();
         
-        let embeddings = task::spawn_blocking(move || {
-            let mut model = model.blocking_lock();
-            model.embed(vec![text], None)
-        }).await.context("Join error during embedding")?
-        .context("Embedding failed")?;
-
-        let embedding = embeddings.first().context("No embedding generated")?.clone();
+        let embedding = self.embedder.embed_one(text).await?;
         let created_at = existing.created_at;
 
         let schema = self.get_memory_schema();
@@ -1259,7 +1332,7 @@ impl VectorStore {
         
         let flat_embeddings = Float32Array::from(embedding);
         let field = Arc::new(Field::new("item", DataType::Float32, true));
-        let embedding_array = FixedSizeListArray::try_new(field, 384, Arc::new(flat_embeddings), None)?;
+        let embedding_array = FixedSizeListArray::try_new(field, self.dimensions(), Arc::new(flat_embeddings), None)?;
         
         let type_array = StringArray::from(vec![existing.r#type.to_string()]);
         let session_id_array = StringArray::from(vec![existing.session_id.clone()]);
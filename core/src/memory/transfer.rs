@@ -0,0 +1,351 @@
+//! JSONL export and import of the memory store
+//!
+//! An export is one JSON object per line. The first line is a header, then
+//! every category, then every memory with its embedding:
+//!
+//! ```text
+//! {"kind":"header","version":1,"backend":"lance","exported_at":1760000000}
+//! {"kind":"category","id":"rust","name":"Rust","summary":"...","last_updated":1760000000}
+//! {"kind":"memory","id":1759990000000000000,"content":"...","created_at":1759990000,"type":"decision","embedding":[0.01,...]}
+//! ```
+//!
+//! Imports keep ids and timestamps, so importing the same file twice is a
+//! no-op. Files written by the older JSON-array export are also accepted.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::memory::backend::MemoryBackend;
+use crate::memory::store::{Memory, MemoryCategory, MemoryType};
+
+/// Format version written in the header line
+pub const EXPORT_VERSION: u32 = 1;
+
+/// One line of an export file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExportRecord {
+    Header {
+        version: u32,
+        backend: String,
+        exported_at: i64,
+    },
+    Category(CategoryRecord),
+    Memory(MemoryRecord),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryRecord {
+    pub id: String,
+    pub name: String,
+    pub summary: String,
+    pub last_updated: i64,
+}
+
+/// A memory with its embedding, which `Memory` itself does not serialize
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryRecord {
+    pub id: i64,
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    pub created_at: i64,
+    pub r#type: MemoryType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedding: Option<Vec<f32>>,
+}
+
+impl From<Memory> for MemoryRecord {
+    fn from(m: Memory) -> Self {
+        Self {
+            id: m.id,
+            content: m.content,
+            summary: m.summary,
+            created_at: m.created_at,
+            r#type: m.r#type,
+            session_id: m.session_id,
+            metadata: m.metadata,
+            category_id: m.category_id,
+            embedding: m.embedding,
+        }
+    }
+}
+
+impl From<MemoryRecord> for Memory {
+    fn from(r: MemoryRecord) -> Self {
+        Self {
+            id: r.id,
+            content: r.content,
+            summary: r.summary,
+            created_at: r.created_at,
+            r#type: r.r#type,
+            session_id: r.session_id,
+            metadata: r.metadata,
+            category_id: r.category_id,
+            embedding: r.embedding,
+        }
+    }
+}
+
+impl From<MemoryCategory> for CategoryRecord {
+    fn from(c: MemoryCategory) -> Self {
+        Self {
+            id: c.id,
+            name: c.name,
+            summary: c.summary,
+            last_updated: c.last_updated,
+        }
+    }
+}
+
+impl From<CategoryRecord> for MemoryCategory {
+    fn from(r: CategoryRecord) -> Self {
+        Self {
+            id: r.id,
+            name: r.name,
+            summary: r.summary,
+            last_updated: r.last_updated,
+            embedding: None,
+        }
+    }
+}
+
+/// Counts reported by an export or import
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransferStats {
+    pub memories: usize,
+    pub categories: usize,
+    /// Memories whose id was already present
+    pub skipped: usize,
+    /// Lines or records that could not be read or written
+    pub failed: usize,
+}
+
+/// Write the whole store to `out` as JSONL
+pub async fn export_jsonl<W: Write>(store: &dyn MemoryBackend, out: W) -> Result<TransferStats> {
+    let categories = store.get_all_categories().await?;
+    let memories = store.export_memories().await?;
+
+    let mut out = BufWriter::new(out);
+    let mut write = |record: &ExportRecord| -> Result<()> {
+        serde_json::to_writer(&mut out, record)?;
+        out.write_all(b"\n")?;
+        Ok(())
+    };
+
+    write(&ExportRecord::Header {
+        version: EXPORT_VERSION,
+        backend: store.name().to_string(),
+        exported_at: chrono::Utc::now().timestamp(),
+    })?;
+
+    let mut stats = TransferStats::default();
+    for category in categories {
+        write(&ExportRecord::Category(category.into()))?;
+        stats.categories += 1;
+    }
+    for memory in memories {
+        write(&ExportRecord::Memory(memory.into()))?;
+        stats.memories += 1;
+    }
+    out.flush()?;
+
+    info!("Exported {} memories and {} categories", stats.memories, stats.categories);
+    Ok(stats)
+}
+
+/// Read a JSONL export from `input` into the store
+///
+/// Malformed lines and records the backend rejects are counted as failed
+/// and the import carries on.
+pub async fn import_jsonl<R: BufRead>(store: &dyn MemoryBackend, input: R) -> Result<TransferStats> {
+    let mut stats = TransferStats::default();
+    for (index, line) in input.lines().enumerate() {
+        let line = line.context("Failed to read import file")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<ExportRecord>(&line) {
+            Ok(record) => import_record(store, record, &mut stats).await,
+            Err(e) => {
+                warn!("Skipping line {} of memory import: {}", index + 1, e);
+                stats.failed += 1;
+            }
+        }
+    }
+
+    info!(
+        "Imported {} memories and {} categories ({} skipped, {} failed)",
+        stats.memories, stats.categories, stats.skipped, stats.failed
+    );
+    Ok(stats)
+}
+
+async fn import_record(store: &dyn MemoryBackend, record: ExportRecord, stats: &mut TransferStats) {
+    match record {
+        ExportRecord::Header { version, .. } => {
+            if version > EXPORT_VERSION {
+                warn!("Memory export version {} is newer than {}, importing anyway", version, EXPORT_VERSION);
+            }
+        }
+        ExportRecord::Category(category) => match store.update_category(category.into()).await {
+            Ok(()) => stats.categories += 1,
+            Err(e) => {
+                warn!("Failed to import category: {}", e);
+                stats.failed += 1;
+            }
+        },
+        ExportRecord::Memory(memory) => match store.import_memory(memory.into()).await {
+            Ok(true) => stats.memories += 1,
+            Ok(false) => stats.skipped += 1,
+            Err(e) => {
+                warn!("Failed to import memory: {}", e);
+                stats.failed += 1;
+            }
+        },
+    }
+}
+
+/// Export the store to a file, replacing it if it exists
+pub async fn export_to_file(store: &dyn MemoryBackend, path: &Path) -> Result<TransferStats> {
+    let file = File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    export_jsonl(store, file).await
+}
+
+/// Import a file written by [`export_to_file`] or the older JSON-array export
+pub async fn import_from_file(store: &dyn MemoryBackend, path: &Path) -> Result<TransferStats> {
+    let mut reader = BufReader::new(File::open(path).with_context(|| format!("Failed to open {}", path.display()))?);
+    let first = reader.fill_buf()?.iter().find(|b| !b.is_ascii_whitespace()).copied();
+    if first != Some(b'[') {
+        return import_jsonl(store, reader).await;
+    }
+
+    let mut json = String::new();
+    reader.read_to_string(&mut json)?;
+    let memories: Vec<Memory> = serde_json::from_str(&json).context("Failed to parse JSON memory export")?;
+    let mut stats = TransferStats::default();
+    for memory in memories {
+        import_record(store, ExportRecord::Memory(memory.into()), &mut stats).await;
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::sqlite::SqliteStore;
+
+    async fn seeded_store() -> SqliteStore {
+        let store = SqliteStore::in_memory().unwrap();
+        store
+            .update_category(MemoryCategory {
+                id: "db".to_string(),
+                name: "Databases".to_string(),
+                summary: "Storage choices".to_string(),
+                last_updated: 100,
+                embedding: None,
+            })
+            .await
+            .unwrap();
+        for (id, content) in [(1, "use postgres for billing"), (2, "prefer tabs")] {
+            store
+                .import_memory(Memory {
+                    id,
+                    content: content.to_string(),
+                    summary: None,
+                    created_at: 1000 + id,
+                    r#type: MemoryType::Decision,
+                    session_id: Some("s1".to_string()),
+                    metadata: Some(serde_json::json!({"source": "test"})),
+                    category_id: Some("db".to_string()),
+                    embedding: None,
+                })
+                .await
+                .unwrap();
+        }
+        store
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_keeps_ids_and_timestamps() {
+        let source = seeded_store().await;
+        let mut buf = Vec::new();
+        let exported = export_jsonl(&source, &mut buf).await.unwrap();
+        assert_eq!(exported.memories, 2);
+        assert_eq!(exported.categories, 1);
+
+        let target = SqliteStore::in_memory().unwrap();
+        let imported = import_jsonl(&target, buf.as_slice()).await.unwrap();
+        assert_eq!(imported, TransferStats { memories: 2, categories: 1, ..Default::default() });
+
+        let memory = target.get_memory_by_id(1).await.unwrap().unwrap();
+        assert_eq!(memory.created_at, 1001);
+        assert_eq!(memory.session_id.as_deref(), Some("s1"));
+        assert_eq!(memory.metadata, Some(serde_json::json!({"source": "test"})));
+        assert_eq!(target.get_category_by_id("db").await.unwrap().unwrap().last_updated, 100);
+        assert_eq!(target.search_memory("postgres", 5).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reimport_skips_existing_and_bad_lines() {
+        let store = seeded_store().await;
+        let mut buf = Vec::new();
+        export_jsonl(&store, &mut buf).await.unwrap();
+        buf.extend_from_slice(b"not json\n\n");
+
+        let stats = import_jsonl(&store, buf.as_slice()).await.unwrap();
+        assert_eq!(stats.memories, 0);
+        assert_eq!(stats.skipped, 2);
+        assert_eq!(stats.failed, 1);
+        assert_eq!(store.count_memories().await.unwrap(), 2);
+    }
+
+    #[test]
+    fn test_memory_record_serializes_embedding() {
+        let record = ExportRecord::Memory(MemoryRecord {
+            id: 7,
+            content: "x".to_string(),
+            summary: None,
+            created_at: 1,
+            r#type: MemoryType::UserNote,
+            session_id: None,
+            metadata: None,
+            category_id: None,
+            embedding: Some(vec![0.5, -1.0]),
+        });
+        let line = serde_json::to_string(&record).unwrap();
+        assert!(line.starts_with(r#"{"kind":"memory","id":7"#));
+        let parsed: ExportRecord = serde_json::from_str(&line).unwrap();
+        match parsed {
+            ExportRecord::Memory(m) => assert_eq!(m.embedding, Some(vec![0.5, -1.0])),
+            other => panic!("unexpected record {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_legacy_json_array_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("old.json");
+        std::fs::write(
+            &path,
+            r#"[{"id":5,"content":"old note","summary":null,"created_at":42,"type":"user_note","session_id":null,"metadata":null,"category_id":null}]"#,
+        )
+        .unwrap();
+
+        let store = SqliteStore::in_memory().unwrap();
+        let stats = import_from_file(&store, &path).await.unwrap();
+        assert_eq!(stats.memories, 1);
+        assert_eq!(store.get_memory_by_id(5).await.unwrap().unwrap().created_at, 42);
+    }
+}
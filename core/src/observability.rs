@@ -0,0 +1,137 @@
+//! Structured tracing of agent sessions
+//!
+//! With `features.tracing.enabled`, `init` installs a `tracing` subscriber
+//! and the log macros (`info_log!` and friends) emit events to it instead
+//! of writing debug.log themselves. debug.log then gets each line with the
+//! spans it happened in:
+//!
+//! - `agent.turn`: one user message, until the agent waits for input again
+//! - `llm.call`: one request to one endpoint, with model and token counts
+//! - `tool.call`: one tool execution
+//! - `worker.run`: a delegated worker, under the tool call that spawned it
+//!
+//! With `otlp_endpoint` set and the `otel` feature built in, the spans are
+//! also exported over OTLP/gRPC, so long sessions can be read as traces in
+//! Jaeger or Grafana.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::{Context, Result};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+use crate::config::TracingSettings;
+
+/// Set once the subscriber is installed
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Whether log macros go through the tracing subscriber
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Keeps the pipeline running; dropping it flushes exported spans, so hold
+/// it until the process exits
+pub struct TracingGuard {
+    #[cfg(feature = "otel")]
+    provider: Option<opentelemetry_sdk::trace::TracerProvider>,
+}
+
+impl Drop for TracingGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "otel")]
+        if let Some(provider) = self.provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Could not flush trace spans: {}", e);
+            }
+        }
+    }
+}
+
+/// The filter `settings` describe
+pub fn filter(settings: &TracingSettings) -> Result<EnvFilter> {
+    EnvFilter::try_new(&settings.filter)
+        .with_context(|| format!("Invalid features.tracing.filter '{}'", settings.filter))
+}
+
+/// Install the tracing pipeline: `log_path` gets formatted events, and an
+/// OTLP exporter gets spans when configured
+///
+/// Events go through the debug log's writer, so `features.log` rotation
+/// applies to them. Fails if another subscriber is installed already.
+pub fn init(settings: &TracingSettings, log_path: &Path) -> Result<TracingGuard> {
+    let filter = filter(settings)?;
+    if crate::logging::path().as_deref() != Some(log_path) {
+        crate::logging::init(Some(log_path.to_path_buf()))
+            .with_context(|| format!("Cannot open {}", log_path.display()))?;
+    }
+    let log = tracing_subscriber::fmt::layer()
+        .with_writer(|| crate::logging::LogWriter)
+        .with_ansi(false)
+        .with_target(false);
+    let registry = tracing_subscriber::registry().with(filter).with(log);
+
+    #[cfg(feature = "otel")]
+    let (registry, provider) = {
+        use opentelemetry::trace::TracerProvider as _;
+
+        let provider = match settings.otlp_endpoint {
+            Some(ref endpoint) => Some(otlp_provider(endpoint, &settings.service_name)?),
+            None => None,
+        };
+        let layer = provider
+            .as_ref()
+            .map(|provider| tracing_opentelemetry::layer().with_tracer(provider.tracer("mylm")));
+        (registry.with(layer), provider)
+    };
+    #[cfg(not(feature = "otel"))]
+    if let Some(ref endpoint) = settings.otlp_endpoint {
+        eprintln!("mylm was built without the otel feature; spans are not exported to {}", endpoint);
+    }
+
+    registry.try_init().context("A tracing subscriber is already installed")?;
+    ENABLED.store(true, Ordering::Relaxed);
+    Ok(TracingGuard {
+        #[cfg(feature = "otel")]
+        provider,
+    })
+}
+
+/// Batch exporter of spans to the OTLP/gRPC collector at `endpoint`
+#[cfg(feature = "otel")]
+fn otlp_provider(endpoint: &str, service_name: &str) -> Result<opentelemetry_sdk::trace::TracerProvider> {
+    use opentelemetry_otlp::WithExportConfig;
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()
+        .with_context(|| format!("Cannot export spans to {}", endpoint))?;
+    Ok(opentelemetry_sdk::trace::TracerProvider::builder()
+        .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+        .with_resource(opentelemetry_sdk::Resource::new([opentelemetry::KeyValue::new(
+            "service.name",
+            service_name.to_string(),
+        )]))
+        .build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_is_checked_before_installing() {
+        assert!(filter(&TracingSettings::default()).is_ok());
+        let settings = TracingSettings {
+            filter: "mylm=loud".to_string(),
+            ..Default::default()
+        };
+        let dir = tempfile::TempDir::new().unwrap();
+        let err = init(&settings, &dir.path().join("debug.log")).err().unwrap();
+        assert!(err.to_string().contains("features.tracing.filter"), "{}", err);
+        assert!(!is_enabled());
+    }
+}
@@ -6,6 +6,9 @@
 //! # Main Types
 //! - `ClientMessage`: Messages sent from client to server
 //! - `ServerEvent`: Events broadcast from server to clients
+//!
+//! Sessions are shared: every connection that subscribes to a session id
+//! gets its events, and any subscriber may message it or settle approvals.
 //! - `ToolRequest`/`ToolResponse`: Tool execution protocol
 //! - `MessageEnvelope`: Wrapper for versioned message payloads
 //! - Supporting types: `SessionSummary`, `Workflow`, `Stage`, `SystemInfo`, etc.
@@ -47,9 +50,25 @@ pub enum ClientMessage {
     SwitchWorkspace {
         path: String,
     },
+    /// Follow a session's events alongside any other clients; with `replay`
+    /// (the default) its recent history is sent first
+    SubscribeSession {
+        session_id: Uuid,
+        #[serde(default = "default_replay")]
+        replay: bool,
+    },
+    /// Stop receiving a session's events (and drop out of its presence)
+    UnsubscribeSession {
+        session_id: Uuid,
+    },
+    /// Older name for `SubscribeSession` with replay
     ResumeSession {
         session_id: Uuid,
     },
+    /// Older name for `UnsubscribeSession`
+    LeaveSession {
+        session_id: Uuid,
+    },
     SendUserMessage {
         session_id: Uuid,
         message: UserMessage,
@@ -95,10 +114,17 @@ pub enum ClientMessage {
     },
 }
 
+fn default_replay() -> bool {
+    true
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ClientInfo {
     pub name: String,
     pub version: String,
+    /// Name shown to others in shared sessions (ignored for server users)
+    #[serde(default)]
+    pub display_name: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -119,6 +145,9 @@ pub enum ServerEvent {
     HelloAck {
         server: ServerInfo,
         capabilities: Capabilities,
+        /// Name of the authenticated user on a multi-user server
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        user: Option<String>,
     },
     ProjectInfo {
         root_path: String,
@@ -136,6 +165,9 @@ pub enum ServerEvent {
         session_id: Uuid,
         message_id: Uuid,
         role: String,
+        /// Who sent a user message
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        author: Option<String>,
     },
     TokenDelta {
         session_id: Uuid,
@@ -191,6 +223,22 @@ pub enum ServerEvent {
         session_id: Uuid,
         is_typing: bool,
     },
+    /// This connection now receives the session's events
+    Subscribed {
+        session_id: Uuid,
+        /// Connections following the session, this one included
+        subscribers: usize,
+        status: String,
+    },
+    Unsubscribed {
+        session_id: Uuid,
+    },
+    /// Who follows a session, and whose message the agent is handling
+    Presence {
+        session_id: Uuid,
+        participants: Vec<String>,
+        turn: Option<String>,
+    },
     JobsUpdate {
         session_id: Uuid,
         jobs: Vec<serde_json::Value>,
@@ -271,6 +319,8 @@ pub struct SessionSummary {
     pub title: String,
     pub status: String,
     pub created_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -0,0 +1,273 @@
+//! On-disk cache of LLM responses
+//!
+//! With `features.response_cache.enabled`, `LlmClient` answers a request it
+//! has seen before from disk instead of the provider, so re-running a batch
+//! or replaying a session returns at once and costs nothing. A request is
+//! keyed by a SHA-256 of the endpoint, the model, the messages (images
+//! included) and every sampling parameter; any change is a miss. Entries
+//! older than `ttl_secs` are dropped when looked up or when a new entry is
+//! stored; storing also trims the oldest entries once the cache passes
+//! `max_size_mb`. `mylm cache clear` removes them all.
+//!
+//! ```toml
+//! [features.response_cache]
+//! enabled = true
+//! ttl_secs = 86400
+//! ```
+//!
+//! Cached answers are replayed as they were, so leave the cache off for
+//! sampling you want to vary between runs.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::chat::{ChatMessage, ChatRequest, ChatResponse, Choice, StreamEvent, Usage};
+use super::{LlmConfig, ToolCall};
+use crate::config::ResponseCacheSettings;
+
+/// One cached response
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    /// Unix time the response was stored
+    stored_at: i64,
+    response: ChatResponse,
+}
+
+/// Responses to earlier requests, one JSON file per request
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    ttl: Duration,
+    /// Bytes the cache is trimmed to, None when unlimited
+    max_bytes: Option<u64>,
+}
+
+impl ResponseCache {
+    /// Cache in `dir` whose entries expire after `ttl`
+    pub fn new(dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl,
+            max_bytes: None,
+        }
+    }
+
+    /// Trim the oldest entries once the cache passes `max_bytes`
+    pub fn with_max_bytes(mut self, max_bytes: Option<u64>) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Default location: `<cache dir>/mylm/responses`
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("mylm").join("responses"))
+    }
+
+    /// The cache `settings` describe, enabled or not; None without a
+    /// directory to keep it in
+    pub fn from_settings(settings: &ResponseCacheSettings) -> Option<Self> {
+        let dir = settings.dir.clone().or_else(Self::default_dir)?;
+        let max_bytes = (settings.max_size_mb > 0).then(|| settings.max_size_mb * 1024 * 1024);
+        Some(Self::new(dir, Duration::from_secs(settings.ttl_secs)).with_max_bytes(max_bytes))
+    }
+
+    /// Where entries are kept
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Key of `request` sent with `config`: hex SHA-256 of everything that
+    /// shapes the answer. Streaming and non-streaming requests share keys.
+    pub fn key(config: &LlmConfig, request: &ChatRequest) -> String {
+        let messages: Vec<serde_json::Value> = request.messages.iter().map(ChatMessage::to_openai_json).collect();
+        let shape = serde_json::json!({
+            "base_url": config.base_url,
+            "model": config.model,
+            "messages": messages,
+            "max_tokens": request.max_tokens.or(config.max_tokens),
+            "temperature": request.temperature.or(config.temperature),
+            "stop": request.stop,
+            "tools": request.tools,
+            "tool_choice": request.tool_choice,
+            "response_format": request.response_format,
+        });
+        hex::encode(Sha256::digest(shape.to_string().as_bytes()))
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    /// Response stored under `key`, unless missing or expired
+    ///
+    /// Expired and unreadable entries are removed.
+    pub fn get(&self, key: &str) -> Option<ChatResponse> {
+        let path = self.path(key);
+        let bytes = std::fs::read(&path).ok()?;
+        let fresh = serde_json::from_slice::<Entry>(&bytes).ok().filter(|entry| {
+            let age = chrono::Utc::now().timestamp() - entry.stored_at;
+            (0..self.ttl.as_secs() as i64).contains(&age)
+        });
+        if fresh.is_none() {
+            let _ = std::fs::remove_file(&path);
+        }
+        fresh.map(|entry| entry.response)
+    }
+
+    /// Store `response` under `key`, readable by the owner only since
+    /// prompts and answers can hold whatever the agent read
+    pub fn put(&self, key: &str, response: &ChatResponse) -> Result<()> {
+        crate::util::create_private_dir(&self.dir)?;
+        let entry = Entry {
+            stored_at: chrono::Utc::now().timestamp(),
+            response: response.clone(),
+        };
+        // Write aside and rename, so a reader never sees half an entry
+        let temp = self.dir.join(format!("{}.{}.tmp", key, std::process::id()));
+        crate::util::write_private(&temp, &serde_json::to_vec(&entry)?)
+            .with_context(|| format!("Cannot write {}", temp.display()))?;
+        std::fs::rename(&temp, self.path(key)).context("Cannot store cache entry")?;
+        self.prune();
+        Ok(())
+    }
+
+    /// Remove expired entries, then the oldest ones while over `max_bytes`
+    ///
+    /// Age is taken from file modification times, so entries are not parsed.
+    fn prune(&self) {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+        let mut files: Vec<(std::time::SystemTime, u64, PathBuf)> = entries
+            .flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|e| e == "json"))
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok()?;
+                Some((metadata.modified().ok()?, metadata.len(), entry.path()))
+            })
+            .collect();
+        // Newest first: the budget goes to recent entries
+        files.sort_by(|a, b| b.0.cmp(&a.0));
+        let mut total = 0;
+        for (modified, size, path) in files {
+            let expired = modified.elapsed().is_ok_and(|age| age >= self.ttl);
+            total += size;
+            let over = self.max_bytes.is_some_and(|max| total > max);
+            if expired || over {
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+    }
+
+    /// Remove every entry; returns how many there were
+    pub fn clear(&self) -> Result<usize> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e).with_context(|| format!("Cannot read {}", self.dir.display())),
+        };
+        let mut removed = 0;
+        for entry in entries {
+            let path = entry?.path();
+            if matches!(path.extension().and_then(|e| e.to_str()), Some("json" | "tmp")) {
+                std::fs::remove_file(&path).with_context(|| format!("Cannot remove {}", path.display()))?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// The response a finished stream amounts to, for caching
+pub fn response_from_stream(
+    model: &str,
+    content: String,
+    tool_calls: Vec<ToolCall>,
+    usage: Option<Usage>,
+) -> ChatResponse {
+    let mut message = ChatMessage::assistant(content);
+    let finish_reason = if tool_calls.is_empty() { "stop" } else { "tool_calls" };
+    message.tool_calls = (!tool_calls.is_empty()).then_some(tool_calls);
+    ChatResponse {
+        id: format!("stream-{}", uuid::Uuid::new_v4()),
+        object: "chat.completion".to_string(),
+        created: chrono::Utc::now().timestamp() as u64,
+        model: model.to_string(),
+        choices: vec![Choice {
+            index: 0,
+            message,
+            finish_reason: Some(finish_reason.to_string()),
+        }],
+        usage,
+    }
+}
+
+/// Stream events replaying a cached `response`
+///
+/// No usage is reported: a cached answer uses no tokens.
+pub fn replay(response: &ChatResponse) -> Vec<StreamEvent> {
+    let mut events = Vec::new();
+    let content = response.content();
+    if !content.is_empty() {
+        events.push(StreamEvent::Content(content));
+    }
+    events.extend(response.tool_calls().into_iter().map(StreamEvent::ToolCall));
+    events.push(StreamEvent::Done);
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::LlmProvider;
+
+    #[test]
+    fn test_entries_are_keyed_by_request_and_expire() {
+        let config = LlmConfig::new(LlmProvider::Mock, "mock://".to_string(), "m".to_string(), None, 8192);
+        let request = ChatRequest::new("m".to_string(), vec![ChatMessage::user("hello")]);
+        let key = ResponseCache::key(&config, &request);
+        assert_eq!(key, ResponseCache::key(&config, &request.clone().with_streaming(true)));
+        assert_ne!(key, ResponseCache::key(&config, &request.clone().with_temperature(0.0)));
+        assert_ne!(
+            key,
+            ResponseCache::key(&config, &ChatRequest::new("m".to_string(), vec![ChatMessage::user("hello!")]))
+        );
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache = ResponseCache::new(dir.path().join("responses"), Duration::from_secs(60));
+        assert!(cache.get(&key).is_none());
+        let response = response_from_stream("m", "hi".to_string(), Vec::new(), None);
+        cache.put(&key, &response).unwrap();
+        assert_eq!(cache.get(&key).unwrap().content(), "hi");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = |path: &std::path::Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode(&cache.path(&key)), 0o600);
+            assert_eq!(mode(cache.dir()), 0o700);
+        }
+        assert!(matches!(replay(&response).as_slice(), [StreamEvent::Content(c), StreamEvent::Done] if c == "hi"));
+
+        let expired = ResponseCache::new(cache.dir(), Duration::ZERO);
+        assert!(expired.get(&key).is_none());
+        assert!(!cache.path(&key).exists());
+
+        cache.put(&key, &response).unwrap();
+        assert_eq!(cache.clear().unwrap(), 1);
+        assert!(cache.get(&key).is_none());
+
+        // Over the size limit, the oldest entries go
+        cache.put("first", &response).unwrap();
+        let size = std::fs::metadata(cache.path("first")).unwrap().len();
+        let hour_ago = std::time::SystemTime::now() - Duration::from_secs(3600);
+        std::fs::File::options().write(true).open(cache.path("first")).unwrap().set_modified(hour_ago).unwrap();
+        let small = ResponseCache::new(cache.dir(), Duration::from_secs(7200)).with_max_bytes(Some(size * 3 / 2));
+        small.put("second", &response).unwrap();
+        assert!(small.get("first").is_none());
+        assert!(small.get("second").is_some());
+    }
+}
@@ -2,7 +2,19 @@
 //!
 //! Defines the message structures used for chat completions,
 //! supporting both OpenAI-compatible and Google Gemini APIs.
-
+//!
+//! User messages can carry images. Only the attach and paste commands create
+//! them: the file is read and base64-encoded once, when it is attached, and
+//! travels with the message through the session history as structured data.
+//! Providers encode it into `image_url` parts for OpenAI-compatible APIs and
+//! `inlineData` parts for Gemini. The `[image: <path>]` line left in the
+//! message text (see `image_marker`) is only a label for the chat view;
+//! nothing reads the path it names.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use base64::Engine;
 use serde::{Deserialize, Serialize};
 use regex::Regex;
 
@@ -51,6 +63,69 @@ pub struct ChatMessage {
     /// This must be preserved in assistant messages when sending back to the API
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reasoning_content: Option<String>,
+    /// Images sent with the message (user messages only); providers encode
+    /// them into their own content parts
+    #[serde(skip)]
+    pub images: Vec<ImageAttachment>,
+}
+
+/// An image attached to a message, base64-encoded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageAttachment {
+    /// MIME type, e.g. `image/png`
+    pub media_type: String,
+    /// Base64 of the image bytes
+    pub data: String,
+}
+
+/// Largest image file sent to a model
+pub const MAX_IMAGE_BYTES: u64 = 20 * 1024 * 1024;
+
+impl ImageAttachment {
+    /// Read and encode the image at `path`; the type comes from its extension
+    pub fn from_path(path: &Path) -> anyhow::Result<Self> {
+        let media_type = image_media_type(path)
+            .with_context(|| format!("{} is not a PNG, JPEG, GIF or WebP image", path.display()))?;
+        let size = std::fs::metadata(path)
+            .with_context(|| format!("Cannot read {}", path.display()))?
+            .len();
+        if size > MAX_IMAGE_BYTES {
+            anyhow::bail!("{} is {} MB; images are limited to {} MB", path.display(), size / (1024 * 1024), MAX_IMAGE_BYTES / (1024 * 1024));
+        }
+        let bytes = std::fs::read(path).with_context(|| format!("Cannot read {}", path.display()))?;
+        Ok(Self {
+            media_type: media_type.to_string(),
+            data: base64::engine::general_purpose::STANDARD.encode(bytes),
+        })
+    }
+
+    /// `data:` URL for OpenAI-style `image_url` parts
+    pub fn data_url(&self) -> String {
+        format!("data:{};base64,{}", self.media_type, self.data)
+    }
+}
+
+/// MIME type of a supported image file
+pub fn image_media_type(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
+
+/// Label line for an image attached from `path`, shown in the chat view
+pub fn image_marker(path: &Path) -> String {
+    format!("[image: {}]", path.display())
+}
+
+/// The path an `[image: <path>]` label names
+pub fn image_marker_path(line: &str) -> Option<PathBuf> {
+    let path = line.trim().strip_prefix("[image: ")?.strip_suffix(']')?;
+    (!path.is_empty()).then(|| PathBuf::from(path))
 }
 
 
@@ -64,9 +139,40 @@ impl ChatMessage {
             tool_call_id: None,
             tool_calls: None,
             reasoning_content: None,
+            images: Vec::new(),
         }
     }
 
+    /// Create a user message with the image at `path` attached
+    pub fn user_with_image(content: impl Into<String>, path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        Ok(Self::user(content).with_image(ImageAttachment::from_path(path.as_ref())?))
+    }
+
+    /// Attach an image
+    pub fn with_image(mut self, image: ImageAttachment) -> Self {
+        self.images.push(image);
+        self
+    }
+
+    /// Attach already-read images
+    pub fn with_images(mut self, images: &[ImageAttachment]) -> Self {
+        self.images.extend_from_slice(images);
+        self
+    }
+
+    /// The message in OpenAI wire format; text and images become content parts
+    pub fn to_openai_json(&self) -> serde_json::Value {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        if !self.images.is_empty() {
+            let mut parts = vec![serde_json::json!({ "type": "text", "text": self.content })];
+            parts.extend(self.images.iter().map(|image| {
+                serde_json::json!({ "type": "image_url", "image_url": { "url": image.data_url() } })
+            }));
+            value["content"] = serde_json::Value::Array(parts);
+        }
+        value
+    }
+
     /// Create a new system message
     pub fn system(content: impl Into<String>) -> Self {
         ChatMessage {
@@ -76,6 +182,7 @@ impl ChatMessage {
             tool_call_id: None,
             tool_calls: None,
             reasoning_content: None,
+            images: Vec::new(),
         }
     }
 
@@ -88,6 +195,7 @@ impl ChatMessage {
             tool_call_id: None,
             tool_calls: None,
             reasoning_content: None,
+            images: Vec::new(),
         }
     }
 
@@ -100,6 +208,7 @@ impl ChatMessage {
             tool_call_id: None,
             tool_calls: None,
             reasoning_content: Some(reasoning_content.into()),
+            images: Vec::new(),
         }
     }
     /// Create a new tool result message
@@ -111,11 +220,16 @@ impl ChatMessage {
             tool_call_id: Some(tool_call_id.into()),
             tool_calls: None,
             reasoning_content: None,
+            images: Vec::new(),
         }
     }
 }
 
 /// A tool call generated by the model
+///
+/// Provider-agnostic representation of a native function call. OpenAI-style
+/// responses map onto it directly; Anthropic `tool_use` blocks are converted
+/// via [`ToolCall::from_anthropic_tool_use`].
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolCall {
     pub id: String,
@@ -124,6 +238,47 @@ pub struct ToolCall {
     pub function: ToolCallFunction,
 }
 
+impl ToolCall {
+    /// Create a new function tool call
+    pub fn new(id: impl Into<String>, name: impl Into<String>, arguments: impl Into<String>) -> Self {
+        ToolCall {
+            id: id.into(),
+            type_: "function".to_string(),
+            function: ToolCallFunction {
+                name: name.into(),
+                arguments: arguments.into(),
+            },
+        }
+    }
+
+    /// Parse the raw argument string into JSON
+    ///
+    /// Models occasionally return an empty string for argument-less calls,
+    /// or arguments that are not valid JSON. Empty arguments become an empty
+    /// object; anything unparseable is passed through as a JSON string so the
+    /// tool can still report a sensible error.
+    pub fn parsed_arguments(&self) -> serde_json::Value {
+        let raw = self.function.arguments.trim();
+        if raw.is_empty() {
+            return serde_json::json!({});
+        }
+        serde_json::from_str(raw).unwrap_or_else(|_| serde_json::Value::String(raw.to_string()))
+    }
+
+    /// Convert an Anthropic `tool_use` content block into a ToolCall
+    ///
+    /// Returns None if the block is not a `tool_use` block.
+    pub fn from_anthropic_tool_use(block: &serde_json::Value) -> Option<Self> {
+        if block.get("type").and_then(|t| t.as_str()) != Some("tool_use") {
+            return None;
+        }
+        let id = block.get("id").and_then(|v| v.as_str()).unwrap_or_default();
+        let name = block.get("name").and_then(|v| v.as_str())?;
+        let input = block.get("input").cloned().unwrap_or_else(|| serde_json::json!({}));
+        Some(ToolCall::new(id, name, input.to_string()))
+    }
+}
+
 /// Function information for a tool call
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolCallFunction {
@@ -153,6 +308,9 @@ pub struct ChatRequest {
     /// Optional tools for the model to use
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<Vec<ChatTool>>,
+    /// How the model should choose between the provided tools
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
     /// Response format - set to JSON mode to force JSON output
     #[serde(skip_serializing_if = "Option::is_none")]
     pub response_format: Option<ResponseFormat>,
@@ -174,6 +332,24 @@ pub struct ChatTool {
     pub function: ChatFunction,
 }
 
+impl ChatTool {
+    /// Create a function tool definition
+    pub fn function(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        parameters: serde_json::Value,
+    ) -> Self {
+        ChatTool {
+            type_: "function".to_string(),
+            function: ChatFunction {
+                name: name.into(),
+                description: Some(description.into()),
+                parameters: Some(parameters),
+            },
+        }
+    }
+}
+
 /// Function definition for tools
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatFunction {
@@ -184,6 +360,35 @@ pub struct ChatFunction {
     pub parameters: Option<serde_json::Value>,
 }
 
+/// Tool selection strategy for native tool calling
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolChoice {
+    /// Model decides whether to call a tool
+    Auto,
+    /// Model must not call any tool
+    None,
+    /// Model must call at least one tool
+    Required,
+    /// Model must call the named function
+    Function(String),
+}
+
+impl ToolChoice {
+    /// Convert to the OpenAI `tool_choice` wire format
+    pub fn to_openai(&self) -> serde_json::Value {
+        match self {
+            ToolChoice::Auto => serde_json::json!("auto"),
+            ToolChoice::None => serde_json::json!("none"),
+            ToolChoice::Required => serde_json::json!("required"),
+            ToolChoice::Function(name) => serde_json::json!({
+                "type": "function",
+                "function": { "name": name },
+            }),
+        }
+    }
+}
+
 fn default_stream() -> bool {
     false
 }
@@ -200,6 +405,7 @@ impl ChatRequest {
             stream: false,
             stop: None,
             tools: None,
+            tool_choice: None,
             response_format: None,
         }
     }
@@ -210,6 +416,12 @@ impl ChatRequest {
         self
     }
 
+    /// Set the tool selection strategy
+    pub fn with_tool_choice(mut self, choice: ToolChoice) -> Self {
+        self.tool_choice = Some(choice);
+        self
+    }
+
     /// Add a system message
     pub fn with_system_prompt(mut self, prompt: impl Into<String>) -> Self {
         self.messages.insert(0, ChatMessage::system(prompt));
@@ -302,6 +514,14 @@ impl ChatResponse {
             .map(|c| c.message.content.clone())
             .unwrap_or_default()
     }
+
+    /// Native tool calls requested by the first choice (empty if none)
+    pub fn tool_calls(&self) -> Vec<ToolCall> {
+        self.choices
+            .first()
+            .and_then(|c| c.message.tool_calls.clone())
+            .unwrap_or_default()
+    }
 }
 
 /// A single completion choice
@@ -325,6 +545,9 @@ pub struct Usage {
     pub completion_tokens: u32,
     /// Total tokens used
     pub total_tokens: u32,
+    /// Prompt tokens the provider read from its prompt cache
+    #[serde(default)]
+    pub cached_tokens: u32,
 }
 
 /// Stream event types for streaming responses
@@ -337,6 +560,8 @@ pub enum StreamEvent {
     Done,
     /// Token usage information
     Usage(crate::provider::TokenUsage),
+    /// A complete native tool call (emitted once all argument deltas arrived)
+    ToolCall(ToolCall),
     /// Error occurred
     Error(String),
 }
@@ -361,3 +586,69 @@ impl StreamEvent {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_call_parsed_arguments() {
+        let call = ToolCall::new("call_1", "read_file", r#"{"path": "Cargo.toml"}"#);
+        assert_eq!(call.parsed_arguments()["path"], "Cargo.toml");
+
+        let empty = ToolCall::new("call_2", "list_files", "");
+        assert_eq!(empty.parsed_arguments(), serde_json::json!({}));
+
+        let invalid = ToolCall::new("call_3", "shell", "ls -la");
+        assert_eq!(invalid.parsed_arguments(), serde_json::json!("ls -la"));
+    }
+
+    #[test]
+    fn test_tool_call_from_anthropic_tool_use() {
+        let block = serde_json::json!({
+            "type": "tool_use",
+            "id": "toolu_01",
+            "name": "shell",
+            "input": {"command": "ls"}
+        });
+        let call = ToolCall::from_anthropic_tool_use(&block).unwrap();
+        assert_eq!(call.id, "toolu_01");
+        assert_eq!(call.function.name, "shell");
+        assert_eq!(call.parsed_arguments()["command"], "ls");
+
+        let text = serde_json::json!({"type": "text", "text": "hello"});
+        assert!(ToolCall::from_anthropic_tool_use(&text).is_none());
+    }
+
+    #[test]
+    fn test_tool_choice_wire_formats() {
+        assert_eq!(ToolChoice::Auto.to_openai(), serde_json::json!("auto"));
+        assert_eq!(
+            ToolChoice::Function("shell".to_string()).to_openai(),
+            serde_json::json!({"type": "function", "function": {"name": "shell"}})
+        );
+    }
+
+    #[test]
+    fn test_images_become_content_parts() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let png = dir.path().join("shot.png");
+        std::fs::write(&png, b"\x89PNG").unwrap();
+        assert_eq!(image_marker_path(&image_marker(&png)), Some(png.clone()));
+
+        let image = ImageAttachment::from_path(&png).unwrap();
+        // The file is not needed once it is read
+        std::fs::remove_file(&png).unwrap();
+        let message = ChatMessage::user("What is this?").with_images(&[image]);
+        assert_eq!(message.images.len(), 1);
+
+        let json = message.to_openai_json();
+        assert_eq!(json["content"][0]["type"], "text");
+        assert_eq!(json["content"][1]["image_url"]["url"], "data:image/png;base64,iVBORw==");
+        assert!(json.get("images").is_none());
+        // Text-only messages keep plain string content
+        assert_eq!(ChatMessage::user("hi").to_openai_json()["content"], "hi");
+
+        assert!(ChatMessage::user_with_image("x", dir.path().join("notes.txt")).is_err());
+    }
+}
@@ -5,12 +5,14 @@
 //! - Google Generative AI (Gemini)
 
 use super::{
-    chat::{ChatMessage, ChatRequest, ChatResponse, Choice, StreamEvent, Usage},
+    cache,
+    chat::{ChatMessage, ChatRequest, ChatResponse, Choice, ImageAttachment, StreamEvent, ToolCall, ToolChoice, Usage},
+    mock::MockProvider,
     LlmConfig, TokenUsage,
 };
 use super::super::util::{sanitize_base_url, validate_api_key};
 use super::super::config::ConfigManager;
-use super::rate_limiter::RateLimiter;
+use super::rate_limiter::{RateLimitHeaders, RateLimiter};
 use anyhow::{bail, Context, Result};
 use futures::{Stream, StreamExt};
 use reqwest::{
@@ -24,6 +26,10 @@ use std::sync::Arc;
 use rand::Rng;
 use tokio::time::{sleep, Duration};
 
+/// Longest wait a provider may ask for before a retry; a quota that resets
+/// later (e.g. a daily limit) fails the request instead
+const MAX_PROVIDER_WAIT: Duration = Duration::from_secs(300);
+
 /// LLM Provider type
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LlmProvider {
@@ -33,6 +39,8 @@ pub enum LlmProvider {
     GoogleGenerativeAi,
     /// Moonshot AI (Kimi)
     MoonshotKimi,
+    /// Scripted responses served in-process, for tests
+    Mock,
 }
 
 impl std::str::FromStr for LlmProvider {
@@ -44,6 +52,7 @@ impl std::str::FromStr for LlmProvider {
             "google" | "gemini" | "google-ai" | "google-generativeai" => Ok(LlmProvider::GoogleGenerativeAi),
             "moonshot" | "kimi" => Ok(LlmProvider::MoonshotKimi),
             "inception" | "inceptionlabs" => Ok(LlmProvider::OpenAiCompatible),
+            "mock" => Ok(LlmProvider::Mock),
             _ => Err(format!("Unknown LLM provider: {}", s)),
         }
     }
@@ -55,10 +64,48 @@ impl std::fmt::Display for LlmProvider {
             LlmProvider::OpenAiCompatible => write!(f, "OpenAI Compatible"),
             LlmProvider::GoogleGenerativeAi => write!(f, "Google Generative AI"),
             LlmProvider::MoonshotKimi => write!(f, "Moonshot AI (Kimi)"),
+            LlmProvider::Mock => write!(f, "Mock"),
+        }
+    }
+}
+
+/// An HTTP error response from a provider
+///
+/// Kept as a typed error so the fallback chain can tell rate limits and
+/// server errors apart from bad requests.
+#[derive(Debug, thiserror::Error)]
+#[error("{message}")]
+pub struct ProviderHttpError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl ProviderHttpError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
         }
     }
 }
 
+/// Whether an error should move the request to the next fallback endpoint
+///
+/// True for 429, 408 and 5xx responses, timeouts and connection failures.
+pub(crate) fn is_failover_error(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        if let Some(http) = cause.downcast_ref::<ProviderHttpError>() {
+            return http.status == StatusCode::TOO_MANY_REQUESTS
+                || http.status == StatusCode::REQUEST_TIMEOUT
+                || http.status.is_server_error();
+        }
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            return e.is_timeout() || e.is_connect() || e.status().is_some_and(|s| s.is_server_error());
+        }
+        false
+    })
+}
+
 /// Main LLM Client
 pub struct LlmClient {
     config: LlmConfig,
@@ -75,6 +122,16 @@ pub struct LlmClient {
     job_id: Mutex<Option<String>>,
     /// Cancellation token for aborting retries
     cancel_token: Mutex<Option<tokio_util::sync::CancellationToken>>,
+    /// Clients for `config.fallbacks`, tried in order when this one fails
+    fallbacks: Vec<LlmClient>,
+    /// Skip retries so the next endpoint in the chain takes over at once
+    fail_fast: bool,
+    /// Optional callback notified on every failover
+    failover_callback: Mutex<Option<crate::provider::FailoverCallback>>,
+    /// Optional callback notified when a request finishes, for the usage log
+    usage_callback: Mutex<Option<crate::provider::UsageCallback>>,
+    /// Script answering requests for the mock provider
+    mock: Option<Arc<MockProvider>>,
     // TODO: restore job_registry with new architecture
 }
 
@@ -87,6 +144,23 @@ impl LlmClient {
             .build()
             .context("Failed to build HTTP client")?;
 
+        let fallback_count = config.fallbacks.len();
+        let fallbacks = config.fallbacks.iter()
+            .enumerate()
+            .map(|(i, fallback)| {
+                let mut fallback = fallback.clone();
+                fallback.fallbacks.clear();
+                let mut client = LlmClient::new(fallback)?;
+                client.fail_fast = i + 1 < fallback_count;
+                Ok(client)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mock = match config.provider {
+            LlmProvider::Mock => Some(Arc::new(MockProvider::from_base_url(&config.base_url)?)),
+            _ => None,
+        };
+
         Ok(LlmClient {
             config,
             http_client,
@@ -96,40 +170,99 @@ impl LlmClient {
             is_worker: false,
             job_id: Mutex::new(None),
             cancel_token: Mutex::new(None),
+            fail_fast: !fallbacks.is_empty(),
+            fallbacks,
+            failover_callback: Mutex::new(None),
+            usage_callback: Mutex::new(None),
+            mock,
         })
     }
 
+    /// Answer from `mock` instead of the configured script (mock provider only)
+    pub fn with_mock(mut self, mock: Arc<MockProvider>) -> Self {
+        if self.config.provider == LlmProvider::Mock {
+            self.mock = Some(mock);
+        }
+        self
+    }
+
+    /// The script of a mock provider client
+    fn mock(&self) -> Result<&MockProvider> {
+        self.mock.as_deref().context("Mock provider has no script")
+    }
+
     /// Set the config manager for rate limiting
     pub fn with_config_manager(mut self, config_manager: Arc<ConfigManager>) -> Self {
+        self.fallbacks = std::mem::take(&mut self.fallbacks)
+            .into_iter()
+            .map(|f| f.with_config_manager(Arc::clone(&config_manager)))
+            .collect();
         self.config_manager = Some(config_manager);
         self
     }
 
     /// Set a status callback for reporting retry attempts and other status updates
     pub fn with_status_callback(self, callback: crate::provider::StatusCallback) -> Self {
-        *self.status_callback.lock() = Some(callback);
+        self.set_status_callback(callback);
         self
     }
 
     /// Set a status callback after the client has been created (for use with Arc<LlmClient>)
     pub fn set_status_callback(&self, callback: crate::provider::StatusCallback) {
+        for fallback in &self.fallbacks {
+            fallback.set_status_callback(Arc::clone(&callback));
+        }
         *self.status_callback.lock() = Some(callback);
     }
 
+    /// Set a callback notified whenever a request fails over to a fallback endpoint
+    pub fn set_failover_callback(&self, callback: crate::provider::FailoverCallback) {
+        *self.failover_callback.lock() = Some(callback);
+    }
+
+    /// Set a callback notified with the model and token counts of every request
+    pub fn set_usage_callback(&self, callback: crate::provider::UsageCallback) {
+        *self.usage_callback.lock() = Some(callback);
+    }
+
+    /// Take over the callbacks of `previous`, for a client replacing it
+    pub fn adopt_callbacks(&self, previous: &LlmClient) {
+        if let Some(callback) = previous.status_callback.lock().clone() {
+            self.set_status_callback(callback);
+        }
+        if let Some(callback) = previous.failover_callback.lock().clone() {
+            self.set_failover_callback(callback);
+        }
+        if let Some(callback) = previous.usage_callback.lock().clone() {
+            self.set_usage_callback(callback);
+        }
+    }
+
     /// Set the rate limiter for this client
     pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.fallbacks = std::mem::take(&mut self.fallbacks)
+            .into_iter()
+            .map(|f| f.with_rate_limiter(Arc::clone(&rate_limiter)))
+            .collect();
         self.rate_limiter = Some(rate_limiter);
         self
     }
 
     /// Set whether this is a worker client
     pub fn set_worker(mut self, is_worker: bool) -> Self {
+        self.fallbacks = std::mem::take(&mut self.fallbacks)
+            .into_iter()
+            .map(|f| f.set_worker(is_worker))
+            .collect();
         self.is_worker = is_worker;
         self
     }
 
     /// Set the job ID for tracking metrics
     pub fn set_job_id(&self, job_id: Option<String>) {
+        for fallback in &self.fallbacks {
+            fallback.set_job_id(job_id.clone());
+        }
         *self.job_id.lock() = job_id;
     }
 
@@ -151,6 +284,9 @@ impl LlmClient {
 
     /// Set the cancellation token for this client
     pub fn set_cancel_token(&self, token: tokio_util::sync::CancellationToken) {
+        for fallback in &self.fallbacks {
+            fallback.set_cancel_token(token.clone());
+        }
         *self.cancel_token.lock() = Some(token);
     }
 
@@ -169,6 +305,46 @@ impl LlmClient {
         }
     }
 
+    /// Endpoint label used in failover logs and events
+    fn endpoint(&self) -> String {
+        format!("{}@{}", self.config.model, self.config.base_url)
+    }
+
+    /// Log and report a move from `from` to `to` in the fallback chain
+    fn report_failover(&self, from: &LlmClient, to: &LlmClient, error: &anyhow::Error) {
+        let event = crate::provider::FailoverEvent {
+            from: from.endpoint(),
+            to: to.endpoint(),
+            reason: error.to_string(),
+        };
+        crate::warn_log!("[LLM_CLIENT] Failing over from {} to {}: {}", event.from, event.to, event.reason);
+        self.report_status(&format!("{} failed, switching to {}", event.from, event.to));
+        if let Some(callback) = self.failover_callback.lock().as_ref() {
+            callback(&event);
+        }
+    }
+
+    /// Report a finished request on `endpoint` to the usage callback
+    ///
+    /// Requests cancelled by the user say nothing about the model and are skipped.
+    fn report_usage(&self, endpoint: &LlmClient, prompt_tokens: u32, completion_tokens: u32, ok: bool) {
+        if !ok && self.is_cancelled() {
+            return;
+        }
+        if let Some(callback) = self.usage_callback.lock().as_ref() {
+            let cost = (prompt_tokens as f64 * endpoint.config.input_price_per_1m
+                + completion_tokens as f64 * endpoint.config.output_price_per_1m)
+                / 1_000_000.0;
+            callback(&crate::provider::ModelCall {
+                model: endpoint.config.model.clone(),
+                prompt_tokens,
+                completion_tokens,
+                cost,
+                ok,
+            });
+        }
+    }
+
     /// Log request and response to file for debugging WAF issues
     /// status: "SENT" (before sending), "SUCCESS" (after success), "ERROR" (after error)
     fn log_request_to_file(&self, body_json: &str, status: &str, error_msg: Option<&str>) {
@@ -232,10 +408,10 @@ impl LlmClient {
         // First check legacy rate limit
         self.check_rate_limit_legacy(estimated_tokens).await?;
 
-        // Then check new rate limiter if available
+        // Then wait for the new rate limiter if available
         if let Some(ref limiter) = self.rate_limiter {
             let base_url = &self.config.base_url;
-            match limiter.acquire(base_url, self.is_worker, estimated_tokens as u32).await {
+            match limiter.wait_for_budget(base_url, self.is_worker, estimated_tokens as u32).await {
                 Ok(()) => Ok(()),
                 Err(e) => {
                     bail!("Rate limit exceeded: {}", e);
@@ -262,15 +438,79 @@ impl LlmClient {
         }
     }
 
+    /// Key of `request` in the response cache; None without a cache
+    fn cache_key(&self, request: &ChatRequest) -> Option<String> {
+        self.config.response_cache.as_ref()?;
+        Some(cache::ResponseCache::key(&self.config, request))
+    }
+
+    /// Cached response stored under `key`
+    fn cached_response(&self, key: &str) -> Option<ChatResponse> {
+        let response = self.config.response_cache.as_ref()?.get(key)?;
+        crate::info_log!("[LLM_CACHE] Answered from cache: model={}, key={}", self.config.model, &key[..12]);
+        Some(response)
+    }
+
+    /// Store `response` under `key`; a failed write only costs the next hit
+    fn cache_response(&self, key: &str, response: &ChatResponse) {
+        if let Some(ref cache) = self.config.response_cache {
+            if let Err(e) = cache.put(key, response) {
+                crate::warn_log!("[LLM_CACHE] Cannot cache response: {:#}", e);
+            }
+        }
+    }
+
     /// Send a chat request and get a response
+    ///
+    /// Retryable failures (429, 5xx, timeouts) move the request along the
+    /// fallback chain until an endpoint succeeds or the chain runs out.
+    /// With a response cache, a request seen before is answered from it.
     pub async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse> {
+        let cache_key = self.cache_key(request);
+        if let Some(response) = cache_key.as_deref().and_then(|key| self.cached_response(key)) {
+            return Ok(response);
+        }
+        let mut current = self;
+        let mut result = self.chat_once(request).await;
+        for fallback in &self.fallbacks {
+            match result {
+                Err(ref e) if is_failover_error(e) => {
+                    self.report_usage(current, 0, 0, false);
+                    self.report_failover(current, fallback, e);
+                    current = fallback;
+                    result = fallback.chat_once(request).await;
+                }
+                _ => break,
+            }
+        }
+        match &result {
+            Ok(response) => {
+                let (prompt, completion) = response
+                    .usage
+                    .as_ref()
+                    .map(|u| (u.prompt_tokens, u.completion_tokens))
+                    .unwrap_or_default();
+                self.report_usage(current, prompt, completion, true);
+                if let Some(ref key) = cache_key {
+                    self.cache_response(key, response);
+                }
+            }
+            Err(_) => self.report_usage(current, 0, 0, false),
+        }
+        result
+    }
+
+    /// Send a chat request to this client's own endpoint only
+    #[tracing::instrument(
+        name = "llm.call",
+        skip_all,
+        fields(model = %self.config.model, endpoint = %self.config.base_url, prompt_tokens, completion_tokens)
+    )]
+    async fn chat_once(&self, request: &ChatRequest) -> Result<ChatResponse> {
         let agent_type = if self.is_worker { "WORKER" } else { "MAIN" };
         let job_info = self.job_id.lock().as_ref().map(|j| format!("job={}", &j[..8.min(j.len())])).unwrap_or_default();
         
-        // Estimate input tokens (rough approximation: 3 chars per token - more conservative)
-        let estimated_input_tokens: usize = request.messages.iter()
-            .map(|m| m.content.len() / 3 + 1)
-            .sum();
+        let estimated_input_tokens = estimate_input_tokens(request);
         
         crate::info_log!("[{}] {} Chat request: model={}, messages={}, estimated_tokens={}",
             agent_type, job_info, self.config.model, request.messages.len(), estimated_input_tokens);
@@ -369,6 +609,10 @@ impl LlmClient {
         let result = match self.config.provider {
             LlmProvider::OpenAiCompatible | LlmProvider::MoonshotKimi => self.chat_openai(&final_request).await,
             LlmProvider::GoogleGenerativeAi => self.chat_gemini(&final_request).await,
+            LlmProvider::Mock => match self.mock() {
+                Ok(mock) => mock.chat(&final_request).await,
+                Err(e) => Err(e),
+            },
         };
         let request_duration = request_start.elapsed();
         
@@ -400,6 +644,9 @@ impl LlmClient {
                 if self.is_worker {
                     self.update_job_metrics(usage.prompt_tokens, usage.completion_tokens, estimated_input_tokens);
                 }
+                let span = tracing::Span::current();
+                span.record("prompt_tokens", usage.prompt_tokens);
+                span.record("completion_tokens", usage.completion_tokens);
                 
                 // Record actual usage to correct rate limiter state if needed
                 if let Some(ref limiter) = self.rate_limiter {
@@ -423,18 +670,141 @@ impl LlmClient {
     }
 
     /// Send a chat request with streaming response
-    
+    ///
+    /// Fails over like [`chat`](Self::chat), but only while nothing has been
+    /// streamed yet; an error after the first event is returned as is. A
+    /// cached response is replayed as one content event.
     pub fn chat_stream<'a>(
         &'a self,
         request: &'a ChatRequest,
+    ) -> Pin<Box<dyn Stream<Item = Result<StreamEvent>> + Send + 'a>> {
+        let cache_key = self.cache_key(request);
+        if self.fallbacks.is_empty() && self.usage_callback.lock().is_none() && cache_key.is_none() {
+            return self.chat_stream_once(request);
+        }
+
+        Box::pin(async_stream::try_stream! {
+            if let Some(response) = cache_key.as_deref().and_then(|key| self.cached_response(key)) {
+                for event in cache::replay(&response) {
+                    yield event;
+                }
+                return;
+            }
+            let mut current = self;
+            let mut remaining = self.fallbacks.iter();
+            loop {
+                let mut stream = current.chat_stream_once(request);
+                let mut started = false;
+                let mut failure = None;
+                let mut usage = TokenUsage::default();
+                // What the stream amounts to, for the cache
+                let mut content = String::new();
+                let mut tool_calls = Vec::new();
+                let mut complete = true;
+                while let Some(event) = stream.next().await {
+                    match event {
+                        Ok(event) => {
+                            started = true;
+                            match event {
+                                StreamEvent::Usage(ref u) => usage = u.clone(),
+                                StreamEvent::Content(ref chunk) => content.push_str(chunk),
+                                StreamEvent::ToolCall(ref call) => tool_calls.push(call.clone()),
+                                StreamEvent::Error(_) => complete = false,
+                                StreamEvent::Done => {}
+                            }
+                            yield event;
+                        }
+                        Err(e) => {
+                            failure = Some(e);
+                            break;
+                        }
+                    }
+                }
+
+                let Some(error) = failure else {
+                    self.report_usage(current, usage.prompt_tokens, usage.completion_tokens, true);
+                    if let (Some(key), true) = (cache_key.as_deref(), complete) {
+                        let usage = Usage {
+                            prompt_tokens: usage.prompt_tokens,
+                            completion_tokens: usage.completion_tokens,
+                            total_tokens: usage.total_tokens,
+                            cached_tokens: usage.cached_tokens,
+                        };
+                        let response = cache::response_from_stream(&current.config.model, content, tool_calls, Some(usage));
+                        self.cache_response(key, &response);
+                    }
+                    return;
+                };
+                self.report_usage(current, usage.prompt_tokens, usage.completion_tokens, false);
+                match remaining.next() {
+                    Some(next) if !started && is_failover_error(&error) => {
+                        self.report_failover(current, next, &error);
+                        current = next;
+                    }
+                    _ => {
+                        Err(error)?;
+                        return;
+                    }
+                }
+            }
+        })
+    }
+
+    /// Stream a chat request from this client's own endpoint only
+    fn chat_stream_once<'a>(
+        &'a self,
+        request: &'a ChatRequest,
     ) -> Pin<Box<dyn Stream<Item = Result<StreamEvent>> + Send + 'a>> {
         crate::debug_log!("[LLM_CLIENT] chat_stream called with provider: {:?}", self.config.provider);
         crate::debug_log!("[LLM_CLIENT] chat_stream base_url: {}", self.config.base_url);
         crate::debug_log!("[LLM_CLIENT] chat_stream model: {}", self.config.model);
-        match self.config.provider {
+        let stream = match self.config.provider {
             LlmProvider::OpenAiCompatible | LlmProvider::MoonshotKimi => self.chat_stream_openai(request),
             LlmProvider::GoogleGenerativeAi => self.chat_stream_gemini(request),
-        }
+            LlmProvider::Mock => match self.mock() {
+                Ok(mock) => mock.chat_stream(request),
+                Err(e) => Box::pin(futures::stream::once(async move { Err(e) })),
+            },
+        };
+        // Open until the stream ends, so the trace shows the whole call
+        let span = tracing::info_span!(
+            "llm.call",
+            model = %self.config.model,
+            endpoint = %self.config.base_url,
+            stream = true,
+            prompt_tokens = tracing::field::Empty,
+            completion_tokens = tracing::field::Empty,
+        );
+
+        let recorder = span.clone();
+
+        // Wait for rate-limit budget before the first event, settle it after the last
+        let mut events: Pin<Box<dyn Stream<Item = Result<StreamEvent>> + Send + 'a>> = Box::pin(async_stream::try_stream! {
+            let estimated = estimate_input_tokens(request);
+            if self.rate_limiter.is_some() {
+                self.check_rate_limit(estimated).await?;
+            }
+            let mut stream = stream;
+            let mut actual = 0;
+            while let Some(event) = stream.next().await {
+                let event = event?;
+                if let StreamEvent::Usage(ref usage) = event {
+                    actual = usage.total_tokens;
+                    recorder.record("prompt_tokens", usage.prompt_tokens);
+                    recorder.record("completion_tokens", usage.completion_tokens);
+                }
+                yield event;
+            }
+            if let Some(ref limiter) = self.rate_limiter {
+                limiter.record_usage(&self.config.base_url, self.is_worker, actual, estimated as u32);
+            }
+        });
+        // Every poll runs in the span, so the request, retries and log lines
+        // of the stream are attributed to this call
+        Box::pin(futures::stream::poll_fn(move |cx| {
+            let _entered = span.enter();
+            events.as_mut().poll_next(cx)
+        }))
     }
 
     /// Helper with jittered backoff retry, respecting Retry-After headers and cancellation
@@ -447,7 +817,7 @@ impl LlmClient {
         Fut: std::future::Future<Output = Result<reqwest::Response, reqwest::Error>>,
     {
         let mut attempt = 0;
-        let max_retries = 5;
+        let max_retries = if self.fail_fast { 0 } else { 5 };
         let mut delay = Duration::from_secs(3);
 
         loop {
@@ -456,10 +826,17 @@ impl LlmClient {
                 bail!("Request cancelled by user");
             }
 
+            // Wait the provider asked for, replacing the backoff delay
+            let mut provider_wait = None;
             match operation().await {
                 Ok(response) => {
                     let status = response.status();
+                    let headers = RateLimitHeaders::parse(response.headers());
                     if status.is_success() {
+                        // Hold off the next request if this one used up a quota
+                        if let Some(ref limiter) = self.rate_limiter {
+                            limiter.record_headers(&self.config.base_url, self.is_worker, &headers);
+                        }
                         return Ok(response);
                     }
                     
@@ -472,12 +849,8 @@ impl LlmClient {
                     
                     // Handle 429 Rate Limit
                     if status == StatusCode::TOO_MANY_REQUESTS {
-                        // Extract Retry-After header
-                        let retry_after = response.headers()
-                            .get("retry-after")
-                            .and_then(|v| v.to_str().ok())
-                            .and_then(|v| v.parse::<u64>().ok())
-                            .map(Duration::from_secs);
+                        // Retry-After, or the reset of the exhausted quota
+                        let retry_after = headers.wait();
                         
                         // Record rate limit error
                         if let Some(ref limiter) = self.rate_limiter {
@@ -488,8 +861,12 @@ impl LlmClient {
                             crate::error_log!("Rate limit (429) exceeded max retries ({}), giving up", max_retries);
                             return Ok(response);
                         }
+                        if let Some(wait) = retry_after.filter(|wait| *wait > MAX_PROVIDER_WAIT) {
+                            crate::error_log!("Rate limit (429) resets in {:?}, too long to wait; giving up", wait);
+                            return Ok(response);
+                        }
 
-                        // Use Retry-After if available, otherwise use exponential backoff
+                        // Use the provider's wait if it gave one, otherwise use exponential backoff
                         let wait_duration = retry_after.unwrap_or(delay);
                         let agent_type = if self.is_worker { "WORKER" } else { "MAIN" };
                         let job_info = self.job_id.lock().as_ref().map(|j| format!("job={}", &j[..8.min(j.len())])).unwrap_or_default();
@@ -518,7 +895,9 @@ impl LlmClient {
                     }
                     
                     if status.is_server_error() && attempt < max_retries {
-                        let msg = format!("Provider error {}, retrying in {:?}...", status, delay);
+                        // An overloaded provider (503) may say when to come back
+                        provider_wait = headers.retry_after.filter(|wait| *wait <= MAX_PROVIDER_WAIT);
+                        let msg = format!("Provider error {}, retrying in {:?}...", status, provider_wait.unwrap_or(delay));
                         self.report_status(&msg);
                     } else {
                         return Ok(response);
@@ -536,16 +915,17 @@ impl LlmClient {
             attempt += 1;
             
             // Check cancellation before sleep
+            let wait = provider_wait.unwrap_or(delay);
             let token_opt = self.cancel_token.lock().clone();
             if let Some(token) = token_opt {
                 tokio::select! {
-                    _ = sleep(delay) => {},
+                    _ = sleep(wait) => {},
                     _ = token.cancelled() => {
                         bail!("Request cancelled by user");
                     }
                 }
             } else {
-                sleep(delay).await;
+                sleep(wait).await;
             }
             
             // Jitter: +/- 500ms
@@ -561,17 +941,7 @@ impl LlmClient {
         let base_url = sanitize_base_url(&self.config.base_url, "Base URL")?;
         let url = format!("{}/chat/completions", base_url.trim_end_matches('/'));
 
-        let body = OpenAiRequest {
-            model: self.config.model.clone(),
-            messages: &request.messages,
-            max_completion_tokens: request.max_tokens,
-            stream: Some(false),
-            // Do NOT send native API tool definitions.
-            // Tool descriptions are embedded in the system prompt as JSON-format instructions.
-            // Sending tools here causes some models (Claude-family, Hermes, etc.) to activate
-            // their native XML tool-calling format, which breaks our ShortKey JSON parser.
-            tools: None,
-        };
+        let body = self.openai_body(request, false);
 
         // Serialize body for request and logging
         let body_json = serde_json::to_string(&body)
@@ -617,24 +987,39 @@ impl LlmClient {
                     }
                 };
                 
-                let choices = response_body.choices.into_iter().map(|c| Choice {
-                    index: c.index,
-                    message: ChatMessage {
-                        role: super::chat::MessageRole::Assistant,
-                        content: c.message.content.unwrap_or_default(),
-                        name: None,
-                        tool_call_id: None,
-                        tool_calls: c.message.tool_calls.as_ref().map(|tcs| tcs.iter().map(|tc| crate::provider::chat::ToolCall {
-                            id: tc.id.clone(),
-                            type_: tc.type_.clone(),
+                let choices = response_body.choices.into_iter().map(|c| {
+                    let (content, block_tool_calls) = match c.message.content {
+                        Some(OpenAiContent::Text(text)) => (text, Vec::new()),
+                        Some(OpenAiContent::Blocks(blocks)) => split_content_blocks(&blocks),
+                        None => (String::new(), Vec::new()),
+                    };
+                    let mut tool_calls: Vec<ToolCall> = c.message.tool_calls
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|tc| ToolCall {
+                            id: tc.id,
+                            type_: tc.type_,
                             function: crate::provider::chat::ToolCallFunction {
-                                name: tc.function.name.clone(),
-                                arguments: tc.function.arguments.clone(),
+                                name: tc.function.name,
+                                arguments: tc.function.arguments,
                             },
-                        }).collect()),
-                        reasoning_content: c.message.reasoning_content,
-                    },
-                    finish_reason: c.finish_reason,
+                        })
+                        .collect();
+                    tool_calls.extend(block_tool_calls);
+
+                    Choice {
+                        index: c.index,
+                        message: ChatMessage {
+                            role: super::chat::MessageRole::Assistant,
+                            content,
+                            name: None,
+                            tool_call_id: None,
+                            tool_calls: if tool_calls.is_empty() { None } else { Some(tool_calls) },
+                            reasoning_content: c.message.reasoning_content,
+                            images: Vec::new(),
+                        },
+                        finish_reason: c.finish_reason,
+                    }
                 }).collect();
 
                 Ok(ChatResponse {
@@ -647,6 +1032,7 @@ impl LlmClient {
                         prompt_tokens: u.prompt_tokens,
                         completion_tokens: u.completion_tokens,
                         total_tokens: u.total_tokens,
+                        cached_tokens: u.cached(),
                     }),
                 })
             }
@@ -658,7 +1044,7 @@ impl LlmClient {
             StatusCode::TOO_MANY_REQUESTS => {
                 let err_msg = "Rate limit exceeded. Please try again later.";
                 self.log_request_to_file(&body_json, "ERROR", Some(err_msg));
-                bail!("{}", err_msg);
+                Err(ProviderHttpError::new(StatusCode::TOO_MANY_REQUESTS, err_msg).into())
             }
             status => {
                 let error_body: Option<serde_json::Value> = response.json().await.ok();
@@ -671,7 +1057,7 @@ impl LlmClient {
                 
                 let full_error = format!("API request failed ({}): {}", status, error_msg);
                 self.log_request_to_file(&body_json, "ERROR", Some(&full_error));
-                bail!("{}", full_error);
+                Err(ProviderHttpError::new(status, full_error).into())
             }
         }
     }
@@ -690,7 +1076,7 @@ impl LlmClient {
 
             match m.role.as_str() {
                 "system" => {
-                    system_parts.push(GeminiPart { text: m.content.clone() });
+                    system_parts.push(GeminiPart::text(m.content.clone()));
                 }
                 _ => {
                     let role = match m.role.as_str() {
@@ -706,15 +1092,18 @@ impl LlmClient {
                                 first_part.text.push_str("\n\n");
                                 first_part.text.push_str(&m.content);
                             } else {
-                                last.parts.push(GeminiPart { text: m.content.clone() });
+                                last.parts.push(GeminiPart::text(m.content.clone()));
                             }
+                            last.parts.extend(m.images.iter().map(GeminiPart::image));
                             continue;
                         }
                     }
                     
                     contents.push(GeminiContent {
                         role: role.to_string(),
-                        parts: vec![GeminiPart { text: m.content.clone() }],
+                        parts: std::iter::once(GeminiPart::text(m.content.clone()))
+                            .chain(m.images.iter().map(GeminiPart::image))
+                            .collect(),
                     });
                 }
             }
@@ -728,7 +1117,7 @@ impl LlmClient {
 
         // Add system prompt from config if present
         if let Some(sys_prompt) = &self.config.system_prompt {
-            system_parts.insert(0, GeminiPart { text: sys_prompt.clone() });
+            system_parts.insert(0, GeminiPart::text(sys_prompt.clone()));
         }
 
         let system_instruction_text = if system_parts.is_empty() {
@@ -748,7 +1137,7 @@ impl LlmClient {
 
         let system_instruction = system_instruction_text.map(|text| GeminiContent {
             role: "system".to_string(),
-            parts: vec![GeminiPart { text }],
+            parts: vec![GeminiPart::text(text)],
         });
 
         // Validate and sanitize the base URL before constructing the request URL
@@ -817,6 +1206,7 @@ impl LlmClient {
                             tool_call_id: None,
                             tool_calls: None,
                             reasoning_content: None,
+                            images: Vec::new(),
                         },
                         finish_reason: c.finish_reason,
                     })
@@ -832,6 +1222,7 @@ impl LlmClient {
                         prompt_tokens: u.prompt_token_count,
                         completion_tokens: u.candidates_token_count,
                         total_tokens: u.total_token_count,
+                        cached_tokens: u.cached_content_token_count,
                     }),
                 })
             }
@@ -839,7 +1230,7 @@ impl LlmClient {
                 bail!("Authentication failed. Check your API key.");
             }
             StatusCode::TOO_MANY_REQUESTS => {
-                bail!("Rate limit exceeded. Please try again later.");
+                Err(ProviderHttpError::new(StatusCode::TOO_MANY_REQUESTS, "Rate limit exceeded. Please try again later.").into())
             }
             status => {
                 let error_body: Option<serde_json::Value> = response.json().await.ok();
@@ -848,7 +1239,7 @@ impl LlmClient {
                     .and_then(|v| v.get("error").and_then(|e| e.get("message")))
                     .and_then(|v| v.as_str())
                     .unwrap_or("Unknown error");
-                bail!("Gemini API request failed ({}): {}", status, error_msg);
+                Err(ProviderHttpError::new(status, format!("Gemini API request failed ({}): {}", status, error_msg)).into())
             }
         }
     }
@@ -863,17 +1254,7 @@ impl LlmClient {
         let base_url = sanitize_base_url(&self.config.base_url, "Base URL").expect("Base URL should have been validated in LlmClient::new");
         let url = format!("{}/chat/completions", base_url.trim_end_matches('/'));
 
-        let body = OpenAiRequest {
-            model: self.config.model.clone(),
-            messages: &request.messages,
-            max_completion_tokens: request.max_tokens,
-            stream: Some(true),
-            // Do NOT send native API tool definitions.
-            // Tool descriptions are embedded in the system prompt as JSON-format instructions.
-            // Sending tools here causes some models (Claude-family, Hermes, etc.) to activate
-            // their native XML tool-calling format, which breaks our ShortKey JSON parser.
-            tools: None,
-        };
+        let body = self.openai_body(request, true);
 
         let http_client = self.http_client.clone();
         let headers_res = self.build_headers();
@@ -941,12 +1322,13 @@ impl LlmClient {
                     error_body.chars().take(200).collect::<String>()
                 };
                 
-                Err(anyhow::anyhow!("LLM API error: {}", error_display))?;
+                Err(ProviderHttpError::new(status, format!("LLM API error: {}", error_display)))?;
                 return; // Explicit return to satisfy compiler
             }
 
             let mut stream = response.bytes_stream();
             let mut buffer = String::new();
+            let mut tool_calls = ToolCallAccumulator::default();
 
             while let Some(chunk_res) = stream.next().await {
                 let chunk = chunk_res.context("Failed to read chunk")?;
@@ -959,6 +1341,9 @@ impl LlmClient {
 
                     if let Some(data) = line.strip_prefix("data: ") {
                         if data == "[DONE]" {
+                            for call in tool_calls.finish() {
+                                yield StreamEvent::ToolCall(call);
+                            }
                             yield StreamEvent::Done;
                             return;
                         }
@@ -967,11 +1352,15 @@ impl LlmClient {
                             if let Some(delta) = parsed.choices.first().and_then(|c| c.delta.content.as_ref()) {
                                 yield StreamEvent::Content(delta.clone());
                             }
+                            if let Some(deltas) = parsed.choices.first().and_then(|c| c.delta.tool_calls.as_ref()) {
+                                tool_calls.push(deltas);
+                            }
                             if let Some(usage) = parsed.usage {
                                 yield StreamEvent::Usage(TokenUsage {
                                     prompt_tokens: usage.prompt_tokens,
                                     completion_tokens: usage.completion_tokens,
                                     total_tokens: usage.total_tokens,
+                                    cached_tokens: usage.cached(),
                                 });
                             }
                         }
@@ -979,6 +1368,9 @@ impl LlmClient {
                 }
             }
 
+            for call in tool_calls.finish() {
+                yield StreamEvent::ToolCall(call);
+            }
             yield StreamEvent::Done;
         })
     }
@@ -1001,7 +1393,7 @@ impl LlmClient {
 
                 match m.role.as_str() {
                     "system" => {
-                        system_parts.push(GeminiPart { text: m.content.clone() });
+                        system_parts.push(GeminiPart::text(m.content.clone()));
                     }
                     _ => {
                         let role = match m.role.as_str() {
@@ -1016,15 +1408,18 @@ impl LlmClient {
                                     first_part.text.push_str("\n\n");
                                     first_part.text.push_str(&m.content);
                                 } else {
-                                    last.parts.push(GeminiPart { text: m.content.clone() });
+                                    last.parts.push(GeminiPart::text(m.content.clone()));
                                 }
+                                last.parts.extend(m.images.iter().map(GeminiPart::image));
                                 continue;
                             }
                         }
                         
                         contents.push(GeminiContent {
                             role: role.to_string(),
-                            parts: vec![GeminiPart { text: m.content.clone() }],
+                            parts: std::iter::once(GeminiPart::text(m.content.clone()))
+                                .chain(m.images.iter().map(GeminiPart::image))
+                                .collect(),
                         });
                     }
                 }
@@ -1037,7 +1432,7 @@ impl LlmClient {
 
             // Add system prompt from config if present
             if let Some(sys_prompt) = &self.config.system_prompt {
-                system_parts.insert(0, GeminiPart { text: sys_prompt.clone() });
+                system_parts.insert(0, GeminiPart::text(sys_prompt.clone()));
             }
 
             let system_instruction_text = if system_parts.is_empty() {
@@ -1056,7 +1451,7 @@ impl LlmClient {
 
             let system_instruction = system_instruction_text.map(|text| GeminiContent {
                 role: "system".to_string(),
-                parts: vec![GeminiPart { text }],
+                parts: vec![GeminiPart::text(text)],
             });
 
             let base_url = sanitize_base_url(&self.config.base_url, "Base URL")?;
@@ -1094,7 +1489,7 @@ impl LlmClient {
                 response.bytes_stream()
             } else {
                 let error_text = response.text().await.unwrap_or_default();
-                Err(anyhow::anyhow!("Gemini API error ({}): {}", status, error_text))?;
+                Err(ProviderHttpError::new(status, format!("Gemini API error ({}): {}", status, error_text)))?;
                 unreachable!()
             };
             let mut buffer = String::new();
@@ -1183,6 +1578,86 @@ impl LlmClient {
         })
     }
 
+    /// Whether requests from this client use the provider's native tool-calling API
+    ///
+    /// Disabled by default: tool descriptions are normally embedded in the system
+    /// prompt, and sending native definitions makes some models (Claude-family,
+    /// Hermes, etc.) switch to their own tool-calling format. Gemini has no
+    /// native support here and always falls back to text parsing.
+    pub fn supports_native_tools(&self) -> bool {
+        self.config.native_tool_calling && self.config.provider != LlmProvider::GoogleGenerativeAi
+    }
+
+    /// Whether the configured endpoint is Anthropic
+    fn is_anthropic(&self) -> bool {
+        let provider_type = self.config.extra_params.get("provider_type")
+            .map(|s| s.as_str())
+            .unwrap_or("");
+        provider_type.contains("anthropic") || self.config.base_url.contains("anthropic")
+    }
+
+    /// Whether requests need explicit prompt-cache breakpoints
+    ///
+    /// OpenAI, DeepSeek and Moonshot cache repeated prompt prefixes on their
+    /// own; Claude models only cache up to a block marked `cache_control`.
+    fn wants_cache_breakpoints(&self) -> bool {
+        self.config.prompt_cache_hints
+            && (self.is_anthropic() || self.config.model.to_ascii_lowercase().contains("claude"))
+    }
+
+    /// Body of an OpenAI-compatible chat request
+    fn openai_body(&self, request: &ChatRequest, stream: bool) -> OpenAiRequest {
+        let (mut tools, tool_choice) = self.native_tool_fields(request);
+        let mut messages: Vec<serde_json::Value> = request.messages.iter().map(ChatMessage::to_openai_json).collect();
+        if self.wants_cache_breakpoints() {
+            add_cache_breakpoints(&mut messages, tools.as_deref_mut());
+        }
+        OpenAiRequest {
+            model: self.config.model.clone(),
+            messages,
+            max_completion_tokens: request.max_tokens,
+            stream: Some(stream),
+            // The agent runs one tool per step, so it asks for one call at a time
+            parallel_tool_calls: tools.as_ref().map(|_| false),
+            tools,
+            tool_choice,
+        }
+    }
+
+    /// Build the `tools` / `tool_choice` request fields
+    ///
+    /// Returns `(None, None)` unless native tool calling is enabled and the
+    /// request actually carries tool definitions.
+    fn native_tool_fields(&self, request: &ChatRequest) -> (Option<Vec<serde_json::Value>>, Option<serde_json::Value>) {
+        if !self.supports_native_tools() {
+            return (None, None);
+        }
+        let tools = match request.tools.as_ref() {
+            Some(tools) if !tools.is_empty() => tools,
+            _ => return (None, None),
+        };
+        let choice = request.tool_choice.clone().unwrap_or(ToolChoice::Auto);
+
+        // Every request built here goes to a `/chat/completions` endpoint, so
+        // Anthropic base URLs get the OpenAI shape too
+        match self.config.provider {
+            LlmProvider::OpenAiCompatible | LlmProvider::MoonshotKimi | LlmProvider::Mock => {}
+            LlmProvider::GoogleGenerativeAi => return (None, None),
+        }
+
+        let defs = tools.iter()
+            .filter_map(|t| serde_json::to_value(OpenAiTool {
+                type_: t.type_.clone(),
+                function: OpenAiFunction {
+                    name: t.function.name.clone(),
+                    description: t.function.description.clone(),
+                    parameters: t.function.parameters.clone(),
+                },
+            }).ok())
+            .collect();
+        (Some(defs), Some(choice.to_openai()))
+    }
+
     /// Build headers for API requests
     fn build_headers(&self) -> Result<HeaderMap> {
         let mut headers = HeaderMap::new();
@@ -1197,7 +1672,7 @@ impl LlmClient {
                 headers.insert(CONTENT_TYPE, "application/json".parse().context("Invalid content-type header")?);
                 
                 // Anthropic/Claude uses different auth header
-                if provider_type.contains("anthropic") || self.config.base_url.contains("anthropic") {
+                if self.is_anthropic() {
                     if let Some(api_key) = &self.config.api_key {
                         let validated_key = validate_api_key(api_key)?;
                         headers.insert(
@@ -1244,6 +1719,7 @@ impl LlmClient {
                 // API key is included in URL, not headers
                 headers.insert(CONTENT_TYPE, "application/json".parse().context("Invalid content-type header")?);
             }
+            LlmProvider::Mock => {}
         }
 
         Ok(headers)
@@ -1269,10 +1745,10 @@ impl LlmClient {
 
 // OpenAI-compatible API types
 #[derive(Serialize)]
-struct OpenAiRequest<'a> {
+struct OpenAiRequest {
     model: String,
-    #[serde(borrow)]
-    messages: &'a Vec<ChatMessage>,
+    /// Messages in wire format (see `ChatMessage::to_openai_json`)
+    messages: Vec<serde_json::Value>,
     #[serde(rename = "max_completion_tokens")]
     max_completion_tokens: Option<u32>,
     // Note: max_tokens removed - newer models only support max_completion_tokens
@@ -1280,7 +1756,11 @@ struct OpenAiRequest<'a> {
     #[serde(default)]
     stream: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    tools: Option<Vec<OpenAiTool>>,
+    tools: Option<Vec<serde_json::Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parallel_tool_calls: Option<bool>,
 }
 
 #[derive(Serialize)]
@@ -1330,7 +1810,7 @@ struct OpenAiChoice {
 struct OpenAiMessage {
     role: String,
     #[serde(default)]
-    content: Option<String>,
+    content: Option<OpenAiContent>,
     #[serde(default)]
     tool_calls: Option<Vec<OpenAiResponseToolCall>>,
     /// Reasoning content for thinking-enabled models (e.g., Kimi K2.5, DeepSeek)
@@ -1339,6 +1819,54 @@ struct OpenAiMessage {
     reasoning_content: Option<String>,
 }
 
+/// Message content: plain text, or a list of content blocks
+/// (Anthropic-style `text` / `tool_use` blocks)
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum OpenAiContent {
+    Text(String),
+    Blocks(Vec<serde_json::Value>),
+}
+
+/// Rough prompt size of `request` for rate limiting: 3 characters per
+/// token, on the high side
+fn estimate_input_tokens(request: &ChatRequest) -> usize {
+    request.messages.iter().map(|m| m.content.len() / 3 + 1).sum()
+}
+
+/// Mark the static prefix of a request as cacheable (Anthropic `cache_control`)
+///
+/// Breakpoints go on the last tool definition and on the last system message
+/// before the conversation starts, so the provider caches tools and system
+/// prompt once and reads them back on every later turn.
+fn add_cache_breakpoints(messages: &mut [serde_json::Value], tools: Option<&mut [serde_json::Value]>) {
+    let ephemeral = serde_json::json!({ "type": "ephemeral" });
+    if let Some(tool) = tools.and_then(|tools| tools.last_mut()) {
+        tool["cache_control"] = ephemeral.clone();
+    }
+    let system = messages.iter().take_while(|m| m["role"] == "system").count();
+    let Some(message) = system.checked_sub(1).map(|i| &mut messages[i]) else {
+        return;
+    };
+    if let Some(text) = message["content"].as_str().map(str::to_string) {
+        message["content"] = serde_json::json!([{ "type": "text", "text": text, "cache_control": ephemeral }]);
+    }
+}
+
+/// Split content blocks into concatenated text and native tool calls
+fn split_content_blocks(blocks: &[serde_json::Value]) -> (String, Vec<ToolCall>) {
+    let mut text = String::new();
+    let mut calls = Vec::new();
+    for block in blocks {
+        if let Some(call) = ToolCall::from_anthropic_tool_use(block) {
+            calls.push(call);
+        } else if let Some(t) = block.get("text").and_then(|t| t.as_str()) {
+            text.push_str(t);
+        }
+    }
+    (text, calls)
+}
+
 #[derive(Deserialize, Serialize)]
 struct OpenAiResponseToolCall {
     id: String,
@@ -1359,6 +1887,29 @@ struct OpenAiUsage {
     prompt_tokens: u32,
     completion_tokens: u32,
     total_tokens: u32,
+    /// OpenAI, OpenRouter: prompt tokens served from the prompt cache
+    #[serde(default)]
+    prompt_tokens_details: Option<OpenAiPromptTokensDetails>,
+    /// Moonshot reports cache hits at the top level
+    #[serde(default)]
+    cached_tokens: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiPromptTokensDetails {
+    #[serde(default)]
+    cached_tokens: u32,
+}
+
+impl OpenAiUsage {
+    /// Prompt tokens read from the provider's prompt cache
+    fn cached(&self) -> u32 {
+        self.prompt_tokens_details
+            .as_ref()
+            .map(|d| d.cached_tokens)
+            .or(self.cached_tokens)
+            .unwrap_or(0)
+    }
 }
 
 #[derive(Deserialize)]
@@ -1389,6 +1940,61 @@ struct OpenAiDelta {
     #[allow(dead_code)]
     role: Option<String>,
     content: Option<String>,
+    #[serde(default)]
+    tool_calls: Option<Vec<OpenAiStreamToolCall>>,
+}
+
+/// Partial tool call carried by a streaming delta
+#[derive(Deserialize)]
+struct OpenAiStreamToolCall {
+    #[serde(default)]
+    index: usize,
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    function: Option<OpenAiStreamToolFunction>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiStreamToolFunction {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    arguments: Option<String>,
+}
+
+/// Reassembles streamed tool calls from their per-index argument fragments
+#[derive(Default)]
+struct ToolCallAccumulator {
+    calls: std::collections::BTreeMap<usize, (String, String, String)>,
+}
+
+impl ToolCallAccumulator {
+    fn push(&mut self, deltas: &[OpenAiStreamToolCall]) {
+        for delta in deltas {
+            let entry = self.calls.entry(delta.index).or_default();
+            if let Some(ref id) = delta.id {
+                entry.0.push_str(id);
+            }
+            if let Some(ref function) = delta.function {
+                if let Some(ref name) = function.name {
+                    entry.1.push_str(name);
+                }
+                if let Some(ref args) = function.arguments {
+                    entry.2.push_str(args);
+                }
+            }
+        }
+    }
+
+    /// Drain all complete calls in index order
+    fn finish(&mut self) -> Vec<ToolCall> {
+        std::mem::take(&mut self.calls)
+            .into_values()
+            .filter(|(_, name, _)| !name.is_empty())
+            .map(|(id, name, args)| ToolCall::new(id, name, args))
+            .collect()
+    }
 }
 
 // Gemini API types
@@ -1410,7 +2016,33 @@ struct GeminiContent {
 
 #[derive(Serialize, Deserialize, Clone)]
 struct GeminiPart {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     text: String,
+    #[serde(rename = "inlineData", default, skip_serializing_if = "Option::is_none")]
+    inline_data: Option<GeminiInlineData>,
+}
+
+impl GeminiPart {
+    fn text(text: String) -> Self {
+        Self { text, inline_data: None }
+    }
+
+    fn image(image: &ImageAttachment) -> Self {
+        Self {
+            text: String::new(),
+            inline_data: Some(GeminiInlineData {
+                mime_type: image.media_type.clone(),
+                data: image.data.clone(),
+            }),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct GeminiInlineData {
+    mime_type: String,
+    data: String,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -1444,4 +2076,119 @@ struct GeminiUsageMetadata {
     prompt_token_count: u32,
     candidates_token_count: u32,
     total_token_count: u32,
+    #[serde(default)]
+    cached_content_token_count: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Answer one request on a local port with a canned JSON body
+    async fn serve_once(body: &'static str) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 64 * 1024];
+            let _ = socket.read(&mut buf).await;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    /// A local URL nothing is listening on
+    async fn closed_port() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    }
+
+    fn config(base_url: String, model: &str) -> LlmConfig {
+        LlmConfig::new(LlmProvider::OpenAiCompatible, base_url, model.to_string(), None, 8192)
+    }
+
+    #[test]
+    fn test_failover_errors() {
+        let retryable = |status| is_failover_error(&ProviderHttpError::new(status, "x").into());
+        assert!(retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(retryable(StatusCode::BAD_GATEWAY));
+        assert!(!retryable(StatusCode::BAD_REQUEST));
+        assert!(!retryable(StatusCode::UNAUTHORIZED));
+        assert!(!is_failover_error(&anyhow::anyhow!("Context limit exceeded")));
+
+        let wrapped = anyhow::Error::from(ProviderHttpError::new(StatusCode::SERVICE_UNAVAILABLE, "x"))
+            .context("Failed to send request");
+        assert!(is_failover_error(&wrapped));
+    }
+
+    #[tokio::test]
+    async fn test_chat_fails_over_to_next_endpoint() {
+        let fallback_url = serve_once(
+            r#"{"choices":[{"index":0,"message":{"role":"assistant","content":"from fallback"},"finish_reason":"stop"}]}"#,
+        )
+        .await;
+        let primary = config(closed_port().await, "primary")
+            .with_fallbacks(vec![config(fallback_url, "local")]);
+        let client = LlmClient::new(primary).unwrap();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        client.set_failover_callback(Arc::new(move |event: &crate::provider::FailoverEvent| {
+            sink.lock().push(event.clone());
+        }));
+
+        let request = ChatRequest::new("primary".to_string(), vec![ChatMessage::user("hi")]);
+        let response = client.chat(&request).await.unwrap();
+        assert_eq!(response.choices[0].message.content, "from fallback");
+
+        let events = events.lock();
+        assert_eq!(events.len(), 1);
+        assert!(events[0].from.starts_with("primary@"));
+        assert!(events[0].to.starts_with("local@"));
+    }
+
+    #[tokio::test]
+    async fn test_claude_prefix_is_cacheable_and_cache_hits_are_reported() {
+        let request = ChatRequest::new(
+            String::new(),
+            vec![ChatMessage::system("rules"), ChatMessage::user("hi")],
+        );
+        let claude = LlmClient::new(config(closed_port().await, "anthropic/claude-sonnet-4")).unwrap();
+        let body = serde_json::to_value(claude.openai_body(&request, false)).unwrap();
+        assert_eq!(body["messages"][0]["content"][0]["text"], "rules");
+        assert_eq!(body["messages"][0]["content"][0]["cache_control"]["type"], "ephemeral");
+        assert_eq!(body["messages"][1]["content"], "hi");
+        // Other providers cache on their own and get the prompt unchanged
+        let gpt = LlmClient::new(config(closed_port().await, "gpt-4o")).unwrap();
+        let body = serde_json::to_value(gpt.openai_body(&request, false)).unwrap();
+        assert_eq!(body["messages"][0]["content"], "rules");
+
+        let url = serve_once(
+            r#"{"choices":[{"index":0,"message":{"role":"assistant","content":"ok"}}],"usage":{"prompt_tokens":2000,"completion_tokens":5,"total_tokens":2005,"prompt_tokens_details":{"cached_tokens":1536}}}"#,
+        )
+        .await;
+        let client = LlmClient::new(config(url, "gpt-4o")).unwrap();
+        let usage = client.chat(&request).await.unwrap().usage.unwrap();
+        assert_eq!((usage.prompt_tokens, usage.cached_tokens), (2000, 1536));
+    }
+
+    #[tokio::test]
+    async fn test_native_tools_use_the_chat_completions_shape() {
+        let request = ChatRequest::new(String::new(), vec![ChatMessage::user("hi")])
+            .with_tools(vec![super::super::chat::ChatTool::function("shell", "Run a command", serde_json::json!({"type": "object"}))])
+            .with_tool_choice(ToolChoice::Required);
+        let anthropic = config("https://api.anthropic.com/v1".to_string(), "claude-sonnet-4").with_native_tool_calling(true);
+        let body = serde_json::to_value(LlmClient::new(anthropic).unwrap().openai_body(&request, false)).unwrap();
+        assert_eq!(body["tools"][0]["type"], "function");
+        assert_eq!(body["tools"][0]["function"]["name"], "shell");
+        assert!(body["tools"][0].get("input_schema").is_none());
+        assert_eq!(body["tool_choice"], "required");
+        assert_eq!(body["parallel_tool_calls"], false);
+    }
 }
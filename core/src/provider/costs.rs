@@ -0,0 +1,152 @@
+//! Spend ledger built from the usage log
+//!
+//! Every `UsageRecord` carries the cost of its request at the prices of the
+//! endpoint that answered (`input_price`/`output_price` on the profile, or
+//! on a fallback endpoint). `ledger` totals the records per day or ISO week,
+//! per profile and per model; `mylm costs` prints the tables.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Days, NaiveTime, TimeZone};
+
+use super::usage::UsageRecord;
+
+/// Length of a ledger period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Day,
+    /// ISO week, Monday to Sunday
+    Week,
+}
+
+impl Period {
+    /// Label of the period holding Unix time `at` in `tz`, e.g. `2026-10-16`
+    /// or `2026-W42`
+    pub fn label<Tz: TimeZone>(&self, at: u64, tz: &Tz) -> String {
+        let date = DateTime::from_timestamp(at as i64, 0)
+            .unwrap_or_default()
+            .with_timezone(tz)
+            .date_naive();
+        match self {
+            Period::Day => date.format("%Y-%m-%d").to_string(),
+            Period::Week => {
+                let week = date.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+        }
+    }
+
+    /// Unix time at which the latest `count` periods up to `now` begin
+    pub fn start_of_last<Tz: TimeZone>(&self, count: u32, now: u64, tz: &Tz) -> u64 {
+        let today = DateTime::from_timestamp(now as i64, 0)
+            .unwrap_or_default()
+            .with_timezone(tz)
+            .date_naive();
+        let earlier = u64::from(count.saturating_sub(1));
+        let first = match self {
+            Period::Day => today - Days::new(earlier),
+            Period::Week => today - Days::new(u64::from(today.weekday().num_days_from_monday()) + 7 * earlier),
+        };
+        tz.from_local_datetime(&first.and_time(NaiveTime::MIN))
+            .earliest()
+            .map(|start| start.timestamp().max(0) as u64)
+            .unwrap_or(0)
+    }
+}
+
+/// Spend of one profile and model in one period
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostLine {
+    pub period: String,
+    /// `-` for records made before profiles were logged
+    pub profile: String,
+    pub model: String,
+    pub requests: usize,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// USD
+    pub cost: f64,
+}
+
+/// Totals per period, profile and model: newest period first, and the
+/// costliest lines first within a period
+pub fn ledger<Tz: TimeZone>(records: &[UsageRecord], period: Period, tz: &Tz) -> Vec<CostLine> {
+    let mut lines: HashMap<(String, &str, &str), CostLine> = HashMap::new();
+    for record in records {
+        let label = period.label(record.at, tz);
+        let profile = record.profile.as_deref().unwrap_or("-");
+        let line = lines
+            .entry((label.clone(), profile, &record.model))
+            .or_insert_with(|| CostLine {
+                period: label,
+                profile: profile.to_string(),
+                model: record.model.clone(),
+                requests: 0,
+                prompt_tokens: 0,
+                completion_tokens: 0,
+                cost: 0.0,
+            });
+        line.requests += 1;
+        line.prompt_tokens += u64::from(record.prompt_tokens);
+        line.completion_tokens += u64::from(record.completion_tokens);
+        line.cost += record.cost;
+    }
+    let mut lines: Vec<CostLine> = lines.into_values().collect();
+    lines.sort_by(|a, b| {
+        b.period
+            .cmp(&a.period)
+            .then(b.cost.total_cmp(&a.cost))
+            .then_with(|| (&a.profile, &a.model).cmp(&(&b.profile, &b.model)))
+    });
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ModelRole;
+
+    fn record(at: u64, profile: Option<&str>, model: &str, cost: f64) -> UsageRecord {
+        UsageRecord {
+            at,
+            role: ModelRole::Main,
+            profile: profile.map(str::to_string),
+            model: model.to_string(),
+            prompt_tokens: 1000,
+            completion_tokens: 100,
+            cost,
+            ok: true,
+        }
+    }
+
+    #[test]
+    fn test_ledger_totals_per_period_profile_and_model() {
+        // Friday 2026-10-16 12:00 UTC
+        let friday = 1_792_152_000;
+        let day = 24 * 60 * 60;
+        assert_eq!(Period::Day.label(friday, &chrono::Utc), "2026-10-16");
+        assert_eq!(Period::Week.label(friday, &chrono::Utc), "2026-W42");
+        assert_eq!(Period::Day.start_of_last(2, friday, &chrono::Utc), friday - day - 12 * 60 * 60);
+        // Back to Monday of the previous week
+        assert_eq!(Period::Week.start_of_last(2, friday, &chrono::Utc), friday - 11 * day - 12 * 60 * 60);
+
+        let records = vec![
+            record(friday, Some("default"), "gpt-4o", 0.02),
+            record(friday + 60, Some("default"), "gpt-4o", 0.03),
+            record(friday + 120, Some("worker"), "gpt-4o-mini", 0.001),
+            record(friday - day, None, "gpt-4o", 0.5),
+        ];
+        let daily = ledger(&records, Period::Day, &chrono::Utc);
+        assert_eq!(daily.len(), 3);
+        assert_eq!((daily[0].period.as_str(), daily[0].profile.as_str()), ("2026-10-16", "default"));
+        assert_eq!((daily[0].requests, daily[0].prompt_tokens), (2, 2000));
+        assert!((daily[0].cost - 0.05).abs() < 1e-9);
+        assert_eq!(daily[1].model, "gpt-4o-mini");
+        assert_eq!((daily[2].period.as_str(), daily[2].profile.as_str()), ("2026-10-15", "-"));
+
+        let weekly = ledger(&records, Period::Week, &chrono::Utc);
+        assert_eq!(weekly.len(), 3);
+        assert!(weekly.iter().all(|line| line.period == "2026-W42"));
+        assert_eq!(weekly[0].profile, "-");
+    }
+}
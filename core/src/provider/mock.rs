@@ -0,0 +1,356 @@
+//! Built-in mock LLM provider for integration tests
+//!
+//! A provider with `type = "mock"` answers from a script instead of a
+//! network API, so CI and downstream users can run the whole agent loop
+//! without keys or a server. The provider's `base_url` is the path of a TOML
+//! script; without one the mock echoes the last user message.
+//!
+//! ```toml
+//! latency_ms = 20               # added to every response
+//!
+//! [[responses]]
+//! content = '{"t": "Checking the tree", "a": "execute_command", "i": "ls"}'
+//!
+//! [[responses]]
+//! error = 429                   # fail like a rate-limited endpoint
+//!
+//! [[responses]]
+//! content = '{"f": "Two files."}'
+//! latency_ms = 500              # this response only
+//! ```
+//!
+//! Responses are served in order; once the script runs out the mock echoes,
+//! or starts over with `repeat = true`. Every request is recorded so tests
+//! can assert on what the agent sent.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use futures::Stream;
+use parking_lot::Mutex;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::pin::Pin;
+
+use crate::provider::chat::{ChatMessage, ChatRequest, ChatResponse, Choice, MessageRole, StreamEvent, ToolCall, Usage};
+use crate::provider::{ProviderHttpError, TokenUsage};
+
+/// Content chunks a streamed mock response is split into, at most
+const STREAM_CHUNKS: usize = 8;
+
+/// Responses the mock provider serves, in order
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MockScript {
+    /// Delay before every response, in milliseconds
+    #[serde(default)]
+    pub latency_ms: u64,
+    /// Start over from the first response once the script runs out,
+    /// instead of echoing
+    #[serde(default)]
+    pub repeat: bool,
+    #[serde(default)]
+    pub responses: Vec<MockResponse>,
+}
+
+impl MockScript {
+    /// A script answering with `contents` in order
+    pub fn replies<I, S>(contents: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            responses: contents.into_iter().map(MockResponse::reply).collect(),
+            ..Self::default()
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Cannot read mock script {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Invalid mock script {}", path.display()))
+    }
+
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency_ms = latency.as_millis() as u64;
+        self
+    }
+
+    pub fn then(mut self, response: MockResponse) -> Self {
+        self.responses.push(response);
+        self
+    }
+}
+
+/// One scripted response
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MockResponse {
+    /// Text of the reply
+    #[serde(default)]
+    pub content: String,
+    /// Native tool calls in the reply
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<MockToolCall>,
+    /// Fail with this HTTP status instead of replying
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<u16>,
+    /// Delay for this response, in addition to the script's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+}
+
+impl MockResponse {
+    pub fn reply(content: impl Into<String>) -> Self {
+        Self {
+            content: content.into(),
+            ..Self::default()
+        }
+    }
+
+    /// A failure with the given HTTP status (429 and 5xx trigger failover)
+    pub fn error(status: u16) -> Self {
+        Self {
+            error: Some(status),
+            ..Self::default()
+        }
+    }
+
+    pub fn tool_call(name: impl Into<String>, arguments: serde_json::Value) -> Self {
+        Self {
+            tool_calls: vec![MockToolCall {
+                name: name.into(),
+                arguments,
+            }],
+            ..Self::default()
+        }
+    }
+
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency_ms = Some(latency.as_millis() as u64);
+        self
+    }
+}
+
+/// A native tool call in a scripted response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MockToolCall {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: serde_json::Value,
+}
+
+/// Scripted provider shared by an [`LlmClient`](crate::provider::LlmClient)
+/// and the test driving it
+#[derive(Debug, Default)]
+pub struct MockProvider {
+    script: MockScript,
+    served: AtomicUsize,
+    requests: Mutex<Vec<ChatRequest>>,
+}
+
+impl MockProvider {
+    pub fn new(script: MockScript) -> Self {
+        Self {
+            script,
+            ..Self::default()
+        }
+    }
+
+    /// The provider for a `mock` config: the script at `base_url`, or an
+    /// echo when it is empty
+    pub fn from_base_url(base_url: &str) -> Result<Self> {
+        let path = base_url.trim().trim_start_matches("mock://");
+        if path.is_empty() {
+            return Ok(Self::default());
+        }
+        MockScript::load(Path::new(path)).map(Self::new)
+    }
+
+    /// Requests received so far, oldest first
+    pub fn requests(&self) -> Vec<ChatRequest> {
+        self.requests.lock().clone()
+    }
+
+    /// Number of requests received so far
+    pub fn calls(&self) -> usize {
+        self.requests.lock().len()
+    }
+
+    /// Record `request` and wait out the latency of its response
+    async fn next(&self, request: &ChatRequest) -> Result<MockResponse> {
+        self.requests.lock().push(request.clone());
+        let index = self.served.fetch_add(1, Ordering::SeqCst);
+        let scripted = match self.script.responses.len() {
+            0 => None,
+            len if self.script.repeat => self.script.responses.get(index % len),
+            _ => self.script.responses.get(index),
+        };
+        let response = scripted.cloned().unwrap_or_else(|| echo(request));
+
+        let delay = self.script.latency_ms + response.latency_ms.unwrap_or(0);
+        if delay > 0 {
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+        }
+        if let Some(status) = response.error {
+            let status = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(ProviderHttpError {
+                status,
+                message: format!("Mock provider error: {}", status),
+            }
+            .into());
+        }
+        Ok(response)
+    }
+
+    pub async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse> {
+        let response = self.next(request).await?;
+        let usage = usage(request, &response);
+        let tool_calls = tool_calls(&response);
+        let mut message = ChatMessage::assistant(response.content);
+        let finish_reason = if tool_calls.is_empty() { "stop" } else { "tool_calls" };
+        if !tool_calls.is_empty() {
+            message.tool_calls = Some(tool_calls);
+        }
+        Ok(ChatResponse {
+            id: format!("mock-{}", self.calls()),
+            object: "chat.completion".to_string(),
+            created: chrono::Utc::now().timestamp() as u64,
+            model: request.model.clone(),
+            choices: vec![Choice {
+                index: 0,
+                message,
+                finish_reason: Some(finish_reason.to_string()),
+            }],
+            usage: Some(Usage {
+                prompt_tokens: usage.prompt_tokens,
+                completion_tokens: usage.completion_tokens,
+                total_tokens: usage.total_tokens,
+                cached_tokens: usage.cached_tokens,
+            }),
+        })
+    }
+
+    /// Stream the next response in a few content chunks; a scripted error
+    /// fails before the first event, like a rejected HTTP request
+    pub fn chat_stream<'a>(
+        &'a self,
+        request: &'a ChatRequest,
+    ) -> Pin<Box<dyn Stream<Item = Result<StreamEvent>> + Send + 'a>> {
+        Box::pin(async_stream::try_stream! {
+            let response = self.next(request).await?;
+            for chunk in chunks(&response.content) {
+                yield StreamEvent::Content(chunk);
+            }
+            for call in tool_calls(&response) {
+                yield StreamEvent::ToolCall(call);
+            }
+            yield StreamEvent::Usage(usage(request, &response));
+            yield StreamEvent::Done;
+        })
+    }
+}
+
+/// Reply with the last user message, for unscripted requests
+fn echo(request: &ChatRequest) -> MockResponse {
+    let last = request
+        .messages
+        .iter()
+        .rev()
+        .find(|m| m.role == MessageRole::User)
+        .map(|m| m.content.as_str())
+        .unwrap_or_default();
+    MockResponse::reply(format!("Echo: {}", last))
+}
+
+fn tool_calls(response: &MockResponse) -> Vec<ToolCall> {
+    response
+        .tool_calls
+        .iter()
+        .enumerate()
+        .map(|(i, call)| ToolCall::new(format!("mock_call_{}", i), &call.name, call.arguments.to_string()))
+        .collect()
+}
+
+/// Token counts estimated at four characters per token
+fn usage(request: &ChatRequest, response: &MockResponse) -> TokenUsage {
+    let prompt: usize = request.messages.iter().map(|m| m.content.len()).sum();
+    let prompt_tokens = (prompt / 4) as u32;
+    let completion_tokens = (response.content.len() / 4) as u32;
+    TokenUsage {
+        prompt_tokens,
+        completion_tokens,
+        total_tokens: prompt_tokens + completion_tokens,
+        cached_tokens: 0,
+    }
+}
+
+/// `content` split at char boundaries into at most `STREAM_CHUNKS` pieces
+fn chunks(content: &str) -> Vec<String> {
+    let chars: Vec<char> = content.chars().collect();
+    if chars.is_empty() {
+        return Vec::new();
+    }
+    let size = chars.len().div_ceil(STREAM_CHUNKS);
+    chars.chunks(size).map(|c| c.iter().collect()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::{LlmClient, LlmConfig, LlmProvider};
+    use futures::StreamExt;
+    use std::sync::Arc;
+
+    fn request(text: &str) -> ChatRequest {
+        ChatRequest::new("mock-model".to_string(), vec![ChatMessage::user(text)])
+    }
+
+    #[tokio::test]
+    async fn test_script_is_served_in_order_then_echoes() {
+        let mock = MockProvider::new(
+            MockScript::replies(["first"])
+                .then(MockResponse::tool_call("read_file", serde_json::json!({"path": "Cargo.toml"})))
+                .then(MockResponse::error(500)),
+        );
+
+        assert_eq!(mock.chat(&request("a")).await.unwrap().content(), "first");
+        let calls = mock.chat(&request("b")).await.unwrap().tool_calls();
+        assert_eq!(calls[0].function.name, "read_file");
+        assert_eq!(calls[0].parsed_arguments()["path"], "Cargo.toml");
+        let err = mock.chat(&request("c")).await.unwrap_err();
+        assert_eq!(err.downcast_ref::<ProviderHttpError>().unwrap().status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(mock.chat(&request("d")).await.unwrap().content(), "Echo: d");
+
+        let sent: Vec<String> = mock.requests().iter().map(|r| r.messages[0].content.clone()).collect();
+        assert_eq!(sent, ["a", "b", "c", "d"]);
+    }
+
+    #[tokio::test]
+    async fn test_stream_reassembles_content() {
+        let mock = MockProvider::new(MockScript::replies(["streamed reply with ünïcode"]));
+        let events: Vec<StreamEvent> = mock
+            .chat_stream(&request("hi"))
+            .map(|e| e.unwrap())
+            .collect()
+            .await;
+        let text: String = events.iter().filter_map(|e| e.content()).collect();
+        assert_eq!(text, "streamed reply with ünïcode");
+        assert!(events.last().unwrap().is_done());
+    }
+
+    #[tokio::test]
+    async fn test_injected_rate_limit_fails_over() {
+        let primary = Arc::new(MockProvider::new(MockScript::default().then(MockResponse::error(429))));
+        let config = |model: &str| LlmConfig::new(LlmProvider::Mock, String::new(), model.to_string(), None, 8192);
+
+        // The fallback has no script, so it echoes
+        let client = LlmClient::new(config("primary").with_fallbacks(vec![config("backup")]))
+            .unwrap()
+            .with_mock(primary.clone());
+        let response = client.chat(&request("hello")).await.unwrap();
+        assert_eq!(response.content(), "Echo: hello");
+        assert_eq!(primary.calls(), 1);
+    }
+}
@@ -4,13 +4,23 @@
 //! - OpenAI-compatible API (OpenAI, Ollama, LM Studio, local models)
 //! - Google Generative AI (Gemini)
 //! - Moonshot AI (Kimi)
+//! - A scripted mock provider for integration tests
 
+pub mod cache;
 pub mod client;
+pub mod costs;
 pub mod chat;
+pub mod mock;
 pub mod rate_limiter;
+pub mod pipeline;
+pub mod usage;
 
-pub use client::{LlmClient, LlmProvider};
-pub use chat::ChatResponse;
+pub use cache::ResponseCache;
+pub use client::{LlmClient, LlmProvider, ProviderHttpError};
+pub use chat::{ChatResponse, ToolCall, ToolChoice};
+pub use mock::{MockProvider, MockResponse, MockScript};
+pub use pipeline::{FollowUpKind, FollowUpPipeline, FollowUpResult};
+pub use usage::{ModelCall, ModelRole, UsageCallback, UsageLog};
 
 use anyhow::{Context, Result};
 use std::sync::Arc;
@@ -18,6 +28,20 @@ use std::sync::Arc;
 /// Callback type for status updates from the LLM client
 /// Used to report retry attempts, rate limiting, etc. to the UI
 pub type StatusCallback = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Callback type for fallback-chain failovers, used to feed telemetry
+pub type FailoverCallback = Arc<dyn Fn(&FailoverEvent) + Send + Sync>;
+
+/// A request moved from one endpoint to the next in the fallback chain
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailoverEvent {
+    /// Endpoint that failed, as `model@base_url`
+    pub from: String,
+    /// Endpoint the request was retried on
+    pub to: String,
+    /// Error that triggered the failover
+    pub reason: String,
+}
 use std::collections::HashMap;
 
 /// LLM Configuration
@@ -51,6 +75,16 @@ pub struct LlmConfig {
     pub extra_params: HashMap<String, String>,
     /// Enable web search for providers that support it (e.g., Kimi)
     pub web_search_enabled: bool,
+    /// Send tool definitions through the provider's native function-calling API
+    pub native_tool_calling: bool,
+    /// Mark the system prompt and tool definitions as cacheable where the
+    /// provider needs explicit `cache_control` breakpoints
+    pub prompt_cache_hints: bool,
+    /// Endpoints tried in order when this one fails with 429, 5xx or a
+    /// network error
+    pub fallbacks: Vec<LlmConfig>,
+    /// Answer repeated requests from this on-disk cache
+    pub response_cache: Option<ResponseCache>,
 }
 
 impl LlmConfig {
@@ -77,6 +111,10 @@ impl LlmConfig {
             memory: crate::config::MemoryConfig::default(),
             extra_params: HashMap::new(),
             web_search_enabled: false,
+            native_tool_calling: false,
+            prompt_cache_hints: true,
+            fallbacks: Vec::new(),
+            response_cache: None,
         }
     }
 
@@ -125,6 +163,24 @@ impl LlmConfig {
         self.web_search_enabled = enabled;
         self
     }
+
+    /// Enable native (structured) tool calling
+    pub fn with_native_tool_calling(mut self, enabled: bool) -> Self {
+        self.native_tool_calling = enabled;
+        self
+    }
+
+    /// Set the failover chain
+    pub fn with_fallbacks(mut self, fallbacks: Vec<LlmConfig>) -> Self {
+        self.fallbacks = fallbacks;
+        self
+    }
+
+    /// Set the response cache
+    pub fn with_response_cache(mut self, cache: Option<ResponseCache>) -> Self {
+        self.response_cache = cache;
+        self
+    }
 }
 
 /// Token usage information
@@ -133,6 +189,9 @@ pub struct TokenUsage {
     pub prompt_tokens: u32,
     pub completion_tokens: u32,
     pub total_tokens: u32,
+    /// Prompt tokens served from the provider's prompt cache
+    #[serde(default)]
+    pub cached_tokens: u32,
 }
 
 /// Convert TokenUsage to a display string
@@ -140,9 +199,13 @@ impl std::fmt::Display for TokenUsage {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Tokens: {} (prompt: {}, completion: {})",
+            "Tokens: {} (prompt: {}, completion: {}",
             self.total_tokens, self.prompt_tokens, self.completion_tokens
-        )
+        )?;
+        if self.cached_tokens > 0 {
+            write!(f, ", cached: {}", self.cached_tokens)?;
+        }
+        write!(f, ")")
     }
 }
 
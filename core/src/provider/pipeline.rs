@@ -0,0 +1,203 @@
+//! Pipelining for secondary LLM calls
+//!
+//! Some turns need follow-up requests whose results never feed back into the
+//! main answer (session titles, memory categorization). Running them serially
+//! once the turn ends adds their full latency to every exchange.
+//!
+//! `FollowUpPipeline` starts them as soon as their inputs are known and lets
+//! the UI pick up results from its render tick with `poll_ready`, so the user
+//! never waits on them.
+
+use crate::provider::chat::{ChatMessage, ChatRequest};
+use crate::provider::LlmClient;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+
+/// Maximum characters kept from a generated title
+const MAX_TITLE_CHARS: usize = 60;
+
+/// Characters of each message sent to the title prompt
+const TITLE_INPUT_CHARS: usize = 1000;
+
+/// What a secondary call is for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FollowUpKind {
+    /// Short title for the current session
+    Title,
+    /// Caller-defined follow-up
+    Custom(String),
+}
+
+/// Outcome of a finished secondary call
+#[derive(Debug, Clone)]
+pub struct FollowUpResult {
+    pub kind: FollowUpKind,
+    /// Response text, or the error message if the call failed
+    pub result: Result<String, String>,
+    /// Wall time of the call
+    pub elapsed: Duration,
+}
+
+/// Runs secondary LLM calls concurrently with the main turn
+pub struct FollowUpPipeline {
+    client: Arc<LlmClient>,
+    tasks: JoinSet<FollowUpResult>,
+}
+
+impl FollowUpPipeline {
+    /// Create a pipeline that issues calls through `client`
+    pub fn new(client: Arc<LlmClient>) -> Self {
+        Self {
+            client,
+            tasks: JoinSet::new(),
+        }
+    }
+
+    /// Start a secondary call immediately
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub fn spawn(&mut self, kind: FollowUpKind, request: ChatRequest) {
+        let client = self.client.clone();
+        crate::debug_log!("[PIPELINE] Spawning follow-up {:?}", kind);
+        self.tasks.spawn(async move {
+            let start = Instant::now();
+            let result = client
+                .chat(&request)
+                .await
+                .map(|response| response.content())
+                .map_err(|e| e.to_string());
+            FollowUpResult {
+                kind,
+                result,
+                elapsed: start.elapsed(),
+            }
+        });
+    }
+
+    /// Start title generation for a session from its first exchange
+    pub fn spawn_title(&mut self, user_message: &str, assistant_message: &str) {
+        let request = title_request(self.client.model(), user_message, assistant_message);
+        self.spawn(FollowUpKind::Title, request);
+    }
+
+    /// Number of calls still in flight
+    pub fn pending(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Collect finished calls without waiting
+    pub fn poll_ready(&mut self) -> Vec<FollowUpResult> {
+        let mut ready = Vec::new();
+        while let Some(joined) = self.tasks.try_join_next() {
+            if let Some(result) = Self::unwrap_joined(joined) {
+                ready.push(result);
+            }
+        }
+        ready
+    }
+
+    /// Wait for every call in flight
+    pub async fn drain(&mut self) -> Vec<FollowUpResult> {
+        let mut results = Vec::new();
+        while let Some(joined) = self.tasks.join_next().await {
+            if let Some(result) = Self::unwrap_joined(joined) {
+                results.push(result);
+            }
+        }
+        results
+    }
+
+    fn unwrap_joined(
+        joined: Result<FollowUpResult, tokio::task::JoinError>,
+    ) -> Option<FollowUpResult> {
+        match joined {
+            Ok(result) => {
+                crate::debug_log!(
+                    "[PIPELINE] Follow-up {:?} finished in {}ms",
+                    result.kind,
+                    result.elapsed.as_millis()
+                );
+                Some(result)
+            }
+            Err(e) => {
+                crate::warn_log!("[PIPELINE] Follow-up task failed: {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// Build the request used to title a session
+pub fn title_request(model: &str, user_message: &str, assistant_message: &str) -> ChatRequest {
+    let user: String = user_message.chars().take(TITLE_INPUT_CHARS).collect();
+    let assistant: String = assistant_message.chars().take(TITLE_INPUT_CHARS).collect();
+    let prompt = format!(
+        "Write a title of at most six words for the conversation below. \
+        Respond with only the title, no quotes or punctuation at the end.\n\n\
+        User: {}\n\nAssistant: {}",
+        user, assistant
+    );
+    ChatRequest::new(
+        model.to_string(),
+        vec![
+            ChatMessage::system("You name conversations with short, specific titles."),
+            ChatMessage::user(prompt),
+        ],
+    )
+    .with_max_tokens(24)
+}
+
+/// Normalize a model-generated title
+///
+/// Takes the first non-empty line, strips quotes, a `Title:` prefix and
+/// trailing punctuation, and caps the length. Returns None if nothing is left.
+pub fn clean_title(raw: &str) -> Option<String> {
+    let line = raw.lines().map(str::trim).find(|l| !l.is_empty())?;
+    let line = line
+        .strip_prefix("Title:")
+        .or_else(|| line.strip_prefix("title:"))
+        .unwrap_or(line)
+        .trim();
+    let title = line
+        .trim_matches(|c: char| c == '"' || c == '\'' || c == '*' || c == '`')
+        .trim_end_matches(['.', '!', ':'])
+        .trim();
+    if title.is_empty() {
+        return None;
+    }
+    let capped: String = title.chars().take(MAX_TITLE_CHARS).collect();
+    Some(capped.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_title_strips_decoration() {
+        assert_eq!(clean_title("\"Fixing the build.\""), Some("Fixing the build".to_string()));
+        assert_eq!(clean_title("Title: Rust lifetimes"), Some("Rust lifetimes".to_string()));
+        assert_eq!(clean_title("\n\n  **Docker setup**\nextra"), Some("Docker setup".to_string()));
+    }
+
+    #[test]
+    fn test_clean_title_rejects_empty() {
+        assert_eq!(clean_title(""), None);
+        assert_eq!(clean_title("  \"\" "), None);
+    }
+
+    #[test]
+    fn test_clean_title_caps_length() {
+        let long = "word ".repeat(40);
+        assert!(clean_title(&long).unwrap().chars().count() <= MAX_TITLE_CHARS);
+    }
+
+    #[test]
+    fn test_title_request_truncates_inputs() {
+        let long = "x".repeat(5000);
+        let req = title_request("model", &long, "answer");
+        assert_eq!(req.model, "model");
+        assert!(req.messages[1].content.len() < 2 * TITLE_INPUT_CHARS + 300);
+    }
+}
@@ -2,6 +2,17 @@
 //!
 //! Provides per-endpoint rate limiting with separate quotas for main agent and workers.
 //! Respects Retry-After headers from providers.
+//!
+//! On top of the per-agent quotas, a `RateLimiter` can hold global request
+//! and token budgets shared by the main agent and all workers
+//! (`features.rate_limits`). Token use is reserved from an estimate and
+//! settled with the usage the provider reports. `wait_for_budget` queues
+//! requests until the last minute's use leaves room instead of failing.
+//!
+//! Providers also say how long to hold off: `Retry-After` on a 429 and
+//! `x-ratelimit-remaining-*`/`x-ratelimit-reset-*` on every response.
+//! `RateLimitHeaders` reads them so waits match the provider's instead of
+//! a blind exponential backoff.
 
 use parking_lot::Mutex;
 use std::collections::HashMap;
@@ -10,6 +21,11 @@ use std::time::{Duration, Instant};
 use tokio::sync::Semaphore;
 use tokio::time::sleep;
 
+use crate::config::RateLimitSettings;
+
+/// Sliding window the limits apply to
+const WINDOW: Duration = Duration::from_secs(60);
+
 /// Configuration for rate limiting
 #[derive(Debug, Clone)]
 pub struct RateLimitConfig {
@@ -23,6 +39,12 @@ pub struct RateLimitConfig {
     pub workers_tpm: u32,
     /// Burst allowance (requests that can exceed RPM temporarily)
     pub burst_size: u32,
+    /// Requests per minute across main agent and workers
+    pub global_rpm: Option<u32>,
+    /// Tokens per minute across main agent and workers
+    pub global_tpm: Option<u32>,
+    /// Longest `wait_for_budget` queues a request
+    pub max_queue_wait: Duration,
 }
 
 impl Default for RateLimitConfig {
@@ -33,10 +55,19 @@ impl Default for RateLimitConfig {
             main_tpm: 100_000,
             workers_tpm: 50_000,
             burst_size: 3,
+            global_rpm: None,
+            global_tpm: None,
+            max_queue_wait: DEFAULT_MAX_QUEUE_WAIT,
         }
     }
 }
 
+/// Default for `RateLimitConfig::max_queue_wait`
+const DEFAULT_MAX_QUEUE_WAIT: Duration = Duration::from_secs(120);
+
+/// Longest wait a rate-limit header can impose
+const MAX_HEADER_WAIT: Duration = Duration::from_secs(24 * 3600);
+
 impl RateLimitConfig {
     /// Load from config file or environment
     pub fn from_settings(main_rpm: Option<u32>, workers_rpm: Option<u32>) -> Self {
@@ -50,6 +81,24 @@ impl RateLimitConfig {
         config
     }
 
+    /// Only the global budgets of `settings`, without per-agent quotas;
+    /// None when neither budget is set
+    pub fn shared_budget(settings: &RateLimitSettings) -> Option<Self> {
+        if settings.requests_per_minute.is_none() && settings.tokens_per_minute.is_none() {
+            return None;
+        }
+        Some(Self {
+            main_rpm: u32::MAX,
+            workers_rpm: u32::MAX,
+            main_tpm: u32::MAX,
+            workers_tpm: u32::MAX,
+            burst_size: 100,
+            global_rpm: settings.requests_per_minute,
+            global_tpm: settings.tokens_per_minute,
+            max_queue_wait: Duration::from_secs(settings.max_wait_secs),
+        })
+    }
+
     /// Conservative limits for basic tier providers (default)
     pub fn conservative() -> Self {
         Self {
@@ -58,6 +107,9 @@ impl RateLimitConfig {
             main_tpm: 100_000,
             workers_tpm: 50_000,
             burst_size: 3,
+            global_rpm: None,
+            global_tpm: None,
+            max_queue_wait: DEFAULT_MAX_QUEUE_WAIT,
         }
     }
 
@@ -69,6 +121,9 @@ impl RateLimitConfig {
             main_tpm: 250_000,
             workers_tpm: 500_000,
             burst_size: 10,
+            global_rpm: None,
+            global_tpm: None,
+            max_queue_wait: DEFAULT_MAX_QUEUE_WAIT,
         }
     }
 
@@ -80,6 +135,9 @@ impl RateLimitConfig {
             main_tpm: 1_000_000,
             workers_tpm: 5_000_000,
             burst_size: 25,
+            global_rpm: None,
+            global_tpm: None,
+            max_queue_wait: DEFAULT_MAX_QUEUE_WAIT,
         }
     }
 
@@ -91,6 +149,9 @@ impl RateLimitConfig {
             main_tpm: 5_000_000,
             workers_tpm: 50_000_000,
             burst_size: 100,
+            global_rpm: None,
+            global_tpm: None,
+            max_queue_wait: DEFAULT_MAX_QUEUE_WAIT,
         }
     }
 
@@ -215,6 +276,13 @@ impl EndpointState {
         }
     }
 
+    /// Hold off requests for a specific agent type for `wait`
+    fn block_for(&mut self, is_worker: bool, wait: Duration) {
+        let circuit = self.circuit(is_worker);
+        let until = Instant::now() + wait;
+        circuit.blocked_until = Some(circuit.blocked_until.map_or(until, |blocked| blocked.max(until)));
+    }
+
     /// Record a rate limit hit (429 error) for a specific agent type
     fn record_rate_limit(&mut self, is_worker: bool, retry_after: Option<Duration>) {
         let circuit = self.circuit(is_worker);
@@ -248,10 +316,9 @@ impl EndpointState {
         self.token_usage(is_worker).push((Instant::now(), tokens));
     }
 
-    /// Correct usage based on actual token count
-    fn correct_usage(&mut self, excess_tokens: u32, is_worker: bool) {
-        // Just record the excess as a new usage event at current time
-        self.record_tokens(excess_tokens, is_worker);
+    /// Replace the estimate recorded for a request with the actual count
+    fn correct_usage(&mut self, estimated_tokens: u32, actual_tokens: u32, is_worker: bool) {
+        settle(self.token_usage(is_worker), estimated_tokens, actual_tokens);
     }
 
     /// Get current RPM for specific agent type
@@ -295,6 +362,63 @@ impl EndpointState {
     }
 }
 
+/// Requests and tokens of the last minute across endpoints and agent types
+#[derive(Debug, Default)]
+struct GlobalWindow {
+    request_times: Vec<Instant>,
+    token_usage: Vec<(Instant, u32)>,
+}
+
+impl GlobalWindow {
+    fn cleanup_old_entries(&mut self) {
+        let cutoff = Instant::now() - WINDOW;
+        self.request_times.retain(|&t| t > cutoff);
+        self.token_usage.retain(|(t, _)| *t > cutoff);
+    }
+}
+
+/// Time until one more request fits under `limit`, given the `times` of
+/// the requests still in the window (oldest first)
+fn wait_for_request(times: &[Instant], limit: u32) -> Duration {
+    let limit = limit.max(1) as usize;
+    if times.len() < limit {
+        return Duration::ZERO;
+    }
+    (times[times.len() - limit] + WINDOW).saturating_duration_since(Instant::now())
+}
+
+/// Time until `needed` more tokens fit under `limit` as the oldest entries
+/// of `usage` leave the window. A request larger than the whole budget
+/// only needs an empty window.
+fn wait_for_tokens(usage: &[(Instant, u32)], needed: u32, limit: u32) -> Duration {
+    let needed = u64::from(needed.min(limit));
+    let limit = u64::from(limit);
+    let mut used: u64 = usage.iter().map(|(_, t)| u64::from(*t)).sum();
+    if used + needed <= limit {
+        return Duration::ZERO;
+    }
+    for (at, tokens) in usage {
+        used -= u64::from(*tokens);
+        if used + needed <= limit {
+            return (*at + WINDOW).saturating_duration_since(Instant::now());
+        }
+    }
+    WINDOW
+}
+
+/// Swap the `estimated` entry of a request in `usage` for the `actual`
+/// count; 0 means the provider reported no usage and the estimate stands
+fn settle(usage: &mut Vec<(Instant, u32)>, estimated: u32, actual: u32) {
+    if actual == 0 {
+        return;
+    }
+    match usage.iter().rposition(|(_, t)| *t == estimated) {
+        Some(i) => usage[i].1 = actual,
+        None if actual > estimated => usage.push((Instant::now(), actual - estimated)),
+        None => {}
+    }
+}
+
 /// Rate limiter for a specific agent type (main or worker)
 #[derive(Debug)]
 struct AgentRateLimiter {
@@ -330,6 +454,13 @@ pub struct RateLimiter {
     main_limiter: Arc<AgentRateLimiter>,
     /// Worker rate limiter (shared across all workers)
     worker_limiter: Arc<AgentRateLimiter>,
+    /// Use counted against the global budgets
+    global: Arc<Mutex<GlobalWindow>>,
+    global_rpm: Option<u32>,
+    global_tpm: Option<u32>,
+    /// Requests waiting in `wait_for_budget`, served in arrival order
+    queue: Arc<tokio::sync::Mutex<()>>,
+    max_queue_wait: Duration,
 }
 
 impl RateLimiter {
@@ -347,6 +478,11 @@ impl RateLimiter {
                 config.workers_tpm,
                 config.burst_size,
             )),
+            global: Arc::new(Mutex::new(GlobalWindow::default())),
+            global_rpm: config.global_rpm,
+            global_tpm: config.global_tpm,
+            queue: Arc::new(tokio::sync::Mutex::new(())),
+            max_queue_wait: config.max_queue_wait,
         }
     }
 
@@ -400,7 +536,7 @@ impl RateLimiter {
         let current_rpm = endpoint.current_rpm(is_worker);
         if current_rpm >= limiter.max_rpm {
             // Calculate time until oldest request falls out of window
-            let wait = Duration::from_secs(60) / limiter.max_rpm.max(1);
+            let wait = wait_for_request(endpoint.request_times(is_worker), limiter.max_rpm);
             return Err(RateLimitError::RateLimitExceeded { 
                 resource: "requests",
                 current: current_rpm,
@@ -411,8 +547,8 @@ impl RateLimiter {
 
         // Check TPM limit (for specific agent type)
         let current_tpm = endpoint.current_tpm(is_worker);
-        if current_tpm + estimated_tokens > limiter.max_tpm {
-            let wait = Duration::from_secs(60) / limiter.max_rpm.max(1);
+        let wait = wait_for_tokens(endpoint.token_usage(is_worker), estimated_tokens, limiter.max_tpm);
+        if wait > Duration::ZERO {
             return Err(RateLimitError::RateLimitExceeded {
                 resource: "tokens",
                 current: current_tpm,
@@ -421,13 +557,69 @@ impl RateLimiter {
             });
         }
 
+        // Check the budgets shared by main agent and workers
+        let mut global = self.global.lock();
+        global.cleanup_old_entries();
+        if let Some(limit) = self.global_rpm {
+            let wait = wait_for_request(&global.request_times, limit);
+            if wait > Duration::ZERO {
+                return Err(RateLimitError::RateLimitExceeded {
+                    resource: "requests (global)",
+                    current: global.request_times.len() as u32,
+                    limit,
+                    retry_after: wait,
+                });
+            }
+        }
+        if let Some(limit) = self.global_tpm {
+            let wait = wait_for_tokens(&global.token_usage, estimated_tokens, limit);
+            if wait > Duration::ZERO {
+                return Err(RateLimitError::RateLimitExceeded {
+                    resource: "tokens (global)",
+                    current: global.token_usage.iter().map(|(_, t)| t).sum(),
+                    limit,
+                    retry_after: wait,
+                });
+            }
+        }
+
         // Record the request (for specific agent type)
         endpoint.record_success(is_worker);
         endpoint.record_tokens(estimated_tokens, is_worker);
+        global.request_times.push(Instant::now());
+        global.token_usage.push((Instant::now(), estimated_tokens));
 
         Ok(())
     }
 
+    /// Like `acquire`, but waits for room instead of failing
+    ///
+    /// Callers queue in arrival order and the first in line sleeps until
+    /// the window has room. Fails once the wait would pass the configured
+    /// `max_queue_wait`, and at once while the circuit breaker is open.
+    pub async fn wait_for_budget(
+        &self,
+        base_url: &str,
+        is_worker: bool,
+        estimated_tokens: u32,
+    ) -> Result<(), RateLimitError> {
+        let started = Instant::now();
+        let _turn = self.queue.lock().await;
+        loop {
+            match self.acquire(base_url, is_worker, estimated_tokens).await {
+                Ok(()) => return Ok(()),
+                Err(RateLimitError::RateLimitExceeded { retry_after, .. } | RateLimitError::Blocked { retry_after })
+                    if started.elapsed() + retry_after <= self.max_queue_wait =>
+                {
+                    crate::debug_log!("[RATE_LIMIT] Over budget, waiting {:?}", retry_after);
+                    // Never spin: entries leave the window at whole instants
+                    sleep(retry_after.max(Duration::from_millis(10))).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
     /// Record a rate limit error (429) from the provider for a specific agent type
     pub fn record_rate_limit_error(&self, base_url: &str, is_worker: bool, retry_after: Option<Duration>) {
         let mut endpoints = self.endpoints.lock();
@@ -436,16 +628,35 @@ impl RateLimiter {
         }
     }
 
-    /// Record actual usage and correct if estimate was too low
+    /// Replace the token estimate of a request with the actual usage the
+    /// provider reported
     pub fn record_usage(&self, base_url: &str, is_worker: bool, actual_tokens: u32, estimated_tokens: u32) {
-        if actual_tokens > estimated_tokens {
-            let excess = actual_tokens - estimated_tokens;
-            let mut endpoints = self.endpoints.lock();
-            // Only correct if endpoint state exists
-            if let Some(endpoint) = endpoints.get_mut(base_url) {
-                endpoint.correct_usage(excess, is_worker);
-            }
+        // Only correct if endpoint state exists
+        if let Some(endpoint) = self.endpoints.lock().get_mut(base_url) {
+            endpoint.correct_usage(estimated_tokens, actual_tokens, is_worker);
         }
+        settle(&mut self.global.lock().token_usage, estimated_tokens, actual_tokens);
+    }
+
+    /// Hold off requests to `base_url` until a quota the provider reports
+    /// as exhausted resets
+    pub fn record_headers(&self, base_url: &str, is_worker: bool, headers: &RateLimitHeaders) {
+        let Some(wait) = headers.wait() else {
+            return;
+        };
+        crate::debug_log!("[RATE_LIMIT] {} reports its quota exhausted, holding off {:?}", base_url, wait);
+        self.endpoints
+            .lock()
+            .entry(base_url.to_string())
+            .or_insert_with(EndpointState::new)
+            .block_for(is_worker, wait);
+    }
+
+    /// Tokens counted against the global budget in the last minute
+    pub fn global_tpm(&self) -> u32 {
+        let mut global = self.global.lock();
+        global.cleanup_old_entries();
+        global.token_usage.iter().map(|(_, t)| t).sum()
     }
 
     /// Get current rate limit status for an endpoint
@@ -533,6 +744,130 @@ impl RateLimiter {
     }
 }
 
+/// Rate-limit state a provider reports in its response headers
+///
+/// Reads `Retry-After` (seconds or HTTP date) and `retry-after-ms`, the
+/// OpenAI-style `x-ratelimit-{remaining,reset}-{requests,tokens}` (also
+/// sent by Groq, DeepSeek and others), OpenRouter's `x-ratelimit-remaining`
+/// and `x-ratelimit-reset`, and Anthropic's `anthropic-ratelimit-*`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RateLimitHeaders {
+    /// How long the provider asks to wait before retrying
+    pub retry_after: Option<Duration>,
+    pub remaining_requests: Option<u64>,
+    pub remaining_tokens: Option<u64>,
+    /// Time until the request quota refills
+    pub reset_requests: Option<Duration>,
+    /// Time until the token quota refills
+    pub reset_tokens: Option<Duration>,
+}
+
+impl RateLimitHeaders {
+    pub fn parse(headers: &reqwest::header::HeaderMap) -> Self {
+        let get = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| headers.get(*name).and_then(|v| v.to_str().ok()))
+                .map(str::trim)
+        };
+        let count = |names: &[&str]| get(names).and_then(|v| v.parse::<u64>().ok());
+        let retry_after = get(&["retry-after-ms"])
+            .and_then(|v| v.parse::<f64>().ok())
+            .map(|ms| secs(ms / 1000.0))
+            .or_else(|| get(&["retry-after"]).and_then(parse_retry_after));
+        Self {
+            retry_after,
+            remaining_requests: count(&[
+                "x-ratelimit-remaining-requests",
+                "anthropic-ratelimit-requests-remaining",
+                "x-ratelimit-remaining",
+            ]),
+            remaining_tokens: count(&["x-ratelimit-remaining-tokens", "anthropic-ratelimit-tokens-remaining"]),
+            reset_requests: get(&[
+                "x-ratelimit-reset-requests",
+                "anthropic-ratelimit-requests-reset",
+                "x-ratelimit-reset",
+            ])
+            .and_then(parse_reset),
+            reset_tokens: get(&["x-ratelimit-reset-tokens", "anthropic-ratelimit-tokens-reset"]).and_then(parse_reset),
+        }
+    }
+
+    /// How long to hold off before the next request: the Retry-After, else
+    /// the time until an exhausted quota resets; None while quota remains
+    pub fn wait(&self) -> Option<Duration> {
+        if self.retry_after.is_some() {
+            return self.retry_after;
+        }
+        let requests = self.reset_requests.filter(|_| self.remaining_requests == Some(0));
+        let tokens = self.reset_tokens.filter(|_| self.remaining_tokens == Some(0));
+        requests.max(tokens)
+    }
+}
+
+/// `Retry-After`: delay in seconds, or an HTTP date
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Some(secs(seconds));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some(until(at.timestamp_millis()))
+}
+
+/// A reset header: a duration such as `1s`, `6m0s` or `20ms`, plain
+/// seconds, an RFC 3339 time, or a Unix time in seconds or milliseconds
+fn parse_reset(value: &str) -> Option<Duration> {
+    if let Ok(number) = value.parse::<f64>() {
+        return Some(match number {
+            n if n >= 1e12 => until(n as i64),
+            n if n >= 1e9 => until((n * 1000.0) as i64),
+            n => secs(n),
+        });
+    }
+    if let Ok(at) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(until(at.timestamp_millis()));
+    }
+    parse_go_duration(value)
+}
+
+/// Time from now until Unix time `at_ms` (zero if past), capped at
+/// `MAX_HEADER_WAIT`
+fn until(at_ms: i64) -> Duration {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    Duration::from_millis(at_ms.saturating_sub(now_ms).max(0) as u64).min(MAX_HEADER_WAIT)
+}
+
+/// `value` seconds as a wait, capped at `MAX_HEADER_WAIT`; negative and NaN
+/// values are no wait, so a malformed header can never panic
+fn secs(value: f64) -> Duration {
+    Duration::try_from_secs_f64(value.max(0.0)).map_or(MAX_HEADER_WAIT, |wait| wait.min(MAX_HEADER_WAIT))
+}
+
+/// Durations in Go's format, e.g. `1h2m3.5s` or `250ms`
+fn parse_go_duration(value: &str) -> Option<Duration> {
+    if value.is_empty() {
+        return None;
+    }
+    let mut total = 0.0;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !(c.is_ascii_digit() || c == '.'))?;
+        let number: f64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let unit_len = rest.find(|c: char| c.is_ascii_digit() || c == '.').unwrap_or(rest.len());
+        total += number
+            * match &rest[..unit_len] {
+                "ms" => 0.001,
+                "s" => 1.0,
+                "m" => 60.0,
+                "h" => 3600.0,
+                _ => return None,
+            };
+        rest = &rest[unit_len..];
+    }
+    Some(secs(total))
+}
+
 /// Status of an endpoint
 #[derive(Debug, Clone)]
 pub struct EndpointStatus {
@@ -619,3 +954,92 @@ impl std::fmt::Display for ProviderError {
 }
 
 impl std::error::Error for ProviderError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shared(tpm: u32, max_wait_secs: u64) -> RateLimiter {
+        let settings = RateLimitSettings {
+            requests_per_minute: Some(100),
+            tokens_per_minute: Some(tpm),
+            max_wait_secs,
+        };
+        RateLimiter::new(RateLimitConfig::shared_budget(&settings).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_token_budget_is_shared_and_settled_with_actual_usage() {
+        assert!(RateLimitConfig::shared_budget(&RateLimitSettings::default()).is_none());
+
+        let limiter = shared(1000, 0);
+        let url = "http://localhost:1234/v1";
+        limiter.acquire(url, false, 600).await.unwrap();
+        // Workers draw on the same budget as the main agent
+        match limiter.acquire(url, true, 600).await {
+            Err(RateLimitError::RateLimitExceeded { resource, retry_after, .. }) => {
+                assert_eq!(resource, "tokens (global)");
+                assert!(retry_after > Duration::from_secs(55) && retry_after <= WINDOW);
+            }
+            other => panic!("expected the token budget to be exhausted, got {:?}", other),
+        }
+        // Nothing to queue for: the wait passes max_wait_secs
+        assert!(limiter.wait_for_budget(url, true, 600).await.is_err());
+
+        // The provider counted fewer tokens than estimated
+        limiter.record_usage(url, false, 300, 600);
+        assert_eq!(limiter.global_tpm(), 300);
+        limiter.wait_for_budget(url, true, 600).await.unwrap();
+        assert_eq!(limiter.global_tpm(), 900);
+        limiter.record_usage(url, true, 700, 600);
+        assert_eq!(limiter.global_tpm(), 1000);
+
+        // A request larger than the whole budget runs once the window is empty
+        let limiter = shared(100, 0);
+        limiter.acquire(url, false, 5000).await.unwrap();
+        assert!(limiter.acquire(url, false, 1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_headers_give_the_wait_the_provider_asks_for() {
+        use reqwest::header::{HeaderMap, HeaderValue};
+
+        let headers = |pairs: &[(&'static str, &str)]| {
+            let mut map = HeaderMap::new();
+            for (name, value) in pairs {
+                map.insert(*name, HeaderValue::from_str(value).unwrap());
+            }
+            RateLimitHeaders::parse(&map)
+        };
+        assert_eq!(headers(&[("retry-after", "7")]).wait(), Some(Duration::from_secs(7)));
+        assert_eq!(headers(&[("retry-after-ms", "1500"), ("retry-after", "2")]).wait(), Some(Duration::from_millis(1500)));
+
+        let openai = headers(&[
+            ("x-ratelimit-remaining-requests", "0"),
+            ("x-ratelimit-reset-requests", "1m30.5s"),
+            ("x-ratelimit-remaining-tokens", "0"),
+            ("x-ratelimit-reset-tokens", "250ms"),
+        ]);
+        assert_eq!(openai.wait(), Some(Duration::from_secs_f64(90.5)));
+        // Quota left: no need to wait
+        let remaining = headers(&[("x-ratelimit-remaining-tokens", "1200"), ("x-ratelimit-reset-tokens", "6m0s")]);
+        assert_eq!(remaining.reset_tokens, Some(Duration::from_secs(360)));
+        assert_eq!(remaining.wait(), None);
+        assert_eq!(parse_go_duration("2x"), None);
+        // Malformed or absurd values neither panic nor block for ever
+        assert_eq!(headers(&[("retry-after", "NaN")]).wait(), Some(Duration::ZERO));
+        assert_eq!(headers(&[("retry-after", "1e400")]).wait(), Some(MAX_HEADER_WAIT));
+        assert_eq!(headers(&[("retry-after-ms", "-5")]).wait(), Some(Duration::ZERO));
+        assert_eq!(parse_go_duration("99999999999999999999h"), Some(MAX_HEADER_WAIT));
+
+        // An exhausted quota blocks the endpoint until it resets
+        let limiter = shared(1000, 0);
+        let url = "http://localhost:1234/v1";
+        let exhausted = headers(&[("x-ratelimit-remaining-requests", "0"), ("x-ratelimit-reset-requests", "20s")]);
+        limiter.record_headers(url, false, &exhausted);
+        match limiter.acquire(url, false, 10).await {
+            Err(RateLimitError::Blocked { retry_after }) => assert!(retry_after > Duration::from_secs(19)),
+            other => panic!("expected the endpoint to be blocked, got {:?}", other),
+        }
+    }
+}
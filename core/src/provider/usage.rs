@@ -0,0 +1,499 @@
+//! Per-model usage history and model recommendations
+//!
+//! With `features.telemetry` on, every chat request appends a `UsageRecord`
+//! (role, profile, model, token counts, cost, success) to
+//! `<data dir>/mylm/usage.jsonl`. The settings hub reads the recent history
+//! back, compares it with the provider's model catalog and suggests models
+//! that would have been cheaper or more reliable for each role; `mylm costs`
+//! totals the same records into a spend ledger (see `costs`).
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Requests a model needs in a role before its history is judged
+pub const MIN_REQUESTS: usize = 5;
+/// Failure rate above which a model is considered unreliable
+const UNRELIABLE_FAILURE_RATE: f64 = 0.2;
+/// A cheaper model must cost at most this share of the current one
+const CHEAPER_RATIO: f64 = 0.5;
+/// Share of the context window a role may use before it is tight
+const TIGHT_CONTEXT: f64 = 0.9;
+
+/// What a model is used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelRole {
+    /// The main agent
+    Main,
+    /// Delegated workers
+    Worker,
+    /// The fast-path model that classifies and answers trivial messages
+    Categorizer,
+}
+
+impl std::fmt::Display for ModelRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModelRole::Main => write!(f, "main"),
+            ModelRole::Worker => write!(f, "worker"),
+            ModelRole::Categorizer => write!(f, "categorizer"),
+        }
+    }
+}
+
+/// One chat request as reported by the LLM client
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelCall {
+    pub model: String,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    /// USD at the prices configured for the endpoint that answered
+    pub cost: f64,
+    /// False when the request failed
+    pub ok: bool,
+}
+
+/// Callback type for finished chat requests, used to record usage
+pub type UsageCallback = Arc<dyn Fn(&ModelCall) + Send + Sync>;
+
+/// One line of the usage log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageRecord {
+    /// Unix time in seconds
+    pub at: u64,
+    pub role: ModelRole,
+    /// Profile the request was made with (absent in older records)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    pub model: String,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    /// USD, 0 when the endpoint has no prices configured
+    #[serde(default)]
+    pub cost: f64,
+    pub ok: bool,
+}
+
+/// Append-only JSONL file of usage records
+#[derive(Debug, Clone)]
+pub struct UsageLog {
+    path: PathBuf,
+}
+
+impl UsageLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// `<data dir>/mylm/usage.jsonl`
+    pub fn default_log() -> Option<Self> {
+        dirs::data_dir().map(|dir| Self::new(dir.join("mylm").join("usage.jsonl")))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&self, record: &UsageRecord) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(record)?)?;
+        Ok(())
+    }
+
+    /// Records made at or after `since` (Unix seconds); unreadable lines are skipped
+    pub fn read_since(&self, since: u64) -> Result<Vec<UsageRecord>> {
+        let text = match std::fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(text
+            .lines()
+            .filter_map(|line| serde_json::from_str::<UsageRecord>(line).ok())
+            .filter(|record| record.at >= since)
+            .collect())
+    }
+
+    /// A callback that logs calls made in `role` on `profile`
+    pub fn recorder(self, role: ModelRole, profile: &str) -> UsageCallback {
+        let profile = profile.to_string();
+        Arc::new(move |call: &ModelCall| {
+            let record = UsageRecord {
+                at: now_secs(),
+                role,
+                profile: Some(profile.clone()),
+                model: call.model.clone(),
+                prompt_tokens: call.prompt_tokens,
+                completion_tokens: call.completion_tokens,
+                cost: call.cost,
+                ok: call.ok,
+            };
+            if let Err(e) = self.append(&record) {
+                crate::warn_log!("[USAGE] Could not write {}: {}", self.path.display(), e);
+            }
+        })
+    }
+}
+
+pub fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// A model offered by a provider, from its `/models` endpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelOffer {
+    pub id: String,
+    /// USD per million input tokens, when the provider publishes prices
+    pub input_price_per_1m: Option<f64>,
+    pub output_price_per_1m: Option<f64>,
+    pub context_length: Option<usize>,
+}
+
+impl ModelOffer {
+    /// Price of a request mix, if both prices are known
+    fn cost(&self, prompt_tokens: u64, completion_tokens: u64) -> Option<f64> {
+        Some(
+            (prompt_tokens as f64 * self.input_price_per_1m? + completion_tokens as f64 * self.output_price_per_1m?)
+                / 1_000_000.0,
+        )
+    }
+}
+
+/// Models from a `/models` response, in the OpenAI shape (`data`) or under `models`
+///
+/// OpenRouter adds `pricing` (USD per token, as strings) and `context_length`.
+pub fn parse_catalog(body: &serde_json::Value) -> Vec<ModelOffer> {
+    let entries = body
+        .get("data")
+        .and_then(|v| v.as_array())
+        .filter(|entries| !entries.is_empty())
+        .or_else(|| body.get("models").and_then(|v| v.as_array()));
+    let per_million = |entry: &serde_json::Value, key: &str| {
+        let price = entry.get("pricing")?.get(key)?;
+        let per_token = match price {
+            serde_json::Value::String(s) => s.parse::<f64>().ok()?,
+            other => other.as_f64()?,
+        };
+        // Negative prices mark variable-priced routers
+        (per_token >= 0.0).then_some(per_token * 1_000_000.0)
+    };
+    let mut offers: Vec<ModelOffer> = entries
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            Some(ModelOffer {
+                id: entry.get("id")?.as_str()?.to_string(),
+                input_price_per_1m: per_million(entry, "prompt"),
+                output_price_per_1m: per_million(entry, "completion"),
+                context_length: entry.get("context_length").and_then(|v| v.as_u64()).map(|n| n as usize),
+            })
+        })
+        .collect();
+    offers.sort_by(|a, b| a.id.cmp(&b.id));
+    offers
+}
+
+/// How one model did in one role
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelStats {
+    pub role: ModelRole,
+    pub model: String,
+    pub requests: usize,
+    pub failures: usize,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// Largest prompt seen
+    pub peak_prompt_tokens: u32,
+}
+
+impl ModelStats {
+    pub fn failure_rate(&self) -> f64 {
+        if self.requests == 0 {
+            0.0
+        } else {
+            self.failures as f64 / self.requests as f64
+        }
+    }
+
+    pub fn avg_prompt_tokens(&self) -> u64 {
+        self.prompt_tokens / self.requests.max(1) as u64
+    }
+
+    pub fn avg_completion_tokens(&self) -> u64 {
+        self.completion_tokens / self.requests.max(1) as u64
+    }
+}
+
+/// Per role and model totals, busiest first
+pub fn summarize(records: &[UsageRecord]) -> Vec<ModelStats> {
+    let mut by_key: HashMap<(ModelRole, &str), ModelStats> = HashMap::new();
+    for record in records {
+        let stats = by_key.entry((record.role, &record.model)).or_insert_with(|| ModelStats {
+            role: record.role,
+            model: record.model.clone(),
+            requests: 0,
+            failures: 0,
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            peak_prompt_tokens: 0,
+        });
+        stats.requests += 1;
+        stats.failures += usize::from(!record.ok);
+        stats.prompt_tokens += u64::from(record.prompt_tokens);
+        stats.completion_tokens += u64::from(record.completion_tokens);
+        stats.peak_prompt_tokens = stats.peak_prompt_tokens.max(record.prompt_tokens);
+    }
+    let mut stats: Vec<ModelStats> = by_key.into_values().collect();
+    stats.sort_by(|a, b| b.requests.cmp(&a.requests).then_with(|| a.model.cmp(&b.model)));
+    stats
+}
+
+/// A suggested model change for one role
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recommendation {
+    pub role: ModelRole,
+    pub current: String,
+    pub suggested: String,
+    pub reason: String,
+}
+
+/// Suggest replacements for `current` in `role`, given recent usage and the
+/// models its provider offers
+///
+/// A model counts as proven when it has handled enough requests (in any role)
+/// with a failure rate no worse than the current model's. The main agent is
+/// only pointed at proven models; workers and the categorizer may also move
+/// to an untried model from the same vendor, since their requests are small
+/// and easy to check.
+pub fn recommend(role: ModelRole, current: &str, stats: &[ModelStats], catalog: &[ModelOffer]) -> Vec<Recommendation> {
+    let Some(mine) = stats.iter().find(|s| s.role == role && s.model == current) else {
+        return Vec::new();
+    };
+    if mine.requests < MIN_REQUESTS {
+        return Vec::new();
+    }
+
+    // Overall record of each model across roles
+    let mut track: HashMap<&str, (usize, usize)> = HashMap::new();
+    for s in stats {
+        let entry = track.entry(s.model.as_str()).or_default();
+        entry.0 += s.requests;
+        entry.1 += s.failures;
+    }
+    let failure_rate = |model: &str| {
+        track
+            .get(model)
+            .filter(|(requests, _)| *requests >= MIN_REQUESTS)
+            .map(|(requests, failures)| *failures as f64 / *requests as f64)
+    };
+    let vendor = |model: &str| model.split_once('/').map(|(vendor, _)| vendor.to_string());
+    let fits = |offer: &ModelOffer, tokens: u64| offer.context_length.is_some_and(|len| len as u64 >= tokens);
+    let needed = u64::from(mine.peak_prompt_tokens) + mine.avg_completion_tokens();
+    let eligible = |offer: &ModelOffer| {
+        if offer.id == current {
+            return false;
+        }
+        match failure_rate(&offer.id) {
+            Some(rate) => rate <= mine.failure_rate(),
+            None => role != ModelRole::Main && vendor(&offer.id).is_some() && vendor(&offer.id) == vendor(current),
+        }
+    };
+    let cost_of = |offer: &ModelOffer| offer.cost(mine.prompt_tokens, mine.completion_tokens);
+    let described = |offer: &ModelOffer| match failure_rate(&offer.id) {
+        Some(rate) => format!("{} has failed {:.0}% of its requests", offer.id, rate * 100.0),
+        None => format!("{} is untried", offer.id),
+    };
+
+    let mut out = Vec::new();
+    let mut suggest = |suggested: &str, reason: String| {
+        if !out.iter().any(|r: &Recommendation| r.suggested == suggested) {
+            out.push(Recommendation {
+                role,
+                current: current.to_string(),
+                suggested: suggested.to_string(),
+                reason,
+            });
+        }
+    };
+
+    // Unreliable: the proven model with the fewest failures, preferring
+    // models that have already done this role's work
+    if mine.failure_rate() >= UNRELIABLE_FAILURE_RATE {
+        let best = stats
+            .iter()
+            .filter(|s| s.model != current)
+            .filter_map(|s| Some((s.model.as_str(), s.role == role && s.requests >= MIN_REQUESTS, failure_rate(&s.model)?)))
+            .filter(|(_, _, rate)| *rate < mine.failure_rate() / 2.0)
+            .filter(|(model, _, _)| catalog.is_empty() || catalog.iter().any(|o| o.id == *model && fits(o, needed)))
+            .min_by(|a, b| b.1.cmp(&a.1).then(a.2.total_cmp(&b.2)))
+            .map(|(model, _, rate)| (model, rate));
+        if let Some((model, rate)) = best {
+            suggest(
+                model,
+                format!(
+                    "{} failed {:.0}% of {} requests; {} failed {:.0}%",
+                    current,
+                    mine.failure_rate() * 100.0,
+                    mine.requests,
+                    model,
+                    rate * 100.0
+                ),
+            );
+        }
+    }
+
+    let current_offer = catalog.iter().find(|o| o.id == current);
+
+    // Cheaper: the cheapest eligible model that fits the largest prompt
+    if let Some(current_cost) = current_offer.and_then(cost_of).filter(|cost| *cost > 0.0) {
+        let cheapest = catalog
+            .iter()
+            .filter(|o| eligible(o) && fits(o, needed))
+            .filter_map(|o| Some((o, cost_of(o)?)))
+            .filter(|(_, cost)| *cost <= current_cost * CHEAPER_RATIO)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((offer, cost)) = cheapest {
+            suggest(
+                &offer.id,
+                format!(
+                    "${:.2} instead of ${:.2} for your last {} {} requests (avg {} prompt tokens); {}",
+                    cost,
+                    current_cost,
+                    mine.requests,
+                    role,
+                    mine.avg_prompt_tokens(),
+                    described(offer)
+                ),
+            );
+        }
+    }
+
+    // Tight context: the cheapest eligible model with twice the room
+    if let Some(context) = current_offer.and_then(|o| o.context_length) {
+        if mine.peak_prompt_tokens as f64 >= context as f64 * TIGHT_CONTEXT {
+            let roomy = catalog
+                .iter()
+                .filter(|o| eligible(o) && fits(o, needed * 2))
+                .min_by(|a, b| {
+                    let a = cost_of(a).unwrap_or(f64::MAX);
+                    let b = cost_of(b).unwrap_or(f64::MAX);
+                    a.total_cmp(&b)
+                });
+            if let Some(offer) = roomy {
+                suggest(
+                    &offer.id,
+                    format!(
+                        "prompts reached {} of {}'s {} token context; {} has {}",
+                        mine.peak_prompt_tokens,
+                        current,
+                        context,
+                        offer.id,
+                        offer.context_length.unwrap_or_default()
+                    ),
+                );
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(role: ModelRole, model: &str, prompt: u32, ok: bool) -> UsageRecord {
+        UsageRecord {
+            at: 1_700_000_000,
+            role,
+            profile: None,
+            model: model.to_string(),
+            prompt_tokens: prompt,
+            completion_tokens: 100,
+            cost: 0.0,
+            ok,
+        }
+    }
+
+    fn offer(id: &str, input: f64, output: f64, context: usize) -> ModelOffer {
+        ModelOffer {
+            id: id.to_string(),
+            input_price_per_1m: Some(input),
+            output_price_per_1m: Some(output),
+            context_length: Some(context),
+        }
+    }
+
+    #[test]
+    fn test_log_round_trip_and_catalog() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = UsageLog::new(dir.path().join("usage.jsonl"));
+        assert!(log.read_since(0).unwrap().is_empty());
+        let record = record(ModelRole::Worker, "openai/gpt-4o-mini", 900, true);
+        log.append(&record).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(log.path())
+            .and_then(|mut f| writeln!(f, "not json"))
+            .unwrap();
+        assert_eq!(log.read_since(0).unwrap(), vec![record]);
+        assert!(log.read_since(1_800_000_000).unwrap().is_empty());
+
+        let body = serde_json::json!({ "data": [
+            { "id": "openai/gpt-4o", "context_length": 128000,
+              "pricing": { "prompt": "0.0000025", "completion": "0.00001" } },
+            { "id": "openrouter/auto", "pricing": { "prompt": "-1", "completion": "-1" } },
+            { "id": "local-model" }
+        ]});
+        let catalog = parse_catalog(&body);
+        assert_eq!(catalog.len(), 3);
+        assert_eq!(catalog[1].id, "openai/gpt-4o");
+        assert!((catalog[1].input_price_per_1m.unwrap() - 2.5).abs() < 1e-9);
+        assert_eq!(catalog[1].context_length, Some(128000));
+        assert_eq!(catalog[2].input_price_per_1m, None);
+    }
+
+    #[test]
+    fn test_recommends_cheaper_and_more_reliable_models() {
+        let mut records = Vec::new();
+        for i in 0..10 {
+            records.push(record(ModelRole::Worker, "openai/gpt-4o", 2000, true));
+            // Main model fails 4 times in 10
+            records.push(record(ModelRole::Main, "acme/big", 20000, i >= 4));
+            records.push(record(ModelRole::Main, "anthropic/claude-sonnet", 20000, i != 0));
+        }
+        let stats = summarize(&records);
+        let catalog = vec![
+            offer("openai/gpt-4o", 2.5, 10.0, 128000),
+            offer("openai/gpt-4o-mini", 0.15, 0.6, 128000),
+            offer("openai/tiny", 0.01, 0.01, 1000),
+            offer("anthropic/claude-sonnet", 3.0, 15.0, 200000),
+            offer("acme/big", 1.0, 1.0, 200000),
+        ];
+
+        // An untried model from the same vendor is fine for workers
+        let worker = recommend(ModelRole::Worker, "openai/gpt-4o", &stats, &catalog);
+        assert_eq!(worker.len(), 1);
+        assert_eq!(worker[0].suggested, "openai/gpt-4o-mini");
+
+        // The main agent only moves to models with a good record
+        let main = recommend(ModelRole::Main, "acme/big", &stats, &catalog);
+        assert_eq!(main.len(), 1);
+        assert_eq!(main[0].suggested, "anthropic/claude-sonnet");
+        assert!(main[0].reason.contains("40%"));
+
+        // Too little history says nothing
+        assert!(recommend(ModelRole::Categorizer, "openai/gpt-4o", &stats, &catalog).is_empty());
+    }
+}
@@ -0,0 +1,157 @@
+//! Five-field cron expressions
+//!
+//! `minute hour day-of-month month day-of-week`, each `*`, a number, a range
+//! `a-b`, a list `a,b` or a step `*/n`, `a-b/n`. Day of week runs 0-7 with
+//! both 0 and 7 for Sunday. As in cron, when both day fields are restricted
+//! a day matching either one counts. `@hourly`, `@daily`, `@weekly` and
+//! `@monthly` are accepted too.
+//!
+//! Runs are found in a time zone's wall-clock time, so `0 9 * * *` stays at
+//! 9:00 local across DST changes. A time skipped by a DST jump does not run
+//! that day; a time that repeats runs once, the first time.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// How far ahead to look for a run (`0 0 29 2 *` may wait years)
+const SEARCH_DAYS: i64 = 366 * 8;
+
+/// A parsed cron expression; each field is a bit set of allowed values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronExpr {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+fn field(text: &str, min: u32, max: u32, name: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid {} field '{}'", name, text);
+    let number = |s: &str| s.parse::<u32>().map_err(|_| invalid());
+    let mut bits = 0u64;
+    for part in text.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, number(step).ok().filter(|s| *s > 0).ok_or_else(invalid)?),
+            None => (part, 1),
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (number(start)?, number(end)?),
+            // `5/15` runs from 5 to the end of the range
+            None if step > 1 => (number(range)?, max),
+            None => (number(range)?, number(range)?),
+        };
+        if start < min || end > max || start > end {
+            return Err(format!("{} field '{}' is outside {}-{}", name, text, min, max));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+fn has(bits: u64, value: u32) -> bool {
+    bits & (1 << value) != 0
+}
+
+impl CronExpr {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expression = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!("Cron expression '{}' needs 5 fields, not {}", expression, fields.len()));
+        };
+        let mut weekdays = field(weekday, 0, 7, "day-of-week")?;
+        if has(weekdays, 7) {
+            weekdays = (weekdays & !(1 << 7)) | 1;
+        }
+        Ok(Self {
+            minutes: field(minute, 0, 59, "minute")?,
+            hours: field(hour, 0, 23, "hour")?,
+            days: field(day, 1, 31, "day-of-month")?,
+            months: field(month, 1, 12, "month")?,
+            weekdays,
+            any_day: day.starts_with('*'),
+            any_weekday: weekday.starts_with('*'),
+        })
+    }
+
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if !has(self.months, date.month()) {
+            return false;
+        }
+        let day = has(self.days, date.day());
+        let weekday = has(self.weekdays, date.weekday().num_days_from_sunday());
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        }
+    }
+
+    /// The first run strictly after `after`, reading the fields in `tz`
+    pub fn next_after(&self, after: DateTime<Utc>, tz: Tz) -> Option<DateTime<Utc>> {
+        let start = after.with_timezone(&tz).date_naive();
+        for offset in 0..SEARCH_DAYS {
+            let date = start + Duration::days(offset);
+            if !self.matches_date(date) {
+                continue;
+            }
+            for hour in (0..24).filter(|h| has(self.hours, *h)) {
+                for minute in (0..60).filter(|m| has(self.minutes, *m)) {
+                    let Some(naive) = date.and_hms_opt(hour, minute, 0) else { continue };
+                    // None when a DST jump skips this wall-clock time
+                    let Some(at) = tz.from_local_datetime(&naive).earliest() else { continue };
+                    let at = at.with_timezone(&Utc);
+                    if at > after {
+                        return Some(at);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_cron_follows_local_time_across_dst() {
+        let berlin: Tz = "Europe/Berlin".parse().unwrap();
+        let daily = CronExpr::parse("0 9 * * *").unwrap();
+        // 9:00 CET is 08:00Z; after the switch to CEST it is 07:00Z
+        assert_eq!(daily.next_after(utc("2026-03-28T08:00:00Z"), berlin), Some(utc("2026-03-29T07:00:00Z")));
+        // 02:30 does not exist on the day clocks jump forward
+        let skipped = CronExpr::parse("30 2 * * *").unwrap();
+        assert_eq!(skipped.next_after(utc("2026-03-28T02:00:00Z"), berlin), Some(utc("2026-03-30T00:30:00Z")));
+
+        let weekdays = CronExpr::parse("*/15 9-17 * * 1-5").unwrap();
+        // Friday after hours -> Monday 09:00
+        assert_eq!(weekdays.next_after(utc("2026-10-16T17:50:00Z"), Tz::UTC), Some(utc("2026-10-19T09:00:00Z")));
+        assert_eq!(CronExpr::parse("0 0 * * 7").unwrap(), CronExpr::parse("@weekly").unwrap());
+        // Day 13 or any Friday
+        let either = CronExpr::parse("0 0 13 * 5").unwrap();
+        assert_eq!(either.next_after(utc("2026-10-14T00:00:00Z"), Tz::UTC), Some(utc("2026-10-16T00:00:00Z")));
+
+        assert!(CronExpr::parse("61 * * * *").is_err());
+        assert!(CronExpr::parse("0 9 * *").is_err());
+        assert!(CronExpr::parse("*/0 * * * *").is_err());
+    }
+}
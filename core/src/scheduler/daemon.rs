@@ -1,3 +1,4 @@
+use crate::environment::TimeContext;
 use crate::scheduler::model::{JobAction, JobSchedule, ScheduledJob};
 use crate::scheduler::store::JobStore;
 use anyhow::{Context, Result};
@@ -10,12 +11,20 @@ use tokio::time::{sleep, Duration};
 pub struct SchedulerDaemon {
     store: JobStore,
     pid_path: PathBuf,
+    /// Zone local cron schedules are read in
+    time: TimeContext,
 }
 
 impl SchedulerDaemon {
     pub fn new(store: JobStore) -> Self {
         let pid_path = store.root_dir().join("daemon.pid");
-        Self { store, pid_path }
+        Self { store, pid_path, time: TimeContext::default() }
+    }
+
+    /// Read local cron schedules in `time`'s zone (`features.time`)
+    pub fn with_time_context(mut self, time: TimeContext) -> Self {
+        self.time = time;
+        self
     }
 
     pub async fn start_loop(&self) -> Result<()> {
@@ -56,7 +65,10 @@ impl SchedulerDaemon {
                 match self.execute_job(job).await {
                     Ok(_) => {
                         job.last_run_at = Some(now);
-                        job.next_run_at = self.calculate_next_run(job, now);
+                        job.next_run_at = job.next_run_after(now, &self.time);
+                        if matches!(job.schedule, JobSchedule::Once(_)) {
+                            job.enabled = false;
+                        }
                         job.updated_at = now;
                         changed = true;
                     }
@@ -75,9 +87,13 @@ impl SchedulerDaemon {
     }
 
     fn is_due(&self, job: &ScheduledJob, now: DateTime<Utc>) -> bool {
-        match job.next_run_at {
-            Some(next) => now >= next,
-            None => {
+        match (job.next_run_at, &job.schedule) {
+            (Some(next), _) => now >= next,
+            (None, JobSchedule::Once(once)) => job.last_run_at.is_none() && now >= once.at,
+            (None, JobSchedule::Cron(_)) => job
+                .next_run_after(job.last_run_at.unwrap_or(job.created_at), &self.time)
+                .is_some_and(|next| now >= next),
+            (None, _) => {
                 // If never run and no next_run_at, it's due now if we want to start it immediately
                 // For v1, let's assume if next_run_at is None, we should compute it or run now.
                 // Let's run it now to initialize.
@@ -86,19 +102,6 @@ impl SchedulerDaemon {
         }
     }
 
-    fn calculate_next_run(&self, job: &ScheduledJob, last_run: DateTime<Utc>) -> Option<DateTime<Utc>> {
-        match &job.schedule {
-            JobSchedule::Interval(interval) => {
-                let duration = self.parse_duration(&interval.every.raw).ok()?;
-                Some(last_run + chrono::Duration::from_std(duration).ok()?)
-            }
-            JobSchedule::Cron(_) => {
-                // Cron not supported in v1 without deps
-                None
-            }
-        }
-    }
-
     async fn execute_job(&self, job: &ScheduledJob) -> Result<()> {
         match &job.action {
             JobAction::Shell(shell) => {
@@ -125,16 +128,4 @@ impl SchedulerDaemon {
             }
         }
     }
-
-    fn parse_duration(&self, raw: &str) -> Result<Duration> {
-        let (num_str, unit) = raw.split_at(raw.len() - 1);
-        let num: u64 = num_str.parse().context("Invalid duration number")?;
-        match unit {
-            "s" => Ok(Duration::from_secs(num)),
-            "m" => Ok(Duration::from_secs(num * 60)),
-            "h" => Ok(Duration::from_secs(num * 3600)),
-            "d" => Ok(Duration::from_secs(num * 86400)),
-            _ => anyhow::bail!("Invalid duration unit: {}", unit),
-        }
-    }
 }
@@ -1,12 +1,17 @@
+pub mod cron;
 pub mod daemon;
 pub mod model;
+pub mod pool;
 pub mod store;
 
+pub use cron::CronExpr;
 pub use daemon::SchedulerDaemon;
 pub use model::{
     AgentContextSpec, AgentTaskAction, CronSchedule, DurationSpec, IntervalSchedule, JobAction,
-    JobId, JobPolicy, JobSchedule, JobTimezone, MisfirePolicy, OverlapPolicy, ScheduledJob,
+    JobId, JobPolicy, JobSchedule, JobTimezone, MisfirePolicy, OnceSchedule, OverlapPolicy,
+    ScheduledJob, ShellAction,
 };
+pub use pool::{PoolSlot, PoolStatus, RequestLimit, WorkerPool};
 
 pub use store::{JobStore, JobsFile};
 
@@ -4,6 +4,9 @@ use std::collections::BTreeMap;
 use std::path::PathBuf;
 use uuid::Uuid;
 
+use crate::environment::TimeContext;
+use crate::scheduler::cron::CronExpr;
+
 pub type JobId = Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +54,48 @@ impl ScheduledJob {
             policy: JobPolicy::default(),
         }
     }
+
+    /// A job that runs once, at a time given in words ("tomorrow 9am")
+    /// read in the user's time zone
+    pub fn once(name: impl Into<String>, when: &str, time: &TimeContext, action: JobAction) -> Result<Self, String> {
+        let now = Utc::now();
+        let at = time.parse(when, now)?;
+        if at <= now {
+            return Err(format!("'{}' is in the past ({})", when, at.format("%Y-%m-%d %H:%M %Z")));
+        }
+        let at = at.with_timezone(&Utc);
+        let mut job = Self::new_now(name, JobSchedule::Once(OnceSchedule { at }), action);
+        job.next_run_at = Some(at);
+        Ok(job)
+    }
+
+    /// A job on a cron schedule read in the user's time zone
+    pub fn cron(name: impl Into<String>, expression: &str, time: &TimeContext, action: JobAction) -> Result<Self, String> {
+        CronExpr::parse(expression)?;
+        let schedule = CronSchedule {
+            expression: expression.to_string(),
+            timezone: JobTimezone::Local,
+        };
+        let mut job = Self::new_now(name, JobSchedule::Cron(schedule), action);
+        job.next_run_at = job.next_run_after(job.created_at, time);
+        Ok(job)
+    }
+
+    /// The run after `after`; cron fields are read in `time`'s zone for
+    /// `JobTimezone::Local`
+    pub fn next_run_after(&self, after: DateTime<Utc>, time: &TimeContext) -> Option<DateTime<Utc>> {
+        match &self.schedule {
+            JobSchedule::Interval(interval) => Some(after + chrono::Duration::from_std(interval.every.duration()?).ok()?),
+            JobSchedule::Cron(cron) => {
+                let tz = match cron.timezone {
+                    JobTimezone::Local => time.timezone(),
+                    JobTimezone::Utc => chrono_tz::Tz::UTC,
+                };
+                CronExpr::parse(&cron.expression).ok()?.next_after(after, tz)
+            }
+            JobSchedule::Once(_) => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,6 +103,13 @@ impl ScheduledJob {
 pub enum JobSchedule {
     Cron(CronSchedule),
     Interval(IntervalSchedule),
+    Once(OnceSchedule),
+}
+
+/// A single run, e.g. a reminder; the job is disabled once it ran
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnceSchedule {
+    pub at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -83,6 +135,23 @@ pub struct DurationSpec {
     pub raw: String,
 }
 
+impl DurationSpec {
+    /// `30s`, `5m`, `2h` or `1d`
+    pub fn duration(&self) -> Option<std::time::Duration> {
+        let raw = self.raw.trim();
+        let unit = raw.chars().last()?;
+        let count: u64 = raw[..raw.len() - unit.len_utf8()].parse().ok()?;
+        let seconds = match unit {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86400,
+            _ => return None,
+        };
+        Some(std::time::Duration::from_secs(count.checked_mul(seconds)?))
+    }
+}
+
 impl From<&str> for DurationSpec {
     fn from(value: &str) -> Self {
         Self {
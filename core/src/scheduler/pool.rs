@@ -0,0 +1,217 @@
+//! Worker pool
+//!
+//! Bounds how many delegated workers run their objective at once. Workers
+//! beyond the pool's capacity are created right away but wait in the queue
+//! (in spawn order) for a free slot, so one large delegation cannot flood the
+//! provider. Each worker can also be given its own request limit, which
+//! spaces its LLM calls evenly instead of failing them.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+use crate::agent::runtime::orchestrator::commonbox::JobId;
+use crate::config::WorkerPoolSettings;
+
+/// Snapshot of the pool for status displays
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolStatus {
+    pub capacity: usize,
+    pub running: usize,
+    pub queued: usize,
+}
+
+#[derive(Default)]
+struct PoolState {
+    running: Vec<JobId>,
+    queued: VecDeque<JobId>,
+}
+
+/// Concurrency limit shared by all workers of a session
+pub struct WorkerPool {
+    capacity: usize,
+    permits: Arc<Semaphore>,
+    /// Per-worker request limit, 0 when unlimited
+    requests_per_minute: AtomicU32,
+    state: Mutex<PoolState>,
+}
+
+impl WorkerPool {
+    /// Pool running at most `capacity` workers at once
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            permits: Arc::new(Semaphore::new(capacity)),
+            requests_per_minute: AtomicU32::new(0),
+            state: Mutex::new(PoolState::default()),
+        }
+    }
+
+    /// Pool sized from `features.worker_pool`
+    pub fn from_settings(settings: &WorkerPoolSettings) -> Self {
+        Self::new(settings.max_concurrent).with_requests_per_minute(settings.requests_per_minute)
+    }
+
+    /// Limit every worker to `rpm` LLM requests per minute (None = unlimited)
+    pub fn with_requests_per_minute(self, rpm: Option<u32>) -> Self {
+        self.set_requests_per_minute(rpm);
+        self
+    }
+
+    /// Change the per-worker limit; workers spawned from now on use it
+    pub fn set_requests_per_minute(&self, rpm: Option<u32>) {
+        self.requests_per_minute.store(rpm.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn status(&self) -> PoolStatus {
+        let state = self.state.lock();
+        PoolStatus {
+            capacity: self.capacity,
+            running: state.running.len(),
+            queued: state.queued.len(),
+        }
+    }
+
+    /// Jobs waiting for a slot, first in line first
+    pub fn queued_jobs(&self) -> Vec<JobId> {
+        self.state.lock().queued.iter().copied().collect()
+    }
+
+    /// A fresh request limit for one worker, if the pool has one
+    pub fn request_limit(&self) -> Option<Arc<RequestLimit>> {
+        match self.requests_per_minute.load(Ordering::Relaxed) {
+            0 => None,
+            rpm => Some(Arc::new(RequestLimit::per_minute(rpm))),
+        }
+    }
+
+    /// Wait for a free slot for `job_id`; the slot is released when dropped
+    ///
+    /// A cancelled acquire (its future dropped) leaves the queue.
+    pub async fn acquire(self: &Arc<Self>, job_id: JobId) -> PoolSlot {
+        self.state.lock().queued.push_back(job_id);
+        let queued = QueuedJob { pool: self, job_id };
+        // Semaphore waiters are served in order, so the queue is FIFO
+        let permit = Arc::clone(&self.permits)
+            .acquire_owned()
+            .await
+            .expect("Worker pool semaphore is never closed");
+        drop(queued);
+        self.state.lock().running.push(job_id);
+        PoolSlot {
+            pool: Arc::clone(self),
+            job_id,
+            _permit: permit,
+        }
+    }
+}
+
+/// A job's place in the queue, left when dropped
+struct QueuedJob<'a> {
+    pool: &'a WorkerPool,
+    job_id: JobId,
+}
+
+impl Drop for QueuedJob<'_> {
+    fn drop(&mut self) {
+        self.pool.state.lock().queued.retain(|queued| *queued != self.job_id);
+    }
+}
+
+/// A running worker's place in the pool
+pub struct PoolSlot {
+    pool: Arc<WorkerPool>,
+    job_id: JobId,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Drop for PoolSlot {
+    fn drop(&mut self) {
+        self.pool.state.lock().running.retain(|running| *running != self.job_id);
+    }
+}
+
+/// Evenly spaced request limit for a single worker
+pub struct RequestLimit {
+    interval: Duration,
+    next: tokio::sync::Mutex<Option<Instant>>,
+}
+
+impl RequestLimit {
+    pub fn per_minute(rpm: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(60) / rpm.max(1),
+            next: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// Wait until the next request may be sent
+    pub async fn wait(&self) {
+        let mut next = self.next.lock().await;
+        let now = Instant::now();
+        if let Some(at) = *next {
+            if at > now {
+                tokio::time::sleep_until(at).await;
+            }
+        }
+        *next = Some(Instant::now() + self.interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_pool_queues_beyond_capacity() {
+        let pool = Arc::new(WorkerPool::new(2));
+        let jobs: Vec<JobId> = (0..3).map(|_| JobId::new()).collect();
+        let first = pool.acquire(jobs[0]).await;
+        let _second = pool.acquire(jobs[1]).await;
+
+        let waiting = tokio::spawn({
+            let pool = Arc::clone(&pool);
+            let job = jobs[2];
+            async move { pool.acquire(job).await }
+        });
+        tokio::task::yield_now().await;
+        assert_eq!(pool.status(), PoolStatus { capacity: 2, running: 2, queued: 1 });
+        assert_eq!(pool.queued_jobs(), vec![jobs[2]]);
+
+        drop(first);
+        let _third = waiting.await.unwrap();
+        assert_eq!(pool.status(), PoolStatus { capacity: 2, running: 2, queued: 0 });
+
+        // A cancelled wait gives up its place in the queue
+        let cancelled = tokio::spawn({
+            let pool = Arc::clone(&pool);
+            async move { pool.acquire(JobId::new()).await }
+        });
+        tokio::task::yield_now().await;
+        assert_eq!(pool.status().queued, 1);
+        cancelled.abort();
+        assert!(cancelled.await.unwrap_err().is_cancelled());
+        assert_eq!(pool.status().queued, 0);
+    }
+
+    #[tokio::test]
+    async fn test_request_limit_spaces_requests() {
+        // 1200 per minute = one request every 50ms
+        let limit = RequestLimit::per_minute(1200);
+        let start = Instant::now();
+        for _ in 0..3 {
+            limit.wait().await;
+        }
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+}
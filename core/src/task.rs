@@ -0,0 +1,165 @@
+//! Declarative task files
+//!
+//! A task file describes one unattended job for the agent: what to do, which
+//! files to read first, which tools it may use, how to check the result and
+//! how much it may spend. `mylm task run` executes one headlessly, which is
+//! what CI pipelines need:
+//!
+//! ```yaml
+//! objective: Fix the clippy warnings introduced by this branch and commit the fix
+//! context_files:
+//!   - CONTRIBUTING.md
+//! allowed_tools: [shell, read_file, write_file, apply_patch, code_search, git_diff]
+//! auto_approve_commands: ["cargo *", "git add *", "git commit *"]
+//! success_command: cargo clippy --all-targets -- -D warnings
+//! max_cost_usd: 0.50
+//! timeout_secs: 900
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Context files larger than this are cut before being sent
+const MAX_CONTEXT_FILE_BYTES: usize = 64 * 1024;
+/// Used when a task does not set `timeout_secs`
+const DEFAULT_TIMEOUT_SECS: u64 = 30 * 60;
+
+/// One headless job, usually loaded from `task.yaml`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TaskFile {
+    /// What the agent should achieve
+    pub objective: String,
+    /// Files read into the first message, relative to the task file
+    #[serde(default)]
+    pub context_files: Vec<PathBuf>,
+    /// Tools the agent may use; listed tools get an `allow` policy rule,
+    /// so chained shell commands and `deny`/`ask` rules still stop them.
+    /// Empty means every tool, with dangerous ones denied.
+    #[serde(default)]
+    pub allowed_tools: Vec<String>,
+    /// Command patterns (`*` wildcard) approved without asking
+    #[serde(default)]
+    pub auto_approve_commands: Vec<String>,
+    /// Command patterns always denied, even for allowed tools
+    #[serde(default)]
+    pub forbidden_commands: Vec<String>,
+    /// Shell command whose exit status decides whether the task succeeded
+    #[serde(default)]
+    pub success_command: Option<String>,
+    #[serde(default)]
+    pub max_cost_usd: Option<f64>,
+    #[serde(default)]
+    pub max_tokens: Option<u64>,
+    /// Wall-clock limit for the agent's work
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Profile to run with (default: the active profile)
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// `mylm task run` works in a new git worktree and branch, leaving the
+    /// checkout untouched until the result is merged
+    #[serde(default)]
+    pub worktree: bool,
+}
+
+impl TaskFile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let task = Self::parse(&content).with_context(|| format!("Invalid task file {}", path.display()))?;
+        Ok(task)
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let task: Self = serde_yml::from_str(content)?;
+        task.validate()?;
+        Ok(task)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.objective.trim().is_empty() {
+            anyhow::bail!("`objective` must not be empty");
+        }
+        if self.max_cost_usd.is_some_and(|cost| cost <= 0.0 || !cost.is_finite()) {
+            anyhow::bail!("`max_cost_usd` must be a positive number");
+        }
+        if self.max_tokens == Some(0) || self.timeout_secs == Some(0) {
+            anyhow::bail!("`max_tokens` and `timeout_secs` must be greater than zero");
+        }
+        Ok(())
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS))
+    }
+
+    /// The first message sent to the agent: objective, rules and context files
+    ///
+    /// Relative context paths are resolved against `base_dir`.
+    pub fn prompt(&self, base_dir: &Path) -> Result<String> {
+        let mut prompt = format!(
+            "You are running unattended; nobody will answer questions, so make reasonable decisions and finish the work.\n\nObjective:\n{}\n",
+            self.objective.trim()
+        );
+        if let Some(command) = &self.success_command {
+            prompt.push_str(&format!(
+                "\nThe task succeeds only if `{}` exits with status 0 afterwards; run it yourself to check before you finish.\n",
+                command
+            ));
+        }
+        for file in &self.context_files {
+            let path = base_dir.join(file);
+            let bytes = std::fs::read(&path).with_context(|| format!("Failed to read context file {}", path.display()))?;
+            let text = String::from_utf8_lossy(&bytes[..bytes.len().min(MAX_CONTEXT_FILE_BYTES)]);
+            let cut = if bytes.len() > MAX_CONTEXT_FILE_BYTES { "\n[... truncated]" } else { "" };
+            prompt.push_str(&format!("\n--- {} ---\n{}{}\n", file.display(), text.trim_end(), cut));
+        }
+        Ok(prompt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_validate() {
+        let task = TaskFile::parse(
+            "objective: Fix the lints\nallowed_tools: [shell, read_file]\nsuccess_command: cargo clippy\nmax_cost_usd: 0.5\n",
+        )
+        .unwrap();
+        assert_eq!(task.allowed_tools, vec!["shell", "read_file"]);
+        assert_eq!(task.success_command.as_deref(), Some("cargo clippy"));
+        assert_eq!(task.timeout(), Duration::from_secs(DEFAULT_TIMEOUT_SECS));
+
+        assert!(TaskFile::parse("objective: ''\n").is_err());
+        assert!(TaskFile::parse("objective: x\nmax_cost_usd: 0\n").is_err());
+        // Typos are reported instead of silently ignored
+        assert!(TaskFile::parse("objective: x\nallowed_tool: [shell]\n").is_err());
+    }
+
+    #[test]
+    fn test_prompt_includes_context_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("NOTES.md"), "Use rustfmt defaults.\n").unwrap();
+        let task = TaskFile {
+            objective: "Format the code".to_string(),
+            context_files: vec![PathBuf::from("NOTES.md")],
+            success_command: Some("cargo fmt --check".to_string()),
+            ..TaskFile::default()
+        };
+        let prompt = task.prompt(dir.path()).unwrap();
+        assert!(prompt.contains("Objective:\nFormat the code"));
+        assert!(prompt.contains("`cargo fmt --check`"));
+        assert!(prompt.contains("--- NOTES.md ---\nUse rustfmt defaults."));
+
+        let missing = TaskFile {
+            context_files: vec![PathBuf::from("nope.md")],
+            ..task
+        };
+        assert!(missing.prompt(dir.path()).is_err());
+    }
+}
@@ -1,5 +1,9 @@
 //! Shared utility functions for the application
 
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
 use anyhow::{bail, Context, Result};
 
 /// Sanitize a string value for safe use in HTTP headers
@@ -114,6 +118,122 @@ pub fn sanitize_base_url(url: &str, field_name: &str) -> Result<String> {
     Ok(trimmed.to_string())
 }
 
+/// Open `options` on `path`, creating the file readable by the owner only
+///
+/// The mode applies when the file is created; existing files keep theirs.
+pub fn open_private(path: &Path, options: &mut OpenOptions) -> Result<File> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))
+}
+
+/// Replace `path` with `contents`, readable by the owner only
+pub fn write_private(path: &Path, contents: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = open_private(path, OpenOptions::new().write(true).create(true).truncate(true))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(contents)?;
+    Ok(())
+}
+
+/// Create `dir` and its parents, with `dir` accessible by the owner only
+pub fn create_private_dir(dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(())
+}
+
+/// Levenshtein distance between `a` and `b`, in characters
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = (diagonal + usize::from(ca != *cb)).min(row[j] + 1).min(above + 1);
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// The candidate within two edits of a mistyped `name`, for "did you mean" hints
+pub fn closest_match<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Human-readable byte count, e.g. `512 B` or `1.5 MB`
+pub fn format_size(bytes: u64) -> String {
+    match scale_bytes(bytes) {
+        (_, "B") => format!("{} B", bytes),
+        (size, unit) => format!("{:.1} {}", size, unit),
+    }
+}
+
+/// `format_size` rounded to whole units, e.g. `512 B` or `2 MB`
+pub fn format_size_rounded(bytes: u64) -> String {
+    let (size, unit) = scale_bytes(bytes);
+    format!("{:.0} {}", size, unit)
+}
+
+/// `bytes` in the largest unit it reaches, up to TB
+fn scale_bytes(bytes: u64) -> (f64, &'static str) {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit_idx = 0;
+    while size >= 1024.0 && unit_idx < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit_idx += 1;
+    }
+    (size, UNITS[unit_idx])
+}
+
+/// Lowercase ASCII words of `text` joined by '-', for file and branch names
+///
+/// Whole words are kept up to `max_len` bytes; `fallback` stands in when
+/// `text` has none.
+pub fn slug(text: &str, max_len: usize, fallback: &str) -> String {
+    let mut slug = String::new();
+    for word in text.split(|c: char| !c.is_ascii_alphanumeric()).filter(|word| !word.is_empty()) {
+        if slug.is_empty() {
+            // A first word that is too long is cut rather than dropped
+            slug.push_str(&word[..word.len().min(max_len)]);
+        } else if slug.len() + word.len() < max_len {
+            slug.push('-');
+            slug.push_str(word);
+        } else {
+            break;
+        }
+    }
+    if slug.is_empty() {
+        fallback.to_string()
+    } else {
+        slug.to_ascii_lowercase()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -149,6 +269,29 @@ mod tests {
         assert!(sanitize_base_url("http://localhost:11434/v1", "url").is_ok());
     }
 
+    #[test]
+    fn test_closest_match() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(closest_match("modle", ["model", "mode", "max_tokens"]), Some("mode"));
+        assert_eq!(closest_match("xyzzy", ["model"]), None);
+    }
+
+    #[test]
+    fn test_format_size_rounded() {
+        assert_eq!(format_size_rounded(512), "512 B");
+        assert_eq!(format_size_rounded(2048), "2 KB");
+        assert_eq!(format_size_rounded(10 * 1024 * 1024), "10 MB");
+    }
+
+    #[test]
+    fn test_slug() {
+        assert_eq!(slug("Fix the lints!", 40, "task"), "fix-the-lints");
+        assert_eq!(slug("Rename parser into lexer", 12, "patch"), "rename");
+        assert_eq!(slug("Rename parser into lexer", 13, "patch"), "rename-parser");
+        assert_eq!(slug("Supercalifragilistic", 5, "task"), "super");
+    }
+
     #[test]
     fn test_sanitize_base_url_invalid() {
         assert!(sanitize_base_url("", "url").is_err());
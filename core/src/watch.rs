@@ -0,0 +1,366 @@
+//! File-system watch triggers
+//!
+//! Starts agent tasks when files change, configured in `features.watch`:
+//!
+//! ```toml
+//! [features.watch]
+//! enabled = true
+//!
+//! [[features.watch.triggers]]
+//! name = "review"
+//! paths = ["src/**/*.rs"]
+//! debounce_ms = 3000
+//! prompt = "Review this diff of {files} for bugs; be brief.\n\n{diff}"
+//! ```
+//!
+//! A `notify` watcher reports changes under the root. Changed paths are
+//! matched against each trigger's globs, and a trigger fires once its
+//! changes have been quiet for its debounce time, with every file that
+//! changed in the meantime. Frontends decide when to send the prompt.
+//!
+//! The agent's file tools call [`note_agent_write`] before they write, and
+//! changes to those paths are ignored for a moment, so a trigger never fires
+//! on the edits its own task made.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use glob::{MatchOptions, Pattern};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::Mutex;
+use tokio::sync::mpsc;
+
+use crate::config::{WatchSettings, WatchTrigger};
+
+/// Directories whose changes never fire triggers
+const IGNORED_DIRS: &[&str] = &[".git", "target", "node_modules", ".mylm"];
+/// Size of the `{diff}` text
+const MAX_DIFF_CHARS: usize = 20_000;
+/// How long changes to a file the agent wrote are ignored
+const AGENT_WRITE_WINDOW: Duration = Duration::from_secs(2);
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+fn agent_writes() -> &'static Mutex<HashMap<PathBuf, Instant>> {
+    static WRITES: OnceLock<Mutex<HashMap<PathBuf, Instant>>> = OnceLock::new();
+    WRITES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// `path` made absolute, with its directory resolved the way the watcher
+/// reports it; the file itself may not exist yet
+fn resolved(path: &Path) -> PathBuf {
+    let path = match std::env::current_dir() {
+        Ok(cwd) if path.is_relative() => cwd.join(path),
+        _ => path.to_path_buf(),
+    };
+    match (path.parent().and_then(|p| p.canonicalize().ok()), path.file_name()) {
+        (Some(parent), Some(name)) => parent.join(name),
+        _ => path,
+    }
+}
+
+/// Note that the agent is about to write or remove `path`, so its change
+/// events do not fire watch triggers
+pub fn note_agent_write(path: &Path) {
+    let now = Instant::now();
+    let mut writes = agent_writes().lock();
+    writes.retain(|_, at| now.saturating_duration_since(*at) < AGENT_WRITE_WINDOW);
+    writes.insert(resolved(path), now);
+}
+
+/// Whether a change to `path` is the agent's own recent write
+fn is_agent_write(path: &Path, now: Instant) -> bool {
+    agent_writes()
+        .lock()
+        .get(path)
+        .is_some_and(|at| now.saturating_duration_since(*at) < AGENT_WRITE_WINDOW)
+}
+
+/// A trigger that fired
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriggerFire {
+    pub trigger: String,
+    /// Changed files, relative to the watch root
+    pub files: Vec<PathBuf>,
+    /// The trigger's prompt with placeholders filled in
+    pub prompt: String,
+}
+
+/// A trigger with its globs compiled
+#[derive(Debug, Clone)]
+pub struct CompiledTrigger {
+    pub name: String,
+    paths: Vec<Pattern>,
+    ignore: Vec<Pattern>,
+    pub debounce: Duration,
+    prompt: String,
+}
+
+/// `src/` means everything under src
+fn compile(glob: &str) -> Result<Pattern, String> {
+    let glob = match glob.strip_suffix('/') {
+        Some(dir) => format!("{}/**", dir),
+        None => glob.to_string(),
+    };
+    Pattern::new(&glob).map_err(|e| format!("Invalid glob '{}': {}", glob, e))
+}
+
+impl CompiledTrigger {
+    pub fn new(trigger: &WatchTrigger) -> Result<Self, String> {
+        if trigger.paths.is_empty() {
+            return Err(format!("Watch trigger '{}' has no paths", trigger.name));
+        }
+        Ok(Self {
+            name: trigger.name.clone(),
+            paths: trigger.paths.iter().map(|g| compile(g)).collect::<Result<_, _>>()?,
+            ignore: trigger.ignore.iter().map(|g| compile(g)).collect::<Result<_, _>>()?,
+            debounce: Duration::from_millis(trigger.debounce_ms),
+            prompt: trigger.prompt.clone(),
+        })
+    }
+
+    /// Whether a change to `path` (relative to the root) fires this trigger
+    pub fn matches(&self, path: &Path) -> bool {
+        self.paths.iter().any(|p| p.matches_path_with(path, MATCH_OPTIONS))
+            && !self.ignore.iter().any(|p| p.matches_path_with(path, MATCH_OPTIONS))
+    }
+}
+
+/// Collects changes per trigger until they have been quiet long enough
+#[derive(Debug, Default)]
+pub struct Debouncer {
+    /// Trigger index -> (changed files, time of the last change)
+    pending: HashMap<usize, (BTreeSet<PathBuf>, Instant)>,
+}
+
+impl Debouncer {
+    pub fn record(&mut self, trigger: usize, path: PathBuf, now: Instant) {
+        let entry = self.pending.entry(trigger).or_insert_with(|| (BTreeSet::new(), now));
+        entry.0.insert(path);
+        entry.1 = now;
+    }
+
+    /// When the next trigger is due
+    pub fn next_deadline(&self, triggers: &[CompiledTrigger]) -> Option<Instant> {
+        self.pending
+            .iter()
+            .map(|(i, (_, last))| *last + triggers[*i].debounce)
+            .min()
+    }
+
+    /// Triggers whose changes have been quiet for their debounce time
+    pub fn take_due(&mut self, triggers: &[CompiledTrigger], now: Instant) -> Vec<(usize, Vec<PathBuf>)> {
+        let due: Vec<usize> = self
+            .pending
+            .iter()
+            .filter(|(i, (_, last))| now >= *last + triggers[**i].debounce)
+            .map(|(i, _)| *i)
+            .collect();
+        let mut fired: Vec<(usize, Vec<PathBuf>)> = due
+            .into_iter()
+            .filter_map(|i| self.pending.remove(&i).map(|(files, _)| (i, files.into_iter().collect())))
+            .collect();
+        fired.sort_by_key(|(i, _)| *i);
+        fired
+    }
+}
+
+/// `git diff` of `files` in `root`, cut to a readable size
+async fn diff(root: &Path, files: &[PathBuf]) -> String {
+    let output = tokio::process::Command::new("git")
+        .args(["diff", "--no-color", "--"])
+        .args(files)
+        .current_dir(root)
+        .output()
+        .await;
+    let text = match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).to_string(),
+        _ => String::new(),
+    };
+    if text.trim().is_empty() {
+        return "(no diff: the files are untracked or unchanged since the last commit)".to_string();
+    }
+    match text.char_indices().nth(MAX_DIFF_CHARS) {
+        Some((cut, _)) => format!("{}\n[... diff truncated]", &text[..cut]),
+        None => text,
+    }
+}
+
+/// Fill in `{trigger}`, `{files}` and `{diff}` (the diff is only computed if used)
+pub async fn render_prompt(trigger: &CompiledTrigger, root: &Path, files: &[PathBuf]) -> String {
+    let list: Vec<String> = files.iter().map(|f| format!("- {}", f.display())).collect();
+    let mut prompt = trigger
+        .prompt
+        .replace("{trigger}", &trigger.name)
+        .replace("{files}", &list.join("\n"));
+    if prompt.contains("{diff}") {
+        prompt = prompt.replace("{diff}", &diff(root, files).await);
+    }
+    prompt
+}
+
+/// Watches a directory and reports fired triggers; stops when dropped
+pub struct FileWatcher {
+    _watcher: RecommendedWatcher,
+    fires: mpsc::UnboundedReceiver<TriggerFire>,
+    task: tokio::task::JoinHandle<()>,
+    triggers: Vec<String>,
+}
+
+impl FileWatcher {
+    /// Watch `root` for the enabled triggers in `settings`
+    pub fn start(root: &Path, settings: &WatchSettings) -> Result<Self, String> {
+        let triggers: Vec<CompiledTrigger> = settings
+            .triggers
+            .iter()
+            .filter(|t| t.enabled)
+            .map(CompiledTrigger::new)
+            .collect::<Result<_, _>>()?;
+        if triggers.is_empty() {
+            return Err("No watch triggers are enabled".to_string());
+        }
+        let root = root.canonicalize().map_err(|e| format!("Cannot watch {}: {}", root.display(), e))?;
+
+        let (changed_tx, mut changed_rx) = mpsc::unbounded_channel::<PathBuf>();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event {
+                if event.kind.is_create() || event.kind.is_modify() || event.kind.is_remove() {
+                    for path in event.paths {
+                        let _ = changed_tx.send(path);
+                    }
+                }
+            }
+        })
+        .map_err(|e| format!("Cannot start the file watcher: {}", e))?;
+        watcher
+            .watch(&root, RecursiveMode::Recursive)
+            .map_err(|e| format!("Cannot watch {}: {}", root.display(), e))?;
+
+        let names = triggers.iter().map(|t| t.name.clone()).collect();
+        let (fire_tx, fires) = mpsc::unbounded_channel();
+        let task = tokio::spawn(async move {
+            let mut debouncer = Debouncer::default();
+            loop {
+                let changed = match debouncer.next_deadline(&triggers) {
+                    Some(deadline) => {
+                        match tokio::time::timeout_at(deadline.into(), changed_rx.recv()).await {
+                            Ok(changed) => changed.map(Some),
+                            Err(_) => Some(None),
+                        }
+                    }
+                    None => changed_rx.recv().await.map(Some),
+                };
+                match changed {
+                    None => return,
+                    Some(Some(path)) => {
+                        if is_agent_write(&path, Instant::now()) {
+                            continue;
+                        }
+                        let Ok(relative) = path.strip_prefix(&root) else { continue };
+                        let ignored = relative
+                            .components()
+                            .any(|c| IGNORED_DIRS.iter().any(|d| c.as_os_str() == *d));
+                        if ignored {
+                            continue;
+                        }
+                        for (i, trigger) in triggers.iter().enumerate() {
+                            if trigger.matches(relative) {
+                                debouncer.record(i, relative.to_path_buf(), Instant::now());
+                            }
+                        }
+                    }
+                    Some(None) => {}
+                }
+                for (i, files) in debouncer.take_due(&triggers, Instant::now()) {
+                    let trigger = &triggers[i];
+                    crate::info_log!("[WATCH] Trigger '{}' fired for {} file(s)", trigger.name, files.len());
+                    let prompt = render_prompt(trigger, &root, &files).await;
+                    let fire = TriggerFire { trigger: trigger.name.clone(), files, prompt };
+                    if fire_tx.send(fire).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(Self { _watcher: watcher, fires, task, triggers: names })
+    }
+
+    /// A fired trigger, if one is waiting
+    pub fn try_next(&mut self) -> Option<TriggerFire> {
+        self.fires.try_recv().ok()
+    }
+
+    /// Names of the active triggers
+    pub fn triggers(&self) -> &[String] {
+        &self.triggers
+    }
+}
+
+impl Drop for FileWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trigger(paths: &[&str], ignore: &[&str], debounce_ms: u64) -> CompiledTrigger {
+        CompiledTrigger::new(&WatchTrigger {
+            name: "review".to_string(),
+            paths: paths.iter().map(|p| p.to_string()).collect(),
+            ignore: ignore.iter().map(|p| p.to_string()).collect(),
+            debounce_ms,
+            prompt: "Review {files}".to_string(),
+            enabled: true,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_trigger_globs() {
+        let rust = trigger(&["src/**/*.rs"], &["src/generated/**"], 0);
+        assert!(rust.matches(Path::new("src/main.rs")));
+        assert!(rust.matches(Path::new("src/agent/mod.rs")));
+        assert!(!rust.matches(Path::new("src/generated/api.rs")));
+        assert!(!rust.matches(Path::new("tests/cli.rs")));
+
+        let docs = trigger(&["docs/"], &[], 0);
+        assert!(docs.matches(Path::new("docs/guide/intro.md")));
+        assert!(!docs.matches(Path::new("README.md")));
+    }
+
+    #[tokio::test]
+    async fn test_debounce_collects_changes_until_quiet() {
+        let triggers = vec![trigger(&["src/**"], &[], 1_000)];
+        let mut debouncer = Debouncer::default();
+        let start = Instant::now();
+        debouncer.record(0, PathBuf::from("src/a.rs"), start);
+        debouncer.record(0, PathBuf::from("src/b.rs"), start + Duration::from_millis(800));
+        debouncer.record(0, PathBuf::from("src/a.rs"), start + Duration::from_millis(900));
+
+        assert!(debouncer.take_due(&triggers, start + Duration::from_millis(1_500)).is_empty());
+        assert_eq!(debouncer.next_deadline(&triggers), Some(start + Duration::from_millis(1_900)));
+        let fired = debouncer.take_due(&triggers, start + Duration::from_millis(1_900));
+        assert_eq!(fired, vec![(0, vec![PathBuf::from("src/a.rs"), PathBuf::from("src/b.rs")])]);
+        assert!(debouncer.next_deadline(&triggers).is_none());
+
+        let files = [PathBuf::from("src/a.rs")];
+        assert_eq!(render_prompt(&triggers[0], Path::new("."), &files).await, "Review - src/a.rs");
+
+        let dir = tempfile::tempdir().unwrap();
+        let written = dir.path().canonicalize().unwrap().join("new.rs");
+        note_agent_write(&dir.path().join("new.rs"));
+        assert!(is_agent_write(&written, Instant::now()));
+        assert!(!is_agent_write(&written, Instant::now() + AGENT_WRITE_WINDOW));
+        assert!(!is_agent_write(&written.with_file_name("other.rs"), Instant::now()));
+    }
+}
@@ -0,0 +1,167 @@
+//! Git worktree sandbox
+//!
+//! Lets the agent edit code in a separate git worktree on a new branch, so
+//! the user's checkout stays untouched. When the agent is done its changes
+//! are committed on the branch and shown as a diff; merging the branch (or
+//! discarding it) is up to the user.
+//!
+//! Worktrees are created under `<data dir>/mylm/worktrees/`, outside the
+//! repository, and branches are named `mylm/<label>-<timestamp>`.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+
+/// Longest label used in branch and directory names
+const MAX_LABEL_LEN: usize = 40;
+
+/// A worktree and branch created for one agent run
+#[derive(Debug, Clone)]
+pub struct Worktree {
+    /// Top-level directory of the user's checkout
+    pub repo: PathBuf,
+    /// Directory of the worktree
+    pub path: PathBuf,
+    pub branch: String,
+    /// Commit the branch started from
+    pub base: String,
+}
+
+impl Worktree {
+    /// Create a worktree of the repository containing `dir`, on a new branch
+    /// from its HEAD, named after `label`
+    pub fn create(dir: &Path, label: &str) -> Result<Self> {
+        let root = dirs::data_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("mylm")
+            .join("worktrees");
+        Self::create_in(dir, label, &root)
+    }
+
+    /// Like `create`, with the worktree directory under `root`
+    pub fn create_in(dir: &Path, label: &str, root: &Path) -> Result<Self> {
+        let repo = PathBuf::from(git(dir, &["rev-parse", "--show-toplevel"]).context("Not in a git repository")?);
+        let base = git(&repo, &["rev-parse", "HEAD"]).context("The repository has no commits yet")?;
+        let repo_name = repo.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let name = format!("{}-{}", slug(label), chrono::Local::now().format("%Y%m%d-%H%M%S"));
+        let branch = format!("mylm/{}", name);
+        let path = root.join(format!("{}-{}", repo_name, name));
+        std::fs::create_dir_all(root).with_context(|| format!("Failed to create {}", root.display()))?;
+        git(&repo, &["worktree", "add", "-b", &branch, &path.to_string_lossy(), &base])?;
+        Ok(Self { repo, path, branch, base })
+    }
+
+    /// Where `dir`, inside the user's checkout, is in the worktree
+    pub fn map(&self, dir: &Path) -> PathBuf {
+        match dir.strip_prefix(&self.repo) {
+            Ok(relative) => self.path.join(relative),
+            Err(_) => self.path.clone(),
+        }
+    }
+
+    /// Commit every change left in the worktree; false if there was none
+    pub fn commit(&self, message: &str) -> Result<bool> {
+        if git(&self.path, &["status", "--porcelain"])?.is_empty() {
+            return Ok(false);
+        }
+        git(&self.path, &["add", "-A"])?;
+        git(&self.path, &["commit", "-q", "-m", message])?;
+        Ok(true)
+    }
+
+    /// Whether the branch has commits beyond its base
+    pub fn has_changes(&self) -> Result<bool> {
+        Ok(git(&self.path, &["rev-parse", "HEAD"])? != self.base)
+    }
+
+    /// Summary of the branch's changes: commits and files
+    pub fn stat(&self) -> Result<String> {
+        let range = format!("{}..HEAD", self.base);
+        let log = git(&self.path, &["log", "--oneline", &range])?;
+        let stat = git(&self.path, &["diff", "--stat", &range])?;
+        Ok(format!("{}\n\n{}", log, stat))
+    }
+
+    /// Full diff of the branch against its base
+    pub fn diff(&self) -> Result<String> {
+        git(&self.path, &["diff", &format!("{}..HEAD", self.base)])
+    }
+
+    /// Merge the branch into the user's checkout, then remove the worktree
+    pub fn merge(&self) -> Result<()> {
+        git(&self.repo, &["merge", "--no-edit", &self.branch])?;
+        self.remove()
+    }
+
+    /// Remove the worktree and delete its branch
+    pub fn remove(&self) -> Result<()> {
+        git(&self.repo, &["worktree", "remove", "--force", &self.path.to_string_lossy()])?;
+        git(&self.repo, &["branch", "-D", &self.branch])?;
+        Ok(())
+    }
+}
+
+/// Run git in `dir`; trimmed stdout, or an error with its stderr
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Branch-name form of `label`
+fn slug(label: &str) -> String {
+    crate::util::slug(label, MAX_LABEL_LEN, "task")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_worktree_commit_diff_and_merge() {
+        let temp = TempDir::new().unwrap();
+        let repo = temp.path().join("repo");
+        std::fs::create_dir_all(repo.join("src")).unwrap();
+        for args in [
+            &["init", "-q"][..],
+            &["config", "user.name", "Test"],
+            &["config", "user.email", "test@example.com"],
+        ] {
+            git(&repo, args).unwrap();
+        }
+        std::fs::write(repo.join("src/lib.rs"), "fn a() {}\n").unwrap();
+        git(&repo, &["add", "-A"]).unwrap();
+        git(&repo, &["commit", "-q", "-m", "init"]).unwrap();
+
+        let worktree = Worktree::create_in(&repo.join("src"), "Fix the lints!", &temp.path().join("trees")).unwrap();
+        assert!(worktree.branch.starts_with("mylm/fix-the-lints-"));
+        assert_eq!(worktree.map(&worktree.repo.join("src")), worktree.path.join("src"));
+        assert!(!worktree.commit("nothing").unwrap());
+
+        std::fs::write(worktree.path.join("src/lib.rs"), "fn b() {}\n").unwrap();
+        assert!(worktree.commit("Rename a to b").unwrap());
+        assert!(worktree.has_changes().unwrap());
+        assert!(worktree.diff().unwrap().contains("+fn b() {}"));
+        assert!(worktree.stat().unwrap().contains("Rename a to b"));
+        // The checkout is untouched until the merge
+        assert_eq!(std::fs::read_to_string(repo.join("src/lib.rs")).unwrap(), "fn a() {}\n");
+
+        worktree.merge().unwrap();
+        assert_eq!(std::fs::read_to_string(repo.join("src/lib.rs")).unwrap(), "fn b() {}\n");
+        assert!(!worktree.path.exists());
+        assert_eq!(slug("  "), "task");
+    }
+}
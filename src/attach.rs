@@ -0,0 +1,320 @@
+//! `mylm attach` - watch a server session from another terminal
+//!
+//! Connects to a running `mylm serve`, follows one session and prints its
+//! chat, tool activity and terminal output as it happens. The connection is
+//! read-only: nothing typed here reaches the agent.
+
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use console::style;
+use futures_util::{SinkExt, StreamExt};
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+use uuid::Uuid;
+
+use mylm_core::protocol::{
+    AuthInfo, ClientInfo, ClientMessage, MessageEnvelope, ServerEvent, SessionSummary,
+};
+
+/// Tool output lines shown before cutting
+const TOOL_OUTPUT_LINES: usize = 20;
+
+/// Where and as whom to connect
+pub struct AttachOptions {
+    pub url: String,
+    pub token: Option<String>,
+    /// Session id, id prefix or title fragment; None lists sessions
+    pub watch: Option<String>,
+    pub name: Option<String>,
+}
+
+pub async fn run(options: AttachOptions) -> Result<()> {
+    let (ws, _) = connect_async(options.url.as_str())
+        .await
+        .with_context(|| format!("Could not connect to {}; is `mylm serve` running?", options.url))?;
+    let (mut sink, mut stream) = ws.split();
+
+    let name = options.name.unwrap_or_else(whoami);
+    sink.send(encode(ClientMessage::Hello {
+        client: ClientInfo {
+            name: "mylm-attach".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            display_name: Some(format!("{} (watching)", name)),
+        },
+        auth: AuthInfo {
+            mode: "token".to_string(),
+            pairing_token: options.token,
+        },
+    })?)
+    .await?;
+    sink.send(encode(ClientMessage::ListSessions)?).await?;
+
+    let mut renderer = Renderer::default();
+    let mut watching: Option<Uuid> = None;
+    while let Some(message) = stream.next().await {
+        let Message::Text(text) = message? else {
+            continue;
+        };
+        let Ok(envelope) = serde_json::from_str::<MessageEnvelope<ServerEvent>>(&text) else {
+            continue;
+        };
+        match envelope.payload {
+            ServerEvent::Sessions { sessions } if watching.is_none() => {
+                let Some(query) = options.watch.as_deref() else {
+                    print_sessions(&sessions);
+                    return Ok(());
+                };
+                let session = find_session(&sessions, query)?;
+                println!(
+                    "{} {} {}",
+                    style("Watching").green().bold(),
+                    session.title,
+                    style(format!("({}) - read-only, Ctrl+C to stop", session.session_id)).dim()
+                );
+                watching = Some(session.session_id);
+                sink.send(encode(ClientMessage::SubscribeSession {
+                    session_id: session.session_id,
+                    replay: true,
+                })?)
+                .await?;
+            }
+            ServerEvent::Error { code, message } if code == "unauthorized" => {
+                anyhow::bail!("Server rejected the token: {} (pass --token or set MYLM_TOKEN)", message);
+            }
+            event => {
+                if let Some(output) = renderer.render(&event, watching) {
+                    print!("{}", output);
+                    std::io::stdout().flush()?;
+                }
+            }
+        }
+    }
+    println!("\n{}", style("Server closed the connection").dim());
+    Ok(())
+}
+
+fn encode(message: ClientMessage) -> Result<Message> {
+    let envelope = MessageEnvelope {
+        v: 1,
+        msg_type: "request".to_string(),
+        request_id: None,
+        event_id: None,
+        payload: message,
+    };
+    Ok(Message::Text(serde_json::to_string(&envelope)?))
+}
+
+fn whoami() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "someone".to_string())
+}
+
+fn print_sessions(sessions: &[SessionSummary]) {
+    if sessions.is_empty() {
+        println!("No sessions on this server");
+        return;
+    }
+    for session in sessions {
+        let owner = session.owner.as_deref().map(|o| format!(" [{}]", o)).unwrap_or_default();
+        println!(
+            "{}  {:<12} {}{}",
+            &session.session_id.to_string()[..8],
+            session.status,
+            session.title,
+            owner
+        );
+    }
+    println!("\nWatch one with: mylm attach --watch <id or title>");
+}
+
+/// Match a session by id, id prefix or title fragment
+fn find_session<'a>(sessions: &'a [SessionSummary], query: &str) -> Result<&'a SessionSummary> {
+    let query_lower = query.to_lowercase();
+    let matches: Vec<&SessionSummary> = sessions
+        .iter()
+        .filter(|s| {
+            s.session_id.to_string().starts_with(&query_lower) || s.title.to_lowercase().contains(&query_lower)
+        })
+        .collect();
+    match matches.as_slice() {
+        [one] => Ok(one),
+        [] => anyhow::bail!("No session matches '{}'; run `mylm attach` to list them", query),
+        _ => anyhow::bail!("'{}' matches {} sessions; use more of the id", query, matches.len()),
+    }
+}
+
+/// Turns session events into terminal text
+#[derive(Default)]
+struct Renderer {
+    /// Assistant message currently streaming
+    streaming: Option<Uuid>,
+    /// Whether any of its tokens were printed (replayed history has none)
+    streamed: bool,
+    /// Author of each user message, printed when its text arrives
+    authors: std::collections::HashMap<Uuid, String>,
+}
+
+impl Renderer {
+    fn render(&mut self, event: &ServerEvent, watching: Option<Uuid>) -> Option<String> {
+        let session = event_session(event);
+        if session.is_some() && session != watching {
+            return None;
+        }
+        let mut out = String::new();
+        match event {
+            ServerEvent::MessageStarted { message_id, role, author, .. } => {
+                if role == "user" {
+                    self.authors.insert(*message_id, author.clone().unwrap_or_else(|| "user".to_string()));
+                    return None;
+                }
+                self.finish_stream(&mut out);
+                out.push_str(&format!("\n{}\n", style("── assistant ──").cyan().bold()));
+                self.streaming = Some(*message_id);
+                self.streamed = false;
+            }
+            ServerEvent::TokenDelta { message_id, text, .. } if self.streaming == Some(*message_id) => {
+                self.streamed = true;
+                out.push_str(text);
+            }
+            ServerEvent::MessageFinal { message_id, text, .. } => {
+                if let Some(author) = self.authors.remove(message_id) {
+                    self.finish_stream(&mut out);
+                    out.push_str(&format!("\n{}\n{}\n", style(format!("── {} ──", author)).yellow().bold(), text));
+                } else if self.streaming == Some(*message_id) {
+                    self.streaming = None;
+                    // Live replies were already printed token by token
+                    if !self.streamed {
+                        out.push_str(text);
+                    }
+                    out.push('\n');
+                } else {
+                    out.push_str(&format!("\n{}\n{}\n", style("── assistant ──").cyan().bold(), text));
+                }
+            }
+            ServerEvent::ToolCall { tool, input, .. } => {
+                self.finish_stream(&mut out);
+                let input = match input {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                out.push_str(&format!("{} {} {}\n", style("🔧").bold(), style(tool).bold(), style(input).dim()));
+            }
+            // Its output came as `TerminalOutput`
+            ServerEvent::ToolResult { tool, .. } if tool == "shell" => {}
+            ServerEvent::ToolResult { ok, output, .. } => {
+                let text = match output {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => serde_json::to_string_pretty(other).unwrap_or_default(),
+                };
+                let lines: Vec<&str> = text.lines().collect();
+                for line in lines.iter().take(TOOL_OUTPUT_LINES) {
+                    out.push_str(&format!("   {}\n", if *ok { style(*line).dim() } else { style(*line).red() }));
+                }
+                if lines.len() > TOOL_OUTPUT_LINES {
+                    out.push_str(&format!("   {}\n", style(format!("… {} more lines", lines.len() - TOOL_OUTPUT_LINES)).dim()));
+                }
+            }
+            ServerEvent::ApprovalRequested { summary, .. } => {
+                self.finish_stream(&mut out);
+                out.push_str(&format!("{} {}\n", style("🔒 waiting for approval:").yellow(), summary));
+            }
+            ServerEvent::Activity { kind, detail, .. } => match kind.as_str() {
+                "approved" | "denied" => out.push_str(&format!("   {}\n", style(kind).yellow())),
+                "thought" => out.push_str(&format!("{} {}\n", style("💭").dim(), style(detail.clone().unwrap_or_default()).dim())),
+                _ => out.push_str(&format!("{}\n", style(format!("{}: {}", kind, detail.clone().unwrap_or_default())).dim())),
+            },
+            ServerEvent::TerminalOutput { data, .. } => {
+                let bytes = base64::engine::general_purpose::STANDARD.decode(data).ok()?;
+                out.push_str(&String::from_utf8_lossy(&bytes));
+            }
+            ServerEvent::Presence { participants, turn, .. } => {
+                let turn = turn.as_ref().map(|t| format!(", answering {}", t)).unwrap_or_default();
+                out.push_str(&format!("{}\n", style(format!("👥 {}{}", participants.join(", "), turn)).dim()));
+            }
+            ServerEvent::Error { code, message } => {
+                out.push_str(&format!("{}\n", style(format!("error {}: {}", code, message)).red()));
+            }
+            _ => return None,
+        }
+        Some(out)
+    }
+
+    fn finish_stream(&mut self, out: &mut String) {
+        if self.streaming.take().is_some() {
+            out.push('\n');
+        }
+    }
+}
+
+fn event_session(event: &ServerEvent) -> Option<Uuid> {
+    match event {
+        ServerEvent::MessageStarted { session_id, .. }
+        | ServerEvent::TokenDelta { session_id, .. }
+        | ServerEvent::MessageFinal { session_id, .. }
+        | ServerEvent::Activity { session_id, .. }
+        | ServerEvent::ToolCall { session_id, .. }
+        | ServerEvent::ToolResult { session_id, .. }
+        | ServerEvent::ApprovalRequested { session_id, .. }
+        | ServerEvent::TerminalOutput { session_id, .. }
+        | ServerEvent::Presence { session_id, .. } => Some(*session_id),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mylm_core::provider::TokenUsage;
+
+    fn summary(title: &str) -> SessionSummary {
+        SessionSummary {
+            session_id: Uuid::new_v4(),
+            title: title.to_string(),
+            status: "idle".to_string(),
+            created_at: 0,
+            owner: None,
+        }
+    }
+
+    #[test]
+    fn test_find_session() {
+        let sessions = vec![summary("Fix the login bug"), summary("Deploy staging")];
+        let prefix = &sessions[1].session_id.to_string()[..8];
+        assert_eq!(find_session(&sessions, prefix).unwrap().title, "Deploy staging");
+        assert_eq!(find_session(&sessions, "LOGIN").unwrap().title, "Fix the login bug");
+        assert!(find_session(&sessions, "nothing").is_err());
+    }
+
+    #[test]
+    fn test_renders_only_the_watched_session() {
+        console::set_colors_enabled(false);
+        let watched = Uuid::new_v4();
+        let mut renderer = Renderer::default();
+        let user = Uuid::new_v4();
+        let reply = Uuid::new_v4();
+        let usage = TokenUsage::default();
+
+        let events = [
+            ServerEvent::MessageStarted { session_id: watched, message_id: user, role: "user".into(), author: Some("alice".into()) },
+            ServerEvent::MessageFinal { session_id: watched, message_id: user, text: "why is CI red?".into(), usage: usage.clone() },
+            ServerEvent::MessageStarted { session_id: watched, message_id: reply, role: "assistant".into(), author: None },
+            ServerEvent::TokenDelta { session_id: watched, message_id: reply, seq: 1, text: "A flaky ".into() },
+            ServerEvent::TokenDelta { session_id: watched, message_id: reply, seq: 2, text: "test.".into() },
+            ServerEvent::MessageFinal { session_id: watched, message_id: reply, text: "A flaky test.".into(), usage },
+            ServerEvent::Activity { session_id: Uuid::new_v4(), kind: "status".into(), detail: Some("elsewhere".into()) },
+        ];
+        let text: String = events.iter().filter_map(|e| renderer.render(e, Some(watched))).collect();
+        assert!(text.contains("── alice ──\nwhy is CI red?"));
+        // Streamed text is not printed twice
+        assert_eq!(text.matches("A flaky test.").count(), 1);
+        assert!(!text.contains("elsewhere"));
+
+        // Replayed history has no token deltas, so the final text is printed
+        let mut late = Renderer::default();
+        let replay: String = [&events[2], &events[5]].iter().filter_map(|e| late.render(e, Some(watched))).collect();
+        assert!(replay.contains("A flaky test."));
+    }
+}
@@ -0,0 +1,124 @@
+//! `mylm cmd "<request>"` - turn a plain-language request into one shell
+//! command
+//!
+//! A single completion from the worker model, with a prompt that asks for
+//! the command alone; no agent loop, no tools. The reply is reduced to the
+//! command (fences, `$ ` prompts and prose around it are dropped) and
+//! checked like the shell tool checks what it runs: a blocked command is
+//! refused, and what the command would write, delete or reach over the
+//! network is reported on stderr so stdout holds only the command.
+
+use anyhow::{bail, Context, Result};
+
+use mylm_core::agent::runtime::core::{Shell, ShellKind};
+use mylm_core::agent::tools::CommandPreview;
+use mylm_core::config::Config;
+use mylm_core::provider::LlmClient;
+
+const CMD_INSTRUCTIONS: &str = "Translate the request below into a single shell command. Reply \
+with the command only, on one line, with no explanation and no code fences. Use tools that are \
+commonly installed, prefer commands that only read unless the request asks for a change, and \
+never use sudo unless the request asks for it.";
+
+/// The shell the command is written for, as told to the model
+fn shell_description() -> &'static str {
+    match Shell::for_commands().kind {
+        ShellKind::Posix => "POSIX sh",
+        ShellKind::PowerShell => "PowerShell",
+        ShellKind::Cmd => "Windows cmd.exe",
+    }
+}
+
+fn build_prompt(request: &str) -> String {
+    let cwd = std::env::current_dir().map(|dir| dir.display().to_string()).unwrap_or_default();
+    format!(
+        "{}\n\nOS: {}\nShell: {}\nWorking directory: {}\n\nRequest: {}",
+        CMD_INSTRUCTIONS,
+        std::env::consts::OS,
+        shell_description(),
+        cwd,
+        request
+    )
+}
+
+/// The command in a reply: the first fenced block if there is one, else
+/// the first non-empty line
+fn parse_command(reply: &str) -> Option<String> {
+    let reply = reply.trim();
+    if let Some(start) = reply.find("```") {
+        let block = &reply[start + 3..];
+        // Skip the language tag after the opening fence
+        let block = block.split_once('\n').map_or("", |(_, rest)| rest);
+        let block = block.split("```").next().unwrap_or_default();
+        let command = block.lines().map(strip_prompt).filter(|l| !l.is_empty()).collect::<Vec<_>>().join("\n");
+        return (!command.is_empty()).then_some(command);
+    }
+    reply
+        .lines()
+        .map(strip_prompt)
+        .find(|line| !line.is_empty())
+        .map(|line| line.trim_matches('`').trim().to_string())
+        .filter(|line| !line.is_empty())
+}
+
+/// A line without a leading `$ ` or `> ` prompt
+fn strip_prompt(line: &str) -> &str {
+    let line = line.trim();
+    line.strip_prefix("$ ").or_else(|| line.strip_prefix("> ")).unwrap_or(line).trim()
+}
+
+/// Ask the worker model for the command `request` describes and check it
+pub async fn translate(config: &Config, request: &str) -> Result<CommandPreview> {
+    let llm_config = mylm_core::config::worker_llm_config(config)?
+        .with_max_tokens(256)
+        .with_temperature(0.0);
+    let client = LlmClient::new(llm_config).context("Translating a request needs a configured provider")?;
+    let reply = client.complete(&build_prompt(request)).await?.content();
+    let command = parse_command(&reply).context("The model did not return a command")?;
+    let preview = CommandPreview::analyze(&command);
+    if let Some(reason) = &preview.blocked {
+        bail!("Refusing `{}`: {}", command, reason);
+    }
+    Ok(preview)
+}
+
+/// What the command would change, for stderr; empty for a read-only command
+pub fn warnings(preview: &CommandPreview) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for (label, list) in [
+        ("Writes", &preview.writes),
+        ("Deletes", &preview.deletes),
+        ("May modify", &preview.may_modify),
+        ("Network access", &preview.network),
+    ] {
+        if !list.is_empty() {
+            warnings.push(format!("{}: {}", label, list.join(", ")));
+        }
+    }
+    warnings.extend(preview.notes.iter().cloned());
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(
+            parse_command("find . -size +1G -mtime -7").as_deref(),
+            Some("find . -size +1G -mtime -7")
+        );
+        assert_eq!(
+            parse_command("Here you go:\n```bash\n$ du -sh * | sort -h\n```\nThis lists sizes.").as_deref(),
+            Some("du -sh * | sort -h")
+        );
+        assert_eq!(parse_command("`ls -la`\n").as_deref(), Some("ls -la"));
+        assert_eq!(parse_command("```\n```"), None);
+        assert_eq!(parse_command("  \n"), None);
+
+        let preview = CommandPreview::analyze("rm old.log");
+        assert!(warnings(&preview).iter().any(|w| w.starts_with("Deletes")));
+        assert!(warnings(&CommandPreview::analyze("ls -la")).is_empty());
+    }
+}
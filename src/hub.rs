@@ -8,8 +8,10 @@ use console::Style;
 use dialoguer::{Input, Password, Select, Confirm};
 use inquire::Select as InquireSelect;
 use mylm_core::config::{
-    Config, ProfileConfig, ProviderConfig, ProviderType, SearchProvider,
+    secrets, Config, KeyringStore, ProfileConfig, ProviderConfig, ProviderType, SearchProvider,
 };
+use mylm_core::environment::multiplexer;
+use mylm_core::provider::usage::{self, ModelOffer, ModelRole, Recommendation, UsageLog};
 
 
 /// ============================================================================
@@ -19,7 +21,6 @@ use mylm_core::config::{
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum HubChoice {
     PopTerminal,
-    PopTerminalMissing,
     ResumeSession,
     StartTui,
     StartIncognito,
@@ -33,14 +34,7 @@ pub enum HubChoice {
 impl std::fmt::Display for HubChoice {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            HubChoice::PopTerminal => {
-                if is_tmux_available() {
-                    write!(f, "🚀 Pop Terminal (tmux)")
-                } else {
-                    write!(f, "🚀 Pop Terminal (no tmux)")
-                }
-            },
-            HubChoice::PopTerminalMissing => write!(f, "🚀 Pop Terminal (install tmux)"),
+            HubChoice::PopTerminal => write!(f, "🚀 Pop Terminal ({})", multiplexer::detect().name()),
             HubChoice::ResumeSession => write!(f, "🔄 Resume Session"),
             HubChoice::StartTui => write!(f, "✨ TUI Session"),
             HubChoice::StartIncognito => write!(f, "🕵️  Incognito"),
@@ -64,6 +58,7 @@ pub enum SettingsMenuChoice {
     WorkerLLMSettings,    // Worker LLM comprehensive settings
     TestMainConnection,   // Test Main LLM connection
     TestWorkerConnection, // Test Worker LLM connection
+    ModelRecommendations, // Suggest models from recorded usage
     WebSearchSettings,    // Web search provider config
     MemoryManagement,     // Export, delete, import memory
     ApplicationSettings,  // Global application settings (tmux, alias, etc)
@@ -78,10 +73,11 @@ impl std::fmt::Display for SettingsMenuChoice {
             SettingsMenuChoice::WorkerLLMSettings => write!(f, "⚡ [3] Worker LLM Settings"),
             SettingsMenuChoice::TestMainConnection => write!(f, "🧪 [4] Test Main Connection"),
             SettingsMenuChoice::TestWorkerConnection => write!(f, "🧪 [5] Test Worker Connection"),
-            SettingsMenuChoice::WebSearchSettings => write!(f, "🌐 [6] Web Search"),
-            SettingsMenuChoice::MemoryManagement => write!(f, "🧠 [7] Memory Management"),
-            SettingsMenuChoice::ApplicationSettings => write!(f, "🔧 [8] Application Settings"),
-            SettingsMenuChoice::Back => write!(f, "⬅️  [9] Back"),
+            SettingsMenuChoice::ModelRecommendations => write!(f, "📊 [6] Model Recommendations"),
+            SettingsMenuChoice::WebSearchSettings => write!(f, "🌐 [7] Web Search"),
+            SettingsMenuChoice::MemoryManagement => write!(f, "🧠 [8] Memory Management"),
+            SettingsMenuChoice::ApplicationSettings => write!(f, "🔧 [9] Application Settings"),
+            SettingsMenuChoice::Back => write!(f, "⬅️  [10] Back"),
         }
     }
 }
@@ -164,9 +160,9 @@ impl std::fmt::Display for ApplicationSettingsChoice {
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MemoryManagementChoice {
     ViewMemoryStats,      // Show memory count, size, etc
-    ExportArchive,        // Export all memories to JSON file
+    ExportArchive,        // Export all memories to JSONL file
     DeleteAll,            // Delete all memories (with confirmation)
-    ImportMemories,       // Import memories from JSON file
+    ImportMemories,       // Import memories from JSONL file
     Back,
 }
 
@@ -294,12 +290,8 @@ pub async fn show_hub(_config: &Config) -> Result<HubChoice> {
     // Check if session file exists
     let session_exists = crate::hub::session_exists();
     
-    // Pop Terminal option
-    if is_tmux_available() {
-        options.push(HubChoice::PopTerminal);
-    } else {
-        options.push(HubChoice::PopTerminalMissing);
-    }
+    // Pop Terminal option; without a multiplexer it falls back to the PTY
+    options.push(HubChoice::PopTerminal);
     
     // Resume Session if exists
     if session_exists {
@@ -339,6 +331,7 @@ pub fn show_settings_dashboard(config: &Config) -> Result<SettingsMenuChoice> {
         SettingsMenuChoice::WorkerLLMSettings,
         SettingsMenuChoice::TestMainConnection,
         SettingsMenuChoice::TestWorkerConnection,
+        SettingsMenuChoice::ModelRecommendations,
         SettingsMenuChoice::WebSearchSettings,
         SettingsMenuChoice::MemoryManagement,
         SettingsMenuChoice::ApplicationSettings,
@@ -739,6 +732,13 @@ pub async fn handle_add_provider(config: &mut Config) -> Result<bool> {
             input_price: None,
             output_price: None,
             web_search: Default::default(),
+            native_tools: false,
+            prompt_caching: true,
+            write_permissions: Default::default(),
+            http_permissions: Default::default(),
+            fallbacks: Vec::new(),
+            sandbox: Default::default(),
+            read_only: false,
         };
         config.profiles.insert("default".to_string(), profile);
     }
@@ -806,7 +806,8 @@ pub async fn handle_edit_provider(config: &mut Config) -> Result<bool> {
             .allow_empty_password(true)
             .interact()?;
         if !new_key.is_empty() {
-            provider.api_key = Some(new_key);
+            // A key kept in the keyring stays there
+            provider.api_key = Some(secrets::store_key(provider.api_key.as_deref(), new_key, &KeyringStore::new())?);
         }
         
         // Edit default model
@@ -920,8 +921,8 @@ pub async fn handle_select_main_model(config: &mut Config) -> Result<bool> {
     // Step 2: Fetch models from provider
     println!("\n🔄 Fetching models from {}...", selected_provider);
     
-    let models = match fetch_models(&provider_cfg.base_url, 
-                      &provider_cfg.api_key.clone().unwrap_or_default()).await {
+    let models = match fetch_models(&provider_cfg.resolved_base_url(), 
+                      &provider_cfg.resolved_api_key().unwrap_or_default()).await {
         Ok(m) => {
             if m.is_empty() {
                 println!("   No models returned, using manual entry.");
@@ -1011,8 +1012,8 @@ pub async fn handle_select_worker_model(config: &mut Config) -> Result<bool> {
     // Step 2: Fetch models from provider
     println!("\n🔄 Fetching models from {}...", selected_provider);
     
-    let models = match fetch_models(&provider_cfg.base_url, 
-                      &provider_cfg.api_key.clone().unwrap_or_default()).await {
+    let models = match fetch_models(&provider_cfg.resolved_base_url(), 
+                      &provider_cfg.resolved_api_key().unwrap_or_default()).await {
         Ok(m) => {
             if m.is_empty() {
                 println!("   No models returned, using manual entry.");
@@ -1065,6 +1066,13 @@ pub async fn handle_select_worker_model(config: &mut Config) -> Result<bool> {
             input_price: None,
             output_price: None,
             web_search: Default::default(),
+            native_tools: false,
+            prompt_caching: true,
+            write_permissions: Default::default(),
+            http_permissions: Default::default(),
+            fallbacks: Vec::new(),
+            sandbox: Default::default(),
+            read_only: false,
         };
         config.profiles.insert("worker".to_string(), worker_profile);
     } else {
@@ -1119,13 +1127,13 @@ pub async fn test_profile_connection(config: &mut Config, profile_name: &str) ->
     // Test 1: Check if API key is present (if needed)
     println!("\n🔄 Testing connection...");
     
-    let api_key = provider_cfg.api_key.clone().unwrap_or_default();
+    let api_key = provider_cfg.resolved_api_key().unwrap_or_default();
     if api_key.is_empty() && !provider_cfg.base_url.contains("localhost") && !provider_cfg.base_url.contains("127.0.0.1") {
         println!("   ⚠️  No API key configured (may fail for cloud providers)");
     }
     
     // Test 2: Try to fetch models
-    match fetch_models(&provider_cfg.base_url, &api_key).await {
+    match fetch_models(&provider_cfg.resolved_base_url(), &api_key).await {
         Ok(models) => {
             println!("   ✅ API endpoint reachable");
             println!("   ✅ Found {} models", models.len());
@@ -1168,7 +1176,12 @@ pub async fn test_profile_connection(config: &mut Config, profile_name: &str) ->
 }
 
 /// Fetch models from the API
-async fn fetch_models(base_url: &str, api_key: &str) -> Result<Vec<String>> {
+pub async fn fetch_models(base_url: &str, api_key: &str) -> Result<Vec<String>> {
+    Ok(fetch_model_catalog(base_url, api_key).await?.into_iter().map(|offer| offer.id).collect())
+}
+
+/// Fetch models from the API, with prices and context sizes where published
+async fn fetch_model_catalog(base_url: &str, api_key: &str) -> Result<Vec<ModelOffer>> {
     let client = reqwest::Client::new();
     
     let url = if base_url.ends_with('/') {
@@ -1192,29 +1205,154 @@ async fn fetch_models(base_url: &str, api_key: &str) -> Result<Vec<String>> {
     }
 
     let body: serde_json::Value = response.json().await?;
-    
-    let mut models = Vec::new();
-    if let Some(data) = body.get("data").and_then(|v| v.as_array()) {
-        for model in data {
-            if let Some(id) = model.get("id").and_then(|v| v.as_str()) {
-                models.push(id.to_string());
+    Ok(usage::parse_catalog(&body))
+}
+
+/// How far back usage is considered for recommendations
+const RECOMMENDATION_WINDOW_DAYS: u64 = 30;
+
+/// Profile serving `role` and the model it runs on; None when the role is unused
+fn role_model(config: &Config, role: ModelRole) -> Option<(String, String)> {
+    let profile_name = match role {
+        ModelRole::Main => config.active_profile.clone(),
+        ModelRole::Worker => "worker".to_string(),
+        ModelRole::Categorizer if config.features.fast_path.enabled => config
+            .features
+            .fast_path
+            .profile
+            .clone()
+            .unwrap_or_else(|| config.active_profile.clone()),
+        ModelRole::Categorizer => return None,
+    };
+    let profile = config.profiles.get(&profile_name)?;
+    let model = match role {
+        ModelRole::Categorizer => config.features.fast_path.model.clone(),
+        _ => None,
+    }
+    .or_else(|| profile.model.clone())
+    .or_else(|| config.providers.get(&profile.provider).map(|p| p.default_model.clone()))?;
+    Some((profile_name, model))
+}
+
+/// Switch the role of `rec` to its suggested model and save
+fn apply_recommendation(config: &mut Config, rec: &Recommendation) -> Result<()> {
+    match rec.role {
+        ModelRole::Categorizer => config.features.fast_path.model = Some(rec.suggested.clone()),
+        role => {
+            let Some((profile_name, _)) = role_model(config, role) else {
+                return Ok(());
+            };
+            if let Some(profile) = config.profiles.get_mut(&profile_name) {
+                profile.model = Some(rec.suggested.clone());
             }
+            config.mark_profile_needs_test(&profile_name);
         }
     }
-    
-    // Fallback: try "models" key (some providers use this)
-    if models.is_empty() {
-        if let Some(data) = body.get("models").and_then(|v| v.as_array()) {
-            for model in data {
-                if let Some(id) = model.get("id").and_then(|v| v.as_str()) {
-                    models.push(id.to_string());
-                }
-            }
+    config.save_default()
+}
+
+/// Show recorded usage per model and offer model changes that fit it better
+pub async fn handle_model_recommendations(config: &mut Config) -> Result<bool> {
+    print!("\x1B[2J\x1B[1;1H");
+    println!("\n{}", Style::new().bold().apply_to("Model Recommendations"));
+    println!("{}", Style::new().dim().apply_to("─".repeat(60)));
+
+    if !config.features.telemetry {
+        println!("\nUsage is only recorded while telemetry is enabled.");
+        let enable = Confirm::new()
+            .with_prompt("Record model usage from now on?")
+            .default(true)
+            .interact()?;
+        if enable {
+            config.features.telemetry = true;
+            config.save_default()?;
+            println!("✅ Usage recording enabled; check back after a few sessions");
+            tokio::time::sleep(std::time::Duration::from_millis(800)).await;
+        }
+        return Ok(enable);
+    }
+
+    let Some(log) = UsageLog::default_log() else {
+        println!("\n❌ Could not locate the data directory.");
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        return Ok(false);
+    };
+    let since = usage::now_secs().saturating_sub(RECOMMENDATION_WINDOW_DAYS * 24 * 60 * 60);
+    let stats = usage::summarize(&log.read_since(since)?);
+    if stats.is_empty() {
+        println!("\nNo usage recorded in the last {} days yet.", RECOMMENDATION_WINDOW_DAYS);
+        println!("\nPress Enter to continue...");
+        let _ = std::io::stdin().read_line(&mut String::new());
+        return Ok(false);
+    }
+
+    println!("\nLast {} days:\n", RECOMMENDATION_WINDOW_DAYS);
+    println!("  {:<12} {:<40} {:>8} {:>7} {:>10} {:>10}", "Role", "Model", "Requests", "Failed", "Avg in", "Avg out");
+    for s in &stats {
+        println!(
+            "  {:<12} {:<40} {:>8} {:>6.0}% {:>10} {:>10}",
+            s.role.to_string(),
+            s.model,
+            s.requests,
+            s.failure_rate() * 100.0,
+            s.avg_prompt_tokens(),
+            s.avg_completion_tokens()
+        );
+    }
+
+    println!("\n🔄 Checking provider model lists...");
+    let mut catalogs: std::collections::HashMap<String, Vec<ModelOffer>> = std::collections::HashMap::new();
+    let mut recommendations = Vec::new();
+    for role in [ModelRole::Main, ModelRole::Worker, ModelRole::Categorizer] {
+        let Some((profile_name, model)) = role_model(config, role) else {
+            continue;
+        };
+        let Some(provider_name) = config.profiles.get(&profile_name).map(|p| p.provider.clone()) else {
+            continue;
+        };
+        if !catalogs.contains_key(&provider_name) {
+            let catalog = match config.providers.get(&provider_name) {
+                Some(provider) => fetch_model_catalog(&provider.resolved_base_url(), &provider.resolved_api_key().unwrap_or_default())
+                    .await
+                    .unwrap_or_else(|e| {
+                        println!("   ⚠️  Could not fetch models from {}: {}", provider_name, e);
+                        Vec::new()
+                    }),
+                None => Vec::new(),
+            };
+            catalogs.insert(provider_name.clone(), catalog);
         }
+        recommendations.extend(usage::recommend(role, &model, &stats, &catalogs[&provider_name]));
+    }
+
+    if recommendations.is_empty() {
+        println!("\n✅ No changes suggested: the current models fit your usage.");
+        println!("\nPress Enter to continue...");
+        let _ = std::io::stdin().read_line(&mut String::new());
+        return Ok(false);
     }
 
-    models.sort();
-    Ok(models)
+    let mut items: Vec<String> = recommendations
+        .iter()
+        .map(|r| format!("[{}] {} → {}: {}", r.role, r.current, r.suggested, r.reason))
+        .collect();
+    items.push("Keep current models".to_string());
+    println!();
+    let selection = Select::new()
+        .with_prompt("Apply a recommendation")
+        .items(&items)
+        .default(items.len() - 1)
+        .interact()?;
+    let Some(rec) = recommendations.get(selection) else {
+        return Ok(false);
+    };
+    apply_recommendation(config, rec)?;
+    println!("\n✅ {} model set to: {}", rec.role, rec.suggested);
+    if rec.role != ModelRole::Categorizer {
+        println!("   ⚠️  Run Test Connection to verify configuration");
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(800)).await;
+    Ok(true)
 }
 
 /// Handle web search settings
@@ -1321,7 +1459,11 @@ pub async fn handle_web_search_settings(config: &mut Config) -> Result<bool> {
                         println!("\n✅ API key cleared (will use environment variable)");
                         tokio::time::sleep(tokio::time::Duration::from_millis(800)).await;
                     } else {
-                        profile.web_search.api_key = Some(api_key.clone());
+                        profile.web_search.api_key = Some(secrets::store_key(
+                            profile.web_search.api_key.as_deref(),
+                            api_key.clone(),
+                            &KeyringStore::new(),
+                        )?);
                         config.save_default()?;
                         log::info!("[CONFIG] Web search API key set for provider {:?}", provider);
                         println!("\n✅ API key set");
@@ -1561,6 +1703,13 @@ pub fn set_max_tokens(config: &mut Config, is_main: bool) -> Result<bool> {
             input_price: None,
             output_price: None,
             web_search: Default::default(),
+            native_tools: false,
+            prompt_caching: true,
+            write_permissions: Default::default(),
+            http_permissions: Default::default(),
+            fallbacks: Vec::new(),
+            sandbox: Default::default(),
+            read_only: false,
         };
         config.profiles.insert(profile_name.clone(), new_profile);
     }
@@ -1593,6 +1742,13 @@ pub fn set_condense_threshold(config: &mut Config, is_main: bool) -> Result<bool
             input_price: None,
             output_price: None,
             web_search: Default::default(),
+            native_tools: false,
+            prompt_caching: true,
+            write_permissions: Default::default(),
+            http_permissions: Default::default(),
+            fallbacks: Vec::new(),
+            sandbox: Default::default(),
+            read_only: false,
         };
         config.profiles.insert(profile_name.clone(), new_profile);
     }
@@ -1647,6 +1803,13 @@ pub fn set_input_price(config: &mut Config, is_main: bool) -> Result<bool> {
             input_price: None,
             output_price: None,
             web_search: Default::default(),
+            native_tools: false,
+            prompt_caching: true,
+            write_permissions: Default::default(),
+            http_permissions: Default::default(),
+            fallbacks: Vec::new(),
+            sandbox: Default::default(),
+            read_only: false,
         };
         config.profiles.insert(profile_name.clone(), new_profile);
     }
@@ -1697,6 +1860,13 @@ pub fn set_output_price(config: &mut Config, is_main: bool) -> Result<bool> {
             input_price: None,
             output_price: None,
             web_search: Default::default(),
+            native_tools: false,
+            prompt_caching: true,
+            write_permissions: Default::default(),
+            http_permissions: Default::default(),
+            fallbacks: Vec::new(),
+            sandbox: Default::default(),
+            read_only: false,
         };
         config.profiles.insert(profile_name.clone(), new_profile);
     }
@@ -1747,6 +1917,13 @@ pub fn set_rate_limit_rpm(config: &mut Config, is_main: bool) -> Result<bool> {
             input_price: None,
             output_price: None,
             web_search: Default::default(),
+            native_tools: false,
+            prompt_caching: true,
+            write_permissions: Default::default(),
+            http_permissions: Default::default(),
+            fallbacks: Vec::new(),
+            sandbox: Default::default(),
+            read_only: false,
         };
         config.profiles.insert(profile_name.clone(), new_profile);
     }
@@ -1817,11 +1994,6 @@ pub fn set_max_actions_before_stall(_config: &mut Config, _is_main: bool) -> Res
 /// UTILITY FUNCTIONS
 /// ============================================================================
 
-/// Check if tmux is available
-pub fn is_tmux_available() -> bool {
-    which::which("tmux").is_ok()
-}
-
 /// Check if session file exists
 pub fn session_exists() -> bool {
     dirs::data_dir()
@@ -4,25 +4,384 @@
 //! All handlers are stubbed for individual implementation.
 
 use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
 use mylm_core::config::Config;
 use mylm_core::agent::runtime::orchestrator::commonbox::Commonbox;
 
+mod attach;
+mod cmd;
 mod hub;
+mod pipe;
+mod redact;
+mod review;
+mod server;
 mod settings;
+mod suggest;
+mod task;
+mod testgen;
 mod tui;
 
 use hub::HubChoice;
 use hub::show_hub;
 
+/// ============================================================================
+/// COMMAND LINE
+/// ============================================================================
+
+/// Terminal AI assistant. Run without arguments to open the hub.
+#[derive(Parser)]
+#[command(name = "mylm", version, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Question to answer without opening the hub; text piped on stdin is
+    /// sent with it, e.g. `cat error.log | mylm "explain this"`
+    query: Vec<String>,
+    /// Refuse tool calls that would change files or system state
+    #[arg(long, global = true)]
+    read_only: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Ask one question and print the answer
+    Ask {
+        /// The question
+        #[arg(required = true, trailing_var_arg = true)]
+        query: Vec<String>,
+        /// Image to send with the question (PNG, JPEG, GIF or WebP); repeatable
+        #[arg(long, value_name = "PATH")]
+        image: Vec<PathBuf>,
+    },
+    /// Manage long-term memory
+    Memory {
+        #[command(subcommand)]
+        action: MemoryCommand,
+    },
+    /// Run the agent server for remote clients and the web UI
+    Serve {
+        /// Port for WebSocket clients (and the web UI)
+        #[arg(long, default_value_t = 8765)]
+        port: u16,
+        /// Address to listen on; use 0.0.0.0 to share with teammates
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: String,
+        /// Serve the browser UI on the same port
+        #[arg(long)]
+        web: bool,
+        /// Pairing token clients must present (default: generated once and
+        /// kept in server_token in the config directory)
+        #[arg(long)]
+        token: Option<String>,
+        /// Users file mapping tokens to users (default: users.toml in the config directory)
+        #[arg(long)]
+        users: Option<PathBuf>,
+    },
+    /// Follow a server session read-only; without --watch, list sessions
+    Attach {
+        /// Session id, id prefix or part of its title
+        #[arg(long)]
+        watch: Option<String>,
+        /// Server address
+        #[arg(long, default_value = "ws://127.0.0.1:8765")]
+        url: String,
+        /// Pairing token (default: $MYLM_TOKEN, then the local server's token)
+        #[arg(long)]
+        token: Option<String>,
+        /// Name shown to others in the session
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Run a shell command through the agent's shell tool (safety checks and
+    /// command limits apply)
+    Execute {
+        /// Command to run
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+        /// Show what the command would read, write, delete or reach over the
+        /// network, without running it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Re-run a recorded agent trace with its recorded LLM outputs and
+    /// report the first decision that differs (exits 1 on divergence)
+    Replay {
+        /// Trace file (JSONL) from `traces/` in the config directory, written
+        /// while `features.record_traces` is on
+        trace: PathBuf,
+    },
+    /// Work with saved chat sessions
+    Session {
+        #[command(subcommand)]
+        action: SessionCommand,
+    },
+    /// Run declarative task files without a UI (for CI)
+    Task {
+        #[command(subcommand)]
+        action: TaskCommand,
+    },
+    /// Generate code with the agent
+    Gen {
+        #[command(subcommand)]
+        action: GenCommand,
+    },
+    /// Maintain the configuration file
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+    /// Manage the on-disk LLM response cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheCommand,
+    },
+    /// Run shell commands later: once, on a cron schedule or at an
+    /// interval; times are read in `features.time.timezone`
+    Schedule {
+        #[command(subcommand)]
+        action: ScheduleCommand,
+    },
+    /// Show spend per day and per week, by profile and model (needs
+    /// `features.telemetry`)
+    Costs {
+        /// Days in the daily table
+        #[arg(long, default_value_t = 7)]
+        days: u32,
+        /// Weeks in the weekly table
+        #[arg(long, default_value_t = 4)]
+        weeks: u32,
+    },
+    /// Commit changes with a Conventional Commits message drafted by the
+    /// worker model
+    Commit {
+        /// Files to commit (default: what is staged, or every change)
+        files: Vec<String>,
+        /// Commit without showing the message first
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Review a diff like a pull request: `base..head`, `base` (for
+    /// `base...HEAD`), or the uncommitted changes
+    Review {
+        range: Option<String>,
+        /// Print the findings as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print shell hooks that record each command and its exit code for
+    /// the agent's terminal context, e.g. `eval "$(mylm shell-init bash)"`
+    ShellInit {
+        #[arg(value_parser = ["bash", "zsh", "fish"])]
+        shell: String,
+    },
+    /// Print the shell command for a plain-language request, e.g.
+    /// `mylm cmd find files over 1GB modified this week`
+    Cmd {
+        /// What the command should do
+        #[arg(required = true)]
+        request: Vec<String>,
+        /// Run the command after confirmation
+        #[arg(long)]
+        run: bool,
+    },
+    /// Print a completion for a partly typed shell command (used by the
+    /// `shell-init` keybinding); prints nothing when there is none
+    Suggest {
+        /// The command line typed so far
+        #[arg(allow_hyphen_values = true)]
+        line: String,
+        /// Milliseconds to wait for the model
+        #[arg(long, default_value_t = 1500)]
+        timeout_ms: u64,
+    },
+    /// Restore files changed by agent turns in this workspace; without
+    /// --last, list the turns that can be undone
+    Rollback {
+        /// Undo the most recent turn
+        #[arg(long)]
+        last: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ScheduleCommand {
+    /// Add a job; give exactly one of --at, --cron and --every
+    Add {
+        /// Run once, at a time in words: "tomorrow 9am", "in 2h", "next friday 14:30"
+        #[arg(long)]
+        at: Option<String>,
+        /// Five-field cron expression, e.g. "0 9 * * 1-5"
+        #[arg(long)]
+        cron: Option<String>,
+        /// Interval such as 30m, 2h or 1d
+        #[arg(long)]
+        every: Option<String>,
+        /// Name in the job list (default: the command)
+        #[arg(long)]
+        name: Option<String>,
+        /// Command to run, in the current directory
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
+    /// List jobs with their next run
+    List,
+    /// Remove a job by id or id prefix
+    Remove { id: String },
+    /// Run due jobs every minute until stopped
+    Daemon,
+}
+
+#[derive(Subcommand)]
+enum CacheCommand {
+    /// Remove every cached response
+    Clear,
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Move plaintext API keys into the OS keyring, leaving `secret://`
+    /// references in the config file
+    SecureKeys,
+    /// Check the config file for unknown keys, wrong types, undefined
+    /// profiles or providers and invalid patterns; exits 1 on errors
+    Validate {
+        /// Config file to check (default: the global config)
+        path: Option<PathBuf>,
+    },
+    /// Trust the `.mylm.yaml` and `.mylm/permissions.toml` above a directory
+    /// as they are now, so their profiles, auto-approvals, permissions and
+    /// instructions apply
+    Trust {
+        /// Directory to look from (default: the current directory)
+        path: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum GenCommand {
+    /// Write unit tests for a source file where the project keeps its tests,
+    /// then run them until they compile; exits 0 if they pass, 1 if some
+    /// fail, 2 if they never compiled
+    Tests {
+        file: PathBuf,
+        /// Rounds of fixing compile errors before giving up
+        #[arg(long, default_value_t = 3)]
+        max_attempts: usize,
+        /// Profile to use (default: the active profile)
+        #[arg(long)]
+        profile: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum TaskCommand {
+    /// Run a task file; exits 0 on success, 1 if the success command fails,
+    /// 2 if the agent did not finish (halted, over budget or timed out)
+    Run {
+        file: PathBuf,
+        /// Publish findings as GitHub Actions annotations and a step summary
+        /// (on by default inside GitHub Actions)
+        #[arg(long)]
+        annotations: bool,
+        /// Work in a new git worktree and branch, then show the diff and
+        /// offer to merge it
+        #[arg(long)]
+        worktree: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum SessionCommand {
+    /// Write a copy of a session with secrets and sensitive values replaced
+    /// by placeholders, safe to paste into tickets
+    Redact {
+        /// Session id or id prefix
+        id: String,
+        /// Output file (default: session-<id>-redacted.<ext> in the current directory)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        #[arg(long, value_enum, default_value = "markdown")]
+        format: redact::RedactFormat,
+        /// Skip the LLM pass; only the built-in secret detectors run
+        #[arg(long)]
+        no_llm: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum MemoryCommand {
+    /// List memories, newest first, or the best matches for a search
+    List {
+        /// Show the memories that best match this text
+        #[arg(long)]
+        search: Option<String>,
+        /// Only memories in this category
+        #[arg(long)]
+        category: Option<String>,
+        /// Most memories to show
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        /// List memories compaction has archived instead
+        #[arg(long)]
+        archived: bool,
+    },
+    /// Edit a memory's content and category in your editor
+    Edit {
+        id: i64,
+        /// Set the category without opening the editor (empty to clear it)
+        #[arg(long)]
+        category: Option<String>,
+    },
+    /// Delete memories by id
+    Delete {
+        #[arg(required = true)]
+        ids: Vec<i64>,
+    },
+    /// Write every memory, category and embedding to a JSONL file
+    Export { file: PathBuf },
+    /// Load memories from a JSONL file written by `memory export`
+    Import { file: PathBuf },
+    /// Write a curated memory (title, tags, scope, expiry) in your editor
+    Teach {
+        /// Text to prefill as the memory content
+        content: Vec<String>,
+    },
+    /// List memories that are often rated irrelevant
+    Prune {
+        /// Delete the listed memories
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Archive memories that decayed or outlived their category's TTL
+    Compact {
+        /// Only list what would be archived
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Merge near-duplicate memories into the oldest of each group
+    Dedupe {
+        /// Embedding similarity (0-1) at which memories count as duplicates
+        #[arg(long, default_value_t = mylm_core::memory::dedup::DEFAULT_THRESHOLD)]
+        threshold: f32,
+        /// Only list what would be merged
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
 /// ============================================================================
 /// MAIN ENTRY
 /// ============================================================================
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
     // Initialize debug.log in current directory
     let _ = mylm_core::init_debug_log(Some(std::path::PathBuf::from("debug.log")));
     
@@ -34,6 +393,159 @@ async fn main() -> Result<()> {
     
     // Load configuration
     let mut config = Config::load_or_default();
+    config.read_only = cli.read_only;
+    if let Err(e) = mylm_core::logging::configure(&config.features.log) {
+        eprintln!("Ignoring invalid log filter: {}", e);
+    }
+    // Flushes exported spans when main returns
+    let _tracing = if config.features.tracing.enabled {
+        mylm_core::observability::init(&config.features.tracing, Path::new("debug.log"))
+            .map_err(|e| eprintln!("Tracing is off: {:#}", e))
+            .ok()
+    } else {
+        None
+    };
+    // Unattended commands also follow the project's .mylm.yaml; the global
+    // config stays as loaded for the hub, which saves it
+    let project_config = || {
+        let cwd = std::env::current_dir()?;
+        prompt_project_trust(&cwd)?;
+        config.clone().with_project_overlay(&cwd)
+    };
+
+    // A question on the command line, or input piped without one, is a
+    // one-shot query
+    if cli.command.is_none() && (!cli.query.is_empty() || !std::io::stdin().is_terminal()) {
+        let input = pipe::read_piped_stdin()?;
+        // Without piped input a lone word is more likely a mistyped command
+        // than a question; `cat err.log | mylm explain` is a question
+        if let [word] = cli.query.as_slice() {
+            if input.is_none() && pipe::looks_like_command(word) {
+                anyhow::bail!(
+                    "Unknown command '{}'. See `mylm --help`, or ask with `mylm ask {}`",
+                    word,
+                    word
+                );
+            }
+        }
+        let query = if !cli.query.is_empty() {
+            cli.query.join(" ")
+        } else if input.is_some() {
+            pipe::DEFAULT_QUESTION.to_string()
+        } else {
+            anyhow::bail!("Nothing was piped on stdin. Pass a question, e.g. `mylm \"explain this\"`");
+        };
+        return quick_query(&config, &query, &[], input).await;
+    }
+
+    match cli.command {
+        Some(Command::Ask { query, image }) => {
+            let input = pipe::read_piped_stdin()?;
+            return quick_query(&config, &query.join(" "), &image, input).await;
+        }
+        Some(Command::Memory { action }) => return run_memory_command(&config, action).await,
+        Some(Command::Serve { port, bind, web, token, users }) => {
+            return server::start_server(server::ServerOptions { bind, port, web, token, users }).await;
+        }
+        Some(Command::Attach { watch, url, token, name }) => {
+            let token = token
+                .or_else(|| std::env::var("MYLM_TOKEN").ok())
+                .or_else(server::pairing::stored_token);
+            return attach::run(attach::AttachOptions { url, token, watch, name }).await;
+        }
+        Some(Command::Execute { command, dry_run }) => {
+            return run_execute_command(&project_config()?, &command.join(" "), dry_run).await;
+        }
+        Some(Command::Replay { trace }) => {
+            return run_replay_command(&trace).await;
+        }
+        Some(Command::Session { action: SessionCommand::Redact { id, output, format, no_llm } }) => {
+            return redact::run(&config, redact::RedactOptions { id, output, format, use_llm: !no_llm }).await;
+        }
+        Some(Command::Task { action: TaskCommand::Run { file, annotations, worktree } }) => {
+            let annotations = annotations || task::github::in_github_actions();
+            let report = task::run(&project_config()?, &file, annotations, worktree).await?;
+            if let task::TaskStatus::Incomplete(reason) = &report.status {
+                eprintln!("Task did not finish: {}", reason);
+            }
+            std::process::exit(report.status.exit_code());
+        }
+        Some(Command::Gen { action: GenCommand::Tests { file, max_attempts, profile } }) => {
+            let status = testgen::run(&project_config()?, testgen::GenTestsOptions { file, max_attempts, profile }).await?;
+            if let testgen::GenStatus::Unfinished(reason) = &status {
+                eprintln!("Tests are not done: {}", reason);
+            }
+            std::process::exit(status.exit_code());
+        }
+        Some(Command::Config { action: ConfigCommand::SecureKeys }) => {
+            return run_secure_keys(&mut config);
+        }
+        Some(Command::Config { action: ConfigCommand::Validate { path } }) => {
+            return run_validate_config(path);
+        }
+        Some(Command::Config { action: ConfigCommand::Trust { path } }) => {
+            return run_trust_project(path);
+        }
+        Some(Command::Cache { action: CacheCommand::Clear }) => {
+            return run_cache_clear(&config);
+        }
+        Some(Command::Schedule { action }) => {
+            return run_schedule_command(&config, action).await;
+        }
+        Some(Command::Costs { days, weeks }) => {
+            return run_costs(days, weeks);
+        }
+        Some(Command::Commit { files, yes }) => {
+            return run_commit(&project_config()?, &files, yes).await;
+        }
+        Some(Command::Review { range, json }) => {
+            let report = review::run(&project_config()?, range.as_deref()).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                print!("{}", review::render(&report));
+            }
+            std::process::exit(report.exit_code());
+        }
+        Some(Command::ShellInit { shell }) => {
+            use mylm_core::environment::shell_hooks::{self, HookShell};
+            let shell = HookShell::from_name(&shell).context("Unsupported shell")?;
+            let log = shell_hooks::log_path().context("No data directory for the command log")?;
+            print!("{}", shell_hooks::init_script(shell, &log));
+            return Ok(());
+        }
+        Some(Command::Cmd { request, run }) => {
+            let config = project_config()?;
+            let preview = cmd::translate(&config, &request.join(" ")).await?;
+            for warning in cmd::warnings(&preview) {
+                eprintln!("⚠️  {}", warning);
+            }
+            if !run {
+                println!("{}", preview.command);
+                return Ok(());
+            }
+            eprintln!("{}", preview.command);
+            let confirmed = dialoguer::Confirm::new()
+                .with_prompt("Run this command?")
+                .default(preview.is_read_only())
+                .interact()?;
+            if !confirmed {
+                return Ok(());
+            }
+            return run_execute_command(&config, &preview.command, false).await;
+        }
+        Some(Command::Suggest { line, timeout_ms }) => {
+            let timeout = std::time::Duration::from_millis(timeout_ms);
+            if let Some(completion) = suggest::suggest(&project_config()?, &line, timeout).await? {
+                println!("{}", completion);
+            }
+            return Ok(());
+        }
+        Some(Command::Rollback { last }) => {
+            return run_rollback(last);
+        }
+        None => {}
+    }
     
     // Check for first-run onboarding
     if !config.is_initialized() && config.providers.is_empty() {
@@ -42,12 +554,575 @@ async fn main() -> Result<()> {
         return Ok(());
     }
     
+    prompt_project_trust(&std::env::current_dir()?)?;
     // Show hub menu
     run_hub_menu(&mut config).await?;
     
     Ok(())
 }
 
+/// Run `mylm config secure-keys`
+fn run_secure_keys(config: &mut Config) -> Result<()> {
+    use mylm_core::config::{secrets, KeyringStore};
+
+    let moved = secrets::secure_keys(config, &KeyringStore::new())?;
+    if moved.is_empty() {
+        println!("No plaintext API keys in the config.");
+        return Ok(());
+    }
+    config.save_default()?;
+    for name in &moved {
+        println!("🔒 {} -> {}", name, secrets::reference(name));
+    }
+    println!("Moved {} key(s) to the OS keyring.", moved.len());
+    Ok(())
+}
+
+/// Run `mylm config validate`, printing findings as `file:line:col: level: message`
+fn run_validate_config(path: Option<PathBuf>) -> Result<()> {
+    let path = path
+        .or_else(Config::default_path)
+        .context("Could not determine the config file location")?;
+    let diagnostics = mylm_core::config::validate::validate_file(&path)?;
+    for diagnostic in &diagnostics {
+        match diagnostic.line {
+            Some(_) => eprintln!("{}:{}", path.display(), diagnostic),
+            None => eprintln!("{}: {}", path.display(), diagnostic),
+        }
+    }
+    let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+    if errors > 0 {
+        eprintln!("{} error(s) in {}", errors, path.display());
+        std::process::exit(1);
+    }
+    println!("✅ {} is valid", path.display());
+    Ok(())
+}
+
+/// Ask whether to trust a new or changed `.mylm.yaml` or
+/// `.mylm/permissions.toml` above `cwd`
+///
+/// Only asked on a terminal and only when a file sets something that needs
+/// trust; otherwise it applies in its narrowing-only form.
+fn prompt_project_trust(cwd: &Path) -> Result<()> {
+    use mylm_core::agent::runtime::governance::write_scope::untrusted_overlay_file;
+    use mylm_core::config::{ProjectConfig, ProjectTrust};
+
+    if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+        return Ok(());
+    }
+    let project = ProjectConfig::discover(cwd)?.filter(|p| !p.trusted && !p.untrusted_sections().is_empty());
+    let permissions = untrusted_overlay_file(cwd);
+    if project.is_none() && permissions.is_none() {
+        return Ok(());
+    }
+    if let Some(project) = &project {
+        let path = project.root.join(mylm_core::config::project::FILE_NAME);
+        eprintln!("⚠️  {} is new or changed. It sets: {}", path.display(), project.untrusted_sections().join(", "));
+    }
+    if let Some(path) = &permissions {
+        eprintln!("⚠️  {} is new or changed. It sets: write auto_approve", path.display());
+    }
+    let trusted = dialoguer::Confirm::new()
+        .with_prompt("Trust it? Until then only its restrictions apply")
+        .default(false)
+        .interact()?;
+    if trusted {
+        let mut trust = ProjectTrust::load();
+        if let Some(project) = &project {
+            trust.trust(project);
+        }
+        if let Some(path) = &permissions {
+            trust.trust_file(path)?;
+        }
+        trust.save()?;
+    }
+    Ok(())
+}
+
+/// Run `mylm config trust`
+fn run_trust_project(path: Option<PathBuf>) -> Result<()> {
+    use mylm_core::agent::runtime::governance::write_scope::untrusted_overlay_file;
+    use mylm_core::config::{ProjectConfig, ProjectTrust};
+
+    let start = match path {
+        Some(path) => path,
+        None => std::env::current_dir()?,
+    };
+    let project = ProjectConfig::discover(&start)?;
+    let permissions = untrusted_overlay_file(&start);
+    if project.is_none() && permissions.is_none() {
+        anyhow::bail!("No {} at or above {}", mylm_core::config::project::FILE_NAME, start.display());
+    }
+    let mut trust = ProjectTrust::load();
+    if let Some(project) = &project {
+        trust.trust(project);
+    }
+    if let Some(path) = &permissions {
+        trust.trust_file(path)?;
+    }
+    trust.save()?;
+    if let Some(project) = &project {
+        println!("✅ Trusted {}", project.root.join(mylm_core::config::project::FILE_NAME).display());
+    }
+    if let Some(path) = &permissions {
+        println!("✅ Trusted {}", path.display());
+    }
+    Ok(())
+}
+
+/// Run `mylm cache clear`
+fn run_cache_clear(config: &Config) -> Result<()> {
+    use mylm_core::provider::ResponseCache;
+
+    let cache = ResponseCache::from_settings(&config.features.response_cache)
+        .context("Could not determine the cache directory")?;
+    let removed = cache.clear()?;
+    println!("🧹 Removed {} cached response(s) from {}", removed, cache.dir().display());
+    Ok(())
+}
+
+/// Run `mylm schedule`
+async fn run_schedule_command(config: &Config, action: ScheduleCommand) -> Result<()> {
+    use mylm_core::environment::TimeContext;
+    use mylm_core::scheduler::{IntervalSchedule, JobAction, JobSchedule, JobStore, ScheduledJob, SchedulerDaemon, ShellAction};
+
+    let store = JobStore::new()?;
+    let time = TimeContext::detect(&config.features.time);
+    match action {
+        ScheduleCommand::Add { at, cron, every, name, command } => {
+            let action = JobAction::Shell(ShellAction {
+                program: command[0].clone(),
+                args: command[1..].to_vec(),
+                cwd: std::env::current_dir().ok(),
+                env: Default::default(),
+                timeout_secs: None,
+            });
+            let name = name.unwrap_or_else(|| command.join(" "));
+            let job = match (at, cron, every) {
+                (Some(at), None, None) => ScheduledJob::once(name, &at, &time, action).map_err(anyhow::Error::msg)?,
+                (None, Some(cron), None) => ScheduledJob::cron(name, &cron, &time, action).map_err(anyhow::Error::msg)?,
+                (None, None, Some(every)) => {
+                    let schedule = IntervalSchedule { every: every.as_str().into(), offset: None, timezone: Default::default() };
+                    if schedule.every.duration().is_none() {
+                        anyhow::bail!("--every takes a number and s, m, h or d, e.g. 30m");
+                    }
+                    ScheduledJob::new_now(name, JobSchedule::Interval(schedule), action)
+                }
+                _ => anyhow::bail!("Give exactly one of --at, --cron and --every"),
+            };
+            let mut jobs = store.load_jobs()?;
+            let next = job.next_run_at.map(|at| at.with_timezone(&time.timezone()).format("%Y-%m-%d %H:%M %Z").to_string());
+            println!("✅ Scheduled {} ({}), next run {}", job.name, &job.id.to_string()[..8], next.as_deref().unwrap_or("when the daemon starts"));
+            jobs.jobs.push(job);
+            store.save_jobs(&jobs)?;
+        }
+        ScheduleCommand::List => {
+            let jobs = store.load_jobs()?;
+            if jobs.jobs.is_empty() {
+                println!("No scheduled jobs.");
+            }
+            for job in &jobs.jobs {
+                let next = match job.next_run_at {
+                    _ if !job.enabled => "done".to_string(),
+                    Some(at) => at.with_timezone(&time.timezone()).format("%Y-%m-%d %H:%M %Z").to_string(),
+                    None => "next daemon tick".to_string(),
+                };
+                println!("{}  {:<24}  {}", &job.id.to_string()[..8], next, job.name);
+            }
+        }
+        ScheduleCommand::Remove { id } => {
+            let mut jobs = store.load_jobs()?;
+            let matching: Vec<usize> = (0..jobs.jobs.len()).filter(|&i| jobs.jobs[i].id.to_string().starts_with(&id)).collect();
+            let [index] = matching[..] else {
+                anyhow::bail!("'{}' matches {} jobs; give more of the id", id, matching.len());
+            };
+            let job = jobs.jobs.remove(index);
+            store.save_jobs(&jobs)?;
+            println!("🗑️  Removed {}", job.name);
+        }
+        ScheduleCommand::Daemon => {
+            let daemon = SchedulerDaemon::new(store).with_time_context(time);
+            let result = tokio::select! {
+                result = daemon.start_loop() => result,
+                _ = tokio::signal::ctrl_c() => Ok(()),
+            };
+            daemon.cleanup();
+            result?;
+        }
+    }
+    Ok(())
+}
+
+/// Run `mylm commit`: draft a message for the pending change, let the user
+/// accept or edit it, and commit
+async fn run_commit(config: &Config, files: &[String], yes: bool) -> Result<()> {
+    use mylm_core::agent::tools::git_commit;
+    use mylm_core::provider::LlmClient;
+
+    let guard = mylm_core::agent::runtime::governance::SecretGuard::from_config(config);
+    let diff = git_commit::pending_diff(files, &guard).await.map_err(anyhow::Error::msg)?;
+    let files = git_commit::commit_files(files).await.map_err(anyhow::Error::msg)?;
+    let client = mylm_core::config::worker_llm_config(config)
+        .map_err(anyhow::Error::from)
+        .and_then(LlmClient::new)
+        .context("Drafting a commit message needs a configured provider")?;
+    let mut message = git_commit::draft_message(&client, &diff).await.map_err(anyhow::Error::msg)?;
+    if !files.is_empty() {
+        println!("Files: {}", files.join(", "));
+    }
+    if !yes {
+        println!("\n{}\n", message);
+        let choice = dialoguer::Select::new()
+            .with_prompt("Commit with this message?")
+            .items(&["Commit", "Edit message", "Cancel"])
+            .default(0)
+            .interact()?;
+        let edited = match choice {
+            0 => Some(message.clone()),
+            1 => dialoguer::Editor::new().edit(&message)?.filter(|text| !text.trim().is_empty()),
+            _ => None,
+        };
+        match edited {
+            Some(text) => message = text.trim().to_string(),
+            None => {
+                println!("Commit cancelled.");
+                return Ok(());
+            }
+        }
+    }
+    let summary = git_commit::commit(&files, &message).await.map_err(anyhow::Error::msg)?;
+    println!("✅ {}", summary.trim_end());
+    Ok(())
+}
+
+/// Run `mylm rollback`: undo the latest turn in the workspace's undo journal
+fn run_rollback(last: bool) -> Result<()> {
+    use mylm_core::agent::tools::UndoJournal;
+
+    let journal = UndoJournal::for_workspace(&std::env::current_dir()?);
+    if last {
+        let turn = journal.rollback_last().map_err(anyhow::Error::msg)?;
+        println!("↩️  Undid {}", turn.summary());
+        return Ok(());
+    }
+    let turns = journal.turns();
+    if turns.is_empty() {
+        println!("No agent edits to undo in this workspace.");
+        return Ok(());
+    }
+    println!("Turns, latest first (`mylm rollback --last` undoes the first):");
+    for turn in turns.iter().rev() {
+        println!("{}", turn.summary());
+    }
+    Ok(())
+}
+
+/// Run `mylm costs`: daily and weekly spend from the usage log
+fn run_costs(days: u32, weeks: u32) -> Result<()> {
+    use mylm_core::provider::costs::{ledger, Period};
+    use mylm_core::provider::usage::{now_secs, UsageLog};
+
+    let log = UsageLog::default_log().context("Could not determine the data directory")?;
+    let now = now_secs();
+    let since = Period::Day
+        .start_of_last(days, now, &chrono::Local)
+        .min(Period::Week.start_of_last(weeks, now, &chrono::Local));
+    let records = log.read_since(since)?;
+    if records.is_empty() {
+        println!("No usage recorded in {}.", log.path().display());
+        println!("Enable features.telemetry to keep a cost ledger.");
+        return Ok(());
+    }
+
+    for (period, count, title) in [(Period::Day, days, "Daily"), (Period::Week, weeks, "Weekly")] {
+        let start = period.start_of_last(count, now, &chrono::Local);
+        let window: Vec<_> = records.iter().filter(|r| r.at >= start).cloned().collect();
+        let lines = ledger(&window, period, &chrono::Local);
+        println!("\n{} spend", title);
+        println!(
+            "  {:<10} {:<14} {:<32} {:>8} {:>12} {:>12} {:>10}",
+            "Period", "Profile", "Model", "Requests", "Tokens in", "Tokens out", "Cost"
+        );
+        for line in &lines {
+            println!(
+                "  {:<10} {:<14} {:<32} {:>8} {:>12} {:>12} {:>10}",
+                line.period,
+                line.profile,
+                line.model,
+                line.requests,
+                line.prompt_tokens,
+                line.completion_tokens,
+                format!("${:.4}", line.cost)
+            );
+        }
+        let total: f64 = lines.iter().map(|line| line.cost).sum();
+        println!("  {:<10} {:>93}", "Total", format!("${:.4}", total));
+    }
+    if records.iter().all(|r| r.cost == 0.0) {
+        println!("\nNo prices are configured: set input_price and output_price on profiles and fallbacks.");
+    }
+    Ok(())
+}
+
+/// Run `mylm execute`; a failing or blocked command exits with status 1
+async fn run_execute_command(config: &Config, command: &str, dry_run: bool) -> Result<()> {
+    use mylm_core::agent::runtime::core::{RuntimeContext, ToolCapability};
+    use mylm_core::agent::tools::ShellTool;
+    use mylm_core::agent::types::events::ToolResult;
+    use mylm_core::agent::types::intents::ToolCall;
+
+    let tool = ShellTool::new().with_limits(config.features.command_limits.clone());
+    let call = ToolCall {
+        name: "shell".to_string(),
+        arguments: serde_json::json!({ "command": command, "dry_run": dry_run }),
+        working_dir: None,
+        timeout_secs: None,
+    };
+    match tool.execute(&RuntimeContext::new(), call).await? {
+        ToolResult::Success { output, .. } => {
+            print!("{}", output);
+            Ok(())
+        }
+        ToolResult::Error { message, .. } => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+        ToolResult::Cancelled => anyhow::bail!("Command was cancelled"),
+    }
+}
+
+/// Replay a trace and print whether the engine still makes the same decisions
+async fn run_replay_command(path: &std::path::Path) -> Result<()> {
+    use mylm_core::agent::cognition::{AgentDecision, LlmEngine};
+    use mylm_core::agent::runtime::executor::trace::load_trace;
+    use mylm_core::agent::runtime::TraceEntry;
+
+    let trace = load_trace(path).map_err(anyhow::Error::msg)?;
+    // The system prompt carries the recording time; reuse the recorded one
+    let system_prompt = trace.iter().find_map(|entry| match entry {
+        TraceEntry::Transition { decision: AgentDecision::RequestLLM(req), .. } => Some(req.context.system_prompt.clone()),
+        _ => None,
+    });
+    let engine = match system_prompt {
+        Some(prompt) => LlmEngine::new().with_system_prompt(prompt),
+        None => LlmEngine::new(),
+    };
+    let report = mylm_core::agent::session::replay(engine, &trace).await;
+
+    let describe = |decision: &Option<AgentDecision>| -> String {
+        let text = match decision {
+            None => return "(nothing)".to_string(),
+            Some(AgentDecision::RequestLLM(req)) => format!("RequestLLM with {} history message(s)", req.context.history.len()),
+            Some(AgentDecision::CallTool(call)) => format!("CallTool {} {}", call.name, call.arguments),
+            Some(other) => format!("{:?}", other),
+        };
+        text.chars().take(300).collect()
+    };
+    match &report.divergence {
+        None if report.unused_events == 0 => {
+            println!("Replayed {} input(s), {} decision(s): identical", report.inputs, report.transitions);
+            Ok(())
+        }
+        None => {
+            println!(
+                "Replayed {} decision(s) identically, but {} recorded runtime event(s) were never requested",
+                report.transitions, report.unused_events
+            );
+            std::process::exit(1);
+        }
+        Some(divergence) => {
+            println!("Diverged at decision {} of {}:", divergence.index + 1, report.transitions);
+            println!("  recorded: {}", describe(&divergence.expected));
+            println!("  replayed: {}", describe(&divergence.actual));
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Run a `mylm memory` subcommand against the default memory store
+async fn run_memory_command(config: &Config, action: MemoryCommand) -> Result<()> {
+    use mylm_core::agent::memory::{edit, teach, AgentMemoryManager};
+    use mylm_core::config::agent::MemoryConfig;
+    use mylm_core::memory::transfer;
+
+    let manager = AgentMemoryManager::new(MemoryConfig {
+        enabled: true,
+        embedding: config.features.memory_settings.embedding.clone(),
+        retention: config.features.memory_settings.retention.clone(),
+        backend: config.features.memory_settings.backend,
+        ..MemoryConfig::default()
+    })
+    .await?;
+
+    match action {
+        MemoryCommand::List { search, category, limit, archived } => {
+            let store = manager.store();
+            let memories = match (&search, &category) {
+                _ if archived => {
+                    let query = search.as_deref().unwrap_or_default().to_lowercase();
+                    let mut memories = manager.archived_memories()?;
+                    memories.reverse();
+                    memories.retain(|m| m.content.to_lowercase().contains(&query));
+                    memories
+                }
+                (Some(query), _) => store.search_memory(query, limit).await?,
+                (None, Some(category)) => store.get_memories_by_category(category).await?,
+                (None, None) => store.get_recent_memories(limit).await?,
+            };
+            let memories: Vec<_> = memories
+                .into_iter()
+                .filter(|m| category.is_none() || m.category_id == category)
+                .take(limit)
+                .collect();
+            if memories.is_empty() {
+                println!("No memories found.");
+                return Ok(());
+            }
+            for memory in &memories {
+                let preview: String = memory.content.chars().take(80).collect();
+                let category = memory
+                    .category_id
+                    .as_deref()
+                    .filter(|c| !c.is_empty())
+                    .map(|c| format!(" ({})", c))
+                    .unwrap_or_default();
+                println!("{}  [{}]{}  {}", memory.id, memory.r#type, category, preview.replace('\n', " "));
+            }
+        }
+        MemoryCommand::Edit { id, category } => {
+            let store = manager.store();
+            let Some(memory) = store.get_memory_by_id(id).await? else {
+                anyhow::bail!("No memory with id {}", id);
+            };
+            let change = match category {
+                Some(category) => Some(edit::MemoryEdit {
+                    category: Some(category).filter(|c| !c.is_empty()),
+                    ..edit::MemoryEdit::of(&memory)
+                }),
+                None => {
+                    let editor = config.app.editor.clone();
+                    let original = memory.clone();
+                    tokio::task::spawn_blocking(move || edit::edit_interactive(&editor, &original)).await??
+                }
+            };
+            match change {
+                Some(change) if change.apply(store.as_ref(), &memory).await? => println!("Updated memory {}", id),
+                _ => println!("Nothing changed."),
+            }
+        }
+        MemoryCommand::Delete { ids } => {
+            for id in &ids {
+                if manager.store().get_memory_by_id(*id).await?.is_none() {
+                    anyhow::bail!("No memory with id {}", id);
+                }
+            }
+            for id in &ids {
+                manager.delete_memory(*id).await?;
+            }
+            println!("Deleted {} memor{}.", ids.len(), if ids.len() == 1 { "y" } else { "ies" });
+        }
+        MemoryCommand::Export { file } => {
+            let stats = transfer::export_to_file(manager.store().as_ref(), &file).await?;
+            println!(
+                "Exported {} memories and {} categories to {}",
+                stats.memories,
+                stats.categories,
+                file.display()
+            );
+        }
+        MemoryCommand::Import { file } => {
+            let stats = transfer::import_from_file(manager.store().as_ref(), &file).await?;
+            println!(
+                "Imported {} memories and {} categories from {} ({} already present, {} failed)",
+                stats.memories,
+                stats.categories,
+                file.display(),
+                stats.skipped,
+                stats.failed
+            );
+        }
+        MemoryCommand::Teach { content } => {
+            let editor = config.app.editor.clone();
+            let prefill = content.join(" ");
+            let taught = tokio::task::spawn_blocking(move || teach::teach_interactive(&editor, &prefill)).await??;
+            let Some(memory) = taught else {
+                println!("Nothing saved.");
+                return Ok(());
+            };
+            let cwd = std::env::current_dir()?;
+            let id = manager.teach(&memory, &cwd).await?;
+            println!("Saved memory {} ({} scope)", id, memory.scope);
+        }
+        MemoryCommand::Prune { apply } => {
+            let suggestions = manager.prune_suggestions().await?;
+            if suggestions.is_empty() {
+                println!("No memories to prune.");
+                return Ok(());
+            }
+            for (memory, counts) in &suggestions {
+                let preview: String = memory.content.chars().take(80).collect();
+                println!(
+                    "{}  +{} / -{}  {}",
+                    memory.id,
+                    counts.helpful,
+                    counts.irrelevant,
+                    preview.replace('\n', " ")
+                );
+            }
+            if apply {
+                for (memory, _) in &suggestions {
+                    manager.delete_memory(memory.id).await?;
+                }
+                println!("Deleted {} memories.", suggestions.len());
+            } else {
+                println!("\n{} memories suggested for pruning. Run with --apply to delete them.", suggestions.len());
+            }
+        }
+        MemoryCommand::Compact { dry_run } => {
+            let archived = manager.compact(&config.features.memory_settings.retention, dry_run).await?;
+            if archived.is_empty() {
+                println!("No memories to archive.");
+                return Ok(());
+            }
+            for (memory, reason) in &archived {
+                let preview: String = memory.content.chars().take(80).collect();
+                println!("{}  ({})  {}", memory.id, reason, preview.replace('\n', " "));
+            }
+            if dry_run {
+                println!("\n{} memories would be archived. Run without --dry-run to archive them.", archived.len());
+            } else {
+                println!("\nArchived {} memories. `mylm memory list --archived` shows them.", archived.len());
+            }
+        }
+        MemoryCommand::Dedupe { threshold, dry_run } => {
+            if !(0.0..=1.0).contains(&threshold) {
+                anyhow::bail!("--threshold must be between 0 and 1");
+            }
+            let groups = manager.dedupe(threshold, dry_run).await?;
+            if groups.is_empty() {
+                println!("No duplicate memories found.");
+                return Ok(());
+            }
+            for group in &groups {
+                let preview: String = group.keeper.content.chars().take(80).collect();
+                println!("{}  {}", group.keeper.id, preview.replace('\n', " "));
+                for (duplicate, similarity) in &group.duplicates {
+                    let preview: String = duplicate.content.chars().take(72).collect();
+                    println!("  ← {}  ({:.2})  {}", duplicate.id, similarity, preview.replace('\n', " "));
+                }
+            }
+            let merged: usize = groups.iter().map(|g| g.duplicates.len()).sum();
+            if dry_run {
+                println!("\n{} memories would be merged into {}. Run without --dry-run to merge them.", merged, groups.len());
+            } else {
+                println!("\nMerged {} duplicate memories into {}.", merged, groups.len());
+            }
+        }
+    }
+    Ok(())
+}
+
 /// ============================================================================
 /// HUB MENU LOOP - EXACT original structure with stubs
 /// ============================================================================
@@ -56,16 +1131,21 @@ async fn run_hub_menu(config: &mut Config) -> Result<()> {
     loop {
         match show_hub(config).await? {
             HubChoice::PopTerminal => {
-                // Pop Terminal = TUI Session with tmux context injected
-                println!("\n[STUB] Pop Terminal - Starting TUI with tmux context injection...\n");
-                // TODO: Inject tmux context into TUI session
-                // This would capture the current tmux pane's content and inject it into the terminal parser
-                if let Err(e) = run_tui_with_session(config, false).await {
-                    eprintln!("TUI error: {}", e);
+                // Pop Terminal = TUI Session with the terminal's scrollback injected
+                let backend = mylm_core::environment::multiplexer::detect().name();
+                let scrollback = mylm_core::environment::multiplexer::capture_scrollback();
+                match &scrollback {
+                    Some(_) => println!("\n🚀 Pop Terminal - Starting TUI with the {} scrollback...\n", backend),
+                    None => println!("\n🚀 Pop Terminal - No {} scrollback to bring along, starting TUI...\n", backend),
+                }
+                match run_tui_with_session(config, false, scrollback.as_deref()).await {
+                    Ok(tui::TuiResult::ReturnToHub) => {}
+                    Ok(tui::TuiResult::Exit) => {
+                        println!("\n👋 Goodbye!\n");
+                        return Ok(());
+                    }
+                    Err(e) => eprintln!("TUI error: {}", e),
                 }
-            }
-            HubChoice::PopTerminalMissing => {
-                println!("\n⚠️  tmux is not installed. Please install tmux to use Pop Terminal.\n");
             }
             HubChoice::ResumeSession => {
                 use crate::tui::app::session_manager::SessionManager;
@@ -88,7 +1168,7 @@ async fn run_hub_menu(config: &mut Config) -> Result<()> {
                 }
             }
             HubChoice::StartTui => {
-                match run_tui_with_session(config, false).await {
+                match run_tui_with_session(config, false, None).await {
                     Ok(tui::TuiResult::ReturnToHub) => {
                         // Continue to next hub iteration
                     }
@@ -104,7 +1184,7 @@ async fn run_hub_menu(config: &mut Config) -> Result<()> {
             HubChoice::StartIncognito => {
                 // Incognito = TUI Session without memory enabled
                 println!("\n🕵️  Incognito Mode - Starting TUI without memory persistence...\n");
-                match run_tui_with_session(config, false).await {
+                match run_tui_with_session(config, false, None).await {
                     Ok(tui::TuiResult::ReturnToHub) => {}
                     Ok(tui::TuiResult::Exit) => {
                         println!("\n👋 Goodbye!\n");
@@ -117,7 +1197,7 @@ async fn run_hub_menu(config: &mut Config) -> Result<()> {
                 let query: String = dialoguer::Input::new()
                     .with_prompt("Enter your query")
                     .interact()?;
-                quick_query(config, &query).await?;
+                quick_query(config, &query, &[], None).await?;
             }
             HubChoice::ManageSessions => {
                 // Manage Sessions = Load/view/delete saved TUI Sessions
@@ -186,7 +1266,9 @@ async fn run_session_manager(config: &Config) -> Result<()> {
         // Build menu items
         let mut items: Vec<String> = sessions.iter().enumerate().map(|(i, s)| {
             let date = s.timestamp.format("%Y-%m-%d %H:%M");
-            let preview = if s.metadata.last_message_preview.len() > 30 {
+            let preview = if let Some(ref title) = s.metadata.title {
+                title.clone()
+            } else if s.metadata.last_message_preview.len() > 30 {
                 format!("{}...", &s.metadata.last_message_preview[..30])
             } else {
                 s.metadata.last_message_preview.clone()
@@ -322,139 +1404,47 @@ async fn setup_wizard(config: &mut Config) -> Result<()> {
 /// TUI SESSION WRAPPER - Composition root for TUI
 /// ============================================================================
 
-async fn run_tui_with_session(config: &Config, resume: bool) -> Result<tui::TuiResult> {
-    
-    use mylm_core::agent::runtime::Session;
-    use tokio::sync::mpsc;
-    
-    mylm_core::info_log!("[MAIN] Starting TUI session (profile: {}, resume: {})", config.active_profile, resume);
-    
-    // Create PTY manager FIRST (needed for terminal executor)
-    let cwd = std::env::current_dir().ok();
-    let (pty_manager, pty_rx) = match cwd {
-        Some(path) => match tui::spawn_pty(Some(path)) {
-            Ok((pm, rx)) => (pm, rx),
-            Err(e) => {
-                mylm_core::error_log!("[MAIN] Failed to spawn PTY: {}", e);
-                eprintln!("❌ Failed to spawn PTY: {}", e);
-                return Ok(tui::TuiResult::ReturnToHub);
-            }
-        },
-        None => {
-            mylm_core::error_log!("[MAIN] Could not determine current directory");
-            eprintln!("❌ Could not determine current directory");
-            return Ok(tui::TuiResult::ReturnToHub);
+/// Start the `features.watch` triggers for the directory mylm runs in
+fn start_file_watcher(config: &Config) -> Option<mylm_core::watch::FileWatcher> {
+    if !config.features.watch.enabled {
+        return None;
+    }
+    let root = std::env::current_dir().ok()?;
+    match mylm_core::watch::FileWatcher::start(&root, &config.features.watch) {
+        Ok(watcher) => {
+            mylm_core::info_log!("[MAIN] Watching {} for triggers {:?}", root.display(), watcher.triggers());
+            Some(watcher)
         }
-    };
-    
-    // Create job registry
-    let job_registry = tui::app::types::JobRegistry::new();
-    
-    // Create App with PTY (but no session yet)
-    // Note: AppStateContainer::new is now async
-    let mut app = tui::app::App::new(
-        pty_manager,
-        config.clone(),
-        job_registry,
-        false, // not incognito
-    ).await;
-    app.pty_rx = Some(pty_rx);
-    
-    // Create approval capability for interactive tool approval
-    let (approval_capability, approval_rx) = tui::app::approval::TuiApprovalCapability::new();
-    
-    // Create terminal executor that uses the App's screen
-    let app_weak = Arc::downgrade(&std::sync::Arc::new(std::sync::Mutex::new(())));
-    let _ = app_weak; // Placeholder - will use actual app reference
-    
-    // For now, use default terminal executor (commands run via std::process::Command)
-    // The terminal context is collected separately via app.get_screen()
-    
-    // Create commonbox for worker spawning (enables delegate tool)
-    let commonbox = Arc::new(Commonbox::new());
-    
-    // Create agent session factory with approval and commonbox
-    let factory = tui::agent_setup::create_session_factory(
-        config,
-        None, // Use default terminal executor for now
-        Some(Arc::new(approval_capability)),
-        Some(commonbox), // Enable worker spawning
-    );
-    
-    // Create session - resumable if requested
-    let (mut session, session_data) = if resume {
-        match factory.create_resumable_session().await {
-            Ok((s, data)) => (s, data),
-            Err(e) => {
-                mylm_core::error_log!("[MAIN] Failed to create resumable agent session: {}", e);
-                eprintln!("❌ Failed to create agent session: {}", e);
-                return Ok(tui::TuiResult::ReturnToHub);
-            }
+        Err(e) => {
+            mylm_core::warn_log!("[MAIN] File watcher not started: {}", e);
+            None
         }
-    } else {
-        match factory.create_default_session().await {
-            Ok(s) => (s, None),
-            Err(e) => {
-                mylm_core::error_log!("[MAIN] Failed to create agent session: {}", e);
-                eprintln!("❌ Failed to create agent session: {}", e);
-                return Ok(tui::TuiResult::ReturnToHub);
-            }
+    }
+}
+
+async fn run_tui_with_session(config: &Config, resume: bool, scrollback: Option<&str>) -> Result<tui::TuiResult> {
+    mylm_core::info_log!("[MAIN] Starting TUI session (profile: {}, resume: {})", config.active_profile, resume);
+
+    let mut tab = match tui::tabs::start_tab(config, resume).await {
+        Ok(tab) => tab,
+        Err(e) => {
+            mylm_core::error_log!("[MAIN] Failed to start TUI session: {:#}", e);
+            eprintln!("❌ {:#}", e);
+            return Ok(tui::TuiResult::ReturnToHub);
         }
     };
-    
-    // Restore session data if available (UI stays dumb, just displays what core provides)
-    if let Some(ref data) = session_data {
-        app.restore_from_session(data);
-        println!("✅ Previous session restored with {} messages", data.history.len());
+    if resume && !tab.app.chat_history.is_empty() {
+        println!("✅ Previous session restored with {} messages", tab.app.chat_history.len());
     }
-    
-    // Get input sender and subscribe to output events
-    let input_tx = session.input_sender();
-    let mut broadcast_rx = session.subscribe_output();
-    
-    // Create mpsc channel to bridge broadcast to unbounded for TUI
-    let (output_tx, output_rx) = mpsc::unbounded_channel::<mylm_core::agent::OutputEvent>();
-    
-    // Spawn bridge task to forward events from broadcast to mpsc
-    tokio::spawn(async move {
-        let mut event_count = 0u64;
-        let mut chunk_count = 0u64;
-        loop {
-            match broadcast_rx.recv().await {
-                Ok(event) => {
-                    event_count += 1;
-                    use mylm_core::agent::OutputEvent;
-                    if matches!(event, OutputEvent::ResponseChunk { .. }) {
-                        chunk_count += 1;
-                    } else {
-                        mylm_core::debug_log!("[BRIDGE] Forwarding event #{} (chunks={}): {:?}", 
-                            event_count, chunk_count, std::mem::discriminant(&event));
-                        chunk_count = 0; // Reset after reporting
-                    }
-                    if output_tx.send(event).is_err() {
-                        mylm_core::warn_log!("[BRIDGE] Output channel closed, stopping bridge");
-                        break;
-                    }
-                }
-                Err(e) => {
-                    mylm_core::warn_log!("[BRIDGE] Broadcast recv error: {:?}", e);
-                    break;
-                }
-            }
-        }
-        mylm_core::debug_log!("[BRIDGE] Bridge task ended, forwarded {} events", event_count);
-    });
-    
-    // Set the input channel
-    app.input_tx = Some(input_tx);
-    
-    let session_handle = tokio::spawn(async move {
-        mylm_core::debug_log!("[SESSION_TASK] Session started");
-        session.run().await
-    });
-    
+    // The terminal pane starts with the scrollback Pop Terminal captured
+    if let Some(scrollback) = scrollback {
+        tab.app.process_terminal_data(format!("{}\r\n", scrollback).as_bytes());
+    }
+    // File triggers go to the first tab only
+    tab.app.file_watcher = start_file_watcher(&tab.app.config);
+
     // Run TUI
-    match tui::run_tui_session(app, output_rx, approval_rx, session_handle).await {
+    match tui::run_tui_session(tab).await {
         Ok(result) => {
             mylm_core::debug_log!("[MAIN] TUI session ended: {:?}", result);
             Ok(result)
@@ -468,104 +1458,39 @@ async fn run_tui_with_session(config: &Config, resume: bool) -> Result<tui::TuiR
 
 /// Run TUI with a saved session (restores chat history)
 async fn run_tui_with_saved_session(config: &Config, saved_session: crate::tui::app::session::Session) -> Result<tui::TuiResult> {
-    use mylm_core::agent::runtime::Session;
-    use tokio::sync::mpsc;
-    
+    use crate::tui::app::TimestampedChatMessage;
+
     mylm_core::info_log!("[MAIN] Starting TUI with saved session (id: {}, messages: {})", 
         saved_session.id, saved_session.history.len());
-    
-    // Create PTY manager
-    let cwd = std::env::current_dir().ok();
-    let (pty_manager, pty_rx) = match cwd {
-        Some(path) => match tui::spawn_pty(Some(path)) {
-            Ok((pm, rx)) => (pm, rx),
-            Err(e) => {
-                mylm_core::error_log!("[MAIN] Failed to spawn PTY: {}", e);
-                eprintln!("❌ Failed to spawn PTY: {}", e);
-                return Ok(tui::TuiResult::ReturnToHub);
-            }
-        },
-        None => {
-            mylm_core::error_log!("[MAIN] Could not determine current directory");
-            eprintln!("❌ Could not determine current directory");
+
+    // A new agent session; we don't restore agent state, just UI state
+    let mut tab = match tui::tabs::start_tab(config, false).await {
+        Ok(tab) => tab,
+        Err(e) => {
+            mylm_core::error_log!("[MAIN] Failed to start TUI session: {:#}", e);
+            eprintln!("❌ {:#}", e);
             return Ok(tui::TuiResult::ReturnToHub);
         }
     };
-    
-    // Create job registry
-    let job_registry = tui::app::types::JobRegistry::new();
-    
-    // Create App with PTY
-    let mut app = tui::app::App::new(
-        pty_manager,
-        config.clone(),
-        job_registry,
-        false, // not incognito
-    ).await;
-    app.pty_rx = Some(pty_rx);
-    
+
     // Restore chat history from saved session
-    use crate::tui::app::TimestampedChatMessage;
+    let app = &mut tab.app;
     app.chat_history = saved_session.history.into_iter()
         .map(TimestampedChatMessage::from)
         .collect();
     app.session_id = saved_session.id;
-    
+    app.title_requested = saved_session.metadata.title.is_some();
+    app.session_title = saved_session.metadata.title;
+    app.bookmarks = saved_session.metadata.bookmarks;
+    app.pins = saved_session.metadata.pins;
+    tui::app::controls::model::restore_message_models(&mut app.chat_history, &saved_session.metadata.models);
+    app.send_pins();
+    app.file_watcher = start_file_watcher(&app.config);
+
     println!("✅ Loaded {} messages from saved session", app.chat_history.len());
-    
-    // Create approval capability
-    let (approval_capability, approval_rx) = tui::app::approval::TuiApprovalCapability::new();
-    
-    // Create commonbox for worker spawning
-    let commonbox = Arc::new(Commonbox::new());
-    
-    // Create agent session factory
-    let factory = tui::agent_setup::create_session_factory(
-        config,
-        None,
-        Some(Arc::new(approval_capability)),
-        Some(commonbox),
-    );
-    
-    // Create new agent session (we don't restore agent state, just UI state)
-    let mut session = match factory.create_default_session().await {
-        Ok(s) => s,
-        Err(e) => {
-            mylm_core::error_log!("[MAIN] Failed to create agent session: {}", e);
-            eprintln!("❌ Failed to create agent session: {}", e);
-            return Ok(tui::TuiResult::ReturnToHub);
-        }
-    };
-    
-    // Get input sender and subscribe to output events
-    let input_tx = session.input_sender();
-    let mut broadcast_rx = session.subscribe_output();
-    
-    // Create mpsc channel to bridge broadcast to unbounded for TUI
-    let (output_tx, output_rx) = mpsc::unbounded_channel::<mylm_core::agent::OutputEvent>();
-    
-    // Spawn bridge task
-    tokio::spawn(async move {
-        loop {
-            match broadcast_rx.recv().await {
-                Ok(event) => {
-                    if output_tx.send(event).is_err() {
-                        break;
-                    }
-                }
-                Err(_) => break,
-            }
-        }
-    });
-    
-    app.input_tx = Some(input_tx);
-    
-    let session_handle = tokio::spawn(async move {
-        session.run().await
-    });
-    
+
     // Run TUI
-    tui::run_tui_session(app, output_rx, approval_rx, session_handle).await
+    tui::run_tui_session(tab).await
         .map_err(|e| e.into())
 }
 
@@ -573,16 +1498,40 @@ async fn run_tui_with_saved_session(config: &Config, saved_session: crate::tui::
 /// QUICK QUERY - Using new Agent Session Factory
 /// ============================================================================
 
-async fn quick_query(config: &Config, query: &str) -> Result<()> {
+async fn quick_query(config: &Config, query: &str, images: &[PathBuf], piped: Option<String>) -> Result<()> {
     println!("\n⚡ Quick Query: {}", query);
+
+    // Piped input goes with the question, cut to the context budget
+    let mut message = match piped {
+        Some(input) => {
+            let budget = pipe::token_budget(config.active_profile().context_window);
+            println!("📥 Read {} lines from stdin", input.lines().count());
+            pipe::with_piped_input(query, &input, budget)
+        }
+        None => query.to_string(),
+    };
+
+    // Read the images before starting a session; they go with the message as data
+    let mut attachments = Vec::new();
+    for path in images {
+        let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.clone());
+        attachments.push(mylm_core::provider::chat::ImageAttachment::from_path(&path)?);
+        println!("🖼️  Attached {}", path.display());
+        message.push('\n');
+        message.push_str(&mylm_core::provider::chat::image_marker(&path));
+    }
     
     // Create agent session factory with commonbox (enables delegate tool)
     use mylm_core::agent::AgentSessionFactory;
     use mylm_core::agent::runtime::Session as ContractSession;
     
     let commonbox = Arc::new(Commonbox::new());
+    // Tool approvals are asked on the terminal, under the same policy as the TUI
+    use mylm_core::agent::runtime::capabilities::{PolicyApprovalCapability, TerminalApprovalCapability};
+    let approval = PolicyApprovalCapability::from_config(config, Some(Arc::new(TerminalApprovalCapability::new())));
     let factory = AgentSessionFactory::new(config.clone())
-        .with_commonbox(commonbox);
+        .with_commonbox(commonbox)
+        .with_approval(Arc::new(approval));
     
     // Create session for default profile
     let mut session = match factory.create_default_session().await {
@@ -598,7 +1547,12 @@ async fn quick_query(config: &Config, query: &str) -> Result<()> {
     
     // Submit user input
     use mylm_core::agent::UserInput;
-    if let Err(e) = session.submit_input(UserInput::Message(query.to_string())).await {
+    let input = if attachments.is_empty() {
+        UserInput::Message(message)
+    } else {
+        UserInput::MessageWithImages { content: message, images: attachments }
+    };
+    if let Err(e) = session.submit_input(input).await {
         println!("❌ Failed to submit input: {}", e);
         return Ok(());
     }
@@ -608,7 +1562,11 @@ async fn quick_query(config: &Config, query: &str) -> Result<()> {
         session.run().await
     });
     
-    // Print output events as they arrive
+    // Print output events as they arrive, showing only the final answer
+    // decoded from the streamed Short-Key JSON
+    use mylm_core::agent::types::parser::{ShortKeyStreamParser, StreamField};
+    let mut stream_parser = ShortKeyStreamParser::new();
+    let mut raw_response = String::new();
     println!("\n🤔 Thinking...\n");
     loop {
         match output_rx.try_recv() {
@@ -616,9 +1574,19 @@ async fn quick_query(config: &Config, query: &str) -> Result<()> {
                 use mylm_core::agent::OutputEvent;
                 match event {
                     OutputEvent::ResponseChunk { content } => {
-                        print!("{}", content);
+                        raw_response.push_str(&content);
+                        for delta in stream_parser.push(&content) {
+                            if delta.field == StreamField::Final {
+                                print!("{}", delta.text);
+                            }
+                        }
+                        let _ = std::io::Write::flush(&mut std::io::stdout());
                     }
                     OutputEvent::ResponseComplete { .. } => {
+                        // Plain-text replies never open a JSON object; show them as-is
+                        if stream_parser.final_answer().is_empty() && stream_parser.thought().is_empty() {
+                            print!("{}", raw_response.trim());
+                        }
                         println!("\n");
                         break;
                     }
@@ -0,0 +1,166 @@
+//! Piped standard input for one-shot queries
+//!
+//! `cat error.log | mylm "explain this"` sends the piped text along with the
+//! question. At most `MAX_STDIN_BYTES` are read; the rest is drained in the
+//! background so the writer is never left blocked on a full pipe. Input that
+//! does not fit the profile's context budget loses its middle: the start
+//! and, at greater length, the end are kept, since logs usually end with
+//! the error. Stdin is used up by the input, so tool approvals are asked on
+//! `/dev/tty`.
+
+use std::io::{IsTerminal, Read};
+
+use anyhow::{Context, Result};
+use mylm_core::conversation::TokenCounter;
+
+/// Most bytes read from stdin
+pub const MAX_STDIN_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Share of the context window piped input may use
+const CONTEXT_SHARE: f64 = 0.5;
+
+/// Question asked when input is piped without one
+pub const DEFAULT_QUESTION: &str = "Explain this input.";
+
+/// Text piped on stdin; None when stdin is a terminal or nothing was piped
+pub fn read_piped_stdin() -> Result<Option<String>> {
+    let stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        return Ok(None);
+    }
+    let mut bytes = Vec::new();
+    stdin
+        .lock()
+        .take(MAX_STDIN_BYTES + 1)
+        .read_to_end(&mut bytes)
+        .context("Cannot read standard input")?;
+    let cut = bytes.len() as u64 > MAX_STDIN_BYTES;
+    if cut {
+        std::thread::spawn(|| std::io::copy(&mut std::io::stdin(), &mut std::io::sink()));
+    }
+    bytes.truncate(MAX_STDIN_BYTES as usize);
+    let mut text = String::from_utf8_lossy(&bytes).into_owned();
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    if cut {
+        text.push_str(&format!("\n[... input cut at {} MB ...]", MAX_STDIN_BYTES / (1024 * 1024)));
+    }
+    Ok(Some(text))
+}
+
+/// Whether a one-word query looks like a subcommand name, such as a typo
+/// of `status`, rather than a question
+pub fn looks_like_command(word: &str) -> bool {
+    word.len() > 1 && word.chars().all(|c| c.is_ascii_lowercase() || c == '-')
+}
+
+/// Tokens piped input may use with a `context_window`-token model
+pub fn token_budget(context_window: usize) -> usize {
+    (context_window as f64 * CONTEXT_SHARE) as usize
+}
+
+/// `text` cut down to about `max_tokens`, keeping whole lines from the
+/// start (a quarter) and the end (the rest)
+///
+/// When not even one line fits, as with minified JSON, characters are cut
+/// instead.
+pub fn fit_to_budget(text: &str, max_tokens: usize) -> String {
+    if TokenCounter::estimate(text) <= max_tokens {
+        return text.to_string();
+    }
+    let lines: Vec<&str> = text.lines().collect();
+    // Room for the omission note
+    let budget = max_tokens.saturating_sub(20);
+    let fits = |limit: usize| {
+        let mut used = 0;
+        move |line: &&&str| {
+            used += TokenCounter::estimate(line) + 1;
+            used <= limit
+        }
+    };
+    let head = lines.iter().take_while(fits(budget / 4)).count();
+    let tail = lines[head..].iter().rev().take_while(fits(budget - budget / 4)).count();
+    if head == 0 && tail == 0 {
+        return fit_chars(text, max_tokens);
+    }
+    let omitted = lines.len() - head - tail;
+    format!(
+        "{}\n[... {} lines omitted to fit the context window ...]\n{}",
+        lines[..head].join("\n"),
+        omitted,
+        lines[lines.len() - tail..].join("\n")
+    )
+}
+
+/// `text` cut down to about `max_tokens` by characters, a quarter from the
+/// start and the rest from the end
+fn fit_chars(text: &str, max_tokens: usize) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let budget = max_tokens.saturating_sub(20);
+    // Start from the text's own characters per token and shrink until it fits
+    let mut keep = chars.len() * budget / TokenCounter::estimate(text).max(1);
+    loop {
+        let head = keep / 4;
+        let tail = keep - head;
+        let fitted = format!(
+            "{}\n[... {} characters omitted to fit the context window ...]\n{}",
+            chars[..head].iter().collect::<String>(),
+            chars.len() - keep,
+            chars[chars.len() - tail..].iter().collect::<String>()
+        );
+        if keep == 0 || TokenCounter::estimate(&fitted) <= max_tokens {
+            return fitted;
+        }
+        keep = keep * 9 / 10;
+    }
+}
+
+/// The question with the piped `input` attached as a fenced section
+pub fn with_piped_input(question: &str, input: &str, max_tokens: usize) -> String {
+    let input = fit_to_budget(input.trim_end_matches('\n'), max_tokens);
+    // A fence longer than any backtick run inside keeps the section closed
+    let longest_run = input.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat((longest_run + 1).max(3));
+    format!(
+        "{}\n\n## Piped input ({} lines)\n{}text\n{}\n{}",
+        question,
+        input.lines().count(),
+        fence,
+        input,
+        fence
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_large_input_keeps_start_and_end() {
+        assert_eq!(fit_to_budget("short", 100), "short");
+
+        let log: String = (1..=1000).map(|i| format!("line {:04}\n", i)).collect();
+        let fitted = fit_to_budget(&log, 500);
+        assert!(TokenCounter::estimate(&fitted) <= 500, "{}", TokenCounter::estimate(&fitted));
+        assert!(fitted.starts_with("line 0001\n"));
+        assert!(fitted.ends_with("line 1000"));
+        assert!(fitted.contains("lines omitted to fit the context window"));
+
+        // One line larger than the budget
+        let json = format!("{{\"start\": 1, {}\"end\": 2}}", "\"key\": \"value\", ".repeat(2000));
+        let fitted = fit_to_budget(&json, 500);
+        assert!(TokenCounter::estimate(&fitted) <= 500, "{}", TokenCounter::estimate(&fitted));
+        assert!(fitted.starts_with("{\"start\": 1"));
+        assert!(fitted.ends_with("\"end\": 2}"));
+        assert!(fitted.contains("characters omitted to fit the context window"));
+
+        let message = with_piped_input("explain this", "error: ```boom```\n", 100);
+        assert_eq!(message, "explain this\n\n## Piped input (1 lines)\n````text\nerror: ```boom```\n````");
+        assert_eq!(token_budget(8192), 4096);
+        assert!(looks_like_command("stauts"));
+        assert!(looks_like_command("shell-init"));
+        assert!(!looks_like_command("why?"));
+        assert!(!looks_like_command("Kubernetes"));
+    }
+}
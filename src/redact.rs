@@ -0,0 +1,211 @@
+//! `mylm session redact` — shareable copies of saved sessions
+//!
+//! Runs the secret detectors over every message of a saved TUI session, then
+//! (unless `--no-llm`) asks the default model for anything sensitive they
+//! missed, such as internal hostnames, customer names or personal data. Each
+//! value is replaced with a placeholder and the result is written to a new
+//! file; the saved session itself is never modified.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use mylm_core::agent::runtime::governance::SecretRedactor;
+use mylm_core::config::Config;
+use mylm_core::provider::LlmClient;
+use mylm_core::provider::chat::ChatMessage;
+
+use crate::tui::app::session::Session;
+use crate::tui::app::session_manager::SessionManager;
+
+/// Transcript text sent to the model per request
+const LLM_CHUNK_CHARS: usize = 24_000;
+
+const LLM_INSTRUCTIONS: &str = "The transcript below is about to be shared outside the team. List every value in it that should not be shared: credentials, tokens, internal hostnames and IP addresses, email addresses, personal names, customer or account identifiers. Placeholders like [REDACTED_API_KEY_1] are already safe; skip them. Reply with only a JSON array of the exact strings as they appear in the transcript, or [] if there are none.\n\nTranscript:\n";
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum RedactFormat {
+    Markdown,
+    Json,
+}
+
+pub struct RedactOptions {
+    pub id: String,
+    pub output: Option<PathBuf>,
+    pub format: RedactFormat,
+    pub use_llm: bool,
+}
+
+pub async fn run(config: &Config, options: RedactOptions) -> Result<()> {
+    let mut session = find_session(&options.id)?;
+    let mut redactor = SecretRedactor::new();
+
+    for message in session.history.iter_mut().chain(session.agent_history.iter_mut()) {
+        apply(message, |text| redactor.redact(text));
+    }
+    if let Some(title) = &session.metadata.title {
+        session.metadata.title = Some(redactor.redact(title));
+    }
+
+    if options.use_llm {
+        let messages = session.history.iter().chain(session.agent_history.iter());
+        let values = llm_findings(config, &transcript(messages)).await?;
+        if !values.is_empty() {
+            for message in session.history.iter_mut().chain(session.agent_history.iter_mut()) {
+                apply(message, |text| redactor.redact_values(text, &values, "SENSITIVE"));
+            }
+        }
+    }
+
+    // Raw terminal output is not part of the shareable copy
+    session.terminal_history.clear();
+    session.metadata.last_message_preview.clear();
+
+    let short_id: String = session.id.chars().take(8).collect();
+    let output = options.output.unwrap_or_else(|| {
+        let extension = match options.format {
+            RedactFormat::Markdown => "md",
+            RedactFormat::Json => "json",
+        };
+        PathBuf::from(format!("session-{}-redacted.{}", short_id, extension))
+    });
+    let content = match options.format {
+        RedactFormat::Markdown => markdown(&session),
+        RedactFormat::Json => serde_json::to_string_pretty(&session)?,
+    };
+    write_new(&output, &content)?;
+
+    if redactor.total() == 0 {
+        eprintln!("No sensitive values found.");
+    } else {
+        for (kind, count) in redactor.counts() {
+            eprintln!("  {:<16} {}", kind, count);
+        }
+        eprintln!("Redacted {} distinct value(s).", redactor.total());
+    }
+    eprintln!("Wrote {}", output.display());
+    Ok(())
+}
+
+/// Find a saved session by id or unique id prefix
+fn find_session(id: &str) -> Result<Session> {
+    let matches: Vec<Session> = SessionManager::load_sessions()
+        .into_iter()
+        .filter(|s| s.id.starts_with(id))
+        .collect();
+    match matches.len() {
+        0 => anyhow::bail!("No saved session matches '{}'", id),
+        1 => Ok(matches.into_iter().next().expect("one match")),
+        n => {
+            if let Some(exact) = matches.iter().find(|s| s.id == id) {
+                return Ok(exact.clone());
+            }
+            anyhow::bail!("'{}' matches {} sessions; use a longer id", id, n)
+        }
+    }
+}
+
+fn apply(message: &mut ChatMessage, mut redact: impl FnMut(&str) -> String) {
+    message.content = redact(&message.content);
+    if let Some(reasoning) = &message.reasoning_content {
+        message.reasoning_content = Some(redact(reasoning));
+    }
+    for call in message.tool_calls.iter_mut().flatten() {
+        call.function.arguments = redact(&call.function.arguments);
+    }
+}
+
+/// Every text `apply` redacts: content, reasoning and tool call arguments
+fn transcript<'a>(messages: impl Iterator<Item = &'a ChatMessage>) -> String {
+    messages
+        .map(|m| {
+            let mut text = format!("{}: {}", m.role.as_str(), m.content);
+            if let Some(reasoning) = &m.reasoning_content {
+                text.push_str(&format!("\n(reasoning) {}", reasoning));
+            }
+            for call in m.tool_calls.iter().flatten() {
+                text.push_str(&format!("\n(tool call {}) {}", call.function.name, call.function.arguments));
+            }
+            text
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Ask the model for sensitive values the detectors missed
+async fn llm_findings(config: &Config, transcript: &str) -> Result<Vec<String>> {
+    let client = mylm_core::config::default_llm_config(config)
+        .map_err(anyhow::Error::from)
+        .and_then(LlmClient::new)
+        .context("LLM pass needs a configured provider (use --no-llm to skip it)")?;
+    let mut values = Vec::new();
+    for chunk in chunks(transcript, LLM_CHUNK_CHARS) {
+        let response = client.complete(&format!("{}{}", LLM_INSTRUCTIONS, chunk)).await?;
+        values.extend(parse_values(&response.content()));
+    }
+    Ok(values)
+}
+
+/// Split on char boundaries into pieces of at most `size` chars
+fn chunks(text: &str, size: usize) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    chars.chunks(size).map(|c| c.iter().collect()).collect()
+}
+
+/// The JSON array in a model reply, tolerating prose or code fences around it
+fn parse_values(reply: &str) -> Vec<String> {
+    let (Some(start), Some(end)) = (reply.find('['), reply.rfind(']')) else {
+        return Vec::new();
+    };
+    if end < start {
+        return Vec::new();
+    }
+    serde_json::from_str::<Vec<String>>(&reply[start..=end]).unwrap_or_default()
+}
+
+fn markdown(session: &Session) -> String {
+    let title = session.metadata.title.as_deref().unwrap_or("Untitled session");
+    let mut md = format!(
+        "# {}\n\n_Session {} · {} · redacted with mylm_\n",
+        title,
+        session.id,
+        session.timestamp.format("%Y-%m-%d %H:%M UTC")
+    );
+    for message in &session.history {
+        md.push_str(&format!("\n## {}\n\n{}\n", message.role.as_str(), message.content.trim()));
+    }
+    md
+}
+
+/// Write `content` to a file that must not exist yet
+fn write_new(path: &Path, content: &str) -> Result<()> {
+    use std::io::Write;
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .with_context(|| format!("Failed to create {} (it must not exist)", path.display()))?;
+    file.write_all(content.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_values() {
+        assert_eq!(
+            parse_values("Here you go:\n```json\n[\"db-7.corp\", \"Jane Doe\"]\n```"),
+            vec!["db-7.corp", "Jane Doe"]
+        );
+        assert!(parse_values("[]").is_empty());
+        assert!(parse_values("nothing sensitive").is_empty());
+        assert!(parse_values("] oops [").is_empty());
+
+        let history = vec![ChatMessage::user("deploy it")];
+        let mut agent = ChatMessage::assistant("done");
+        agent.reasoning_content = Some("ssh db-7.corp".to_string());
+        let text = transcript(history.iter().chain(std::iter::once(&agent)));
+        assert!(text.contains("deploy it") && text.contains("ssh db-7.corp"));
+    }
+}
@@ -0,0 +1,305 @@
+//! `mylm review [base..head]` - review a diff like a pull request
+//!
+//! The diff is split into chunks of whole files (a file too large for one
+//! chunk is split between hunks). Each chunk goes to its own read-only
+//! worker session, created the way delegated workers are and sharing their
+//! pool, so at most `features.worker_pool.max_concurrent` chunks are
+//! reviewed at once. Workers report findings as `FINDING` lines (see
+//! `task::github`), which are printed grouped by file or, with `--json`,
+//! as one JSON document on stdout.
+//!
+//! Without a range the uncommitted changes are reviewed; `base` alone means
+//! `base...HEAD`, the changes a pull request from HEAD into `base` would make.
+
+use std::process::Command;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use console::style;
+use serde::Serialize;
+
+use mylm_core::agent::factory::{AgentSessionFactory, WorkerSessionConfig};
+use mylm_core::agent::runtime::orchestrator::commonbox::JobId;
+use mylm_core::agent::runtime::Session;
+use mylm_core::agent::UserInput;
+use mylm_core::config::Config;
+
+use crate::task::{self, github};
+
+/// Diff text per chunk; files larger than this are split between hunks
+const MAX_CHUNK_CHARS: usize = 24_000;
+/// Time one chunk's review may take
+const CHUNK_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+/// Tools a reviewer may use to look at surrounding code
+const TOOLS: &[&str] = &["read_file", "list_files", "code_outline", "code_search"];
+/// Separates a finding's problem from the suggested fix
+const SUGGESTION_SEPARATOR: &str = " => ";
+
+const REVIEW_INSTRUCTIONS: &str = "You are reviewing part of a pull request. Look for bugs, \
+security problems, missing error handling, races, performance problems and unclear code in the \
+changed lines; read the surrounding code when the diff alone is not enough. Do not comment on \
+formatting a formatter would fix, and do not report what the diff does well.\n\
+Report each problem on its own line of your final answer, formatted as \
+`FINDING <error|warning|notice> <path>:<line> <problem> => <suggested fix>`, where <line> is the \
+line in the new version of the file. Use error for bugs and security problems, warning for likely \
+problems, notice for suggestions. Answer `No findings.` when there is nothing to report.";
+
+/// One problem found in the diff
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReviewFinding {
+    pub severity: github::Severity,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub message: String,
+    pub suggestion: Option<String>,
+}
+
+impl From<github::Finding> for ReviewFinding {
+    fn from(finding: github::Finding) -> Self {
+        let (message, suggestion) = match finding.message.split_once(SUGGESTION_SEPARATOR) {
+            Some((message, suggestion)) if !suggestion.trim().is_empty() => {
+                (message.trim().to_string(), Some(suggestion.trim().to_string()))
+            }
+            _ => (finding.message.trim_end_matches(SUGGESTION_SEPARATOR.trim()).trim().to_string(), None),
+        };
+        Self {
+            severity: finding.severity,
+            file: finding.file,
+            line: finding.line,
+            message,
+            suggestion,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReviewReport {
+    /// What was reviewed, as given to `git diff`
+    pub range: String,
+    pub chunks: usize,
+    /// Chunks whose review did not finish, with the reason
+    pub failed_chunks: Vec<String>,
+    pub findings: Vec<ReviewFinding>,
+}
+
+impl ReviewReport {
+    /// 1 when an error was found or a chunk was not reviewed, else 0
+    pub fn exit_code(&self) -> i32 {
+        let errors = self.findings.iter().any(|f| f.severity == github::Severity::Error);
+        if errors || !self.failed_chunks.is_empty() {
+            1
+        } else {
+            0
+        }
+    }
+}
+
+/// `git diff` arguments for `range` (see the module docs)
+fn diff_args(range: Option<&str>) -> Vec<String> {
+    let mut args = vec!["diff".to_string(), "--no-color".to_string(), "--no-ext-diff".to_string()];
+    match range {
+        None => args.push("HEAD".to_string()),
+        Some(range) if range.contains("..") => args.push(range.to_string()),
+        Some(base) => args.push(format!("{}...HEAD", base)),
+    }
+    args
+}
+
+fn collect_diff(range: Option<&str>) -> Result<String> {
+    let args = diff_args(range);
+    let output = Command::new("git").args(&args).output().context("Failed to run git")?;
+    if !output.status.success() {
+        anyhow::bail!("git diff failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Split `text` before every line starting with `marker`; the first piece
+/// holds what comes before the first marker
+fn split_before<'a>(text: &'a str, marker: &str) -> Vec<&'a str> {
+    let mut starts = vec![0];
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if line.starts_with(marker) && offset > 0 {
+            starts.push(offset);
+        }
+        offset += line.len();
+    }
+    starts.push(text.len());
+    starts.windows(2).map(|w| &text[w[0]..w[1]]).filter(|piece| !piece.is_empty()).collect()
+}
+
+/// Split a diff into chunks of at most about `max_chars`, keeping files
+/// together and repeating a file's header on each piece of a split file
+pub fn chunk_diff(diff: &str, max_chars: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    for file in split_before(diff, "diff --git ") {
+        if file.len() <= max_chars {
+            pieces.push(file.to_string());
+            continue;
+        }
+        let mut hunks = split_before(file, "@@ ").into_iter();
+        let header = hunks.next().unwrap_or_default();
+        let mut piece = header.to_string();
+        for hunk in hunks {
+            if piece.len() > header.len() && piece.len() + hunk.len() > max_chars {
+                pieces.push(std::mem::replace(&mut piece, header.to_string()));
+            }
+            piece.push_str(hunk);
+        }
+        pieces.push(piece);
+    }
+
+    let mut chunks: Vec<String> = Vec::new();
+    for piece in pieces {
+        match chunks.last_mut() {
+            Some(chunk) if chunk.len() + piece.len() <= max_chars => chunk.push_str(&piece),
+            _ => chunks.push(piece),
+        }
+    }
+    chunks
+}
+
+/// Review one chunk in a read-only worker session; the findings it reported
+async fn review_chunk(
+    factory: &AgentSessionFactory,
+    index: usize,
+    total: usize,
+    chunk: String,
+) -> Result<Vec<ReviewFinding>, String> {
+    let _slot = factory.worker_pool().acquire(JobId::new()).await;
+    let id = format!("review-{}", index + 1);
+    let worker = WorkerSessionConfig {
+        allowed_tools: TOOLS.iter().map(|t| t.to_string()).collect(),
+        allowed_commands: Vec::new(),
+        forbidden_commands: Vec::new(),
+        scratchpad: None,
+        output_tx: None,
+        objective: format!("Review part {} of {} of a pull request", index + 1, total),
+        instructions: Some(REVIEW_INSTRUCTIONS.to_string()),
+        tags: Some(vec!["review".to_string()]),
+        commonbox: None,
+    };
+    let mut session = factory
+        .create_configured_worker_session(&id, worker)
+        .await
+        .map_err(|e| format!("Failed to create reviewer: {}", e))?;
+    let input_tx = session.input_sender();
+    let mut output_rx = session.subscribe_output();
+    let session_task = tokio::spawn(async move { session.run().await });
+
+    let message = format!("{}\n\nPart {} of {} of the diff:\n\n```diff\n{}```", REVIEW_INSTRUCTIONS, index + 1, total, chunk);
+    let answer = match input_tx.send(UserInput::Message(message)).await {
+        Ok(()) => match tokio::time::timeout(CHUNK_TIMEOUT, task::follow(&mut output_rx)).await {
+            Ok(answer) => answer,
+            Err(_) => Err(format!("timed out after {}s", CHUNK_TIMEOUT.as_secs())),
+        },
+        Err(_) => Err("reviewer session stopped".to_string()),
+    };
+    session_task.abort();
+    let (findings, _) = github::extract_findings(&answer?);
+    Ok(findings.into_iter().map(ReviewFinding::from).collect())
+}
+
+/// Review the diff `range` describes: `base..head`, `base...head`, or
+/// `base`; None for the uncommitted changes
+pub async fn run(config: &Config, range: Option<&str>) -> Result<ReviewReport> {
+    let diff = collect_diff(range)?;
+    let range = diff_args(range).pop().unwrap_or_default();
+    if diff.trim().is_empty() {
+        anyhow::bail!("No changes to review in {}", range);
+    }
+    let chunks = chunk_diff(&diff, MAX_CHUNK_CHARS);
+    eprintln!(
+        "{} {} ({} chunk(s))",
+        style("Reviewing:").bold(),
+        range,
+        chunks.len()
+    );
+
+    let factory = AgentSessionFactory::new(config.clone()).with_read_only();
+    let total = chunks.len();
+    let reviews = chunks
+        .into_iter()
+        .enumerate()
+        .map(|(index, chunk)| review_chunk(&factory, index, total, chunk));
+    let results = futures::future::join_all(reviews).await;
+
+    let mut report = ReviewReport {
+        range,
+        chunks: total,
+        failed_chunks: Vec::new(),
+        findings: Vec::new(),
+    };
+    for (index, result) in results.into_iter().enumerate() {
+        match result {
+            Ok(findings) => report.findings.extend(findings),
+            Err(reason) => report.failed_chunks.push(format!("chunk {}: {}", index + 1, reason)),
+        }
+    }
+    if report.failed_chunks.len() == total {
+        anyhow::bail!("No chunk was reviewed:\n{}", report.failed_chunks.join("\n"));
+    }
+    report.findings.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    Ok(report)
+}
+
+/// Findings grouped by file, for the terminal
+pub fn render(report: &ReviewReport) -> String {
+    let mut out = String::new();
+    let mut current: Option<&Option<String>> = None;
+    for finding in &report.findings {
+        if current != Some(&finding.file) {
+            let title = finding.file.as_deref().unwrap_or("General");
+            out.push_str(&format!("\n{}\n", style(title).bold().underlined()));
+            current = Some(&finding.file);
+        }
+        let line = finding.line.map(|l| format!("{:>5}", l)).unwrap_or_else(|| "    -".to_string());
+        out.push_str(&format!("{} {} {}\n", finding.severity.icon(), style(line).dim(), finding.message));
+        if let Some(suggestion) = &finding.suggestion {
+            out.push_str(&format!("        {} {}\n", style("→").cyan(), suggestion));
+        }
+    }
+    let count = |severity| report.findings.iter().filter(|f| f.severity == severity).count();
+    out.push_str(&format!(
+        "\n{} {} error(s), {} warning(s), {} notice(s) in {} chunk(s)\n",
+        style("Review:").bold(),
+        count(github::Severity::Error),
+        count(github::Severity::Warning),
+        count(github::Severity::Notice),
+        report.chunks
+    ));
+    for failed in &report.failed_chunks {
+        out.push_str(&format!("{} {}\n", style("not reviewed:").yellow(), failed));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_diff_and_findings() {
+        let small = "diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n@@ -1 +1 @@\n-a\n+b\n";
+        let hunk = format!("@@ -1,3 +1,3 @@\n{}", "+line\n".repeat(10));
+        let large = format!("diff --git a/big.rs b/big.rs\n--- a/big.rs\n+++ b/big.rs\n{}{}", hunk, hunk);
+        let diff = format!("{}{}{}", small, small, large);
+
+        let chunks = chunk_diff(&diff, 150);
+        assert_eq!(chunks[0], format!("{}{}", small, small));
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks[1].starts_with("diff --git a/big.rs") && chunks[2].starts_with("diff --git a/big.rs"));
+        assert!(chunks[2].ends_with(&hunk));
+        assert_eq!(chunk_diff(&diff, 10_000), vec![diff.clone()]);
+
+        assert_eq!(diff_args(Some("main")).last().map(String::as_str), Some("main...HEAD"));
+        let (findings, _) = github::extract_findings(
+            "FINDING error src/a.rs:3 unwrap on user input => return an error instead\nFINDING notice - tidy up =>",
+        );
+        let findings: Vec<ReviewFinding> = findings.into_iter().map(ReviewFinding::from).collect();
+        assert_eq!(findings[0].message, "unwrap on user input");
+        assert_eq!(findings[0].suggestion.as_deref(), Some("return an error instead"));
+        assert_eq!((findings[1].message.as_str(), findings[1].suggestion.as_ref()), ("tidy up", None));
+    }
+}
@@ -1,79 +1,342 @@
+//! mylm server - a shared agent daemon
+//!
+//! Speaks the client protocol (`mylm_core::protocol`) over WebSocket. Each
+//! session runs on the same contract runtime as the TUI, and every connection
+//! that creates, subscribes to or messages a session receives its events, so
+//! several clients can watch and drive the same session. With
+//! `--web`, plain HTTP requests on the same port get the embedded browser UI.
+//! With a users file, tokens identify users and each user's sessions, memories
+//! and settings are kept apart (see `users`). `POST /trigger/<name>` starts
+//! a headless task from a template (see `trigger`).
+
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::SystemTime;
-use tokio::net::TcpListener;
-use tokio::sync::{mpsc, Mutex, oneshot};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, mpsc, Mutex};
 use tokio_tungstenite::accept_async;
 use tokio_tungstenite::tungstenite::Message;
 use futures_util::{StreamExt, SinkExt};
 use uuid::Uuid;
 use anyhow::{Context, Result};
-use base64::Engine;
 
-use mylm_core::config::Config;
-use mylm_core::agent::core::AgentDecision;
-use mylm_core::agent::tool::ToolOutput;
-use mylm_core::provider::chat::ChatMessage;
-use crate::tui::app::state::TuiEvent;
-use mylm_core::protocol::{ServerEvent, ClientMessage, MessageEnvelope, ServerInfo, Capabilities, SessionSummary, SystemInfo};
+use mylm_core::config::{Config, ConfigUpdate};
+use mylm_core::protocol::{ServerEvent, ClientMessage, MessageEnvelope, ServerInfo, Capabilities, SystemInfo};
+
+pub(crate) mod pairing;
+mod session;
+mod trigger;
+mod users;
+mod web;
+
+pub use session::{SessionBusy, SessionRuntime, SessionShared};
+pub use users::{ServerUser, UserRegistry};
+
+/// How the server listens
+#[derive(Debug, Clone)]
+pub struct ServerOptions {
+    pub bind: String,
+    pub port: u16,
+    /// Serve the embedded web UI on the same port
+    pub web: bool,
+    /// Pairing token clients must send in `hello`
+    pub token: Option<String>,
+    /// Users file; defaults to `users.toml` in the config directory if present
+    pub users: Option<PathBuf>,
+}
 
 pub struct AppState {
     pub config: Arc<Mutex<Config>>,
     pub sessions: Arc<Mutex<HashMap<Uuid, Arc<SessionRuntime>>>>,
     pub workflows: Arc<Mutex<Vec<mylm_core::protocol::Workflow>>>,
     pub stages: Arc<Mutex<Vec<mylm_core::protocol::Stage>>>,
+    pub users: UserRegistry,
+    pub options: ServerOptions,
+    /// Permits for running trigger tasks
+    pub triggers: Arc<tokio::sync::Semaphore>,
 }
 
-pub struct SessionRuntime {
-    pub title: Arc<Mutex<String>>,
-    pub status: Arc<Mutex<String>>,
-    pub created_at: u64,
-    pub agent: Arc<Mutex<mylm_core::BuiltAgent>>,
-    pub _event_tx: mpsc::UnboundedSender<TuiEvent>,
-    pub terminal_buffer: Arc<Mutex<String>>,
-    pub pending_approvals: Arc<Mutex<HashMap<Uuid, oneshot::Sender<bool>>>>,
-    pub run_lock: Arc<Mutex<()>>,
-    pub auto_approve: bool,
-}
+pub async fn start_server(mut options: ServerOptions) -> Result<()> {
+    let users = match options.users.clone().or_else(|| UserRegistry::default_path().filter(|p| p.exists())) {
+        Some(path) => UserRegistry::load(&path)?,
+        None => UserRegistry::default(),
+    };
+
+    let addr = format!("{}:{}", options.bind, options.port);
+    let listener = TcpListener::bind(&addr).await.context("Failed to bind server")?;
+
+    println!("mylm server listening on: ws://{}", addr);
+    if options.web {
+        println!("Web UI: http://{}/", addr);
+    }
+    if !users.is_empty() {
+        println!("Multi-user mode: {} users", users.len());
+    }
+    if options.token.is_none() && users.is_empty() {
+        let token = pairing::load_or_create_token().context("Failed to create a pairing token")?;
+        if let Some(path) = pairing::token_path() {
+            println!("Pairing token: {} (mylm attach reads it from there)", path.display());
+        }
+        if options.web {
+            println!("Open the web UI with: http://{}/?token={}", addr, token);
+        }
+        options.token = Some(token);
+    }
 
-pub async fn start_server(port: u16) -> Result<()> {
-  let addr = format!("127.0.0.1:{}", port);
-  let listener = TcpListener::bind(&addr).await.context("Failed to bind server")?;
-  
-  println!("mylm Server listening on: ws://{}", addr);
-
-  let config = Arc::new(Mutex::new(Config::load().unwrap_or_default()));
-  {
-      let cfg = config.lock().await;
-      println!("[Server] Config loaded from: {:?}", cfg.save_to_default_location().err());
-  }
-  let sessions = Arc::new(Mutex::new(HashMap::new()));
-    
+    let config = Arc::new(Mutex::new(Config::load_or_default()));
     let (initial_workflows, initial_stages) = load_workflows().await;
-    let workflows = Arc::new(Mutex::new(initial_workflows));
-    let stages = Arc::new(Mutex::new(initial_stages));
 
     let state = Arc::new(AppState {
         config,
-        sessions,
-        workflows,
-        stages,
+        sessions: Arc::new(Mutex::new(HashMap::new())),
+        workflows: Arc::new(Mutex::new(initial_workflows)),
+        stages: Arc::new(Mutex::new(initial_stages)),
+        users,
+        options,
+        triggers: Arc::new(tokio::sync::Semaphore::new(trigger::MAX_CONCURRENT_TRIGGERS)),
     });
 
+    tokio::spawn(suspend_idle_sessions(Arc::clone(&state.sessions)));
+    tokio::spawn(reload_config(Arc::clone(&state)));
+    tokio::spawn(compact_memories(Arc::clone(&state)));
+
     while let Ok((stream, _)) = listener.accept().await {
         let state_clone = state.clone();
         tokio::spawn(async move {
-            if let Ok(ws_stream) = accept_async(stream).await {
-                handle_connection(ws_stream, state_clone).await;
-            }
+            handle_stream(stream, state_clone).await;
         });
     }
 
     Ok(())
 }
 
+/// Apply edits to the config file to the server config and its sessions
+async fn reload_config(state: Arc<AppState>) {
+    let Some(path) = Config::default_path() else {
+        return;
+    };
+    let mut watcher = match mylm_core::config::ConfigWatcher::start(&path, None, None) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            mylm_core::warn_log!("[SERVER] Config watcher not started: {}", e);
+            return;
+        }
+    };
+    while let Some(update) = watcher.next().await {
+        let config = match update {
+            ConfigUpdate::Changed(config) => config,
+            ConfigUpdate::Invalid(e) => {
+                mylm_core::warn_log!("[SERVER] Config not reloaded: {}", e);
+                continue;
+            }
+        };
+        *state.config.lock().await = (*config).clone();
+        let runtimes: Vec<(Uuid, Arc<SessionRuntime>)> =
+            state.sessions.lock().await.iter().map(|(id, r)| (*id, Arc::clone(r))).collect();
+        for (session_id, runtime) in runtimes {
+            if let Err(e) = runtime.reload(&config) {
+                mylm_core::warn_log!("[SERVER] Session {} keeps its config: {}", session_id, e);
+            }
+        }
+        mylm_core::info_log!("[SERVER] Config reloaded from {}", path.display());
+    }
+}
+
+/// How often sessions are checked for suspension
+const IDLE_SWEEP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Release the resources of sessions nobody has used for a while
+async fn suspend_idle_sessions(sessions: Arc<Mutex<HashMap<Uuid, Arc<SessionRuntime>>>>) {
+    let mut interval = tokio::time::interval(IDLE_SWEEP_INTERVAL);
+    loop {
+        interval.tick().await;
+        let runtimes: Vec<Arc<SessionRuntime>> = sessions.lock().await.values().cloned().collect();
+        for runtime in runtimes {
+            runtime.suspend_if_idle();
+        }
+    }
+}
+
+/// How often the retention settings are checked for a due compaction
+const COMPACT_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// Archive stale memories of the shared store and every user's store while
+/// `features.memory_settings.retention.enabled` is set
+async fn compact_memories(state: Arc<AppState>) {
+    let mut last_run: Option<std::time::Instant> = None;
+    let mut interval = tokio::time::interval(COMPACT_CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        let settings = state.config.lock().await.features.memory_settings.clone();
+        let every = std::time::Duration::from_secs(settings.retention.compact_interval_hours.max(1) * 3600);
+        if !settings.retention.enabled || last_run.is_some_and(|at| at.elapsed() < every) {
+            continue;
+        }
+        last_run = Some(std::time::Instant::now());
+
+        // Each user's ratings live next to their store
+        let stores = mylm_core::agent::factory::shared_memory_path()
+            .map(|path| (path, None))
+            .into_iter()
+            .chain(state.users.iter().map(|user| (user.memory_path(), Some(user.data_dir()))))
+            .filter(|(path, _)| path.exists());
+        for (path, data_dir) in stores {
+            let config = mylm_core::agent::MemoryConfig {
+                enabled: true,
+                storage_path: Some(path.clone()),
+                data_dir,
+                embedding: settings.embedding.clone(),
+                backend: settings.backend,
+                ..Default::default()
+            };
+            let archived = match mylm_core::agent::memory::AgentMemoryManager::new(config).await {
+                Ok(manager) => manager.compact(&settings.retention, false).await,
+                Err(e) => Err(e),
+            };
+            match archived {
+                Ok(archived) if !archived.is_empty() => mylm_core::info_log!(
+                    "[SERVER] Archived {} stale memories from {}",
+                    archived.len(),
+                    path.display()
+                ),
+                Ok(_) => {}
+                Err(e) => mylm_core::warn_log!("[SERVER] Memory compaction failed for {}: {}", path.display(), e),
+            }
+        }
+    }
+}
+
+/// Route a new TCP connection to the WebSocket protocol or the web UI
+async fn handle_stream(stream: TcpStream, state: Arc<AppState>) {
+    let head = match web::peek_request_head(&stream).await {
+        Ok(head) => head,
+        Err(e) => {
+            mylm_core::debug_log!("[SERVER] Dropping connection: {}", e);
+            return;
+        }
+    };
+    if !pairing::origin_allowed(&head) {
+        mylm_core::warn_log!("[SERVER] Refusing request from origin {:?}", pairing::header(&head, "origin"));
+        let _ = web::reject(stream, &head, 403, "Cross-origin requests are not allowed").await;
+        return;
+    }
+    if web::is_websocket_upgrade(&head) {
+        match accept_async(stream).await {
+            Ok(ws_stream) => handle_connection(ws_stream, state).await,
+            Err(e) => mylm_core::warn_log!("[SERVER] WebSocket handshake failed: {}", e),
+        }
+    } else if trigger::is_trigger_request(&head) {
+        if let Err(e) = trigger::serve(stream, &head, state).await {
+            mylm_core::debug_log!("[SERVER] Trigger response failed: {}", e);
+        }
+    } else if let Err(e) = web::serve(stream, &head, state.options.web).await {
+        mylm_core::debug_log!("[SERVER] HTTP response failed: {}", e);
+    }
+}
+
+/// Source of connection ids for session presence
+static NEXT_CONNECTION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// One client connection and the sessions it follows
+struct Connection {
+    id: u64,
+    /// Name shown in presence and on this connection's messages
+    name: String,
+    tx: mpsc::UnboundedSender<ServerEvent>,
+    authenticated: bool,
+    /// Who is connected; None in single-user mode or for the `--token` holder
+    user: Option<Arc<ServerUser>>,
+    subscriptions: HashMap<Uuid, (tokio::task::JoinHandle<()>, Arc<SessionShared>)>,
+}
+
+impl Connection {
+    /// Admins (and everyone in single-user mode) see every session
+    fn is_admin(&self) -> bool {
+        self.user.as_ref().is_none_or(|user| user.admin)
+    }
+
+    fn can_access(&self, runtime: &SessionRuntime) -> bool {
+        self.is_admin() || runtime.owner.as_deref() == self.user.as_ref().map(|user| user.name.as_str())
+    }
+
+    /// Config for this connection's sessions, with the user's overlay applied
+    fn effective_config(&self, base: &Config) -> Result<Config> {
+        match &self.user {
+            Some(user) => user.effective_config(base),
+            None => Ok(base.clone()),
+        }
+    }
+
+    /// Forward the session's events as they happen, after its history
+    /// when `replay` is set; does nothing if already following it
+    fn follow(&mut self, session_id: Uuid, runtime: &Arc<SessionRuntime>, replay: bool) {
+        if self.subscriptions.contains_key(&session_id) {
+            return;
+        }
+        let shared = Arc::clone(runtime.shared());
+        let (backlog, mut rx) = shared.subscribe();
+        if replay {
+            for event in backlog {
+                let _ = self.tx.send(event);
+            }
+        }
+        let tx = self.tx.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                match rx.recv().await {
+                    Ok(event) => {
+                        if tx.send(event).is_err() {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        mylm_core::warn_log!("[SERVER] Client fell behind session {} by {} events", session_id, n);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+        shared.join(self.id, &self.name);
+        self.subscriptions.insert(session_id, (task, shared));
+    }
+
+    fn unfollow(&mut self, session_id: Uuid) {
+        if let Some((task, shared)) = self.subscriptions.remove(&session_id) {
+            task.abort();
+            shared.leave(self.id);
+        }
+    }
+
+    /// Follow a session and confirm it with `Subscribed`
+    fn subscribe(&mut self, session_id: Uuid, runtime: &Arc<SessionRuntime>, replay: bool) {
+        self.follow(session_id, runtime, replay);
+        let _ = self.tx.send(ServerEvent::Subscribed {
+            session_id,
+            subscribers: runtime.shared().subscribers(),
+            status: runtime.shared().status(),
+        });
+    }
+
+    fn error(&self, code: &str, message: impl Into<String>) {
+        let _ = self.tx.send(ServerEvent::Error {
+            code: code.to_string(),
+            message: message.into(),
+        });
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        for (task, shared) in self.subscriptions.values() {
+            task.abort();
+            shared.leave(self.id);
+        }
+    }
+}
+
 async fn handle_connection(
-    ws_stream: tokio_tungstenite::WebSocketStream<tokio::net::TcpStream>,
+    ws_stream: tokio_tungstenite::WebSocketStream<TcpStream>,
     state: Arc<AppState>,
 ) {
     let (mut ws_sender, mut ws_receiver) = ws_stream.split();
@@ -99,165 +362,182 @@ async fn handle_connection(
         }
     });
 
+    let id = NEXT_CONNECTION_ID.fetch_add(1, Ordering::Relaxed);
+    let mut connection = Connection {
+        id,
+        name: format!("guest-{}", id),
+        tx,
+        authenticated: state.options.token.is_none() && state.users.is_empty(),
+        user: None,
+        subscriptions: HashMap::new(),
+    };
+
     // Handle incoming WebSocket messages
     while let Some(Ok(msg)) = ws_receiver.next().await {
         if let Message::Text(text) = msg {
             match serde_json::from_str::<MessageEnvelope<ClientMessage>>(text.as_str()) {
                 Ok(envelope) => {
-                    let _ = handle_client_message(envelope.payload, &state, &tx).await;
+                    if let Err(e) = handle_client_message(envelope.payload, &state, &mut connection).await {
+                        connection.error("request_failed", e.to_string());
+                    }
                 }
                 Err(e) => {
-                    let preview = if text.len() > 200 {
-                        format!("{}...", &text[..200])
-                    } else {
-                        text.clone()
-                    };
-                    println!("[Server] Failed to parse client message envelope: {} - Preview: {}", e, preview);
+                    let preview: String = text.chars().take(200).collect();
+                    mylm_core::warn_log!("[SERVER] Failed to parse client message envelope: {} - Preview: {}", e, preview);
+                    connection.error("bad_request", format!("Could not parse message: {}", e));
                 }
             }
         }
     }
-    
+
+    drop(connection);
     send_task.abort();
 }
 
 async fn handle_client_message(
     msg: ClientMessage,
     state: &Arc<AppState>,
-    tx: &mpsc::UnboundedSender<ServerEvent>,
+    conn: &mut Connection,
 ) -> Result<()> {
+    if !conn.authenticated && !matches!(msg, ClientMessage::Hello { .. } | ClientMessage::Ping) {
+        conn.error("unauthorized", "Send hello with the server's pairing token first");
+        return Ok(());
+    }
+    let tx = conn.tx.clone();
     match msg {
-        ClientMessage::Hello { .. } => {
+        ClientMessage::Hello { client, auth } => {
+            if let Some(name) = client.display_name.map(|n| n.trim().chars().take(32).collect::<String>()) {
+                if !name.is_empty() {
+                    conn.name = name;
+                }
+            }
+            if !conn.authenticated {
+                let token = auth.pairing_token.unwrap_or_default();
+                if let Some(user) = state.users.authenticate(&token) {
+                    mylm_core::info_log!("[SERVER] User '{}' connected", user.name);
+                    conn.name = user.name.clone();
+                    conn.user = Some(user);
+                } else if !state
+                    .options
+                    .token
+                    .as_ref()
+                    .is_some_and(|expected| users::constant_time_eq(expected.as_bytes(), token.as_bytes()))
+                {
+                    conn.error("unauthorized", "Invalid pairing token");
+                    return Ok(());
+                }
+                conn.authenticated = true;
+            }
             let _ = tx.send(ServerEvent::HelloAck {
                 server: ServerInfo {
                     name: "mylm-server".to_string(),
                     version: env!("CARGO_PKG_VERSION").to_string(),
                 },
                 capabilities: Capabilities {
-                    terminal: true,
+                    terminal: false,
                     approvals: true,
                     tools: vec![],
                 },
+                user: conn.user.as_ref().map(|user| user.name.clone()),
             });
         }
-        ClientMessage::CreateSession { config: custom_config, .. } => {
+        ClientMessage::CreateSession { profile, config: custom_config, .. } => {
+            // A client-supplied config could point the session at any
+            // provider, permissions or sandbox; sessions only run on the
+            // server's own config
+            if custom_config.is_some() {
+                conn.error("custom_config_refused", "Sessions use the server config; pick a profile instead");
+                return Ok(());
+            }
             let session_id = Uuid::new_v4();
-            let mut config = state.config.lock().await.clone();
-            
-            if let Some(cfg_val) = custom_config {
-                if let Ok(overridden) = serde_json::from_value::<mylm_core::config::Config>(cfg_val) {
-                    config = overridden;
-                    println!("[Server] Using custom configuration for session {}", session_id);
-                }
+            let config = conn.effective_config(&*state.config.lock().await)?;
+            let profile = if profile.is_empty() { config.active_profile.clone() } else { profile };
+            if !config.profiles.contains_key(&profile) {
+                conn.error("unknown_profile", format!("No profile named '{}'", profile));
+                return Ok(());
             }
 
-            // Agent version is determined by profile settings in V2
-            // For server, we default to V2 behavior
-            
-            let auto_approve = false; // V2 doesn't have allow_execution setting, default to false for safety
-            
-            let (event_tx, event_rx) = mpsc::unbounded_channel::<TuiEvent>();
-            let agent = mylm_core::factory::create_agent_for_session(&config, event_tx.clone()).await?;
-            let agent = Arc::new(Mutex::new(agent));
-
-            let created_at = SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs();
-
-            let runtime = Arc::new(SessionRuntime {
-                title: Arc::new(Mutex::new("New Task".to_string())),
-                status: Arc::new(Mutex::new("idle".to_string())),
-                created_at,
-                agent,
-                _event_tx: event_tx.clone(),
-                terminal_buffer: Arc::new(Mutex::new(String::new())),
-                pending_approvals: Arc::new(Mutex::new(HashMap::new())),
-                run_lock: Arc::new(Mutex::new(())),
-                auto_approve,
-            });
-
-            // Start TUI event loop
-            tokio::spawn(spawn_tui_event_loop(
-                session_id,
-                event_rx,
-                runtime.clone(),
-                tx.clone(),
-            ));
-
-            // Start Job Heartbeat for V2
-            tokio::spawn(spawn_job_heartbeat(
-                session_id,
-                runtime.clone(),
-                tx.clone(),
-            ));
-
-            state.sessions.lock().await.insert(session_id, runtime);
+            let runtime = SessionRuntime::spawn(session_id, config, profile, conn.user.as_deref(), true).await?;
+            state.sessions.lock().await.insert(session_id, Arc::clone(&runtime));
+            conn.follow(session_id, &runtime, true);
             let _ = tx.send(ServerEvent::SessionCreated { session_id });
             let _ = tx.send(ServerEvent::CreateSessionAck { session_id });
         }
         ClientMessage::ListSessions => {
-            let sessions_map = state.sessions.lock().await;
-            let mut sessions = Vec::new();
-            for (id, runtime) in sessions_map.iter() {
-                sessions.push(SessionSummary {
-                    session_id: *id,
-                    title: runtime.title.lock().await.clone(),
-                    status: runtime.status.lock().await.clone(),
-                    created_at: runtime.created_at,
-                });
-            }
+            let mut sessions: Vec<_> = state
+                .sessions
+                .lock()
+                .await
+                .values()
+                .filter(|r| conn.can_access(r))
+                .map(|r| r.summary())
+                .collect();
+            sessions.sort_by_key(|s| std::cmp::Reverse(s.created_at));
             let _ = tx.send(ServerEvent::Sessions { sessions });
         }
+        ClientMessage::SubscribeSession { session_id, replay } => {
+            match state.sessions.lock().await.get(&session_id).cloned() {
+                Some(runtime) if conn.can_access(&runtime) => conn.subscribe(session_id, &runtime, replay),
+                _ => conn.error("unknown_session", format!("No session {}", session_id)),
+            }
+        }
+        ClientMessage::UnsubscribeSession { session_id } => {
+            conn.unfollow(session_id);
+            let _ = tx.send(ServerEvent::Unsubscribed { session_id });
+        }
+        ClientMessage::ResumeSession { session_id } => {
+            match state.sessions.lock().await.get(&session_id).cloned() {
+                Some(runtime) if conn.can_access(&runtime) => conn.follow(session_id, &runtime, true),
+                _ => conn.error("unknown_session", format!("No session {}", session_id)),
+            }
+        }
+        ClientMessage::LeaveSession { session_id } => conn.unfollow(session_id),
         ClientMessage::GetProjectInfo => {
             let root_path = std::env::current_dir()
                 .unwrap_or_else(|_| std::path::PathBuf::from("."))
                 .to_string_lossy()
                 .to_string();
-            
+
             let files = crawl_directory(std::path::Path::new(".")).await.unwrap_or_default();
             let stats = calculate_project_stats(std::path::Path::new(".")).await.ok();
             let _ = tx.send(ServerEvent::ProjectInfo { root_path, files, stats });
         }
         ClientMessage::SendUserMessage { session_id, message } => {
-            if let Some(runtime) = state.sessions.lock().await.get(&session_id).cloned() {
-                tokio::spawn(run_agent_for_user_message(
-                    session_id,
-                    runtime,
-                    message.text,
-                    tx.clone(),
-                ));
+            match state.sessions.lock().await.get(&session_id).cloned() {
+                Some(runtime) if conn.can_access(&runtime) => {
+                    conn.follow(session_id, &runtime, true);
+                    if let Err(e) = runtime.send_message(&conn.name, message.text).await {
+                        match e.downcast_ref::<SessionBusy>() {
+                            Some(busy) => conn.error("session_busy", busy.to_string()),
+                            None => return Err(e),
+                        }
+                    }
+                }
+                _ => conn.error("unknown_session", format!("No session {}", session_id)),
             }
         }
         ClientMessage::ApproveAction { session_id, approval_id, decision } => {
-            if let Some(runtime) = state.sessions.lock().await.get(&session_id) {
-                let approve = matches!(decision.as_str(), "approve" | "approved" | "yes" | "true");
-                let mut pending = runtime.pending_approvals.lock().await;
-                if let Some(sender) = pending.remove(&approval_id) {
-                    let _ = sender.send(approve);
-                }
+            let approve = matches!(decision.as_str(), "approve" | "approved" | "yes" | "true");
+            let runtime = state.sessions.lock().await.get(&session_id).cloned();
+            let resolved = match runtime {
+                Some(runtime) if conn.can_access(&runtime) => runtime.resolve_approval(approval_id, approve).await,
+                _ => false,
+            };
+            if !resolved {
+                conn.error("unknown_approval", "Approval was already settled or does not exist");
             }
         }
         ClientMessage::GetServerConfig => {
-            let config = state.config.lock().await.clone();
-            let _ = tx.send(ServerEvent::Config {
-                config: serde_json::to_value(config).unwrap_or(serde_json::Value::Null),
-            });
+            let config = conn.effective_config(&*state.config.lock().await)?;
+            let mut config = serde_json::to_value(config).unwrap_or(serde_json::Value::Null);
+            redact_secrets(&mut config);
+            let _ = tx.send(ServerEvent::Config { config });
         }
-        ClientMessage::UpdateServerConfig { config: new_config_val } => {
-            if let Ok(new_config) = serde_json::from_value::<Config>(new_config_val) {
-                {
-                    let mut config = state.config.lock().await;
-                    *config = new_config.clone();
-                    if let Err(e) = config.save_to_default_location() {
-                        eprintln!("[Server] Failed to save config: {}", e);
-                    }
-                }
-                // Broadcast the updated config back
-                let _ = tx.send(ServerEvent::Config {
-                    config: serde_json::to_value(new_config).unwrap_or(serde_json::Value::Null),
-                });
-            }
+        ClientMessage::UpdateServerConfig { .. } => {
+            // Replacing the whole config would let a client rewrite
+            // providers, keys and approval rules; the file on the server
+            // is the only way to change them (edits are picked up live)
+            conn.error("forbidden", "The server config is edited on the server; changes to the file are reloaded");
         }
         ClientMessage::GetWorkflows => {
             let workflows = state.workflows.lock().await.clone();
@@ -272,7 +552,7 @@ async fn handle_client_message(
                 *s_lock = new_stages.clone();
             }
             if let Err(e) = save_workflows(&new_workflows, &new_stages).await {
-                eprintln!("[Server] Failed to save workflows: {}", e);
+                mylm_core::error_log!("[SERVER] Failed to save workflows: {}", e);
             }
             // Broadcast back to acknowledge
             let _ = tx.send(ServerEvent::Workflows {
@@ -287,20 +567,24 @@ async fn handle_client_message(
             let config_dir = mylm_core::config::get_config_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
             let config_path = mylm_core::config::find_config_file()
                 .unwrap_or_else(|| config_dir.join("mylm.yaml"));
-            
+
             let info = SystemInfo {
                 config_path: config_path.to_string_lossy().to_string(),
                 data_path: config_dir.to_string_lossy().to_string(),
-                memory_db_path: config_dir.join("memory").to_string_lossy().to_string(),
+                memory_db_path: conn
+                    .user
+                    .as_ref()
+                    .map(|user| user.memory_path())
+                    .unwrap_or_else(|| config_dir.join("memory"))
+                    .to_string_lossy()
+                    .to_string(),
                 sessions_path: config_dir.join("sessions.json").to_string_lossy().to_string(),
                 workflows_path: config_dir.join("workflows.json").to_string_lossy().to_string(),
                 version: env!("CARGO_PKG_VERSION").to_string(),
             };
-            println!("[Server] Sending SystemInfo: {:?}", info);
             let _ = tx.send(ServerEvent::SystemInfo { info });
         }
         ClientMessage::TestConnection { provider, base_url, api_key } => {
-            let tx = tx.clone();
             tokio::spawn(async move {
                 let result = validate_api_key(&provider, base_url.as_deref(), &api_key).await;
                 match result {
@@ -324,6 +608,25 @@ async fn handle_client_message(
     Ok(())
 }
 
+/// Replace API keys, tokens and passwords in a serialized config so clients
+/// only learn whether one is set
+fn redact_secrets(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let secret = key == "api_key" || key.ends_with("_api_key") || matches!(key.as_str(), "token" | "password" | "secret");
+                if secret && value.is_string() {
+                    *value = serde_json::Value::String("[redacted]".to_string());
+                } else {
+                    redact_secrets(value);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_secrets),
+        _ => {}
+    }
+}
+
 async fn validate_api_key(provider: &str, base_url: Option<&str>, api_key: &str) -> Result<()> {
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(10))
@@ -380,364 +683,6 @@ async fn validate_api_key(provider: &str, base_url: Option<&str>, api_key: &str)
     }
 }
 
-async fn spawn_tui_event_loop(
-    session_id: Uuid,
-    mut event_rx: mpsc::UnboundedReceiver<TuiEvent>,
-    runtime: Arc<SessionRuntime>,
-    tx: mpsc::UnboundedSender<ServerEvent>,
-) {
-    while let Some(ev) = event_rx.recv().await {
-        match ev {
-            TuiEvent::StatusUpdate(status) => {
-                {
-                    let mut s = runtime.status.lock().await;
-                    *s = status.clone();
-                }
-                let _ = tx.send(ServerEvent::StatusUpdate {
-                    session_id,
-                    status: if status.is_empty() { "idle".to_string() } else { status },
-                });
-            }
-            TuiEvent::InternalObservation(bytes) => {
-                let data = base64::engine::general_purpose::STANDARD.encode(&bytes);
-                let _ = tx.send(ServerEvent::TerminalOutput { session_id, data });
-            }
-            TuiEvent::GetTerminalScreen(reply) => {
-                let buf = runtime.terminal_buffer.lock().await.clone();
-                let _ = reply.send(buf);
-            }
-            TuiEvent::ExecuteTerminalCommand(cmd, reply) => {
-                let _ = tx.send(ServerEvent::Activity {
-                    session_id,
-                    kind: "executing_tool".to_string(),
-                    detail: Some(format!("execute_command: {cmd}")),
-                });
-
-                let output = exec_shell_command(&cmd).await;
-                {
-                    let mut buf = runtime.terminal_buffer.lock().await;
-                    buf.push('\n');
-                    buf.push_str(&output);
-                    if buf.len() > 200_000 {
-                        *buf = buf.chars().rev().take(200_000).collect::<String>().chars().rev().collect();
-                    }
-                }
-
-                let _ = tx.send(ServerEvent::TerminalOutput {
-                    session_id,
-                    data: base64::engine::general_purpose::STANDARD.encode(output.as_bytes()),
-                });
-
-                let _ = reply.send(output);
-            }
-            _ => {}
-        }
-    }
-}
-
-async fn spawn_job_heartbeat(
-    session_id: Uuid,
-    runtime: Arc<SessionRuntime>,
-    tx: mpsc::UnboundedSender<ServerEvent>,
-) {
-    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(2));
-    loop {
-        interval.tick().await;
-        let mut jobs_to_send = Vec::new();
-        
-        {
-            let agent_enum = runtime.agent.lock().await;
-            if let mylm_core::BuiltAgent::V2(agent) = &*agent_enum {
-                let jobs = agent.job_registry.list_active_jobs();
-                for job in jobs {
-                    jobs_to_send.push(serde_json::to_value(job).unwrap_or(serde_json::Value::Null));
-                }
-            }
-        }
-
-        if !jobs_to_send.is_empty()
-            && tx.send(ServerEvent::JobsUpdate {
-                session_id,
-                jobs: jobs_to_send,
-            }).is_err()
-        {
-            break;
-        }
-    }
-}
-
-async fn run_agent_for_user_message(
-    session_id: Uuid,
-    runtime: Arc<SessionRuntime>,
-    user_text: String,
-    tx: mpsc::UnboundedSender<ServerEvent>,
-) {
-    let _guard = runtime.run_lock.lock().await;
-    let message_id = Uuid::new_v4();
-
-    let _ = tx.send(ServerEvent::TypingIndicator {
-        session_id,
-        is_typing: true,
-    });
-
-    let _ = tx.send(ServerEvent::MessageStarted {
-        session_id,
-        message_id,
-        role: "assistant".to_string(),
-    });
-
-    {
-        let mut agent_enum = runtime.agent.lock().await;
-        let res = match &mut *agent_enum {
-            mylm_core::BuiltAgent::V1(agent) => {
-                agent.history.push(ChatMessage::user(user_text.clone()));
-                agent.inject_memory_context().await
-            }
-            mylm_core::BuiltAgent::V2(agent) => {
-                agent.history.push(ChatMessage::user(user_text.clone()));
-                agent.inject_memory_context().await
-            }
-        };
-        if let Err(e) = res {
-            let _ = tx.send(ServerEvent::Error { 
-                code: "memory_error".to_string(), 
-                message: e.to_string() 
-            });
-        }
-    }
-
-    let mut last_observation: Option<String> = None;
-    let mut seq: u64 = 0;
-
-    loop {
-        let _ = tx.send(ServerEvent::Activity {
-            session_id,
-            kind: "thinking".to_string(),
-            detail: None,
-        });
-
-        let decision_res = {
-            let mut agent_enum = runtime.agent.lock().await;
-            match &mut *agent_enum {
-                mylm_core::BuiltAgent::V1(agent) => {
-                    agent.step(last_observation.take()).await
-                        .map(|d| match d {
-                            mylm_core::agent::AgentDecision::Message(m, u) => AgentDecision::Message(m, u),
-                            mylm_core::agent::AgentDecision::Action { tool, args, kind } => AgentDecision::Action { tool, args, kind },
-                            mylm_core::agent::AgentDecision::MalformedAction(e) => AgentDecision::MalformedAction(e),
-                            mylm_core::agent::AgentDecision::Error(e) => AgentDecision::Error(e),
-                        })
-                }
-                mylm_core::BuiltAgent::V2(agent) => {
-                    agent.step(last_observation.take()).await
-                        .map(|d| match d {
-                            mylm_core::agent::v2::AgentDecision::Message(m, u) => AgentDecision::Message(m, u),
-                            mylm_core::agent::v2::AgentDecision::Action { tool, args, kind } => AgentDecision::Action { tool, args, kind },
-                            mylm_core::agent::v2::AgentDecision::MalformedAction(e) => AgentDecision::MalformedAction(e),
-                            mylm_core::agent::v2::AgentDecision::Error(e) => AgentDecision::Error(e),
-                        })
-                }
-            }
-        };
-
-        match decision_res {
-            Ok(decision) => {
-                match decision {
-                    AgentDecision::Message(msg, usage) => {
-                        let has_pending = {
-                            let agent_enum = runtime.agent.lock().await;
-                            match &*agent_enum {
-                                mylm_core::BuiltAgent::V1(agent) => agent.has_pending_decision(),
-                                mylm_core::BuiltAgent::V2(agent) => agent.has_pending_decision(),
-                            }
-                        };
-                        if has_pending {
-                            let _ = tx.send(ServerEvent::Activity {
-                                session_id,
-                                kind: "thought".to_string(),
-                                detail: Some(msg),
-                            });
-                            continue;
-                        }
-
-                        // Chunked output
-                        for chunk in msg.as_bytes().chunks(64 * 4) {
-                            seq += 1;
-                            let _ = tx.send(ServerEvent::TokenDelta {
-                                session_id,
-                                message_id,
-                                seq,
-                                text: String::from_utf8_lossy(chunk).to_string(),
-                            });
-                        }
-
-                        let _ = tx.send(ServerEvent::MessageFinal {
-                            session_id,
-                            message_id,
-                            text: msg,
-                            usage,
-                        });
-
-                        let _ = tx.send(ServerEvent::TypingIndicator {
-                            session_id,
-                            is_typing: false,
-                        });
-                        break;
-                    }
-                    AgentDecision::Action { tool, args, kind } => {
-                        let call_id = Uuid::new_v4();
-                        let input = serde_json::from_str::<serde_json::Value>(&args)
-                            .unwrap_or_else(|_| serde_json::Value::String(args.clone()));
-                        
-                        let _ = tx.send(ServerEvent::ToolCall {
-                            session_id,
-                            tool: tool.clone(),
-                            call_id,
-                            input: input.clone(),
-                        });
-
-                        if should_require_approval(&runtime, &tool, kind).await {
-                             let approval_id = Uuid::new_v4();
-                             let (atx, arx) = oneshot::channel::<bool>();
-                             {
-                                 let mut pending = runtime.pending_approvals.lock().await;
-                                 pending.insert(approval_id, atx);
-                             }
-
-                             let _ = tx.send(ServerEvent::ApprovalRequested {
-                                 session_id,
-                                 approval_id,
-                                 kind: "tool".to_string(),
-                                 summary: format!("{tool} {args}"),
-                                 details: serde_json::json!({ "tool": tool, "args": input }),
-                             });
-
-                             match arx.await {
-                                 Ok(true) => {
-                                     let _ = tx.send(ServerEvent::Activity {
-                                         session_id,
-                                         kind: "approved".to_string(),
-                                         detail: None,
-                                     });
-                                 }
-                                 Ok(false) | Err(_) => {
-                                     let _ = tx.send(ServerEvent::ToolResult {
-                                         session_id,
-                                         tool: tool.clone(),
-                                         call_id,
-                                         ok: false,
-                                         output: serde_json::json!({ "error": "Denied" }),
-                                     });
-                                     last_observation = Some(format!("Error: User denied the execution of tool '{tool}'."));
-                                     continue;
-                                 }
-                             }
-                        }
-
-                        // Execute tool
-                        let output = {
-                            let agent_enum = runtime.agent.lock().await;
-                            match &*agent_enum {
-                                mylm_core::BuiltAgent::V1(agent) => {
-                                    match agent.tool_registry.execute_tool(&tool, &args).await {
-                                        Ok(output) => output,
-                                        Err(e) => ToolOutput::Immediate(serde_json::Value::String(format!("Tool Error: {e}"))),
-                                    }
-                                }
-                                mylm_core::BuiltAgent::V2(agent) => {
-                                    // V2 tools are in a HashMap
-                                    if let Some(t) = agent.tools.get(&tool) {
-                                        match t.call(&args).await {
-                                            Ok(out) => out,
-                                            Err(e) => ToolOutput::Immediate(serde_json::Value::String(format!("Tool Error: {e}"))),
-                                        }
-                                    } else {
-                                        ToolOutput::Immediate(serde_json::Value::String(format!("Error: Tool '{tool}' not found.")))
-                                    }
-                                }
-                            }
-                        };
-
-                        let output_str = output.as_string();
-                        let _ = tx.send(ServerEvent::ToolResult {
-                            session_id,
-                            tool: tool.clone(),
-                            call_id,
-                            ok: !output_str.starts_with("Tool Error:"),
-                            output: serde_json::Value::String(output_str.clone()),
-                        });
-
-                        last_observation = Some(output_str);
-                    }
-                    AgentDecision::MalformedAction(err) => {
-                        let _ = tx.send(ServerEvent::Error {
-                            code: "malformed_action".to_string(),
-                            message: err,
-                        });
-                        break;
-                    }
-                    AgentDecision::Error(err) => {
-                        let _ = tx.send(ServerEvent::Error {
-                            code: "agent_error".to_string(),
-                            message: err,
-                        });
-                        break;
-                    }
-                }
-            }
-            Err(e) => {
-                let _ = tx.send(ServerEvent::Error {
-                    code: "agent_error".to_string(),
-                    message: e.to_string(),
-                });
-                break;
-            }
-        }
-    }
-}
-
-async fn exec_shell_command(cmd: &str) -> String {
-    #[cfg(windows)]
-    let output_res = tokio::process::Command::new("cmd")
-        .args(["/C", cmd])
-        .output()
-        .await;
-
-    #[cfg(not(windows))]
-    let output_res = tokio::process::Command::new("bash")
-        .args(["-lc", cmd])
-        .output()
-        .await;
-
-    match output_res {
-        Ok(out) => {
-            let mut s = String::new();
-            if !out.stdout.is_empty() {
-                s.push_str(&String::from_utf8_lossy(&out.stdout));
-            }
-            if !out.stderr.is_empty() {
-                if !s.is_empty() {
-                    s.push_str("\n--- stderr ---\n");
-                }
-                s.push_str(&String::from_utf8_lossy(&out.stderr));
-            }
-            s
-        }
-        Err(e) => format!("Failed to execute command: {e}"),
-    }
-}
-
-async fn should_require_approval(runtime: &SessionRuntime, tool: &str, kind: mylm_core::agent::tool::ToolKind) -> bool {
-    if runtime.auto_approve {
-        return false;
-    }
-    match kind {
-        mylm_core::agent::tool::ToolKind::Terminal => true,
-        mylm_core::agent::tool::ToolKind::Web => true,
-        mylm_core::agent::tool::ToolKind::Internal => tool == "write_file",
-    }
-}
-
 async fn calculate_project_stats(path: &std::path::Path) -> Result<mylm_core::protocol::ProjectStats> {
     let mut file_count = 0;
     let mut total_size = 0;
@@ -852,3 +797,23 @@ async fn save_workflows(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_secrets() {
+        let mut config = serde_json::json!({
+            "providers": { "openai": { "api_key": "sk-live", "base_url": "https://api.openai.com" } },
+            "profiles": [{ "api_key": null, "max_session_tokens": 1000 }],
+            "features": { "web_search_api_key": "ws-key" }
+        });
+        redact_secrets(&mut config);
+        assert_eq!(config["providers"]["openai"]["api_key"], "[redacted]");
+        assert_eq!(config["providers"]["openai"]["base_url"], "https://api.openai.com");
+        assert!(config["profiles"][0]["api_key"].is_null());
+        assert_eq!(config["profiles"][0]["max_session_tokens"], 1000);
+        assert_eq!(config["features"]["web_search_api_key"], "[redacted]");
+    }
+}
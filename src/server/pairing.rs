@@ -0,0 +1,109 @@
+//! Pairing token and Origin checks
+//!
+//! Without `--token` or a users file the server generates a random pairing
+//! token on first start and keeps it in `server_token` in the config
+//! directory, readable by the owner only. Local clients (`mylm attach`)
+//! read it from there; the browser gets it once through `/?token=...`.
+//!
+//! Browsers send an `Origin` header with WebSocket handshakes and
+//! cross-site requests. Only pages served from a loopback address or from
+//! the server itself may connect, so a web page the user happens to visit
+//! cannot drive the agent on `ws://127.0.0.1`.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+/// File name of the generated token inside the config directory
+const TOKEN_FILE: &str = "server_token";
+
+pub fn token_path() -> Option<PathBuf> {
+    mylm_core::config::get_config_dir().map(|dir| dir.join(TOKEN_FILE))
+}
+
+/// The stored pairing token, if one was generated
+pub fn stored_token() -> Option<String> {
+    let token = std::fs::read_to_string(token_path()?).ok()?;
+    Some(token.trim().to_string()).filter(|t| !t.is_empty())
+}
+
+/// The stored pairing token, generating and saving one if needed
+pub fn load_or_create_token() -> Result<String> {
+    if let Some(token) = stored_token() {
+        return Ok(token);
+    }
+    let path = token_path().ok_or_else(|| anyhow::anyhow!("Could not find the config directory"))?;
+    let token = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+    mylm_core::util::write_private(&path, token.as_bytes())?;
+    Ok(token)
+}
+
+/// Value of header `name` in a request head
+pub fn header<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+    head.lines().skip(1).find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+    })
+}
+
+fn is_loopback_host(host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Host of an `Origin` or `Host` value, without scheme or port
+fn host_of(value: &str) -> &str {
+    let rest = value.split_once("://").map_or(value, |(_, rest)| rest);
+    let authority = rest.split('/').next().unwrap_or_default();
+    if let Some(end) = authority.strip_prefix('[').and_then(|a| a.find(']')) {
+        return &authority[..end + 2];
+    }
+    authority.split(':').next().unwrap_or_default()
+}
+
+/// Whether a request may reach the server, judged by its `Origin`
+///
+/// Requests without one come from non-browser clients. A browser request is
+/// allowed from a loopback page, or from a page the server itself served at
+/// an IP address (the Origin matches the Host it was sent to). Host names
+/// other than `localhost` are refused so DNS rebinding cannot pass as the
+/// server's own page.
+pub fn origin_allowed(head: &str) -> bool {
+    let Some(origin) = header(head, "origin") else {
+        return true;
+    };
+    if origin == "null" {
+        return false;
+    }
+    let origin_authority = origin.split_once("://").map_or(origin, |(_, rest)| rest);
+    let same_server = header(head, "host").is_some_and(|host| host.eq_ignore_ascii_case(origin_authority));
+    let host = host_of(origin);
+    let is_ip = host.trim_start_matches('[').trim_end_matches(']').parse::<std::net::IpAddr>().is_ok();
+    (same_server && is_ip) || is_loopback_host(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn head(origin: Option<&str>, host: &str) -> String {
+        let origin = origin.map(|o| format!("Origin: {}\r\n", o)).unwrap_or_default();
+        format!("GET / HTTP/1.1\r\nHost: {}\r\n{}Upgrade: websocket\r\n\r\n", host, origin)
+    }
+
+    #[test]
+    fn test_origin_allowed() {
+        assert!(origin_allowed(&head(None, "127.0.0.1:8765")));
+        assert!(origin_allowed(&head(Some("http://127.0.0.1:8765"), "127.0.0.1:8765")));
+        assert!(origin_allowed(&head(Some("http://localhost:3000"), "127.0.0.1:8765")));
+        assert!(origin_allowed(&head(Some("http://[::1]:8765"), "[::1]:8765")));
+        assert!(origin_allowed(&head(Some("http://10.0.0.5:8765"), "10.0.0.5:8765")));
+        assert!(!origin_allowed(&head(Some("https://evil.example"), "127.0.0.1:8765")));
+        assert!(!origin_allowed(&head(Some("http://localhost.evil.example"), "127.0.0.1:8765")));
+        assert!(!origin_allowed(&head(Some("null"), "127.0.0.1:8765")));
+        assert!(!origin_allowed(&head(Some("http://rebind.example:8765"), "rebind.example:8765")));
+    }
+}
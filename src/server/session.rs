@@ -0,0 +1,783 @@
+//! Server-side agent sessions
+//!
+//! Each `SessionRuntime` owns one contract-runtime session (the same kind the
+//! TUI runs) and republishes its `OutputEvent`s as protocol `ServerEvent`s on
+//! a broadcast channel, so any number of connections can follow it. Approvals
+//! are sent to clients as `ApprovalRequested` and settled by `ApproveAction`.
+//!
+//! Everyone following a session shows up in its `Presence`. Any of them may
+//! send a message, but only one at a time: sending takes the turn, and the
+//! turn is released when the agent goes idle again.
+//!
+//! A session left idle for `features.idle.suspend_after_mins` releases its
+//! memory store (see `mylm_core::idle`); the next message reopens it.
+//!
+//! Sessions built from the server config pick up edits to the config file
+//! through `reload`; sessions a client created with its own config keep it.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use base64::Engine;
+use tokio::sync::{broadcast, mpsc, oneshot, Mutex};
+use uuid::Uuid;
+
+use mylm_core::agent::factory::AgentSessionFactory;
+use mylm_core::agent::SessionReloader;
+use mylm_core::agent::runtime::capabilities::{ApprovalPrompter, PolicyApprovalCapability};
+use mylm_core::agent::runtime::core::{ApprovalError, RuntimeContext};
+use mylm_core::agent::runtime::orchestrator::commonbox::Commonbox;
+use mylm_core::agent::runtime::Session;
+use mylm_core::agent::types::events::ApprovalOutcome;
+use mylm_core::agent::types::intents::ApprovalRequest;
+use mylm_core::agent::types::parser::{ShortKeyStreamParser, StreamField};
+use mylm_core::agent::{OutputEvent, UserInput};
+use mylm_core::config::Config;
+use mylm_core::idle::{IdleResources, IdleTimer};
+use mylm_core::protocol::{ServerEvent, SessionSummary};
+use mylm_core::scheduler::WorkerPool;
+use mylm_core::provider::TokenUsage;
+
+use super::users::ServerUser;
+
+/// Events kept for clients that join a running session
+const EVENT_LOG_LIMIT: usize = 1000;
+/// Characters of the first user message used as the session title
+const TITLE_CHARS: usize = 48;
+
+/// State shared between the session task, its approval prompter and clients
+pub struct SessionShared {
+    session_id: Uuid,
+    events: broadcast::Sender<ServerEvent>,
+    /// Everything but token deltas, replayed to late joiners
+    log: std::sync::Mutex<VecDeque<ServerEvent>>,
+    title: std::sync::Mutex<String>,
+    status: std::sync::Mutex<String>,
+    pending_approvals: Mutex<HashMap<Uuid, oneshot::Sender<bool>>>,
+    /// Connection id -> display name of everyone following the session
+    participants: std::sync::Mutex<BTreeMap<u64, String>>,
+    /// Who sent the message the agent is working on
+    turn: std::sync::Mutex<Option<String>>,
+    /// Last activity, for suspending the session when nobody uses it
+    idle: std::sync::Mutex<IdleTimer>,
+}
+
+/// Returned by `send_message` while someone else's message is being handled
+#[derive(Debug, thiserror::Error)]
+#[error("{holder} is waiting on the agent; send your message when it replies")]
+pub struct SessionBusy {
+    pub holder: String,
+}
+
+impl SessionShared {
+    fn new(session_id: Uuid, idle: IdleTimer) -> Self {
+        let (events, _) = broadcast::channel(1024);
+        Self {
+            session_id,
+            events,
+            log: std::sync::Mutex::new(VecDeque::new()),
+            title: std::sync::Mutex::new("New Task".to_string()),
+            status: std::sync::Mutex::new("idle".to_string()),
+            pending_approvals: Mutex::new(HashMap::new()),
+            participants: std::sync::Mutex::new(BTreeMap::new()),
+            turn: std::sync::Mutex::new(None),
+            idle: std::sync::Mutex::new(idle),
+        }
+    }
+
+    /// Send an event to every subscriber and remember it for late joiners
+    pub fn publish(&self, event: ServerEvent) {
+        let mut released = false;
+        if let ServerEvent::StatusUpdate { status, .. } = &event {
+            self.touch();
+            *self.status.lock().unwrap_or_else(|e| e.into_inner()) = status.clone();
+            // The agent finished the turn
+            if matches!(status.as_str(), "idle" | "halted") {
+                released = self.turn.lock().unwrap_or_else(|e| e.into_inner()).take().is_some();
+            }
+        }
+        {
+            let mut log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+            if !matches!(
+                event,
+                ServerEvent::TokenDelta { .. } | ServerEvent::TypingIndicator { .. } | ServerEvent::Presence { .. }
+            ) {
+                log.push_back(event.clone());
+                if log.len() > EVENT_LOG_LIMIT {
+                    log.pop_front();
+                }
+            }
+            // Subscribing happens under the same lock, so nothing is missed or doubled
+            let _ = self.events.send(event);
+        }
+        if released {
+            self.publish_presence();
+        }
+    }
+
+    /// Record activity; true if the session was suspended until now
+    fn touch(&self) -> bool {
+        self.idle.lock().unwrap_or_else(|e| e.into_inner()).touch(Instant::now())
+    }
+
+    /// Past events plus a receiver for everything after them
+    pub fn subscribe(&self) -> (Vec<ServerEvent>, broadcast::Receiver<ServerEvent>) {
+        let log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+        (log.iter().cloned().collect(), self.events.subscribe())
+    }
+
+    fn set_status(&self, status: &str) {
+        self.publish(ServerEvent::StatusUpdate {
+            session_id: self.session_id,
+            status: status.to_string(),
+        });
+    }
+
+    /// A connection started following the session
+    pub fn join(&self, connection_id: u64, name: &str) {
+        self.participants
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(connection_id, name.to_string());
+        self.publish_presence();
+    }
+
+    /// Number of connections following the session
+    pub fn subscribers(&self) -> usize {
+        self.participants.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn status(&self) -> String {
+        self.status.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn leave(&self, connection_id: u64) {
+        let removed = self
+            .participants
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&connection_id)
+            .is_some();
+        if removed {
+            self.publish_presence();
+        }
+    }
+
+    /// Take the turn for `name`, or say who holds it
+    fn take_turn(&self, name: &str) -> Result<(), SessionBusy> {
+        let mut turn = self.turn.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(holder) = turn.as_ref() {
+            return Err(SessionBusy { holder: holder.clone() });
+        }
+        *turn = Some(name.to_string());
+        Ok(())
+    }
+
+    fn publish_presence(&self) {
+        let mut participants: Vec<String> = self
+            .participants
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .cloned()
+            .collect();
+        // One entry per person, even with several tabs open
+        participants.sort();
+        participants.dedup();
+        let turn = self.turn.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        self.publish(ServerEvent::Presence {
+            session_id: self.session_id,
+            participants,
+            turn,
+        });
+    }
+}
+
+/// Asks connected clients to approve tool calls
+struct ServerApprovalPrompter {
+    shared: Arc<SessionShared>,
+}
+
+#[async_trait]
+impl ApprovalPrompter for ServerApprovalPrompter {
+    async fn prompt(
+        &self,
+        _ctx: &RuntimeContext,
+        req: &ApprovalRequest,
+    ) -> Result<ApprovalOutcome, ApprovalError> {
+        let approval_id = Uuid::new_v4();
+        let (tx, rx) = oneshot::channel();
+        self.shared.pending_approvals.lock().await.insert(approval_id, tx);
+
+        let args = serde_json::from_str::<serde_json::Value>(&req.args)
+            .unwrap_or_else(|_| serde_json::Value::String(req.args.clone()));
+        self.shared.set_status("awaiting approval");
+        self.shared.publish(ServerEvent::ApprovalRequested {
+            session_id: self.shared.session_id,
+            approval_id,
+            kind: "tool".to_string(),
+            summary: format!("{} {}", req.tool, truncate(&req.args, 200)),
+            details: serde_json::json!({ "tool": req.tool, "args": args, "reason": req.reason }),
+        });
+
+        let approved = rx.await.unwrap_or(false);
+        self.shared.pending_approvals.lock().await.remove(&approval_id);
+        // A denial goes back to the agent, which keeps working
+        self.shared.set_status("running");
+        Ok(if approved {
+            ApprovalOutcome::Granted
+        } else {
+            ApprovalOutcome::Denied {
+                reason: Some("Denied by a server client".to_string()),
+            }
+        })
+    }
+}
+
+/// A running agent session owned by the server
+pub struct SessionRuntime {
+    pub created_at: u64,
+    /// User that created the session; None in single-user mode
+    pub owner: Option<String>,
+    input_tx: mpsc::Sender<UserInput>,
+    shared: Arc<SessionShared>,
+    task: tokio::task::JoinHandle<()>,
+    /// Released when the session has been idle for a while
+    resources: Arc<IdleResources>,
+    worker_pool: Arc<WorkerPool>,
+    /// Applies server config edits; None for sessions with a client config
+    reloader: Option<Arc<SessionReloader>>,
+    /// Owner whose overlay is applied to reloaded config
+    user: Option<ServerUser>,
+}
+
+impl SessionRuntime {
+    /// Build a session for `profile` and start running it; an owner's
+    /// memories are kept apart from everyone else's
+    ///
+    /// `reloadable` sessions follow edits to the server config.
+    pub async fn spawn(
+        session_id: Uuid,
+        config: Config,
+        profile: String,
+        owner: Option<&ServerUser>,
+        reloadable: bool,
+    ) -> Result<Arc<Self>> {
+        let shared = Arc::new(SessionShared::new(session_id, IdleTimer::new(&config.features.idle)));
+        let prompter = ServerApprovalPrompter { shared: Arc::clone(&shared) };
+        let approval = Arc::new(PolicyApprovalCapability::from_config(&config, Some(Arc::new(prompter))));
+        // The config file is the server's, shared by every user's sessions
+        let mut factory = AgentSessionFactory::new(config)
+            .with_commonbox(Arc::new(Commonbox::new()))
+            .with_approval(approval.clone())
+            .without_config_tool();
+        if let Some(user) = owner {
+            factory = factory.with_memory_path(user.memory_path()).with_data_dir(user.data_dir());
+        }
+        let mut session = factory
+            .create_session(&profile, None)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to create agent session: {}", e))?;
+        let resources = factory.idle_resources();
+        let worker_pool = factory.worker_pool();
+        let reloader = reloadable.then(|| {
+            factory.reloader().register_approval(approval);
+            factory.reloader()
+        });
+
+        let input_tx = session.input_sender();
+        let mut output_rx = session.subscribe_output();
+        let forward = Arc::clone(&shared);
+        tokio::spawn(async move {
+            let mut translator = EventTranslator::new(session_id);
+            loop {
+                match output_rx.recv().await {
+                    Ok(event) => {
+                        for server_event in translator.translate(event) {
+                            forward.publish(server_event);
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        mylm_core::warn_log!("[SERVER] Session {} dropped {} output events", session_id, n);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+
+        let finished = Arc::clone(&shared);
+        let task = tokio::spawn(async move {
+            if let Err(e) = session.run().await {
+                mylm_core::warn_log!("[SERVER] Session {} ended: {}", session_id, e);
+                finished.publish(ServerEvent::Error {
+                    code: "session_ended".to_string(),
+                    message: e.to_string(),
+                });
+            }
+            finished.set_status("halted");
+        });
+
+        let created_at = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        Ok(Arc::new(Self {
+            created_at,
+            owner: owner.map(|user| user.name.clone()),
+            input_tx,
+            shared,
+            task,
+            resources,
+            worker_pool,
+            reloader,
+            user: owner.cloned(),
+        }))
+    }
+
+    /// Apply the edited server config `base` (with the owner's overlay)
+    pub fn reload(&self, base: &Config) -> Result<()> {
+        let Some(ref reloader) = self.reloader else {
+            return Ok(());
+        };
+        let config = match &self.user {
+            Some(user) => user.effective_config(base)?,
+            None => base.clone(),
+        };
+        reloader.apply(&config)?;
+        Ok(())
+    }
+
+    pub fn shared(&self) -> &Arc<SessionShared> {
+        &self.shared
+    }
+
+    pub fn summary(&self) -> SessionSummary {
+        SessionSummary {
+            session_id: self.shared.session_id,
+            title: self.shared.title.lock().unwrap_or_else(|e| e.into_inner()).clone(),
+            status: self.shared.status(),
+            created_at: self.created_at,
+            owner: self.owner.clone(),
+        }
+    }
+
+    /// Post `author`'s message to the session and echo it to every client;
+    /// fails with `SessionBusy` while the agent is handling another message
+    pub async fn send_message(&self, author: &str, text: String) -> Result<()> {
+        if self.task.is_finished() {
+            anyhow::bail!("Session has ended");
+        }
+        self.shared.take_turn(author)?;
+        if self.shared.touch() {
+            mylm_core::info_log!("[SERVER] Resuming suspended session {}", self.shared.session_id);
+        }
+        self.shared.publish_presence();
+        {
+            let mut title = self.shared.title.lock().unwrap_or_else(|e| e.into_inner());
+            if title.as_str() == "New Task" {
+                *title = truncate(text.lines().next().unwrap_or_default(), TITLE_CHARS);
+            }
+        }
+        let session_id = self.shared.session_id;
+        let message_id = Uuid::new_v4();
+        self.shared.publish(ServerEvent::MessageStarted {
+            session_id,
+            message_id,
+            role: "user".to_string(),
+            author: Some(author.to_string()),
+        });
+        self.shared.publish(ServerEvent::MessageFinal {
+            session_id,
+            message_id,
+            text: text.clone(),
+            usage: TokenUsage::default(),
+        });
+        self.shared.publish(ServerEvent::TypingIndicator {
+            session_id,
+            is_typing: true,
+        });
+        if self.input_tx.send(UserInput::Message(text)).await.is_err() {
+            self.shared.set_status("halted");
+            anyhow::bail!("Session is no longer accepting input");
+        }
+        Ok(())
+    }
+
+    /// Release the session's resources if it has been idle long enough;
+    /// true if it was suspended now
+    pub fn suspend_if_idle(&self) -> bool {
+        let status = self.shared.status.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let pool = self.worker_pool.status();
+        if status != "idle" || pool.running > 0 || pool.queued > 0 {
+            return false;
+        }
+        {
+            let mut idle = self.shared.idle.lock().unwrap_or_else(|e| e.into_inner());
+            if !idle.is_due(Instant::now()) {
+                return false;
+            }
+            idle.set_suspended();
+        }
+        let released = self.resources.suspend_all();
+        mylm_core::info_log!(
+            "[SERVER] Suspended idle session {} (released: {})",
+            self.shared.session_id,
+            if released.is_empty() { "nothing".to_string() } else { released.join(", ") }
+        );
+        true
+    }
+
+    /// Settle a pending approval; false if it was unknown or already settled
+    pub async fn resolve_approval(&self, approval_id: Uuid, approve: bool) -> bool {
+        let Some(sender) = self.shared.pending_approvals.lock().await.remove(&approval_id) else {
+            return false;
+        };
+        let _ = sender.send(approve);
+        self.shared.publish(ServerEvent::Activity {
+            session_id: self.shared.session_id,
+            kind: if approve { "approved" } else { "denied" }.to_string(),
+            detail: Some(approval_id.to_string()),
+        });
+        true
+    }
+}
+
+/// A worker job as shown in the jobs view
+#[derive(Debug, Clone, serde::Serialize)]
+struct JobView {
+    job_id: String,
+    worker_id: u64,
+    agent_id: String,
+    objective: String,
+    status: &'static str,
+    current_tool: Option<String>,
+    error: Option<String>,
+}
+
+/// Turns runtime output events into protocol events
+///
+/// Assistant replies arrive as streamed Short-Key JSON; only the final answer
+/// is forwarded as message text, and thoughts of tool steps become activity.
+pub struct EventTranslator {
+    session_id: Uuid,
+    parser: ShortKeyStreamParser,
+    raw: String,
+    message_id: Option<Uuid>,
+    seq: u64,
+    calls: HashMap<u64, (Uuid, String)>,
+    jobs: BTreeMap<String, JobView>,
+}
+
+impl EventTranslator {
+    pub fn new(session_id: Uuid) -> Self {
+        Self {
+            session_id,
+            parser: ShortKeyStreamParser::new(),
+            raw: String::new(),
+            message_id: None,
+            seq: 0,
+            calls: HashMap::new(),
+            jobs: BTreeMap::new(),
+        }
+    }
+
+    fn status(&self, status: impl Into<String>) -> ServerEvent {
+        ServerEvent::StatusUpdate {
+            session_id: self.session_id,
+            status: status.into(),
+        }
+    }
+
+    fn start_message(&mut self, out: &mut Vec<ServerEvent>) -> Uuid {
+        if let Some(id) = self.message_id {
+            return id;
+        }
+        let id = Uuid::new_v4();
+        self.message_id = Some(id);
+        out.push(ServerEvent::MessageStarted {
+            session_id: self.session_id,
+            message_id: id,
+            role: "assistant".to_string(),
+            author: None,
+        });
+        id
+    }
+
+    fn jobs_update(&self) -> ServerEvent {
+        ServerEvent::JobsUpdate {
+            session_id: self.session_id,
+            jobs: self
+                .jobs
+                .values()
+                .map(|job| serde_json::to_value(job).unwrap_or(serde_json::Value::Null))
+                .collect(),
+        }
+    }
+
+    fn update_job(&mut self, job_id: String, f: impl FnOnce(&mut JobView)) -> Option<ServerEvent> {
+        let job = self.jobs.get_mut(&job_id)?;
+        f(job);
+        Some(self.jobs_update())
+    }
+
+    pub fn translate(&mut self, event: OutputEvent) -> Vec<ServerEvent> {
+        let session_id = self.session_id;
+        let mut out = Vec::new();
+        match event {
+            OutputEvent::Thinking { .. } => out.push(self.status("thinking")),
+            OutputEvent::ResponseChunk { content } => {
+                self.raw.push_str(&content);
+                for delta in self.parser.push(&content) {
+                    if delta.field == StreamField::Final {
+                        let message_id = self.start_message(&mut out);
+                        self.seq += 1;
+                        out.push(ServerEvent::TokenDelta {
+                            session_id,
+                            message_id,
+                            seq: self.seq,
+                            text: delta.text,
+                        });
+                    }
+                }
+            }
+            OutputEvent::ResponseComplete { usage } => {
+                let final_answer = self.parser.final_answer().to_string();
+                let thought = self.parser.thought().to_string();
+                // Plain-text replies never open a JSON object
+                let text = if final_answer.is_empty() && thought.is_empty() {
+                    self.raw.trim().to_string()
+                } else {
+                    final_answer
+                };
+                if !thought.is_empty() {
+                    out.push(ServerEvent::Activity {
+                        session_id,
+                        kind: "thought".to_string(),
+                        detail: Some(thought),
+                    });
+                }
+                if !text.is_empty() {
+                    let message_id = self.start_message(&mut out);
+                    out.push(ServerEvent::MessageFinal {
+                        session_id,
+                        message_id,
+                        text,
+                        usage: usage
+                            .map(|u| TokenUsage {
+                                prompt_tokens: u.prompt_tokens,
+                                completion_tokens: u.completion_tokens,
+                                total_tokens: u.total_tokens,
+                                cached_tokens: u.cached_tokens,
+                            })
+                            .unwrap_or_default(),
+                    });
+                    out.push(ServerEvent::TypingIndicator {
+                        session_id,
+                        is_typing: false,
+                    });
+                    out.push(self.status("idle"));
+                }
+                self.parser.reset();
+                self.raw.clear();
+                self.message_id = None;
+                self.seq = 0;
+            }
+            OutputEvent::ToolExecuting { intent_id, tool, args } => {
+                let call_id = Uuid::new_v4();
+                self.calls.insert(intent_id.0, (call_id, tool.clone()));
+                out.push(self.status(format!("running {}", tool)));
+                out.push(ServerEvent::ToolCall {
+                    session_id,
+                    tool,
+                    call_id,
+                    input: serde_json::from_str(&args).unwrap_or(serde_json::Value::String(args)),
+                });
+            }
+            OutputEvent::ToolCompleted { intent_id, result } => {
+                if let Some((call_id, tool)) = self.calls.remove(&intent_id.0) {
+                    // What a shell command printed is the session's terminal output
+                    if tool == "shell" {
+                        let text = if result.ends_with('\n') { result.clone() } else { format!("{}\n", result) };
+                        out.push(ServerEvent::TerminalOutput {
+                            session_id,
+                            data: base64::engine::general_purpose::STANDARD.encode(text),
+                        });
+                    }
+                    out.push(ServerEvent::ToolResult {
+                        session_id,
+                        tool,
+                        call_id,
+                        ok: !result.starts_with("Error"),
+                        output: serde_json::Value::String(result),
+                    });
+                }
+            }
+            OutputEvent::WorkerSpawned { worker_id, job_id, objective, agent_id } => {
+                let key = job_id.0.to_string();
+                self.jobs.insert(
+                    key.clone(),
+                    JobView {
+                        job_id: key,
+                        worker_id: worker_id.0,
+                        agent_id,
+                        objective,
+                        status: "running",
+                        current_tool: None,
+                        error: None,
+                    },
+                );
+                out.push(self.jobs_update());
+            }
+            OutputEvent::WorkerToolExecuting { job_id, tool, .. } => {
+                out.extend(self.update_job(job_id.0.to_string(), |job| job.current_tool = Some(tool)));
+            }
+            OutputEvent::WorkerCompleted { job_id, .. } => {
+                out.extend(self.update_job(job_id.0.to_string(), |job| {
+                    job.status = "completed";
+                    job.current_tool = None;
+                }));
+            }
+            OutputEvent::WorkerFailed { job_id, error, .. } => {
+                out.extend(self.update_job(job_id.0.to_string(), |job| {
+                    job.status = "failed";
+                    job.current_tool = None;
+                    job.error = Some(error);
+                }));
+            }
+            OutputEvent::Status { message } => out.push(ServerEvent::Activity {
+                session_id,
+                kind: "status".to_string(),
+                detail: Some(message),
+            }),
+            OutputEvent::Error { message } => out.push(ServerEvent::Error {
+                code: "agent_error".to_string(),
+                message,
+            }),
+            OutputEvent::Halted { reason } => {
+                out.push(ServerEvent::TypingIndicator {
+                    session_id,
+                    is_typing: false,
+                });
+                out.push(ServerEvent::Activity {
+                    session_id,
+                    kind: "halted".to_string(),
+                    detail: Some(reason),
+                });
+                out.push(self.status("idle"));
+            }
+            OutputEvent::MemoryAdded { content_preview, .. } => out.push(ServerEvent::Activity {
+                session_id,
+                kind: "memory".to_string(),
+                detail: Some(content_preview),
+            }),
+            _ => {}
+        }
+        out
+    }
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max_chars).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mylm_core::agent::types::events::WorkerId;
+    use mylm_core::agent::runtime::orchestrator::commonbox::JobId;
+    use mylm_core::agent::types::ids::IntentId;
+
+    #[test]
+    fn test_streamed_answer_becomes_message() {
+        let mut translator = EventTranslator::new(Uuid::new_v4());
+        let mut events = translator.translate(OutputEvent::ResponseChunk {
+            content: r#"{"t": "easy", "f": "Hel"#.to_string(),
+        });
+        events.extend(translator.translate(OutputEvent::ResponseChunk { content: r#"lo"}"#.to_string() }));
+        events.extend(translator.translate(OutputEvent::ResponseComplete { usage: None }));
+
+        let deltas: String = events
+            .iter()
+            .filter_map(|e| match e {
+                ServerEvent::TokenDelta { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(deltas, "Hello");
+        assert!(events.iter().any(|e| matches!(e, ServerEvent::MessageFinal { text, .. } if text == "Hello")));
+        assert!(events.iter().any(|e| matches!(e, ServerEvent::Activity { kind, .. } if kind == "thought")));
+    }
+
+    #[test]
+    fn test_presence_and_turn_taking() {
+        let shared = SessionShared::new(Uuid::new_v4(), IdleTimer::with_timeout(None));
+        let (_, mut rx) = shared.subscribe();
+        shared.join(1, "alice");
+        shared.join(2, "bob");
+        shared.join(3, "alice");
+
+        shared.take_turn("alice").unwrap();
+        let busy = shared.take_turn("bob").unwrap_err();
+        assert_eq!(busy.holder, "alice");
+
+        // The agent going idle hands the floor back
+        shared.set_status("idle");
+        shared.take_turn("bob").unwrap();
+        shared.leave(2);
+        assert_eq!(shared.subscribers(), 2);
+
+        let mut last = None;
+        while let Ok(event) = rx.try_recv() {
+            if let ServerEvent::Presence { participants, turn, .. } = event {
+                last = Some((participants, turn));
+            }
+        }
+        assert_eq!(last, Some((vec!["alice".to_string()], Some("bob".to_string()))));
+        // Presence is live state, not history
+        assert!(shared.subscribe().0.iter().all(|e| !matches!(e, ServerEvent::Presence { .. })));
+    }
+
+    #[test]
+    fn test_tool_and_job_events() {
+        let mut translator = EventTranslator::new(Uuid::new_v4());
+        let call = translator.translate(OutputEvent::ToolExecuting {
+            intent_id: IntentId(7),
+            tool: "shell".to_string(),
+            args: r#"{"command": "ls"}"#.to_string(),
+        });
+        let result = translator.translate(OutputEvent::ToolCompleted {
+            intent_id: IntentId(7),
+            result: "Cargo.toml".to_string(),
+        });
+        let call_id = call.iter().find_map(|e| match e {
+            ServerEvent::ToolCall { call_id, input, .. } => {
+                assert_eq!(input["command"], "ls");
+                Some(*call_id)
+            }
+            _ => None,
+        });
+        assert!(matches!(&result[0], ServerEvent::TerminalOutput { data, .. } if data == "Q2FyZ28udG9tbAo="));
+        assert!(matches!(&result[1], ServerEvent::ToolResult { call_id: id, ok: true, .. } if Some(*id) == call_id));
+
+        let job_id = JobId::new();
+        translator.translate(OutputEvent::WorkerSpawned {
+            worker_id: WorkerId(1),
+            job_id,
+            objective: "list files".to_string(),
+            agent_id: "file_lister".to_string(),
+        });
+        let update = translator.translate(OutputEvent::WorkerCompleted { worker_id: WorkerId(1), job_id });
+        match &update[0] {
+            ServerEvent::JobsUpdate { jobs, .. } => assert_eq!(jobs[0]["status"], "completed"),
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+}
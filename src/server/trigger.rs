@@ -0,0 +1,251 @@
+//! HTTP trigger endpoint
+//!
+//! `POST /trigger/<name>` runs the task file `<config dir>/triggers/<name>.yaml`
+//! headlessly, the way `mylm task run` does, with the request body as its
+//! input: it replaces `{input}` in the objective, or is appended to it. CI
+//! and chatops systems use it to start work without a WebSocket client:
+//!
+//! ```text
+//! curl -X POST -H "Authorization: Bearer $TOKEN" \
+//!      -H "Content-Type: application/octet-stream" \
+//!      --data-binary @failure.log http://host:7777/trigger/triage
+//! ```
+//!
+//! The reply is `202 Accepted` as soon as the task starts. With `?wait=1`
+//! it comes when the task is done and carries its status and answer.
+//! The request needs the server token or a user's token as a bearer token;
+//! a user's settings apply to tasks they start. Bodies with a content type
+//! a browser form may send without a CORS preflight are refused, and at
+//! most `MAX_CONCURRENT_TRIGGERS` tasks run at once.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use serde_json::json;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use mylm_core::task::TaskFile;
+
+use super::{users, web, AppState};
+use crate::task::{self, TaskStatus};
+
+/// Largest request body accepted as input
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+/// Time a client gets to send the body
+const BODY_TIMEOUT: Duration = Duration::from_secs(30);
+/// Trigger tasks running at once; more are answered with 429
+pub const MAX_CONCURRENT_TRIGGERS: usize = 4;
+/// Content types a cross-site form or `fetch` may send without a preflight
+const SIMPLE_CONTENT_TYPES: &[&str] = &["text/plain", "application/x-www-form-urlencoded", "multipart/form-data"];
+
+/// `<config dir>/triggers`, where trigger templates live
+pub fn triggers_dir() -> Option<PathBuf> {
+    mylm_core::config::get_config_dir().map(|dir| dir.join("triggers"))
+}
+
+/// Whether the request is for the trigger endpoint
+pub fn is_trigger_request(head: &str) -> bool {
+    head.lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .is_some_and(|path| path.starts_with("/trigger/"))
+}
+
+/// Template name and whether to wait, from a request path
+fn parse_path(path: &str) -> Option<(&str, bool)> {
+    let (path, query) = path.split_once('?').unwrap_or((path, ""));
+    let name = path.strip_prefix("/trigger/")?;
+    // The name becomes a file name, so keep it to a safe alphabet
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return None;
+    }
+    let wait = query.split('&').any(|pair| matches!(pair, "wait" | "wait=1" | "wait=true"));
+    Some((name, wait))
+}
+
+fn header<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+    head.lines().skip(1).find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+    })
+}
+
+/// Whether the request declares a content type browsers cannot send cross-site
+fn content_type_allowed(head: &str) -> bool {
+    let Some(value) = header(head, "content-type") else {
+        return false;
+    };
+    let media_type = value.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    !media_type.is_empty() && !SIMPLE_CONTENT_TYPES.contains(&media_type.as_str())
+}
+
+/// The template with `input` filled in
+pub fn with_input(mut task: TaskFile, input: &str) -> TaskFile {
+    let input = input.trim();
+    if task.objective.contains("{input}") {
+        task.objective = task.objective.replace("{input}", input);
+    } else if !input.is_empty() {
+        task.objective = format!("{}\n\nInput:\n{}", task.objective.trim_end(), input);
+    }
+    task
+}
+
+/// Load the template `name` from `dir`
+fn load_template(dir: &Path, name: &str) -> Result<Option<TaskFile>> {
+    for ext in ["yaml", "yml"] {
+        let path = dir.join(format!("{}.{}", name, ext));
+        if path.is_file() {
+            return TaskFile::load(&path).map(Some);
+        }
+    }
+    Ok(None)
+}
+
+fn status_json(status: &TaskStatus) -> serde_json::Value {
+    match status {
+        TaskStatus::Succeeded => json!({ "status": "succeeded" }),
+        TaskStatus::CheckFailed => json!({ "status": "check_failed" }),
+        TaskStatus::Incomplete(reason) => json!({ "status": "incomplete", "reason": reason }),
+    }
+}
+
+/// Answer a trigger request
+pub async fn serve(mut stream: TcpStream, head: &str, state: Arc<AppState>) -> Result<()> {
+    let head_len = head.find("\r\n\r\n").map(|i| i + 4).unwrap_or(head.len());
+    let mut discard = vec![0u8; head_len];
+    stream.read_exact(&mut discard).await?;
+
+    let (status, body) = respond(&mut stream, head, state).await;
+    let response = web::response(status, "application/json", &body.to_string());
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+async fn respond(stream: &mut TcpStream, head: &str, state: Arc<AppState>) -> (u16, serde_json::Value) {
+    let error = |status: u16, message: &str| (status, json!({ "error": message }));
+    let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default();
+    let Some((name, wait)) = request_line.next().and_then(parse_path) else {
+        return error(404, "Unknown trigger");
+    };
+    if method != "POST" {
+        return error(405, "Use POST");
+    }
+
+    // Same credentials as the WebSocket hello
+    let token = header(head, "authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default();
+    let user = state.users.authenticate(token);
+    let token_ok = state
+        .options
+        .token
+        .as_ref()
+        .is_some_and(|expected| users::constant_time_eq(expected.as_bytes(), token.as_bytes()));
+    if user.is_none() && !token_ok {
+        return error(401, "Missing or invalid bearer token");
+    }
+    if !content_type_allowed(head) {
+        return error(415, "Send the input with a Content-Type such as application/octet-stream");
+    }
+    let Ok(permit) = Arc::clone(&state.triggers).try_acquire_owned() else {
+        return error(429, "Too many triggers running");
+    };
+
+    let length: usize = header(head, "content-length").and_then(|v| v.parse().ok()).unwrap_or(0);
+    if length > MAX_BODY_BYTES {
+        return error(413, "Request body is too large");
+    }
+    let mut body = vec![0u8; length];
+    match tokio::time::timeout(BODY_TIMEOUT, stream.read_exact(&mut body)).await {
+        Ok(Ok(_)) => {}
+        _ => return error(400, "Incomplete request body"),
+    }
+
+    let Some(dir) = triggers_dir() else {
+        return error(500, "No config directory");
+    };
+    let template = match load_template(&dir, name) {
+        Ok(Some(template)) => template,
+        Ok(None) => return error(404, &format!("No trigger template {}/{}.yaml", dir.display(), name)),
+        Err(e) => return error(500, &format!("{:#}", e)),
+    };
+    let task = with_input(template, &String::from_utf8_lossy(&body));
+    let prompt = match task.prompt(&dir) {
+        Ok(prompt) => prompt,
+        Err(e) => return error(500, &format!("{:#}", e)),
+    };
+    let base = state.config.lock().await.clone();
+    let config = match &user {
+        Some(user) => match user.effective_config(&base) {
+            Ok(config) => config,
+            Err(e) => return error(500, &format!("{:#}", e)),
+        },
+        None => base,
+    };
+
+    mylm_core::info_log!(
+        "[SERVER] Trigger '{}' started{}",
+        name,
+        user.as_ref().map(|u| format!(" by {}", u.name)).unwrap_or_default()
+    );
+    let name = name.to_string();
+    let run = tokio::spawn(async move {
+        let _permit = permit;
+        let result = task::execute(&config, &task, prompt).await;
+        match &result {
+            Ok((status, _)) => mylm_core::info_log!("[SERVER] Trigger '{}' finished: {:?}", name, status),
+            Err(e) => mylm_core::warn_log!("[SERVER] Trigger '{}' failed: {}", name, e),
+        }
+        result
+    });
+    if !wait {
+        return (202, json!({ "status": "started" }));
+    }
+    match run.await {
+        Ok(Ok((status, answer))) => {
+            let mut reply = status_json(&status);
+            reply["exit_code"] = json!(status.exit_code());
+            reply["answer"] = json!(answer);
+            (200, reply)
+        }
+        Ok(Err(e)) => error(500, &format!("{:#}", e)),
+        Err(e) => error(500, &e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trigger_paths_and_input() {
+        assert_eq!(parse_path("/trigger/triage"), Some(("triage", false)));
+        assert_eq!(parse_path("/trigger/nightly-lint?wait=1"), Some(("nightly-lint", true)));
+        assert_eq!(parse_path("/trigger/../users"), None);
+        assert_eq!(parse_path("/trigger/"), None);
+        assert!(is_trigger_request("POST /trigger/triage HTTP/1.1\r\nHost: x\r\n\r\n"));
+        assert!(!is_trigger_request("GET /app.js HTTP/1.1\r\n\r\n"));
+        let head = |content_type: &str| format!("POST /trigger/triage HTTP/1.1\r\n{}\r\n\r\n", content_type);
+        assert!(content_type_allowed(&head("Content-Type: application/json")));
+        assert!(content_type_allowed(&head("content-type: application/octet-stream")));
+        assert!(!content_type_allowed(&head("Content-Type: text/plain; charset=utf-8")));
+        assert!(!content_type_allowed(&head("Content-Type: application/x-www-form-urlencoded")));
+        assert!(!content_type_allowed(&head("Host: x")));
+
+        let template = TaskFile {
+            objective: "Triage this CI failure:\n{input}".to_string(),
+            ..TaskFile::default()
+        };
+        assert_eq!(with_input(template, "test x failed\n").objective, "Triage this CI failure:\ntest x failed");
+        let plain = TaskFile {
+            objective: "Summarize the report".to_string(),
+            ..TaskFile::default()
+        };
+        assert_eq!(with_input(plain, "42 issues").objective, "Summarize the report\n\nInput:\n42 issues");
+    }
+}
@@ -0,0 +1,213 @@
+//! Server users
+//!
+//! With a users file (`users.toml` in the config directory, or `--users`),
+//! every pairing token belongs to a named user. A user only sees their own
+//! sessions, keeps memories, journal, profile and memory ratings in their
+//! own data directory and can overlay settings on the server config (which
+//! `GetServerConfig` returns with API keys redacted):
+//!
+//! ```toml
+//! [[users]]
+//! name = "alice"
+//! token = "long-random-string"
+//! admin = true
+//!
+//! [[users]]
+//! name = "bob"
+//! token = "another-random-string"
+//! [users.config]
+//! active_profile = "fast"
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use mylm_core::config::Config;
+
+/// One person allowed to use the server
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServerUser {
+    pub name: String,
+    pub token: String,
+    /// Sees every session and may change the server config
+    #[serde(default)]
+    pub admin: bool,
+    /// Settings merged over the server config for this user's sessions
+    #[serde(default)]
+    pub config: Option<toml::Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct UsersFile {
+    #[serde(default)]
+    users: Vec<ServerUser>,
+}
+
+impl ServerUser {
+    /// Where this user's memories and other server-side data live
+    pub fn data_dir(&self) -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("mylm")
+            .join("users")
+            .join(&self.name)
+    }
+
+    pub fn memory_path(&self) -> PathBuf {
+        self.data_dir().join("memory")
+    }
+
+    /// The server config with this user's overlay applied
+    pub fn effective_config(&self, base: &Config) -> Result<Config> {
+        let Some(overlay) = &self.config else {
+            return Ok(base.clone());
+        };
+        let mut merged = serde_json::to_value(base)?;
+        merge(&mut merged, serde_json::to_value(overlay)?);
+        serde_json::from_value(merged).with_context(|| format!("Config overlay for user '{}' is invalid", self.name))
+    }
+}
+
+/// Recursively merge `overlay` into `base`; tables merge, everything else replaces
+fn merge(base: &mut serde_json::Value, overlay: serde_json::Value) {
+    match (base, overlay) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Users known to the server; empty means single-user mode
+#[derive(Debug, Default)]
+pub struct UserRegistry {
+    users: Vec<Arc<ServerUser>>,
+}
+
+impl UserRegistry {
+    /// `users.toml` in the mylm config directory
+    pub fn default_path() -> Option<PathBuf> {
+        mylm_core::config::get_config_dir().map(|dir| dir.join("users.toml"))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let file: UsersFile = toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))?;
+        Self::from_users(file.users)
+    }
+
+    fn from_users(users: Vec<ServerUser>) -> Result<Self> {
+        let mut seen_names = std::collections::HashSet::new();
+        let mut seen_tokens = std::collections::HashSet::new();
+        for user in &users {
+            // The name becomes a directory, so keep it to a safe alphabet
+            if user.name.is_empty()
+                || !user.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                anyhow::bail!("Invalid user name '{}': use letters, digits, '-' and '_'", user.name);
+            }
+            if user.token.len() < 8 {
+                anyhow::bail!("Token for user '{}' is too short (at least 8 characters)", user.name);
+            }
+            if !seen_names.insert(user.name.as_str()) {
+                anyhow::bail!("User '{}' is listed twice", user.name);
+            }
+            if !seen_tokens.insert(user.token.as_str()) {
+                anyhow::bail!("User '{}' shares a token with another user", user.name);
+            }
+        }
+        Ok(Self {
+            users: users.into_iter().map(Arc::new).collect(),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.users.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<ServerUser>> {
+        self.users.iter()
+    }
+
+    /// The user a pairing token belongs to
+    pub fn authenticate(&self, token: &str) -> Option<Arc<ServerUser>> {
+        self.users
+            .iter()
+            .find(|user| constant_time_eq(user.token.as_bytes(), token.as_bytes()))
+            .cloned()
+    }
+}
+
+/// Compare tokens without leaking how many leading bytes matched
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USERS: &str = r#"
+[[users]]
+name = "alice"
+token = "alice-token-1"
+admin = true
+
+[[users]]
+name = "bob"
+token = "bob-token-22"
+[users.config]
+active_profile = "fast"
+[users.config.features]
+memory = false
+"#;
+
+    #[test]
+    fn test_tokens_map_to_users() {
+        let file: UsersFile = toml::from_str(USERS).unwrap();
+        let registry = UserRegistry::from_users(file.users).unwrap();
+        assert_eq!(registry.len(), 2);
+        assert!(registry.authenticate("alice-token-1").unwrap().admin);
+        assert_eq!(registry.authenticate("bob-token-22").unwrap().name, "bob");
+        assert!(registry.authenticate("bob-token-2").is_none());
+
+        let dup: UsersFile = toml::from_str(&USERS.replace("bob-token-22", "alice-token-1")).unwrap();
+        assert!(UserRegistry::from_users(dup.users).is_err());
+        let bad: UsersFile = toml::from_str(&USERS.replace("\"bob\"", "\"../bob\"")).unwrap();
+        assert!(UserRegistry::from_users(bad.users).is_err());
+    }
+
+    #[test]
+    fn test_config_overlay() {
+        let file: UsersFile = toml::from_str(USERS).unwrap();
+        let base = Config::default();
+        let alice = &file.users[0];
+        let bob = &file.users[1];
+
+        assert_eq!(alice.effective_config(&base).unwrap().active_profile, base.active_profile);
+        let config = bob.effective_config(&base).unwrap();
+        assert_eq!(config.active_profile, "fast");
+        assert!(!config.features.memory);
+        // Untouched settings come from the server config
+        assert_eq!(config.profiles.len(), base.profiles.len());
+        assert!(bob.memory_path().ends_with("users/bob/memory"));
+        assert!(bob.memory_path().starts_with(bob.data_dir()));
+    }
+}
@@ -0,0 +1,136 @@
+//! Embedded web UI
+//!
+//! Static assets compiled into the binary and served over plain HTTP on the
+//! server's port. The page talks to the server through the same WebSocket
+//! protocol as every other client; the only other HTTP route is the trigger
+//! endpoint (see `trigger`).
+
+use std::time::Duration;
+
+use anyhow::Result;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Largest request head read before giving up
+const MAX_HEAD_BYTES: usize = 8192;
+/// Time a client gets to send its request head
+const HEAD_TIMEOUT: Duration = Duration::from_secs(10);
+
+const INDEX_HTML: &str = include_str!("web/index.html");
+const APP_JS: &str = include_str!("web/app.js");
+const STYLE_CSS: &str = include_str!("web/style.css");
+
+/// Asset for a request path
+fn asset(path: &str) -> Option<(&'static str, &'static str)> {
+    match path.split('?').next().unwrap_or_default() {
+        "/" | "/index.html" => Some(("text/html; charset=utf-8", INDEX_HTML)),
+        "/app.js" => Some(("application/javascript; charset=utf-8", APP_JS)),
+        "/style.css" => Some(("text/css; charset=utf-8", STYLE_CSS)),
+        _ => None,
+    }
+}
+
+/// Read the request head without consuming it, so a WebSocket handshake can
+/// still be accepted on the same stream
+pub async fn peek_request_head(stream: &TcpStream) -> Result<String> {
+    let mut buf = vec![0u8; MAX_HEAD_BYTES];
+    let peek = async {
+        loop {
+            let n = stream.peek(&mut buf).await?;
+            if n == 0 {
+                anyhow::bail!("connection closed before a request arrived");
+            }
+            if buf[..n].windows(4).any(|w| w == b"\r\n\r\n") || n == buf.len() {
+                return Ok(String::from_utf8_lossy(&buf[..n]).to_string());
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    tokio::time::timeout(HEAD_TIMEOUT, peek)
+        .await
+        .map_err(|_| anyhow::anyhow!("timed out waiting for a request"))?
+}
+
+pub fn is_websocket_upgrade(head: &str) -> bool {
+    head.lines().skip(1).any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.trim().eq_ignore_ascii_case("upgrade") && value.trim().eq_ignore_ascii_case("websocket")
+        })
+    })
+}
+
+/// Answer a plain HTTP request with an embedded asset
+pub async fn serve(mut stream: TcpStream, head: &str, enabled: bool) -> Result<()> {
+    // Consume the head that was only peeked so far
+    let head_len = head.find("\r\n\r\n").map(|i| i + 4).unwrap_or(head.len());
+    let mut discard = vec![0u8; head_len];
+    stream.read_exact(&mut discard).await?;
+
+    let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default();
+    let path = request_line.next().unwrap_or("/");
+
+    let response = if !enabled {
+        response(404, "text/plain", "The web UI is disabled; start the server with --web")
+    } else if method != "GET" {
+        response(405, "text/plain", "Method not allowed")
+    } else {
+        match asset(path) {
+            Some((content_type, body)) => response(200, content_type, body),
+            None => response(404, "text/plain", "Not found"),
+        }
+    };
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Answer a request with an error status and close the connection
+pub async fn reject(mut stream: TcpStream, head: &str, status: u16, message: &str) -> Result<()> {
+    let head_len = head.find("\r\n\r\n").map(|i| i + 4).unwrap_or(head.len());
+    let mut discard = vec![0u8; head_len];
+    stream.read_exact(&mut discard).await?;
+    stream.write_all(response(status, "text/plain", message).as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+pub(super) fn response(status: u16, content_type: &str, body: &str) -> String {
+    let reason = match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        415 => "Unsupported Media Type",
+        429 => "Too Many Requests",
+        _ => "Error",
+    };
+    format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nX-Content-Type-Options: nosniff\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        content_type,
+        body.len(),
+        body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_routing() {
+        let ws = "GET / HTTP/1.1\r\nHost: x\r\nConnection: Upgrade\r\nUpgrade: WebSocket\r\n\r\n";
+        assert!(is_websocket_upgrade(ws));
+        assert!(!is_websocket_upgrade("GET / HTTP/1.1\r\nHost: x\r\n\r\n"));
+
+        assert_eq!(asset("/?token=abc").map(|(t, _)| t), Some("text/html; charset=utf-8"));
+        assert!(asset("/app.js").is_some());
+        assert!(asset("/../Cargo.toml").is_none());
+    }
+}
@@ -0,0 +1,340 @@
+// mylm web UI: a thin client for the server's WebSocket protocol.
+// All state lives on the server; this page only renders events.
+"use strict";
+
+const state = {
+  ws: null,
+  connected: false,
+  current: null,          // selected session id
+  following: new Set(),   // sessions this connection receives events for
+  sessions: new Map(),    // id -> session view
+  summaries: [],          // latest session list
+};
+
+const $ = (id) => document.getElementById(id);
+
+function token() {
+  const fromUrl = new URLSearchParams(location.search).get("token");
+  if (fromUrl) localStorage.setItem("mylm-token", fromUrl);
+  return localStorage.getItem("mylm-token");
+}
+
+function displayName() {
+  const fromUrl = new URLSearchParams(location.search).get("name");
+  if (fromUrl) localStorage.setItem("mylm-name", fromUrl);
+  return localStorage.getItem("mylm-name");
+}
+
+function send(payload) {
+  if (!state.connected) return;
+  state.ws.send(JSON.stringify({ v: 1, type: payload.type, payload }));
+}
+
+function connect() {
+  const scheme = location.protocol === "https:" ? "wss" : "ws";
+  const ws = new WebSocket(`${scheme}://${location.host}/`);
+  state.ws = ws;
+  ws.onopen = () => {
+    state.connected = true;
+    state.following.clear();
+    setConnection(true);
+    send({
+      type: "hello",
+      client: { name: "mylm-web", version: "1", display_name: displayName() },
+      auth: { mode: "token", pairing_token: token() },
+    });
+  };
+  ws.onmessage = (msg) => {
+    const envelope = JSON.parse(msg.data);
+    handleEvent(envelope.payload);
+  };
+  ws.onclose = () => {
+    state.connected = false;
+    setConnection(false);
+    setTimeout(connect, 2000);
+  };
+}
+
+function setConnection(online) {
+  const el = $("connection");
+  el.textContent = online ? "connected" : "disconnected, retrying…";
+  el.className = "connection " + (online ? "online" : "offline");
+}
+
+function session(id) {
+  if (!state.sessions.has(id)) {
+    state.sessions.set(id, { items: [], messages: new Map(), tools: new Map(), approvals: new Map(), jobs: [], status: "", typing: false, participants: [], turn: null });
+  }
+  return state.sessions.get(id);
+}
+
+function select(id) {
+  // Leaving drops us from the old session's presence list
+  if (state.current && state.current !== id && state.following.has(state.current)) {
+    send({ type: "unsubscribe_session", session_id: state.current });
+    state.following.delete(state.current);
+  }
+  state.current = id;
+  if (!state.following.has(id)) {
+    // The server replays the session's history when we start following it
+    state.sessions.delete(id);
+    state.following.add(id);
+    send({ type: "subscribe_session", session_id: id, replay: true });
+  }
+  renderSessions();
+  renderSession();
+}
+
+// ---- events ---------------------------------------------------------------
+
+function handleEvent(ev) {
+  const s = ev.session_id ? session(ev.session_id) : null;
+  switch (ev.type) {
+    case "hello_ack":
+      if (ev.user) $("connection").textContent = `connected as ${ev.user}`;
+      send({ type: "list_sessions" });
+      if (state.current) select(state.current);
+      break;
+    case "sessions":
+      state.summaries = ev.sessions;
+      renderSessions();
+      break;
+    case "session_created":
+      state.following.add(ev.session_id);
+      select(ev.session_id);
+      send({ type: "list_sessions" });
+      break;
+    case "message_started":
+      if (!s.messages.has(ev.message_id)) {
+        const item = { kind: "message", role: ev.role, author: ev.author, text: "" };
+        s.messages.set(ev.message_id, item);
+        s.items.push(item);
+      }
+      break;
+    case "token_delta":
+      append(s, ev.message_id).text += ev.text;
+      break;
+    case "message_final": {
+      const item = append(s, ev.message_id);
+      item.text = ev.text;
+      // The first user message titles the session
+      if (item.role === "user") send({ type: "list_sessions" });
+      break;
+    }
+    case "activity":
+      if (ev.kind === "approved" || ev.kind === "denied") {
+        const approval = s.approvals.get(ev.detail);
+        if (approval) approval.settled = ev.kind;
+      } else if (ev.kind !== "thinking") {
+        s.items.push({ kind: "note", text: noteText(ev) });
+      }
+      break;
+    case "tool_call": {
+      const item = { kind: "tool", tool: ev.tool, input: ev.input, output: null, ok: true };
+      s.tools.set(ev.call_id, item);
+      s.items.push(item);
+      break;
+    }
+    case "tool_result": {
+      const item = s.tools.get(ev.call_id);
+      if (item) {
+        item.output = ev.output;
+        item.ok = ev.ok;
+      }
+      break;
+    }
+    case "approval_requested": {
+      const item = { kind: "approval", id: ev.approval_id, summary: ev.summary, details: ev.details, settled: null };
+      s.approvals.set(ev.approval_id, item);
+      s.items.push(item);
+      break;
+    }
+    case "status_update":
+      s.status = ev.status;
+      break;
+    case "typing_indicator":
+      s.typing = ev.is_typing;
+      break;
+    case "presence":
+      s.participants = ev.participants;
+      s.turn = ev.turn;
+      break;
+    case "jobs_update":
+      s.jobs = ev.jobs;
+      break;
+    case "error":
+      if (ev.code === "unauthorized") {
+        const entered = prompt("This server needs a pairing token:");
+        if (entered) {
+          localStorage.setItem("mylm-token", entered);
+          state.ws.close();
+        }
+        return;
+      }
+      if (state.current) session(state.current).items.push({ kind: "error", text: `${ev.code}: ${ev.message}` });
+      break;
+    default:
+      return;
+  }
+  if (!s || ev.session_id === state.current) renderSession();
+  if (ev.type === "status_update") renderSessions();
+}
+
+// Messages can arrive mid-stream when following a running session
+function append(s, messageId) {
+  if (!s.messages.has(messageId)) {
+    const item = { kind: "message", role: "assistant", text: "" };
+    s.messages.set(messageId, item);
+    s.items.push(item);
+  }
+  return s.messages.get(messageId);
+}
+
+function noteText(ev) {
+  const labels = { thought: "💭", status: "ℹ️", halted: "⏹", memory: "🧠" };
+  return `${labels[ev.kind] || ev.kind + ":"} ${ev.detail || ""}`;
+}
+
+// ---- rendering ------------------------------------------------------------
+
+function el(tag, className, text) {
+  const node = document.createElement(tag);
+  if (className) node.className = className;
+  if (text !== undefined) node.textContent = text;
+  return node;
+}
+
+function renderSessions() {
+  const list = $("sessions");
+  list.replaceChildren();
+  if (state.summaries.length === 0) list.append(el("li", "empty", "No sessions yet"));
+  for (const summary of state.summaries) {
+    const li = el("li", summary.session_id === state.current ? "active" : "", summary.title);
+    const view = state.sessions.get(summary.session_id);
+    const status = (view && view.status) || summary.status;
+    // Admins see everyone's sessions, labelled with their owner
+    li.append(el("span", "status", summary.owner ? `${summary.owner} · ${status}` : status));
+    li.onclick = () => select(summary.session_id);
+    list.append(li);
+  }
+}
+
+function pretty(value) {
+  return typeof value === "string" ? value : JSON.stringify(value, null, 2);
+}
+
+// Diffs get +/- colouring so patch approvals are easy to review
+function codeBlock(text) {
+  const pre = el("pre");
+  for (const line of text.split("\n")) {
+    const cls = line.startsWith("+") ? "add" : line.startsWith("-") ? "del" : "";
+    pre.append(el("span", cls, line + "\n"));
+  }
+  return pre;
+}
+
+function renderItem(item) {
+  switch (item.kind) {
+    case "message": {
+      const node = el("div", "message " + item.role);
+      node.append(el("span", "role", item.author || item.role), el("div", "body", item.text));
+      return node;
+    }
+    case "tool": {
+      const node = el("details", "tool");
+      node.append(el("summary", "", `🔧 ${item.tool}${item.output === null ? " (running)" : item.ok ? "" : " (failed)"}`));
+      node.append(codeBlock(pretty(item.input)));
+      if (item.output !== null) node.append(codeBlock(pretty(item.output)));
+      return node;
+    }
+    case "approval": {
+      const node = el("div", "approval" + (item.settled ? " settled" : ""));
+      node.append(el("strong", "", "🔒 Approval needed: "), el("span", "", item.summary));
+      const args = item.details && item.details.args;
+      if (args && args.patch) {
+        node.append(codeBlock(args.patch));
+      } else if (args) {
+        node.append(codeBlock(pretty(args)));
+      }
+      if (item.settled) {
+        node.append(el("div", "note", item.settled));
+      } else {
+        const actions = el("div", "actions");
+        const approve = el("button", "", "Approve");
+        const deny = el("button", "deny", "Deny");
+        approve.onclick = () => decide(item, "approve");
+        deny.onclick = () => decide(item, "deny");
+        actions.append(approve, deny);
+        node.append(actions);
+      }
+      return node;
+    }
+    case "error":
+      return el("div", "note error", item.text);
+    default:
+      return el("div", "note", item.text);
+  }
+}
+
+function decide(item, decision) {
+  send({ type: "approve_action", session_id: state.current, approval_id: item.id, decision });
+}
+
+function renderSession() {
+  const chat = $("chat");
+  const s = state.current ? session(state.current) : null;
+  const summary = state.summaries.find((x) => x.session_id === state.current);
+  $("session-title").textContent = summary ? summary.title : state.current ? "Session" : "No session selected";
+  $("session-status").textContent = s ? (s.typing ? "● " : "") + s.status : "";
+  $("presence").textContent = s && s.participants.length ? "👥 " + s.participants.join(", ") : "";
+  // One message at a time: the composer waits until the agent has replied
+  $("input").disabled = !s || !!s.turn;
+  $("input").placeholder = s && s.turn
+    ? `Waiting for the agent to answer ${s.turn}…`
+    : "Message the agent (Enter to send, Shift+Enter for a new line)";
+  $("send").disabled = !s || !!s.turn;
+
+  const atBottom = chat.scrollHeight - chat.scrollTop - chat.clientHeight < 40;
+  chat.replaceChildren(...(s ? s.items.map(renderItem) : []));
+  if (atBottom) chat.scrollTop = chat.scrollHeight;
+
+  const jobs = $("jobs");
+  jobs.replaceChildren();
+  if (!s || s.jobs.length === 0) jobs.append(el("li", "empty", "No jobs"));
+  for (const job of s ? s.jobs : []) {
+    const li = el("li", job.status, job.objective);
+    const detail = job.error || job.current_tool || "";
+    li.append(el("span", "status", `${job.agent_id} · ${job.status}${detail ? " · " + detail : ""}`));
+    jobs.append(li);
+  }
+}
+
+// ---- input ----------------------------------------------------------------
+
+$("set-name").onclick = () => {
+  const name = prompt("Name shown to others in shared sessions:", displayName() || "");
+  if (name === null) return;
+  localStorage.setItem("mylm-name", name.trim());
+  // The name is sent in hello, so reconnect to apply it
+  state.ws.close();
+};
+
+$("new-session").onclick = () => send({ type: "create_session", profile: "" });
+
+$("composer").onsubmit = (e) => {
+  e.preventDefault();
+  const text = $("input").value.trim();
+  if (!text || !state.current) return;
+  send({ type: "send_user_message", session_id: state.current, message: { text, attachments: [] } });
+  $("input").value = "";
+};
+
+$("input").onkeydown = (e) => {
+  if (e.key === "Enter" && !e.shiftKey) {
+    e.preventDefault();
+    $("composer").requestSubmit();
+  }
+};
+
+setInterval(() => send({ type: "list_sessions" }), 5000);
+connect();
@@ -5,7 +5,7 @@
 
 use anyhow::Result;
 use mylm_core::config::Config;
-use std::io::Write;
+use mylm_core::memory::MemoryBackendKind;
 
 use crate::hub;
 use crate::hub::{SettingsMenuChoice, MainLLMSettingsChoice, WorkerLLMSettingsChoice};
@@ -34,6 +34,9 @@ pub async fn run_settings_dashboard(config: &mut Config) -> Result<()> {
             SettingsMenuChoice::TestWorkerConnection => {
                 hub::test_profile_connection(config, "worker").await?;
             }
+            SettingsMenuChoice::ModelRecommendations => {
+                hub::handle_model_recommendations(config).await?;
+            }
             SettingsMenuChoice::WebSearchSettings => {
                 run_web_search_menu(config).await?;
             }
@@ -263,24 +266,25 @@ pub async fn run_application_settings(config: &mut Config) -> Result<()> {
 /// MEMORY MANAGEMENT
 /// ============================================================================
 
-pub async fn run_memory_management(_config: &mut Config) -> Result<()> {
+pub async fn run_memory_management(config: &mut Config) -> Result<()> {
+    let backend = config.features.memory_settings.backend;
     loop {
         match hub::show_memory_management_menu()? {
             MemoryManagementChoice::ViewMemoryStats => {
-                show_memory_stats().await;
+                show_memory_stats(backend).await;
             }
             MemoryManagementChoice::ExportArchive => {
-                if let Err(e) = export_memories().await {
+                if let Err(e) = export_memories(backend).await {
                     eprintln!("\n❌ Export failed: {}", e);
                 }
             }
             MemoryManagementChoice::DeleteAll => {
-                if let Err(e) = delete_all_memories().await {
+                if let Err(e) = delete_all_memories(backend).await {
                     eprintln!("\n❌ Delete failed: {}", e);
                 }
             }
             MemoryManagementChoice::ImportMemories => {
-                if let Err(e) = import_memories().await {
+                if let Err(e) = import_memories(backend).await {
                     eprintln!("\n❌ Import failed: {}", e);
                 }
             }
@@ -291,7 +295,7 @@ pub async fn run_memory_management(_config: &mut Config) -> Result<()> {
 }
 
 /// Show memory statistics
-async fn show_memory_stats() {
+async fn show_memory_stats(backend: MemoryBackendKind) {
     use mylm_core::config::agent::MemoryConfig;
     use mylm_core::agent::memory::AgentMemoryManager;
     
@@ -307,6 +311,7 @@ async fn show_memory_stats() {
     // Try to get actual count from manager
     let memory_config = MemoryConfig {
         enabled: true,
+        backend,
         ..MemoryConfig::default()
     };
     
@@ -367,17 +372,17 @@ fn calculate_dir_size(path: &std::path::Path) -> u64 {
         .unwrap_or(0)
 }
 
-/// Export all memories to JSON file
-async fn export_memories() -> Result<()> {
+/// Export all memories to a JSONL file
+async fn export_memories(backend: MemoryBackendKind) -> Result<()> {
     use mylm_core::config::agent::MemoryConfig;
     use mylm_core::agent::memory::AgentMemoryManager;
-    use std::io::Write;
+    use mylm_core::memory::transfer;
     
     println!("\n💾 Exporting Memories");
     println!("{}", "─".repeat(40));
     
     // Get output path
-    let default_name = format!("mylm_memory_backup_{}.json", 
+    let default_name = format!("mylm_memory_backup_{}.jsonl", 
         chrono::Local::now().format("%Y%m%d_%H%M%S"));
     
     let export_path: String = dialoguer::Input::new()
@@ -402,25 +407,17 @@ async fn export_memories() -> Result<()> {
     // Connect to memory
     let memory_config = MemoryConfig {
         enabled: true,
+        backend,
         ..MemoryConfig::default()
     };
     
     let manager = AgentMemoryManager::new(memory_config).await?;
     
-    // Get all memories (load with large limit)
-    println!("Loading memories...");
-    let memories = manager.get_recent_memories(10000).await?;
-    
-    println!("Found {} memories to export", memories.len());
-    
-    // Serialize to JSON
-    let json = serde_json::to_string_pretty(&memories)?;
-    
-    // Write to file
-    let mut file = std::fs::File::create(&export_path)?;
-    file.write_all(json.as_bytes())?;
+    println!("Exporting memories, categories and embeddings...");
+    let stats = transfer::export_to_file(manager.store().as_ref(), &export_path).await?;
     
-    println!("✅ Exported {} memories to {}", memories.len(), export_path.display());
+    println!("✅ Exported {} memories and {} categories to {}",
+        stats.memories, stats.categories, export_path.display());
     
     // Show file size
     if let Ok(metadata) = std::fs::metadata(&export_path) {
@@ -432,7 +429,7 @@ async fn export_memories() -> Result<()> {
 }
 
 /// Delete all memories with confirmation
-async fn delete_all_memories() -> Result<()> {
+async fn delete_all_memories(backend: MemoryBackendKind) -> Result<()> {
     use mylm_core::config::agent::MemoryConfig;
     use mylm_core::agent::memory::AgentMemoryManager;
     
@@ -447,6 +444,7 @@ async fn delete_all_memories() -> Result<()> {
     // Get count first
     let memory_config = MemoryConfig {
         enabled: true,
+        backend,
         ..MemoryConfig::default()
     };
     
@@ -496,10 +494,11 @@ async fn delete_all_memories() -> Result<()> {
     Ok(())
 }
 
-/// Import memories from JSON file
-async fn import_memories() -> Result<()> {
+/// Import memories from a JSONL (or older JSON) export
+async fn import_memories(backend: MemoryBackendKind) -> Result<()> {
     use mylm_core::config::agent::MemoryConfig;
     use mylm_core::agent::memory::AgentMemoryManager;
+    use mylm_core::memory::transfer;
     
     println!("\n📥 Import Memories");
     println!("{}", "─".repeat(40));
@@ -516,21 +515,6 @@ async fn import_memories() -> Result<()> {
         return Ok(());
     }
     
-    // Read JSON
-    println!("Reading file...");
-    let json = std::fs::read_to_string(&import_path)?;
-    
-    // Parse memories
-    println!("Parsing memories...");
-    let memories: Vec<mylm_core::memory::store::Memory> = serde_json::from_str(&json)?;
-    
-    println!("Found {} memories to import", memories.len());
-    
-    if memories.is_empty() {
-        println!("No memories to import.");
-        return Ok(());
-    }
-    
     // Confirm
     let proceed = dialoguer::Confirm::new()
         .with_prompt("Proceed with import?")
@@ -545,37 +529,17 @@ async fn import_memories() -> Result<()> {
     // Connect to memory
     let memory_config = MemoryConfig {
         enabled: true,
+        backend,
         ..MemoryConfig::default()
     };
     
     let manager = AgentMemoryManager::new(memory_config).await?;
     
-    // Import each memory
     println!("Importing...");
-    let mut imported = 0;
-    let mut failed = 0;
-    
-    for memory in memories {
-        match manager.add_memory_full(
-            &memory.content,
-            memory.r#type,
-            memory.session_id.clone(),
-            memory.metadata.clone(),
-            memory.category_id.clone(),
-            memory.summary.clone(),
-        ).await {
-            Ok(_) => imported += 1,
-            Err(_) => failed += 1,
-        }
-        
-        // Progress every 100
-        if imported % 100 == 0 {
-            print!("\r   Progress: {} imported", imported);
-            std::io::stdout().flush().ok();
-        }
-    }
+    let stats = transfer::import_from_file(manager.store().as_ref(), &import_path).await?;
     
-    println!("\r✅ Import complete: {} imported, {} failed", imported, failed);
+    println!("✅ Import complete: {} memories and {} categories imported, {} already present, {} failed",
+        stats.memories, stats.categories, stats.skipped, stats.failed);
     
     Ok(())
 }
@@ -0,0 +1,181 @@
+//! `mylm suggest <partial command>` - one completion for a shell prompt
+//!
+//! Called from the keybinding `mylm shell-init` installs (Ctrl-Space), so it
+//! has to answer while the user waits: the worker model gets the partial
+//! line, the working directory, a few file names and the commands the shell
+//! hooks recorded, and has `timeout` to propose the whole command. Only the
+//! text after what was typed is printed, or nothing at all.
+//!
+//! Completions are kept per directory in `<cache dir>/mylm/suggestions.json`.
+//! A cached completion is reused while the user keeps typing what it
+//! suggested, the way fish autosuggestions follow the typed text, so most
+//! keypresses after the first never reach the model.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use mylm_core::config::Config;
+use mylm_core::environment::shell_hooks;
+use mylm_core::provider::LlmClient;
+
+/// Completions kept in the cache
+const MAX_CACHED: usize = 500;
+/// Age after which a cached completion is no longer offered
+const CACHE_TTL_SECS: i64 = 24 * 60 * 60;
+/// Recorded commands and file names sent as context
+const CONTEXT_COMMANDS: usize = 10;
+const CONTEXT_FILES: usize = 40;
+
+const SUGGEST_INSTRUCTIONS: &str = "Complete the shell command the user is typing. Reply with \
+the complete command line on one line, starting with exactly the text typed so far, and nothing \
+else: no explanation, no code fences. Prefer commands that fit the recent commands and the files \
+in the directory. If there is no likely completion, reply with the typed text unchanged.";
+
+/// A completion the model proposed for `line` typed in `cwd`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CachedSuggestion {
+    cwd: String,
+    line: String,
+    /// Text after `line`
+    completion: String,
+    /// Unix time it was stored
+    stored_at: i64,
+}
+
+fn cache_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("mylm").join("suggestions.json"))
+}
+
+fn load_cache(path: &Path) -> Vec<CachedSuggestion> {
+    std::fs::read(path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+/// The rest of a cached completion `line` extends, if any
+fn cached_completion(cache: &[CachedSuggestion], cwd: &str, line: &str, now: i64) -> Option<String> {
+    cache
+        .iter()
+        .rev()
+        .filter(|entry| entry.cwd == cwd && now - entry.stored_at < CACHE_TTL_SECS)
+        .find_map(|entry| {
+            let full = format!("{}{}", entry.line, entry.completion);
+            let rest = full.strip_prefix(line)?;
+            (line.starts_with(&entry.line) && !rest.is_empty()).then(|| rest.to_string())
+        })
+}
+
+fn store(path: &Path, mut cache: Vec<CachedSuggestion>, entry: CachedSuggestion) -> Result<()> {
+    cache.retain(|cached| cached.cwd != entry.cwd || cached.line != entry.line);
+    cache.push(entry);
+    let excess = cache.len().saturating_sub(MAX_CACHED);
+    cache.drain(..excess);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("Cannot create {}", dir.display()))?;
+    }
+    // Write aside and rename, so a concurrent lookup never reads half a file
+    let temp = path.with_extension(format!("{}.tmp", std::process::id()));
+    std::fs::write(&temp, serde_json::to_vec(&cache)?)?;
+    std::fs::rename(&temp, path).context("Cannot store suggestion")?;
+    Ok(())
+}
+
+fn build_prompt(line: &str, cwd: &Path) -> String {
+    let mut prompt = format!("{}\n\nDirectory: {}\n", SUGGEST_INSTRUCTIONS, cwd.display());
+    let mut files: Vec<String> = std::fs::read_dir(cwd)
+        .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.file_name().to_string_lossy().to_string()).collect())
+        .unwrap_or_default();
+    files.sort();
+    files.truncate(CONTEXT_FILES);
+    if !files.is_empty() {
+        prompt.push_str(&format!("Files: {}\n", files.join(" ")));
+    }
+    let recent = shell_hooks::recent_commands(CONTEXT_COMMANDS);
+    if !recent.is_empty() {
+        prompt.push_str("Recent commands (exit code):\n");
+        for command in &recent {
+            prompt.push_str(&format!("  [{}] {}\n", command.exit_code, command.command));
+        }
+    }
+    prompt.push_str(&format!("\nTyped so far: {}", line));
+    prompt
+}
+
+/// The text to append to `line`, from the model's reply
+fn completion_from_reply(line: &str, reply: &str) -> Option<String> {
+    let full = reply
+        .lines()
+        .map(|l| l.trim_end())
+        .find(|l| !l.trim().is_empty() && !l.trim_start().starts_with("```"))?;
+    let full = full.trim_start_matches('`').trim_end_matches('`');
+    let rest = full.strip_prefix(line)?;
+    (!rest.is_empty()).then(|| rest.to_string())
+}
+
+/// The completion for `line`, from the cache or the worker model; None
+/// when there is none or the model did not answer within `timeout`
+pub async fn suggest(config: &Config, line: &str, timeout: Duration) -> Result<Option<String>> {
+    if line.trim().is_empty() {
+        return Ok(None);
+    }
+    let cwd = std::env::current_dir()?;
+    let cwd_key = cwd.to_string_lossy().to_string();
+    let now = chrono::Utc::now().timestamp();
+    let cache_file = cache_path();
+    let cache = cache_file.as_deref().map(load_cache).unwrap_or_default();
+    if let Some(rest) = cached_completion(&cache, &cwd_key, line, now) {
+        return Ok(Some(rest));
+    }
+
+    // No failover and a short answer: a late suggestion is no suggestion
+    let llm_config = mylm_core::config::worker_llm_config(config)?
+        .with_fallbacks(Vec::new())
+        .with_max_tokens(64)
+        .with_temperature(0.0);
+    let client = LlmClient::new(llm_config)?;
+    let reply = match tokio::time::timeout(timeout, client.complete(&build_prompt(line, &cwd))).await {
+        Ok(reply) => reply?.content(),
+        Err(_) => return Ok(None),
+    };
+    let completion = completion_from_reply(line, &reply);
+    if let (Some(path), Some(completion)) = (cache_file, &completion) {
+        let entry = CachedSuggestion {
+            cwd: cwd_key,
+            line: line.to_string(),
+            completion: completion.clone(),
+            stored_at: now,
+        };
+        let _ = store(&path, cache, entry);
+    }
+    Ok(completion)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_follows_typing_and_reply_parsing() {
+        let cache = vec![CachedSuggestion {
+            cwd: "/repo".to_string(),
+            line: "git ch".to_string(),
+            completion: "eckout main".to_string(),
+            stored_at: 1_000,
+        }];
+        assert_eq!(cached_completion(&cache, "/repo", "git chec", 1_010).as_deref(), Some("kout main"));
+        assert_eq!(cached_completion(&cache, "/repo", "git checkout main", 1_010), None);
+        assert_eq!(cached_completion(&cache, "/repo", "git cl", 1_010), None);
+        assert_eq!(cached_completion(&cache, "/other", "git chec", 1_010), None);
+        assert_eq!(cached_completion(&cache, "/repo", "git chec", 1_000 + CACHE_TTL_SECS), None);
+
+        assert_eq!(completion_from_reply("cargo t", "cargo test --workspace\n").as_deref(), Some("est --workspace"));
+        assert_eq!(completion_from_reply("ls", "```\nls -la\n```").as_deref(), Some(" -la"));
+        assert_eq!(completion_from_reply("ls", "`ls -la`").as_deref(), Some(" -la"));
+        assert_eq!(completion_from_reply("ls", "ls"), None);
+        assert_eq!(completion_from_reply("ls", "dir /w"), None);
+    }
+}
@@ -0,0 +1,317 @@
+//! GitHub Actions output for headless task runs
+//!
+//! Inside a workflow (`GITHUB_ACTIONS=true`, or `--annotations`), findings the
+//! agent reports become `::error`/`::warning`/`::notice` workflow commands,
+//! which GitHub shows inline on the PR diff, and a markdown report is appended
+//! to `$GITHUB_STEP_SUMMARY`.
+//!
+//! The agent reports findings as lines of its final answer:
+//!
+//! ```text
+//! FINDING error src/lib.rs:42 `parse` panics on empty input
+//! FINDING notice - Consider enabling clippy::pedantic
+//! ```
+//!
+//! Everything the agent writes is untrusted: a line like `::add-mask::` or
+//! `::error::` in its answer or tool arguments would otherwise be run as a
+//! workflow command. While the agent works, command processing is stopped
+//! with `::stop-commands::` and a random token (see `CommandsPaused`), and
+//! findings only reach the log escaped, as annotation data.
+
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use super::{TaskReport, TaskStatus};
+
+/// Prefix of a finding line in the agent's answer
+const FINDING_PREFIX: &str = "FINDING ";
+
+/// Told to the agent when annotations are on
+pub const FINDINGS_INSTRUCTIONS: &str = "\nReport every problem you found but did not fix on its own line of your final answer, formatted as `FINDING <error|warning|notice> <path>:<line> <message>` (use `-` instead of the location when there is no file). These become annotations on the pull request.\n";
+
+/// Whether this process runs inside a GitHub Actions job
+pub fn in_github_actions() -> bool {
+    std::env::var("GITHUB_ACTIONS").is_ok_and(|v| v == "true")
+}
+
+/// Workflow commands stay off until this is dropped (a no-op outside
+/// GitHub Actions)
+pub struct CommandsPaused {
+    token: Option<String>,
+}
+
+impl CommandsPaused {
+    pub fn start() -> Self {
+        let token = in_github_actions().then(|| uuid::Uuid::new_v4().simple().to_string());
+        if let Some(token) = &token {
+            println!("{}", stop_commands(token));
+        }
+        Self { token }
+    }
+}
+
+impl Drop for CommandsPaused {
+    fn drop(&mut self) {
+        if let Some(token) = &self.token {
+            println!("{}", resume_commands(token));
+        }
+    }
+}
+
+fn stop_commands(token: &str) -> String {
+    format!("::stop-commands::{}", token)
+}
+
+fn resume_commands(token: &str) -> String {
+    format!("::{}::", token)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Notice,
+}
+
+impl Severity {
+    fn command(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Notice => "notice",
+        }
+    }
+
+    pub(crate) fn icon(self) -> &'static str {
+        match self {
+            Severity::Error => "❌",
+            Severity::Warning => "⚠️",
+            Severity::Notice => "ℹ️",
+        }
+    }
+}
+
+/// One problem the agent reported
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub severity: Severity,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub message: String,
+}
+
+/// Pull `FINDING` lines out of an answer; returns the findings and the rest
+pub fn extract_findings(answer: &str) -> (Vec<Finding>, String) {
+    let mut findings = Vec::new();
+    let mut rest = Vec::new();
+    for line in answer.lines() {
+        match parse_finding(line) {
+            Some(finding) => findings.push(finding),
+            None => rest.push(line),
+        }
+    }
+    (findings, rest.join("\n").trim().to_string())
+}
+
+fn parse_finding(line: &str) -> Option<Finding> {
+    let line = line.trim().trim_start_matches(['-', '*']).trim_start().trim_matches('`');
+    let body = line.strip_prefix(FINDING_PREFIX)?;
+    let (severity, body) = body.trim_start().split_once(' ')?;
+    let severity = match severity.to_lowercase().as_str() {
+        "error" => Severity::Error,
+        "warning" | "warn" => Severity::Warning,
+        "notice" | "info" => Severity::Notice,
+        _ => return None,
+    };
+    let (location, message) = body.trim_start().split_once(' ')?;
+    let (file, line) = match location {
+        "-" => (None, None),
+        location => match location.rsplit_once(':') {
+            Some((file, line)) if line.parse::<u32>().is_ok() => (Some(file.to_string()), line.parse().ok()),
+            _ => (Some(location.to_string()), None),
+        },
+    };
+    let message = message.trim();
+    if message.is_empty() {
+        return None;
+    }
+    Some(Finding {
+        severity,
+        file,
+        line,
+        message: message.to_string(),
+    })
+}
+
+/// Workflow command data: newlines and `%` are escaped
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// Workflow command properties also escape `:` and `,`
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// The workflow command lines for a finished run
+pub fn annotations(report: &TaskReport) -> Vec<String> {
+    let mut lines: Vec<String> = report
+        .findings
+        .iter()
+        .map(|finding| {
+            let mut properties = Vec::new();
+            if let Some(file) = &finding.file {
+                properties.push(format!("file={}", escape_property(file)));
+            }
+            if let Some(line) = finding.line {
+                properties.push(format!("line={}", line));
+            }
+            properties.push("title=mylm".to_string());
+            format!("::{} {}::{}", finding.severity.command(), properties.join(","), escape_data(&finding.message))
+        })
+        .collect();
+    match &report.status {
+        TaskStatus::Succeeded => {}
+        TaskStatus::CheckFailed => lines.push(format!(
+            "::error title={}::{}",
+            escape_property("mylm success check failed"),
+            escape_data(&format!("`{}` failed after the agent finished", report.success_command.as_deref().unwrap_or_default()))
+        )),
+        TaskStatus::Incomplete(reason) => lines.push(format!(
+            "::error title={}::{}",
+            escape_property("mylm task did not finish"),
+            escape_data(reason)
+        )),
+    }
+    lines
+}
+
+/// Markdown for the job's step summary
+pub fn step_summary(report: &TaskReport) -> String {
+    let mut md = format!("## mylm task: {}\n\n", report.objective.lines().next().unwrap_or_default());
+    let result = match &report.status {
+        TaskStatus::Succeeded => "✅ Succeeded".to_string(),
+        TaskStatus::CheckFailed => format!(
+            "❌ Success check `{}` failed",
+            report.success_command.as_deref().unwrap_or_default()
+        ),
+        TaskStatus::Incomplete(reason) => format!("⚠️ Did not finish: {}", reason),
+    };
+    md.push_str(&format!("**Result:** {}\n\n", result));
+
+    if !report.findings.is_empty() {
+        md.push_str("### Findings\n\n| | Location | Message |\n|---|---|---|\n");
+        for finding in &report.findings {
+            let location = match (&finding.file, finding.line) {
+                (Some(file), Some(line)) => format!("`{}:{}`", file, line),
+                (Some(file), None) => format!("`{}`", file),
+                _ => String::new(),
+            };
+            md.push_str(&format!(
+                "| {} | {} | {} |\n",
+                finding.severity.icon(),
+                location,
+                finding.message.replace('|', "\\|")
+            ));
+        }
+        md.push('\n');
+    }
+    if !report.answer.is_empty() {
+        md.push_str(&format!("### Agent summary\n\n{}\n", report.answer));
+    }
+    md
+}
+
+/// Print annotations and append the step summary, if the job provides one
+///
+/// Call once commands are resumed: the annotations are commands, with the
+/// agent's text escaped so it cannot start a line of its own.
+pub fn publish(report: &TaskReport) -> Result<()> {
+    for line in annotations(report) {
+        println!("{}", line);
+    }
+    let Some(path) = std::env::var_os("GITHUB_STEP_SUMMARY") else {
+        return Ok(());
+    };
+    let path = Path::new(&path);
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open step summary {}", path.display()))?;
+    writeln!(file, "{}", step_summary(report))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_findings() {
+        let answer = "Fixed two lints.\n- FINDING error src/lib.rs:42 `parse` panics on empty input\nFINDING warning Cargo.toml serde is pinned\nFINDING notice - Consider clippy::pedantic\nFINDING bogus x y\nDone.";
+        let (findings, rest) = extract_findings(answer);
+        assert_eq!(findings.len(), 3);
+        assert_eq!(findings[0].file.as_deref(), Some("src/lib.rs"));
+        assert_eq!(findings[0].line, Some(42));
+        assert_eq!(findings[0].message, "`parse` panics on empty input");
+        assert_eq!(findings[1].file.as_deref(), Some("Cargo.toml"));
+        assert_eq!(findings[1].line, None);
+        assert_eq!(findings[2].file, None);
+        assert_eq!(rest, "Fixed two lints.\nFINDING bogus x y\nDone.");
+    }
+
+    #[test]
+    fn test_annotations_and_summary() {
+        let (findings, answer) = extract_findings("FINDING error src/a,b.rs:3 bad\nvalue: 100%\nAll done.");
+        let report = TaskReport {
+            objective: "Fix the lints".to_string(),
+            success_command: Some("cargo clippy".to_string()),
+            status: TaskStatus::CheckFailed,
+            answer,
+            findings,
+        };
+        let lines = annotations(&report);
+        assert_eq!(lines[0], "::error file=src/a%2Cb.rs,line=3,title=mylm::bad");
+        assert!(lines[1].starts_with("::error title=mylm success check failed::`cargo clippy`"));
+
+        let md = step_summary(&report);
+        assert!(md.contains("**Result:** ❌ Success check `cargo clippy` failed"));
+        assert!(md.contains("| ❌ | `src/a,b.rs:3` | bad |"));
+        assert!(md.contains("### Agent summary\n\nvalue: 100%\nAll done."));
+    }
+
+    #[test]
+    fn test_untrusted_text_cannot_issue_commands() {
+        let (findings, answer) = extract_findings("FINDING error - bad\n::add-mask::x\nok");
+        let report = TaskReport {
+            objective: "Check".to_string(),
+            success_command: None,
+            status: TaskStatus::Succeeded,
+            answer,
+            findings,
+        };
+        let lines = annotations(&report);
+        assert_eq!(lines, vec!["::error title=mylm::bad"]);
+        // A message can carry newlines only escaped
+        let finding = Finding {
+            severity: Severity::Notice,
+            file: None,
+            line: None,
+            message: "a\n::add-mask::x".to_string(),
+        };
+        let report = TaskReport {
+            findings: vec![finding],
+            ..report
+        };
+        assert_eq!(annotations(&report), vec!["::notice title=mylm::a%0A::add-mask::x"]);
+
+        let token = "0123abcd";
+        assert_eq!(stop_commands(token), "::stop-commands::0123abcd");
+        assert_eq!(resume_commands(token), "::0123abcd::");
+    }
+}
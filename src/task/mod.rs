@@ -0,0 +1,301 @@
+//! `mylm task run` - execute a task file headlessly
+//!
+//! Runs the agent on a `TaskFile` with no one to ask: listed tools get an
+//! `allow` policy rule (so shell commands that chain or substitute, `deny`
+//! and `ask` rules and write `confirm` scopes still stop them), everything
+//! else dangerous is denied. Progress goes to
+//! stderr, the agent's final answer to stdout, and the exit code tells CI
+//! how it went (see `TaskStatus`). In GitHub Actions the result is also
+//! published as annotations and a step summary (see `github`).
+//!
+//! With `--worktree` (or `worktree: true` in the task file) the agent works
+//! in a git worktree on a new branch; its changes are committed there and
+//! shown as a diff, and the branch is merged only if the user agrees.
+
+pub mod github;
+
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use console::style;
+use tokio::sync::broadcast;
+
+use mylm_core::agent::factory::AgentSessionFactory;
+use mylm_core::agent::runtime::capabilities::PolicyApprovalCapability;
+use mylm_core::agent::runtime::Session;
+use mylm_core::agent::types::parser::ShortKeyStreamParser;
+use mylm_core::agent::{OutputEvent, UserInput};
+use mylm_core::config::Config;
+use mylm_core::task::TaskFile;
+use mylm_core::worktree::Worktree;
+
+/// How a task ended; each maps to a process exit code
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskStatus {
+    /// The agent finished and the success command (if any) passed
+    Succeeded,
+    /// The agent finished but the success command failed
+    CheckFailed,
+    /// The agent stopped before finishing: halted, over budget or timed out
+    Incomplete(String),
+}
+
+impl TaskStatus {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            TaskStatus::Succeeded => 0,
+            TaskStatus::CheckFailed => 1,
+            TaskStatus::Incomplete(_) => 2,
+        }
+    }
+}
+
+/// Everything known about a finished run
+#[derive(Debug, Clone)]
+pub struct TaskReport {
+    pub objective: String,
+    pub success_command: Option<String>,
+    pub status: TaskStatus,
+    /// The agent's final answer, without finding lines
+    pub answer: String,
+    pub findings: Vec<github::Finding>,
+}
+
+/// Run a task file; with `annotations`, ask the agent for findings and
+/// publish the result as GitHub Actions annotations and step summary; with
+/// `worktree`, work in a git worktree
+pub async fn run(config: &Config, path: &Path, annotations: bool, worktree: bool) -> Result<TaskReport> {
+    let task = TaskFile::load(path)?;
+    let base_dir = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let mut prompt = task.prompt(base_dir)?;
+    if annotations {
+        prompt.push_str(github::FINDINGS_INSTRUCTIONS);
+    }
+    let sandbox = if worktree || task.worktree { Some(enter_worktree(&task)?) } else { None };
+    // The agent's progress and answer must not run as workflow commands
+    let paused = github::CommandsPaused::start();
+    let result = execute(config, &task, prompt).await;
+    if let Some((worktree, checkout)) = sandbox {
+        leave_worktree(&worktree, &checkout, &task)?;
+    }
+    drop(paused);
+    let (status, answer) = result?;
+    let (findings, answer) = github::extract_findings(&answer);
+    let report = TaskReport {
+        objective: task.objective.clone(),
+        success_command: task.success_command.clone(),
+        status,
+        answer,
+        findings,
+    };
+    if annotations {
+        github::publish(&report)?;
+    }
+    Ok(report)
+}
+
+/// Let the agent work on `prompt`, then run the success command
+pub async fn execute(config: &Config, task: &TaskFile, prompt: String) -> Result<(TaskStatus, String)> {
+    let mut config = config.clone();
+    if task.max_cost_usd.is_some() {
+        config.features.budget.max_session_cost_usd = task.max_cost_usd;
+    }
+    if task.max_tokens.is_some() {
+        config.features.budget.max_session_tokens = task.max_tokens;
+    }
+    let profile = task.profile.clone().unwrap_or_else(|| config.active_profile.clone());
+
+    let mut settings = config.features.approval.clone();
+    settings.auto_approve_commands.extend(task.auto_approve_commands.iter().cloned());
+    settings.forbidden_commands.extend(task.forbidden_commands.iter().cloned());
+    settings.rules.extend(allow_rules(&task.allowed_tools));
+    let approval = PolicyApprovalCapability::from_config(&config, None).with_settings(&settings);
+
+    let mut factory = AgentSessionFactory::new(config).with_approval(Arc::new(approval));
+    if !task.allowed_tools.is_empty() {
+        factory = factory.with_allowed_tools(task.allowed_tools.clone());
+    }
+    let mut session = factory
+        .create_session(&profile, None)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to create agent session: {}", e))?;
+
+    let input_tx = session.input_sender();
+    let mut output_rx = session.subscribe_output();
+    let session_task = tokio::spawn(async move { session.run().await });
+
+    eprintln!("{} {}", style("Task:").bold(), task.objective.lines().next().unwrap_or_default());
+    input_tx
+        .send(UserInput::Message(prompt))
+        .await
+        .context("Agent session stopped before the task was sent")?;
+
+    let outcome = tokio::time::timeout(task.timeout(), follow(&mut output_rx)).await;
+    session_task.abort();
+    let answer = match outcome {
+        Ok(Ok(answer)) => answer,
+        Ok(Err(reason)) => return Ok((TaskStatus::Incomplete(reason), String::new())),
+        Err(_) => {
+            let reason = format!("timed out after {}s", task.timeout().as_secs());
+            return Ok((TaskStatus::Incomplete(reason), String::new()));
+        }
+    };
+    println!("{}", answer);
+
+    let Some(command) = &task.success_command else {
+        return Ok((TaskStatus::Succeeded, answer));
+    };
+    eprintln!("{} {}", style("Checking:").bold(), command);
+    let status = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .status()
+        .await
+        .with_context(|| format!("Failed to run success command `{}`", command))?;
+    let status = if status.success() { TaskStatus::Succeeded } else { TaskStatus::CheckFailed };
+    Ok((status, answer))
+}
+
+/// Policy rules letting the task's chosen tools run without a prompt
+fn allow_rules(allowed_tools: &[String]) -> Vec<String> {
+    allowed_tools.iter().map(|tool| format!("allow {}", tool)).collect()
+}
+
+/// Create a worktree for `task` and move into it; returns it and the
+/// directory to come back to
+fn enter_worktree(task: &TaskFile) -> Result<(Worktree, PathBuf)> {
+    let checkout = std::env::current_dir()?;
+    let label = task.objective.lines().next().unwrap_or_default();
+    let worktree = Worktree::create(&checkout, label)?;
+    std::env::set_current_dir(worktree.map(&checkout))
+        .with_context(|| format!("Failed to enter {}", worktree.path.display()))?;
+    eprintln!(
+        "{} {} (branch {})",
+        style("Worktree:").bold(),
+        worktree.path.display(),
+        worktree.branch
+    );
+    Ok((worktree, checkout))
+}
+
+/// Commit what the agent left in the worktree, show the diff, and merge it
+/// if the user agrees; otherwise the branch is kept for later
+fn leave_worktree(worktree: &Worktree, checkout: &Path, task: &TaskFile) -> Result<()> {
+    std::env::set_current_dir(checkout)?;
+    let subject = task.objective.lines().next().unwrap_or("mylm task").trim();
+    worktree.commit(subject)?;
+    if !worktree.has_changes()? {
+        eprintln!("{} no changes, worktree removed", style("Worktree:").bold());
+        return worktree.remove();
+    }
+
+    eprintln!("
+{}
+{}
+
+{}", style("Changes on").bold(), worktree.stat()?, worktree.diff()?);
+    let interactive = std::io::stdin().is_terminal() && std::io::stderr().is_terminal();
+    let merge = interactive
+        && dialoguer::Confirm::new()
+            .with_prompt(format!("Merge {} into your checkout?", worktree.branch))
+            .default(false)
+            .interact()?;
+    if merge {
+        worktree.merge()?;
+        eprintln!("{} merged {}", style("Worktree:").bold(), worktree.branch);
+    } else {
+        eprintln!(
+            "{} kept {}
+  merge:   git merge {}
+  discard: git worktree remove --force {} && git branch -D {}",
+            style("Worktree:").bold(),
+            worktree.branch,
+            worktree.branch,
+            worktree.path.display(),
+            worktree.branch
+        );
+    }
+    Ok(())
+}
+
+/// Print progress until the agent gives its final answer, or say why it stopped
+pub async fn follow(output_rx: &mut broadcast::Receiver<OutputEvent>) -> Result<String, String> {
+    let mut parser = ShortKeyStreamParser::new();
+    let mut raw = String::new();
+    loop {
+        let event = match output_rx.recv().await {
+            Ok(event) => event,
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => return Err("agent session ended".to_string()),
+        };
+        match event {
+            OutputEvent::ResponseChunk { content } => {
+                raw.push_str(&content);
+                parser.push(&content);
+            }
+            OutputEvent::ResponseComplete { .. } => {
+                let answer = final_text(&parser, &raw);
+                parser.reset();
+                raw.clear();
+                // Responses that only carried a tool call have no final text
+                if let Some(answer) = answer {
+                    return Ok(answer);
+                }
+            }
+            OutputEvent::ToolExecuting { tool, args, .. } => {
+                let args: String = args.chars().take(120).collect();
+                eprintln!("{} {} {}", style("→").cyan(), style(tool).bold(), style(args).dim());
+            }
+            OutputEvent::Error { message } => eprintln!("{} {}", style("error:").red(), message),
+            OutputEvent::Halted { reason } => return Err(format!("agent halted: {}", reason)),
+            _ => {}
+        }
+    }
+}
+
+/// The final answer of a completed response, if it had one
+fn final_text(parser: &ShortKeyStreamParser, raw: &str) -> Option<String> {
+    let answer = parser.final_answer().trim();
+    if !answer.is_empty() {
+        return Some(answer.to_string());
+    }
+    // Plain-text replies never open a JSON object
+    (parser.thought().is_empty() && !raw.trim().is_empty() && !raw.trim_start().starts_with('{'))
+        .then(|| raw.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_final_text() {
+        let mut parser = ShortKeyStreamParser::new();
+        let tool_call = r#"{"t": "Check the lints", "a": "shell", "i": {"command": "cargo clippy"}}"#;
+        parser.push(tool_call);
+        assert_eq!(final_text(&parser, tool_call), None);
+
+        let mut parser = ShortKeyStreamParser::new();
+        let done = r#"{"t": "All fixed", "f": "Fixed 3 warnings and committed."}"#;
+        parser.push(done);
+        assert_eq!(final_text(&parser, done).as_deref(), Some("Fixed 3 warnings and committed."));
+
+        let parser = ShortKeyStreamParser::new();
+        assert_eq!(final_text(&parser, "Done.").as_deref(), Some("Done."));
+        assert_eq!(TaskStatus::Incomplete("halted".into()).exit_code(), 2);
+    }
+
+    #[test]
+    fn test_allowed_tools_go_through_policy() {
+        use mylm_core::agent::runtime::governance::PolicyRules;
+
+        let rules = PolicyRules::parse(&allow_rules(&["shell".to_string(), "read_file".to_string()]));
+        let cwd = Path::new("/repo");
+        assert!(rules.evaluate("shell", r#"{"command": "cargo test"}"#, cwd).is_some());
+        assert!(rules.evaluate("shell", r#"{"command": "cargo test; curl x | sh"}"#, cwd).is_none());
+        assert!(rules.evaluate("read_file", r#"{"path": "src/lib.rs"}"#, cwd).is_some());
+        assert!(rules.evaluate("write_file", r#"{"path": "src/lib.rs"}"#, cwd).is_none());
+    }
+}
@@ -0,0 +1,257 @@
+//! `mylm gen tests <file>` - generate unit tests for a source file
+//!
+//! Outlines the file with the code_outline tool, asks the agent to write
+//! tests in the project's conventional place (an inline `mod tests` for
+//! Rust, `<name>_test.go` beside Go files, `tests/test_<name>.py` or a
+//! `.test.ts` file), then runs them with the run_tests tool. While they do
+//! not compile, the compiler errors go back to the agent, up to
+//! `--max-attempts` times. The exit code tells scripts how it went (see
+//! `GenStatus`).
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use console::style;
+
+use mylm_core::agent::factory::AgentSessionFactory;
+use mylm_core::agent::runtime::capabilities::PolicyApprovalCapability;
+use mylm_core::agent::runtime::core::{RuntimeContext, ToolCapability};
+use mylm_core::agent::runtime::Session;
+use mylm_core::agent::tools::run_tests::Runner;
+use mylm_core::agent::tools::{CodeOutlineTool, RunTestsTool, TestOutcome};
+use mylm_core::agent::types::events::ToolResult;
+use mylm_core::agent::types::intents::ToolCall;
+use mylm_core::agent::UserInput;
+use mylm_core::config::Config;
+
+use crate::task;
+
+/// Tools the agent gets; all are approved up front
+const TOOLS: &[&str] = &[
+    "read_file", "write_file", "apply_patch", "list_files", "code_outline", "code_search", "run_tests",
+];
+/// Time the agent gets per attempt, and a test run gets
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+const TEST_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+pub struct GenTestsOptions {
+    pub file: PathBuf,
+    pub max_attempts: usize,
+    pub profile: Option<String>,
+}
+
+/// How generation ended; each maps to a process exit code
+#[derive(Debug, Clone, PartialEq)]
+pub enum GenStatus {
+    /// The tests compile and pass
+    Passing,
+    /// The tests compile but some fail (possibly real bugs)
+    Failing,
+    /// The tests never compiled, or the agent stopped
+    Unfinished(String),
+}
+
+impl GenStatus {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            GenStatus::Passing => 0,
+            GenStatus::Failing => 1,
+            GenStatus::Unfinished(_) => 2,
+        }
+    }
+}
+
+/// Where the tests for a file go
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestLocation {
+    pub path: PathBuf,
+    /// Tests go in the source file itself (`#[cfg(test)] mod tests`)
+    pub inline: bool,
+    /// run_tests target: a test file relative to the root, or a name filter
+    pub target: Option<String>,
+}
+
+/// `src/parser/lexer.rs` -> `parser::lexer::tests`
+fn rust_test_module(root: &Path, file: &Path) -> String {
+    let relative = file.strip_prefix(root.join("src")).unwrap_or(file);
+    let mut parts: Vec<String> = relative
+        .with_extension("")
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    if matches!(parts.last().map(String::as_str), Some("mod" | "lib" | "main")) {
+        parts.pop();
+    }
+    parts.push("tests".to_string());
+    parts.join("::")
+}
+
+/// The project's conventional place for tests of `file`
+pub fn test_location(runner: Runner, root: &Path, file: &Path) -> TestLocation {
+    let dir = file.parent().unwrap_or(root);
+    let stem = file.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+    let ext = file.extension().unwrap_or_default().to_string_lossy().into_owned();
+    let beside = |name: String| dir.join(name);
+    let path = match runner {
+        Runner::Cargo => {
+            return TestLocation {
+                path: file.to_path_buf(),
+                inline: true,
+                target: Some(rust_test_module(root, file)),
+            }
+        }
+        Runner::Go => beside(format!("{}_test.go", stem)),
+        Runner::Pytest if root.join("tests").is_dir() => root.join("tests").join(format!("test_{}.py", stem)),
+        Runner::Pytest => beside(format!("test_{}.py", stem)),
+        Runner::Npm if dir.join("__tests__").is_dir() => dir.join("__tests__").join(format!("{}.test.{}", stem, ext)),
+        Runner::Npm => beside(format!("{}.test.{}", stem, ext)),
+    };
+    let target = path.strip_prefix(root).ok().map(|p| p.display().to_string());
+    TestLocation { path, inline: false, target }
+}
+
+fn instructions(file: &Path, outline: &str, location: &TestLocation) -> String {
+    let place = if location.inline {
+        format!("a `#[cfg(test)] mod tests` at the end of {} (add to it if there is one)", file.display())
+    } else if location.path.exists() {
+        format!("{}, which exists: add to it and keep its tests", location.path.display())
+    } else {
+        format!("a new file {}", location.path.display())
+    };
+    let run = serde_json::json!({ "path": file.display().to_string(), "target": location.target });
+    format!(
+        "Write unit tests for {file}.\n\nOutline of {file}:\n{outline}\n\n\
+         Put the tests in {place}. Read the code you test first, and follow the style of the \
+         project's existing tests. Cover the public behaviour, including edge cases and errors. \
+         Do not change the code under test{inline_note}.\n\n\
+         When the tests are written, run them with the run_tests tool ({run}) and fix them until \
+         they compile. A test that fails because of a real bug may stay; name the bug in your answer.\n\
+         Answer with a short summary of the tests you wrote.",
+        file = file.display(),
+        outline = outline,
+        place = place,
+        inline_note = if location.inline { " outside the tests module" } else { "" },
+        run = run,
+    )
+}
+
+/// Generate tests for `options.file`, then run them until they compile
+pub async fn run(config: &Config, options: GenTestsOptions) -> Result<GenStatus> {
+    let file = options
+        .file
+        .canonicalize()
+        .with_context(|| format!("Cannot find {}", options.file.display()))?;
+    let (runner, root) = Runner::detect(&file)
+        .with_context(|| format!("No project (Cargo.toml, go.mod, package.json, pyproject.toml) around {}", file.display()))?;
+    let location = test_location(runner, &root, &file);
+
+    let ctx = RuntimeContext::new();
+    let outline_call = ToolCall::new("code_outline", serde_json::json!({ "path": file.display().to_string() }));
+    let outline = match CodeOutlineTool::new().execute(&ctx, outline_call).await {
+        Ok(ToolResult::Success { output, .. }) => output,
+        Ok(ToolResult::Error { message, .. }) => anyhow::bail!("Cannot outline {}: {}", file.display(), message),
+        Ok(_) => anyhow::bail!("Cannot outline {}", file.display()),
+        Err(e) => anyhow::bail!("Cannot outline {}: {}", file.display(), e),
+    };
+
+    let approval = PolicyApprovalCapability::from_config(config, None);
+    approval.auto_approve_flag().store(true, Ordering::SeqCst);
+    let factory = AgentSessionFactory::new(config.clone())
+        .with_approval(Arc::new(approval))
+        .with_allowed_tools(TOOLS.iter().map(|t| t.to_string()).collect());
+    let profile = options.profile.clone().unwrap_or_else(|| config.active_profile.clone());
+    let mut session = factory
+        .create_session(&profile, None)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to create agent session: {}", e))?;
+    let input_tx = session.input_sender();
+    let mut output_rx = session.subscribe_output();
+    let session_task = tokio::spawn(async move { session.run().await });
+
+    eprintln!(
+        "{} {} -> {}",
+        style("Generating tests:").bold(),
+        file.display(),
+        location.path.display()
+    );
+    let tests = RunTestsTool::new();
+    let mut message = instructions(&file, &outline, &location);
+    let mut status = GenStatus::Unfinished("no attempts were made".to_string());
+    for attempt in 1..=options.max_attempts.max(1) {
+        input_tx
+            .send(UserInput::Message(message))
+            .await
+            .context("Agent session stopped")?;
+        match tokio::time::timeout(ATTEMPT_TIMEOUT, task::follow(&mut output_rx)).await {
+            Ok(Ok(answer)) => println!("{}", answer),
+            Ok(Err(reason)) => {
+                status = GenStatus::Unfinished(reason);
+                break;
+            }
+            Err(_) => {
+                status = GenStatus::Unfinished(format!("timed out after {}s", ATTEMPT_TIMEOUT.as_secs()));
+                break;
+            }
+        }
+
+        let summary = tests
+            .run(&file, location.target.as_deref(), TEST_TIMEOUT)
+            .await
+            .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        eprintln!(
+            "{} attempt {}: `{}` {:?} ({} passed, {} failed)",
+            style("Tests:").bold(),
+            attempt,
+            summary.command,
+            summary.outcome,
+            summary.passed,
+            summary.failed
+        );
+        match summary.outcome {
+            TestOutcome::Passed => {
+                status = GenStatus::Passing;
+                break;
+            }
+            TestOutcome::Failed => {
+                status = GenStatus::Failing;
+                break;
+            }
+            TestOutcome::CompileError => {
+                status = GenStatus::Unfinished(format!("tests did not compile after {} attempt(s)", attempt));
+                message = format!(
+                    "The tests do not compile (`{}`):\n\n{}\n\nFix the tests so they compile.",
+                    summary.command, summary.excerpt
+                );
+            }
+        }
+    }
+    session_task.abort();
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conventional_locations() {
+        let root = Path::new("/work/demo");
+        let rust = test_location(Runner::Cargo, root, &root.join("src/parser/mod.rs"));
+        assert!(rust.inline);
+        assert_eq!(rust.target.as_deref(), Some("parser::tests"));
+        assert_eq!(
+            test_location(Runner::Cargo, root, &root.join("src/lib.rs")).target.as_deref(),
+            Some("tests")
+        );
+
+        let go = test_location(Runner::Go, root, &root.join("pkg/store/cache.go"));
+        assert_eq!(go.path, root.join("pkg/store/cache_test.go"));
+        assert_eq!(go.target.as_deref(), Some("pkg/store/cache_test.go"));
+
+        let ts = test_location(Runner::Npm, root, &root.join("src/date.ts"));
+        assert_eq!((ts.path, ts.inline), (root.join("src/date.test.ts"), false));
+    }
+}
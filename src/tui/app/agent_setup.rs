@@ -21,7 +21,8 @@ pub fn create_session_factory(
     approval: Option<Arc<dyn ApprovalCapability>>,
     commonbox: Option<Arc<Commonbox>>,
 ) -> AgentSessionFactory {
-    let mut factory = AgentSessionFactory::new(config.clone());
+    // The agent may annotate the terminal pane shown next to the chat
+    let mut factory = AgentSessionFactory::new(config.clone()).with_terminal_pane();
     
     if let Some(terminal) = terminal {
         factory = factory.with_terminal(terminal);
@@ -7,13 +7,15 @@
 //! - `app/clipboard.rs` - Clipboard operations
 //! - `app/session.rs` - Session persistence
 
+use crate::tui::app::controls::paste::render_blocks;
+use crate::tui::app::controls::rewind::Submission;
 use crate::tui::app::state::AppStateContainer;
 use crate::tui::app::types::{AppState, TuiEvent, TimestampedChatMessage};
 
 use mylm_core::agent::UserInput;
 use mylm_core::environment::ContextBuilder;
 use mylm_core::conversation::ContextCompression;
-use mylm_core::provider::chat::ChatMessage;
+use mylm_core::provider::chat::{image_marker, ChatMessage};
 use tokio::sync::mpsc::UnboundedSender;
 
 pub use crate::tui::app::state::AppStateContainer as App;
@@ -23,7 +25,7 @@ impl AppStateContainer {
     pub async fn submit_message(&mut self, _event_tx: UnboundedSender<TuiEvent>) {
         mylm_core::info_log!("[APP] submit_message called");
         
-        if self.chat_input.is_empty() {
+        if self.chat_input.is_empty() && self.pasted_blocks.is_empty() {
             mylm_core::debug_log!("[APP] submit_message: input is empty, returning");
             return;
         }
@@ -102,10 +104,11 @@ impl AppStateContainer {
         // Formula: available = max_context - (system_prompt + chat_history + input)
         let max_context = self.config.active_profile().context_window;
         let system_prompt_estimate = 1000; // ~800 for tools/instructions + margin
+        let tokenizer = self.context_manager.config().tokenizer;
         let chat_history_tokens: usize = self.chat_history.iter()
-            .map(|m| m.message.content.len() / 4)
+            .map(|m| tokenizer.count(&m.message.content))
             .sum();
-        let input_tokens = input.len() / 4;
+        let input_tokens = tokenizer.count(&input);
         let used_tokens = system_prompt_estimate + chat_history_tokens + input_tokens;
         let available_tokens = max_context.saturating_sub(used_tokens);
         
@@ -115,6 +118,24 @@ impl AppStateContainer {
         let builder = ContextBuilder::with_budget(available_tokens);
         let mut final_message = input.clone();
 
+        self.last_submission = Some(Submission {
+            input: input.clone(),
+            pasted_blocks: self.pasted_blocks.clone(),
+            images: self.pending_images.clone(),
+        });
+
+        // Pasted blocks travel as delimited sections, never as input lines
+        let pasted = std::mem::take(&mut self.pasted_blocks);
+        self.pasted_blocks_expanded = false;
+        final_message.push_str(&render_blocks(&pasted));
+
+        // Attached images travel as data; the marker lines only label them in the chat
+        let attached = std::mem::take(&mut self.pending_images);
+        for image in &attached {
+            final_message.push('\n');
+            final_message.push_str(&image_marker(&image.path));
+        }
+
         // Only include terminal snapshot if it has changed from the last one
         let should_include_snapshot = self.last_terminal_snapshot.as_ref()
             .map(|last| last != &terminal_content)
@@ -130,8 +151,26 @@ impl AppStateContainer {
             }
         }
 
+        // Git status and system info: in full on the first turn, afterwards
+        // only what changed. Kept in history so later deltas have a base.
+        let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+        for pack in self.context_tracker.changes(&cwd).await {
+            final_message.push_str(&pack.render());
+        }
+
+        // Plan edits made with /plan since the agent last saw its plan
+        if let Some(edited) = self.plan.as_ref().and_then(|plan| plan.take_user_edits()) {
+            if edited.steps.is_empty() {
+                final_message.push_str("\n\n## Plan\nThe user cleared your plan.");
+            } else {
+                final_message.push_str(&format!("\n\n## Plan (edited by the user)\n{}", edited.checklist()));
+            }
+        }
+
         mylm_core::debug_log!("[APP] Final message length: {}", final_message.len());
-        self.chat_history.push(TimestampedChatMessage::user(&final_message));
+        let mut user_message = TimestampedChatMessage::user(&final_message);
+        user_message.message.images = attached.into_iter().map(|a| a.image).collect();
+        self.chat_history.push(user_message);
         mylm_core::info_log!(
             "[APP] Added message to chat history, now have {} messages", 
             self.chat_history.len()
@@ -213,6 +252,9 @@ impl AppStateContainer {
             let last_message = self.chat_history.last()
                 .map(|m| m.message.content.clone())
                 .unwrap_or_default();
+            let images = self.chat_history.last()
+                .map(|m| m.message.images.clone())
+                .unwrap_or_default();
             
             let msg_preview = &last_message[..last_message.len().min(50)];
             mylm_core::info_log!(
@@ -221,7 +263,21 @@ impl AppStateContainer {
                 msg_preview
             );
             
-            match input_tx.send(UserInput::Message(last_message)).await {
+            // Edits made while answering this message form one queued patch
+            // and one undo step
+            if let Some(queue) = &self.patch_queue {
+                queue.begin_patch(&last_message);
+            }
+            if let Some(journal) = &self.undo_journal {
+                journal.begin_turn(&last_message);
+            }
+            
+            let input = if images.is_empty() {
+                UserInput::Message(last_message)
+            } else {
+                UserInput::MessageWithImages { content: last_message, images }
+            };
+            match input_tx.send(input).await {
                 Ok(_) => {
                     mylm_core::info_log!("[APP] Message sent successfully to session");
                 }
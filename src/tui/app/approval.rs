@@ -1,12 +1,13 @@
-//! TUI Approval Capability
+//! TUI Approval Prompter
 //!
 //! Bridges the core approval system to the TUI.
-//! Uses oneshot channels for request/response pattern.
+//! Uses oneshot channels for request/response pattern. Policy, timeouts and
+//! auditing are handled by `PolicyApprovalCapability`, which wraps this.
 
 use async_trait::async_trait;
-use mylm_core::agent::runtime::core::{
-    ApprovalCapability, Capability, RuntimeContext, ApprovalError,
-};
+use mylm_core::agent::runtime::capabilities::{ApprovalPrompter, PolicyApprovalCapability};
+use mylm_core::agent::runtime::governance::SessionRules;
+use mylm_core::agent::runtime::core::{RuntimeContext, ApprovalError};
 use mylm_core::agent::types::{
     intents::ApprovalRequest,
     events::ApprovalOutcome,
@@ -31,24 +32,22 @@ struct CurrentPending {
     _responded: bool,
 }
 
-/// TUI-based approval capability
+/// TUI-based approval prompter
 ///
-/// This capability sends approval requests to the TUI via a channel
-/// and waits for the UI to respond via the `ApprovalHandle`.
+/// This prompter sends approval requests to the TUI via a channel
+/// and waits for the UI to respond through the `PendingApproval` sender.
 #[derive(Clone)]
 pub struct TuiApprovalCapability {
     /// Sender for pending approvals to the UI
     pending_tx: mpsc::Sender<PendingApproval>,
     /// Current pending approval request (without sender - stored separately)
     current: Arc<Mutex<Option<CurrentPending>>>,
-    /// Auto-approve flag - when true, all approvals are auto-granted
-    auto_approve: Arc<std::sync::atomic::AtomicBool>,
 }
 
 impl TuiApprovalCapability {
-    /// Create a new TUI approval capability
+    /// Create a new TUI approval prompter
     ///
-    /// Returns the capability and a receiver for pending approvals
+    /// Returns the prompter and a receiver for pending approvals
     pub fn new() -> (Self, mpsc::Receiver<PendingApproval>) {
         let (pending_tx, pending_rx) = mpsc::channel(10);
         let current = Arc::new(Mutex::new(None));
@@ -56,35 +55,50 @@ impl TuiApprovalCapability {
         (Self {
             pending_tx,
             current,
-            auto_approve: Arc::new(std::sync::atomic::AtomicBool::new(false)),
         }, pending_rx)
     }
     
+    /// Build the session's approval capability around a TUI prompter
+    ///
+    /// `auto_approve` is the App's toggle and `rules` the rules added from
+    /// approval prompts, so both take effect for the next request.
+    pub fn policy(
+        config: &mylm_core::config::Config,
+        auto_approve: Arc<std::sync::atomic::AtomicBool>,
+        rules: SessionRules,
+    ) -> (PolicyApprovalCapability, mpsc::Receiver<PendingApproval>) {
+        let (prompter, approval_rx) = Self::new();
+        let capability = PolicyApprovalCapability::from_config(config, Some(Arc::new(prompter)))
+            .with_auto_approve(auto_approve)
+            .with_session_rules(rules);
+        (capability, approval_rx)
+    }
+    
+    /// Add `rule` to `features.approval.rules` of the global config file
+    ///
+    /// The file is read afresh, so a project overlay in the running config
+    /// is not written into it.
+    pub fn persist_rule(rule: &str) -> anyhow::Result<()> {
+        mylm_core::config::Config::update_default(|config| {
+            if !config.features.approval.rules.iter().any(|r| r == rule) {
+                config.features.approval.rules.push(rule.to_string());
+            }
+        })
+    }
+
     /// Clear the current pending approval
     async fn clear_pending(&self) {
         *self.current.lock().await = None;
     }
 }
 
-impl Capability for TuiApprovalCapability {
-    fn name(&self) -> &'static str {
-        "tui-approval"
-    }
-}
-
 #[async_trait]
-impl ApprovalCapability for TuiApprovalCapability {
-    async fn request(
+impl ApprovalPrompter for TuiApprovalCapability {
+    async fn prompt(
         &self,
         _ctx: &RuntimeContext,
-        req: ApprovalRequest,
+        req: &ApprovalRequest,
     ) -> Result<ApprovalOutcome, ApprovalError> {
-        // Check if auto-approve is enabled
-        if self.auto_approve.load(std::sync::atomic::Ordering::SeqCst) {
-            mylm_core::info_log!("[TUI_APPROVAL] Auto-approve enabled, granting approval for '{}'", req.tool);
-            return Ok(ApprovalOutcome::Granted);
-        }
-        
         // Create oneshot channel for response
         let (tx, rx) = oneshot::channel();
         
@@ -0,0 +1,109 @@
+//! Bookmarked assistant messages and the notes panel
+//!
+//! F8 bookmarks the assistant message at the bottom of the chat view (or
+//! removes its bookmark), F9 opens the notes panel listing every bookmark,
+//! and Alt+1..9 scrolls the chat back to one. Bookmarks are stored in the
+//! session metadata so they come back with a resumed session.
+use crate::tui::app::state::AppStateContainer;
+use mylm_core::agent::SessionBookmark;
+use mylm_core::provider::chat::MessageRole;
+
+/// Longest excerpt kept for a bookmark, in characters
+const EXCERPT_CHARS: usize = 160;
+
+/// Start of a message as shown in the chat, on a single line
+pub fn excerpt(content: &str) -> String {
+    // Context packs appended to the message are not part of what was said
+    let content = content
+        .find("\n\n## Terminal Snapshot")
+        .map_or(content, |idx| &content[..idx]);
+    // Short-key responses show their final answer
+    let text = serde_json::from_str::<serde_json::Value>(content.trim())
+        .ok()
+        .and_then(|val| val.get("f").and_then(|f| f.as_str()).map(str::to_string))
+        .unwrap_or_else(|| content.to_string());
+
+    let flat = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if flat.chars().count() <= EXCERPT_CHARS {
+        flat
+    } else {
+        let cut: String = flat.chars().take(EXCERPT_CHARS - 1).collect();
+        format!("{}…", cut.trim_end())
+    }
+}
+
+impl AppStateContainer {
+    /// Assistant message at the bottom of the chat view
+    fn message_in_view(&self) -> Option<usize> {
+        self.chat_message_lines
+            .iter()
+            .rev()
+            .filter(|(_, line)| *line < self.chat_visible_end_idx)
+            .map(|(idx, _)| *idx)
+            .find(|idx| {
+                self.chat_history
+                    .get(*idx)
+                    .is_some_and(|m| m.message.role == MessageRole::Assistant)
+            })
+    }
+
+    /// Bookmark the assistant message in view, or drop its bookmark
+    pub async fn toggle_bookmark(&mut self) {
+        let Some(message) = self.message_in_view() else {
+            self.status_message = Some("⚠️ No AI response to bookmark".to_string());
+            return;
+        };
+        if let Some(pos) = self.bookmarks.iter().position(|b| b.message == message) {
+            self.bookmarks.remove(pos);
+            self.status_message = Some("🔖 Bookmark removed".to_string());
+        } else {
+            let excerpt = excerpt(&self.chat_history[message].message.content);
+            self.bookmarks.push(SessionBookmark { message, excerpt });
+            self.bookmarks.sort_by_key(|b| b.message);
+            let number = self.bookmarks.iter().position(|b| b.message == message).unwrap_or(0) + 1;
+            self.status_message = Some(format!("🔖 Bookmarked as note {} · F9 to show notes", number));
+        }
+        if !self.incognito {
+            let session = self.build_current_session().await;
+            self.session_manager.set_current_session(session);
+        }
+    }
+
+    pub fn toggle_notes_panel(&mut self) {
+        self.show_notes_panel = !self.show_notes_panel;
+    }
+
+    /// Scroll the chat to the `number`th bookmark (1-based)
+    pub fn jump_to_bookmark(&mut self, number: usize) {
+        match number.checked_sub(1).and_then(|i| self.bookmarks.get(i)) {
+            Some(bookmark) => {
+                self.chat_jump_to = Some(bookmark.message);
+                self.show_notes_panel = true;
+            }
+            None => {
+                self.status_message = Some(format!("⚠️ No note {}", number));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_excerpt_is_the_visible_text_on_one_line() {
+        assert_eq!(
+            excerpt("The leak is in\n  `Pool::drop`.\n\n## Terminal Snapshot\n$ ls"),
+            "The leak is in `Pool::drop`."
+        );
+        assert_eq!(
+            excerpt(r#"{"t": "done", "f": "Root cause: stale lock file"}"#),
+            "Root cause: stale lock file"
+        );
+
+        let long = excerpt(&"word ".repeat(100));
+        assert_eq!(long.chars().count(), EXCERPT_CHARS);
+        assert!(long.ends_with("word…"));
+    }
+}
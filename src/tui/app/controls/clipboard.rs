@@ -35,6 +35,29 @@ impl AppStateContainer {
         }
     }
 
+    /// Copy code block `number` (1-based) of the last AI response
+    pub fn copy_code_block_to_clipboard(&mut self, number: usize) {
+        use crate::tui::app::ui::highlight::code_blocks;
+
+        let blocks = self
+            .chat_history
+            .iter()
+            .rev()
+            .find(|m| m.message.role == MessageRole::Assistant)
+            .map(|m| code_blocks(&m.message.content))
+            .unwrap_or_default();
+        match number.checked_sub(1).and_then(|i| blocks.get(i)) {
+            Some(block) => self.copy_text_to_clipboard(block.code.clone()),
+            None if blocks.is_empty() => {
+                self.status_message = Some("⚠️ The last AI response has no code blocks".to_string());
+            }
+            None => {
+                self.status_message =
+                    Some(format!("⚠️ No code block {} (the last AI response has {})", number, blocks.len()));
+            }
+        }
+    }
+
     pub fn copy_terminal_buffer_to_clipboard(&mut self) {
         let history_height = 5000;
         let width = self.terminal_size.1;
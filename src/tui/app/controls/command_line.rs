@@ -0,0 +1,274 @@
+//! Command line mode - the agent types a command into the terminal
+//!
+//! With command mode on (F7), a message is not sent to the agent. A single
+//! model call turns it into one shell command, streamed token by token onto
+//! the terminal's input line. Nothing is executed: the filter never lets a
+//! newline or control character through, so the command sits at the prompt
+//! to be edited in place and run with Enter.
+
+use std::sync::Arc;
+
+use futures::StreamExt;
+use mylm_core::provider::chat::{ChatMessage, ChatRequest, StreamEvent};
+use mylm_core::provider::LlmClient;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::tui::app::state::{AppStateContainer, Focus};
+use crate::tui::app::types::TimestampedChatMessage;
+
+/// Terminal lines sent along so the model sees the cwd and recent errors
+const SCREEN_LINES: usize = 30;
+/// Ctrl+U: clear what is already typed at the prompt
+const CLEAR_LINE: &[u8] = b"\x15";
+/// Shown instead of typing into vim, less and other full-screen programs
+const ALTERNATE_SCREEN: &str = "⚠️  A full-screen program is running in the terminal; leave it to type a command";
+
+/// What the generation task reports
+#[derive(Debug)]
+pub enum CommandChunk {
+    Text(String),
+    Done,
+    Failed(String),
+}
+
+/// A command being typed into the terminal
+pub struct CommandStream {
+    rx: mpsc::UnboundedReceiver<CommandChunk>,
+    task: JoinHandle<()>,
+    filter: CommandFilter,
+    typed: String,
+}
+
+/// Keeps a streamed reply down to one typeable command line
+///
+/// Drops a leading code fence, backticks or `$ ` prompt, holds back trailing
+/// spaces and backticks until more text follows, stops at the first newline
+/// and never passes control characters.
+#[derive(Debug, Default)]
+pub struct CommandFilter {
+    head: String,
+    started: bool,
+    pending: String,
+    done: bool,
+}
+
+impl CommandFilter {
+    /// Text from `chunk` that is safe to type
+    pub fn push(&mut self, chunk: &str) -> String {
+        let mut out = String::new();
+        for c in chunk.chars() {
+            if self.done {
+                break;
+            }
+            if self.started {
+                self.emit(c, &mut out);
+                continue;
+            }
+            self.head.push(c);
+            let trimmed = self.head.trim_start();
+            if trimmed.is_empty() || trimmed == "$" {
+                continue;
+            }
+            if trimmed.starts_with("```") || "```".starts_with(trimmed) {
+                // A fence header like "```bash" ends with its line
+                if c == '\n' {
+                    self.head.clear();
+                }
+                continue;
+            }
+            self.started = true;
+            let head = std::mem::take(&mut self.head);
+            let head = head.trim_start().trim_start_matches('`');
+            for c in head.strip_prefix("$ ").unwrap_or(head).chars() {
+                self.emit(c, &mut out);
+            }
+        }
+        out
+    }
+
+    fn emit(&mut self, c: char, out: &mut String) {
+        match c {
+            '\n' => self.done = true,
+            '`' | ' ' => self.pending.push(c),
+            '\t' => self.pending.push(' '),
+            c if c.is_control() => {}
+            c => {
+                out.push_str(&std::mem::take(&mut self.pending));
+                out.push(c);
+            }
+        }
+    }
+}
+
+/// One command for `prompt`, given the shell and what the terminal shows
+pub fn command_request(model: &str, prompt: &str, screen: &str) -> ChatRequest {
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "sh".to_string());
+    let cwd = std::env::current_dir().map(|d| d.display().to_string()).unwrap_or_default();
+    let system = format!(
+        "You turn requests into shell commands for {} on {}. Reply with exactly one \
+        command line that does what the user asks, chaining steps with && or pipes if \
+        needed. No explanation, no markdown, no leading $.",
+        shell,
+        std::env::consts::OS
+    );
+    let user = format!(
+        "Working directory: {}\n\nTerminal (most recent last):\n{}\n\nRequest: {}",
+        cwd, screen, prompt
+    );
+    ChatRequest::new(model.to_string(), vec![ChatMessage::system(system), ChatMessage::user(user)])
+        .with_temperature(0.2)
+        .with_max_tokens(256)
+}
+
+impl AppStateContainer {
+    pub fn toggle_command_mode(&mut self) {
+        self.command_mode = !self.command_mode;
+        self.status_message = Some(if self.command_mode {
+            "⌨️  Command mode: messages become commands typed into the terminal (F7 to leave)".to_string()
+        } else {
+            "💬 Chat mode".to_string()
+        });
+    }
+
+    /// Start typing a command for the chat input into the terminal
+    pub fn start_command_generation(&mut self) {
+        let prompt = std::mem::take(&mut self.chat_input).trim().to_string();
+        self.cursor_position = 0;
+        if prompt.is_empty() {
+            return;
+        }
+        let Some(client) = self.command_client.clone() else {
+            self.chat_history.push(TimestampedChatMessage::assistant(
+                "❌ Command mode needs a configured model".to_string(),
+            ));
+            return;
+        };
+        self.cancel_command_generation();
+        if self.terminal_parser.screen().alternate_screen() {
+            self.chat_input = prompt;
+            self.cursor_position = self.chat_input.chars().count();
+            self.status_message = Some(ALTERNATE_SCREEN.to_string());
+            return;
+        }
+
+        let screen = self.terminal_parser.screen().contents();
+        let lines: Vec<&str> = screen.lines().filter(|l| !l.trim().is_empty()).collect();
+        let screen = lines[lines.len().saturating_sub(SCREEN_LINES)..].join("\n");
+        let request = command_request(client.model(), &prompt, &screen);
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let task = tokio::spawn(stream_command(client, request, tx));
+        self.handle_terminal_input(CLEAR_LINE);
+        self.command_stream = Some(CommandStream {
+            rx,
+            task,
+            filter: CommandFilter::default(),
+            typed: String::new(),
+        });
+        self.status_message = Some(format!("⌨️  Writing a command for: {}", prompt));
+    }
+
+    /// Type what arrived since the last tick; hands the terminal to the user
+    /// once the command is complete
+    pub fn poll_command_stream(&mut self) {
+        if self.command_stream.is_none() {
+            return;
+        }
+        // A full-screen program took over: keystrokes would drive it instead
+        if self.terminal_parser.screen().alternate_screen() {
+            self.cancel_command_generation();
+            self.status_message = Some(ALTERNATE_SCREEN.to_string());
+            return;
+        }
+        let Some(stream) = self.command_stream.as_mut() else {
+            return;
+        };
+        let mut finished = None;
+        let mut text = String::new();
+        while let Ok(chunk) = stream.rx.try_recv() {
+            match chunk {
+                CommandChunk::Text(chunk) => text.push_str(&stream.filter.push(&chunk)),
+                CommandChunk::Done => finished = Some(None),
+                CommandChunk::Failed(e) => finished = Some(Some(e)),
+            }
+        }
+        stream.typed.push_str(&text);
+        let typed = stream.typed.clone();
+        if !text.is_empty() {
+            self.handle_terminal_input(text.as_bytes());
+        }
+        match finished {
+            None => {}
+            Some(None) => {
+                self.command_stream = None;
+                self.focus = Focus::Terminal;
+                self.status_message = Some(if typed.is_empty() {
+                    "⚠️  The model returned no command".to_string()
+                } else {
+                    "💡 Command ready in terminal: edit it or press Enter to run".to_string()
+                });
+            }
+            Some(Some(e)) => {
+                self.command_stream = None;
+                self.chat_history.push(TimestampedChatMessage::assistant(format!(
+                    "❌ Command generation failed: {}",
+                    e
+                )));
+            }
+        }
+    }
+
+    /// Stop a command being typed; true if one was
+    pub fn cancel_command_generation(&mut self) -> bool {
+        match self.command_stream.take() {
+            Some(stream) => {
+                stream.task.abort();
+                self.status_message = Some("Command generation cancelled".to_string());
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+async fn stream_command(client: Arc<LlmClient>, request: ChatRequest, tx: mpsc::UnboundedSender<CommandChunk>) {
+    let mut stream = client.chat_stream(&request);
+    while let Some(event) = stream.next().await {
+        let chunk = match event {
+            Ok(StreamEvent::Content(text)) => CommandChunk::Text(text),
+            Ok(StreamEvent::Done) => break,
+            Ok(StreamEvent::Error(e)) => CommandChunk::Failed(e),
+            Ok(_) => continue,
+            Err(e) => CommandChunk::Failed(e.to_string()),
+        };
+        let failed = matches!(chunk, CommandChunk::Failed(_));
+        if tx.send(chunk).is_err() || failed {
+            return;
+        }
+    }
+    let _ = tx.send(CommandChunk::Done);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filtered(chunks: &[&str]) -> String {
+        let mut filter = CommandFilter::default();
+        chunks.iter().map(|chunk| filter.push(chunk)).collect()
+    }
+
+    #[test]
+    fn test_filter_keeps_one_typeable_line() {
+        assert_eq!(filtered(&["find . -name", " '*.rs' | wc -l"]), "find . -name '*.rs' | wc -l");
+        assert_eq!(filtered(&["``", "`bash\n", "ls -la", "\n```\n"]), "ls -la");
+        assert_eq!(filtered(&["`git status", "`"]), "git status");
+        assert_eq!(filtered(&["$ ", "du -sh *  "]), "du -sh *");
+        // Nothing that could run or move the cursor gets through
+        assert_eq!(filtered(&["rm -rf build\nmake\r\n"]), "rm -rf build");
+        assert_eq!(filtered(&["echo \x1b[31mhi"]), "echo [31mhi");
+        // Backticks inside the command survive
+        assert_eq!(filtered(&["echo `date` done"]), "echo `date` done");
+    }
+}
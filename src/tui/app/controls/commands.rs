@@ -1,38 +1,273 @@
 //! Slash command handling for the terminal UI
+use super::slash::{self, Invocation, SlashCommand, Usage};
 use crate::tui::app::state::AppStateContainer;
 use crate::tui::app::types::{TuiEvent, TimestampedChatMessage};
-use mylm_core::provider::chat::ChatMessage as Message;
+use mylm_core::agent::UserInput;
+use mylm_core::config::Config;
+use mylm_core::memory::MemoryRating;
 
 use tokio::sync::mpsc::UnboundedSender;
 
+const fn usage(args: &'static str, help: &'static str) -> Usage {
+    Usage { args, help }
+}
+
+/// Every slash command, in `/help` order
+pub const COMMANDS: &[SlashCommand] = &[
+    SlashCommand {
+        name: "profile",
+        usages: &[usage("<name>", "Switch profile")],
+        run: |app, call| app.handle_profile_command(&call.parts, call.event_tx.clone()),
+    },
+    SlashCommand {
+        name: "model",
+        usages: &[
+            usage("", "List the provider's models"),
+            usage("<name>|clear", "Switch models for the next turns, or clear to use the default"),
+        ],
+        run: |app, call| app.handle_model_command(&call.parts),
+    },
+    SlashCommand {
+        name: "config",
+        usages: &[usage("<key> <value>", "Update active profile")],
+        run: |app, call| app.handle_config_command(&call.parts, call.event_tx.clone()),
+    },
+    SlashCommand {
+        name: "exec",
+        usages: &[usage("<command>", "Execute shell command (not yet implemented)")],
+        run: |app, call| app.handle_exec_command(&call.parts, call.event_tx.clone()),
+    },
+    SlashCommand {
+        name: "jobs",
+        usages: &[
+            usage("", "List active jobs with metrics"),
+            usage("cancel <id>", "Cancel a specific job"),
+            usage("cancel-all", "Cancel all jobs"),
+            usage("list", "List all jobs"),
+        ],
+        run: |app, call| app.handle_jobs_command(&call.parts),
+    },
+    SlashCommand {
+        name: "pacore",
+        usages: &[usage("[on|off|rounds <n,n>|status|save]", "Show or change PaCoRe reasoning rounds")],
+        run: |app, call| app.handle_pacore_command(&call.parts),
+    },
+    SlashCommand {
+        name: "logs",
+        usages: &[usage("[<n>]", "Show the last n log lines (default 20)")],
+        run: |app, call| app.handle_logs_command(&call.parts),
+    },
+    SlashCommand {
+        name: "prompt",
+        usages: &[usage("", "Dump system prompt to mylm/logs/")],
+        run: |app, call| app.handle_prompt_command(call.event_tx.clone()),
+    },
+    SlashCommand {
+        name: "context",
+        usages: &[usage("", "Show the memories injected into the last prompt")],
+        run: |app, _| app.handle_context_command(),
+    },
+    SlashCommand {
+        name: "rate",
+        usages: &[usage("<n> helpful|irrelevant", "Rate an injected memory")],
+        run: |app, call| app.handle_rate_command(&call.parts),
+    },
+    SlashCommand {
+        name: "teach",
+        usages: &[usage("[<text>]", "Write a curated memory in your editor")],
+        run: |app, call| app.pending_teach = Some(call.parts[1..].join(" ")),
+    },
+    SlashCommand {
+        name: "attach",
+        usages: &[
+            usage("<image>", "Send a PNG, JPEG, GIF or WebP image with the next message"),
+            usage("[list|clear]", "Show or drop the attached images"),
+        ],
+        run: |app, call| app.handle_attach_command(call.input),
+    },
+    SlashCommand {
+        name: "paste-image",
+        usages: &[usage(
+            "",
+            "Attach the image on the clipboard (pasting an image file path attaches it too)",
+        )],
+        run: |app, _| app.paste_clipboard_image(),
+    },
+    SlashCommand {
+        name: "pruned",
+        usages: &[usage("", "List conversation segments condensed into summaries")],
+        run: |app, _| app.handle_pruned_command(),
+    },
+    SlashCommand {
+        name: "restore",
+        usages: &[usage("<n>", "Put a condensed segment back into the conversation")],
+        run: |app, call| app.handle_restore_command(&call.parts),
+    },
+    SlashCommand {
+        name: "budget",
+        usages: &[usage("", "Show session cost and token budget")],
+        run: |app, _| app.handle_budget_command(),
+    },
+    SlashCommand {
+        name: "queue",
+        usages: &[
+            usage("start <name>", "Queue file edits in a changeset instead of writing them"),
+            usage(
+                "[list|show <n>|move <from> <to>|drop <n>|apply [n...]|export <dir>|stop|discard]",
+                "Review, reorder, apply or drop the queued edits",
+            ),
+        ],
+        run: |app, call| app.handle_queue_command(&call.parts),
+    },
+    SlashCommand {
+        name: "undo",
+        usages: &[
+            usage("", "Restore the files changed by the agent's last turn"),
+            usage("list", "List the turns that can be undone"),
+        ],
+        run: |app, call| app.handle_undo_command(&call.parts),
+    },
+    SlashCommand {
+        name: "edit",
+        usages: &[
+            usage("", "Put your last message back in the input; Enter runs the turn again"),
+            usage("<text>", "Replace your last message with <text> and run the turn again"),
+        ],
+        run: |app, call| app.handle_edit_command(call.rest()),
+    },
+    SlashCommand {
+        name: "retry",
+        usages: &[usage("[<model>]", "Regenerate the last response, switching to <model> first if given")],
+        run: |app, call| app.handle_retry_command(&call.parts),
+    },
+    SlashCommand {
+        name: "plan",
+        usages: &[
+            usage("", "Show the agent's plan"),
+            usage(
+                "[add <text>|edit <n> <text>|done <n>|skip <n>|undo <n>|remove <n>|clear]",
+                "Edit it; the agent sees your changes",
+            ),
+        ],
+        run: |app, call| app.handle_plan_command(&call.parts),
+    },
+    SlashCommand {
+        name: "pin",
+        usages: &[
+            usage("", "Pin or unpin the message in view (Alt+P); pins are kept when context is condensed"),
+            usage("[clear|remove <n>]", "Unpin everything, or the nth pin of the pinned panel (F10)"),
+        ],
+        run: |app, call| app.handle_pin_command(&call.parts),
+    },
+    SlashCommand {
+        name: "annotations",
+        usages: &[usage(
+            "[clear]",
+            "List or remove the agent's terminal annotations (F5 shows the next)",
+        )],
+        run: |app, call| app.handle_annotations_command(&call.parts),
+    },
+    SlashCommand {
+        name: "copy-code",
+        usages: &[usage("[<n>]", "Copy code block n (default 1) of the last AI response")],
+        run: |app, call| app.handle_copy_code_command(&call.parts),
+    },
+    SlashCommand {
+        name: "verbose",
+        usages: &[usage("", "Toggle verbose mode")],
+        run: |app, _| app.handle_verbose_command(),
+    },
+    SlashCommand {
+        name: "raw",
+        usages: &[usage("", "Toggle between rendered and raw markdown in AI responses")],
+        run: |app, _| app.handle_raw_command(),
+    },
+    SlashCommand {
+        name: "usage",
+        usages: &[usage("", "Toggle tokens, cost and latency under AI responses")],
+        run: |app, _| app.handle_usage_command(),
+    },
+    SlashCommand {
+        name: "theme",
+        usages: &[usage("[<name>|list]", "Switch the colour theme, or list the themes")],
+        run: |app, call| app.handle_theme_command(&call.parts),
+    },
+    SlashCommand {
+        name: "search",
+        usages: &[usage(
+            "<term>",
+            "Highlight <term> in the chat (Ctrl+F); n/N older/newer match, Esc to close",
+        )],
+        run: |app, call| app.search_chat(call.rest()),
+    },
+    SlashCommand {
+        name: "layout",
+        usages: &[usage(
+            "[swap|zoom|reset]",
+            "Show the pane layout, swap the panes, zoom the focused pane (Alt+Z) or reset",
+        )],
+        run: |app, call| app.handle_layout_command(&call.parts),
+    },
+    SlashCommand {
+        name: "tab",
+        usages: &[usage(
+            "[new|close|next|prev|<n>]",
+            "Open, close or switch conversation tabs (Ctrl+T new, Ctrl+Tab/Alt+→ next); lists them without arguments",
+        )],
+        run: |app, call| app.handle_tab_command(&call.parts),
+    },
+    SlashCommand {
+        name: "help",
+        usages: &[usage("", "Show this help")],
+        run: |app, _| app.handle_help_command(),
+    },
+];
+
+/// Key reference appended to `/help`
+const INPUT_SHORTCUTS: &str = "Input Shortcuts:\n\
+    Ctrl+a / Home - Start of line\n\
+    Ctrl+e / End - End of line\n\
+    Ctrl+k - Kill to end\n\
+    Ctrl+u - Kill to start\n\
+    Arrows - Navigate lines/history\n\
+    Tab - Complete a /command or its argument; again to cycle the matches\n\
+    Enter (empty input) - Expand or collapse the lowest tool result in view\n\
+    Ctrl+Shift+Left/Right - Narrow/widen the chat (or drag the border between the panes)\n\
+    Alt+S - Swap the terminal and chat sides; Alt+Z - Zoom the focused pane, Esc to unzoom\n\
+    With [app.keymap] vim = true: Esc for normal mode, i/a to insert, h/l move, j/k scroll, / search, n next match";
+
 impl AppStateContainer {
     pub fn handle_slash_command(&mut self, input: &str, event_tx: UnboundedSender<TuiEvent>) {
         let parts: Vec<&str> = input.split_whitespace().collect();
-        let cmd = parts[0];
-
-        match cmd {
-            "/profile" => self.handle_profile_command(&parts, event_tx),
-            "/config" => self.handle_config_command(&parts, event_tx),
-            "/exec" => self.handle_exec_command(&parts, event_tx),
-            "/help" => self.handle_help_command(),
-            "/model" => self.handle_model_command(&parts, event_tx),
-            "/verbose" => self.handle_verbose_command(),
-            "/logs" => self.handle_logs_command(&parts),
-            "/pacore" => self.handle_pacore_command(&parts),
-            "/jobs" => self.handle_jobs_command(&parts),
-            "/prompt" => self.handle_prompt_command(event_tx),
-            "/context" => self.handle_context_command(event_tx),
-            "/pruned" => self.handle_pruned_command(),
-            "/restore" => self.handle_restore_command(&parts),
-            _ => {
+        let name = parts[0].trim_start_matches('/');
+
+        match slash::find(name) {
+            Some(command) => {
+                let call = Invocation {
+                    input,
+                    parts,
+                    event_tx: &event_tx,
+                };
+                (command.run)(self, &call);
+            }
+            None => {
+                let suggestion = slash::closest(name).map(|near| format!(" Did you mean /{}?", near)).unwrap_or_default();
                 self.chat_history.push(TimestampedChatMessage::assistant(format!(
-                    "Unknown command: {}",
-                    cmd
+                    "Unknown command: {}.{} /help lists them all.",
+                    parts[0], suggestion
                 )));
             }
         }
     }
 
+    fn handle_copy_code_command(&mut self, parts: &[&str]) {
+        match parts.get(1).map(|n| n.parse::<usize>()) {
+            None => self.copy_code_block_to_clipboard(1),
+            Some(Ok(number)) => self.copy_code_block_to_clipboard(number),
+            Some(Err(_)) => self.status_message = Some("Usage: /copy-code <n>".to_string()),
+        }
+    }
+
     fn handle_prompt_command(&mut self, _event_tx: UnboundedSender<TuiEvent>) {
         use mylm_core::config::prompt::{PromptManager, RenderContext, ToolInfo, ToolCategory};
         use std::path::PathBuf;
@@ -165,17 +400,77 @@ impl AppStateContainer {
         }
     }
 
-    fn handle_context_command(&mut self, event_tx: UnboundedSender<TuiEvent>) {
-        let event_tx_clone = event_tx.clone();
-        
-        tokio::spawn(async move {
-            let message = "Context dump not available in current architecture.".to_string();
-            
-            let _ = event_tx_clone.send(TuiEvent::AgentResponse(
-                Message::assistant(message),
-                mylm_core::provider::TokenUsage::default()
+    /// Handle /context command - list the memories injected into the last prompt
+    fn handle_context_command(&mut self) {
+        let Some(ref manager) = self.memory_manager else {
+            self.chat_history.push(TimestampedChatMessage::assistant(
+                "Memory is disabled for this session.".to_string(),
             ));
-        });
+            return;
+        };
+        let feedback = manager.feedback();
+        if feedback.last_injected.is_empty() {
+            self.chat_history.push(TimestampedChatMessage::assistant(
+                "No stored memories were injected into the last prompt.".to_string(),
+            ));
+            return;
+        }
+
+        let mut output = String::from("Memories injected into the last prompt:\n");
+        for (i, memory) in feedback.last_injected.iter().enumerate() {
+            let counts = feedback.counts(memory.id);
+            output.push_str(&format!(
+                "\n{}. {} (+{} / -{})",
+                i + 1,
+                memory.preview.replace('\n', " "),
+                counts.helpful,
+                counts.irrelevant
+            ));
+            if counts.suggests_pruning() {
+                output.push_str(" - consider deleting");
+            }
+        }
+        output.push_str("\n\nRate one with /rate <number> helpful|irrelevant");
+        self.chat_history.push(TimestampedChatMessage::assistant(output));
+    }
+
+    /// Handle /rate command - record feedback on an injected memory
+    fn handle_rate_command(&mut self, parts: &[&str]) {
+        let usage = "Usage: /rate <number> helpful|irrelevant\nUse /context to see the numbered memories.";
+        let Some(ref manager) = self.memory_manager else {
+            self.chat_history.push(TimestampedChatMessage::assistant(
+                "Memory is disabled for this session.".to_string(),
+            ));
+            return;
+        };
+        let (Some(index), Some(rating)) = (
+            parts.get(1).and_then(|n| n.parse::<usize>().ok()),
+            parts.get(2).and_then(|r| r.parse::<MemoryRating>().ok()),
+        ) else {
+            self.chat_history.push(TimestampedChatMessage::assistant(usage.to_string()));
+            return;
+        };
+        let feedback = manager.feedback();
+        let Some(memory) = index.checked_sub(1).and_then(|i| feedback.last_injected.get(i)) else {
+            self.chat_history.push(TimestampedChatMessage::assistant(format!(
+                "No injected memory #{}. {}",
+                index, usage
+            )));
+            return;
+        };
+
+        let message = match manager.rate_memory(memory.id, rating) {
+            Ok(counts) if counts.suggests_pruning() => format!(
+                "Rated memory {} as {} (+{} / -{}). It is often irrelevant; `mylm memory prune` can delete it.",
+                index, rating, counts.helpful, counts.irrelevant
+            ),
+            Ok(counts) => format!(
+                "Rated memory {} as {} (+{} / -{}). Future retrievals will take this into account.",
+                index, rating, counts.helpful, counts.irrelevant
+            ),
+            Err(e) => format!("Failed to rate memory: {}", e),
+        };
+        self.chat_history.push(TimestampedChatMessage::assistant(message));
     }
 
     fn handle_profile_command(&mut self, parts: &[&str], event_tx: UnboundedSender<TuiEvent>) {
@@ -265,74 +560,90 @@ impl AppStateContainer {
     }
 
     fn handle_help_command(&mut self) {
-        self.chat_history.push(TimestampedChatMessage::assistant(
-            "Available commands:\n\
-            /profile <name> - Switch profile\n\
-            /model <name> - Set model for active profile\n\
-            /config <key> <value> - Update active profile\n\
-            /exec <command> - Execute shell command (not yet implemented)\n\
-            /jobs - List active jobs with metrics\n\
-            /jobs cancel <id> - Cancel a specific job\n\
-            /jobs cancel-all - Cancel all jobs\n\
-            /jobs list - List all jobs\n\
-            /prompt - Dump system prompt to mylm/logs/\n\
-            /context - Dump LLM context to mylm/logs/ (not yet implemented)\n\
-            /verbose - Toggle verbose mode\n\
-            /help - Show this help\n\n\
-            Input Shortcuts:\n\
-            Ctrl+a / Home - Start of line\n\
-            Ctrl+e / End - End of line\n\
-            Ctrl+k - Kill to end\n\
-            Ctrl+u - Kill to start\n\
-            Arrows - Navigate lines/history"
-                .to_string(),
-        ));
+        self.chat_history
+            .push(TimestampedChatMessage::assistant(format!("{}\n{}", slash::help_text(), INPUT_SHORTCUTS)));
     }
 
-    fn handle_model_command(&mut self, parts: &[&str], event_tx: UnboundedSender<TuiEvent>) {
-        if parts.len() < 2 {
-            let active_profile = &self.config.active_profile;
-            let profile = self.config.active_profile();
-            let provider = &profile.provider;
-            let model = profile.model.as_deref().unwrap_or("default");
+    fn handle_verbose_command(&mut self) {
+        self.verbose_mode = !self.verbose_mode;
+        let status = if self.verbose_mode { "ON" } else { "OFF" };
+        self.chat_history
+            .push(TimestampedChatMessage::assistant(format!("Verbose mode: {}", status)));
+    }
 
-            self.chat_history.push(TimestampedChatMessage::assistant(format!(
-                "Current profile: {}\nProvider: {}\nModel: {}\n\nUsage: /model <model-name> to set model, or /model clear to use default.",
-                active_profile, provider, model
-            )));
-            return;
-        }
+    fn handle_usage_command(&mut self) {
+        self.show_reply_usage = !self.show_reply_usage;
+        self.status_message = Some(if self.show_reply_usage {
+            "Showing usage under AI responses".to_string()
+        } else {
+            "Hiding usage under AI responses".to_string()
+        });
+    }
 
-        let value = parts[1];
+    fn handle_raw_command(&mut self) {
+        self.show_raw_markdown = !self.show_raw_markdown;
+        self.status_message = Some(if self.show_raw_markdown {
+            "Showing raw markdown".to_string()
+        } else {
+            "Rendering markdown".to_string()
+        });
+    }
 
-        if value == "clear" {
-            if let Some(profile) = self.config.profiles.get_mut(&self.config.active_profile) {
-                profile.model = None;
-                let _ = event_tx.send(TuiEvent::ConfigUpdate("model=clear".to_string()));
-                self.chat_history.push(TimestampedChatMessage::assistant(format!(
-                    "Model cleared for profile '{}'. Using default.",
-                    self.config.active_profile
-                )));
+    /// `/tab [new|close|next|prev|<n>]`; without arguments, lists the tabs
+    fn handle_tab_command(&mut self, parts: &[&str]) {
+        use crate::tui::tabs::TabRequest;
+        self.tab_request = match parts.get(1).copied() {
+            None | Some("list") => {
+                let list = if self.tab_labels.is_empty() {
+                    "Only this tab is open (Ctrl+T or /tab new opens another)".to_string()
+                } else {
+                    self.tab_labels
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, label)| format!("{} {}", if idx == self.tab_index { "*" } else { " " }, label))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                };
+                self.chat_history.push(TimestampedChatMessage::assistant(list));
+                None
             }
-        } else {
-            if let Some(profile) = self.config.profiles.get_mut(&self.config.active_profile) {
-                profile.model = Some(value.to_string());
-                let _ = event_tx.send(TuiEvent::ConfigUpdate(format!("model={}", value)));
+            Some("new") => Some(TabRequest::New),
+            Some("close") => Some(TabRequest::Close),
+            Some("next") => Some(TabRequest::Next),
+            Some("prev") => Some(TabRequest::Previous),
+            Some(n) => match n.parse::<usize>() {
+                Ok(n) if n >= 1 => Some(TabRequest::Go(n - 1)),
+                _ => {
+                    self.status_message = Some("Usage: /tab [new|close|next|prev|<n>]".to_string());
+                    None
+                }
+            },
+        };
+    }
+
+    fn handle_theme_command(&mut self, parts: &[&str]) {
+        use crate::tui::app::theme::Theme;
+        match parts.get(1).copied() {
+            None | Some("list") => {
+                let names: Vec<String> = Theme::available(&self.config)
+                    .into_iter()
+                    .map(|name| if name == self.theme.name { format!("* {}", name) } else { format!("  {}", name) })
+                    .collect();
                 self.chat_history.push(TimestampedChatMessage::assistant(format!(
-                    "Model set to '{}' for profile '{}'",
-                    value, self.config.active_profile
+                    "Themes (set `app.theme` in the config to keep one):\n{}",
+                    names.join("\n")
                 )));
             }
+            Some(name) => match Theme::load(&self.config, name) {
+                Ok(theme) => {
+                    self.status_message = Some(format!("Theme: {}", theme.name));
+                    self.theme = theme;
+                }
+                Err(e) => self.status_message = Some(format!("⚠️ {}", e)),
+            },
         }
     }
 
-    fn handle_verbose_command(&mut self) {
-        self.verbose_mode = !self.verbose_mode;
-        let status = if self.verbose_mode { "ON" } else { "OFF" };
-        self.chat_history
-            .push(TimestampedChatMessage::assistant(format!("Verbose mode: {}", status)));
-    }
-
     fn handle_logs_command(&mut self, parts: &[&str]) {
         let n = parts
             .get(1)
@@ -379,7 +690,8 @@ impl AppStateContainer {
                         let rounds_clone = new_rounds.clone();
                         self.pacore_rounds = rounds_clone.parse::<usize>().unwrap_or(3);
                         self.config.features.pacore.rounds = self.pacore_rounds;
-                        let _ = self.config.save_default();
+                        let rounds = self.pacore_rounds;
+                        let _ = Config::update_default(|config| config.features.pacore.rounds = rounds);
                         self.chat_history.push(TimestampedChatMessage::assistant(format!(
                             "PaCoRe rounds set to: {}",
                             rounds_clone
@@ -401,7 +713,8 @@ impl AppStateContainer {
             }
             "save" => {
                 self.config.features.pacore.rounds = self.pacore_rounds;
-                match self.config.save_default() {
+                let rounds = self.pacore_rounds;
+                match Config::update_default(|config| config.features.pacore.rounds = rounds) {
                     Ok(_) => {
                         self.chat_history
                             .push(TimestampedChatMessage::assistant("PaCoRe configuration saved.".to_string()));
@@ -533,6 +846,298 @@ impl AppStateContainer {
         }
     }
     
+    /// Handle /attach command - attach an image to the next message
+    fn handle_attach_command(&mut self, input: &str) {
+        let arg = input.trim_start_matches("/attach").trim();
+        let reply = match arg {
+            "" | "list" if self.pending_images.is_empty() => "No images attached. Use /attach <path>.".to_string(),
+            "" | "list" => format!(
+                "Attached to the next message:\n{}",
+                self.pending_images.iter().map(|p| format!("  {}", p.path.display())).collect::<Vec<_>>().join("\n")
+            ),
+            "clear" => {
+                let count = self.pending_images.len();
+                self.pending_images.clear();
+                format!("Dropped {} attached image{}.", count, if count == 1 { "" } else { "s" })
+            }
+            path => {
+                let path = std::path::PathBuf::from(mylm_core::agent::tools::expand_tilde(path));
+                // Read it now so a bad file is reported before the message is sent
+                match self.attach_image(path) {
+                    Ok(path) => format!("Attached {}; it is sent with your next message.", path.display()),
+                    Err(e) => format!("Cannot attach: {:#}", e),
+                }
+            }
+        };
+        self.chat_history.push(TimestampedChatMessage::assistant(reply));
+    }
+
+    /// Handle /budget command - show session cost and token budget
+    fn handle_budget_command(&mut self) {
+        let Some(ref budget) = self.budget else {
+            self.chat_history.push(TimestampedChatMessage::assistant(
+                "No agent session is running.".to_string(),
+            ));
+            return;
+        };
+        let status = budget.status();
+
+        let tokens = match status.max_session_tokens {
+            Some(limit) => format!(
+                "{} / {} ({} remaining)",
+                status.total_tokens(),
+                limit,
+                status.remaining_tokens().unwrap_or(0)
+            ),
+            None => format!("{} (no limit)", status.total_tokens()),
+        };
+        let cost = match status.max_session_cost_usd {
+            Some(_) if !status.priced => format!(
+                "${:.4} (limit not enforced: set input_price/output_price on the profile)",
+                status.cost_usd
+            ),
+            Some(limit) => format!(
+                "${:.4} / ${:.2} (${:.4} remaining)",
+                status.cost_usd,
+                limit,
+                status.remaining_cost_usd().unwrap_or(0.0)
+            ),
+            None => format!("${:.4} (no limit)", status.cost_usd),
+        };
+        let state = match budget.check() {
+            Ok(()) => "within budget".to_string(),
+            Err(exceeded) => format!("exhausted: {}", exceeded),
+        };
+
+        self.chat_history.push(TimestampedChatMessage::assistant(format!(
+            "Session budget ({})\n\
+             Tokens: {} (prompt {}, completion {})\n\
+             Cost:   {}\n\n\
+             Limits are set with features.budget.max_session_tokens and max_session_cost_usd.",
+            state, tokens, status.prompt_tokens, status.completion_tokens, cost
+        )));
+    }
+
+    /// Handle /undo command - restore the files changed by the last agent turn
+    fn handle_undo_command(&mut self, parts: &[&str]) {
+        let Some(journal) = self.undo_journal.clone() else {
+            self.chat_history.push(TimestampedChatMessage::assistant(
+                "No agent session is running.".to_string(),
+            ));
+            return;
+        };
+        let message = match parts.get(1).copied() {
+            Some("list") => {
+                let turns = journal.turns();
+                if turns.is_empty() {
+                    "No agent edits to undo.".to_string()
+                } else {
+                    let lines: Vec<String> = turns.iter().rev().map(|turn| turn.summary()).collect();
+                    format!("Turns that /undo restores, latest first:\n{}", lines.join("\n"))
+                }
+            }
+            None => match journal.rollback_last() {
+                Ok(turn) => format!("Undid {}", turn.summary()),
+                Err(e) => format!("Undo failed: {}", e),
+            },
+            Some(_) => "Usage: /undo [list]".to_string(),
+        };
+        self.chat_history.push(TimestampedChatMessage::assistant(message));
+    }
+
+    /// Handle /queue command - review and apply the changeset of queued edits
+    fn handle_queue_command(&mut self, parts: &[&str]) {
+        let Some(queue) = self.patch_queue.clone() else {
+            self.chat_history.push(TimestampedChatMessage::assistant(
+                "No agent session is running.".to_string(),
+            ));
+            return;
+        };
+        let number = |i: usize| parts.get(i).and_then(|n| n.parse::<usize>().ok());
+        let usage = "Usage: /queue start <name> | list | show <n> | move <from> <to> | drop <n> | \
+                     apply [n...] | export <dir> | stop | discard";
+
+        let result: Result<String, String> = match parts.get(1).copied().unwrap_or("list") {
+            "start" => match parts.get(2) {
+                Some(name) => queue.open(name).map(|changeset| {
+                    format!(
+                        "Queueing file edits in changeset '{}' ({} patch(es) so far). \
+                         Each message you send starts a new patch; nothing is written until /queue apply.",
+                        changeset.name,
+                        changeset.patches.len()
+                    )
+                }),
+                None => Err(format!("{}\nSaved changesets: {}", usage, queue.saved().join(", "))),
+            },
+            "list" => queue
+                .changeset()
+                .map(|changeset| changeset.summary())
+                .ok_or_else(|| "No changeset is open. Start one with /queue start <name>".to_string()),
+            "show" => match (queue.changeset(), number(2)) {
+                (Some(changeset), Some(n)) => changeset.show(n).ok_or_else(|| format!("No patch {}", n)),
+                (None, _) => Err("No changeset is open".to_string()),
+                (_, None) => Err(usage.to_string()),
+            },
+            "move" => match (number(2), number(3)) {
+                (Some(from), Some(to)) => queue
+                    .reorder(from, to)
+                    .map(|()| queue.changeset().map(|c| c.summary()).unwrap_or_default()),
+                _ => Err(usage.to_string()),
+            },
+            "drop" => match number(2) {
+                Some(n) => queue.drop_patch(n).map(|patch| format!("Dropped patch {}: {}", n, patch.title)),
+                None => Err(usage.to_string()),
+            },
+            "apply" => {
+                let numbers: Result<Vec<usize>, String> = parts[2..]
+                    .iter()
+                    .map(|n| n.parse().map_err(|_| format!("Not a patch number: {}", n)))
+                    .collect();
+                numbers.and_then(|numbers| queue.apply(&numbers)).map(|written| {
+                    let left = queue.changeset().map_or(0, |c| c.patches.len());
+                    format!(
+                        "Wrote {} file(s):\n{}\n{} patch(es) left in the queue.",
+                        written.len(),
+                        written.iter().map(|p| format!("  {}", p.display())).collect::<Vec<_>>().join("\n"),
+                        left
+                    )
+                })
+            }
+            "export" => match parts.get(2) {
+                Some(dir) => queue.export(std::path::Path::new(dir)).map(|files| {
+                    format!(
+                        "Exported {} patch(es); apply them with `git am {}/*.patch`:\n{}",
+                        files.len(),
+                        dir,
+                        files.iter().map(|p| format!("  {}", p.display())).collect::<Vec<_>>().join("\n")
+                    )
+                }),
+                None => Err(usage.to_string()),
+            },
+            "stop" => queue
+                .close()
+                .map(|changeset| {
+                    format!(
+                        "Stopped queueing; file edits go to disk again. Changeset '{}' is saved, resume it with /queue start {}",
+                        changeset.name, changeset.name
+                    )
+                })
+                .ok_or_else(|| "No changeset is open".to_string()),
+            "discard" => queue.discard().map(|changeset| {
+                format!(
+                    "Discarded changeset '{}' and its {} unapplied patch(es)",
+                    changeset.name,
+                    changeset.patches.len()
+                )
+            }),
+            _ => Err(usage.to_string()),
+        };
+
+        self.chat_history.push(TimestampedChatMessage::assistant(
+            result.unwrap_or_else(|e| format!("Error: {}", e)),
+        ));
+    }
+
+    /// Handle /plan command - show or edit the agent's plan
+    ///
+    /// Edits are flagged on the session plan; the next message sent to the
+    /// agent carries the edited checklist.
+    fn handle_plan_command(&mut self, parts: &[&str]) {
+        use mylm_core::agent::runtime::core::PlanCapability;
+        use mylm_core::agent::types::plan::{PlanError, StepStatus};
+
+        let Some(plan) = self.plan.clone() else {
+            self.chat_history.push(TimestampedChatMessage::assistant(
+                "No agent session is running.".to_string(),
+            ));
+            return;
+        };
+        let number = parts.get(2).and_then(|n| n.parse::<usize>().ok());
+        let text = |from: usize| parts.get(from..).map(|words| words.join(" ")).unwrap_or_default();
+        let usage = "Usage: /plan [show | add <text> | edit <n> <text> | done <n> | skip <n> | undo <n> | remove <n> | clear]";
+        let set_status = |status: StepStatus| match number {
+            Some(n) => plan
+                .user_edit(&mut |p| p.set_status(n, status, None))
+                .map_err(|e| e.to_string()),
+            None => Err(usage.to_string()),
+        };
+
+        let result: Result<String, String> = match parts.get(1).copied().unwrap_or("show") {
+            "show" => plan
+                .current()
+                .map(|p| p.checklist())
+                .ok_or_else(|| "The agent has no plan. It writes one for multi-step tasks.".to_string()),
+            "add" if parts.len() > 2 => {
+                let title = text(2);
+                plan.user_edit(&mut |p| p.add_step(title.clone(), Vec::new()).map(|_| ()))
+                    .map(|p| p.checklist())
+                    .map_err(|e| e.to_string())
+            }
+            "edit" => match number {
+                Some(n) if parts.len() > 3 => {
+                    let title = text(3);
+                    plan.user_edit(&mut |p| {
+                        let step = p.steps.iter_mut().find(|s| s.id == n).ok_or_else(|| PlanError(format!("No step {}", n)))?;
+                        step.title = title.clone();
+                        Ok(())
+                    })
+                    .map(|p| p.checklist())
+                    .map_err(|e| e.to_string())
+                }
+                _ => Err(usage.to_string()),
+            },
+            "done" => set_status(StepStatus::Done).map(|p| p.checklist()),
+            "skip" => set_status(StepStatus::Skipped).map(|p| p.checklist()),
+            "undo" => set_status(StepStatus::Pending).map(|p| p.checklist()),
+            "remove" => match number {
+                Some(n) => plan
+                    .user_edit(&mut |p| p.remove_step(n).map(|_| ()))
+                    .map(|p| p.checklist())
+                    .map_err(|e| e.to_string()),
+                None => Err(usage.to_string()),
+            },
+            "clear" => plan
+                .user_clear()
+                .map(|p| format!("Cleared the plan '{}'.", p.goal))
+                .ok_or_else(|| "There is no plan to clear.".to_string()),
+            _ => Err(usage.to_string()),
+        };
+
+        self.chat_history.push(TimestampedChatMessage::assistant(
+            result.unwrap_or_else(|e| format!("Error: {}", e)),
+        ));
+    }
+
+    /// Handle /annotations command - list or clear the agent's terminal annotations
+    fn handle_annotations_command(&mut self, parts: &[&str]) {
+        if parts.get(1) == Some(&"clear") {
+            let count = self.terminal_annotations.len();
+            self.terminal_annotations.clear();
+            self.selected_annotation = None;
+            self.chat_history.push(TimestampedChatMessage::assistant(format!(
+                "Removed {} terminal annotation(s).",
+                count
+            )));
+            return;
+        }
+        if self.terminal_annotations.is_empty() {
+            self.chat_history.push(TimestampedChatMessage::assistant(
+                "The agent has not annotated the terminal.".to_string(),
+            ));
+            return;
+        }
+        let list: Vec<String> = self
+            .terminal_annotations
+            .iter()
+            .enumerate()
+            .map(|(i, a)| format!("{}. [{:?}] \"{}\"\n   {}", i + 1, a.severity, a.anchor, a.note))
+            .collect();
+        self.chat_history.push(TimestampedChatMessage::assistant(format!(
+            "Terminal annotations (hover a margin marker or press F5 to show one):\n{}",
+            list.join("\n")
+        )));
+    }
+
     /// Handle /pruned command - show compressed message history
     fn handle_pruned_command(&mut self) {
         let output = self.context_manager.compression_archive().format_list();
@@ -580,6 +1185,12 @@ impl AppStateContainer {
                 for msg in messages {
                     self.context_manager.add_message(&msg.role, &msg.content);
                 }
+                // and to the agent's history, in place of their summary
+                if let Some(input_tx) = &self.input_tx {
+                    if let Err(e) = input_tx.try_send(UserInput::Restore { segment_id }) {
+                        mylm_core::warn_log!("[TUI] Cannot send /restore to the session: {}", e);
+                    }
+                }
                 
                 self.chat_history.push(TimestampedChatMessage::assistant(format!(
                     "✅ Restored segment {} ({} messages).\n\nNote: Context size increased. Further pruning may occur if limits are exceeded.",
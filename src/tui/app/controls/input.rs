@@ -48,8 +48,6 @@ impl AppStateContainer {
         self.cursor_position += 1;
     }
 
-    
-    #[allow(dead_code)]
     pub fn enter_string(&mut self, text: &str) {
         let clean_text = text.replace('\r', "");
         if clean_text.is_empty() {
@@ -87,6 +85,13 @@ impl AppStateContainer {
         }
     }
 
+    /// Delete from the cursor to the end of the input
+    pub fn kill_to_end(&mut self) {
+        if let Some((byte_start, _)) = self.chat_input.char_indices().nth(self.cursor_position) {
+            self.chat_input.truncate(byte_start);
+        }
+    }
+
     pub fn delete_at_cursor(&mut self) {
         let char_count = self.chat_input.chars().count();
         if self.cursor_position < char_count {
@@ -114,6 +119,16 @@ impl AppStateContainer {
         self.terminal_auto_scroll = false;
     }
 
+    /// Open the next annotation's popup; closes after the last one
+    pub fn cycle_annotation(&mut self) {
+        self.selected_annotation = match self.selected_annotation {
+            _ if self.terminal_annotations.is_empty() => None,
+            None => Some(0),
+            Some(i) if i + 1 < self.terminal_annotations.len() => Some(i + 1),
+            Some(_) => None,
+        };
+    }
+
     pub fn scroll_terminal_down(&mut self) {
         self.terminal_scroll = self.terminal_scroll.saturating_sub(1);
         if self.terminal_scroll == 0 {
@@ -121,12 +136,6 @@ impl AppStateContainer {
         }
     }
 
-    // Layout
-    pub fn adjust_chat_width(&mut self, delta: i16) {
-        let new_width = self.chat_width_percent as i16 + delta;
-        self.chat_width_percent = new_width.clamp(20, 100) as u16;
-    }
-
     // Selection
     
     #[allow(dead_code)]
@@ -507,6 +516,10 @@ impl AppStateContainer {
                 output_tokens: stats.output_tokens as u32,
                 cost: stats.cost,
                 elapsed_seconds: self.session_monitor.duration().as_secs(),
+                title: self.session_title.clone(),
+                bookmarks: self.bookmarks.clone(),
+                pins: self.pins.clone(),
+                models: crate::tui::app::controls::model::message_models(&self.chat_history),
             },
             terminal_history: self.raw_buffer.clone(),
             agent_session_id: String::new(), // No legacy agent in new architecture
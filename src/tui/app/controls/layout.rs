@@ -0,0 +1,167 @@
+//! Pane layout: the terminal/chat split, which side the chat is on and
+//! zooming one pane
+//!
+//! The split and side are saved under `[app.layouts.<class>]` for the
+//! window's size class, so a narrow and a wide window each keep their own.
+//! Only that entry is written to the global config file.
+
+use crate::tui::app::state::AppStateContainer;
+use crate::tui::app::types::{Focus, TimestampedChatMessage};
+use mylm_core::config::{Config, PaneLayout};
+
+/// Narrowest chat, in percent of the window
+const MIN_CHAT_WIDTH: u16 = 20;
+/// Widest chat the divider can be dragged to, leaving some terminal
+const MAX_DRAG_WIDTH: u16 = 90;
+
+/// Size class of a window `width` columns wide
+pub fn size_class(width: u16) -> &'static str {
+    match width {
+        0..=99 => "narrow",
+        100..=159 => "medium",
+        _ => "wide",
+    }
+}
+
+/// Chat width in percent with the divider at `column` of `area`
+pub fn chat_width_at(area_x: u16, area_width: u16, column: u16, chat_left: bool) -> u16 {
+    let offset = column.saturating_sub(area_x).min(area_width);
+    let chat_columns = if chat_left { offset } else { area_width - offset };
+    let percent = (chat_columns as u32 * 100 / area_width.max(1) as u32) as u16;
+    percent.clamp(MIN_CHAT_WIDTH, MAX_DRAG_WIDTH)
+}
+
+impl AppStateContainer {
+    /// Switch to the saved layout when the window changes size class
+    pub fn apply_layout_for_width(&mut self, width: u16) {
+        let class = size_class(width);
+        if self.layout_class == Some(class) {
+            return;
+        }
+        self.layout_class = Some(class);
+        if let Some(layout) = self.config.app.layouts.get(class).copied() {
+            self.chat_width_percent = layout.chat_width.clamp(MIN_CHAT_WIDTH, 100);
+            self.chat_left = layout.chat_left;
+        }
+    }
+
+    pub fn adjust_chat_width(&mut self, delta: i16) {
+        let new_width = self.chat_width_percent as i16 + delta;
+        self.chat_width_percent = new_width.clamp(MIN_CHAT_WIDTH as i16, 100) as u16;
+        self.save_layout();
+    }
+
+    pub fn swap_panes(&mut self) {
+        self.chat_left = !self.chat_left;
+        self.save_layout();
+    }
+
+    /// Show the focused pane alone, or both again
+    pub fn toggle_zoom(&mut self) {
+        self.zoomed_pane = match (self.zoomed_pane, self.focus) {
+            (Some(_), _) => None,
+            (None, focus @ (Focus::Terminal | Focus::Chat)) => Some(focus),
+            (None, Focus::Jobs) => None,
+        };
+    }
+
+    /// Start dragging if (`column`, `row`) is on the divider: the borders of
+    /// the two panes either side of it
+    pub fn start_divider_drag(&mut self, column: u16, row: u16) -> bool {
+        let in_panes = self.panes_area.is_some_and(|area| (area.y..area.y + area.height).contains(&row));
+        self.dragging_divider =
+            in_panes && self.pane_divider.is_some_and(|divider| column + 1 == divider || column == divider);
+        self.dragging_divider
+    }
+
+    pub fn drag_divider(&mut self, column: u16) {
+        if let Some(area) = self.panes_area {
+            self.chat_width_percent = chat_width_at(area.x, area.width, column, self.chat_left);
+        }
+    }
+
+    pub fn end_divider_drag(&mut self) {
+        self.dragging_divider = false;
+        self.save_layout();
+    }
+
+    /// `/layout [swap|zoom|reset]`
+    pub fn handle_layout_command(&mut self, parts: &[&str]) {
+        match parts.get(1).copied() {
+            None => {}
+            Some("swap") => self.swap_panes(),
+            Some("zoom") => self.toggle_zoom(),
+            Some("reset") => {
+                let layout = PaneLayout::default();
+                self.chat_width_percent = layout.chat_width;
+                self.chat_left = layout.chat_left;
+                if let Some(class) = self.layout_class {
+                    self.config.app.layouts.remove(class);
+                    if let Err(e) = Config::update_default(|config| {
+                        config.app.layouts.remove(class);
+                    }) {
+                        mylm_core::warn_log!("[LAYOUT] Failed to save config: {}", e);
+                    }
+                }
+            }
+            Some(other) => {
+                self.chat_history.push(TimestampedChatMessage::assistant(format!(
+                    "Unknown layout option '{}'. Usage: /layout [swap|zoom|reset]",
+                    other
+                )));
+                return;
+            }
+        }
+        self.chat_history.push(TimestampedChatMessage::assistant(format!(
+            "Layout ({}): chat {}% on the {}{}",
+            self.layout_class.unwrap_or("unknown size"),
+            self.chat_width_percent,
+            if self.chat_left { "left" } else { "right" },
+            match self.zoomed_pane {
+                Some(Focus::Terminal) => ", terminal zoomed",
+                Some(_) => ", chat zoomed",
+                None => "",
+            }
+        )));
+    }
+
+    /// Remember the layout for the current size class
+    fn save_layout(&mut self) {
+        let Some(class) = self.layout_class else {
+            return;
+        };
+        let layout = PaneLayout {
+            chat_width: self.chat_width_percent,
+            chat_left: self.chat_left,
+        };
+        if self.config.app.layouts.get(class) == Some(&layout) {
+            return;
+        }
+        self.config.app.layouts.insert(class.to_string(), layout);
+        if let Err(e) = Config::update_default(|config| {
+            config.app.layouts.insert(class.to_string(), layout);
+        }) {
+            mylm_core::warn_log!("[LAYOUT] Failed to save config: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_class_and_divider() {
+        assert_eq!(size_class(80), "narrow");
+        assert_eq!(size_class(120), "medium");
+        assert_eq!(size_class(200), "wide");
+
+        // Chat on the right of a 100-column area starting at column 0
+        assert_eq!(chat_width_at(0, 100, 60, false), 40);
+        assert_eq!(chat_width_at(0, 100, 95, false), MIN_CHAT_WIDTH);
+        assert_eq!(chat_width_at(0, 100, 2, false), MAX_DRAG_WIDTH);
+        // Chat on the left, area offset by 10 columns
+        assert_eq!(chat_width_at(10, 100, 45, true), 35);
+        assert_eq!(chat_width_at(10, 100, 0, true), MIN_CHAT_WIDTH);
+    }
+}
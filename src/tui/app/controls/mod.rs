@@ -1,6 +1,18 @@
 //! UI Controls - Input handling, clipboard, commands, and session operations
 
 pub mod app_session;
+pub mod bookmarks;
 pub mod clipboard;
+pub mod command_line;
 pub mod commands;
 pub mod input;
+pub mod layout;
+pub mod model;
+pub mod paste;
+pub mod pins;
+pub mod rewind;
+pub mod scratchpad;
+pub mod search;
+pub mod slash;
+pub mod tool_results;
+pub mod vim;
@@ -0,0 +1,150 @@
+//! `/model`: list the active provider's models and switch between them
+//!
+//! Switching rebuilds the session's LLM client and keeps the history, so the
+//! next turn runs on the new model. Every AI response records the model that
+//! wrote it; the chat shows it under the message and the session file keeps it.
+
+use std::collections::BTreeMap;
+
+use crate::tui::app::state::AppStateContainer;
+use crate::tui::app::types::TimestampedChatMessage;
+use tokio::sync::oneshot;
+
+/// The provider's model list being fetched for `/model`
+pub type ModelListing = oneshot::Receiver<Result<Vec<String>, String>>;
+
+/// Model that wrote each message, by index, for the session file
+pub fn message_models(history: &[TimestampedChatMessage]) -> BTreeMap<usize, String> {
+    history
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, m)| Some((idx, m.model.clone()?)))
+        .collect()
+}
+
+/// Put the models saved with a session back on its messages
+pub fn restore_message_models(history: &mut [TimestampedChatMessage], models: &BTreeMap<usize, String>) {
+    for (idx, model) in models {
+        if let Some(message) = history.get_mut(*idx) {
+            message.model = Some(model.clone());
+        }
+    }
+}
+
+/// `models` one per line, the one in use marked
+pub fn format_model_list(provider: &str, models: &[String], current: &str) -> String {
+    let mut text = format!("Models from {} ({}):\n", provider, models.len());
+    for model in models {
+        let marker = if model == current { "▶" } else { " " };
+        text.push_str(&format!("{} {}\n", marker, model));
+    }
+    text.push_str("\n/model <name> switches for the next turns; the conversation carries over.");
+    text
+}
+
+impl AppStateContainer {
+    /// Model the next turn runs on
+    pub fn active_model(&self) -> String {
+        self.config.resolve_profile().model.unwrap_or_default()
+    }
+
+    /// `/model [<name>|clear]`
+    pub fn handle_model_command(&mut self, parts: &[&str]) {
+        let model = match parts.get(1).copied() {
+            None => return self.list_models(),
+            Some("clear") => None,
+            Some(name) => Some(name),
+        };
+        let message = match self.switch_model(model) {
+            Ok(()) => format!(
+                "Model set to '{}' for profile '{}'. The next turns use it.",
+                self.active_model(),
+                self.config.active_profile
+            ),
+            Err(e) => format!("❌ Cannot switch models: {}", e),
+        };
+        self.chat_history.push(TimestampedChatMessage::assistant(message));
+    }
+
+    /// Fetch the active provider's models in the background
+    fn list_models(&mut self) {
+        let resolved = self.config.resolve_profile();
+        let Some(base_url) = resolved.base_url else {
+            self.chat_history.push(TimestampedChatMessage::assistant(format!(
+                "Profile '{}' has no provider to list models from.",
+                self.config.active_profile
+            )));
+            return;
+        };
+        let api_key = resolved.api_key.unwrap_or_default();
+        let (tx, rx) = oneshot::channel();
+        tokio::spawn(async move {
+            let models = crate::hub::fetch_models(&base_url, &api_key).await;
+            let _ = tx.send(models.map_err(|e| e.to_string()));
+        });
+        self.model_listing = Some(rx);
+        self.status_message = Some(format!(
+            "Fetching models from {}...",
+            self.config.active_profile().provider
+        ));
+    }
+
+    /// Show the model list once it arrives
+    pub fn poll_model_listing(&mut self) {
+        let Some(listing) = self.model_listing.as_mut() else {
+            return;
+        };
+        let message = match listing.try_recv() {
+            Err(oneshot::error::TryRecvError::Empty) => return,
+            Err(oneshot::error::TryRecvError::Closed) => "❌ The model list was not fetched".to_string(),
+            Ok(Err(e)) => format!("❌ Cannot list models: {}", e),
+            Ok(Ok(models)) => format_model_list(&self.config.active_profile().provider, &models, &self.active_model()),
+        };
+        self.model_listing = None;
+        self.status_message = None;
+        self.chat_history.push(TimestampedChatMessage::assistant(message));
+    }
+
+    /// Point the active profile at `model`, or its provider's default, and
+    /// rebuild the session's client
+    pub fn switch_model(&mut self, model: Option<&str>) -> Result<(), String> {
+        let Some(reloader) = self.reloader.clone() else {
+            return Err("this session cannot change models".to_string());
+        };
+        let mut config = self.config.clone();
+        config.active_profile_mut().model = model.map(str::to_string);
+        reloader.apply(&config).map_err(|e| e.to_string())?;
+        self.config = config;
+        self.context_manager
+            .set_tokenizer(mylm_core::conversation::Tokenizer::for_model(&self.active_model()));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_models_round_trip() {
+        let mut history = vec![
+            TimestampedChatMessage::user("hi"),
+            TimestampedChatMessage::assistant("hello").with_model("gpt-4o"),
+            TimestampedChatMessage::user("again"),
+            TimestampedChatMessage::assistant("hello again").with_model("claude-sonnet"),
+        ];
+        let models = message_models(&history);
+        assert_eq!(models.keys().copied().collect::<Vec<_>>(), [1, 3]);
+
+        for message in &mut history {
+            message.model = None;
+        }
+        restore_message_models(&mut history, &models);
+        assert_eq!(history[1].model.as_deref(), Some("gpt-4o"));
+        assert_eq!(history[3].model.as_deref(), Some("claude-sonnet"));
+        assert!(history[0].model.is_none());
+
+        let listing = format_model_list("openai", &["a".to_string(), "b".to_string()], "b");
+        assert!(listing.contains("▶ b") && listing.contains("  a"));
+    }
+}
@@ -0,0 +1,318 @@
+//! Bracketed paste - large pastes become attached blocks instead of input lines
+//!
+//! A multi-line or very long paste into the chat input is kept out of the
+//! input box as a numbered "pasted block", shown as a chip under the input
+//! (F6 expands them). The blocks go out with the next message as delimited
+//! sections, so pasted newlines can never submit a half-written message.
+//!
+//! Images are attached instead of typed: a paste that is the path of an
+//! image file (what terminals paste for a dropped file) or a `data:image/`
+//! URL, and `/paste-image`, which reads the system clipboard. Each image is
+//! read once, when it is attached, and sent with the message as data.
+//! Clipboard and data-URL images are saved under the data directory too,
+//! so the chat can show where they came from. Images over
+//! `MAX_IMAGE_BYTES` are refused before they are saved, and saved images
+//! are deleted after `SAVED_IMAGE_DAYS` or once more than
+//! `MAX_SAVED_IMAGES` newer ones exist.
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use base64::Engine;
+use mylm_core::provider::chat::{image_media_type, ImageAttachment, MAX_IMAGE_BYTES};
+
+use crate::tui::app::state::{AppStateContainer, Focus};
+use crate::tui::app::types::{AppState, TimestampedChatMessage};
+
+/// Single-line pastes longer than this are attached as a block too
+const BLOCK_MIN_CHARS: usize = 500;
+/// Saved images older than this are deleted
+const SAVED_IMAGE_DAYS: u64 = 30;
+/// Saved images kept at most, newest first
+const MAX_SAVED_IMAGES: usize = 100;
+
+/// An image attached to the next message
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttachedImage {
+    /// Where it was read from, for the chat view
+    pub path: PathBuf,
+    pub image: ImageAttachment,
+}
+
+/// Text pasted into the chat input, sent with the next message
+#[derive(Debug, Clone, PartialEq)]
+pub struct PastedBlock {
+    pub text: String,
+}
+
+impl PastedBlock {
+    pub fn line_count(&self) -> usize {
+        self.text.lines().count()
+    }
+}
+
+/// Whether a paste should become a block rather than inline input
+fn is_block(text: &str) -> bool {
+    text.trim_end_matches('\n').contains('\n') || text.chars().count() > BLOCK_MIN_CHARS
+}
+
+/// Blocks as delimited sections appended to the user's message
+pub fn render_blocks(blocks: &[PastedBlock]) -> String {
+    blocks
+        .iter()
+        .enumerate()
+        .map(|(i, block)| {
+            // A fence longer than any backtick run inside keeps the block closed
+            let longest_run = block
+                .text
+                .split(|c| c != '`')
+                .map(str::len)
+                .max()
+                .unwrap_or(0);
+            let fence = "`".repeat((longest_run + 1).max(3));
+            format!(
+                "\n\n## Pasted block {} ({} lines)\n{}text\n{}\n{}",
+                i + 1,
+                block.line_count(),
+                fence,
+                block.text.trim_end_matches('\n'),
+                fence
+            )
+        })
+        .collect()
+}
+
+/// Directory pasted images are saved to
+fn pasted_image_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("mylm")
+        .join("attachments")
+}
+
+/// Delete saved images past their age or beyond the newest `MAX_SAVED_IMAGES`
+fn prune_saved_images(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut images: Vec<(std::time::SystemTime, PathBuf)> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .collect();
+    images.sort_by(|a, b| b.0.cmp(&a.0));
+    let max_age = std::time::Duration::from_secs(SAVED_IMAGE_DAYS * 24 * 60 * 60);
+    for (i, (modified, path)) in images.iter().enumerate() {
+        let expired = modified.elapsed().is_ok_and(|age| age > max_age);
+        if i >= MAX_SAVED_IMAGES || expired {
+            if let Err(e) = std::fs::remove_file(path) {
+                mylm_core::warn_log!("[TUI] Cannot delete old pasted image {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+/// Save clipboard pixels as a PNG in `dir`
+fn save_png(dir: &Path, width: usize, height: usize, rgba: &[u8]) -> anyhow::Result<PathBuf> {
+    // Compressed it is smaller, but a screenful never comes near this
+    if rgba.len() as u64 > MAX_IMAGE_BYTES * 4 {
+        anyhow::bail!("The clipboard image ({}x{}) is too large", width, height);
+    }
+    std::fs::create_dir_all(dir)?;
+    prune_saved_images(dir);
+    let path = dir.join(format!("clipboard-{}.png", chrono::Local::now().format("%Y%m%d-%H%M%S%3f")));
+    let file = std::io::BufWriter::new(std::fs::File::create(&path)?);
+    let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().context("Cannot encode the clipboard image")?;
+    writer.write_image_data(rgba).context("Cannot encode the clipboard image")?;
+    writer.finish()?;
+    Ok(path)
+}
+
+/// Save a `data:image/...;base64,` URL to `dir`; None if `text` is not one
+fn save_data_url(dir: &Path, text: &str) -> Option<anyhow::Result<PathBuf>> {
+    let (header, data) = text.strip_prefix("data:image/")?.split_once(";base64,")?;
+    let extension = match header {
+        "png" | "gif" | "webp" => header,
+        "jpeg" | "jpg" => "jpg",
+        _ => return None,
+    };
+    Some((|| {
+        // Base64 takes 4 characters for every 3 bytes
+        if data.trim().len() as u64 / 4 * 3 > MAX_IMAGE_BYTES {
+            anyhow::bail!("The pasted image is over {} MB", MAX_IMAGE_BYTES / (1024 * 1024));
+        }
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(data.trim())
+            .context("The pasted image is not valid base64")?;
+        std::fs::create_dir_all(dir)?;
+        prune_saved_images(dir);
+        let path = dir.join(format!("pasted-{}.{}", chrono::Local::now().format("%Y%m%d-%H%M%S%3f"), extension));
+        std::fs::write(&path, bytes)?;
+        Ok(path)
+    })())
+}
+
+/// The image file a paste names, if it is exactly one path to one
+fn pasted_image_path(text: &str) -> Option<PathBuf> {
+    let text = text.trim();
+    if text.contains('\n') {
+        return None;
+    }
+    // Terminals quote dropped paths with spaces, or paste them as file URLs
+    let unquoted = text.trim_matches(|c| c == '\'' || c == '"');
+    let unquoted = unquoted.strip_prefix("file://").unwrap_or(unquoted);
+    let path = PathBuf::from(mylm_core::agent::tools::expand_tilde(&unquoted.replace("\\ ", " ")));
+    (image_media_type(&path).is_some() && path.is_file()).then_some(path)
+}
+
+impl AppStateContainer {
+    /// Read the image at `path` and attach it to the next message
+    pub fn attach_image(&mut self, path: PathBuf) -> anyhow::Result<PathBuf> {
+        let path = std::fs::canonicalize(&path).unwrap_or(path);
+        let image = ImageAttachment::from_path(&path)?;
+        self.pending_images.push(AttachedImage { path: path.clone(), image });
+        Ok(path)
+    }
+
+    /// `/paste-image`: attach the image on the system clipboard
+    pub fn paste_clipboard_image(&mut self) {
+        let image = match self.clipboard.as_mut() {
+            None => Err(anyhow::anyhow!("No system clipboard is available")),
+            Some(clipboard) => clipboard.get_image().context("The clipboard holds no image"),
+        };
+        let result = image
+            .and_then(|image| save_png(&pasted_image_dir(), image.width, image.height, &image.bytes))
+            .and_then(|path| {
+                // Keep no copy of an image that cannot be sent
+                self.attach_image(path.clone()).inspect_err(|_| {
+                    let _ = std::fs::remove_file(&path);
+                })
+            });
+        let reply = match result {
+            Ok(path) => format!("Attached {} from the clipboard; it is sent with your next message.", path.display()),
+            Err(e) => format!("Cannot paste an image: {:#}", e),
+        };
+        self.chat_history.push(TimestampedChatMessage::assistant(reply));
+    }
+
+    /// Attach a pasted image; false if the paste is not one
+    fn paste_image(&mut self, text: &str) -> bool {
+        let saved = match save_data_url(&pasted_image_dir(), text.trim()) {
+            Some(saved) => saved,
+            None => match pasted_image_path(text) {
+                Some(path) => Ok(path),
+                None => return false,
+            },
+        };
+        self.status_message = Some(match saved.and_then(|path| self.attach_image(path)) {
+            Ok(path) => format!(
+                "🖼️ Attached {} ({} image{} with the next message)",
+                path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default(),
+                self.pending_images.len(),
+                if self.pending_images.len() == 1 { "" } else { "s" }
+            ),
+            Err(e) => format!("Cannot attach the pasted image: {:#}", e),
+        });
+        true
+    }
+
+    /// Handle a bracketed paste from the terminal
+    pub fn handle_paste(&mut self, text: String) {
+        self.note_activity();
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+
+        if self.state == AppState::NamingSession {
+            self.enter_string(text.lines().next().unwrap_or_default());
+            return;
+        }
+        match self.focus {
+            Focus::Terminal => {
+                // Let the shell see a paste when it asked for one
+                let body = text.replace('\n', "\r");
+                if self.terminal_parser.screen().bracketed_paste() {
+                    self.handle_terminal_input(format!("\x1b[200~{}\x1b[201~", body).as_bytes());
+                } else {
+                    self.handle_terminal_input(body.as_bytes());
+                }
+            }
+            Focus::Chat if self.paste_image(&text) => {}
+            Focus::Chat if is_block(&text) => {
+                self.pasted_blocks.push(PastedBlock { text });
+                let lines: usize = self.pasted_blocks.iter().map(PastedBlock::line_count).sum();
+                self.status_message = Some(format!(
+                    "📋 Attached pasted block {} ({} lines total) · F6 to expand",
+                    self.pasted_blocks.len(),
+                    lines
+                ));
+            }
+            Focus::Chat => self.enter_string(text.trim_end_matches('\n')),
+            Focus::Jobs => {}
+        }
+    }
+
+    /// Drop the most recent pasted block; false if there were none
+    pub fn remove_last_pasted_block(&mut self) -> bool {
+        let removed = self.pasted_blocks.pop().is_some();
+        if self.pasted_blocks.is_empty() {
+            self.pasted_blocks_expanded = false;
+        }
+        removed
+    }
+
+    pub fn toggle_pasted_blocks(&mut self) {
+        self.pasted_blocks_expanded = !self.pasted_blocks_expanded && !self.pasted_blocks.is_empty();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocks_are_detected_and_fenced() {
+        assert!(!is_block("cargo build\n"));
+        assert!(is_block("line one\nline two"));
+        assert!(is_block(&"x".repeat(BLOCK_MIN_CHARS + 1)));
+
+        let blocks = vec![
+            PastedBlock { text: "error[E0308]: mismatched types\n  --> src/main.rs:4:5\n".to_string() },
+            PastedBlock { text: "```rust\nfn main() {}\n```".to_string() },
+        ];
+        let rendered = render_blocks(&blocks);
+        assert!(rendered.starts_with("\n\n## Pasted block 1 (2 lines)\n```text\nerror[E0308]"));
+        assert!(rendered.contains("src/main.rs:4:5\n```\n\n## Pasted block 2 (3 lines)\n````text\n```rust"));
+        assert!(rendered.ends_with("```\n````"));
+    }
+
+    #[test]
+    fn test_pasted_images_are_saved_and_recognized() {
+        let dir = std::env::temp_dir().join(format!("mylm-paste-{}", uuid::Uuid::new_v4()));
+
+        let png = save_png(&dir, 2, 1, &[255, 0, 0, 255, 0, 0, 255, 255]).unwrap();
+        assert_eq!(image_media_type(&png), Some("image/png"));
+        assert!(ImageAttachment::from_path(&png).is_ok());
+        // A dropped file arrives quoted or as a file URL
+        assert_eq!(pasted_image_path(&format!("'{}'\n", png.display())), Some(png.clone()));
+        assert_eq!(pasted_image_path(&format!("file://{}", png.display())), Some(png.clone()));
+        assert_eq!(pasted_image_path("notes.png"), None);
+        assert_eq!(pasted_image_path(&format!("{}\nmore", png.display())), None);
+
+        let saved = save_data_url(&dir, "data:image/gif;base64,R0lGODlh").unwrap().unwrap();
+        assert_eq!(std::fs::read(&saved).unwrap(), b"GIF89a");
+        assert!(save_data_url(&dir, "data:text/plain;base64,aGk=").is_none());
+        assert!(save_data_url(&dir, "data:image/png;base64,***").unwrap().is_err());
+        let huge = format!("data:image/png;base64,{}", "A".repeat((MAX_IMAGE_BYTES / 3 * 4 + 8) as usize));
+        assert!(save_data_url(&dir, &huge).unwrap().is_err());
+
+        // Only the newest MAX_SAVED_IMAGES are kept
+        for i in 0..MAX_SAVED_IMAGES + 2 {
+            std::fs::write(dir.join(format!("old-{}.png", i)), b"x").unwrap();
+        }
+        prune_saved_images(&dir);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), MAX_SAVED_IMAGES);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,125 @@
+//! Pinned messages and the pinned panel
+//!
+//! Alt+P (or `p` in vim normal mode) pins the message at the bottom of the
+//! chat view, or unpins it. The agent gets the pinned texts and keeps those
+//! messages verbatim when it condenses history; F10 expands the panel
+//! above the chat that lists them. Pins are saved in the session metadata.
+
+use super::bookmarks::excerpt;
+use crate::tui::app::state::AppStateContainer;
+use crate::tui::app::types::TimestampedChatMessage;
+use mylm_core::agent::UserInput;
+use mylm_core::provider::chat::MessageRole;
+
+/// Add `message` to the sorted `pins`, or remove it; true if now pinned
+pub fn toggle(pins: &mut Vec<usize>, message: usize) -> bool {
+    match pins.binary_search(&message) {
+        Ok(pos) => {
+            pins.remove(pos);
+            false
+        }
+        Err(pos) => {
+            pins.insert(pos, message);
+            true
+        }
+    }
+}
+
+impl AppStateContainer {
+    /// Lowest message in the chat view that can be pinned
+    fn message_to_pin(&self) -> Option<usize> {
+        let (start, end) = (self.chat_visible_start_idx, self.chat_visible_end_idx);
+        self.chat_message_lines
+            .iter()
+            .rev()
+            .filter(|(_, line)| (start..end).contains(line))
+            .map(|(idx, _)| *idx)
+            .find(|idx| {
+                self.chat_history
+                    .get(*idx)
+                    .is_some_and(|m| m.message.role != MessageRole::System)
+            })
+    }
+
+    /// Pin the message in view, or unpin it
+    pub fn toggle_pin(&mut self) {
+        let Some(message) = self.message_to_pin() else {
+            self.status_message = Some("⚠️ No message to pin".to_string());
+            return;
+        };
+        self.status_message = Some(if toggle(&mut self.pins, message) {
+            format!("📌 Pinned ({} in all) · F10 lists them", self.pins.len())
+        } else {
+            "📌 Unpinned".to_string()
+        });
+        self.send_pins();
+    }
+
+    /// Excerpts of the pinned messages, in chat order
+    pub fn pin_excerpts(&self) -> Vec<String> {
+        self.pins
+            .iter()
+            .filter_map(|idx| self.chat_history.get(*idx))
+            .map(|m| excerpt(&m.message.content))
+            .collect()
+    }
+
+    /// Tell the agent which texts are pinned
+    pub fn send_pins(&self) {
+        let contents = self
+            .pins
+            .iter()
+            .filter_map(|idx| self.chat_history.get(*idx))
+            .map(|m| m.message.content.clone())
+            .collect();
+        if let Some(input_tx) = &self.input_tx {
+            if let Err(e) = input_tx.try_send(UserInput::Pinned { contents }) {
+                mylm_core::warn_log!("[TUI] Cannot send the pins to the session: {}", e);
+            }
+        }
+    }
+
+    /// `/pin [clear|remove <n>]`
+    pub fn handle_pin_command(&mut self, parts: &[&str]) {
+        match (parts.get(1).copied(), parts.get(2).map(|n| n.parse::<usize>())) {
+            (None, _) => self.toggle_pin(),
+            (Some("clear"), _) => {
+                let count = self.pins.len();
+                self.pins.clear();
+                self.send_pins();
+                self.status_message = Some(format!("📌 Unpinned {} message{}", count, if count == 1 { "" } else { "s" }));
+            }
+            (Some("remove"), Some(Ok(n))) if (1..=self.pins.len()).contains(&n) => {
+                self.pins.remove(n - 1);
+                self.send_pins();
+                self.status_message = Some(format!("📌 Unpinned {}", n));
+            }
+            _ => {
+                self.chat_history.push(TimestampedChatMessage::assistant(
+                    "Usage: /pin [clear|remove <n>]\nWithout arguments pins or unpins the message in view (Alt+P)."
+                        .to_string(),
+                ));
+            }
+        }
+    }
+
+    pub fn toggle_pinned_panel(&mut self) {
+        self.show_pinned = !self.show_pinned;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggle_keeps_pins_sorted() {
+        let mut pins = Vec::new();
+        assert!(toggle(&mut pins, 5));
+        assert!(toggle(&mut pins, 2));
+        assert!(toggle(&mut pins, 9));
+        assert_eq!(pins, [2, 5, 9]);
+        assert!(!toggle(&mut pins, 5));
+        assert_eq!(pins, [2, 9]);
+    }
+}
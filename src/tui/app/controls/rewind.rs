@@ -0,0 +1,122 @@
+//! `/edit` and `/retry`: take back the last message and run the turn again
+//!
+//! Both drop the last message you sent, and everything after it, from the
+//! chat and from the agent's history, then put the message back: in the
+//! input for `/edit`, straight to the agent for `/retry`.
+
+use crate::tui::app::controls::paste::{AttachedImage, PastedBlock};
+use crate::tui::app::state::AppStateContainer;
+use crate::tui::app::types::{AppState, TimestampedChatMessage};
+use mylm_core::agent::UserInput;
+use mylm_core::provider::chat::MessageRole;
+
+/// What went with the last message sent, to put back when it is taken back
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Submission {
+    pub input: String,
+    pub pasted_blocks: Vec<PastedBlock>,
+    pub images: Vec<AttachedImage>,
+}
+
+/// `/edit` or `/retry`, carried out by the event loop
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RewindRequest {
+    /// Put the last message back in the input, or send this text instead
+    Edit(Option<String>),
+    /// Send the last message again, switching to this model first
+    Retry(Option<String>),
+}
+
+/// Index of the last message the user sent
+pub fn last_user_message(history: &[TimestampedChatMessage]) -> Option<usize> {
+    history.iter().rposition(|m| m.message.role == MessageRole::User)
+}
+
+impl AppStateContainer {
+    /// `/edit [text]`
+    pub fn handle_edit_command(&mut self, text: &str) {
+        let text = text.trim();
+        self.rewind_request = Some(RewindRequest::Edit((!text.is_empty()).then(|| text.to_string())));
+    }
+
+    /// `/retry [model]`
+    pub fn handle_retry_command(&mut self, parts: &[&str]) {
+        self.rewind_request = Some(RewindRequest::Retry(parts.get(1).map(|model| model.to_string())));
+    }
+
+    /// Carry out `/edit` or `/retry`; true if the input is ready to send
+    pub fn apply_rewind(&mut self, request: RewindRequest) -> bool {
+        if !matches!(self.state, AppState::Idle | AppState::WaitingForUser) {
+            self.status_message = Some("Wait for the agent to finish (Ctrl+C stops it)".to_string());
+            return false;
+        }
+        if let RewindRequest::Retry(Some(model)) = &request {
+            if let Err(e) = self.switch_model(Some(model)) {
+                self.status_message = Some(format!("❌ Cannot switch to {}: {}", model, e));
+                return false;
+            }
+        }
+        let Some(submission) = self.rewind() else {
+            self.status_message = Some("No message to take back yet".to_string());
+            return false;
+        };
+        self.chat_input = submission.input;
+        self.pasted_blocks.extend(submission.pasted_blocks);
+        self.pending_images.extend(submission.images);
+        match request {
+            RewindRequest::Edit(None) => {
+                self.move_cursor_end();
+                self.status_message = Some("Editing your last message: Enter runs the turn again".to_string());
+                false
+            }
+            RewindRequest::Edit(Some(text)) => {
+                self.chat_input = text;
+                true
+            }
+            RewindRequest::Retry(_) => true,
+        }
+    }
+
+    /// Drop the last user message and what followed it, returning what was sent
+    fn rewind(&mut self) -> Option<Submission> {
+        let idx = last_user_message(&self.chat_history)?;
+        let sent = self.chat_history[idx].message.content.clone();
+        self.chat_history.truncate(idx);
+        self.pins.retain(|&pin| pin < idx);
+        self.bookmarks.retain(|b| b.message < idx);
+        self.context_manager
+            .set_history(&self.chat_history.iter().map(|m| m.message.clone()).collect::<Vec<_>>());
+        if let Some(input_tx) = &self.input_tx {
+            if let Err(e) = input_tx.try_send(UserInput::Rewind) {
+                mylm_core::warn_log!("[TUI] Cannot send the rewind to the session: {}", e);
+            }
+        }
+        self.send_pins();
+        // The dropped message carried the terminal and environment context
+        self.last_terminal_snapshot = None;
+        self.context_tracker.reset();
+        // A restored session only has the message as sent, context included
+        Some(self.last_submission.take().unwrap_or(Submission {
+            input: sent,
+            ..Default::default()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_user_message() {
+        let mut history = vec![
+            TimestampedChatMessage::user("list files"),
+            TimestampedChatMessage::assistant("a.txt"),
+        ];
+        assert_eq!(last_user_message(&history), Some(0));
+        history.push(TimestampedChatMessage::user("and hidden ones?"));
+        history.push(TimestampedChatMessage::assistant(".env"));
+        assert_eq!(last_user_message(&history), Some(2));
+        assert_eq!(last_user_message(&history[1..2]), None);
+    }
+}
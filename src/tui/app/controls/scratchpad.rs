@@ -0,0 +1,221 @@
+//! Scratchpad view: the agent's working notes, editable while it works
+//!
+//! F11 opens the scratchpad as text, each entry under a `--- <id>` header
+//! line. Ctrl+S writes the edits back: changed sections update their entry,
+//! new sections (under a `--- new` line) become entries and deleted ones are
+//! removed. Ctrl+R reloads what the agent has written since; Esc closes.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use mylm_core::agent::tools::scratchpad::EntryId;
+
+use crate::tui::app::state::AppStateContainer;
+
+/// The scratchpad being edited
+#[derive(Debug, Clone)]
+pub struct ScratchpadEditor {
+    pub lines: Vec<String>,
+    /// Cursor line, and column in characters
+    pub row: usize,
+    pub col: usize,
+    /// First line shown
+    pub scroll: usize,
+    /// Edited since opened or saved
+    pub dirty: bool,
+    /// Entries in the text when it was loaded
+    shown: Vec<EntryId>,
+}
+
+impl ScratchpadEditor {
+    pub fn new(document: &str, shown: Vec<EntryId>) -> Self {
+        let mut lines: Vec<String> = document.lines().map(str::to_string).collect();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        Self {
+            lines,
+            row: 0,
+            col: 0,
+            scroll: 0,
+            dirty: false,
+            shown,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// Byte offset of the cursor in its line
+    fn offset(&self) -> usize {
+        let line = &self.lines[self.row];
+        line.char_indices().nth(self.col).map_or(line.len(), |(idx, _)| idx)
+    }
+
+    fn line_len(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    /// Edit the text or move the cursor; false if `key` does neither
+    pub fn handle_key(&mut self, key: &KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char(c) if !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+                let offset = self.offset();
+                self.lines[self.row].insert(offset, c);
+                self.col += 1;
+                self.dirty = true;
+            }
+            KeyCode::Enter => {
+                let offset = self.offset();
+                let rest = self.lines[self.row].split_off(offset);
+                self.lines.insert(self.row + 1, rest);
+                self.row += 1;
+                self.col = 0;
+                self.dirty = true;
+            }
+            KeyCode::Backspace if self.col > 0 => {
+                self.col -= 1;
+                let offset = self.offset();
+                self.lines[self.row].remove(offset);
+                self.dirty = true;
+            }
+            KeyCode::Backspace if self.row > 0 => {
+                let line = self.lines.remove(self.row);
+                self.row -= 1;
+                self.col = self.line_len(self.row);
+                self.lines[self.row].push_str(&line);
+                self.dirty = true;
+            }
+            KeyCode::Delete if self.col < self.line_len(self.row) => {
+                let offset = self.offset();
+                self.lines[self.row].remove(offset);
+                self.dirty = true;
+            }
+            KeyCode::Delete if self.row + 1 < self.lines.len() => {
+                let next = self.lines.remove(self.row + 1);
+                self.lines[self.row].push_str(&next);
+                self.dirty = true;
+            }
+            KeyCode::Left if self.col > 0 => self.col -= 1,
+            KeyCode::Left if self.row > 0 => {
+                self.row -= 1;
+                self.col = self.line_len(self.row);
+            }
+            KeyCode::Right if self.col < self.line_len(self.row) => self.col += 1,
+            KeyCode::Right if self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.col = 0;
+            }
+            KeyCode::Up => self.move_rows(-1),
+            KeyCode::Down => self.move_rows(1),
+            KeyCode::PageUp => self.move_rows(-10),
+            KeyCode::PageDown => self.move_rows(10),
+            KeyCode::Home => self.col = 0,
+            KeyCode::End => self.col = self.line_len(self.row),
+            KeyCode::Backspace | KeyCode::Delete | KeyCode::Left | KeyCode::Right => {}
+            _ => return false,
+        }
+        true
+    }
+
+    fn move_rows(&mut self, by: isize) {
+        self.row = self.row.saturating_add_signed(by).min(self.lines.len() - 1);
+        self.col = self.col.min(self.line_len(self.row));
+    }
+
+    /// Scroll so the cursor is among the `height` lines shown
+    pub fn scroll_to_cursor(&mut self, height: usize) {
+        if self.row < self.scroll {
+            self.scroll = self.row;
+        } else if height > 0 && self.row >= self.scroll + height {
+            self.scroll = self.row + 1 - height;
+        }
+    }
+}
+
+impl AppStateContainer {
+    /// Open the scratchpad view, or close it, dropping unsaved edits
+    pub async fn toggle_scratchpad(&mut self) {
+        if let Some(editor) = self.scratchpad_editor.take() {
+            if editor.dirty {
+                self.status_message = Some("Scratchpad edits discarded".to_string());
+            }
+            return;
+        }
+        self.reload_scratchpad().await;
+    }
+
+    /// Load the scratchpad as it is now
+    pub async fn reload_scratchpad(&mut self) {
+        let Some(scratchpad) = self.scratchpad.clone() else {
+            self.status_message = Some("⚠️ This session has no scratchpad".to_string());
+            return;
+        };
+        let scratchpad = scratchpad.read().await;
+        let mut editor = ScratchpadEditor::new(&scratchpad.to_document(), scratchpad.get_all_ids());
+        if let Some(previous) = &self.scratchpad_editor {
+            editor.row = previous.row.min(editor.lines.len() - 1);
+            editor.col = previous.col.min(editor.line_len(editor.row));
+            editor.scroll = previous.scroll;
+        }
+        self.scratchpad_editor = Some(editor);
+    }
+
+    /// Write the edited text back to the scratchpad
+    pub async fn save_scratchpad(&mut self) {
+        let (Some(scratchpad), Some(editor)) = (self.scratchpad.clone(), self.scratchpad_editor.as_ref()) else {
+            return;
+        };
+        let changes = scratchpad.write().await.apply_document(&editor.text(), &editor.shown);
+        self.status_message = Some(format!(
+            "📝 Scratchpad saved: {} updated, {} added, {} removed",
+            changes.updated, changes.added, changes.removed
+        ));
+        self.reload_scratchpad().await;
+    }
+
+    /// Keys while the scratchpad view is open
+    pub async fn handle_scratchpad_key(&mut self, key: &KeyEvent) {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => self.toggle_scratchpad().await,
+            KeyCode::Char('s') if ctrl => self.save_scratchpad().await,
+            KeyCode::Char('r') if ctrl => {
+                self.reload_scratchpad().await;
+                self.status_message = Some("Scratchpad reloaded".to_string());
+            }
+            _ => {
+                if let Some(editor) = self.scratchpad_editor.as_mut() {
+                    editor.handle_key(key);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(editor: &mut ScratchpadEditor, code: KeyCode) {
+        editor.handle_key(&KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn test_editing_lines() {
+        let mut editor = ScratchpadEditor::new("--- 1a2b3c4d\nnoté", Vec::new());
+        press(&mut editor, KeyCode::Down);
+        press(&mut editor, KeyCode::End);
+        press(&mut editor, KeyCode::Char('s'));
+        press(&mut editor, KeyCode::Enter);
+        press(&mut editor, KeyCode::Char('x'));
+        assert_eq!(editor.text(), "--- 1a2b3c4d\nnotés\nx");
+        assert!(editor.dirty);
+
+        press(&mut editor, KeyCode::Backspace);
+        press(&mut editor, KeyCode::Backspace);
+        press(&mut editor, KeyCode::Left);
+        press(&mut editor, KeyCode::Delete);
+        assert_eq!(editor.text(), "--- 1a2b3c4d\nnoté");
+        assert_eq!((editor.row, editor.col), (1, 4));
+    }
+}
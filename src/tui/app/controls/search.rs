@@ -0,0 +1,130 @@
+//! Searching the chat history
+//!
+//! `/search <term>` or Ctrl+F starts a search, ignoring case. The chat
+//! highlights every occurrence and scrolls to the newest; `n` moves to the
+//! next older match and `N` to the next newer one, wrapping around. Right
+//! after a search starts, n/N navigate until any other key is pressed, which
+//! goes to the input as usual; in vim normal mode they always navigate.
+//! Tool results and other lines hidden outside verbose mode are shown for
+//! messages that match. Esc ends the search.
+use std::collections::HashMap;
+
+use crate::tui::app::keymap::VimMode;
+use crate::tui::app::state::{AppStateContainer, Focus};
+
+/// Character ranges of `text` that match `term`, ignoring case
+pub fn match_ranges(text: &str, term: &str) -> Vec<(usize, usize)> {
+    let haystack: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let needle: Vec<char> = term.chars().flat_map(char::to_lowercase).collect();
+    // Lowercasing can change the length; such lines are not highlighted
+    if needle.is_empty() || haystack.len() != text.chars().count() {
+        return Vec::new();
+    }
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while start + needle.len() <= haystack.len() {
+        if haystack[start..start + needle.len()] == needle[..] {
+            ranges.push((start, start + needle.len()));
+            start += needle.len();
+        } else {
+            start += 1;
+        }
+    }
+    ranges
+}
+
+/// Match ranges by line text for one search term, kept between frames
+#[derive(Debug, Default)]
+pub struct MatchCache {
+    term: String,
+    ranges: HashMap<String, Vec<(usize, usize)>>,
+}
+
+impl MatchCache {
+    /// `match_ranges(text, term)`, computed once per term and line
+    pub fn ranges(&mut self, term: &str, text: &str) -> &[(usize, usize)] {
+        if self.term != term {
+            self.term = term.to_string();
+            self.ranges.clear();
+        }
+        if !self.ranges.contains_key(text) {
+            self.ranges.insert(text.to_string(), match_ranges(text, term));
+        }
+        &self.ranges[text]
+    }
+}
+
+impl AppStateContainer {
+    /// Start a search for `term` at the newest match
+    pub fn search_chat(&mut self, term: &str) {
+        let term = term.trim();
+        if term.is_empty() {
+            self.status_message = Some("Usage: /search <term>".to_string());
+            return;
+        }
+        self.chat_search = Some(term.to_string());
+        self.search_match = None;
+        self.search_jump = true;
+        self.search_navigating = true;
+    }
+
+    /// Move to the next older match
+    pub fn next_search_match(&mut self) {
+        self.step_search(|current, count| current.checked_sub(1).unwrap_or(count - 1));
+    }
+
+    /// Move to the next newer match
+    pub fn previous_search_match(&mut self) {
+        self.step_search(|current, count| (current + 1) % count);
+    }
+
+    fn step_search(&mut self, step: impl Fn(usize, usize) -> usize) {
+        if self.chat_search.is_none() {
+            self.status_message = Some("No search yet (Ctrl+F)".to_string());
+            return;
+        }
+        let count = self.search_hits.len();
+        if count > 0 {
+            self.search_match = Some(match self.search_match {
+                Some(current) => step(current.min(count - 1), count),
+                None => count - 1,
+            });
+        }
+        self.search_jump = true;
+    }
+
+    pub fn clear_search(&mut self) {
+        self.chat_search = None;
+        self.search_match = None;
+        self.search_navigating = false;
+        self.search_hits.clear();
+        self.search_cache = MatchCache::default();
+        self.status_message = None;
+    }
+
+    /// Put `/search ` in the chat input, ready for the term
+    pub fn open_search_prompt(&mut self) {
+        self.focus = Focus::Chat;
+        self.chat_input = "/search ".to_string();
+        self.move_cursor_end();
+        self.vim_mode = VimMode::Insert;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_ranges() {
+        assert_eq!(match_ranges("Error: disk error", "ERROR"), vec![(0, 5), (12, 17)]);
+        assert_eq!(match_ranges("aaaa", "aa"), vec![(0, 2), (2, 4)]);
+        assert_eq!(match_ranges("héllo wörld", "wö"), vec![(6, 8)]);
+        assert!(match_ranges("nothing here", "missing").is_empty());
+        assert!(match_ranges("anything", "").is_empty());
+
+        let mut cache = MatchCache::default();
+        assert_eq!(cache.ranges("disk", "disk error"), [(0, 4)]);
+        assert_eq!(cache.ranges("error", "disk error"), [(5, 10)]);
+    }
+}
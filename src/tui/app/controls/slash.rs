@@ -0,0 +1,253 @@
+//! Slash command registry: dispatch, `/help`, Tab completion and the popup
+//! above the chat input all read from [`COMMANDS`](super::commands::COMMANDS)
+//!
+//! Argument schemas use `<x>` for a value and `[a|b]` for optional words;
+//! the literal words a command's first argument can be are completed too.
+
+use super::commands::COMMANDS;
+use crate::tui::app::state::AppStateContainer;
+use crate::tui::app::types::TuiEvent;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// One way to call a command: its arguments and what it does
+pub struct Usage {
+    pub args: &'static str,
+    pub help: &'static str,
+}
+
+/// A `/name` command, its usages and handler
+pub struct SlashCommand {
+    pub name: &'static str,
+    pub usages: &'static [Usage],
+    pub run: fn(&mut AppStateContainer, &Invocation),
+}
+
+/// A submitted command line
+pub struct Invocation<'a> {
+    pub input: &'a str,
+    pub parts: Vec<&'a str>,
+    pub event_tx: &'a UnboundedSender<TuiEvent>,
+}
+
+impl Invocation<'_> {
+    /// Everything after the command name, as typed
+    pub fn rest(&self) -> &str {
+        self.input
+            .trim_start()
+            .split_once(char::is_whitespace)
+            .map_or("", |(_, rest)| rest.trim_start())
+    }
+}
+
+/// A suggestion for what is being typed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    pub text: String,
+    pub hint: String,
+}
+
+impl SlashCommand {
+    /// Literal words the first argument can be, in usage order
+    pub fn subcommands(&self) -> Vec<&'static str> {
+        let mut words = Vec::new();
+        for word in self.usages.iter().flat_map(|usage| first_arg_words(usage.args)) {
+            if !words.contains(&word) {
+                words.push(word);
+            }
+        }
+        words
+    }
+
+    /// `<args> - <help>` of the first usage
+    fn hint(&self) -> String {
+        match self.usages.first() {
+            Some(usage) if usage.args.is_empty() => usage.help.to_string(),
+            Some(usage) => format!("{} - {}", usage.args, usage.help),
+            None => String::new(),
+        }
+    }
+}
+
+/// Literal words in the first argument of `args`
+fn first_arg_words(args: &'static str) -> Vec<&'static str> {
+    let first = match args.strip_prefix('[') {
+        Some(inner) => {
+            let mut depth = 0;
+            let end = inner
+                .char_indices()
+                .find(|&(_, c)| {
+                    match c {
+                        '[' => depth += 1,
+                        ']' if depth == 0 => return true,
+                        ']' => depth -= 1,
+                        _ => {}
+                    }
+                    false
+                })
+                .map_or(inner.len(), |(idx, _)| idx);
+            &inner[..end]
+        }
+        None => args.split_whitespace().next().unwrap_or_default(),
+    };
+    first
+        .split('|')
+        .filter_map(|alternative| alternative.split_whitespace().next())
+        .filter(|word| word.chars().all(|c| c.is_ascii_lowercase() || c == '-'))
+        .collect()
+}
+
+/// The command called `name` (without the slash)
+pub fn find(name: &str) -> Option<&'static SlashCommand> {
+    COMMANDS.iter().find(|command| command.name == name)
+}
+
+/// Completions for `input`: commands while the name is typed, then the
+/// literal words of the command's first argument
+pub fn completions(input: &str) -> Vec<Completion> {
+    let Some(typed) = input.strip_prefix('/') else {
+        return Vec::new();
+    };
+    let Some((name, arg)) = typed.split_once(' ') else {
+        return COMMANDS
+            .iter()
+            .filter(|command| command.name.starts_with(typed))
+            .map(|command| Completion {
+                text: format!("/{}", command.name),
+                hint: command.hint(),
+            })
+            .collect();
+    };
+    let Some(command) = find(name).filter(|_| !arg.contains(char::is_whitespace)) else {
+        return Vec::new();
+    };
+    command
+        .subcommands()
+        .into_iter()
+        .filter(|word| word.starts_with(arg))
+        .map(|word| Completion {
+            text: format!("/{} {}", name, word),
+            hint: command
+                .usages
+                .iter()
+                .find(|usage| first_arg_words(usage.args).contains(&word))
+                .map_or("", |usage| usage.help)
+                .to_string(),
+        })
+        .collect()
+}
+
+/// Longest text every completion starts with
+fn common_prefix(completions: &[Completion]) -> String {
+    let Some((first, rest)) = completions.split_first() else {
+        return String::new();
+    };
+    let mut prefix = first.text.as_str();
+    for completion in rest {
+        let len = prefix
+            .char_indices()
+            .zip(completion.text.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(prefix.len().min(completion.text.len()), |((idx, _), _)| idx);
+        prefix = &prefix[..len];
+    }
+    prefix.to_string()
+}
+
+/// The known command closest to a mistyped `name`, if any is close
+pub fn closest(name: &str) -> Option<&'static str> {
+    mylm_core::util::closest_match(name, COMMANDS.iter().map(|command| command.name))
+}
+
+/// `/help`: every usage of every command
+pub fn help_text() -> String {
+    let mut text = String::from("Available commands:\n");
+    for command in COMMANDS {
+        for usage in command.usages {
+            let call = if usage.args.is_empty() {
+                format!("/{}", command.name)
+            } else {
+                format!("/{} {}", command.name, usage.args)
+            };
+            text.push_str(&format!("{} - {}\n", call, usage.help));
+        }
+    }
+    text
+}
+
+impl AppStateContainer {
+    /// Tab in the chat input: complete the command or argument being typed,
+    /// cycling through the matches on repeated presses; false if none match
+    pub fn complete_slash_command(&mut self) -> bool {
+        let (typed, next) = match self.slash_completion.take() {
+            Some((typed, index))
+                if completions(&typed)
+                    .get(index)
+                    .is_some_and(|c| c.text == self.chat_input) =>
+            {
+                (typed, index + 1)
+            }
+            _ => (self.chat_input.clone(), 0),
+        };
+        let matches = completions(&typed);
+        let text = match matches.as_slice() {
+            [] => return false,
+            [only] => format!("{} ", only.text),
+            _ => {
+                let common = common_prefix(&matches);
+                if next == 0 && common.len() > typed.len() {
+                    common
+                } else {
+                    let index = next % matches.len();
+                    let text = matches[index].text.clone();
+                    self.slash_completion = Some((typed, index));
+                    text
+                }
+            }
+        };
+        self.chat_input = text;
+        self.move_cursor_end();
+        true
+    }
+
+    /// What the popup above the chat input lists, and which is selected
+    pub fn slash_popup(&self) -> (Vec<Completion>, Option<usize>) {
+        if let Some((typed, index)) = &self.slash_completion {
+            let matches = completions(typed);
+            if matches.get(*index).is_some_and(|c| c.text == self.chat_input) {
+                return (matches, Some(*index));
+            }
+        }
+        (completions(&self.chat_input), None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(input: &str) -> Vec<String> {
+        completions(input).into_iter().map(|c| c.text).collect()
+    }
+
+    #[test]
+    fn test_completions_from_registry() {
+        assert_eq!(texts("/lay"), ["/layout"]);
+        assert_eq!(texts("/layout "), ["/layout swap", "/layout zoom", "/layout reset"]);
+        assert_eq!(texts("/tab n"), ["/tab new", "/tab next"]);
+        assert_eq!(texts("/jobs c"), ["/jobs cancel", "/jobs cancel-all"]);
+        assert!(texts("/search foo ").is_empty());
+        assert!(texts("hello").is_empty());
+
+        assert_eq!(
+            first_arg_words("[list|show <n>|apply [n...]|stop]"),
+            ["list", "show", "apply", "stop"]
+        );
+        assert_eq!(first_arg_words("<n> helpful|irrelevant"), Vec::<&str>::new());
+        assert_eq!(first_arg_words("<name>|clear"), ["clear"]);
+
+        assert_eq!(common_prefix(&completions("/p")), "/p");
+        assert_eq!(common_prefix(&completions("/pa")), "/pa");
+        assert_eq!(closest("layuot"), Some("layout"));
+        assert_eq!(closest("xyzzy"), None);
+    }
+}
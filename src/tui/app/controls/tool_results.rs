@@ -0,0 +1,147 @@
+//! Tool results in the chat: one-line summaries that Enter expands
+
+use crate::tui::app::state::AppStateContainer;
+use crate::tui::app::types::{TimestampedChatMessage, ToolRun};
+use mylm_core::util::format_size;
+
+/// Longest argument preview in a summary, in characters
+const ARGS_PREVIEW: usize = 40;
+
+/// Whether a tool result reports a failure
+pub fn is_failure(result: &str) -> bool {
+    result.starts_with("❌ Error:")
+        || result.starts_with("Error:")
+        || result == "Cancelled"
+        || result.contains("rate limited")
+        || result.contains("timed out")
+}
+
+/// Exit code a shell tool reported in its error ("Exit code N: ...")
+pub fn exit_code(result: &str) -> Option<i32> {
+    let rest = &result[result.find("Exit code ")? + "Exit code ".len()..];
+    let end = rest.find(|c: char| !(c.is_ascii_digit() || c == '-')).unwrap_or(rest.len());
+    rest[..end].parse().ok()
+}
+
+/// `shell ls -la · 1.2s · ✗ exit 2 · 14 lines, 812 B`
+pub fn summary(run: &ToolRun, result: &str) -> String {
+    let mut parts = vec![match args_preview(&run.args) {
+        Some(args) => format!("{} {}", run.tool, args),
+        None => run.tool.clone(),
+    }];
+    if let Some(ms) = run.duration_ms {
+        parts.push(format_duration(ms));
+    }
+    parts.push(if result == "Cancelled" {
+        "cancelled".to_string()
+    } else if !is_failure(result) {
+        "✓".to_string()
+    } else if let Some(code) = exit_code(result) {
+        format!("✗ exit {}", code)
+    } else {
+        "✗ failed".to_string()
+    });
+    let lines = result.lines().count();
+    parts.push(format!(
+        "{} line{}, {}",
+        lines,
+        if lines == 1 { "" } else { "s" },
+        format_size(result.len() as u64)
+    ));
+    parts.join(" · ")
+}
+
+/// The command or path a tool was called with, else its raw arguments
+fn args_preview(args: &str) -> Option<String> {
+    let json = serde_json::from_str::<serde_json::Value>(args).ok();
+    let text = json
+        .as_ref()
+        .and_then(|json| ["command", "path", "query", "url"].iter().find_map(|key| json.get(key)?.as_str()))
+        .unwrap_or(args);
+    let text = text.lines().next().unwrap_or_default().trim();
+    if text.is_empty() || text == "{}" {
+        return None;
+    }
+    if text.chars().count() > ARGS_PREVIEW {
+        Some(format!("{}…", text.chars().take(ARGS_PREVIEW).collect::<String>()))
+    } else {
+        Some(text.to_string())
+    }
+}
+
+fn format_duration(ms: u64) -> String {
+    match ms {
+        0..=999 => format!("{}ms", ms),
+        1_000..=59_999 => format!("{:.1}s", ms as f64 / 1000.0),
+        _ => format!("{}m {:02}s", ms / 60_000, ms % 60_000 / 1000),
+    }
+}
+
+impl AppStateContainer {
+    /// Add a finished tool's result to the chat, expanded or not as
+    /// `app.tool_results` says for that tool
+    pub fn record_tool_result(&mut self, intent_id: u64, result: &str) {
+        let (tool, args, duration_ms) = match self.running_tools.remove(&intent_id) {
+            Some((tool, args, started)) => (tool, args, Some(started.elapsed().as_millis() as u64)),
+            None => ("tool".to_string(), String::new(), None),
+        };
+        let run = ToolRun {
+            expanded: self.config.app.tool_results.expanded_for(&tool),
+            tool,
+            args,
+            duration_ms,
+        };
+        self.chat_history.push(TimestampedChatMessage::tool_result(intent_id, run, result));
+    }
+
+    /// Expand or collapse the lowest tool result in view; false if none is
+    pub fn toggle_tool_result(&mut self) -> bool {
+        let (start, end) = (self.chat_visible_start_idx, self.chat_visible_end_idx);
+        let target = self
+            .chat_message_lines
+            .iter()
+            .rev()
+            .filter(|(_, line)| (start..end).contains(line))
+            .map(|(idx, _)| *idx)
+            .find(|idx| self.chat_history.get(*idx).is_some_and(|m| m.tool_run.is_some()));
+        let Some(run) = target.and_then(|idx| self.chat_history[idx].tool_run.as_mut()) else {
+            return false;
+        };
+        run.expanded = !run.expanded;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(tool: &str, args: &str, duration_ms: Option<u64>) -> ToolRun {
+        ToolRun {
+            tool: tool.to_string(),
+            args: args.to_string(),
+            duration_ms,
+            expanded: false,
+        }
+    }
+
+    #[test]
+    fn test_tool_result_summary() {
+        assert_eq!(exit_code("Error: Exit code 2: no such file"), Some(2));
+        assert_eq!(exit_code("all good"), None);
+
+        let shell = run("shell", r#"{"command":"ls -la\nwc"}"#, Some(1234));
+        assert_eq!(summary(&shell, "a\nb"), "shell ls -la · 1.2s · ✓ · 2 lines, 3 B");
+        assert_eq!(
+            summary(&shell, "Error: Exit code 2: ls: nope"),
+            "shell ls -la · 1.2s · ✗ exit 2 · 1 line, 28 B"
+        );
+
+        let long = run("web_search", &"x".repeat(50), Some(75_000));
+        let text = summary(&long, &"y".repeat(2048));
+        assert!(text.starts_with(&format!("web_search {}… · 1m 15s", "x".repeat(40))));
+        assert!(text.ends_with("✓ · 1 line, 2.0 KB"));
+
+        assert_eq!(summary(&run("tool", "{}", None), "Cancelled"), "tool · cancelled · 1 line, 9 B");
+    }
+}
@@ -0,0 +1,110 @@
+//! Vim-style editing of the chat input
+//!
+//! With `app.keymap.vim` on, Esc in the chat input switches to normal mode
+//! instead of asking to exit. Normal mode: `i`/`a`/`I`/`A` back to insert,
+//! `h`/`l`/`w`/`b`/`0`/`$` move the cursor, `j`/`k` scroll the chat and
+//! `g`/`G` jump to its top or bottom, `x`/`D`/`C` edit, `p` pins the message
+//! in view, `/` starts a search and `n`/`N` go to the next older or newer
+//! match. Keys with Ctrl or Alt, arrows and Enter work as without vim.
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::tui::app::keymap::VimMode;
+use crate::tui::app::state::AppStateContainer;
+
+/// Start of the next word after `pos`
+fn next_word(chars: &[char], pos: usize) -> usize {
+    let mut i = pos;
+    while i < chars.len() && !chars[i].is_whitespace() {
+        i += 1;
+    }
+    while i < chars.len() && chars[i].is_whitespace() {
+        i += 1;
+    }
+    i
+}
+
+/// Start of the word before `pos`
+fn previous_word(chars: &[char], pos: usize) -> usize {
+    let mut i = pos.min(chars.len());
+    while i > 0 && chars[i - 1].is_whitespace() {
+        i -= 1;
+    }
+    while i > 0 && !chars[i - 1].is_whitespace() {
+        i -= 1;
+    }
+    i
+}
+
+impl AppStateContainer {
+    /// Handle `key` in normal mode; false when the regular handling applies
+    pub fn handle_vim_normal_key(&mut self, key: &KeyEvent) -> bool {
+        if key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) {
+            return false;
+        }
+        let KeyCode::Char(c) = key.code else {
+            return false;
+        };
+        let chars: Vec<char> = self.chat_input.chars().collect();
+        match c {
+            'i' => self.vim_mode = VimMode::Insert,
+            'a' => {
+                self.move_cursor_right();
+                self.vim_mode = VimMode::Insert;
+            }
+            'I' => {
+                self.move_cursor_home();
+                self.vim_mode = VimMode::Insert;
+            }
+            'A' => {
+                self.move_cursor_end();
+                self.vim_mode = VimMode::Insert;
+            }
+            'h' => self.move_cursor_left(),
+            'l' => self.move_cursor_right(),
+            '0' | '^' => self.move_cursor_home(),
+            '$' => self.move_cursor_end(),
+            'w' => self.cursor_position = next_word(&chars, self.cursor_position),
+            'b' => self.cursor_position = previous_word(&chars, self.cursor_position),
+            'j' => self.scroll_chat_down(),
+            'k' => self.scroll_chat_up(),
+            'g' => {
+                // Clamped to the first line when the chat is drawn
+                self.chat_scroll = usize::MAX;
+                self.chat_auto_scroll = false;
+            }
+            'G' => {
+                self.chat_scroll = 0;
+                self.chat_auto_scroll = true;
+            }
+            'p' => self.toggle_pin(),
+            'x' => self.delete_at_cursor(),
+            'D' => self.kill_to_end(),
+            'C' => {
+                self.kill_to_end();
+                self.vim_mode = VimMode::Insert;
+            }
+            '/' => self.open_search_prompt(),
+            'n' => self.next_search_match(),
+            'N' => self.previous_search_match(),
+            // Anything else is not typed in normal mode
+            _ => {}
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_motions() {
+        let chars: Vec<char> = "git  commit -m msg".chars().collect();
+        assert_eq!(next_word(&chars, 0), 5);
+        assert_eq!(next_word(&chars, 5), 12);
+        assert_eq!(next_word(&chars, 15), chars.len());
+        assert_eq!(previous_word(&chars, 12), 5);
+        assert_eq!(previous_word(&chars, 7), 5);
+        assert_eq!(previous_word(&chars, 3), 0);
+    }
+}
@@ -4,17 +4,64 @@
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
 
+use crate::tui::app::keymap::{Action, VimMode};
 use crate::tui::app::state::{AppState, AppStateContainer, Focus};
 use crate::tui::app::types::TimestampedChatMessage;
+use crate::tui::tabs::TabRequest;
 use mylm_core::memory::graph::MemoryGraph;
 
+/// Grant the pending approval and add an `allow` rule for calls like it,
+/// for this session or, with `persist`, in the config file too
+fn remember_approval(app: &mut AppStateContainer, persist: bool) {
+    use mylm_core::agent::runtime::governance::PolicyRule;
+    use mylm_core::agent::types::events::ApprovalOutcome;
+
+    let Some(pending) = app.pending_approval_with_response.take() else {
+        return;
+    };
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let rule = PolicyRule::allow_for(&pending.request.tool, &pending.request.args, &cwd);
+    if let Some(rule) = &rule {
+        app.approval_rules.add(rule.clone());
+    }
+    if pending.response_tx.send(ApprovalOutcome::Granted).is_err() {
+        mylm_core::error_log!("[EVENT_LOOP] Failed to send approval - receiver dropped");
+    }
+    let _ = app.pending_approval.take();
+
+    let tool = pending.request.tool;
+    let Some(rule) = rule else {
+        app.chat_history.push(TimestampedChatMessage::assistant(format!(
+            "✅ {} approved once; chained or substituted commands cannot be allowed by a rule",
+            tool
+        )));
+        return;
+    };
+    let message = if !persist {
+        format!("✅ {} approved; `{}` for the rest of this session", tool, rule)
+    } else {
+        app.config.features.approval.rules.push(rule.to_string());
+        match crate::tui::app::approval::TuiApprovalCapability::persist_rule(&rule.to_string()) {
+            Ok(()) => format!("✅ {} approved; saved rule `{}`", tool, rule),
+            Err(e) => format!("✅ {} approved; `{}` for this session only (config not saved: {})", tool, rule, e),
+        }
+    };
+    mylm_core::info_log!("[EVENT_LOOP] Approval rule added (persist={}): {}", persist, rule);
+    app.chat_history.push(TimestampedChatMessage::assistant(message));
+}
+
 /// Handle key events
 pub async fn handle_key_event(app: &mut AppStateContainer, key: KeyEvent) -> LoopAction {
+    app.note_activity();
+
     // Handle special states first
     match &app.state {
         AppState::AwaitingApproval { tool: _tool, .. } => {
-            match key.code {
-                KeyCode::Char('y') | KeyCode::Char('Y') => {
+            let answer = [Action::Approve, Action::ApproveSession, Action::ApproveAlways, Action::Deny]
+                .into_iter()
+                .find(|action| app.keymap.is(*action, &key));
+            match answer {
+                Some(Action::Approve) => {
                     // Get the pending approval with response channel first
                     if let Some(pending) = app.pending_approval_with_response.take() {
                         let tool_name = pending.request.tool.clone();
@@ -39,11 +86,23 @@ pub async fn handle_key_event(app: &mut AppStateContainer, key: KeyEvent) -> Loo
                     app.state = AppState::Idle;
                     return LoopAction::Continue;
                 }
-                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                Some(Action::ApproveSession) => {
+                    // Approve, and approve calls like this one for the rest of the session
+                    remember_approval(app, false);
+                    app.set_state(AppState::Idle);
+                    return LoopAction::Continue;
+                }
+                Some(Action::ApproveAlways) => {
+                    // Approve, and save the rule to the config file
+                    remember_approval(app, true);
+                    app.set_state(AppState::Idle);
+                    return LoopAction::Continue;
+                }
+                Some(Action::Deny) => {
                     // Get the pending approval with response channel first
                     if let Some(pending) = app.pending_approval_with_response.take() {
                         let tool_name = pending.request.tool.clone();
-                        
+
                         // Send denial response through the oneshot channel (unblocks runtime)
                         use mylm_core::agent::types::events::ApprovalOutcome;
                         match pending.response_tx.send(ApprovalOutcome::Denied { reason: Some("User denied".to_string()) }) {
@@ -107,18 +166,28 @@ pub async fn handle_key_event(app: &mut AppStateContainer, key: KeyEvent) -> Loo
         }
         _ => {}
     }
+
+    // The scratchpad editor takes every key while open
+    if app.scratchpad_editor.is_some() {
+        if app.keymap.is(Action::ToggleScratchpad, &key) {
+            app.toggle_scratchpad().await;
+        } else {
+            app.handle_scratchpad_key(&key).await;
+        }
+        return LoopAction::Continue;
+    }
     
     // Global shortcuts
     match key.code {
-        KeyCode::F(1) => {
+        _ if app.keymap.is(Action::ToggleHelp, &key) => {
             app.show_help_view = !app.show_help_view;
             return LoopAction::Continue;
         }
-        KeyCode::F(2) => {
+        _ if app.keymap.is(Action::ToggleFocus, &key) => {
             app.toggle_focus();
             return LoopAction::Continue;
         }
-        KeyCode::F(3) => {
+        _ if app.keymap.is(Action::ToggleMemory, &key) => {
             let was_showing = app.show_memory_view;
             app.show_memory_view = !was_showing;
             
@@ -128,18 +197,98 @@ pub async fn handle_key_event(app: &mut AppStateContainer, key: KeyEvent) -> Loo
             }
             return LoopAction::Continue;
         }
-        KeyCode::F(4) => {
+        _ if app.keymap.is(Action::ToggleJobs, &key) => {
             app.show_jobs_panel = !app.show_jobs_panel;
             if !app.show_jobs_panel && app.focus == Focus::Jobs {
                 app.focus = Focus::Terminal;
             }
             return LoopAction::Continue;
         }
+        _ if app.keymap.is(Action::GrowChat, &key) => {
+            app.adjust_chat_width(5);
+            return LoopAction::Continue;
+        }
+        _ if app.keymap.is(Action::ShrinkChat, &key) => {
+            app.adjust_chat_width(-5);
+            return LoopAction::Continue;
+        }
+        _ if app.keymap.is(Action::SwapPanes, &key) => {
+            app.swap_panes();
+            return LoopAction::Continue;
+        }
+        _ if app.keymap.is(Action::ZoomPane, &key) => {
+            app.toggle_zoom();
+            return LoopAction::Continue;
+        }
+        _ if app.keymap.is(Action::PinMessage, &key) => {
+            app.toggle_pin();
+            return LoopAction::Continue;
+        }
+        _ if app.keymap.is(Action::TogglePinned, &key) => {
+            app.toggle_pinned_panel();
+            return LoopAction::Continue;
+        }
+        _ if app.keymap.is(Action::ToggleScratchpad, &key) => {
+            app.toggle_scratchpad().await;
+            return LoopAction::Continue;
+        }
+        _ if app.keymap.is(Action::NewTab, &key) => {
+            app.tab_request = Some(TabRequest::New);
+            return LoopAction::Continue;
+        }
+        _ if app.keymap.is(Action::NextTab, &key) => {
+            app.tab_request = Some(TabRequest::Next);
+            return LoopAction::Continue;
+        }
+        _ if app.keymap.is(Action::PreviousTab, &key) => {
+            app.tab_request = Some(TabRequest::Previous);
+            return LoopAction::Continue;
+        }
+        KeyCode::F(5) => {
+            app.cycle_annotation();
+            return LoopAction::Continue;
+        }
+        KeyCode::F(6) => {
+            app.toggle_pasted_blocks();
+            return LoopAction::Continue;
+        }
+        KeyCode::F(7) => {
+            app.toggle_command_mode();
+            return LoopAction::Continue;
+        }
+        KeyCode::F(8) => {
+            app.toggle_bookmark().await;
+            return LoopAction::Continue;
+        }
+        KeyCode::F(9) => {
+            app.toggle_notes_panel();
+            return LoopAction::Continue;
+        }
+        KeyCode::Char(c @ '1'..='9') if key.modifiers.contains(KeyModifiers::ALT) => {
+            app.jump_to_bookmark(c as usize - '0' as usize);
+            return LoopAction::Continue;
+        }
         KeyCode::Esc => {
+            if app.keymap.vim && app.focus == Focus::Chat && app.vim_mode == VimMode::Insert {
+                app.vim_mode = VimMode::Normal;
+                app.move_cursor_left();
+                return LoopAction::Continue;
+            }
+            if app.cancel_command_generation() {
+                return LoopAction::Continue;
+            }
+            if app.chat_search.is_some() {
+                app.clear_search();
+                return LoopAction::Continue;
+            }
             if app.show_help_view {
                 app.show_help_view = false;
                 return LoopAction::Continue;
             }
+            if app.zoomed_pane.is_some() {
+                app.zoomed_pane = None;
+                return LoopAction::Continue;
+            }
             if app.show_memory_view {
                 app.show_memory_view = false;
                 return LoopAction::Continue;
@@ -180,6 +329,12 @@ pub async fn handle_key_event(app: &mut AppStateContainer, key: KeyEvent) -> Loo
             export_selected_memory(app);
             return LoopAction::Continue;
         }
+        // Enter to edit the selected memory's content and category
+        KeyCode::Enter if app.show_memory_view => {
+            let idx = app.memory_graph_scroll.min(app.memory_graph.nodes.len().saturating_sub(1));
+            app.pending_memory_edit = app.memory_graph.nodes.get(idx).map(|node| node.memory.clone());
+            return LoopAction::Continue;
+        }
         // Memory view: real-time filter input (lowercase letters only, not action keys)
         KeyCode::Char(c) if app.show_memory_view && c.is_lowercase() && !matches!(c, 'r' | 'd' | 's' | 'e') => {
             app.memory_search_query.push(c);
@@ -253,15 +408,27 @@ pub async fn handle_key_event(app: &mut AppStateContainer, key: KeyEvent) -> Loo
 }
 
 async fn handle_chat_focus(app: &mut AppStateContainer, key: KeyEvent) -> LoopAction {
+    if app.keymap.vim && app.vim_mode == VimMode::Normal && app.handle_vim_normal_key(&key) {
+        return LoopAction::Continue;
+    }
+    // Search navigation ends at the first other key, which is typed as usual
+    let navigation_key = matches!(key.code, KeyCode::Char('n' | 'N'))
+        && !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+    if !navigation_key {
+        app.search_navigating = false;
+    }
     match key.code {
-        KeyCode::Enter => {
+        _ if app.keymap.is(Action::Submit, &key) => {
             mylm_core::info_log!("[EVENT_LOOP] Enter pressed in chat focus");
-            if !app.chat_input.is_empty() {
+            // Slash commands still work in command mode
+            if app.command_mode && !app.chat_input.starts_with('/') {
+                app.start_command_generation();
+            } else if !app.chat_input.is_empty() || !app.pasted_blocks.is_empty() {
                 mylm_core::debug_log!("[EVENT_LOOP] Input not empty, calling submit_message");
                 // Create a dummy event sender since we're handling directly
                 let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
                 app.submit_message(tx).await;
-            } else {
+            } else if !app.toggle_tool_result() {
                 mylm_core::debug_log!("[EVENT_LOOP] Input is empty, ignoring Enter");
             }
             LoopAction::Continue
@@ -293,22 +460,20 @@ async fn handle_chat_focus(app: &mut AppStateContainer, key: KeyEvent) -> LoopAc
             app.verbose_mode = !app.verbose_mode;
             LoopAction::Continue
         }
-        KeyCode::Left if key.modifiers.contains(KeyModifiers::CONTROL) && key.modifiers.contains(KeyModifiers::SHIFT) => {
-            app.adjust_chat_width(-5);
+        KeyCode::Char('k') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.kill_to_end();
             LoopAction::Continue
         }
-        KeyCode::Right if key.modifiers.contains(KeyModifiers::CONTROL) && key.modifiers.contains(KeyModifiers::SHIFT) => {
-            app.adjust_chat_width(5);
+        KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.open_search_prompt();
             LoopAction::Continue
         }
-        KeyCode::Char('k') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-            // Kill to end of line
-            let pos = app.cursor_position;
-            let char_count = app.chat_input.chars().count();
-            if pos < char_count {
-                let byte_start = app.chat_input.char_indices().nth(pos).map(|(i, _)| i).unwrap_or(0);
-                app.chat_input.truncate(byte_start);
-            }
+        KeyCode::Char('n') if app.search_navigating => {
+            app.next_search_match();
+            LoopAction::Continue
+        }
+        KeyCode::Char('N') if app.search_navigating => {
+            app.previous_search_match();
             LoopAction::Continue
         }
         KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
@@ -321,10 +486,18 @@ async fn handle_chat_focus(app: &mut AppStateContainer, key: KeyEvent) -> LoopAc
             }
             LoopAction::Continue
         }
+        KeyCode::Tab if app.chat_input.starts_with('/') => {
+            app.complete_slash_command();
+            LoopAction::Continue
+        }
         KeyCode::Char(c) => {
             app.enter_char(c);
             LoopAction::Continue
         }
+        KeyCode::Backspace if app.chat_input.is_empty() && !app.pasted_blocks.is_empty() => {
+            app.remove_last_pasted_block();
+            LoopAction::Continue
+        }
         KeyCode::Backspace => {
             app.delete_char();
             LoopAction::Continue
@@ -349,11 +522,11 @@ async fn handle_chat_focus(app: &mut AppStateContainer, key: KeyEvent) -> LoopAc
             app.move_cursor_end();
             LoopAction::Continue
         }
-        KeyCode::Up => {
+        _ if app.keymap.is(Action::ScrollUp, &key) => {
             app.scroll_chat_up();
             LoopAction::Continue
         }
-        KeyCode::Down => {
+        _ if app.keymap.is(Action::ScrollDown, &key) => {
             app.scroll_chat_down();
             LoopAction::Continue
         }
@@ -399,6 +572,27 @@ fn handle_terminal_focus(app: &mut AppStateContainer, key: KeyEvent) -> LoopActi
             app.handle_terminal_input(b"\t");
             LoopAction::Continue
         }
+        // Line editing keys, e.g. for a command typed in command mode
+        KeyCode::Left => {
+            app.handle_terminal_input(b"\x1b[D");
+            LoopAction::Continue
+        }
+        KeyCode::Right => {
+            app.handle_terminal_input(b"\x1b[C");
+            LoopAction::Continue
+        }
+        KeyCode::Home => {
+            app.handle_terminal_input(b"\x1b[H");
+            LoopAction::Continue
+        }
+        KeyCode::End => {
+            app.handle_terminal_input(b"\x1b[F");
+            LoopAction::Continue
+        }
+        KeyCode::Delete => {
+            app.handle_terminal_input(b"\x1b[3~");
+            LoopAction::Continue
+        }
         _ => LoopAction::Continue,
     }
 }
@@ -488,19 +682,26 @@ fn handle_jobs_focus(app: &mut AppStateContainer, key: KeyEvent) -> LoopAction {
 pub fn handle_mouse_event(app: &mut AppStateContainer, mouse: MouseEvent) {
     match mouse.kind {
         MouseEventKind::Down(_) => {
-            // Determine which pane was clicked
-            // This is simplified - in reality we'd need to track pane areas
+            // Grabbing the border between the panes resizes them
+            app.start_divider_drag(mouse.column, mouse.row);
         }
         MouseEventKind::Drag(_) => {
-            if app.is_selecting {
+            if app.dragging_divider {
+                app.drag_divider(mouse.column);
+            } else if app.is_selecting {
                 app.update_selection(mouse.column, mouse.row);
             }
         }
         MouseEventKind::Up(_) => {
-            if app.is_selecting {
+            if app.dragging_divider {
+                app.end_divider_drag();
+            } else if app.is_selecting {
                 app.end_selection();
             }
         }
+        MouseEventKind::Moved => {
+            app.mouse_position = Some((mouse.column, mouse.row));
+        }
         MouseEventKind::ScrollDown => {
             match app.focus {
                 Focus::Chat => app.scroll_chat_up(),
@@ -701,7 +902,7 @@ async fn star_selected_memory(app: &mut AppStateContainer) {
         return;
     };
     
-    match manager.vector_store().update_memory_category(memory_id, new_category.to_string()).await {
+    match manager.store().update_memory_category(memory_id, new_category.to_string()).await {
         Ok(_) => {
             mylm_core::info_log!("[MEMORY_VIEW] {} memory {}", 
                 if is_starred { "Unstarred" } else { "Starred" }, memory_id);
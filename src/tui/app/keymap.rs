@@ -0,0 +1,297 @@
+//! Configurable key bindings
+//!
+//! The keys for the main actions come from `[app.keymap.bindings]`, where
+//! each action lists its keys as `"f2, ctrl+o"`; an action missing there
+//! keeps its default keys. Keys are a name (`enter`, `esc`, `pageup`,
+//! `comma`, `f5`, ...) or a single character, with optional `ctrl+`, `alt+` and
+//! `shift+` in front; letters match either case. Everything else (text
+//! editing, the memory view, the F5-F9 panels) stays on fixed keys.
+//!
+//! `app.keymap.vim` turns on modal editing of the chat input; see
+//! `controls::vim`.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use mylm_core::config::KeymapSettings;
+
+/// Actions whose keys can be rebound
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    ToggleHelp,
+    /// Move focus between the terminal, chat and jobs panes
+    ToggleFocus,
+    ToggleMemory,
+    ToggleJobs,
+    /// Move the split between the terminal and chat panes
+    GrowChat,
+    ShrinkChat,
+    /// Put the chat on the other side of the terminal
+    SwapPanes,
+    /// Show only the focused pane until pressed again
+    ZoomPane,
+    /// Open a conversation tab, or move between them
+    NewTab,
+    NextTab,
+    PreviousTab,
+    /// Pin the message in view, or unpin it
+    PinMessage,
+    /// Expand or collapse the pinned panel
+    TogglePinned,
+    /// Open or close the scratchpad editor
+    ToggleScratchpad,
+    /// Scroll the chat while it has focus
+    ScrollUp,
+    ScrollDown,
+    /// Send the chat input
+    Submit,
+    /// Answer a tool approval prompt: once, for the session, saved to the
+    /// config, or deny
+    Approve,
+    ApproveSession,
+    ApproveAlways,
+    Deny,
+}
+
+impl Action {
+    pub const ALL: [Action; 21] = [
+        Action::ToggleHelp,
+        Action::ToggleFocus,
+        Action::ToggleMemory,
+        Action::ToggleJobs,
+        Action::GrowChat,
+        Action::ShrinkChat,
+        Action::SwapPanes,
+        Action::ZoomPane,
+        Action::NewTab,
+        Action::NextTab,
+        Action::PreviousTab,
+        Action::PinMessage,
+        Action::TogglePinned,
+        Action::ToggleScratchpad,
+        Action::ScrollUp,
+        Action::ScrollDown,
+        Action::Submit,
+        Action::Approve,
+        Action::ApproveSession,
+        Action::ApproveAlways,
+        Action::Deny,
+    ];
+
+    /// Name used in the config
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::ToggleHelp => "toggle_help",
+            Action::ToggleFocus => "toggle_focus",
+            Action::ToggleMemory => "toggle_memory",
+            Action::ToggleJobs => "toggle_jobs",
+            Action::GrowChat => "grow_chat",
+            Action::ShrinkChat => "shrink_chat",
+            Action::SwapPanes => "swap_panes",
+            Action::ZoomPane => "zoom_pane",
+            Action::NewTab => "new_tab",
+            Action::NextTab => "next_tab",
+            Action::PreviousTab => "previous_tab",
+            Action::PinMessage => "pin_message",
+            Action::TogglePinned => "toggle_pinned",
+            Action::ToggleScratchpad => "toggle_scratchpad",
+            Action::ScrollUp => "scroll_up",
+            Action::ScrollDown => "scroll_down",
+            Action::Submit => "submit",
+            Action::Approve => "approve",
+            Action::ApproveSession => "approve_session",
+            Action::ApproveAlways => "approve_always",
+            Action::Deny => "deny",
+        }
+    }
+
+    fn default_keys(self) -> &'static str {
+        match self {
+            Action::ToggleHelp => "f1",
+            Action::ToggleFocus => "f2",
+            Action::ToggleMemory => "f3",
+            Action::ToggleJobs => "f4",
+            Action::GrowChat => "ctrl+shift+right",
+            Action::ShrinkChat => "ctrl+shift+left",
+            Action::SwapPanes => "alt+s",
+            Action::ZoomPane => "alt+z",
+            Action::NewTab => "ctrl+t",
+            Action::NextTab => "ctrl+tab, alt+right",
+            Action::PreviousTab => "alt+left",
+            Action::PinMessage => "alt+p",
+            Action::TogglePinned => "f10",
+            Action::ToggleScratchpad => "f11",
+            Action::ScrollUp => "up",
+            Action::ScrollDown => "down",
+            Action::Submit => "enter",
+            Action::Approve => "y",
+            Action::ApproveSession => "a",
+            Action::ApproveAlways => "p",
+            Action::Deny => "n, esc",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+}
+
+/// A key with its modifiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    /// Parse `ctrl+o`, `shift+up`, `f2`, `pageup`, `y`, ...
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        // A lone `+` is the key itself, not a separator
+        while let Some((prefix, key)) = rest.split_once('+').filter(|(_, key)| !key.is_empty()) {
+            modifiers |= match prefix.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                other => bail!("unknown modifier '{}' in '{}'", other, text),
+            };
+            rest = key;
+        }
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "space" => KeyCode::Char(' '),
+                "comma" => KeyCode::Char(','),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                name => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => bail!("unknown key '{}'", text),
+                },
+            },
+        };
+        Ok(Self { code, modifiers })
+    }
+
+    /// Whether `key` is this binding; letters match either case and Shift
+    /// is ignored for characters, since terminals report it inconsistently
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        match (self.code, key.code) {
+            (KeyCode::Char(bound), KeyCode::Char(pressed)) => {
+                bound.eq_ignore_ascii_case(&pressed)
+                    && (self.modifiers - KeyModifiers::SHIFT) == (key.modifiers - KeyModifiers::SHIFT)
+            }
+            (bound, pressed) => bound == pressed && self.modifiers == key.modifiers,
+        }
+    }
+}
+
+/// Keys of every action
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: HashMap<Action, Vec<KeyBinding>>,
+    /// Modal editing of the chat input
+    pub vim: bool,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::from_settings(&KeymapSettings::default()).expect("default key bindings parse")
+    }
+}
+
+impl Keymap {
+    pub fn from_settings(settings: &KeymapSettings) -> Result<Self> {
+        let mut bindings = HashMap::new();
+        for action in Action::ALL {
+            bindings.insert(action, parse_keys(action.default_keys())?);
+        }
+        for (name, keys) in &settings.bindings {
+            let action = Action::from_name(name).ok_or_else(|| {
+                let names: Vec<&str> = Action::ALL.iter().map(|a| a.name()).collect();
+                anyhow!("unknown keymap action '{}' (actions: {})", name, names.join(", "))
+            })?;
+            let keys = parse_keys(keys).map_err(|e| anyhow!("keymap action '{}': {}", name, e))?;
+            bindings.insert(action, keys);
+        }
+        Ok(Self {
+            bindings,
+            vim: settings.vim,
+        })
+    }
+
+    /// Whether `key` is bound to `action`
+    pub fn is(&self, action: Action, key: &KeyEvent) -> bool {
+        self.bindings
+            .get(&action)
+            .is_some_and(|keys| keys.iter().any(|binding| binding.matches(key)))
+    }
+}
+
+fn parse_keys(keys: &str) -> Result<Vec<KeyBinding>> {
+    keys.split(',').filter(|key| !key.trim().is_empty()).map(KeyBinding::parse).collect()
+}
+
+/// Mode of the chat input with vim bindings on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VimMode {
+    #[default]
+    Insert,
+    Normal,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_keymap_defaults_and_overrides() {
+        assert_eq!(
+            KeyBinding::parse("ctrl+o").unwrap(),
+            KeyBinding { code: KeyCode::Char('o'), modifiers: KeyModifiers::CONTROL }
+        );
+        assert_eq!(KeyBinding::parse("shift+PageUp").unwrap().code, KeyCode::PageUp);
+        assert_eq!(KeyBinding::parse("f12").unwrap().code, KeyCode::F(12));
+        assert_eq!(KeyBinding::parse("+").unwrap().code, KeyCode::Char('+'));
+        assert!(KeyBinding::parse("f13").is_err());
+        assert!(KeyBinding::parse("hyper+x").is_err());
+
+        let defaults = Keymap::default();
+        assert!(defaults.is(Action::Approve, &key(KeyCode::Char('Y'), KeyModifiers::SHIFT)));
+        assert!(defaults.is(Action::Deny, &key(KeyCode::Esc, KeyModifiers::NONE)));
+        assert!(!defaults.is(Action::Submit, &key(KeyCode::Enter, KeyModifiers::ALT)));
+        assert!(!defaults.vim);
+
+        let mut settings = KeymapSettings { vim: true, ..Default::default() };
+        settings.bindings.insert("toggle_focus".into(), "ctrl+o, f2".into());
+        settings.bindings.insert("scroll_up".into(), "k".into());
+        let keymap = Keymap::from_settings(&settings).unwrap();
+        assert!(keymap.is(Action::ToggleFocus, &key(KeyCode::Char('o'), KeyModifiers::CONTROL)));
+        assert!(keymap.is(Action::ToggleFocus, &key(KeyCode::F(2), KeyModifiers::NONE)));
+        assert!(!keymap.is(Action::ToggleFocus, &key(KeyCode::Char('o'), KeyModifiers::NONE)));
+        assert!(!keymap.is(Action::ScrollUp, &key(KeyCode::Up, KeyModifiers::NONE)));
+
+        settings.bindings.insert("fly".into(), "x".into());
+        assert!(Keymap::from_settings(&settings).is_err());
+    }
+}
@@ -11,6 +11,7 @@ pub mod approval;
 pub mod controls;
 
 pub mod event_loop;
+pub mod keymap;
 
 pub mod pty;
 pub mod session;
@@ -18,6 +19,7 @@ pub mod session_manager;
 pub mod state;
 pub mod status_tracker;
 pub mod terminal_executor;
+pub mod theme;
 pub mod types;
 pub mod ui;
 pub mod app;
@@ -1,9 +1,12 @@
 //! PTY (Pseudo-Terminal) Management Module
 //!
 //! Handles spawning and managing pseudo-terminals for the TUI.
-//! Uses portable-pty for cross-platform PTY support.
+//! Uses portable-pty for cross-platform PTY support: a Unix PTY running
+//! bash, or a ConPTY running PowerShell on Windows (see `Shell::interactive`).
 
 use anyhow::Result;
+use mylm_core::agent::runtime::core::{Shell, ShellKind};
+use mylm_core::environment::multiplexer::PtyScrollback;
 use portable_pty::{native_pty_system, CommandBuilder, PtySize, MasterPty};
 use std::io::Write;
 use std::path::PathBuf;
@@ -28,19 +31,18 @@ impl PtyManager {
             pixel_height: 0,
         })?;
 
-        let shell = if cfg!(target_os = "windows") {
-            "powershell.exe"
-        } else {
-            "bash"
-        };
-
-        let mut cmd = CommandBuilder::new(shell);
+        let shell = Shell::interactive();
+        let mut cmd = CommandBuilder::new(&shell.program);
+        if shell.kind == ShellKind::PowerShell {
+            cmd.arg("-NoLogo");
+        }
         if let Some(cwd) = cwd {
             cmd.cwd(cwd);
         }
         let _child = pair.slave.spawn_command(cmd)?;
 
-        // Move the reader to a separate thread
+        // Move the reader to a separate thread; the output is also kept as
+        // the scrollback fallback for context capture outside a multiplexer
         let mut reader = pair.master.try_clone_reader()?;
         thread::spawn(move || {
             let mut buffer = [0u8; 1024];
@@ -48,6 +50,7 @@ impl PtyManager {
                 if n == 0 {
                     break;
                 }
+                PtyScrollback::record(&buffer[..n]);
                 if tx.send(buffer[..n].to_vec()).is_err() {
                     break;
                 }
@@ -1,5 +1,6 @@
 use std::time::{Duration, Instant};
 use mylm_core::provider::TokenUsage;
+use mylm_core::agent::SessionBookmark;
 use mylm_core::provider::chat::ChatMessage;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
@@ -31,6 +32,18 @@ pub struct SessionMetadata {
     pub cost: f64,
     #[serde(default)]
     pub elapsed_seconds: u64,
+    /// Generated session title
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Messages the user bookmarked
+    #[serde(default)]
+    pub bookmarks: Vec<SessionBookmark>,
+    /// Indices of the messages the user pinned
+    #[serde(default)]
+    pub pins: Vec<usize>,
+    /// Model that wrote each AI response, by message index
+    #[serde(default)]
+    pub models: std::collections::BTreeMap<usize, String>,
 }
 
 impl Default for SessionMetadata {
@@ -43,6 +56,10 @@ impl Default for SessionMetadata {
             output_tokens: 0,
             cost: 0.0,
             elapsed_seconds: 0,
+            title: None,
+            bookmarks: Vec::new(),
+            pins: Vec::new(),
+            models: Default::default(),
         }
     }
 }
@@ -53,6 +70,8 @@ pub struct SessionStats {
     pub input_tokens: u32,
     pub output_tokens: u32,
     pub total_tokens: u32,
+    /// Input tokens the provider served from its prompt cache
+    pub cached_tokens: u32,
     pub cost: f64,
     pub start_time: Instant,
     pub base_duration: Duration,
@@ -70,6 +89,7 @@ impl Default for SessionStats {
             input_tokens: 0,
             output_tokens: 0,
             total_tokens: 0,
+            cached_tokens: 0,
             cost: 0.0,
             start_time: Instant::now(),
             base_duration: Duration::from_secs(0),
@@ -123,23 +143,36 @@ impl SessionMonitor {
     }
 
     /// Add usage from a single LLM interaction
-    
     pub fn add_usage(&mut self, usage: &TokenUsage, input_price_1m: f64, output_price_1m: f64) {
         self.stats.input_tokens += usage.prompt_tokens;
         self.stats.output_tokens += usage.completion_tokens;
         self.stats.total_tokens += usage.total_tokens;
+        self.stats.cached_tokens += usage.cached_tokens;
 
         // Active context is what the LLM just processed (prompt + completion)
         self.stats.active_context_tokens = usage.total_tokens;
 
+        self.stats.cost += self.cost_of(usage, input_price_1m, output_price_1m);
+    }
+
+    /// History lost about `tokens_saved` tokens to condensation
+    ///
+    /// The gauge drops now rather than at the next reply's usage report.
+    pub fn context_condensed(&mut self, tokens_saved: usize) {
+        let saved = u32::try_from(tokens_saved).unwrap_or(u32::MAX);
+        self.stats.active_context_tokens = self.stats.active_context_tokens.saturating_sub(saved);
+    }
+
+    /// Estimated cost of `usage`, at the given prices or the stored ones
+    pub fn cost_of(&self, usage: &TokenUsage, input_price_1m: f64, output_price_1m: f64) -> f64 {
         // Cost per token = price_per_1m / 1,000,000
         // Use provided prices or fall back to stored prices
         let input_price = if input_price_1m > 0.0 { input_price_1m } else { self.stats.input_price_per_million };
         let output_price = if output_price_1m > 0.0 { output_price_1m } else { self.stats.output_price_per_million };
-        
+
         let input_cost = usage.prompt_tokens as f64 * (input_price / 1_000_000.0);
         let output_cost = usage.completion_tokens as f64 * (output_price / 1_000_000.0);
-        self.stats.cost += input_cost + output_cost;
+        input_cost + output_cost
     }
 
     /// Set pricing for cost calculation
@@ -148,6 +181,14 @@ impl SessionMonitor {
         self.stats.output_price_per_million = output_price_per_million;
     }
 
+    /// Share of input tokens read from the provider's prompt cache
+    pub fn cache_hit_ratio(&self) -> f64 {
+        if self.stats.input_tokens == 0 {
+            return 0.0;
+        }
+        self.stats.cached_tokens as f64 / self.stats.input_tokens as f64
+    }
+
     #[allow(dead_code)]
     pub fn get_context_ratio(&self) -> f64 {
         if self.stats.max_context_tokens == 0 {
@@ -7,13 +7,16 @@
 // Re-export types from the types module (authoritative source)
 pub use crate::tui::app::types::{
     PtyManager, JobRegistry,
-    StreamState, AppState, Focus,
+    AppState, Focus,
     TimestampedChatMessage,
 };
 use mylm_core::agent::{OutputEvent, UserInput};
 use mylm_core::agent::memory::AgentMemoryManager;
+use mylm_core::agent::runtime::core::Shell;
+use mylm_core::agent::types::parser::ShortKeyStreamParser;
 use mylm_core::conversation::ContextManager;
 use mylm_core::memory::graph::MemoryGraph;
+use mylm_core::provider::{FollowUpPipeline, LlmClient};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
@@ -24,6 +27,9 @@ use tokio::sync::mpsc;
 use crate::tui::app::session::SessionMonitor;
 use crate::tui::app::session_manager::SessionManager;
 
+/// Raw terminal output kept while the session is suspended
+const SUSPENDED_RAW_BUFFER_BYTES: usize = 256 * 1024;
+
 #[derive(Debug, Clone)]
 pub struct TerminalDelegate;
 
@@ -54,11 +60,37 @@ pub struct AppStateContainer {
     pub terminal_scroll: usize,
     pub terminal_auto_scroll: bool,
     pub terminal_history: Vec<String>,
+    /// Notes the agent attached to terminal lines with `annotate_terminal`
+    pub terminal_annotations: Vec<mylm_core::agent::tools::TerminalAnnotation>,
+    /// Annotation whose popup is open (F5 cycles)
+    pub selected_annotation: Option<usize>,
+    /// Last mouse position, for annotation popups on hover
+    pub mouse_position: Option<(u16, u16)>,
     pub raw_buffer: Vec<u8>,
 
     // Chat state
     pub chat_input: String,
     pub cursor_position: usize,
+    /// Pastes attached to the next message instead of typed into the input
+    pub pasted_blocks: Vec<crate::tui::app::controls::paste::PastedBlock>,
+    /// Whether the pasted blocks popup is open (F6)
+    pub pasted_blocks_expanded: bool,
+    /// Messages become commands typed into the terminal (F7)
+    pub command_mode: bool,
+    /// Command currently being typed into the terminal
+    pub command_stream: Option<crate::tui::app::controls::command_line::CommandStream>,
+    /// Assistant messages bookmarked with F8, saved in the session metadata
+    pub bookmarks: Vec<mylm_core::agent::SessionBookmark>,
+    /// Whether the notes panel listing the bookmarks is open (F9)
+    pub show_notes_panel: bool,
+    /// Indices of pinned messages, kept in the agent's context (Alt+P)
+    pub pins: Vec<usize>,
+    /// Whether the pinned panel lists the pins or only counts them (F10)
+    pub show_pinned: bool,
+    /// Message to bring into view on the next render (Alt+1..9)
+    pub chat_jump_to: Option<usize>,
+    /// First visual line of each rendered message, as (message index, line)
+    pub chat_message_lines: Vec<(usize, usize)>,
     pub chat_history: Vec<TimestampedChatMessage>,
     pub chat_scroll: usize,
     pub chat_auto_scroll: bool,
@@ -96,6 +128,18 @@ pub struct AppStateContainer {
     
     pub exit_name_input: String,
     pub chat_width_percent: u16,
+    /// Chat drawn left of the terminal
+    pub chat_left: bool,
+    /// Pane shown alone until zoom is toggled off
+    pub zoomed_pane: Option<Focus>,
+    /// Terminal size class whose saved layout is in use
+    pub layout_class: Option<&'static str>,
+    /// Area shared by the terminal and chat panes, and the column between
+    /// them, from the last draw
+    pub panes_area: Option<Rect>,
+    pub pane_divider: Option<u16>,
+    /// The divider is being dragged with the mouse
+    pub dragging_divider: bool,
     #[allow(dead_code)]
     pub show_terminal: bool,
     #[allow(dead_code)]
@@ -128,6 +172,8 @@ pub struct AppStateContainer {
     // Using contract session for agent communication
     #[allow(dead_code)]
     pub agent_session_factory: Option<mylm_core::agent::factory::AgentSessionFactory>,
+    /// The running config, project overlay merged in; never saved as a
+    /// whole, changes go to the file through `Config::update_default`
     pub config: mylm_core::config::Config,
     pub session_monitor: SessionMonitor,
     pub session_id: String,
@@ -164,18 +210,8 @@ pub struct AppStateContainer {
     #[allow(dead_code)]
     pub pending_command_tx: Option<tokio::sync::oneshot::Sender<String>>,
 
-    #[allow(dead_code)]
-    pub stream_state: Option<StreamState>,
-    
-    // Streaming parser state fields
-    #[allow(dead_code)]
-    pub stream_escape_next: bool,
-    #[allow(dead_code)]
-    pub stream_key_buffer: String,
-    #[allow(dead_code)]
-    pub stream_lookback: String,
-    #[allow(dead_code)]
-    pub stream_thought: Option<String>,
+    /// Incremental Short-Key parser for the response being streamed
+    pub stream_parser: ShortKeyStreamParser,
     
     // Current response buffer for streaming
     #[allow(dead_code)]
@@ -196,8 +232,37 @@ pub struct AppStateContainer {
     pub verbose_mode: bool,
     #[allow(dead_code)]
     pub show_thoughts: bool,
+    /// Show assistant messages as raw markdown instead of rendered (`/raw`)
+    pub show_raw_markdown: bool,
+    /// Show tokens, cost and latency under each AI response (`/usage`)
+    pub show_reply_usage: bool,
+    /// Colours the panes are drawn with (`/theme`)
+    pub theme: crate::tui::app::theme::Theme,
+    /// Keys of the rebindable actions
+    pub keymap: crate::tui::app::keymap::Keymap,
+    /// Mode of the chat input when vim bindings are on
+    pub vim_mode: crate::tui::app::keymap::VimMode,
+    /// Term of the chat search in progress
+    pub chat_search: Option<String>,
+    /// Chat lines containing the search term, filled in when drawing
+    pub search_hits: Vec<usize>,
+    /// Index into `search_hits` of the current match
+    pub search_match: Option<usize>,
+    /// Scroll to the current match on the next draw
+    pub search_jump: bool,
+    /// n/N step through matches until another key is pressed
+    pub search_navigating: bool,
+    /// Match ranges of the search term by chat line
+    pub search_cache: crate::tui::app::controls::search::MatchCache,
+    /// Tab completion being cycled: the input before the first Tab and the
+    /// index of the match shown
+    pub slash_completion: Option<(String, usize)>,
+    /// Tools running, by intent ID: name, arguments and start time
+    pub running_tools: std::collections::HashMap<u64, (String, String, Instant)>,
     #[allow(dead_code)]
     pub auto_approve: Arc<AtomicBool>,
+    /// Approval rules added from prompts ("always allow")
+    pub approval_rules: mylm_core::agent::runtime::governance::SessionRules,
     pub incognito: bool,
     
     // Animation frame counter for status bar
@@ -226,6 +291,9 @@ pub struct AppStateContainer {
     #[allow(dead_code)]
     pub last_terminal_snapshot: Option<String>,
     
+    /// Git status and system info already sent, so later turns only carry changes
+    pub context_tracker: mylm_core::environment::ContextTracker,
+    
     /// Phase 4: Agent session factory for creating sessions
     #[allow(dead_code)]
     pub session_factory: Option<mylm_core::agent::factory::AgentSessionFactory>,
@@ -262,15 +330,83 @@ pub struct AppStateContainer {
     
     /// Flag to request session save
     pub save_session_request: bool,
-    
-    /// Streaming state - currently in final answer
-    pub stream_in_final: bool,
+
+    /// `/teach` was entered; holds the text to prefill in the editor
+    pub pending_teach: Option<String>,
+    /// Memory to open in the editor from the F3 view, done by the event loop
+    pub pending_memory_edit: Option<mylm_core::memory::store::Memory>,
+
+    /// Tab change asked for by a key or `/tab`, done by the event loop
+    pub tab_request: Option<crate::tui::tabs::TabRequest>,
+
+    /// `/edit` or `/retry` was entered, done by the event loop
+    pub rewind_request: Option<crate::tui::app::controls::rewind::RewindRequest>,
+    /// What went with the last message sent, for `/edit` and `/retry`
+    pub last_submission: Option<crate::tui::app::controls::rewind::Submission>,
+    /// Models being fetched for `/model`
+    pub model_listing: Option<crate::tui::app::controls::model::ModelListing>,
+    /// Labels of the open tabs when there is more than one, and which is shown
+    pub tab_labels: Vec<String>,
+    pub tab_index: usize,
+
+    /// Images added with `/attach`, sent with the next message
+    pub pending_images: Vec<crate::tui::app::controls::paste::AttachedImage>,
+
+    /// Cost/token budget of the running agent session, shown by `/budget`
+    pub budget: Option<Arc<mylm_core::agent::runtime::governance::SessionBudget>>,
+
+    /// Changeset queue of the running agent session, managed with `/queue`
+    pub patch_queue: Option<Arc<mylm_core::agent::tools::PatchQueue>>,
+
+    /// Undo journal of the running agent session, rolled back with `/undo`
+    pub undo_journal: Option<Arc<mylm_core::agent::tools::UndoJournal>>,
+
+    /// Worker pool of the running agent session, shown in the jobs panel
+    pub worker_pool: Option<Arc<mylm_core::scheduler::WorkerPool>>,
+
+    /// Plan of the running agent session, shown as a checklist and edited with `/plan`
+    pub plan: Option<Arc<mylm_core::agent::runtime::capabilities::SessionPlan>>,
+
+    /// Scratchpad of the running agent session, edited in the F11 view
+    pub scratchpad: Option<mylm_core::agent::tools::SharedScratchpad>,
+    /// The scratchpad view, while open
+    pub scratchpad_editor: Option<crate::tui::app::controls::scratchpad::ScratchpadEditor>,
+
+    /// File watcher for `features.watch` triggers
+    pub file_watcher: Option<mylm_core::watch::FileWatcher>,
+
+    /// Fired watch triggers waiting for the agent to be idle
+    pub pending_triggers: std::collections::VecDeque<mylm_core::watch::TriggerFire>,
+
+    /// Watcher reporting edits to the config file and `.mylm.yaml`
+    pub config_watcher: Option<mylm_core::config::ConfigWatcher>,
+
+    /// Applies reloaded config to the running agent session
+    pub reloader: Option<Arc<mylm_core::agent::SessionReloader>>,
+
+    /// Time since the last input or agent activity (`features.idle`)
+    pub idle: mylm_core::idle::IdleTimer,
+
+    /// Resources of the running agent session released while idle
+    pub idle_resources: Option<Arc<mylm_core::idle::IdleResources>>,
     
     /// Session active flag - false when session has halted
     pub session_active: bool,
     
     /// Status tracker for deriving UI state from output events
     pub status_tracker: crate::tui::app::status_tracker::StatusTracker,
+
+    /// Secondary LLM calls (session title) running alongside the UI
+    pub follow_ups: Option<FollowUpPipeline>,
+
+    /// Client for command mode
+    pub command_client: Option<Arc<LlmClient>>,
+
+    /// Generated session title, once the follow-up call has finished
+    pub session_title: Option<String>,
+
+    /// Title generation has been started for this session
+    pub title_requested: bool,
 }
 
 impl AppStateContainer {
@@ -297,12 +433,22 @@ impl AppStateContainer {
 
         let session_id = uuid::Uuid::new_v4().to_string();
         let pacore_enabled = config.features.pacore.enabled;
+        let theme = crate::tui::app::theme::Theme::load(&config, &config.app.theme).unwrap_or_else(|e| {
+            mylm_core::warn_log!("[THEME] {}; using the dark theme", e);
+            crate::tui::app::theme::Theme::default()
+        });
+        let keymap = crate::tui::app::keymap::Keymap::from_settings(&config.app.keymap).unwrap_or_else(|e| {
+            mylm_core::warn_log!("[KEYMAP] {}; using the default keys", e);
+            crate::tui::app::keymap::Keymap::default()
+        });
         let pacore_rounds = config.features.pacore.rounds;
 
         // Create context manager with actual config values and pricing
         // System prompt tokens are estimated at ~800 (tools + instructions + date)
+        let model = config.resolve_profile().model.unwrap_or_default();
         let ctx_config = mylm_core::conversation::ContextConfig::new(max_ctx)
-            .with_pricing(input_price, output_price);
+            .with_pricing(input_price, output_price)
+            .with_tokenizer(mylm_core::conversation::Tokenizer::for_model(&model));
         let context_manager = ContextManager::new(ctx_config);
 
         // Initialize shared memory manager (if memory feature enabled and not incognito)
@@ -311,6 +457,9 @@ impl AppStateContainer {
             let memory_config = MemoryConfig {
                 enabled: true,
                 incognito: false,
+                embedding: config.features.memory_settings.embedding.clone(),
+                retention: config.features.memory_settings.retention.clone(),
+                backend: config.features.memory_settings.backend,
                 ..MemoryConfig::default()
             };
             match AgentMemoryManager::new(memory_config).await {
@@ -328,6 +477,36 @@ impl AppStateContainer {
             None
         };
 
+        let command_client = match mylm_core::config::default_llm_config(&config)
+            .map_err(anyhow::Error::from)
+            .and_then(LlmClient::new)
+        {
+            Ok(client) => Some(Arc::new(client)),
+            Err(e) => {
+                mylm_core::warn_log!("[STATE] Follow-up pipeline and command mode disabled: {}", e);
+                None
+            }
+        };
+        // Titles are opt-in and go to the worker model. Secondary calls are
+        // skipped in incognito mode so nothing about the conversation leaves
+        // the main request path
+        let follow_ups = if config.features.session_titles && !incognito {
+            match mylm_core::config::worker_llm_config(&config)
+                .map_err(anyhow::Error::from)
+                .and_then(LlmClient::new)
+            {
+                Ok(client) => Some(FollowUpPipeline::new(Arc::new(client))),
+                Err(e) => {
+                    mylm_core::warn_log!("[STATE] Session titles disabled: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let idle = mylm_core::idle::IdleTimer::new(&config.features.idle);
+        let time_context = mylm_core::environment::TimeContext::detect(&config.features.time);
         let app = Self {
             terminal_parser: vt100::Parser::new(24, 80, 5000), // 5000 lines scrollback
             pty_manager,
@@ -335,6 +514,16 @@ impl AppStateContainer {
             agent_session_factory: None,
             chat_input: String::new(),
             cursor_position: 0,
+            pasted_blocks: Vec::new(),
+            pasted_blocks_expanded: false,
+            command_mode: false,
+            command_stream: None,
+            bookmarks: Vec::new(),
+            pins: Vec::new(),
+            show_pinned: false,
+            show_notes_panel: false,
+            chat_jump_to: None,
+            chat_message_lines: Vec::new(),
             chat_history: Vec::new(),
             chat_visual_lines: Vec::new(),
             chat_history_start_col: None,
@@ -355,11 +544,7 @@ impl AppStateContainer {
             status_message: None,
             state_started_at: Instant::now(),
             activity_log: Vec::new(),
-            stream_state: None,
-            stream_escape_next: false,
-            stream_key_buffer: String::new(),
-            stream_lookback: String::new(),
-            stream_thought: None,
+            stream_parser: ShortKeyStreamParser::new(),
             current_response: String::new(),
             response_start_time: None,
 
@@ -367,7 +552,21 @@ impl AppStateContainer {
             interrupt_flag: Arc::new(AtomicBool::new(false)),
             verbose_mode,
             show_thoughts: true,
+            show_raw_markdown: false,
+            show_reply_usage: true,
+            theme,
+            keymap,
+            vim_mode: Default::default(),
+            chat_search: None,
+            search_hits: Vec::new(),
+            search_match: None,
+            search_jump: false,
+            search_navigating: false,
+            search_cache: Default::default(),
+            slash_completion: None,
+            running_tools: Default::default(),
             auto_approve,
+            approval_rules: Default::default(),
             active_task: None,
             capturing_command_output: false,
             command_output_buffer: String::new(),
@@ -376,6 +575,9 @@ impl AppStateContainer {
             output_price,
             tick_count: 0,
             terminal_history: Vec::new(),
+            terminal_annotations: Vec::new(),
+            selected_annotation: None,
+            mouse_position: None,
             pending_echo_suppression: String::new(),
             pending_clean_command: None,
             raw_buffer: Vec::new(),
@@ -398,6 +600,12 @@ impl AppStateContainer {
             jobs_list_scroll: 0,
             active_worker_count: 0,
             chat_width_percent: 30,
+            chat_left: false,
+            zoomed_pane: None,
+            layout_class: None,
+            panes_area: None,
+            pane_divider: None,
+            dragging_divider: false,
             show_terminal: true,
             selection_start: None,
             selection_end: None,
@@ -414,6 +622,7 @@ impl AppStateContainer {
             session_manager: SessionManager::new(),
             incognito,
             last_terminal_snapshot: None,
+            context_tracker: mylm_core::environment::ContextTracker::new().with_time_context(time_context),
             // Phase 4 fields
             session_factory: None,
             chat_session_handle: None,
@@ -426,9 +635,34 @@ impl AppStateContainer {
             pending_approval_with_response: None,
             _pending_suggestion: None,
             save_session_request: false,
-            stream_in_final: false,
+            pending_teach: None,
+            pending_memory_edit: None,
+            tab_request: None,
+            rewind_request: None,
+            last_submission: None,
+            model_listing: None,
+            tab_labels: Vec::new(),
+            tab_index: 0,
+            pending_images: Vec::new(),
+            budget: None,
+            patch_queue: None,
+            undo_journal: None,
+            worker_pool: None,
+            plan: None,
+            scratchpad: None,
+            scratchpad_editor: None,
+            file_watcher: None,
+            pending_triggers: std::collections::VecDeque::new(),
+            config_watcher: None,
+            reloader: None,
+            idle,
+            idle_resources: None,
             session_active: true,
             status_tracker: crate::tui::app::status_tracker::StatusTracker::new(),
+            follow_ups,
+            command_client,
+            session_title: None,
+            title_requested: false,
             // Memory provider - currently initialized on-demand in event_loop.rs
             memory_provider: None,
             status_animation_frame: 0,
@@ -465,7 +699,6 @@ impl AppStateContainer {
         }
     }
 
-    #[allow(dead_code)]
     pub fn process_terminal_data(&mut self, data: &[u8]) {
         // Reduced logging to avoid I/O overhead
         // mylm_core::info_log!("process_terminal_data: processing {} bytes", data.len());
@@ -485,6 +718,59 @@ impl AppStateContainer {
         self.terminal_parser = new_parser;
     }
 
+    /// Input or agent activity; brings back the scrollback an idle
+    /// suspension dropped (the memory store reopens on its own)
+    pub fn note_activity(&mut self) {
+        if self.idle.touch(Instant::now()) {
+            mylm_core::info_log!("[IDLE] Resuming suspended session");
+            self.restore_terminal_buffers();
+        }
+    }
+
+    /// Release the session's resources once it has been idle long enough
+    pub fn suspend_if_idle(&mut self) {
+        if self.state != AppState::Idle {
+            self.note_activity();
+            return;
+        }
+        if !self.idle.is_due(Instant::now()) {
+            return;
+        }
+        self.idle.set_suspended();
+        let mut released = self.idle_resources.as_ref().map(|r| r.suspend_all()).unwrap_or_default();
+        if self.memory_manager.as_ref().is_some_and(|mm| mm.release_store()) {
+            released.push("memory panel store".to_string());
+        }
+        self.shrink_terminal_buffers();
+        mylm_core::info_log!("[IDLE] Suspended idle session (released: {})", released.join(", "));
+    }
+
+    /// Drop terminal scrollback, keeping the screen and the recent output
+    fn shrink_terminal_buffers(&mut self) {
+        if self.raw_buffer.len() > SUSPENDED_RAW_BUFFER_BYTES {
+            let cut = self.raw_buffer.len() - SUSPENDED_RAW_BUFFER_BYTES;
+            // Start at a line so no escape sequence is cut in half
+            let cut = self.raw_buffer[cut..]
+                .iter()
+                .position(|b| *b == b'\n')
+                .map_or(cut, |i| cut + i + 1);
+            self.raw_buffer.drain(..cut);
+        }
+        self.raw_buffer.shrink_to_fit();
+        let (rows, cols) = self.terminal_parser.screen().size();
+        let mut parser = vt100::Parser::new(rows, cols, 0);
+        parser.process(&self.terminal_parser.screen().contents_formatted());
+        self.terminal_parser = parser;
+    }
+
+    /// Rebuild the scrollback from the output kept while suspended
+    fn restore_terminal_buffers(&mut self) {
+        let (rows, cols) = self.terminal_parser.screen().size();
+        let mut parser = vt100::Parser::new(rows, cols, 5000); // 5000 lines scrollback
+        parser.process(&self.raw_buffer);
+        self.terminal_parser = parser;
+    }
+
     pub fn toggle_focus(&mut self) {
         self.focus = match self.focus {
             Focus::Terminal => Focus::Chat,
@@ -514,9 +800,9 @@ impl AppStateContainer {
             let _ = tx.send("Error: Command aborted by user".to_string());
             if self.capturing_command_output {
                 let _ = self.pty_manager.write_all(&[3, 13]);
-                let _ = self
-                    .pty_manager
-                    .write_all(b"([ -t 0 ] && stty echo) 2>/dev/null\r");
+                if let Some(restore) = Shell::interactive().restore_echo() {
+                    let _ = self.pty_manager.write_all(restore);
+                }
             }
         }
         self.capturing_command_output = false;
@@ -540,12 +826,14 @@ impl AppStateContainer {
             session_data.id, session_data.history.len());
         
         // Convert core Message to UI ChatMessage using the role string
+        let tools_expanded = self.config.app.tool_results.expanded;
         self.chat_history = session_data.history.iter().map(|m| {
             let chat_msg = ChatMessage {
                 role: match m.role.as_str() {
                     "user" => mylm_core::provider::chat::MessageRole::User,
                     "assistant" => mylm_core::provider::chat::MessageRole::Assistant,
                     "system" => mylm_core::provider::chat::MessageRole::System,
+                    "tool" => mylm_core::provider::chat::MessageRole::Tool,
                     _ => mylm_core::provider::chat::MessageRole::User,
                 },
                 content: m.content.clone(),
@@ -553,8 +841,18 @@ impl AppStateContainer {
                 tool_call_id: None,
                 tool_calls: None,
                 reasoning_content: None,
+                images: Vec::new(),
             };
-            TimestampedChatMessage::new(chat_msg)
+            let mut msg = TimestampedChatMessage::new(chat_msg);
+            if m.role == "tool" {
+                msg.tool_run = Some(crate::tui::app::types::ToolRun {
+                    tool: "tool".to_string(),
+                    args: String::new(),
+                    duration_ms: None,
+                    expanded: tools_expanded,
+                });
+            }
+            msg
         }).collect();
         
         // Update context manager with restored history
@@ -575,7 +873,16 @@ impl AppStateContainer {
             output_tokens: session_data.metadata.output_tokens,
             cost: session_data.metadata.cost,
             elapsed_seconds: session_data.metadata.elapsed_seconds,
+            title: session_data.metadata.title.clone(),
+            bookmarks: session_data.metadata.bookmarks.clone(),
+            pins: session_data.metadata.pins.clone(),
+            models: session_data.metadata.models.clone(),
         };
+        self.bookmarks = session_data.metadata.bookmarks.clone();
+        self.pins = session_data.metadata.pins.clone();
+        crate::tui::app::controls::model::restore_message_models(&mut self.chat_history, &metadata.models);
+        self.session_title = session_data.metadata.title.clone();
+        self.title_requested = self.session_title.is_some();
         self.session_monitor.resume_stats(&metadata, self.config.active_profile().context_window as u32);
         
         mylm_core::info_log!("[STATE] Session restored successfully");
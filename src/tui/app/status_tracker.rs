@@ -74,7 +74,7 @@ impl StatusInfo {
                 } else {
                     args.clone()
                 };
-                format!("Approve: {} {}? (y/n)", tool, args_preview)
+                format!("Approve: {} {}? (y/n/a/p)", tool, args_preview)
             }
         }
     }
@@ -302,6 +302,10 @@ impl StatusTracker {
                 self.last_activity = Instant::now();
                 mylm_core::debug_log!("[STATUS_TRACKER] Memory retrieved: {} results for '{}'", result_count, query);
             }
+            
+            OutputEvent::ConfigChanged { .. } | OutputEvent::TerminalAnnotations { .. } => {
+                self.last_activity = Instant::now();
+            }
         }
     }
 
@@ -10,7 +10,8 @@
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use async_trait::async_trait;
-use mylm_core::agent::runtime::core::terminal::TerminalExecutor;
+use mylm_core::agent::runtime::core::terminal::{ExecError, TerminalExecutor};
+use mylm_core::agent::runtime::core::Shell;
 
 use crate::tui::app::state::AppStateContainer;
 
@@ -57,23 +58,18 @@ impl TuiTerminalExecutor {
 
 #[async_trait]
 impl TerminalExecutor for TuiTerminalExecutor {
-    async fn execute_command(&self, command: String, _timeout: Option<Duration>) -> Result<String, String> {
+    async fn execute_command(&self, command: String, timeout: Option<Duration>) -> Result<String, ExecError> {
         // Get screen before command
         let screen_before = self.get_screen().await.unwrap_or_default();
         
         // Execute using std::process::Command for reliability
         // In the future, this could use the PTY for true shared session
-        let output = if cfg!(target_os = "windows") {
-            tokio::process::Command::new("cmd")
-                .args(["/C", &command])
-                .output()
-                .await
-        } else {
-            tokio::process::Command::new("sh")
-                .args(["-c", &command])
-                .output()
-                .await
-        }.map_err(|e| format!("Failed to execute command: {}", e))?;
+        let child = Shell::for_commands().command(&command).kill_on_drop(true).output();
+        let output = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, child).await.map_err(|_| ExecError::TimedOut)?,
+            None => child.await,
+        }
+        .map_err(|e| ExecError::Failed(format!("Failed to execute command: {}", e)))?;
         
         let mut result = String::new();
         
@@ -107,7 +103,7 @@ impl TerminalExecutor for TuiTerminalExecutor {
             Ok(combined)
         } else {
             let exit_code = output.status.code().unwrap_or(-1);
-            Err(format!("Exit code {}: {}", exit_code, combined))
+            Err(ExecError::Failed(format!("Exit code {}: {}", exit_code, combined)))
         }
     }
     
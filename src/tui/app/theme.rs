@@ -0,0 +1,319 @@
+//! Colour themes for the TUI
+//!
+//! A theme gives a colour to each role the panes draw with: borders, the
+//! status bar, user and assistant messages, approvals and so on. Four are
+//! built in; more can be defined under `[app.themes.<name>]` in the config
+//! or as `<config dir>/themes/<name>.toml`, each setting some roles over a
+//! base theme. `app.theme` picks the theme the TUI starts with and `/theme`
+//! switches it while running.
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context, Result};
+use mylm_core::config::{Config, ThemeDefinition};
+use ratatui::style::Color;
+
+/// Names of the built-in themes
+pub const BUILTIN: [&str; 4] = ["dark", "light", "solarized", "high-contrast"];
+
+/// Roles a theme definition can set
+pub const ROLES: [&str; 17] = [
+    "text",
+    "muted",
+    "border",
+    "border_focused",
+    "user",
+    "assistant",
+    "system",
+    "accent",
+    "info",
+    "warning",
+    "error",
+    "success",
+    "approval",
+    "selection_fg",
+    "selection_bg",
+    "badge_fg",
+    "badge_bg",
+];
+
+/// How many `base` links a definition may follow
+const MAX_BASE_DEPTH: usize = 8;
+
+/// Colours by role
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    /// Live terminal output
+    pub text: Color,
+    /// Timestamps, hints, scrollback and toggles that are off
+    pub muted: Color,
+    pub border: Color,
+    /// Border of the focused pane
+    pub border_focused: Color,
+    pub user: Color,
+    pub assistant: Color,
+    pub system: Color,
+    /// Tool activity, actions and headings
+    pub accent: Color,
+    /// Scroll indicators
+    pub info: Color,
+    pub warning: Color,
+    pub error: Color,
+    pub success: Color,
+    /// Approval prompts
+    pub approval: Color,
+    pub selection_fg: Color,
+    pub selection_bg: Color,
+    /// The `mylm` badge in the status bar
+    pub badge_fg: Color,
+    pub badge_bg: Color,
+    /// syntect theme fenced code blocks are highlighted with
+    pub code: &'static str,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            name: "dark".to_string(),
+            text: Color::White,
+            muted: Color::DarkGray,
+            border: Color::Reset,
+            border_focused: Color::Yellow,
+            user: Color::Cyan,
+            assistant: Color::Green,
+            system: Color::Gray,
+            accent: Color::Cyan,
+            info: Color::Blue,
+            warning: Color::Yellow,
+            error: Color::Red,
+            success: Color::Green,
+            approval: Color::Magenta,
+            selection_fg: Color::Black,
+            selection_bg: Color::Cyan,
+            badge_fg: Color::Black,
+            badge_bg: Color::Green,
+            code: "base16-ocean.dark",
+        }
+    }
+}
+
+impl Theme {
+    /// A built-in theme; `default` is the dark one
+    pub fn builtin(name: &str) -> Option<Self> {
+        let dark = Self::default();
+        let theme = match name {
+            "dark" | "default" => dark,
+            "light" => Self {
+                name: name.to_string(),
+                text: Color::Black,
+                muted: Color::Rgb(118, 118, 118),
+                border_focused: Color::Blue,
+                user: Color::Rgb(0, 95, 175),
+                assistant: Color::Rgb(0, 128, 0),
+                system: Color::Rgb(88, 88, 88),
+                accent: Color::Rgb(0, 128, 128),
+                warning: Color::Rgb(175, 95, 0),
+                error: Color::Rgb(175, 0, 0),
+                success: Color::Rgb(0, 128, 0),
+                approval: Color::Rgb(135, 0, 135),
+                selection_fg: Color::White,
+                selection_bg: Color::Blue,
+                badge_fg: Color::White,
+                badge_bg: Color::Rgb(0, 128, 0),
+                code: "InspiredGitHub",
+                ..dark
+            },
+            "solarized" => Self {
+                name: name.to_string(),
+                text: Color::Rgb(0x83, 0x94, 0x96),
+                muted: Color::Rgb(0x58, 0x6e, 0x75),
+                border: Color::Rgb(0x58, 0x6e, 0x75),
+                border_focused: Color::Rgb(0xb5, 0x89, 0x00),
+                user: Color::Rgb(0x26, 0x8b, 0xd2),
+                assistant: Color::Rgb(0x85, 0x99, 0x00),
+                system: Color::Rgb(0x93, 0xa1, 0xa1),
+                accent: Color::Rgb(0x2a, 0xa1, 0x98),
+                info: Color::Rgb(0x6c, 0x71, 0xc4),
+                warning: Color::Rgb(0xb5, 0x89, 0x00),
+                error: Color::Rgb(0xdc, 0x32, 0x2f),
+                success: Color::Rgb(0x85, 0x99, 0x00),
+                approval: Color::Rgb(0xd3, 0x36, 0x82),
+                selection_fg: Color::Rgb(0x00, 0x2b, 0x36),
+                selection_bg: Color::Rgb(0x2a, 0xa1, 0x98),
+                badge_fg: Color::Rgb(0x00, 0x2b, 0x36),
+                badge_bg: Color::Rgb(0x85, 0x99, 0x00),
+                code: "Solarized (dark)",
+            },
+            "high-contrast" => Self {
+                name: name.to_string(),
+                text: Color::White,
+                muted: Color::Gray,
+                border: Color::White,
+                border_focused: Color::LightYellow,
+                user: Color::LightCyan,
+                assistant: Color::LightGreen,
+                system: Color::White,
+                accent: Color::LightCyan,
+                info: Color::LightBlue,
+                warning: Color::LightYellow,
+                error: Color::LightRed,
+                success: Color::LightGreen,
+                approval: Color::LightMagenta,
+                selection_fg: Color::Black,
+                selection_bg: Color::LightYellow,
+                badge_fg: Color::Black,
+                badge_bg: Color::White,
+                code: "base16-eighties.dark",
+            },
+            _ => return None,
+        };
+        Some(theme)
+    }
+
+    /// Theme `name`, looked up in the config, then the theme files, then the
+    /// built-ins
+    pub fn load(config: &Config, name: &str) -> Result<Self> {
+        Self::load_at_depth(config, name, 0)
+    }
+
+    fn load_at_depth(config: &Config, name: &str, depth: usize) -> Result<Self> {
+        if depth > MAX_BASE_DEPTH {
+            bail!("Theme '{}' has too many base themes (is there a loop?)", name);
+        }
+        let definition = match config.app.themes.get(name) {
+            Some(definition) => Some(definition.clone()),
+            None => read_theme_file(name)?,
+        };
+        let Some(definition) = definition else {
+            return Self::builtin(name).ok_or_else(|| {
+                anyhow!("Unknown theme '{}' (available: {})", name, Self::available(config).join(", "))
+            });
+        };
+        // A definition named after a built-in adjusts that built-in
+        let base = definition.base.as_deref().unwrap_or(if Self::builtin(name).is_some() { "" } else { "dark" });
+        let mut theme = if base.is_empty() {
+            Self::builtin(name).unwrap_or_default()
+        } else {
+            Self::load_at_depth(config, base, depth + 1)?
+        };
+        theme.name = name.to_string();
+        for (role, value) in &definition.colors {
+            let color = Color::from_str(value.trim())
+                .map_err(|_| anyhow!("Theme '{}': '{}' is not a colour (for {})", name, value, role))?;
+            theme.set(role, color).map_err(|e| anyhow!("Theme '{}': {}", name, e))?;
+        }
+        Ok(theme)
+    }
+
+    fn set(&mut self, role: &str, color: Color) -> Result<()> {
+        let slot = match role {
+            "text" => &mut self.text,
+            "muted" => &mut self.muted,
+            "border" => &mut self.border,
+            "border_focused" => &mut self.border_focused,
+            "user" => &mut self.user,
+            "assistant" => &mut self.assistant,
+            "system" => &mut self.system,
+            "accent" => &mut self.accent,
+            "info" => &mut self.info,
+            "warning" => &mut self.warning,
+            "error" => &mut self.error,
+            "success" => &mut self.success,
+            "approval" => &mut self.approval,
+            "selection_fg" => &mut self.selection_fg,
+            "selection_bg" => &mut self.selection_bg,
+            "badge_fg" => &mut self.badge_fg,
+            "badge_bg" => &mut self.badge_bg,
+            _ => bail!("unknown role '{}' (roles: {})", role, ROLES.join(", ")),
+        };
+        *slot = color;
+        Ok(())
+    }
+
+    /// Every theme `/theme` can switch to
+    pub fn available(config: &Config) -> Vec<String> {
+        let mut names: Vec<String> = BUILTIN.iter().map(|name| name.to_string()).collect();
+        names.extend(config.app.themes.keys().cloned());
+        if let Some(dir) = themes_dir() {
+            if let Ok(entries) = std::fs::read_dir(dir) {
+                for entry in entries.flatten() {
+                    let path = entry.path();
+                    if path.extension().is_some_and(|ext| ext == "toml") {
+                        if let Some(stem) = path.file_stem() {
+                            names.push(stem.to_string_lossy().into_owned());
+                        }
+                    }
+                }
+            }
+        }
+        let mut seen = std::collections::HashSet::new();
+        names.retain(|name| seen.insert(name.clone()));
+        names
+    }
+}
+
+fn themes_dir() -> Option<std::path::PathBuf> {
+    mylm_core::config::get_config_dir().map(|dir| dir.join("themes"))
+}
+
+/// `<config dir>/themes/<name>.toml`, if there is one
+fn read_theme_file(name: &str) -> Result<Option<ThemeDefinition>> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Ok(None);
+    }
+    let Some(path) = themes_dir().map(|dir| dir.join(format!("{}.toml", name))) else {
+        return Ok(None);
+    };
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(&path).with_context(|| format!("Cannot read {}", path.display()))?;
+    let definition = toml::from_str(&content).with_context(|| format!("Invalid theme file {}", path.display()))?;
+    Ok(Some(definition))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definition(base: Option<&str>, colors: &[(&str, &str)]) -> ThemeDefinition {
+        ThemeDefinition {
+            base: base.map(str::to_string),
+            colors: colors.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        }
+    }
+
+    #[test]
+    fn test_builtin_and_custom_themes() {
+        for name in BUILTIN {
+            assert_eq!(Theme::builtin(name).unwrap().name, name);
+        }
+        assert_eq!(Theme::builtin("default"), Some(Theme::default()));
+        assert_eq!(Theme::builtin("neon"), None);
+
+        let mut config = Config::default();
+        config.app.themes.insert("paper".into(), definition(Some("light"), &[("user", "#112233"), ("border", "red")]));
+        config.app.themes.insert("dark".into(), definition(None, &[("approval", "202")]));
+        config.app.themes.insert("typo".into(), definition(None, &[("usr", "red")]));
+        config.app.themes.insert("bad".into(), definition(None, &[("user", "not-a-colour")]));
+        config.app.themes.insert("loop".into(), definition(Some("loop"), &[]));
+
+        let paper = Theme::load(&config, "paper").unwrap();
+        assert_eq!(paper.name, "paper");
+        assert_eq!(paper.user, Color::Rgb(0x11, 0x22, 0x33));
+        assert_eq!(paper.border, Color::Red);
+        assert_eq!(paper.text, Color::Black);
+        assert_eq!(paper.code, "InspiredGitHub");
+
+        let dark = Theme::load(&config, "dark").unwrap();
+        assert_eq!(dark.approval, Color::Indexed(202));
+        assert_eq!(dark.user, Color::Cyan);
+
+        assert!(Theme::load(&config, "typo").is_err());
+        assert!(Theme::load(&config, "bad").is_err());
+        assert!(Theme::load(&config, "loop").is_err());
+        assert!(Theme::load(&config, "neon").is_err());
+        assert!(Theme::available(&config).contains(&"paper".to_string()));
+    }
+}
@@ -20,6 +20,7 @@ pub use mylm_core::provider::TokenUsage;
 
 // Use the real ChatMessage from mylm_core (with fallback to stub for compatibility)
 pub use mylm_core::provider::chat::ChatMessage;
+use mylm_core::provider::chat::MessageRole;
 
 // Memory types
 // pub use mylm_core::memory::graph::MemoryGraph;  // Currently unused
@@ -48,6 +49,61 @@ pub struct TimestampedChatMessage {
     pub timestamp: i64,
     /// Generation time in milliseconds (for AI responses)
     pub generation_time_ms: Option<u64>,
+    /// Set on tool results, which show as a one-line summary until expanded
+    pub tool_run: Option<ToolRun>,
+    /// Model that wrote it (for AI responses)
+    pub model: Option<String>,
+    /// Tokens and cost of the call that produced it (for AI responses)
+    pub usage: Option<ReplyUsage>,
+}
+
+/// What one AI response cost
+#[derive(Debug, Clone, Default)]
+pub struct ReplyUsage {
+    pub tokens: TokenUsage,
+    /// Estimated, in USD
+    pub cost: f64,
+}
+
+impl ReplyUsage {
+    /// Add the usage of one LLM call of the current turn
+    ///
+    /// A turn with tool calls takes several calls. The total goes on the
+    /// turn's last assistant message, even when a tool result follows it;
+    /// earlier assistant messages of the turn give theirs up to it.
+    pub fn add_to_turn(history: &mut [TimestampedChatMessage], tokens: TokenUsage, cost: f64) {
+        let turn_start = history
+            .iter()
+            .rposition(|m| m.message.role == MessageRole::User)
+            .map_or(0, |i| i + 1);
+        let turn = &mut history[turn_start..];
+        let Some(last) = turn.iter().rposition(|m| m.message.role == MessageRole::Assistant) else {
+            return;
+        };
+        let mut total = ReplyUsage { tokens, cost };
+        for message in turn.iter_mut() {
+            if let Some(earlier) = message.usage.take() {
+                total.tokens.prompt_tokens += earlier.tokens.prompt_tokens;
+                total.tokens.completion_tokens += earlier.tokens.completion_tokens;
+                total.tokens.total_tokens += earlier.tokens.total_tokens;
+                total.tokens.cached_tokens += earlier.tokens.cached_tokens;
+                total.cost += earlier.cost;
+            }
+        }
+        turn[last].usage = Some(total);
+    }
+}
+
+/// How a tool result shows in the chat
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolRun {
+    pub tool: String,
+    /// Arguments the tool was called with
+    pub args: String,
+    /// How long it ran, when known
+    pub duration_ms: Option<u64>,
+    /// Showing the whole result rather than the summary
+    pub expanded: bool,
 }
 
 impl TimestampedChatMessage {
@@ -57,6 +113,9 @@ impl TimestampedChatMessage {
             message,
             timestamp: chrono::Utc::now().timestamp(),
             generation_time_ms: None,
+            tool_run: None,
+            model: None,
+            usage: None,
         }
     }
     
@@ -75,12 +134,27 @@ impl TimestampedChatMessage {
         Self::new(ChatMessage::system(content))
     }
     
+    /// Create a tool result
+    pub fn tool_result(intent_id: u64, run: ToolRun, result: impl Into<String>) -> Self {
+        let message = ChatMessage::tool(intent_id.to_string(), run.tool.clone(), result);
+        Self {
+            tool_run: Some(run),
+            ..Self::new(message)
+        }
+    }
+
     /// Set the generation time
     pub fn with_generation_time(mut self, ms: u64) -> Self {
         self.generation_time_ms = Some(ms);
         self
     }
     
+    /// Record the model that wrote it
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
     /// Get formatted timestamp for display
     pub fn formatted_time(&self) -> String {
         use chrono::{DateTime, Local, Utc};
@@ -369,6 +443,7 @@ pub enum TuiEvent {
     #[allow(dead_code)]
     Pty(Vec<u8>),
     /// Agent response event
+    #[allow(dead_code)]
     AgentResponse(#[allow(dead_code)] ChatMessage, #[allow(dead_code)] TokenUsage),
     /// Tool output event
     #[allow(dead_code)]
@@ -386,50 +461,6 @@ pub enum TuiEvent {
     StatusUpdate(String),
 }
 
-// ---------------------------------------------------------------------------
-// Streaming Parser Types (TUI JSON streaming)
-// ---------------------------------------------------------------------------
-
-/// Streaming JSON parser state machine for LLM response parsing
-/// 
-/// Uses pattern-based detection:
-/// - `{"t": "..."` for thought field
-/// - `: "..."` for final answer (after "f" key)
-#[derive(Debug, Clone, Copy, PartialEq, Default)]
-
-pub enum StreamState {
-    #[default]
-    /// Looking for {"t": pattern
-    LookingForThought,
-    /// Found {, looking for "t":
-    #[allow(dead_code)]
-    SawOpenBrace,
-    /// Found {"t, looking for "":
-    #[allow(dead_code)]
-    SawThoughtT,
-    /// Found {"t":, waiting for thought value
-    #[allow(dead_code)]
-    ExpectingThoughtValue,
-    /// Streaming thought content
-    #[allow(dead_code)]
-    InThoughtValue,
-    /// Looking for "f": pattern after thought or at start
-    #[allow(dead_code)]
-    LookingForFinal,
-    /// Found "f", looking for :
-    #[allow(dead_code)]
-    SawFinalF,
-    /// Found "f":, waiting for final value
-    #[allow(dead_code)]
-    ExpectingFinalValue,
-    /// Streaming final answer content
-    #[allow(dead_code)]
-    InFinalValue,
-    /// Finished streaming final - ignore rest
-    #[allow(dead_code)]
-    Done,
-}
-
 // ---------------------------------------------------------------------------
 // PTY Types (Connection/Networking Layer)
 // ---------------------------------------------------------------------------
@@ -440,3 +471,32 @@ pub use crate::tui::app::pty::PtyManager;
 
 /// Spawn a new PTY with the given working directory
 pub use crate::tui::app::pty::spawn_pty;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turn_usage_accumulates_on_last_reply() {
+        let tokens = |total| TokenUsage { prompt_tokens: total - 1, completion_tokens: 1, total_tokens: total, cached_tokens: 0 };
+        let run = ToolRun { tool: "shell".to_string(), args: String::new(), duration_ms: None, expanded: false };
+        let mut history = vec![
+            TimestampedChatMessage::user("first"),
+            TimestampedChatMessage::assistant("done"),
+            TimestampedChatMessage::user("list files"),
+            TimestampedChatMessage::assistant("running ls"),
+        ];
+        ReplyUsage::add_to_turn(&mut history, tokens(10), 0.5);
+        ReplyUsage::add_to_turn(&mut history[..2], tokens(7), 0.1);
+        history.push(TimestampedChatMessage::tool_result(1, run, "a.txt"));
+        // The tool result is last; the reply before it keeps the usage
+        ReplyUsage::add_to_turn(&mut history, tokens(20), 1.0);
+        assert_eq!(history[3].usage.as_ref().map(|u| (u.tokens.total_tokens, u.cost)), Some((30, 1.5)));
+        history.push(TimestampedChatMessage::assistant("a.txt is there"));
+        ReplyUsage::add_to_turn(&mut history, tokens(5), 0.25);
+        assert!(history[3].usage.is_none());
+        assert_eq!(history[5].usage.as_ref().map(|u| u.tokens.total_tokens), Some(35));
+        // The previous turn is untouched
+        assert_eq!(history[1].usage.as_ref().map(|u| u.tokens.total_tokens), Some(7));
+    }
+}
@@ -2,12 +2,16 @@
 
 use crate::tui::app::state::AppStateContainer as App;
 use crate::tui::app::types::{AppState, Focus};
-use mylm_core::provider::chat::MessageRole;
+use super::{highlight, markdown};
+use crate::tui::app::controls::tool_results;
+use crate::tui::app::theme::Theme;
+use std::collections::HashMap;
+use mylm_core::provider::chat::{image_marker_path, MessageRole};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
     Frame,
 };
 
@@ -20,6 +24,8 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
 
     // Clear visual lines mapping at the start of rendering
     app.chat_visual_lines.clear();
+    app.chat_message_lines.clear();
+    let theme = app.theme.clone();
 
     let input_width = area.width.saturating_sub(2) as usize;
     let input_content = if app.state != AppState::Idle && app.state != AppState::WaitingForUser {
@@ -77,7 +83,7 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
             );
 
             let progress_widget = Paragraph::new(bar_str)
-                .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+                .style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD))
                 .alignment(ratatui::layout::Alignment::Center);
 
             frame.render_widget(progress_widget, chunks[1]);
@@ -99,22 +105,31 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
         prefix_len: usize,
         prefix_style: Style,
         content_style: Style,
+        /// Per-character styles of highlighted code, aligned with `full_text`
+        char_styles: Option<Vec<Style>>,
     }
 
     let mut all_visual_lines: Vec<VisualLineInfo> = Vec::new();
     let mut abs_line_idx: usize = 0;
+    let search_needle = app.chat_search.as_ref().map(|term| term.to_lowercase());
 
-    for msg_meta in &app.chat_history {
+    for (msg_idx, msg_meta) in app.chat_history.iter().enumerate() {
         let m = &msg_meta.message;
+        // Messages matching the search show what verbose mode would
+        let matched = search_needle.as_ref().is_some_and(|needle| m.content.to_lowercase().contains(needle));
+        let verbose = app.verbose_mode || matched;
+        // Tool results show as a summary line, and in full once expanded
+        let tool_run = msg_meta.tool_run.as_ref();
+        let tool_expanded = tool_run.is_some_and(|run| run.expanded || matched);
         // Aggressively hide command outputs in non-verbose mode
-        if !app.verbose_mode && m.content.contains("CMD_OUTPUT:") {
+        if tool_run.is_none() && !verbose && m.content.contains("CMD_OUTPUT:") {
             if m.role == MessageRole::Tool
                 || (m.role == MessageRole::User && m.content.contains("Observation:"))
             {
                 // Placeholder line: "AI: Command executed. Check terminal."
                 let prefix = "AI: ";
                 let prefix_len = prefix.len();
-                let prefix_style = Style::default().fg(Color::Green).add_modifier(Modifier::BOLD);
+                let prefix_style = Style::default().fg(theme.assistant).add_modifier(Modifier::BOLD);
                 let content = "Command executed. Check terminal.";
                 let full_text = format!("{}{}", prefix, content);
                 all_visual_lines.push(VisualLineInfo {
@@ -122,8 +137,9 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
                     prefix_len,
                     prefix_style,
                     content_style: Style::default()
-                        .fg(Color::DarkGray)
+                        .fg(theme.muted)
                         .add_modifier(Modifier::ITALIC),
+                    char_styles: None,
                 });
                 app.chat_visual_lines.push((full_text, abs_line_idx));
                 abs_line_idx += 1;
@@ -133,6 +149,7 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
                     prefix_len: 0,
                     prefix_style: Style::default(),
                     content_style: Style::default(),
+                    char_styles: None,
                 });
                 app.chat_visual_lines.push((String::new(), abs_line_idx));
                 abs_line_idx += 1;
@@ -141,7 +158,8 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
         }
 
         // Skip Tool messages for commands in non-verbose mode
-        if !app.verbose_mode
+        if !verbose
+            && tool_run.is_none()
             && m.role == MessageRole::Tool
             && m.name.as_deref() == Some("execute_command")
         {
@@ -157,10 +175,11 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
         });
 
         let (role_prefix, color) = match m.role {
-            MessageRole::User => ("You: ", Color::Cyan),
-            MessageRole::Assistant => ("AI: ", Color::Green),
-            MessageRole::System => ("Sys: ", Color::Gray),
-            _ => ("AI: ", Color::Green),
+            MessageRole::User => ("You: ", theme.user),
+            MessageRole::Assistant => ("AI: ", theme.assistant),
+            MessageRole::System => ("Sys: ", theme.system),
+            MessageRole::Tool if tool_run.is_some() => ("Tool: ", theme.accent),
+            _ => ("AI: ", theme.assistant),
         };
 
         // Prefix is just the role (timestamp shown at bottom of message)
@@ -172,9 +191,29 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
 
         let mut lines_to_render = Vec::new();
 
+        let pinned = app.pins.contains(&msg_idx);
+        if let Some(run) = tool_run {
+            let marker = match (tool_expanded, pinned) {
+                (true, _) => "▾",
+                (false, true) => "▸ 📌",
+                (false, false) => "▸",
+            };
+            let status_color = if tool_results::is_failure(&m.content) { theme.error } else { theme.muted };
+            lines_to_render.push((
+                format!("{} {}", marker, tool_results::summary(run, &m.content)),
+                Style::default().fg(status_color),
+            ));
+            if tool_expanded {
+                let output = Style::default().fg(theme.text);
+                lines_to_render.extend(m.content.lines().map(|line| (line.replace('\r', ""), output)));
+            }
+        }
+
         // Hide Context Packs (Terminal Snapshot, etc.)
         let delimiter = "\n\n## Terminal Snapshot";
-        let raw_display_content = if let Some(idx) = m.content.find(delimiter) {
+        let raw_display_content = if tool_run.is_some() {
+            ""
+        } else if let Some(idx) = m.content.find(delimiter) {
             &m.content[..idx]
         } else {
             m.content.as_str()
@@ -209,16 +248,60 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
                 raw_display_content.to_string()
             };
 
-        let raw_lines: Vec<&str> = processed_content.split('\n').collect();
+        let raw_lines: Vec<&str> = if tool_run.is_some() {
+            Vec::new()
+        } else {
+            processed_content.split('\n').collect()
+        };
+
+        // Fenced code block being collected: (language, lines)
+        let mut code_block: Option<(String, Vec<String>)> = None;
+        let mut code_blocks_seen = 0;
+        // Rows of the markdown table being collected
+        let mut table_rows: Vec<String> = Vec::new();
+        // Per-character styles of highlighted and markdown lines, by index
+        // into `lines_to_render`
+        let mut span_styles: HashMap<usize, Vec<Style>> = HashMap::new();
+        let render_markdown = m.role == MessageRole::Assistant && !app.show_raw_markdown;
 
         for raw_line in raw_lines {
             let line = raw_line.replace('\r', "");
+            if !table_rows.is_empty() && !markdown::is_table_row(&line) {
+                push_styled(&mut lines_to_render, &mut span_styles, markdown::render_table(&table_rows, Style::default(), &theme));
+                table_rows.clear();
+            }
+            let fence = highlight::fence_lang(&line).filter(|_| !app.show_raw_markdown);
+            if let Some(lang) = fence {
+                match code_block.take() {
+                    Some((lang, code)) => push_code(&mut lines_to_render, &mut span_styles, &lang, code, &theme),
+                    None => {
+                        code_blocks_seen += 1;
+                        lines_to_render.push((
+                            highlight::label(code_blocks_seen, lang),
+                            Style::default().fg(theme.muted),
+                        ));
+                        code_block = Some((lang.to_string(), Vec::new()));
+                    }
+                }
+                continue;
+            }
+            if let Some((_, code)) = code_block.as_mut() {
+                code.push(highlight::display_line(&line));
+                continue;
+            }
             let trimmed = line.trim();
             if trimmed.is_empty() {
                 lines_to_render.push((line, Style::default()));
                 continue;
             }
 
+            // Attached images show as a placeholder thumbnail
+            if let Some(path) = image_marker_path(trimmed) {
+                let style = Style::default().fg(Color::Magenta);
+                lines_to_render.extend(image_placeholder(&path).into_iter().map(|l| (l, style)));
+                continue;
+            }
+
             let is_thought = trimmed.starts_with("Thought:")
                 || trimmed.starts_with("**Thought:**")
                 || trimmed.starts_with("💭");
@@ -227,7 +310,7 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
                 let thought_style = Style::default()
                     .fg(Color::Rgb(128, 128, 128))
                     .add_modifier(Modifier::ITALIC);
-                if app.verbose_mode {
+                if verbose {
                     lines_to_render.push((line, thought_style));
                 }
                 continue;
@@ -242,17 +325,17 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
                     let has_final = val.get("f").is_some();
 
                     if has_thought || has_action || has_final {
-                        if app.verbose_mode && has_thought {
+                        if verbose && has_thought {
                             if let Some(t) = val.get("t").and_then(|v| v.as_str()) {
                                 lines_to_render.push((
                                     format!("Thought: {}", t),
                                     Style::default()
-                                        .fg(Color::DarkGray)
+                                        .fg(theme.muted)
                                         .add_modifier(Modifier::ITALIC),
                                 ));
                             }
                         }
-                        if has_action && app.verbose_mode {
+                        if has_action && verbose {
                             if let Some(a) = val.get("a").and_then(|v| v.as_str()) {
                                 let i = val
                                     .get("i")
@@ -260,7 +343,7 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
                                     .unwrap_or_default();
                                 lines_to_render.push((
                                     format!("Action: {} ({})", a, i),
-                                    Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+                                    Style::default().fg(theme.accent).add_modifier(Modifier::BOLD),
                                 ));
                             }
                         }
@@ -273,7 +356,7 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
             if is_action {
                 lines_to_render.push((
                     line,
-                    Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+                    Style::default().fg(theme.accent).add_modifier(Modifier::BOLD),
                 ));
                 continue;
             }
@@ -281,16 +364,16 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
             let is_action_input = trimmed.starts_with("Action Input:")
                 || trimmed.starts_with("**Action Input:**");
             if is_action_input {
-                if !app.verbose_mode {
+                if !verbose {
                     continue;
                 }
-                lines_to_render.push((line, Style::default().fg(Color::DarkGray)));
+                lines_to_render.push((line, Style::default().fg(theme.muted)));
                 continue;
             }
 
             let is_observation = trimmed.starts_with("Observation:")
                 || trimmed.starts_with("**Observation:**");
-            if !app.verbose_mode && (is_observation || trimmed.contains("CMD_OUTPUT:")) {
+            if !verbose && (is_observation || trimmed.contains("CMD_OUTPUT:")) {
                 continue;
             }
 
@@ -300,11 +383,31 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
                 let content = line
                     .replace("Final Answer:", "")
                     .replace("**Final Answer:**", "");
-                lines_to_render.push((content.trim().to_string(), Style::default()));
+                let content = content.trim().to_string();
+                if render_markdown {
+                    let rendered = markdown::render_line(&content, Style::default(), &theme);
+                    push_styled(&mut lines_to_render, &mut span_styles, vec![rendered]);
+                } else {
+                    lines_to_render.push((content, Style::default()));
+                }
                 continue;
             }
 
-            lines_to_render.push((line, Style::default()));
+            if render_markdown && markdown::is_table_row(&line) {
+                table_rows.push(line);
+            } else if render_markdown {
+                let rendered = markdown::render_line(&line, Style::default(), &theme);
+                push_styled(&mut lines_to_render, &mut span_styles, vec![rendered]);
+            } else {
+                lines_to_render.push((line, Style::default()));
+            }
+        }
+        // A block still open (a reply being streamed) is shown as it is so far
+        if let Some((lang, code)) = code_block.take() {
+            push_code(&mut lines_to_render, &mut span_styles, &lang, code, &theme);
+        }
+        if !table_rows.is_empty() {
+            push_styled(&mut lines_to_render, &mut span_styles, markdown::render_table(&table_rows, Style::default(), &theme));
         }
 
         // Skip AI messages that would have no visible content (before adding timestamp)
@@ -314,8 +417,16 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
             continue;
         }
 
-        // Add timestamp at bottom for all messages, with generation time for AI
-        let bottom_text = if m.role == MessageRole::Assistant {
+        app.chat_message_lines.push((msg_idx, abs_line_idx));
+
+        // Add timestamp at bottom for all messages, with generation time for
+        // AI responses unless the usage footer shows it
+        let usage_footer = if m.role == MessageRole::Assistant && app.show_reply_usage {
+            super::utils::format_reply_usage(msg_meta.usage.as_ref(), msg_meta.generation_time_ms)
+        } else {
+            None
+        };
+        let mut bottom_text = if m.role == MessageRole::Assistant && usage_footer.is_none() {
             if let Some(ref gen_time) = gen_time_str {
                 format!("[{}] took {}", timestamp_str, gen_time)
             } else {
@@ -324,21 +435,39 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
         } else {
             format!("[{}]", timestamp_str)
         };
-        // Right-align the timestamp
+        if let Some(model) = msg_meta.model.as_deref().filter(|_| m.role == MessageRole::Assistant) {
+            bottom_text = format!("{} · {}", bottom_text, model);
+        }
+        if let Some(note) = app.bookmarks.iter().position(|b| b.message == msg_idx) {
+            bottom_text = format!("🔖 {} {}", note + 1, bottom_text);
+        }
+        if pinned {
+            bottom_text = format!("📌 {}", bottom_text);
+        }
+        // Right-align the timestamp; a collapsed tool result stays one line
         let padding = available_width.saturating_sub(prefix_len).saturating_sub(bottom_text.len());
         let padded_bottom = format!("{}{}", " ".repeat(padding), bottom_text);
-        lines_to_render.push((
-            padded_bottom,
-            Style::default()
-                .fg(Color::DarkGray)
-                .add_modifier(Modifier::ITALIC),
-        ));
+        if tool_run.is_none() || tool_expanded {
+            lines_to_render.push((
+                padded_bottom,
+                Style::default()
+                    .fg(theme.muted)
+                    .add_modifier(Modifier::ITALIC),
+            ));
+        }
+        if let Some(footer) = usage_footer {
+            let padding = available_width.saturating_sub(prefix_len).saturating_sub(footer.chars().count());
+            lines_to_render.push((
+                format!("{}{}", " ".repeat(padding), footer),
+                Style::default().fg(theme.muted).add_modifier(Modifier::DIM),
+            ));
+        }
 
         // Process lines for visual representation
         // Subtract prefix_len to account for indentation on continuation lines
         let content_width = available_width.saturating_sub(prefix.len()).saturating_sub(prefix_len);
         let mut first_line_flag = true;
-        for (text, style) in lines_to_render {
+        for (line_idx, (text, style)) in lines_to_render.into_iter().enumerate() {
             if text.is_empty() {
                 if first_line_flag {
                     // Skip empty lines at the start (don't render "AI:" alone)
@@ -350,6 +479,7 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
                     prefix_len: 0,
                     prefix_style: Style::default(),
                     content_style: Style::default(),
+                    char_styles: None,
                 });
                 app.chat_visual_lines.push((String::new(), abs_line_idx));
                 abs_line_idx += 1;
@@ -357,6 +487,7 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
             }
 
             let wrapped = super::utils::wrap_text(&text, content_width);
+            let spans = span_styles.remove(&line_idx).map(|styles| wrap_styles(&text, &styles, &wrapped));
             for (wrapped_idx, line_str) in wrapped.iter().enumerate() {
                 let is_first = first_line_flag && wrapped_idx == 0;
                 let full_text = if is_first {
@@ -367,11 +498,18 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
                     format!("{}{}", " ".repeat(prefix_len), line_str)
                 };
                 let current_prefix_style = if is_first { prefix_style } else { Style::default() };
+                let char_styles = spans.as_ref().map(|spans| {
+                    let lead = full_text.chars().count() - line_str.chars().count();
+                    let mut styles = vec![style; lead];
+                    styles.extend_from_slice(&spans[wrapped_idx]);
+                    styles
+                });
                 all_visual_lines.push(VisualLineInfo {
                     full_text: full_text.clone(),
                     prefix_len: if is_first { prefix.len() } else { prefix_len },
                     prefix_style: current_prefix_style,
                     content_style: style,
+                    char_styles,
                 });
                 app.chat_visual_lines.push((full_text, abs_line_idx));
                 abs_line_idx += 1;
@@ -384,6 +522,7 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
             prefix_len: 0,
             prefix_style: Style::default(),
             content_style: Style::default(),
+            char_styles: None,
         });
         app.chat_visual_lines.push((String::new(), abs_line_idx));
         abs_line_idx += 1;
@@ -399,11 +538,12 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
             full_text: "── Action Stamps ──".to_string(),
             prefix_len: 0,
             prefix_style: Style::default()
-                .fg(Color::DarkGray)
+                .fg(theme.muted)
                 .add_modifier(Modifier::ITALIC),
             content_style: Style::default()
-                .fg(Color::DarkGray)
+                .fg(theme.muted)
                 .add_modifier(Modifier::ITALIC),
+            char_styles: None,
         });
         app.chat_visual_lines.push(("── Action Stamps ──".to_string(), abs_line_idx));
         abs_line_idx += 1;
@@ -411,16 +551,16 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
         // Render each stamp as a line item
         for stamp in recent_stamps.iter().rev().take(5) {
             let color = match stamp.stamp_type {
-                ActionStampType::ToolSuccess => Color::Green,
-                ActionStampType::ToolFailed => Color::Red,
-                ActionStampType::ContextCondensed => Color::Yellow,
+                ActionStampType::ToolSuccess => theme.success,
+                ActionStampType::ToolFailed => theme.error,
+                ActionStampType::ContextCondensed => theme.warning,
                 ActionStampType::MemoryRecalled => Color::Magenta,
-                ActionStampType::FileRead => Color::Cyan,
-                ActionStampType::FileWritten => Color::Blue,
-                ActionStampType::CommandExecuted => Color::Yellow,
-                ActionStampType::WebSearch => Color::Cyan,
-                ActionStampType::Thinking => Color::DarkGray,
-                ActionStampType::TaskComplete => Color::Green,
+                ActionStampType::FileRead => theme.accent,
+                ActionStampType::FileWritten => theme.info,
+                ActionStampType::CommandExecuted => theme.warning,
+                ActionStampType::WebSearch => theme.accent,
+                ActionStampType::Thinking => theme.muted,
+                ActionStampType::TaskComplete => theme.success,
             };
 
             let icon = stamp.stamp_type.icon();
@@ -445,6 +585,7 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
                     prefix_len: prefix.len(),
                     prefix_style: Style::default(),
                     content_style: Style::default().fg(color),
+                    char_styles: None,
                 });
                 app.chat_visual_lines.push((full_text, abs_line_idx));
                 abs_line_idx += 1;
@@ -457,12 +598,23 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
             prefix_len: 0,
             prefix_style: Style::default(),
             content_style: Style::default(),
+            char_styles: None,
         });
         app.chat_visual_lines.push((String::new(), abs_line_idx));
     }
 
     let total_lines = all_visual_lines.len();
 
+    app.search_hits = match &app.chat_search {
+        Some(term) => all_visual_lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| !app.search_cache.ranges(term, &line.full_text).is_empty())
+            .map(|(idx, _)| idx)
+            .collect(),
+        None => Vec::new(),
+    };
+
     // Smart Scrolling logic (adjust scroll if content grew)
     let height = chunks[0].height.saturating_sub(2) as usize;
     if let Some(last) = app.last_total_chat_lines {
@@ -476,6 +628,37 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
     // Calculate max scroll based on current content
     let max_scroll = total_lines.saturating_sub(height);
 
+    // Bring a bookmarked message to the top of the view (Alt+1..9)
+    if let Some(target) = app.chat_jump_to.take() {
+        if let Some((_, line)) = app.chat_message_lines.iter().find(|(idx, _)| *idx == target) {
+            app.chat_scroll = max_scroll.saturating_sub(*line);
+            app.chat_auto_scroll = app.chat_scroll == 0;
+        }
+    }
+
+    // Bring the current search match into view, a third of the way down
+    if std::mem::take(&mut app.search_jump) {
+        if let Some(term) = app.chat_search.clone() {
+            let count = app.search_hits.len();
+            if count == 0 {
+                app.search_match = None;
+                app.status_message = Some(format!("🔍 No match for '{}'", term));
+            } else {
+                let current = app.search_match.map_or(count - 1, |idx| idx.min(count - 1));
+                app.search_match = Some(current);
+                let top = app.search_hits[current].saturating_sub(height / 3);
+                app.chat_scroll = max_scroll.saturating_sub(top);
+                app.chat_auto_scroll = app.chat_scroll == 0;
+                app.status_message = Some(format!(
+                    "🔍 '{}': {} of {} · n older · N newer · Esc to close",
+                    term,
+                    count - current,
+                    count
+                ));
+            }
+        }
+    }
+
     // Always clamp scroll to valid bounds first
     app.chat_scroll = app.chat_scroll.clamp(0, max_scroll);
 
@@ -491,6 +674,8 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
     app.chat_visible_start_idx = start_index;
     app.chat_visible_end_idx = end_index;
 
+    let current_hit = app.search_match.and_then(|idx| app.search_hits.get(idx)).copied();
+
     // Build list_items for visible lines only, with correct row calculation and selection
     let mut list_items = Vec::new();
     for (abs_line_idx, visual_line) in all_visual_lines
@@ -505,16 +690,27 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
             list_items.push(ListItem::new(Line::from("")));
             continue;
         }
+        let hits = match &app.chat_search {
+            Some(term) => app.search_cache.ranges(term, full_text).to_vec(),
+            None => Vec::new(),
+        };
+        let hit_bg = if current_hit == Some(abs_line_idx) { theme.accent } else { theme.warning };
         let mut spans = Vec::new();
         for (char_idx, c) in full_text.chars().enumerate() {
             let col = chunks[0].x + 1 + char_idx as u16;
             let is_selected = app.is_in_selection(col, current_row, Focus::Chat);
             let style = if is_selected {
-                Style::default().bg(Color::Cyan).fg(Color::Black)
+                Style::default().bg(theme.selection_bg).fg(theme.selection_fg)
+            } else if hits.iter().any(|(start, end)| (*start..*end).contains(&char_idx)) {
+                Style::default().bg(hit_bg).fg(theme.selection_fg)
             } else if char_idx < visual_line.prefix_len {
                 visual_line.prefix_style
             } else {
-                visual_line.content_style
+                visual_line
+                    .char_styles
+                    .as_ref()
+                    .and_then(|styles| styles.get(char_idx).copied())
+                    .unwrap_or(visual_line.content_style)
             };
             spans.push(Span::styled(c.to_string(), style));
         }
@@ -526,9 +722,9 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
         .borders(Borders::ALL)
         .title(title)
         .border_style(if app.focus == Focus::Chat {
-            Style::default().fg(Color::Yellow)
+            Style::default().fg(theme.border_focused)
         } else {
-            Style::default()
+            Style::default().fg(theme.border)
         });
 
     // Check status tracker first for errors and tool execution status
@@ -538,7 +734,7 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
         chat_block = chat_block.title_bottom(Line::from(vec![Span::styled(
             format!(" {} ", status),
             Style::default()
-                .fg(Color::Yellow)
+                .fg(theme.warning)
                 .add_modifier(Modifier::ITALIC),
         )]));
     } else if let crate::tui::app::status_tracker::StatusInfo::Error { message } = status_info {
@@ -550,7 +746,7 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
         };
         chat_block = chat_block.title_bottom(Line::from(vec![Span::styled(
             format!(" ❌ Error: {} ", err_preview),
-            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            Style::default().fg(theme.error).add_modifier(Modifier::BOLD),
         )]));
     } else if app.state != AppState::Idle {
         let spinner = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
@@ -558,23 +754,23 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
 
         let (status_text, color) = match &app.state {
             AppState::Thinking(info) => {
-                (format!(" {} Thinking ({}) ", frame, info), Color::Yellow)
+                (format!(" {} Thinking ({}) ", frame, info), theme.warning)
             }
             AppState::Streaming(info) => {
-                (format!(" {} Streaming: {} ", frame, info), Color::Green)
+                (format!(" {} Streaming: {} ", frame, info), theme.assistant)
             }
             AppState::ExecutingTool(tool) => {
-                (format!(" {} Executing: {} ", frame, tool), Color::Cyan)
+                (format!(" {} Executing: {} ", frame, tool), theme.accent)
             }
             AppState::WaitingForUser => {
-                (" ⏳ Waiting for Approval ".to_string(), Color::Magenta)
+                (" ⏳ Waiting for Approval ".to_string(), theme.approval)
             }
             AppState::AwaitingApproval { .. } => {
-                (" ⏳ Awaiting your response ".to_string(), Color::Yellow)
+                (" ⏳ Awaiting your response ".to_string(), theme.approval)
             }
-            AppState::Error(err) => (format!(" ❌ Error: {} ", err), Color::Red),
-            AppState::ConfirmExit => (" ⚠️  Confirm Exit? ".to_string(), Color::Yellow),
-            AppState::NamingSession => (" 💾 Name Session ".to_string(), Color::Cyan),
+            AppState::Error(err) => (format!(" ❌ Error: {} ", err), theme.error),
+            AppState::ConfirmExit => (" ⚠️  Confirm Exit? ".to_string(), theme.warning),
+            AppState::NamingSession => (" 💾 Name Session ".to_string(), theme.accent),
             AppState::Idle => unreachable!(),
         };
         chat_block = chat_block.title_bottom(Line::from(vec![Span::styled(
@@ -584,7 +780,7 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
     } else if !app.chat_auto_scroll {
         chat_block = chat_block.title_bottom(Line::from(vec![Span::styled(
             " [SCROLLING] ",
-            Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD),
+            Style::default().fg(theme.info).add_modifier(Modifier::BOLD),
         )]));
     }
 
@@ -592,7 +788,11 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
     frame.render_widget(chat_list, chunks[0]);
 
     // Chat input
-    let input_title = if app.focus == Focus::Chat {
+    let input_title = if app.command_stream.is_some() {
+        " Command (typing into the terminal - Esc to cancel) "
+    } else if app.command_mode {
+        " Command (Enter: type it into the terminal) [F7: chat] "
+    } else if app.focus == Focus::Chat {
         if app.state != AppState::Idle && app.state != AppState::WaitingForUser {
             " Input (Locked - Ctrl+c to stop) "
         } else {
@@ -602,19 +802,32 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
         " Input (F2 to focus for Esc/Commands) "
     };
 
-    let input_block = Block::default()
+    let mut input_block = Block::default()
         .borders(Borders::ALL)
         .title(input_title)
         .border_style(if app.focus == Focus::Chat {
-            Style::default().fg(Color::Yellow)
+            Style::default().fg(theme.border_focused)
         } else {
-            Style::default()
+            Style::default().fg(theme.border)
         });
+    if app.keymap.vim && app.focus == Focus::Chat {
+        let mode = match app.vim_mode {
+            crate::tui::app::keymap::VimMode::Insert => " INSERT ",
+            crate::tui::app::keymap::VimMode::Normal => " NORMAL ",
+        };
+        input_block = input_block.title(Line::styled(mode, Style::default().fg(theme.accent)).right_aligned());
+    }
+    if !app.pasted_blocks.is_empty() || !app.pending_images.is_empty() {
+        input_block = input_block.title_bottom(pasted_chips(app));
+    }
+    if app.pasted_blocks_expanded {
+        render_pasted_blocks(frame, app, chunks[0]);
+    }
 
     if app.state != AppState::Idle && app.state != AppState::WaitingForUser {
         let p = Paragraph::new(Span::styled(
             &input_content,
-            Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC),
+            Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
         ))
         .block(input_block)
         .wrap(Wrap { trim: true });
@@ -653,6 +866,7 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
         frame.render_widget(input_paragraph, chunks[2]);
 
         if app.focus == Focus::Chat {
+            render_slash_popup(frame, app, chunks[0]);
             let visible_cursor_y = cursor_y.saturating_sub(start_line as u16);
 
             frame.set_cursor_position((
@@ -662,3 +876,170 @@ pub fn render_chat(frame: &mut Frame, app: &mut App, area: Rect) {
         }
     }
 }
+
+/// Add the highlighted lines of a code block to a message's lines
+fn push_code(
+    lines: &mut Vec<(String, Style)>,
+    span_styles: &mut HashMap<usize, Vec<Style>>,
+    lang: &str,
+    code: Vec<String>,
+    theme: &Theme,
+) {
+    let styles = highlight::highlight(lang, &code, theme.code);
+    push_styled(lines, span_styles, code.into_iter().zip(styles).collect());
+}
+
+/// Add lines with per-character styles to a message's lines, keeping the
+/// styles by line index
+fn push_styled(
+    lines: &mut Vec<(String, Style)>,
+    span_styles: &mut HashMap<usize, Vec<Style>>,
+    styled: Vec<markdown::StyledLine>,
+) {
+    for (line, line_styles) in styled {
+        span_styles.insert(lines.len(), line_styles);
+        lines.push((line, Style::default()));
+    }
+}
+
+/// Character styles of each wrapped piece of `text`; `wrap_text` drops the
+/// spaces it breaks lines at, so those are skipped
+fn wrap_styles(text: &str, styles: &[Style], wrapped: &[String]) -> Vec<Vec<Style>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut pos = 0;
+    wrapped
+        .iter()
+        .map(|piece| {
+            piece
+                .chars()
+                .map(|c| {
+                    while pos < chars.len() && chars[pos] != c && chars[pos] == ' ' {
+                        pos += 1;
+                    }
+                    let style = styles.get(pos).copied().unwrap_or_default();
+                    pos += 1;
+                    style
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Framed box standing in for an attached image
+///
+/// The chat is redrawn every frame, so each file's size is looked up once.
+fn image_placeholder(path: &std::path::Path) -> Vec<String> {
+    static SIZES: std::sync::OnceLock<std::sync::Mutex<HashMap<std::path::PathBuf, Option<u64>>>> =
+        std::sync::OnceLock::new();
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let size = *SIZES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(path.to_path_buf())
+        .or_insert_with(|| std::fs::metadata(path).ok().map(|meta| meta.len()));
+    let detail = match size {
+        Some(size) => format!("{} KB", size.div_ceil(1024)),
+        None => "missing".to_string(),
+    };
+    let label = format!(" 🖼️  {} · {} ", name, detail);
+    let width = label.chars().count() + 1;
+    vec![
+        format!("┌{}┐", "─".repeat(width)),
+        format!("│{} │", label),
+        format!("└{}┘", "─".repeat(width)),
+    ]
+}
+
+/// One chip per pasted block and attached image, shown on the input border
+fn pasted_chips(app: &App) -> Line<'static> {
+    let theme = &app.theme;
+    let chip = Style::default().fg(theme.selection_fg).bg(theme.selection_bg);
+    let mut spans = vec![Span::raw(" ")];
+    for (i, block) in app.pasted_blocks.iter().enumerate() {
+        spans.push(Span::styled(format!(" 📋 #{} {} lines ", i + 1, block.line_count()), chip));
+        spans.push(Span::raw(" "));
+    }
+    for attached in &app.pending_images {
+        let name = attached.path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        spans.push(Span::styled(format!(" 🖼️ {} ", name), Style::default().fg(theme.selection_fg).bg(theme.approval)));
+        spans.push(Span::raw(" "));
+    }
+    if app.pasted_blocks.is_empty() {
+        spans.push(Span::styled("/attach clear to drop ", Style::default().fg(theme.muted)));
+        return Line::from(spans);
+    }
+    let hint = if app.pasted_blocks_expanded { "F6 hide" } else { "F6 expand" };
+    spans.push(Span::styled(
+        format!("{} · ⌫ remove ", hint),
+        Style::default().fg(theme.muted),
+    ));
+    Line::from(spans)
+}
+
+/// The pasted blocks in full, over the chat history
+fn render_pasted_blocks(frame: &mut Frame, app: &App, area: Rect) {
+    let mut lines = Vec::new();
+    for (i, block) in app.pasted_blocks.iter().enumerate() {
+        lines.push(Line::from(Span::styled(
+            format!("── Pasted block {} ({} lines) ──", i + 1, block.line_count()),
+            Style::default().fg(app.theme.accent).add_modifier(Modifier::BOLD),
+        )));
+        lines.extend(block.text.lines().map(|line| Line::from(line.to_string())));
+    }
+    let popup = Rect::new(
+        area.x + 1,
+        area.y + 1,
+        area.width.saturating_sub(2),
+        area.height.saturating_sub(2),
+    );
+    frame.render_widget(Clear, popup);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.accent))
+        .title(" Pasted blocks (sent with your next message) ");
+    frame.render_widget(Paragraph::new(lines).block(block).wrap(Wrap { trim: false }), popup);
+}
+
+/// Commands (or arguments) matching the `/` being typed, just above the input
+fn render_slash_popup(frame: &mut Frame, app: &App, area: Rect) {
+    const MAX_ROWS: usize = 8;
+    let (matches, selected) = app.slash_popup();
+    if matches.is_empty() || area.height < 3 {
+        return;
+    }
+    let rows = matches.len().min(MAX_ROWS).min(area.height as usize - 2);
+    // Scroll so the selected match stays in view
+    let first = selected.map_or(0, |idx| (idx + 1).saturating_sub(rows));
+    let name_width = matches.iter().map(|m| m.text.chars().count()).max().unwrap_or(0);
+    let lines: Vec<Line> = matches
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(rows)
+        .map(|(idx, m)| {
+            let style = if selected == Some(idx) {
+                Style::default().fg(app.theme.accent).add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default().fg(app.theme.accent)
+            };
+            Line::from(vec![
+                Span::styled(format!("{:width$}", m.text, width = name_width), style),
+                Span::styled(format!("  {}", m.hint), Style::default().fg(app.theme.muted)),
+            ])
+        })
+        .collect();
+    let height = rows as u16 + 2;
+    let popup = Rect::new(area.x, area.y + area.height - height, area.width, height);
+    frame.render_widget(Clear, popup);
+    let title = if matches.len() > rows {
+        format!(" Commands ({} of {}, Tab to complete) ", rows, matches.len())
+    } else {
+        " Commands (Tab to complete) ".to_string()
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.border))
+        .title(title);
+    frame.render_widget(Paragraph::new(lines).block(block), popup);
+}
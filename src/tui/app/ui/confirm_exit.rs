@@ -3,13 +3,13 @@
 use crate::tui::app::state::AppStateContainer as App;
 use ratatui::{
     layout::{Constraint, Direction, Layout},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
     Frame,
 };
 
-pub fn render_confirm_exit(frame: &mut Frame, _app: &mut App) {
+pub fn render_confirm_exit(frame: &mut Frame, app: &mut App) {
     let area = frame.area();
 
     // Simple centered dialog for y/n confirmation
@@ -23,10 +23,9 @@ pub fn render_confirm_exit(frame: &mut Frame, _app: &mut App) {
         .borders(Borders::ALL)
         .border_style(
             Style::default()
-                .fg(Color::Yellow)
+                .fg(app.theme.warning)
                 .add_modifier(Modifier::BOLD),
-        )
-        .style(Style::default().bg(Color::Black));
+        );
 
     frame.render_widget(block, dialog_area);
 
@@ -49,14 +48,14 @@ pub fn render_confirm_exit(frame: &mut Frame, _app: &mut App) {
         Span::styled(
             " [Y] ",
             Style::default()
-                .fg(Color::Green)
+                .fg(app.theme.success)
                 .add_modifier(Modifier::BOLD),
         ),
         Span::raw("Yes, exit"),
         Span::raw("  "),
         Span::styled(
             " [N] ",
-            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            Style::default().fg(app.theme.error).add_modifier(Modifier::BOLD),
         ),
         Span::raw("No, cancel"),
     ]))
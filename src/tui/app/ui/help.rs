@@ -46,6 +46,7 @@ impl HelpSystem {
         output.push_str("║ MOUSE                                                                        ║\n");
         output.push_str("║   Shift+Click          Bypass mylm mouse capture (use terminal's native      ║\n");
         output.push_str("║                        selection & right-click menu)                         ║\n");
+        output.push_str("║   Hover margin marker  Show the agent's note on a terminal line              ║\n");
         output.push_str("╚══════════════════════════════════════════════════════════════════════════════╝\n");
 
         output
@@ -64,15 +65,71 @@ impl HelpSystem {
             },
             Keybinding {
                 keys: "F3",
-                description: "Toggle Memory View",
+                description: "Toggle Memory View (Enter edits the selected memory)",
             },
             Keybinding {
                 keys: "F4",
                 description: "Toggle Jobs Panel",
             },
+            Keybinding {
+                keys: "F5",
+                description: "Show next terminal annotation",
+            },
+            Keybinding {
+                keys: "F6",
+                description: "Expand/hide pasted blocks",
+            },
+            Keybinding {
+                keys: "F7",
+                description: "Command mode: describe a command, get it typed into the terminal",
+            },
+            Keybinding {
+                keys: "F8",
+                description: "Bookmark/unbookmark the AI response in view",
+            },
+            Keybinding {
+                keys: "F9",
+                description: "Show/hide the notes panel of bookmarks",
+            },
+            Keybinding {
+                keys: "Alt+P",
+                description: "Pin/unpin the message in view; pins stay in the agent's context",
+            },
+            Keybinding {
+                keys: "F10",
+                description: "Expand/collapse the pinned panel",
+            },
+            Keybinding {
+                keys: "F11",
+                description: "Review and edit the agent's scratchpad (Ctrl+S saves)",
+            },
+            Keybinding {
+                keys: "Alt+1..9",
+                description: "Scroll the chat to a bookmarked response",
+            },
             Keybinding {
                 keys: "Ctrl+Shift+←/→",
-                description: "Adjust chat/terminal split (20%-100%)",
+                description: "Adjust chat/terminal split (20%-100%), or drag the border",
+            },
+            Keybinding {
+                keys: "Alt+S",
+                description: "Swap the terminal and chat sides",
+            },
+            Keybinding {
+                keys: "Alt+Z",
+                description: "Zoom the focused pane (again or Esc to restore)",
+            },
+            Keybinding {
+                keys: "Ctrl+T",
+                description: "Open a conversation tab (/tab close closes it)",
+            },
+            Keybinding {
+                keys: "Ctrl+Tab / Alt+←/→",
+                description: "Switch between tabs",
+            },
+            Keybinding {
+                keys: "Tab",
+                description: "Complete the /command being typed (again to cycle)",
             },
             Keybinding {
                 keys: "Ctrl+C",
@@ -124,7 +181,7 @@ pub fn render_help_view(frame: &mut Frame, app: &mut App, area: Rect) {
     let block = Block::default()
         .borders(Borders::ALL)
         .title(" [ myLM Help (F1 to close, ↑/↓ to scroll) ] ")
-        .border_style(Style::default().fg(Color::Yellow));
+        .border_style(Style::default().fg(app.theme.border_focused));
 
     let paragraph = Paragraph::new(help_text)
         .block(block)
@@ -0,0 +1,164 @@
+//! Syntax highlighting for fenced code blocks in chat messages
+//!
+//! Blocks are highlighted with syntect's bundled grammars and themes, the
+//! theme picked by the TUI's colour theme (`Theme::code`); a block whose
+//! language syntect does not know is shown as plain text. Blocks are numbered per message, which is the
+//! number `/copy-code <n>` takes.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Mutex, OnceLock};
+
+use ratatui::style::{Color, Modifier, Style};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{FontStyle, Theme, ThemeSet};
+use syntect::parsing::SyntaxSet;
+
+/// syntect theme used when the one asked for is not bundled
+const FALLBACK_THEME: &str = "base16-ocean.dark";
+/// Tabs in code are shown as this many spaces
+const TAB_WIDTH: usize = 4;
+/// Highlighted blocks kept between frames
+const CACHED_BLOCKS: usize = 256;
+
+/// A fenced code block in a message
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeBlock {
+    /// Language after the opening fence, empty when there is none
+    pub lang: String,
+    pub code: String,
+}
+
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn theme(name: &str) -> &'static Theme {
+    static THEMES: OnceLock<ThemeSet> = OnceLock::new();
+    static PLAIN: OnceLock<Theme> = OnceLock::new();
+    let themes = &THEMES.get_or_init(ThemeSet::load_defaults).themes;
+    themes
+        .get(name)
+        .or_else(|| themes.get(FALLBACK_THEME))
+        .unwrap_or_else(|| PLAIN.get_or_init(Theme::default))
+}
+
+/// The language of an opening or closing fence line (empty for a bare
+/// fence); None when `line` is not a fence
+pub fn fence_lang(line: &str) -> Option<&str> {
+    let rest = line.trim().strip_prefix("```")?;
+    Some(rest.split_whitespace().next().unwrap_or(""))
+}
+
+/// Every fenced code block in `content`, in order; a block still open at
+/// the end (a streaming reply) counts too
+pub fn code_blocks(content: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut open: Option<(String, Vec<&str>)> = None;
+    for line in content.lines() {
+        match (open.take(), fence_lang(line)) {
+            (None, Some(lang)) => open = Some((lang.to_string(), Vec::new())),
+            (Some((lang, lines)), Some(_)) => blocks.push(CodeBlock { lang, code: lines.join("\n") }),
+            (Some((lang, mut lines)), None) => {
+                lines.push(line);
+                open = Some((lang, lines));
+            }
+            (None, None) => {}
+        }
+    }
+    if let Some((lang, lines)) = open {
+        blocks.push(CodeBlock { lang, code: lines.join("\n") });
+    }
+    blocks
+}
+
+/// A code line as displayed: tabs expanded, carriage returns dropped
+pub fn display_line(line: &str) -> String {
+    line.replace('\r', "").replace('\t', &" ".repeat(TAB_WIDTH))
+}
+
+/// One style per character of each of `lines` (already passed through
+/// [`display_line`]), highlighted as `lang` with syntect theme `theme_name`
+///
+/// The chat is redrawn every frame, so results are kept by block.
+pub fn highlight(lang: &str, lines: &[String], theme_name: &str) -> Vec<Vec<Style>> {
+    static CACHE: OnceLock<Mutex<HashMap<u64, Vec<Vec<Style>>>>> = OnceLock::new();
+    let mut hasher = DefaultHasher::new();
+    (lang, lines, theme_name).hash(&mut hasher);
+    let key = hasher.finish();
+    let mut cache = CACHE.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(styles) = cache.get(&key) {
+        return styles.clone();
+    }
+    let styles = highlight_uncached(lang, lines, theme_name);
+    if cache.len() >= CACHED_BLOCKS {
+        cache.clear();
+    }
+    cache.insert(key, styles.clone());
+    styles
+}
+
+fn highlight_uncached(lang: &str, lines: &[String], theme_name: &str) -> Vec<Vec<Style>> {
+    let syntaxes = syntaxes();
+    let syntax = syntaxes
+        .find_syntax_by_token(lang)
+        .unwrap_or_else(|| syntaxes.find_syntax_plain_text());
+    let mut highlighter = HighlightLines::new(syntax, theme(theme_name));
+    lines
+        .iter()
+        .map(|line| {
+            let with_newline = format!("{}\n", line);
+            let ranges = highlighter.highlight_line(&with_newline, syntaxes).unwrap_or_default();
+            let mut styles = Vec::with_capacity(line.chars().count());
+            for (style, text) in ranges {
+                let fg = style.foreground;
+                let mut converted = Style::default().fg(Color::Rgb(fg.r, fg.g, fg.b));
+                if style.font_style.contains(FontStyle::BOLD) {
+                    converted = converted.add_modifier(Modifier::BOLD);
+                }
+                if style.font_style.contains(FontStyle::ITALIC) {
+                    converted = converted.add_modifier(Modifier::ITALIC);
+                }
+                styles.extend(text.chars().filter(|c| *c != '\n').map(|_| converted));
+            }
+            // A line syntect could not highlight stays plain
+            styles.resize(line.chars().count(), Style::default());
+            styles
+        })
+        .collect()
+}
+
+/// Label shown above block `number` of a message
+pub fn label(number: usize, lang: &str) -> String {
+    let lang = if lang.is_empty() { "code" } else { lang };
+    format!("┌─ [{}] {}", number, lang)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_blocks_and_highlighting() {
+        let content = "Try this:\n```rust\nfn main() {}\n```\nthen\n```\nls -la\n```\n```python\nprint(1)";
+        let blocks = code_blocks(content);
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0], CodeBlock { lang: "rust".into(), code: "fn main() {}".into() });
+        assert_eq!(blocks[1].lang, "");
+        assert_eq!(blocks[2].code, "print(1)");
+        assert_eq!(fence_lang("  ```sh title"), Some("sh"));
+        assert_eq!(fence_lang("code"), None);
+
+        let lines = vec![display_line("fn main() {\tlet x = 1; }")];
+        let styles = highlight("rust", &lines, FALLBACK_THEME);
+        assert_eq!(styles[0].len(), lines[0].chars().count());
+        // The keyword and the identifier get different colours
+        assert_ne!(styles[0][0].fg, styles[0][3].fg);
+        assert_ne!(highlight("rust", &lines, "InspiredGitHub")[0][0].fg, styles[0][0].fg);
+        assert_eq!(highlight("rust", &lines, "no-such-theme"), styles);
+        assert_eq!(highlight("no-such-language", &lines, FALLBACK_THEME)[0].len(), lines[0].chars().count());
+        assert_eq!(label(2, ""), "┌─ [2] code");
+    }
+}
@@ -4,21 +4,37 @@ use crate::tui::app::state::AppStateContainer as App;
 use crate::tui::app::types::{ActionType, Focus, JobStatus};
 use ratatui::{
     layout::{Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
     Frame,
 };
 
 pub fn render_jobs_panel(frame: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme.clone();
     // Get all jobs from the registry (including completed and failed)
     let jobs = app.job_registry.list_all_jobs();
 
+    // Workers waiting for a pool slot (short job IDs, as the registry keeps them)
+    let queued: Vec<String> = app
+        .worker_pool
+        .as_ref()
+        .map(|pool| pool.queued_jobs().iter().map(|id| id.to_string()).collect())
+        .unwrap_or_default();
+    let pool_status = app
+        .worker_pool
+        .as_ref()
+        .map(|pool| pool.status())
+        .filter(|status| status.running + status.queued > 0)
+        .map(|status| format!(" pool {}/{} running, {} queued", status.running, status.capacity, status.queued))
+        .unwrap_or_default();
+
     // Show different title when focused
     let title = if app.focus == Focus::Jobs {
         format!(
-            " Jobs [{} active] ↑↓:sel S-↑↓:scroll Enter:view c:cancel a:cancel-all Esc:close ",
-            jobs.iter().filter(|j| matches!(j.status, JobStatus::Running)).count()
+            " Jobs [{} active{}] ↑↓:sel S-↑↓:scroll Enter:view c:cancel a:cancel-all Esc:close ",
+            jobs.iter().filter(|j| matches!(j.status, JobStatus::Running)).count(),
+            pool_status
         )
     } else {
         " Background Jobs [NOT FOCUSED - Press F2 to focus, F4 to close] ".to_string()
@@ -28,17 +44,17 @@ pub fn render_jobs_panel(frame: &mut Frame, app: &mut App, area: Rect) {
         .borders(Borders::ALL)
         .title(title)
         .border_style(if app.focus == Focus::Jobs {
-            Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)
+            Style::default().fg(theme.success).add_modifier(Modifier::BOLD)
         } else if !jobs.is_empty() {
-            Style::default().fg(Color::Cyan)
+            Style::default().fg(theme.accent)
         } else {
-            Style::default().fg(Color::DarkGray)
+            Style::default().fg(theme.muted)
         });
 
     if jobs.is_empty() {
         let empty_text = Paragraph::new("No background jobs")
             .block(block)
-            .style(Style::default().fg(Color::DarkGray));
+            .style(Style::default().fg(theme.muted));
         frame.render_widget(empty_text, area);
         return;
     }
@@ -71,7 +87,9 @@ pub fn render_jobs_panel(frame: &mut Frame, app: &mut App, area: Rect) {
     let mut items = Vec::new();
     for (idx, job) in visible_jobs {
         let short_id = &job.id[..8.min(job.id.len())];
+        let is_queued = matches!(job.status, JobStatus::Running) && queued.contains(&job.job_id);
         let status_str = match job.status {
+            JobStatus::Running if is_queued => "◌",
             JobStatus::Running => "●",
             JobStatus::Completed => "✓",
             JobStatus::Failed => "✗",
@@ -80,23 +98,26 @@ pub fn render_jobs_panel(frame: &mut Frame, app: &mut App, area: Rect) {
             JobStatus::Stalled => "⚠",
         };
         let status_color = match job.status {
-            JobStatus::Running => Color::Yellow,
-            JobStatus::Completed => Color::Green,
-            JobStatus::Failed => Color::Red,
-            JobStatus::Cancelled => Color::Magenta,
-            JobStatus::TimeoutPending => Color::Yellow,
-            JobStatus::Stalled => Color::Red,
+            JobStatus::Running if is_queued => theme.muted,
+            JobStatus::Running => theme.warning,
+            JobStatus::Completed => theme.success,
+            JobStatus::Failed => theme.error,
+            JobStatus::Cancelled => theme.approval,
+            JobStatus::TimeoutPending => theme.warning,
+            JobStatus::Stalled => theme.error,
         };
 
         // Get current step from action log
-        let current_step = job
-            .current_action()
-            .unwrap_or_else(|| match job.status {
+        let current_step = if is_queued {
+            mylm_core::agent::tools::delegate::runner::QUEUED_STATUS
+        } else {
+            job.current_action().unwrap_or_else(|| match job.status {
                 JobStatus::Completed => "Completed",
                 JobStatus::Failed => "Failed",
                 JobStatus::Cancelled => "Cancelled",
                 _ => "Starting...",
-            });
+            })
+        };
 
         // Calculate progress based on action log
         let total_steps = job
@@ -127,11 +148,11 @@ pub fn render_jobs_panel(frame: &mut Frame, app: &mut App, area: Rect) {
 
         // Choose color based on usage
         let token_color = if token_ratio >= 0.8 {
-            Color::Red
+            theme.error
         } else if token_ratio >= 0.5 {
-            Color::Yellow
+            theme.warning
         } else {
-            Color::Green
+            theme.success
         };
 
         // Build progress bar with unicode blocks (or show "—" when not tracked)
@@ -164,13 +185,13 @@ pub fn render_jobs_panel(frame: &mut Frame, app: &mut App, area: Rect) {
                 Style::default().fg(status_color).add_modifier(Modifier::BOLD),
             ),
             Span::raw(" "),
-            Span::styled(format!("#{}", short_id), Style::default().fg(Color::DarkGray)),
+            Span::styled(format!("#{}", short_id), Style::default().fg(theme.muted)),
             Span::raw(" "),
-            Span::styled(desc, Style::default().fg(Color::White)),
-            Span::styled(progress, Style::default().fg(Color::DarkGray)),
+            Span::styled(desc, Style::default().fg(theme.text)),
+            Span::styled(progress, Style::default().fg(theme.muted)),
             Span::raw(" "),
             Span::styled(progress_bar, Style::default().fg(token_color)),
-            Span::styled(token_text, Style::default().fg(Color::DarkGray)),
+            Span::styled(token_text, Style::default().fg(theme.muted)),
         ]);
 
         // Second line: current step (indented) - optional if there's a current action
@@ -183,8 +204,8 @@ pub fn render_jobs_panel(frame: &mut Frame, app: &mut App, area: Rect) {
             };
             Some(Line::from(vec![
                 Span::raw("       "),
-                Span::styled("└─ ", Style::default().fg(Color::DarkGray)),
-                Span::styled(step_display, Style::default().fg(Color::Cyan)),
+                Span::styled("└─ ", Style::default().fg(theme.muted)),
+                Span::styled(step_display, Style::default().fg(theme.accent)),
             ]))
         } else {
             None
@@ -203,11 +224,12 @@ pub fn render_jobs_panel(frame: &mut Frame, app: &mut App, area: Rect) {
 }
 
 pub fn render_job_detail(frame: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme.clone();
     // Use a split view: left side for journey/steps, right side for details
     let block = Block::default()
         .borders(Borders::ALL)
         .title(" Job Journey (Esc/q: close, ↑↓: scroll) ")
-        .border_style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD));
+        .border_style(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD));
 
     let inner_area = block.inner(area);
     frame.render_widget(block, area);
@@ -232,43 +254,43 @@ pub fn render_job_detail(frame: &mut Frame, app: &mut App, area: Rect) {
     // Header with mission
     lines.push(Line::from(vec![Span::styled(
         "╔══════════════════════════════════════════════════════════════╗",
-        Style::default().fg(Color::Cyan),
+        Style::default().fg(theme.accent),
     )]));
     lines.push(Line::from(vec![
-        Span::styled("║  ", Style::default().fg(Color::Cyan)),
+        Span::styled("║  ", Style::default().fg(theme.accent)),
         Span::styled(
             "MISSION",
             Style::default()
-                .fg(Color::Cyan)
+                .fg(theme.accent)
                 .add_modifier(Modifier::BOLD),
         ),
         Span::styled(
             "                                                    ║",
-            Style::default().fg(Color::Cyan),
+            Style::default().fg(theme.accent),
         ),
     ]));
     lines.push(Line::from(vec![Span::styled(
         "╚══════════════════════════════════════════════════════════════╝",
-        Style::default().fg(Color::Cyan),
+        Style::default().fg(theme.accent),
     )]));
 
     // Word wrap the description nicely
     for line in super::utils::wrap_text(&job.description, inner_area.width.saturating_sub(4) as usize) {
         lines.push(Line::from(vec![
             Span::raw("  "),
-            Span::styled(line, Style::default().fg(Color::White)),
+            Span::styled(line, Style::default().fg(theme.text)),
         ]));
     }
     lines.push(Line::from(""));
 
     // Status line
     let (status_text, status_color) = match job.status {
-        JobStatus::Running => ("▶ RUNNING", Color::Yellow),
-        JobStatus::Completed => ("✓ COMPLETED", Color::Green),
-        JobStatus::Failed => ("✗ FAILED", Color::Red),
-        JobStatus::Cancelled => ("⊘ CANCELLED", Color::Magenta),
-        JobStatus::TimeoutPending => ("⏱ TIMEOUT PENDING", Color::Yellow),
-        JobStatus::Stalled => ("⚠ STALLED", Color::Red),
+        JobStatus::Running => ("▶ RUNNING", theme.warning),
+        JobStatus::Completed => ("✓ COMPLETED", theme.success),
+        JobStatus::Failed => ("✗ FAILED", theme.error),
+        JobStatus::Cancelled => ("⊘ CANCELLED", theme.approval),
+        JobStatus::TimeoutPending => ("⏱ TIMEOUT PENDING", theme.warning),
+        JobStatus::Stalled => ("⚠ STALLED", theme.error),
     };
     lines.push(Line::from(vec![
         Span::raw("  Status: "),
@@ -287,24 +309,24 @@ pub fn render_job_detail(frame: &mut Frame, app: &mut App, area: Rect) {
     // Journey Timeline
     lines.push(Line::from(vec![Span::styled(
         "╔══════════════════════════════════════════════════════════════╗",
-        Style::default().fg(Color::Cyan),
+        Style::default().fg(theme.accent),
     )]));
     lines.push(Line::from(vec![
-        Span::styled("║  ", Style::default().fg(Color::Cyan)),
+        Span::styled("║  ", Style::default().fg(theme.accent)),
         Span::styled(
             "EXECUTION JOURNEY",
             Style::default()
-                .fg(Color::Cyan)
+                .fg(theme.accent)
                 .add_modifier(Modifier::BOLD),
         ),
         Span::styled(
             "                                           ║",
-            Style::default().fg(Color::Cyan),
+            Style::default().fg(theme.accent),
         ),
     ]));
     lines.push(Line::from(vec![Span::styled(
         "╚══════════════════════════════════════════════════════════════╝",
-        Style::default().fg(Color::Cyan),
+        Style::default().fg(theme.accent),
     )]));
     lines.push(Line::from(""));
 
@@ -312,7 +334,7 @@ pub fn render_job_detail(frame: &mut Frame, app: &mut App, area: Rect) {
     if job.action_log.is_empty() {
         lines.push(Line::from(vec![Span::styled(
             "  Waiting for execution to start...",
-            Style::default().fg(Color::DarkGray),
+            Style::default().fg(theme.muted),
         )]));
     } else {
         let mut step_num = 1;
@@ -328,13 +350,13 @@ pub fn render_job_detail(frame: &mut Frame, app: &mut App, area: Rect) {
                         Span::styled(
                             format!("  Step {}: ", step_num),
                             Style::default()
-                                .fg(Color::Yellow)
+                                .fg(theme.warning)
                                 .add_modifier(Modifier::BOLD),
                         ),
-                        Span::styled("💭 Thinking", Style::default().fg(Color::Cyan)),
+                        Span::styled("💭 Thinking", Style::default().fg(theme.accent)),
                         Span::styled(
                             format!(" [{}]", entry.timestamp.format("%H:%M:%S")),
-                            Style::default().fg(Color::DarkGray),
+                            Style::default().fg(theme.muted),
                         ),
                     ]));
                     for line in super::utils::wrap_text(
@@ -343,7 +365,7 @@ pub fn render_job_detail(frame: &mut Frame, app: &mut App, area: Rect) {
                     ) {
                         lines.push(Line::from(vec![
                             Span::raw("           "),
-                            Span::styled(line, Style::default().fg(Color::Gray)),
+                            Span::styled(line, Style::default().fg(theme.system)),
                         ]));
                     }
                     step_num += 1;
@@ -355,18 +377,18 @@ pub fn render_job_detail(frame: &mut Frame, app: &mut App, area: Rect) {
                         Span::styled(
                             format!("  Step {}: ", step_num),
                             Style::default()
-                                .fg(Color::Yellow)
+                                .fg(theme.warning)
                                 .add_modifier(Modifier::BOLD),
                         ),
-                        Span::styled("🔧 Action", Style::default().fg(Color::Blue)),
+                        Span::styled("🔧 Action", Style::default().fg(theme.info)),
                         Span::styled(
                             format!(" [{}]", entry.timestamp.format("%H:%M:%S")),
-                            Style::default().fg(Color::DarkGray),
+                            Style::default().fg(theme.muted),
                         ),
                     ]));
                     lines.push(Line::from(vec![
                         Span::raw("           "),
-                        Span::styled(&entry.content, Style::default().fg(Color::White)),
+                        Span::styled(&entry.content, Style::default().fg(theme.text)),
                     ]));
                     step_num += 1;
                     last_was_tool = true;
@@ -374,25 +396,25 @@ pub fn render_job_detail(frame: &mut Frame, app: &mut App, area: Rect) {
                 ActionType::ToolResult => {
                     lines.push(Line::from(vec![
                         Span::raw("           "),
-                        Span::styled("└─ ", Style::default().fg(Color::DarkGray)),
-                        Span::styled("📤 Result: ", Style::default().fg(Color::Green)),
-                        Span::styled(&entry.content, Style::default().fg(Color::Gray)),
+                        Span::styled("└─ ", Style::default().fg(theme.muted)),
+                        Span::styled("📤 Result: ", Style::default().fg(theme.success)),
+                        Span::styled(&entry.content, Style::default().fg(theme.system)),
                     ]));
                     last_was_tool = false;
                 }
                 ActionType::Error => {
                     lines.push(Line::from(""));
                     lines.push(Line::from(vec![
-                        Span::styled("  ⚠️  ", Style::default().fg(Color::Red)),
+                        Span::styled("  ⚠️  ", Style::default().fg(theme.error)),
                         Span::styled(
                             "ERROR",
                             Style::default()
-                                .fg(Color::Red)
+                                .fg(theme.error)
                                 .add_modifier(Modifier::BOLD),
                         ),
                         Span::styled(
                             format!(" [{}]", entry.timestamp.format("%H:%M:%S")),
-                            Style::default().fg(Color::DarkGray),
+                            Style::default().fg(theme.muted),
                         ),
                     ]));
                     for line in super::utils::wrap_text(
@@ -401,7 +423,7 @@ pub fn render_job_detail(frame: &mut Frame, app: &mut App, area: Rect) {
                     ) {
                         lines.push(Line::from(vec![
                             Span::raw("      "),
-                            Span::styled(line, Style::default().fg(Color::Red)),
+                            Span::styled(line, Style::default().fg(theme.error)),
                         ]));
                     }
                     last_was_tool = false;
@@ -411,22 +433,22 @@ pub fn render_job_detail(frame: &mut Frame, app: &mut App, area: Rect) {
                     lines.push(Line::from(vec![
                         Span::styled(
                             "  ════════════════════════════════════════════════════════════",
-                            Style::default().fg(Color::Green),
+                            Style::default().fg(theme.success),
                         ),
                     ]));
                     lines.push(Line::from(vec![
-                        Span::styled("  ✓ ", Style::default().fg(Color::Green)),
+                        Span::styled("  ✓ ", Style::default().fg(theme.success)),
                         Span::styled(
                             "TASK COMPLETE",
                             Style::default()
-                                .fg(Color::Green)
+                                .fg(theme.success)
                                 .add_modifier(Modifier::BOLD),
                         ),
                     ]));
                     lines.push(Line::from(vec![
                         Span::styled(
                             "  ════════════════════════════════════════════════════════════",
-                            Style::default().fg(Color::Green),
+                            Style::default().fg(theme.success),
                         ),
                     ]));
                     last_was_tool = false;
@@ -434,8 +456,8 @@ pub fn render_job_detail(frame: &mut Frame, app: &mut App, area: Rect) {
                 ActionType::System => {
                     lines.push(Line::from(vec![
                         Span::raw("  "),
-                        Span::styled("⚙ ", Style::default().fg(Color::DarkGray)),
-                        Span::styled(&entry.content, Style::default().fg(Color::DarkGray)),
+                        Span::styled("⚙ ", Style::default().fg(theme.muted)),
+                        Span::styled(&entry.content, Style::default().fg(theme.muted)),
                     ]));
                 }
                 _ => {
@@ -443,17 +465,17 @@ pub fn render_job_detail(frame: &mut Frame, app: &mut App, area: Rect) {
                     lines.push(Line::from(vec![
                         Span::styled(
                             format!("  Step {}: ", step_num),
-                            Style::default().fg(Color::Yellow),
+                            Style::default().fg(theme.warning),
                         ),
-                        Span::styled("⚡ Action", Style::default().fg(Color::Blue)),
+                        Span::styled("⚡ Action", Style::default().fg(theme.info)),
                         Span::styled(
                             format!(" [{}]", entry.timestamp.format("%H:%M:%S")),
-                            Style::default().fg(Color::DarkGray),
+                            Style::default().fg(theme.muted),
                         ),
                     ]));
                     lines.push(Line::from(vec![
                         Span::raw("           "),
-                        Span::styled(&entry.content, Style::default().fg(Color::White)),
+                        Span::styled(&entry.content, Style::default().fg(theme.text)),
                     ]));
                     step_num += 1;
                     last_was_tool = false;
@@ -467,24 +489,24 @@ pub fn render_job_detail(frame: &mut Frame, app: &mut App, area: Rect) {
         lines.push(Line::from(""));
         lines.push(Line::from(vec![Span::styled(
             "╔══════════════════════════════════════════════════════════════╗",
-            Style::default().fg(Color::Cyan),
+            Style::default().fg(theme.accent),
         )]));
         lines.push(Line::from(vec![
-            Span::styled("║  ", Style::default().fg(Color::Cyan)),
+            Span::styled("║  ", Style::default().fg(theme.accent)),
             Span::styled(
                 "FINAL OUTPUT",
                 Style::default()
-                    .fg(Color::Cyan)
+                    .fg(theme.accent)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::styled(
                 "                                          ║",
-                Style::default().fg(Color::Cyan),
+                Style::default().fg(theme.accent),
             ),
         ]));
         lines.push(Line::from(vec![Span::styled(
             "╚══════════════════════════════════════════════════════════════╝",
-            Style::default().fg(Color::Cyan),
+            Style::default().fg(theme.accent),
         )]));
         lines.push(Line::from(""));
         for line in job.output.lines() {
@@ -497,30 +519,30 @@ pub fn render_job_detail(frame: &mut Frame, app: &mut App, area: Rect) {
         lines.push(Line::from(""));
         lines.push(Line::from(vec![Span::styled(
             "╔══════════════════════════════════════════════════════════════╗",
-            Style::default().fg(Color::Red),
+            Style::default().fg(theme.error),
         )]));
         lines.push(Line::from(vec![
-            Span::styled("║  ", Style::default().fg(Color::Red)),
+            Span::styled("║  ", Style::default().fg(theme.error)),
             Span::styled(
                 "ERROR DETAILS",
                 Style::default()
-                    .fg(Color::Red)
+                    .fg(theme.error)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::styled(
                 "                                          ║",
-                Style::default().fg(Color::Red),
+                Style::default().fg(theme.error),
             ),
         ]));
         lines.push(Line::from(vec![Span::styled(
             "╚══════════════════════════════════════════════════════════════╝",
-            Style::default().fg(Color::Red),
+            Style::default().fg(theme.error),
         )]));
         lines.push(Line::from(""));
         for line in error.lines() {
             lines.push(Line::from(vec![Span::styled(
                 format!("  {}", line),
-                Style::default().fg(Color::Red),
+                Style::default().fg(theme.error),
             )]));
         }
     }
@@ -529,11 +551,11 @@ pub fn render_job_detail(frame: &mut Frame, app: &mut App, area: Rect) {
     lines.push(Line::from(""));
     lines.push(Line::from(vec![Span::styled(
         "─".repeat(inner_area.width as usize),
-        Style::default().fg(Color::DarkGray),
+        Style::default().fg(theme.muted),
     )]));
     let metrics = &job.metrics;
     lines.push(Line::from(vec![
-        Span::styled("  📊 Metrics: ", Style::default().fg(Color::DarkGray)),
+        Span::styled("  📊 Metrics: ", Style::default().fg(theme.muted)),
         Span::raw(format!(
             "Tokens: {}↑ {}↓ ({} total) | Requests: {} | Errors: {}",
             metrics.prompt_tokens,
@@ -0,0 +1,295 @@
+//! Markdown rendering for assistant messages
+//!
+//! Works line by line, like the rest of the chat pane: headings, list
+//! items, block quotes and rules are recognised at the start of a line and
+//! inline bold, italic, code and links inside it. Each line comes back as
+//! its display text plus one style per character. Tables are rendered a
+//! block of rows at a time so their columns line up. Fenced code is left to
+//! `highlight`; `/raw` shows messages unrendered.
+
+use ratatui::style::{Modifier, Style};
+
+use crate::tui::app::theme::Theme;
+
+/// A rendered line: text and one style per character
+pub type StyledLine = (String, Vec<Style>);
+
+const RULE_WIDTH: usize = 40;
+
+fn heading_style(level: usize, theme: &Theme) -> Style {
+    let style = Style::default().fg(theme.accent).add_modifier(Modifier::BOLD);
+    match level {
+        1 => style.add_modifier(Modifier::UNDERLINED),
+        2 => style,
+        _ => Style::default().add_modifier(Modifier::BOLD),
+    }
+}
+
+/// Render one line that is not part of a table or a code block
+pub fn render_line(line: &str, base: Style, theme: &Theme) -> StyledLine {
+    let indent_len = line.len() - line.trim_start().len();
+    let (indent, rest) = line.split_at(indent_len);
+    let rest = rest.trim_end();
+
+    // Heading: `#` to `######` then a space
+    let hashes = rest.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&hashes) && rest[hashes..].starts_with(' ') {
+        return inline(rest[hashes..].trim(), heading_style(hashes, theme), theme);
+    }
+    if is_rule(rest) {
+        let rule = "─".repeat(RULE_WIDTH);
+        let styles = vec![Style::default().fg(theme.muted); RULE_WIDTH];
+        return (rule, styles);
+    }
+    if let Some(quoted) = rest.strip_prefix('>') {
+        let style = base.fg(theme.system).add_modifier(Modifier::ITALIC);
+        return prefixed(indent, "│ ", Style::default().fg(theme.muted), inline(quoted.trim_start(), style, theme));
+    }
+    let bullet = Style::default().fg(theme.warning);
+    for marker in ["- ", "* ", "+ "] {
+        if let Some(item) = rest.strip_prefix(marker) {
+            let (mark, item) = match item {
+                _ if item.starts_with("[ ] ") => ("☐ ", &item[4..]),
+                _ if item.starts_with("[x] ") || item.starts_with("[X] ") => ("☑ ", &item[4..]),
+                _ => ("• ", item),
+            };
+            return prefixed(indent, mark, bullet, inline(item, base, theme));
+        }
+    }
+    let digits = rest.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 && rest[digits..].starts_with(". ") {
+        let number = &rest[..digits + 2];
+        return prefixed(indent, number, bullet, inline(&rest[digits + 2..], base, theme));
+    }
+    prefixed(indent, "", base, inline(rest, base, theme))
+}
+
+/// `---`, `***` or `___`, spaces allowed
+fn is_rule(line: &str) -> bool {
+    let compact: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3 && matches!(compact[0], '-' | '*' | '_') && compact.iter().all(|c| *c == compact[0])
+}
+
+/// `body` after the indentation and a marker
+fn prefixed(indent: &str, marker: &str, marker_style: Style, body: StyledLine) -> StyledLine {
+    let mut styles = vec![Style::default(); indent.chars().count()];
+    styles.extend(std::iter::repeat(marker_style).take(marker.chars().count()));
+    styles.extend(body.1);
+    (format!("{}{}{}", indent, marker, body.0), styles)
+}
+
+/// Inline markup: `**bold**`, `__bold__`, `*italic*`, `_italic_`, `` `code` ``
+/// and `[text](url)` (shown as the text); markers without a closing one
+/// are kept as they are
+pub fn inline(text: &str, base: Style, theme: &Theme) -> StyledLine {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::new();
+    let mut styles = Vec::new();
+    let mut bold: Option<&str> = None;
+    let mut italic: Option<char> = None;
+    let mut i = 0;
+    let closes = |from: usize, delim: &[char]| -> bool {
+        chars[from..].windows(delim.len()).any(|w| w == delim)
+    };
+    while i < chars.len() {
+        let style = {
+            let mut style = base;
+            if bold.is_some() {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            if italic.is_some() {
+                style = style.add_modifier(Modifier::ITALIC);
+            }
+            style
+        };
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let prev = i.checked_sub(1).map(|p| chars[p]);
+        match c {
+            '\\' if next.is_some_and(|n| n.is_ascii_punctuation()) => {
+                out.push(chars[i + 1]);
+                styles.push(style);
+                i += 2;
+            }
+            '`' if closes(i + 1, &['`']) => {
+                let end = i + 1 + chars[i + 1..].iter().position(|ch| *ch == '`').unwrap_or(0);
+                for ch in &chars[i + 1..end] {
+                    out.push(*ch);
+                    styles.push(base.fg(theme.warning));
+                }
+                i = end + 1;
+            }
+            '*' | '_' if next == Some(c) => {
+                let delim = if c == '*' { "**" } else { "__" };
+                if bold == Some(delim) {
+                    bold = None;
+                } else if bold.is_none() && closes(i + 2, &[c, c]) {
+                    bold = Some(delim);
+                } else {
+                    out.push_str(delim);
+                    styles.extend([style, style]);
+                }
+                i += 2;
+            }
+            '*' | '_' => {
+                let word_inside = prev.is_some_and(char::is_alphanumeric) && next.is_some_and(char::is_alphanumeric);
+                if italic == Some(c) && !(c == '_' && word_inside) {
+                    italic = None;
+                } else if italic.is_none()
+                    && next.is_some_and(|n| !n.is_whitespace())
+                    && !(c == '_' && prev.is_some_and(char::is_alphanumeric))
+                    && closes(i + 1, &[c])
+                {
+                    italic = Some(c);
+                } else {
+                    out.push(c);
+                    styles.push(style);
+                }
+                i += 1;
+            }
+            '[' => match link_at(&chars, i) {
+                Some((label, end)) => {
+                    let link = style.fg(theme.info).add_modifier(Modifier::UNDERLINED);
+                    for ch in label {
+                        out.push(ch);
+                        styles.push(link);
+                    }
+                    i = end;
+                }
+                None => {
+                    out.push(c);
+                    styles.push(style);
+                    i += 1;
+                }
+            },
+            _ => {
+                out.push(c);
+                styles.push(style);
+                i += 1;
+            }
+        }
+    }
+    (out, styles)
+}
+
+/// `[label](url)` starting at `start`: the label and the index after `)`
+fn link_at(chars: &[char], start: usize) -> Option<(Vec<char>, usize)> {
+    let close = start + chars[start..].iter().position(|c| *c == ']')?;
+    if chars.get(close + 1) != Some(&'(') {
+        return None;
+    }
+    let end = close + 1 + chars[close + 1..].iter().position(|c| *c == ')')?;
+    Some((chars[start + 1..close].to_vec(), end + 1))
+}
+
+/// Whether `line` is a row of a pipe table
+pub fn is_table_row(line: &str) -> bool {
+    let line = line.trim();
+    line.len() > 1 && line.starts_with('|')
+}
+
+fn cells(row: &str) -> Vec<&str> {
+    let row = row.trim();
+    let row = row.strip_prefix('|').unwrap_or(row);
+    let row = row.strip_suffix('|').unwrap_or(row);
+    row.split('|').map(str::trim).collect()
+}
+
+fn is_separator(cells: &[&str]) -> bool {
+    cells.iter().all(|cell| {
+        let dashes = cell.trim_matches(':');
+        !dashes.is_empty() && dashes.chars().all(|c| c == '-')
+    })
+}
+
+/// Render the rows of a pipe table with aligned columns; the row before the
+/// `|---|` separator is the header
+pub fn render_table(rows: &[String], base: Style, theme: &Theme) -> Vec<StyledLine> {
+    let parsed: Vec<Vec<&str>> = rows.iter().map(|row| cells(row)).collect();
+    let header_rows = parsed.iter().position(|row| is_separator(row)).unwrap_or(0);
+    let rendered: Vec<Option<Vec<StyledLine>>> = parsed
+        .iter()
+        .enumerate()
+        .map(|(index, row)| {
+            if is_separator(row) {
+                return None;
+            }
+            let style = if index < header_rows { base.add_modifier(Modifier::BOLD) } else { base };
+            Some(row.iter().map(|cell| inline(cell, style, theme)).collect())
+        })
+        .collect();
+    let columns = parsed.iter().map(Vec::len).max().unwrap_or(0);
+    let mut widths = vec![0; columns];
+    for row in rendered.iter().flatten() {
+        for (column, (text, _)) in row.iter().enumerate() {
+            widths[column] = widths[column].max(text.chars().count());
+        }
+    }
+
+    let border = Style::default().fg(theme.muted);
+    rendered
+        .into_iter()
+        .map(|row| {
+            let mut text = String::new();
+            let mut styles = Vec::new();
+            for (column, width) in widths.iter().enumerate() {
+                if column > 0 {
+                    let joint = if row.is_some() { " │ " } else { "─┼─" };
+                    text.push_str(joint);
+                    styles.extend([border; 3]);
+                }
+                match &row {
+                    Some(cells) => {
+                        let (cell, cell_styles) = cells.get(column).cloned().unwrap_or_default();
+                        let pad = width - cell.chars().count();
+                        text.push_str(&cell);
+                        styles.extend(cell_styles);
+                        text.push_str(&" ".repeat(pad));
+                        styles.extend(std::iter::repeat(base).take(pad));
+                    }
+                    None => {
+                        text.push_str(&"─".repeat(*width));
+                        styles.extend(std::iter::repeat(border).take(*width));
+                    }
+                }
+            }
+            (text, styles)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(line: StyledLine) -> String {
+        assert_eq!(line.0.chars().count(), line.1.len());
+        line.0
+    }
+
+    #[test]
+    fn test_markdown_lines_and_tables() {
+        let base = Style::default();
+        let theme = Theme::default();
+        let (plain, styles) = inline("a **bold** and *it* `x` [docs](https://x.io) snake_case_name", base, &theme);
+        assert_eq!(plain, "a bold and it x docs snake_case_name");
+        assert!(styles[2].add_modifier.contains(Modifier::BOLD));
+        assert!(!styles[0].add_modifier.contains(Modifier::BOLD));
+        assert!(styles[11].add_modifier.contains(Modifier::ITALIC));
+        assert!(!styles[27].add_modifier.contains(Modifier::ITALIC));
+        assert_eq!(text(inline("2 * 3 = 6, **open", base, &theme)), "2 * 3 = 6, **open");
+
+        assert_eq!(text(render_line("## Setup", base, &theme)), "Setup");
+        assert_eq!(text(render_line("  - [x] done", base, &theme)), "  ☑ done");
+        assert_eq!(text(render_line("3. third", base, &theme)), "3. third");
+        assert_eq!(text(render_line("> quoted", base, &theme)), "│ quoted");
+        assert_eq!(text(render_line("---", base, &theme)), "─".repeat(RULE_WIDTH));
+
+        let rows: Vec<String> = ["| Name | Size |", "|------|-----:|", "| **a** | 10 |", "| bb |"]
+            .iter()
+            .map(|r| r.to_string())
+            .collect();
+        let table: Vec<String> = render_table(&rows, base, &theme).into_iter().map(text).collect();
+        assert_eq!(table, ["Name │ Size", "─────┼─────", "a    │ 10  ", "bb   │     "]);
+    }
+}
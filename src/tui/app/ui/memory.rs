@@ -1,17 +1,18 @@
 //! Memory view rendering (F3)
 
 use crate::tui::app::state::AppStateContainer as App;
+use crate::tui::app::theme::Theme;
 use mylm_core::config::agent::UserProfile;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
     Frame,
 };
 
 /// Render user profile panel
-fn render_profile_panel(profile: &UserProfile) -> Vec<Line<'_>> {
+fn render_profile_panel<'a>(profile: &'a UserProfile, theme: &Theme) -> Vec<Line<'a>> {
     let mut lines = Vec::new();
     
     if profile.is_empty() {
@@ -24,7 +25,7 @@ fn render_profile_panel(profile: &UserProfile) -> Vec<Line<'_>> {
     if !profile.preferences.is_empty() {
         lines.push(Line::from(Span::styled(
             "Preferences:",
-            Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan),
+            Style::default().add_modifier(Modifier::BOLD).fg(theme.accent),
         )));
         for (k, v) in &profile.preferences {
             lines.push(Line::from(format!("  • {}: {}", k, v)));
@@ -36,7 +37,7 @@ fn render_profile_panel(profile: &UserProfile) -> Vec<Line<'_>> {
     if !profile.facts.is_empty() {
         lines.push(Line::from(Span::styled(
             "Known Facts:",
-            Style::default().add_modifier(Modifier::BOLD).fg(Color::Green),
+            Style::default().add_modifier(Modifier::BOLD).fg(theme.success),
         )));
         for (k, v) in &profile.facts {
             lines.push(Line::from(format!("  • {}: {}", k, v)));
@@ -48,7 +49,7 @@ fn render_profile_panel(profile: &UserProfile) -> Vec<Line<'_>> {
     if !profile.patterns.is_empty() {
         lines.push(Line::from(Span::styled(
             "Behavioral Patterns:",
-            Style::default().add_modifier(Modifier::BOLD).fg(Color::Yellow),
+            Style::default().add_modifier(Modifier::BOLD).fg(theme.warning),
         )));
         for pattern in &profile.patterns {
             lines.push(Line::from(format!("  • {}", pattern)));
@@ -60,7 +61,7 @@ fn render_profile_panel(profile: &UserProfile) -> Vec<Line<'_>> {
     if !profile.active_goals.is_empty() {
         lines.push(Line::from(Span::styled(
             "Active Goals:",
-            Style::default().add_modifier(Modifier::BOLD).fg(Color::Magenta),
+            Style::default().add_modifier(Modifier::BOLD).fg(theme.approval),
         )));
         for goal in &profile.active_goals {
             lines.push(Line::from(format!("  • {}", goal)));
@@ -71,6 +72,7 @@ fn render_profile_panel(profile: &UserProfile) -> Vec<Line<'_>> {
 }
 
 pub fn render_memory_view(frame: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme.clone();
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
@@ -85,7 +87,7 @@ pub fn render_memory_view(frame: &mut Frame, app: &mut App, area: Rect) {
     let title = if !app.memory_search_query.is_empty() {
         // Showing filtered results
         format!(
-            " Memories (filter: '{}' - {}/{}) ↑↓:Scroll d:Del s:Star r:Reload ",
+            " Memories (filter: '{}' - {}/{}) ↑↓:Scroll Enter:Edit d:Del s:Star r:Reload ",
             app.memory_search_query,
             app.memory_graph_scroll + 1,
             app.memory_graph.nodes.len()
@@ -103,7 +105,7 @@ pub fn render_memory_view(frame: &mut Frame, app: &mut App, area: Rect) {
     } else {
         // Normal view (all loaded)
         format!(
-            " Memories ({}/{}) ↑↓:Scroll d:Del s:Star e:Export r:Reload ",
+            " Memories ({}/{}) ↑↓:Scroll Enter:Edit d:Del s:Star e:Export r:Reload ",
             app.memory_graph_scroll + 1,
             app.memory_graph.nodes.len()
         )
@@ -112,7 +114,7 @@ pub fn render_memory_view(frame: &mut Frame, app: &mut App, area: Rect) {
     let list_block = Block::default()
         .borders(Borders::ALL)
         .title(title)
-        .border_style(Style::default().fg(Color::Yellow));
+        .border_style(Style::default().fg(theme.border_focused));
 
     let mut items = Vec::new();
     for node in &app.memory_graph.nodes {
@@ -134,11 +136,19 @@ pub fn render_memory_view(frame: &mut Frame, app: &mut App, area: Rect) {
         };
 
         let type_tag = format!("[{}] ", node.memory.r#type);
+        let category_tag = node
+            .memory
+            .category_id
+            .as_deref()
+            .filter(|c| !c.is_empty() && *c != "starred")
+            .map(|c| format!("({}) ", c))
+            .unwrap_or_default();
         items.push(ListItem::new(Line::from(vec![
-            Span::styled(timestamp_str, Style::default().fg(Color::DarkGray)),
+            Span::styled(timestamp_str, Style::default().fg(theme.muted)),
             Span::raw(" "),
             Span::raw(star_indicator),
-            Span::styled(type_tag, Style::default().fg(Color::Cyan)),
+            Span::styled(type_tag, Style::default().fg(theme.accent)),
+            Span::styled(category_tag, Style::default().fg(theme.approval)),
             Span::raw(truncated_title),
         ])));
     }
@@ -157,7 +167,7 @@ pub fn render_memory_view(frame: &mut Frame, app: &mut App, area: Rect) {
 
     let list = List::new(items)
         .block(list_block)
-        .highlight_style(Style::default().add_modifier(Modifier::BOLD).bg(Color::DarkGray))
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD).fg(theme.selection_fg).bg(theme.selection_bg))
         .highlight_symbol("> ");
 
     // Clamp scroll to valid bounds and select highlighted item
@@ -174,7 +184,7 @@ pub fn render_memory_view(frame: &mut Frame, app: &mut App, area: Rect) {
     let detail_block = Block::default()
         .borders(Borders::ALL)
         .title(" Memory Details & Connections ")
-        .border_style(Style::default().fg(Color::Cyan));
+        .border_style(Style::default().fg(theme.accent));
 
     if !app.memory_graph.nodes.is_empty() {
         let idx = app
@@ -184,26 +194,26 @@ pub fn render_memory_view(frame: &mut Frame, app: &mut App, area: Rect) {
 
         let mut detail_lines = Vec::new();
         detail_lines.push(Line::from(vec![
-            Span::styled("ID: ", Style::default().fg(Color::Gray)),
+            Span::styled("ID: ", Style::default().fg(theme.system)),
             Span::raw(node.memory.id.to_string()),
         ]));
         detail_lines.push(Line::from(vec![
-            Span::styled("Time: ", Style::default().fg(Color::Gray)),
+            Span::styled("Time: ", Style::default().fg(theme.system)),
             Span::raw(super::utils::format_timestamp_full(node.memory.created_at)),
         ]));
         detail_lines.push(Line::from(vec![
-            Span::styled("Type: ", Style::default().fg(Color::Gray)),
+            Span::styled("Type: ", Style::default().fg(theme.system)),
             Span::raw(node.memory.r#type.to_string()),
         ]));
         if let Some(cat) = &node.memory.category_id {
             detail_lines.push(Line::from(vec![
-                Span::styled("Category: ", Style::default().fg(Color::Gray)),
+                Span::styled("Category: ", Style::default().fg(theme.system)),
                 Span::raw(cat),
             ]));
         }
         if let Some(summary) = &node.memory.summary {
             detail_lines.push(Line::from(vec![
-                Span::styled("Summary (Index): ", Style::default().fg(Color::Gray)),
+                Span::styled("Summary (Index): ", Style::default().fg(theme.system)),
                 Span::raw(summary),
             ]));
         }
@@ -251,11 +261,11 @@ pub fn render_memory_view(frame: &mut Frame, app: &mut App, area: Rect) {
     let profile_block = Block::default()
         .borders(Borders::ALL)
         .title(" User Profile (Auto-Learned) ")
-        .border_style(Style::default().fg(Color::Magenta));
+        .border_style(Style::default().fg(theme.approval));
 
     let profile = app.memory_manager.as_ref().map(|m| m.get_profile());
     let profile_lines = if let Some(ref p) = profile {
-        render_profile_panel(p)
+        render_profile_panel(p, &theme)
     } else {
         vec![Line::from("Memory manager not available.")]
     };
@@ -269,7 +279,7 @@ pub fn render_memory_view(frame: &mut Frame, app: &mut App, area: Rect) {
     let stats_block = Block::default()
         .borders(Borders::ALL)
         .title(" Memory Stats & Actions ")
-        .border_style(Style::default().fg(Color::Green));
+        .border_style(Style::default().fg(theme.success));
 
     let total_loaded = app.memory_graph_original.as_ref()
         .map(|g| g.nodes.len())
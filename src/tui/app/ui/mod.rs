@@ -5,8 +5,14 @@
 pub mod chat;
 pub mod confirm_exit;
 pub mod help;
+pub mod highlight;
 pub mod jobs;
+pub mod markdown;
 pub mod memory;
+pub mod notes;
+pub mod pinned;
+pub mod plan;
+pub mod scratchpad;
 pub mod terminal;
 pub mod top_bar;
 pub mod utils;
@@ -14,9 +20,9 @@ pub mod utils;
 
 
 use crate::tui::app::state::AppStateContainer as App;
-use crate::tui::app::types::AppState;
+use crate::tui::app::types::{AppState, Focus};
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     Frame,
 };
 
@@ -28,11 +34,14 @@ pub fn render(frame: &mut Frame, app: &mut App) {
 
     // Job panel height (fixed at 8 rows when visible to show 2-line job entries)
     let job_panel_height = if app.show_jobs_panel { 8u16 } else { 0u16 };
+    // Tab bar only while more than one tab is open
+    let tab_bar_height = if app.tab_labels.is_empty() { 0u16 } else { 1u16 };
 
     let main_layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(top_bar_height),
+            Constraint::Length(tab_bar_height),
             Constraint::Min(0),
             Constraint::Length(bottom_bar_height),
             Constraint::Length(job_panel_height),
@@ -40,34 +49,54 @@ pub fn render(frame: &mut Frame, app: &mut App) {
         .split(frame.area());
 
     top_bar::render_top_bar(frame, app, main_layout[0], top_bar_height);
+    if !app.tab_labels.is_empty() {
+        render_tab_bar(frame, app, main_layout[1]);
+    }
 
     // Compute layout first - needed for all view modes
-    let terminal_visible = app.show_terminal && app.chat_width_percent < 100;
-    let chunks = if terminal_visible {
-        let chat_pct = app.chat_width_percent;
+    app.apply_layout_for_width(frame.area().width);
+    let (chat_pct, terminal_visible) = match app.zoomed_pane {
+        Some(Focus::Terminal) => (0, true),
+        Some(_) => (100, false),
+        None => (app.chat_width_percent, app.show_terminal && app.chat_width_percent < 100),
+    };
+    let chunks = if chat_pct == 0 {
+        [main_layout[2], Rect::default()]
+    } else if terminal_visible {
         let term_pct = 100u16.saturating_sub(chat_pct);
-        Layout::default()
+        let (first, second) = if app.chat_left { (chat_pct, term_pct) } else { (term_pct, chat_pct) };
+        let split = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(term_pct), Constraint::Percentage(chat_pct)])
-            .split(main_layout[1])
+            .constraints([Constraint::Percentage(first), Constraint::Percentage(second)])
+            .split(main_layout[2]);
+        // Terminal first, chat second, whichever side each is drawn on
+        if app.chat_left {
+            [split[1], split[0]]
+        } else {
+            [split[0], split[1]]
+        }
     } else {
         // Terminal hidden or chat at 100%, chat takes full width
-        Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Min(0), Constraint::Percentage(100)])
-            .split(main_layout[1])
+        [Rect::default(), main_layout[2]]
     };
+    app.panes_area = Some(main_layout[2]);
+    let chat_visible = chat_pct > 0;
+    app.pane_divider = (terminal_visible && chat_visible).then(|| chunks[0].x.max(chunks[1].x));
 
     // Render based on view state
     if app.show_job_detail && terminal_visible {
         // Job detail renders over terminal pane only (like Help)
         jobs::render_job_detail(frame, app, chunks[0]);
-        chat::render_chat(frame, app, chunks[1]);
+        if chat_visible {
+            chat::render_chat(frame, app, chunks[1]);
+        }
     } else if app.show_job_detail {
         // Terminal hidden - use full width for job detail
-        jobs::render_job_detail(frame, app, main_layout[1]);
+        jobs::render_job_detail(frame, app, main_layout[2]);
+    } else if app.scratchpad_editor.is_some() {
+        scratchpad::render_scratchpad_view(frame, app, main_layout[2]);
     } else if app.show_memory_view {
-        memory::render_memory_view(frame, app, main_layout[1]);
+        memory::render_memory_view(frame, app, main_layout[2]);
     } else {
         // Normal layout
         if terminal_visible {
@@ -77,16 +106,54 @@ pub fn render(frame: &mut Frame, app: &mut App) {
                 terminal::render_terminal(frame, app, chunks[0]);
             }
         }
-        // Chat is always rendered
-        chat::render_chat(frame, app, chunks[1]);
+        // Notes panel beside the chat while open
+        let chat_area = if app.show_notes_panel && chat_visible {
+            let split = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Percentage(100 - notes::PANEL_PERCENT),
+                    Constraint::Percentage(notes::PANEL_PERCENT),
+                ])
+                .split(chunks[1]);
+            notes::render_notes_panel(frame, app, split[1]);
+            split[0]
+        } else {
+            chunks[1]
+        };
+        // Pinned messages above the chat: a title line, or the list (F10)
+        let pinned_height = pinned::panel_height(app);
+        let chat_area = if pinned_height > 0 && chat_visible {
+            let split = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(pinned_height), Constraint::Min(0)])
+                .split(chat_area);
+            pinned::render_pinned_panel(frame, app, split[0]);
+            split[1]
+        } else {
+            chat_area
+        };
+        // Chat is always rendered unless the terminal is zoomed, under the
+        // plan checklist while one is in progress
+        match plan::visible_plan(app).filter(|_| chat_visible) {
+            Some(current) => {
+                let split = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(plan::panel_height(&current)), Constraint::Min(0)])
+                    .split(chat_area);
+                plan::render_plan_panel(frame, &current, &app.theme, split[0]);
+                chat::render_chat(frame, app, split[1]);
+            }
+            None if chat_visible => chat::render_chat(frame, app, chat_area),
+            None => {}
+        }
     }
 
     // Bottom bar with F-keys and toggles
-    render_bottom_bar(frame, app, main_layout[2]);
+    render_bottom_bar(frame, app, main_layout[3]);
 
     // Render job panel at bottom if visible
     if app.show_jobs_panel {
-        jobs::render_jobs_panel(frame, app, main_layout[3]);
+        jobs::render_jobs_panel(frame, app, main_layout[4]);
     }
 
     if app.state == AppState::ConfirmExit {
@@ -94,6 +161,27 @@ pub fn render(frame: &mut Frame, app: &mut App) {
     }
 }
 
+/// Render the open tabs, the shown one highlighted
+fn render_tab_bar(frame: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    use ratatui::{
+        style::{Modifier, Style},
+        text::{Line, Span},
+        widgets::Paragraph,
+    };
+    let theme = &app.theme;
+    let mut spans = Vec::new();
+    for (idx, label) in app.tab_labels.iter().enumerate() {
+        let style = if idx == app.tab_index {
+            Style::default().fg(theme.selection_fg).bg(theme.selection_bg).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.muted)
+        };
+        spans.push(Span::styled(format!(" {} ", label), style));
+        spans.push(Span::raw(" "));
+    }
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
 /// Render bottom bar - now empty since everything moved to top
 fn render_bottom_bar(_frame: &mut Frame, _app: &mut App, _area: ratatui::layout::Rect) {
     // All controls moved to top bar
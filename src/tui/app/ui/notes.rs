@@ -0,0 +1,51 @@
+//! Notes panel rendering - the session's bookmarked AI responses
+
+use crate::tui::app::state::AppStateContainer as App;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+/// Width of the notes panel beside the chat, as a percentage of the chat column
+pub const PANEL_PERCENT: u16 = 40;
+
+pub fn render_notes_panel(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let title = format!(" Notes [{}] Alt+n: jump · F9: hide ", app.bookmarks.len());
+    let width = area.width.saturating_sub(5) as usize;
+
+    let mut lines: Vec<Line> = Vec::new();
+    if app.bookmarks.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No bookmarks yet. F8 bookmarks the AI response in view.",
+            Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+        )));
+    }
+    for (i, bookmark) in app.bookmarks.iter().enumerate() {
+        let time = app
+            .chat_history
+            .get(bookmark.message)
+            .map(|m| m.formatted_time())
+            .unwrap_or_default();
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{}. ", i + 1),
+                Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(format!("[{}]", time), Style::default().fg(theme.muted)),
+        ]));
+        for text in super::utils::wrap_text(&bookmark.excerpt, width) {
+            lines.push(Line::from(format!("   {}", text)));
+        }
+        lines.push(Line::from(""));
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(Style::default().fg(theme.border_focused));
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
@@ -0,0 +1,57 @@
+//! Pinned panel rendering - the messages kept in the agent's context
+
+use crate::tui::app::state::AppStateContainer as App;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+/// Most pins listed while the panel is expanded
+const MAX_ROWS: usize = 6;
+
+/// Height of the panel above the chat: its title line while collapsed,
+/// 0 without pins
+pub fn panel_height(app: &App) -> u16 {
+    match (app.pins.len(), app.show_pinned) {
+        (0, _) => 0,
+        (_, false) => 1,
+        (count, true) => (count.min(MAX_ROWS) + 2) as u16,
+    }
+}
+
+pub fn render_pinned_panel(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let count = app.pins.len();
+    let title = if app.show_pinned {
+        format!(" 📌 Pinned [{}] F10: collapse · /pin remove <n> ", count)
+    } else {
+        format!(" 📌 Pinned [{}] F10: expand ", count)
+    };
+    let block = Block::default()
+        .borders(if app.show_pinned { Borders::ALL } else { Borders::TOP })
+        .title(title)
+        .border_style(Style::default().fg(theme.accent));
+    if !app.show_pinned {
+        frame.render_widget(block, area);
+        return;
+    }
+
+    let width = area.width.saturating_sub(6) as usize;
+    let lines: Vec<Line> = app
+        .pin_excerpts()
+        .into_iter()
+        .enumerate()
+        .take(MAX_ROWS)
+        .map(|(i, text)| {
+            let text: String = text.chars().take(width).collect();
+            Line::from(vec![
+                Span::styled(format!("{}. ", i + 1), Style::default().fg(theme.accent).add_modifier(Modifier::BOLD)),
+                Span::styled(text, Style::default().fg(theme.text)),
+            ])
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
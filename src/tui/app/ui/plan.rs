@@ -0,0 +1,81 @@
+//! Plan checklist rendering
+
+use crate::tui::app::state::AppStateContainer as App;
+use crate::tui::app::theme::Theme;
+use mylm_core::agent::runtime::core::PlanCapability;
+use mylm_core::agent::types::plan::{Plan, StepStatus};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+/// Most checklist rows shown above the chat
+const MAX_ROWS: usize = 8;
+
+/// The plan to show above the chat: one that still has steps to do
+pub fn visible_plan(app: &App) -> Option<Plan> {
+    app.plan
+        .as_ref()
+        .and_then(|plan| plan.current())
+        .filter(|plan| !plan.is_complete())
+}
+
+/// Height of the checklist panel for `plan`, borders included
+pub fn panel_height(plan: &Plan) -> u16 {
+    (plan.steps.len().min(MAX_ROWS) + 2) as u16
+}
+
+pub fn render_plan_panel(frame: &mut Frame, plan: &Plan, theme: &Theme, area: Rect) {
+    let (done, total) = plan.progress();
+    let title = format!(" Plan: {} [{}/{}] /plan to edit ", plan.goal, done, total);
+
+    // Keep the step in progress (or the first unsettled one) in view
+    let focus = plan
+        .steps
+        .iter()
+        .position(|s| s.status == StepStatus::InProgress)
+        .or_else(|| plan.steps.iter().position(|s| !s.status.is_settled()))
+        .unwrap_or(0);
+    let rows = (area.height as usize).saturating_sub(2).max(1);
+    let start = focus.saturating_sub(rows / 2).min(plan.steps.len().saturating_sub(rows));
+
+    let lines: Vec<Line> = plan
+        .steps
+        .iter()
+        .skip(start)
+        .take(rows)
+        .map(|step| {
+            let style = match step.status {
+                StepStatus::Pending => Style::default().fg(theme.text),
+                StepStatus::InProgress => Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
+                StepStatus::Done => Style::default().fg(theme.success),
+                StepStatus::Failed => Style::default().fg(theme.error),
+                StepStatus::Skipped => Style::default().fg(theme.muted),
+            };
+            let mut spans = vec![
+                Span::styled(format!("{} ", step.status.marker()), style),
+                Span::styled(format!("{}. {}", step.id, step.title), style),
+            ];
+            if !plan.blockers(step.id).is_empty() && step.status == StepStatus::Pending {
+                let deps: Vec<String> = step.depends_on.iter().map(|d| d.to_string()).collect();
+                spans.push(Span::styled(
+                    format!("  after {}", deps.join(", ")),
+                    Style::default().fg(theme.muted),
+                ));
+            }
+            if let Some(note) = &step.note {
+                spans.push(Span::styled(format!("  {}", note), Style::default().fg(theme.muted)));
+            }
+            Line::from(spans)
+        })
+        .collect();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(Style::default().fg(theme.accent));
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
@@ -0,0 +1,51 @@
+//! Scratchpad view rendering (F11)
+
+use crate::tui::app::state::AppStateContainer as App;
+use mylm_core::agent::tools::scratchpad::document_header;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+pub fn render_scratchpad_view(frame: &mut Frame, app: &mut App, area: Rect) {
+    let (text, muted, border) = (app.theme.text, app.theme.muted, app.theme.border_focused);
+    let Some(editor) = app.scratchpad_editor.as_mut() else {
+        return;
+    };
+    let height = area.height.saturating_sub(2) as usize;
+    editor.scroll_to_cursor(height);
+
+    let title = format!(
+        " 📝 Scratchpad{} · Ctrl+S save · Ctrl+R reload · Esc close ",
+        if editor.dirty { " [modified]" } else { "" }
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(Style::default().fg(border));
+
+    let lines: Vec<Line> = editor
+        .lines
+        .iter()
+        .skip(editor.scroll)
+        .take(height)
+        .map(|line| {
+            let style = if document_header(line).is_some() {
+                Style::default().fg(muted).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(text)
+            };
+            Line::styled(line.as_str(), style)
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+
+    // Long lines run past the edge rather than wrap, so the cursor maps to
+    // its line and column directly
+    let x = area.x + 1 + (editor.col as u16).min(area.width.saturating_sub(3));
+    let y = area.y + 1 + (editor.row - editor.scroll) as u16;
+    frame.set_cursor_position((x, y));
+}
@@ -2,11 +2,12 @@
 
 use crate::tui::app::state::AppStateContainer as App;
 use crate::tui::app::types::Focus;
+use mylm_core::agent::tools::{AnnotationSeverity, TerminalAnnotation};
 use ratatui::{
     layout::Rect,
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
     Frame,
 };
 use tui_term::widget::PseudoTerminal;
@@ -24,15 +25,15 @@ pub fn render_terminal(frame: &mut Frame, app: &mut App, area: Rect) {
         .borders(Borders::ALL)
         .title(title)
         .border_style(if app.focus == Focus::Terminal {
-            Style::default().fg(Color::Yellow)
+            Style::default().fg(app.theme.border_focused)
         } else {
-            Style::default()
+            Style::default().fg(app.theme.border)
         });
 
     if !app.terminal_auto_scroll {
         block = block.title_bottom(Line::from(vec![Span::styled(
             " [SCROLLBACK] ",
-            Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD),
+            Style::default().fg(app.theme.info).add_modifier(Modifier::BOLD),
         )]));
     }
 
@@ -44,15 +45,6 @@ pub fn render_terminal(frame: &mut Frame, app: &mut App, area: Rect) {
     }
 
     let screen = app.terminal_parser.screen();
-
-    // If we're auto-scrolling AND no selection is active, use the efficient PseudoTerminal widget from tui-term
-    if app.terminal_auto_scroll && !app.is_selecting && app.selection_start.is_none() {
-        let terminal = PseudoTerminal::new(screen).block(block);
-        frame.render_widget(terminal, area);
-        return;
-    }
-
-    // Custom Renderer for Scrolling and Selection
     let height = inner_height as usize;
 
     // Combine manual history with visible screen
@@ -63,9 +55,7 @@ pub fn render_terminal(frame: &mut Frame, app: &mut App, area: Rect) {
 
     let screen_contents = screen.contents();
     let screen_lines: Vec<&str> = screen_contents.split('\n').collect();
-    for s in screen_lines {
-        all_lines.push(s);
-    }
+    all_lines.extend(screen_lines.iter().copied());
 
     let total_lines = all_lines.len();
     let max_scroll = total_lines.saturating_sub(height);
@@ -79,6 +69,18 @@ pub fn render_terminal(frame: &mut Frame, app: &mut App, area: Rect) {
     };
     let end_idx = (start_idx + height).min(total_lines);
 
+    // If we're auto-scrolling AND no selection is active, use the efficient PseudoTerminal widget from tui-term
+    if app.terminal_auto_scroll && !app.is_selecting && app.selection_start.is_none() {
+        // The widget draws the screen rows from the top of the pane
+        let annotated = annotated_rows(&app.terminal_annotations, &screen_lines, 0, screen_lines.len().min(height));
+        let terminal = PseudoTerminal::new(screen).block(block);
+        frame.render_widget(terminal, area);
+        render_annotations(frame, app, area, &annotated);
+        return;
+    }
+    let annotated = annotated_rows(&app.terminal_annotations, &all_lines, start_idx, end_idx);
+
+    // Custom Renderer for Scrolling and Selection
     let mut list_items = Vec::new();
 
     for (i, abs_line_idx) in (start_idx..end_idx).enumerate() {
@@ -91,11 +93,11 @@ pub fn render_terminal(frame: &mut Frame, app: &mut App, area: Rect) {
                 let is_selected = app.is_in_selection(col, row, Focus::Terminal);
 
                 let style = if is_selected {
-                    Style::default().bg(Color::Cyan).fg(Color::Black)
+                    Style::default().bg(app.theme.selection_bg).fg(app.theme.selection_fg)
                 } else if abs_line_idx < app.terminal_history.len() {
-                    Style::default().fg(Color::DarkGray)
+                    Style::default().fg(app.theme.muted)
                 } else {
-                    Style::default().fg(Color::White)
+                    Style::default().fg(app.theme.text)
                 };
 
                 spans.push(Span::styled(c.to_string(), style));
@@ -111,4 +113,83 @@ pub fn render_terminal(frame: &mut Frame, app: &mut App, area: Rect) {
 
     let list = List::new(list_items).block(block);
     frame.render_widget(list, area);
+    render_annotations(frame, app, area, &annotated);
+}
+
+/// Pane rows (0-based, inside the border) of annotations whose anchor is
+/// visible, paired with the annotation index; the latest matching line wins
+fn annotated_rows(annotations: &[TerminalAnnotation], lines: &[&str], start: usize, end: usize) -> Vec<(usize, usize)> {
+    annotations
+        .iter()
+        .enumerate()
+        .filter_map(|(i, annotation)| {
+            let line = lines.iter().rposition(|l| l.contains(&annotation.anchor))?;
+            (start..end).contains(&line).then_some((line - start, i))
+        })
+        .collect()
+}
+
+fn severity_style(severity: AnnotationSeverity) -> (Style, &'static str) {
+    match severity {
+        AnnotationSeverity::Error => (Style::default().fg(Color::Red).add_modifier(Modifier::BOLD), "error"),
+        AnnotationSeverity::Warning => (Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD), "warning"),
+        AnnotationSeverity::Info => (Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD), "note"),
+    }
+}
+
+/// Margin markers on the left border, plus a popup for the hovered or selected row
+fn render_annotations(frame: &mut Frame, app: &App, area: Rect, annotated: &[(usize, usize)]) {
+    for &(row, index) in annotated {
+        let (style, _) = severity_style(app.terminal_annotations[index].severity);
+        let marker = Rect::new(area.x, area.y + 1 + row as u16, 1, 1);
+        frame.render_widget(Span::styled("●", style), marker);
+    }
+
+    // Hovering a marker opens its popup; otherwise the one selected with F5
+    let hovered = app.mouse_position.and_then(|(col, row)| {
+        if col > area.x + 1 {
+            return None;
+        }
+        annotated.iter().find(|(r, _)| area.y + 1 + *r as u16 == row).map(|&(r, _)| r)
+    });
+    let selected = app
+        .selected_annotation
+        .and_then(|selected| annotated.iter().find(|(_, i)| *i == selected).map(|&(r, _)| r));
+    let Some(row) = hovered.or(selected) else {
+        return;
+    };
+
+    let notes: Vec<&TerminalAnnotation> = annotated
+        .iter()
+        .filter(|(r, _)| *r == row)
+        .map(|&(_, i)| &app.terminal_annotations[i])
+        .collect();
+    let (style, label) = severity_style(notes.iter().map(|a| a.severity).min().unwrap_or_default());
+    let text: Vec<Line> = notes.iter().map(|a| Line::from(a.note.as_str())).collect();
+
+    // `min` last, so the popup never outgrows a narrow pane
+    let max_width = area.width.saturating_sub(4);
+    let longest = notes.iter().map(|a| a.note.chars().count()).max().unwrap_or(0).min(u16::MAX as usize) as u16;
+    let width = longest.saturating_add(2).max(label.len() as u16 + 4).min(max_width);
+    if width < 3 || area.height < 5 {
+        return;
+    }
+    let wrapped: u16 = notes
+        .iter()
+        .map(|a| (a.note.chars().count() as u16).div_ceil(width.saturating_sub(2).max(1)).max(1))
+        .sum();
+    let height = (wrapped + 2).min(area.height.saturating_sub(2)).max(3);
+
+    // Just below the annotated line, or above it when there is no room
+    let line_y = area.y + 1 + row as u16;
+    let bottom = area.y + area.height.saturating_sub(1);
+    let y = if line_y + 1 + height <= bottom { line_y + 1 } else { line_y.saturating_sub(height).max(area.y) };
+    let popup = Rect::new(area.x + 2, y, width, height);
+
+    frame.render_widget(Clear, popup);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(style)
+        .title(Span::styled(format!(" {} ", label), style));
+    frame.render_widget(Paragraph::new(text).block(block).wrap(Wrap { trim: true }), popup);
 }
@@ -4,7 +4,7 @@ use crate::tui::app::state::AppStateContainer as App;
 use crate::tui::app::types::AppState;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Gauge, Paragraph},
     Frame,
@@ -13,6 +13,7 @@ use std::sync::atomic::Ordering;
 
 pub fn render_top_bar(frame: &mut Frame, app: &mut App, area: Rect, _height: u16) {
     let stats = app.session_monitor.get_stats();
+    let theme = app.theme.clone();
     let auto_approve = app.auto_approve.load(Ordering::SeqCst);
 
     // Get status info from tracker
@@ -37,7 +38,7 @@ pub fn render_top_bar(frame: &mut Frame, app: &mut App, area: Rect, _height: u16
             } else {
                 message.clone()
             };
-            (format!("⚠ Error: {}", msg), Color::Red, false)
+            (format!("⚠ Error: {}", msg), theme.error, false)
         }
         crate::tui::app::status_tracker::StatusInfo::Executing { tool, args } => {
             let args_preview = if args.len() > 25 {
@@ -47,26 +48,26 @@ pub fn render_top_bar(frame: &mut Frame, app: &mut App, area: Rect, _height: u16
             } else {
                 format!(" {}", args)
             };
-            (format!("⚡ {}{}", tool, args_preview), Color::Cyan, true)
+            (format!("⚡ {}{}", tool, args_preview), theme.accent, true)
         }
         crate::tui::app::status_tracker::StatusInfo::Thinking => {
-            ("💭 Thinking...".to_string(), Color::Yellow, true)
+            ("💭 Thinking...".to_string(), theme.warning, true)
         }
         crate::tui::app::status_tracker::StatusInfo::AwaitingApproval { tool, .. } => {
-            (format!("⏸ Approve {}? (y/n)", tool), Color::Magenta, true)
+            (format!("⏸ Approve {}? (y/n/a/p)", tool), theme.approval, true)
         }
         crate::tui::app::status_tracker::StatusInfo::Idle => match &app.state {
-            AppState::Idle => ("✓ Ready".to_string(), Color::Green, false),
-            AppState::Thinking(info) => (format!("💭 {}", info), Color::Yellow, true),
-            AppState::Streaming(info) => (format!("📡 {}", info), Color::Cyan, true),
-            AppState::ExecutingTool(tool) => (format!("⚡ {}", tool), Color::Cyan, true),
-            AppState::WaitingForUser => ("⏸ Waiting".to_string(), Color::Magenta, false),
+            AppState::Idle => ("✓ Ready".to_string(), theme.success, false),
+            AppState::Thinking(info) => (format!("💭 {}", info), theme.warning, true),
+            AppState::Streaming(info) => (format!("📡 {}", info), theme.accent, true),
+            AppState::ExecutingTool(tool) => (format!("⚡ {}", tool), theme.accent, true),
+            AppState::WaitingForUser => ("⏸ Waiting".to_string(), theme.approval, false),
             AppState::AwaitingApproval { tool, .. } => {
-                (format!("⏸ Approve {}? (y/n)", tool), Color::Magenta, true)
+                (format!("⏸ Approve {}? (y/n/a/p)", tool), theme.approval, true)
             }
-            AppState::Error(err) => (format!("⚠ {}", err), Color::Red, false),
-            AppState::ConfirmExit => ("❓ Exit? (y/n)".to_string(), Color::Yellow, false),
-            AppState::NamingSession => ("✎ Naming...".to_string(), Color::Cyan, true),
+            AppState::Error(err) => (format!("⚠ {}", err), theme.error, false),
+            AppState::ConfirmExit => ("❓ Exit? (y/n)".to_string(), theme.warning, false),
+            AppState::NamingSession => ("✎ Naming...".to_string(), theme.accent, true),
         },
     };
 
@@ -78,13 +79,13 @@ pub fn render_top_bar(frame: &mut Frame, app: &mut App, area: Rect, _height: u16
         Span::styled(
             " mylm ",
             Style::default()
-                .bg(Color::Green)
-                .fg(Color::Black)
+                .bg(theme.badge_bg)
+                .fg(theme.badge_fg)
                 .add_modifier(Modifier::BOLD),
         ),
         Span::styled(
             format!("v{} ", env!("CARGO_PKG_VERSION")),
-            Style::default().fg(Color::DarkGray),
+            Style::default().fg(theme.muted),
         ),
     ];
 
@@ -98,9 +99,9 @@ pub fn render_top_bar(frame: &mut Frame, app: &mut App, area: Rect, _height: u16
                 "[Auto-approval ✗]"
             },
             Style::default().fg(if auto_approve {
-                Color::Green
+                theme.success
             } else {
-                Color::DarkGray
+                theme.muted
             }),
         ),
         Span::raw(" "),
@@ -112,9 +113,9 @@ pub fn render_top_bar(frame: &mut Frame, app: &mut App, area: Rect, _height: u16
                 "[Verbose off]"
             },
             Style::default().fg(if app.verbose_mode {
-                Color::Green
+                theme.success
             } else {
-                Color::DarkGray
+                theme.muted
             }),
         ),
         Span::raw(" "),
@@ -122,29 +123,29 @@ pub fn render_top_bar(frame: &mut Frame, app: &mut App, area: Rect, _height: u16
         Span::styled(
             "[F1 Help]",
             Style::default().fg(if app.show_help_view {
-                Color::Green
+                theme.success
             } else {
-                Color::Yellow
+                theme.warning
             }),
         ),
-        Span::styled("[F2 Focus]", Style::default().fg(Color::Yellow)),
+        Span::styled("[F2 Focus]", Style::default().fg(theme.warning)),
         Span::styled(
             "[F3 Memory]",
             Style::default().fg(if app.show_memory_view {
-                Color::Green
+                theme.success
             } else {
-                Color::Yellow
+                theme.warning
             }),
         ),
         Span::styled(
             "[F4 Jobs]",
             Style::default().fg(if app.show_jobs_panel {
-                Color::Green
+                theme.success
             } else {
-                Color::Yellow
+                theme.warning
             }),
         ),
-        Span::styled("[Esc: Exit]", Style::default().fg(Color::Red)),
+        Span::styled("[Esc: Exit]", Style::default().fg(theme.error)),
     ];
 
     // Right side: animated spinner + state + elapsed
@@ -161,7 +162,7 @@ pub fn render_top_bar(frame: &mut Frame, app: &mut App, area: Rect, _height: u16
         ),
         Span::styled(
             format!(" {}", elapsed_text),
-            Style::default().fg(Color::DarkGray),
+            Style::default().fg(theme.muted),
         ),
     ];
 
@@ -170,17 +171,25 @@ pub fn render_top_bar(frame: &mut Frame, app: &mut App, area: Rect, _height: u16
     let (cached_tokens, max_tokens) = app.context_manager.get_cached_token_usage();
     let ratio = app.context_manager.get_cached_context_ratio();
     let gauge_color = if ratio >= 0.9 {
-        Color::Red
+        theme.error
     } else if ratio >= 0.7 {
-        Color::Yellow
+        theme.warning
     } else {
-        Color::Green
+        theme.success
     };
 
-    // Gauge label with cost and context
+    // Gauge label with cost, prompt-cache hits and context
+    let cache_hits = if stats.cached_tokens > 0 {
+        format!(" │ CACHE:{:.0}%", app.session_monitor.cache_hit_ratio() * 100.0)
+    } else {
+        String::new()
+    };
     let label = format!(
-        "${:.2} │ CTX:{}/{} {:.0}%",
+        "↑{} ↓{} │ ${:.2}{} │ CTX:{}/{} {:.0}%",
+        super::utils::format_tokens(stats.input_tokens),
+        super::utils::format_tokens(stats.output_tokens),
         stats.cost,
+        cache_hits,
         super::utils::format_tokens(cached_tokens as u32),
         super::utils::format_tokens(max_tokens as u32),
         (ratio * 100.0).clamp(0.0, 100.0)
@@ -211,7 +220,7 @@ pub fn render_top_bar(frame: &mut Frame, app: &mut App, area: Rect, _height: u16
 
     let gauge = Gauge::default()
         .block(Block::default())
-        .gauge_style(Style::default().fg(gauge_color).bg(Color::DarkGray))
+        .gauge_style(Style::default().fg(gauge_color).bg(theme.muted))
         .ratio(ratio.clamp(0.0, 1.0))
         .label(label);
 
@@ -1,5 +1,7 @@
 //! UI Utility functions
 
+use crate::tui::app::types::ReplyUsage;
+
 /// Format elapsed time in human-readable form
 pub fn format_elapsed(duration: std::time::Duration) -> String {
     let secs = duration.as_secs();
@@ -25,6 +27,20 @@ pub fn format_tokens(tokens: u32) -> String {
     }
 }
 
+/// Footer under an AI response: tokens in and out, cost and latency
+pub fn format_reply_usage(usage: Option<&ReplyUsage>, generation_ms: Option<u64>) -> Option<String> {
+    let mut parts = Vec::new();
+    if let Some(usage) = usage {
+        parts.push(format!("↑ {} in", format_tokens(usage.tokens.prompt_tokens)));
+        parts.push(format!("↓ {} out", format_tokens(usage.tokens.completion_tokens)));
+        parts.push(format!("${:.4}", usage.cost));
+    }
+    if let Some(ms) = generation_ms {
+        parts.push(format!("{:.1}s", (ms as f64 / 1000.0).max(0.1)));
+    }
+    (!parts.is_empty()).then(|| parts.join(" · "))
+}
+
 /// Wrap text to fit within a given width
 pub fn wrap_text(text: &str, width: usize) -> Vec<String> {
     let width = if width == 0 { 1 } else { width };
@@ -148,3 +164,28 @@ pub fn format_timestamp_full(ts: i64) -> String {
         .map(|dt| dt.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string())
         .unwrap_or_else(|| "Unknown".to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tui::app::types::TokenUsage;
+
+    #[test]
+    fn test_format_reply_usage() {
+        let usage = ReplyUsage {
+            tokens: TokenUsage {
+                prompt_tokens: 1_240,
+                completion_tokens: 340,
+                total_tokens: 1_580,
+                cached_tokens: 0,
+            },
+            cost: 0.0031,
+        };
+        assert_eq!(
+            format_reply_usage(Some(&usage), Some(2_140)).as_deref(),
+            Some("↑ 1.2K in · ↓ 340 out · $0.0031 · 2.1s")
+        );
+        assert_eq!(format_reply_usage(None, Some(20)).as_deref(), Some("0.1s"));
+        assert_eq!(format_reply_usage(None, None), None);
+    }
+}
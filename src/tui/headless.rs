@@ -0,0 +1,159 @@
+//! Headless TUI driver for tests
+//!
+//! Runs the real `App`, agent session and key handling against an in-memory
+//! ratatui backend, so a test can type messages, press keys and read the
+//! rendered screen without a terminal. Pair it with a `mock` provider to run
+//! the full agent loop offline:
+//!
+//! ```ignore
+//! let mut config = Config::default();
+//! config.providers.insert("mock".into(), ProviderConfig::mock("tests/script.toml"));
+//! config.active_profile_mut().provider = "mock".into();
+//!
+//! let mut tui = HeadlessTui::start(&config).await?;
+//! tui.submit("list the files").await;
+//! tui.wait_idle(Duration::from_secs(5)).await?;
+//! assert!(tui.screen().contains("Two files."));
+//! ```
+
+use crate::tui::app::event_loop::{handle_key_event, LoopAction};
+use crate::tui::app::types::{AppState, Focus, JobRegistry};
+use crate::tui::app::App;
+use anyhow::{Context, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use mylm_core::agent::OutputEvent;
+use mylm_core::config::Config;
+use ratatui::{backend::TestBackend, Terminal};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Screen size the headless TUI renders at
+const WIDTH: u16 = 160;
+const HEIGHT: u16 = 48;
+
+/// A TUI session driven from code instead of a terminal
+pub struct HeadlessTui {
+    pub app: App,
+    terminal: Terminal<TestBackend>,
+    output_rx: mpsc::UnboundedReceiver<OutputEvent>,
+}
+
+impl HeadlessTui {
+    /// Start an incognito session on `config` with tools auto-approved
+    pub async fn start(config: &Config) -> Result<Self> {
+        let (pty_manager, pty_rx) = crate::tui::spawn_pty(std::env::current_dir().ok())?;
+        let mut app = App::new(pty_manager, config.clone(), JobRegistry::new(), true).await;
+        app.pty_rx = Some(pty_rx);
+        app.focus = Focus::Chat;
+        app.auto_approve.store(true, std::sync::atomic::Ordering::SeqCst);
+
+        let factory = crate::tui::agent_setup::create_session_factory(config, None, None, None);
+        let mut session = factory
+            .create_default_session()
+            .await
+            .context("Failed to create agent session")?;
+        app.budget = Some(factory.budget());
+        app.patch_queue = Some(factory.patch_queue());
+        app.undo_journal = Some(factory.undo_journal());
+        app.plan = Some(factory.plan());
+        app.input_tx = Some(session.input_sender());
+
+        let mut broadcast_rx = session.subscribe_output();
+        let (output_tx, output_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok(event) = broadcast_rx.recv().await {
+                if output_tx.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+        tokio::spawn(async move { session.run().await });
+
+        let terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT))?;
+        Ok(Self { app, terminal, output_rx })
+    }
+
+    /// Press a key; false once the key would have closed the TUI
+    pub async fn key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        matches!(handle_key_event(&mut self.app, KeyEvent::new(code, modifiers)).await, LoopAction::Continue)
+    }
+
+    pub async fn type_text(&mut self, text: &str) {
+        for c in text.chars() {
+            self.key(KeyCode::Char(c), KeyModifiers::NONE).await;
+        }
+    }
+
+    /// Type `text` into the chat input and press Enter
+    pub async fn submit(&mut self, text: &str) {
+        self.app.focus = Focus::Chat;
+        self.type_text(text).await;
+        self.key(KeyCode::Enter, KeyModifiers::NONE).await;
+    }
+
+    /// Apply agent events until the agent is idle again
+    pub async fn wait_idle(&mut self, timeout: Duration) -> Result<()> {
+        tokio::time::timeout(timeout, async {
+            while !matches!(self.app.state, AppState::Idle | AppState::WaitingForUser | AppState::Error(_)) {
+                match self.output_rx.recv().await {
+                    Some(event) => super::handle_agent_event(&mut self.app, event).await,
+                    None => break,
+                }
+            }
+        })
+        .await
+        .with_context(|| format!("Agent still busy after {:?} ({:?})", timeout, self.app.state))
+    }
+
+    /// Render a frame and return the screen as text, one line per row
+    pub fn screen(&mut self) -> String {
+        if let Err(e) = self.terminal.draw(|f| crate::tui::app::ui::render(f, &mut self.app)) {
+            mylm_core::warn_log!("[HEADLESS] Render failed: {}", e);
+        }
+        let buffer = self.terminal.backend().buffer();
+        let width = buffer.area.width as usize;
+        buffer
+            .content
+            .chunks(width)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>().trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Content of the last assistant message in the chat
+    pub fn last_response(&self) -> Option<String> {
+        self.app
+            .chat_history
+            .iter()
+            .rev()
+            .find(|m| m.message.role == mylm_core::provider::chat::MessageRole::Assistant)
+            .map(|m| m.message.content.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mylm_core::config::ProviderConfig;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_scripted_turn() {
+        let script = std::env::temp_dir().join(format!("mylm-headless-{}.toml", uuid::Uuid::new_v4().simple()));
+        std::fs::write(&script, "[[responses]]\ncontent = '{\"f\": \"Two files.\"}'\n").unwrap();
+
+        let mut config = Config::default();
+        config
+            .providers
+            .insert("mock".into(), ProviderConfig::mock(script.to_string_lossy()));
+        config.active_profile_mut().provider = "mock".into();
+
+        let mut tui = HeadlessTui::start(&config).await.unwrap();
+        tui.submit("list the files").await;
+        let idle = tui.wait_idle(Duration::from_secs(10)).await;
+        let _ = std::fs::remove_file(&script);
+        idle.unwrap();
+
+        assert!(tui.last_response().is_some_and(|r| r.contains("Two files.")));
+        assert!(tui.screen().contains("Two files."));
+    }
+}
@@ -7,7 +7,7 @@
 //! - Hub: Session management, settings, help
 
 use crate::tui::app::TimestampedChatMessage;
-use crossterm::event::{self, DisableMouseCapture, EnableMouseCapture};
+use crossterm::event::{self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture};
 use std::io;
 use std::time::Duration;
 use tokio::sync::mpsc;
@@ -18,6 +18,13 @@ pub mod app;
 // Setup utilities
 pub mod setup;
 
+// Several conversations in one TUI
+pub mod tabs;
+
+// Scripted TUI driver for tests
+#[cfg(test)]
+mod headless;
+
 // Re-export commonly used types from app module for public API
 pub use app::App;
 
@@ -25,6 +32,9 @@ pub use app::App;
 pub use app::spawn_pty;
 pub use app::agent_setup;
 
+/// Most agent annotations kept on the terminal pane
+const MAX_TERMINAL_ANNOTATIONS: usize = 50;
+
 /// Result type for TUI session
 #[derive(Debug)]
 pub enum TuiResult {
@@ -34,13 +44,8 @@ pub enum TuiResult {
     Exit,
 }
 
-/// Main entry point for TUI session
-pub async fn run_tui_session(
-    mut app: App,
-    output_rx: mpsc::UnboundedReceiver<mylm_core::agent::OutputEvent>,
-    approval_rx: mpsc::Receiver<crate::tui::app::approval::PendingApproval>,
-    session_handle: tokio::task::JoinHandle<Result<mylm_core::agent::runtime::SessionResult, mylm_core::agent::runtime::SessionError>>,
-) -> io::Result<TuiResult> {
+/// Main entry point for TUI session; `first` is the tab shown at start
+pub async fn run_tui_session(first: tabs::Tab) -> io::Result<TuiResult> {
     use crossterm::{
         execute,
         terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
@@ -50,14 +55,13 @@ pub async fn run_tui_session(
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // Store output and approval receivers in app
-    app.output_rx = Some(output_rx);
-    app.approval_rx = Some(approval_rx);
-    
+    let mut tabs = tabs::Tabs::new(first);
+    let app = &mut tabs.active().app;
+
     // Force initial resize to fix terminal display (workaround for tmux/pty initialization issue)
     let size = terminal.size()?;
     let (term_width, term_height) = crate::tui::setup::calculate_terminal_dimensions(
@@ -70,28 +74,32 @@ pub async fn run_tui_session(
     let _ = app.pty_manager.write_all(b"\x0c"); // Send Ctrl+L (form feed/clear) to force redraw
 
     // Main event loop
-    let result = run_event_loop(&mut terminal, &mut app, session_handle).await;
+    let result = run_event_loop(&mut terminal, &mut tabs).await;
 
     // Restore terminal
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        DisableBracketedPaste
     )?;
     terminal.show_cursor()?;
 
-    // Save session before returning to hub
-    if !app.incognito {
-        mylm_core::info_log!("[TUI] Saving session before exit");
-        let session = app.build_current_session().await;
-        app.session_manager.set_current_session(session);
-        // Give the background task a moment to save
-        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    // Save every tab's session before returning to hub
+    for tab in tabs.iter_mut() {
+        let app = &mut tab.app;
+        if !app.incognito {
+            mylm_core::info_log!("[TUI] Saving session {} before exit", app.session_id);
+            let session = app.build_current_session().await;
+            app.session_manager.set_current_session(session);
+        }
     }
+    // Give the background task a moment to save
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
 
     // Determine return result based on app state
-    let tui_result = if app.should_quit {
+    let tui_result = if tabs.active().app.should_quit {
         TuiResult::Exit
     } else {
         TuiResult::ReturnToHub
@@ -129,15 +137,17 @@ async fn handle_agent_event(
         OutputEvent::ToolExecuting { intent_id, tool, args } => {
             mylm_core::info_log!("[AGENT_EVENT] Tool executing: {} (intent_id={})", tool, intent_id.0);
             app.state = crate::tui::app::AppState::ExecutingTool(format!("{} {}", tool, args));
+            app.running_tools.insert(intent_id.0, (tool.clone(), args.clone(), std::time::Instant::now()));
             app.pending_approval = Some((intent_id.0, tool, args));
         }
         
-        OutputEvent::ToolCompleted { result, .. } => {
+        OutputEvent::ToolCompleted { intent_id, result } => {
             mylm_core::info_log!("[AGENT_EVENT] Tool completed, result len={}", result.len());
             
             // Check if this is a suggested command (not actually executed)
             if result.starts_with("SUGGESTED_COMMAND: ") {
                 let command = result.strip_prefix("SUGGESTED_COMMAND: ").unwrap_or("");
+                app.running_tools.remove(&intent_id.0);
                 // Insert command into terminal for user to run (user presses Enter)
                 let _ = app.pty_manager.write_all(command.as_bytes());
                 // Show confirmation in chat
@@ -148,18 +158,8 @@ async fn handle_agent_event(
                 // Auto-focus terminal so user can press Enter immediately
                 app.focus = crate::tui::app::types::Focus::Terminal;
             } else {
-                // Only show errors in chat, not successful results (they're visible in terminal)
-                let is_error = result.starts_with("❌ Error:") 
-                    || result.starts_with("Error:") 
-                    || result.contains("rate limited")
-                    || result.contains("timed out");
-                
-                if is_error {
-                    app.chat_history.push(TimestampedChatMessage::assistant(format!(
-                        "❌ Tool failed: {}", result
-                    )));
-                }
-                // Successful tool results are not shown in chat (visible in terminal)
+                // Shown as a one-line summary, expanded with Enter
+                app.record_tool_result(intent_id.0, &result);
             }
             
             // Add tool result to context manager history
@@ -183,16 +183,16 @@ async fn handle_agent_event(
                     app.response_start_time = Some(std::time::Instant::now());
                 }
                 // Initialize with empty assistant message
-                app.chat_history.push(TimestampedChatMessage::assistant(String::new()));
+                let model = app.active_model();
+                app.chat_history.push(TimestampedChatMessage::assistant(String::new()).with_model(model));
             }
             
-            // Accumulate response for streaming parse
+            // Accumulate raw response (shown in verbose mode) and feed the
+            // incremental parser, which keeps partial "t"/"f" values
             app.current_response.push_str(&content);
-            
-            // Extract partial "t" and "f" values from streaming JSON
-            use mylm_core::agent::types::parser::ShortKeyParser;
-            let parser = ShortKeyParser::new();
-            let (thought, final_answer, _is_complete) = parser.extract_streaming_content(&app.current_response);
+            app.stream_parser.push(&content);
+            let thought = app.stream_parser.thought().to_string();
+            let final_answer = app.stream_parser.final_answer().to_string();
             
             // Build display content: show thought if present, then final answer
             let display_content = if !thought.is_empty() && !final_answer.is_empty() {
@@ -215,8 +215,21 @@ async fn handle_agent_event(
             }
         }
         
-        OutputEvent::ResponseComplete { .. } => {
+        OutputEvent::ResponseComplete { usage } => {
             mylm_core::info_log!("[AGENT_EVENT] Response complete");
+
+            if let Some(u) = usage.filter(|u| u.total_tokens > 0) {
+                let usage = mylm_core::provider::TokenUsage {
+                    prompt_tokens: u.prompt_tokens,
+                    completion_tokens: u.completion_tokens,
+                    total_tokens: u.total_tokens,
+                    cached_tokens: u.cached_tokens,
+                };
+                let (input_price, output_price) = (app.input_price, app.output_price);
+                app.session_monitor.add_usage(&usage, input_price, output_price);
+                let cost = app.session_monitor.cost_of(&usage, input_price, output_price);
+                crate::tui::app::types::ReplyUsage::add_to_turn(&mut app.chat_history, usage, cost);
+            }
             
             // Normal completion - calculate generation time and update context
             if let Some(start_time) = app.response_start_time.take() {
@@ -235,6 +248,18 @@ async fn handle_agent_event(
             let (cached_tokens, max_tokens) = app.context_manager.get_cached_token_usage();
             mylm_core::info_log!("[AGENT_EVENT] Context updated: {}/{} cached tokens", cached_tokens, max_tokens);
             
+            // Title the session from its first exchange. The call runs in the
+            // background and is picked up by the event loop, so the turn ends now.
+            if !app.title_requested {
+                let first_user = app.chat_history.iter()
+                    .find(|m| m.message.role == mylm_core::provider::chat::MessageRole::User)
+                    .map(|m| m.message.content.clone());
+                if let (Some(pipeline), Some(user)) = (app.follow_ups.as_mut(), first_user) {
+                    pipeline.spawn_title(&user, &response_content);
+                    app.title_requested = true;
+                }
+            }
+            
             // CRITICAL: Save session after AI response (includes both user message and AI response)
             if !app.incognito {
                 mylm_core::debug_log!("[AGENT_EVENT] Auto-saving session after AI response");
@@ -244,12 +269,7 @@ async fn handle_agent_event(
             
             // Reset all streaming state
             app.current_response.clear();
-            app.stream_thought = None;
-            app.stream_in_final = false;
-            app.stream_state = None;
-            app.stream_lookback.clear();
-            app.stream_key_buffer.clear();
-            app.stream_escape_next = false;
+            app.stream_parser.reset();
             app.state = crate::tui::app::AppState::Idle;
         }
         
@@ -259,33 +279,52 @@ async fn handle_agent_event(
             app.pending_approval = Some((intent_id.0, tool.clone(), args.clone()));
             // Add approval request to chat history (show tool and command)
             // Try to parse args as JSON to extract just the command
-            let display_args = if let Ok(json) = serde_json::from_str::<serde_json::Value>(&args) {
+            let json = serde_json::from_str::<serde_json::Value>(&args).ok();
+            let patch = json.as_ref().and_then(|json| json.get("patch")).and_then(|v| v.as_str());
+            let display_args = if let Some(json) = json.as_ref() {
                 if let Some(cmd) = json.get("command").and_then(|v| v.as_str()) {
                     cmd.to_string()
+                } else if let Some(patch) = patch {
+                    // Show the diff itself so the user reviews the actual change
+                    let path = json.get("path").and_then(|v| v.as_str()).unwrap_or("?");
+                    format!("{}\n{}", path, patch_preview(patch))
                 } else {
                     args.clone()
                 }
             } else {
                 args.clone()
             };
-            let truncated_args = if display_args.len() > 150 {
+            let truncated_args = if patch.is_some() {
+                display_args
+            } else if display_args.len() > 150 {
                 format!("{}...", &display_args[..150])
             } else {
                 display_args
             };
+            // What 'a' and 'p' would remember
+            let rule = mylm_core::agent::runtime::governance::PolicyRule::allow_for(
+                &tool,
+                &args,
+                &std::env::current_dir().unwrap_or_default(),
+            );
+            let keys = match rule {
+                Some(rule) => format!(
+                    "Press 'y' to run, 'n' to cancel\n'a' to always allow `{}` this session, 'p' to save that rule",
+                    rule
+                ),
+                None => "Press 'y' to run, 'n' to cancel".to_string(),
+            };
             // Format approval message nicely - no markdown, clean layout
             let approval_msg = if truncated_args.lines().count() == 1 {
                 // Single line command - compact format
-                format!(
-                    "🔒 Approve: {}\n\n  ▶ {}\n\nPress 'y' to run, 'n' to cancel",
-                    tool, truncated_args
-                )
+                format!("🔒 Approve: {}\n\n  ▶ {}\n\n{}", tool, truncated_args, keys)
             } else {
                 // Multi-line - use block format with left border
                 format!(
-                    "🔒 Approve: {}\n\n{}\n\nPress 'y' to run, 'n' to cancel",
+                    "🔒 Approve: {}\n\n{}\n\n{}",
                     tool,
-                    truncated_args.lines().map(|l| format!("  │ {}", l)).collect::<Vec<_>>().join("\n")
+                    truncated_args.lines().map(|l| format!("  │ {}", l)).collect::<Vec<_>>().join("\n"),
+                    keys
                 )
             };
             app.chat_history.push(TimestampedChatMessage::assistant(approval_msg));
@@ -428,8 +467,7 @@ async fn handle_agent_event(
                 | crate::tui::app::AppState::Thinking(_)) {
                 app.state = crate::tui::app::AppState::Idle;
                 app.current_response.clear();
-                app.stream_thought = None;
-                app.stream_in_final = false;
+                app.stream_parser.reset();
             }
         }
         
@@ -481,6 +519,10 @@ async fn handle_agent_event(
                 "[AGENT_EVENT] Context pruned: {} messages, ~{} tokens saved",
                 message_count, tokens_saved
             );
+            app.session_monitor.context_condensed(tokens_saved);
+            // The summary may have swallowed the git status and system info
+            // sent earlier; send them in full next turn
+            app.context_tracker.reset();
             
             let mem_info = if extracted_memories.is_empty() {
                 String::new()
@@ -496,8 +538,14 @@ async fn handle_agent_event(
                 summary
             )));
             
-            // Store segment ID for potential recovery
-            mylm_core::debug_log!("[AGENT_EVENT] Pruned segment ID: {}", segment_id);
+            // List the saved segment under /pruned so /restore can bring it back
+            let saved = mylm_core::conversation::CompressedSegment::archive_dir()
+                .map(|dir| mylm_core::conversation::CompressedSegment::load(&dir, &segment_id));
+            match saved {
+                Some(Ok(segment)) => app.context_manager.compression_archive_mut().push(segment),
+                Some(Err(e)) => mylm_core::warn_log!("[AGENT_EVENT] Cannot load pruned segment {}: {}", segment_id, e),
+                None => {}
+            }
         }
         
         OutputEvent::MemoryAdded { content_preview, .. } => {
@@ -507,6 +555,152 @@ async fn handle_agent_event(
         OutputEvent::MemoryRetrieved { query, result_count, .. } => {
             mylm_core::info_log!("[AGENT_EVENT] Memory retrieved: {} results for '{}'", result_count, query);
         }
+        
+        OutputEvent::ConfigChanged { key, value } => {
+            mylm_core::info_log!("[AGENT_EVENT] Config changed: {} = {}", key, value);
+            // Keep the TUI's copy in sync so /model and /config show the new value
+            if let Err(e) = mylm_core::agent::tools::config::apply_setting(&mut app.config, &key, &value) {
+                mylm_core::warn_log!("[AGENT_EVENT] Could not mirror config change: {}", e);
+            }
+        }
+        
+        OutputEvent::TerminalAnnotations { annotations, clear } => {
+            mylm_core::info_log!("[AGENT_EVENT] Terminal annotations: {} (clear={})", annotations.len(), clear);
+            if clear {
+                app.terminal_annotations.clear();
+                app.selected_annotation = None;
+            }
+            app.terminal_annotations.extend(annotations);
+            // Keep the newest ones; anchors of old output rarely match anyway
+            let excess = app.terminal_annotations.len().saturating_sub(MAX_TERMINAL_ANNOTATIONS);
+            if excess > 0 {
+                app.terminal_annotations.drain(..excess);
+                app.selected_annotation = None;
+            }
+        }
+    }
+}
+
+/// Diff shown in an apply_patch approval, capped so huge patches stay readable
+fn patch_preview(patch: &str) -> String {
+    const MAX_LINES: usize = 60;
+    let lines: Vec<&str> = patch.lines().collect();
+    if lines.len() <= MAX_LINES {
+        return lines.join("\n");
+    }
+    format!(
+        "{}\n... ({} more lines)",
+        lines[..MAX_LINES].join("\n"),
+        lines.len() - MAX_LINES
+    )
+}
+
+/// Apply results of secondary LLM calls that finished since the last tick
+async fn apply_follow_ups(app: &mut App) {
+    use mylm_core::provider::FollowUpKind;
+
+    let Some(pipeline) = app.follow_ups.as_mut() else {
+        return;
+    };
+    for follow_up in pipeline.poll_ready() {
+        match (follow_up.kind, follow_up.result) {
+            (FollowUpKind::Title, Ok(raw)) => {
+                if let Some(title) = mylm_core::provider::pipeline::clean_title(&raw) {
+                    mylm_core::info_log!("[FOLLOW_UP] Session titled '{}' ({}ms)", title, follow_up.elapsed.as_millis());
+                    app.session_title = Some(title);
+                    if !app.incognito {
+                        let session = app.build_current_session().await;
+                        app.session_manager.set_current_session(session);
+                    }
+                }
+            }
+            (kind, Err(e)) => {
+                mylm_core::warn_log!("[FOLLOW_UP] {:?} failed: {}", kind, e);
+            }
+            (kind, Ok(_)) => {
+                mylm_core::debug_log!("[FOLLOW_UP] Ignoring result for {:?}", kind);
+            }
+        }
+    }
+}
+
+/// Queue fired watch triggers and send the oldest when the agent is idle
+/// and the user is not typing
+async fn apply_watch_triggers(app: &mut App) {
+    let Some(watcher) = app.file_watcher.as_mut() else {
+        return;
+    };
+    while let Some(fire) = watcher.try_next() {
+        app.pending_triggers.push_back(fire);
+    }
+    let ready = app.state == crate::tui::app::AppState::Idle && app.chat_input.is_empty() && app.session_active;
+    if !ready {
+        return;
+    }
+    let Some(fire) = app.pending_triggers.pop_front() else {
+        return;
+    };
+    mylm_core::info_log!("[WATCH] Sending trigger '{}' ({} file(s))", fire.trigger, fire.files.len());
+    app.chat_history.push(TimestampedChatMessage::assistant(format!(
+        "Watch trigger '{}' fired for {} changed file(s)",
+        fire.trigger,
+        fire.files.len()
+    )));
+    app.chat_input = fire.prompt;
+    let (tx, _rx) = mpsc::unbounded_channel();
+    app.submit_message(tx).await;
+}
+
+/// Apply edits to the config file to the running session
+fn apply_config_updates(app: &mut App) {
+    use mylm_core::config::ConfigUpdate;
+    let Some(watcher) = app.config_watcher.as_mut() else {
+        return;
+    };
+    let Some(update) = std::iter::from_fn(|| watcher.try_next()).last() else {
+        return;
+    };
+    match update {
+        ConfigUpdate::Changed(config) => {
+            let applied = match app.reloader {
+                Some(ref reloader) => reloader.apply(&config).map(|summary| summary.clients),
+                None => Ok(Vec::new()),
+            };
+            match applied {
+                Ok(clients) => {
+                    let models: Vec<String> = clients.into_iter().map(|(_, model)| model).collect();
+                    app.status_message = Some(if models.is_empty() {
+                        "🔄 Config reloaded".to_string()
+                    } else {
+                        format!("🔄 Config reloaded ({})", models.join(", "))
+                    });
+                    match crate::tui::app::keymap::Keymap::from_settings(&config.app.keymap) {
+                        Ok(keymap) => app.keymap = keymap,
+                        Err(e) => mylm_core::warn_log!("[CONFIG] Keymap not applied: {}", e),
+                    }
+                    // Keep a theme picked with /theme unless the config's themes changed
+                    let theme_changed = app.config.app.theme != config.app.theme || app.config.app.themes != config.app.themes;
+                    app.config = *config;
+                    if theme_changed {
+                        match crate::tui::app::theme::Theme::load(&app.config, &app.config.app.theme) {
+                            Ok(theme) => app.theme = theme,
+                            Err(e) => mylm_core::warn_log!("[CONFIG] Theme not applied: {}", e),
+                        }
+                    }
+                    let model = app.config.resolve_profile().model.unwrap_or_default();
+                    app.context_manager
+                        .set_tokenizer(mylm_core::conversation::Tokenizer::for_model(&model));
+                }
+                Err(e) => {
+                    mylm_core::warn_log!("[CONFIG] Reload not applied: {}", e);
+                    app.status_message = Some(format!("⚠️ Config not applied: {}", e));
+                }
+            }
+        }
+        ConfigUpdate::Invalid(e) => {
+            mylm_core::warn_log!("[CONFIG] Reload skipped: {}", e);
+            app.status_message = Some(format!("⚠️ Config not reloaded: {}", e));
+        }
     }
 }
 
@@ -514,21 +708,114 @@ async fn handle_agent_event(
 use crate::tui::app::event_loop::LoopAction;
 
 /// Main event loop
-async fn run_event_loop<B: ratatui::backend::Backend>(
+/// Suspend the TUI, run the teach editor, and save the resulting memory
+async fn run_teach<B: ratatui::backend::Backend>(
     terminal: &mut ratatui::Terminal<B>,
     app: &mut App,
-    session_handle: tokio::task::JoinHandle<Result<mylm_core::agent::runtime::SessionResult, mylm_core::agent::runtime::SessionError>>,
+    prefill: String,
+) -> io::Result<()> {
+    use crossterm::{
+        execute,
+        terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    };
+    use mylm_core::agent::memory::teach;
+
+    let Some(manager) = app.memory_manager.clone() else {
+        app.chat_history.push(TimestampedChatMessage::assistant(
+            "❌ Memory is disabled for this session".to_string(),
+        ));
+        return Ok(());
+    };
+
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, DisableBracketedPaste)?;
+    let editor = app.config.app.editor.clone();
+    let taught = tokio::task::spawn_blocking(move || teach::teach_interactive(&editor, &prefill)).await;
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste)?;
+    terminal.clear()?;
+
+    let message = match taught {
+        Ok(Ok(Some(memory))) => {
+            let cwd = std::env::current_dir().unwrap_or_default();
+            match manager.teach(&memory, &cwd).await {
+                Ok(id) => format!("🧠 Saved memory {} ({} scope)", id, memory.scope),
+                Err(e) => format!("❌ Failed to save memory: {}", e),
+            }
+        }
+        Ok(Ok(None)) => "Teach cancelled, nothing saved.".to_string(),
+        Ok(Err(e)) => format!("❌ Teach failed: {}", e),
+        Err(e) => format!("❌ Teach failed: {}", e),
+    };
+    app.chat_history.push(TimestampedChatMessage::assistant(message));
+    Ok(())
+}
+
+/// Edit a memory's content and category in the configured editor, then
+/// write back what changed
+async fn run_memory_edit<B: ratatui::backend::Backend>(
+    terminal: &mut ratatui::Terminal<B>,
+    app: &mut App,
+    memory: mylm_core::memory::store::Memory,
+) -> io::Result<()> {
+    use crossterm::{
+        execute,
+        terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    };
+    use mylm_core::agent::memory::edit;
+
+    let Some(manager) = app.memory_manager.clone() else {
+        return Ok(());
+    };
+
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, DisableBracketedPaste)?;
+    let editor = app.config.app.editor.clone();
+    let original = memory.clone();
+    let edited = tokio::task::spawn_blocking(move || edit::edit_interactive(&editor, &original)).await;
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste)?;
+    terminal.clear()?;
+
+    app.status_message = Some(match edited {
+        Ok(Ok(Some(change))) => match change.apply(manager.store().as_ref(), &memory).await {
+            Ok(_) => {
+                let updated = change.applied_to(&memory);
+                let graphs = std::iter::once(&mut app.memory_graph).chain(app.memory_graph_original.as_mut());
+                for node in graphs.flat_map(|graph| graph.nodes.iter_mut()) {
+                    if node.memory.id == memory.id {
+                        node.memory = updated.clone();
+                    }
+                }
+                format!("🧠 Updated memory {}", memory.id)
+            }
+            Err(e) => format!("❌ Failed to update memory {}: {}", memory.id, e),
+        },
+        Ok(Ok(None)) => "Memory unchanged".to_string(),
+        Ok(Err(e)) => format!("❌ Edit failed: {}", e),
+        Err(e) => format!("❌ Edit failed: {}", e),
+    });
+    Ok(())
+}
+
+async fn run_event_loop<B: ratatui::backend::Backend>(
+    terminal: &mut ratatui::Terminal<B>,
+    tabs: &mut tabs::Tabs,
 ) -> io::Result<TuiResult> {
     use crate::tui::app::event_loop::handle_key_event;
     use crossterm::event::Event;
 
     let tick_rate = Duration::from_millis(16); // ~60 FPS
-    let mut session_completed = false;
-
-    // Track session completion using the passed handle
-    let mut session_handle = session_handle;
 
     loop {
+        // Switch, open or close tabs as asked, keeping the others running
+        if let Some(request) = tabs.active().app.tab_request.take() {
+            tabs.handle(request).await;
+        }
+        tabs.poll_background().await;
+        tabs.update_tab_bar();
+        let tabs::Tab { app, session_handle, session_completed } = tabs.active();
+
         // Check if we need to save session
         if app.save_session_request {
             if let Err(e) = app.save_session(None).await {
@@ -537,6 +824,40 @@ async fn run_event_loop<B: ratatui::backend::Backend>(
             app.save_session_request = false;
         }
 
+        // Hand the terminal to the editor for /teach
+        if let Some(prefill) = app.pending_teach.take() {
+            run_teach(terminal, app, prefill).await?;
+        }
+
+        // Hand the terminal to the editor for a memory picked in the F3 view
+        if let Some(memory) = app.pending_memory_edit.take() {
+            run_memory_edit(terminal, app, memory).await?;
+        }
+
+        // Take back the last message for /edit and /retry
+        if let Some(request) = app.rewind_request.take() {
+            if app.apply_rewind(request) {
+                let (tx, _rx) = mpsc::unbounded_channel();
+                app.submit_message(tx).await;
+            }
+        }
+
+        // Apply finished follow-up calls
+        apply_follow_ups(app).await;
+
+        // Type what command mode generated since the last tick
+        app.poll_command_stream();
+
+        // List the models fetched for /model
+        app.poll_model_listing();
+
+        // Send fired watch triggers once the agent is free
+        apply_watch_triggers(app).await;
+        apply_config_updates(app);
+
+        // Release resources after a stretch of inactivity
+        app.suspend_if_idle();
+
         // Draw UI
         terminal.draw(|f| crate::tui::app::ui::render(f, app))?;
         std::io::Write::flush(&mut std::io::stdout())?;
@@ -561,7 +882,7 @@ async fn run_event_loop<B: ratatui::backend::Backend>(
         // Update animation frame (slower than tick rate for visibility)
         app.status_animation_frame = app.status_animation_frame.wrapping_add(1);
         
-        if session_completed {
+        if *session_completed {
             // Session done - just handle UI events and PTY
             tokio::select! {
                 // Handle crossterm events
@@ -577,6 +898,7 @@ async fn run_event_loop<B: ratatui::backend::Backend>(
                             Event::Mouse(mouse) => {
                                 crate::tui::app::event_loop::handle_mouse_event(app, mouse);
                             }
+                            Event::Paste(text) => app.handle_paste(text),
                             Event::Resize(width, height) => {
                                 // Calculate new terminal dimensions
                                 let (term_width, term_height) = crate::tui::setup::calculate_terminal_dimensions(
@@ -625,6 +947,7 @@ async fn run_event_loop<B: ratatui::backend::Backend>(
                             Event::Mouse(mouse) => {
                                 crate::tui::app::event_loop::handle_mouse_event(app, mouse);
                             }
+                            Event::Paste(text) => app.handle_paste(text),
                             Event::Resize(_, _) => {}
                             _ => {}
                         }
@@ -674,20 +997,9 @@ async fn run_event_loop<B: ratatui::backend::Backend>(
                 }
                 
                 // Handle session completion
-                result = &mut session_handle => {
-                    session_completed = true;
-                    match result {
-                        Ok(_) => {
-                            app.chat_history.push(TimestampedChatMessage::assistant(
-                                "Session completed.".to_string()
-                            ));
-                        }
-                        Err(e) => {
-                            app.chat_history.push(TimestampedChatMessage::assistant(format!(
-                                "Session panicked: {}", e
-                            )));
-                        }
-                    }
+                result = &mut *session_handle => {
+                    *session_completed = true;
+                    tabs::on_session_finished(app, result);
                 }
             }
         }
@@ -0,0 +1,307 @@
+//! Conversation tabs - several sessions in one TUI
+//!
+//! Each tab is a whole `App` with its own agent session, chat history and
+//! PTY. Only the active tab is drawn and gets keys; the others keep running,
+//! their agent and terminal output applied every tick so a long task in one
+//! tab carries on while you chat in another.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use mylm_core::agent::runtime::orchestrator::commonbox::Commonbox;
+use mylm_core::agent::runtime::{SessionError, SessionResult};
+use mylm_core::config::Config;
+use tokio::sync::mpsc;
+
+use crate::tui::app::types::{AppState, TimestampedChatMessage};
+use crate::tui::App;
+
+/// The agent session task of a tab
+pub type SessionHandle = tokio::task::JoinHandle<Result<SessionResult, SessionError>>;
+
+/// What a key or command asked of the tabs, handled by the event loop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TabRequest {
+    New,
+    Next,
+    Previous,
+    Close,
+    /// Switch to the tab at this index
+    Go(usize),
+}
+
+/// One conversation: its app state and agent task
+pub struct Tab {
+    pub app: App,
+    pub session_handle: SessionHandle,
+    pub session_completed: bool,
+}
+
+/// Start a session in the current directory: PTY, app state and agent,
+/// resuming the last session if `resume`
+pub async fn start_tab(config: &Config, resume: bool) -> Result<Tab> {
+    use mylm_core::agent::runtime::Session;
+
+    let cwd = std::env::current_dir().context("Could not determine current directory")?;
+
+    // Merge the project's .mylm.yaml into this session's config
+    let config = &config
+        .clone()
+        .with_project_overlay(&cwd)
+        .map_err(|e| anyhow!("Invalid project config: {:#}", e))?;
+
+    let (pty_manager, pty_rx) = crate::tui::spawn_pty(Some(cwd)).map_err(|e| anyhow!("Failed to spawn PTY: {}", e))?;
+
+    let job_registry = crate::tui::app::types::JobRegistry::new();
+    let mut app = App::new(pty_manager, config.clone(), job_registry, false).await;
+    app.pty_rx = Some(pty_rx);
+
+    // Approval capability for interactive tool approval, driven by the App's toggle
+    let (approval_capability, approval_rx) = crate::tui::app::approval::TuiApprovalCapability::policy(
+        config,
+        app.auto_approve.clone(),
+        app.approval_rules.clone(),
+    );
+    let approval_capability = Arc::new(approval_capability);
+
+    // Commonbox for worker spawning (enables the delegate tool)
+    let commonbox = Arc::new(Commonbox::new());
+    let factory = crate::tui::agent_setup::create_session_factory(
+        config,
+        None,
+        Some(approval_capability.clone()),
+        Some(commonbox),
+    );
+
+    let mut session = if resume {
+        let (session, data) = factory
+            .create_resumable_session()
+            .await
+            .map_err(|e| anyhow!("Failed to create agent session: {}", e))?;
+        // Restore session data if available (UI stays dumb, just displays what core provides)
+        if let Some(data) = data {
+            app.restore_from_session(&data);
+        }
+        session
+    } else {
+        factory
+            .create_default_session()
+            .await
+            .map_err(|e| anyhow!("Failed to create agent session: {}", e))?
+    };
+
+    app.budget = Some(factory.budget());
+    app.patch_queue = Some(factory.patch_queue());
+    app.undo_journal = Some(factory.undo_journal());
+    app.worker_pool = Some(factory.worker_pool());
+    app.plan = Some(factory.plan());
+    app.scratchpad = Some(factory.scratchpad());
+    app.idle_resources = Some(factory.idle_resources());
+    factory.reloader().register_approval(approval_capability);
+    app.reloader = Some(factory.reloader());
+    app.config_watcher = start_config_watcher(&app.config);
+
+    // Bridge the output broadcast to an unbounded channel for the TUI
+    let mut broadcast_rx = session.subscribe_output();
+    let (output_tx, output_rx) = mpsc::unbounded_channel::<mylm_core::agent::OutputEvent>();
+    tokio::spawn(async move {
+        let mut event_count = 0u64;
+        loop {
+            match broadcast_rx.recv().await {
+                Ok(event) => {
+                    event_count += 1;
+                    if output_tx.send(event).is_err() {
+                        mylm_core::warn_log!("[BRIDGE] Output channel closed, stopping bridge");
+                        break;
+                    }
+                }
+                Err(e) => {
+                    mylm_core::warn_log!("[BRIDGE] Broadcast recv error: {:?}", e);
+                    break;
+                }
+            }
+        }
+        mylm_core::debug_log!("[BRIDGE] Bridge task ended, forwarded {} events", event_count);
+    });
+
+    app.input_tx = Some(session.input_sender());
+    // Pins of a resumed session go to its agent before the first message
+    app.send_pins();
+    app.output_rx = Some(output_rx);
+    app.approval_rx = Some(approval_rx);
+
+    let session_handle = tokio::spawn(async move {
+        mylm_core::debug_log!("[SESSION_TASK] Session started");
+        session.run().await
+    });
+
+    Ok(Tab {
+        app,
+        session_handle,
+        session_completed: false,
+    })
+}
+
+/// Watch the config file (and `.mylm.yaml`) for edits to apply while running;
+/// the overlay's permissions stay as `config` has them
+fn start_config_watcher(config: &Config) -> Option<mylm_core::config::ConfigWatcher> {
+    let path = Config::default_path()?;
+    let cwd = std::env::current_dir().ok()?;
+    match mylm_core::config::ConfigWatcher::start(&path, Some(&cwd), config.project.as_ref()) {
+        Ok(watcher) => {
+            mylm_core::info_log!("[TABS] Watching {:?} for config changes", watcher.files());
+            Some(watcher)
+        }
+        Err(e) => {
+            mylm_core::warn_log!("[TABS] Config watcher not started: {}", e);
+            None
+        }
+    }
+}
+
+/// Note the end of a tab's agent session in its chat
+pub fn on_session_finished(app: &mut App, result: Result<Result<SessionResult, SessionError>, tokio::task::JoinError>) {
+    match result {
+        Ok(_) => {
+            app.chat_history.push(TimestampedChatMessage::assistant("Session completed.".to_string()));
+        }
+        Err(e) => {
+            app.chat_history.push(TimestampedChatMessage::assistant(format!("Session panicked: {}", e)));
+        }
+    }
+}
+
+/// Index of the tab `request` moves to from `active` of `count`
+pub fn target_index(request: TabRequest, active: usize, count: usize) -> Option<usize> {
+    match request {
+        TabRequest::Next => Some((active + 1) % count.max(1)),
+        TabRequest::Previous => Some((active + count.max(1) - 1) % count.max(1)),
+        TabRequest::Go(index) => (index < count).then_some(index),
+        TabRequest::New | TabRequest::Close => None,
+    }
+}
+
+/// All open tabs and which one is shown
+pub struct Tabs {
+    tabs: Vec<Tab>,
+    active: usize,
+}
+
+impl Tabs {
+    pub fn new(first: Tab) -> Self {
+        Self {
+            tabs: vec![first],
+            active: 0,
+        }
+    }
+
+    pub fn active(&mut self) -> &mut Tab {
+        &mut self.tabs[self.active]
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Tab> {
+        self.tabs.iter_mut()
+    }
+
+    /// Carry out a request from the active tab
+    pub async fn handle(&mut self, request: TabRequest) {
+        match request {
+            TabRequest::New => {
+                // A new tab starts from the global config as saved; start_tab
+                // merges the project overlay into it once
+                let mut config = Config::load_or_default();
+                config.read_only = self.active().app.config.read_only;
+                match start_tab(&config, false).await {
+                    Ok(tab) => {
+                        self.tabs.push(tab);
+                        self.active = self.tabs.len() - 1;
+                    }
+                    Err(e) => {
+                        mylm_core::error_log!("[TABS] Failed to open tab: {:#}", e);
+                        self.active().app.status_message = Some(format!("❌ New tab: {:#}", e));
+                    }
+                }
+            }
+            TabRequest::Close => {
+                if self.tabs.len() == 1 {
+                    self.active().app.status_message = Some("Cannot close the last tab".to_string());
+                    return;
+                }
+                let mut tab = self.tabs.remove(self.active);
+                if !tab.app.incognito {
+                    let session = tab.app.build_current_session().await;
+                    tab.app.session_manager.set_current_session(session);
+                }
+                tab.session_handle.abort();
+                self.active = self.active.min(self.tabs.len() - 1);
+            }
+            other => {
+                if let Some(index) = target_index(other, self.active, self.tabs.len()) {
+                    self.active = index;
+                }
+            }
+        }
+    }
+
+    /// Apply what the background tabs' agents and terminals produced
+    pub async fn poll_background(&mut self) {
+        let active = self.active;
+        for (index, tab) in self.tabs.iter_mut().enumerate() {
+            if index == active {
+                continue;
+            }
+            let app = &mut tab.app;
+            while let Some(data) = app.pty_rx.as_mut().and_then(|rx| rx.try_recv().ok()) {
+                app.process_pty_data(&data);
+            }
+            while let Some(event) = app.output_rx.as_mut().and_then(|rx| rx.try_recv().ok()) {
+                super::handle_agent_event(app, event).await;
+            }
+            if let Some(pending) = app.approval_rx.as_mut().and_then(|rx| rx.try_recv().ok()) {
+                app.pending_approval_with_response = Some(pending);
+            }
+            if !tab.session_completed && tab.session_handle.is_finished() {
+                tab.session_completed = true;
+                let result = (&mut tab.session_handle).await;
+                on_session_finished(&mut tab.app, result);
+            }
+        }
+    }
+
+    /// Give the active tab the labels to draw in its tab bar
+    pub fn update_tab_bar(&mut self) {
+        let labels: Vec<String> = self
+            .tabs
+            .iter()
+            .enumerate()
+            .map(|(index, tab)| {
+                let name = tab.app.session_title.clone().unwrap_or_else(|| format!("Tab {}", index + 1));
+                let marker = match tab.app.state {
+                    AppState::AwaitingApproval { .. } => " ⏸",
+                    AppState::Idle | AppState::WaitingForUser | AppState::ConfirmExit => "",
+                    _ => " ◐",
+                };
+                format!("{}: {}{}", index + 1, name, marker)
+            })
+            .collect();
+        let active = self.active;
+        let app = &mut self.active().app;
+        app.tab_labels = if labels.len() > 1 { labels } else { Vec::new() };
+        app.tab_index = active;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_index_wraps() {
+        assert_eq!(target_index(TabRequest::Next, 2, 3), Some(0));
+        assert_eq!(target_index(TabRequest::Previous, 0, 3), Some(2));
+        assert_eq!(target_index(TabRequest::Next, 0, 1), Some(0));
+        assert_eq!(target_index(TabRequest::Go(1), 0, 3), Some(1));
+        assert_eq!(target_index(TabRequest::Go(3), 0, 3), None);
+        assert_eq!(target_index(TabRequest::New, 0, 3), None);
+    }
+}